@@ -0,0 +1,13 @@
+CREATE TABLE IF NOT EXISTS pending_interaction (
+  record_id          TEXT PRIMARY KEY,
+  flow               TEXT NOT NULL,
+  guild_id           TEXT NOT NULL,
+  user_id            TEXT NOT NULL,
+  channel_id         TEXT NOT NULL,
+  message_id         TEXT NOT NULL,
+  payload            TEXT NOT NULL,
+  expires_at         TIMESTAMP WITH TIME ZONE NOT NULL,
+  created_at         TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS pending_interaction_flow_idx ON pending_interaction (flow);
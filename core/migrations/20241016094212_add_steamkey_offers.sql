@@ -0,0 +1,7 @@
+DROP TABLE IF EXISTS pending_interaction;
+
+ALTER TABLE steamkey
+  ADD COLUMN IF NOT EXISTS offer_id          TEXT UNIQUE,
+  ADD COLUMN IF NOT EXISTS offer_channel_id  TEXT,
+  ADD COLUMN IF NOT EXISTS offer_message_id  TEXT,
+  ADD COLUMN IF NOT EXISTS offer_expires_at  TIMESTAMP WITH TIME ZONE;
@@ -0,0 +1 @@
+ALTER TABLE bookmarks ALTER COLUMN guild_id DROP NOT NULL;
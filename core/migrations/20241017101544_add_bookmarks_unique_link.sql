@@ -0,0 +1,8 @@
+-- Keep the earliest bookmark where a user has bookmarked the same message more than once
+DELETE FROM bookmarks a
+  USING bookmarks b
+  WHERE a.user_id = b.user_id
+    AND a.message_link = b.message_link
+    AND a.record_id > b.record_id;
+
+CREATE UNIQUE INDEX IF NOT EXISTS bookmarks_user_link_idx ON bookmarks (user_id, message_link);
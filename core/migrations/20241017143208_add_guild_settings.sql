@@ -0,0 +1,4 @@
+CREATE TABLE IF NOT EXISTS guild_settings (
+  guild_id           TEXT PRIMARY KEY,
+  quotes_on_add      BOOLEAN DEFAULT TRUE NOT NULL
+);
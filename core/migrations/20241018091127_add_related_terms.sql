@@ -0,0 +1 @@
+ALTER TABLE term ADD COLUMN IF NOT EXISTS related TEXT[] DEFAULT ARRAY[]::TEXT[];
@@ -0,0 +1 @@
+ALTER TABLE guild_settings ADD COLUMN IF NOT EXISTS search_threshold DOUBLE PRECISION DEFAULT 0.3 NOT NULL;
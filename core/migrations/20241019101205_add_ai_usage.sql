@@ -0,0 +1,9 @@
+CREATE TABLE IF NOT EXISTS ai_usage (
+  guild_id           TEXT NOT NULL,
+  usage_date         DATE NOT NULL,
+  requests           BIGINT DEFAULT 0 NOT NULL,
+  tokens             BIGINT DEFAULT 0 NOT NULL,
+  PRIMARY KEY (guild_id, usage_date)
+);
+
+ALTER TABLE guild_settings ADD COLUMN IF NOT EXISTS ai_monthly_cap DOUBLE PRECISION;
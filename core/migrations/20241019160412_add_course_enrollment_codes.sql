@@ -0,0 +1,21 @@
+CREATE TABLE IF NOT EXISTS course_enrollment_code (
+  record_id          TEXT PRIMARY KEY,
+  code               TEXT NOT NULL,
+  course_name        TEXT NOT NULL REFERENCES course (course_name) ON UPDATE CASCADE ON DELETE CASCADE,
+  guild_id           TEXT NOT NULL,
+  max_uses           INTEGER,
+  uses               INTEGER DEFAULT 0 NOT NULL,
+  created_by         TEXT NOT NULL,
+  created_at         TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+  UNIQUE (guild_id, code)
+);
+
+CREATE TABLE IF NOT EXISTS course_enrollment (
+  record_id          TEXT PRIMARY KEY,
+  course_name        TEXT NOT NULL REFERENCES course (course_name) ON UPDATE CASCADE ON DELETE CASCADE,
+  guild_id           TEXT NOT NULL,
+  user_id            TEXT NOT NULL,
+  enrollment_code    TEXT,
+  enrolled_at        TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+  UNIQUE (guild_id, course_name, user_id)
+);
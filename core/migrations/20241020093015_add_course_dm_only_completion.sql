@@ -0,0 +1 @@
+ALTER TABLE course ADD COLUMN IF NOT EXISTS dm_only_completion BOOLEAN DEFAULT FALSE NOT NULL;
@@ -0,0 +1,2 @@
+ALTER TABLE guild_settings ADD COLUMN IF NOT EXISTS tracking_channel TEXT;
+ALTER TABLE guild_settings ADD COLUMN IF NOT EXISTS tracking_hints   BOOLEAN DEFAULT TRUE NOT NULL;
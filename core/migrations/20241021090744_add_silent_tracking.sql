@@ -0,0 +1 @@
+ALTER TABLE tracking_profile ADD COLUMN silent_tracking BOOLEAN DEFAULT FALSE NOT NULL;
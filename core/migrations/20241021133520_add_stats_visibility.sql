@@ -0,0 +1,4 @@
+ALTER TABLE tracking_profile ADD COLUMN stats_ephemeral BOOLEAN DEFAULT FALSE NOT NULL;
+
+-- Private stats have always been shown ephemerally by default, so keep that behavior
+UPDATE tracking_profile SET stats_ephemeral = stats_private;
@@ -0,0 +1,17 @@
+CREATE TABLE IF NOT EXISTS guild_goal (
+  record_id          TEXT PRIMARY KEY,
+  guild_id           TEXT NOT NULL,
+  channel_id         TEXT NOT NULL,
+  period             TEXT NOT NULL,
+  target_minutes     BIGINT NOT NULL,
+  starts_at          TIMESTAMP WITH TIME ZONE NOT NULL,
+  ends_at            TIMESTAMP WITH TIME ZONE NOT NULL,
+  last_update_at     TIMESTAMP WITH TIME ZONE,
+  completed_at       TIMESTAMP WITH TIME ZONE,
+  closed             BOOLEAN DEFAULT FALSE NOT NULL,
+  created_by         TEXT NOT NULL,
+  created_at         TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Only one goal can run at a time in each guild
+CREATE UNIQUE INDEX IF NOT EXISTS guild_goal_active_idx ON guild_goal (guild_id) WHERE closed = FALSE;
@@ -0,0 +1,9 @@
+ALTER TABLE guild_settings ADD COLUMN IF NOT EXISTS milestone_interval BIGINT DEFAULT 60000;
+
+CREATE TABLE IF NOT EXISTS guild_milestone (
+  record_id          TEXT PRIMARY KEY,
+  guild_id           TEXT NOT NULL,
+  milestone_minutes  BIGINT NOT NULL,
+  reached_at         TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+  UNIQUE (guild_id, milestone_minutes)
+);
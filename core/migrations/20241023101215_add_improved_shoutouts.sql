@@ -0,0 +1,2 @@
+ALTER TABLE guild_settings ADD COLUMN IF NOT EXISTS improved_channel TEXT;
+ALTER TABLE guild_settings ADD COLUMN IF NOT EXISTS improved_posted_for DATE;
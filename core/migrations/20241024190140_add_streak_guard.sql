@@ -0,0 +1,2 @@
+ALTER TABLE tracking_profile ADD COLUMN IF NOT EXISTS streak_guard_hour SMALLINT;
+ALTER TABLE tracking_profile ADD COLUMN IF NOT EXISTS streak_guard_sent_on DATE;
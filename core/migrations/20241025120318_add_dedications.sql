@@ -0,0 +1,13 @@
+CREATE TABLE IF NOT EXISTS dedication (
+  record_id           TEXT PRIMARY KEY,
+  guild_id            TEXT NOT NULL,
+  user_id             TEXT NOT NULL,
+  recipient_id        TEXT NOT NULL,
+  meditation_id       TEXT NOT NULL UNIQUE,
+  meditation_minutes  INTEGER NOT NULL,
+  message             TEXT NOT NULL,
+  dedicated_at        TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS dedication_recipient_idx ON dedication (guild_id, recipient_id);
+CREATE INDEX IF NOT EXISTS dedication_user_idx ON dedication (guild_id, user_id);
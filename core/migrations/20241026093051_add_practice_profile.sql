@@ -0,0 +1,4 @@
+ALTER TABLE tracking_profile ADD COLUMN IF NOT EXISTS tradition TEXT;
+ALTER TABLE tracking_profile ADD COLUMN IF NOT EXISTS favorite_teacher TEXT;
+ALTER TABLE tracking_profile ADD COLUMN IF NOT EXISTS years_practicing SMALLINT;
+ALTER TABLE tracking_profile ADD COLUMN IF NOT EXISTS directory_listed BOOLEAN DEFAULT FALSE NOT NULL;
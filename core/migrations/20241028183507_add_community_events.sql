@@ -0,0 +1,35 @@
+CREATE TABLE IF NOT EXISTS community_event (
+  record_id          TEXT PRIMARY KEY,
+  guild_id           TEXT NOT NULL,
+  channel_id         TEXT NOT NULL,
+  title              TEXT NOT NULL,
+  description        TEXT,
+  starts_at          TIMESTAMP WITH TIME ZONE NOT NULL,
+  duration_minutes   INTEGER NOT NULL,
+  repeat_days        INTEGER,
+  created_by         TEXT NOT NULL,
+  created_at         TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Events are looked up by title, so titles are unique in each guild
+CREATE UNIQUE INDEX IF NOT EXISTS community_event_title_idx ON community_event (guild_id, LOWER(title));
+CREATE INDEX IF NOT EXISTS community_event_channel_idx ON community_event (guild_id, channel_id);
+
+CREATE TABLE IF NOT EXISTS event_rsvp (
+  record_id          TEXT PRIMARY KEY,
+  event_id           TEXT NOT NULL REFERENCES community_event (record_id) ON DELETE CASCADE,
+  user_id            TEXT NOT NULL,
+  occurrence         TIMESTAMP WITH TIME ZONE NOT NULL,
+  created_at         TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+  UNIQUE (event_id, user_id, occurrence)
+);
+
+CREATE TABLE IF NOT EXISTS event_attendance (
+  record_id          TEXT PRIMARY KEY,
+  event_id           TEXT NOT NULL REFERENCES community_event (record_id) ON DELETE CASCADE,
+  user_id            TEXT NOT NULL,
+  occurrence         TIMESTAMP WITH TIME ZONE NOT NULL,
+  source             TEXT NOT NULL,
+  created_at         TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+  UNIQUE (event_id, user_id, occurrence)
+);
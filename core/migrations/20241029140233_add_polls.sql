@@ -0,0 +1,24 @@
+CREATE TABLE IF NOT EXISTS poll (
+  record_id          TEXT PRIMARY KEY,
+  guild_id           TEXT NOT NULL,
+  channel_id         TEXT NOT NULL,
+  message_id         TEXT,
+  question           TEXT NOT NULL,
+  choices            TEXT[] NOT NULL,
+  multiple_choice    BOOLEAN DEFAULT FALSE NOT NULL,
+  anonymous          BOOLEAN DEFAULT FALSE NOT NULL,
+  closes_at          TIMESTAMP WITH TIME ZONE,
+  closed             BOOLEAN DEFAULT FALSE NOT NULL,
+  created_by         TEXT NOT NULL,
+  created_at         TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS poll_closes_at_idx ON poll (closes_at) WHERE closed = FALSE;
+
+CREATE TABLE IF NOT EXISTS poll_vote (
+  poll_id            TEXT NOT NULL REFERENCES poll (record_id) ON DELETE CASCADE,
+  user_id            TEXT NOT NULL,
+  choice             SMALLINT NOT NULL,
+  voted_at           TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+  PRIMARY KEY (poll_id, user_id, choice)
+);
@@ -0,0 +1,23 @@
+CREATE TABLE IF NOT EXISTS suggestion (
+  record_id          TEXT PRIMARY KEY,
+  guild_id           TEXT NOT NULL,
+  channel_id         TEXT NOT NULL,
+  message_id         TEXT,
+  user_id            TEXT NOT NULL,
+  content            TEXT NOT NULL,
+  status             TEXT DEFAULT 'open' NOT NULL,
+  status_note        TEXT,
+  status_updated_by  TEXT,
+  status_updated_at  TIMESTAMP WITH TIME ZONE,
+  created_at         TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS suggestion_status_idx ON suggestion (guild_id, status);
+
+CREATE TABLE IF NOT EXISTS suggestion_vote (
+  suggestion_id      TEXT NOT NULL REFERENCES suggestion (record_id) ON DELETE CASCADE,
+  user_id            TEXT NOT NULL,
+  upvote             BOOLEAN NOT NULL,
+  voted_at           TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+  PRIMARY KEY (suggestion_id, user_id)
+);
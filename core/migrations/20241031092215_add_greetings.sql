@@ -0,0 +1,3 @@
+ALTER TABLE guild_settings ADD COLUMN IF NOT EXISTS greeting_channel TEXT;
+ALTER TABLE guild_settings ADD COLUMN IF NOT EXISTS welcome_message TEXT;
+ALTER TABLE guild_settings ADD COLUMN IF NOT EXISTS farewell_message TEXT DEFAULT 'We wish you well on your future endeavors, {user} :pray:';
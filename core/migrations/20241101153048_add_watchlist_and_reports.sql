@@ -0,0 +1,27 @@
+CREATE TABLE IF NOT EXISTS watchlist_term (
+  record_id          TEXT PRIMARY KEY,
+  guild_id           TEXT NOT NULL,
+  pattern            TEXT NOT NULL,
+  is_regex           BOOLEAN DEFAULT FALSE NOT NULL,
+  added_by           TEXT NOT NULL,
+  created_at         TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+  UNIQUE (guild_id, pattern)
+);
+
+CREATE TABLE IF NOT EXISTS report (
+  record_id          TEXT PRIMARY KEY,
+  guild_id           TEXT NOT NULL,
+  channel_id         TEXT NOT NULL,
+  message_id         TEXT NOT NULL,
+  user_id            TEXT NOT NULL,
+  reported_by        TEXT,
+  source             TEXT NOT NULL,
+  matched_term       TEXT,
+  content            TEXT NOT NULL,
+  status             TEXT DEFAULT 'open' NOT NULL,
+  handled_by         TEXT,
+  handled_at         TIMESTAMP WITH TIME ZONE,
+  created_at         TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS report_user_idx ON report (guild_id, user_id);
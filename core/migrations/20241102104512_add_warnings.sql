@@ -0,0 +1,12 @@
+CREATE TABLE IF NOT EXISTS warning (
+  record_id          TEXT PRIMARY KEY,
+  guild_id           TEXT NOT NULL,
+  user_id            TEXT NOT NULL,
+  issued_by          TEXT NOT NULL,
+  reason             TEXT NOT NULL,
+  timeout_until      TIMESTAMP WITH TIME ZONE,
+  message_link       TEXT,
+  occurred_at        TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS warning_user_idx ON warning (guild_id, user_id);
@@ -0,0 +1,27 @@
+ALTER TABLE guild_settings ADD COLUMN IF NOT EXISTS ticket_channel TEXT;
+
+CREATE TABLE IF NOT EXISTS ticket (
+  record_id          TEXT PRIMARY KEY,
+  guild_id           TEXT NOT NULL,
+  user_id            TEXT NOT NULL,
+  thread_id          TEXT NOT NULL,
+  subject            TEXT,
+  status             TEXT DEFAULT 'open' NOT NULL,
+  closed_by          TEXT,
+  opened_at          TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL,
+  closed_at          TIMESTAMP WITH TIME ZONE
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS ticket_open_user_idx ON ticket (guild_id, user_id) WHERE status = 'open';
+CREATE INDEX IF NOT EXISTS ticket_thread_idx ON ticket (thread_id);
+
+CREATE TABLE IF NOT EXISTS ticket_message (
+  record_id          TEXT PRIMARY KEY,
+  ticket_id          TEXT NOT NULL REFERENCES ticket (record_id) ON DELETE CASCADE,
+  author_id          TEXT NOT NULL,
+  from_staff         BOOLEAN NOT NULL,
+  content            TEXT NOT NULL,
+  created_at         TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS ticket_message_ticket_idx ON ticket_message (ticket_id, created_at);
@@ -0,0 +1 @@
+ALTER TABLE guild_settings ADD COLUMN IF NOT EXISTS auto_publish BOOLEAN DEFAULT FALSE NOT NULL;
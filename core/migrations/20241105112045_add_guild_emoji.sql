@@ -0,0 +1,8 @@
+CREATE TABLE IF NOT EXISTS guild_emoji (
+  record_id          TEXT PRIMARY KEY,
+  guild_id           TEXT NOT NULL,
+  name               TEXT NOT NULL,
+  emoji              TEXT NOT NULL,
+  created_at         TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+  UNIQUE (guild_id, name)
+);
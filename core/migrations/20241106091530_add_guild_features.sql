@@ -0,0 +1,8 @@
+CREATE TABLE IF NOT EXISTS guild_feature (
+  record_id          TEXT PRIMARY KEY,
+  guild_id           TEXT NOT NULL,
+  feature            TEXT NOT NULL,
+  enabled            BOOLEAN NOT NULL,
+  created_at         TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+  UNIQUE (guild_id, feature)
+);
@@ -0,0 +1 @@
+ALTER TABLE tracking_profile ADD COLUMN IF NOT EXISTS chart_format TEXT DEFAULT 'standard' NOT NULL;
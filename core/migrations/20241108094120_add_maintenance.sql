@@ -0,0 +1,7 @@
+-- Holds a single row, so there's only ever one maintenance setting
+CREATE TABLE IF NOT EXISTS maintenance (
+  singleton          BOOLEAN PRIMARY KEY DEFAULT TRUE CHECK (singleton),
+  enabled            BOOLEAN NOT NULL,
+  reason             TEXT,
+  updated_at         TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
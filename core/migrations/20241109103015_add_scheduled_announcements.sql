@@ -0,0 +1,13 @@
+CREATE TABLE IF NOT EXISTS scheduled_announcement (
+  record_id          TEXT PRIMARY KEY,
+  guild_id           TEXT NOT NULL,
+  channel_id         TEXT NOT NULL,
+  title              TEXT NOT NULL,
+  message            TEXT NOT NULL,
+  send_at            TIMESTAMP WITH TIME ZONE NOT NULL,
+  sent               BOOLEAN DEFAULT FALSE NOT NULL,
+  created_by         TEXT NOT NULL,
+  created_at         TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS scheduled_announcement_send_at_idx ON scheduled_announcement (send_at) WHERE sent = FALSE;
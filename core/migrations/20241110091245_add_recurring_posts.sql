@@ -0,0 +1,17 @@
+CREATE TABLE IF NOT EXISTS recurring_post (
+  record_id          TEXT PRIMARY KEY,
+  guild_id           TEXT NOT NULL,
+  channel_id         TEXT NOT NULL,
+  name               TEXT NOT NULL,
+  message            TEXT NOT NULL,
+  thread_name        TEXT,
+  weekday            SMALLINT,
+  minute_of_day      SMALLINT NOT NULL,
+  utc_offset         SMALLINT NOT NULL,
+  next_post_at       TIMESTAMP WITH TIME ZONE NOT NULL,
+  created_by         TEXT NOT NULL,
+  created_at         TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS recurring_post_name_idx ON recurring_post (guild_id, LOWER(name));
+CREATE INDEX IF NOT EXISTS recurring_post_next_post_at_idx ON recurring_post (next_post_at);
@@ -0,0 +1,10 @@
+CREATE TABLE IF NOT EXISTS mood_checkin (
+  record_id          TEXT PRIMARY KEY,
+  guild_id           TEXT NOT NULL,
+  user_id            TEXT NOT NULL,
+  checkin_date       DATE NOT NULL,
+  mood               SMALLINT NOT NULL CHECK (mood BETWEEN 1 AND 5),
+  note               TEXT,
+  created_at         TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+  UNIQUE (guild_id, user_id, checkin_date)
+);
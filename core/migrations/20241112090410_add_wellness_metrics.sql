@@ -0,0 +1,9 @@
+CREATE TABLE IF NOT EXISTS wellness_metrics (
+  record_id          TEXT PRIMARY KEY,
+  guild_id           TEXT NOT NULL,
+  user_id            TEXT NOT NULL,
+  metric_date        DATE NOT NULL,
+  sleep_hours        REAL CHECK (sleep_hours BETWEEN 0 AND 24),
+  created_at         TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+  UNIQUE (guild_id, user_id, metric_date)
+);
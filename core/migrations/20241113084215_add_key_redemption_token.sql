@@ -0,0 +1,2 @@
+ALTER TABLE steamkey ADD COLUMN IF NOT EXISTS redemption_token TEXT;
+CREATE UNIQUE INDEX IF NOT EXISTS steamkey_redemption_token_idx ON steamkey (redemption_token);
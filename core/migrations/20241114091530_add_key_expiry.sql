@@ -0,0 +1,4 @@
+ALTER TABLE steamkey ADD COLUMN IF NOT EXISTS expires_at TIMESTAMP WITH TIME ZONE;
+ALTER TABLE steamkey ADD COLUMN IF NOT EXISTS reserved_at TIMESTAMP WITH TIME ZONE;
+ALTER TABLE steamkey ADD COLUMN IF NOT EXISTS flagged BOOLEAN NOT NULL DEFAULT FALSE;
+UPDATE steamkey SET reserved_at = NOW() WHERE reserved IS NOT NULL;
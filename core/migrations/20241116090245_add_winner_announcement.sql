@@ -0,0 +1,3 @@
+ALTER TABLE guild_settings ADD COLUMN IF NOT EXISTS winner_title TEXT;
+ALTER TABLE guild_settings ADD COLUMN IF NOT EXISTS winner_message TEXT;
+ALTER TABLE guild_settings ADD COLUMN IF NOT EXISTS winner_image TEXT;
@@ -0,0 +1,4 @@
+ALTER TABLE meditation ADD COLUMN IF NOT EXISTS channel_id TEXT;
+CREATE INDEX IF NOT EXISTS meditation_guild_channel_idx ON meditation (guild_id, channel_id) WHERE channel_id IS NOT NULL;
+
+ALTER TABLE guild_settings ADD COLUMN IF NOT EXISTS channel_segments BOOLEAN NOT NULL DEFAULT FALSE;
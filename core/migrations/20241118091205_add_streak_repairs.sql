@@ -0,0 +1,14 @@
+CREATE TABLE IF NOT EXISTS streak_repair (
+  record_id          TEXT PRIMARY KEY,
+  guild_id           TEXT NOT NULL,
+  user_id            TEXT NOT NULL,
+  repaired_date      DATE NOT NULL,
+  reason             TEXT NOT NULL,
+  status             TEXT DEFAULT 'open' NOT NULL,
+  handled_by         TEXT,
+  handled_at         TIMESTAMP WITH TIME ZONE,
+  created_at         TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Each day can only be repaired once, but can be requested again after a denial
+CREATE UNIQUE INDEX IF NOT EXISTS streak_repair_day_idx ON streak_repair (guild_id, user_id, repaired_date) WHERE status <> 'denied';
@@ -0,0 +1 @@
+ALTER TABLE tracking_profile ADD COLUMN IF NOT EXISTS redact_minutes BOOLEAN NOT NULL DEFAULT FALSE;
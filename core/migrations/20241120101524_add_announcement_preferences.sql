@@ -0,0 +1,2 @@
+ALTER TABLE tracking_profile ADD COLUMN IF NOT EXISTS display_name TEXT;
+ALTER TABLE tracking_profile ADD COLUMN IF NOT EXISTS pronouns TEXT;
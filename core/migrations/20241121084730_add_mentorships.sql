@@ -0,0 +1,26 @@
+CREATE TABLE IF NOT EXISTS mentorship_signup (
+  record_id          TEXT PRIMARY KEY,
+  guild_id           TEXT NOT NULL,
+  user_id            TEXT NOT NULL,
+  mentor_role        TEXT NOT NULL,
+  interests          TEXT NOT NULL,
+  utc_offset         SMALLINT DEFAULT 0 NOT NULL,
+  signed_up_at       TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+  UNIQUE (guild_id, user_id)
+);
+
+CREATE TABLE IF NOT EXISTS mentorships (
+  record_id          TEXT PRIMARY KEY,
+  guild_id           TEXT NOT NULL,
+  mentor_id          TEXT NOT NULL,
+  mentee_id          TEXT NOT NULL,
+  matched_by         TEXT NOT NULL,
+  started_at         TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+  last_check_in      TIMESTAMP WITH TIME ZONE,
+  last_reminder      TIMESTAMP WITH TIME ZONE,
+  ended_at           TIMESTAMP WITH TIME ZONE
+);
+
+-- Members can only be in one active mentorship at a time
+CREATE UNIQUE INDEX IF NOT EXISTS mentorships_active_mentor_idx ON mentorships (guild_id, mentor_id) WHERE ended_at IS NULL;
+CREATE UNIQUE INDEX IF NOT EXISTS mentorships_active_mentee_idx ON mentorships (guild_id, mentee_id) WHERE ended_at IS NULL;
@@ -0,0 +1,22 @@
+CREATE TABLE IF NOT EXISTS study_group (
+  record_id          TEXT PRIMARY KEY,
+  guild_id           TEXT NOT NULL,
+  name               TEXT NOT NULL,
+  reading            TEXT NOT NULL,
+  channel_id         TEXT NOT NULL,
+  recurring_post_id  TEXT REFERENCES recurring_post (record_id) ON DELETE SET NULL,
+  created_by         TEXT NOT NULL,
+  created_at         TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS study_group_name_idx ON study_group (guild_id, LOWER(name));
+
+CREATE TABLE IF NOT EXISTS study_group_member (
+  record_id          TEXT PRIMARY KEY,
+  group_id           TEXT NOT NULL REFERENCES study_group (record_id) ON DELETE CASCADE,
+  user_id            TEXT NOT NULL,
+  progress           TEXT,
+  progress_week      DATE,
+  joined_at          TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+  UNIQUE (group_id, user_id)
+);
@@ -0,0 +1,18 @@
+CREATE TABLE IF NOT EXISTS retreat (
+  record_id          TEXT PRIMARY KEY,
+  guild_id           TEXT NOT NULL,
+  user_id            TEXT NOT NULL,
+  start_date         DATE NOT NULL,
+  end_date           DATE NOT NULL,
+  minutes_per_day    INTEGER NOT NULL,
+  status             TEXT DEFAULT 'open' NOT NULL,
+  handled_by         TEXT,
+  handled_at         TIMESTAMP WITH TIME ZONE,
+  created_at         TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS retreat_user_idx ON retreat (guild_id, user_id);
+
+-- Entries expanded from a retreat are linked to it, so they can be removed together
+ALTER TABLE meditation ADD COLUMN IF NOT EXISTS retreat_id TEXT REFERENCES retreat (record_id) ON DELETE CASCADE;
+CREATE INDEX IF NOT EXISTS meditation_retreat_id_idx ON meditation (retreat_id);
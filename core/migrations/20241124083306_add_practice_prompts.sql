@@ -0,0 +1,20 @@
+CREATE TABLE IF NOT EXISTS practice_prompt (
+  record_id          TEXT PRIMARY KEY,
+  guild_id           TEXT NOT NULL,
+  category           TEXT NOT NULL,
+  prompt             TEXT NOT NULL,
+  added_by           TEXT NOT NULL,
+  created_at         TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS practice_prompt_guild_idx ON practice_prompt (guild_id, category);
+
+CREATE TABLE IF NOT EXISTS intention_subscription (
+  record_id          TEXT PRIMARY KEY,
+  guild_id           TEXT NOT NULL,
+  user_id            TEXT NOT NULL,
+  reminder_hour      SMALLINT NOT NULL,
+  sent_on            DATE,
+  created_at         TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+  UNIQUE (guild_id, user_id)
+);
@@ -0,0 +1,12 @@
+CREATE TABLE IF NOT EXISTS sutta_cache (
+  record_id          TEXT PRIMARY KEY,
+  sutta_uid          TEXT NOT NULL,
+  translator         TEXT NOT NULL,
+  author             TEXT NOT NULL,
+  lang               TEXT NOT NULL,
+  title              TEXT NOT NULL,
+  translated_title   TEXT,
+  passage            TEXT NOT NULL,
+  fetched_at         TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+  UNIQUE (sutta_uid, translator)
+);
@@ -0,0 +1,17 @@
+CREATE TABLE IF NOT EXISTS feed_subscription (
+  record_id          TEXT PRIMARY KEY,
+  guild_id           TEXT NOT NULL,
+  channel_id         TEXT NOT NULL,
+  url                TEXT NOT NULL,
+  title              TEXT NOT NULL,
+  added_by           TEXT NOT NULL,
+  created_at         TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+  UNIQUE (guild_id, channel_id, url)
+);
+
+CREATE TABLE IF NOT EXISTS feed_item (
+  subscription_id    TEXT NOT NULL REFERENCES feed_subscription (record_id) ON DELETE CASCADE,
+  item_key           TEXT NOT NULL,
+  seen_at            TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+  PRIMARY KEY (subscription_id, item_key)
+);
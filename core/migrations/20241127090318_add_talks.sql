@@ -0,0 +1,12 @@
+CREATE TABLE IF NOT EXISTS talk (
+  record_id          TEXT PRIMARY KEY,
+  guild_id           TEXT NOT NULL,
+  name               TEXT NOT NULL,
+  url                TEXT NOT NULL,
+  added_by           TEXT NOT NULL,
+  created_at         TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS talk_name_idx ON talk (guild_id, LOWER(name));
+
+ALTER TABLE guild_settings ADD COLUMN IF NOT EXISTS talk_role TEXT;
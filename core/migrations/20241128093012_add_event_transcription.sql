@@ -0,0 +1 @@
+ALTER TABLE community_event ADD COLUMN IF NOT EXISTS transcribe BOOLEAN NOT NULL DEFAULT FALSE;
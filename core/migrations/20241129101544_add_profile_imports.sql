@@ -0,0 +1,27 @@
+CREATE TABLE IF NOT EXISTS profile_link (
+  token_hash         TEXT PRIMARY KEY,
+  guild_id           TEXT NOT NULL,
+  user_id            TEXT NOT NULL,
+  expires_at         TIMESTAMP WITH TIME ZONE NOT NULL,
+  used_at            TIMESTAMP WITH TIME ZONE,
+  created_at         TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS profile_link_user_idx ON profile_link (guild_id, user_id);
+
+CREATE TABLE IF NOT EXISTS profile_import (
+  record_id          TEXT PRIMARY KEY,
+  guild_id           TEXT NOT NULL,
+  user_id            TEXT NOT NULL,
+  source_guild_id    TEXT NOT NULL,
+  entries            INTEGER NOT NULL,
+  created_at         TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS profile_import_user_idx ON profile_import (guild_id, user_id);
+
+-- Entries copied from another guild are linked to the import and the entry they were copied
+-- from, so the same entry is never imported twice
+ALTER TABLE meditation ADD COLUMN IF NOT EXISTS import_id TEXT REFERENCES profile_import (record_id) ON DELETE CASCADE;
+ALTER TABLE meditation ADD COLUMN IF NOT EXISTS source_id TEXT;
+CREATE INDEX IF NOT EXISTS meditation_import_id_idx ON meditation (import_id);
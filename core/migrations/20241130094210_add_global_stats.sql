@@ -0,0 +1 @@
+ALTER TABLE guild_settings ADD COLUMN IF NOT EXISTS global_stats BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,18 @@
+CREATE TABLE IF NOT EXISTS link_code (
+  code_hash          TEXT PRIMARY KEY,
+  user_id            TEXT NOT NULL,
+  expires_at         TIMESTAMP WITH TIME ZONE NOT NULL,
+  used_at            TIMESTAMP WITH TIME ZONE,
+  created_at         TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS link_code_user_idx ON link_code (user_id);
+
+CREATE TABLE IF NOT EXISTS api_token (
+  token_hash         TEXT PRIMARY KEY,
+  user_id            TEXT NOT NULL,
+  expires_at         TIMESTAMP WITH TIME ZONE NOT NULL,
+  created_at         TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS api_token_user_idx ON api_token (user_id);
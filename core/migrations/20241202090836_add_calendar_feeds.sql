@@ -0,0 +1,20 @@
+CREATE TABLE IF NOT EXISTS calendar_feed (
+  guild_id           TEXT PRIMARY KEY,
+  token              TEXT NOT NULL,
+  created_at         TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS calendar_feed_token_idx ON calendar_feed (token);
+
+-- Copies of Discord scheduled events, such as community sits, for calendar feeds
+CREATE TABLE IF NOT EXISTS discord_event (
+  record_id          TEXT PRIMARY KEY,
+  guild_id           TEXT NOT NULL,
+  name               TEXT NOT NULL,
+  description        TEXT,
+  location           TEXT,
+  starts_at          TIMESTAMP WITH TIME ZONE NOT NULL,
+  ends_at            TIMESTAMP WITH TIME ZONE
+);
+
+CREATE INDEX IF NOT EXISTS discord_event_guild_idx ON discord_event (guild_id);
@@ -0,0 +1,9 @@
+CREATE TABLE IF NOT EXISTS webhook_token (
+  token_hash         TEXT PRIMARY KEY,
+  guild_id           TEXT NOT NULL,
+  user_id            TEXT NOT NULL,
+  last_used_at       TIMESTAMP WITH TIME ZONE,
+  created_at         TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS webhook_token_member_idx ON webhook_token (guild_id, user_id);
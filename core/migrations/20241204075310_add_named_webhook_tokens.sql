@@ -0,0 +1,18 @@
+ALTER TABLE webhook_token
+  ADD COLUMN IF NOT EXISTS record_id TEXT,
+  ADD COLUMN IF NOT EXISTS name TEXT,
+  ADD COLUMN IF NOT EXISTS hourly_limit INTEGER NOT NULL DEFAULT 10,
+  ADD COLUMN IF NOT EXISTS window_started_at TIMESTAMP WITH TIME ZONE,
+  ADD COLUMN IF NOT EXISTS window_uses INTEGER NOT NULL DEFAULT 0;
+
+-- Members previously had a single token per guild, so existing tokens share a name.
+UPDATE webhook_token SET record_id = LEFT(token_hash, 26), name = 'Webhook' WHERE record_id IS NULL;
+
+ALTER TABLE webhook_token
+  ALTER COLUMN record_id SET NOT NULL,
+  ALTER COLUMN name SET NOT NULL;
+
+DROP INDEX IF EXISTS webhook_token_member_idx;
+
+CREATE UNIQUE INDEX IF NOT EXISTS webhook_token_record_idx ON webhook_token (record_id);
+CREATE UNIQUE INDEX IF NOT EXISTS webhook_token_name_idx ON webhook_token (guild_id, user_id, name);
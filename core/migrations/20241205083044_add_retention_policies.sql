@@ -0,0 +1,19 @@
+CREATE TABLE IF NOT EXISTS retention_policy (
+  guild_id            TEXT PRIMARY KEY,
+  erase_days          INTEGER,
+  prune_star_messages BOOLEAN NOT NULL DEFAULT FALSE,
+  archive_years       INTEGER,
+  dry_run             BOOLEAN NOT NULL DEFAULT TRUE,
+  last_run_at         TIMESTAMP WITH TIME ZONE,
+  created_at          TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Archived entries are moved here unchanged, so columns added to meditation must also be
+-- added to meditation_archive, in the same order.
+CREATE TABLE IF NOT EXISTS meditation_archive (LIKE meditation INCLUDING DEFAULTS);
+
+CREATE INDEX IF NOT EXISTS meditation_archive_guild_user_idx ON meditation_archive (guild_id, user_id);
+
+-- When each starred message was last checked for having been deleted, so pruning checks the
+-- least recently checked messages first.
+ALTER TABLE star ADD COLUMN IF NOT EXISTS checked_at TIMESTAMP WITH TIME ZONE;
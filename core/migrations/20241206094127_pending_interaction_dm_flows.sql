@@ -0,0 +1,5 @@
+-- Paginators and confirmations can be used in DMs, and are looked up by the custom IDs of
+-- their buttons rather than their message.
+ALTER TABLE pending_interaction
+  ALTER COLUMN guild_id DROP NOT NULL,
+  DROP COLUMN IF EXISTS message_id;
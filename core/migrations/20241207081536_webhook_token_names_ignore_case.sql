@@ -0,0 +1,11 @@
+-- Token names are compared without regard to case. Names which only differ in case are
+-- made unique first, keeping the oldest token's name as it is.
+UPDATE webhook_token SET name = name || ' (' || RIGHT(record_id, 6) || ')'
+WHERE record_id NOT IN (
+  SELECT DISTINCT ON (guild_id, user_id, lower(name)) record_id
+  FROM webhook_token
+  ORDER BY guild_id, user_id, lower(name), created_at
+);
+
+DROP INDEX IF EXISTS webhook_token_name_idx;
+CREATE UNIQUE INDEX IF NOT EXISTS webhook_token_name_idx ON webhook_token (guild_id, user_id, lower(name));
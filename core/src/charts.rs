@@ -0,0 +1,55 @@
+use poise::ChoiceParameter;
+
+/// The image format of a chart. WebP is the smallest, while high resolution PNG and SVG
+/// stay sharp on high-DPI displays. SVG charts can't be shown in embeds, so they're attached.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ChoiceParameter)]
+pub enum ChartFormat {
+  #[default]
+  #[name = "standard"]
+  Standard,
+  #[name = "high resolution (2x PNG)"]
+  HighRes,
+  #[name = "SVG"]
+  Svg,
+}
+
+impl ChartFormat {
+  /// The value stored in the database.
+  pub fn as_str(self) -> &'static str {
+    match self {
+      Self::Standard => "standard",
+      Self::HighRes => "hires",
+      Self::Svg => "svg",
+    }
+  }
+
+  /// Reads a value stored in the database, using [`ChartFormat::Standard`] for unknown values.
+  pub fn from_db(value: &str) -> Self {
+    match value {
+      "hires" => Self::HighRes,
+      "svg" => Self::Svg,
+      _ => Self::Standard,
+    }
+  }
+
+  pub fn filename(self) -> &'static str {
+    match self {
+      Self::Standard => "attachment.webp",
+      Self::HighRes => "attachment.png",
+      Self::Svg => "attachment.svg",
+    }
+  }
+
+  pub fn content_type(self) -> &'static str {
+    match self {
+      Self::Standard => "image/webp",
+      Self::HighRes => "image/png",
+      Self::Svg => "image/svg+xml",
+    }
+  }
+
+  /// Whether the chart can be shown as an embed image.
+  pub fn is_embeddable(self) -> bool {
+    !matches!(self, Self::Svg)
+  }
+}
@@ -0,0 +1,186 @@
+use chrono::{DateTime, Duration, Utc};
+use poise::serenity_prelude::UserId;
+use rand::Rng;
+use sha2::{Digest, Sha256};
+use sqlx::postgres::{PgArguments, PgRow};
+use sqlx::query::{Query, QueryAs, QueryScalar};
+use sqlx::{Error as SqlxError, FromRow, Postgres, Result as SqlxResult, Row};
+
+use crate::data::common;
+use crate::database::InsertQuery;
+
+/// The characters link codes are made of, leaving out ones which are easily confused, such
+/// as `0` and `O`, since members type them into the dashboard by hand.
+const CODE_CHARS: &[u8] = b"23456789ABCDEFGHJKLMNPQRSTUVWXYZ";
+/// Codes are shown in groups of this many characters, separated by dashes.
+const CODE_GROUP_LENGTH: usize = 4;
+const CODE_GROUPS: usize = 3;
+/// How long a link code can be exchanged for, in minutes.
+pub const CODE_EXPIRY_MINUTES: i64 = 10;
+
+/// Hashes a link code or API token for storage and lookup. Only hashes are stored, so a
+/// leaked database can't be used to sign in. Case, dashes, and whitespace are ignored, so a
+/// code still matches if it's retyped slightly differently.
+pub fn hash_secret(secret: &str) -> String {
+  let normalized: String = secret
+    .chars()
+    .filter(char::is_ascii_alphanumeric)
+    .map(|c| c.to_ascii_uppercase())
+    .collect();
+
+  hex::encode(Sha256::digest(normalized.as_bytes()))
+}
+
+/// A one-time code from `/link`, which the dashboard exchanges for an [`ApiToken`] with
+/// [`DatabaseHandler::exchange_link_code`][exchange]. Proves that whoever enters the code
+/// controls the Discord account, without Bloom handling any passwords.
+///
+/// [exchange]: crate::database::DatabaseHandler::exchange_link_code
+pub struct LinkCode {
+  pub code_hash: String,
+  pub user_id: UserId,
+  pub expires_at: DateTime<Utc>,
+}
+
+impl LinkCode {
+  /// Generates a new code for a member, such as `7KQ2-MX9D-4HWT`. Returns the code to show
+  /// them, along with the [`LinkCode`] to store, which only holds its hash.
+  pub fn generate(user_id: UserId) -> (String, Self) {
+    let mut rng = rand::thread_rng();
+    let code = (0..CODE_GROUPS)
+      .map(|_| {
+        (0..CODE_GROUP_LENGTH)
+          .map(|_| char::from(CODE_CHARS[rng.gen_range(0..CODE_CHARS.len())]))
+          .collect::<String>()
+      })
+      .collect::<Vec<_>>()
+      .join("-");
+
+    let link_code = Self {
+      code_hash: hash_secret(&code),
+      user_id,
+      expires_at: Utc::now() + Duration::minutes(CODE_EXPIRY_MINUTES),
+    };
+
+    (code, link_code)
+  }
+
+  /// Marks a code as used, returning the ID of the member it was issued to. Returns nothing
+  /// if the code doesn't exist, has expired, or has already been used, so each code can
+  /// only be exchanged once.
+  pub fn redeem(code_hash: &str) -> QueryScalar<'_, Postgres, String, PgArguments> {
+    sqlx::query_scalar(
+      "UPDATE link_code SET used_at = NOW() WHERE code_hash = $1 AND used_at IS NULL AND expires_at > NOW() RETURNING user_id",
+    )
+    .bind(code_hash)
+  }
+
+  /// Removes a member's unused codes, so only their newest code works.
+  pub fn remove_unused<'a>(user_id: UserId) -> Query<'a, Postgres, PgArguments> {
+    query!(
+      "DELETE FROM link_code WHERE user_id = $1 AND used_at IS NULL",
+      user_id.to_string(),
+    )
+  }
+}
+
+impl InsertQuery for LinkCode {
+  fn insert_query(&self) -> Query<Postgres, PgArguments> {
+    query!(
+      "INSERT INTO link_code (code_hash, user_id, expires_at) VALUES ($1, $2, $3)",
+      self.code_hash,
+      self.user_id.to_string(),
+      self.expires_at,
+    )
+  }
+}
+
+/// A token the dashboard and REST API use to act for a member, issued in exchange for a
+/// [`LinkCode`]. Sent as a bearer token and looked up by its hash with
+/// [`DatabaseHandler::get_api_token`][get].
+///
+/// [get]: crate::database::DatabaseHandler::get_api_token
+pub struct ApiToken {
+  pub token_hash: String,
+  pub user_id: UserId,
+  pub expires_at: DateTime<Utc>,
+}
+
+impl ApiToken {
+  /// Generates a new token for a member, valid for `lifetime`. Returns the token to send to
+  /// the client, along with the [`ApiToken`] to store, which only holds its hash.
+  pub fn generate(user_id: UserId, lifetime: Duration) -> (String, Self) {
+    let token = hex::encode(rand::thread_rng().gen::<[u8; 32]>());
+
+    let api_token = Self {
+      token_hash: hash_secret(&token),
+      user_id,
+      expires_at: Utc::now() + lifetime,
+    };
+
+    (token, api_token)
+  }
+
+  /// Retrieves an [`ApiToken`] by its hash, unless it has expired.
+  pub fn retrieve(token_hash: &str) -> QueryAs<'_, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT token_hash, user_id, expires_at FROM api_token WHERE token_hash = $1 AND expires_at > NOW()",
+    )
+    .bind(token_hash)
+  }
+
+  /// Removes every [`ApiToken`] issued to a member, signing them out of the dashboard
+  /// everywhere.
+  pub fn remove_all<'a>(user_id: UserId) -> Query<'a, Postgres, PgArguments> {
+    query!(
+      "DELETE FROM api_token WHERE user_id = $1",
+      user_id.to_string(),
+    )
+  }
+}
+
+impl InsertQuery for ApiToken {
+  fn insert_query(&self) -> Query<Postgres, PgArguments> {
+    query!(
+      "INSERT INTO api_token (token_hash, user_id, expires_at) VALUES ($1, $2, $3)",
+      self.token_hash,
+      self.user_id.to_string(),
+      self.expires_at,
+    )
+  }
+}
+
+impl FromRow<'_, PgRow> for ApiToken {
+  fn from_row(row: &'_ PgRow) -> SqlxResult<Self, SqlxError> {
+    Ok(Self {
+      token_hash: row.try_get("token_hash")?,
+      user_id: UserId::new(common::decode_id_row(row, "user_id")?),
+      expires_at: row.try_get("expires_at")?,
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_generate_code() {
+    let (code, link_code) = LinkCode::generate(UserId::new(123u64));
+    assert_eq!(code.len(), CODE_GROUPS * (CODE_GROUP_LENGTH + 1) - 1);
+    assert!(code.split('-').all(
+      |group| group.len() == CODE_GROUP_LENGTH && group.bytes().all(|c| CODE_CHARS.contains(&c))
+    ));
+    assert_eq!(link_code.code_hash, hash_secret(&code));
+  }
+
+  #[test]
+  fn test_hash_secret() {
+    assert_eq!(
+      hash_secret("7KQ2-MX9D-4HWT"),
+      hash_secret(" 7kq2 mx9d 4hwt ")
+    );
+    assert_ne!(hash_secret("7KQ2-MX9D-4HWT"), hash_secret("7KQ2-MX9D-4HWV"));
+    assert_eq!(hash_secret("7KQ2-MX9D-4HWT").len(), 64);
+  }
+}
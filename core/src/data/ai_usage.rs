@@ -0,0 +1,77 @@
+use chrono::{NaiveDate, Utc};
+use poise::serenity_prelude::GuildId;
+use sqlx::postgres::PgArguments;
+use sqlx::query::{Query, QueryAs};
+use sqlx::{FromRow, Postgres};
+
+/// The cost of the embedding model, in US dollars per million tokens.
+pub const COST_PER_MILLION_TOKENS: f64 = 0.10;
+
+/// OpenAI API usage for a guild, aggregated over a period of time.
+#[derive(Debug, Default, FromRow)]
+pub struct AiUsage {
+  pub requests: i64,
+  pub tokens: i64,
+}
+
+/// OpenAI API usage for a guild on a single day (UTC).
+#[derive(Debug, FromRow)]
+pub struct DailyAiUsage {
+  pub usage_date: NaiveDate,
+  pub requests: i64,
+  pub tokens: i64,
+}
+
+impl AiUsage {
+  /// Records a single request using the specified number of `tokens` against the current
+  /// day's usage for the guild.
+  pub fn record<'a>(guild_id: GuildId, tokens: i64) -> Query<'a, Postgres, PgArguments> {
+    query!(
+      "INSERT INTO ai_usage (guild_id, usage_date, requests, tokens) VALUES ($1, $2, 1, $3) ON CONFLICT (guild_id, usage_date) DO UPDATE SET requests = ai_usage.requests + 1, tokens = ai_usage.tokens + $3",
+      guild_id.to_string(),
+      Utc::now().date_naive(),
+      tokens,
+    )
+  }
+
+  /// Retrieves the total [`AiUsage`] for the guild from `start_date` onwards.
+  pub fn retrieve_total<'a>(
+    guild_id: GuildId,
+    start_date: NaiveDate,
+  ) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT COALESCE(SUM(requests), 0)::BIGINT AS requests, COALESCE(SUM(tokens), 0)::BIGINT AS tokens FROM ai_usage WHERE guild_id = $1 AND usage_date >= $2",
+    )
+    .bind(guild_id.to_string())
+    .bind(start_date)
+  }
+
+  /// Retrieves the [`DailyAiUsage`] for the guild from `start_date` onwards, most recent first.
+  pub fn retrieve_daily<'a>(
+    guild_id: GuildId,
+    start_date: NaiveDate,
+  ) -> QueryAs<'a, Postgres, DailyAiUsage, PgArguments> {
+    sqlx::query_as(
+      "SELECT usage_date, requests, tokens FROM ai_usage WHERE guild_id = $1 AND usage_date >= $2 ORDER BY usage_date DESC",
+    )
+    .bind(guild_id.to_string())
+    .bind(start_date)
+  }
+
+  /// Calculates the estimated cost of the usage, in US dollars.
+  pub fn cost(&self) -> f64 {
+    estimated_cost(self.tokens)
+  }
+}
+
+impl DailyAiUsage {
+  /// Calculates the estimated cost of the usage, in US dollars.
+  pub fn cost(&self) -> f64 {
+    estimated_cost(self.tokens)
+  }
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn estimated_cost(tokens: i64) -> f64 {
+  tokens as f64 / 1_000_000.0 * COST_PER_MILLION_TOKENS
+}
@@ -0,0 +1,192 @@
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use poise::serenity_prelude::GuildId;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// The version of the backup format. Backups with a newer version can't be restored.
+pub const BACKUP_VERSION: u32 = 1;
+
+/// The largest backup which can be restored, once decompressed, in bytes.
+const MAX_RESTORE_SIZE: u64 = 512 * 1024 * 1024;
+
+/// A table included in guild backups. Tables are listed in the order they're restored, so
+/// that tables are restored before the tables which reference them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BackupTable {
+  GuildSettings,
+  GuildEmoji,
+  GuildFeature,
+  Quote,
+  Term,
+  Course,
+  CourseEnrollmentCode,
+  CourseEnrollment,
+  Meditation,
+  TrackingProfile,
+  Streak,
+}
+
+impl BackupTable {
+  pub const ALL: [Self; 11] = [
+    Self::GuildSettings,
+    Self::GuildEmoji,
+    Self::GuildFeature,
+    Self::Quote,
+    Self::Term,
+    Self::Course,
+    Self::CourseEnrollmentCode,
+    Self::CourseEnrollment,
+    Self::Meditation,
+    Self::TrackingProfile,
+    Self::Streak,
+  ];
+
+  /// The name of the table, which is also its key in a [`Backup`]. Every table has a
+  /// `guild_id` column, which is used to select the guild's rows.
+  pub fn as_str(self) -> &'static str {
+    match self {
+      Self::GuildSettings => "guild_settings",
+      Self::GuildEmoji => "guild_emoji",
+      Self::GuildFeature => "guild_feature",
+      Self::Quote => "quote",
+      Self::Term => "term",
+      Self::Course => "course",
+      Self::CourseEnrollmentCode => "course_enrollment_code",
+      Self::CourseEnrollment => "course_enrollment",
+      Self::Meditation => "meditation",
+      Self::TrackingProfile => "tracking_profile",
+      Self::Streak => "streak",
+    }
+  }
+}
+
+/// A guild's data, with each table's rows stored as they're returned by Postgres'
+/// `json_agg`, so that every column is included without listing them here.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Backup {
+  pub version: u32,
+  pub guild_id: GuildId,
+  pub created_at: DateTime<Utc>,
+  pub tables: BTreeMap<String, Value>,
+}
+
+impl Backup {
+  pub fn new(guild_id: GuildId) -> Self {
+    Self {
+      version: BACKUP_VERSION,
+      guild_id,
+      created_at: Utc::now(),
+      tables: BTreeMap::new(),
+    }
+  }
+
+  /// Adds a table's rows, given as a JSON array.
+  ///
+  /// # Errors
+  /// Returns an error if the rows aren't valid JSON.
+  pub fn add_table(&mut self, table: BackupTable, rows: &str) -> Result<()> {
+    let rows: Value = serde_json::from_str(rows)
+      .with_context(|| format!("Invalid rows for table {}", table.as_str()))?;
+    self.tables.insert(table.as_str().to_owned(), rows);
+
+    Ok(())
+  }
+
+  /// Returns a table's rows as a JSON array, or `None` if the table isn't in the backup.
+  pub fn table(&self, table: BackupTable) -> Option<String> {
+    self.tables.get(table.as_str()).map(Value::to_string)
+  }
+
+  /// The number of rows in each table.
+  pub fn row_counts(&self) -> Vec<(BackupTable, usize)> {
+    BackupTable::ALL
+      .into_iter()
+      .filter_map(|table| {
+        let rows = self.tables.get(table.as_str())?;
+        Some((table, rows.as_array().map_or(0, Vec::len)))
+      })
+      .collect()
+  }
+
+  /// Serializes the backup as gzip-compressed JSON.
+  ///
+  /// # Errors
+  /// Returns an error if the backup can't be serialized or compressed.
+  pub fn to_gzip(&self) -> Result<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    serde_json::to_writer(&mut encoder, self)?;
+    encoder.write_all(b"\n")?;
+
+    Ok(encoder.finish()?)
+  }
+
+  /// Reads a backup from gzip-compressed JSON, such as the parts of a backup joined back
+  /// together.
+  ///
+  /// # Errors
+  /// Returns an error if the data isn't a valid backup, or was made by a newer version of
+  /// Bloom.
+  pub fn from_gzip(bytes: &[u8]) -> Result<Self> {
+    let mut json = Vec::new();
+    GzDecoder::new(bytes)
+      .take(MAX_RESTORE_SIZE + 1)
+      .read_to_end(&mut json)
+      .with_context(|| "Backup is not a valid gzip file")?;
+    if json.len() as u64 > MAX_RESTORE_SIZE {
+      bail!(
+        "Backup is larger than {} MB",
+        MAX_RESTORE_SIZE / 1024 / 1024
+      );
+    }
+
+    let backup: Self =
+      serde_json::from_slice(&json).with_context(|| "Backup is not a valid Bloom backup")?;
+    if backup.version > BACKUP_VERSION {
+      bail!(
+        "Backup was made with a newer version of Bloom (version {})",
+        backup.version
+      );
+    }
+
+    Ok(backup)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_backup_round_trip() -> Result<()> {
+    let mut backup = Backup::new(GuildId::new(123u64));
+    backup.add_table(
+      BackupTable::Quote,
+      r#"[{"record_id": "01", "quote": "Breathe.", "author": null, "guild_id": "123"}]"#,
+    )?;
+    backup.add_table(BackupTable::Term, "[]")?;
+    assert!(backup.add_table(BackupTable::Course, "[{").is_err());
+
+    let restored = Backup::from_gzip(&backup.to_gzip()?)?;
+    assert_eq!(restored, backup);
+    assert_eq!(
+      restored.row_counts(),
+      vec![(BackupTable::Quote, 1), (BackupTable::Term, 0)]
+    );
+    assert!(restored.table(BackupTable::Meditation).is_none());
+
+    assert!(Backup::from_gzip(b"not a backup").is_err());
+
+    let mut newer = Backup::new(GuildId::new(123u64));
+    newer.version = BACKUP_VERSION + 1;
+    assert!(Backup::from_gzip(&newer.to_gzip()?).is_err());
+
+    Ok(())
+  }
+}
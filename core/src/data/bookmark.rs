@@ -5,14 +5,15 @@ use sqlx::query::{Query, QueryAs};
 use sqlx::{Error as SqlxError, FromRow, Postgres, Result as SqlxResult, Row};
 use ulid::Ulid;
 
-use crate::commands::helpers::pagination::{PageRow, PageType};
 use crate::data::common;
-use crate::handlers::database::{DeleteQuery, InsertQuery};
+use crate::database::{InsertQuery, UpdateQuery};
+use crate::pagination::{PageRow, PageType};
 
 #[derive(Default)]
 pub struct Bookmark {
   id: String,
-  guild_id: GuildId,
+  /// [`None`] for bookmarks added in DMs.
+  guild_id: Option<GuildId>,
   user_id: UserId,
   pub link: String,
   pub description: Option<String>,
@@ -20,8 +21,8 @@ pub struct Bookmark {
 }
 
 impl Bookmark {
-  pub(crate) fn new(
-    guild_id: GuildId,
+  pub fn new(
+    guild_id: Option<GuildId>,
     user_id: UserId,
     link: String,
     description: Option<String>,
@@ -44,45 +45,74 @@ impl Bookmark {
     self.added.as_ref()
   }
 
-  /// Retrieves the total number of [`Bookmark`]s for the specified `user_id`.
+  /// Retrieves the total number of [`Bookmark`]s for the specified `user_id`. If `guild_id`
+  /// is [`None`], only bookmarks added in DMs are counted.
   pub fn user_total<'a, T: for<'r> FromRow<'r, PgRow>>(
-    guild_id: GuildId,
+    guild_id: Option<GuildId>,
     user_id: UserId,
   ) -> QueryAs<'a, Postgres, T, PgArguments> {
     sqlx::query_as(
-      "SELECT COUNT(record_id) AS count FROM bookmarks WHERE user_id = $1 AND guild_id = $2",
+      "SELECT COUNT(record_id) AS count FROM bookmarks WHERE user_id = $1 AND guild_id IS NOT DISTINCT FROM $2",
     )
     .bind(user_id.to_string())
-    .bind(guild_id.to_string())
+    .bind(guild_id.map(|guild_id| guild_id.to_string()))
   }
 
-  /// Retrieves all [`Bookmark`]s for the specified `user_id`.
+  /// Retrieves all [`Bookmark`]s for the specified `user_id`. If `guild_id` is [`None`],
+  /// only bookmarks added in DMs are retrieved.
   pub fn retrieve_all<'a>(
-    guild_id: GuildId,
+    guild_id: Option<GuildId>,
     user_id: UserId,
   ) -> QueryAs<'a, Postgres, Self, PgArguments> {
     sqlx::query_as(
-      "SELECT record_id, message_link, user_desc, occurred_at FROM bookmarks WHERE guild_id = $1 AND user_id = $2 ORDER BY occurred_at ASC",
+      "SELECT record_id, message_link, user_desc, occurred_at FROM bookmarks WHERE guild_id IS NOT DISTINCT FROM $1 AND user_id = $2 ORDER BY occurred_at ASC",
     )
-    .bind(guild_id.to_string())
+    .bind(guild_id.map(|guild_id| guild_id.to_string()))
     .bind(user_id.to_string())
   }
 
+  /// Retrieves the [`Bookmark`] a user has added for the specified `link`, if any.
+  pub fn retrieve_by_link<'a>(
+    user_id: UserId,
+    link: &str,
+  ) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT record_id, user_id, guild_id, message_link, user_desc, occurred_at FROM bookmarks WHERE user_id = $1 AND message_link = $2",
+    )
+    .bind(user_id.to_string())
+    .bind(link.to_string())
+  }
+
   /// Searches a user's [`Bookmark`]s using a [PostgreSQL websearch query][ws] defined in `keyword`.
   ///
   /// [ws]: https://www.postgresql.org/docs/17/textsearch-controls.html#TEXTSEARCH-PARSING-QUERIES
   pub fn search<'a>(
-    guild_id: GuildId,
+    guild_id: Option<GuildId>,
     user_id: UserId,
     keyword: &str,
   ) -> QueryAs<'a, Postgres, Self, PgArguments> {
     sqlx::query_as(
-      "SELECT record_id, message_link, user_desc, occurred_at FROM bookmarks WHERE user_id = $1 AND guild_id = $2 AND (desc_tsv @@ websearch_to_tsquery('english', $3)) ORDER BY ts_rank(desc_tsv, websearch_to_tsquery('english', $3)) DESC",
+      "SELECT record_id, message_link, user_desc, occurred_at FROM bookmarks WHERE user_id = $1 AND guild_id IS NOT DISTINCT FROM $2 AND (desc_tsv @@ websearch_to_tsquery('english', $3)) ORDER BY ts_rank(desc_tsv, websearch_to_tsquery('english', $3)) DESC",
     )
     .bind(user_id.to_string())
-    .bind(guild_id.to_string())
+    .bind(guild_id.map(|guild_id| guild_id.to_string()))
     .bind(keyword.to_string())
   }
+
+  /// Deletes one of a user's [`Bookmark`]s. If `guild_id` is [`None`], only bookmarks
+  /// added in DMs can be deleted.
+  pub fn remove<'a>(
+    guild_id: Option<GuildId>,
+    user_id: UserId,
+    id: impl Into<String>,
+  ) -> Query<'a, Postgres, PgArguments> {
+    query!(
+      "DELETE FROM bookmarks WHERE record_id = $1 AND user_id = $2 AND guild_id IS NOT DISTINCT FROM $3",
+      id.into(),
+      user_id.to_string(),
+      guild_id.map(|guild_id| guild_id.to_string()),
+    )
+  }
 }
 
 impl InsertQuery for Bookmark {
@@ -92,19 +122,22 @@ impl InsertQuery for Bookmark {
       "INSERT INTO bookmarks (record_id, user_id, guild_id, message_link, user_desc) VALUES ($1, $2, $3, $4, $5)",
       self.id,
       self.user_id.to_string(),
-      self.guild_id.to_string(),
+      self.guild_id.map(|guild_id| guild_id.to_string()),
       self.link,
       self.description,
     )
   }
 }
 
-impl DeleteQuery for Bookmark {
-  fn delete_query<'a>(
-    _guild_id: GuildId,
-    id: impl Into<String>,
-  ) -> Query<'a, Postgres, PgArguments> {
-    query!("DELETE FROM bookmarks WHERE record_id = $1", id.into())
+impl UpdateQuery for Bookmark {
+  /// Updates the description of a [`Bookmark`].
+  fn update_query(&self) -> Query<Postgres, PgArguments> {
+    query!(
+      "UPDATE bookmarks SET user_desc = $1 WHERE record_id = $2 AND user_id = $3",
+      self.description,
+      self.id,
+      self.user_id.to_string(),
+    )
   }
 }
 
@@ -133,7 +166,7 @@ impl PageRow for Bookmark {
 
 impl FromRow<'_, PgRow> for Bookmark {
   fn from_row(row: &'_ PgRow) -> SqlxResult<Self, SqlxError> {
-    let guild_id = GuildId::new(common::decode_id_row(row, "guild_id")?);
+    let guild_id = common::decode_option_id_row(row, "guild_id")?.map(GuildId::new);
     let user_id = UserId::new(common::decode_id_row(row, "user_id")?);
 
     Ok(Self {
@@ -0,0 +1,132 @@
+use chrono::{DateTime, Utc};
+use poise::serenity_prelude::GuildId;
+use rand::Rng;
+use sqlx::postgres::{PgArguments, PgRow};
+use sqlx::query::{Query, QueryAs};
+use sqlx::{Error as SqlxError, FromRow, Postgres, Result as SqlxResult, Row};
+
+use crate::data::common;
+use crate::database::{DeleteQuery, InsertQuery};
+
+/// The secret part of a guild's calendar feed URL, which members subscribe to in their
+/// calendar apps. Feeds are looked up by token rather than guild ID, so they can't be found
+/// by guessing, and staff can reset the token to stop a leaked URL from working.
+pub struct CalendarFeed {
+  pub guild_id: GuildId,
+  pub token: String,
+}
+
+impl CalendarFeed {
+  /// Creates a [`CalendarFeed`] with a new random token.
+  pub fn new(guild_id: GuildId) -> Self {
+    Self {
+      guild_id,
+      token: hex::encode(rand::thread_rng().gen::<[u8; 16]>()),
+    }
+  }
+
+  pub fn retrieve<'a>(guild_id: GuildId) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as("SELECT guild_id, token FROM calendar_feed WHERE guild_id = $1")
+      .bind(guild_id.to_string())
+  }
+
+  pub fn retrieve_by_token(token: &str) -> QueryAs<'_, Postgres, Self, PgArguments> {
+    sqlx::query_as("SELECT guild_id, token FROM calendar_feed WHERE token = $1").bind(token)
+  }
+}
+
+impl InsertQuery for CalendarFeed {
+  /// Saves a [`CalendarFeed`], replacing the guild's previous token.
+  fn insert_query(&self) -> Query<Postgres, PgArguments> {
+    query!(
+      "INSERT INTO calendar_feed (guild_id, token) VALUES ($1, $2) ON CONFLICT (guild_id) DO UPDATE SET token = EXCLUDED.token",
+      self.guild_id.to_string(),
+      self.token,
+    )
+  }
+}
+
+impl FromRow<'_, PgRow> for CalendarFeed {
+  fn from_row(row: &'_ PgRow) -> SqlxResult<Self, SqlxError> {
+    Ok(Self {
+      guild_id: GuildId::new(common::decode_id_row(row, "guild_id")?),
+      token: row.try_get("token")?,
+    })
+  }
+}
+
+/// A copy of one of a guild's Discord scheduled events, such as a community sit, kept up to
+/// date from gateway events so calendar feeds can include it without calling Discord.
+pub struct DiscordEvent {
+  /// Discord's ID for the scheduled event.
+  pub id: String,
+  pub guild_id: GuildId,
+  pub name: String,
+  pub description: Option<String>,
+  /// Where the event takes place, either a link to its channel or a place it names.
+  pub location: Option<String>,
+  pub starts_at: DateTime<Utc>,
+  pub ends_at: Option<DateTime<Utc>>,
+}
+
+impl DiscordEvent {
+  /// Retrieves every [`DiscordEvent`] in a guild, in the order they start.
+  pub fn retrieve_all<'a>(guild_id: GuildId) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT record_id, guild_id, name, description, location, starts_at, ends_at FROM discord_event WHERE guild_id = $1 ORDER BY starts_at ASC",
+    )
+    .bind(guild_id.to_string())
+  }
+
+  /// Removes every [`DiscordEvent`] in a guild, so they can be replaced with the current
+  /// list when the guild becomes available.
+  pub fn remove_all<'a>(guild_id: GuildId) -> Query<'a, Postgres, PgArguments> {
+    query!(
+      "DELETE FROM discord_event WHERE guild_id = $1",
+      guild_id.to_string(),
+    )
+  }
+}
+
+impl InsertQuery for DiscordEvent {
+  /// Saves a [`DiscordEvent`], replacing the previous copy of the event.
+  fn insert_query(&self) -> Query<Postgres, PgArguments> {
+    query!(
+      "INSERT INTO discord_event (record_id, guild_id, name, description, location, starts_at, ends_at) VALUES ($1, $2, $3, $4, $5, $6, $7) ON CONFLICT (record_id) DO UPDATE SET name = EXCLUDED.name, description = EXCLUDED.description, location = EXCLUDED.location, starts_at = EXCLUDED.starts_at, ends_at = EXCLUDED.ends_at",
+      self.id,
+      self.guild_id.to_string(),
+      self.name,
+      self.description,
+      self.location,
+      self.starts_at,
+      self.ends_at,
+    )
+  }
+}
+
+impl DeleteQuery for DiscordEvent {
+  fn delete_query<'a>(
+    guild_id: GuildId,
+    event_id: impl Into<String>,
+  ) -> Query<'a, Postgres, PgArguments> {
+    query!(
+      "DELETE FROM discord_event WHERE record_id = $1 AND guild_id = $2",
+      event_id.into(),
+      guild_id.to_string(),
+    )
+  }
+}
+
+impl FromRow<'_, PgRow> for DiscordEvent {
+  fn from_row(row: &'_ PgRow) -> SqlxResult<Self, SqlxError> {
+    Ok(Self {
+      id: row.try_get("record_id")?,
+      guild_id: GuildId::new(common::decode_id_row(row, "guild_id")?),
+      name: row.try_get("name")?,
+      description: row.try_get("description")?,
+      location: row.try_get("location")?,
+      starts_at: row.try_get("starts_at")?,
+      ends_at: row.try_get("ends_at")?,
+    })
+  }
+}
@@ -3,8 +3,8 @@ use sqlx::postgres::{PgArguments, PgRow};
 use sqlx::query::Query;
 use sqlx::{Error as SqlxError, Postgres, Row};
 
-use crate::commands::helpers::time::Timeframe;
-use crate::handlers::database::UpdateQuery;
+use crate::database::UpdateQuery;
+use crate::time::Timeframe;
 
 #[derive(Default, sqlx::FromRow)]
 #[sqlx(default)]
@@ -101,6 +101,15 @@ impl MaterializedView {
   }
 }
 
+/// Escapes the wildcard characters in `text`, so that it is matched literally when used
+/// as part of a `LIKE` or `ILIKE` pattern.
+pub fn escape_like(text: &str) -> String {
+  text
+    .replace('\\', "\\\\")
+    .replace('%', "\\%")
+    .replace('_', "\\_")
+}
+
 pub fn decode_id_row(row: &'_ PgRow, index: &str) -> Result<u64, SqlxError> {
   let string: String = row.try_get(index).unwrap_or("1".to_string());
   match string.parse::<u64>() {
@@ -124,3 +133,62 @@ pub fn decode_option_id_row(row: &'_ PgRow, index: &str) -> Result<Option<u64>,
     Err(_) => Ok(None),
   }
 }
+
+/// The Pearson correlation between the pairs of values in `points`, or [`None`] if there are
+/// fewer than three pairs or either value doesn't vary.
+pub fn correlation(points: &[(f64, f64)]) -> Option<f64> {
+  if points.len() < 3 {
+    return None;
+  }
+
+  #[allow(clippy::cast_precision_loss)]
+  let count = points.len() as f64;
+  let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / count;
+  let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / count;
+
+  let (covariance, x_variance, y_variance) = points.iter().fold(
+    (0.0, 0.0, 0.0),
+    |(covariance, x_variance, y_variance), (x, y)| {
+      let x = x - mean_x;
+      let y = y - mean_y;
+      (covariance + x * y, x_variance + x * x, y_variance + y * y)
+    },
+  );
+  if x_variance == 0.0 || y_variance == 0.0 {
+    return None;
+  }
+
+  Some(covariance / (x_variance * y_variance).sqrt())
+}
+
+/// Describes the strength and direction of a correlation, e.g., `moderate positive`.
+pub fn describe_correlation(correlation: f64) -> &'static str {
+  match correlation {
+    c if c >= 0.7 => "strong positive",
+    c if c >= 0.4 => "moderate positive",
+    c if c >= 0.1 => "weak positive",
+    c if c > -0.1 => "little to no",
+    c if c > -0.4 => "weak negative",
+    c if c > -0.7 => "moderate negative",
+    _ => "strong negative",
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_escape_like() {
+    assert_eq!(escape_like("Zen"), "Zen");
+    assert_eq!(escape_like("100%"), "100\\%");
+    assert_eq!(escape_like("a_b\\c"), "a\\_b\\\\c");
+  }
+
+  #[test]
+  fn test_describe_correlation() {
+    assert_eq!(describe_correlation(0.5), "moderate positive");
+    assert_eq!(describe_correlation(0.0), "little to no");
+    assert_eq!(describe_correlation(-0.8), "strong negative");
+  }
+}
@@ -0,0 +1,477 @@
+use chrono::{DateTime, Duration, Utc};
+use poise::serenity_prelude::{ChannelId, GuildId, UserId};
+use poise::ChoiceParameter;
+use sqlx::postgres::{PgArguments, PgRow};
+use sqlx::query::{Query, QueryAs};
+use sqlx::{Error as SqlxError, FromRow, Postgres, Result as SqlxResult, Row};
+use ulid::Ulid;
+
+use crate::data::common;
+use crate::database::InsertQuery;
+
+/// How long before an occurrence starts that members are counted as attending, so that
+/// joining the voice channel a little early still counts.
+const ATTENDANCE_GRACE: Duration = Duration::minutes(15);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ChoiceParameter)]
+pub enum EventRepeat {
+  #[name = "does not repeat"]
+  Never,
+  #[name = "daily"]
+  Daily,
+  #[name = "weekly"]
+  Weekly,
+  #[name = "every two weeks"]
+  Fortnightly,
+}
+
+impl EventRepeat {
+  /// Returns the number of days between occurrences, or [`None`] for one-off events.
+  pub fn days(self) -> Option<i32> {
+    match self {
+      Self::Never => None,
+      Self::Daily => Some(1),
+      Self::Weekly => Some(7),
+      Self::Fortnightly => Some(14),
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttendanceSource {
+  Message,
+  Voice,
+}
+
+impl AttendanceSource {
+  fn as_str(self) -> &'static str {
+    match self {
+      Self::Message => "message",
+      Self::Voice => "voice",
+    }
+  }
+}
+
+/// A community event, such as a book club or Q&A, which may repeat every few days.
+/// Members RSVP to individual occurrences, identified by their start time, and attendance
+/// is recorded when they send a message or join voice in the event's channel while an
+/// occurrence is running.
+pub struct CommunityEvent {
+  pub id: String,
+  pub guild_id: GuildId,
+  pub channel_id: ChannelId,
+  pub title: String,
+  pub description: Option<String>,
+  pub starts_at: DateTime<Utc>,
+  pub duration_minutes: i32,
+  pub repeat_days: Option<i32>,
+  pub created_by: UserId,
+  /// Whether staff can record and transcribe the event with `/transcript`, for members who
+  /// consent.
+  pub transcribe: bool,
+}
+
+/// RSVPs and attendance for a single occurrence of a [`CommunityEvent`].
+#[derive(Debug, Default)]
+pub struct OccurrenceStats {
+  pub occurrence: DateTime<Utc>,
+  pub rsvps: i64,
+  pub attended: i64,
+  /// The number of members who both RSVPed and attended.
+  pub rsvps_attended: i64,
+}
+
+pub struct EventTitle {
+  pub title: String,
+}
+
+impl CommunityEvent {
+  #[allow(clippy::too_many_arguments)]
+  pub fn new(
+    guild_id: GuildId,
+    channel_id: ChannelId,
+    title: String,
+    description: Option<String>,
+    starts_at: DateTime<Utc>,
+    duration_minutes: i32,
+    repeat: EventRepeat,
+    created_by: UserId,
+  ) -> Self {
+    Self {
+      id: Ulid::new().to_string(),
+      guild_id,
+      channel_id,
+      title,
+      description,
+      starts_at,
+      duration_minutes,
+      repeat_days: repeat.days(),
+      created_by,
+      transcribe: false,
+    }
+  }
+
+  fn period(&self) -> Option<Duration> {
+    self
+      .repeat_days
+      .filter(|days| *days > 0)
+      .map(|days| Duration::days(i64::from(days)))
+  }
+
+  fn duration(&self) -> Duration {
+    Duration::minutes(i64::from(self.duration_minutes))
+  }
+
+  /// Returns the start of the most recent occurrence starting at or before `time`, if any.
+  fn latest_occurrence(&self, time: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    if time < self.starts_at {
+      return None;
+    }
+
+    match self.period() {
+      Some(period) => {
+        let elapsed = (time - self.starts_at).num_seconds() / period.num_seconds();
+        Some(self.starts_at + period * i32::try_from(elapsed).ok()?)
+      }
+      None => Some(self.starts_at),
+    }
+  }
+
+  /// Returns the start of the occurrence running at `now`, if any. Members are counted as
+  /// attending from [`ATTENDANCE_GRACE`] before an occurrence starts until it ends.
+  pub fn current_occurrence(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    self
+      .latest_occurrence(now + ATTENDANCE_GRACE)
+      .filter(|start| now < *start + self.duration())
+  }
+
+  /// Returns the start of the next occurrence which hasn't ended as of `now`, which may be
+  /// running already. Returns [`None`] once a one-off event has ended.
+  pub fn next_occurrence(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let Some(latest) = self.latest_occurrence(now) else {
+      return Some(self.starts_at);
+    };
+
+    if now < latest + self.duration() {
+      Some(latest)
+    } else {
+      self.period().map(|period| latest + period)
+    }
+  }
+
+  /// Returns the starts of up to `limit` occurrences which started at or before `now`,
+  /// most recent first.
+  pub fn past_occurrences(&self, now: DateTime<Utc>, limit: usize) -> Vec<DateTime<Utc>> {
+    let Some(latest) = self.latest_occurrence(now) else {
+      return Vec::new();
+    };
+
+    match self.period() {
+      Some(period) => std::iter::successors(Some(latest), |occurrence| {
+        Some(*occurrence - period).filter(|previous| *previous >= self.starts_at)
+      })
+      .take(limit)
+      .collect(),
+      None => vec![latest],
+    }
+  }
+
+  /// Retrieves a [`CommunityEvent`] by title, ignoring case.
+  pub fn retrieve_by_title<'a>(
+    guild_id: GuildId,
+    title: &str,
+  ) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT record_id, guild_id, channel_id, title, description, starts_at, duration_minutes, repeat_days, created_by, transcribe FROM community_event WHERE guild_id = $1 AND LOWER(title) = LOWER($2)",
+    )
+    .bind(guild_id.to_string())
+    .bind(title.to_owned())
+  }
+
+  /// Retrieves every [`CommunityEvent`] which takes place in a channel.
+  pub fn retrieve_in_channel<'a>(
+    guild_id: GuildId,
+    channel_id: ChannelId,
+  ) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT record_id, guild_id, channel_id, title, description, starts_at, duration_minutes, repeat_days, created_by, transcribe FROM community_event WHERE guild_id = $1 AND channel_id = $2",
+    )
+    .bind(guild_id.to_string())
+    .bind(channel_id.to_string())
+  }
+
+  /// Retrieves every [`CommunityEvent`] in a guild, in the order they first start.
+  pub fn retrieve_all<'a>(guild_id: GuildId) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT record_id, guild_id, channel_id, title, description, starts_at, duration_minutes, repeat_days, created_by, transcribe FROM community_event WHERE guild_id = $1 ORDER BY starts_at ASC",
+    )
+    .bind(guild_id.to_string())
+  }
+
+  /// Retrieves up to 25 event titles containing `partial`, in alphabetical order, for use
+  /// as autocomplete suggestions.
+  pub fn retrieve_titles<'a>(
+    guild_id: GuildId,
+    partial: &str,
+  ) -> QueryAs<'a, Postgres, EventTitle, PgArguments> {
+    sqlx::query_as(
+      "SELECT title FROM community_event WHERE guild_id = $1 AND title ILIKE '%' || $2 || '%' ORDER BY title ASC LIMIT 25",
+    )
+    .bind(guild_id.to_string())
+    .bind(common::escape_like(partial))
+  }
+
+  /// Sets whether an event can be transcribed.
+  pub fn set_transcribe(event_id: &str, transcribe: bool) -> Query<'_, Postgres, PgArguments> {
+    query!(
+      "UPDATE community_event SET transcribe = $1 WHERE record_id = $2",
+      transcribe,
+      event_id,
+    )
+  }
+
+  /// Adds an RSVP for an occurrence, unless the member has already RSVPed.
+  pub fn add_rsvp<'a>(
+    event_id: &'a str,
+    user_id: UserId,
+    occurrence: &'a DateTime<Utc>,
+  ) -> Query<'a, Postgres, PgArguments> {
+    query!(
+      "INSERT INTO event_rsvp (record_id, event_id, user_id, occurrence) VALUES ($1, $2, $3, $4) ON CONFLICT (event_id, user_id, occurrence) DO NOTHING",
+      Ulid::new().to_string(),
+      event_id,
+      user_id.to_string(),
+      occurrence,
+    )
+  }
+
+  /// Removes a member's RSVP for an occurrence.
+  pub fn remove_rsvp<'a>(
+    event_id: &'a str,
+    user_id: UserId,
+    occurrence: &'a DateTime<Utc>,
+  ) -> Query<'a, Postgres, PgArguments> {
+    query!(
+      "DELETE FROM event_rsvp WHERE event_id = $1 AND user_id = $2 AND occurrence = $3",
+      event_id,
+      user_id.to_string(),
+      occurrence,
+    )
+  }
+
+  /// Records a member as attending an occurrence, unless they have already been recorded.
+  pub fn add_attendance<'a>(
+    event_id: &'a str,
+    user_id: UserId,
+    occurrence: &'a DateTime<Utc>,
+    source: AttendanceSource,
+  ) -> Query<'a, Postgres, PgArguments> {
+    query!(
+      "INSERT INTO event_attendance (record_id, event_id, user_id, occurrence, source) VALUES ($1, $2, $3, $4, $5) ON CONFLICT (event_id, user_id, occurrence) DO NOTHING",
+      Ulid::new().to_string(),
+      event_id,
+      user_id.to_string(),
+      occurrence,
+      source.as_str(),
+    )
+  }
+
+  /// Counts the RSVPs for an occurrence.
+  pub fn rsvp_count<'a, T: for<'r> FromRow<'r, PgRow>>(
+    event_id: &'a str,
+    occurrence: &'a DateTime<Utc>,
+  ) -> QueryAs<'a, Postgres, T, PgArguments> {
+    sqlx::query_as(
+      "SELECT COUNT(*) AS count FROM event_rsvp WHERE event_id = $1 AND occurrence = $2",
+    )
+    .bind(event_id)
+    .bind(occurrence)
+  }
+
+  /// Retrieves [`OccurrenceStats`] for each occurrence with any RSVPs or attendance, most
+  /// recent first.
+  pub fn occurrence_stats(event_id: &str) -> QueryAs<'_, Postgres, OccurrenceStats, PgArguments> {
+    sqlx::query_as(
+      "SELECT occurrence, COUNT(rsvp.user_id) AS rsvps, COUNT(attendance.user_id) AS attended, COUNT(rsvp.user_id) FILTER (WHERE attendance.user_id IS NOT NULL) AS rsvps_attended FROM (SELECT user_id, occurrence FROM event_rsvp WHERE event_id = $1) AS rsvp FULL OUTER JOIN (SELECT user_id, occurrence FROM event_attendance WHERE event_id = $1) AS attendance USING (user_id, occurrence) GROUP BY occurrence ORDER BY occurrence DESC",
+    )
+    .bind(event_id)
+  }
+
+  /// Counts the members who have attended any occurrence of an event.
+  pub fn unique_attendees<'a, T: for<'r> FromRow<'r, PgRow>>(
+    event_id: &'a str,
+  ) -> QueryAs<'a, Postgres, T, PgArguments> {
+    sqlx::query_as(
+      "SELECT COUNT(DISTINCT user_id) AS count FROM event_attendance WHERE event_id = $1",
+    )
+    .bind(event_id)
+  }
+}
+
+impl InsertQuery for CommunityEvent {
+  /// Adds a [`CommunityEvent`] to the database.
+  fn insert_query(&self) -> Query<Postgres, PgArguments> {
+    query!(
+      "INSERT INTO community_event (record_id, guild_id, channel_id, title, description, starts_at, duration_minutes, repeat_days, created_by) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
+      self.id,
+      self.guild_id.to_string(),
+      self.channel_id.to_string(),
+      self.title,
+      self.description,
+      self.starts_at,
+      self.duration_minutes,
+      self.repeat_days,
+      self.created_by.to_string(),
+    )
+  }
+}
+
+impl FromRow<'_, PgRow> for CommunityEvent {
+  fn from_row(row: &'_ PgRow) -> SqlxResult<Self, SqlxError> {
+    let guild_id = GuildId::new(common::decode_id_row(row, "guild_id")?);
+    let channel_id = ChannelId::new(common::decode_id_row(row, "channel_id")?);
+    let created_by = UserId::new(common::decode_id_row(row, "created_by")?);
+
+    Ok(Self {
+      id: row.try_get("record_id")?,
+      guild_id,
+      channel_id,
+      title: row.try_get("title")?,
+      description: row.try_get("description")?,
+      starts_at: row.try_get("starts_at")?,
+      duration_minutes: row.try_get("duration_minutes")?,
+      repeat_days: row.try_get("repeat_days")?,
+      created_by,
+      transcribe: row.try_get("transcribe")?,
+    })
+  }
+}
+
+impl FromRow<'_, PgRow> for OccurrenceStats {
+  fn from_row(row: &'_ PgRow) -> SqlxResult<Self, SqlxError> {
+    Ok(Self {
+      occurrence: row.try_get("occurrence")?,
+      rsvps: row.try_get("rsvps")?,
+      attended: row.try_get("attended")?,
+      rsvps_attended: row.try_get("rsvps_attended")?,
+    })
+  }
+}
+
+impl FromRow<'_, PgRow> for EventTitle {
+  fn from_row(row: &'_ PgRow) -> SqlxResult<Self, SqlxError> {
+    Ok(Self {
+      title: row.try_get("title")?,
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use chrono::NaiveDate;
+
+  fn datetime(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+    NaiveDate::from_ymd_opt(year, month, day)
+      .and_then(|date| date.and_hms_opt(hour, minute, 0))
+      .map(|datetime| datetime.and_utc())
+      .unwrap_or_default()
+  }
+
+  fn event(repeat: EventRepeat) -> CommunityEvent {
+    CommunityEvent::new(
+      GuildId::new(123u64),
+      ChannelId::new(456u64),
+      "Book Club".to_owned(),
+      None,
+      datetime(2024, 10, 1, 18, 0),
+      60,
+      repeat,
+      UserId::new(789u64),
+    )
+  }
+
+  #[test]
+  fn test_current_occurrence() {
+    let weekly = event(EventRepeat::Weekly);
+    assert_eq!(
+      weekly.current_occurrence(datetime(2024, 9, 30, 18, 0)),
+      None
+    );
+    assert_eq!(
+      weekly.current_occurrence(datetime(2024, 10, 1, 17, 50)),
+      Some(datetime(2024, 10, 1, 18, 0))
+    );
+    assert_eq!(
+      weekly.current_occurrence(datetime(2024, 10, 15, 18, 59)),
+      Some(datetime(2024, 10, 15, 18, 0))
+    );
+    assert_eq!(
+      weekly.current_occurrence(datetime(2024, 10, 15, 19, 0)),
+      None
+    );
+    assert_eq!(
+      weekly.current_occurrence(datetime(2024, 10, 16, 18, 30)),
+      None
+    );
+
+    let once = event(EventRepeat::Never);
+    assert_eq!(
+      once.current_occurrence(datetime(2024, 10, 1, 18, 30)),
+      Some(datetime(2024, 10, 1, 18, 0))
+    );
+    assert_eq!(once.current_occurrence(datetime(2024, 10, 8, 18, 30)), None);
+  }
+
+  #[test]
+  fn test_next_occurrence() {
+    let weekly = event(EventRepeat::Weekly);
+    assert_eq!(
+      weekly.next_occurrence(datetime(2024, 9, 1, 0, 0)),
+      Some(datetime(2024, 10, 1, 18, 0))
+    );
+    assert_eq!(
+      weekly.next_occurrence(datetime(2024, 10, 8, 18, 30)),
+      Some(datetime(2024, 10, 8, 18, 0))
+    );
+    assert_eq!(
+      weekly.next_occurrence(datetime(2024, 10, 8, 19, 0)),
+      Some(datetime(2024, 10, 15, 18, 0))
+    );
+
+    let once = event(EventRepeat::Never);
+    assert_eq!(
+      once.next_occurrence(datetime(2024, 10, 1, 18, 30)),
+      Some(datetime(2024, 10, 1, 18, 0))
+    );
+    assert_eq!(once.next_occurrence(datetime(2024, 10, 1, 19, 0)), None);
+  }
+
+  #[test]
+  fn test_past_occurrences() {
+    let weekly = event(EventRepeat::Weekly);
+    assert!(weekly
+      .past_occurrences(datetime(2024, 9, 1, 0, 0), 5)
+      .is_empty());
+    assert_eq!(
+      weekly.past_occurrences(datetime(2024, 10, 16, 0, 0), 5),
+      vec![
+        datetime(2024, 10, 15, 18, 0),
+        datetime(2024, 10, 8, 18, 0),
+        datetime(2024, 10, 1, 18, 0)
+      ]
+    );
+    assert_eq!(
+      weekly.past_occurrences(datetime(2024, 10, 16, 0, 0), 2),
+      vec![datetime(2024, 10, 15, 18, 0), datetime(2024, 10, 8, 18, 0)]
+    );
+
+    let daily = event(EventRepeat::Daily);
+    assert_eq!(
+      daily
+        .past_occurrences(datetime(2024, 10, 31, 0, 0), 5)
+        .len(),
+      5
+    );
+  }
+}
@@ -0,0 +1,340 @@
+use poise::serenity_prelude::{GuildId, Mentionable, RoleId, UserId};
+use sqlx::postgres::{PgArguments, PgRow};
+use sqlx::query::{Query, QueryAs};
+use sqlx::{Error as SqlxError, FromRow, Postgres, Result as SqlxResult, Row};
+use ulid::Ulid;
+
+use crate::data::common;
+use crate::database::{DeleteQuery, ExistsQuery, InsertQuery, UpdateQuery};
+use crate::pagination::{PageRow, PageType};
+
+pub struct Course {
+  pub name: String,
+  pub participant_role: RoleId,
+  pub graduate_role: RoleId,
+  pub guild_id: GuildId,
+  pub dm_only_completion: bool,
+}
+
+/// A code which members can redeem with `/course enroll` to join a [`Course`]. Codes
+/// without a `max_uses` limit can be redeemed any number of times.
+pub struct EnrollmentCode {
+  pub code: String,
+  pub course_name: String,
+  pub guild_id: GuildId,
+  pub max_uses: Option<i32>,
+  pub uses: i32,
+  pub created_by: UserId,
+}
+
+/// A record of a member joining a [`Course`] by redeeming an [`EnrollmentCode`].
+pub struct Enrollment {
+  pub course_name: String,
+  pub guild_id: GuildId,
+  pub user_id: UserId,
+  pub code: String,
+}
+
+impl Course {
+  pub fn new(
+    name: impl Into<String>,
+    participant_role: RoleId,
+    graduate_role: RoleId,
+    guild_id: GuildId,
+  ) -> Self {
+    Self {
+      name: name.into(),
+      participant_role,
+      graduate_role,
+      guild_id,
+      dm_only_completion: false,
+    }
+  }
+
+  /// Restricts `/coursecomplete` for the [`Course`] to DMs when set to `true`.
+  pub fn dm_only_completion(mut self, dm_only_completion: bool) -> Self {
+    self.dm_only_completion = dm_only_completion;
+    self
+  }
+
+  /// Retrieves a [`Course`] from the database.
+  pub fn retrieve<'a>(
+    guild_id: GuildId,
+    course_name: &str,
+  ) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT course_name, participant_role, graduate_role, guild_id, dm_only_completion FROM course WHERE LOWER(course_name) = LOWER($1) AND guild_id = $2",
+    )
+    .bind(course_name.to_string())
+    .bind(guild_id.to_string())
+  }
+
+  /// Retrieves a [`Course`] from the database while in DMs, matching by course name only.
+  pub fn retrieve_in_dm<'a>(course_name: &str) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT course_name, participant_role, graduate_role, guild_id, dm_only_completion FROM course WHERE LOWER(course_name) = LOWER($1)",
+    )
+    .bind(course_name.to_string())
+  }
+
+  /// Retrieves the [`Course`] from the database with the name most similar to the specified
+  /// `course_name`, unless none meets the similarity threshold set by `similarity`.
+  pub fn retrieve_similar<'a>(
+    guild_id: GuildId,
+    course_name: &str,
+    similarity: f32,
+  ) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT course_name, participant_role, graduate_role, SET_LIMIT($2) FROM course WHERE LOWER(course_name) % LOWER($1) AND guild_id = $3 ORDER BY SIMILARITY(LOWER(course_name), LOWER($1)) DESC LIMIT 1",
+    )
+    .bind(course_name.to_string())
+    .bind(similarity)
+    .bind(guild_id.to_string())
+  }
+
+  /// Retrieves all [`Course`]s from the database.
+  pub fn retrieve_all<'a>(guild_id: GuildId) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT course_name, participant_role, graduate_role, dm_only_completion FROM course WHERE guild_id = $1 ORDER BY course_name ASC",
+    )
+    .bind(guild_id.to_string())
+  }
+}
+
+impl InsertQuery for Course {
+  /// Adds a [`Course`] to the database.
+  fn insert_query(&self) -> Query<Postgres, PgArguments> {
+    query!(
+      "INSERT INTO course (record_id, course_name, participant_role, graduate_role, guild_id, dm_only_completion) VALUES ($1, $2, $3, $4, $5, $6)",
+      Ulid::new().to_string(),
+      self.name,
+      self.participant_role.to_string(),
+      self.graduate_role.to_string(),
+      self.guild_id.to_string(),
+      self.dm_only_completion,
+    )
+  }
+}
+
+impl UpdateQuery for Course {
+  /// Updates a [`Course`] in the database.
+  fn update_query(&self) -> Query<Postgres, PgArguments> {
+    query!(
+      "UPDATE course SET participant_role = $1, graduate_role = $2, dm_only_completion = $3 WHERE LOWER(course_name) = LOWER($4) AND guild_id = $5",
+      self.participant_role.to_string(),
+      self.graduate_role.to_string(),
+      self.dm_only_completion,
+      self.name,
+      self.guild_id.to_string(),
+    )
+  }
+}
+
+impl DeleteQuery for Course {
+  /// Removes a [`Course`] from the database.
+  fn delete_query<'a>(
+    guild_id: GuildId,
+    course_name: impl Into<String>,
+  ) -> Query<'a, Postgres, PgArguments> {
+    query!(
+      "DELETE FROM course WHERE course_name = $1 AND guild_id = $2",
+      course_name.into(),
+      guild_id.to_string(),
+    )
+  }
+}
+
+impl ExistsQuery for Course {
+  type Item<'a> = &'a str;
+
+  /// Checks to see if a [`Course`] exists in the database.
+  fn exists_query<'a, T: for<'r> FromRow<'r, PgRow>>(
+    guild_id: GuildId,
+    course_name: Self::Item<'a>,
+  ) -> QueryAs<'a, Postgres, T, PgArguments> {
+    sqlx::query_as("SELECT EXISTS(SELECT 1 FROM course WHERE course_name = $1 AND guild_id = $2)")
+      .bind(course_name)
+      .bind(guild_id.to_string())
+  }
+}
+
+impl PageRow for Course {
+  fn title(&self, _page_type: PageType) -> String {
+    self.name.clone()
+  }
+
+  fn body(&self) -> String {
+    let completion = if self.dm_only_completion {
+      "DMs only"
+    } else {
+      "DMs or server"
+    };
+
+    format!(
+      "Participants: {}\nGraduates: {}\nCompletion: {completion}",
+      self.participant_role.mention(),
+      self.graduate_role.mention()
+    )
+  }
+}
+
+impl FromRow<'_, PgRow> for Course {
+  fn from_row(row: &'_ PgRow) -> SqlxResult<Self, SqlxError> {
+    let guild_id = GuildId::new(common::decode_id_row(row, "guild_id")?);
+    let participant_role = RoleId::new(common::decode_id_row(row, "participant_role")?);
+    let graduate_role = RoleId::new(common::decode_id_row(row, "graduate_role")?);
+
+    Ok(Self {
+      name: row.try_get("course_name").unwrap_or_default(),
+      participant_role,
+      graduate_role,
+      guild_id,
+      dm_only_completion: row.try_get("dm_only_completion").unwrap_or_default(),
+    })
+  }
+}
+
+impl EnrollmentCode {
+  pub fn new(
+    code: impl Into<String>,
+    course_name: impl Into<String>,
+    guild_id: GuildId,
+    max_uses: Option<i32>,
+    created_by: UserId,
+  ) -> Self {
+    Self {
+      code: code.into(),
+      course_name: course_name.into(),
+      guild_id,
+      max_uses,
+      uses: 0,
+      created_by,
+    }
+  }
+
+  /// Returns `true` if the [`EnrollmentCode`] has no uses remaining.
+  pub fn exhausted(&self) -> bool {
+    self.max_uses.is_some_and(|max_uses| self.uses >= max_uses)
+  }
+
+  /// Retrieves an [`EnrollmentCode`] from the database, matching the code case-insensitively.
+  pub fn retrieve<'a>(guild_id: GuildId, code: &str) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT code, course_name, guild_id, max_uses, uses, created_by FROM course_enrollment_code WHERE UPPER(code) = UPPER($1) AND guild_id = $2",
+    )
+    .bind(code.to_string())
+    .bind(guild_id.to_string())
+  }
+
+  /// Retrieves all [`EnrollmentCode`]s for a [`Course`] from the database.
+  pub fn retrieve_all<'a>(
+    guild_id: GuildId,
+    course_name: &str,
+  ) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT code, course_name, guild_id, max_uses, uses, created_by FROM course_enrollment_code WHERE LOWER(course_name) = LOWER($1) AND guild_id = $2 ORDER BY created_at DESC",
+    )
+    .bind(course_name.to_string())
+    .bind(guild_id.to_string())
+  }
+
+  /// Uses up one redemption of an [`EnrollmentCode`], unless it has no uses remaining.
+  pub fn redeem(guild_id: GuildId, code: &str) -> Query<'_, Postgres, PgArguments> {
+    query!(
+      "UPDATE course_enrollment_code SET uses = uses + 1 WHERE UPPER(code) = UPPER($1) AND guild_id = $2 AND (max_uses IS NULL OR uses < max_uses)",
+      code,
+      guild_id.to_string(),
+    )
+  }
+}
+
+impl InsertQuery for EnrollmentCode {
+  /// Adds an [`EnrollmentCode`] to the database.
+  fn insert_query(&self) -> Query<Postgres, PgArguments> {
+    query!(
+      "INSERT INTO course_enrollment_code (record_id, code, course_name, guild_id, max_uses, created_by) VALUES ($1, $2, $3, $4, $5, $6)",
+      Ulid::new().to_string(),
+      self.code,
+      self.course_name,
+      self.guild_id.to_string(),
+      self.max_uses,
+      self.created_by.to_string(),
+    )
+  }
+}
+
+impl DeleteQuery for EnrollmentCode {
+  /// Removes an [`EnrollmentCode`] from the database.
+  fn delete_query<'a>(
+    guild_id: GuildId,
+    code: impl Into<String>,
+  ) -> Query<'a, Postgres, PgArguments> {
+    query!(
+      "DELETE FROM course_enrollment_code WHERE UPPER(code) = UPPER($1) AND guild_id = $2",
+      code.into(),
+      guild_id.to_string(),
+    )
+  }
+}
+
+impl PageRow for EnrollmentCode {
+  fn title(&self, _page_type: PageType) -> String {
+    format!("`{}`", self.code)
+  }
+
+  fn body(&self) -> String {
+    let uses = match self.max_uses {
+      Some(max_uses) => format!("{} of {max_uses}", self.uses),
+      None => format!("{} (unlimited)", self.uses),
+    };
+
+    format!("Uses: {uses}\nCreated by: {}", self.created_by.mention())
+  }
+}
+
+impl FromRow<'_, PgRow> for EnrollmentCode {
+  fn from_row(row: &'_ PgRow) -> SqlxResult<Self, SqlxError> {
+    let guild_id = GuildId::new(common::decode_id_row(row, "guild_id")?);
+    let created_by = UserId::new(common::decode_id_row(row, "created_by")?);
+
+    Ok(Self {
+      code: row.try_get("code")?,
+      course_name: row.try_get("course_name")?,
+      guild_id,
+      max_uses: row.try_get("max_uses")?,
+      uses: row.try_get("uses")?,
+      created_by,
+    })
+  }
+}
+
+impl Enrollment {
+  pub fn new(
+    course_name: impl Into<String>,
+    guild_id: GuildId,
+    user_id: UserId,
+    code: impl Into<String>,
+  ) -> Self {
+    Self {
+      course_name: course_name.into(),
+      guild_id,
+      user_id,
+      code: code.into(),
+    }
+  }
+}
+
+impl InsertQuery for Enrollment {
+  /// Records an [`Enrollment`] in the database. Members who rejoin a [`Course`] keep
+  /// their original enrollment record.
+  fn insert_query(&self) -> Query<Postgres, PgArguments> {
+    query!(
+      "INSERT INTO course_enrollment (record_id, course_name, guild_id, user_id, enrollment_code) VALUES ($1, $2, $3, $4, $5) ON CONFLICT (guild_id, course_name, user_id) DO NOTHING",
+      Ulid::new().to_string(),
+      self.course_name,
+      self.guild_id.to_string(),
+      self.user_id.to_string(),
+      self.code,
+    )
+  }
+}
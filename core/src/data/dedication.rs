@@ -0,0 +1,125 @@
+use chrono::{DateTime, Utc};
+use poise::serenity_prelude::{GuildId, UserId};
+use sqlx::postgres::{PgArguments, PgRow};
+use sqlx::query::{Query, QueryAs};
+use sqlx::{Error as SqlxError, FromRow, Postgres, Result as SqlxResult, Row};
+use ulid::Ulid;
+
+use crate::data::common;
+use crate::database::InsertQuery;
+use crate::pagination::{PageRow, PageType};
+
+/// A member's session dedicated to another member. Dedications are purely a message of
+/// goodwill, so the session's time still counts towards the member who added it.
+pub struct Dedication {
+  pub id: String,
+  pub guild_id: GuildId,
+  pub user_id: UserId,
+  pub recipient_id: UserId,
+  pub meditation_id: String,
+  pub minutes: i32,
+  pub message: String,
+  pub dedicated_at: Option<DateTime<Utc>>,
+}
+
+impl Dedication {
+  pub fn new(
+    guild_id: GuildId,
+    user_id: UserId,
+    recipient_id: UserId,
+    meditation_id: String,
+    minutes: i32,
+    message: String,
+  ) -> Self {
+    Self {
+      id: Ulid::new().to_string(),
+      guild_id,
+      user_id,
+      recipient_id,
+      meditation_id,
+      minutes,
+      message,
+      dedicated_at: None,
+    }
+  }
+
+  /// Retrieves all [`Dedication`]s received by a member, most recent first.
+  pub fn received<'a>(
+    guild_id: GuildId,
+    recipient_id: UserId,
+  ) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT record_id, guild_id, user_id, recipient_id, meditation_id, meditation_minutes, message, dedicated_at FROM dedication WHERE guild_id = $1 AND recipient_id = $2 ORDER BY dedicated_at DESC",
+    )
+    .bind(guild_id.to_string())
+    .bind(recipient_id.to_string())
+  }
+
+  /// Retrieves all [`Dedication`]s made by a member, most recent first.
+  pub fn given<'a>(guild_id: GuildId, user_id: UserId) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT record_id, guild_id, user_id, recipient_id, meditation_id, meditation_minutes, message, dedicated_at FROM dedication WHERE guild_id = $1 AND user_id = $2 ORDER BY dedicated_at DESC",
+    )
+    .bind(guild_id.to_string())
+    .bind(user_id.to_string())
+  }
+}
+
+impl InsertQuery for Dedication {
+  /// Adds a [`Dedication`] to the database, unless the session has already been dedicated.
+  fn insert_query(&self) -> Query<Postgres, PgArguments> {
+    query!(
+      "INSERT INTO dedication (record_id, guild_id, user_id, recipient_id, meditation_id, meditation_minutes, message) VALUES ($1, $2, $3, $4, $5, $6, $7) ON CONFLICT (meditation_id) DO NOTHING",
+      self.id,
+      self.guild_id.to_string(),
+      self.user_id.to_string(),
+      self.recipient_id.to_string(),
+      self.meditation_id,
+      self.minutes,
+      self.message,
+    )
+  }
+}
+
+impl PageRow for Dedication {
+  fn title(&self, _page_type: PageType) -> String {
+    format!(
+      "{} {}",
+      self.minutes,
+      if self.minutes == 1 {
+        "minute"
+      } else {
+        "minutes"
+      }
+    )
+  }
+
+  fn body(&self) -> String {
+    format!(
+      "> {}\n> -# <@{}> → <@{}> on <t:{}:f>\n** **",
+      self.message,
+      self.user_id,
+      self.recipient_id,
+      self.dedicated_at.unwrap_or_default().timestamp()
+    )
+  }
+}
+
+impl FromRow<'_, PgRow> for Dedication {
+  fn from_row(row: &'_ PgRow) -> SqlxResult<Self, SqlxError> {
+    let guild_id = GuildId::new(common::decode_id_row(row, "guild_id")?);
+    let user_id = UserId::new(common::decode_id_row(row, "user_id")?);
+    let recipient_id = UserId::new(common::decode_id_row(row, "recipient_id")?);
+
+    Ok(Self {
+      id: row.try_get("record_id")?,
+      guild_id,
+      user_id,
+      recipient_id,
+      meditation_id: row.try_get("meditation_id")?,
+      minutes: row.try_get("meditation_minutes")?,
+      message: row.try_get("message")?,
+      dedicated_at: row.try_get("dedicated_at")?,
+    })
+  }
+}
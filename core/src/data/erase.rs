@@ -5,9 +5,9 @@ use sqlx::query::{Query, QueryAs};
 use sqlx::{Error as SqlxError, FromRow, Postgres, Result as SqlxResult, Row};
 use ulid::Ulid;
 
-use crate::commands::helpers::pagination::{PageRow, PageType};
 use crate::data::common;
-use crate::handlers::database::InsertQuery;
+use crate::database::InsertQuery;
+use crate::pagination::{PageRow, PageType};
 
 pub struct Erase {
   id: String,
@@ -38,6 +38,11 @@ impl Erase {
     }
   }
 
+  /// Returns when the message was erased.
+  pub fn occurred_at(&self) -> DateTime<Utc> {
+    self.occurred_at
+  }
+
   /// Retrieves all [`Erase`]s for the specified `user_id`.
   pub fn retrieve_all<'a>(
     guild_id: GuildId,
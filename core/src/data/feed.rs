@@ -0,0 +1,120 @@
+use poise::serenity_prelude::{ChannelId, GuildId, UserId};
+use sqlx::postgres::{PgArguments, PgRow};
+use sqlx::query::{Query, QueryAs};
+use sqlx::{Error as SqlxError, FromRow, Postgres, Result as SqlxResult, Row};
+use ulid::Ulid;
+
+use crate::data::common;
+use crate::database::{DeleteQuery, InsertQuery};
+use crate::pagination::{PageRow, PageType};
+
+/// A channel's subscription to an RSS or Atom feed, such as a teacher's dharma talks. New
+/// items in the feed are posted to the channel.
+pub struct FeedSubscription {
+  pub id: String,
+  pub guild_id: GuildId,
+  pub channel_id: ChannelId,
+  pub url: String,
+  /// The title of the feed when it was added, shown when listing subscriptions.
+  pub title: String,
+  pub added_by: UserId,
+}
+
+impl FeedSubscription {
+  pub fn new(
+    guild_id: GuildId,
+    channel_id: ChannelId,
+    url: String,
+    title: String,
+    added_by: UserId,
+  ) -> Self {
+    Self {
+      id: Ulid::new().to_string(),
+      guild_id,
+      channel_id,
+      url,
+      title,
+      added_by,
+    }
+  }
+
+  /// Retrieves every [`FeedSubscription`] in a guild, oldest first.
+  pub fn retrieve_all<'a>(guild_id: GuildId) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT record_id, guild_id, channel_id, url, title, added_by FROM feed_subscription WHERE guild_id = $1 ORDER BY record_id",
+    )
+    .bind(guild_id.to_string())
+  }
+
+  /// Retrieves every [`FeedSubscription`] in every guild, to check for new items.
+  pub fn retrieve_every<'a>() -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT record_id, guild_id, channel_id, url, title, added_by FROM feed_subscription ORDER BY record_id",
+    )
+  }
+
+  /// Records that an item in the feed has been seen, so it's only posted once. Affects no
+  /// rows if the item has been seen before.
+  pub fn mark_seen<'a>(&'a self, item_key: &'a str) -> Query<'a, Postgres, PgArguments> {
+    query!(
+      "INSERT INTO feed_item (subscription_id, item_key) VALUES ($1, $2) ON CONFLICT (subscription_id, item_key) DO NOTHING",
+      self.id,
+      item_key,
+    )
+  }
+}
+
+impl InsertQuery for FeedSubscription {
+  /// Adds a [`FeedSubscription`] to the database.
+  fn insert_query(&self) -> Query<Postgres, PgArguments> {
+    query!(
+      "INSERT INTO feed_subscription (record_id, guild_id, channel_id, url, title, added_by) VALUES ($1, $2, $3, $4, $5, $6)",
+      self.id,
+      self.guild_id.to_string(),
+      self.channel_id.to_string(),
+      self.url,
+      self.title,
+      self.added_by.to_string(),
+    )
+  }
+}
+
+impl DeleteQuery for FeedSubscription {
+  /// Removes a [`FeedSubscription`], along with the record of which items have been seen.
+  fn delete_query<'a>(
+    guild_id: GuildId,
+    subscription_id: impl Into<String>,
+  ) -> Query<'a, Postgres, PgArguments> {
+    query!(
+      "DELETE FROM feed_subscription WHERE guild_id = $1 AND record_id = $2",
+      guild_id.to_string(),
+      subscription_id.into(),
+    )
+  }
+}
+
+impl PageRow for FeedSubscription {
+  fn title(&self, _page_type: PageType) -> String {
+    self.title.clone()
+  }
+
+  fn body(&self) -> String {
+    format!(
+      "{}\nPosted in <#{}> · Added by <@{}>\n-# ID: {}",
+      self.url, self.channel_id, self.added_by, self.id
+    )
+  }
+}
+
+impl FromRow<'_, PgRow> for FeedSubscription {
+  fn from_row(row: &'_ PgRow) -> SqlxResult<Self, SqlxError> {
+    Ok(Self {
+      id: row.try_get("record_id")?,
+      guild_id: GuildId::new(common::decode_id_row(row, "guild_id")?),
+      channel_id: ChannelId::new(common::decode_id_row(row, "channel_id")?),
+      url: row.try_get("url")?,
+      title: row.try_get("title")?,
+      added_by: UserId::new(common::decode_id_row(row, "added_by")?),
+    })
+  }
+}
@@ -0,0 +1,241 @@
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+use poise::serenity_prelude::{ChannelId, GuildId, UserId};
+use poise::ChoiceParameter;
+use sqlx::postgres::{PgArguments, PgRow};
+use sqlx::query::{Query, QueryAs};
+use sqlx::{Error as SqlxError, FromRow, Postgres, Result as SqlxResult, Row};
+use ulid::Ulid;
+
+use crate::data::common;
+use crate::database::InsertQuery;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ChoiceParameter)]
+pub enum GoalPeriod {
+  #[name = "weekly"]
+  Weekly,
+  #[name = "monthly"]
+  Monthly,
+}
+
+/// A collective meditation goal for a guild, running for the current week or month.
+/// Progress is the total time tracked by all members between `starts_at` and `ends_at`.
+pub struct Goal {
+  pub id: String,
+  pub guild_id: GuildId,
+  pub channel_id: ChannelId,
+  pub period: GoalPeriod,
+  pub target_minutes: i64,
+  pub starts_at: DateTime<Utc>,
+  pub ends_at: DateTime<Utc>,
+  pub last_update_at: Option<DateTime<Utc>>,
+  pub completed_at: Option<DateTime<Utc>>,
+  pub created_by: UserId,
+}
+
+impl GoalPeriod {
+  /// Returns the start and end of the week or month containing `now`, in UTC. Weeks
+  /// start on Monday.
+  pub fn bounds(self, now: DateTime<Utc>) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    let today = now.date_naive();
+
+    let (start, end) = match self {
+      Self::Weekly => {
+        let start = today - Duration::days(i64::from(today.weekday().num_days_from_monday()));
+        (start, start + Duration::days(7))
+      }
+      Self::Monthly => {
+        let start = NaiveDate::from_ymd_opt(today.year(), today.month(), 1)?;
+        let end = if today.month() == 12 {
+          NaiveDate::from_ymd_opt(today.year() + 1, 1, 1)?
+        } else {
+          NaiveDate::from_ymd_opt(today.year(), today.month() + 1, 1)?
+        };
+        (start, end)
+      }
+    };
+
+    Some((
+      start.and_hms_opt(0, 0, 0)?.and_utc(),
+      end.and_hms_opt(0, 0, 0)?.and_utc(),
+    ))
+  }
+
+  fn as_str(self) -> &'static str {
+    match self {
+      Self::Weekly => "weekly",
+      Self::Monthly => "monthly",
+    }
+  }
+}
+
+impl Goal {
+  /// Creates a new [`Goal`] for the week or month containing `now`. Returns [`None`] if
+  /// the bounds of the period could not be determined.
+  pub fn new(
+    guild_id: GuildId,
+    channel_id: ChannelId,
+    period: GoalPeriod,
+    target_minutes: i64,
+    now: DateTime<Utc>,
+    created_by: UserId,
+  ) -> Option<Self> {
+    let (starts_at, ends_at) = period.bounds(now)?;
+
+    Some(Self {
+      id: Ulid::new().to_string(),
+      guild_id,
+      channel_id,
+      period,
+      target_minutes,
+      starts_at,
+      ends_at,
+      last_update_at: None,
+      completed_at: None,
+      created_by,
+    })
+  }
+
+  /// Retrieves the active [`Goal`] for a guild, if there is one.
+  pub fn retrieve_active<'a>(guild_id: GuildId) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT record_id, guild_id, channel_id, period, target_minutes, starts_at, ends_at, last_update_at, completed_at, created_by FROM guild_goal WHERE guild_id = $1 AND closed = FALSE",
+    )
+    .bind(guild_id.to_string())
+  }
+
+  /// Retrieves the active [`Goal`]s for all guilds.
+  pub fn retrieve_all_active<'a>() -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT record_id, guild_id, channel_id, period, target_minutes, starts_at, ends_at, last_update_at, completed_at, created_by FROM guild_goal WHERE closed = FALSE",
+    )
+  }
+
+  /// Records the time of the most recent progress update for a [`Goal`].
+  pub fn mark_updated<'a>(
+    goal_id: &'a str,
+    updated_at: &'a DateTime<Utc>,
+  ) -> Query<'a, Postgres, PgArguments> {
+    query!(
+      "UPDATE guild_goal SET last_update_at = $1 WHERE record_id = $2",
+      updated_at,
+      goal_id,
+    )
+  }
+
+  /// Marks a [`Goal`] as completed and closes it. Only affects goals which are still open,
+  /// so a goal can only be completed once.
+  pub fn complete<'a>(
+    goal_id: &'a str,
+    completed_at: &'a DateTime<Utc>,
+  ) -> Query<'a, Postgres, PgArguments> {
+    query!(
+      "UPDATE guild_goal SET completed_at = $1, closed = TRUE WHERE record_id = $2 AND closed = FALSE",
+      completed_at,
+      goal_id,
+    )
+  }
+
+  /// Closes a [`Goal`] without completing it, e.g., when it ends or is cancelled. Only
+  /// affects goals which are still open.
+  pub fn close(guild_id: GuildId, goal_id: &str) -> Query<'_, Postgres, PgArguments> {
+    query!(
+      "UPDATE guild_goal SET closed = TRUE WHERE record_id = $1 AND guild_id = $2 AND closed = FALSE",
+      goal_id,
+      guild_id.to_string(),
+    )
+  }
+
+  /// Calculates the total minutes tracked in a guild between the start of a [`Goal`]
+  /// and its end.
+  pub fn progress<'a, T: for<'r> FromRow<'r, PgRow>>(
+    guild_id: GuildId,
+    starts_at: &'a DateTime<Utc>,
+    ends_at: &'a DateTime<Utc>,
+  ) -> QueryAs<'a, Postgres, T, PgArguments> {
+    sqlx::query_as(
+      "SELECT COALESCE(SUM(meditation_minutes) + (SUM(meditation_seconds) / 60), 0) AS sum FROM meditation WHERE guild_id = $1 AND occurred_at >= $2 AND occurred_at < $3",
+    )
+    .bind(guild_id.to_string())
+    .bind(starts_at)
+    .bind(ends_at)
+  }
+}
+
+impl InsertQuery for Goal {
+  /// Adds a [`Goal`] to the database.
+  fn insert_query(&self) -> Query<Postgres, PgArguments> {
+    query!(
+      "INSERT INTO guild_goal (record_id, guild_id, channel_id, period, target_minutes, starts_at, ends_at, created_by) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+      self.id,
+      self.guild_id.to_string(),
+      self.channel_id.to_string(),
+      self.period.as_str(),
+      self.target_minutes,
+      self.starts_at,
+      self.ends_at,
+      self.created_by.to_string(),
+    )
+  }
+}
+
+impl FromRow<'_, PgRow> for Goal {
+  fn from_row(row: &'_ PgRow) -> SqlxResult<Self, SqlxError> {
+    let guild_id = GuildId::new(common::decode_id_row(row, "guild_id")?);
+    let channel_id = ChannelId::new(common::decode_id_row(row, "channel_id")?);
+    let created_by = UserId::new(common::decode_id_row(row, "created_by")?);
+    let period = match row.try_get::<&str, &str>("period")? {
+      "weekly" => GoalPeriod::Weekly,
+      _ => GoalPeriod::Monthly,
+    };
+
+    Ok(Self {
+      id: row.try_get("record_id")?,
+      guild_id,
+      channel_id,
+      period,
+      target_minutes: row.try_get("target_minutes")?,
+      starts_at: row.try_get("starts_at")?,
+      ends_at: row.try_get("ends_at")?,
+      last_update_at: row.try_get("last_update_at")?,
+      completed_at: row.try_get("completed_at")?,
+      created_by,
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn datetime(year: i32, month: u32, day: u32, hour: u32) -> DateTime<Utc> {
+    NaiveDate::from_ymd_opt(year, month, day)
+      .and_then(|date| date.and_hms_opt(hour, 0, 0))
+      .map(|datetime| datetime.and_utc())
+      .unwrap_or_default()
+  }
+
+  #[test]
+  fn test_goal_period_bounds() {
+    // Wednesday
+    let now = datetime(2024, 10, 23, 15);
+
+    assert_eq!(
+      GoalPeriod::Weekly.bounds(now),
+      Some((datetime(2024, 10, 21, 0), datetime(2024, 10, 28, 0)))
+    );
+    assert_eq!(
+      GoalPeriod::Monthly.bounds(now),
+      Some((datetime(2024, 10, 1, 0), datetime(2024, 11, 1, 0)))
+    );
+
+    let now = datetime(2024, 12, 31, 23);
+    assert_eq!(
+      GoalPeriod::Monthly.bounds(now),
+      Some((datetime(2024, 12, 1, 0), datetime(2025, 1, 1, 0)))
+    );
+    assert_eq!(
+      GoalPeriod::Weekly.bounds(now),
+      Some((datetime(2024, 12, 30, 0), datetime(2025, 1, 6, 0)))
+    );
+  }
+}
@@ -0,0 +1,119 @@
+use poise::serenity_prelude::GuildId;
+use poise::ChoiceParameter;
+use sqlx::postgres::{PgArguments, PgRow};
+use sqlx::query::{Query, QueryAs};
+use sqlx::{Error as SqlxError, FromRow, Postgres, Result as SqlxResult, Row};
+use ulid::Ulid;
+
+use crate::data::common;
+use crate::database::InsertQuery;
+
+/// An emoji Bloom uses in its messages, which servers can replace with `/config emoji`.
+#[derive(ChoiceParameter, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EmojiName {
+  #[name = "info"]
+  Info,
+  #[name = "check"]
+  Check,
+  #[name = "cross"]
+  Cross,
+  #[name = "glow"]
+  Glow,
+  #[name = "aww"]
+  Aww,
+  #[name = "love it"]
+  LoveIt,
+}
+
+impl EmojiName {
+  pub fn as_str(self) -> &'static str {
+    match self {
+      Self::Info => "mminfo",
+      Self::Check => "mmcheck",
+      Self::Cross => "mmx",
+      Self::Glow => "pepeglow",
+      Self::Aww => "aww",
+      Self::LoveIt => "loveit",
+    }
+  }
+
+  fn from_name(name: &str) -> Option<Self> {
+    match name {
+      "mminfo" => Some(Self::Info),
+      "mmcheck" => Some(Self::Check),
+      "mmx" => Some(Self::Cross),
+      "pepeglow" => Some(Self::Glow),
+      "aww" => Some(Self::Aww),
+      "loveit" => Some(Self::LoveIt),
+      _ => None,
+    }
+  }
+}
+
+/// A server's replacement for one of Bloom's emoji. The emoji is stored as it appears in
+/// messages, either a Unicode emoji or a custom emoji such as `<:name:id>`.
+pub struct GuildEmoji {
+  pub id: String,
+  pub guild_id: GuildId,
+  pub name: EmojiName,
+  pub emoji: String,
+}
+
+impl GuildEmoji {
+  pub fn new(guild_id: GuildId, name: EmojiName, emoji: String) -> Self {
+    Self {
+      id: Ulid::new().to_string(),
+      guild_id,
+      name,
+      emoji,
+    }
+  }
+
+  pub fn retrieve_all<'a>(guild_id: GuildId) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as("SELECT record_id, guild_id, name, emoji FROM guild_emoji WHERE guild_id = $1")
+      .bind(guild_id.to_string())
+  }
+
+  /// Retrieves the replacement emoji for every server, so they can be loaded on startup.
+  pub fn retrieve_every_guild<'a>() -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as("SELECT record_id, guild_id, name, emoji FROM guild_emoji")
+  }
+
+  pub fn remove(guild_id: GuildId, name: EmojiName) -> Query<'static, Postgres, PgArguments> {
+    query!(
+      "DELETE FROM guild_emoji WHERE guild_id = $1 AND name = $2",
+      guild_id.to_string(),
+      name.as_str(),
+    )
+  }
+}
+
+impl InsertQuery for GuildEmoji {
+  /// Adds a [`GuildEmoji`] to the database, replacing the server's previous choice for the
+  /// same emoji.
+  fn insert_query(&self) -> Query<Postgres, PgArguments> {
+    query!(
+      "INSERT INTO guild_emoji (record_id, guild_id, name, emoji) VALUES ($1, $2, $3, $4) ON CONFLICT (guild_id, name) DO UPDATE SET emoji = EXCLUDED.emoji",
+      self.id,
+      self.guild_id.to_string(),
+      self.name.as_str(),
+      self.emoji,
+    )
+  }
+}
+
+impl FromRow<'_, PgRow> for GuildEmoji {
+  fn from_row(row: &'_ PgRow) -> SqlxResult<Self, SqlxError> {
+    let name: String = row.try_get("name")?;
+
+    Ok(Self {
+      id: row.try_get("record_id")?,
+      guild_id: GuildId::new(common::decode_id_row(row, "guild_id")?),
+      name: EmojiName::from_name(&name).ok_or_else(|| SqlxError::ColumnDecode {
+        index: "name".to_owned(),
+        source: format!("Unknown emoji name: {name}").into(),
+      })?,
+      emoji: row.try_get("emoji")?,
+    })
+  }
+}
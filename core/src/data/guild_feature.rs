@@ -0,0 +1,116 @@
+use poise::serenity_prelude::GuildId;
+use poise::ChoiceParameter;
+use sqlx::postgres::{PgArguments, PgRow};
+use sqlx::query::{Query, QueryAs};
+use sqlx::{Error as SqlxError, FromRow, Postgres, Result as SqlxResult, Row};
+use ulid::Ulid;
+
+use crate::data::common;
+use crate::database::InsertQuery;
+
+/// A part of Bloom which servers can turn off with `/config features`. Servers which haven't
+/// turned a feature on or off use the default from the config file, which is on.
+#[derive(ChoiceParameter, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Feature {
+  #[name = "starboard"]
+  Starboard,
+  #[name = "quotes"]
+  Quotes,
+  #[name = "streak announcements"]
+  StreakAnnouncements,
+  #[name = "AI search"]
+  AiSearch,
+}
+
+impl Feature {
+  pub fn as_str(self) -> &'static str {
+    match self {
+      Self::Starboard => "starboard",
+      Self::Quotes => "quotes",
+      Self::StreakAnnouncements => "streak_announcements",
+      Self::AiSearch => "ai_search",
+    }
+  }
+
+  fn from_name(feature: &str) -> Option<Self> {
+    match feature {
+      "starboard" => Some(Self::Starboard),
+      "quotes" => Some(Self::Quotes),
+      "streak_announcements" => Some(Self::StreakAnnouncements),
+      "ai_search" => Some(Self::AiSearch),
+      _ => None,
+    }
+  }
+
+  /// A short description of the feature, for `/config features`.
+  pub fn description(self) -> &'static str {
+    match self {
+      Self::Starboard => "Starred messages are posted to the starboard",
+      Self::Quotes => "Random quotes with `/quote` and on tracking confirmations",
+      Self::StreakAnnouncements => "Congratulations when members earn a new streak role",
+      Self::AiSearch => "Semantic search with OpenAI in `/glossary search`",
+    }
+  }
+}
+
+/// Whether a server has turned a [`Feature`] on or off.
+pub struct GuildFeature {
+  pub id: String,
+  pub guild_id: GuildId,
+  pub feature: Feature,
+  pub enabled: bool,
+}
+
+impl GuildFeature {
+  pub fn new(guild_id: GuildId, feature: Feature, enabled: bool) -> Self {
+    Self {
+      id: Ulid::new().to_string(),
+      guild_id,
+      feature,
+      enabled,
+    }
+  }
+
+  pub fn retrieve_all<'a>(guild_id: GuildId) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT record_id, guild_id, feature, enabled FROM guild_feature WHERE guild_id = $1",
+    )
+    .bind(guild_id.to_string())
+  }
+
+  /// Retrieves the features every server has turned on or off, so they can be loaded on
+  /// startup.
+  pub fn retrieve_every_guild<'a>() -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as("SELECT record_id, guild_id, feature, enabled FROM guild_feature")
+  }
+}
+
+impl InsertQuery for GuildFeature {
+  /// Adds a [`GuildFeature`] to the database, replacing the server's previous choice for
+  /// the same feature.
+  fn insert_query(&self) -> Query<Postgres, PgArguments> {
+    query!(
+      "INSERT INTO guild_feature (record_id, guild_id, feature, enabled) VALUES ($1, $2, $3, $4) ON CONFLICT (guild_id, feature) DO UPDATE SET enabled = EXCLUDED.enabled",
+      self.id,
+      self.guild_id.to_string(),
+      self.feature.as_str(),
+      self.enabled,
+    )
+  }
+}
+
+impl FromRow<'_, PgRow> for GuildFeature {
+  fn from_row(row: &'_ PgRow) -> SqlxResult<Self, SqlxError> {
+    let feature: String = row.try_get("feature")?;
+
+    Ok(Self {
+      id: row.try_get("record_id")?,
+      guild_id: GuildId::new(common::decode_id_row(row, "guild_id")?),
+      feature: Feature::from_name(&feature).ok_or_else(|| SqlxError::ColumnDecode {
+        index: "feature".to_owned(),
+        source: format!("Unknown feature: {feature}").into(),
+      })?,
+      enabled: row.try_get("enabled")?,
+    })
+  }
+}
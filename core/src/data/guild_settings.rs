@@ -0,0 +1,333 @@
+use chrono::NaiveDate;
+use poise::serenity_prelude::{ChannelId, GuildId, RoleId};
+use sqlx::postgres::{PgArguments, PgRow};
+use sqlx::query::{Query, QueryAs};
+use sqlx::{Error as SqlxError, FromRow, Postgres, Result as SqlxResult, Row};
+
+use crate::data::common;
+use crate::database::InsertQuery;
+
+/// The farewell message used until staff set their own. Matches the default in the
+/// `guild_settings` table, so existing servers keep their farewells.
+pub const DEFAULT_FAREWELL: &str = "We wish you well on your future endeavors, {user} :pray:";
+
+/// Server-wide settings, configured by staff using `/config`.
+#[derive(Debug)]
+pub struct GuildSettings {
+  pub guild_id: GuildId,
+  /// Whether tracking confirmations include a random quote.
+  pub quotes_on_add: bool,
+  /// The maximum distance score for `/glossary search` results, from 0 (exact matches
+  /// only) to 1. Lower values return fewer, more relevant results.
+  pub search_threshold: f64,
+  /// The soft monthly limit on estimated OpenAI API spend, in US dollars. Semantic
+  /// search is disabled for the rest of the month once the limit is reached.
+  pub ai_monthly_cap: Option<f64>,
+  /// The official tracking channel. When set, `/add` can only be used in this channel, and
+  /// members who describe a session in a plain message here are reminded to use `/add`.
+  pub tracking_channel: Option<ChannelId>,
+  /// Whether plain messages in the tracking channel which look like a session get a hint
+  /// to use `/add`.
+  pub tracking_hints: bool,
+  /// How often the server's collective meditation time is celebrated, in minutes. For
+  /// example, `60000` announces every 1,000 hours. `None` turns milestones off.
+  pub milestone_interval: Option<i64>,
+  /// The channel for the monthly "most improved" shout-out. `None` turns the shout-out off.
+  pub improved_channel: Option<ChannelId>,
+  /// The channel where `/sitnow` sits are announced. When `None`, sits are announced in the
+  /// channel where `/sitnow` is used.
+  pub sit_channel: Option<ChannelId>,
+  /// The channel for welcome and farewell messages. `None` turns both off.
+  pub greeting_channel: Option<ChannelId>,
+  /// The message posted when a member joins, with `{user}` and `{member_count}`
+  /// placeholders. `None` turns welcomes off.
+  pub welcome_message: Option<String>,
+  /// The message posted when a member leaves, with the same placeholders as
+  /// `welcome_message`. `None` turns farewells off.
+  pub farewell_message: Option<String>,
+  /// The channel where private threads are created for support tickets. `None` turns
+  /// tickets off.
+  pub ticket_channel: Option<ChannelId>,
+  /// Whether announcements Bloom posts in announcement channels are published, so that
+  /// servers following the channel receive them.
+  pub auto_publish: bool,
+  /// The channel where giveaway winners who don't accept DMs are pinged to claim their key
+  /// privately. `None` means staff hand out the key manually instead.
+  pub key_claim_channel: Option<ChannelId>,
+  /// The title of the monthly challenge winner announcement. `None` uses the default.
+  pub winner_title: Option<String>,
+  /// The body of the monthly challenge winner announcement, with `{user}`, `{month}`,
+  /// `{year}`, and `{minutes}` placeholders. `None` uses the default.
+  pub winner_message: Option<String>,
+  /// The URL of an image shown in the monthly challenge winner announcement.
+  pub winner_image: Option<String>,
+  /// Whether meditation is recorded with the channel it was tracked in, so that stats and
+  /// leaderboards can be filtered by channel.
+  pub channel_segments: bool,
+  /// The role needed to play talks in voice channels with `/talk`. `None` lets everyone
+  /// play talks.
+  pub talk_role: Option<RoleId>,
+  /// Whether the guild's meditation counts toward the anonymous totals shown with
+  /// `/stats global`.
+  pub global_stats: bool,
+}
+
+impl GuildSettings {
+  /// Creates [`GuildSettings`] for the specified [`GuildId`], with all settings
+  /// set to their defaults.
+  pub fn new(guild_id: GuildId) -> Self {
+    Self {
+      guild_id,
+      quotes_on_add: true,
+      search_threshold: 0.3,
+      ai_monthly_cap: None,
+      tracking_channel: None,
+      tracking_hints: true,
+      milestone_interval: Some(60000),
+      improved_channel: None,
+      sit_channel: None,
+      greeting_channel: None,
+      welcome_message: None,
+      farewell_message: Some(DEFAULT_FAREWELL.to_owned()),
+      ticket_channel: None,
+      auto_publish: false,
+      key_claim_channel: None,
+      winner_title: None,
+      winner_message: None,
+      winner_image: None,
+      channel_segments: false,
+      talk_role: None,
+      global_stats: false,
+    }
+  }
+
+  /// Sets whether tracking confirmations include a random quote.
+  pub fn quotes_on_add(mut self, quotes_on_add: bool) -> Self {
+    self.quotes_on_add = quotes_on_add;
+    self
+  }
+
+  /// Sets the maximum distance score for `/glossary search` results.
+  pub fn search_threshold(mut self, search_threshold: f64) -> Self {
+    self.search_threshold = search_threshold;
+    self
+  }
+
+  /// Sets the soft monthly limit on estimated OpenAI API spend, or removes it if `None`.
+  pub fn ai_monthly_cap(mut self, ai_monthly_cap: Option<f64>) -> Self {
+    self.ai_monthly_cap = ai_monthly_cap;
+    self
+  }
+
+  /// Sets the official tracking channel, or removes it if `None`.
+  pub fn tracking_channel(mut self, tracking_channel: Option<ChannelId>) -> Self {
+    self.tracking_channel = tracking_channel;
+    self
+  }
+
+  /// Sets whether session-like messages in the tracking channel get a hint to use `/add`.
+  pub fn tracking_hints(mut self, tracking_hints: bool) -> Self {
+    self.tracking_hints = tracking_hints;
+    self
+  }
+
+  /// Sets how often collective meditation time is celebrated, in minutes, or turns
+  /// milestones off if `None`.
+  pub fn milestone_interval(mut self, milestone_interval: Option<i64>) -> Self {
+    self.milestone_interval = milestone_interval;
+    self
+  }
+
+  /// Sets the channel for the monthly "most improved" shout-out, or turns it off if `None`.
+  pub fn improved_channel(mut self, improved_channel: Option<ChannelId>) -> Self {
+    self.improved_channel = improved_channel;
+    self
+  }
+
+  /// Sets the channel where `/sitnow` sits are announced, or removes it if `None`.
+  pub fn sit_channel(mut self, sit_channel: Option<ChannelId>) -> Self {
+    self.sit_channel = sit_channel;
+    self
+  }
+
+  /// Sets the channel for welcome and farewell messages, or turns both off if `None`.
+  pub fn greeting_channel(mut self, greeting_channel: Option<ChannelId>) -> Self {
+    self.greeting_channel = greeting_channel;
+    self
+  }
+
+  /// Sets the message posted when a member joins, or turns welcomes off if `None`.
+  pub fn welcome_message(mut self, welcome_message: Option<String>) -> Self {
+    self.welcome_message = welcome_message;
+    self
+  }
+
+  /// Sets the message posted when a member leaves, or turns farewells off if `None`.
+  pub fn farewell_message(mut self, farewell_message: Option<String>) -> Self {
+    self.farewell_message = farewell_message;
+    self
+  }
+
+  /// Sets the channel for support ticket threads, or turns tickets off if `None`.
+  pub fn ticket_channel(mut self, ticket_channel: Option<ChannelId>) -> Self {
+    self.ticket_channel = ticket_channel;
+    self
+  }
+
+  /// Sets whether announcements in announcement channels are published automatically.
+  pub fn auto_publish(mut self, auto_publish: bool) -> Self {
+    self.auto_publish = auto_publish;
+    self
+  }
+
+  /// Sets the channel where winners who don't accept DMs claim their key, or removes it if
+  /// `None`.
+  pub fn key_claim_channel(mut self, key_claim_channel: Option<ChannelId>) -> Self {
+    self.key_claim_channel = key_claim_channel;
+    self
+  }
+
+  /// Sets the title of the winner announcement, or restores the default if `None`.
+  pub fn winner_title(mut self, winner_title: Option<String>) -> Self {
+    self.winner_title = winner_title;
+    self
+  }
+
+  /// Sets the body of the winner announcement, or restores the default if `None`.
+  pub fn winner_message(mut self, winner_message: Option<String>) -> Self {
+    self.winner_message = winner_message;
+    self
+  }
+
+  /// Sets the image shown in the winner announcement, or removes it if `None`.
+  pub fn winner_image(mut self, winner_image: Option<String>) -> Self {
+    self.winner_image = winner_image;
+    self
+  }
+
+  /// Sets whether meditation is recorded with the channel it was tracked in.
+  pub fn channel_segments(mut self, channel_segments: bool) -> Self {
+    self.channel_segments = channel_segments;
+    self
+  }
+
+  /// Sets the role needed to play talks, or lets everyone play them if `None`.
+  pub fn talk_role(mut self, talk_role: Option<RoleId>) -> Self {
+    self.talk_role = talk_role;
+    self
+  }
+
+  /// Sets whether the guild's meditation counts toward global stats.
+  pub fn global_stats(mut self, global_stats: bool) -> Self {
+    self.global_stats = global_stats;
+    self
+  }
+
+  /// Retrieves the [`GuildSettings`] for the specified [`GuildId`], if any have been saved.
+  pub fn retrieve<'a>(guild_id: GuildId) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT guild_id, quotes_on_add, search_threshold, ai_monthly_cap, tracking_channel, tracking_hints, milestone_interval, improved_channel, sit_channel, greeting_channel, welcome_message, farewell_message, ticket_channel, auto_publish, key_claim_channel, winner_title, winner_message, winner_image, channel_segments, talk_role, global_stats FROM guild_settings WHERE guild_id = $1",
+    )
+    .bind(guild_id.to_string())
+  }
+
+  /// Retrieves the [`GuildSettings`] for all guilds with a "most improved" shout-out which
+  /// hasn't yet been posted for the month starting on `month`.
+  pub fn retrieve_improved_due<'a>(month: NaiveDate) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT guild_id, quotes_on_add, search_threshold, ai_monthly_cap, tracking_channel, tracking_hints, milestone_interval, improved_channel, sit_channel, greeting_channel, welcome_message, farewell_message, ticket_channel, auto_publish, key_claim_channel, winner_title, winner_message, winner_image, channel_segments, talk_role, global_stats FROM guild_settings WHERE improved_channel IS NOT NULL AND (improved_posted_for IS NULL OR improved_posted_for < $1)",
+    )
+    .bind(month)
+  }
+
+  /// Retrieves the [`GuildSettings`] for all guilds with support tickets turned on.
+  pub fn retrieve_ticket_enabled<'a>() -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT guild_id, quotes_on_add, search_threshold, ai_monthly_cap, tracking_channel, tracking_hints, milestone_interval, improved_channel, sit_channel, greeting_channel, welcome_message, farewell_message, ticket_channel, auto_publish, key_claim_channel, winner_title, winner_message, winner_image, channel_segments, talk_role, global_stats FROM guild_settings WHERE ticket_channel IS NOT NULL",
+    )
+  }
+
+  /// Records that the "most improved" shout-out has been posted for the month starting on
+  /// `month`.
+  pub fn mark_improved_posted<'a>(
+    guild_id: GuildId,
+    month: NaiveDate,
+  ) -> Query<'a, Postgres, PgArguments> {
+    query!(
+      "UPDATE guild_settings SET improved_posted_for = $1 WHERE guild_id = $2",
+      month,
+      guild_id.to_string(),
+    )
+  }
+}
+
+impl InsertQuery for GuildSettings {
+  /// Saves [`GuildSettings`] to the database, replacing any existing settings for the guild.
+  fn insert_query(&self) -> Query<Postgres, PgArguments> {
+    query!(
+      "INSERT INTO guild_settings (guild_id, quotes_on_add, search_threshold, ai_monthly_cap, tracking_channel, tracking_hints, milestone_interval, improved_channel, sit_channel, greeting_channel, welcome_message, farewell_message, ticket_channel, auto_publish, key_claim_channel, winner_title, winner_message, winner_image, channel_segments, talk_role, global_stats) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20, $21) ON CONFLICT (guild_id) DO UPDATE SET quotes_on_add = $2, search_threshold = $3, ai_monthly_cap = $4, tracking_channel = $5, tracking_hints = $6, milestone_interval = $7, improved_channel = $8, sit_channel = $9, greeting_channel = $10, welcome_message = $11, farewell_message = $12, ticket_channel = $13, auto_publish = $14, key_claim_channel = $15, winner_title = $16, winner_message = $17, winner_image = $18, channel_segments = $19, talk_role = $20, global_stats = $21",
+      self.guild_id.to_string(),
+      self.quotes_on_add,
+      self.search_threshold,
+      self.ai_monthly_cap,
+      self.tracking_channel.map(|channel_id| channel_id.to_string()),
+      self.tracking_hints,
+      self.milestone_interval,
+      self.improved_channel.map(|channel_id| channel_id.to_string()),
+      self.sit_channel.map(|channel_id| channel_id.to_string()),
+      self.greeting_channel.map(|channel_id| channel_id.to_string()),
+      self.welcome_message,
+      self.farewell_message,
+      self.ticket_channel.map(|channel_id| channel_id.to_string()),
+      self.auto_publish,
+      self.key_claim_channel.map(|channel_id| channel_id.to_string()),
+      self.winner_title,
+      self.winner_message,
+      self.winner_image,
+      self.channel_segments,
+      self.talk_role.map(|role_id| role_id.to_string()),
+      self.global_stats,
+    )
+  }
+}
+
+impl FromRow<'_, PgRow> for GuildSettings {
+  fn from_row(row: &'_ PgRow) -> SqlxResult<Self, SqlxError> {
+    let guild_id = GuildId::new(common::decode_id_row(row, "guild_id")?);
+    let tracking_channel =
+      common::decode_option_id_row(row, "tracking_channel")?.map(ChannelId::new);
+    let improved_channel =
+      common::decode_option_id_row(row, "improved_channel")?.map(ChannelId::new);
+    let sit_channel = common::decode_option_id_row(row, "sit_channel")?.map(ChannelId::new);
+    let greeting_channel =
+      common::decode_option_id_row(row, "greeting_channel")?.map(ChannelId::new);
+    let ticket_channel = common::decode_option_id_row(row, "ticket_channel")?.map(ChannelId::new);
+    let key_claim_channel =
+      common::decode_option_id_row(row, "key_claim_channel")?.map(ChannelId::new);
+    let talk_role = common::decode_option_id_row(row, "talk_role")?.map(RoleId::new);
+
+    Ok(Self {
+      guild_id,
+      quotes_on_add: row.try_get("quotes_on_add")?,
+      search_threshold: row.try_get("search_threshold")?,
+      ai_monthly_cap: row.try_get("ai_monthly_cap")?,
+      tracking_channel,
+      tracking_hints: row.try_get("tracking_hints")?,
+      milestone_interval: row.try_get("milestone_interval")?,
+      improved_channel,
+      sit_channel,
+      greeting_channel,
+      welcome_message: row.try_get("welcome_message")?,
+      farewell_message: row.try_get("farewell_message")?,
+      ticket_channel,
+      auto_publish: row.try_get("auto_publish")?,
+      key_claim_channel,
+      winner_title: row.try_get("winner_title")?,
+      winner_message: row.try_get("winner_message")?,
+      winner_image: row.try_get("winner_image")?,
+      channel_segments: row.try_get("channel_segments")?,
+      talk_role,
+      global_stats: row.try_get("global_stats")?,
+    })
+  }
+}
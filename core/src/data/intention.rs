@@ -0,0 +1,318 @@
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+use poise::serenity_prelude::{GuildId, UserId};
+use poise::ChoiceParameter;
+use sqlx::postgres::{PgArguments, PgRow};
+use sqlx::query::{Query, QueryAs};
+use sqlx::{Error as SqlxError, FromRow, Postgres, Result as SqlxResult, Row};
+use ulid::Ulid;
+
+use crate::data::common;
+use crate::database::{DeleteQuery, InsertQuery};
+use crate::pagination::{PageRow, PageType};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ChoiceParameter)]
+pub enum PromptCategory {
+  #[name = "mindfulness"]
+  Mindfulness,
+  #[name = "compassion"]
+  Compassion,
+  #[name = "gratitude"]
+  Gratitude,
+  #[name = "letting go"]
+  LettingGo,
+  #[name = "everyday life"]
+  EverydayLife,
+}
+
+impl PromptCategory {
+  pub fn as_str(self) -> &'static str {
+    match self {
+      Self::Mindfulness => "mindfulness",
+      Self::Compassion => "compassion",
+      Self::Gratitude => "gratitude",
+      Self::LettingGo => "letting go",
+      Self::EverydayLife => "everyday life",
+    }
+  }
+
+  fn from_name(category: &str) -> Self {
+    match category {
+      "compassion" => Self::Compassion,
+      "gratitude" => Self::Gratitude,
+      "letting go" => Self::LettingGo,
+      "everyday life" => Self::EverydayLife,
+      _ => Self::Mindfulness,
+    }
+  }
+}
+
+/// A practice prompt curated by staff, such as "Notice three breaths before each meal". One
+/// prompt from the pool is the intention for each day, shown by `/intention today`, sent to
+/// members who have asked to be prompted each morning, and filled in for `{intention}` in
+/// recurring posts.
+pub struct PracticePrompt {
+  pub id: String,
+  pub guild_id: GuildId,
+  pub category: PromptCategory,
+  pub prompt: String,
+  pub added_by: UserId,
+}
+
+impl PracticePrompt {
+  pub fn new(
+    guild_id: GuildId,
+    category: PromptCategory,
+    prompt: String,
+    added_by: UserId,
+  ) -> Self {
+    Self {
+      id: Ulid::new().to_string(),
+      guild_id,
+      category,
+      prompt,
+      added_by,
+    }
+  }
+
+  /// Retrieves every [`PracticePrompt`] in a guild, optionally only from one category, oldest
+  /// first.
+  pub fn retrieve_all<'a>(
+    guild_id: GuildId,
+    category: Option<PromptCategory>,
+  ) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT record_id, guild_id, category, prompt, added_by FROM practice_prompt WHERE guild_id = $1 AND ($2::TEXT IS NULL OR category = $2) ORDER BY record_id",
+    )
+    .bind(guild_id.to_string())
+    .bind(category.map(PromptCategory::as_str))
+  }
+
+  /// Picks the prompt for `date` from a pool. The pick looks random, but is the same for
+  /// everyone all day, so members, morning DMs, and scheduled posts share one intention.
+  /// Returns [`None`] if the pool is empty.
+  pub fn for_day(prompts: &[Self], date: NaiveDate) -> Option<&Self> {
+    if prompts.is_empty() {
+      return None;
+    }
+
+    // SplitMix64, which spreads consecutive days across the pool
+    let mut hash = u64::try_from(date.num_days_from_ce())
+      .unwrap_or_default()
+      .wrapping_add(0x9E37_79B9_7F4A_7C15);
+    hash = (hash ^ (hash >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    hash = (hash ^ (hash >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    hash ^= hash >> 31;
+
+    let pool = u64::try_from(prompts.len()).unwrap_or(u64::MAX);
+    let index = usize::try_from(hash % pool).unwrap_or_default();
+    prompts.get(index)
+  }
+
+  /// Fills in the `{intention}` placeholder of a recurring post with the prompt.
+  pub fn render(&self, template: &str) -> String {
+    template.replace("{intention}", &self.prompt)
+  }
+}
+
+impl InsertQuery for PracticePrompt {
+  /// Adds a [`PracticePrompt`] to the database.
+  fn insert_query(&self) -> Query<Postgres, PgArguments> {
+    query!(
+      "INSERT INTO practice_prompt (record_id, guild_id, category, prompt, added_by) VALUES ($1, $2, $3, $4, $5)",
+      self.id,
+      self.guild_id.to_string(),
+      self.category.as_str(),
+      self.prompt,
+      self.added_by.to_string(),
+    )
+  }
+}
+
+impl DeleteQuery for PracticePrompt {
+  fn delete_query<'a>(
+    guild_id: GuildId,
+    prompt_id: impl Into<String>,
+  ) -> Query<'a, Postgres, PgArguments> {
+    query!(
+      "DELETE FROM practice_prompt WHERE guild_id = $1 AND record_id = $2",
+      guild_id.to_string(),
+      prompt_id.into(),
+    )
+  }
+}
+
+impl PageRow for PracticePrompt {
+  fn title(&self, _page_type: PageType) -> String {
+    format!("ID: {}", self.id)
+  }
+
+  fn body(&self) -> String {
+    format!(
+      "> {}\n> -# Category: {}\n** **",
+      self.prompt,
+      self.category.name()
+    )
+  }
+}
+
+impl FromRow<'_, PgRow> for PracticePrompt {
+  fn from_row(row: &'_ PgRow) -> SqlxResult<Self, SqlxError> {
+    Ok(Self {
+      id: row.try_get("record_id")?,
+      guild_id: GuildId::new(common::decode_id_row(row, "guild_id")?),
+      category: PromptCategory::from_name(row.try_get("category")?),
+      prompt: row.try_get("prompt")?,
+      added_by: UserId::new(common::decode_id_row(row, "added_by")?),
+    })
+  }
+}
+
+/// A member who has asked to be sent the day's intention by DM each morning, at
+/// `reminder_hour` in the UTC offset set with `/customize offset`.
+pub struct IntentionSubscription {
+  pub id: String,
+  pub guild_id: GuildId,
+  pub user_id: UserId,
+  pub reminder_hour: i16,
+}
+
+impl IntentionSubscription {
+  pub fn new(guild_id: GuildId, user_id: UserId, reminder_hour: i16) -> Self {
+    Self {
+      id: Ulid::new().to_string(),
+      guild_id,
+      user_id,
+      reminder_hour,
+    }
+  }
+
+  pub fn retrieve<'a>(
+    guild_id: GuildId,
+    user_id: UserId,
+  ) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT record_id, guild_id, user_id, reminder_hour FROM intention_subscription WHERE guild_id = $1 AND user_id = $2",
+    )
+    .bind(guild_id.to_string())
+    .bind(user_id.to_string())
+  }
+}
+
+impl InsertQuery for IntentionSubscription {
+  /// Adds an [`IntentionSubscription`] to the database, or changes the hour of the member's
+  /// existing subscription.
+  fn insert_query(&self) -> Query<Postgres, PgArguments> {
+    query!(
+      "INSERT INTO intention_subscription (record_id, guild_id, user_id, reminder_hour) VALUES ($1, $2, $3, $4) ON CONFLICT (guild_id, user_id) DO UPDATE SET reminder_hour = $4",
+      self.id,
+      self.guild_id.to_string(),
+      self.user_id.to_string(),
+      self.reminder_hour,
+    )
+  }
+}
+
+impl DeleteQuery for IntentionSubscription {
+  fn delete_query<'a>(
+    guild_id: GuildId,
+    user_id: impl Into<String>,
+  ) -> Query<'a, Postgres, PgArguments> {
+    query!(
+      "DELETE FROM intention_subscription WHERE guild_id = $1 AND user_id = $2",
+      guild_id.to_string(),
+      user_id.into(),
+    )
+  }
+}
+
+impl FromRow<'_, PgRow> for IntentionSubscription {
+  fn from_row(row: &'_ PgRow) -> SqlxResult<Self, SqlxError> {
+    Ok(Self {
+      id: row.try_get("record_id")?,
+      guild_id: GuildId::new(common::decode_id_row(row, "guild_id")?),
+      user_id: UserId::new(common::decode_id_row(row, "user_id")?),
+      reminder_hour: row.try_get("reminder_hour")?,
+    })
+  }
+}
+
+/// A member whose local time has passed the hour they asked to be sent the day's intention,
+/// and who hasn't been sent it yet today.
+pub struct IntentionReminder {
+  pub user_id: UserId,
+  pub guild_id: GuildId,
+  /// The member's local date, used to pick the day's intention and to only send one
+  /// reminder per day.
+  pub local_date: NaiveDate,
+}
+
+impl IntentionReminder {
+  /// Retrieves an [`IntentionReminder`] for every subscribed member who is due one. Days
+  /// are local to each member's UTC offset, or UTC if they haven't set one.
+  pub fn retrieve_due(now: &DateTime<Utc>) -> QueryAs<'_, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "WITH subscriptions AS (SELECT intention_subscription.user_id, intention_subscription.guild_id, intention_subscription.reminder_hour, intention_subscription.sent_on, ($1 AT TIME ZONE 'UTC') + make_interval(mins => COALESCE(tracking_profile.utc_offset, 0)) AS local_now FROM intention_subscription LEFT JOIN tracking_profile ON tracking_profile.user_id = intention_subscription.user_id AND tracking_profile.guild_id = intention_subscription.guild_id) SELECT user_id, guild_id, date_trunc('day', local_now)::date AS local_date FROM subscriptions WHERE EXTRACT(HOUR FROM local_now) >= reminder_hour AND (sent_on IS NULL OR sent_on < date_trunc('day', local_now)::date)",
+    )
+    .bind(now)
+  }
+
+  /// Records that the day's intention has been sent on the member's local date.
+  pub fn mark_sent(&self) -> Query<'_, Postgres, PgArguments> {
+    query!(
+      "UPDATE intention_subscription SET sent_on = $1 WHERE user_id = $2 AND guild_id = $3",
+      self.local_date,
+      self.user_id.to_string(),
+      self.guild_id.to_string(),
+    )
+  }
+}
+
+impl FromRow<'_, PgRow> for IntentionReminder {
+  fn from_row(row: &'_ PgRow) -> SqlxResult<Self, SqlxError> {
+    Ok(Self {
+      user_id: UserId::new(common::decode_id_row(row, "user_id")?),
+      guild_id: GuildId::new(common::decode_id_row(row, "guild_id")?),
+      local_date: row.try_get("local_date")?,
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_for_day() {
+    let prompts: Vec<PracticePrompt> = (0..5)
+      .map(|number| {
+        PracticePrompt::new(
+          GuildId::new(123u64),
+          PromptCategory::Mindfulness,
+          format!("Prompt {number}"),
+          UserId::new(456u64),
+        )
+      })
+      .collect();
+    let date = |day| NaiveDate::from_ymd_opt(2024, 11, day).unwrap_or_default();
+
+    assert!(PracticePrompt::for_day(&[], date(1)).is_none());
+
+    let pick = |day| {
+      PracticePrompt::for_day(&prompts, date(day))
+        .map(|prompt| prompt.prompt.clone())
+        .unwrap_or_default()
+    };
+    assert_eq!(pick(1), "Prompt 0");
+    assert_eq!(pick(2), "Prompt 4");
+    assert_eq!(pick(2), pick(2));
+
+    // Every prompt comes up over the month
+    let picks: std::collections::HashSet<String> = (1..=30).map(pick).collect();
+    assert_eq!(picks.len(), 5);
+
+    assert_eq!(
+      prompts[0].render("Today's intention: {intention}"),
+      "Today's intention: Prompt 0"
+    );
+  }
+}
@@ -0,0 +1,33 @@
+use sqlx::postgres::PgArguments;
+use sqlx::query::{Query, QueryAs};
+use sqlx::{FromRow, Postgres};
+
+use crate::database::InsertQuery;
+
+/// Whether Bloom is undergoing maintenance, during which only staff can use commands.
+#[derive(Debug, Clone, Default, PartialEq, Eq, FromRow)]
+pub struct Maintenance {
+  pub enabled: bool,
+  pub reason: Option<String>,
+}
+
+impl Maintenance {
+  pub fn new(enabled: bool, reason: Option<String>) -> Self {
+    Self { enabled, reason }
+  }
+
+  pub fn retrieve<'a>() -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as("SELECT enabled, reason FROM maintenance")
+  }
+}
+
+impl InsertQuery for Maintenance {
+  /// Saves the [`Maintenance`] setting, replacing the previous one.
+  fn insert_query(&self) -> Query<Postgres, PgArguments> {
+    query!(
+      "INSERT INTO maintenance (enabled, reason) VALUES ($1, $2) ON CONFLICT (singleton) DO UPDATE SET enabled = EXCLUDED.enabled, reason = EXCLUDED.reason, updated_at = CURRENT_TIMESTAMP",
+      self.enabled,
+      self.reason,
+    )
+  }
+}
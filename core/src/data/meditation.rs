@@ -1,13 +1,13 @@
 use chrono::{DateTime, Utc};
-use poise::serenity_prelude::{GuildId, UserId};
+use poise::serenity_prelude::{ChannelId, GuildId, UserId};
 use sqlx::postgres::{PgArguments, PgRow};
-use sqlx::query::{Query, QueryAs};
+use sqlx::query::{Query, QueryAs, QueryScalar};
 use sqlx::{Error as SqlxError, FromRow, Postgres, Result as SqlxResult, Row};
 use ulid::Ulid;
 
-use crate::commands::helpers::pagination::{PageRow, PageType};
 use crate::data::common;
-use crate::handlers::database::{DeleteQuery, InsertQuery, UpdateQuery};
+use crate::database::{DeleteQuery, InsertQuery, UpdateQuery};
+use crate::pagination::{PageRow, PageType};
 
 #[derive(Default)]
 pub struct Meditation {
@@ -17,6 +17,8 @@ pub struct Meditation {
   pub minutes: i32,
   pub seconds: i32,
   pub occurred_at: DateTime<Utc>,
+  /// The channel the meditation was tracked in, when the guild has channel segments turned on.
+  pub channel_id: Option<ChannelId>,
 }
 
 impl Meditation {
@@ -38,9 +40,17 @@ impl Meditation {
       minutes,
       seconds,
       occurred_at: *datetime,
+      channel_id: None,
     }
   }
 
+  /// Records the channel the meditation was tracked in.
+  #[must_use]
+  pub fn channel(mut self, channel_id: Option<ChannelId>) -> Self {
+    self.channel_id = channel_id;
+    self
+  }
+
   /// Creates a new [`Meditation`] with the specified `minutes`, `seconds`, and `datetime`,
   /// taking all other values from `self`. Used for updating a meditation entry, while still
   /// being able to reference the previous values.
@@ -52,6 +62,7 @@ impl Meditation {
       minutes,
       seconds,
       occurred_at: *datetime,
+      channel_id: self.channel_id,
     }
   }
 
@@ -61,7 +72,7 @@ impl Meditation {
     meditation_id: &str,
   ) -> QueryAs<'_, Postgres, Self, PgArguments> {
     sqlx::query_as(
-      "SELECT record_id, guild_id, user_id, meditation_minutes, meditation_seconds, occurred_at FROM meditation WHERE record_id = $1 AND guild_id = $2",
+      "SELECT record_id, guild_id, user_id, meditation_minutes, meditation_seconds, occurred_at, channel_id FROM meditation WHERE record_id = $1 AND guild_id = $2",
     )
     .bind(meditation_id)
     .bind(guild_id.to_string())
@@ -137,18 +148,47 @@ impl Meditation {
     sqlx::query_as("SELECT COUNT(record_id) AS count FROM meditation WHERE guild_id = $1")
       .bind(guild_id.to_string())
   }
+
+  /// Retrieves a guild's [`Meditation`] entries for an anonymized dataset, oldest first.
+  /// Only members with at least `min_sessions` entries are included, so that members who
+  /// meditate rarely can't be singled out, and members whose stats are private are left out.
+  pub fn anonymizable<'a>(
+    guild_id: GuildId,
+    min_sessions: i64,
+  ) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT m.record_id, m.guild_id, m.user_id, m.meditation_minutes, m.meditation_seconds, m.occurred_at FROM meditation m LEFT JOIN tracking_profile t ON t.user_id = m.user_id AND t.guild_id = m.guild_id WHERE m.guild_id = $1 AND COALESCE(t.stats_private, FALSE) = FALSE AND m.user_id IN (SELECT user_id FROM meditation WHERE guild_id = $1 GROUP BY user_id HAVING COUNT(*) >= $2) ORDER BY m.occurred_at",
+    )
+    .bind(guild_id.to_string())
+    .bind(min_sessions)
+  }
+
+  /// Retrieves the ID of the guild a user most recently tracked meditation in.
+  pub fn latest_guild<'a>(user_id: UserId) -> QueryScalar<'a, Postgres, String, PgArguments> {
+    sqlx::query_scalar(
+      "SELECT guild_id FROM meditation WHERE user_id = $1 ORDER BY occurred_at DESC LIMIT 1",
+    )
+    .bind(user_id.to_string())
+  }
+
+  /// Retrieves the IDs of every user with meditation entries in a guild.
+  pub fn guild_users<'a>(guild_id: GuildId) -> QueryScalar<'a, Postgres, String, PgArguments> {
+    sqlx::query_scalar("SELECT DISTINCT user_id FROM meditation WHERE guild_id = $1")
+      .bind(guild_id.to_string())
+  }
 }
 
 impl InsertQuery for Meditation {
   fn insert_query(&self) -> Query<Postgres, PgArguments> {
     query!(
-      "INSERT INTO meditation (record_id, user_id, meditation_minutes, meditation_seconds, guild_id, occurred_at) VALUES ($1, $2, $3, $4, $5, $6)",
+      "INSERT INTO meditation (record_id, user_id, meditation_minutes, meditation_seconds, guild_id, occurred_at, channel_id) VALUES ($1, $2, $3, $4, $5, $6, $7)",
       self.id,
       self.user_id.to_string(),
       self.minutes,
       self.seconds,
       self.guild_id.to_string(),
       self.occurred_at,
+      self.channel_id.map(|channel_id| channel_id.to_string()),
     )
   }
 }
@@ -156,23 +196,25 @@ impl InsertQuery for Meditation {
 impl UpdateQuery for Meditation {
   fn update_query(&self) -> Query<Postgres, PgArguments> {
     query!(
-      "UPDATE meditation SET meditation_minutes = $1, meditation_seconds = $2, occurred_at = $3 WHERE record_id = $4",
+      "UPDATE meditation SET meditation_minutes = $1, meditation_seconds = $2, occurred_at = $3 WHERE record_id = $4 AND guild_id = $5",
       self.minutes,
       self.seconds,
       self.occurred_at,
       self.id,
+      self.guild_id.to_string(),
     )
   }
 }
 
 impl DeleteQuery for Meditation {
   fn delete_query<'a>(
-    _guild_id: GuildId,
+    guild_id: GuildId,
     meditation_id: impl Into<String>,
   ) -> Query<'a, Postgres, PgArguments> {
     query!(
-      "DELETE FROM meditation WHERE record_id = $1",
+      "DELETE FROM meditation WHERE record_id = $1 AND guild_id = $2",
       meditation_id.into(),
+      guild_id.to_string(),
     )
   }
 }
@@ -221,6 +263,7 @@ impl FromRow<'_, PgRow> for Meditation {
   fn from_row(row: &'_ PgRow) -> SqlxResult<Self, SqlxError> {
     let guild_id = GuildId::new(common::decode_id_row(row, "guild_id")?);
     let user_id = UserId::new(common::decode_id_row(row, "user_id")?);
+    let channel_id = common::decode_option_id_row(row, "channel_id")?.map(ChannelId::new);
 
     Ok(Self {
       id: row.try_get("record_id").unwrap_or_default(),
@@ -229,6 +272,7 @@ impl FromRow<'_, PgRow> for Meditation {
       minutes: row.try_get("meditation_minutes").unwrap_or_default(),
       seconds: row.try_get("meditation_seconds").unwrap_or_default(),
       occurred_at: row.try_get("occurred_at").unwrap_or_default(),
+      channel_id,
     })
   }
 }
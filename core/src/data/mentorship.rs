@@ -0,0 +1,276 @@
+use chrono::{DateTime, Utc};
+use poise::serenity_prelude::{GuildId, UserId};
+use poise::ChoiceParameter;
+use sqlx::postgres::{PgArguments, PgRow};
+use sqlx::query::{Query, QueryAs};
+use sqlx::{Error as SqlxError, FromRow, Postgres, Result as SqlxResult, Row};
+use ulid::Ulid;
+
+use crate::data::common;
+use crate::database::{DeleteQuery, InsertQuery};
+use crate::pagination::{PageRow, PageType};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ChoiceParameter)]
+pub enum MentorRole {
+  #[name = "mentor"]
+  Mentor,
+  #[name = "mentee"]
+  Mentee,
+}
+
+impl MentorRole {
+  pub fn as_str(self) -> &'static str {
+    match self {
+      Self::Mentor => "mentor",
+      Self::Mentee => "mentee",
+    }
+  }
+
+  fn from_name(role: &str) -> Self {
+    match role {
+      "mentor" => Self::Mentor,
+      _ => Self::Mentee,
+    }
+  }
+}
+
+/// A member's signup for the mentorship program, as either a mentor or a mentee. Signups
+/// stay in place after a match, so members are back in the pool when a mentorship ends,
+/// until they withdraw.
+pub struct MentorSignup {
+  pub id: String,
+  pub guild_id: GuildId,
+  pub user_id: UserId,
+  pub role: MentorRole,
+  /// What the member would like to focus on, as a comma-separated list.
+  pub interests: String,
+  /// The member's UTC offset, in minutes.
+  pub utc_offset: i16,
+  pub signed_up_at: Option<DateTime<Utc>>,
+}
+
+impl MentorSignup {
+  pub fn new(
+    guild_id: GuildId,
+    user_id: UserId,
+    role: MentorRole,
+    interests: String,
+    utc_offset: i16,
+  ) -> Self {
+    Self {
+      id: Ulid::new().to_string(),
+      guild_id,
+      user_id,
+      role,
+      interests,
+      utc_offset,
+      signed_up_at: None,
+    }
+  }
+
+  pub fn retrieve<'a>(
+    guild_id: GuildId,
+    user_id: UserId,
+  ) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT record_id, guild_id, user_id, mentor_role, interests, utc_offset, signed_up_at FROM mentorship_signup WHERE guild_id = $1 AND user_id = $2",
+    )
+    .bind(guild_id.to_string())
+    .bind(user_id.to_string())
+  }
+
+  /// Retrieves every [`MentorSignup`] in a guild from members who aren't in an active
+  /// [`Mentorship`], oldest first.
+  pub fn retrieve_unmatched<'a>(guild_id: GuildId) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT record_id, guild_id, user_id, mentor_role, interests, utc_offset, signed_up_at FROM mentorship_signup WHERE guild_id = $1 AND NOT EXISTS (SELECT 1 FROM mentorships WHERE mentorships.guild_id = mentorship_signup.guild_id AND mentorships.ended_at IS NULL AND (mentorships.mentor_id = mentorship_signup.user_id OR mentorships.mentee_id = mentorship_signup.user_id)) ORDER BY signed_up_at",
+    )
+    .bind(guild_id.to_string())
+  }
+}
+
+impl InsertQuery for MentorSignup {
+  /// Adds a [`MentorSignup`] to the database, replacing the member's role, interests, and
+  /// UTC offset if they've already signed up.
+  fn insert_query(&self) -> Query<Postgres, PgArguments> {
+    query!(
+      "INSERT INTO mentorship_signup (record_id, guild_id, user_id, mentor_role, interests, utc_offset) VALUES ($1, $2, $3, $4, $5, $6) ON CONFLICT (guild_id, user_id) DO UPDATE SET mentor_role = $4, interests = $5, utc_offset = $6",
+      self.id,
+      self.guild_id.to_string(),
+      self.user_id.to_string(),
+      self.role.as_str(),
+      self.interests,
+      self.utc_offset,
+    )
+  }
+}
+
+impl DeleteQuery for MentorSignup {
+  fn delete_query<'a>(
+    guild_id: GuildId,
+    user_id: impl Into<String>,
+  ) -> Query<'a, Postgres, PgArguments> {
+    query!(
+      "DELETE FROM mentorship_signup WHERE guild_id = $1 AND user_id = $2",
+      guild_id.to_string(),
+      user_id.into(),
+    )
+  }
+}
+
+impl FromRow<'_, PgRow> for MentorSignup {
+  fn from_row(row: &'_ PgRow) -> SqlxResult<Self, SqlxError> {
+    Ok(Self {
+      id: row.try_get("record_id")?,
+      guild_id: GuildId::new(common::decode_id_row(row, "guild_id")?),
+      user_id: UserId::new(common::decode_id_row(row, "user_id")?),
+      role: MentorRole::from_name(row.try_get("mentor_role")?),
+      interests: row.try_get("interests")?,
+      utc_offset: row.try_get("utc_offset")?,
+      signed_up_at: row.try_get("signed_up_at")?,
+    })
+  }
+}
+
+/// A pairing of a mentor and a mentee, made by staff with `/mentorships match`. Members
+/// can only be in one active mentorship at a time.
+pub struct Mentorship {
+  pub id: String,
+  pub guild_id: GuildId,
+  pub mentor_id: UserId,
+  pub mentee_id: UserId,
+  pub matched_by: UserId,
+  pub started_at: Option<DateTime<Utc>>,
+  pub last_check_in: Option<DateTime<Utc>>,
+}
+
+impl Mentorship {
+  pub fn new(guild_id: GuildId, mentor_id: UserId, mentee_id: UserId, matched_by: UserId) -> Self {
+    Self {
+      id: Ulid::new().to_string(),
+      guild_id,
+      mentor_id,
+      mentee_id,
+      matched_by,
+      started_at: None,
+      last_check_in: None,
+    }
+  }
+
+  /// Retrieves every active [`Mentorship`] in a guild, oldest first.
+  pub fn retrieve_active<'a>(guild_id: GuildId) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT record_id, guild_id, mentor_id, mentee_id, matched_by, started_at, last_check_in FROM mentorships WHERE guild_id = $1 AND ended_at IS NULL ORDER BY started_at",
+    )
+    .bind(guild_id.to_string())
+  }
+
+  /// Retrieves the active [`Mentorship`] a member is in, as either the mentor or the mentee.
+  pub fn retrieve_active_for<'a>(
+    guild_id: GuildId,
+    user_id: UserId,
+  ) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT record_id, guild_id, mentor_id, mentee_id, matched_by, started_at, last_check_in FROM mentorships WHERE guild_id = $1 AND (mentor_id = $2 OR mentee_id = $2) AND ended_at IS NULL",
+    )
+    .bind(guild_id.to_string())
+    .bind(user_id.to_string())
+  }
+
+  /// Retrieves every active [`Mentorship`] in any guild which hasn't had a check-in, or a
+  /// check-in reminder, in the last `interval_days`.
+  pub fn retrieve_due_check_ins<'a>(
+    now: &'a DateTime<Utc>,
+    interval_days: i32,
+  ) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT record_id, guild_id, mentor_id, mentee_id, matched_by, started_at, last_check_in FROM mentorships WHERE ended_at IS NULL AND COALESCE(last_check_in, started_at) <= $1 - make_interval(days => $2) AND (last_reminder IS NULL OR last_reminder <= $1 - make_interval(days => $2))",
+    )
+    .bind(now)
+    .bind(interval_days)
+  }
+
+  /// Records a check-in for the active [`Mentorship`] a member is in.
+  pub fn check_in<'a>(
+    guild_id: GuildId,
+    user_id: UserId,
+    now: &'a DateTime<Utc>,
+  ) -> Query<'a, Postgres, PgArguments> {
+    query!(
+      "UPDATE mentorships SET last_check_in = $1 WHERE guild_id = $2 AND (mentor_id = $3 OR mentee_id = $3) AND ended_at IS NULL",
+      now,
+      guild_id.to_string(),
+      user_id.to_string(),
+    )
+  }
+
+  /// Records that a check-in reminder has been sent for a [`Mentorship`].
+  pub fn mark_reminded<'a>(
+    mentorship_id: &'a str,
+    now: &'a DateTime<Utc>,
+  ) -> Query<'a, Postgres, PgArguments> {
+    query!(
+      "UPDATE mentorships SET last_reminder = $1 WHERE record_id = $2",
+      now,
+      mentorship_id,
+    )
+  }
+
+  /// Ends a [`Mentorship`]. Only affects mentorships which are still active.
+  pub fn end(guild_id: GuildId, mentorship_id: &str) -> Query<'_, Postgres, PgArguments> {
+    query!(
+      "UPDATE mentorships SET ended_at = NOW() WHERE record_id = $1 AND guild_id = $2 AND ended_at IS NULL",
+      mentorship_id,
+      guild_id.to_string(),
+    )
+  }
+}
+
+impl InsertQuery for Mentorship {
+  /// Adds a [`Mentorship`] to the database, unless either member is already in an active
+  /// mentorship.
+  fn insert_query(&self) -> Query<Postgres, PgArguments> {
+    query!(
+      "INSERT INTO mentorships (record_id, guild_id, mentor_id, mentee_id, matched_by) VALUES ($1, $2, $3, $4, $5) ON CONFLICT DO NOTHING",
+      self.id,
+      self.guild_id.to_string(),
+      self.mentor_id.to_string(),
+      self.mentee_id.to_string(),
+      self.matched_by.to_string(),
+    )
+  }
+}
+
+impl PageRow for Mentorship {
+  fn title(&self, _page_type: PageType) -> String {
+    format!("ID: {}", self.id)
+  }
+
+  fn body(&self) -> String {
+    let last_check_in = self.last_check_in.map_or_else(
+      || "No check-ins yet".to_owned(),
+      |checked_in_at| format!("Last check-in <t:{}:R>", checked_in_at.timestamp()),
+    );
+
+    format!(
+      "> <@{}> mentoring <@{}>\n> -# Matched <t:{}:D> · {last_check_in}\n** **",
+      self.mentor_id,
+      self.mentee_id,
+      self.started_at.unwrap_or_default().timestamp(),
+    )
+  }
+}
+
+impl FromRow<'_, PgRow> for Mentorship {
+  fn from_row(row: &'_ PgRow) -> SqlxResult<Self, SqlxError> {
+    Ok(Self {
+      id: row.try_get("record_id")?,
+      guild_id: GuildId::new(common::decode_id_row(row, "guild_id")?),
+      mentor_id: UserId::new(common::decode_id_row(row, "mentor_id")?),
+      mentee_id: UserId::new(common::decode_id_row(row, "mentee_id")?),
+      matched_by: UserId::new(common::decode_id_row(row, "matched_by")?),
+      started_at: row.try_get("started_at")?,
+      last_check_in: row.try_get("last_check_in")?,
+    })
+  }
+}
@@ -0,0 +1,32 @@
+use poise::serenity_prelude::GuildId;
+use sqlx::postgres::PgArguments;
+use sqlx::query::Query;
+use sqlx::Postgres;
+use ulid::Ulid;
+
+use crate::database::InsertQuery;
+
+/// A milestone of collective meditation time reached by a guild. Milestones are recorded
+/// so each one is only ever announced once.
+pub struct Milestone {
+  pub guild_id: GuildId,
+  pub minutes: i64,
+}
+
+impl Milestone {
+  pub fn new(guild_id: GuildId, minutes: i64) -> Self {
+    Self { guild_id, minutes }
+  }
+}
+
+impl InsertQuery for Milestone {
+  /// Records a [`Milestone`], unless it has already been recorded for the guild.
+  fn insert_query(&self) -> Query<Postgres, PgArguments> {
+    query!(
+      "INSERT INTO guild_milestone (record_id, guild_id, milestone_minutes) VALUES ($1, $2, $3) ON CONFLICT (guild_id, milestone_minutes) DO NOTHING",
+      Ulid::new().to_string(),
+      self.guild_id.to_string(),
+      self.minutes,
+    )
+  }
+}
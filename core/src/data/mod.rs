@@ -0,0 +1,46 @@
+pub mod account_link;
+pub mod ai_usage;
+pub mod backup;
+pub mod bookmark;
+pub mod calendar;
+pub mod common;
+pub mod community_event;
+pub mod course;
+pub mod dedication;
+pub mod erase;
+pub mod feed;
+pub mod goal;
+pub mod guild_emoji;
+pub mod guild_feature;
+pub mod guild_settings;
+pub mod intention;
+pub mod maintenance;
+pub mod meditation;
+pub mod mentorship;
+pub mod milestone;
+pub mod mood_checkin;
+pub mod pending_interaction;
+pub mod pick_winner;
+pub mod poll;
+pub mod profile_import;
+pub mod quote;
+pub mod recurring_post;
+pub mod report;
+pub mod retention;
+pub mod retreat;
+pub mod scheduled_announcement;
+pub mod session_webhook;
+pub mod star_message;
+pub mod stats;
+pub mod steam_key;
+pub mod streak_repair;
+pub mod study_group;
+pub mod suggestion;
+pub mod sutta;
+pub mod talk;
+pub mod term;
+pub mod ticket;
+pub mod tracking_profile;
+pub mod warning;
+pub mod watchlist;
+pub mod wellness_metric;
@@ -0,0 +1,184 @@
+use chrono::NaiveDate;
+use poise::serenity_prelude::{GuildId, UserId};
+use poise::ChoiceParameter;
+use sqlx::postgres::{PgArguments, PgRow};
+use sqlx::query::{Query, QueryAs};
+use sqlx::{Error as SqlxError, FromRow, Postgres, Result as SqlxResult, Row};
+use ulid::Ulid;
+
+use crate::data::common;
+use crate::database::InsertQuery;
+
+/// How calm a member felt, from 1 (very unsettled) to 5 (very calm).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ChoiceParameter)]
+pub enum Mood {
+  #[name = "5 - Very calm"]
+  VeryCalm,
+  #[name = "4 - Calm"]
+  Calm,
+  #[name = "3 - Neutral"]
+  Neutral,
+  #[name = "2 - Unsettled"]
+  Unsettled,
+  #[name = "1 - Very unsettled"]
+  VeryUnsettled,
+}
+
+impl Mood {
+  pub fn rating(self) -> i16 {
+    match self {
+      Self::VeryCalm => 5,
+      Self::Calm => 4,
+      Self::Neutral => 3,
+      Self::Unsettled => 2,
+      Self::VeryUnsettled => 1,
+    }
+  }
+}
+
+/// A member's mood check-in for a day. Members check in at most once a day, with later
+/// check-ins replacing earlier ones.
+pub struct MoodCheckin {
+  pub id: String,
+  pub guild_id: GuildId,
+  pub user_id: UserId,
+  pub date: NaiveDate,
+  pub mood: i16,
+  pub note: Option<String>,
+}
+
+/// A member's mood and meditation minutes for a day. Days without a check-in have no mood.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MoodDay {
+  pub date: NaiveDate,
+  pub mood: Option<i16>,
+  pub minutes: i64,
+}
+
+impl MoodCheckin {
+  pub fn new(
+    guild_id: GuildId,
+    user_id: UserId,
+    date: NaiveDate,
+    mood: Mood,
+    note: Option<String>,
+  ) -> Self {
+    Self {
+      id: Ulid::new().to_string(),
+      guild_id,
+      user_id,
+      date,
+      mood: mood.rating(),
+      note,
+    }
+  }
+
+  /// Retrieves a member's mood and meditation minutes for each of the `days` days up to and
+  /// including `today`, oldest first. Meditation minutes are counted on the day they were
+  /// recorded in the member's UTC offset, given in minutes.
+  pub fn retrieve_days<'a>(
+    guild_id: GuildId,
+    user_id: UserId,
+    today: NaiveDate,
+    days: i32,
+    utc_offset: i16,
+  ) -> QueryAs<'a, Postgres, MoodDay, PgArguments> {
+    sqlx::query_as(
+      "
+        WITH days AS (
+          SELECT generate_series($3::date - ($4 - 1), $3::date, INTERVAL '1 day')::date AS day
+        ),
+        minutes AS (
+          SELECT (occurred_at AT TIME ZONE 'UTC' + $5 * INTERVAL '1 minute')::date AS day, SUM(meditation_minutes) AS minutes
+          FROM meditation
+          WHERE guild_id = $1 AND user_id = $2
+            AND (occurred_at AT TIME ZONE 'UTC' + $5 * INTERVAL '1 minute')::date > $3::date - $4
+          GROUP BY 1
+        )
+        SELECT days.day, mood_checkin.mood, COALESCE(minutes.minutes, 0) AS minutes
+        FROM days
+        LEFT JOIN mood_checkin ON mood_checkin.guild_id = $1 AND mood_checkin.user_id = $2 AND mood_checkin.checkin_date = days.day
+        LEFT JOIN minutes ON minutes.day = days.day
+        ORDER BY days.day ASC
+      ",
+    )
+    .bind(guild_id.to_string())
+    .bind(user_id.to_string())
+    .bind(today)
+    .bind(days)
+    .bind(i32::from(utc_offset))
+  }
+}
+
+impl InsertQuery for MoodCheckin {
+  /// Adds a [`MoodCheckin`] to the database, replacing the member's check-in for the same day.
+  fn insert_query(&self) -> Query<Postgres, PgArguments> {
+    query!(
+      "INSERT INTO mood_checkin (record_id, guild_id, user_id, checkin_date, mood, note) VALUES ($1, $2, $3, $4, $5, $6) ON CONFLICT (guild_id, user_id, checkin_date) DO UPDATE SET mood = EXCLUDED.mood, note = EXCLUDED.note, created_at = CURRENT_TIMESTAMP",
+      self.id,
+      self.guild_id.to_string(),
+      self.user_id.to_string(),
+      self.date,
+      self.mood,
+      self.note,
+    )
+  }
+}
+
+impl FromRow<'_, PgRow> for MoodDay {
+  fn from_row(row: &'_ PgRow) -> SqlxResult<Self, SqlxError> {
+    Ok(Self {
+      date: row.try_get("day")?,
+      mood: row.try_get("mood")?,
+      minutes: row.try_get("minutes")?,
+    })
+  }
+}
+
+/// The correlation between mood and meditation minutes on days with a check-in, or [`None`]
+/// if there are fewer than three such days or either doesn't vary.
+pub fn correlation(days: &[MoodDay]) -> Option<f64> {
+  #[allow(clippy::cast_precision_loss)]
+  let points: Vec<(f64, f64)> = days
+    .iter()
+    .filter_map(|day| day.mood.map(|mood| (f64::from(mood), day.minutes as f64)))
+    .collect();
+
+  common::correlation(&points)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn day(day: u32, mood: Option<i16>, minutes: i64) -> MoodDay {
+    MoodDay {
+      date: NaiveDate::from_ymd_opt(2024, 11, day).unwrap_or_default(),
+      mood,
+      minutes,
+    }
+  }
+
+  #[test]
+  fn test_correlation() {
+    let days = [
+      day(1, Some(2), 0),
+      day(2, None, 60),
+      day(3, Some(3), 10),
+      day(4, Some(4), 20),
+      day(5, Some(5), 30),
+    ];
+    assert!(correlation(&days).is_some_and(|c| (c - 1.0).abs() < 1e-9));
+
+    let days = [day(1, Some(5), 0), day(2, Some(3), 20), day(3, Some(1), 40)];
+    assert!(correlation(&days).is_some_and(|c| (c + 1.0).abs() < 1e-9));
+
+    // Too few check-ins, or no variation, can't be correlated
+    assert_eq!(
+      correlation(&[day(1, Some(3), 10), day(2, Some(4), 20)]),
+      None
+    );
+    let days = [day(1, Some(3), 0), day(2, Some(3), 10), day(3, Some(3), 20)];
+    assert_eq!(correlation(&days), None);
+  }
+}
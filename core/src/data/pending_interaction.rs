@@ -0,0 +1,132 @@
+use chrono::{DateTime, Utc};
+use poise::serenity_prelude::{ChannelId, GuildId, UserId};
+use sqlx::postgres::{PgArguments, PgRow};
+use sqlx::query::{Query, QueryAs};
+use sqlx::{Error as SqlxError, FromRow, Postgres, Result as SqlxResult, Row};
+use ulid::Ulid;
+
+use crate::data::common;
+use crate::database::InsertQuery;
+
+/// Interaction flows whose state is kept in the database, so their buttons can still be
+/// handled after a restart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InteractionFlow {
+  /// A list shown a page at a time, with Previous and Next buttons.
+  Pagination,
+  /// A prompt asking the member to confirm an action with Yes and No buttons.
+  Confirmation,
+}
+
+impl InteractionFlow {
+  fn name(self) -> &'static str {
+    match self {
+      Self::Pagination => "pagination",
+      Self::Confirmation => "confirmation",
+    }
+  }
+
+  fn from_name(name: &str) -> Option<Self> {
+    match name {
+      "pagination" => Some(Self::Pagination),
+      "confirmation" => Some(Self::Confirmation),
+      _ => None,
+    }
+  }
+}
+
+/// A button flow awaiting a response from a member. The `id` is included in the custom IDs
+/// of the flow's buttons, so presses can be matched to the flow's state from the global
+/// event handler, even if the bot has restarted since the buttons were sent.
+pub struct PendingInteraction {
+  pub id: String,
+  pub flow: InteractionFlow,
+  /// The guild the flow was started in, or [`None`] in DMs.
+  pub guild_id: Option<GuildId>,
+  pub user_id: UserId,
+  pub channel_id: ChannelId,
+  /// Flow-specific state, serialized as JSON.
+  pub payload: String,
+  pub expires_at: DateTime<Utc>,
+}
+
+impl PendingInteraction {
+  /// Creates a [`PendingInteraction`] with a new ID.
+  pub fn new(
+    flow: InteractionFlow,
+    guild_id: Option<GuildId>,
+    user_id: UserId,
+    channel_id: ChannelId,
+    payload: impl Into<String>,
+    expires_at: DateTime<Utc>,
+  ) -> Self {
+    Self {
+      id: Ulid::new().to_string(),
+      flow,
+      guild_id,
+      user_id,
+      channel_id,
+      payload: payload.into(),
+      expires_at,
+    }
+  }
+
+  /// Retrieves a [`PendingInteraction`] of the specified [`InteractionFlow`], unless it
+  /// has expired.
+  pub fn retrieve<'a>(flow: InteractionFlow, id: &str) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT record_id, flow, guild_id, user_id, channel_id, payload, expires_at FROM pending_interaction WHERE record_id = $1 AND flow = $2 AND expires_at > NOW()",
+    )
+    .bind(id.to_owned())
+    .bind(flow.name())
+  }
+
+  pub fn remove<'a>(id: &str) -> Query<'a, Postgres, PgArguments> {
+    query!(
+      "DELETE FROM pending_interaction WHERE record_id = $1",
+      id.to_owned(),
+    )
+  }
+
+  /// Removes every [`PendingInteraction`] which has expired.
+  pub fn remove_expired<'a>() -> Query<'a, Postgres, PgArguments> {
+    query!("DELETE FROM pending_interaction WHERE expires_at <= NOW()")
+  }
+}
+
+impl InsertQuery for PendingInteraction {
+  fn insert_query(&self) -> Query<Postgres, PgArguments> {
+    query!(
+      "INSERT INTO pending_interaction (record_id, flow, guild_id, user_id, channel_id, payload, expires_at) VALUES ($1, $2, $3, $4, $5, $6, $7)",
+      self.id,
+      self.flow.name(),
+      self.guild_id.map(|guild_id| guild_id.to_string()),
+      self.user_id.to_string(),
+      self.channel_id.to_string(),
+      self.payload,
+      self.expires_at,
+    )
+  }
+}
+
+impl FromRow<'_, PgRow> for PendingInteraction {
+  fn from_row(row: &'_ PgRow) -> SqlxResult<Self, SqlxError> {
+    let flow_name: String = row.try_get("flow")?;
+    let Some(flow) = InteractionFlow::from_name(&flow_name) else {
+      return Err(SqlxError::ColumnDecode {
+        index: "flow".to_string(),
+        source: format!("Unknown interaction flow: {flow_name}").into(),
+      });
+    };
+
+    Ok(Self {
+      id: row.try_get("record_id")?,
+      flow,
+      guild_id: common::decode_option_id_row(row, "guild_id")?.map(GuildId::new),
+      user_id: UserId::new(common::decode_id_row(row, "user_id")?),
+      channel_id: ChannelId::new(common::decode_id_row(row, "channel_id")?),
+      payload: row.try_get("payload")?,
+      expires_at: row.try_get("expires_at")?,
+    })
+  }
+}
@@ -7,9 +7,7 @@ use sqlx::{FromRow, Postgres};
 /// Retrieves a monthly challenge winner candidate from the database.
 /// Candidates include any user who logged time during the specified period.
 /// Criteria further restricting the pool of candidates are defined in the
-/// [`pick_winner`][pw] command.
-///
-/// [pw]: crate::commands::pick_winner
+/// `/pick_winner` command.
 pub fn retrieve_candidate<'a, T: for<'r> FromRow<'r, PgRow>>(
   guild_id: GuildId,
   start_date: &'a DateTime<Utc>,
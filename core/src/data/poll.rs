@@ -0,0 +1,234 @@
+use chrono::{DateTime, Utc};
+use poise::serenity_prelude::{ChannelId, GuildId, MessageId, UserId};
+use sqlx::postgres::{PgArguments, PgRow};
+use sqlx::query::{Query, QueryAs};
+use sqlx::{Error as SqlxError, FromRow, Postgres, Result as SqlxResult, Row};
+use ulid::Ulid;
+
+use crate::data::common;
+use crate::database::InsertQuery;
+
+/// A poll which members vote on using buttons. Polls may allow more than one choice, hide
+/// who voted for what, and close automatically at `closes_at`.
+pub struct Poll {
+  pub id: String,
+  pub guild_id: GuildId,
+  pub channel_id: ChannelId,
+  pub message_id: Option<MessageId>,
+  pub question: String,
+  pub choices: Vec<String>,
+  pub multiple_choice: bool,
+  pub anonymous: bool,
+  pub closes_at: Option<DateTime<Utc>>,
+  pub closed: bool,
+  pub created_by: UserId,
+}
+
+/// A member's vote for one of the choices in a [`Poll`], by index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PollVote {
+  pub user_id: UserId,
+  pub choice: i16,
+}
+
+impl Poll {
+  #[allow(clippy::too_many_arguments)]
+  pub fn new(
+    guild_id: GuildId,
+    channel_id: ChannelId,
+    question: String,
+    choices: Vec<String>,
+    multiple_choice: bool,
+    anonymous: bool,
+    closes_at: Option<DateTime<Utc>>,
+    created_by: UserId,
+  ) -> Self {
+    Self {
+      id: Ulid::new().to_string(),
+      guild_id,
+      channel_id,
+      message_id: None,
+      question,
+      choices,
+      multiple_choice,
+      anonymous,
+      closes_at,
+      closed: false,
+      created_by,
+    }
+  }
+
+  /// Returns `true` if the poll is no longer accepting votes as of `now`.
+  pub fn is_closed(&self, now: DateTime<Utc>) -> bool {
+    self.closed || self.closes_at.is_some_and(|closes_at| closes_at <= now)
+  }
+
+  /// Counts the votes for each choice, in the order of [`Poll::choices`]. Votes for
+  /// choices which don't exist are ignored.
+  pub fn tally(&self, votes: &[PollVote]) -> Vec<u64> {
+    let mut counts = vec![0; self.choices.len()];
+    for vote in votes {
+      if let Some(count) = usize::try_from(vote.choice)
+        .ok()
+        .and_then(|choice| counts.get_mut(choice))
+      {
+        *count += 1;
+      }
+    }
+
+    counts
+  }
+
+  pub fn retrieve<'a>(poll_id: &str) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT record_id, guild_id, channel_id, message_id, question, choices, multiple_choice, anonymous, closes_at, closed, created_by FROM poll WHERE record_id = $1",
+    )
+    .bind(poll_id.to_owned())
+  }
+
+  /// Retrieves open polls which were due to close at or before `now`.
+  pub fn retrieve_expired(now: &DateTime<Utc>) -> QueryAs<'_, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT record_id, guild_id, channel_id, message_id, question, choices, multiple_choice, anonymous, closes_at, closed, created_by FROM poll WHERE closed = FALSE AND closes_at <= $1",
+    )
+    .bind(now)
+  }
+
+  /// Records the message a [`Poll`] was posted in, so it can be updated when it closes.
+  pub fn set_message(poll_id: &str, message_id: MessageId) -> Query<'_, Postgres, PgArguments> {
+    query!(
+      "UPDATE poll SET message_id = $1 WHERE record_id = $2",
+      message_id.to_string(),
+      poll_id,
+    )
+  }
+
+  /// Closes a [`Poll`]. Only affects polls which are still open, so a poll can only be
+  /// closed once.
+  pub fn close(guild_id: GuildId, poll_id: &str) -> Query<'_, Postgres, PgArguments> {
+    query!(
+      "UPDATE poll SET closed = TRUE WHERE record_id = $1 AND guild_id = $2 AND closed = FALSE",
+      poll_id,
+      guild_id.to_string(),
+    )
+  }
+
+  /// Retrieves all votes for a [`Poll`], in the order they were cast.
+  pub fn votes(poll_id: &str) -> QueryAs<'_, Postgres, PollVote, PgArguments> {
+    sqlx::query_as(
+      "SELECT user_id, choice FROM poll_vote WHERE poll_id = $1 ORDER BY voted_at ASC, user_id ASC",
+    )
+    .bind(poll_id)
+  }
+
+  /// Adds a vote, unless the member has already voted for that choice.
+  pub fn add_vote(poll_id: &str, vote: PollVote) -> Query<'_, Postgres, PgArguments> {
+    query!(
+      "INSERT INTO poll_vote (poll_id, user_id, choice) VALUES ($1, $2, $3) ON CONFLICT (poll_id, user_id, choice) DO NOTHING",
+      poll_id,
+      vote.user_id.to_string(),
+      vote.choice,
+    )
+  }
+
+  pub fn remove_vote(poll_id: &str, vote: PollVote) -> Query<'_, Postgres, PgArguments> {
+    query!(
+      "DELETE FROM poll_vote WHERE poll_id = $1 AND user_id = $2 AND choice = $3",
+      poll_id,
+      vote.user_id.to_string(),
+      vote.choice,
+    )
+  }
+
+  /// Removes all of a member's votes for a [`Poll`].
+  pub fn clear_votes(poll_id: &str, user_id: UserId) -> Query<'_, Postgres, PgArguments> {
+    query!(
+      "DELETE FROM poll_vote WHERE poll_id = $1 AND user_id = $2",
+      poll_id,
+      user_id.to_string(),
+    )
+  }
+}
+
+impl InsertQuery for Poll {
+  /// Adds a [`Poll`] to the database.
+  fn insert_query(&self) -> Query<Postgres, PgArguments> {
+    query!(
+      "INSERT INTO poll (record_id, guild_id, channel_id, question, choices, multiple_choice, anonymous, closes_at, created_by) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
+      self.id,
+      self.guild_id.to_string(),
+      self.channel_id.to_string(),
+      self.question,
+      &self.choices,
+      self.multiple_choice,
+      self.anonymous,
+      self.closes_at,
+      self.created_by.to_string(),
+    )
+  }
+}
+
+impl FromRow<'_, PgRow> for Poll {
+  fn from_row(row: &'_ PgRow) -> SqlxResult<Self, SqlxError> {
+    let guild_id = GuildId::new(common::decode_id_row(row, "guild_id")?);
+    let channel_id = ChannelId::new(common::decode_id_row(row, "channel_id")?);
+    let message_id = common::decode_option_id_row(row, "message_id")?.map(MessageId::new);
+    let created_by = UserId::new(common::decode_id_row(row, "created_by")?);
+
+    Ok(Self {
+      id: row.try_get("record_id")?,
+      guild_id,
+      channel_id,
+      message_id,
+      question: row.try_get("question")?,
+      choices: row.try_get("choices")?,
+      multiple_choice: row.try_get("multiple_choice")?,
+      anonymous: row.try_get("anonymous")?,
+      closes_at: row.try_get("closes_at")?,
+      closed: row.try_get("closed")?,
+      created_by,
+    })
+  }
+}
+
+impl FromRow<'_, PgRow> for PollVote {
+  fn from_row(row: &'_ PgRow) -> SqlxResult<Self, SqlxError> {
+    Ok(Self {
+      user_id: UserId::new(common::decode_id_row(row, "user_id")?),
+      choice: row.try_get("choice")?,
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_tally() {
+    let poll = Poll::new(
+      GuildId::new(123u64),
+      ChannelId::new(456u64),
+      "Next month's theme?".to_owned(),
+      vec![
+        "Metta".to_owned(),
+        "Body scan".to_owned(),
+        "Walking".to_owned(),
+      ],
+      true,
+      false,
+      None,
+      UserId::new(789u64),
+    );
+    let vote = |user_id: u64, choice| PollVote {
+      user_id: UserId::new(user_id),
+      choice,
+    };
+
+    assert_eq!(poll.tally(&[]), vec![0, 0, 0]);
+    assert_eq!(
+      poll.tally(&[vote(1, 0), vote(2, 0), vote(2, 2), vote(3, 5), vote(4, -1)]),
+      vec![2, 0, 1]
+    );
+  }
+}
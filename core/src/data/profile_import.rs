@@ -0,0 +1,160 @@
+use chrono::{DateTime, Utc};
+use poise::serenity_prelude::{GuildId, UserId};
+use sqlx::postgres::{PgArguments, PgRow};
+use sqlx::query::{Query, QueryAs};
+use sqlx::{Error as SqlxError, FromRow, Postgres, Result as SqlxResult, Row};
+use ulid::Ulid;
+
+use crate::data::common;
+use crate::data::meditation::Meditation;
+use crate::database::{DeleteQuery, InsertQuery};
+
+/// A single-use token created with `/customize link`, which lets a member copy their entries
+/// from the guild it was created in to another guild with `/customize import-from`. Only a
+/// hash of the token is stored.
+pub struct ProfileLink {
+  pub token_hash: String,
+  pub guild_id: GuildId,
+  pub user_id: UserId,
+  pub expires_at: DateTime<Utc>,
+}
+
+impl ProfileLink {
+  pub fn new(
+    token_hash: String,
+    guild_id: GuildId,
+    user_id: UserId,
+    expires_at: DateTime<Utc>,
+  ) -> Self {
+    Self {
+      token_hash,
+      guild_id,
+      user_id,
+      expires_at,
+    }
+  }
+
+  /// Retrieves a [`ProfileLink`] by the hash of its token, unless it has been used or has
+  /// expired.
+  pub fn retrieve(token_hash: &str) -> QueryAs<'_, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT token_hash, guild_id, user_id, expires_at FROM profile_link WHERE token_hash = $1 AND used_at IS NULL AND expires_at > NOW()",
+    )
+    .bind(token_hash)
+  }
+
+  /// Marks a [`ProfileLink`] as used. Only affects unused links, so each token is only
+  /// redeemed once.
+  pub fn mark_used(token_hash: &str) -> Query<'_, Postgres, PgArguments> {
+    query!(
+      "UPDATE profile_link SET used_at = NOW() WHERE token_hash = $1 AND used_at IS NULL",
+      token_hash,
+    )
+  }
+}
+
+impl InsertQuery for ProfileLink {
+  fn insert_query(&self) -> Query<Postgres, PgArguments> {
+    query!(
+      "INSERT INTO profile_link (token_hash, guild_id, user_id, expires_at) VALUES ($1, $2, $3, $4)",
+      self.token_hash,
+      self.guild_id.to_string(),
+      self.user_id.to_string(),
+      self.expires_at,
+    )
+  }
+}
+
+impl DeleteQuery for ProfileLink {
+  /// Removes a member's unused links in a guild, so only their newest token works.
+  fn delete_query<'a>(
+    guild_id: GuildId,
+    user_id: impl Into<String>,
+  ) -> Query<'a, Postgres, PgArguments> {
+    query!(
+      "DELETE FROM profile_link WHERE guild_id = $1 AND user_id = $2 AND used_at IS NULL",
+      guild_id.to_string(),
+      user_id.into(),
+    )
+  }
+}
+
+impl FromRow<'_, PgRow> for ProfileLink {
+  fn from_row(row: &'_ PgRow) -> SqlxResult<Self, SqlxError> {
+    Ok(Self {
+      token_hash: row.try_get("token_hash")?,
+      guild_id: GuildId::new(common::decode_id_row(row, "guild_id")?),
+      user_id: UserId::new(common::decode_id_row(row, "user_id")?),
+      expires_at: row.try_get("expires_at")?,
+    })
+  }
+}
+
+/// A copy of a member's entries from another guild, made with `/customize import-from`. The
+/// copied [`Meditation`] entries are linked to the import and to the entries they were copied
+/// from, so the source guild's entries are left untouched and can't be imported twice.
+pub struct ProfileImport {
+  pub id: String,
+  pub guild_id: GuildId,
+  pub user_id: UserId,
+  pub source_guild_id: GuildId,
+  pub entries: i32,
+}
+
+impl ProfileImport {
+  pub fn new(guild_id: GuildId, user_id: UserId, source_guild_id: GuildId, entries: i32) -> Self {
+    Self {
+      id: Ulid::new().to_string(),
+      guild_id,
+      user_id,
+      source_guild_id,
+      entries,
+    }
+  }
+
+  /// Retrieves a member's entries in `source_guild_id` which can be copied to `guild_id`.
+  /// Entries which were themselves imported are left out, as are entries which have already
+  /// been copied to `guild_id`.
+  pub fn importable_entries<'a>(
+    source_guild_id: GuildId,
+    guild_id: GuildId,
+    user_id: UserId,
+  ) -> QueryAs<'a, Postgres, Meditation, PgArguments> {
+    sqlx::query_as(
+      "SELECT record_id, meditation_minutes, meditation_seconds, occurred_at FROM meditation WHERE guild_id = $1 AND user_id = $3 AND import_id IS NULL AND record_id NOT IN (SELECT source_id FROM meditation WHERE guild_id = $2 AND user_id = $3 AND source_id IS NOT NULL) ORDER BY occurred_at ASC",
+    )
+    .bind(source_guild_id.to_string())
+    .bind(guild_id.to_string())
+    .bind(user_id.to_string())
+  }
+
+  /// Adds a copy of a [`Meditation`] entry from the source guild, linked to the import.
+  pub fn entry_query<'a>(&'a self, source: &'a Meditation) -> Query<'a, Postgres, PgArguments> {
+    query!(
+      "INSERT INTO meditation (record_id, user_id, meditation_minutes, meditation_seconds, guild_id, occurred_at, import_id, source_id) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+      Ulid::new().to_string(),
+      self.user_id.to_string(),
+      source.minutes,
+      source.seconds,
+      self.guild_id.to_string(),
+      source.occurred_at,
+      self.id,
+      source.id,
+    )
+  }
+}
+
+impl InsertQuery for ProfileImport {
+  /// Adds a [`ProfileImport`] to the database. Its entries are added separately with
+  /// [`ProfileImport::entry_query`].
+  fn insert_query(&self) -> Query<Postgres, PgArguments> {
+    query!(
+      "INSERT INTO profile_import (record_id, guild_id, user_id, source_guild_id, entries) VALUES ($1, $2, $3, $4, $5)",
+      self.id,
+      self.guild_id.to_string(),
+      self.user_id.to_string(),
+      self.source_guild_id.to_string(),
+      self.entries,
+    )
+  }
+}
@@ -6,8 +6,9 @@ use sqlx::query::QueryAs;
 use sqlx::{FromRow, Postgres};
 use ulid::Ulid;
 
-use crate::commands::helpers::pagination::{PageRow, PageType};
-use crate::handlers::database::{DeleteQuery, ExistsQuery, InsertQuery, UpdateQuery};
+use crate::data::common;
+use crate::database::{DeleteQuery, ExistsQuery, InsertQuery, UpdateQuery};
+use crate::pagination::{PageRow, PageType};
 
 #[allow(clippy::struct_field_names)]
 #[derive(Default, FromRow)]
@@ -21,6 +22,12 @@ pub struct Quote {
   guild_id: GuildId,
 }
 
+/// The name of a [`Quote`] author, as shown in `/quote` author suggestions.
+#[derive(FromRow)]
+pub struct QuoteAuthor {
+  pub author: String,
+}
+
 #[allow(clippy::module_name_repetitions)]
 #[derive(Debug, Modal)]
 #[name = "Add/Edit Quote"]
@@ -70,19 +77,34 @@ impl Quote {
     .bind(guild_id.to_string())
   }
 
+  /// Retrieves a random [`Quote`] by the specified `author` from the database. Authors are
+  /// matched case-insensitively, with quotes that have no author attributed to "Anonymous".
+  pub fn retrieve_random_by_author<'a>(
+    guild_id: GuildId,
+    author: &str,
+  ) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT record_id, quote, author FROM quote WHERE guild_id = $1 AND LOWER(COALESCE(author, 'Anonymous')) = LOWER($2) ORDER BY RANDOM() LIMIT 1",
+    )
+    .bind(guild_id.to_string())
+    .bind(author.to_string())
+  }
+
   /// Retrieves a random [`Quote`] from the database, with the quote pool refined by
-  /// a [PostgreSQL websearch query][ws] defined in `keyword`.
+  /// a [PostgreSQL websearch query][ws] defined in `keyword`, and optionally by `author`.
   ///
   /// [ws]: https://www.postgresql.org/docs/17/textsearch-controls.html#TEXTSEARCH-PARSING-QUERIES
   pub fn retrieve_random_with_keyword<'a>(
     guild_id: GuildId,
     keyword: &str,
+    author: Option<&str>,
   ) -> QueryAs<'a, Postgres, Self, PgArguments> {
     sqlx::query_as(
-      "SELECT record_id, quote, author FROM quote WHERE guild_id = $1 AND (quote_tsv @@ websearch_to_tsquery('english', $2)) ORDER BY RANDOM() LIMIT 1",
+      "SELECT record_id, quote, author FROM quote WHERE guild_id = $1 AND (quote_tsv @@ websearch_to_tsquery('english', $2)) AND ($3::TEXT IS NULL OR LOWER(COALESCE(author, 'Anonymous')) = LOWER($3)) ORDER BY RANDOM() LIMIT 1",
     )
     .bind(guild_id.to_string())
     .bind(keyword.to_string())
+    .bind(author.map(str::to_string))
   }
 
   /// Retrieves all [`Quote`]s from the database.
@@ -91,6 +113,32 @@ impl Quote {
       .bind(guild_id.to_string())
   }
 
+  /// Retrieves all [`Quote`]s by the specified `author` from the database. Authors are
+  /// matched as in [`Quote::retrieve_random_by_author`].
+  pub fn retrieve_all_by_author<'a>(
+    guild_id: GuildId,
+    author: &str,
+  ) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT record_id, quote, author FROM quote WHERE guild_id = $1 AND LOWER(COALESCE(author, 'Anonymous')) = LOWER($2)",
+    )
+    .bind(guild_id.to_string())
+    .bind(author.to_string())
+  }
+
+  /// Retrieves up to 25 distinct [`QuoteAuthor`]s whose names contain `partial`, in
+  /// alphabetical order, for use as autocomplete suggestions.
+  pub fn retrieve_authors<'a>(
+    guild_id: GuildId,
+    partial: &str,
+  ) -> QueryAs<'a, Postgres, QuoteAuthor, PgArguments> {
+    sqlx::query_as(
+      "SELECT DISTINCT COALESCE(author, 'Anonymous') AS author FROM quote WHERE guild_id = $1 AND COALESCE(author, 'Anonymous') ILIKE '%' || $2 || '%' ORDER BY author ASC LIMIT 25",
+    )
+    .bind(guild_id.to_string())
+    .bind(common::escape_like(partial))
+  }
+
   /// Searches available [`Quote`]s using a [PostgreSQL websearch query][ws] defined in `keyword`.
   ///
   /// [ws]: https://www.postgresql.org/docs/17/textsearch-controls.html#TEXTSEARCH-PARSING-QUERIES
@@ -120,10 +168,11 @@ impl UpdateQuery for Quote {
   /// Updates a [`Quote`] in the database.
   fn update_query(&self) -> sqlx::query::Query<Postgres, PgArguments> {
     query!(
-      "UPDATE quote SET quote = $1, author = $2 WHERE record_id = $3",
+      "UPDATE quote SET quote = $1, author = $2 WHERE record_id = $3 AND guild_id = $4",
       self.quote,
       self.author,
       self.id,
+      self.guild_id.to_string(),
     )
   }
 }
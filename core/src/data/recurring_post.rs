@@ -0,0 +1,316 @@
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, Timelike, Utc, Weekday};
+use poise::serenity_prelude::{ChannelId, GuildId, UserId};
+use poise::ChoiceParameter;
+use sqlx::postgres::{PgArguments, PgRow};
+use sqlx::query::{Query, QueryAs};
+use sqlx::{Error as SqlxError, FromRow, Postgres, Result as SqlxResult, Row};
+use ulid::Ulid;
+
+use crate::data::common;
+use crate::database::InsertQuery;
+
+/// The days a [`RecurringPost`] is posted on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ChoiceParameter)]
+pub enum PostDay {
+  #[name = "every day"]
+  Daily,
+  #[name = "Mondays"]
+  Monday,
+  #[name = "Tuesdays"]
+  Tuesday,
+  #[name = "Wednesdays"]
+  Wednesday,
+  #[name = "Thursdays"]
+  Thursday,
+  #[name = "Fridays"]
+  Friday,
+  #[name = "Saturdays"]
+  Saturday,
+  #[name = "Sundays"]
+  Sunday,
+}
+
+impl PostDay {
+  /// Returns the day of the week posts are made on, or [`None`] for daily posts.
+  pub fn weekday(self) -> Option<Weekday> {
+    match self {
+      Self::Daily => None,
+      Self::Monday => Some(Weekday::Mon),
+      Self::Tuesday => Some(Weekday::Tue),
+      Self::Wednesday => Some(Weekday::Wed),
+      Self::Thursday => Some(Weekday::Thu),
+      Self::Friday => Some(Weekday::Fri),
+      Self::Saturday => Some(Weekday::Sat),
+      Self::Sunday => Some(Weekday::Sun),
+    }
+  }
+}
+
+/// A message Bloom posts in a channel on a schedule, such as a weekly check-in or a daily
+/// prompt, optionally opening a thread on it. Posts are made every day or once a week, at
+/// `minute_of_day` in the UTC offset of the staff member who set them up.
+pub struct RecurringPost {
+  pub id: String,
+  pub guild_id: GuildId,
+  pub channel_id: ChannelId,
+  pub name: String,
+  pub message: String,
+  pub thread_name: Option<String>,
+  pub weekday: Option<Weekday>,
+  pub minute_of_day: i16,
+  pub utc_offset: i16,
+  pub next_post_at: DateTime<Utc>,
+  pub created_by: UserId,
+}
+
+impl RecurringPost {
+  #[allow(clippy::too_many_arguments)]
+  pub fn new(
+    guild_id: GuildId,
+    channel_id: ChannelId,
+    name: String,
+    message: String,
+    thread_name: Option<String>,
+    day: PostDay,
+    time: NaiveTime,
+    utc_offset: i16,
+    created_by: UserId,
+  ) -> Self {
+    // There are at most 1,440 minutes in a day
+    #[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+    let minute_of_day = (time.num_seconds_from_midnight() / 60) as i16;
+
+    let mut post = Self {
+      id: Ulid::new().to_string(),
+      guild_id,
+      channel_id,
+      name,
+      message,
+      thread_name,
+      weekday: day.weekday(),
+      minute_of_day,
+      utc_offset,
+      next_post_at: Utc::now(),
+      created_by,
+    };
+    post.next_post_at = post.next_after(post.next_post_at);
+
+    post
+  }
+
+  fn offset(&self) -> Duration {
+    Duration::minutes(i64::from(self.utc_offset))
+  }
+
+  /// Returns the first time the post is due after `time`.
+  pub fn next_after(&self, time: DateTime<Utc>) -> DateTime<Utc> {
+    let local = time.naive_utc() + self.offset();
+    let post_time = local.date().and_time(NaiveTime::default())
+      + Duration::minutes(i64::from(self.minute_of_day));
+
+    (0..=7)
+      .map(|days| post_time + Duration::days(days))
+      .find(|candidate| {
+        *candidate > local
+          && self
+            .weekday
+            .is_none_or(|weekday| candidate.weekday() == weekday)
+      })
+      .map_or(time + Duration::days(1), |candidate| {
+        (candidate - self.offset()).and_utc()
+      })
+  }
+
+  /// The date of a post made at `time`, in the post's UTC offset.
+  pub fn local_date(&self, time: DateTime<Utc>) -> NaiveDate {
+    (time.naive_utc() + self.offset()).date()
+  }
+
+  /// Fills in the `{date}` placeholder of a template, e.g., `Friday, November 8`.
+  pub fn render(template: &str, date: NaiveDate) -> String {
+    template.replace("{date}", &date.format("%A, %B %-d").to_string())
+  }
+
+  /// Describes when the post is made, e.g., `Fridays at 18:00 (UTC+02:00)`.
+  pub fn schedule(&self) -> String {
+    let days = match self.weekday {
+      None => "Every day",
+      Some(Weekday::Mon) => "Mondays",
+      Some(Weekday::Tue) => "Tuesdays",
+      Some(Weekday::Wed) => "Wednesdays",
+      Some(Weekday::Thu) => "Thursdays",
+      Some(Weekday::Fri) => "Fridays",
+      Some(Weekday::Sat) => "Saturdays",
+      Some(Weekday::Sun) => "Sundays",
+    };
+    let offset = self.utc_offset.unsigned_abs();
+    let sign = if self.utc_offset < 0 { '-' } else { '+' };
+
+    format!(
+      "{days} at {:02}:{:02} (UTC{sign}{:02}:{:02})",
+      self.minute_of_day / 60,
+      self.minute_of_day % 60,
+      offset / 60,
+      offset % 60
+    )
+  }
+
+  /// Retrieves a server's recurring posts, in alphabetical order.
+  pub fn retrieve_all<'a>(guild_id: GuildId) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT record_id, guild_id, channel_id, name, message, thread_name, weekday, minute_of_day, utc_offset, next_post_at, created_by FROM recurring_post WHERE guild_id = $1 ORDER BY LOWER(name) ASC",
+    )
+    .bind(guild_id.to_string())
+  }
+
+  /// Retrieves recurring posts in every server which were due at or before `now`.
+  pub fn retrieve_due(now: &DateTime<Utc>) -> QueryAs<'_, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT record_id, guild_id, channel_id, name, message, thread_name, weekday, minute_of_day, utc_offset, next_post_at, created_by FROM recurring_post WHERE next_post_at <= $1",
+    )
+    .bind(now)
+  }
+
+  /// Moves a [`RecurringPost`] on to its next time. Only affects the post if it's still due
+  /// at `due`, so each time is only claimed for posting once.
+  pub fn advance<'a>(
+    post_id: &'a str,
+    due: &'a DateTime<Utc>,
+    next_post_at: &'a DateTime<Utc>,
+  ) -> Query<'a, Postgres, PgArguments> {
+    query!(
+      "UPDATE recurring_post SET next_post_at = $1 WHERE record_id = $2 AND next_post_at = $3",
+      next_post_at,
+      post_id,
+      due,
+    )
+  }
+
+  /// Removes a [`RecurringPost`] by name, ignoring case.
+  pub fn remove(guild_id: GuildId, name: &str) -> Query<'_, Postgres, PgArguments> {
+    query!(
+      "DELETE FROM recurring_post WHERE guild_id = $1 AND LOWER(name) = LOWER($2)",
+      guild_id.to_string(),
+      name,
+    )
+  }
+}
+
+impl InsertQuery for RecurringPost {
+  /// Adds a [`RecurringPost`] to the database.
+  #[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+  fn insert_query(&self) -> Query<Postgres, PgArguments> {
+    query!(
+      "INSERT INTO recurring_post (record_id, guild_id, channel_id, name, message, thread_name, weekday, minute_of_day, utc_offset, next_post_at, created_by) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)",
+      self.id,
+      self.guild_id.to_string(),
+      self.channel_id.to_string(),
+      self.name,
+      self.message,
+      self.thread_name,
+      self.weekday.map(|weekday| weekday.num_days_from_monday() as i16),
+      self.minute_of_day,
+      self.utc_offset,
+      self.next_post_at,
+      self.created_by.to_string(),
+    )
+  }
+}
+
+impl FromRow<'_, PgRow> for RecurringPost {
+  fn from_row(row: &'_ PgRow) -> SqlxResult<Self, SqlxError> {
+    let weekday: Option<i16> = row.try_get("weekday")?;
+    let weekday = weekday
+      .map(|day| {
+        u8::try_from(day)
+          .ok()
+          .and_then(|day| Weekday::try_from(day).ok())
+          .ok_or_else(|| SqlxError::ColumnDecode {
+            index: "weekday".to_owned(),
+            source: format!("Invalid weekday: {day}").into(),
+          })
+      })
+      .transpose()?;
+
+    Ok(Self {
+      id: row.try_get("record_id")?,
+      guild_id: GuildId::new(common::decode_id_row(row, "guild_id")?),
+      channel_id: ChannelId::new(common::decode_id_row(row, "channel_id")?),
+      name: row.try_get("name")?,
+      message: row.try_get("message")?,
+      thread_name: row.try_get("thread_name")?,
+      weekday,
+      minute_of_day: row.try_get("minute_of_day")?,
+      utc_offset: row.try_get("utc_offset")?,
+      next_post_at: row.try_get("next_post_at")?,
+      created_by: UserId::new(common::decode_id_row(row, "created_by")?),
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn post(day: PostDay, time: &str, utc_offset: i16) -> RecurringPost {
+    RecurringPost::new(
+      GuildId::new(123u64),
+      ChannelId::new(456u64),
+      "Check-in".to_owned(),
+      "How was your week?".to_owned(),
+      None,
+      day,
+      NaiveTime::parse_from_str(time, "%H:%M").unwrap_or_default(),
+      utc_offset,
+      UserId::new(789u64),
+    )
+  }
+
+  fn utc(datetime: &str) -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(datetime)
+      .map(|datetime| datetime.with_timezone(&Utc))
+      .unwrap_or_default()
+  }
+
+  #[test]
+  fn test_next_after() {
+    // Friday, November 8, 2024
+    let daily = post(PostDay::Daily, "09:00", 0);
+    assert_eq!(
+      daily.next_after(utc("2024-11-08T08:00:00Z")),
+      utc("2024-11-08T09:00:00Z")
+    );
+    assert_eq!(
+      daily.next_after(utc("2024-11-08T09:00:00Z")),
+      utc("2024-11-09T09:00:00Z")
+    );
+
+    let friday = post(PostDay::Friday, "18:00", 120);
+    assert_eq!(
+      friday.next_after(utc("2024-11-08T15:59:00Z")),
+      utc("2024-11-08T16:00:00Z")
+    );
+    assert_eq!(
+      friday.next_after(utc("2024-11-08T16:00:00Z")),
+      utc("2024-11-15T16:00:00Z")
+    );
+    assert_eq!(friday.schedule(), "Fridays at 18:00 (UTC+02:00)");
+
+    // The day is in the post's offset, which may differ from the day in UTC
+    let monday = post(PostDay::Monday, "01:30", -300);
+    assert_eq!(
+      monday.next_after(utc("2024-11-08T12:00:00Z")),
+      utc("2024-11-11T06:30:00Z")
+    );
+    assert_eq!(monday.schedule(), "Mondays at 01:30 (UTC-05:00)");
+  }
+
+  #[test]
+  fn test_render() {
+    let date = NaiveDate::from_ymd_opt(2024, 11, 8).unwrap_or_default();
+    assert_eq!(
+      RecurringPost::render("Check-in for {date}", date),
+      "Check-in for Friday, November 8"
+    );
+  }
+}
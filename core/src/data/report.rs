@@ -0,0 +1,175 @@
+use poise::serenity_prelude::{ChannelId, GuildId, MessageId, UserId};
+use sqlx::postgres::{PgArguments, PgRow};
+use sqlx::query::{Query, QueryAs};
+use sqlx::{Error as SqlxError, FromRow, Postgres, Result as SqlxResult, Row};
+use ulid::Ulid;
+
+use crate::data::common;
+use crate::database::InsertQuery;
+
+/// How a message came to be reported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportSource {
+  /// Reported by a member using the "Report Message" context menu.
+  Member,
+  /// Flagged automatically for matching a term on the watchlist.
+  Watchlist,
+}
+
+impl ReportSource {
+  pub fn as_str(self) -> &'static str {
+    match self {
+      Self::Member => "member",
+      Self::Watchlist => "watchlist",
+    }
+  }
+
+  fn from_name(source: &str) -> Self {
+    match source {
+      "watchlist" => Self::Watchlist,
+      _ => Self::Member,
+    }
+  }
+}
+
+/// What staff did about a report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportStatus {
+  Open,
+  /// Reviewed and no action was needed.
+  Ignored,
+  /// The message was erased.
+  Erased,
+  /// Escalated to the moderation team.
+  Reported,
+}
+
+impl ReportStatus {
+  pub fn as_str(self) -> &'static str {
+    match self {
+      Self::Open => "open",
+      Self::Ignored => "ignored",
+      Self::Erased => "erased",
+      Self::Reported => "reported",
+    }
+  }
+
+  fn from_name(status: &str) -> Self {
+    match status {
+      "ignored" => Self::Ignored,
+      "erased" => Self::Erased,
+      "reported" => Self::Reported,
+      _ => Self::Open,
+    }
+  }
+}
+
+/// A message which was reported to staff, either by a member or by the watchlist.
+pub struct Report {
+  pub id: String,
+  pub guild_id: GuildId,
+  pub channel_id: ChannelId,
+  pub message_id: MessageId,
+  pub user_id: UserId,
+  pub reported_by: Option<UserId>,
+  pub source: ReportSource,
+  pub matched_term: Option<String>,
+  pub content: String,
+  pub status: ReportStatus,
+  pub handled_by: Option<UserId>,
+}
+
+impl Report {
+  pub fn new(
+    guild_id: GuildId,
+    channel_id: ChannelId,
+    message_id: MessageId,
+    user_id: UserId,
+    source: ReportSource,
+    content: String,
+  ) -> Self {
+    Self {
+      id: Ulid::new().to_string(),
+      guild_id,
+      channel_id,
+      message_id,
+      user_id,
+      reported_by: None,
+      source,
+      matched_term: None,
+      content,
+      status: ReportStatus::Open,
+      handled_by: None,
+    }
+  }
+
+  /// Sets the member who reported the message.
+  pub fn reported_by(mut self, reported_by: UserId) -> Self {
+    self.reported_by = Some(reported_by);
+    self
+  }
+
+  /// Sets the watchlist term which the message matched.
+  pub fn matched_term(mut self, matched_term: String) -> Self {
+    self.matched_term = Some(matched_term);
+    self
+  }
+
+  pub fn retrieve(report_id: &str) -> QueryAs<'_, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT record_id, guild_id, channel_id, message_id, user_id, reported_by, source, matched_term, content, status, handled_by FROM report WHERE record_id = $1",
+    )
+    .bind(report_id)
+  }
+
+  /// Records what staff did about a [`Report`]. Only affects open reports, so each report
+  /// is only handled once.
+  pub fn set_status(
+    report_id: &str,
+    status: ReportStatus,
+    handled_by: UserId,
+  ) -> Query<'_, Postgres, PgArguments> {
+    query!(
+      "UPDATE report SET status = $1, handled_by = $2, handled_at = NOW() WHERE record_id = $3 AND status = 'open'",
+      status.as_str(),
+      handled_by.to_string(),
+      report_id,
+    )
+  }
+}
+
+impl InsertQuery for Report {
+  /// Adds a [`Report`] to the database.
+  fn insert_query(&self) -> Query<Postgres, PgArguments> {
+    query!(
+      "INSERT INTO report (record_id, guild_id, channel_id, message_id, user_id, reported_by, source, matched_term, content) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
+      self.id,
+      self.guild_id.to_string(),
+      self.channel_id.to_string(),
+      self.message_id.to_string(),
+      self.user_id.to_string(),
+      self.reported_by.map(|user_id| user_id.to_string()),
+      self.source.as_str(),
+      self.matched_term,
+      self.content,
+    )
+  }
+}
+
+impl FromRow<'_, PgRow> for Report {
+  fn from_row(row: &'_ PgRow) -> SqlxResult<Self, SqlxError> {
+    Ok(Self {
+      id: row.try_get("record_id")?,
+      guild_id: GuildId::new(common::decode_id_row(row, "guild_id")?),
+      channel_id: ChannelId::new(common::decode_id_row(row, "channel_id")?),
+      message_id: MessageId::new(common::decode_id_row(row, "message_id")?),
+      user_id: UserId::new(common::decode_id_row(row, "user_id")?),
+      reported_by: common::decode_option_id_row(row, "reported_by")?.map(UserId::new),
+      source: ReportSource::from_name(row.try_get("source")?),
+      matched_term: row.try_get("matched_term")?,
+      content: row.try_get("content")?,
+      status: ReportStatus::from_name(row.try_get("status")?),
+      handled_by: common::decode_option_id_row(row, "handled_by")?.map(UserId::new),
+    })
+  }
+}
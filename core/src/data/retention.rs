@@ -0,0 +1,242 @@
+use chrono::{DateTime, Duration, Months, Utc};
+use poise::serenity_prelude::GuildId;
+use sqlx::postgres::{PgArguments, PgRow};
+use sqlx::query::{Query, QueryAs, QueryScalar};
+use sqlx::{Error as SqlxError, FromRow, Postgres, Result as SqlxResult, Row};
+
+use crate::data::common;
+use crate::database::InsertQuery;
+
+/// A guild's data retention policy, which the scheduled retention job applies each day.
+/// Each kind of data is kept indefinitely unless the policy says otherwise.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetentionPolicy {
+  pub guild_id: GuildId,
+  /// How many days erase records are kept for. `None` keeps them indefinitely.
+  pub erase_days: Option<i32>,
+  /// Whether starboard posts are removed when the starred message has been deleted, such
+  /// as while Bloom was offline and missed the deletion.
+  pub prune_star_messages: bool,
+  /// How many years meditation entries stay in the main table before being moved to the
+  /// archive. Archived entries no longer count toward stats. `None` never archives entries.
+  pub archive_years: Option<i32>,
+  /// Whether the retention job only reports what it would remove, without removing it.
+  /// New policies start as dry runs, so staff can check the reports before any data is
+  /// removed.
+  pub dry_run: bool,
+  pub last_run_at: Option<DateTime<Utc>>,
+}
+
+impl RetentionPolicy {
+  /// Creates a [`RetentionPolicy`] which keeps everything, as a dry run.
+  pub fn new(guild_id: GuildId) -> Self {
+    Self {
+      guild_id,
+      erase_days: None,
+      prune_star_messages: false,
+      archive_years: None,
+      dry_run: true,
+      last_run_at: None,
+    }
+  }
+
+  /// Sets how many days erase records are kept for, or keeps them indefinitely if `None`.
+  #[must_use]
+  pub fn erase_days(mut self, erase_days: Option<i32>) -> Self {
+    self.erase_days = erase_days;
+    self
+  }
+
+  /// Sets whether starboard posts for deleted messages are removed.
+  #[must_use]
+  pub fn prune_star_messages(mut self, prune_star_messages: bool) -> Self {
+    self.prune_star_messages = prune_star_messages;
+    self
+  }
+
+  /// Sets how many years meditation entries are kept before being archived, or never
+  /// archives them if `None`.
+  #[must_use]
+  pub fn archive_years(mut self, archive_years: Option<i32>) -> Self {
+    self.archive_years = archive_years;
+    self
+  }
+
+  /// Sets whether the retention job only reports what it would remove.
+  #[must_use]
+  pub fn dry_run(mut self, dry_run: bool) -> Self {
+    self.dry_run = dry_run;
+    self
+  }
+
+  /// Whether the policy keeps everything, so there's nothing to apply.
+  pub fn is_empty(&self) -> bool {
+    self.erase_days.is_none() && !self.prune_star_messages && self.archive_years.is_none()
+  }
+
+  /// Whether the policy is due to be applied, as it hasn't been applied in the `interval`
+  /// before `now`.
+  pub fn is_due(&self, now: DateTime<Utc>, interval: Duration) -> bool {
+    match self.last_run_at {
+      Some(last_run_at) => last_run_at <= now - interval,
+      None => true,
+    }
+  }
+
+  /// Erase records from before this time are removed.
+  pub fn erase_cutoff(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    self
+      .erase_days
+      .map(|days| now - Duration::days(i64::from(days)))
+  }
+
+  /// Meditation entries from before this time are archived.
+  pub fn archive_cutoff(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let years = u32::try_from(self.archive_years?).ok()?;
+    now.checked_sub_months(Months::new(years.checked_mul(12)?))
+  }
+
+  pub fn retrieve<'a>(guild_id: GuildId) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT guild_id, erase_days, prune_star_messages, archive_years, dry_run, last_run_at FROM retention_policy WHERE guild_id = $1",
+    )
+    .bind(guild_id.to_string())
+  }
+
+  /// Retrieves every guild's [`RetentionPolicy`], for the retention job.
+  pub fn retrieve_all<'a>() -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT guild_id, erase_days, prune_star_messages, archive_years, dry_run, last_run_at FROM retention_policy",
+    )
+  }
+
+  pub fn remove<'a>(guild_id: GuildId) -> Query<'a, Postgres, PgArguments> {
+    query!(
+      "DELETE FROM retention_policy WHERE guild_id = $1",
+      guild_id.to_string(),
+    )
+  }
+
+  /// Records that the retention job has applied the policy.
+  pub fn mark_run<'a>(guild_id: GuildId) -> Query<'a, Postgres, PgArguments> {
+    query!(
+      "UPDATE retention_policy SET last_run_at = NOW() WHERE guild_id = $1",
+      guild_id.to_string(),
+    )
+  }
+
+  /// Counts the erase records from before `cutoff`.
+  pub fn count_erases<'a>(
+    guild_id: GuildId,
+    cutoff: DateTime<Utc>,
+  ) -> QueryScalar<'a, Postgres, i64, PgArguments> {
+    sqlx::query_scalar("SELECT COUNT(*) FROM erases WHERE guild_id = $1 AND occurred_at < $2")
+      .bind(guild_id.to_string())
+      .bind(cutoff)
+  }
+
+  /// Removes the erase records from before `cutoff`.
+  pub fn remove_erases<'a>(
+    guild_id: GuildId,
+    cutoff: DateTime<Utc>,
+  ) -> Query<'a, Postgres, PgArguments> {
+    query!(
+      "DELETE FROM erases WHERE guild_id = $1 AND occurred_at < $2",
+      guild_id.to_string(),
+      cutoff,
+    )
+  }
+
+  /// Counts the meditation entries from before `cutoff`.
+  pub fn count_archivable<'a>(
+    guild_id: GuildId,
+    cutoff: DateTime<Utc>,
+  ) -> QueryScalar<'a, Postgres, i64, PgArguments> {
+    sqlx::query_scalar("SELECT COUNT(*) FROM meditation WHERE guild_id = $1 AND occurred_at < $2")
+      .bind(guild_id.to_string())
+      .bind(cutoff)
+  }
+
+  /// Moves the meditation entries from before `cutoff` to the archive.
+  pub fn archive_meditations<'a>(
+    guild_id: GuildId,
+    cutoff: DateTime<Utc>,
+  ) -> Query<'a, Postgres, PgArguments> {
+    query!(
+      "WITH archived AS (DELETE FROM meditation WHERE guild_id = $1 AND occurred_at < $2 RETURNING record_id, user_id, occurred_at, meditation_minutes, meditation_seconds, guild_id, channel_id, retreat_id, import_id, source_id) INSERT INTO meditation_archive (record_id, user_id, occurred_at, meditation_minutes, meditation_seconds, guild_id, channel_id, retreat_id, import_id, source_id) SELECT record_id, user_id, occurred_at, meditation_minutes, meditation_seconds, guild_id, channel_id, retreat_id, import_id, source_id FROM archived",
+      guild_id.to_string(),
+      cutoff,
+    )
+  }
+}
+
+impl InsertQuery for RetentionPolicy {
+  /// Saves a [`RetentionPolicy`], replacing the guild's previous policy.
+  fn insert_query(&self) -> Query<Postgres, PgArguments> {
+    query!(
+      "INSERT INTO retention_policy (guild_id, erase_days, prune_star_messages, archive_years, dry_run) VALUES ($1, $2, $3, $4, $5) ON CONFLICT (guild_id) DO UPDATE SET erase_days = EXCLUDED.erase_days, prune_star_messages = EXCLUDED.prune_star_messages, archive_years = EXCLUDED.archive_years, dry_run = EXCLUDED.dry_run",
+      self.guild_id.to_string(),
+      self.erase_days,
+      self.prune_star_messages,
+      self.archive_years,
+      self.dry_run,
+    )
+  }
+}
+
+impl FromRow<'_, PgRow> for RetentionPolicy {
+  fn from_row(row: &'_ PgRow) -> SqlxResult<Self, SqlxError> {
+    Ok(Self {
+      guild_id: GuildId::new(common::decode_id_row(row, "guild_id")?),
+      erase_days: row.try_get("erase_days")?,
+      prune_star_messages: row.try_get("prune_star_messages")?,
+      archive_years: row.try_get("archive_years")?,
+      dry_run: row.try_get("dry_run")?,
+      last_run_at: row.try_get("last_run_at")?,
+    })
+  }
+}
+
+/// What applying a [`RetentionPolicy`] removed, or would remove in a dry run. Starboard
+/// posts are checked against Discord, so they're counted by the retention job instead.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct RetentionSummary {
+  pub erases: u64,
+  pub meditations: u64,
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_cutoffs() {
+    let now = DateTime::from_timestamp(1_733_385_600, 0).unwrap_or_default();
+    let policy = RetentionPolicy::new(GuildId::new(1u64));
+    assert!(policy.is_empty());
+    assert_eq!(policy.erase_cutoff(now), None);
+    assert_eq!(policy.archive_cutoff(now), None);
+
+    let policy = policy.erase_days(Some(180)).archive_years(Some(5));
+    assert!(!policy.is_empty());
+    assert_eq!(policy.erase_cutoff(now), Some(now - Duration::days(180)));
+    assert_eq!(
+      policy.archive_cutoff(now),
+      DateTime::from_timestamp(1_575_532_800, 0)
+    );
+    assert_eq!(policy.archive_years(Some(-1)).archive_cutoff(now), None);
+  }
+
+  #[test]
+  fn test_is_due() {
+    let now = DateTime::from_timestamp(1_733_385_600, 0).unwrap_or_default();
+    let mut policy = RetentionPolicy::new(GuildId::new(1u64));
+    assert!(policy.is_due(now, Duration::days(1)));
+
+    policy.last_run_at = Some(now - Duration::hours(23));
+    assert!(!policy.is_due(now, Duration::days(1)));
+
+    policy.last_run_at = Some(now - Duration::hours(24));
+    assert!(policy.is_due(now, Duration::days(1)));
+  }
+}
@@ -0,0 +1,203 @@
+use chrono::{Duration, NaiveDate};
+use poise::serenity_prelude::{GuildId, UserId};
+use sqlx::postgres::{PgArguments, PgRow};
+use sqlx::query::{Query, QueryAs};
+use sqlx::{Error as SqlxError, FromRow, Postgres, Result as SqlxResult, Row};
+use ulid::Ulid;
+
+use crate::data::common;
+use crate::data::meditation::Meditation;
+use crate::database::InsertQuery;
+
+/// What staff decided about a logged [`Retreat`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetreatStatus {
+  /// Not yet reviewed. The retreat's entries count in the meantime.
+  Open,
+  Approved,
+  /// The retreat's entries have been removed.
+  Removed,
+}
+
+impl RetreatStatus {
+  pub fn as_str(self) -> &'static str {
+    match self {
+      Self::Open => "open",
+      Self::Approved => "approved",
+      Self::Removed => "removed",
+    }
+  }
+
+  fn from_name(status: &str) -> Self {
+    match status {
+      "approved" => Self::Approved,
+      "removed" => Self::Removed,
+      _ => Self::Open,
+    }
+  }
+}
+
+/// A multi-day retreat logged with `/retreat log`, which is expanded into one
+/// [`Meditation`] entry for each day from `start_date` to `end_date`, inclusive. The entries
+/// are linked to the retreat, so staff can remove them together when reviewing it.
+pub struct Retreat {
+  pub id: String,
+  pub guild_id: GuildId,
+  pub user_id: UserId,
+  pub start_date: NaiveDate,
+  pub end_date: NaiveDate,
+  pub minutes_per_day: i32,
+  pub status: RetreatStatus,
+  pub handled_by: Option<UserId>,
+}
+
+impl Retreat {
+  pub fn new(
+    guild_id: GuildId,
+    user_id: UserId,
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    minutes_per_day: i32,
+  ) -> Self {
+    Self {
+      id: Ulid::new().to_string(),
+      guild_id,
+      user_id,
+      start_date,
+      end_date,
+      minutes_per_day,
+      status: RetreatStatus::Open,
+      handled_by: None,
+    }
+  }
+
+  /// Returns each day of the retreat, in order.
+  pub fn dates(&self) -> impl Iterator<Item = NaiveDate> + '_ {
+    self
+      .start_date
+      .iter_days()
+      .take_while(|date| *date <= self.end_date)
+  }
+
+  /// Returns the number of days the retreat lasted.
+  pub fn days(&self) -> i64 {
+    (self.end_date - self.start_date + Duration::days(1)).num_days()
+  }
+
+  pub fn total_minutes(&self) -> i64 {
+    self.days() * i64::from(self.minutes_per_day)
+  }
+
+  pub fn retrieve(retreat_id: &str) -> QueryAs<'_, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT record_id, guild_id, user_id, start_date, end_date, minutes_per_day, status, handled_by FROM retreat WHERE record_id = $1",
+    )
+    .bind(retreat_id)
+  }
+
+  /// Retrieves a [`Retreat`] the member has logged which overlaps the specified days, unless
+  /// its entries have been removed.
+  pub fn retrieve_overlapping<'a>(
+    guild_id: GuildId,
+    user_id: UserId,
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+  ) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT record_id, guild_id, user_id, start_date, end_date, minutes_per_day, status, handled_by FROM retreat WHERE guild_id = $1 AND user_id = $2 AND start_date <= $4 AND end_date >= $3 AND status <> 'removed' LIMIT 1",
+    )
+    .bind(guild_id.to_string())
+    .bind(user_id.to_string())
+    .bind(start_date)
+    .bind(end_date)
+  }
+
+  /// Adds a [`Meditation`] entry for one day of the retreat, linked to the retreat.
+  pub fn entry_query<'a>(&'a self, meditation: &'a Meditation) -> Query<'a, Postgres, PgArguments> {
+    query!(
+      "INSERT INTO meditation (record_id, user_id, meditation_minutes, meditation_seconds, guild_id, occurred_at, retreat_id) VALUES ($1, $2, $3, $4, $5, $6, $7)",
+      meditation.id,
+      meditation.user_id.to_string(),
+      meditation.minutes,
+      meditation.seconds,
+      meditation.guild_id.to_string(),
+      meditation.occurred_at,
+      self.id,
+    )
+  }
+
+  /// Removes the [`Meditation`] entries added for a retreat.
+  pub fn remove_entries(retreat_id: &str) -> Query<'_, Postgres, PgArguments> {
+    query!("DELETE FROM meditation WHERE retreat_id = $1", retreat_id)
+  }
+
+  /// Records what staff decided about a [`Retreat`]. Only affects retreats which haven't
+  /// been reviewed, so each retreat is only handled once.
+  pub fn set_status(
+    retreat_id: &str,
+    status: RetreatStatus,
+    handled_by: UserId,
+  ) -> Query<'_, Postgres, PgArguments> {
+    query!(
+      "UPDATE retreat SET status = $1, handled_by = $2, handled_at = NOW() WHERE record_id = $3 AND status = 'open'",
+      status.as_str(),
+      handled_by.to_string(),
+      retreat_id,
+    )
+  }
+}
+
+impl InsertQuery for Retreat {
+  /// Adds a [`Retreat`] to the database. Its entries are added separately with
+  /// [`Retreat::entry_query`].
+  fn insert_query(&self) -> Query<Postgres, PgArguments> {
+    query!(
+      "INSERT INTO retreat (record_id, guild_id, user_id, start_date, end_date, minutes_per_day) VALUES ($1, $2, $3, $4, $5, $6)",
+      self.id,
+      self.guild_id.to_string(),
+      self.user_id.to_string(),
+      self.start_date,
+      self.end_date,
+      self.minutes_per_day,
+    )
+  }
+}
+
+impl FromRow<'_, PgRow> for Retreat {
+  fn from_row(row: &'_ PgRow) -> SqlxResult<Self, SqlxError> {
+    Ok(Self {
+      id: row.try_get("record_id")?,
+      guild_id: GuildId::new(common::decode_id_row(row, "guild_id")?),
+      user_id: UserId::new(common::decode_id_row(row, "user_id")?),
+      start_date: row.try_get("start_date")?,
+      end_date: row.try_get("end_date")?,
+      minutes_per_day: row.try_get("minutes_per_day")?,
+      status: RetreatStatus::from_name(row.try_get("status")?),
+      handled_by: common::decode_option_id_row(row, "handled_by")?.map(UserId::new),
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_dates() {
+    let date = |day| NaiveDate::from_ymd_opt(2024, 11, day).unwrap_or_default();
+    let retreat = Retreat::new(
+      GuildId::new(123u64),
+      UserId::new(456u64),
+      date(8),
+      date(10),
+      360,
+    );
+
+    assert_eq!(
+      retreat.dates().collect::<Vec<_>>(),
+      vec![date(8), date(9), date(10)]
+    );
+    assert_eq!(retreat.days(), 3);
+    assert_eq!(retreat.total_minutes(), 1080);
+  }
+}
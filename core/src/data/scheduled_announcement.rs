@@ -0,0 +1,121 @@
+use chrono::{DateTime, Utc};
+use poise::serenity_prelude::{ChannelId, GuildId, UserId};
+use poise::Modal;
+use sqlx::postgres::{PgArguments, PgRow};
+use sqlx::query::{Query, QueryAs};
+use sqlx::{Error as SqlxError, FromRow, Postgres, Result as SqlxResult, Row};
+use ulid::Ulid;
+
+use crate::data::common;
+use crate::database::InsertQuery;
+
+/// An announcement composed by staff, which is posted as an embed in `channel_id` at
+/// `send_at`.
+pub struct ScheduledAnnouncement {
+  pub id: String,
+  pub guild_id: GuildId,
+  pub channel_id: ChannelId,
+  pub title: String,
+  pub message: String,
+  pub send_at: DateTime<Utc>,
+  pub created_by: UserId,
+}
+
+#[allow(clippy::module_name_repetitions)]
+#[derive(Debug, Modal)]
+#[name = "Compose Announcement"]
+pub struct AnnouncementModal {
+  #[name = "Title"]
+  #[placeholder = "The title of the announcement"]
+  #[max_length = 256]
+  pub title: String,
+  #[name = "Message"]
+  #[placeholder = "The announcement itself, which may use Markdown"]
+  #[paragraph]
+  #[max_length = 4000]
+  pub message: String,
+}
+
+impl ScheduledAnnouncement {
+  pub fn new(
+    guild_id: GuildId,
+    channel_id: ChannelId,
+    modal: AnnouncementModal,
+    send_at: DateTime<Utc>,
+    created_by: UserId,
+  ) -> Self {
+    Self {
+      id: Ulid::new().to_string(),
+      guild_id,
+      channel_id,
+      title: modal.title.trim().to_owned(),
+      message: modal.message.trim().to_owned(),
+      send_at,
+      created_by,
+    }
+  }
+
+  /// Retrieves a server's announcements which haven't been posted yet, soonest first.
+  pub fn retrieve_pending<'a>(guild_id: GuildId) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT record_id, guild_id, channel_id, title, message, send_at, created_by FROM scheduled_announcement WHERE guild_id = $1 AND sent = FALSE ORDER BY send_at ASC",
+    )
+    .bind(guild_id.to_string())
+  }
+
+  /// Retrieves announcements in every server which were due to be posted at or before `now`.
+  pub fn retrieve_due(now: &DateTime<Utc>) -> QueryAs<'_, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT record_id, guild_id, channel_id, title, message, send_at, created_by FROM scheduled_announcement WHERE sent = FALSE AND send_at <= $1 ORDER BY send_at ASC",
+    )
+    .bind(now)
+  }
+
+  /// Marks a [`ScheduledAnnouncement`] as posted. Only affects announcements which haven't
+  /// been posted, so an announcement can only be claimed for posting once.
+  pub fn mark_sent(announcement_id: &str) -> Query<'_, Postgres, PgArguments> {
+    query!(
+      "UPDATE scheduled_announcement SET sent = TRUE WHERE record_id = $1 AND sent = FALSE",
+      announcement_id,
+    )
+  }
+
+  /// Removes a [`ScheduledAnnouncement`] which hasn't been posted yet.
+  pub fn remove(guild_id: GuildId, announcement_id: &str) -> Query<'_, Postgres, PgArguments> {
+    query!(
+      "DELETE FROM scheduled_announcement WHERE record_id = $1 AND guild_id = $2 AND sent = FALSE",
+      announcement_id,
+      guild_id.to_string(),
+    )
+  }
+}
+
+impl InsertQuery for ScheduledAnnouncement {
+  /// Adds a [`ScheduledAnnouncement`] to the database.
+  fn insert_query(&self) -> Query<Postgres, PgArguments> {
+    query!(
+      "INSERT INTO scheduled_announcement (record_id, guild_id, channel_id, title, message, send_at, created_by) VALUES ($1, $2, $3, $4, $5, $6, $7)",
+      self.id,
+      self.guild_id.to_string(),
+      self.channel_id.to_string(),
+      self.title,
+      self.message,
+      self.send_at,
+      self.created_by.to_string(),
+    )
+  }
+}
+
+impl FromRow<'_, PgRow> for ScheduledAnnouncement {
+  fn from_row(row: &'_ PgRow) -> SqlxResult<Self, SqlxError> {
+    Ok(Self {
+      id: row.try_get("record_id")?,
+      guild_id: GuildId::new(common::decode_id_row(row, "guild_id")?),
+      channel_id: ChannelId::new(common::decode_id_row(row, "channel_id")?),
+      title: row.try_get("title")?,
+      message: row.try_get("message")?,
+      send_at: row.try_get("send_at")?,
+      created_by: UserId::new(common::decode_id_row(row, "created_by")?),
+    })
+  }
+}
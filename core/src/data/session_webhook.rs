@@ -0,0 +1,303 @@
+use std::fmt;
+
+use chrono::{DateTime, Duration, Utc};
+use poise::serenity_prelude::{GuildId, UserId};
+use rand::Rng;
+use serde::Deserialize;
+use sqlx::postgres::{PgArguments, PgRow};
+use sqlx::query::{Query, QueryAs, QueryScalar};
+use sqlx::{Error as SqlxError, FromRow, Postgres, Result as SqlxResult, Row};
+use ulid::Ulid;
+
+use crate::data::account_link::hash_secret;
+use crate::data::common;
+use crate::database::{DeleteQuery, InsertQuery};
+
+/// The longest session a webhook can log. Longer sessions need to be confirmed when added
+/// with `/add`, which a webhook can't do.
+pub const MAX_MINUTES: i32 = 300;
+/// How far in the future a session's timestamp can be, to allow for clocks being slightly
+/// out of sync.
+const MAX_CLOCK_SKEW_MINUTES: i64 = 5;
+/// How far in the past a session's timestamp can be. Older sessions can be added with
+/// `/addmulti`, or by staff.
+pub const MAX_DAYS_BACK: i64 = 7;
+
+/// The most tokens a member can have in a guild.
+pub const MAX_TOKENS: usize = 10;
+/// The number of sessions a token can log each hour, unless another limit is chosen.
+pub const DEFAULT_HOURLY_LIMIT: i32 = 10;
+
+/// A member's personal token for logging sessions in a guild from an automation, such as an
+/// iOS Shortcut, a Tasker task, or a Zapier or IFTTT applet. Tokens can only log sessions,
+/// for the member and guild they were created for, and each has its own limit on how many
+/// sessions it can log an hour. Only the token's hash is stored.
+pub struct WebhookToken {
+  pub id: String,
+  pub token_hash: String,
+  pub guild_id: GuildId,
+  pub user_id: UserId,
+  /// The name the member gave the token, such as "Apple Watch", to tell it apart when
+  /// listing or revoking tokens.
+  pub name: String,
+  pub hourly_limit: i32,
+  pub last_used_at: Option<DateTime<Utc>>,
+}
+
+impl WebhookToken {
+  /// Generates a new token for a member. Returns the token to show them, along with the
+  /// [`WebhookToken`] to store, which only holds its hash.
+  pub fn generate(
+    guild_id: GuildId,
+    user_id: UserId,
+    name: impl Into<String>,
+    hourly_limit: i32,
+  ) -> (String, Self) {
+    let token = hex::encode(rand::thread_rng().gen::<[u8; 24]>());
+
+    let webhook_token = Self {
+      id: Ulid::new().to_string(),
+      token_hash: hash_secret(&token),
+      guild_id,
+      user_id,
+      name: name.into(),
+      hourly_limit,
+      last_used_at: None,
+    };
+
+    (token, webhook_token)
+  }
+
+  pub fn retrieve(token_hash: &str) -> QueryAs<'_, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT record_id, token_hash, guild_id, user_id, name, hourly_limit, last_used_at FROM webhook_token WHERE token_hash = $1",
+    )
+    .bind(token_hash)
+  }
+
+  /// Retrieves every [`WebhookToken`] a member has in a guild, sorted by name.
+  pub fn retrieve_all<'a>(
+    guild_id: GuildId,
+    user_id: UserId,
+  ) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT record_id, token_hash, guild_id, user_id, name, hourly_limit, last_used_at FROM webhook_token WHERE guild_id = $1 AND user_id = $2 ORDER BY name ASC",
+    )
+    .bind(guild_id.to_string())
+    .bind(user_id.to_string())
+  }
+
+  /// Records a use of a token, returning its ID. Returns nothing if the token has already
+  /// logged its hourly limit of sessions. Uses are counted in hour-long windows, starting
+  /// from the first use after the previous window ended.
+  pub fn consume(token_hash: &str) -> QueryScalar<'_, Postgres, String, PgArguments> {
+    sqlx::query_scalar(
+      "UPDATE webhook_token SET last_used_at = NOW(), window_started_at = CASE WHEN window_started_at IS NULL OR window_started_at <= NOW() - INTERVAL '1 hour' THEN NOW() ELSE window_started_at END, window_uses = CASE WHEN window_started_at IS NULL OR window_started_at <= NOW() - INTERVAL '1 hour' THEN 1 ELSE window_uses + 1 END WHERE token_hash = $1 AND (window_started_at IS NULL OR window_started_at <= NOW() - INTERVAL '1 hour' OR window_uses < hourly_limit) RETURNING record_id",
+    )
+    .bind(token_hash)
+  }
+
+  /// Locks a member's tokens in a guild until the end of the transaction, so that tokens
+  /// can be counted and added without racing another request to add one.
+  pub fn lock<'a>(guild_id: GuildId, user_id: UserId) -> Query<'a, Postgres, PgArguments> {
+    sqlx::query("SELECT pg_advisory_xact_lock(hashtext('webhook_token:' || $1 || ':' || $2))")
+      .bind(guild_id.to_string())
+      .bind(user_id.to_string())
+  }
+
+  /// Counts the tokens a member has in a guild.
+  pub fn count<'a>(
+    guild_id: GuildId,
+    user_id: UserId,
+  ) -> QueryScalar<'a, Postgres, i64, PgArguments> {
+    sqlx::query_scalar("SELECT COUNT(*) FROM webhook_token WHERE guild_id = $1 AND user_id = $2")
+      .bind(guild_id.to_string())
+      .bind(user_id.to_string())
+  }
+
+  /// Removes one of a member's tokens by name, ignoring case.
+  pub fn remove<'a>(
+    guild_id: GuildId,
+    user_id: UserId,
+    name: &'a str,
+  ) -> Query<'a, Postgres, PgArguments> {
+    query!(
+      "DELETE FROM webhook_token WHERE guild_id = $1 AND user_id = $2 AND lower(name) = lower($3)",
+      guild_id.to_string(),
+      user_id.to_string(),
+      name,
+    )
+  }
+}
+
+impl InsertQuery for WebhookToken {
+  fn insert_query(&self) -> Query<Postgres, PgArguments> {
+    query!(
+      "INSERT INTO webhook_token (record_id, token_hash, guild_id, user_id, name, hourly_limit) VALUES ($1, $2, $3, $4, $5, $6)",
+      self.id,
+      self.token_hash,
+      self.guild_id.to_string(),
+      self.user_id.to_string(),
+      self.name,
+      self.hourly_limit,
+    )
+  }
+}
+
+impl DeleteQuery for WebhookToken {
+  /// Removes every token a member has in a guild.
+  fn delete_query<'a>(
+    guild_id: GuildId,
+    user_id: impl Into<String>,
+  ) -> Query<'a, Postgres, PgArguments> {
+    query!(
+      "DELETE FROM webhook_token WHERE guild_id = $1 AND user_id = $2",
+      guild_id.to_string(),
+      user_id.into(),
+    )
+  }
+}
+
+impl FromRow<'_, PgRow> for WebhookToken {
+  fn from_row(row: &'_ PgRow) -> SqlxResult<Self, SqlxError> {
+    Ok(Self {
+      id: row.try_get("record_id")?,
+      token_hash: row.try_get("token_hash")?,
+      guild_id: GuildId::new(common::decode_id_row(row, "guild_id")?),
+      user_id: UserId::new(common::decode_id_row(row, "user_id")?),
+      name: row.try_get("name")?,
+      hourly_limit: row.try_get("hourly_limit")?,
+      last_used_at: row.try_get("last_used_at")?,
+    })
+  }
+}
+
+/// The JSON body of a request to the session webhook, such as:
+///
+/// ```json
+/// { "token": "...", "minutes": 20, "timestamp": "2024-12-03T07:30:00Z" }
+/// ```
+///
+/// The session is logged at the time of the request if `timestamp` is left out.
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SessionPayload {
+  pub token: String,
+  pub minutes: i32,
+  #[serde(default)]
+  pub seconds: i32,
+  pub timestamp: Option<DateTime<Utc>>,
+}
+
+impl SessionPayload {
+  /// Checks the session can be logged, returning when it took place.
+  ///
+  /// # Errors
+  /// Returns an [`InvalidSession`] if the length is out of range or the timestamp is in the
+  /// future or more than [`MAX_DAYS_BACK`] days ago.
+  pub fn occurred_at(&self, now: DateTime<Utc>) -> Result<DateTime<Utc>, InvalidSession> {
+    if !(1..=MAX_MINUTES).contains(&self.minutes) {
+      return Err(InvalidSession::Minutes);
+    }
+    if !(0..60).contains(&self.seconds) {
+      return Err(InvalidSession::Seconds);
+    }
+
+    let occurred_at = self.timestamp.unwrap_or(now);
+    if occurred_at > now + Duration::minutes(MAX_CLOCK_SKEW_MINUTES) {
+      return Err(InvalidSession::FutureTimestamp);
+    }
+    if occurred_at < now - Duration::days(MAX_DAYS_BACK) {
+      return Err(InvalidSession::PastTimestamp);
+    }
+
+    Ok(occurred_at)
+  }
+}
+
+/// Why a [`SessionPayload`] was rejected, which the webhook returns to the caller, along
+/// with a client error status.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InvalidSession {
+  Minutes,
+  Seconds,
+  FutureTimestamp,
+  PastTimestamp,
+  /// The token has already logged its hourly limit of sessions, which should be reported
+  /// with a `429 Too Many Requests` status.
+  RateLimited,
+}
+
+impl fmt::Display for InvalidSession {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Self::Minutes => write!(f, "minutes must be between 1 and {MAX_MINUTES}"),
+      Self::Seconds => write!(f, "seconds must be between 0 and 59"),
+      Self::FutureTimestamp => write!(f, "timestamp can't be in the future"),
+      Self::PastTimestamp => write!(f, "timestamp can't be more than {MAX_DAYS_BACK} days ago"),
+      Self::RateLimited => write!(
+        f,
+        "too many sessions logged with this token, try again later"
+      ),
+    }
+  }
+}
+
+impl std::error::Error for InvalidSession {}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_payload() {
+    let now = DateTime::from_timestamp(1_733_211_000, 0).unwrap_or_default();
+    let payload = |json: &str| serde_json::from_str::<SessionPayload>(json);
+
+    let Ok(session) = payload(r#"{ "token": "abc", "minutes": 20 }"#) else {
+      panic!("Expected a valid payload");
+    };
+    assert_eq!(session.occurred_at(now), Ok(now));
+
+    let Ok(session) = payload(
+      r#"{ "token": "abc", "minutes": 20, "seconds": 30, "timestamp": "2024-12-03T07:00:00Z" }"#,
+    ) else {
+      panic!("Expected a valid payload");
+    };
+    assert_eq!(session.seconds, 30);
+    assert_eq!(
+      session.occurred_at(now),
+      Ok(DateTime::from_timestamp(1_733_209_200, 0).unwrap_or_default())
+    );
+
+    let invalid = |json: &str| payload(json).map(|session| session.occurred_at(now));
+    assert_eq!(
+      invalid(r#"{ "token": "abc", "minutes": 0 }"#).ok(),
+      Some(Err(InvalidSession::Minutes))
+    );
+    assert_eq!(
+      invalid(r#"{ "token": "abc", "minutes": 301 }"#).ok(),
+      Some(Err(InvalidSession::Minutes))
+    );
+    assert_eq!(
+      invalid(r#"{ "token": "abc", "minutes": 20, "seconds": 60 }"#).ok(),
+      Some(Err(InvalidSession::Seconds))
+    );
+    assert_eq!(
+      invalid(r#"{ "token": "abc", "minutes": 20, "timestamp": "2024-12-04T07:00:00Z" }"#).ok(),
+      Some(Err(InvalidSession::FutureTimestamp))
+    );
+    assert_eq!(
+      invalid(r#"{ "token": "abc", "minutes": 20, "timestamp": "2024-11-27T07:00:00Z" }"#).ok(),
+      Some(Ok(
+        DateTime::from_timestamp(1_732_690_800, 0).unwrap_or_default()
+      ))
+    );
+    assert_eq!(
+      invalid(r#"{ "token": "abc", "minutes": 20, "timestamp": "2024-11-25T07:00:00Z" }"#).ok(),
+      Some(Err(InvalidSession::PastTimestamp))
+    );
+    assert!(payload(r#"{ "token": "abc" }"#).is_err());
+    assert!(payload(r#"{ "token": "abc", "minutes": 20, "user": "123" }"#).is_err());
+  }
+}
@@ -0,0 +1,110 @@
+use poise::serenity_prelude::{ChannelId, GuildId, MessageId};
+use sqlx::postgres::{PgArguments, PgRow};
+use sqlx::query::{Query, QueryAs};
+use sqlx::{Error as SqlxError, FromRow, Postgres, Result as SqlxResult, Row};
+use ulid::Ulid;
+
+use crate::data::common;
+use crate::database::{DeleteQuery, InsertQuery};
+
+pub struct StarMessage {
+  pub id: String,
+  pub guild_id: Option<GuildId>,
+  pub starred_channel: ChannelId,
+  pub starred_message: MessageId,
+  pub board_message: MessageId,
+}
+
+impl StarMessage {
+  pub fn new(
+    guild_id: GuildId,
+    starred_channel: ChannelId,
+    starred_message: MessageId,
+    board_message: MessageId,
+  ) -> Self {
+    Self {
+      id: Ulid::new().to_string(),
+      guild_id: Some(guild_id),
+      starred_channel,
+      starred_message,
+      board_message,
+    }
+  }
+
+  /// Retrieves a [`StarMessage`] from the database. Entries recorded before starboard
+  /// messages were scoped to a guild have no `guild_id` and will match any guild.
+  pub fn retrieve<'a>(
+    guild_id: GuildId,
+    message_id: MessageId,
+  ) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT record_id, guild_id, starred_message_id, board_message_id, starred_channel_id FROM star WHERE starred_message_id = $1 AND (guild_id = $2 OR guild_id IS NULL)",
+    )
+    .bind(message_id.to_string())
+    .bind(guild_id.to_string())
+  }
+
+  /// Retrieves up to `limit` of a guild's [`StarMessage`]s, starting with those checked
+  /// least recently for having been deleted.
+  pub fn retrieve_least_checked<'a>(
+    guild_id: GuildId,
+    limit: i64,
+  ) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT record_id, guild_id, starred_message_id, board_message_id, starred_channel_id FROM star WHERE guild_id = $1 ORDER BY checked_at ASC NULLS FIRST LIMIT $2",
+    )
+    .bind(guild_id.to_string())
+    .bind(limit)
+  }
+
+  /// Records that the starred messages still exist.
+  pub fn mark_checked<'a>(record_ids: &'a [String]) -> Query<'a, Postgres, PgArguments> {
+    query!(
+      "UPDATE star SET checked_at = NOW() WHERE record_id = ANY($1)",
+      record_ids,
+    )
+  }
+}
+
+impl InsertQuery for StarMessage {
+  fn insert_query(&self) -> Query<Postgres, PgArguments> {
+    query!(
+      "INSERT INTO star (record_id, starred_message_id, board_message_id, starred_channel_id, guild_id) VALUES ($1, $2, $3, $4, $5) ON CONFLICT (starred_message_id) DO UPDATE SET board_message_id = $3, guild_id = $5",
+      self.id,
+      self.starred_message.to_string(),
+      self.board_message.to_string(),
+      self.starred_channel.to_string(),
+      self.guild_id.map(|guild_id| guild_id.to_string()),
+    )
+  }
+}
+
+impl DeleteQuery for StarMessage {
+  fn delete_query<'a>(
+    guild_id: GuildId,
+    record_id: impl Into<String>,
+  ) -> Query<'a, Postgres, PgArguments> {
+    query!(
+      "DELETE FROM star WHERE record_id = $1 AND (guild_id = $2 OR guild_id IS NULL)",
+      record_id.into(),
+      guild_id.to_string(),
+    )
+  }
+}
+
+impl FromRow<'_, PgRow> for StarMessage {
+  fn from_row(row: &'_ PgRow) -> SqlxResult<Self, SqlxError> {
+    let guild_id = common::decode_option_id_row(row, "guild_id")?.map(GuildId::new);
+    let starred_channel = ChannelId::new(common::decode_id_row(row, "starred_channel_id")?);
+    let starred_message = MessageId::new(common::decode_id_row(row, "starred_message_id")?);
+    let board_message = MessageId::new(common::decode_id_row(row, "board_message_id")?);
+
+    Ok(Self {
+      id: row.try_get("record_id").unwrap_or_default(),
+      guild_id,
+      starred_channel,
+      starred_message,
+      board_message,
+    })
+  }
+}
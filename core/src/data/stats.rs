@@ -0,0 +1,833 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use poise::serenity_prelude::{ChannelId, GuildId, UserId};
+use poise::ChoiceParameter;
+use sqlx::postgres::{PgArguments, PgRow};
+use sqlx::query::{Query, QueryAs};
+use sqlx::{Error as SqlxError, FromRow, Postgres, Result as SqlxResult, Row};
+use ulid::Ulid;
+
+use crate::data::common;
+use crate::database::UpdateQuery;
+use crate::time::Timeframe as StatsTimeframe;
+
+/// The stat a leaderboard is sorted by.
+#[derive(ChoiceParameter)]
+pub enum SortBy {
+  #[name = "minutes"]
+  Minutes,
+  #[name = "sessions"]
+  Sessions,
+  #[name = "streak"]
+  Streak,
+}
+
+/// The number of members shown on a leaderboard.
+#[derive(ChoiceParameter)]
+pub enum LeaderboardType {
+  #[name = "Top 5"]
+  Top5,
+  #[name = "Top 10"]
+  Top10,
+}
+
+#[derive(Default)]
+pub struct Streak {
+  guild_id: GuildId,
+  user_id: UserId,
+  pub current: i32,
+  pub longest: i32,
+}
+
+#[derive(Debug, FromRow)]
+pub struct MeditationCountByDay {
+  pub days_ago: i32,
+}
+
+#[derive(Debug, FromRow)]
+pub struct Timeframe {
+  pub sum: Option<i64>,
+  pub count: Option<i64>,
+}
+
+#[derive(Debug, Default, FromRow)]
+#[sqlx(default)]
+pub struct LeaderboardUser {
+  pub name: Option<String>,
+  pub minutes: Option<i64>,
+  pub sessions: Option<i64>,
+  pub streak: Option<i32>,
+  pub anonymous_tracking: Option<bool>,
+  pub streaks_active: Option<bool>,
+  pub streaks_private: Option<bool>,
+}
+
+/// A member's minutes for a timeframe compared to the timeframe before it.
+#[derive(Debug)]
+pub struct Improvement {
+  pub user_id: UserId,
+  pub current_minutes: i64,
+  pub previous_minutes: i64,
+  pub anonymous_tracking: bool,
+}
+
+#[derive(Debug, Default, FromRow)]
+#[sqlx(default)]
+pub struct ByInterval {
+  pub times_ago: Option<f64>,
+  pub meditation_minutes: Option<i64>,
+  pub meditation_count: Option<i64>,
+}
+
+/// Meditation in a recurring period, such as an hour of the day or a day of the week.
+#[derive(Debug, Default, FromRow)]
+#[sqlx(default)]
+pub struct ByRecurringPeriod {
+  pub period: Option<i32>,
+  pub meditation_minutes: Option<i64>,
+  pub meditation_count: Option<i64>,
+}
+
+/// Meditation in a period of a date range, identified by the date the period starts on.
+#[derive(Debug, Default, FromRow)]
+#[sqlx(default)]
+pub struct ByPeriod {
+  pub period: Option<NaiveDate>,
+  pub meditation_minutes: Option<i64>,
+  pub meditation_count: Option<i64>,
+}
+
+/// A member's progress in a server, used to work out which time and streak roles they've
+/// earned.
+#[derive(Debug)]
+pub struct MemberProgress {
+  pub user_id: UserId,
+  pub minutes: i64,
+  pub streaks_active: bool,
+  pub last_meditation: DateTime<Utc>,
+}
+
+pub struct User {
+  pub all_minutes: i64,
+  pub all_count: u64,
+  pub timeframe_stats: Timeframe,
+  pub streak: Streak,
+}
+
+pub struct Guild {
+  pub all_minutes: i64,
+  pub all_count: u64,
+  pub timeframe_stats: Timeframe,
+}
+
+/// Anonymous totals across every guild which has turned on global stats. Members with private
+/// stats are left out, as are entries copied from another guild, so nobody is counted twice.
+#[derive(Debug, Default, FromRow)]
+#[sqlx(default)]
+pub struct Global {
+  /// The number of guilds which have turned on global stats.
+  pub guilds: i64,
+  pub minutes_today: i64,
+  pub sessions_today: i64,
+  pub meditators_today: i64,
+  pub meditators_week: i64,
+}
+
+impl Streak {
+  pub fn new(guild_id: GuildId, user_id: UserId, current: i32, longest: i32) -> Self {
+    Self {
+      guild_id,
+      user_id,
+      current,
+      longest,
+    }
+  }
+
+  /// Updates the streak from the days a member meditated, given as days before today in
+  /// ascending order without duplicates. The current streak runs back from the most recent
+  /// day, as long as that was no more than two days ago. A single day isn't a streak.
+  ///
+  /// If the longest streak has been calculated before, only the current streak is compared
+  /// with it, since entries are almost always added for today. Otherwise, every run of
+  /// consecutive days is counted.
+  pub fn count_days(&mut self, days_ago: &[i32]) {
+    let counted = |run: i32| if run < 2 { 0 } else { run };
+
+    // The length of each run of consecutive days, most recent first
+    let mut runs: Vec<i32> = Vec::new();
+    let mut previous = None;
+    for &day in days_ago {
+      match (previous, runs.last_mut()) {
+        (Some(previous), Some(run)) if day == previous + 1 => *run += 1,
+        _ => runs.push(1),
+      }
+      previous = Some(day);
+    }
+
+    let recent = counted(runs.first().copied().unwrap_or_default());
+    self.current = if days_ago.first().is_some_and(|day| *day <= 2) {
+      recent
+    } else {
+      0
+    };
+
+    self.longest = if self.longest > 0 {
+      self.longest.max(recent)
+    } else {
+      runs.into_iter().map(counted).max().unwrap_or_default()
+    };
+  }
+
+  pub fn calculate<'a>(
+    guild_id: GuildId,
+    user_id: UserId,
+  ) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT current_streak, longest_streak FROM streak WHERE guild_id = $1 AND user_id = $2",
+    )
+    .bind(guild_id.to_string())
+    .bind(user_id.to_string())
+  }
+
+  /// Retrieves every stored [`Streak`] in a guild.
+  pub fn guild<'a>(guild_id: GuildId) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT user_id, guild_id, current_streak, longest_streak FROM streak WHERE guild_id = $1",
+    )
+    .bind(guild_id.to_string())
+  }
+
+  pub fn user_id(&self) -> UserId {
+    self.user_id
+  }
+
+  /// Clears the stored [`Streak`] for a user, so that the longest streak is calculated from
+  /// scratch the next time it is retrieved.
+  pub fn reset<'a>(guild_id: GuildId, user_id: UserId) -> Query<'a, Postgres, PgArguments> {
+    query!(
+      "DELETE FROM streak WHERE guild_id = $1 AND user_id = $2",
+      guild_id.to_string(),
+      user_id.to_string(),
+    )
+  }
+}
+
+impl UpdateQuery for Streak {
+  fn update_query(&self) -> Query<Postgres, PgArguments> {
+    query!(
+      "INSERT INTO streak (record_id, user_id, guild_id, current_streak, longest_streak) VALUES ($1, $2, $3, $4, $5) ON CONFLICT (user_id, guild_id) DO UPDATE SET current_streak = $4, longest_streak = $5",
+      Ulid::new().to_string(),
+      self.user_id.to_string(),
+      self.guild_id.to_string(),
+      self.current,
+      self.longest,
+    )
+  }
+}
+
+impl FromRow<'_, PgRow> for Streak {
+  fn from_row(row: &'_ PgRow) -> SqlxResult<Self, SqlxError> {
+    let guild_id = GuildId::new(common::decode_id_row(row, "guild_id")?);
+    let user_id = UserId::new(common::decode_id_row(row, "user_id")?);
+
+    Ok(Self {
+      guild_id,
+      user_id,
+      current: row.try_get("current_streak").unwrap_or_default(),
+      longest: row.try_get("longest_streak").unwrap_or_default(),
+    })
+  }
+}
+
+impl MeditationCountByDay {
+  /// Retrieves the days a member meditated, as days before today. Days with an approved
+  /// [`StreakRepair`][crate::data::streak_repair::StreakRepair] count as days meditated.
+  pub fn calculate<'a>(
+    guild_id: GuildId,
+    user_id: UserId,
+  ) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "WITH cte AS (SELECT date_part('day', NOW() - DATE_TRUNC('day', occurred_at))::int AS days_ago FROM meditation WHERE user_id = $1 AND guild_id = $2 AND occurred_at::date <= NOW()::date UNION ALL SELECT NOW()::date - repaired_date AS days_ago FROM streak_repair WHERE user_id = $1 AND guild_id = $2 AND status = 'approved' AND repaired_date <= NOW()::date) SELECT days_ago FROM cte GROUP BY days_ago ORDER BY days_ago ASC",
+    )
+    .bind(user_id.to_string())
+    .bind(guild_id.to_string())
+  }
+}
+
+impl Timeframe {
+  pub fn new(sum: Option<i64>, count: Option<i64>) -> Self {
+    Self { sum, count }
+  }
+
+  pub fn user_sum_and_count<'a>(
+    guild_id: GuildId,
+    user_id: UserId,
+    start_time: &'a DateTime<Utc>,
+    end_time: &'a DateTime<Utc>,
+  ) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT COUNT(record_id) AS count, (SUM(meditation_minutes) + (SUM(meditation_seconds) / 60)) AS sum FROM meditation WHERE guild_id = $1 AND user_id = $2 AND occurred_at >= $3 AND occurred_at <= $4",
+    )
+    .bind(guild_id.to_string())
+    .bind(user_id.to_string())
+    .bind(start_time)
+    .bind(end_time)
+  }
+
+  pub fn user_total_sum_and_count<'a>(
+    guild_id: GuildId,
+    user_id: UserId,
+  ) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT COUNT(record_id) AS count, (SUM(meditation_minutes) + (SUM(meditation_seconds) / 60)) AS sum FROM meditation WHERE guild_id = $1 AND user_id = $2",
+    )
+    .bind(guild_id.to_string())
+    .bind(user_id.to_string())
+  }
+
+  pub fn guild_sum_and_count<'a>(
+    guild_id: GuildId,
+    start_time: &'a DateTime<Utc>,
+    end_time: &'a DateTime<Utc>,
+  ) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT COUNT(record_id) AS count, (SUM(meditation_minutes) + (SUM(meditation_seconds) / 60)) AS sum FROM meditation WHERE guild_id = $1 AND occurred_at >= $2 AND occurred_at <= $3",
+    )
+    .bind(guild_id.to_string())
+    .bind(start_time)
+    .bind(end_time)
+  }
+
+  pub fn guild_total_sum_and_count<'a>(
+    guild_id: GuildId,
+  ) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT COUNT(record_id) AS count, (SUM(meditation_minutes) + (SUM(meditation_seconds) / 60)) AS sum FROM meditation WHERE guild_id = $1",
+    )
+    .bind(guild_id.to_string())
+  }
+}
+
+impl LeaderboardUser {
+  pub fn stats<'a>(
+    guild_id: GuildId,
+    timeframe: &StatsTimeframe,
+    sort_by: &SortBy,
+    leaderboard_type: &LeaderboardType,
+  ) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    let limit = match leaderboard_type {
+      LeaderboardType::Top5 => 5,
+      LeaderboardType::Top10 => 10,
+    };
+    let query = match timeframe {
+      StatsTimeframe::Daily => match sort_by {
+        SortBy::Minutes => {
+          "SELECT name, minutes, sessions, streak, anonymous_tracking, streaks_active, streaks_private FROM daily_leaderboard WHERE guild = $1 ORDER BY minutes DESC LIMIT $2"
+        }
+        SortBy::Sessions => {
+          "SELECT name, minutes, sessions, streak, anonymous_tracking, streaks_active, streaks_private FROM daily_leaderboard WHERE guild = $1 ORDER BY sessions DESC LIMIT $2"
+        }
+        SortBy::Streak => {
+          "SELECT name, minutes, sessions, streak, anonymous_tracking, streaks_active, streaks_private FROM daily_leaderboard WHERE guild = $1 ORDER BY streak DESC NULLS LAST LIMIT $2"
+        }
+      },
+      StatsTimeframe::Weekly => match sort_by {
+        SortBy::Minutes => {
+          "SELECT name, minutes, sessions, streak, anonymous_tracking, streaks_active, streaks_private FROM weekly_leaderboard WHERE guild = $1 ORDER BY minutes DESC LIMIT $2"
+        }
+        SortBy::Sessions => {
+          "SELECT name, minutes, sessions, streak, anonymous_tracking, streaks_active, streaks_private FROM weekly_leaderboard WHERE guild = $1 ORDER BY sessions DESC LIMIT $2"
+        }
+        SortBy::Streak => {
+          "SELECT name, minutes, sessions, streak, anonymous_tracking, streaks_active, streaks_private FROM weekly_leaderboard WHERE guild = $1 ORDER BY streak DESC NULLS LAST LIMIT $2"
+        }
+      },
+      StatsTimeframe::Monthly => match sort_by {
+        SortBy::Minutes => {
+          "SELECT name, minutes, sessions, streak, anonymous_tracking, streaks_active, streaks_private FROM monthly_leaderboard WHERE guild = $1 ORDER BY minutes DESC LIMIT $2"
+        }
+        SortBy::Sessions => {
+          "SELECT name, minutes, sessions, streak, anonymous_tracking, streaks_active, streaks_private FROM monthly_leaderboard WHERE guild = $1 ORDER BY sessions DESC LIMIT $2"
+        }
+        SortBy::Streak => {
+          "SELECT name, minutes, sessions, streak, anonymous_tracking, streaks_active, streaks_private FROM monthly_leaderboard WHERE guild = $1 ORDER BY streak DESC NULLS LAST LIMIT $2"
+        }
+      },
+      StatsTimeframe::Yearly => match sort_by {
+        SortBy::Minutes => {
+          "SELECT name, minutes, sessions, streak, anonymous_tracking, streaks_active, streaks_private FROM yearly_leaderboard WHERE guild = $1 ORDER BY minutes DESC LIMIT $2"
+        }
+        SortBy::Sessions => {
+          "SELECT name, minutes, sessions, streak, anonymous_tracking, streaks_active, streaks_private FROM yearly_leaderboard WHERE guild = $1 ORDER BY sessions DESC LIMIT $2"
+        }
+        SortBy::Streak => {
+          "SELECT name, minutes, sessions, streak, anonymous_tracking, streaks_active, streaks_private FROM yearly_leaderboard WHERE guild = $1 ORDER BY streak DESC NULLS LAST LIMIT $2"
+        }
+      },
+    };
+
+    sqlx::query_as(query).bind(guild_id.to_string()).bind(limit)
+  }
+
+  /// Calculates a guild's leaderboard from meditation tracked in a channel, for guilds with
+  /// channel segments turned on. Unlike [`Self::stats`], this isn't cached in a view.
+  pub fn channel_stats<'a>(
+    guild_id: GuildId,
+    channel_id: ChannelId,
+    timeframe: &StatsTimeframe,
+    sort_by: &SortBy,
+    leaderboard_type: &LeaderboardType,
+  ) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    let limit = match leaderboard_type {
+      LeaderboardType::Top5 => 5,
+      LeaderboardType::Top10 => 10,
+    };
+    let query = match sort_by {
+      SortBy::Minutes => {
+        "SELECT m.user_id AS name, (SUM(m.meditation_minutes) + (SUM(m.meditation_seconds) / 60)) AS minutes, COUNT(m.record_id) AS sessions, s.current_streak AS streak, t.anonymous_tracking, t.streaks_active, t.streaks_private FROM meditation m LEFT JOIN streak s ON m.user_id = s.user_id AND m.guild_id = s.guild_id LEFT JOIN tracking_profile t ON m.user_id = t.user_id AND m.guild_id = t.guild_id WHERE m.guild_id = $1 AND m.channel_id = $2 AND m.occurred_at >= date_trunc($3, now()) GROUP BY name, streak, t.anonymous_tracking, t.streaks_active, t.streaks_private ORDER BY minutes DESC LIMIT $4"
+      }
+      SortBy::Sessions => {
+        "SELECT m.user_id AS name, (SUM(m.meditation_minutes) + (SUM(m.meditation_seconds) / 60)) AS minutes, COUNT(m.record_id) AS sessions, s.current_streak AS streak, t.anonymous_tracking, t.streaks_active, t.streaks_private FROM meditation m LEFT JOIN streak s ON m.user_id = s.user_id AND m.guild_id = s.guild_id LEFT JOIN tracking_profile t ON m.user_id = t.user_id AND m.guild_id = t.guild_id WHERE m.guild_id = $1 AND m.channel_id = $2 AND m.occurred_at >= date_trunc($3, now()) GROUP BY name, streak, t.anonymous_tracking, t.streaks_active, t.streaks_private ORDER BY sessions DESC LIMIT $4"
+      }
+      SortBy::Streak => {
+        "SELECT m.user_id AS name, (SUM(m.meditation_minutes) + (SUM(m.meditation_seconds) / 60)) AS minutes, COUNT(m.record_id) AS sessions, s.current_streak AS streak, t.anonymous_tracking, t.streaks_active, t.streaks_private FROM meditation m LEFT JOIN streak s ON m.user_id = s.user_id AND m.guild_id = s.guild_id LEFT JOIN tracking_profile t ON m.user_id = t.user_id AND m.guild_id = t.guild_id WHERE m.guild_id = $1 AND m.channel_id = $2 AND m.occurred_at >= date_trunc($3, now()) GROUP BY name, streak, t.anonymous_tracking, t.streaks_active, t.streaks_private ORDER BY streak DESC NULLS LAST LIMIT $4"
+      }
+    };
+
+    sqlx::query_as(query)
+      .bind(guild_id.to_string())
+      .bind(channel_id.to_string())
+      .bind(timeframe.unit())
+      .bind(limit)
+  }
+
+  /// Retrieves every member on a guild's leaderboard, as of when it was last refreshed.
+  pub fn all<'a>(
+    guild_id: GuildId,
+    timeframe: &StatsTimeframe,
+  ) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    let query = match timeframe {
+      StatsTimeframe::Daily => {
+        "SELECT name, minutes, sessions, streak FROM daily_leaderboard WHERE guild = $1"
+      }
+      StatsTimeframe::Weekly => {
+        "SELECT name, minutes, sessions, streak FROM weekly_leaderboard WHERE guild = $1"
+      }
+      StatsTimeframe::Monthly => {
+        "SELECT name, minutes, sessions, streak FROM monthly_leaderboard WHERE guild = $1"
+      }
+      StatsTimeframe::Yearly => {
+        "SELECT name, minutes, sessions, streak FROM yearly_leaderboard WHERE guild = $1"
+      }
+    };
+
+    sqlx::query_as(query).bind(guild_id.to_string())
+  }
+
+  /// Calculates every member's leaderboard stats from their meditation entries, as the
+  /// leaderboard would show them if it were refreshed now.
+  pub fn calculate<'a>(
+    guild_id: GuildId,
+    timeframe: &StatsTimeframe,
+  ) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    let unit = match timeframe {
+      StatsTimeframe::Daily => "day",
+      StatsTimeframe::Weekly => "week",
+      StatsTimeframe::Monthly => "month",
+      StatsTimeframe::Yearly => "year",
+    };
+
+    sqlx::query_as(
+      "SELECT m.user_id AS name, (SUM(m.meditation_minutes) + (SUM(m.meditation_seconds) / 60)) AS minutes, COUNT(m.record_id) AS sessions, s.current_streak AS streak FROM meditation m LEFT JOIN streak s ON m.user_id = s.user_id AND m.guild_id = s.guild_id WHERE m.guild_id = $1 AND m.occurred_at >= date_trunc($2, now()) GROUP BY name, streak",
+    )
+    .bind(guild_id.to_string())
+    .bind(unit)
+  }
+}
+
+impl Improvement {
+  /// The increase in minutes from the previous timeframe.
+  pub fn increase(&self) -> i64 {
+    self.current_minutes - self.previous_minutes
+  }
+
+  /// Ranks members by the increase in minutes between `[previous_start, current_start)` and
+  /// `[current_start, end)`. Members whose stats are private are excluded, as are members
+  /// who didn't improve.
+  pub fn ranking<'a>(
+    guild_id: GuildId,
+    previous_start: &'a DateTime<Utc>,
+    current_start: &'a DateTime<Utc>,
+    end: &'a DateTime<Utc>,
+    limit: i64,
+  ) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "WITH totals AS (SELECT user_id, COALESCE(SUM(meditation_minutes) FILTER (WHERE occurred_at >= $3) + (SUM(meditation_seconds) FILTER (WHERE occurred_at >= $3) / 60), 0) AS current_minutes, COALESCE(SUM(meditation_minutes) FILTER (WHERE occurred_at < $3) + (SUM(meditation_seconds) FILTER (WHERE occurred_at < $3) / 60), 0) AS previous_minutes FROM meditation WHERE guild_id = $1 AND occurred_at >= $2 AND occurred_at < $4 GROUP BY user_id) SELECT totals.user_id, totals.current_minutes, totals.previous_minutes, COALESCE(tracking_profile.anonymous_tracking, FALSE) AS anonymous_tracking FROM totals LEFT JOIN tracking_profile ON tracking_profile.user_id = totals.user_id AND tracking_profile.guild_id = $1 WHERE COALESCE(tracking_profile.stats_private, FALSE) = FALSE AND totals.current_minutes > totals.previous_minutes ORDER BY (totals.current_minutes - totals.previous_minutes) DESC, totals.user_id LIMIT $5",
+    )
+    .bind(guild_id.to_string())
+    .bind(previous_start)
+    .bind(current_start)
+    .bind(end)
+    .bind(limit)
+  }
+}
+
+impl FromRow<'_, PgRow> for Improvement {
+  fn from_row(row: &'_ PgRow) -> SqlxResult<Self, SqlxError> {
+    let user_id = UserId::new(common::decode_id_row(row, "user_id")?);
+
+    Ok(Self {
+      user_id,
+      current_minutes: row.try_get("current_minutes")?,
+      previous_minutes: row.try_get("previous_minutes")?,
+      anonymous_tracking: row.try_get("anonymous_tracking")?,
+    })
+  }
+}
+
+impl ByInterval {
+  /// Calculates stats from meditation entries. For weekly, monthly, and yearly stats, only
+  /// the current period is included. Daily stats include the 12 days starting `periods_ago`
+  /// days ago.
+  pub fn user_fresh<'a>(
+    guild_id: GuildId,
+    user_id: UserId,
+    timeframe: &StatsTimeframe,
+    now_offset: &'a DateTime<Utc>,
+    periods_ago: i32,
+  ) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    let query = match timeframe {
+      StatsTimeframe::Yearly => {
+        "WITH current_year_data AS (SELECT floor(extract(epoch from ((date_trunc('year', now()) + interval '1 year') - interval '1 second') - occurred_at) / extract(epoch from (((date_trunc('year', occurred_at) + interval '1 year') - interval '1 second') - (date_trunc('year', occurred_at)))))::float AS times_ago, meditation_minutes, meditation_seconds FROM meditation WHERE guild_id = $1 AND user_id = $2) SELECT times_ago, (SUM(meditation_minutes) + (SUM(meditation_seconds) / 60)) AS meditation_minutes, COUNT(*) AS meditation_count FROM current_year_data WHERE times_ago = 0 GROUP BY times_ago"
+      }
+      StatsTimeframe::Monthly => {
+        "WITH current_month_data AS (SELECT floor(extract(epoch from ((date_trunc('month', now()) + interval '1 month') - interval '1 second') - occurred_at) / extract(epoch from (((date_trunc('month', occurred_at) + interval '1 month') - interval '1 second') - (date_trunc('month', occurred_at)))))::float AS times_ago, meditation_minutes, meditation_seconds FROM meditation WHERE guild_id = $1 AND user_id = $2) SELECT times_ago, (SUM(meditation_minutes) + (SUM(meditation_seconds) / 60)) AS meditation_minutes, COUNT(*) AS meditation_count FROM current_month_data WHERE times_ago = 0 GROUP BY times_ago"
+      }
+      StatsTimeframe::Weekly => {
+        "WITH current_week_data AS (SELECT floor(extract(epoch from ((date_trunc('week', now()) + interval '1 week') - interval '1 second') - occurred_at) / (60*60*24*7))::float AS times_ago, meditation_minutes, meditation_seconds FROM meditation WHERE guild_id = $1 AND user_id = $2) SELECT times_ago, (SUM(meditation_minutes) + (SUM(meditation_seconds) / 60)) AS meditation_minutes, COUNT(*) AS meditation_count FROM current_week_data WHERE times_ago = 0 GROUP BY times_ago"
+      }
+      StatsTimeframe::Daily => {
+        "WITH daily_data AS (SELECT date_part('day', $1 - DATE_TRUNC('day', occurred_at)) AS times_ago, meditation_minutes, meditation_seconds FROM meditation WHERE guild_id = $2 AND user_id = $3 AND occurred_at <= $1) SELECT times_ago, (SUM(meditation_minutes) + (SUM(meditation_seconds) / 60)) AS meditation_minutes, COUNT(*) AS meditation_count FROM daily_data WHERE times_ago >= $4 AND times_ago <= $4 + 12 GROUP BY times_ago"
+      }
+    };
+
+    match timeframe {
+      StatsTimeframe::Daily => sqlx::query_as(query)
+        .bind(now_offset)
+        .bind(guild_id.to_string())
+        .bind(user_id.to_string())
+        .bind(periods_ago),
+      _ => sqlx::query_as(query)
+        .bind(guild_id.to_string())
+        .bind(user_id.to_string()),
+    }
+  }
+
+  /// Retrieves stats for the 12 weeks, months, or years starting `periods_ago` periods ago,
+  /// excluding the current period, from the materialized views.
+  pub fn user_from_view<'a>(
+    guild_id: GuildId,
+    user_id: UserId,
+    timeframe: &StatsTimeframe,
+    periods_ago: i32,
+  ) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    let query = match timeframe {
+      StatsTimeframe::Yearly => {
+        "SELECT times_ago, (SUM(meditation_minutes) + (SUM(meditation_seconds) / 60)) AS meditation_minutes, COUNT(*) AS meditation_count FROM yearly_data WHERE guild_id = $1 AND user_id = $2 AND times_ago >= GREATEST($3, 1) AND times_ago <= $3 + 12 GROUP BY times_ago"
+      }
+      StatsTimeframe::Monthly => {
+        "SELECT times_ago, (SUM(meditation_minutes) + (SUM(meditation_seconds) / 60)) AS meditation_minutes, COUNT(*) AS meditation_count FROM monthly_data WHERE guild_id = $1 AND user_id = $2 AND times_ago >= GREATEST($3, 1) AND times_ago <= $3 + 12 GROUP BY times_ago"
+      }
+      StatsTimeframe::Weekly => {
+        "SELECT times_ago, (SUM(meditation_minutes) + (SUM(meditation_seconds) / 60)) AS meditation_minutes, COUNT(*) AS meditation_count FROM weekly_data WHERE guild_id = $1 AND user_id = $2 AND times_ago >= GREATEST($3, 1) AND times_ago <= $3 + 12 GROUP BY times_ago"
+      }
+      StatsTimeframe::Daily => unreachable!("No daily_data materialized view"),
+    };
+
+    sqlx::query_as(query)
+      .bind(guild_id.to_string())
+      .bind(user_id.to_string())
+      .bind(periods_ago)
+  }
+
+  /// Calculates stats from meditation entries. For weekly, monthly, and yearly stats, only
+  /// the current period is included. Daily stats include the 12 days starting `periods_ago`
+  /// days ago.
+  pub fn guild_fresh<'a>(
+    guild_id: GuildId,
+    timeframe: &StatsTimeframe,
+    periods_ago: i32,
+  ) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    let query = match timeframe {
+      StatsTimeframe::Yearly => {
+        "WITH current_year_data AS (SELECT floor(extract(epoch from ((date_trunc('year', now()) + interval '1 year') - interval '1 second') - occurred_at) / extract(epoch from (((date_trunc('year', occurred_at) + interval '1 year') - interval '1 second') - (date_trunc('year', occurred_at)))))::float AS times_ago, meditation_minutes, meditation_seconds FROM meditation WHERE guild_id = $1) SELECT times_ago, (SUM(meditation_minutes) + (SUM(meditation_seconds) / 60)) AS meditation_minutes, COUNT(*) AS meditation_count FROM current_year_data WHERE times_ago = 0 GROUP BY times_ago"
+      }
+      StatsTimeframe::Monthly => {
+        "WITH current_month_data AS (SELECT floor(extract(epoch from ((date_trunc('month', now()) + interval '1 month') - interval '1 second') - occurred_at) / extract(epoch from (((date_trunc('month', occurred_at) + interval '1 month') - interval '1 second') - (date_trunc('month', occurred_at)))))::float AS times_ago, meditation_minutes, meditation_seconds FROM meditation WHERE guild_id = $1) SELECT times_ago, (SUM(meditation_minutes) + (SUM(meditation_seconds) / 60)) AS meditation_minutes, COUNT(*) AS meditation_count FROM current_month_data WHERE times_ago = 0 GROUP BY times_ago"
+      }
+      StatsTimeframe::Weekly => {
+        "WITH current_week_data AS (SELECT floor(extract(epoch from ((date_trunc('week', now()) + interval '1 week') - interval '1 second') - occurred_at) / (60*60*24*7))::float AS times_ago, meditation_minutes, meditation_seconds FROM meditation WHERE guild_id = $1) SELECT times_ago, (SUM(meditation_minutes) + (SUM(meditation_seconds) / 60)) AS meditation_minutes, COUNT(*) AS meditation_count FROM current_week_data WHERE times_ago = 0 GROUP BY times_ago"
+      }
+      StatsTimeframe::Daily => {
+        "WITH daily_data AS (SELECT date_part('day', NOW() - DATE_TRUNC('day', occurred_at)) AS times_ago, meditation_minutes, meditation_seconds FROM meditation WHERE guild_id = $1) SELECT times_ago, (SUM(meditation_minutes) + (SUM(meditation_seconds) / 60)) AS meditation_minutes, COUNT(*) AS meditation_count FROM daily_data WHERE times_ago >= $2 AND times_ago <= $2 + 12 GROUP BY times_ago"
+      }
+    };
+
+    match timeframe {
+      StatsTimeframe::Daily => sqlx::query_as(query)
+        .bind(guild_id.to_string())
+        .bind(periods_ago),
+      _ => sqlx::query_as(query).bind(guild_id.to_string()),
+    }
+  }
+
+  /// Retrieves stats for the 12 weeks, months, or years starting `periods_ago` periods ago,
+  /// excluding the current period, from the materialized views.
+  pub fn guild_from_view<'a>(
+    guild_id: GuildId,
+    timeframe: &StatsTimeframe,
+    periods_ago: i32,
+  ) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    let query = match timeframe {
+      StatsTimeframe::Yearly => {
+        "SELECT times_ago, (SUM(meditation_minutes) + (SUM(meditation_seconds) / 60)) AS meditation_minutes, COUNT(*) AS meditation_count FROM yearly_data WHERE guild_id = $1 AND times_ago >= GREATEST($2, 1) AND times_ago <= $2 + 12 GROUP BY times_ago"
+      }
+      StatsTimeframe::Monthly => {
+        "SELECT times_ago, (SUM(meditation_minutes) + (SUM(meditation_seconds) / 60)) AS meditation_minutes, COUNT(*) AS meditation_count FROM monthly_data WHERE guild_id = $1 AND times_ago >= GREATEST($2, 1) AND times_ago <= $2 + 12 GROUP BY times_ago"
+      }
+      StatsTimeframe::Weekly => {
+        "SELECT times_ago, (SUM(meditation_minutes) + (SUM(meditation_seconds) / 60)) AS meditation_minutes, COUNT(*) AS meditation_count FROM weekly_data WHERE guild_id = $1 AND times_ago >= GREATEST($2, 1) AND times_ago <= $2 + 12 GROUP BY times_ago"
+      }
+      StatsTimeframe::Daily => unreachable!("No daily_data materialized view"),
+    };
+
+    sqlx::query_as(query)
+      .bind(guild_id.to_string())
+      .bind(periods_ago)
+  }
+}
+
+impl ByRecurringPeriod {
+  /// Calculates a server's stats for each day of the week in UTC since `since`, with
+  /// Monday as 1 and Sunday as 7.
+  pub fn guild_by_weekday<'a>(
+    guild_id: GuildId,
+    since: &'a DateTime<Utc>,
+  ) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT extract(isodow FROM occurred_at AT TIME ZONE 'UTC')::int AS period, (SUM(meditation_minutes) + (SUM(meditation_seconds) / 60)) AS meditation_minutes, COUNT(*) AS meditation_count FROM meditation WHERE guild_id = $1 AND occurred_at >= $2 GROUP BY period",
+    )
+    .bind(guild_id.to_string())
+    .bind(since)
+  }
+
+  /// Calculates a server's stats for each hour of the day in UTC since `since`.
+  pub fn guild_by_hour<'a>(
+    guild_id: GuildId,
+    since: &'a DateTime<Utc>,
+  ) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT extract(hour FROM occurred_at AT TIME ZONE 'UTC')::int AS period, (SUM(meditation_minutes) + (SUM(meditation_seconds) / 60)) AS meditation_minutes, COUNT(*) AS meditation_count FROM meditation WHERE guild_id = $1 AND occurred_at >= $2 GROUP BY period",
+    )
+    .bind(guild_id.to_string())
+    .bind(since)
+  }
+}
+
+impl ByPeriod {
+  /// Calculates a member's stats for each period of `timeframe` with meditation between
+  /// `start` and `end` inclusive, with dates in the member's UTC offset. When `channel_id`
+  /// is set, only meditation tracked in that channel is included.
+  #[allow(clippy::too_many_arguments)]
+  pub fn user_range<'a>(
+    guild_id: GuildId,
+    user_id: UserId,
+    timeframe: &StatsTimeframe,
+    start: NaiveDate,
+    end: NaiveDate,
+    offset: i16,
+    channel_id: Option<ChannelId>,
+  ) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "WITH local_data AS (SELECT (occurred_at AT TIME ZONE 'UTC') + make_interval(mins => $3) AS local_time, meditation_minutes, meditation_seconds FROM meditation WHERE guild_id = $1 AND user_id = $2 AND ($7::text IS NULL OR channel_id = $7)) SELECT date_trunc($4, local_time)::date AS period, (SUM(meditation_minutes) + (SUM(meditation_seconds) / 60)) AS meditation_minutes, COUNT(*) AS meditation_count FROM local_data WHERE local_time::date >= $5 AND local_time::date <= $6 GROUP BY period",
+    )
+    .bind(guild_id.to_string())
+    .bind(user_id.to_string())
+    .bind(i32::from(offset))
+    .bind(timeframe.unit())
+    .bind(start)
+    .bind(end)
+    .bind(channel_id.map(|channel_id| channel_id.to_string()))
+  }
+
+  /// Calculates a server's stats for each period of `timeframe` with meditation between
+  /// `start` and `end` inclusive, in UTC. When `channel_id` is set, only meditation tracked
+  /// in that channel is included.
+  pub fn guild_range<'a>(
+    guild_id: GuildId,
+    timeframe: &StatsTimeframe,
+    start: NaiveDate,
+    end: NaiveDate,
+    channel_id: Option<ChannelId>,
+  ) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT date_trunc($2, occurred_at AT TIME ZONE 'UTC')::date AS period, (SUM(meditation_minutes) + (SUM(meditation_seconds) / 60)) AS meditation_minutes, COUNT(*) AS meditation_count FROM meditation WHERE guild_id = $1 AND (occurred_at AT TIME ZONE 'UTC')::date >= $3 AND (occurred_at AT TIME ZONE 'UTC')::date <= $4 AND ($5::text IS NULL OR channel_id = $5) GROUP BY period",
+    )
+    .bind(guild_id.to_string())
+    .bind(timeframe.unit())
+    .bind(start)
+    .bind(end)
+    .bind(channel_id.map(|channel_id| channel_id.to_string()))
+  }
+}
+
+impl MemberProgress {
+  /// Retrieves the progress of every member who has meditated in a server. Members without
+  /// a tracking profile have streaks enabled, as that's the default.
+  pub fn guild<'a>(guild_id: GuildId) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT meditation.user_id, (SUM(meditation.meditation_minutes) + (SUM(meditation.meditation_seconds) / 60)) AS minutes, COALESCE(BOOL_AND(tracking_profile.streaks_active), TRUE) AS streaks_active, MAX(meditation.occurred_at) AS last_meditation FROM meditation LEFT JOIN tracking_profile ON tracking_profile.user_id = meditation.user_id AND tracking_profile.guild_id = meditation.guild_id WHERE meditation.guild_id = $1 GROUP BY meditation.user_id",
+    )
+    .bind(guild_id.to_string())
+  }
+}
+
+impl FromRow<'_, PgRow> for MemberProgress {
+  fn from_row(row: &'_ PgRow) -> SqlxResult<Self, SqlxError> {
+    let user_id = UserId::new(common::decode_id_row(row, "user_id")?);
+
+    Ok(Self {
+      user_id,
+      minutes: row.try_get("minutes")?,
+      streaks_active: row.try_get("streaks_active")?,
+      last_meditation: row.try_get("last_meditation")?,
+    })
+  }
+}
+
+impl User {
+  pub fn new(
+    total_minutes: i64,
+    total_sessions: u64,
+    timeframe_stats: Timeframe,
+    streak: Streak,
+  ) -> Self {
+    Self {
+      all_minutes: total_minutes,
+      all_count: total_sessions,
+      timeframe_stats,
+      streak,
+    }
+  }
+
+  pub fn challenge_stats(sum_and_count: Timeframe, streak: Streak) -> Self {
+    Self {
+      all_minutes: 0,
+      all_count: 0,
+      timeframe_stats: sum_and_count,
+      streak,
+    }
+  }
+}
+
+impl Guild {
+  pub fn new(total_minutes: i64, total_sessions: u64, timeframe_stats: Timeframe) -> Self {
+    Self {
+      all_minutes: total_minutes,
+      all_count: total_sessions,
+      timeframe_stats,
+    }
+  }
+}
+
+impl Global {
+  /// Retrieves the [`Global`] totals since `today`, with the number of members who meditated
+  /// since `week_start`.
+  pub fn totals<'a>(
+    today: DateTime<Utc>,
+    week_start: DateTime<Utc>,
+  ) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT (SELECT COUNT(*) FROM guild_settings WHERE global_stats) AS guilds, (COALESCE(SUM(m.meditation_minutes) FILTER (WHERE m.occurred_at >= $1), 0) + COALESCE(SUM(m.meditation_seconds) FILTER (WHERE m.occurred_at >= $1), 0) / 60)::BIGINT AS minutes_today, COUNT(m.record_id) FILTER (WHERE m.occurred_at >= $1) AS sessions_today, COUNT(DISTINCT m.user_id) FILTER (WHERE m.occurred_at >= $1) AS meditators_today, COUNT(DISTINCT m.user_id) AS meditators_week FROM meditation m INNER JOIN guild_settings g ON g.guild_id = m.guild_id AND g.global_stats LEFT JOIN tracking_profile t ON t.guild_id = m.guild_id AND t.user_id = m.user_id WHERE m.occurred_at >= $2 AND m.import_id IS NULL AND NOT COALESCE(t.stats_private, FALSE)",
+    )
+    .bind(today)
+    .bind(week_start)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::collections::BTreeSet;
+
+  use proptest::prelude::*;
+
+  use super::*;
+
+  /// Sets of days before today, as they come from [`MeditationCountByDay`].
+  fn meditation_days() -> impl Strategy<Value = BTreeSet<i32>> {
+    prop::collection::btree_set(0..400i32, 0..80)
+  }
+
+  fn streak(days: &BTreeSet<i32>) -> Streak {
+    let mut streak = Streak::default();
+    streak.count_days(&days.iter().copied().collect::<Vec<_>>());
+    streak
+  }
+
+  #[test]
+  fn test_count_days() {
+    let count = |days: &[i32], longest: i32| {
+      let mut streak = Streak::new(GuildId::new(1u64), UserId::new(1u64), 0, longest);
+      streak.count_days(days);
+      (streak.current, streak.longest)
+    };
+
+    assert_eq!(count(&[], 0), (0, 0));
+    assert_eq!(count(&[0], 0), (0, 0));
+    assert_eq!(count(&[0, 1, 2, 5, 6, 7, 8], 0), (3, 4));
+    // Missing a day doesn't break the current streak until the day after
+    assert_eq!(count(&[2, 3], 0), (2, 2));
+    assert_eq!(count(&[3, 4], 0), (0, 2));
+    // Single days between streaks aren't streaks of one
+    assert_eq!(count(&[5, 10, 20], 0), (0, 0));
+    // A stored longest streak is only compared with the current streak
+    assert_eq!(count(&[0, 1, 5, 6, 7], 9), (2, 9));
+    assert_eq!(count(&[0, 1, 2, 3], 3), (4, 4));
+  }
+
+  proptest! {
+    #[test]
+    fn adding_a_day_never_decreases_longest(days in meditation_days(), day in 0..400i32) {
+      let mut more_days = days.clone();
+      more_days.insert(day);
+      prop_assert!(streak(&more_days).longest >= streak(&days).longest);
+    }
+
+    #[test]
+    fn current_never_exceeds_longest(days in meditation_days()) {
+      let streak = streak(&days);
+      prop_assert!(streak.current <= streak.longest);
+      prop_assert!(streak.current != 1 && streak.longest != 1);
+    }
+
+    #[test]
+    fn gaps_break_the_current_streak(days in meditation_days()) {
+      if !days.first().is_some_and(|day| *day <= 2) {
+        prop_assert_eq!(streak(&days).current, 0);
+      }
+    }
+
+    #[test]
+    fn stored_longest_matches_recount(days in meditation_days()) {
+      // Adding today with the stored longest streak gives the same result as counting every
+      // day again
+      let mut stored = streak(&days);
+      let mut with_today = days.clone();
+      with_today.insert(0);
+      stored.count_days(&with_today.iter().copied().collect::<Vec<_>>());
+
+      let recounted = streak(&with_today);
+      prop_assert_eq!(stored.current, recounted.current);
+      prop_assert_eq!(stored.longest, recounted.longest);
+    }
+  }
+}
@@ -1,12 +1,13 @@
-use poise::serenity_prelude::{GuildId, Mentionable, UserId};
+use chrono::{DateTime, Utc};
+use poise::serenity_prelude::{ChannelId, GuildId, Mentionable, MessageId, UserId};
 use sqlx::postgres::{PgArguments, PgRow};
 use sqlx::query::{Query, QueryAs};
 use sqlx::{Error as SqlxError, FromRow, Postgres, Result as SqlxResult, Row};
 use ulid::Ulid;
 
-use crate::commands::helpers::pagination::{PageRow, PageType};
 use crate::data::common;
-use crate::handlers::database::{DeleteQuery, ExistsQuery, InsertQuery, UpdateQuery};
+use crate::database::{DeleteQuery, ExistsQuery, InsertQuery, UpdateQuery};
+use crate::pagination::{PageRow, PageType};
 
 #[derive(Default)]
 pub struct SteamKey {
@@ -14,6 +15,20 @@ pub struct SteamKey {
   pub key: String,
   pub used: bool,
   pub reserved: Option<UserId>,
+  /// When the key can no longer be redeemed, if it expires.
+  pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// A reserved [`SteamKey`] which has been offered to a user, along with the message
+/// containing the buttons to redeem or decline it.
+pub struct KeyOffer {
+  pub id: String,
+  pub guild_id: GuildId,
+  pub key: String,
+  pub user_id: UserId,
+  pub channel_id: ChannelId,
+  pub message_id: MessageId,
+  pub expires_at: DateTime<Utc>,
 }
 
 #[derive(Default)]
@@ -36,43 +51,85 @@ impl SteamKey {
     }
   }
 
-  /// Marks a [`SteamKey`] as reserved for a user.
+  /// Sets the time after which the [`SteamKey`] can no longer be redeemed.
+  #[must_use]
+  pub fn expiring(mut self, expires_at: DateTime<Utc>) -> Self {
+    self.expires_at = Some(expires_at);
+    self
+  }
+
+  /// Whether the [`SteamKey`] has passed its expiry time.
+  pub fn expired(&self) -> bool {
+    self
+      .expires_at
+      .is_some_and(|expires_at| expires_at <= Utc::now())
+  }
+
+  /// Marks a [`SteamKey`] as reserved for a user. Keys locked by another transaction are
+  /// skipped, so that two users can't be given the same key. Expired keys are never reserved.
   pub fn reserve<'a>(
     guild_id: GuildId,
     user_id: UserId,
   ) -> QueryAs<'a, Postgres, Self, PgArguments> {
     sqlx::query_as(
-      "UPDATE steamkey SET reserved = $1 WHERE steam_key = (SELECT steam_key FROM steamkey WHERE used = FALSE AND reserved IS NULL AND guild_id = $2 ORDER BY RANDOM() LIMIT 1) RETURNING steam_key",
+      "UPDATE steamkey SET reserved = $1, reserved_at = NOW() WHERE steam_key = (SELECT steam_key FROM steamkey WHERE used = FALSE AND reserved IS NULL AND (expires_at IS NULL OR expires_at > NOW()) AND guild_id = $2 ORDER BY RANDOM() LIMIT 1 FOR UPDATE SKIP LOCKED) AND guild_id = $2 RETURNING steam_key",
     )
     .bind(user_id.to_string())
     .bind(guild_id.to_string())
   }
 
   /// Marks a [`SteamKey`] as unreserved.
-  pub fn unreserve(key: &str) -> Query<'_, Postgres, PgArguments> {
+  pub fn unreserve(guild_id: GuildId, key: &str) -> Query<'_, Postgres, PgArguments> {
     query!(
-      "UPDATE steamkey SET reserved = NULL WHERE steam_key = $1",
+      "UPDATE steamkey SET reserved = NULL, reserved_at = NULL WHERE steam_key = $1 AND guild_id = $2",
       key,
+      guild_id.to_string(),
     )
   }
 
   /// Marks a [`SteamKey`] as used.
-  pub fn mark_used(key: &str) -> Query<'_, Postgres, PgArguments> {
-    query!("UPDATE steamkey SET used = TRUE WHERE steam_key = $1", key,)
+  pub fn mark_used(guild_id: GuildId, key: &str) -> Query<'_, Postgres, PgArguments> {
+    query!(
+      "UPDATE steamkey SET used = TRUE WHERE steam_key = $1 AND guild_id = $2",
+      key,
+      guild_id.to_string(),
+    )
   }
 
-  /// Retrieves a [`SteamKey`] and marks it as used.
+  /// Retrieves a [`SteamKey`] and marks it as used, skipping expired keys and keys locked by
+  /// another transaction.
   pub fn consume<'a>(guild_id: GuildId) -> QueryAs<'a, Postgres, Self, PgArguments> {
     sqlx::query_as(
-      "UPDATE steamkey SET used = TRUE WHERE steam_key = (SELECT steam_key FROM steamkey WHERE used = FALSE AND reserved IS NULL AND guild_id = $1 ORDER BY RANDOM() LIMIT 1) RETURNING steam_key",
+      "UPDATE steamkey SET used = TRUE WHERE steam_key = (SELECT steam_key FROM steamkey WHERE used = FALSE AND reserved IS NULL AND (expires_at IS NULL OR expires_at > NOW()) AND guild_id = $1 ORDER BY RANDOM() LIMIT 1 FOR UPDATE SKIP LOCKED) AND guild_id = $1 RETURNING steam_key",
     )
     .bind(guild_id.to_string())
   }
 
   /// Retrieves all [`SteamKey`]s from the database.
   pub fn retrieve_all<'a>(guild_id: GuildId) -> QueryAs<'a, Postgres, Self, PgArguments> {
-    sqlx::query_as("SELECT steam_key, reserved, used, guild_id FROM steamkey WHERE guild_id = $1")
-      .bind(guild_id.to_string())
+    sqlx::query_as(
+      "SELECT steam_key, reserved, used, guild_id, expires_at FROM steamkey WHERE guild_id = $1",
+    )
+    .bind(guild_id.to_string())
+  }
+
+  /// Flags unused [`SteamKey`]s which have passed their expiry time, returning them to the
+  /// pool if they were reserved and clearing any outstanding offer. Each key is only flagged
+  /// once. The keys are returned with who they were reserved for, if anyone.
+  pub fn flag_expired<'a>() -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "WITH expired AS (SELECT record_id, reserved FROM steamkey WHERE used = FALSE AND flagged = FALSE AND expires_at <= NOW() FOR UPDATE SKIP LOCKED) UPDATE steamkey SET flagged = TRUE, reserved = NULL, reserved_at = NULL, offer_id = NULL, offer_channel_id = NULL, offer_message_id = NULL, offer_expires_at = NULL FROM expired WHERE steamkey.record_id = expired.record_id RETURNING steamkey.steam_key, expired.reserved, steamkey.used, steamkey.guild_id, steamkey.expires_at",
+    )
+  }
+
+  /// Returns [`SteamKey`]s to the pool which were reserved at or before `cutoff` but never
+  /// offered or used, such as when a giveaway was interrupted. The keys are returned with who
+  /// they were reserved for.
+  pub fn release_stale<'a>(cutoff: DateTime<Utc>) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "WITH stale AS (SELECT record_id, reserved FROM steamkey WHERE used = FALSE AND reserved IS NOT NULL AND offer_id IS NULL AND reserved_at <= $1 FOR UPDATE SKIP LOCKED) UPDATE steamkey SET reserved = NULL, reserved_at = NULL FROM stale WHERE steamkey.record_id = stale.record_id RETURNING steamkey.steam_key, stale.reserved, steamkey.used, steamkey.guild_id, steamkey.expires_at",
+    )
+    .bind(cutoff)
   }
 }
 
@@ -80,11 +137,12 @@ impl InsertQuery for SteamKey {
   /// Adds a [`SteamKey`] to the database.
   fn insert_query(&self) -> Query<Postgres, PgArguments> {
     query!(
-      "INSERT INTO steamkey (record_id, steam_key, guild_id, used) VALUES ($1, $2, $3, $4)",
+      "INSERT INTO steamkey (record_id, steam_key, guild_id, used, expires_at) VALUES ($1, $2, $3, $4, $5)",
       Ulid::new().to_string(),
       self.key,
       self.guild_id.to_string(),
       self.used,
+      self.expires_at,
     )
   }
 }
@@ -107,7 +165,7 @@ impl ExistsQuery for SteamKey {
   type Item<'a> = Option<&'a str>;
 
   /// If `key` is [`Some<&str>`], checks the database to see if the specified [`SteamKey`]
-  /// exists. If `key` is [`None`], checks to see if an unused [`SteamKey`] exists.
+  /// exists. If `key` is [`None`], checks to see if an unused, unexpired [`SteamKey`] exists.
   fn exists_query<'a, T: for<'r> FromRow<'r, PgRow>>(
     guild_id: GuildId,
     key: Self::Item<'a>,
@@ -119,7 +177,7 @@ impl ExistsQuery for SteamKey {
       .bind(key)
       .bind(guild_id.to_string()),
       None => sqlx::query_as(
-        "SELECT EXISTS(SELECT 1 FROM steamkey WHERE used = FALSE AND reserved IS NULL AND guild_id = $1)",
+        "SELECT EXISTS(SELECT 1 FROM steamkey WHERE used = FALSE AND reserved IS NULL AND (expires_at IS NULL OR expires_at > NOW()) AND guild_id = $1)",
       )
       .bind(guild_id.to_string()),
     }
@@ -133,12 +191,17 @@ impl PageRow for SteamKey {
 
   fn body(&self) -> String {
     format!(
-      "Used: {}\nReserved for: {}",
+      "Used: {}\nReserved for: {}\nExpires: {}",
       if self.used { "Yes" } else { "No" },
       match self.reserved {
         Some(reserved) => reserved.mention().to_string(),
         None => "Nobody".to_owned(),
       },
+      match self.expires_at {
+        Some(expires_at) if self.expired() => format!("<t:{}:D> (Expired)", expires_at.timestamp()),
+        Some(expires_at) => format!("<t:{}:D>", expires_at.timestamp()),
+        None => "Never".to_owned(),
+      },
     )
   }
 }
@@ -153,6 +216,111 @@ impl FromRow<'_, PgRow> for SteamKey {
       key: row.try_get("steam_key").unwrap_or_default(),
       used: row.try_get("used").unwrap_or_default(),
       reserved,
+      expires_at: row.try_get("expires_at").unwrap_or_default(),
+    })
+  }
+}
+
+impl KeyOffer {
+  pub fn new(
+    id: impl Into<String>,
+    guild_id: GuildId,
+    key: impl Into<String>,
+    user_id: UserId,
+    channel_id: ChannelId,
+    message_id: MessageId,
+    expires_at: DateTime<Utc>,
+  ) -> Self {
+    Self {
+      id: id.into(),
+      guild_id,
+      key: key.into(),
+      user_id,
+      channel_id,
+      message_id,
+      expires_at,
+    }
+  }
+
+  /// Retrieves an outstanding [`KeyOffer`] by its ID.
+  pub fn retrieve(offer_id: &str) -> QueryAs<'_, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT offer_id, guild_id, steam_key, reserved, offer_channel_id, offer_message_id, offer_expires_at FROM steamkey WHERE offer_id = $1 AND used = FALSE AND reserved IS NOT NULL",
+    )
+    .bind(offer_id)
+  }
+
+  /// Retrieves all outstanding [`KeyOffer`]s which have passed their expiry time.
+  pub fn retrieve_expired<'a>() -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT offer_id, guild_id, steam_key, reserved, offer_channel_id, offer_message_id, offer_expires_at FROM steamkey WHERE offer_expires_at <= NOW() AND used = FALSE AND reserved IS NOT NULL",
+    )
+  }
+
+  /// Marks the [`SteamKey`] of a [`KeyOffer`] as used and clears the offer. The offer ID is
+  /// kept as the key's redemption token, so the key can be found again with
+  /// [`Self::retrieve_redeemed`] if the offer is redeemed more than once.
+  pub fn redeem(guild_id: GuildId, offer_id: &str) -> Query<'_, Postgres, PgArguments> {
+    query!(
+      "UPDATE steamkey SET used = TRUE, redemption_token = $1, offer_id = NULL, offer_channel_id = NULL, offer_message_id = NULL, offer_expires_at = NULL WHERE offer_id = $1 AND guild_id = $2 AND used = FALSE",
+      offer_id,
+      guild_id.to_string(),
+    )
+  }
+
+  /// Retrieves the [`SteamKey`] redeemed through an offer by the user it was offered to.
+  pub fn retrieve_redeemed(
+    offer_id: &str,
+    user_id: UserId,
+  ) -> QueryAs<'_, Postgres, SteamKey, PgArguments> {
+    sqlx::query_as(
+      "SELECT steam_key, reserved, used, guild_id, expires_at FROM steamkey WHERE redemption_token = $1 AND reserved = $2 AND used = TRUE",
+    )
+    .bind(offer_id)
+    .bind(user_id.to_string())
+  }
+
+  /// Returns the [`SteamKey`] of a [`KeyOffer`] to the pool and clears the offer.
+  pub fn withdraw(guild_id: GuildId, offer_id: &str) -> Query<'_, Postgres, PgArguments> {
+    query!(
+      "UPDATE steamkey SET reserved = NULL, reserved_at = NULL, offer_id = NULL, offer_channel_id = NULL, offer_message_id = NULL, offer_expires_at = NULL WHERE offer_id = $1 AND guild_id = $2 AND used = FALSE",
+      offer_id,
+      guild_id.to_string(),
+    )
+  }
+}
+
+impl UpdateQuery for KeyOffer {
+  /// Records a [`KeyOffer`] against the [`SteamKey`] reserved for the user.
+  fn update_query(&self) -> Query<Postgres, PgArguments> {
+    query!(
+      "UPDATE steamkey SET offer_id = $1, offer_channel_id = $2, offer_message_id = $3, offer_expires_at = $4 WHERE steam_key = $5 AND guild_id = $6 AND reserved = $7",
+      self.id,
+      self.channel_id.to_string(),
+      self.message_id.to_string(),
+      self.expires_at,
+      self.key,
+      self.guild_id.to_string(),
+      self.user_id.to_string(),
+    )
+  }
+}
+
+impl FromRow<'_, PgRow> for KeyOffer {
+  fn from_row(row: &'_ PgRow) -> SqlxResult<Self, SqlxError> {
+    let guild_id = GuildId::new(common::decode_id_row(row, "guild_id")?);
+    let user_id = UserId::new(common::decode_id_row(row, "reserved")?);
+    let channel_id = ChannelId::new(common::decode_id_row(row, "offer_channel_id")?);
+    let message_id = MessageId::new(common::decode_id_row(row, "offer_message_id")?);
+
+    Ok(Self {
+      id: row.try_get("offer_id")?,
+      guild_id,
+      key: row.try_get("steam_key")?,
+      user_id,
+      channel_id,
+      message_id,
+      expires_at: row.try_get("offer_expires_at")?,
     })
   }
 }
@@ -0,0 +1,114 @@
+use chrono::NaiveDate;
+use poise::serenity_prelude::{GuildId, UserId};
+use sqlx::postgres::{PgArguments, PgRow};
+use sqlx::query::{Query, QueryAs};
+use sqlx::{Error as SqlxError, FromRow, Postgres, Result as SqlxResult, Row};
+use ulid::Ulid;
+
+use crate::data::common;
+use crate::database::InsertQuery;
+
+/// What staff decided about a streak repair request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RepairStatus {
+  Open,
+  /// The day counts toward the member's streak.
+  Approved,
+  Denied,
+}
+
+impl RepairStatus {
+  pub fn as_str(self) -> &'static str {
+    match self {
+      Self::Open => "open",
+      Self::Approved => "approved",
+      Self::Denied => "denied",
+    }
+  }
+
+  fn from_name(status: &str) -> Self {
+    match status {
+      "approved" => Self::Approved,
+      "denied" => Self::Denied,
+      _ => Self::Open,
+    }
+  }
+}
+
+/// A member's request to have a missed day count toward their streak, filed with
+/// `/streak repair`. Once approved, the day is treated as a day they meditated when their
+/// streak is calculated, without adding any meditation time.
+pub struct StreakRepair {
+  pub id: String,
+  pub guild_id: GuildId,
+  pub user_id: UserId,
+  pub date: NaiveDate,
+  pub reason: String,
+  pub status: RepairStatus,
+  pub handled_by: Option<UserId>,
+}
+
+impl StreakRepair {
+  pub fn new(guild_id: GuildId, user_id: UserId, date: NaiveDate, reason: String) -> Self {
+    Self {
+      id: Ulid::new().to_string(),
+      guild_id,
+      user_id,
+      date,
+      reason,
+      status: RepairStatus::Open,
+      handled_by: None,
+    }
+  }
+
+  pub fn retrieve(repair_id: &str) -> QueryAs<'_, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT record_id, guild_id, user_id, repaired_date, reason, status, handled_by FROM streak_repair WHERE record_id = $1",
+    )
+    .bind(repair_id)
+  }
+
+  /// Records what staff decided about a [`StreakRepair`]. Only affects open requests, so
+  /// each request is only handled once.
+  pub fn set_status(
+    repair_id: &str,
+    status: RepairStatus,
+    handled_by: UserId,
+  ) -> Query<'_, Postgres, PgArguments> {
+    query!(
+      "UPDATE streak_repair SET status = $1, handled_by = $2, handled_at = NOW() WHERE record_id = $3 AND status = 'open'",
+      status.as_str(),
+      handled_by.to_string(),
+      repair_id,
+    )
+  }
+}
+
+impl InsertQuery for StreakRepair {
+  /// Adds a [`StreakRepair`] to the database, unless the day has already been requested and
+  /// the request wasn't denied.
+  fn insert_query(&self) -> Query<Postgres, PgArguments> {
+    query!(
+      "INSERT INTO streak_repair (record_id, guild_id, user_id, repaired_date, reason) VALUES ($1, $2, $3, $4, $5) ON CONFLICT (guild_id, user_id, repaired_date) WHERE status <> 'denied' DO NOTHING",
+      self.id,
+      self.guild_id.to_string(),
+      self.user_id.to_string(),
+      self.date,
+      self.reason,
+    )
+  }
+}
+
+impl FromRow<'_, PgRow> for StreakRepair {
+  fn from_row(row: &'_ PgRow) -> SqlxResult<Self, SqlxError> {
+    Ok(Self {
+      id: row.try_get("record_id")?,
+      guild_id: GuildId::new(common::decode_id_row(row, "guild_id")?),
+      user_id: UserId::new(common::decode_id_row(row, "user_id")?),
+      date: row.try_get("repaired_date")?,
+      reason: row.try_get("reason")?,
+      status: RepairStatus::from_name(row.try_get("status")?),
+      handled_by: common::decode_option_id_row(row, "handled_by")?.map(UserId::new),
+    })
+  }
+}
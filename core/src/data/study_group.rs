@@ -0,0 +1,281 @@
+use chrono::{DateTime, Datelike, Days, NaiveDate, Utc};
+use poise::serenity_prelude::{ChannelId, GuildId, UserId};
+use sqlx::postgres::{PgArguments, PgRow};
+use sqlx::query::{Query, QueryAs};
+use sqlx::{Error as SqlxError, FromRow, Postgres, Result as SqlxResult, Row};
+use ulid::Ulid;
+
+use crate::data::common;
+use crate::database::{DeleteQuery, InsertQuery};
+use crate::pagination::{PageRow, PageType};
+
+/// A group of members reading a text together, such as a sutta or a book, and discussing it
+/// in a channel. Discussion prompts are posted on a schedule by a
+/// [`RecurringPost`](crate::data::recurring_post::RecurringPost), if one has been set up.
+pub struct StudyGroup {
+  pub id: String,
+  pub guild_id: GuildId,
+  pub name: String,
+  /// The text the group is reading.
+  pub reading: String,
+  pub channel_id: ChannelId,
+  pub recurring_post_id: Option<String>,
+  pub created_by: UserId,
+  /// When the next discussion prompt is due, if prompts have been set up.
+  pub next_prompt_at: Option<DateTime<Utc>>,
+  pub member_count: i64,
+}
+
+impl StudyGroup {
+  pub fn new(
+    guild_id: GuildId,
+    name: String,
+    reading: String,
+    channel_id: ChannelId,
+    created_by: UserId,
+  ) -> Self {
+    Self {
+      id: Ulid::new().to_string(),
+      guild_id,
+      name,
+      reading,
+      channel_id,
+      recurring_post_id: None,
+      created_by,
+      next_prompt_at: None,
+      member_count: 0,
+    }
+  }
+
+  /// Retrieves a [`StudyGroup`] by name, ignoring case.
+  pub fn retrieve<'a>(guild_id: GuildId, name: &str) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT study_group.record_id, study_group.guild_id, study_group.name, study_group.reading, study_group.channel_id, study_group.recurring_post_id, study_group.created_by, recurring_post.next_post_at AS next_prompt_at, (SELECT COUNT(*) FROM study_group_member WHERE study_group_member.group_id = study_group.record_id) AS member_count FROM study_group LEFT JOIN recurring_post ON recurring_post.record_id = study_group.recurring_post_id WHERE study_group.guild_id = $1 AND LOWER(study_group.name) = LOWER($2)",
+    )
+    .bind(guild_id.to_string())
+    .bind(name.to_string())
+  }
+
+  /// Retrieves every [`StudyGroup`] in a guild, ordered by name.
+  pub fn retrieve_all<'a>(guild_id: GuildId) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT study_group.record_id, study_group.guild_id, study_group.name, study_group.reading, study_group.channel_id, study_group.recurring_post_id, study_group.created_by, recurring_post.next_post_at AS next_prompt_at, (SELECT COUNT(*) FROM study_group_member WHERE study_group_member.group_id = study_group.record_id) AS member_count FROM study_group LEFT JOIN recurring_post ON recurring_post.record_id = study_group.recurring_post_id WHERE study_group.guild_id = $1 ORDER BY LOWER(study_group.name) ASC",
+    )
+    .bind(guild_id.to_string())
+  }
+
+  /// Links a [`StudyGroup`] to the recurring post which posts its discussion prompts.
+  pub fn set_prompts<'a>(group_id: &'a str, post_id: &'a str) -> Query<'a, Postgres, PgArguments> {
+    query!(
+      "UPDATE study_group SET recurring_post_id = $1 WHERE record_id = $2",
+      post_id,
+      group_id,
+    )
+  }
+
+  /// Removes the recurring post which posts a [`StudyGroup`]'s discussion prompts, if any.
+  pub fn remove_prompts(guild_id: GuildId, name: &str) -> Query<'_, Postgres, PgArguments> {
+    query!(
+      "DELETE FROM recurring_post WHERE record_id = (SELECT recurring_post_id FROM study_group WHERE guild_id = $1 AND LOWER(name) = LOWER($2))",
+      guild_id.to_string(),
+      name,
+    )
+  }
+}
+
+impl InsertQuery for StudyGroup {
+  /// Adds a [`StudyGroup`] to the database.
+  fn insert_query(&self) -> Query<Postgres, PgArguments> {
+    query!(
+      "INSERT INTO study_group (record_id, guild_id, name, reading, channel_id, created_by) VALUES ($1, $2, $3, $4, $5, $6)",
+      self.id,
+      self.guild_id.to_string(),
+      self.name,
+      self.reading,
+      self.channel_id.to_string(),
+      self.created_by.to_string(),
+    )
+  }
+}
+
+impl DeleteQuery for StudyGroup {
+  /// Removes a [`StudyGroup`] by name, along with its members.
+  fn delete_query<'a>(
+    guild_id: GuildId,
+    name: impl Into<String>,
+  ) -> Query<'a, Postgres, PgArguments> {
+    query!(
+      "DELETE FROM study_group WHERE guild_id = $1 AND LOWER(name) = LOWER($2)",
+      guild_id.to_string(),
+      name.into(),
+    )
+  }
+}
+
+impl PageRow for StudyGroup {
+  fn title(&self, _page_type: PageType) -> String {
+    self.name.clone()
+  }
+
+  fn body(&self) -> String {
+    let prompts = self.next_prompt_at.map_or_else(
+      || "No discussion prompts".to_owned(),
+      |next_prompt_at| format!("Next prompt <t:{}:R>", next_prompt_at.timestamp()),
+    );
+
+    format!(
+      "> Reading *{}* in <#{}>\n> -# {} {} · {prompts}\n** **",
+      self.reading,
+      self.channel_id,
+      self.member_count,
+      if self.member_count == 1 {
+        "member"
+      } else {
+        "members"
+      },
+    )
+  }
+}
+
+impl FromRow<'_, PgRow> for StudyGroup {
+  fn from_row(row: &'_ PgRow) -> SqlxResult<Self, SqlxError> {
+    Ok(Self {
+      id: row.try_get("record_id")?,
+      guild_id: GuildId::new(common::decode_id_row(row, "guild_id")?),
+      name: row.try_get("name")?,
+      reading: row.try_get("reading")?,
+      channel_id: ChannelId::new(common::decode_id_row(row, "channel_id")?),
+      recurring_post_id: row.try_get("recurring_post_id")?,
+      created_by: UserId::new(common::decode_id_row(row, "created_by")?),
+      next_prompt_at: row.try_get("next_prompt_at")?,
+      member_count: row.try_get("member_count")?,
+    })
+  }
+}
+
+/// A member of a [`StudyGroup`], with how far they've read. Progress is recorded for the
+/// week it was last updated, so stale updates can be told apart from this week's.
+pub struct StudyGroupMember {
+  pub id: String,
+  pub group_id: String,
+  pub user_id: UserId,
+  pub progress: Option<String>,
+  /// The Monday of the week `progress` was last updated.
+  pub progress_week: Option<NaiveDate>,
+  pub joined_at: Option<DateTime<Utc>>,
+}
+
+impl StudyGroupMember {
+  pub fn new(group_id: &str, user_id: UserId) -> Self {
+    Self {
+      id: Ulid::new().to_string(),
+      group_id: group_id.to_owned(),
+      user_id,
+      progress: None,
+      progress_week: None,
+      joined_at: None,
+    }
+  }
+
+  /// Returns the Monday of the week `date` falls in.
+  pub fn week_of(date: NaiveDate) -> NaiveDate {
+    date - Days::new(u64::from(date.weekday().num_days_from_monday()))
+  }
+
+  /// Retrieves the members of a [`StudyGroup`], most recently updated first.
+  pub fn retrieve_all<'a>(group_id: &str) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT record_id, group_id, user_id, progress, progress_week, joined_at FROM study_group_member WHERE group_id = $1 ORDER BY progress_week DESC NULLS LAST, joined_at ASC",
+    )
+    .bind(group_id.to_string())
+  }
+
+  /// Records a member's progress for the week starting on `week`.
+  pub fn update_progress<'a>(
+    group_id: &'a str,
+    user_id: UserId,
+    progress: &'a str,
+    week: NaiveDate,
+  ) -> Query<'a, Postgres, PgArguments> {
+    query!(
+      "UPDATE study_group_member SET progress = $1, progress_week = $2 WHERE group_id = $3 AND user_id = $4",
+      progress,
+      week,
+      group_id,
+      user_id.to_string(),
+    )
+  }
+
+  pub fn remove(group_id: &str, user_id: UserId) -> Query<'_, Postgres, PgArguments> {
+    query!(
+      "DELETE FROM study_group_member WHERE group_id = $1 AND user_id = $2",
+      group_id,
+      user_id.to_string(),
+    )
+  }
+}
+
+impl InsertQuery for StudyGroupMember {
+  /// Adds a [`StudyGroupMember`] to the database, unless they're already in the group.
+  fn insert_query(&self) -> Query<Postgres, PgArguments> {
+    query!(
+      "INSERT INTO study_group_member (record_id, group_id, user_id) VALUES ($1, $2, $3) ON CONFLICT (group_id, user_id) DO NOTHING",
+      self.id,
+      self.group_id,
+      self.user_id.to_string(),
+    )
+  }
+}
+
+impl PageRow for StudyGroupMember {
+  fn title(&self, _page_type: PageType) -> String {
+    self.progress_week.map_or_else(
+      || "No updates yet".to_owned(),
+      |week| format!("Week of {}", week.format("%B %-d, %Y")),
+    )
+  }
+
+  fn body(&self) -> String {
+    match &self.progress {
+      Some(progress) => format!("> <@{}>: {progress}\n** **", self.user_id),
+      None => format!("> <@{}>\n** **", self.user_id),
+    }
+  }
+}
+
+impl FromRow<'_, PgRow> for StudyGroupMember {
+  fn from_row(row: &'_ PgRow) -> SqlxResult<Self, SqlxError> {
+    Ok(Self {
+      id: row.try_get("record_id")?,
+      group_id: row.try_get("group_id")?,
+      user_id: UserId::new(common::decode_id_row(row, "user_id")?),
+      progress: row.try_get("progress")?,
+      progress_week: row.try_get("progress_week")?,
+      joined_at: row.try_get("joined_at")?,
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_week_of() {
+    let date = |date: &str| NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap_or_default();
+
+    // Friday, November 22, 2024
+    assert_eq!(
+      StudyGroupMember::week_of(date("2024-11-22")),
+      date("2024-11-18")
+    );
+    assert_eq!(
+      StudyGroupMember::week_of(date("2024-11-18")),
+      date("2024-11-18")
+    );
+    assert_eq!(
+      StudyGroupMember::week_of(date("2024-11-24")),
+      date("2024-11-18")
+    );
+  }
+}
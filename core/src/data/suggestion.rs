@@ -0,0 +1,280 @@
+use chrono::{DateTime, Utc};
+use poise::serenity_prelude::{ChannelId, GuildId, MessageId, UserId};
+use poise::ChoiceParameter;
+use sqlx::postgres::{PgArguments, PgRow};
+use sqlx::query::{Query, QueryAs};
+use sqlx::{Error as SqlxError, FromRow, Postgres, Result as SqlxResult, Row};
+use ulid::Ulid;
+
+use crate::data::common;
+use crate::database::InsertQuery;
+use crate::pagination::{PageRow, PageType};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ChoiceParameter)]
+pub enum SuggestionStatus {
+  #[name = "open"]
+  Open,
+  #[name = "planned"]
+  Planned,
+  #[name = "done"]
+  Done,
+  #[name = "declined"]
+  Declined,
+}
+
+impl SuggestionStatus {
+  pub fn as_str(self) -> &'static str {
+    match self {
+      Self::Open => "open",
+      Self::Planned => "planned",
+      Self::Done => "done",
+      Self::Declined => "declined",
+    }
+  }
+
+  pub fn label(self) -> &'static str {
+    match self {
+      Self::Open => ":speech_balloon: Open",
+      Self::Planned => ":calendar: Planned",
+      Self::Done => ":white_check_mark: Done",
+      Self::Declined => ":no_entry_sign: Declined",
+    }
+  }
+
+  /// Returns `true` if members can still vote on suggestions with this status.
+  pub fn accepts_votes(self) -> bool {
+    matches!(self, Self::Open | Self::Planned)
+  }
+
+  fn from_name(status: &str) -> Self {
+    match status {
+      "planned" => Self::Planned,
+      "done" => Self::Done,
+      "declined" => Self::Declined,
+      _ => Self::Open,
+    }
+  }
+}
+
+/// A server suggestion, posted anonymously in the suggestions channel. Staff can update
+/// its status as it is considered, optionally with a note explaining the decision.
+pub struct Suggestion {
+  pub id: String,
+  pub guild_id: GuildId,
+  pub channel_id: ChannelId,
+  pub message_id: Option<MessageId>,
+  pub user_id: UserId,
+  pub content: String,
+  pub status: SuggestionStatus,
+  pub status_note: Option<String>,
+  pub status_updated_by: Option<UserId>,
+  pub created_at: Option<DateTime<Utc>>,
+}
+
+/// The number of upvotes and downvotes for a [`Suggestion`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SuggestionVotes {
+  pub upvotes: i64,
+  pub downvotes: i64,
+}
+
+pub struct SuggestionVote {
+  pub upvote: bool,
+}
+
+impl Suggestion {
+  pub fn new(guild_id: GuildId, channel_id: ChannelId, user_id: UserId, content: String) -> Self {
+    Self {
+      id: Ulid::new().to_string(),
+      guild_id,
+      channel_id,
+      message_id: None,
+      user_id,
+      content,
+      status: SuggestionStatus::Open,
+      status_note: None,
+      status_updated_by: None,
+      created_at: None,
+    }
+  }
+
+  pub fn retrieve<'a>(
+    guild_id: GuildId,
+    suggestion_id: &str,
+  ) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT record_id, guild_id, channel_id, message_id, user_id, content, status, status_note, status_updated_by, created_at FROM suggestion WHERE record_id = $1 AND guild_id = $2",
+    )
+    .bind(suggestion_id.to_owned())
+    .bind(guild_id.to_string())
+  }
+
+  /// Retrieves all [`Suggestion`]s with a status, most recent first.
+  pub fn retrieve_by_status<'a>(
+    guild_id: GuildId,
+    status: SuggestionStatus,
+  ) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT record_id, guild_id, channel_id, message_id, user_id, content, status, status_note, status_updated_by, created_at FROM suggestion WHERE guild_id = $1 AND status = $2 ORDER BY created_at DESC",
+    )
+    .bind(guild_id.to_string())
+    .bind(status.as_str())
+  }
+
+  /// Records the message a [`Suggestion`] was posted in, so it can be updated when its
+  /// status changes.
+  pub fn set_message(
+    suggestion_id: &str,
+    message_id: MessageId,
+  ) -> Query<'_, Postgres, PgArguments> {
+    query!(
+      "UPDATE suggestion SET message_id = $1 WHERE record_id = $2",
+      message_id.to_string(),
+      suggestion_id,
+    )
+  }
+
+  /// Updates the status of a [`Suggestion`], replacing any previous note.
+  pub fn update_status<'a>(
+    guild_id: GuildId,
+    suggestion_id: &'a str,
+    status: SuggestionStatus,
+    note: Option<&'a str>,
+    updated_by: UserId,
+  ) -> Query<'a, Postgres, PgArguments> {
+    query!(
+      "UPDATE suggestion SET status = $1, status_note = $2, status_updated_by = $3, status_updated_at = NOW() WHERE record_id = $4 AND guild_id = $5",
+      status.as_str(),
+      note,
+      updated_by.to_string(),
+      suggestion_id,
+      guild_id.to_string(),
+    )
+  }
+
+  /// Retrieves a member's vote on a [`Suggestion`], if they have voted.
+  pub fn vote_of<'a>(
+    suggestion_id: &'a str,
+    user_id: UserId,
+  ) -> QueryAs<'a, Postgres, SuggestionVote, PgArguments> {
+    sqlx::query_as("SELECT upvote FROM suggestion_vote WHERE suggestion_id = $1 AND user_id = $2")
+      .bind(suggestion_id)
+      .bind(user_id.to_string())
+  }
+
+  /// Records a member's vote on a [`Suggestion`], replacing any previous vote.
+  pub fn set_vote(
+    suggestion_id: &str,
+    user_id: UserId,
+    upvote: bool,
+  ) -> Query<'_, Postgres, PgArguments> {
+    query!(
+      "INSERT INTO suggestion_vote (suggestion_id, user_id, upvote) VALUES ($1, $2, $3) ON CONFLICT (suggestion_id, user_id) DO UPDATE SET upvote = $3, voted_at = NOW()",
+      suggestion_id,
+      user_id.to_string(),
+      upvote,
+    )
+  }
+
+  pub fn remove_vote(suggestion_id: &str, user_id: UserId) -> Query<'_, Postgres, PgArguments> {
+    query!(
+      "DELETE FROM suggestion_vote WHERE suggestion_id = $1 AND user_id = $2",
+      suggestion_id,
+      user_id.to_string(),
+    )
+  }
+
+  /// Counts the [`SuggestionVotes`] for a [`Suggestion`].
+  pub fn votes(suggestion_id: &str) -> QueryAs<'_, Postgres, SuggestionVotes, PgArguments> {
+    sqlx::query_as(
+      "SELECT COUNT(*) FILTER (WHERE upvote) AS upvotes, COUNT(*) FILTER (WHERE NOT upvote) AS downvotes FROM suggestion_vote WHERE suggestion_id = $1",
+    )
+    .bind(suggestion_id)
+  }
+}
+
+impl InsertQuery for Suggestion {
+  /// Adds a [`Suggestion`] to the database.
+  fn insert_query(&self) -> Query<Postgres, PgArguments> {
+    query!(
+      "INSERT INTO suggestion (record_id, guild_id, channel_id, message_id, user_id, content) VALUES ($1, $2, $3, $4, $5, $6)",
+      self.id,
+      self.guild_id.to_string(),
+      self.channel_id.to_string(),
+      self.message_id.map(|message_id| message_id.to_string()),
+      self.user_id.to_string(),
+      self.content,
+    )
+  }
+}
+
+impl PageRow for Suggestion {
+  fn title(&self, _page_type: PageType) -> String {
+    if self.content.chars().count() > 100 {
+      format!("{}...", self.content.chars().take(100).collect::<String>())
+    } else {
+      self.content.clone()
+    }
+  }
+
+  fn body(&self) -> String {
+    let note = match &self.status_note {
+      Some(note) => format!("{}: {note}\n", self.status.label()),
+      None => String::new(),
+    };
+    let link = match self.message_id {
+      Some(message_id) => format!(
+        " · [View](https://discord.com/channels/{}/{}/{message_id})",
+        self.guild_id, self.channel_id
+      ),
+      None => String::new(),
+    };
+
+    format!(
+      "{note}-# ID: {} · <t:{}:d>{link}",
+      self.id,
+      self.created_at.unwrap_or_default().timestamp()
+    )
+  }
+}
+
+impl FromRow<'_, PgRow> for Suggestion {
+  fn from_row(row: &'_ PgRow) -> SqlxResult<Self, SqlxError> {
+    let guild_id = GuildId::new(common::decode_id_row(row, "guild_id")?);
+    let channel_id = ChannelId::new(common::decode_id_row(row, "channel_id")?);
+    let message_id = common::decode_option_id_row(row, "message_id")?.map(MessageId::new);
+    let user_id = UserId::new(common::decode_id_row(row, "user_id")?);
+    let status_updated_by =
+      common::decode_option_id_row(row, "status_updated_by")?.map(UserId::new);
+
+    Ok(Self {
+      id: row.try_get("record_id")?,
+      guild_id,
+      channel_id,
+      message_id,
+      user_id,
+      content: row.try_get("content")?,
+      status: SuggestionStatus::from_name(row.try_get("status")?),
+      status_note: row.try_get("status_note")?,
+      status_updated_by,
+      created_at: row.try_get("created_at")?,
+    })
+  }
+}
+
+impl FromRow<'_, PgRow> for SuggestionVotes {
+  fn from_row(row: &'_ PgRow) -> SqlxResult<Self, SqlxError> {
+    Ok(Self {
+      upvotes: row.try_get("upvotes")?,
+      downvotes: row.try_get("downvotes")?,
+    })
+  }
+}
+
+impl FromRow<'_, PgRow> for SuggestionVote {
+  fn from_row(row: &'_ PgRow) -> SqlxResult<Self, SqlxError> {
+    Ok(Self {
+      upvote: row.try_get("upvote")?,
+    })
+  }
+}
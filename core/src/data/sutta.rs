@@ -0,0 +1,99 @@
+use sqlx::postgres::{PgArguments, PgRow};
+use sqlx::query::{Query, QueryAs};
+use sqlx::{Error as SqlxError, FromRow, Postgres, Result as SqlxResult, Row};
+use ulid::Ulid;
+
+use crate::database::InsertQuery;
+
+/// A sutta translation fetched from SuttaCentral, cached so that looking up popular suttas
+/// doesn't call the API every time. Cached translations are refreshed after 30 days.
+pub struct SuttaPassage {
+  pub id: String,
+  /// The SuttaCentral ID of the sutta, e.g., `mn10` or `sn56.11`.
+  pub uid: String,
+  /// The SuttaCentral ID of the translator, e.g., `sujato`.
+  pub translator: String,
+  /// The translator's name, e.g., `Bhikkhu Sujato`.
+  pub author: String,
+  pub lang: String,
+  /// The title in the original language, e.g., `Satipaṭṭhānasutta`.
+  pub title: String,
+  pub translated_title: Option<String>,
+  /// The full text of the translation, with one paragraph per line.
+  pub passage: String,
+}
+
+impl SuttaPassage {
+  pub fn new(
+    uid: String,
+    translator: String,
+    author: String,
+    lang: String,
+    title: String,
+    translated_title: Option<String>,
+    passage: String,
+  ) -> Self {
+    Self {
+      id: Ulid::new().to_string(),
+      uid,
+      translator,
+      author,
+      lang,
+      title,
+      translated_title,
+      passage,
+    }
+  }
+
+  /// The link to the translation on SuttaCentral.
+  pub fn url(&self) -> String {
+    format!(
+      "https://suttacentral.net/{}/{}/{}",
+      self.uid, self.lang, self.translator
+    )
+  }
+
+  /// Retrieves a cached [`SuttaPassage`], unless it was fetched more than 30 days ago.
+  pub fn retrieve<'a>(
+    uid: &'a str,
+    translator: &'a str,
+  ) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT record_id, sutta_uid, translator, author, lang, title, translated_title, passage FROM sutta_cache WHERE sutta_uid = $1 AND translator = $2 AND fetched_at > NOW() - INTERVAL '30 days'",
+    )
+    .bind(uid)
+    .bind(translator)
+  }
+}
+
+impl InsertQuery for SuttaPassage {
+  /// Caches a [`SuttaPassage`], replacing any earlier copy of the same translation.
+  fn insert_query(&self) -> Query<Postgres, PgArguments> {
+    query!(
+      "INSERT INTO sutta_cache (record_id, sutta_uid, translator, author, lang, title, translated_title, passage) VALUES ($1, $2, $3, $4, $5, $6, $7, $8) ON CONFLICT (sutta_uid, translator) DO UPDATE SET author = $4, lang = $5, title = $6, translated_title = $7, passage = $8, fetched_at = NOW()",
+      self.id,
+      self.uid,
+      self.translator,
+      self.author,
+      self.lang,
+      self.title,
+      self.translated_title,
+      self.passage,
+    )
+  }
+}
+
+impl FromRow<'_, PgRow> for SuttaPassage {
+  fn from_row(row: &'_ PgRow) -> SqlxResult<Self, SqlxError> {
+    Ok(Self {
+      id: row.try_get("record_id")?,
+      uid: row.try_get("sutta_uid")?,
+      translator: row.try_get("translator")?,
+      author: row.try_get("author")?,
+      lang: row.try_get("lang")?,
+      title: row.try_get("title")?,
+      translated_title: row.try_get("translated_title")?,
+      passage: row.try_get("passage")?,
+    })
+  }
+}
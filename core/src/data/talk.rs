@@ -0,0 +1,99 @@
+use poise::serenity_prelude::{GuildId, UserId};
+use sqlx::postgres::{PgArguments, PgRow};
+use sqlx::query::{Query, QueryAs};
+use sqlx::{Error as SqlxError, FromRow, Postgres, Result as SqlxResult, Row};
+use ulid::Ulid;
+
+use crate::data::common;
+use crate::database::{DeleteQuery, InsertQuery};
+use crate::pagination::{PageRow, PageType};
+
+/// A dharma talk or guided meditation in a guild's library, which can be played in a voice
+/// channel with `/talk play`. Names are unique within a guild, ignoring case.
+pub struct Talk {
+  pub id: String,
+  pub guild_id: GuildId,
+  pub name: String,
+  /// A direct link to the audio file.
+  pub url: String,
+  pub added_by: UserId,
+}
+
+impl Talk {
+  pub fn new(guild_id: GuildId, name: String, url: String, added_by: UserId) -> Self {
+    Self {
+      id: Ulid::new().to_string(),
+      guild_id,
+      name,
+      url,
+      added_by,
+    }
+  }
+
+  /// Retrieves a [`Talk`] by name, ignoring case.
+  pub fn retrieve<'a>(guild_id: GuildId, name: &str) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT record_id, guild_id, name, url, added_by FROM talk WHERE guild_id = $1 AND LOWER(name) = LOWER($2)",
+    )
+    .bind(guild_id.to_string())
+    .bind(name.to_owned())
+  }
+
+  /// Retrieves every [`Talk`] in a guild's library, sorted by name.
+  pub fn retrieve_all<'a>(guild_id: GuildId) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT record_id, guild_id, name, url, added_by FROM talk WHERE guild_id = $1 ORDER BY LOWER(name) ASC",
+    )
+    .bind(guild_id.to_string())
+  }
+}
+
+impl InsertQuery for Talk {
+  /// Adds a [`Talk`] to the guild's library.
+  fn insert_query(&self) -> Query<Postgres, PgArguments> {
+    query!(
+      "INSERT INTO talk (record_id, guild_id, name, url, added_by) VALUES ($1, $2, $3, $4, $5)",
+      self.id,
+      self.guild_id.to_string(),
+      self.name,
+      self.url,
+      self.added_by.to_string(),
+    )
+  }
+}
+
+impl DeleteQuery for Talk {
+  /// Removes a [`Talk`] by name, ignoring case.
+  fn delete_query<'a>(
+    guild_id: GuildId,
+    name: impl Into<String>,
+  ) -> Query<'a, Postgres, PgArguments> {
+    query!(
+      "DELETE FROM talk WHERE guild_id = $1 AND LOWER(name) = LOWER($2)",
+      guild_id.to_string(),
+      name.into(),
+    )
+  }
+}
+
+impl PageRow for Talk {
+  fn title(&self, _page_type: PageType) -> String {
+    self.name.clone()
+  }
+
+  fn body(&self) -> String {
+    format!("<{}>\nAdded by <@{}>", self.url, self.added_by)
+  }
+}
+
+impl FromRow<'_, PgRow> for Talk {
+  fn from_row(row: &'_ PgRow) -> SqlxResult<Self, SqlxError> {
+    Ok(Self {
+      id: row.try_get("record_id")?,
+      guild_id: GuildId::new(common::decode_id_row(row, "guild_id")?),
+      name: row.try_get("name")?,
+      url: row.try_get("url")?,
+      added_by: UserId::new(common::decode_id_row(row, "added_by")?),
+    })
+  }
+}
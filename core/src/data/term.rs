@@ -6,9 +6,9 @@ use sqlx::query::{Query, QueryAs};
 use sqlx::{Error as SqlxError, FromRow, Postgres, Result as SqlxResult, Row};
 use ulid::Ulid;
 
-use crate::commands::helpers::pagination::{PageRow, PageType};
 use crate::data::common;
-use crate::handlers::database::{DeleteQuery, ExistsQuery, InsertQuery, UpdateQuery};
+use crate::database::{DeleteQuery, ExistsQuery, InsertQuery, UpdateQuery};
+use crate::pagination::{PageRow, PageType};
 
 #[derive(Debug, Default)]
 pub struct Term {
@@ -19,6 +19,7 @@ pub struct Term {
   pub links: Option<Vec<String>>,
   pub category: Option<String>,
   pub aliases: Option<Vec<String>>,
+  pub related: Option<Vec<String>>,
   vector: Option<Vector>,
 }
 
@@ -48,6 +49,12 @@ pub struct VectorSearch {
   pub distance_score: Option<f64>,
 }
 
+/// A term name or alias, as shown in glossary suggestions.
+#[derive(Debug, FromRow)]
+pub struct TermName {
+  pub name: String,
+}
+
 impl Term {
   /// Creates a new [`Term`] with a specified [`GuildId`][gid], `name`,
   /// and `meaning`. All other values are set to `None`.
@@ -67,6 +74,7 @@ impl Term {
       links: None,
       category: None,
       aliases: None,
+      related: None,
       vector,
     }
   }
@@ -151,6 +159,7 @@ impl Term {
       aliases: modal
         .aliases
         .map(|aliases| aliases.split(',').map(|s| s.trim().to_string()).collect()),
+      related: None,
       vector,
     }
   }
@@ -169,13 +178,14 @@ impl Term {
     .bind(vector)
   }
 
-  /// Retrieves a [`Term`] from the database.
+  /// Retrieves a [`Term`] from the database by its name or one of its aliases. If a term
+  /// name matches, it takes precedence over any aliases.
   pub fn retrieve<'a>(
     guild_id: GuildId,
     term_name: &str,
   ) -> QueryAs<'a, Postgres, Self, PgArguments> {
     sqlx::query_as(
-      "SELECT term_name, meaning, usage, links, category, aliases FROM term WHERE guild_id = $2 AND (LOWER(term_name) = LOWER($1)) OR (f_textarr2text(aliases) ~* ('(?:^|,)' || $1 || '(?:$|,)'))",
+      "SELECT term_name, meaning, usage, links, category, aliases, related FROM term WHERE guild_id = $2 AND (LOWER(term_name) = LOWER($1) OR EXISTS (SELECT 1 FROM UNNEST(aliases) AS alias WHERE LOWER(alias) = LOWER($1))) ORDER BY LOWER(term_name) = LOWER($1) DESC LIMIT 1",
     )
     .bind(term_name.to_string())
     .bind(guild_id.to_string())
@@ -209,13 +219,110 @@ impl Term {
     similarity: f32,
   ) -> QueryAs<'a, Postgres, Self, PgArguments> {
     sqlx::query_as(
-      "SELECT term_name, meaning, usage, links, category, aliases, SET_LIMIT($2) FROM term WHERE guild_id = $3 AND (LOWER(term_name) % LOWER($1)) OR (f_textarr2text(aliases) ILIKE '%' || $1 || '%') ORDER BY SIMILARITY(LOWER(term_name), LOWER($1)) DESC LIMIT 5",
+      "SELECT term_name, meaning, usage, links, category, aliases, related, SET_LIMIT($2) FROM term WHERE guild_id = $3 AND (LOWER(term_name) % LOWER($1) OR f_textarr2text(aliases) ILIKE '%' || $1 || '%') ORDER BY SIMILARITY(LOWER(term_name), LOWER($1)) DESC LIMIT 5",
     )
     .bind(term_name.to_string())
     .bind(similarity)
     .bind(guild_id.to_string())
   }
 
+  /// Retrieves up to 25 term names and aliases containing `partial`, in alphabetical order,
+  /// for use as autocomplete suggestions.
+  pub fn retrieve_names<'a>(
+    guild_id: GuildId,
+    partial: &str,
+  ) -> QueryAs<'a, Postgres, TermName, PgArguments> {
+    sqlx::query_as(
+      "SELECT name FROM (SELECT term_name AS name FROM term WHERE guild_id = $1 UNION SELECT UNNEST(aliases) AS name FROM term WHERE guild_id = $1) AS names WHERE name ILIKE '%' || $2 || '%' ORDER BY name ASC LIMIT 25",
+    )
+    .bind(guild_id.to_string())
+    .bind(common::escape_like(partial))
+  }
+
+  /// Checks to see if `name` is already in use as a [`Term`] name or alias.
+  pub fn name_exists<'a, T: for<'r> FromRow<'r, PgRow>>(
+    guild_id: GuildId,
+    name: &str,
+  ) -> QueryAs<'a, Postgres, T, PgArguments> {
+    sqlx::query_as(
+      "SELECT EXISTS (SELECT 1 FROM term WHERE guild_id = $1 AND (LOWER(term_name) = LOWER($2) OR EXISTS (SELECT 1 FROM UNNEST(aliases) AS alias WHERE LOWER(alias) = LOWER($2))))",
+    )
+    .bind(guild_id.to_string())
+    .bind(name.to_string())
+  }
+
+  /// Adds an `alias` to the [`Term`] with the specified `term_name`.
+  pub fn add_alias<'a>(
+    guild_id: GuildId,
+    term_name: &str,
+    alias: &str,
+  ) -> Query<'a, Postgres, PgArguments> {
+    query!(
+      "UPDATE term SET aliases = ARRAY_APPEND(COALESCE(aliases, ARRAY[]::TEXT[]), $3) WHERE guild_id = $1 AND LOWER(term_name) = LOWER($2)",
+      guild_id.to_string(),
+      term_name,
+      alias,
+    )
+  }
+
+  /// Removes an `alias` from the [`Term`] with the specified `term_name`. Aliases are
+  /// matched case-insensitively. No rows are affected if the term does not have the alias.
+  pub fn remove_alias<'a>(
+    guild_id: GuildId,
+    term_name: &str,
+    alias: &str,
+  ) -> Query<'a, Postgres, PgArguments> {
+    query!(
+      "UPDATE term SET aliases = ARRAY(SELECT a FROM UNNEST(aliases) AS a WHERE LOWER(a) <> LOWER($3)) WHERE guild_id = $1 AND LOWER(term_name) = LOWER($2) AND EXISTS (SELECT 1 FROM UNNEST(aliases) AS a WHERE LOWER(a) = LOWER($3))",
+      guild_id.to_string(),
+      term_name,
+      alias,
+    )
+  }
+
+  /// Adds the term `related_name` to the "See also" entries of the [`Term`] with the
+  /// specified `term_name`.
+  pub fn add_related<'a>(
+    guild_id: GuildId,
+    term_name: &str,
+    related_name: &str,
+  ) -> Query<'a, Postgres, PgArguments> {
+    query!(
+      "UPDATE term SET related = ARRAY_APPEND(COALESCE(related, ARRAY[]::TEXT[]), $3) WHERE guild_id = $1 AND LOWER(term_name) = LOWER($2)",
+      guild_id.to_string(),
+      term_name,
+      related_name,
+    )
+  }
+
+  /// Removes the term `related_name` from the "See also" entries of the [`Term`] with the
+  /// specified `term_name`. No rows are affected if the term is not listed.
+  pub fn remove_related<'a>(
+    guild_id: GuildId,
+    term_name: &str,
+    related_name: &str,
+  ) -> Query<'a, Postgres, PgArguments> {
+    query!(
+      "UPDATE term SET related = ARRAY(SELECT r FROM UNNEST(related) AS r WHERE LOWER(r) <> LOWER($3)) WHERE guild_id = $1 AND LOWER(term_name) = LOWER($2) AND EXISTS (SELECT 1 FROM UNNEST(related) AS r WHERE LOWER(r) = LOWER($3))",
+      guild_id.to_string(),
+      term_name,
+      related_name,
+    )
+  }
+
+  /// Removes the term `term_name` from the "See also" entries of all other [`Term`]s, so
+  /// that removed terms are not left linked.
+  pub fn remove_related_references<'a>(
+    guild_id: GuildId,
+    term_name: &str,
+  ) -> Query<'a, Postgres, PgArguments> {
+    query!(
+      "UPDATE term SET related = ARRAY(SELECT r FROM UNNEST(related) AS r WHERE LOWER(r) <> LOWER($2)) WHERE guild_id = $1 AND EXISTS (SELECT 1 FROM UNNEST(related) AS r WHERE LOWER(r) = LOWER($2))",
+      guild_id.to_string(),
+      term_name,
+    )
+  }
+
   /// Calculates the total count of [`Term`]s in the database.
   pub fn count<'a, T: for<'r> FromRow<'r, PgRow>>(
     guild_id: GuildId,
@@ -249,7 +356,7 @@ impl UpdateQuery for Term {
   /// Updates a [`Term`] in the database.
   fn update_query(&self) -> Query<Postgres, PgArguments> {
     sqlx::query(
-      "UPDATE term SET meaning = $1, usage = $2, links = $3, category = $4, aliases = $5, embedding = COALESCE($6, embedding) WHERE LOWER(term_name) = LOWER($7)",
+      "UPDATE term SET meaning = $1, usage = $2, links = $3, category = $4, aliases = $5, embedding = COALESCE($6, embedding) WHERE LOWER(term_name) = LOWER($7) AND guild_id = $8",
     )
     .bind(self.meaning.clone())
     .bind(self.usage.clone())
@@ -258,6 +365,7 @@ impl UpdateQuery for Term {
     .bind(self.aliases.clone())
     .bind(self.vector.clone())
     .bind(self.name.clone())
+    .bind(self.guild_id.to_string())
   }
 }
 
@@ -313,6 +421,7 @@ impl FromRow<'_, PgRow> for Term {
       links: row.try_get("links").unwrap_or_default(),
       category: row.try_get("category").unwrap_or_default(),
       aliases: row.try_get("aliases").unwrap_or_default(),
+      related: row.try_get("related").unwrap_or_default(),
       vector: row.try_get("embedding").unwrap_or_default(),
     })
   }
@@ -336,16 +445,43 @@ impl From<Term> for TermModal {
 }
 
 impl VectorSearch {
+  /// The factor applied to the distance scores of terms listed as related to the closest
+  /// match, so that related entries rank above otherwise similarly close terms.
+  const RELATED_BOOST: f64 = 0.9;
+
+  /// Retrieves up to `limit` terms closest to `search_vector`, with distance scores no
+  /// greater than `threshold`. Terms listed as related to the closest match have their
+  /// distance scores reduced by [`Self::RELATED_BOOST`].
   pub fn result(
     guild_id: GuildId,
     search_vector: &Vector,
     limit: i64,
+    threshold: f64,
   ) -> QueryAs<'_, Postgres, Self, PgArguments> {
     sqlx::query_as(
-      "SELECT term_name, meaning, embedding <=> $1 AS distance_score FROM term WHERE guild_id = $2 ORDER BY distance_score ASC LIMIT $3",
+      "
+        WITH scored AS (
+          SELECT term_name, meaning, related, embedding <=> $1 AS distance_score FROM term WHERE guild_id = $2
+        ), closest AS (
+          SELECT term_name, related FROM scored ORDER BY distance_score ASC LIMIT 1
+        )
+        SELECT term_name, meaning, distance_score FROM (
+          SELECT scored.term_name, scored.meaning,
+            CASE
+              WHEN scored.term_name <> closest.term_name AND scored.term_name = ANY(closest.related) THEN scored.distance_score * $4
+              ELSE scored.distance_score
+            END AS distance_score
+          FROM scored CROSS JOIN closest
+        ) AS boosted
+        WHERE distance_score <= $5
+        ORDER BY distance_score ASC
+        LIMIT $3
+      ",
     )
     .bind(search_vector)
     .bind(guild_id.to_string())
     .bind(limit)
+    .bind(Self::RELATED_BOOST)
+    .bind(threshold)
   }
 }
@@ -0,0 +1,171 @@
+use chrono::{DateTime, Utc};
+use poise::serenity_prelude::{ChannelId, GuildId, UserId};
+use sqlx::postgres::{PgArguments, PgRow};
+use sqlx::query::{Query, QueryAs};
+use sqlx::{Error as SqlxError, FromRow, Postgres, Result as SqlxResult, Row};
+use ulid::Ulid;
+
+use crate::data::common;
+use crate::database::InsertQuery;
+
+/// A private support conversation between a member and staff. Staff discuss the ticket in
+/// a private thread, and replies are relayed to the member by DM.
+pub struct Ticket {
+  pub id: String,
+  pub guild_id: GuildId,
+  pub user_id: UserId,
+  pub thread_id: ChannelId,
+  pub subject: Option<String>,
+  pub open: bool,
+  pub closed_by: Option<UserId>,
+  pub opened_at: DateTime<Utc>,
+  pub closed_at: Option<DateTime<Utc>>,
+}
+
+impl Ticket {
+  pub fn new(
+    guild_id: GuildId,
+    user_id: UserId,
+    thread_id: ChannelId,
+    subject: Option<String>,
+  ) -> Self {
+    Self {
+      id: Ulid::new().to_string(),
+      guild_id,
+      user_id,
+      thread_id,
+      subject,
+      open: true,
+      closed_by: None,
+      opened_at: Utc::now(),
+      closed_at: None,
+    }
+  }
+
+  pub fn retrieve<'a>(
+    guild_id: GuildId,
+    ticket_id: &'a str,
+  ) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT record_id, guild_id, user_id, thread_id, subject, status, closed_by, opened_at, closed_at FROM ticket WHERE guild_id = $1 AND record_id = $2",
+    )
+    .bind(guild_id.to_string())
+    .bind(ticket_id)
+  }
+
+  /// Retrieves the [`Ticket`] discussed in the specified thread.
+  pub fn retrieve_by_thread<'a>(thread_id: ChannelId) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT record_id, guild_id, user_id, thread_id, subject, status, closed_by, opened_at, closed_at FROM ticket WHERE thread_id = $1",
+    )
+    .bind(thread_id.to_string())
+  }
+
+  /// Retrieves the member's open [`Ticket`]s, oldest first. Members can have one open
+  /// ticket per server.
+  pub fn retrieve_open_for_user<'a>(user_id: UserId) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT record_id, guild_id, user_id, thread_id, subject, status, closed_by, opened_at, closed_at FROM ticket WHERE user_id = $1 AND status = 'open' ORDER BY opened_at ASC",
+    )
+    .bind(user_id.to_string())
+  }
+
+  /// Closes a [`Ticket`]. Only affects open tickets, so each ticket is only closed once.
+  pub fn close(ticket_id: &str, closed_by: UserId) -> Query<'_, Postgres, PgArguments> {
+    query!(
+      "UPDATE ticket SET status = 'closed', closed_by = $1, closed_at = NOW() WHERE record_id = $2 AND status = 'open'",
+      closed_by.to_string(),
+      ticket_id,
+    )
+  }
+}
+
+impl InsertQuery for Ticket {
+  /// Adds a [`Ticket`] to the database.
+  fn insert_query(&self) -> Query<Postgres, PgArguments> {
+    query!(
+      "INSERT INTO ticket (record_id, guild_id, user_id, thread_id, subject, opened_at) VALUES ($1, $2, $3, $4, $5, $6)",
+      self.id,
+      self.guild_id.to_string(),
+      self.user_id.to_string(),
+      self.thread_id.to_string(),
+      self.subject,
+      self.opened_at,
+    )
+  }
+}
+
+impl FromRow<'_, PgRow> for Ticket {
+  fn from_row(row: &'_ PgRow) -> SqlxResult<Self, SqlxError> {
+    Ok(Self {
+      id: row.try_get("record_id")?,
+      guild_id: GuildId::new(common::decode_id_row(row, "guild_id")?),
+      user_id: UserId::new(common::decode_id_row(row, "user_id")?),
+      thread_id: ChannelId::new(common::decode_id_row(row, "thread_id")?),
+      subject: row.try_get("subject")?,
+      open: row.try_get::<&str, &str>("status")? == "open",
+      closed_by: common::decode_option_id_row(row, "closed_by")?.map(UserId::new),
+      opened_at: row.try_get("opened_at")?,
+      closed_at: row.try_get("closed_at")?,
+    })
+  }
+}
+
+/// A message in a [`Ticket`] transcript, either from the member or a staff reply.
+pub struct TicketMessage {
+  pub id: String,
+  pub ticket_id: String,
+  pub author_id: UserId,
+  pub from_staff: bool,
+  pub content: String,
+  pub created_at: DateTime<Utc>,
+}
+
+impl TicketMessage {
+  pub fn new(ticket_id: &str, author_id: UserId, from_staff: bool, content: String) -> Self {
+    Self {
+      id: Ulid::new().to_string(),
+      ticket_id: ticket_id.to_owned(),
+      author_id,
+      from_staff,
+      content,
+      created_at: Utc::now(),
+    }
+  }
+
+  /// Retrieves the transcript of a [`Ticket`], oldest first.
+  pub fn retrieve_all(ticket_id: &str) -> QueryAs<'_, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT record_id, ticket_id, author_id, from_staff, content, created_at FROM ticket_message WHERE ticket_id = $1 ORDER BY created_at ASC, record_id ASC",
+    )
+    .bind(ticket_id)
+  }
+}
+
+impl InsertQuery for TicketMessage {
+  /// Adds a [`TicketMessage`] to the database.
+  fn insert_query(&self) -> Query<Postgres, PgArguments> {
+    query!(
+      "INSERT INTO ticket_message (record_id, ticket_id, author_id, from_staff, content, created_at) VALUES ($1, $2, $3, $4, $5, $6)",
+      self.id,
+      self.ticket_id,
+      self.author_id.to_string(),
+      self.from_staff,
+      self.content,
+      self.created_at,
+    )
+  }
+}
+
+impl FromRow<'_, PgRow> for TicketMessage {
+  fn from_row(row: &'_ PgRow) -> SqlxResult<Self, SqlxError> {
+    Ok(Self {
+      id: row.try_get("record_id")?,
+      ticket_id: row.try_get("ticket_id")?,
+      author_id: UserId::new(common::decode_id_row(row, "author_id")?),
+      from_staff: row.try_get("from_staff")?,
+      content: row.try_get("content")?,
+      created_at: row.try_get("created_at")?,
+    })
+  }
+}
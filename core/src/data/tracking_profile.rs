@@ -0,0 +1,672 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use poise::serenity_prelude::{GuildId, UserId};
+use poise::ChoiceParameter;
+use sqlx::postgres::{PgArguments, PgRow};
+use sqlx::query::{Query, QueryAs};
+use sqlx::{Error as SqlxError, FromRow, Postgres, Result as SqlxResult, Row};
+use ulid::Ulid;
+
+use crate::charts::ChartFormat;
+use crate::data::common;
+use crate::database::{DeleteQuery, InsertQuery, UpdateQuery};
+use crate::pagination::{PageRow, PageType};
+use crate::time;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, ChoiceParameter)]
+pub enum Privacy {
+  #[name = "private"]
+  Private,
+  #[default]
+  #[name = "public"]
+  Public,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, ChoiceParameter)]
+pub enum Status {
+  #[default]
+  #[name = "enabled"]
+  Enabled,
+  #[name = "disabled"]
+  Disabled,
+}
+
+#[derive(Debug)]
+pub struct Tracking {
+  pub privacy: Privacy,
+  /// When `true`, nothing is posted to the channel for private tracking,
+  /// not even an anonymized entry.
+  pub silent: bool,
+  /// When `true`, entries posted to the channel say a session was logged without saying
+  /// how long it was. The minutes still count toward stats, and are shared privately.
+  pub redact_minutes: bool,
+}
+
+#[derive(Debug)]
+pub struct Streak {
+  pub status: Status,
+  pub privacy: Privacy,
+  /// The local hour (0-23) by which a reminder is sent if no time has been added that day
+  /// and an active streak would lapse. `None` turns the streak guard off.
+  pub guard_hour: Option<i16>,
+}
+
+#[derive(Debug)]
+pub struct Stats {
+  pub privacy: Privacy,
+  /// Default visibility of your own stats, as opposed to whether others can view them.
+  pub visibility: Privacy,
+  /// Default image format for stats charts.
+  pub chart_format: ChartFormat,
+}
+
+/// Optional details about a member's practice, shown in `/directory` when listed.
+#[derive(Debug, Default)]
+pub struct Profile {
+  pub tradition: Option<String>,
+  pub teacher: Option<String>,
+  pub years_practicing: Option<i16>,
+  /// When `true`, the member is included in `/directory`.
+  pub listed: bool,
+  /// The name to use in announcements, in place of a mention.
+  pub display_name: Option<String>,
+  /// Pronouns to show alongside the member's name in announcements, e.g., "they/them".
+  pub pronouns: Option<String>,
+}
+
+impl Profile {
+  /// The name to congratulate the member by in announcements: their display name if set,
+  /// or `fallback` (usually a mention) otherwise, followed by their pronouns if set.
+  pub fn announce_as(&self, fallback: &str) -> String {
+    let name = self.display_name.as_deref().unwrap_or(fallback);
+    match &self.pronouns {
+      Some(pronouns) => format!("{name} ({pronouns})"),
+      None => name.to_owned(),
+    }
+  }
+}
+
+#[derive(Debug)]
+pub struct TrackingProfile {
+  pub user_id: UserId,
+  pub guild_id: GuildId,
+  pub utc_offset: i16,
+  pub tracking: Tracking,
+  pub streak: Streak,
+  pub stats: Stats,
+  pub profile: Profile,
+}
+
+impl TrackingProfile {
+  /// Creates a new [`TrackingProfile`] with a specified [`UserID`][uid]
+  /// and [`GuildId`][gid]. All other values are set to their defaults.
+  ///
+  /// [uid]: poise::serenity_prelude::model::id::UserId
+  /// [gid]: poise::serenity_prelude::model::id::GuildId
+  pub fn new(guild_id: impl Into<GuildId>, user_id: impl Into<UserId>) -> Self {
+    Self {
+      user_id: user_id.into(),
+      guild_id: guild_id.into(),
+      ..Default::default()
+    }
+  }
+
+  /// Assigns a [`UserID`][uid] to a [`TrackingProfile`].
+  ///
+  /// [uid]: poise::serenity_prelude::model::id::UserId
+  pub fn user_id(mut self, user_id: impl Into<UserId>) -> Self {
+    self.user_id = user_id.into();
+    self
+  }
+
+  /// Assigns a [`GuildId`][gid] to a [`TrackingProfile`].
+  ///
+  /// [gid]: poise::serenity_prelude::model::id::GuildId
+  pub fn guild_id(mut self, guild_id: impl Into<GuildId>) -> Self {
+    self.guild_id = guild_id.into();
+    self
+  }
+
+  /// Assigns a UTC offset, in number of minutes, to a [`TrackingProfile`].
+  /// If the specified offset is not valid, the [`TrackingProfile`] is returned unchanged.
+  /// Valid offsets can be found in [`PlusOffsetChoice`][poc] and [`MinusOffsetChoice`][moc].
+  ///
+  /// [poc]: crate::time::PlusOffsetChoice
+  /// [moc]: crate::time::MinusOffsetChoice
+  pub fn utc_offset(mut self, utc_offset: i16) -> Self {
+    if matches!(time::choice_from_offset(utc_offset), (None, None)) {
+      self
+    } else {
+      self.utc_offset = utc_offset;
+      self
+    }
+  }
+
+  /// Sets tracking [`Privacy`] for a [`TrackingProfile`].
+  /// Default is [`Privacy::Public`].
+  pub fn tracking_privacy(mut self, privacy: Privacy) -> Self {
+    self.tracking.privacy = privacy;
+    self
+  }
+
+  /// Sets whether private tracking is silent for a [`TrackingProfile`]. Only
+  /// applies when tracking [`Privacy`] is [`Privacy::Private`]. Default is `false`.
+  pub fn tracking_silent(mut self, silent: bool) -> Self {
+    self.tracking.silent = silent;
+    self
+  }
+
+  /// Sets whether the minutes of entries posted to the channel are hidden for a
+  /// [`TrackingProfile`]. Default is `false`.
+  pub fn tracking_redacted(mut self, redact_minutes: bool) -> Self {
+    self.tracking.redact_minutes = redact_minutes;
+    self
+  }
+
+  /// Sets streak reporting [`Status`] for a [`TrackingProfile`].
+  /// Default is [`Status::Enabled`].
+  pub fn streak_status(mut self, status: Status) -> Self {
+    self.streak.status = status;
+    self
+  }
+
+  /// Sets streak [`Privacy`] for a [`TrackingProfile`].
+  /// Default is [`Privacy::Public`].
+  pub fn streak_privacy(mut self, privacy: Privacy) -> Self {
+    self.streak.privacy = privacy;
+    self
+  }
+
+  /// Sets the local hour for the streak guard reminder for a [`TrackingProfile`], or turns
+  /// the reminder off if `None`. Hours outside of 0-23 are ignored. Default is `None`.
+  pub fn streak_guard(mut self, guard_hour: Option<i16>) -> Self {
+    if guard_hour.is_none_or(|hour| (0..24).contains(&hour)) {
+      self.streak.guard_hour = guard_hour;
+    }
+    self
+  }
+
+  /// Sets stats [`Privacy`] for a [`TrackingProfile`].
+  /// Default is [`Privacy::Public`].
+  pub fn stats_privacy(mut self, privacy: Privacy) -> Self {
+    self.stats.privacy = privacy;
+    self
+  }
+
+  /// Sets the default visibility of your own stats for a [`TrackingProfile`]. [`Privacy::Private`]
+  /// shows stats in an ephemeral message. Default is [`Privacy::Public`].
+  pub fn stats_visibility(mut self, visibility: Privacy) -> Self {
+    self.stats.visibility = visibility;
+    self
+  }
+
+  /// Sets the default [`ChartFormat`] for stats charts for a [`TrackingProfile`].
+  /// Default is [`ChartFormat::Standard`].
+  pub fn stats_chart_format(mut self, chart_format: ChartFormat) -> Self {
+    self.stats.chart_format = chart_format;
+    self
+  }
+
+  /// Sets the practice tradition for a [`TrackingProfile`], or clears it if `None`.
+  pub fn profile_tradition(mut self, tradition: Option<String>) -> Self {
+    self.profile.tradition = tradition;
+    self
+  }
+
+  /// Sets the favorite teacher for a [`TrackingProfile`], or clears it if `None`.
+  pub fn profile_teacher(mut self, teacher: Option<String>) -> Self {
+    self.profile.teacher = teacher;
+    self
+  }
+
+  /// Sets the number of years practicing for a [`TrackingProfile`], or clears it if `None`.
+  pub fn profile_years(mut self, years_practicing: Option<i16>) -> Self {
+    self.profile.years_practicing = years_practicing;
+    self
+  }
+
+  /// Sets whether a [`TrackingProfile`] is listed in `/directory`. Default is `false`.
+  pub fn profile_listed(mut self, listed: bool) -> Self {
+    self.profile.listed = listed;
+    self
+  }
+
+  /// Sets the name used in announcements for a [`TrackingProfile`], or clears it if `None`.
+  pub fn profile_display_name(mut self, display_name: Option<String>) -> Self {
+    self.profile.display_name = display_name;
+    self
+  }
+
+  /// Sets the pronouns shown in announcements for a [`TrackingProfile`], or clears them if
+  /// `None`.
+  pub fn profile_pronouns(mut self, pronouns: Option<String>) -> Self {
+    self.profile.pronouns = pronouns;
+    self
+  }
+
+  /// Retrieves a [`TrackingProfile`] for a specified `user_id`.
+  pub fn retrieve<'a>(
+    guild_id: GuildId,
+    user_id: UserId,
+  ) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT user_id, guild_id, utc_offset, anonymous_tracking, silent_tracking, redact_minutes, streaks_active, streaks_private, streak_guard_hour, stats_private, stats_ephemeral, chart_format, tradition, favorite_teacher, years_practicing, directory_listed, display_name, pronouns FROM tracking_profile WHERE user_id = $1 AND guild_id = $2",
+    )
+    .bind(user_id.to_string())
+    .bind(guild_id.to_string())
+  }
+}
+
+impl InsertQuery for TrackingProfile {
+  fn insert_query(&self) -> Query<Postgres, PgArguments> {
+    query!(
+      "INSERT INTO tracking_profile (record_id, user_id, guild_id, utc_offset, anonymous_tracking, silent_tracking, redact_minutes, streaks_active, streaks_private, streak_guard_hour, stats_private, stats_ephemeral, chart_format, tradition, favorite_teacher, years_practicing, directory_listed, display_name, pronouns) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19)",
+      Ulid::new().to_string(),
+      self.user_id.to_string(),
+      self.guild_id.to_string(),
+      self.utc_offset,
+      privacy!(self.tracking.privacy),
+      self.tracking.silent,
+      self.tracking.redact_minutes,
+      matches!(self.streak.status, Status::Enabled),
+      privacy!(self.streak.privacy),
+      self.streak.guard_hour,
+      privacy!(self.stats.privacy),
+      privacy!(self.stats.visibility),
+      self.stats.chart_format.as_str(),
+      self.profile.tradition,
+      self.profile.teacher,
+      self.profile.years_practicing,
+      self.profile.listed,
+      self.profile.display_name,
+      self.profile.pronouns,
+    )
+  }
+}
+
+impl UpdateQuery for TrackingProfile {
+  fn update_query(&self) -> Query<Postgres, PgArguments> {
+    query!(
+      "UPDATE tracking_profile SET utc_offset = $1, anonymous_tracking = $2, silent_tracking = $3, redact_minutes = $4, streaks_active = $5, streaks_private = $6, streak_guard_hour = $7, stats_private = $8, stats_ephemeral = $9, chart_format = $10, tradition = $11, favorite_teacher = $12, years_practicing = $13, directory_listed = $14, display_name = $15, pronouns = $16 WHERE user_id = $17 AND guild_id = $18",
+      self.utc_offset,
+      privacy!(self.tracking.privacy),
+      self.tracking.silent,
+      self.tracking.redact_minutes,
+      matches!(self.streak.status, Status::Enabled),
+      privacy!(self.streak.privacy),
+      self.streak.guard_hour,
+      privacy!(self.stats.privacy),
+      privacy!(self.stats.visibility),
+      self.stats.chart_format.as_str(),
+      self.profile.tradition,
+      self.profile.teacher,
+      self.profile.years_practicing,
+      self.profile.listed,
+      self.profile.display_name,
+      self.profile.pronouns,
+      self.user_id.to_string(),
+      self.guild_id.to_string(),
+    )
+  }
+}
+
+impl DeleteQuery for TrackingProfile {
+  fn delete_query<'a>(
+    guild_id: GuildId,
+    user_id: impl Into<String>,
+  ) -> Query<'a, Postgres, PgArguments> {
+    query!(
+      "DELETE FROM tracking_profile WHERE user_id = $1 AND guild_id = $2",
+      user_id.into(),
+      guild_id.to_string(),
+    )
+  }
+}
+
+//Default values for tracking customization
+impl Default for TrackingProfile {
+  fn default() -> Self {
+    Self {
+      user_id: UserId::default(),
+      guild_id: GuildId::default(),
+      utc_offset: 0,
+      tracking: Tracking {
+        privacy: Privacy::Public,
+        silent: false,
+        redact_minutes: false,
+      },
+      streak: Streak {
+        status: Status::Enabled,
+        privacy: Privacy::Public,
+        guard_hour: None,
+      },
+      stats: Stats {
+        privacy: Privacy::Public,
+        visibility: Privacy::Public,
+        chart_format: ChartFormat::Standard,
+      },
+      profile: Profile::default(),
+    }
+  }
+}
+
+impl FromRow<'_, PgRow> for TrackingProfile {
+  fn from_row(row: &'_ PgRow) -> SqlxResult<Self, SqlxError> {
+    let user_id = UserId::new(common::decode_id_row(row, "user_id")?);
+    let guild_id = GuildId::new(common::decode_id_row(row, "guild_id")?);
+    let tracking_privacy = if row.try_get::<bool, &str>("anonymous_tracking")? {
+      Privacy::Private
+    } else {
+      Privacy::Public
+    };
+    let streak_status = if row.try_get::<bool, &str>("streaks_active")? {
+      Status::Enabled
+    } else {
+      Status::Disabled
+    };
+    let streak_privacy = if row.try_get::<bool, &str>("streaks_private")? {
+      Privacy::Private
+    } else {
+      Privacy::Public
+    };
+    let stats_privacy = if row.try_get::<bool, &str>("stats_private")? {
+      Privacy::Private
+    } else {
+      Privacy::Public
+    };
+    let stats_visibility = if row.try_get::<bool, &str>("stats_ephemeral")? {
+      Privacy::Private
+    } else {
+      Privacy::Public
+    };
+
+    Ok(Self {
+      user_id,
+      guild_id,
+      utc_offset: row.try_get("utc_offset").unwrap_or_default(),
+      tracking: Tracking {
+        privacy: tracking_privacy,
+        silent: row.try_get("silent_tracking")?,
+        redact_minutes: row.try_get("redact_minutes")?,
+      },
+      streak: Streak {
+        status: streak_status,
+        privacy: streak_privacy,
+        guard_hour: row.try_get("streak_guard_hour")?,
+      },
+      stats: Stats {
+        privacy: stats_privacy,
+        visibility: stats_visibility,
+        chart_format: ChartFormat::from_db(row.try_get("chart_format")?),
+      },
+      profile: Profile {
+        tradition: row.try_get("tradition")?,
+        teacher: row.try_get("favorite_teacher")?,
+        years_practicing: row.try_get("years_practicing")?,
+        listed: row.try_get("directory_listed")?,
+        display_name: row.try_get("display_name")?,
+        pronouns: row.try_get("pronouns")?,
+      },
+    })
+  }
+}
+
+/// A member whose streak will lapse unless they add time today, and whose local time has
+/// passed the hour set for their streak guard reminder.
+#[derive(Debug)]
+pub struct StreakGuardReminder {
+  pub user_id: UserId,
+  pub guild_id: GuildId,
+  pub current_streak: i32,
+  /// The member's local date, used to only send one reminder per day.
+  pub local_date: NaiveDate,
+}
+
+impl StreakGuardReminder {
+  /// Retrieves a [`StreakGuardReminder`] for every member who has turned on the streak guard,
+  /// has streaks enabled and an active streak, added time yesterday but not yet today, and
+  /// hasn't been reminded today. Days are local to each member's UTC offset, matching how
+  /// sessions are dated.
+  pub fn retrieve_due<'a>(now: &'a DateTime<Utc>) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "WITH profiles AS (SELECT user_id, guild_id, streak_guard_hour, streak_guard_sent_on, ($1 AT TIME ZONE 'UTC') + make_interval(mins => utc_offset) AS local_now FROM tracking_profile WHERE streak_guard_hour IS NOT NULL AND streaks_active = TRUE) SELECT profiles.user_id, profiles.guild_id, streak.current_streak, date_trunc('day', profiles.local_now)::date AS local_date FROM profiles INNER JOIN streak ON streak.user_id = profiles.user_id AND streak.guild_id = profiles.guild_id WHERE streak.current_streak > 0 AND EXTRACT(HOUR FROM profiles.local_now) >= profiles.streak_guard_hour AND (profiles.streak_guard_sent_on IS NULL OR profiles.streak_guard_sent_on < date_trunc('day', profiles.local_now)::date) AND EXISTS (SELECT 1 FROM meditation WHERE meditation.user_id = profiles.user_id AND meditation.guild_id = profiles.guild_id AND (meditation.occurred_at AT TIME ZONE 'UTC') >= date_trunc('day', profiles.local_now) - interval '1 day' AND (meditation.occurred_at AT TIME ZONE 'UTC') < date_trunc('day', profiles.local_now)) AND NOT EXISTS (SELECT 1 FROM meditation WHERE meditation.user_id = profiles.user_id AND meditation.guild_id = profiles.guild_id AND (meditation.occurred_at AT TIME ZONE 'UTC') >= date_trunc('day', profiles.local_now))",
+    )
+    .bind(now)
+  }
+
+  /// Records that a streak guard reminder has been sent on the member's local date.
+  pub fn mark_sent(&self) -> Query<'_, Postgres, PgArguments> {
+    query!(
+      "UPDATE tracking_profile SET streak_guard_sent_on = $1 WHERE user_id = $2 AND guild_id = $3",
+      self.local_date,
+      self.user_id.to_string(),
+      self.guild_id.to_string(),
+    )
+  }
+}
+
+impl FromRow<'_, PgRow> for StreakGuardReminder {
+  fn from_row(row: &'_ PgRow) -> SqlxResult<Self, SqlxError> {
+    let user_id = UserId::new(common::decode_id_row(row, "user_id")?);
+    let guild_id = GuildId::new(common::decode_id_row(row, "guild_id")?);
+
+    Ok(Self {
+      user_id,
+      guild_id,
+      current_streak: row.try_get("current_streak")?,
+      local_date: row.try_get("local_date")?,
+    })
+  }
+}
+
+/// A member listed in `/directory`, along with the details they've chosen to share.
+pub struct DirectoryEntry {
+  pub user_id: UserId,
+  pub profile: Profile,
+}
+
+impl DirectoryEntry {
+  /// Retrieves a [`DirectoryEntry`] for every member of a guild who has opted in to the
+  /// directory. If `search` is given, only members whose tradition or favorite teacher
+  /// contains it, ignoring case, are included.
+  pub fn search<'a>(
+    guild_id: GuildId,
+    search: Option<&str>,
+  ) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT user_id, tradition, favorite_teacher, years_practicing, directory_listed, display_name, pronouns FROM tracking_profile WHERE guild_id = $1 AND directory_listed = TRUE AND ($2::TEXT IS NULL OR tradition ILIKE '%' || $2 || '%' OR favorite_teacher ILIKE '%' || $2 || '%') ORDER BY tradition NULLS LAST, years_practicing DESC NULLS LAST, user_id",
+    )
+    .bind(guild_id.to_string())
+    .bind(search.map(common::escape_like))
+  }
+}
+
+impl PageRow for DirectoryEntry {
+  fn title(&self, _page_type: PageType) -> String {
+    self
+      .profile
+      .tradition
+      .clone()
+      .unwrap_or_else(|| "Tradition not shared".to_owned())
+  }
+
+  fn body(&self) -> String {
+    let mut details = vec![format!("> <@{}>", self.user_id)];
+    if let Some(teacher) = &self.profile.teacher {
+      details.push(format!("> -# Favorite teacher: {teacher}"));
+    }
+    if let Some(years) = self.profile.years_practicing {
+      details.push(format!(
+        "> -# Practicing for {years} {}",
+        if years == 1 { "year" } else { "years" }
+      ));
+    }
+    details.push("** **".to_owned());
+    details.join("\n")
+  }
+}
+
+impl FromRow<'_, PgRow> for DirectoryEntry {
+  fn from_row(row: &'_ PgRow) -> SqlxResult<Self, SqlxError> {
+    let user_id = UserId::new(common::decode_id_row(row, "user_id")?);
+
+    Ok(Self {
+      user_id,
+      profile: Profile {
+        tradition: row.try_get("tradition")?,
+        teacher: row.try_get("favorite_teacher")?,
+        years_practicing: row.try_get("years_practicing")?,
+        listed: row.try_get("directory_listed")?,
+        display_name: row.try_get("display_name")?,
+        pronouns: row.try_get("pronouns")?,
+      },
+    })
+  }
+}
+
+/// Takes [`Privacy`][priv] as an argument and returns `true` for [`Privacy::Private`]
+/// or `false` for [`Privacy::Public`].
+///
+/// [`Option<Privacy>`][priv] can also be passed as an argument, along with a default value
+/// of type [`Privacy`][priv], specified via a second argument. If [`Option<Privacy>`][priv]
+/// is `Some`, the same matching as above is applied to the unwrapped value. If `None`, the
+/// matching is applied to the default value. This means the first value takes precedence.
+///
+/// In most cases, the default value should be taken from the user's [`TrackingProfile`][tp].
+///
+/// # Examples
+///
+/// ```rust
+/// # use bloombot_core::data::tracking_profile::{privacy, Privacy, TrackingProfile};
+/// let privacy = Privacy::Private;
+/// assert!(privacy!(privacy));
+///
+/// let privacy = None;
+/// let profile = TrackingProfile::default().streak_privacy(Privacy::Private);
+/// assert!(privacy!(privacy, profile.streak.privacy));
+///
+/// let privacy = Privacy::Public;
+/// let profile = TrackingProfile::default().streak_privacy(Privacy::Private);
+/// assert!(!(privacy!(privacy, profile.streak.privacy)));
+/// ```
+///
+/// [priv]: crate::data::tracking_profile::Privacy
+/// [tp]: crate::data::tracking_profile::TrackingProfile
+#[macro_export]
+macro_rules! privacy {
+  ($privacy:expr, $default:expr) => {
+    match $privacy {
+      Some(privacy) => match privacy {
+        $crate::data::tracking_profile::Privacy::Private => true,
+        $crate::data::tracking_profile::Privacy::Public => false,
+      },
+      None => match $default {
+        $crate::data::tracking_profile::Privacy::Private => true,
+        $crate::data::tracking_profile::Privacy::Public => false,
+      },
+    }
+  };
+  ($privacy:expr) => {
+    match $privacy {
+      $crate::data::tracking_profile::Privacy::Private => true,
+      $crate::data::tracking_profile::Privacy::Public => false,
+    }
+  };
+}
+pub use crate::privacy;
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_builder() {
+    let profile1 = TrackingProfile {
+      utc_offset: 180,
+      streak: Streak {
+        status: Status::Enabled,
+        privacy: Privacy::Private,
+        guard_hour: None,
+      },
+      stats: Stats {
+        privacy: Privacy::Private,
+        visibility: Privacy::Public,
+      },
+      ..Default::default()
+    };
+    let profile2 = TrackingProfile::default()
+      .utc_offset(180)
+      .streak_privacy(Privacy::Private)
+      .stats_privacy(Privacy::Private);
+    assert_eq!(profile1.utc_offset, profile2.utc_offset);
+    assert_eq!(profile1.streak.privacy, profile2.streak.privacy);
+    assert_eq!(profile1.stats.privacy, profile2.stats.privacy);
+
+    let profile = TrackingProfile::default()
+      .tracking_privacy(Privacy::Private)
+      .tracking_silent(true);
+    assert_eq!(profile.tracking.privacy, Privacy::Private);
+    assert!(profile.tracking.silent);
+    assert!(!TrackingProfile::default().tracking.silent);
+
+    let profile = TrackingProfile::default().stats_visibility(Privacy::Private);
+    assert_eq!(profile.stats.privacy, Privacy::Public);
+    assert_eq!(profile.stats.visibility, Privacy::Private);
+
+    let profile = TrackingProfile::default().streak_guard(Some(20));
+    assert_eq!(profile.streak.guard_hour, Some(20));
+    assert_eq!(profile.streak_guard(Some(24)).streak.guard_hour, Some(20));
+    assert!(TrackingProfile::default().streak.guard_hour.is_none());
+
+    let profile = TrackingProfile::default()
+      .profile_tradition(Some("Theravada".to_owned()))
+      .profile_years(Some(3))
+      .profile_listed(true);
+    assert_eq!(profile.profile.tradition.as_deref(), Some("Theravada"));
+    assert_eq!(profile.profile.years_practicing, Some(3));
+    assert!(profile.profile.teacher.is_none());
+    assert!(profile.profile.listed);
+    assert!(!TrackingProfile::default().profile.listed);
+
+    let profile = TrackingProfile::default().profile_pronouns(Some("she/her".to_owned()));
+    assert_eq!(profile.profile.announce_as("<@123>"), "<@123> (she/her)");
+    let profile = profile.profile_display_name(Some("Sam".to_owned()));
+    assert_eq!(profile.profile.announce_as("<@123>"), "Sam (she/her)");
+    assert_eq!(
+      TrackingProfile::default().profile.announce_as("<@123>"),
+      "<@123>"
+    );
+
+    assert_eq!(TrackingProfile::default().utc_offset, 0);
+    assert_eq!(TrackingProfile::default().utc_offset(5).utc_offset, 0);
+    assert_eq!(TrackingProfile::default().utc_offset(540).utc_offset, 540);
+  }
+
+  #[test]
+  #[allow(clippy::unreadable_literal)]
+  fn test_id_methods() {
+    let guild_id = GuildId::new(1300863845429936139);
+    let profile = TrackingProfile::default().guild_id(guild_id);
+    assert_eq!(profile.guild_id, GuildId::new(1300863845429936139));
+
+    let int_user_id = 1300863845429936139;
+    let str_guild_id = 1300863845429936139;
+
+    let profile = TrackingProfile::default()
+      .user_id(int_user_id)
+      .guild_id(str_guild_id);
+    assert_eq!(profile.user_id, UserId::new(1300863845429936139));
+    assert_eq!(profile.guild_id, GuildId::new(1300863845429936139));
+
+    let profile = TrackingProfile::new(str_guild_id, int_user_id);
+    assert_eq!(profile.user_id, UserId::new(1300863845429936139));
+    assert_eq!(profile.guild_id, GuildId::new(1300863845429936139));
+  }
+
+  #[test]
+  fn test_privacy_macro() {
+    let profile = TrackingProfile::default().streak_privacy(Privacy::Private);
+    assert!(privacy!(Privacy::Private));
+    assert!(!(privacy!(Some(Privacy::Public), profile.streak.privacy)));
+    assert!(privacy!(None, profile.streak.privacy));
+  }
+}
@@ -0,0 +1,121 @@
+use chrono::{DateTime, Utc};
+use poise::serenity_prelude::{GuildId, UserId};
+use sqlx::postgres::{PgArguments, PgRow};
+use sqlx::query::{Query, QueryAs};
+use sqlx::{Error as SqlxError, FromRow, Postgres, Result as SqlxResult, Row};
+use ulid::Ulid;
+
+use crate::data::common;
+use crate::database::InsertQuery;
+use crate::pagination::{PageRow, PageType};
+
+/// A warning issued to a member by staff, optionally with a timeout.
+pub struct Warning {
+  pub id: String,
+  pub guild_id: GuildId,
+  pub user_id: UserId,
+  pub issued_by: UserId,
+  pub reason: String,
+  pub timeout_until: Option<DateTime<Utc>>,
+  pub message_link: Option<String>,
+  pub occurred_at: DateTime<Utc>,
+}
+
+impl Warning {
+  pub fn new(guild_id: GuildId, user_id: UserId, issued_by: UserId, reason: String) -> Self {
+    Self {
+      id: Ulid::new().to_string(),
+      guild_id,
+      user_id,
+      issued_by,
+      reason,
+      timeout_until: None,
+      message_link: None,
+      occurred_at: Utc::now(),
+    }
+  }
+
+  /// Sets when the timeout that came with the warning ends.
+  pub fn timeout_until(mut self, timeout_until: DateTime<Utc>) -> Self {
+    self.timeout_until = Some(timeout_until);
+    self
+  }
+
+  /// Sets the link to the log message for the warning.
+  pub fn message_link(mut self, message_link: String) -> Self {
+    self.message_link = Some(message_link);
+    self
+  }
+
+  /// Retrieves all [`Warning`]s for the specified `user_id`, newest first.
+  pub fn retrieve_all<'a>(
+    guild_id: GuildId,
+    user_id: UserId,
+  ) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT record_id, guild_id, user_id, issued_by, reason, timeout_until, message_link, occurred_at FROM warning WHERE guild_id = $1 AND user_id = $2 ORDER BY occurred_at DESC",
+    )
+    .bind(guild_id.to_string())
+    .bind(user_id.to_string())
+  }
+}
+
+impl InsertQuery for Warning {
+  /// Adds a [`Warning`] to the database.
+  fn insert_query(&self) -> Query<Postgres, PgArguments> {
+    query!(
+      "INSERT INTO warning (record_id, guild_id, user_id, issued_by, reason, timeout_until, message_link, occurred_at) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+      self.id,
+      self.guild_id.to_string(),
+      self.user_id.to_string(),
+      self.issued_by.to_string(),
+      self.reason,
+      self.timeout_until,
+      self.message_link,
+      self.occurred_at,
+    )
+  }
+}
+
+impl PageRow for Warning {
+  fn title(&self, page_type: PageType) -> String {
+    match page_type {
+      PageType::Standard => format!("Date: `{}`", self.occurred_at.format("%Y-%m-%d %H:%M")),
+      PageType::Alternate => format!("Date: `{}`", self.occurred_at.format("%e %B %Y %H:%M")),
+    }
+  }
+
+  fn body(&self) -> String {
+    let mut body = format!(
+      "**Reason:** {}\nIssued by <@{}>",
+      self.reason, self.issued_by
+    );
+    if let Some(timeout_until) = self.timeout_until {
+      body.push_str(&format!(
+        " · Timed out until <t:{}:f>",
+        timeout_until.timestamp()
+      ));
+    }
+    if let Some(message_link) = &self.message_link {
+      body.push_str(&format!("\n[Go to warning log]({message_link})"));
+    }
+    body.push_str(&format!("\n-# Warning ID: {}", self.id));
+
+    body
+  }
+}
+
+impl FromRow<'_, PgRow> for Warning {
+  fn from_row(row: &'_ PgRow) -> SqlxResult<Self, SqlxError> {
+    Ok(Self {
+      id: row.try_get("record_id")?,
+      guild_id: GuildId::new(common::decode_id_row(row, "guild_id")?),
+      user_id: UserId::new(common::decode_id_row(row, "user_id")?),
+      issued_by: UserId::new(common::decode_id_row(row, "issued_by")?),
+      reason: row.try_get("reason")?,
+      timeout_until: row.try_get("timeout_until")?,
+      message_link: row.try_get("message_link")?,
+      occurred_at: row.try_get("occurred_at")?,
+    })
+  }
+}
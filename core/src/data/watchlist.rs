@@ -0,0 +1,170 @@
+use poise::serenity_prelude::{GuildId, UserId};
+use regex::{Regex, RegexBuilder};
+use sqlx::postgres::{PgArguments, PgRow};
+use sqlx::query::{Query, QueryAs};
+use sqlx::{Error as SqlxError, FromRow, Postgres, Result as SqlxResult, Row};
+use ulid::Ulid;
+
+use crate::data::common;
+use crate::database::InsertQuery;
+use crate::pagination::{PageRow, PageType};
+
+/// Upper bound on the compiled size of a watchlist pattern, so that a single pattern can't
+/// slow down every message in the server.
+const PATTERN_SIZE_LIMIT: usize = 1 << 16;
+
+/// A term on a server's watchlist. Messages which match a term are flagged to staff.
+/// Plain terms match whole words or phrases, ignoring case. Regex terms are used as-is.
+pub struct WatchlistTerm {
+  pub id: String,
+  pub guild_id: GuildId,
+  pub pattern: String,
+  pub is_regex: bool,
+  pub added_by: UserId,
+}
+
+impl WatchlistTerm {
+  pub fn new(guild_id: GuildId, pattern: String, is_regex: bool, added_by: UserId) -> Self {
+    Self {
+      id: Ulid::new().to_string(),
+      guild_id,
+      pattern,
+      is_regex,
+      added_by,
+    }
+  }
+
+  /// Compiles the term into a case-insensitive [`Regex`].
+  pub fn matcher(&self) -> Result<Regex, regex::Error> {
+    let pattern = if self.is_regex {
+      self.pattern.clone()
+    } else {
+      // Word boundaries only apply next to word characters, so terms like "@everyone"
+      // still match
+      let is_word = |c: char| c.is_alphanumeric() || c == '_';
+      let start = if self.pattern.starts_with(is_word) {
+        r"\b"
+      } else {
+        ""
+      };
+      let end = if self.pattern.ends_with(is_word) {
+        r"\b"
+      } else {
+        ""
+      };
+      format!("{start}{}{end}", regex::escape(&self.pattern))
+    };
+
+    RegexBuilder::new(&pattern)
+      .case_insensitive(true)
+      .size_limit(PATTERN_SIZE_LIMIT)
+      .build()
+  }
+
+  /// Returns the first term which matches `content`, if any. Terms which fail to compile
+  /// are skipped.
+  pub fn first_match<'a>(terms: &'a [Self], content: &str) -> Option<&'a Self> {
+    terms.iter().find(|term| {
+      term
+        .matcher()
+        .is_ok_and(|matcher| matcher.is_match(content))
+    })
+  }
+
+  pub fn retrieve_all<'a>(guild_id: GuildId) -> QueryAs<'a, Postgres, Self, PgArguments> {
+    sqlx::query_as(
+      "SELECT record_id, guild_id, pattern, is_regex, added_by FROM watchlist_term WHERE guild_id = $1 ORDER BY pattern ASC",
+    )
+    .bind(guild_id.to_string())
+  }
+
+  pub fn remove(guild_id: GuildId, pattern: &str) -> Query<'_, Postgres, PgArguments> {
+    query!(
+      "DELETE FROM watchlist_term WHERE guild_id = $1 AND pattern = $2",
+      guild_id.to_string(),
+      pattern,
+    )
+  }
+}
+
+impl InsertQuery for WatchlistTerm {
+  /// Adds a [`WatchlistTerm`] to the database, unless the pattern is already on the
+  /// watchlist.
+  fn insert_query(&self) -> Query<Postgres, PgArguments> {
+    query!(
+      "INSERT INTO watchlist_term (record_id, guild_id, pattern, is_regex, added_by) VALUES ($1, $2, $3, $4, $5) ON CONFLICT (guild_id, pattern) DO NOTHING",
+      self.id,
+      self.guild_id.to_string(),
+      self.pattern,
+      self.is_regex,
+      self.added_by.to_string(),
+    )
+  }
+}
+
+impl PageRow for WatchlistTerm {
+  fn title(&self, _page_type: PageType) -> String {
+    format!("`{}`", self.pattern)
+  }
+
+  fn body(&self) -> String {
+    format!(
+      "{} · Added by <@{}>",
+      if self.is_regex {
+        "Regex"
+      } else {
+        "Word or phrase"
+      },
+      self.added_by
+    )
+  }
+}
+
+impl FromRow<'_, PgRow> for WatchlistTerm {
+  fn from_row(row: &'_ PgRow) -> SqlxResult<Self, SqlxError> {
+    Ok(Self {
+      id: row.try_get("record_id")?,
+      guild_id: GuildId::new(common::decode_id_row(row, "guild_id")?),
+      pattern: row.try_get("pattern")?,
+      is_regex: row.try_get("is_regex")?,
+      added_by: UserId::new(common::decode_id_row(row, "added_by")?),
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn term(pattern: &str, is_regex: bool) -> WatchlistTerm {
+    WatchlistTerm::new(
+      GuildId::new(123u64),
+      pattern.to_owned(),
+      is_regex,
+      UserId::new(456u64),
+    )
+  }
+
+  #[test]
+  fn test_first_match() {
+    let terms = [term("free nitro", false), term(r"disc[o0]rd\.gift", true)];
+
+    let Some(matched) = WatchlistTerm::first_match(&terms, "Get FREE Nitro here") else {
+      panic!("Expected a match");
+    };
+    assert_eq!(matched.pattern, "free nitro");
+
+    let Some(matched) = WatchlistTerm::first_match(&terms, "https://disc0rd.gift/abc") else {
+      panic!("Expected a match");
+    };
+    assert!(matched.is_regex);
+
+    // Plain terms only match whole words, and their special characters are literal
+    assert!(WatchlistTerm::first_match(&terms, "free nitrogen").is_none());
+    assert!(WatchlistTerm::first_match(&[term("a.b", false)], "axb").is_none());
+    assert!(WatchlistTerm::first_match(&[term("@everyone", false)], "hey @everyone").is_some());
+
+    // Invalid patterns are skipped rather than matching everything
+    assert!(WatchlistTerm::first_match(&[term("(", true)], "(").is_none());
+  }
+}
@@ -0,0 +1,180 @@
+use chrono::NaiveDate;
+use poise::serenity_prelude::{GuildId, UserId};
+use sqlx::postgres::{PgArguments, PgRow};
+use sqlx::query::{Query, QueryAs};
+use sqlx::{Error as SqlxError, FromRow, Postgres, Result as SqlxResult, Row};
+use ulid::Ulid;
+
+use crate::data::common;
+use crate::database::InsertQuery;
+
+/// Wellness measures a member has logged for a day, alongside their meditation. Each member
+/// has at most one record a day, with later logs replacing earlier ones.
+pub struct WellnessMetric {
+  pub id: String,
+  pub guild_id: GuildId,
+  pub user_id: UserId,
+  pub date: NaiveDate,
+  pub sleep_hours: f32,
+}
+
+/// A member's sleep and meditation minutes for a day. Days without logged sleep have no hours.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SleepDay {
+  pub date: NaiveDate,
+  pub hours: Option<f32>,
+  pub minutes: i64,
+}
+
+impl WellnessMetric {
+  pub fn new(guild_id: GuildId, user_id: UserId, date: NaiveDate, sleep_hours: f32) -> Self {
+    Self {
+      id: Ulid::new().to_string(),
+      guild_id,
+      user_id,
+      date,
+      sleep_hours,
+    }
+  }
+
+  /// Retrieves a member's sleep and meditation minutes for each of the `days` days up to and
+  /// including `today`, oldest first. Meditation minutes are counted on the day they were
+  /// recorded in the member's UTC offset, given in minutes.
+  pub fn retrieve_sleep_days<'a>(
+    guild_id: GuildId,
+    user_id: UserId,
+    today: NaiveDate,
+    days: i32,
+    utc_offset: i16,
+  ) -> QueryAs<'a, Postgres, SleepDay, PgArguments> {
+    sqlx::query_as(
+      "
+        WITH days AS (
+          SELECT generate_series($3::date - ($4 - 1), $3::date, INTERVAL '1 day')::date AS day
+        ),
+        minutes AS (
+          SELECT (occurred_at AT TIME ZONE 'UTC' + $5 * INTERVAL '1 minute')::date AS day, SUM(meditation_minutes) AS minutes
+          FROM meditation
+          WHERE guild_id = $1 AND user_id = $2
+            AND (occurred_at AT TIME ZONE 'UTC' + $5 * INTERVAL '1 minute')::date > $3::date - $4
+          GROUP BY 1
+        )
+        SELECT days.day, wellness_metrics.sleep_hours, COALESCE(minutes.minutes, 0) AS minutes
+        FROM days
+        LEFT JOIN wellness_metrics ON wellness_metrics.guild_id = $1 AND wellness_metrics.user_id = $2 AND wellness_metrics.metric_date = days.day
+        LEFT JOIN minutes ON minutes.day = days.day
+        ORDER BY days.day ASC
+      ",
+    )
+    .bind(guild_id.to_string())
+    .bind(user_id.to_string())
+    .bind(today)
+    .bind(days)
+    .bind(i32::from(utc_offset))
+  }
+}
+
+impl InsertQuery for WellnessMetric {
+  /// Adds a [`WellnessMetric`] to the database, replacing the member's sleep for the same day.
+  fn insert_query(&self) -> Query<Postgres, PgArguments> {
+    query!(
+      "INSERT INTO wellness_metrics (record_id, guild_id, user_id, metric_date, sleep_hours) VALUES ($1, $2, $3, $4, $5) ON CONFLICT (guild_id, user_id, metric_date) DO UPDATE SET sleep_hours = EXCLUDED.sleep_hours, created_at = CURRENT_TIMESTAMP",
+      self.id,
+      self.guild_id.to_string(),
+      self.user_id.to_string(),
+      self.date,
+      self.sleep_hours,
+    )
+  }
+}
+
+impl FromRow<'_, PgRow> for SleepDay {
+  fn from_row(row: &'_ PgRow) -> SqlxResult<Self, SqlxError> {
+    Ok(Self {
+      date: row.try_get("day")?,
+      hours: row.try_get("sleep_hours")?,
+      minutes: row.try_get("minutes")?,
+    })
+  }
+}
+
+/// The correlation between hours slept and meditation minutes on days with logged sleep, or
+/// [`None`] if there are fewer than three such days or either doesn't vary.
+pub fn correlation(days: &[SleepDay]) -> Option<f64> {
+  #[allow(clippy::cast_precision_loss)]
+  let points: Vec<(f64, f64)> = days
+    .iter()
+    .filter_map(|day| {
+      day
+        .hours
+        .map(|hours| (f64::from(hours), day.minutes as f64))
+    })
+    .collect();
+
+  common::correlation(&points)
+}
+
+/// Parses a number of hours slept, such as `7.5`, `7h 30m`, or `7:30`, returning [`None`]
+/// if it isn't between 0 and 24 hours.
+pub fn parse_sleep_hours(input: &str) -> Option<f32> {
+  let input = input.trim().to_lowercase();
+
+  let hours = if let Some((hours, minutes)) = input.split_once(':') {
+    let hours: u8 = hours.trim().parse().ok()?;
+    let minutes: u8 = minutes
+      .trim()
+      .parse()
+      .ok()
+      .filter(|minutes| *minutes < 60)?;
+    f32::from(hours) + f32::from(minutes) / 60.0
+  } else if let Some((hours, minutes)) = input.split_once('h') {
+    let hours: u8 = hours.trim().parse().ok()?;
+    let minutes = minutes.trim().trim_end_matches('m').trim();
+    let minutes: u8 = if minutes.is_empty() {
+      0
+    } else {
+      minutes.parse().ok().filter(|minutes| *minutes < 60)?
+    };
+    f32::from(hours) + f32::from(minutes) / 60.0
+  } else {
+    input.parse().ok()?
+  };
+
+  (0.0..=24.0).contains(&hours).then_some(hours)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn day(day: u32, hours: Option<f32>, minutes: i64) -> SleepDay {
+    SleepDay {
+      date: NaiveDate::from_ymd_opt(2024, 11, day).unwrap_or_default(),
+      hours,
+      minutes,
+    }
+  }
+
+  #[test]
+  fn test_correlation() {
+    let days = [
+      day(1, Some(6.0), 0),
+      day(2, None, 60),
+      day(3, Some(7.0), 10),
+      day(4, Some(8.0), 20),
+    ];
+    assert!(correlation(&days).is_some_and(|c| (c - 1.0).abs() < 1e-9));
+    assert_eq!(correlation(&days[..2]), None);
+  }
+
+  #[test]
+  fn test_parse_sleep_hours() {
+    assert_eq!(parse_sleep_hours("7.5"), Some(7.5));
+    assert_eq!(parse_sleep_hours("7:30"), Some(7.5));
+    assert_eq!(parse_sleep_hours("7h 30m"), Some(7.5));
+    assert_eq!(parse_sleep_hours("8h"), Some(8.0));
+    assert_eq!(parse_sleep_hours("25"), None);
+    assert_eq!(parse_sleep_hours("7:75"), None);
+    assert_eq!(parse_sleep_hours("lots"), None);
+  }
+}
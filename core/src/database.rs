@@ -0,0 +1,8688 @@
+use std::collections::HashSet;
+use std::env;
+use std::ops::RangeInclusive;
+use std::pin::Pin;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Datelike, Duration as ChronoDuration, DurationRound, Months};
+use chrono::{NaiveDate, TimeDelta, Timelike, Utc};
+use futures::{stream::Stream, StreamExt};
+use log::{info, warn};
+use pgvector::Vector;
+use poise::serenity_prelude::{ChannelId, GuildId, MessageId, UserId};
+use sqlx::migrate::Migrate;
+use sqlx::pool::PoolConnection;
+use sqlx::postgres::{PgArguments, PgRow};
+use sqlx::query::{Query, QueryAs};
+use sqlx::{Error as SqlxError, FromRow, PgPool, Postgres, Transaction};
+use tokio::time;
+
+use crate::data::account_link::{hash_secret, ApiToken, LinkCode};
+use crate::data::ai_usage::{AiUsage, DailyAiUsage};
+use crate::data::backup::BackupTable;
+use crate::data::bookmark::Bookmark;
+use crate::data::calendar::{CalendarFeed, DiscordEvent};
+use crate::data::common::{Aggregate, Exists, MaterializedView, Migration, ViewType};
+use crate::data::community_event::{AttendanceSource, CommunityEvent, OccurrenceStats};
+use crate::data::course::{Course, Enrollment, EnrollmentCode};
+use crate::data::dedication::Dedication;
+use crate::data::erase::Erase;
+use crate::data::feed::FeedSubscription;
+use crate::data::goal::Goal;
+use crate::data::guild_emoji::{EmojiName, GuildEmoji};
+use crate::data::guild_feature::GuildFeature;
+use crate::data::guild_settings::GuildSettings;
+use crate::data::intention::{
+  IntentionReminder, IntentionSubscription, PracticePrompt, PromptCategory,
+};
+use crate::data::maintenance::Maintenance;
+use crate::data::meditation::Meditation;
+use crate::data::mentorship::{MentorSignup, Mentorship};
+use crate::data::milestone::Milestone;
+use crate::data::mood_checkin::{MoodCheckin, MoodDay};
+use crate::data::pending_interaction::{InteractionFlow, PendingInteraction};
+use crate::data::pick_winner;
+use crate::data::poll::{Poll, PollVote};
+use crate::data::profile_import::{ProfileImport, ProfileLink};
+use crate::data::quote::Quote;
+use crate::data::recurring_post::RecurringPost;
+use crate::data::report::{Report, ReportStatus};
+use crate::data::retention::{RetentionPolicy, RetentionSummary};
+use crate::data::retreat::{Retreat, RetreatStatus};
+use crate::data::scheduled_announcement::ScheduledAnnouncement;
+use crate::data::session_webhook::{InvalidSession, SessionPayload, WebhookToken, MAX_TOKENS};
+use crate::data::star_message::StarMessage;
+use crate::data::stats::{
+  ByInterval, ByPeriod, ByRecurringPeriod, MemberProgress, Streak, Timeframe as TimeframeStats,
+  User,
+};
+use crate::data::stats::{Global, Guild, Improvement, LeaderboardType, LeaderboardUser};
+use crate::data::stats::{MeditationCountByDay, SortBy};
+use crate::data::steam_key::{KeyOffer, Recipient, SteamKey};
+use crate::data::streak_repair::{RepairStatus, StreakRepair};
+use crate::data::study_group::{StudyGroup, StudyGroupMember};
+use crate::data::suggestion::{Suggestion, SuggestionStatus, SuggestionVotes};
+use crate::data::sutta::SuttaPassage;
+use crate::data::talk::Talk;
+use crate::data::term::{Term, VectorSearch};
+use crate::data::ticket::{Ticket, TicketMessage};
+use crate::data::tracking_profile::{DirectoryEntry, StreakGuardReminder, TrackingProfile};
+use crate::data::warning::Warning;
+use crate::data::watchlist::WatchlistTerm;
+use crate::data::wellness_metric::{SleepDay, WellnessMetric};
+use crate::ics::{self, Calendar};
+use crate::time::{ChallengeTimeframe, Timeframe};
+
+/// How many months after the current one calendar feeds include monthly challenges for.
+const CALENDAR_CHALLENGE_MONTHS: u32 = 2;
+
+/// Runs queries against Bloom's database. Connections are pooled, and most queries are
+/// associated functions run in a transaction from [`DatabaseHandler::start_transaction`].
+#[allow(clippy::module_name_repetitions)]
+pub struct DatabaseHandler {
+  pool: sqlx::PgPool,
+}
+
+pub(crate) trait InsertQuery {
+  fn insert_query(&self) -> Query<Postgres, PgArguments>;
+}
+
+pub(crate) trait UpdateQuery {
+  fn update_query(&self) -> Query<Postgres, PgArguments>;
+}
+
+pub(crate) trait DeleteQuery {
+  fn delete_query<'a>(
+    guild_id: GuildId,
+    unique_id: impl Into<String>,
+  ) -> Query<'a, Postgres, PgArguments>;
+}
+
+pub(crate) trait ExistsQuery {
+  type Item<'a>;
+
+  fn exists_query<'a, T: for<'r> FromRow<'r, PgRow>>(
+    guild_id: GuildId,
+    item: Self::Item<'a>,
+  ) -> QueryAs<'a, Postgres, T, PgArguments>;
+}
+
+impl DatabaseHandler {
+  pub fn from_pool(pool: PgPool) -> Self {
+    Self { pool }
+  }
+
+  pub async fn new() -> Result<Self> {
+    let database_url =
+      env::var("DATABASE_URL").with_context(|| "Missing DATABASE_URL environment variable")?;
+    // let pool = sqlx::PgPool::connect(&database_url).await?;
+    let max_retries = 5;
+    let mut attempts = 0;
+
+    loop {
+      let pool = match PgPool::connect(&database_url).await {
+        Ok(pool) => pool,
+        Err(e) => {
+          if attempts >= max_retries {
+            return Err(e.into());
+          }
+
+          // Retry if error is sqlx::Error::Io
+          if let sqlx::Error::Io(io_error) = e {
+            attempts += 1;
+            // Log warning
+            warn!(
+              "Error establishing a database connection ({}): retry attempt {} of {}",
+              io_error.kind(),
+              attempts,
+              max_retries
+            );
+            // Wait before retrying
+            time::sleep(Duration::from_secs(60)).await;
+            continue;
+          }
+
+          // If it's a different kind of error, we might want to return it immediately
+          return Err(e.into());
+        }
+      };
+
+      sqlx::migrate!("./migrations").run(&pool).await?;
+
+      info!(target: "bloombot::database", "Successfully applied migrations.");
+
+      return Ok(Self { pool });
+    }
+  }
+
+  /// Returns the version and description of each migration which hasn't been applied to
+  /// the database. Migrations are applied on startup, so any pending migrations were added
+  /// after the bot was started or failed to apply.
+  pub async fn get_pending_migrations(&self) -> Result<Vec<String>> {
+    let mut connection = self.get_connection().await?;
+    let applied = connection
+      .list_applied_migrations()
+      .await?
+      .into_iter()
+      .map(|migration| migration.version)
+      .collect::<HashSet<_>>();
+
+    Ok(
+      sqlx::migrate!("./migrations")
+        .iter()
+        .filter(|migration| {
+          !migration.migration_type.is_down_migration() && !applied.contains(&migration.version)
+        })
+        .map(|migration| format!("{} {}", migration.version, migration.description))
+        .collect(),
+    )
+  }
+
+  pub async fn get_connection(&self) -> Result<PoolConnection<Postgres>> {
+    Ok(self.pool.acquire().await?)
+  }
+
+  pub async fn get_connection_with_retry(
+    &self,
+    max_retries: usize,
+  ) -> Result<PoolConnection<Postgres>> {
+    let mut attempts = 0;
+
+    loop {
+      match self.get_connection().await {
+        Ok(connection) => return Ok(connection),
+        Err(e) => {
+          if attempts >= max_retries {
+            return Err(e);
+          }
+
+          // Check if error is sqlx::Error::Io
+          if let Some(sqlx::Error::Io(io_error)) = e.downcast_ref::<sqlx::Error>() {
+            // Retry if connection was reset
+            if io_error.kind() == std::io::ErrorKind::ConnectionReset {
+              attempts += 1;
+              // Wait for a moment before retrying
+              time::sleep(Duration::from_secs(1)).await;
+              continue;
+            }
+          }
+
+          // If it's a different kind of error, we might want to return it immediately
+          return Err(e);
+        }
+      }
+    }
+  }
+
+  pub async fn start_transaction(&self) -> Result<Transaction<'_, Postgres>> {
+    Ok(self.pool.begin().await?)
+  }
+
+  pub async fn start_transaction_with_retry(
+    &self,
+    max_retries: usize,
+  ) -> Result<Transaction<'_, Postgres>> {
+    let mut attempts = 0;
+
+    loop {
+      match self.start_transaction().await {
+        Ok(transaction) => return Ok(transaction),
+        Err(e) => {
+          if attempts >= max_retries {
+            return Err(e);
+          }
+
+          // Check if error is sqlx::Error::Io
+          if let Some(sqlx::Error::Io(io_error)) = e.downcast_ref::<sqlx::Error>() {
+            // Retry if connection was reset
+            if io_error.kind() == std::io::ErrorKind::ConnectionReset {
+              attempts += 1;
+              // Wait for a moment before retrying
+              time::sleep(Duration::from_secs(1)).await;
+              continue;
+            }
+          }
+
+          // If it's a different kind of error, we might want to return it immediately
+          return Err(e);
+        }
+      }
+    }
+  }
+
+  pub async fn commit_transaction(transaction: Transaction<'_, Postgres>) -> Result<()> {
+    transaction.commit().await?;
+    Ok(())
+  }
+
+  /// This function is not technically necessary, as the transaction will be rolled back when dropped.
+  /// However, for readability, it is recommended to call this function when you want to rollback a transaction.
+  pub async fn rollback_transaction(transaction: Transaction<'_, Postgres>) -> Result<()> {
+    transaction.rollback().await?;
+    Ok(())
+  }
+
+  pub async fn add_tracking_profile(
+    transaction: &mut Transaction<'_, Postgres>,
+    tracking_profile: &TrackingProfile,
+  ) -> Result<()> {
+    tracking_profile
+      .insert_query()
+      .execute(&mut **transaction)
+      .await?;
+
+    Ok(())
+  }
+
+  pub async fn update_tracking_profile(
+    transaction: &mut Transaction<'_, Postgres>,
+    tracking_profile: &TrackingProfile,
+  ) -> Result<()> {
+    tracking_profile
+      .update_query()
+      .execute(&mut **transaction)
+      .await?;
+
+    Ok(())
+  }
+
+  /// Returns the members of a guild who have opted in to `/directory`, optionally filtered
+  /// by tradition or favorite teacher.
+  pub async fn get_directory(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    search: Option<&str>,
+  ) -> Result<Vec<DirectoryEntry>> {
+    Ok(
+      DirectoryEntry::search(*guild_id, search)
+        .fetch_all(&mut **transaction)
+        .await?,
+    )
+  }
+
+  pub async fn get_streak_guard_reminders(
+    transaction: &mut Transaction<'_, Postgres>,
+    now: &DateTime<Utc>,
+  ) -> Result<Vec<StreakGuardReminder>> {
+    Ok(
+      StreakGuardReminder::retrieve_due(now)
+        .fetch_all(&mut **transaction)
+        .await?,
+    )
+  }
+
+  pub async fn mark_streak_guard_sent(
+    transaction: &mut Transaction<'_, Postgres>,
+    reminder: &StreakGuardReminder,
+  ) -> Result<()> {
+    reminder.mark_sent().execute(&mut **transaction).await?;
+
+    Ok(())
+  }
+
+  pub async fn remove_tracking_profile(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    user_id: &UserId,
+  ) -> Result<()> {
+    TrackingProfile::delete_query(*guild_id, user_id.to_string())
+      .execute(&mut **transaction)
+      .await?;
+
+    Ok(())
+  }
+
+  pub async fn migrate_tracking_profile(
+    transaction: &mut Transaction<'_, Postgres>,
+    migration: &Migration,
+  ) -> Result<()> {
+    migration.update_query().execute(&mut **transaction).await?;
+
+    Ok(())
+  }
+
+  pub async fn get_tracking_profile(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    user_id: &UserId,
+  ) -> Result<Option<TrackingProfile>> {
+    Ok(
+      TrackingProfile::retrieve(*guild_id, *user_id)
+        .fetch_optional(&mut **transaction)
+        .await?,
+    )
+  }
+
+  pub async fn add_steamkey_recipient(
+    transaction: &mut Transaction<'_, Postgres>,
+    recipient: &Recipient,
+  ) -> Result<()> {
+    recipient.insert_query().execute(&mut **transaction).await?;
+
+    Ok(())
+  }
+
+  pub async fn update_steamkey_recipient(
+    transaction: &mut Transaction<'_, Postgres>,
+    recipient: &Recipient,
+  ) -> Result<()> {
+    recipient.update_query().execute(&mut **transaction).await?;
+
+    Ok(())
+  }
+
+  pub async fn remove_steamkey_recipient(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    user_id: &UserId,
+  ) -> Result<()> {
+    Recipient::delete_query(*guild_id, user_id.to_string())
+      .execute(&mut **transaction)
+      .await?;
+
+    Ok(())
+  }
+
+  pub async fn steamkey_recipient_exists(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    user_id: &UserId,
+  ) -> Result<bool> {
+    Ok(
+      Recipient::exists_query::<Exists>(*guild_id, *user_id)
+        .fetch_one(&mut **transaction)
+        .await?
+        .exists,
+    )
+  }
+
+  pub async fn get_steamkey_recipient(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    user_id: &UserId,
+  ) -> Result<Option<Recipient>> {
+    Ok(
+      Recipient::retrieve_one(*guild_id, *user_id)
+        .fetch_optional(&mut **transaction)
+        .await?,
+    )
+  }
+
+  pub async fn get_steamkey_recipients(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+  ) -> Result<Vec<Recipient>> {
+    Ok(
+      Recipient::retrieve_all(*guild_id)
+        .fetch_all(&mut **transaction)
+        .await?,
+    )
+  }
+
+  pub async fn record_steamkey_receipt(
+    connection: &mut PoolConnection<Postgres>,
+    guild_id: &GuildId,
+    user_id: &UserId,
+  ) -> Result<()> {
+    let exists = Recipient::exists_query::<Exists>(*guild_id, *user_id)
+      .fetch_one(&mut **connection)
+      .await?
+      .exists;
+
+    Recipient::record_win(*guild_id, *user_id, exists)
+      .execute(&mut **connection)
+      .await?;
+
+    Ok(())
+  }
+
+  pub async fn add_bookmark(
+    transaction: &mut Transaction<'_, Postgres>,
+    bookmark: &Bookmark,
+  ) -> Result<()> {
+    bookmark.insert_query().execute(&mut **transaction).await?;
+
+    Ok(())
+  }
+
+  /// Adds a [`Dedication`], returning the number of rows affected. Returns `0` if the
+  /// session has already been dedicated.
+  pub async fn add_dedication(
+    transaction: &mut Transaction<'_, Postgres>,
+    dedication: &Dedication,
+  ) -> Result<u64> {
+    Ok(
+      dedication
+        .insert_query()
+        .execute(&mut **transaction)
+        .await?
+        .rows_affected(),
+    )
+  }
+
+  pub async fn get_dedications_received(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    user_id: &UserId,
+  ) -> Result<Vec<Dedication>> {
+    Ok(
+      Dedication::received(*guild_id, *user_id)
+        .fetch_all(&mut **transaction)
+        .await?,
+    )
+  }
+
+  pub async fn get_dedications_given(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    user_id: &UserId,
+  ) -> Result<Vec<Dedication>> {
+    Ok(
+      Dedication::given(*guild_id, *user_id)
+        .fetch_all(&mut **transaction)
+        .await?,
+    )
+  }
+
+  pub async fn update_bookmark(
+    transaction: &mut Transaction<'_, Postgres>,
+    bookmark: &Bookmark,
+  ) -> Result<()> {
+    bookmark.update_query().execute(&mut **transaction).await?;
+
+    Ok(())
+  }
+
+  pub async fn get_bookmark_by_link(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: &UserId,
+    link: &str,
+  ) -> Result<Option<Bookmark>> {
+    Ok(
+      Bookmark::retrieve_by_link(*user_id, link)
+        .fetch_optional(&mut **transaction)
+        .await?,
+    )
+  }
+
+  pub async fn remove_bookmark(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: Option<GuildId>,
+    user_id: &UserId,
+    bookmark_id: &str,
+  ) -> Result<u64> {
+    Ok(
+      Bookmark::remove(guild_id, *user_id, bookmark_id)
+        .execute(&mut **transaction)
+        .await?
+        .rows_affected(),
+    )
+  }
+
+  pub async fn get_bookmarks(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: Option<GuildId>,
+    user_id: &UserId,
+  ) -> Result<Vec<Bookmark>> {
+    Ok(
+      Bookmark::retrieve_all(guild_id, *user_id)
+        .fetch_all(&mut **transaction)
+        .await?,
+    )
+  }
+
+  pub async fn search_bookmarks(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: Option<GuildId>,
+    user_id: &UserId,
+    keyword: &str,
+  ) -> Result<Vec<Bookmark>> {
+    Ok(
+      Bookmark::search(guild_id, *user_id, keyword)
+        .fetch_all(&mut **transaction)
+        .await?,
+    )
+  }
+
+  pub async fn get_bookmark_count(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: Option<GuildId>,
+    user_id: &UserId,
+  ) -> Result<u64> {
+    Ok(
+      Bookmark::user_total::<Aggregate>(guild_id, *user_id)
+        .fetch_one(&mut **transaction)
+        .await?
+        .count,
+    )
+  }
+
+  pub async fn add_erase(transaction: &mut Transaction<'_, Postgres>, erase: &Erase) -> Result<()> {
+    erase.insert_query().execute(&mut **transaction).await?;
+
+    Ok(())
+  }
+
+  pub async fn get_erases(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    user_id: &UserId,
+  ) -> Result<Vec<Erase>> {
+    Ok(
+      Erase::retrieve_all(*guild_id, *user_id)
+        .fetch_all(&mut **transaction)
+        .await?,
+    )
+  }
+
+  pub async fn add_warning(
+    transaction: &mut Transaction<'_, Postgres>,
+    warning: &Warning,
+  ) -> Result<()> {
+    warning.insert_query().execute(&mut **transaction).await?;
+
+    Ok(())
+  }
+
+  pub async fn get_warnings(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    user_id: &UserId,
+  ) -> Result<Vec<Warning>> {
+    Ok(
+      Warning::retrieve_all(*guild_id, *user_id)
+        .fetch_all(&mut **transaction)
+        .await?,
+    )
+  }
+
+  pub async fn add_meditation_entry(
+    transaction: &mut Transaction<'_, Postgres>,
+    meditation_entry: &Meditation,
+  ) -> Result<()> {
+    meditation_entry
+      .insert_query()
+      .execute(&mut **transaction)
+      .await?;
+
+    Ok(())
+  }
+
+  /// Adds a mood check-in, replacing the member's check-in for the same day.
+  pub async fn add_mood_checkin(
+    transaction: &mut Transaction<'_, Postgres>,
+    checkin: &MoodCheckin,
+  ) -> Result<()> {
+    checkin.insert_query().execute(&mut **transaction).await?;
+
+    Ok(())
+  }
+
+  pub async fn get_mood_days(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    user_id: &UserId,
+    today: NaiveDate,
+    days: i32,
+    utc_offset: i16,
+  ) -> Result<Vec<MoodDay>> {
+    Ok(
+      MoodCheckin::retrieve_days(*guild_id, *user_id, today, days, utc_offset)
+        .fetch_all(&mut **transaction)
+        .await?,
+    )
+  }
+
+  /// Logs the hours a member slept, replacing anything logged for the same day.
+  pub async fn add_wellness_metric(
+    transaction: &mut Transaction<'_, Postgres>,
+    metric: &WellnessMetric,
+  ) -> Result<()> {
+    metric.insert_query().execute(&mut **transaction).await?;
+
+    Ok(())
+  }
+
+  pub async fn get_sleep_days(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    user_id: &UserId,
+    today: NaiveDate,
+    days: i32,
+    utc_offset: i16,
+  ) -> Result<Vec<SleepDay>> {
+    Ok(
+      WellnessMetric::retrieve_sleep_days(*guild_id, *user_id, today, days, utc_offset)
+        .fetch_all(&mut **transaction)
+        .await?,
+    )
+  }
+
+  pub async fn add_meditation_entry_batch(
+    transaction: &mut Transaction<'_, Postgres>,
+    batch_query: &str,
+  ) -> Result<u64> {
+    Ok(
+      sqlx::query(batch_query)
+        .execute(&mut **transaction)
+        .await?
+        .rows_affected(),
+    )
+  }
+
+  pub async fn update_meditation_entry(
+    transaction: &mut Transaction<'_, Postgres>,
+    meditation_entry: &Meditation,
+  ) -> Result<()> {
+    meditation_entry
+      .update_query()
+      .execute(&mut **transaction)
+      .await?;
+
+    Ok(())
+  }
+
+  pub async fn remove_meditation_entry(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    meditation_id: &str,
+  ) -> Result<()> {
+    Meditation::delete_query(*guild_id, meditation_id)
+      .execute(&mut **transaction)
+      .await?;
+
+    Ok(())
+  }
+
+  pub async fn reset_user_meditation_entries(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    user_id: &UserId,
+  ) -> Result<()> {
+    Meditation::remove_all(*guild_id, *user_id)
+      .execute(&mut **transaction)
+      .await?;
+
+    Ok(())
+  }
+
+  pub async fn migrate_meditation_entries(
+    transaction: &mut Transaction<'_, Postgres>,
+    migration: &Migration,
+  ) -> Result<()> {
+    migration.update_query().execute(&mut **transaction).await?;
+
+    Ok(())
+  }
+
+  pub async fn get_meditation_entry(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    meditation_id: &str,
+  ) -> Result<Option<Meditation>> {
+    Ok(
+      Meditation::full_entry(*guild_id, meditation_id)
+        .fetch_optional(&mut **transaction)
+        .await?,
+    )
+  }
+
+  pub async fn get_latest_meditation_entry(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    user_id: &UserId,
+  ) -> Result<Option<Meditation>> {
+    Ok(
+      Meditation::latest_entry(*guild_id, *user_id)
+        .fetch_optional(&mut **transaction)
+        .await?,
+    )
+  }
+
+  pub async fn get_user_meditation_entries(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    user_id: &UserId,
+  ) -> Result<Vec<Meditation>> {
+    Ok(
+      Meditation::user_entries(*guild_id, *user_id)
+        .fetch_all(&mut **transaction)
+        .await?,
+    )
+  }
+
+  pub async fn get_user_meditation_sum(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    user_id: &UserId,
+  ) -> Result<i64> {
+    Ok(
+      Meditation::user_sum::<Aggregate>(*guild_id, *user_id)
+        .fetch_one(&mut **transaction)
+        .await?
+        .sum,
+    )
+  }
+
+  pub async fn get_member_progress(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+  ) -> Result<Vec<MemberProgress>> {
+    Ok(
+      MemberProgress::guild(*guild_id)
+        .fetch_all(&mut **transaction)
+        .await?,
+    )
+  }
+
+  pub async fn get_user_meditation_count(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    user_id: &UserId,
+  ) -> Result<u64> {
+    Ok(
+      Meditation::user_count::<Aggregate>(*guild_id, *user_id)
+        .fetch_one(&mut **transaction)
+        .await?
+        .count,
+    )
+  }
+
+  pub async fn get_guild_meditation_sum(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+  ) -> Result<i64> {
+    Ok(
+      Meditation::guild_sum::<Aggregate>(*guild_id)
+        .fetch_one(&mut **transaction)
+        .await?
+        .sum,
+    )
+  }
+
+  pub async fn get_guild_meditation_count(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+  ) -> Result<u64> {
+    Ok(
+      Meditation::guild_count::<Aggregate>(*guild_id)
+        .fetch_one(&mut **transaction)
+        .await?
+        .count,
+    )
+  }
+
+  pub fn get_winner_candidates<'a>(
+    conn: &'a mut PoolConnection<Postgres>,
+    start_date: &'a DateTime<Utc>,
+    end_date: &'a DateTime<Utc>,
+    guild_id: &'a GuildId,
+  ) -> impl Stream<Item = Result<UserId>> + 'a {
+    let stream: Pin<Box<dyn Stream<Item = Result<Meditation, SqlxError>> + Send>> =
+      pick_winner::retrieve_candidate(*guild_id, start_date, end_date).fetch(&mut **conn);
+
+    stream.map(|row| Ok(row?.user_id))
+  }
+
+  pub async fn get_winner_candidate_meditation_sum(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    user_id: &UserId,
+    start_date: &DateTime<Utc>,
+    end_date: &DateTime<Utc>,
+  ) -> Result<i64> {
+    Ok(
+      pick_winner::candidate_sum::<Aggregate>(*guild_id, *user_id, start_date, end_date)
+        .fetch_one(&mut **transaction)
+        .await?
+        .sum,
+    )
+  }
+
+  pub async fn get_winner_candidate_meditation_count(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    user_id: &UserId,
+    start_date: &DateTime<Utc>,
+    end_date: &DateTime<Utc>,
+  ) -> Result<u64> {
+    Ok(
+      pick_winner::candidate_count::<Aggregate>(*guild_id, *user_id, start_date, end_date)
+        .fetch_one(&mut **transaction)
+        .await?
+        .count,
+    )
+  }
+
+  /// Records a milestone reached by a guild, returning the number of milestones added. A
+  /// result of `0` means the milestone has already been recorded.
+  pub async fn record_guild_milestone(
+    transaction: &mut Transaction<'_, Postgres>,
+    milestone: &Milestone,
+  ) -> Result<u64> {
+    Ok(
+      milestone
+        .insert_query()
+        .execute(&mut **transaction)
+        .await?
+        .rows_affected(),
+    )
+  }
+
+  pub async fn add_goal(transaction: &mut Transaction<'_, Postgres>, goal: &Goal) -> Result<()> {
+    goal.insert_query().execute(&mut **transaction).await?;
+
+    Ok(())
+  }
+
+  pub async fn get_active_goal(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+  ) -> Result<Option<Goal>> {
+    Ok(
+      Goal::retrieve_active(*guild_id)
+        .fetch_optional(&mut **transaction)
+        .await?,
+    )
+  }
+
+  pub async fn get_active_goals(transaction: &mut Transaction<'_, Postgres>) -> Result<Vec<Goal>> {
+    Ok(
+      Goal::retrieve_all_active()
+        .fetch_all(&mut **transaction)
+        .await?,
+    )
+  }
+
+  /// Returns the total minutes tracked in a guild during a [`Goal`].
+  pub async fn get_goal_progress(
+    transaction: &mut Transaction<'_, Postgres>,
+    goal: &Goal,
+  ) -> Result<i64> {
+    Ok(
+      Goal::progress::<Aggregate>(goal.guild_id, &goal.starts_at, &goal.ends_at)
+        .fetch_one(&mut **transaction)
+        .await?
+        .sum,
+    )
+  }
+
+  pub async fn mark_goal_updated(
+    transaction: &mut Transaction<'_, Postgres>,
+    goal_id: &str,
+    updated_at: &DateTime<Utc>,
+  ) -> Result<()> {
+    Goal::mark_updated(goal_id, updated_at)
+      .execute(&mut **transaction)
+      .await?;
+
+    Ok(())
+  }
+
+  /// Marks a goal as completed, returning the number of goals affected. A result of `0`
+  /// means the goal has already been closed.
+  pub async fn complete_goal(
+    transaction: &mut Transaction<'_, Postgres>,
+    goal_id: &str,
+    completed_at: &DateTime<Utc>,
+  ) -> Result<u64> {
+    Ok(
+      Goal::complete(goal_id, completed_at)
+        .execute(&mut **transaction)
+        .await?
+        .rows_affected(),
+    )
+  }
+
+  /// Closes a goal without completing it, returning the number of goals affected. A result
+  /// of `0` means the goal has already been closed.
+  pub async fn close_goal(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    goal_id: &str,
+  ) -> Result<u64> {
+    Ok(
+      Goal::close(*guild_id, goal_id)
+        .execute(&mut **transaction)
+        .await?
+        .rows_affected(),
+    )
+  }
+
+  pub async fn add_community_event(
+    transaction: &mut Transaction<'_, Postgres>,
+    event: &CommunityEvent,
+  ) -> Result<()> {
+    event.insert_query().execute(&mut **transaction).await?;
+
+    Ok(())
+  }
+
+  pub async fn get_community_event(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    title: &str,
+  ) -> Result<Option<CommunityEvent>> {
+    Ok(
+      CommunityEvent::retrieve_by_title(*guild_id, title)
+        .fetch_optional(&mut **transaction)
+        .await?,
+    )
+  }
+
+  pub async fn get_community_events_in_channel(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    channel_id: &ChannelId,
+  ) -> Result<Vec<CommunityEvent>> {
+    Ok(
+      CommunityEvent::retrieve_in_channel(*guild_id, *channel_id)
+        .fetch_all(&mut **transaction)
+        .await?,
+    )
+  }
+
+  pub async fn get_community_event_titles(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    partial: &str,
+  ) -> Result<Vec<String>> {
+    Ok(
+      CommunityEvent::retrieve_titles(*guild_id, partial)
+        .fetch_all(&mut **transaction)
+        .await?
+        .into_iter()
+        .map(|event| event.title)
+        .collect(),
+    )
+  }
+
+  pub async fn get_calendar_feed(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+  ) -> Result<Option<CalendarFeed>> {
+    Ok(
+      CalendarFeed::retrieve(*guild_id)
+        .fetch_optional(&mut **transaction)
+        .await?,
+    )
+  }
+
+  /// Saves a guild's [`CalendarFeed`], replacing its previous token.
+  pub async fn add_calendar_feed(
+    transaction: &mut Transaction<'_, Postgres>,
+    feed: &CalendarFeed,
+  ) -> Result<()> {
+    feed.insert_query().execute(&mut **transaction).await?;
+
+    Ok(())
+  }
+
+  /// Builds the calendar for the feed with the specified token, with the guild's community
+  /// events, Discord scheduled events, and upcoming monthly challenges. Returns [`None`] if
+  /// no feed has the token, such as after it has been reset.
+  ///
+  /// Render the calendar with [`Calendar::render`] to serve the feed.
+  pub async fn get_calendar(
+    transaction: &mut Transaction<'_, Postgres>,
+    token: &str,
+    now: DateTime<Utc>,
+  ) -> Result<Option<Calendar>> {
+    let Some(feed) = CalendarFeed::retrieve_by_token(token)
+      .fetch_optional(&mut **transaction)
+      .await?
+    else {
+      return Ok(None);
+    };
+
+    let community_events = CommunityEvent::retrieve_all(feed.guild_id)
+      .fetch_all(&mut **transaction)
+      .await?;
+    let discord_events = DiscordEvent::retrieve_all(feed.guild_id)
+      .fetch_all(&mut **transaction)
+      .await?;
+
+    let mut calendar = Calendar::new("Bloom Events");
+    for event in &community_events {
+      calendar = calendar.event(event);
+    }
+    for event in &discord_events {
+      calendar = calendar.event(event);
+    }
+    calendar.events.extend(ics::monthly_challenges(
+      now.date_naive(),
+      CALENDAR_CHALLENGE_MONTHS,
+    ));
+
+    Ok(Some(calendar))
+  }
+
+  /// Saves a copy of a Discord scheduled event, replacing any previous copy.
+  pub async fn add_discord_event(
+    transaction: &mut Transaction<'_, Postgres>,
+    event: &DiscordEvent,
+  ) -> Result<()> {
+    event.insert_query().execute(&mut **transaction).await?;
+
+    Ok(())
+  }
+
+  pub async fn remove_discord_event(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    event_id: &str,
+  ) -> Result<()> {
+    DiscordEvent::delete_query(*guild_id, event_id)
+      .execute(&mut **transaction)
+      .await?;
+
+    Ok(())
+  }
+
+  /// Replaces the copies of a guild's Discord scheduled events with `events`, so events
+  /// removed while Bloom was offline are dropped.
+  pub async fn replace_discord_events(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    events: &[DiscordEvent],
+  ) -> Result<()> {
+    DiscordEvent::remove_all(*guild_id)
+      .execute(&mut **transaction)
+      .await?;
+    for event in events {
+      event.insert_query().execute(&mut **transaction).await?;
+    }
+
+    Ok(())
+  }
+
+  /// Adds an RSVP for an occurrence of an event, returning the number of rows affected.
+  /// Returns `0` if the member has already RSVPed.
+  pub async fn add_event_rsvp(
+    transaction: &mut Transaction<'_, Postgres>,
+    event_id: &str,
+    user_id: &UserId,
+    occurrence: &DateTime<Utc>,
+  ) -> Result<u64> {
+    Ok(
+      CommunityEvent::add_rsvp(event_id, *user_id, occurrence)
+        .execute(&mut **transaction)
+        .await?
+        .rows_affected(),
+    )
+  }
+
+  pub async fn remove_event_rsvp(
+    transaction: &mut Transaction<'_, Postgres>,
+    event_id: &str,
+    user_id: &UserId,
+    occurrence: &DateTime<Utc>,
+  ) -> Result<u64> {
+    Ok(
+      CommunityEvent::remove_rsvp(event_id, *user_id, occurrence)
+        .execute(&mut **transaction)
+        .await?
+        .rows_affected(),
+    )
+  }
+
+  pub async fn get_event_rsvp_count(
+    transaction: &mut Transaction<'_, Postgres>,
+    event_id: &str,
+    occurrence: &DateTime<Utc>,
+  ) -> Result<u64> {
+    Ok(
+      CommunityEvent::rsvp_count::<Aggregate>(event_id, occurrence)
+        .fetch_one(&mut **transaction)
+        .await?
+        .count,
+    )
+  }
+
+  /// Records a member as attending an occurrence of an event, returning the number of rows
+  /// affected. Returns `0` if their attendance has already been recorded.
+  pub async fn add_event_attendance(
+    transaction: &mut Transaction<'_, Postgres>,
+    event_id: &str,
+    user_id: &UserId,
+    occurrence: &DateTime<Utc>,
+    source: AttendanceSource,
+  ) -> Result<u64> {
+    Ok(
+      CommunityEvent::add_attendance(event_id, *user_id, occurrence, source)
+        .execute(&mut **transaction)
+        .await?
+        .rows_affected(),
+    )
+  }
+
+  pub async fn get_event_occurrence_stats(
+    transaction: &mut Transaction<'_, Postgres>,
+    event_id: &str,
+  ) -> Result<Vec<OccurrenceStats>> {
+    Ok(
+      CommunityEvent::occurrence_stats(event_id)
+        .fetch_all(&mut **transaction)
+        .await?,
+    )
+  }
+
+  pub async fn set_event_transcription(
+    transaction: &mut Transaction<'_, Postgres>,
+    event_id: &str,
+    transcribe: bool,
+  ) -> Result<()> {
+    CommunityEvent::set_transcribe(event_id, transcribe)
+      .execute(&mut **transaction)
+      .await?;
+
+    Ok(())
+  }
+
+  /// Returns the number of members who have attended any occurrence of an event.
+  pub async fn get_event_unique_attendees(
+    transaction: &mut Transaction<'_, Postgres>,
+    event_id: &str,
+  ) -> Result<u64> {
+    Ok(
+      CommunityEvent::unique_attendees::<Aggregate>(event_id)
+        .fetch_one(&mut **transaction)
+        .await?
+        .count,
+    )
+  }
+
+  pub async fn add_poll(transaction: &mut Transaction<'_, Postgres>, poll: &Poll) -> Result<()> {
+    poll.insert_query().execute(&mut **transaction).await?;
+
+    Ok(())
+  }
+
+  pub async fn get_poll(
+    transaction: &mut Transaction<'_, Postgres>,
+    poll_id: &str,
+  ) -> Result<Option<Poll>> {
+    Ok(
+      Poll::retrieve(poll_id)
+        .fetch_optional(&mut **transaction)
+        .await?,
+    )
+  }
+
+  pub async fn get_expired_polls(
+    transaction: &mut Transaction<'_, Postgres>,
+    now: &DateTime<Utc>,
+  ) -> Result<Vec<Poll>> {
+    Ok(
+      Poll::retrieve_expired(now)
+        .fetch_all(&mut **transaction)
+        .await?,
+    )
+  }
+
+  pub async fn set_poll_message(
+    transaction: &mut Transaction<'_, Postgres>,
+    poll_id: &str,
+    message_id: &MessageId,
+  ) -> Result<()> {
+    Poll::set_message(poll_id, *message_id)
+      .execute(&mut **transaction)
+      .await?;
+
+    Ok(())
+  }
+
+  /// Closes a poll, returning the number of polls affected. A result of `0` means the poll
+  /// has already been closed.
+  pub async fn close_poll(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    poll_id: &str,
+  ) -> Result<u64> {
+    Ok(
+      Poll::close(*guild_id, poll_id)
+        .execute(&mut **transaction)
+        .await?
+        .rows_affected(),
+    )
+  }
+
+  pub async fn get_poll_votes(
+    transaction: &mut Transaction<'_, Postgres>,
+    poll_id: &str,
+  ) -> Result<Vec<PollVote>> {
+    Ok(Poll::votes(poll_id).fetch_all(&mut **transaction).await?)
+  }
+
+  /// Adds a vote to a poll, returning the number of rows affected. Returns `0` if the
+  /// member has already voted for that choice.
+  pub async fn add_poll_vote(
+    transaction: &mut Transaction<'_, Postgres>,
+    poll_id: &str,
+    vote: PollVote,
+  ) -> Result<u64> {
+    Ok(
+      Poll::add_vote(poll_id, vote)
+        .execute(&mut **transaction)
+        .await?
+        .rows_affected(),
+    )
+  }
+
+  pub async fn remove_poll_vote(
+    transaction: &mut Transaction<'_, Postgres>,
+    poll_id: &str,
+    vote: PollVote,
+  ) -> Result<u64> {
+    Ok(
+      Poll::remove_vote(poll_id, vote)
+        .execute(&mut **transaction)
+        .await?
+        .rows_affected(),
+    )
+  }
+
+  pub async fn clear_poll_votes(
+    transaction: &mut Transaction<'_, Postgres>,
+    poll_id: &str,
+    user_id: &UserId,
+  ) -> Result<u64> {
+    Ok(
+      Poll::clear_votes(poll_id, *user_id)
+        .execute(&mut **transaction)
+        .await?
+        .rows_affected(),
+    )
+  }
+
+  pub async fn add_scheduled_announcement(
+    transaction: &mut Transaction<'_, Postgres>,
+    announcement: &ScheduledAnnouncement,
+  ) -> Result<()> {
+    announcement
+      .insert_query()
+      .execute(&mut **transaction)
+      .await?;
+
+    Ok(())
+  }
+
+  pub async fn get_pending_announcements(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+  ) -> Result<Vec<ScheduledAnnouncement>> {
+    Ok(
+      ScheduledAnnouncement::retrieve_pending(*guild_id)
+        .fetch_all(&mut **transaction)
+        .await?,
+    )
+  }
+
+  pub async fn get_due_announcements(
+    transaction: &mut Transaction<'_, Postgres>,
+    now: &DateTime<Utc>,
+  ) -> Result<Vec<ScheduledAnnouncement>> {
+    Ok(
+      ScheduledAnnouncement::retrieve_due(now)
+        .fetch_all(&mut **transaction)
+        .await?,
+    )
+  }
+
+  /// Marks an announcement as posted, returning the number of rows affected. Returns `0` if
+  /// it has already been posted or was cancelled.
+  pub async fn mark_announcement_sent(
+    transaction: &mut Transaction<'_, Postgres>,
+    announcement_id: &str,
+  ) -> Result<u64> {
+    Ok(
+      ScheduledAnnouncement::mark_sent(announcement_id)
+        .execute(&mut **transaction)
+        .await?
+        .rows_affected(),
+    )
+  }
+
+  /// Cancels an announcement which hasn't been posted, returning the number of rows
+  /// affected.
+  pub async fn remove_scheduled_announcement(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    announcement_id: &str,
+  ) -> Result<u64> {
+    Ok(
+      ScheduledAnnouncement::remove(*guild_id, announcement_id)
+        .execute(&mut **transaction)
+        .await?
+        .rows_affected(),
+    )
+  }
+
+  pub async fn add_recurring_post(
+    transaction: &mut Transaction<'_, Postgres>,
+    post: &RecurringPost,
+  ) -> Result<()> {
+    post.insert_query().execute(&mut **transaction).await?;
+
+    Ok(())
+  }
+
+  pub async fn get_recurring_posts(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+  ) -> Result<Vec<RecurringPost>> {
+    Ok(
+      RecurringPost::retrieve_all(*guild_id)
+        .fetch_all(&mut **transaction)
+        .await?,
+    )
+  }
+
+  pub async fn get_due_recurring_posts(
+    transaction: &mut Transaction<'_, Postgres>,
+    now: &DateTime<Utc>,
+  ) -> Result<Vec<RecurringPost>> {
+    Ok(
+      RecurringPost::retrieve_due(now)
+        .fetch_all(&mut **transaction)
+        .await?,
+    )
+  }
+
+  /// Moves a recurring post on to its next time, returning the number of rows affected.
+  /// Returns `0` if the post is no longer due at `due`, such as when another process has
+  /// already posted it, or it has been removed.
+  pub async fn advance_recurring_post(
+    transaction: &mut Transaction<'_, Postgres>,
+    post_id: &str,
+    due: &DateTime<Utc>,
+    next_post_at: &DateTime<Utc>,
+  ) -> Result<u64> {
+    Ok(
+      RecurringPost::advance(post_id, due, next_post_at)
+        .execute(&mut **transaction)
+        .await?
+        .rows_affected(),
+    )
+  }
+
+  pub async fn remove_recurring_post(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    name: &str,
+  ) -> Result<u64> {
+    Ok(
+      RecurringPost::remove(*guild_id, name)
+        .execute(&mut **transaction)
+        .await?
+        .rows_affected(),
+    )
+  }
+
+  pub async fn add_practice_prompt(
+    transaction: &mut Transaction<'_, Postgres>,
+    prompt: &PracticePrompt,
+  ) -> Result<()> {
+    prompt.insert_query().execute(&mut **transaction).await?;
+
+    Ok(())
+  }
+
+  pub async fn get_practice_prompts(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    category: Option<PromptCategory>,
+  ) -> Result<Vec<PracticePrompt>> {
+    Ok(
+      PracticePrompt::retrieve_all(*guild_id, category)
+        .fetch_all(&mut **transaction)
+        .await?,
+    )
+  }
+
+  pub async fn remove_practice_prompt(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    prompt_id: &str,
+  ) -> Result<u64> {
+    Ok(
+      PracticePrompt::delete_query(*guild_id, prompt_id)
+        .execute(&mut **transaction)
+        .await?
+        .rows_affected(),
+    )
+  }
+
+  /// Adds an [`IntentionSubscription`], or changes the hour of the member's existing
+  /// subscription.
+  pub async fn add_intention_subscription(
+    transaction: &mut Transaction<'_, Postgres>,
+    subscription: &IntentionSubscription,
+  ) -> Result<()> {
+    subscription
+      .insert_query()
+      .execute(&mut **transaction)
+      .await?;
+
+    Ok(())
+  }
+
+  pub async fn get_intention_subscription(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    user_id: &UserId,
+  ) -> Result<Option<IntentionSubscription>> {
+    Ok(
+      IntentionSubscription::retrieve(*guild_id, *user_id)
+        .fetch_optional(&mut **transaction)
+        .await?,
+    )
+  }
+
+  pub async fn remove_intention_subscription(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    user_id: &UserId,
+  ) -> Result<u64> {
+    Ok(
+      IntentionSubscription::delete_query(*guild_id, user_id.to_string())
+        .execute(&mut **transaction)
+        .await?
+        .rows_affected(),
+    )
+  }
+
+  pub async fn get_intention_reminders(
+    transaction: &mut Transaction<'_, Postgres>,
+    now: &DateTime<Utc>,
+  ) -> Result<Vec<IntentionReminder>> {
+    Ok(
+      IntentionReminder::retrieve_due(now)
+        .fetch_all(&mut **transaction)
+        .await?,
+    )
+  }
+
+  pub async fn mark_intention_reminder_sent(
+    transaction: &mut Transaction<'_, Postgres>,
+    reminder: &IntentionReminder,
+  ) -> Result<()> {
+    reminder.mark_sent().execute(&mut **transaction).await?;
+
+    Ok(())
+  }
+
+  pub async fn add_feed_subscription(
+    transaction: &mut Transaction<'_, Postgres>,
+    subscription: &FeedSubscription,
+  ) -> Result<()> {
+    subscription
+      .insert_query()
+      .execute(&mut **transaction)
+      .await?;
+
+    Ok(())
+  }
+
+  pub async fn get_feed_subscriptions(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+  ) -> Result<Vec<FeedSubscription>> {
+    Ok(
+      FeedSubscription::retrieve_all(*guild_id)
+        .fetch_all(&mut **transaction)
+        .await?,
+    )
+  }
+
+  pub async fn get_all_feed_subscriptions(
+    transaction: &mut Transaction<'_, Postgres>,
+  ) -> Result<Vec<FeedSubscription>> {
+    Ok(
+      FeedSubscription::retrieve_every()
+        .fetch_all(&mut **transaction)
+        .await?,
+    )
+  }
+
+  pub async fn remove_feed_subscription(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    subscription_id: &str,
+  ) -> Result<u64> {
+    Ok(
+      FeedSubscription::delete_query(*guild_id, subscription_id)
+        .execute(&mut **transaction)
+        .await?
+        .rows_affected(),
+    )
+  }
+
+  /// Records that an item in a feed has been seen. Returns `true` if the item is new.
+  pub async fn mark_feed_item_seen(
+    transaction: &mut Transaction<'_, Postgres>,
+    subscription: &FeedSubscription,
+    item_key: &str,
+  ) -> Result<bool> {
+    Ok(
+      subscription
+        .mark_seen(item_key)
+        .execute(&mut **transaction)
+        .await?
+        .rows_affected()
+        > 0,
+    )
+  }
+
+  pub async fn get_cached_sutta(
+    transaction: &mut Transaction<'_, Postgres>,
+    uid: &str,
+    translator: &str,
+  ) -> Result<Option<SuttaPassage>> {
+    Ok(
+      SuttaPassage::retrieve(uid, translator)
+        .fetch_optional(&mut **transaction)
+        .await?,
+    )
+  }
+
+  pub async fn cache_sutta(
+    transaction: &mut Transaction<'_, Postgres>,
+    passage: &SuttaPassage,
+  ) -> Result<()> {
+    passage.insert_query().execute(&mut **transaction).await?;
+
+    Ok(())
+  }
+
+  pub async fn add_talk(transaction: &mut Transaction<'_, Postgres>, talk: &Talk) -> Result<()> {
+    talk.insert_query().execute(&mut **transaction).await?;
+
+    Ok(())
+  }
+
+  pub async fn get_talk(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    name: &str,
+  ) -> Result<Option<Talk>> {
+    Ok(
+      Talk::retrieve(*guild_id, name)
+        .fetch_optional(&mut **transaction)
+        .await?,
+    )
+  }
+
+  pub async fn get_talks(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+  ) -> Result<Vec<Talk>> {
+    Ok(
+      Talk::retrieve_all(*guild_id)
+        .fetch_all(&mut **transaction)
+        .await?,
+    )
+  }
+
+  pub async fn remove_talk(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    name: &str,
+  ) -> Result<u64> {
+    Ok(
+      Talk::delete_query(*guild_id, name)
+        .execute(&mut **transaction)
+        .await?
+        .rows_affected(),
+    )
+  }
+
+  pub async fn add_suggestion(
+    transaction: &mut Transaction<'_, Postgres>,
+    suggestion: &Suggestion,
+  ) -> Result<()> {
+    suggestion
+      .insert_query()
+      .execute(&mut **transaction)
+      .await?;
+
+    Ok(())
+  }
+
+  pub async fn get_suggestion(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    suggestion_id: &str,
+  ) -> Result<Option<Suggestion>> {
+    Ok(
+      Suggestion::retrieve(*guild_id, suggestion_id)
+        .fetch_optional(&mut **transaction)
+        .await?,
+    )
+  }
+
+  pub async fn get_suggestions_by_status(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    status: SuggestionStatus,
+  ) -> Result<Vec<Suggestion>> {
+    Ok(
+      Suggestion::retrieve_by_status(*guild_id, status)
+        .fetch_all(&mut **transaction)
+        .await?,
+    )
+  }
+
+  pub async fn set_suggestion_message(
+    transaction: &mut Transaction<'_, Postgres>,
+    suggestion_id: &str,
+    message_id: &MessageId,
+  ) -> Result<()> {
+    Suggestion::set_message(suggestion_id, *message_id)
+      .execute(&mut **transaction)
+      .await?;
+
+    Ok(())
+  }
+
+  /// Updates the status of a suggestion, returning the number of rows affected. A result
+  /// of `0` means no suggestion with that ID exists in the guild.
+  pub async fn update_suggestion_status(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    suggestion_id: &str,
+    status: SuggestionStatus,
+    note: Option<&str>,
+    updated_by: &UserId,
+  ) -> Result<u64> {
+    Ok(
+      Suggestion::update_status(*guild_id, suggestion_id, status, note, *updated_by)
+        .execute(&mut **transaction)
+        .await?
+        .rows_affected(),
+    )
+  }
+
+  /// Retrieves a member's vote on a suggestion: `Some(true)` for an upvote, `Some(false)`
+  /// for a downvote, or [`None`] if they haven't voted.
+  pub async fn get_suggestion_vote(
+    transaction: &mut Transaction<'_, Postgres>,
+    suggestion_id: &str,
+    user_id: &UserId,
+  ) -> Result<Option<bool>> {
+    Ok(
+      Suggestion::vote_of(suggestion_id, *user_id)
+        .fetch_optional(&mut **transaction)
+        .await?
+        .map(|vote| vote.upvote),
+    )
+  }
+
+  pub async fn set_suggestion_vote(
+    transaction: &mut Transaction<'_, Postgres>,
+    suggestion_id: &str,
+    user_id: &UserId,
+    upvote: bool,
+  ) -> Result<()> {
+    Suggestion::set_vote(suggestion_id, *user_id, upvote)
+      .execute(&mut **transaction)
+      .await?;
+
+    Ok(())
+  }
+
+  pub async fn remove_suggestion_vote(
+    transaction: &mut Transaction<'_, Postgres>,
+    suggestion_id: &str,
+    user_id: &UserId,
+  ) -> Result<u64> {
+    Ok(
+      Suggestion::remove_vote(suggestion_id, *user_id)
+        .execute(&mut **transaction)
+        .await?
+        .rows_affected(),
+    )
+  }
+
+  pub async fn get_suggestion_votes(
+    transaction: &mut Transaction<'_, Postgres>,
+    suggestion_id: &str,
+  ) -> Result<SuggestionVotes> {
+    Ok(
+      Suggestion::votes(suggestion_id)
+        .fetch_one(&mut **transaction)
+        .await?,
+    )
+  }
+
+  /// Adds a term to a guild's watchlist, returning the number of rows affected. Returns `0`
+  /// if the pattern is already on the watchlist.
+  pub async fn add_watchlist_term(
+    transaction: &mut Transaction<'_, Postgres>,
+    term: &WatchlistTerm,
+  ) -> Result<u64> {
+    Ok(
+      term
+        .insert_query()
+        .execute(&mut **transaction)
+        .await?
+        .rows_affected(),
+    )
+  }
+
+  pub async fn get_watchlist(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+  ) -> Result<Vec<WatchlistTerm>> {
+    Ok(
+      WatchlistTerm::retrieve_all(*guild_id)
+        .fetch_all(&mut **transaction)
+        .await?,
+    )
+  }
+
+  pub async fn remove_watchlist_term(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    pattern: &str,
+  ) -> Result<u64> {
+    Ok(
+      WatchlistTerm::remove(*guild_id, pattern)
+        .execute(&mut **transaction)
+        .await?
+        .rows_affected(),
+    )
+  }
+
+  pub async fn add_report(
+    transaction: &mut Transaction<'_, Postgres>,
+    report: &Report,
+  ) -> Result<()> {
+    report.insert_query().execute(&mut **transaction).await?;
+
+    Ok(())
+  }
+
+  pub async fn get_report(
+    transaction: &mut Transaction<'_, Postgres>,
+    report_id: &str,
+  ) -> Result<Option<Report>> {
+    Ok(
+      Report::retrieve(report_id)
+        .fetch_optional(&mut **transaction)
+        .await?,
+    )
+  }
+
+  /// Records what staff did about a report, returning the number of rows affected. A
+  /// result of `0` means the report has already been handled.
+  pub async fn set_report_status(
+    transaction: &mut Transaction<'_, Postgres>,
+    report_id: &str,
+    status: ReportStatus,
+    handled_by: &UserId,
+  ) -> Result<u64> {
+    Ok(
+      Report::set_status(report_id, status, *handled_by)
+        .execute(&mut **transaction)
+        .await?
+        .rows_affected(),
+    )
+  }
+
+  pub async fn add_ticket(
+    transaction: &mut Transaction<'_, Postgres>,
+    ticket: &Ticket,
+  ) -> Result<()> {
+    ticket.insert_query().execute(&mut **transaction).await?;
+
+    Ok(())
+  }
+
+  pub async fn get_ticket(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    ticket_id: &str,
+  ) -> Result<Option<Ticket>> {
+    Ok(
+      Ticket::retrieve(*guild_id, ticket_id)
+        .fetch_optional(&mut **transaction)
+        .await?,
+    )
+  }
+
+  pub async fn get_ticket_by_thread(
+    transaction: &mut Transaction<'_, Postgres>,
+    thread_id: &ChannelId,
+  ) -> Result<Option<Ticket>> {
+    Ok(
+      Ticket::retrieve_by_thread(*thread_id)
+        .fetch_optional(&mut **transaction)
+        .await?,
+    )
+  }
+
+  pub async fn get_open_tickets(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: &UserId,
+  ) -> Result<Vec<Ticket>> {
+    Ok(
+      Ticket::retrieve_open_for_user(*user_id)
+        .fetch_all(&mut **transaction)
+        .await?,
+    )
+  }
+
+  pub async fn close_ticket(
+    transaction: &mut Transaction<'_, Postgres>,
+    ticket_id: &str,
+    closed_by: &UserId,
+  ) -> Result<u64> {
+    Ok(
+      Ticket::close(ticket_id, *closed_by)
+        .execute(&mut **transaction)
+        .await?
+        .rows_affected(),
+    )
+  }
+
+  pub async fn add_ticket_message(
+    transaction: &mut Transaction<'_, Postgres>,
+    message: &TicketMessage,
+  ) -> Result<()> {
+    message.insert_query().execute(&mut **transaction).await?;
+
+    Ok(())
+  }
+
+  pub async fn get_ticket_transcript(
+    transaction: &mut Transaction<'_, Postgres>,
+    ticket_id: &str,
+  ) -> Result<Vec<TicketMessage>> {
+    Ok(
+      TicketMessage::retrieve_all(ticket_id)
+        .fetch_all(&mut **transaction)
+        .await?,
+    )
+  }
+
+  pub async fn add_quote(transaction: &mut Transaction<'_, Postgres>, quote: &Quote) -> Result<()> {
+    quote.insert_query().execute(&mut **transaction).await?;
+
+    Ok(())
+  }
+
+  pub async fn update_quote(
+    transaction: &mut Transaction<'_, Postgres>,
+    quote: &Quote,
+  ) -> Result<()> {
+    quote.update_query().execute(&mut **transaction).await?;
+
+    Ok(())
+  }
+
+  pub async fn remove_quote(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    quote_id: &str,
+  ) -> Result<()> {
+    Quote::delete_query(*guild_id, quote_id)
+      .execute(&mut **transaction)
+      .await?;
+
+    Ok(())
+  }
+
+  pub async fn quote_exists(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    quote_id: &str,
+  ) -> Result<bool> {
+    Ok(
+      Quote::exists_query::<Exists>(*guild_id, quote_id)
+        .fetch_one(&mut **transaction)
+        .await?
+        .exists,
+    )
+  }
+
+  pub async fn get_quote(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    quote_id: &str,
+  ) -> Result<Option<Quote>> {
+    Ok(
+      Quote::retrieve(*guild_id, quote_id)
+        .fetch_optional(&mut **transaction)
+        .await?,
+    )
+  }
+
+  /// Retrieves the [`GuildSettings`] for a guild, falling back to the defaults if none
+  /// have been saved.
+  pub async fn get_guild_settings(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+  ) -> Result<GuildSettings> {
+    Ok(
+      GuildSettings::retrieve(*guild_id)
+        .fetch_optional(&mut **transaction)
+        .await?
+        .unwrap_or_else(|| GuildSettings::new(*guild_id)),
+    )
+  }
+
+  pub async fn update_guild_settings(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_settings: &GuildSettings,
+  ) -> Result<()> {
+    guild_settings
+      .insert_query()
+      .execute(&mut **transaction)
+      .await?;
+
+    Ok(())
+  }
+
+  /// Returns a guild's rows from a [`BackupTable`], as a JSON array.
+  pub async fn get_backup_rows(
+    transaction: &mut Transaction<'_, Postgres>,
+    table: BackupTable,
+    guild_id: &GuildId,
+  ) -> Result<String> {
+    // Table names can't be bound, but only come from `BackupTable`
+    let query = format!(
+      "SELECT COALESCE(json_agg(t), '[]'::json)::text FROM (SELECT * FROM {} WHERE guild_id = $1) t",
+      table.as_str()
+    );
+
+    Ok(
+      sqlx::query_scalar(&query)
+        .bind(guild_id.to_string())
+        .fetch_one(&mut **transaction)
+        .await?,
+    )
+  }
+
+  /// Inserts a guild's rows into a [`BackupTable`] from a JSON array, as returned by
+  /// [`get_backup_rows`][Self::get_backup_rows]. Rows for other guilds and rows which
+  /// already exist are skipped. Generated columns are left for Postgres to fill in, and
+  /// columns missing from older backups use their defaults. Returns the number of rows
+  /// inserted.
+  pub async fn restore_backup_rows(
+    transaction: &mut Transaction<'_, Postgres>,
+    table: BackupTable,
+    guild_id: &GuildId,
+    rows: &str,
+  ) -> Result<u64> {
+    let columns: Vec<String> = sqlx::query_scalar(
+      "SELECT column_name::text FROM information_schema.columns WHERE table_schema = current_schema() AND table_name = $1 AND is_generated = 'NEVER' ORDER BY ordinal_position",
+    )
+    .bind(table.as_str())
+    .fetch_all(&mut **transaction)
+    .await?;
+
+    let columns = columns
+      .iter()
+      .map(|column| format!("\"{column}\""))
+      .collect::<Vec<_>>()
+      .join(", ");
+    let query = format!(
+      "INSERT INTO {table} ({columns}) SELECT {columns} FROM json_populate_recordset(NULL::{table}, $1::json) WHERE guild_id = $2 ON CONFLICT DO NOTHING",
+      table = table.as_str()
+    );
+
+    Ok(
+      sqlx::query(&query)
+        .bind(rows)
+        .bind(guild_id.to_string())
+        .execute(&mut **transaction)
+        .await?
+        .rows_affected(),
+    )
+  }
+
+  /// Returns the [`GuildSettings`] for all guilds with a "most improved" shout-out which
+  /// hasn't yet been posted for the month starting on `month`.
+  pub async fn get_guilds_improved_due(
+    transaction: &mut Transaction<'_, Postgres>,
+    month: NaiveDate,
+  ) -> Result<Vec<GuildSettings>> {
+    Ok(
+      GuildSettings::retrieve_improved_due(month)
+        .fetch_all(&mut **transaction)
+        .await?,
+    )
+  }
+
+  pub async fn get_ticket_guilds(
+    transaction: &mut Transaction<'_, Postgres>,
+  ) -> Result<Vec<GuildSettings>> {
+    Ok(
+      GuildSettings::retrieve_ticket_enabled()
+        .fetch_all(&mut **transaction)
+        .await?,
+    )
+  }
+
+  pub async fn set_guild_emoji(
+    transaction: &mut Transaction<'_, Postgres>,
+    emoji: &GuildEmoji,
+  ) -> Result<()> {
+    emoji.insert_query().execute(&mut **transaction).await?;
+
+    Ok(())
+  }
+
+  /// Removes a server's replacement for an emoji, returning the number of rows affected.
+  /// Returns `0` if the emoji hadn't been replaced.
+  pub async fn remove_guild_emoji(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    name: EmojiName,
+  ) -> Result<u64> {
+    Ok(
+      GuildEmoji::remove(*guild_id, name)
+        .execute(&mut **transaction)
+        .await?
+        .rows_affected(),
+    )
+  }
+
+  pub async fn get_guild_emoji(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+  ) -> Result<Vec<GuildEmoji>> {
+    Ok(
+      GuildEmoji::retrieve_all(*guild_id)
+        .fetch_all(&mut **transaction)
+        .await?,
+    )
+  }
+
+  pub async fn get_every_guild_emoji(
+    transaction: &mut Transaction<'_, Postgres>,
+  ) -> Result<Vec<GuildEmoji>> {
+    Ok(
+      GuildEmoji::retrieve_every_guild()
+        .fetch_all(&mut **transaction)
+        .await?,
+    )
+  }
+
+  pub async fn set_guild_feature(
+    transaction: &mut Transaction<'_, Postgres>,
+    feature: &GuildFeature,
+  ) -> Result<()> {
+    feature.insert_query().execute(&mut **transaction).await?;
+
+    Ok(())
+  }
+
+  pub async fn get_guild_features(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+  ) -> Result<Vec<GuildFeature>> {
+    Ok(
+      GuildFeature::retrieve_all(*guild_id)
+        .fetch_all(&mut **transaction)
+        .await?,
+    )
+  }
+
+  pub async fn get_every_guild_feature(
+    transaction: &mut Transaction<'_, Postgres>,
+  ) -> Result<Vec<GuildFeature>> {
+    Ok(
+      GuildFeature::retrieve_every_guild()
+        .fetch_all(&mut **transaction)
+        .await?,
+    )
+  }
+
+  /// Retrieves the [`Maintenance`] setting, which is off if it has never been set.
+  pub async fn get_maintenance(transaction: &mut Transaction<'_, Postgres>) -> Result<Maintenance> {
+    Ok(
+      Maintenance::retrieve()
+        .fetch_optional(&mut **transaction)
+        .await?
+        .unwrap_or_default(),
+    )
+  }
+
+  pub async fn set_maintenance(
+    transaction: &mut Transaction<'_, Postgres>,
+    maintenance: &Maintenance,
+  ) -> Result<()> {
+    maintenance
+      .insert_query()
+      .execute(&mut **transaction)
+      .await?;
+
+    Ok(())
+  }
+
+  pub async fn mark_improved_posted(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    month: NaiveDate,
+  ) -> Result<()> {
+    GuildSettings::mark_improved_posted(*guild_id, month)
+      .execute(&mut **transaction)
+      .await?;
+
+    Ok(())
+  }
+
+  /// Returns up to `limit` members with the largest increase in minutes between
+  /// `[previous_start, current_start)` and `[current_start, end)`.
+  pub async fn get_most_improved(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    previous_start: &DateTime<Utc>,
+    current_start: &DateTime<Utc>,
+    end: &DateTime<Utc>,
+    limit: i64,
+  ) -> Result<Vec<Improvement>> {
+    Ok(
+      Improvement::ranking(*guild_id, previous_start, current_start, end, limit)
+        .fetch_all(&mut **transaction)
+        .await?,
+    )
+  }
+
+  pub async fn record_ai_usage(
+    connection: &mut PoolConnection<Postgres>,
+    guild_id: &GuildId,
+    tokens: i64,
+  ) -> Result<()> {
+    AiUsage::record(*guild_id, tokens)
+      .execute(&mut **connection)
+      .await?;
+
+    Ok(())
+  }
+
+  /// Retrieves the total [`AiUsage`] for a guild from `start_date` onwards.
+  pub async fn get_ai_usage(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    start_date: NaiveDate,
+  ) -> Result<AiUsage> {
+    Ok(
+      AiUsage::retrieve_total(*guild_id, start_date)
+        .fetch_one(&mut **transaction)
+        .await?,
+    )
+  }
+
+  pub async fn get_daily_ai_usage(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    start_date: NaiveDate,
+  ) -> Result<Vec<DailyAiUsage>> {
+    Ok(
+      AiUsage::retrieve_daily(*guild_id, start_date)
+        .fetch_all(&mut **transaction)
+        .await?,
+    )
+  }
+
+  pub async fn get_random_quote(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+  ) -> Result<Option<Quote>> {
+    Ok(
+      Quote::retrieve_random(*guild_id)
+        .fetch_optional(&mut **transaction)
+        .await?,
+    )
+  }
+
+  pub async fn get_random_quote_with_keyword(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    keyword: &str,
+    author: Option<&str>,
+  ) -> Result<Option<Quote>> {
+    Ok(
+      Quote::retrieve_random_with_keyword(*guild_id, keyword, author)
+        .fetch_optional(&mut **transaction)
+        .await?,
+    )
+  }
+
+  pub async fn get_random_quote_by_author(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    author: &str,
+  ) -> Result<Option<Quote>> {
+    Ok(
+      Quote::retrieve_random_by_author(*guild_id, author)
+        .fetch_optional(&mut **transaction)
+        .await?,
+    )
+  }
+
+  pub async fn get_all_quotes(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+  ) -> Result<Vec<Quote>> {
+    Ok(
+      Quote::retrieve_all(*guild_id)
+        .fetch_all(&mut **transaction)
+        .await?,
+    )
+  }
+
+  pub async fn get_quotes_by_author(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    author: &str,
+  ) -> Result<Vec<Quote>> {
+    Ok(
+      Quote::retrieve_all_by_author(*guild_id, author)
+        .fetch_all(&mut **transaction)
+        .await?,
+    )
+  }
+
+  pub async fn get_quote_authors(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    partial: &str,
+  ) -> Result<Vec<String>> {
+    let authors = Quote::retrieve_authors(*guild_id, partial)
+      .fetch_all(&mut **transaction)
+      .await?;
+
+    Ok(authors.into_iter().map(|row| row.author).collect())
+  }
+
+  pub async fn search_quotes(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    keyword: &str,
+  ) -> Result<Vec<Quote>> {
+    Ok(
+      Quote::search(*guild_id, keyword)
+        .fetch_all(&mut **transaction)
+        .await?,
+    )
+  }
+
+  pub async fn update_streak(
+    transaction: &mut Transaction<'_, Postgres>,
+    streak: &Streak,
+  ) -> Result<()> {
+    streak.update_query().execute(&mut **transaction).await?;
+
+    Ok(())
+  }
+
+  pub async fn get_streak(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    user_id: &UserId,
+  ) -> Result<Streak> {
+    let mut streak_data = Streak::calculate(*guild_id, *user_id)
+      .fetch_optional(&mut **transaction)
+      .await?
+      .unwrap_or_default();
+
+    let days_ago: Vec<i32> = MeditationCountByDay::calculate(*guild_id, *user_id)
+      .fetch_all(&mut **transaction)
+      .await?
+      .into_iter()
+      .map(|day| day.days_ago)
+      .collect();
+    streak_data.count_days(&days_ago);
+
+    let streak = Streak::new(
+      *guild_id,
+      *user_id,
+      streak_data.current,
+      streak_data.longest,
+    );
+    DatabaseHandler::update_streak(transaction, &streak).await?;
+
+    Ok(streak_data)
+  }
+
+  /// Recalculates the [`Streak`] for a user from all of their meditation entries, rather
+  /// than relying on the stored longest streak. Needed when entries have been added to the
+  /// past, such as when merging another user's entries.
+  pub async fn recalculate_streak(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    user_id: &UserId,
+  ) -> Result<Streak> {
+    Streak::reset(*guild_id, *user_id)
+      .execute(&mut **transaction)
+      .await?;
+
+    DatabaseHandler::get_streak(transaction, guild_id, user_id).await
+  }
+
+  /// Adds a [`StreakRepair`] request, returning `false` if the day has already been
+  /// requested and the request wasn't denied.
+  pub async fn add_streak_repair(
+    transaction: &mut Transaction<'_, Postgres>,
+    repair: &StreakRepair,
+  ) -> Result<bool> {
+    Ok(
+      repair
+        .insert_query()
+        .execute(&mut **transaction)
+        .await?
+        .rows_affected()
+        > 0,
+    )
+  }
+
+  pub async fn get_streak_repair(
+    transaction: &mut Transaction<'_, Postgres>,
+    repair_id: &str,
+  ) -> Result<Option<StreakRepair>> {
+    Ok(
+      StreakRepair::retrieve(repair_id)
+        .fetch_optional(&mut **transaction)
+        .await?,
+    )
+  }
+
+  /// Records what staff decided about a streak repair, returning the number of rows
+  /// affected. A result of `0` means the request has already been handled.
+  pub async fn set_streak_repair_status(
+    transaction: &mut Transaction<'_, Postgres>,
+    repair_id: &str,
+    status: RepairStatus,
+    handled_by: &UserId,
+  ) -> Result<u64> {
+    Ok(
+      StreakRepair::set_status(repair_id, status, *handled_by)
+        .execute(&mut **transaction)
+        .await?
+        .rows_affected(),
+    )
+  }
+
+  /// Adds a [`Retreat`], along with a [`Meditation`] entry for each of its days.
+  pub async fn add_retreat(
+    transaction: &mut Transaction<'_, Postgres>,
+    retreat: &Retreat,
+    entries: &[Meditation],
+  ) -> Result<()> {
+    retreat.insert_query().execute(&mut **transaction).await?;
+    for entry in entries {
+      retreat
+        .entry_query(entry)
+        .execute(&mut **transaction)
+        .await?;
+    }
+
+    Ok(())
+  }
+
+  pub async fn get_retreat(
+    transaction: &mut Transaction<'_, Postgres>,
+    retreat_id: &str,
+  ) -> Result<Option<Retreat>> {
+    Ok(
+      Retreat::retrieve(retreat_id)
+        .fetch_optional(&mut **transaction)
+        .await?,
+    )
+  }
+
+  pub async fn get_overlapping_retreat(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    user_id: &UserId,
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+  ) -> Result<Option<Retreat>> {
+    Ok(
+      Retreat::retrieve_overlapping(*guild_id, *user_id, start_date, end_date)
+        .fetch_optional(&mut **transaction)
+        .await?,
+    )
+  }
+
+  /// Records what staff decided about a retreat, returning the number of rows affected. A
+  /// result of `0` means the retreat has already been reviewed.
+  pub async fn set_retreat_status(
+    transaction: &mut Transaction<'_, Postgres>,
+    retreat_id: &str,
+    status: RetreatStatus,
+    handled_by: &UserId,
+  ) -> Result<u64> {
+    Ok(
+      Retreat::set_status(retreat_id, status, *handled_by)
+        .execute(&mut **transaction)
+        .await?
+        .rows_affected(),
+    )
+  }
+
+  /// Removes the meditation entries added for a retreat, returning the number removed.
+  pub async fn remove_retreat_entries(
+    transaction: &mut Transaction<'_, Postgres>,
+    retreat_id: &str,
+  ) -> Result<u64> {
+    Ok(
+      Retreat::remove_entries(retreat_id)
+        .execute(&mut **transaction)
+        .await?
+        .rows_affected(),
+    )
+  }
+
+  /// Adds a [`LinkCode`], replacing any unused codes the member has.
+  pub async fn add_link_code(
+    transaction: &mut Transaction<'_, Postgres>,
+    link_code: &LinkCode,
+  ) -> Result<()> {
+    LinkCode::remove_unused(link_code.user_id)
+      .execute(&mut **transaction)
+      .await?;
+    link_code.insert_query().execute(&mut **transaction).await?;
+
+    Ok(())
+  }
+
+  /// Exchanges a link code from `/link` for an [`ApiToken`], which is stored and returned
+  /// along with the token to give the client. Returns [`None`] if the code doesn't exist, has
+  /// expired, or has already been used.
+  ///
+  /// This is how the dashboard signs members in: they run `/link` in Discord and enter the
+  /// code on the website.
+  pub async fn exchange_link_code(
+    transaction: &mut Transaction<'_, Postgres>,
+    code_hash: &str,
+    lifetime: ChronoDuration,
+  ) -> Result<Option<(String, ApiToken)>> {
+    let Some(user_id) = LinkCode::redeem(code_hash)
+      .fetch_optional(&mut **transaction)
+      .await?
+    else {
+      return Ok(None);
+    };
+
+    let (token, api_token) = ApiToken::generate(UserId::new(user_id.parse::<u64>()?), lifetime);
+    api_token.insert_query().execute(&mut **transaction).await?;
+
+    Ok(Some((token, api_token)))
+  }
+
+  /// Retrieves an unexpired [`ApiToken`] by its hash, to authenticate a dashboard or REST
+  /// API request.
+  pub async fn get_api_token(
+    transaction: &mut Transaction<'_, Postgres>,
+    token_hash: &str,
+  ) -> Result<Option<ApiToken>> {
+    Ok(
+      ApiToken::retrieve(token_hash)
+        .fetch_optional(&mut **transaction)
+        .await?,
+    )
+  }
+
+  /// Removes every [`ApiToken`] and unused link code issued to a member, returning the
+  /// number of tokens removed.
+  pub async fn revoke_api_tokens(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: &UserId,
+  ) -> Result<u64> {
+    LinkCode::remove_unused(*user_id)
+      .execute(&mut **transaction)
+      .await?;
+
+    Ok(
+      ApiToken::remove_all(*user_id)
+        .execute(&mut **transaction)
+        .await?
+        .rows_affected(),
+    )
+  }
+
+  pub async fn get_webhook_tokens(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    user_id: &UserId,
+  ) -> Result<Vec<WebhookToken>> {
+    Ok(
+      WebhookToken::retrieve_all(*guild_id, *user_id)
+        .fetch_all(&mut **transaction)
+        .await?,
+    )
+  }
+
+  /// Adds a [`WebhookToken`], unless the member already has [`MAX_TOKENS`] tokens in the
+  /// guild. Returns whether the token was added. The member's tokens stay locked until the
+  /// transaction ends, so concurrent requests can't exceed the limit.
+  pub async fn add_webhook_token(
+    transaction: &mut Transaction<'_, Postgres>,
+    token: &WebhookToken,
+  ) -> Result<bool> {
+    WebhookToken::lock(token.guild_id, token.user_id)
+      .execute(&mut **transaction)
+      .await?;
+    let count = WebhookToken::count(token.guild_id, token.user_id)
+      .fetch_one(&mut **transaction)
+      .await?;
+    if usize::try_from(count)? >= MAX_TOKENS {
+      return Ok(false);
+    }
+
+    token.insert_query().execute(&mut **transaction).await?;
+
+    Ok(true)
+  }
+
+  /// Removes one of a member's [`WebhookToken`]s by name, returning whether it existed.
+  pub async fn remove_webhook_token(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    user_id: &UserId,
+    name: &str,
+  ) -> Result<bool> {
+    Ok(
+      WebhookToken::remove(*guild_id, *user_id, name)
+        .execute(&mut **transaction)
+        .await?
+        .rows_affected()
+        > 0,
+    )
+  }
+
+  /// Removes every [`WebhookToken`] a member has in a guild, returning the number removed.
+  pub async fn remove_webhook_tokens(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    user_id: &UserId,
+  ) -> Result<u64> {
+    Ok(
+      WebhookToken::delete_query(*guild_id, user_id.to_string())
+        .execute(&mut **transaction)
+        .await?
+        .rows_affected(),
+    )
+  }
+
+  /// Logs a session sent to the session webhook, for the member whose token it includes,
+  /// and updates their streak. Returns the new [`Meditation`], or [`None`] if the token
+  /// doesn't exist.
+  ///
+  /// # Errors
+  /// Returns an [`InvalidSession`][invalid] if the session can't be logged, including when
+  /// the token has reached its hourly limit, which should be reported to the caller as a
+  /// client error.
+  ///
+  /// [invalid]: crate::data::session_webhook::InvalidSession
+  pub async fn log_webhook_session(
+    transaction: &mut Transaction<'_, Postgres>,
+    payload: &SessionPayload,
+    now: DateTime<Utc>,
+  ) -> Result<Option<Meditation>> {
+    let token_hash = hash_secret(&payload.token);
+    let Some(token) = WebhookToken::retrieve(&token_hash)
+      .fetch_optional(&mut **transaction)
+      .await?
+    else {
+      return Ok(None);
+    };
+
+    let occurred_at = payload.occurred_at(now)?;
+    if WebhookToken::consume(&token_hash)
+      .fetch_optional(&mut **transaction)
+      .await?
+      .is_none()
+    {
+      return Err(InvalidSession::RateLimited.into());
+    }
+
+    let meditation = Meditation::new(
+      token.guild_id,
+      token.user_id,
+      payload.minutes,
+      payload.seconds,
+      &occurred_at,
+    );
+    DatabaseHandler::add_meditation_entry(transaction, &meditation).await?;
+    DatabaseHandler::get_streak(transaction, &token.guild_id, &token.user_id).await?;
+
+    Ok(Some(meditation))
+  }
+
+  /// Adds a [`ProfileLink`], replacing any unused links the member has in the guild.
+  pub async fn add_profile_link(
+    transaction: &mut Transaction<'_, Postgres>,
+    link: &ProfileLink,
+  ) -> Result<()> {
+    ProfileLink::delete_query(link.guild_id, link.user_id.to_string())
+      .execute(&mut **transaction)
+      .await?;
+    link.insert_query().execute(&mut **transaction).await?;
+
+    Ok(())
+  }
+
+  /// Retrieves an unused, unexpired [`ProfileLink`] by the hash of its token.
+  pub async fn get_profile_link(
+    transaction: &mut Transaction<'_, Postgres>,
+    token_hash: &str,
+  ) -> Result<Option<ProfileLink>> {
+    Ok(
+      ProfileLink::retrieve(token_hash)
+        .fetch_optional(&mut **transaction)
+        .await?,
+    )
+  }
+
+  /// Marks a profile link as used, returning the number of rows affected. A result of `0`
+  /// means the link has already been used.
+  pub async fn use_profile_link(
+    transaction: &mut Transaction<'_, Postgres>,
+    token_hash: &str,
+  ) -> Result<u64> {
+    Ok(
+      ProfileLink::mark_used(token_hash)
+        .execute(&mut **transaction)
+        .await?
+        .rows_affected(),
+    )
+  }
+
+  /// Retrieves a member's entries in another guild which haven't yet been copied to
+  /// `guild_id`, oldest first.
+  pub async fn get_importable_entries(
+    transaction: &mut Transaction<'_, Postgres>,
+    source_guild_id: &GuildId,
+    guild_id: &GuildId,
+    user_id: &UserId,
+  ) -> Result<Vec<Meditation>> {
+    Ok(
+      ProfileImport::importable_entries(*source_guild_id, *guild_id, *user_id)
+        .fetch_all(&mut **transaction)
+        .await?,
+    )
+  }
+
+  /// Adds a [`ProfileImport`], along with a copy of each of the source guild's entries.
+  pub async fn add_profile_import(
+    transaction: &mut Transaction<'_, Postgres>,
+    import: &ProfileImport,
+    entries: &[Meditation],
+  ) -> Result<()> {
+    import.insert_query().execute(&mut **transaction).await?;
+    for entry in entries {
+      import
+        .entry_query(entry)
+        .execute(&mut **transaction)
+        .await?;
+    }
+
+    Ok(())
+  }
+
+  /// Adds a [`MentorSignup`], or updates the member's existing signup.
+  pub async fn add_mentor_signup(
+    transaction: &mut Transaction<'_, Postgres>,
+    signup: &MentorSignup,
+  ) -> Result<()> {
+    signup.insert_query().execute(&mut **transaction).await?;
+
+    Ok(())
+  }
+
+  pub async fn get_mentor_signup(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    user_id: &UserId,
+  ) -> Result<Option<MentorSignup>> {
+    Ok(
+      MentorSignup::retrieve(*guild_id, *user_id)
+        .fetch_optional(&mut **transaction)
+        .await?,
+    )
+  }
+
+  /// Removes a member's [`MentorSignup`], returning the number of rows affected. A result
+  /// of `0` means the member hadn't signed up.
+  pub async fn remove_mentor_signup(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    user_id: &UserId,
+  ) -> Result<u64> {
+    Ok(
+      MentorSignup::delete_query(*guild_id, user_id.to_string())
+        .execute(&mut **transaction)
+        .await?
+        .rows_affected(),
+    )
+  }
+
+  /// Returns the [`MentorSignup`]s of members who aren't in an active [`Mentorship`].
+  pub async fn get_unmatched_mentor_signups(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+  ) -> Result<Vec<MentorSignup>> {
+    Ok(
+      MentorSignup::retrieve_unmatched(*guild_id)
+        .fetch_all(&mut **transaction)
+        .await?,
+    )
+  }
+
+  /// Adds a [`Mentorship`], returning `false` if either member is already in an active
+  /// mentorship.
+  pub async fn add_mentorship(
+    transaction: &mut Transaction<'_, Postgres>,
+    mentorship: &Mentorship,
+  ) -> Result<bool> {
+    Ok(
+      mentorship
+        .insert_query()
+        .execute(&mut **transaction)
+        .await?
+        .rows_affected()
+        > 0,
+    )
+  }
+
+  pub async fn get_active_mentorships(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+  ) -> Result<Vec<Mentorship>> {
+    Ok(
+      Mentorship::retrieve_active(*guild_id)
+        .fetch_all(&mut **transaction)
+        .await?,
+    )
+  }
+
+  pub async fn get_active_mentorship(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    user_id: &UserId,
+  ) -> Result<Option<Mentorship>> {
+    Ok(
+      Mentorship::retrieve_active_for(*guild_id, *user_id)
+        .fetch_optional(&mut **transaction)
+        .await?,
+    )
+  }
+
+  /// Records a check-in for the member's active [`Mentorship`], returning the number of
+  /// rows affected. A result of `0` means the member isn't in an active mentorship.
+  pub async fn check_in_mentorship(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    user_id: &UserId,
+    now: &DateTime<Utc>,
+  ) -> Result<u64> {
+    Ok(
+      Mentorship::check_in(*guild_id, *user_id, now)
+        .execute(&mut **transaction)
+        .await?
+        .rows_affected(),
+    )
+  }
+
+  pub async fn get_due_mentorship_check_ins(
+    transaction: &mut Transaction<'_, Postgres>,
+    now: &DateTime<Utc>,
+    interval_days: i32,
+  ) -> Result<Vec<Mentorship>> {
+    Ok(
+      Mentorship::retrieve_due_check_ins(now, interval_days)
+        .fetch_all(&mut **transaction)
+        .await?,
+    )
+  }
+
+  pub async fn mark_mentorship_reminded(
+    transaction: &mut Transaction<'_, Postgres>,
+    mentorship_id: &str,
+    now: &DateTime<Utc>,
+  ) -> Result<()> {
+    Mentorship::mark_reminded(mentorship_id, now)
+      .execute(&mut **transaction)
+      .await?;
+
+    Ok(())
+  }
+
+  /// Ends a [`Mentorship`], returning the number of rows affected. A result of `0` means
+  /// the mentorship doesn't exist or has already ended.
+  pub async fn end_mentorship(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    mentorship_id: &str,
+  ) -> Result<u64> {
+    Ok(
+      Mentorship::end(*guild_id, mentorship_id)
+        .execute(&mut **transaction)
+        .await?
+        .rows_affected(),
+    )
+  }
+
+  pub async fn add_study_group(
+    transaction: &mut Transaction<'_, Postgres>,
+    study_group: &StudyGroup,
+  ) -> Result<()> {
+    study_group
+      .insert_query()
+      .execute(&mut **transaction)
+      .await?;
+
+    Ok(())
+  }
+
+  pub async fn get_study_group(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    name: &str,
+  ) -> Result<Option<StudyGroup>> {
+    Ok(
+      StudyGroup::retrieve(*guild_id, name)
+        .fetch_optional(&mut **transaction)
+        .await?,
+    )
+  }
+
+  pub async fn get_study_groups(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+  ) -> Result<Vec<StudyGroup>> {
+    Ok(
+      StudyGroup::retrieve_all(*guild_id)
+        .fetch_all(&mut **transaction)
+        .await?,
+    )
+  }
+
+  /// Removes a [`StudyGroup`], along with its members and discussion prompts, returning the
+  /// number of groups removed.
+  pub async fn remove_study_group(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    name: &str,
+  ) -> Result<u64> {
+    StudyGroup::remove_prompts(*guild_id, name)
+      .execute(&mut **transaction)
+      .await?;
+
+    Ok(
+      StudyGroup::delete_query(*guild_id, name)
+        .execute(&mut **transaction)
+        .await?
+        .rows_affected(),
+    )
+  }
+
+  /// Replaces the recurring post which posts a [`StudyGroup`]'s discussion prompts.
+  pub async fn set_study_group_prompts(
+    transaction: &mut Transaction<'_, Postgres>,
+    study_group: &StudyGroup,
+    post: &RecurringPost,
+  ) -> Result<()> {
+    StudyGroup::remove_prompts(study_group.guild_id, &study_group.name)
+      .execute(&mut **transaction)
+      .await?;
+    post.insert_query().execute(&mut **transaction).await?;
+    StudyGroup::set_prompts(&study_group.id, &post.id)
+      .execute(&mut **transaction)
+      .await?;
+
+    Ok(())
+  }
+
+  /// Adds a member to a [`StudyGroup`], returning `false` if they were already in it.
+  pub async fn add_study_group_member(
+    transaction: &mut Transaction<'_, Postgres>,
+    member: &StudyGroupMember,
+  ) -> Result<bool> {
+    Ok(
+      member
+        .insert_query()
+        .execute(&mut **transaction)
+        .await?
+        .rows_affected()
+        > 0,
+    )
+  }
+
+  pub async fn get_study_group_members(
+    transaction: &mut Transaction<'_, Postgres>,
+    group_id: &str,
+  ) -> Result<Vec<StudyGroupMember>> {
+    Ok(
+      StudyGroupMember::retrieve_all(group_id)
+        .fetch_all(&mut **transaction)
+        .await?,
+    )
+  }
+
+  /// Records a member's reading progress for the week starting on `week`, returning the
+  /// number of rows affected. A result of `0` means the member isn't in the group.
+  pub async fn update_study_group_progress(
+    transaction: &mut Transaction<'_, Postgres>,
+    group_id: &str,
+    user_id: &UserId,
+    progress: &str,
+    week: NaiveDate,
+  ) -> Result<u64> {
+    Ok(
+      StudyGroupMember::update_progress(group_id, *user_id, progress, week)
+        .execute(&mut **transaction)
+        .await?
+        .rows_affected(),
+    )
+  }
+
+  pub async fn remove_study_group_member(
+    transaction: &mut Transaction<'_, Postgres>,
+    group_id: &str,
+    user_id: &UserId,
+  ) -> Result<u64> {
+    Ok(
+      StudyGroupMember::remove(group_id, *user_id)
+        .execute(&mut **transaction)
+        .await?
+        .rows_affected(),
+    )
+  }
+
+  pub async fn delete_streak(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    user_id: &UserId,
+  ) -> Result<()> {
+    Streak::reset(*guild_id, *user_id)
+      .execute(&mut **transaction)
+      .await?;
+
+    Ok(())
+  }
+
+  /// Retrieves every stored [`Streak`] in a guild.
+  pub async fn get_guild_streaks(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+  ) -> Result<Vec<Streak>> {
+    Ok(
+      Streak::guild(*guild_id)
+        .fetch_all(&mut **transaction)
+        .await?,
+    )
+  }
+
+  /// Retrieves a guild's meditation entries for an anonymized dataset, leaving out members
+  /// with fewer than `min_sessions` entries or with private stats.
+  pub async fn get_anonymizable_meditation(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    min_sessions: i64,
+  ) -> Result<Vec<Meditation>> {
+    Ok(
+      Meditation::anonymizable(*guild_id, min_sessions)
+        .fetch_all(&mut **transaction)
+        .await?,
+    )
+  }
+
+  /// Retrieves the guild a user most recently tracked meditation in, if any.
+  pub async fn get_latest_meditation_guild(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: &UserId,
+  ) -> Result<Option<GuildId>> {
+    Meditation::latest_guild(*user_id)
+      .fetch_optional(&mut **transaction)
+      .await?
+      .map(|guild_id| Ok(GuildId::new(guild_id.parse::<u64>()?)))
+      .transpose()
+  }
+
+  /// Retrieves every user with meditation entries in a guild.
+  pub async fn get_meditating_users(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+  ) -> Result<Vec<UserId>> {
+    Meditation::guild_users(*guild_id)
+      .fetch_all(&mut **transaction)
+      .await?
+      .iter()
+      .map(|user_id| Ok(UserId::new(user_id.parse::<u64>()?)))
+      .collect()
+  }
+
+  pub async fn add_course(
+    transaction: &mut Transaction<'_, Postgres>,
+    course: &Course,
+  ) -> Result<()> {
+    course.insert_query().execute(&mut **transaction).await?;
+
+    Ok(())
+  }
+
+  pub async fn update_course(
+    transaction: &mut Transaction<'_, Postgres>,
+    course: &Course,
+  ) -> Result<()> {
+    course.update_query().execute(&mut **transaction).await?;
+
+    Ok(())
+  }
+
+  pub async fn remove_course(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    course_name: &str,
+  ) -> Result<()> {
+    Course::delete_query(*guild_id, course_name)
+      .execute(&mut **transaction)
+      .await?;
+
+    Ok(())
+  }
+
+  pub async fn course_exists(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    course_name: &str,
+  ) -> Result<bool> {
+    Ok(
+      Course::exists_query::<Exists>(*guild_id, course_name)
+        .fetch_one(&mut **transaction)
+        .await?
+        .exists,
+    )
+  }
+
+  pub async fn get_course(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    course_name: &str,
+  ) -> Result<Option<Course>> {
+    Ok(
+      Course::retrieve(*guild_id, course_name)
+        .fetch_optional(&mut **transaction)
+        .await?,
+    )
+  }
+
+  pub async fn get_course_in_dm(
+    transaction: &mut Transaction<'_, Postgres>,
+    course_name: &str,
+  ) -> Result<Option<Course>> {
+    Ok(
+      Course::retrieve_in_dm(course_name)
+        .fetch_optional(&mut **transaction)
+        .await?,
+    )
+  }
+
+  pub async fn get_possible_course(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    course_name: &str,
+    similarity: f32,
+  ) -> Result<Option<Course>> {
+    Ok(
+      Course::retrieve_similar(*guild_id, course_name, similarity)
+        .fetch_optional(&mut **transaction)
+        .await?,
+    )
+  }
+
+  pub async fn get_all_courses(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+  ) -> Result<Vec<Course>> {
+    Ok(
+      Course::retrieve_all(*guild_id)
+        .fetch_all(&mut **transaction)
+        .await?,
+    )
+  }
+
+  pub async fn add_enrollment_code(
+    transaction: &mut Transaction<'_, Postgres>,
+    enrollment_code: &EnrollmentCode,
+  ) -> Result<()> {
+    enrollment_code
+      .insert_query()
+      .execute(&mut **transaction)
+      .await?;
+
+    Ok(())
+  }
+
+  pub async fn remove_enrollment_code(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    code: &str,
+  ) -> Result<u64> {
+    let result = EnrollmentCode::delete_query(*guild_id, code)
+      .execute(&mut **transaction)
+      .await?;
+
+    Ok(result.rows_affected())
+  }
+
+  pub async fn get_enrollment_code(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    code: &str,
+  ) -> Result<Option<EnrollmentCode>> {
+    Ok(
+      EnrollmentCode::retrieve(*guild_id, code)
+        .fetch_optional(&mut **transaction)
+        .await?,
+    )
+  }
+
+  pub async fn get_enrollment_codes(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    course_name: &str,
+  ) -> Result<Vec<EnrollmentCode>> {
+    Ok(
+      EnrollmentCode::retrieve_all(*guild_id, course_name)
+        .fetch_all(&mut **transaction)
+        .await?,
+    )
+  }
+
+  /// Uses up one redemption of an [`EnrollmentCode`], returning the number of codes updated.
+  /// Returns `0` if the code does not exist or has no uses remaining.
+  pub async fn redeem_enrollment_code(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    code: &str,
+  ) -> Result<u64> {
+    let result = EnrollmentCode::redeem(*guild_id, code)
+      .execute(&mut **transaction)
+      .await?;
+
+    Ok(result.rows_affected())
+  }
+
+  pub async fn record_course_enrollment(
+    transaction: &mut Transaction<'_, Postgres>,
+    enrollment: &Enrollment,
+  ) -> Result<()> {
+    enrollment
+      .insert_query()
+      .execute(&mut **transaction)
+      .await?;
+
+    Ok(())
+  }
+
+  pub async fn add_steam_key(
+    transaction: &mut Transaction<'_, Postgres>,
+    steam_key: &SteamKey,
+  ) -> Result<()> {
+    steam_key.insert_query().execute(&mut **transaction).await?;
+
+    Ok(())
+  }
+
+  pub async fn remove_steam_key(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    key: &str,
+  ) -> Result<()> {
+    SteamKey::delete_query(*guild_id, key)
+      .execute(&mut **transaction)
+      .await?;
+
+    Ok(())
+  }
+
+  pub async fn steam_key_exists(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    key: &str,
+  ) -> Result<bool> {
+    Ok(
+      SteamKey::exists_query::<Exists>(*guild_id, Some(key))
+        .fetch_one(&mut **transaction)
+        .await?
+        .exists,
+    )
+  }
+
+  pub async fn unused_key_exists(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+  ) -> Result<bool> {
+    Ok(
+      SteamKey::exists_query::<Exists>(*guild_id, None)
+        .fetch_one(&mut **transaction)
+        .await?
+        .exists,
+    )
+  }
+
+  pub async fn get_key_and_mark_used(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+  ) -> Result<Option<String>> {
+    Ok(
+      SteamKey::consume(*guild_id)
+        .fetch_optional(&mut **transaction)
+        .await?
+        .map(|consumed| consumed.key),
+    )
+  }
+
+  pub async fn get_all_steam_keys(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+  ) -> Result<Vec<SteamKey>> {
+    Ok(
+      SteamKey::retrieve_all(*guild_id)
+        .fetch_all(&mut **transaction)
+        .await?,
+    )
+  }
+
+  pub async fn reserve_key(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    user_id: &UserId,
+  ) -> Result<Option<String>> {
+    Ok(
+      SteamKey::reserve(*guild_id, *user_id)
+        .fetch_optional(&mut **transaction)
+        .await?
+        .map(|reserved| reserved.key),
+    )
+  }
+
+  pub async fn unreserve_key(
+    connection: &mut PoolConnection<Postgres>,
+    guild_id: &GuildId,
+    key: &str,
+  ) -> Result<()> {
+    SteamKey::unreserve(*guild_id, key)
+      .execute(&mut **connection)
+      .await?;
+
+    Ok(())
+  }
+
+  /// Flags unused keys which have expired and returns any which were reserved to the pool.
+  /// Returns the newly flagged keys, with who each was reserved for.
+  pub async fn flag_expired_keys(
+    connection: &mut PoolConnection<Postgres>,
+  ) -> Result<Vec<SteamKey>> {
+    Ok(
+      SteamKey::flag_expired()
+        .fetch_all(&mut **connection)
+        .await?,
+    )
+  }
+
+  /// Returns keys which were reserved at or before `cutoff` without being offered to the
+  /// pool. Returns the released keys, with who each was reserved for.
+  pub async fn release_stale_keys(
+    connection: &mut PoolConnection<Postgres>,
+    cutoff: &DateTime<Utc>,
+  ) -> Result<Vec<SteamKey>> {
+    Ok(
+      SteamKey::release_stale(*cutoff)
+        .fetch_all(&mut **connection)
+        .await?,
+    )
+  }
+
+  pub async fn mark_key_used(
+    connection: &mut PoolConnection<Postgres>,
+    guild_id: &GuildId,
+    key: &str,
+  ) -> Result<()> {
+    SteamKey::mark_used(*guild_id, key)
+      .execute(&mut **connection)
+      .await?;
+
+    Ok(())
+  }
+
+  pub async fn record_key_offer(
+    connection: &mut PoolConnection<Postgres>,
+    key_offer: &KeyOffer,
+  ) -> Result<()> {
+    key_offer.update_query().execute(&mut **connection).await?;
+
+    Ok(())
+  }
+
+  pub async fn get_key_offer(
+    connection: &mut PoolConnection<Postgres>,
+    offer_id: &str,
+  ) -> Result<Option<KeyOffer>> {
+    Ok(
+      KeyOffer::retrieve(offer_id)
+        .fetch_optional(&mut **connection)
+        .await?,
+    )
+  }
+
+  pub async fn get_expired_key_offers(
+    connection: &mut PoolConnection<Postgres>,
+  ) -> Result<Vec<KeyOffer>> {
+    Ok(
+      KeyOffer::retrieve_expired()
+        .fetch_all(&mut **connection)
+        .await?,
+    )
+  }
+
+  /// Marks the key of an outstanding offer as used, returning the number of keys affected.
+  /// A result of `0` means the offer has already been resolved.
+  pub async fn redeem_key_offer(
+    connection: &mut PoolConnection<Postgres>,
+    guild_id: &GuildId,
+    offer_id: &str,
+  ) -> Result<u64> {
+    Ok(
+      KeyOffer::redeem(*guild_id, offer_id)
+        .execute(&mut **connection)
+        .await?
+        .rows_affected(),
+    )
+  }
+
+  /// Retrieves the key redeemed through an offer, so that redeeming the same offer again
+  /// gives the user the same key.
+  pub async fn get_redeemed_key(
+    connection: &mut PoolConnection<Postgres>,
+    offer_id: &str,
+    user_id: &UserId,
+  ) -> Result<Option<String>> {
+    Ok(
+      KeyOffer::retrieve_redeemed(offer_id, *user_id)
+        .fetch_optional(&mut **connection)
+        .await?
+        .map(|redeemed| redeemed.key),
+    )
+  }
+
+  /// Returns the key of an outstanding offer to the pool, returning the number of keys
+  /// affected. A result of `0` means the offer has already been resolved.
+  pub async fn withdraw_key_offer(
+    connection: &mut PoolConnection<Postgres>,
+    guild_id: &GuildId,
+    offer_id: &str,
+  ) -> Result<u64> {
+    Ok(
+      KeyOffer::withdraw(*guild_id, offer_id)
+        .execute(&mut **connection)
+        .await?
+        .rows_affected(),
+    )
+  }
+
+  pub async fn add_pending_interaction(
+    transaction: &mut Transaction<'_, Postgres>,
+    pending_interaction: &PendingInteraction,
+  ) -> Result<()> {
+    pending_interaction
+      .insert_query()
+      .execute(&mut **transaction)
+      .await?;
+
+    Ok(())
+  }
+
+  /// Retrieves a [`PendingInteraction`] of the specified [`InteractionFlow`]. Returns
+  /// [`None`] if it doesn't exist or has expired.
+  pub async fn get_pending_interaction(
+    transaction: &mut Transaction<'_, Postgres>,
+    flow: InteractionFlow,
+    id: &str,
+  ) -> Result<Option<PendingInteraction>> {
+    Ok(
+      PendingInteraction::retrieve(flow, id)
+        .fetch_optional(&mut **transaction)
+        .await?,
+    )
+  }
+
+  /// Removes every [`PendingInteraction`] which has expired, since flows aren't removed when
+  /// members stop responding to them. Returns the number removed.
+  pub async fn remove_expired_pending_interactions(
+    transaction: &mut Transaction<'_, Postgres>,
+  ) -> Result<u64> {
+    Ok(
+      PendingInteraction::remove_expired()
+        .execute(&mut **transaction)
+        .await?
+        .rows_affected(),
+    )
+  }
+
+  /// Removes a [`PendingInteraction`], returning whether it existed. A result of `false`
+  /// means the flow has already been resolved.
+  pub async fn remove_pending_interaction(
+    transaction: &mut Transaction<'_, Postgres>,
+    id: &str,
+  ) -> Result<bool> {
+    Ok(
+      PendingInteraction::remove(id)
+        .execute(&mut **transaction)
+        .await?
+        .rows_affected()
+        > 0,
+    )
+  }
+
+  pub async fn add_term(transaction: &mut Transaction<'_, Postgres>, term: &Term) -> Result<()> {
+    term.insert_query().execute(&mut **transaction).await?;
+
+    Ok(())
+  }
+
+  pub async fn update_term(transaction: &mut Transaction<'_, Postgres>, term: &Term) -> Result<()> {
+    term.update_query().execute(&mut **transaction).await?;
+
+    Ok(())
+  }
+
+  pub async fn update_term_embedding(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    term_name: &str,
+    vector: Option<&Vector>,
+  ) -> Result<()> {
+    Term::update_embedding(*guild_id, term_name, vector)
+      .execute(&mut **transaction)
+      .await?;
+
+    Ok(())
+  }
+
+  pub async fn remove_term(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    term_name: &str,
+  ) -> Result<()> {
+    Term::delete_query(*guild_id, term_name)
+      .execute(&mut **transaction)
+      .await?;
+    Term::remove_related_references(*guild_id, term_name)
+      .execute(&mut **transaction)
+      .await?;
+
+    Ok(())
+  }
+
+  pub async fn get_term(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    term_name: &str,
+  ) -> Result<Option<Term>> {
+    Ok(
+      Term::retrieve(*guild_id, term_name)
+        .fetch_optional(&mut **transaction)
+        .await?,
+    )
+  }
+
+  pub async fn term_name_exists(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    name: &str,
+  ) -> Result<bool> {
+    Ok(
+      Term::name_exists::<Exists>(*guild_id, name)
+        .fetch_one(&mut **transaction)
+        .await?
+        .exists,
+    )
+  }
+
+  pub async fn get_term_names(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    partial: &str,
+  ) -> Result<Vec<String>> {
+    let names = Term::retrieve_names(*guild_id, partial)
+      .fetch_all(&mut **transaction)
+      .await?;
+
+    Ok(names.into_iter().map(|row| row.name).collect())
+  }
+
+  pub async fn add_term_alias(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    term_name: &str,
+    alias: &str,
+  ) -> Result<()> {
+    Term::add_alias(*guild_id, term_name, alias)
+      .execute(&mut **transaction)
+      .await?;
+
+    Ok(())
+  }
+
+  pub async fn remove_term_alias(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    term_name: &str,
+    alias: &str,
+  ) -> Result<u64> {
+    Ok(
+      Term::remove_alias(*guild_id, term_name, alias)
+        .execute(&mut **transaction)
+        .await?
+        .rows_affected(),
+    )
+  }
+
+  pub async fn add_related_term(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    term_name: &str,
+    related_name: &str,
+  ) -> Result<()> {
+    Term::add_related(*guild_id, term_name, related_name)
+      .execute(&mut **transaction)
+      .await?;
+
+    Ok(())
+  }
+
+  pub async fn remove_related_term(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    term_name: &str,
+    related_name: &str,
+  ) -> Result<u64> {
+    Ok(
+      Term::remove_related(*guild_id, term_name, related_name)
+        .execute(&mut **transaction)
+        .await?
+        .rows_affected(),
+    )
+  }
+
+  pub async fn get_term_meaning(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    term_name: &str,
+  ) -> Result<Option<Term>> {
+    Ok(
+      Term::retrieve_meaning(*guild_id, term_name)
+        .fetch_optional(&mut **transaction)
+        .await?,
+    )
+  }
+
+  pub async fn get_term_list(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+  ) -> Result<Vec<Term>> {
+    Ok(
+      Term::retrieve_list(*guild_id)
+        .fetch_all(&mut **transaction)
+        .await?,
+    )
+  }
+
+  pub async fn get_possible_terms(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    term_name: &str,
+    similarity: f32,
+  ) -> Result<Vec<Term>> {
+    Ok(
+      Term::retrieve_similar(*guild_id, term_name, similarity)
+        .fetch_all(&mut **transaction)
+        .await?,
+    )
+  }
+
+  pub async fn search_terms_by_vector(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    search_vector: &Vector,
+    limit: i64,
+    threshold: f64,
+  ) -> Result<Vec<VectorSearch>> {
+    Ok(
+      VectorSearch::result(*guild_id, search_vector, limit, threshold)
+        .fetch_all(&mut **transaction)
+        .await?,
+    )
+  }
+
+  pub async fn get_term_count(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+  ) -> Result<u64> {
+    Ok(
+      Term::count::<Aggregate>(*guild_id)
+        .fetch_one(&mut **transaction)
+        .await?
+        .count,
+    )
+  }
+
+  pub async fn get_challenge_stats(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    user_id: &UserId,
+    timeframe: &ChallengeTimeframe,
+  ) -> Result<User> {
+    // Add 840 minutes to end_time to account for maximum time zone offset.
+    let end_time = Utc::now() + ChronoDuration::minutes(840);
+    let start_time = match timeframe {
+      ChallengeTimeframe::Monthly => Utc::now()
+        .with_day(1)
+        .with_context(|| "Failed to set day to 1")?
+        .with_hour(0)
+        .with_context(|| "Failed to set hour to 0")?
+        .with_minute(0)
+        .with_context(|| "Failed to set minute to 0")?,
+      ChallengeTimeframe::YearRound => Utc::now()
+        .with_month(1)
+        .with_context(|| "Failed to set month to 1")?
+        .with_day(1)
+        .with_context(|| "Failed to set day to 1")?
+        .with_hour(0)
+        .with_context(|| "Failed to set hour to 0")?
+        .with_minute(0)
+        .with_context(|| "Failed to set minute to 0")?,
+    };
+
+    let sum_and_count =
+      TimeframeStats::user_sum_and_count(*guild_id, *user_id, &start_time, &end_time)
+        .fetch_one(&mut **transaction)
+        .await?;
+
+    let streak = DatabaseHandler::get_streak(transaction, guild_id, user_id).await?;
+    let user_stats = User::challenge_stats(sum_and_count, streak);
+
+    Ok(user_stats)
+  }
+
+  pub async fn get_leaderboard_stats(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    timeframe: &Timeframe,
+    sort_by: &SortBy,
+    leaderboard_type: &LeaderboardType,
+  ) -> Result<Vec<LeaderboardUser>> {
+    Ok(
+      LeaderboardUser::stats(*guild_id, timeframe, sort_by, leaderboard_type)
+        .fetch_all(&mut **transaction)
+        .await?,
+    )
+  }
+
+  /// Calculates a guild's leaderboard for a timeframe from meditation tracked in a channel.
+  pub async fn get_channel_leaderboard_stats(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    channel_id: &ChannelId,
+    timeframe: &Timeframe,
+    sort_by: &SortBy,
+    leaderboard_type: &LeaderboardType,
+  ) -> Result<Vec<LeaderboardUser>> {
+    Ok(
+      LeaderboardUser::channel_stats(*guild_id, *channel_id, timeframe, sort_by, leaderboard_type)
+        .fetch_all(&mut **transaction)
+        .await?,
+    )
+  }
+
+  /// Retrieves every member on a guild's leaderboard for a timeframe.
+  pub async fn get_leaderboard_users(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    timeframe: &Timeframe,
+  ) -> Result<Vec<LeaderboardUser>> {
+    Ok(
+      LeaderboardUser::all(*guild_id, timeframe)
+        .fetch_all(&mut **transaction)
+        .await?,
+    )
+  }
+
+  /// Calculates what a guild's leaderboard for a timeframe would show if it were refreshed.
+  pub async fn calculate_leaderboard_users(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    timeframe: &Timeframe,
+  ) -> Result<Vec<LeaderboardUser>> {
+    Ok(
+      LeaderboardUser::calculate(*guild_id, timeframe)
+        .fetch_all(&mut **transaction)
+        .await?,
+    )
+  }
+
+  pub async fn get_user_stats(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    user_id: &UserId,
+    timeframe: &Timeframe,
+  ) -> Result<User> {
+    // Get total count, total sum, and count/sum for timeframe.
+    // If the user has set a custom time zone, we use that for our timeframe.
+    // Otherwise, we default to UTC.
+    let user_offset = DatabaseHandler::get_tracking_profile(transaction, guild_id, user_id)
+      .await?
+      .map_or(0, |profile| profile.utc_offset);
+    let end_time = Utc::now() + ChronoDuration::minutes(user_offset.into());
+    let end_trunc = end_time.duration_trunc(TimeDelta::days(1))?;
+    let start_time = match timeframe {
+      Timeframe::Daily => end_trunc - ChronoDuration::days(11),
+      Timeframe::Weekly => {
+        let target_week = end_trunc - ChronoDuration::weeks(11);
+        target_week - TimeDelta::days(target_week.weekday().number_from_monday().into())
+      }
+      Timeframe::Monthly => end_trunc
+        .checked_sub_months(Months::new(11))
+        .with_context(|| "Failed to subtract 11 months")?
+        .with_day(1)
+        .with_context(|| "Failed to set day to 1")?,
+      Timeframe::Yearly => end_trunc
+        .checked_sub_months(Months::new(12 * 11))
+        .with_context(|| "Failed to subtract 11 years")?
+        .with_month(1)
+        .with_context(|| "Failed to set month to 1")?
+        .with_day(1)
+        .with_context(|| "Failed to set day to 1")?,
+    };
+
+    let total_data = TimeframeStats::user_total_sum_and_count(*guild_id, *user_id)
+      .fetch_one(&mut **transaction)
+      .await?;
+
+    let timeframe_data =
+      TimeframeStats::user_sum_and_count(*guild_id, *user_id, &start_time, &end_time)
+        .fetch_one(&mut **transaction)
+        .await?;
+
+    let user_stats = User::new(
+      total_data.sum.unwrap_or(0),
+      total_data.count.unwrap_or(0).try_into()?,
+      timeframe_data,
+      DatabaseHandler::get_streak(transaction, guild_id, user_id).await?,
+    );
+
+    Ok(user_stats)
+  }
+
+  /// Retrieves anonymous totals across guilds which have turned on global stats, for the
+  /// current UTC day and the past seven days.
+  pub async fn get_global_stats(transaction: &mut Transaction<'_, Postgres>) -> Result<Global> {
+    let today = Utc::now().duration_trunc(TimeDelta::days(1))?;
+    let week_start = today - ChronoDuration::days(6);
+
+    Ok(
+      Global::totals(today, week_start)
+        .fetch_one(&mut **transaction)
+        .await?,
+    )
+  }
+
+  pub async fn get_guild_stats(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    timeframe: &Timeframe,
+  ) -> Result<Guild> {
+    // Get total count, total sum, and count/sum for timeframe.
+    // We use UTC for our timeframe, since it is the bot default.
+    let end_time = Utc::now();
+    let end_trunc = end_time.duration_trunc(TimeDelta::days(1))?;
+    let start_time = match timeframe {
+      Timeframe::Daily => end_trunc - ChronoDuration::days(11),
+      Timeframe::Weekly => {
+        let target_week = end_trunc - ChronoDuration::weeks(11);
+        target_week - TimeDelta::days(target_week.weekday().number_from_monday().into())
+      }
+      Timeframe::Monthly => end_trunc
+        .checked_sub_months(Months::new(11))
+        .with_context(|| "Failed to subtract 11 months")?
+        .with_day(1)
+        .with_context(|| "Failed to set day to 1")?,
+      Timeframe::Yearly => end_trunc
+        .checked_sub_months(Months::new(12 * 11))
+        .with_context(|| "Failed to subtract 11 years")?
+        .with_month(1)
+        .with_context(|| "Failed to set month to 1")?
+        .with_day(1)
+        .with_context(|| "Failed to set day to 1")?,
+    };
+
+    let total_data = TimeframeStats::guild_total_sum_and_count(*guild_id)
+      .fetch_one(&mut **transaction)
+      .await?;
+
+    let timeframe_data = TimeframeStats::guild_sum_and_count(*guild_id, &start_time, &end_time)
+      .fetch_one(&mut **transaction)
+      .await?;
+
+    let guild_stats = Guild::new(
+      total_data.sum.unwrap_or(0),
+      total_data.count.unwrap_or(0).try_into()?,
+      timeframe_data,
+    );
+
+    Ok(guild_stats)
+  }
+
+  /// Retrieves a member's stats for the 12 periods of `timeframe` starting `periods_ago`
+  /// periods ago, in chronological order. With `periods_ago` set to 0, the last period is
+  /// the current one.
+  pub async fn get_user_chart_stats(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    user_id: &UserId,
+    timeframe: &Timeframe,
+    offset: i16,
+    periods_ago: i32,
+  ) -> Result<Vec<TimeframeStats>> {
+    let now_offset = Utc::now() + ChronoDuration::minutes(offset.into());
+
+    let (rows, fresh_data) = if let Timeframe::Daily = timeframe {
+      // Calculate fresh data for 12 days.
+      let rows = ByInterval::user_fresh(*guild_id, *user_id, timeframe, &now_offset, periods_ago)
+        .fetch_all(&mut **transaction)
+        .await?;
+      (rows, None)
+    } else {
+      // Calculate fresh data for present week/month/year, if it's included.
+      let fresh_data = if periods_ago == 0 {
+        let current =
+          ByInterval::user_fresh(*guild_id, *user_id, timeframe, &now_offset, periods_ago)
+            .fetch_optional(&mut **transaction)
+            .await?;
+        Some(Self::interval_stats(current.as_ref()))
+      } else {
+        None
+      };
+
+      // Get data for previous weeks/months/years from materialized view.
+      let rows = ByInterval::user_from_view(*guild_id, *user_id, timeframe, periods_ago)
+        .fetch_all(&mut **transaction)
+        .await?;
+      (rows, fresh_data)
+    };
+
+    Ok(Self::chart_stats_from_rows(&rows, fresh_data, periods_ago))
+  }
+
+  /// Retrieves a guild's stats for the 12 periods of `timeframe` starting `periods_ago`
+  /// periods ago, in chronological order. With `periods_ago` set to 0, the last period is
+  /// the current one.
+  pub async fn get_guild_chart_stats(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    timeframe: &Timeframe,
+    periods_ago: i32,
+  ) -> Result<Vec<TimeframeStats>> {
+    let (rows, fresh_data) = if let Timeframe::Daily = timeframe {
+      // Calculate fresh data for 12 days.
+      let rows = ByInterval::guild_fresh(*guild_id, timeframe, periods_ago)
+        .fetch_all(&mut **transaction)
+        .await?;
+      (rows, None)
+    } else {
+      // Calculate fresh data for present week/month/year, if it's included.
+      let fresh_data = if periods_ago == 0 {
+        let current = ByInterval::guild_fresh(*guild_id, timeframe, periods_ago)
+          .fetch_optional(&mut **transaction)
+          .await?;
+        Some(Self::interval_stats(current.as_ref()))
+      } else {
+        None
+      };
+
+      // Get data for previous weeks/months/years from materialized view.
+      let rows = ByInterval::guild_from_view(*guild_id, timeframe, periods_ago)
+        .fetch_all(&mut **transaction)
+        .await?;
+      (rows, fresh_data)
+    };
+
+    Ok(Self::chart_stats_from_rows(&rows, fresh_data, periods_ago))
+  }
+
+  /// Retrieves a member's stats between `start` and `end` inclusive, in their UTC offset,
+  /// for each period of `timeframe`. Every period in the range is included, in chronological
+  /// order, with the date it starts on. When `channel_id` is set, only meditation tracked in
+  /// that channel is included.
+  #[allow(clippy::too_many_arguments)]
+  pub async fn get_user_range_stats(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    user_id: &UserId,
+    timeframe: &Timeframe,
+    start: NaiveDate,
+    end: NaiveDate,
+    offset: i16,
+    channel_id: Option<ChannelId>,
+  ) -> Result<Vec<(NaiveDate, TimeframeStats)>> {
+    let rows = ByPeriod::user_range(
+      *guild_id, *user_id, timeframe, start, end, offset, channel_id,
+    )
+    .fetch_all(&mut **transaction)
+    .await?;
+
+    Ok(Self::range_stats_from_rows(&rows, timeframe, start, end))
+  }
+
+  /// Retrieves a guild's stats between `start` and `end` inclusive, in UTC, for each period
+  /// of `timeframe`. Every period in the range is included, in chronological order, with the
+  /// date it starts on. When `channel_id` is set, only meditation tracked in that channel is
+  /// included.
+  pub async fn get_guild_range_stats(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    timeframe: &Timeframe,
+    start: NaiveDate,
+    end: NaiveDate,
+    channel_id: Option<ChannelId>,
+  ) -> Result<Vec<(NaiveDate, TimeframeStats)>> {
+    let rows = ByPeriod::guild_range(*guild_id, timeframe, start, end, channel_id)
+      .fetch_all(&mut **transaction)
+      .await?;
+
+    Ok(Self::range_stats_from_rows(&rows, timeframe, start, end))
+  }
+
+  fn range_stats_from_rows(
+    rows: &[ByPeriod],
+    timeframe: &Timeframe,
+    start: NaiveDate,
+    end: NaiveDate,
+  ) -> Vec<(NaiveDate, TimeframeStats)> {
+    timeframe
+      .periods(start, end)
+      .into_iter()
+      .map(|period| {
+        let row = rows.iter().find(|row| row.period == Some(period));
+        let stats = TimeframeStats::new(
+          Some(row.map_or(0, |row| row.meditation_minutes.unwrap_or(0))),
+          Some(row.map_or(0, |row| row.meditation_count.unwrap_or(0))),
+        );
+        (period, stats)
+      })
+      .collect()
+  }
+
+  /// Retrieves a guild's stats for each day of the week in UTC since `since`, starting
+  /// with Monday.
+  pub async fn get_guild_weekday_stats(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    since: &DateTime<Utc>,
+  ) -> Result<Vec<TimeframeStats>> {
+    let rows = ByRecurringPeriod::guild_by_weekday(*guild_id, since)
+      .fetch_all(&mut **transaction)
+      .await?;
+
+    Ok(Self::recurring_stats_from_rows(&rows, 1..=7))
+  }
+
+  /// Retrieves a guild's stats for each hour of the day in UTC since `since`, starting
+  /// with midnight.
+  pub async fn get_guild_hour_stats(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    since: &DateTime<Utc>,
+  ) -> Result<Vec<TimeframeStats>> {
+    let rows = ByRecurringPeriod::guild_by_hour(*guild_id, since)
+      .fetch_all(&mut **transaction)
+      .await?;
+
+    Ok(Self::recurring_stats_from_rows(&rows, 0..=23))
+  }
+
+  /// Arranges recurring period rows in the order of `periods`, filling in periods without
+  /// any meditation.
+  fn recurring_stats_from_rows(
+    rows: &[ByRecurringPeriod],
+    periods: RangeInclusive<i32>,
+  ) -> Vec<TimeframeStats> {
+    periods
+      .map(|period| {
+        let row = rows.iter().find(|row| row.period == Some(period));
+        let meditation_minutes = row.map_or(0, |row| row.meditation_minutes.unwrap_or(0));
+        let meditation_count = row.map_or(0, |row| row.meditation_count.unwrap_or(0));
+
+        TimeframeStats::new(Some(meditation_minutes), Some(meditation_count))
+      })
+      .collect()
+  }
+
+  /// Arranges chart stats rows into 12 periods, oldest first, filling in periods without
+  /// any meditation. `current` is `Some` when the current period was calculated separately,
+  /// in which case it's the last period.
+  fn chart_stats_from_rows(
+    rows: &[ByInterval],
+    current: Option<TimeframeStats>,
+    periods_ago: i32,
+  ) -> Vec<TimeframeStats> {
+    let range = if current.is_some() { 1..12 } else { 0..12 };
+    let mut stats: Vec<TimeframeStats> = range
+      .map(|i| {
+        // Comparison is safe since floor produces integer
+        #[allow(clippy::float_cmp)]
+        #[allow(clippy::expect_used)]
+        let row = rows.iter().find(|row| {
+          row
+            .times_ago
+            .expect("row should include times_ago since it is computed in the DB query")
+            == f64::from(i + periods_ago)
+        });
+
+        Self::interval_stats(row)
+      })
+      .rev()
+      .collect();
+
+    stats.extend(current);
+
+    stats
+  }
+
+  fn interval_stats(row: Option<&ByInterval>) -> TimeframeStats {
+    let meditation_minutes = row.map_or(0, |row| row.meditation_minutes.unwrap_or(0));
+    let meditation_count = row.map_or(0, |row| row.meditation_count.unwrap_or(0));
+
+    TimeframeStats::new(Some(meditation_minutes), Some(meditation_count))
+  }
+
+  pub async fn refresh_leaderboard(
+    transaction: &mut Transaction<'_, Postgres>,
+    timeframe: &Timeframe,
+  ) -> Result<()> {
+    MaterializedView::refresh(&ViewType::Leaderboard, timeframe)
+      .execute(&mut **transaction)
+      .await?;
+
+    Ok(())
+  }
+
+  pub async fn refresh_chart_stats(
+    transaction: &mut Transaction<'_, Postgres>,
+    timeframe: &Timeframe,
+  ) -> Result<()> {
+    MaterializedView::refresh(&ViewType::ChartStats, timeframe)
+      .execute(&mut **transaction)
+      .await?;
+
+    Ok(())
+  }
+
+  pub async fn add_star_message(
+    transaction: &mut Transaction<'_, Postgres>,
+    star_message: &StarMessage,
+  ) -> Result<()> {
+    star_message
+      .insert_query()
+      .execute(&mut **transaction)
+      .await?;
+
+    Ok(())
+  }
+
+  pub async fn remove_star_message(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    star_message: &str,
+  ) -> Result<()> {
+    StarMessage::delete_query(*guild_id, star_message)
+      .execute(&mut **transaction)
+      .await?;
+
+    Ok(())
+  }
+
+  pub async fn get_star_message(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    message_id: &MessageId,
+  ) -> Result<Option<StarMessage>> {
+    Ok(
+      StarMessage::retrieve(*guild_id, *message_id)
+        .fetch_optional(&mut **transaction)
+        .await?,
+    )
+  }
+
+  /// Retrieves up to `limit` of a guild's [`StarMessage`]s to check for deleted originals,
+  /// starting with those checked least recently.
+  pub async fn get_star_messages_to_check(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+    limit: i64,
+  ) -> Result<Vec<StarMessage>> {
+    Ok(
+      StarMessage::retrieve_least_checked(*guild_id, limit)
+        .fetch_all(&mut **transaction)
+        .await?,
+    )
+  }
+
+  pub async fn mark_star_messages_checked(
+    transaction: &mut Transaction<'_, Postgres>,
+    record_ids: &[String],
+  ) -> Result<()> {
+    StarMessage::mark_checked(record_ids)
+      .execute(&mut **transaction)
+      .await?;
+
+    Ok(())
+  }
+
+  pub async fn get_retention_policy(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+  ) -> Result<Option<RetentionPolicy>> {
+    Ok(
+      RetentionPolicy::retrieve(*guild_id)
+        .fetch_optional(&mut **transaction)
+        .await?,
+    )
+  }
+
+  pub async fn get_retention_policies(
+    transaction: &mut Transaction<'_, Postgres>,
+  ) -> Result<Vec<RetentionPolicy>> {
+    Ok(
+      RetentionPolicy::retrieve_all()
+        .fetch_all(&mut **transaction)
+        .await?,
+    )
+  }
+
+  pub async fn update_retention_policy(
+    transaction: &mut Transaction<'_, Postgres>,
+    policy: &RetentionPolicy,
+  ) -> Result<()> {
+    policy.insert_query().execute(&mut **transaction).await?;
+
+    Ok(())
+  }
+
+  /// Removes a guild's [`RetentionPolicy`], so all data is kept. Returns whether the guild
+  /// had a policy.
+  pub async fn remove_retention_policy(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+  ) -> Result<bool> {
+    Ok(
+      RetentionPolicy::remove(*guild_id)
+        .execute(&mut **transaction)
+        .await?
+        .rows_affected()
+        > 0,
+    )
+  }
+
+  pub async fn mark_retention_run(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: &GuildId,
+  ) -> Result<()> {
+    RetentionPolicy::mark_run(*guild_id)
+      .execute(&mut **transaction)
+      .await?;
+
+    Ok(())
+  }
+
+  /// Counts the erase records and meditation entries a [`RetentionPolicy`] would remove or
+  /// archive, without changing anything.
+  pub async fn preview_retention(
+    transaction: &mut Transaction<'_, Postgres>,
+    policy: &RetentionPolicy,
+    now: DateTime<Utc>,
+  ) -> Result<RetentionSummary> {
+    let mut summary = RetentionSummary::default();
+
+    if let Some(cutoff) = policy.erase_cutoff(now) {
+      let erases = RetentionPolicy::count_erases(policy.guild_id, cutoff)
+        .fetch_one(&mut **transaction)
+        .await?;
+      summary.erases = u64::try_from(erases)?;
+    }
+    if let Some(cutoff) = policy.archive_cutoff(now) {
+      let meditations = RetentionPolicy::count_archivable(policy.guild_id, cutoff)
+        .fetch_one(&mut **transaction)
+        .await?;
+      summary.meditations = u64::try_from(meditations)?;
+    }
+
+    Ok(summary)
+  }
+
+  /// Removes expired erase records and archives old meditation entries, as set by a
+  /// [`RetentionPolicy`], returning how many of each were affected. The policy's
+  /// [`dry_run`][RetentionPolicy::dry_run] setting isn't checked, so callers should use
+  /// [`DatabaseHandler::preview_retention`] for dry runs.
+  pub async fn apply_retention(
+    transaction: &mut Transaction<'_, Postgres>,
+    policy: &RetentionPolicy,
+    now: DateTime<Utc>,
+  ) -> Result<RetentionSummary> {
+    let mut summary = RetentionSummary::default();
+
+    if let Some(cutoff) = policy.erase_cutoff(now) {
+      summary.erases = RetentionPolicy::remove_erases(policy.guild_id, cutoff)
+        .execute(&mut **transaction)
+        .await?
+        .rows_affected();
+    }
+    if let Some(cutoff) = policy.archive_cutoff(now) {
+      summary.meditations = RetentionPolicy::archive_meditations(policy.guild_id, cutoff)
+        .execute(&mut **transaction)
+        .await?
+        .rows_affected();
+    }
+
+    Ok(summary)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use anyhow::{Error, Result};
+  use chrono::{DateTime, Duration as ChronoDuration, NaiveDate, NaiveTime, Utc};
+  use pgvector::Vector;
+  use poise::serenity_prelude::{ChannelId, GuildId, MessageId, RoleId, UserId};
+  use proptest::collection::btree_set;
+  use proptest::strategy::{Strategy, ValueTree};
+  use proptest::test_runner::TestRunner;
+  use sqlx::{PgPool, Postgres, Transaction};
+
+  use crate::data::account_link::{hash_secret, ApiToken, LinkCode};
+  use crate::data::backup::{Backup, BackupTable};
+  use crate::data::bookmark::Bookmark;
+  use crate::data::calendar::{CalendarFeed, DiscordEvent};
+  use crate::data::common::{Migration, MigrationType};
+  use crate::data::community_event::{AttendanceSource, CommunityEvent, EventRepeat};
+  use crate::data::course::{Course, Enrollment, EnrollmentCode};
+  use crate::data::dedication::Dedication;
+  use crate::data::erase::Erase;
+  use crate::data::feed::FeedSubscription;
+  use crate::data::goal::{Goal, GoalPeriod};
+  use crate::data::guild_emoji::{EmojiName, GuildEmoji};
+  use crate::data::guild_feature::{Feature, GuildFeature};
+  use crate::data::guild_settings::GuildSettings;
+  use crate::data::intention::{IntentionSubscription, PracticePrompt, PromptCategory};
+  use crate::data::maintenance::Maintenance;
+  use crate::data::meditation::Meditation;
+  use crate::data::mentorship::{MentorRole, MentorSignup, Mentorship};
+  use crate::data::milestone::Milestone;
+  use crate::data::mood_checkin::{Mood, MoodCheckin};
+  use crate::data::pending_interaction::{InteractionFlow, PendingInteraction};
+  use crate::data::poll::{Poll, PollVote};
+  use crate::data::profile_import::{ProfileImport, ProfileLink};
+  use crate::data::recurring_post::{PostDay, RecurringPost};
+  use crate::data::report::{Report, ReportSource, ReportStatus};
+  use crate::data::retention::{RetentionPolicy, RetentionSummary};
+  use crate::data::retreat::{Retreat, RetreatStatus};
+  use crate::data::scheduled_announcement::{AnnouncementModal, ScheduledAnnouncement};
+  use crate::data::session_webhook::{InvalidSession, SessionPayload, WebhookToken, MAX_TOKENS};
+  use crate::data::stats::{LeaderboardType, LeaderboardUser, SortBy, Streak};
+  use crate::data::steam_key::{KeyOffer, SteamKey};
+  use crate::data::streak_repair::{RepairStatus, StreakRepair};
+  use crate::data::study_group::{StudyGroup, StudyGroupMember};
+  use crate::data::suggestion::{Suggestion, SuggestionStatus, SuggestionVotes};
+  use crate::data::sutta::SuttaPassage;
+  use crate::data::talk::Talk;
+  use crate::data::ticket::{Ticket, TicketMessage};
+  use crate::data::tracking_profile::{Privacy, Status, TrackingProfile};
+  use crate::data::warning::Warning;
+  use crate::data::watchlist::WatchlistTerm;
+  use crate::data::wellness_metric::WellnessMetric;
+  use crate::database::DatabaseHandler;
+  use crate::time::Timeframe;
+
+  #[sqlx::test(fixtures(path = "fixtures", scripts("quote")))]
+  async fn test_get_quote_authors(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+
+    let guild_id = &GuildId::new(123u64);
+
+    let authors = DatabaseHandler::get_quote_authors(&mut transaction, guild_id, "").await?;
+    assert_eq!(authors.len(), 8);
+    assert_eq!(authors.first().map(String::as_str), Some("Anonymous"));
+
+    let authors = DatabaseHandler::get_quote_authors(&mut transaction, guild_id, "john").await?;
+    assert_eq!(authors, vec!["John Deere", "John Doe"]);
+
+    let authors = DatabaseHandler::get_quote_authors(&mut transaction, guild_id, "%").await?;
+    assert!(authors.is_empty());
+
+    Ok(())
+  }
+
+  #[sqlx::test(fixtures(path = "fixtures", scripts("quote")))]
+  async fn test_get_quotes_by_author(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+
+    let guild_id = &GuildId::new(123u64);
+
+    let Some(quote) =
+      DatabaseHandler::get_random_quote_by_author(&mut transaction, guild_id, "mr. buddha").await?
+    else {
+      panic!("Expected a quote by Mr. Buddha");
+    };
+    assert_eq!(quote.author.as_deref(), Some("Mr. Buddha"));
+
+    let quotes =
+      DatabaseHandler::get_quotes_by_author(&mut transaction, guild_id, "Anonymous").await?;
+    assert_eq!(quotes.len(), 1);
+    assert!(quotes[0].author.is_none());
+
+    assert!(
+      DatabaseHandler::get_random_quote_by_author(&mut transaction, guild_id, "Nobody")
+        .await?
+        .is_none()
+    );
+
+    Ok(())
+  }
+
+  #[sqlx::test]
+  async fn test_ai_usage(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut connection = handler.get_connection().await?;
+
+    let guild_id = &GuildId::new(123u64);
+
+    DatabaseHandler::record_ai_usage(&mut connection, guild_id, 12).await?;
+    DatabaseHandler::record_ai_usage(&mut connection, guild_id, 30).await?;
+    DatabaseHandler::record_ai_usage(&mut connection, &GuildId::new(456u64), 100).await?;
+
+    drop(connection);
+
+    let mut transaction = handler.start_transaction().await?;
+    let today = Utc::now().date_naive();
+
+    let usage = DatabaseHandler::get_ai_usage(&mut transaction, guild_id, today).await?;
+    assert_eq!(usage.requests, 2);
+    assert_eq!(usage.tokens, 42);
+
+    let daily_usage =
+      DatabaseHandler::get_daily_ai_usage(&mut transaction, guild_id, today).await?;
+    assert_eq!(daily_usage.len(), 1);
+    assert_eq!(daily_usage[0].usage_date, today);
+    assert_eq!(daily_usage[0].tokens, 42);
+
+    let tomorrow = today + ChronoDuration::days(1);
+    let usage = DatabaseHandler::get_ai_usage(&mut transaction, guild_id, tomorrow).await?;
+    assert_eq!(usage.requests, 0);
+    assert_eq!(usage.tokens, 0);
+
+    Ok(())
+  }
+
+  #[sqlx::test(fixtures(path = "fixtures", scripts("term")))]
+  async fn test_get_term_by_alias(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+
+    let guild_id = &GuildId::new(123u64);
+
+    let Some(term) = DatabaseHandler::get_term(&mut transaction, guild_id, "vipassana").await?
+    else {
+      panic!("Expected term to be found by alias");
+    };
+    assert_eq!(term.name, "Insight Meditation");
+
+    let Some(term) = DatabaseHandler::get_term(&mut transaction, guild_id, "metta").await? else {
+      panic!("Expected term to be found by name");
+    };
+    assert_eq!(term.name, "Metta");
+
+    assert!(
+      DatabaseHandler::get_term(&mut transaction, guild_id, "insight.*")
+        .await?
+        .is_none()
+    );
+
+    Ok(())
+  }
+
+  #[sqlx::test(fixtures(path = "fixtures", scripts("term")))]
+  async fn test_term_aliases(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+
+    let guild_id = &GuildId::new(123u64);
+
+    assert!(DatabaseHandler::term_name_exists(&mut transaction, guild_id, "METTA").await?);
+    assert!(DatabaseHandler::term_name_exists(&mut transaction, guild_id, "Vipassana").await?);
+    assert!(!DatabaseHandler::term_name_exists(&mut transaction, guild_id, "Samatha").await?);
+
+    DatabaseHandler::add_term_alias(&mut transaction, guild_id, "metta", "Maitri").await?;
+    DatabaseHandler::add_term_alias(&mut transaction, guild_id, "Jhana", "Dhyana").await?;
+
+    let Some(term) = DatabaseHandler::get_term(&mut transaction, guild_id, "Dhyana").await? else {
+      panic!("Expected term to be found by new alias");
+    };
+    assert_eq!(term.aliases, Some(vec!["Dhyana".to_string()]));
+
+    let removed =
+      DatabaseHandler::remove_term_alias(&mut transaction, guild_id, "Metta", "maitri").await?;
+    assert_eq!(removed, 1);
+
+    let removed =
+      DatabaseHandler::remove_term_alias(&mut transaction, guild_id, "Metta", "maitri").await?;
+    assert_eq!(removed, 0);
+
+    let Some(term) = DatabaseHandler::get_term(&mut transaction, guild_id, "Metta").await? else {
+      panic!("Expected term to be found");
+    };
+    assert_eq!(term.aliases, Some(vec!["Loving-kindness".to_string()]));
+
+    Ok(())
+  }
+
+  #[sqlx::test(fixtures(path = "fixtures", scripts("term")))]
+  async fn test_related_terms(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+
+    let guild_id = &GuildId::new(123u64);
+
+    DatabaseHandler::add_related_term(&mut transaction, guild_id, "Jhana", "Metta").await?;
+
+    let Some(term) = DatabaseHandler::get_term(&mut transaction, guild_id, "Jhana").await? else {
+      panic!("Expected term to be found");
+    };
+    assert_eq!(
+      term.related,
+      Some(vec!["Insight Meditation".to_string(), "Metta".to_string()])
+    );
+
+    let removed =
+      DatabaseHandler::remove_related_term(&mut transaction, guild_id, "Jhana", "metta").await?;
+    assert_eq!(removed, 1);
+
+    let removed =
+      DatabaseHandler::remove_related_term(&mut transaction, guild_id, "Jhana", "metta").await?;
+    assert_eq!(removed, 0);
+
+    // Removing a term also removes it from the entries that link to it
+    DatabaseHandler::remove_term(&mut transaction, guild_id, "Insight Meditation").await?;
+
+    let Some(term) = DatabaseHandler::get_term(&mut transaction, guild_id, "Jhana").await? else {
+      panic!("Expected term to be found");
+    };
+    assert_eq!(term.related, Some(Vec::new()));
+
+    Ok(())
+  }
+
+  #[sqlx::test(fixtures(path = "fixtures", scripts("term")))]
+  async fn test_get_term_names(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+
+    let guild_id = &GuildId::new(123u64);
+
+    let names = DatabaseHandler::get_term_names(&mut transaction, guild_id, "me").await?;
+    assert_eq!(names, vec!["Insight Meditation", "Metta"]);
+
+    let names = DatabaseHandler::get_term_names(&mut transaction, guild_id, "pass").await?;
+    assert_eq!(names, vec!["Vipassana"]);
+
+    Ok(())
+  }
+
+  #[sqlx::test(fixtures(path = "fixtures", scripts("term")))]
+  async fn test_possible_terms(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+
+    let guild_id = &GuildId::new(123u64);
+
+    let terms =
+      DatabaseHandler::get_possible_terms(&mut transaction, guild_id, "insight meditaton", 0.3)
+        .await?;
+    assert_eq!(
+      terms.first().map(|term| term.name.as_str()),
+      Some("Insight Meditation")
+    );
+
+    // Aliases are matched by substring rather than similarity
+    let terms =
+      DatabaseHandler::get_possible_terms(&mut transaction, guild_id, "loving", 0.9).await?;
+    assert_eq!(
+      terms
+        .iter()
+        .map(|term| term.name.as_str())
+        .collect::<Vec<_>>(),
+      vec!["Metta"]
+    );
+
+    let terms =
+      DatabaseHandler::get_possible_terms(&mut transaction, guild_id, "Maitri", 0.3).await?;
+    assert!(terms.is_empty());
+
+    let names = DatabaseHandler::get_term_list(&mut transaction, guild_id)
+      .await?
+      .into_iter()
+      .map(|term| term.name)
+      .collect::<Vec<_>>();
+    assert_eq!(names, vec!["Metta", "Jhana", "Insight Meditation"]);
+    assert_eq!(
+      DatabaseHandler::get_term_count(&mut transaction, guild_id).await?,
+      3
+    );
+    assert_eq!(
+      DatabaseHandler::get_term_count(&mut transaction, &GuildId::new(456u64)).await?,
+      1
+    );
+
+    Ok(())
+  }
+
+  #[sqlx::test(fixtures(path = "fixtures", scripts("term")))]
+  async fn test_search_terms_by_vector(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+
+    let guild_id = &GuildId::new(123u64);
+    let embedding = |x: f32, y: f32| {
+      let mut values = vec![0.0; 1536];
+      values[0] = x;
+      values[1] = y;
+      Vector::from(values)
+    };
+
+    for (term_name, vector) in [
+      ("Insight Meditation", embedding(1.0, 0.0)),
+      ("Jhana", embedding(0.6, 0.8)),
+      ("Metta", embedding(0.0, 1.0)),
+    ] {
+      DatabaseHandler::update_term_embedding(&mut transaction, guild_id, term_name, Some(&vector))
+        .await?;
+    }
+
+    // Jhana is related to the closest match, so its distance of 0.4 is boosted
+    let results = DatabaseHandler::search_terms_by_vector(
+      &mut transaction,
+      guild_id,
+      &embedding(1.0, 0.0),
+      5,
+      0.5,
+    )
+    .await?;
+    assert_eq!(
+      results
+        .iter()
+        .map(|result| result.term_name.as_str())
+        .collect::<Vec<_>>(),
+      vec!["Insight Meditation", "Jhana"]
+    );
+    let jhana = results[1].distance_score.unwrap_or_default();
+    assert!((jhana - 0.36).abs() < 1e-6);
+
+    // Terms from other servers are never returned
+    let results = DatabaseHandler::search_terms_by_vector(
+      &mut transaction,
+      &GuildId::new(456u64),
+      &embedding(1.0, 0.0),
+      5,
+      2.0,
+    )
+    .await?;
+    assert!(results.is_empty());
+
+    Ok(())
+  }
+
+  #[sqlx::test(fixtures(path = "fixtures", scripts("bookmarks")))]
+  async fn test_get_bookmarks(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+    let bookmarks = DatabaseHandler::get_bookmarks(
+      &mut transaction,
+      Some(GuildId::new(123u64)),
+      &UserId::new(123u64),
+    )
+    .await?;
+
+    assert_eq!(bookmarks.len(), 4);
+    assert_eq!(bookmarks[0].link, "https://foo.bar/1234");
+    assert_eq!(bookmarks[0].description, Some("A bar of foo".to_string()));
+    assert_eq!(bookmarks[0].id(), "01JBPTWBXJNAKK288S3D89JK7G");
+    assert_eq!(
+      bookmarks[0].added(),
+      DateTime::from_timestamp(1_704_067_200, 0).as_ref()
+    );
+
+    assert_eq!(bookmarks[1].link, "https://foo.bar/1235");
+    assert_eq!(bookmarks[1].id(), "01JBPTWBXJNAKK288S3D89JK7H");
+    assert_eq!(
+      bookmarks[1].added(),
+      DateTime::from_timestamp(1_704_070_800, 0).as_ref()
+    );
+
+    assert_eq!(bookmarks[2].description, None);
+
+    Ok(())
+  }
+
+  #[sqlx::test(fixtures(path = "fixtures", scripts("bookmarks")))]
+  async fn test_bookmark_count(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+    let count = DatabaseHandler::get_bookmark_count(
+      &mut transaction,
+      Some(GuildId::new(123u64)),
+      &UserId::new(123u64),
+    )
+    .await?;
+
+    assert_eq!(count, 4);
+
+    Ok(())
+  }
+
+  #[sqlx::test(fixtures(path = "fixtures", scripts("bookmarks")))]
+  async fn test_remove_bookmark(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+    let count = DatabaseHandler::remove_bookmark(
+      &mut transaction,
+      Some(GuildId::new(123u64)),
+      &UserId::new(123u64),
+      "01JBPTWBXJNAKK288S3D89JK7J",
+    )
+    .await?;
+
+    assert_eq!(count, 1);
+
+    let new_count = DatabaseHandler::get_bookmark_count(
+      &mut transaction,
+      Some(GuildId::new(123u64)),
+      &UserId::new(123u64),
+    )
+    .await?;
+
+    assert_eq!(new_count, 3);
+
+    Ok(())
+  }
+
+  #[sqlx::test(fixtures(path = "fixtures", scripts("bookmarks")))]
+  async fn test_remove_bookmark_other_user(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+    let count = DatabaseHandler::remove_bookmark(
+      &mut transaction,
+      Some(GuildId::new(123u64)),
+      &UserId::new(124u64),
+      "01JBPTWBXJNAKK288S3D89JK7J",
+    )
+    .await?;
+
+    assert_eq!(count, 0);
+
+    Ok(())
+  }
+
+  #[sqlx::test(fixtures(path = "fixtures", scripts("bookmarks")))]
+  async fn test_dm_bookmarks(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+    let user_id = UserId::new(123u64);
+
+    let bookmarks = DatabaseHandler::get_bookmarks(&mut transaction, None, &user_id).await?;
+
+    assert_eq!(bookmarks.len(), 2);
+    assert_eq!(bookmarks[0].id(), "01JBPV5QH2Y7ZK3M4N6P8R0S1T");
+    assert_eq!(bookmarks[1].link, "https://foo.bar/1243");
+
+    () = DatabaseHandler::add_bookmark(
+      &mut transaction,
+      &Bookmark::new(
+        None,
+        user_id,
+        "https://polyglot.engineer/".to_string(),
+        None,
+      ),
+    )
+    .await?;
+
+    assert_eq!(
+      DatabaseHandler::get_bookmark_count(&mut transaction, None, &user_id).await?,
+      3
+    );
+    // Bookmarks added in DMs don't count towards a guild's bookmarks
+    assert_eq!(
+      DatabaseHandler::get_bookmark_count(&mut transaction, Some(GuildId::new(123u64)), &user_id)
+        .await?,
+      4
+    );
+
+    // Guild bookmarks can't be removed from DMs
+    assert_eq!(
+      DatabaseHandler::remove_bookmark(
+        &mut transaction,
+        None,
+        &user_id,
+        "01JBPTWBXJNAKK288S3D89JK7J"
+      )
+      .await?,
+      0
+    );
+    assert_eq!(
+      DatabaseHandler::remove_bookmark(
+        &mut transaction,
+        None,
+        &user_id,
+        "01JBPV5QH2Y7ZK3M4N6P8R0S1T"
+      )
+      .await?,
+      1
+    );
+
+    Ok(())
+  }
+
+  #[sqlx::test(fixtures(path = "fixtures", scripts("bookmarks")))]
+  async fn test_update_bookmark(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+    let user_id = UserId::new(123u64);
+
+    let Some(mut bookmark) =
+      DatabaseHandler::get_bookmark_by_link(&mut transaction, &user_id, "https://foo.bar/1236")
+        .await?
+    else {
+      panic!("Fixture bookmark should exist");
+    };
+
+    assert_eq!(bookmark.id(), "01JBPTWBXJNAKK288S3D89JK7I");
+    assert_eq!(bookmark.description, None);
+
+    bookmark.description = Some("A bat of baz".to_string());
+    DatabaseHandler::update_bookmark(&mut transaction, &bookmark).await?;
+
+    let Some(updated) =
+      DatabaseHandler::get_bookmark_by_link(&mut transaction, &user_id, "https://foo.bar/1236")
+        .await?
+    else {
+      panic!("Fixture bookmark should exist");
+    };
+
+    assert_eq!(updated.description, Some("A bat of baz".to_string()));
+
+    // Links are only matched against the user's own bookmarks
+    assert!(DatabaseHandler::get_bookmark_by_link(
+      &mut transaction,
+      &UserId::new(124u64),
+      "https://foo.bar/1236"
+    )
+    .await?
+    .is_none());
+
+    Ok(())
+  }
+
+  #[sqlx::test(fixtures(path = "fixtures", scripts("bookmarks")))]
+  async fn test_add_duplicate_bookmark(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+    let result = DatabaseHandler::add_bookmark(
+      &mut transaction,
+      &Bookmark::new(
+        Some(GuildId::new(123u64)),
+        UserId::new(123u64),
+        "https://foo.bar/1234".to_string(),
+        None,
+      ),
+    )
+    .await;
+
+    assert!(result.is_err());
+
+    Ok(())
+  }
+
+  #[sqlx::test(fixtures(path = "fixtures", scripts("bookmarks")))]
+  async fn test_add_bookmark(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+    () = DatabaseHandler::add_bookmark(
+      &mut transaction,
+      &Bookmark::new(
+        Some(GuildId::new(123u64)),
+        UserId::new(123u64),
+        "https://polyglot.engineer/".to_string(),
+        None,
+      ),
+    )
+    .await?;
+
+    let new_count = DatabaseHandler::get_bookmark_count(
+      &mut transaction,
+      Some(GuildId::new(123u64)),
+      &UserId::new(123u64),
+    )
+    .await?;
+
+    assert_eq!(new_count, 5);
+
+    Ok(())
+  }
+
+  #[sqlx::test(fixtures(path = "fixtures", scripts("quote")))]
+  async fn test_quote_exists(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+
+    let guild_id = &GuildId::new(123u64);
+    let valid_id = "01JBPTWBXJNAKK288S3D89JK7I";
+    let invalid_id = "The time is now";
+
+    assert!(DatabaseHandler::quote_exists(&mut transaction, guild_id, valid_id).await?);
+    assert!(!DatabaseHandler::quote_exists(&mut transaction, guild_id, invalid_id).await?);
+
+    DatabaseHandler::remove_quote(&mut transaction, guild_id, valid_id).await?;
+
+    assert!(!DatabaseHandler::quote_exists(&mut transaction, guild_id, valid_id).await?);
+
+    Ok(())
+  }
+
+  #[sqlx::test(fixtures(path = "fixtures", scripts("bookmarks")))]
+  async fn test_remove_bookmark_other_guild(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+    let count = DatabaseHandler::remove_bookmark(
+      &mut transaction,
+      Some(GuildId::new(123u64)),
+      &UserId::new(125u64),
+      "01JBPV1XJNAKK288S3D89JK7M1",
+    )
+    .await?;
+
+    assert_eq!(count, 0);
+
+    let other_count = DatabaseHandler::get_bookmark_count(
+      &mut transaction,
+      Some(GuildId::new(127u64)),
+      &UserId::new(125u64),
+    )
+    .await?;
+
+    assert_eq!(other_count, 2);
+
+    Ok(())
+  }
+
+  #[sqlx::test(fixtures(path = "fixtures", scripts("meditation")))]
+  async fn test_remove_meditation_entry(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+
+    let guild_id = &GuildId::new(123u64);
+    let other_guild_id = &GuildId::new(456u64);
+    let entry_id = "01JBPTWBXJNAKK288S3D89JK7G";
+
+    DatabaseHandler::remove_meditation_entry(&mut transaction, other_guild_id, entry_id).await?;
+
+    assert!(
+      DatabaseHandler::get_meditation_entry(&mut transaction, guild_id, entry_id)
+        .await?
+        .is_some()
+    );
+
+    DatabaseHandler::remove_meditation_entry(&mut transaction, guild_id, entry_id).await?;
+
+    assert!(
+      DatabaseHandler::get_meditation_entry(&mut transaction, guild_id, entry_id)
+        .await?
+        .is_none()
+    );
+
+    let user_id = &UserId::new(123u64);
+    let count =
+      DatabaseHandler::get_user_meditation_count(&mut transaction, guild_id, user_id).await?;
+    let other_count =
+      DatabaseHandler::get_user_meditation_count(&mut transaction, other_guild_id, user_id).await?;
+
+    assert_eq!(count, 1);
+    assert_eq!(other_count, 2);
+
+    Ok(())
+  }
+
+  #[sqlx::test(fixtures(path = "fixtures", scripts("meditation")))]
+  async fn test_update_meditation_entry_other_guild(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+
+    let guild_id = &GuildId::new(123u64);
+    let entry_id = "01JBPV1XJNAKK288S3D89JK7M1";
+
+    let Some(entry) =
+      DatabaseHandler::get_meditation_entry(&mut transaction, &GuildId::new(456u64), entry_id)
+        .await?
+    else {
+      panic!("Fixture entry should exist");
+    };
+
+    let mut updated_entry = entry.with_new(5, 0, &entry.occurred_at);
+    updated_entry.guild_id = *guild_id;
+    DatabaseHandler::update_meditation_entry(&mut transaction, &updated_entry).await?;
+
+    let Some(entry) =
+      DatabaseHandler::get_meditation_entry(&mut transaction, &GuildId::new(456u64), entry_id)
+        .await?
+    else {
+      panic!("Fixture entry should exist");
+    };
+
+    assert_eq!(entry.minutes, 30);
+
+    Ok(())
+  }
+
+  #[sqlx::test(fixtures(path = "fixtures", scripts("meditation")))]
+  async fn test_streak_per_guild(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+
+    let guild_id = GuildId::new(123u64);
+    let other_guild_id = GuildId::new(456u64);
+    let user_id = UserId::new(123u64);
+
+    DatabaseHandler::update_streak(&mut transaction, &Streak::new(guild_id, user_id, 0, 5)).await?;
+    DatabaseHandler::update_streak(
+      &mut transaction,
+      &Streak::new(other_guild_id, user_id, 0, 9),
+    )
+    .await?;
+
+    let streak = DatabaseHandler::get_streak(&mut transaction, &guild_id, &user_id).await?;
+    let other_streak =
+      DatabaseHandler::get_streak(&mut transaction, &other_guild_id, &user_id).await?;
+
+    assert_eq!(streak.longest, 5);
+    assert_eq!(other_streak.longest, 9);
+
+    Ok(())
+  }
+
+  #[sqlx::test(fixtures(path = "fixtures", scripts("leaderboard")))]
+  async fn test_leaderboard_views(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+
+    let guild_id = &GuildId::new(123u64);
+    DatabaseHandler::refresh_leaderboard(&mut transaction, &Timeframe::Daily).await?;
+    DatabaseHandler::refresh_leaderboard(&mut transaction, &Timeframe::Yearly).await?;
+
+    let names = |users: &[LeaderboardUser]| {
+      users
+        .iter()
+        .map(|user| user.name.clone().unwrap_or_default())
+        .collect::<Vec<_>>()
+    };
+
+    let by_minutes = DatabaseHandler::get_leaderboard_stats(
+      &mut transaction,
+      guild_id,
+      &Timeframe::Daily,
+      &SortBy::Minutes,
+      &LeaderboardType::Top5,
+    )
+    .await?;
+    assert_eq!(names(&by_minutes), vec!["202", "201", "203"]);
+    assert_eq!(
+      by_minutes
+        .iter()
+        .map(|user| user.minutes.unwrap_or_default())
+        .collect::<Vec<_>>(),
+      vec![60, 45, 15]
+    );
+    assert_eq!(by_minutes[0].anonymous_tracking, Some(true));
+    assert_eq!(by_minutes[1].anonymous_tracking, None);
+
+    let by_sessions = DatabaseHandler::get_leaderboard_stats(
+      &mut transaction,
+      guild_id,
+      &Timeframe::Daily,
+      &SortBy::Sessions,
+      &LeaderboardType::Top5,
+    )
+    .await?;
+    assert_eq!(names(&by_sessions), vec!["203", "201", "202"]);
+
+    // Members without a streak are listed last
+    let by_streak = DatabaseHandler::get_leaderboard_stats(
+      &mut transaction,
+      guild_id,
+      &Timeframe::Daily,
+      &SortBy::Streak,
+      &LeaderboardType::Top5,
+    )
+    .await?;
+    assert_eq!(names(&by_streak), vec!["201", "203", "202"]);
+    assert_eq!(by_streak[0].streak, Some(12));
+
+    // Entries from before the timeframe and from other servers aren't counted
+    let yearly =
+      DatabaseHandler::get_leaderboard_users(&mut transaction, guild_id, &Timeframe::Yearly)
+        .await?;
+    assert_eq!(yearly.len(), 3);
+    assert!(yearly
+      .iter()
+      .any(|user| user.name.as_deref() == Some("202") && user.minutes == Some(60)));
+
+    Ok(())
+  }
+
+  /// Adds an entry for each of the given days before today, at the current time of day.
+  async fn meditate_on_days(
+    transaction: &mut Transaction<'_, Postgres>,
+    guild_id: GuildId,
+    user_id: UserId,
+    days_ago: &[i32],
+  ) -> Result<()> {
+    let now = Utc::now();
+    for day in days_ago {
+      let occurred_at = now - ChronoDuration::days(i64::from(*day));
+      let entry = Meditation::new(guild_id, user_id, 10, 0, &occurred_at);
+      DatabaseHandler::add_meditation_entry(transaction, &entry).await?;
+    }
+
+    Ok(())
+  }
+
+  #[sqlx::test]
+  async fn test_streak_generated_days(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+
+    let guild_id = GuildId::new(123u64);
+    let other_guild_id = GuildId::new(456u64);
+    let mut runner = TestRunner::deterministic();
+    let days = btree_set(0..60i32, 0..30);
+
+    for case in 1..=20u64 {
+      let user_id = UserId::new(case);
+      let generate = |runner: &mut TestRunner| {
+        days
+          .new_tree(runner)
+          .map(|tree| tree.current().into_iter().collect::<Vec<_>>())
+          .map_err(|e| anyhow::anyhow!("{e}"))
+      };
+      let days_ago = generate(&mut runner)?;
+      let other_days_ago = generate(&mut runner)?;
+
+      // Entries in another server never count toward the streak, and a second entry on the
+      // same day doesn't extend it
+      meditate_on_days(&mut transaction, guild_id, user_id, &days_ago).await?;
+      meditate_on_days(
+        &mut transaction,
+        guild_id,
+        user_id,
+        &days_ago[..days_ago.len() / 2],
+      )
+      .await?;
+      meditate_on_days(&mut transaction, other_guild_id, user_id, &other_days_ago).await?;
+
+      let mut expected = Streak::default();
+      expected.count_days(&days_ago);
+      let streak = DatabaseHandler::get_streak(&mut transaction, &guild_id, &user_id).await?;
+      assert_eq!(
+        (streak.current, streak.longest),
+        (expected.current, expected.longest),
+        "Streak for days {days_ago:?}"
+      );
+
+      // Adding today to a stored streak agrees with recalculating from scratch
+      meditate_on_days(&mut transaction, guild_id, user_id, &[0]).await?;
+      let streak = DatabaseHandler::get_streak(&mut transaction, &guild_id, &user_id).await?;
+      let recalculated =
+        DatabaseHandler::recalculate_streak(&mut transaction, &guild_id, &user_id).await?;
+      assert_eq!(
+        (streak.current, streak.longest),
+        (recalculated.current, recalculated.longest),
+        "Streak for days {days_ago:?} and today"
+      );
+      assert!(streak.longest >= expected.longest);
+    }
+
+    Ok(())
+  }
+
+  #[sqlx::test]
+  async fn test_streak_repairs(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+
+    let guild_id = GuildId::new(123u64);
+    let user_id = UserId::new(123u64);
+    let staff_id = UserId::new(456u64);
+    let today = Utc::now().date_naive();
+
+    // Missing yesterday breaks the streak
+    meditate_on_days(&mut transaction, guild_id, user_id, &[0, 2, 3]).await?;
+    let streak = DatabaseHandler::get_streak(&mut transaction, &guild_id, &user_id).await?;
+    assert_eq!((streak.current, streak.longest), (0, 2));
+
+    let repair = StreakRepair::new(
+      guild_id,
+      user_id,
+      today - ChronoDuration::days(1),
+      "The bot was down".to_owned(),
+    );
+    assert!(DatabaseHandler::add_streak_repair(&mut transaction, &repair).await?);
+    let duplicate = StreakRepair::new(guild_id, user_id, repair.date, "Again".to_owned());
+    assert!(!DatabaseHandler::add_streak_repair(&mut transaction, &duplicate).await?);
+
+    // Open requests don't count toward the streak
+    let streak = DatabaseHandler::recalculate_streak(&mut transaction, &guild_id, &user_id).await?;
+    assert_eq!(streak.current, 0);
+
+    assert_eq!(
+      DatabaseHandler::set_streak_repair_status(
+        &mut transaction,
+        &repair.id,
+        RepairStatus::Approved,
+        &staff_id,
+      )
+      .await?,
+      1
+    );
+    assert_eq!(
+      DatabaseHandler::set_streak_repair_status(
+        &mut transaction,
+        &repair.id,
+        RepairStatus::Denied,
+        &staff_id,
+      )
+      .await?,
+      0
+    );
+
+    let Some(saved) = DatabaseHandler::get_streak_repair(&mut transaction, &repair.id).await?
+    else {
+      panic!("Expected the streak repair to exist");
+    };
+    assert_eq!(saved.status, RepairStatus::Approved);
+    assert_eq!(saved.handled_by, Some(staff_id));
+    assert_eq!(saved.reason, "The bot was down");
+
+    let streak = DatabaseHandler::recalculate_streak(&mut transaction, &guild_id, &user_id).await?;
+    assert_eq!((streak.current, streak.longest), (4, 4));
+
+    // Denied days can be requested again
+    let denied = StreakRepair::new(
+      guild_id,
+      user_id,
+      today - ChronoDuration::days(5),
+      "Forgot".to_owned(),
+    );
+    assert!(DatabaseHandler::add_streak_repair(&mut transaction, &denied).await?);
+    DatabaseHandler::set_streak_repair_status(
+      &mut transaction,
+      &denied.id,
+      RepairStatus::Denied,
+      &staff_id,
+    )
+    .await?;
+    let retry = StreakRepair::new(guild_id, user_id, denied.date, "Forgot".to_owned());
+    assert!(DatabaseHandler::add_streak_repair(&mut transaction, &retry).await?);
+
+    Ok(())
+  }
+
+  #[sqlx::test]
+  async fn test_calendar_feeds(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+
+    let guild_id = GuildId::new(123u64);
+    let now = Utc::now();
+
+    assert!(
+      DatabaseHandler::get_calendar_feed(&mut transaction, &guild_id)
+        .await?
+        .is_none()
+    );
+    let old_feed = CalendarFeed::new(guild_id);
+    DatabaseHandler::add_calendar_feed(&mut transaction, &old_feed).await?;
+    let feed = CalendarFeed::new(guild_id);
+    DatabaseHandler::add_calendar_feed(&mut transaction, &feed).await?;
+    let Some(saved) = DatabaseHandler::get_calendar_feed(&mut transaction, &guild_id).await? else {
+      panic!("Expected the feed to exist");
+    };
+    assert_eq!(saved.token, feed.token);
+
+    DatabaseHandler::add_community_event(
+      &mut transaction,
+      &CommunityEvent::new(
+        guild_id,
+        ChannelId::new(456u64),
+        "Book Club".to_owned(),
+        None,
+        now,
+        60,
+        EventRepeat::Weekly,
+        UserId::new(1u64),
+      ),
+    )
+    .await?;
+
+    let sit = |id: &str, name: &str| DiscordEvent {
+      id: id.to_owned(),
+      guild_id,
+      name: name.to_owned(),
+      description: None,
+      location: None,
+      starts_at: now,
+      ends_at: None,
+    };
+    DatabaseHandler::add_discord_event(&mut transaction, &sit("1", "Silent Sit")).await?;
+    DatabaseHandler::add_discord_event(&mut transaction, &sit("1", "Evening Silent Sit")).await?;
+    DatabaseHandler::add_discord_event(&mut transaction, &sit("2", "Dharma Talk")).await?;
+    DatabaseHandler::remove_discord_event(&mut transaction, &guild_id, "2").await?;
+
+    // Resetting the token stops the old URL from working
+    assert!(
+      DatabaseHandler::get_calendar(&mut transaction, &old_feed.token, now)
+        .await?
+        .is_none()
+    );
+    let Some(calendar) = DatabaseHandler::get_calendar(&mut transaction, &feed.token, now).await?
+    else {
+      panic!("Expected the calendar to exist");
+    };
+    let summaries: Vec<&str> = calendar
+      .events
+      .iter()
+      .map(|event| event.summary.as_str())
+      .collect();
+    assert_eq!(summaries.len(), 5);
+    assert_eq!(&summaries[..2], ["Book Club", "Evening Silent Sit"]);
+
+    DatabaseHandler::replace_discord_events(&mut transaction, &guild_id, &[sit("3", "Retreat")])
+      .await?;
+    let Some(calendar) = DatabaseHandler::get_calendar(&mut transaction, &feed.token, now).await?
+    else {
+      panic!("Expected the calendar to exist");
+    };
+    assert_eq!(calendar.events[1].summary, "Retreat");
+
+    Ok(())
+  }
+
+  #[sqlx::test]
+  async fn test_pending_interactions(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+
+    let user_id = UserId::new(123u64);
+    let channel_id = ChannelId::new(456u64);
+    let now = Utc::now();
+
+    let expired = PendingInteraction::new(
+      InteractionFlow::Confirmation,
+      Some(GuildId::new(123u64)),
+      user_id,
+      channel_id,
+      "{}",
+      now - ChronoDuration::minutes(1),
+    );
+    DatabaseHandler::add_pending_interaction(&mut transaction, &expired).await?;
+    assert!(DatabaseHandler::get_pending_interaction(
+      &mut transaction,
+      InteractionFlow::Confirmation,
+      &expired.id
+    )
+    .await?
+    .is_none());
+
+    // Paginators can be used in DMs
+    let pending_interaction = PendingInteraction::new(
+      InteractionFlow::Pagination,
+      None,
+      user_id,
+      channel_id,
+      r#"{"pages":[]}"#,
+      now + ChronoDuration::hours(24),
+    );
+    DatabaseHandler::add_pending_interaction(&mut transaction, &pending_interaction).await?;
+
+    // Only expired flows are removed
+    assert_eq!(
+      DatabaseHandler::remove_expired_pending_interactions(&mut transaction).await?,
+      1
+    );
+    assert!(!DatabaseHandler::remove_pending_interaction(&mut transaction, &expired.id).await?);
+
+    assert!(DatabaseHandler::get_pending_interaction(
+      &mut transaction,
+      InteractionFlow::Confirmation,
+      &pending_interaction.id
+    )
+    .await?
+    .is_none());
+    let Some(saved) = DatabaseHandler::get_pending_interaction(
+      &mut transaction,
+      InteractionFlow::Pagination,
+      &pending_interaction.id,
+    )
+    .await?
+    else {
+      panic!("Expected the pending interaction to exist");
+    };
+    assert_eq!(saved.guild_id, None);
+    assert_eq!(saved.user_id, user_id);
+    assert_eq!(saved.payload, r#"{"pages":[]}"#);
+
+    assert!(
+      DatabaseHandler::remove_pending_interaction(&mut transaction, &pending_interaction.id)
+        .await?
+    );
+    assert!(
+      !DatabaseHandler::remove_pending_interaction(&mut transaction, &pending_interaction.id)
+        .await?
+    );
+
+    Ok(())
+  }
+
+  #[sqlx::test]
+  async fn test_account_links(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+
+    let user_id = UserId::new(123u64);
+
+    let (old_code, link_code) = LinkCode::generate(user_id);
+    DatabaseHandler::add_link_code(&mut transaction, &link_code).await?;
+    let (code, link_code) = LinkCode::generate(user_id);
+    DatabaseHandler::add_link_code(&mut transaction, &link_code).await?;
+
+    // Only the newest code works, and only once
+    assert!(DatabaseHandler::exchange_link_code(
+      &mut transaction,
+      &hash_secret(&old_code),
+      ChronoDuration::days(30),
+    )
+    .await?
+    .is_none());
+    let Some((token, api_token)) = DatabaseHandler::exchange_link_code(
+      &mut transaction,
+      &hash_secret(&code.to_lowercase()),
+      ChronoDuration::days(30),
+    )
+    .await?
+    else {
+      panic!("Expected the code to be exchanged");
+    };
+    assert_eq!(api_token.user_id, user_id);
+    assert!(DatabaseHandler::exchange_link_code(
+      &mut transaction,
+      &hash_secret(&code),
+      ChronoDuration::days(30),
+    )
+    .await?
+    .is_none());
+
+    let Some(saved) =
+      DatabaseHandler::get_api_token(&mut transaction, &hash_secret(&token)).await?
+    else {
+      panic!("Expected the token to exist");
+    };
+    assert_eq!(saved.user_id, user_id);
+
+    assert_eq!(
+      DatabaseHandler::revoke_api_tokens(&mut transaction, &user_id).await?,
+      1
+    );
+    assert!(
+      DatabaseHandler::get_api_token(&mut transaction, &hash_secret(&token))
+        .await?
+        .is_none()
+    );
+
+    Ok(())
+  }
+
+  #[sqlx::test]
+  async fn test_session_webhooks(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+
+    let guild_id = GuildId::new(123u64);
+    let user_id = UserId::new(456u64);
+    let now = Utc::now();
+    let payload = |token: &str, minutes| SessionPayload {
+      token: token.to_owned(),
+      minutes,
+      seconds: 0,
+      timestamp: None,
+    };
+
+    let (watch_token, webhook_token) = WebhookToken::generate(guild_id, user_id, "Watch", 2);
+    assert!(DatabaseHandler::add_webhook_token(&mut transaction, &webhook_token).await?);
+    let (token, webhook_token) = WebhookToken::generate(guild_id, user_id, "Shortcut", 10);
+    assert!(DatabaseHandler::add_webhook_token(&mut transaction, &webhook_token).await?);
+
+    assert!(
+      DatabaseHandler::log_webhook_session(&mut transaction, &payload("unknown", 20), now)
+        .await?
+        .is_none()
+    );
+    let Some(meditation) =
+      DatabaseHandler::log_webhook_session(&mut transaction, &payload(&token, 20), now).await?
+    else {
+      panic!("Expected the session to be logged");
+    };
+    assert_eq!(
+      (meditation.guild_id, meditation.user_id),
+      (guild_id, user_id)
+    );
+    assert_eq!(
+      DatabaseHandler::get_user_meditation_sum(&mut transaction, &guild_id, &user_id).await?,
+      20
+    );
+
+    let Err(e) =
+      DatabaseHandler::log_webhook_session(&mut transaction, &payload(&token, 0), now).await
+    else {
+      panic!("Expected the session to be rejected");
+    };
+    assert_eq!(
+      e.downcast_ref::<InvalidSession>(),
+      Some(&InvalidSession::Minutes)
+    );
+
+    // Each token has its own hourly limit
+    for _ in 0..2 {
+      DatabaseHandler::log_webhook_session(&mut transaction, &payload(&watch_token, 10), now)
+        .await?;
+    }
+    let Err(e) =
+      DatabaseHandler::log_webhook_session(&mut transaction, &payload(&watch_token, 10), now).await
+    else {
+      panic!("Expected the token to be rate limited");
+    };
+    assert_eq!(
+      e.downcast_ref::<InvalidSession>(),
+      Some(&InvalidSession::RateLimited)
+    );
+    assert!(
+      DatabaseHandler::log_webhook_session(&mut transaction, &payload(&token, 10), now)
+        .await?
+        .is_some()
+    );
+
+    let tokens = DatabaseHandler::get_webhook_tokens(&mut transaction, &guild_id, &user_id).await?;
+    assert_eq!(
+      tokens
+        .iter()
+        .map(|token| token.name.as_str())
+        .collect::<Vec<_>>(),
+      vec!["Shortcut", "Watch"]
+    );
+    assert!(tokens.iter().all(|token| token.last_used_at.is_some()));
+
+    // Names are compared without regard to case
+    assert!(
+      DatabaseHandler::remove_webhook_token(&mut transaction, &guild_id, &user_id, "watch").await?
+    );
+    assert!(
+      !DatabaseHandler::remove_webhook_token(&mut transaction, &guild_id, &user_id, "Watch")
+        .await?
+    );
+    assert!(DatabaseHandler::log_webhook_session(
+      &mut transaction,
+      &payload(&watch_token, 20),
+      now
+    )
+    .await?
+    .is_none());
+    assert_eq!(
+      DatabaseHandler::remove_webhook_tokens(&mut transaction, &guild_id, &user_id).await?,
+      1
+    );
+
+    // Members can't have more than the maximum number of tokens
+    for i in 0..MAX_TOKENS {
+      let (_, webhook_token) = WebhookToken::generate(guild_id, user_id, format!("Token {i}"), 10);
+      assert!(DatabaseHandler::add_webhook_token(&mut transaction, &webhook_token).await?);
+    }
+    let (_, webhook_token) = WebhookToken::generate(guild_id, user_id, "One Too Many", 10);
+    assert!(!DatabaseHandler::add_webhook_token(&mut transaction, &webhook_token).await?);
+
+    Ok(())
+  }
+
+  #[sqlx::test]
+  async fn test_profile_imports(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+
+    let source_guild_id = GuildId::new(123u64);
+    let guild_id = GuildId::new(456u64);
+    let user_id = UserId::new(123u64);
+
+    let link = ProfileLink::new(
+      "abc123".to_owned(),
+      source_guild_id,
+      user_id,
+      Utc::now() + ChronoDuration::hours(1),
+    );
+    DatabaseHandler::add_profile_link(&mut transaction, &link).await?;
+    let expired = ProfileLink::new(
+      "def456".to_owned(),
+      GuildId::new(789u64),
+      user_id,
+      Utc::now() - ChronoDuration::hours(1),
+    );
+    DatabaseHandler::add_profile_link(&mut transaction, &expired).await?;
+
+    let Some(saved) = DatabaseHandler::get_profile_link(&mut transaction, "abc123").await? else {
+      panic!("Expected the link to exist");
+    };
+    assert_eq!(saved.guild_id, source_guild_id);
+    assert_eq!(saved.user_id, user_id);
+    assert!(
+      DatabaseHandler::get_profile_link(&mut transaction, "def456")
+        .await?
+        .is_none()
+    );
+
+    meditate_on_days(&mut transaction, source_guild_id, user_id, &[0, 1, 2]).await?;
+    meditate_on_days(&mut transaction, guild_id, user_id, &[5]).await?;
+
+    let entries = DatabaseHandler::get_importable_entries(
+      &mut transaction,
+      &source_guild_id,
+      &guild_id,
+      &user_id,
+    )
+    .await?;
+    assert_eq!(entries.len(), 3);
+
+    let import = ProfileImport::new(
+      guild_id,
+      user_id,
+      source_guild_id,
+      i32::try_from(entries.len()).unwrap_or_default(),
+    );
+    DatabaseHandler::add_profile_import(&mut transaction, &import, &entries).await?;
+
+    assert_eq!(
+      DatabaseHandler::use_profile_link(&mut transaction, "abc123").await?,
+      1
+    );
+    assert_eq!(
+      DatabaseHandler::use_profile_link(&mut transaction, "abc123").await?,
+      0
+    );
+    assert!(
+      DatabaseHandler::get_profile_link(&mut transaction, "abc123")
+        .await?
+        .is_none()
+    );
+
+    // Entries are copied rather than moved
+    assert_eq!(
+      DatabaseHandler::get_user_meditation_entries(&mut transaction, &source_guild_id, &user_id)
+        .await?
+        .len(),
+      3
+    );
+    assert_eq!(
+      DatabaseHandler::get_user_meditation_entries(&mut transaction, &guild_id, &user_id)
+        .await?
+        .len(),
+      4
+    );
+
+    // Entries already copied, and entries which were themselves imported, are left out
+    assert!(DatabaseHandler::get_importable_entries(
+      &mut transaction,
+      &source_guild_id,
+      &guild_id,
+      &user_id,
+    )
+    .await?
+    .is_empty());
+    assert_eq!(
+      DatabaseHandler::get_importable_entries(
+        &mut transaction,
+        &guild_id,
+        &source_guild_id,
+        &user_id,
+      )
+      .await?
+      .len(),
+      1
+    );
+
+    Ok(())
+  }
+
+  #[sqlx::test]
+  async fn test_retreats(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+
+    let guild_id = GuildId::new(123u64);
+    let user_id = UserId::new(123u64);
+    let staff_id = UserId::new(456u64);
+    let now = Utc::now();
+    let today = now.date_naive();
+
+    meditate_on_days(&mut transaction, guild_id, user_id, &[0, 5]).await?;
+
+    let retreat = Retreat::new(
+      guild_id,
+      user_id,
+      today - ChronoDuration::days(4),
+      today - ChronoDuration::days(1),
+      360,
+    );
+    let entries: Vec<Meditation> = (1..=4)
+      .map(|days_ago| {
+        Meditation::new(
+          guild_id,
+          user_id,
+          retreat.minutes_per_day,
+          0,
+          &(now - ChronoDuration::days(days_ago)),
+        )
+      })
+      .collect();
+    DatabaseHandler::add_retreat(&mut transaction, &retreat, &entries).await?;
+
+    // The retreat bridges the gap between the existing entries
+    let streak = DatabaseHandler::recalculate_streak(&mut transaction, &guild_id, &user_id).await?;
+    assert_eq!((streak.current, streak.longest), (6, 6));
+    assert_eq!(
+      DatabaseHandler::get_user_meditation_sum(&mut transaction, &guild_id, &user_id).await?,
+      1460
+    );
+
+    assert!(DatabaseHandler::get_overlapping_retreat(
+      &mut transaction,
+      &guild_id,
+      &user_id,
+      today - ChronoDuration::days(2),
+      today,
+    )
+    .await?
+    .is_some());
+    assert!(DatabaseHandler::get_overlapping_retreat(
+      &mut transaction,
+      &guild_id,
+      &user_id,
+      today,
+      today,
+    )
+    .await?
+    .is_none());
+
+    assert_eq!(
+      DatabaseHandler::set_retreat_status(
+        &mut transaction,
+        &retreat.id,
+        RetreatStatus::Removed,
+        &staff_id,
+      )
+      .await?,
+      1
+    );
+    assert_eq!(
+      DatabaseHandler::set_retreat_status(
+        &mut transaction,
+        &retreat.id,
+        RetreatStatus::Approved,
+        &staff_id,
+      )
+      .await?,
+      0
+    );
+    assert_eq!(
+      DatabaseHandler::remove_retreat_entries(&mut transaction, &retreat.id).await?,
+      4
+    );
+
+    let Some(saved) = DatabaseHandler::get_retreat(&mut transaction, &retreat.id).await? else {
+      panic!("Expected the retreat to exist");
+    };
+    assert_eq!(saved.status, RetreatStatus::Removed);
+    assert_eq!(saved.handled_by, Some(staff_id));
+
+    // Removed retreats no longer count, and the days can be logged again
+    let streak = DatabaseHandler::recalculate_streak(&mut transaction, &guild_id, &user_id).await?;
+    assert_eq!((streak.current, streak.longest), (1, 1));
+    assert!(DatabaseHandler::get_overlapping_retreat(
+      &mut transaction,
+      &guild_id,
+      &user_id,
+      retreat.start_date,
+      retreat.end_date,
+    )
+    .await?
+    .is_none());
+
+    Ok(())
+  }
+
+  #[sqlx::test(fixtures(path = "fixtures", scripts("meditation")))]
+  async fn test_guild_recurring_stats(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+
+    let guild_id = GuildId::new(123u64);
+    let since = DateTime::parse_from_rfc3339("2023-12-01T00:00:00Z")?.with_timezone(&Utc);
+
+    let weekdays =
+      DatabaseHandler::get_guild_weekday_stats(&mut transaction, &guild_id, &since).await?;
+    assert_eq!(weekdays.len(), 7);
+    // 2024-01-01 was a Monday
+    assert_eq!((weekdays[0].sum, weekdays[0].count), (Some(10), Some(1)));
+    assert_eq!((weekdays[1].sum, weekdays[1].count), (Some(35), Some(2)));
+    assert!(weekdays[2..].iter().all(|stats| stats.count == Some(0)));
+
+    let hours = DatabaseHandler::get_guild_hour_stats(&mut transaction, &guild_id, &since).await?;
+    assert_eq!(hours.len(), 24);
+    assert_eq!((hours[0].sum, hours[0].count), (Some(45), Some(3)));
+    assert!(hours[1..].iter().all(|stats| stats.count == Some(0)));
+
+    let since = DateTime::parse_from_rfc3339("2024-01-02T00:00:00Z")?.with_timezone(&Utc);
+    let weekdays =
+      DatabaseHandler::get_guild_weekday_stats(&mut transaction, &guild_id, &since).await?;
+    assert_eq!(weekdays[0].count, Some(0));
+    assert_eq!(weekdays[1].count, Some(2));
+
+    Ok(())
+  }
+
+  #[sqlx::test(fixtures(path = "fixtures", scripts("meditation")))]
+  async fn test_range_stats(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+
+    let guild_id = GuildId::new(123u64);
+    let user_id = UserId::new(123u64);
+    let start = NaiveDate::from_ymd_opt(2023, 12, 31).unwrap_or_default();
+    let end = NaiveDate::from_ymd_opt(2024, 1, 3).unwrap_or_default();
+
+    let days = DatabaseHandler::get_user_range_stats(
+      &mut transaction,
+      &guild_id,
+      &user_id,
+      &Timeframe::Daily,
+      start,
+      end,
+      0,
+      None,
+    )
+    .await?;
+    let sums: Vec<_> = days.iter().map(|(_, stats)| stats.sum).collect();
+    assert_eq!(sums, [Some(0), Some(10), Some(15), Some(0)]);
+    assert_eq!(days[0].0, start);
+
+    // Two hours behind UTC, both sessions were on the previous day
+    let days = DatabaseHandler::get_user_range_stats(
+      &mut transaction,
+      &guild_id,
+      &user_id,
+      &Timeframe::Daily,
+      start,
+      end,
+      -120,
+      None,
+    )
+    .await?;
+    let sums: Vec<_> = days.iter().map(|(_, stats)| stats.sum).collect();
+    assert_eq!(sums, [Some(10), Some(15), Some(0), Some(0)]);
+
+    let weeks = DatabaseHandler::get_guild_range_stats(
+      &mut transaction,
+      &guild_id,
+      &Timeframe::Weekly,
+      start,
+      end,
+      None,
+    )
+    .await?;
+    assert_eq!(weeks.len(), 2);
+    assert_eq!((weeks[0].1.sum, weeks[0].1.count), (Some(0), Some(0)));
+    assert_eq!((weeks[1].1.sum, weeks[1].1.count), (Some(45), Some(3)));
+    assert_eq!(
+      weeks[1].0,
+      NaiveDate::from_ymd_opt(2024, 1, 1).unwrap_or_default()
+    );
+
+    Ok(())
+  }
+
+  #[sqlx::test(fixtures(path = "fixtures", scripts("meditation")))]
+  async fn test_channel_segments(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+
+    let guild_id = GuildId::new(123u64);
+    let user_id = UserId::new(123u64);
+    let channel_id = ChannelId::new(789u64);
+    let today = Utc::now().date_naive();
+
+    let entry = Meditation::new(guild_id, user_id, 5, 0, &Utc::now()).channel(Some(channel_id));
+    DatabaseHandler::add_meditation_entry(&mut transaction, &entry).await?;
+    DatabaseHandler::add_meditation_entry(
+      &mut transaction,
+      &Meditation::new(guild_id, UserId::new(124u64), 10, 0, &Utc::now()),
+    )
+    .await?;
+
+    let entry = DatabaseHandler::get_meditation_entry(&mut transaction, &guild_id, &entry.id)
+      .await?
+      .unwrap_or_default();
+    assert_eq!(entry.channel_id, Some(channel_id));
+
+    // Only meditation tracked in the channel is included
+    let days = DatabaseHandler::get_guild_range_stats(
+      &mut transaction,
+      &guild_id,
+      &Timeframe::Daily,
+      today,
+      today,
+      Some(channel_id),
+    )
+    .await?;
+    assert_eq!((days[0].1.sum, days[0].1.count), (Some(5), Some(1)));
+
+    let days = DatabaseHandler::get_guild_range_stats(
+      &mut transaction,
+      &guild_id,
+      &Timeframe::Daily,
+      today,
+      today,
+      None,
+    )
+    .await?;
+    assert_eq!((days[0].1.sum, days[0].1.count), (Some(15), Some(2)));
+
+    let leaderboard = DatabaseHandler::get_channel_leaderboard_stats(
+      &mut transaction,
+      &guild_id,
+      &channel_id,
+      &Timeframe::Daily,
+      &SortBy::Minutes,
+      &LeaderboardType::Top5,
+    )
+    .await?;
+    assert_eq!(leaderboard.len(), 1);
+    assert_eq!(leaderboard[0].name.as_deref(), Some("123"));
+    assert_eq!(leaderboard[0].minutes, Some(5));
+
+    Ok(())
+  }
+
+  #[sqlx::test(fixtures(path = "fixtures", scripts("meditation")))]
+  async fn test_get_latest_meditation_guild(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+
+    assert_eq!(
+      DatabaseHandler::get_latest_meditation_guild(&mut transaction, &UserId::new(123u64)).await?,
+      Some(GuildId::new(456u64))
+    );
+    assert_eq!(
+      DatabaseHandler::get_latest_meditation_guild(&mut transaction, &UserId::new(999u64)).await?,
+      None
+    );
+
+    Ok(())
+  }
+
+  #[sqlx::test(fixtures(path = "fixtures", scripts("meditation")))]
+  async fn test_get_anonymizable_meditation(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+    let guild_id = GuildId::new(123u64);
+
+    let entries =
+      DatabaseHandler::get_anonymizable_meditation(&mut transaction, &guild_id, 1).await?;
+    assert_eq!(entries.len(), 3);
+
+    // Members with fewer sessions than the minimum are left out
+    let entries =
+      DatabaseHandler::get_anonymizable_meditation(&mut transaction, &guild_id, 2).await?;
+    assert_eq!(entries.len(), 2);
+    assert!(entries
+      .iter()
+      .all(|entry| entry.user_id == UserId::new(123u64)));
+    assert!(entries[0].occurred_at < entries[1].occurred_at);
+
+    // As are members with private stats
+    DatabaseHandler::add_tracking_profile(
+      &mut transaction,
+      &TrackingProfile::new(guild_id, UserId::new(123u64)).stats_privacy(Privacy::Private),
+    )
+    .await?;
+    let entries =
+      DatabaseHandler::get_anonymizable_meditation(&mut transaction, &guild_id, 1).await?;
+    assert_eq!(entries.len(), 1);
+
+    Ok(())
+  }
+
+  #[sqlx::test(fixtures(path = "fixtures", scripts("meditation")))]
+  async fn test_get_member_progress(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+
+    let guild_id = GuildId::new(123u64);
+    DatabaseHandler::add_tracking_profile(
+      &mut transaction,
+      &TrackingProfile::new(guild_id, UserId::new(124u64)).streak_status(Status::Disabled),
+    )
+    .await?;
+
+    let mut progress = DatabaseHandler::get_member_progress(&mut transaction, &guild_id).await?;
+    progress.sort_by_key(|member| member.user_id);
+
+    assert_eq!(progress.len(), 2);
+    assert_eq!(progress[0].user_id, UserId::new(123u64));
+    assert_eq!(progress[0].minutes, 25);
+    assert!(progress[0].streaks_active);
+    assert_eq!(
+      progress[0].last_meditation,
+      DateTime::parse_from_rfc3339("2024-01-02T00:00:00Z")?.with_timezone(&Utc)
+    );
+    assert_eq!(progress[1].user_id, UserId::new(124u64));
+    assert_eq!(progress[1].minutes, 20);
+    assert!(!progress[1].streaks_active);
+
+    Ok(())
+  }
+
+  #[sqlx::test(fixtures(path = "fixtures", scripts("meditation")))]
+  async fn test_get_guild_streaks(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+
+    let guild_id = GuildId::new(123u64);
+    assert!(
+      DatabaseHandler::get_guild_streaks(&mut transaction, &guild_id)
+        .await?
+        .is_empty()
+    );
+
+    DatabaseHandler::update_streak(
+      &mut transaction,
+      &Streak::new(guild_id, UserId::new(124u64), 0, 2),
+    )
+    .await?;
+    DatabaseHandler::update_streak(
+      &mut transaction,
+      &Streak::new(GuildId::new(456u64), UserId::new(123u64), 0, 3),
+    )
+    .await?;
+
+    let streaks = DatabaseHandler::get_guild_streaks(&mut transaction, &guild_id).await?;
+    assert_eq!(streaks.len(), 1);
+    assert_eq!(streaks[0].user_id(), UserId::new(124u64));
+    assert_eq!(streaks[0].longest, 2);
+
+    Ok(())
+  }
+
+  #[sqlx::test(fixtures(path = "fixtures", scripts("meditation")))]
+  async fn test_get_meditating_users(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+
+    let mut users =
+      DatabaseHandler::get_meditating_users(&mut transaction, &GuildId::new(123u64)).await?;
+    users.sort();
+    assert_eq!(users, vec![UserId::new(123u64), UserId::new(124u64)]);
+
+    let users =
+      DatabaseHandler::get_meditating_users(&mut transaction, &GuildId::new(456u64)).await?;
+    assert_eq!(users, vec![UserId::new(123u64)]);
+
+    Ok(())
+  }
+
+  #[sqlx::test(fixtures(path = "fixtures", scripts("meditation")))]
+  async fn test_merge_meditation_entries(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+
+    let guild_id = GuildId::new(123u64);
+    let old_user_id = UserId::new(123u64);
+    let new_user_id = UserId::new(124u64);
+
+    // A stale longest streak should not survive the merge
+    DatabaseHandler::update_streak(&mut transaction, &Streak::new(guild_id, new_user_id, 0, 5))
+      .await?;
+
+    let migration = Migration::new(
+      guild_id,
+      old_user_id,
+      new_user_id,
+      MigrationType::MeditationEntries,
+    );
+    DatabaseHandler::migrate_meditation_entries(&mut transaction, &migration).await?;
+
+    assert_eq!(
+      DatabaseHandler::get_user_meditation_count(&mut transaction, &guild_id, &new_user_id).await?,
+      3
+    );
+    assert_eq!(
+      DatabaseHandler::get_user_meditation_sum(&mut transaction, &guild_id, &new_user_id).await?,
+      45
+    );
+    assert_eq!(
+      DatabaseHandler::get_user_meditation_count(&mut transaction, &guild_id, &old_user_id).await?,
+      0
+    );
+
+    let streak =
+      DatabaseHandler::recalculate_streak(&mut transaction, &guild_id, &new_user_id).await?;
+    assert_eq!(streak.current, 0);
+    assert_eq!(streak.longest, 2);
+
+    let old_streak =
+      DatabaseHandler::recalculate_streak(&mut transaction, &guild_id, &old_user_id).await?;
+    assert_eq!(old_streak.longest, 0);
+
+    Ok(())
+  }
+
+  #[sqlx::test]
+  async fn test_retention_policies(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+
+    let guild_id = GuildId::new(123u64);
+    let other_guild_id = GuildId::new(456u64);
+    let user_id = UserId::new(789u64);
+    let now = Utc::now();
+
+    for (guild_id, days_ago) in [(guild_id, 200), (guild_id, 10), (other_guild_id, 200)] {
+      let occurred_at = now - ChronoDuration::days(days_ago);
+      DatabaseHandler::add_erase(
+        &mut transaction,
+        &Erase::new(guild_id, user_id, "", "Spam", &occurred_at),
+      )
+      .await?;
+    }
+    for (guild_id, days_ago) in [(guild_id, 800), (guild_id, 30), (other_guild_id, 800)] {
+      let occurred_at = now - ChronoDuration::days(days_ago);
+      DatabaseHandler::add_meditation_entry(
+        &mut transaction,
+        &Meditation::new(guild_id, user_id, 20, 0, &occurred_at),
+      )
+      .await?;
+    }
+
+    assert!(
+      DatabaseHandler::get_retention_policy(&mut transaction, &guild_id)
+        .await?
+        .is_none()
+    );
+    let policy = RetentionPolicy::new(guild_id)
+      .erase_days(Some(180))
+      .archive_years(Some(2));
+    DatabaseHandler::update_retention_policy(&mut transaction, &policy).await?;
+    let Some(saved) = DatabaseHandler::get_retention_policy(&mut transaction, &guild_id).await?
+    else {
+      panic!("Expected the policy to exist");
+    };
+    assert_eq!(saved, policy);
+    assert!(saved.dry_run);
+
+    let expected = RetentionSummary {
+      erases: 1,
+      meditations: 1,
+    };
+    assert_eq!(
+      DatabaseHandler::preview_retention(&mut transaction, &policy, now).await?,
+      expected
+    );
+    assert_eq!(
+      DatabaseHandler::apply_retention(&mut transaction, &policy, now).await?,
+      expected
+    );
+    assert_eq!(
+      DatabaseHandler::preview_retention(&mut transaction, &policy, now).await?,
+      RetentionSummary::default()
+    );
+
+    // Other guilds' data is untouched
+    assert_eq!(
+      DatabaseHandler::get_erases(&mut transaction, &guild_id, &user_id)
+        .await?
+        .len(),
+      1
+    );
+    assert_eq!(
+      DatabaseHandler::get_erases(&mut transaction, &other_guild_id, &user_id)
+        .await?
+        .len(),
+      1
+    );
+    assert_eq!(
+      DatabaseHandler::get_user_meditation_count(&mut transaction, &guild_id, &user_id).await?,
+      1
+    );
+    assert_eq!(
+      DatabaseHandler::get_user_meditation_count(&mut transaction, &other_guild_id, &user_id)
+        .await?,
+      2
+    );
+    let archived: i64 =
+      sqlx::query_scalar("SELECT COUNT(*) FROM meditation_archive WHERE guild_id = $1")
+        .bind(guild_id.to_string())
+        .fetch_one(&mut *transaction)
+        .await?;
+    assert_eq!(archived, 1);
+
+    DatabaseHandler::mark_retention_run(&mut transaction, &guild_id).await?;
+    let policies = DatabaseHandler::get_retention_policies(&mut transaction).await?;
+    assert_eq!(policies.len(), 1);
+    assert!(policies[0].last_run_at.is_some());
+
+    assert!(DatabaseHandler::remove_retention_policy(&mut transaction, &guild_id).await?);
+    assert!(!DatabaseHandler::remove_retention_policy(&mut transaction, &guild_id).await?);
+
+    Ok(())
+  }
+
+  #[sqlx::test(fixtures(path = "fixtures", scripts("star")))]
+  async fn test_star_messages_to_check(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+
+    let guild_id = GuildId::new(123u64);
+
+    // Legacy messages without a guild are checked along with the guild's own
+    let star_messages =
+      DatabaseHandler::get_star_messages_to_check(&mut transaction, &guild_id, 10).await?;
+    assert_eq!(star_messages.len(), 2);
+
+    DatabaseHandler::mark_star_messages_checked(&mut transaction, &[star_messages[0].id.clone()])
+      .await?;
+    let star_messages_after =
+      DatabaseHandler::get_star_messages_to_check(&mut transaction, &guild_id, 1).await?;
+    assert_eq!(star_messages_after.len(), 1);
+    assert_eq!(star_messages_after[0].id, star_messages[1].id);
+
+    Ok(())
+  }
+
+  #[sqlx::test(fixtures(path = "fixtures", scripts("star")))]
+  async fn test_star_message_scoped_to_guild(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+
+    let guild_id = &GuildId::new(123u64);
+    let other_guild_id = &GuildId::new(456u64);
+    let message_id = &MessageId::new(1001u64);
+    let legacy_message_id = &MessageId::new(1003u64);
+
+    assert!(
+      DatabaseHandler::get_star_message(&mut transaction, other_guild_id, message_id)
+        .await?
+        .is_none()
+    );
+
+    let Some(star_message) =
+      DatabaseHandler::get_star_message(&mut transaction, guild_id, message_id).await?
+    else {
+      panic!("Fixture star message should exist");
+    };
+
+    assert_eq!(star_message.guild_id, Some(*guild_id));
+    assert_eq!(star_message.board_message, MessageId::new(2001u64));
+
+    DatabaseHandler::remove_star_message(&mut transaction, other_guild_id, &star_message.id)
+      .await?;
+
+    assert!(
+      DatabaseHandler::get_star_message(&mut transaction, guild_id, message_id)
+        .await?
+        .is_some()
+    );
+
+    DatabaseHandler::remove_star_message(&mut transaction, guild_id, &star_message.id).await?;
+
+    assert!(
+      DatabaseHandler::get_star_message(&mut transaction, guild_id, message_id)
+        .await?
+        .is_none()
+    );
+
+    // Entries recorded before guild scoping have no guild and match any guild.
+    let Some(legacy_message) =
+      DatabaseHandler::get_star_message(&mut transaction, other_guild_id, legacy_message_id)
+        .await?
+    else {
+      panic!("Fixture star message should exist");
+    };
+
+    assert_eq!(legacy_message.guild_id, None);
+
+    Ok(())
+  }
+
+  #[sqlx::test]
+  async fn test_guild_settings(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+    let guild_id = GuildId::new(123u64);
+
+    // Guilds which haven't saved any settings get the defaults
+    assert!(
+      DatabaseHandler::get_guild_settings(&mut transaction, &guild_id)
+        .await?
+        .quotes_on_add
+    );
+
+    let settings = GuildSettings::new(guild_id).quotes_on_add(false);
+    DatabaseHandler::update_guild_settings(&mut transaction, &settings).await?;
+
+    assert!(
+      !DatabaseHandler::get_guild_settings(&mut transaction, &guild_id)
+        .await?
+        .quotes_on_add
+    );
+    assert!(
+      DatabaseHandler::get_guild_settings(&mut transaction, &GuildId::new(456u64))
+        .await?
+        .quotes_on_add
+    );
+
+    // Saving again replaces the existing settings
+    let settings = GuildSettings::new(guild_id)
+      .quotes_on_add(true)
+      .search_threshold(0.5);
+    DatabaseHandler::update_guild_settings(&mut transaction, &settings).await?;
+
+    let settings = DatabaseHandler::get_guild_settings(&mut transaction, &guild_id).await?;
+    assert!(settings.quotes_on_add);
+    assert!((settings.search_threshold - 0.5).abs() < f64::EPSILON);
+    assert!(settings.tracking_channel.is_none());
+    assert!(settings.tracking_hints);
+
+    let settings = settings
+      .tracking_channel(Some(ChannelId::new(789u64)))
+      .tracking_hints(false);
+    DatabaseHandler::update_guild_settings(&mut transaction, &settings).await?;
+
+    let settings = DatabaseHandler::get_guild_settings(&mut transaction, &guild_id).await?;
+    assert_eq!(settings.tracking_channel, Some(ChannelId::new(789u64)));
+    assert!(!settings.tracking_hints);
+    assert_eq!(settings.milestone_interval, Some(60000));
+
+    let settings = settings.milestone_interval(None);
+    DatabaseHandler::update_guild_settings(&mut transaction, &settings).await?;
+
+    let settings = DatabaseHandler::get_guild_settings(&mut transaction, &guild_id).await?;
+    assert!(settings.milestone_interval.is_none());
+    assert!(settings.sit_channel.is_none());
+
+    let settings = settings.sit_channel(Some(ChannelId::new(321u64)));
+    DatabaseHandler::update_guild_settings(&mut transaction, &settings).await?;
+
+    let settings = DatabaseHandler::get_guild_settings(&mut transaction, &guild_id).await?;
+    assert_eq!(settings.sit_channel, Some(ChannelId::new(321u64)));
+    assert!(settings.greeting_channel.is_none());
+    assert!(settings.welcome_message.is_none());
+    assert!(settings.farewell_message.is_some());
+
+    let settings = settings
+      .greeting_channel(Some(ChannelId::new(654u64)))
+      .welcome_message(Some("Welcome, {user}!".to_owned()))
+      .farewell_message(None);
+    DatabaseHandler::update_guild_settings(&mut transaction, &settings).await?;
+
+    let settings = DatabaseHandler::get_guild_settings(&mut transaction, &guild_id).await?;
+    assert_eq!(settings.greeting_channel, Some(ChannelId::new(654u64)));
+    assert_eq!(
+      settings.welcome_message.as_deref(),
+      Some("Welcome, {user}!")
+    );
+    assert!(settings.farewell_message.is_none());
+    assert!(!settings.auto_publish);
+
+    let settings = settings.auto_publish(true);
+    DatabaseHandler::update_guild_settings(&mut transaction, &settings).await?;
+
+    let settings = DatabaseHandler::get_guild_settings(&mut transaction, &guild_id).await?;
+    assert!(settings.auto_publish);
+    assert!(settings.key_claim_channel.is_none());
+
+    let settings = settings
+      .key_claim_channel(Some(ChannelId::new(987u64)))
+      .winner_title(Some("{month} Winner".to_owned()))
+      .winner_image(Some("https://example.com/winner.png".to_owned()));
+    DatabaseHandler::update_guild_settings(&mut transaction, &settings).await?;
+
+    let settings = DatabaseHandler::get_guild_settings(&mut transaction, &guild_id).await?;
+    assert_eq!(settings.key_claim_channel, Some(ChannelId::new(987u64)));
+    assert_eq!(settings.winner_title.as_deref(), Some("{month} Winner"));
+    assert!(settings.winner_message.is_none());
+    assert_eq!(
+      settings.winner_image.as_deref(),
+      Some("https://example.com/winner.png")
+    );
+    assert!(!settings.channel_segments);
+
+    DatabaseHandler::update_guild_settings(&mut transaction, &settings.channel_segments(true))
+      .await?;
+    assert!(
+      DatabaseHandler::get_guild_settings(&mut transaction, &guild_id)
+        .await?
+        .channel_segments
+    );
+
+    let settings = DatabaseHandler::get_guild_settings(&mut transaction, &guild_id).await?;
+    assert!(settings.talk_role.is_none());
+    DatabaseHandler::update_guild_settings(
+      &mut transaction,
+      &settings.talk_role(Some(RoleId::new(321u64))),
+    )
+    .await?;
+    assert_eq!(
+      DatabaseHandler::get_guild_settings(&mut transaction, &guild_id)
+        .await?
+        .talk_role,
+      Some(RoleId::new(321u64))
+    );
+
+    let settings = DatabaseHandler::get_guild_settings(&mut transaction, &guild_id).await?;
+    assert!(!settings.global_stats);
+    DatabaseHandler::update_guild_settings(&mut transaction, &settings.global_stats(true)).await?;
+    assert!(
+      DatabaseHandler::get_guild_settings(&mut transaction, &guild_id)
+        .await?
+        .global_stats
+    );
+
+    Ok(())
+  }
+
+  #[sqlx::test]
+  async fn test_global_stats(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+
+    let guild_id = GuildId::new(123u64);
+    let other_guild_id = GuildId::new(456u64);
+    let private_guild_id = GuildId::new(789u64);
+    for guild_id in [guild_id, other_guild_id] {
+      DatabaseHandler::update_guild_settings(
+        &mut transaction,
+        &GuildSettings::new(guild_id).global_stats(true),
+      )
+      .await?;
+    }
+
+    let user_id = UserId::new(1u64);
+    let private_user_id = UserId::new(2u64);
+    meditate_on_days(&mut transaction, guild_id, user_id, &[0, 3]).await?;
+    meditate_on_days(&mut transaction, other_guild_id, user_id, &[0]).await?;
+    meditate_on_days(
+      &mut transaction,
+      other_guild_id,
+      UserId::new(3u64),
+      &[3, 10],
+    )
+    .await?;
+    // Guilds which haven't turned on global stats are left out
+    meditate_on_days(&mut transaction, private_guild_id, UserId::new(4u64), &[0]).await?;
+    // As are members with private stats
+    meditate_on_days(&mut transaction, guild_id, private_user_id, &[0]).await?;
+    DatabaseHandler::add_tracking_profile(
+      &mut transaction,
+      &TrackingProfile::new(guild_id, private_user_id).stats_privacy(Privacy::Private),
+    )
+    .await?;
+
+    let stats = DatabaseHandler::get_global_stats(&mut transaction).await?;
+    assert_eq!(stats.guilds, 2);
+    assert_eq!(stats.minutes_today, 20);
+    assert_eq!(stats.sessions_today, 2);
+    // Members are only counted once, however many guilds they meditate in
+    assert_eq!(stats.meditators_today, 1);
+    assert_eq!(stats.meditators_week, 2);
+
+    Ok(())
+  }
+
+  #[sqlx::test]
+  async fn test_record_guild_milestone(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+    let guild_id = GuildId::new(123u64);
+
+    let milestone = Milestone::new(guild_id, 60000);
+    assert_eq!(
+      DatabaseHandler::record_guild_milestone(&mut transaction, &milestone).await?,
+      1
+    );
+
+    // Milestones which have already been reached aren't recorded again
+    assert_eq!(
+      DatabaseHandler::record_guild_milestone(&mut transaction, &milestone).await?,
+      0
+    );
+
+    // Other guilds can reach the same milestone
+    let milestone = Milestone::new(GuildId::new(456u64), 60000);
+    assert_eq!(
+      DatabaseHandler::record_guild_milestone(&mut transaction, &milestone).await?,
+      1
+    );
+
+    Ok(())
+  }
+
+  #[sqlx::test(fixtures(path = "fixtures", scripts("steamkey")))]
+  async fn test_get_key_offer(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut connection = handler.get_connection().await?;
+
+    let Some(offer) = DatabaseHandler::get_key_offer(&mut connection, "1234567890").await? else {
+      panic!("Expected key offer to exist");
+    };
+
+    assert_eq!(offer.key, "AAAAA-BBBBB-CCCCC");
+    assert_eq!(offer.guild_id, GuildId::new(123u64));
+    assert_eq!(offer.user_id, UserId::new(123u64));
+    assert_eq!(offer.channel_id, ChannelId::new(456u64));
+    assert_eq!(offer.message_id, MessageId::new(789u64));
+
+    assert!(DatabaseHandler::get_key_offer(&mut connection, "0")
+      .await?
+      .is_none());
+
+    Ok(())
+  }
+
+  #[sqlx::test(fixtures(path = "fixtures", scripts("steamkey")))]
+  async fn test_get_expired_key_offers(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut connection = handler.get_connection().await?;
+
+    let expired = DatabaseHandler::get_expired_key_offers(&mut connection).await?;
+
+    assert_eq!(expired.len(), 1);
+    assert_eq!(expired[0].id, "1234567891");
+    assert_eq!(expired[0].key, "DDDDD-EEEEE-FFFFF");
+
+    Ok(())
+  }
+
+  #[sqlx::test(fixtures(path = "fixtures", scripts("steamkey")))]
+  async fn test_redeem_key_offer(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut connection = handler.get_connection().await?;
+    let guild_id = GuildId::new(123u64);
+
+    // Offers are scoped to the guild the key belongs to
+    assert_eq!(
+      DatabaseHandler::redeem_key_offer(&mut connection, &GuildId::new(456u64), "1234567890")
+        .await?,
+      0
+    );
+    assert_eq!(
+      DatabaseHandler::redeem_key_offer(&mut connection, &guild_id, "1234567890").await?,
+      1
+    );
+    // Pressing the button again must not resolve the offer twice
+    assert_eq!(
+      DatabaseHandler::redeem_key_offer(&mut connection, &guild_id, "1234567890").await?,
+      0
+    );
+    // But the key can still be found again by the winner, using the offer as a token
+    assert_eq!(
+      DatabaseHandler::get_redeemed_key(&mut connection, "1234567890", &UserId::new(123u64))
+        .await?
+        .as_deref(),
+      Some("AAAAA-BBBBB-CCCCC")
+    );
+    assert!(
+      DatabaseHandler::get_redeemed_key(&mut connection, "1234567890", &UserId::new(321u64))
+        .await?
+        .is_none()
+    );
+    assert_eq!(
+      DatabaseHandler::withdraw_key_offer(&mut connection, &guild_id, "1234567890").await?,
+      0
+    );
+    assert!(
+      DatabaseHandler::get_key_offer(&mut connection, "1234567890")
+        .await?
+        .is_none()
+    );
+
+    Ok(())
+  }
+
+  #[sqlx::test(fixtures(path = "fixtures", scripts("steamkey")))]
+  async fn test_withdraw_key_offer(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut connection = handler.get_connection().await?;
+    let guild_id = GuildId::new(123u64);
+
+    assert_eq!(
+      DatabaseHandler::withdraw_key_offer(&mut connection, &guild_id, "1234567891").await?,
+      1
+    );
+
+    let mut transaction = handler.start_transaction().await?;
+    let keys = DatabaseHandler::get_all_steam_keys(&mut transaction, &guild_id).await?;
+    let Some(key) = keys.iter().find(|key| key.key == "DDDDD-EEEEE-FFFFF") else {
+      panic!("Expected key to exist");
+    };
+
+    assert!(!key.used);
+    assert!(key.reserved.is_none());
+
+    Ok(())
+  }
+
+  #[sqlx::test(fixtures(path = "fixtures", scripts("steamkey")))]
+  async fn test_record_key_offer(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut connection = handler.get_connection().await?;
+    let guild_id = GuildId::new(123u64);
+    let user_id = UserId::new(555u64);
+
+    let mut transaction = handler.start_transaction().await?;
+    let Some(key) = DatabaseHandler::reserve_key(&mut transaction, &guild_id, &user_id).await?
+    else {
+      panic!("Expected an unreserved key to exist");
+    };
+    DatabaseHandler::commit_transaction(transaction).await?;
+
+    let expires_at = DateTime::from_timestamp(4_102_444_800, 0).unwrap_or_default();
+    let offer = KeyOffer::new(
+      "2345678901",
+      guild_id,
+      key.as_str(),
+      user_id,
+      ChannelId::new(111u64),
+      MessageId::new(222u64),
+      expires_at,
+    );
+    DatabaseHandler::record_key_offer(&mut connection, &offer).await?;
+
+    let Some(recorded) = DatabaseHandler::get_key_offer(&mut connection, "2345678901").await?
+    else {
+      panic!("Expected key offer to be recorded");
+    };
+
+    assert_eq!(recorded.key, key);
+    assert_eq!(recorded.user_id, user_id);
+    assert_eq!(recorded.expires_at, expires_at);
+
+    Ok(())
+  }
+
+  #[sqlx::test(fixtures(path = "fixtures", scripts("steamkey")))]
+  async fn test_reserve_key_race(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut first = handler.start_transaction().await?;
+    let mut second = handler.start_transaction().await?;
+
+    let guild_id = &GuildId::new(123u64);
+
+    // While the only available key is reserved by one transaction, the other skips it
+    // rather than waiting for it or reserving it twice
+    let reserved = DatabaseHandler::reserve_key(&mut first, guild_id, &UserId::new(1u64)).await?;
+    assert_eq!(reserved.as_deref(), Some("GGGGG-HHHHH-IIIII"));
+    assert!(
+      DatabaseHandler::reserve_key(&mut second, guild_id, &UserId::new(2u64))
+        .await?
+        .is_none()
+    );
+    assert!(
+      DatabaseHandler::get_key_and_mark_used(&mut second, guild_id)
+        .await?
+        .is_none()
+    );
+
+    // Once the reservation is rolled back, the key is available again
+    DatabaseHandler::rollback_transaction(first).await?;
+    let consumed = DatabaseHandler::get_key_and_mark_used(&mut second, guild_id).await?;
+    assert_eq!(consumed.as_deref(), Some("GGGGG-HHHHH-IIIII"));
+    DatabaseHandler::commit_transaction(second).await?;
+
+    let mut transaction = handler.start_transaction().await?;
+    assert!(!DatabaseHandler::unused_key_exists(&mut transaction, guild_id).await?);
+    assert!(
+      DatabaseHandler::reserve_key(&mut transaction, guild_id, &UserId::new(1u64))
+        .await?
+        .is_none()
+    );
+
+    Ok(())
+  }
+
+  #[sqlx::test(fixtures(path = "fixtures", scripts("steamkey_expiry")))]
+  async fn test_key_pool_hygiene(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut connection = handler.get_connection().await?;
+    let guild_id = &GuildId::new(123u64);
+
+    // Expired keys are never handed out
+    let mut transaction = handler.start_transaction().await?;
+    let reserved =
+      DatabaseHandler::reserve_key(&mut transaction, guild_id, &UserId::new(1u64)).await?;
+    assert_eq!(reserved.as_deref(), Some("VALID-UNTIL-FFFFF"));
+    assert!(!DatabaseHandler::unused_key_exists(&mut transaction, guild_id).await?);
+    DatabaseHandler::rollback_transaction(transaction).await?;
+
+    let mut flagged = DatabaseHandler::flag_expired_keys(&mut connection).await?;
+    flagged.sort_by(|a, b| a.key.cmp(&b.key));
+    assert_eq!(flagged.len(), 2);
+    assert_eq!(flagged[0].key, "EXPIR-EDKEY-AAAAA");
+    assert!(flagged[0].reserved.is_none());
+    assert_eq!(flagged[1].key, "EXPIR-EDKEY-BBBBB");
+    assert_eq!(flagged[1].reserved, Some(UserId::new(321u64)));
+
+    // The offer for the expired key is cleared, and keys are only flagged once
+    assert!(
+      DatabaseHandler::get_key_offer(&mut connection, "3456789012")
+        .await?
+        .is_none()
+    );
+    assert!(DatabaseHandler::flag_expired_keys(&mut connection)
+      .await?
+      .is_empty());
+
+    let cutoff = Utc::now() - ChronoDuration::days(1);
+    let released = DatabaseHandler::release_stale_keys(&mut connection, &cutoff).await?;
+    assert_eq!(released.len(), 1);
+    assert_eq!(released[0].key, "STALE-RESER-DDDDD");
+    assert_eq!(released[0].reserved, Some(UserId::new(555u64)));
+
+    let mut transaction = handler.start_transaction().await?;
+    let keys = DatabaseHandler::get_all_steam_keys(&mut transaction, guild_id).await?;
+    let reserved_for = |key: &str| {
+      keys
+        .iter()
+        .find(|steam_key| steam_key.key == key)
+        .and_then(|steam_key| steam_key.reserved)
+    };
+    assert_eq!(reserved_for("EXPIR-EDKEY-BBBBB"), None);
+    assert_eq!(reserved_for("STALE-RESER-DDDDD"), None);
+    assert_eq!(reserved_for("FRESH-RESER-EEEEE"), Some(UserId::new(666u64)));
+    assert!(keys
+      .iter()
+      .filter(|steam_key| steam_key.key.starts_with("EXPIR"))
+      .all(SteamKey::expired));
+
+    Ok(())
+  }
+
+  #[sqlx::test(fixtures(path = "fixtures", scripts("course")))]
+  async fn test_course_dm_only_completion(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+
+    let guild_id = &GuildId::new(123u64);
+
+    let Some(course) =
+      DatabaseHandler::get_course(&mut transaction, guild_id, "compassion course").await?
+    else {
+      panic!("Expected course to be found");
+    };
+    assert!(course.dm_only_completion);
+    assert_eq!(course.guild_id, *guild_id);
+
+    let course = Course::new(
+      course.name,
+      course.participant_role,
+      course.graduate_role,
+      *guild_id,
+    );
+    DatabaseHandler::update_course(&mut transaction, &course).await?;
+
+    let Some(course) =
+      DatabaseHandler::get_course_in_dm(&mut transaction, "Compassion Course").await?
+    else {
+      panic!("Expected course to be found");
+    };
+    assert!(!course.dm_only_completion);
+
+    Ok(())
+  }
+
+  #[sqlx::test(fixtures(path = "fixtures", scripts("course")))]
+  async fn test_get_enrollment_code(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+
+    let guild_id = &GuildId::new(123u64);
+
+    let Some(code) =
+      DatabaseHandler::get_enrollment_code(&mut transaction, guild_id, "singleus").await?
+    else {
+      panic!("Expected enrollment code to be found");
+    };
+
+    assert_eq!(code.code, "SINGLEUS");
+    assert_eq!(code.course_name, "Mindfulness Course");
+    assert_eq!(code.max_uses, Some(1));
+    assert!(!code.exhausted());
+
+    assert!(DatabaseHandler::get_enrollment_code(
+      &mut transaction,
+      &GuildId::new(456u64),
+      "SINGLEUS"
+    )
+    .await?
+    .is_none());
+
+    let codes =
+      DatabaseHandler::get_enrollment_codes(&mut transaction, guild_id, "mindfulness course")
+        .await?;
+    assert_eq!(codes.len(), 2);
+    assert_eq!(
+      codes.first().map(|code| code.code.as_str()),
+      Some("UNLIMITD")
+    );
+
+    Ok(())
+  }
+
+  #[sqlx::test(fixtures(path = "fixtures", scripts("course")))]
+  async fn test_redeem_enrollment_code(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+
+    let guild_id = &GuildId::new(123u64);
+
+    assert_eq!(
+      DatabaseHandler::redeem_enrollment_code(&mut transaction, guild_id, "SINGLEUS").await?,
+      1
+    );
+    assert_eq!(
+      DatabaseHandler::redeem_enrollment_code(&mut transaction, guild_id, "SINGLEUS").await?,
+      0
+    );
+    assert_eq!(
+      DatabaseHandler::redeem_enrollment_code(&mut transaction, guild_id, "USEDUP22").await?,
+      0
+    );
+    assert_eq!(
+      DatabaseHandler::redeem_enrollment_code(&mut transaction, guild_id, "unlimitd").await?,
+      1
+    );
+
+    let Some(code) =
+      DatabaseHandler::get_enrollment_code(&mut transaction, guild_id, "SINGLEUS").await?
+    else {
+      panic!("Expected enrollment code to be found");
+    };
+    assert_eq!(code.uses, 1);
+    assert!(code.exhausted());
+
+    let Some(code) =
+      DatabaseHandler::get_enrollment_code(&mut transaction, guild_id, "UNLIMITD").await?
+    else {
+      panic!("Expected enrollment code to be found");
+    };
+    assert_eq!(code.uses, 6);
+    assert!(!code.exhausted());
+
+    // Rejoining a course keeps the original enrollment record
+    let enrollment = Enrollment::new(
+      "Mindfulness Course",
+      *guild_id,
+      UserId::new(789u64),
+      "SINGLEUS",
+    );
+    DatabaseHandler::record_course_enrollment(&mut transaction, &enrollment).await?;
+    DatabaseHandler::record_course_enrollment(&mut transaction, &enrollment).await?;
+
+    Ok(())
+  }
+
+  #[sqlx::test(fixtures(path = "fixtures", scripts("course")))]
+  async fn test_add_remove_enrollment_code(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+
+    let guild_id = &GuildId::new(123u64);
+
+    let code = EnrollmentCode::new(
+      "NEWCODE2",
+      "Compassion Course",
+      *guild_id,
+      None,
+      UserId::new(789u64),
+    );
+    DatabaseHandler::add_enrollment_code(&mut transaction, &code).await?;
+
+    let codes =
+      DatabaseHandler::get_enrollment_codes(&mut transaction, guild_id, "Compassion Course")
+        .await?;
+    assert_eq!(codes.len(), 2);
+
+    assert_eq!(
+      DatabaseHandler::remove_enrollment_code(&mut transaction, guild_id, "newcode2").await?,
+      1
+    );
+    assert_eq!(
+      DatabaseHandler::remove_enrollment_code(&mut transaction, guild_id, "NEWCODE2").await?,
+      0
+    );
+
+    // Codes are removed along with their course
+    DatabaseHandler::remove_course(&mut transaction, guild_id, "Compassion Course").await?;
+    assert!(
+      DatabaseHandler::get_enrollment_code(&mut transaction, guild_id, "USEDUP22")
+        .await?
+        .is_none()
+    );
+
+    Ok(())
+  }
+
+  #[sqlx::test(fixtures(path = "fixtures", scripts("course")))]
+  async fn test_course_queries(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+
+    let guild_id = &GuildId::new(123u64);
+    let other_guild_id = &GuildId::new(456u64);
+
+    assert!(
+      DatabaseHandler::course_exists(&mut transaction, guild_id, "Mindfulness Course").await?
+    );
+    assert!(
+      !DatabaseHandler::course_exists(&mut transaction, other_guild_id, "Mindfulness Course")
+        .await?
+    );
+
+    let Some(course) =
+      DatabaseHandler::get_course(&mut transaction, guild_id, "mindfulness course").await?
+    else {
+      panic!("Expected course to be found regardless of case");
+    };
+    assert_eq!(course.name, "Mindfulness Course");
+    assert_eq!(course.participant_role, RoleId::new(111u64));
+    assert_eq!(course.graduate_role, RoleId::new(222u64));
+    assert!(!course.dm_only_completion);
+
+    // Courses can be found by name alone in DMs, where there's no server
+    let Some(course) =
+      DatabaseHandler::get_course_in_dm(&mut transaction, "Compassion Course").await?
+    else {
+      panic!("Expected course to be found in DMs");
+    };
+    assert_eq!(course.guild_id, *guild_id);
+    assert!(course.dm_only_completion);
+
+    let similar =
+      DatabaseHandler::get_possible_course(&mut transaction, guild_id, "Mindfulnes Corse", 0.3)
+        .await?;
+    assert_eq!(
+      similar.map(|course| course.name).as_deref(),
+      Some("Mindfulness Course")
+    );
+    assert!(
+      DatabaseHandler::get_possible_course(&mut transaction, guild_id, "Yoga", 0.3)
+        .await?
+        .is_none()
+    );
+
+    let courses = DatabaseHandler::get_all_courses(&mut transaction, guild_id).await?;
+    assert_eq!(
+      courses
+        .iter()
+        .map(|course| course.name.as_str())
+        .collect::<Vec<_>>(),
+      vec!["Compassion Course", "Mindfulness Course"]
+    );
+    assert!(
+      DatabaseHandler::get_all_courses(&mut transaction, other_guild_id)
+        .await?
+        .is_empty()
+    );
+
+    Ok(())
+  }
+
+  #[sqlx::test]
+  async fn test_get_directory(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+    let guild_id = GuildId::new(123u64);
+
+    let profiles = [
+      TrackingProfile::new(guild_id, UserId::new(123u64))
+        .profile_tradition(Some("Zen".to_owned()))
+        .profile_teacher(Some("Thich Nhat Hanh".to_owned()))
+        .profile_listed(true),
+      TrackingProfile::new(guild_id, UserId::new(124u64))
+        .profile_tradition(Some("Theravada".to_owned()))
+        .profile_listed(true),
+      // Members who haven't opted in aren't listed
+      TrackingProfile::new(guild_id, UserId::new(125u64)).profile_tradition(Some("Zen".to_owned())),
+    ];
+    for profile in &profiles {
+      DatabaseHandler::add_tracking_profile(&mut transaction, profile).await?;
+    }
+
+    let directory = DatabaseHandler::get_directory(&mut transaction, &guild_id, None).await?;
+    assert_eq!(directory.len(), 2);
+    assert_eq!(directory[0].user_id, UserId::new(124u64));
+
+    let directory =
+      DatabaseHandler::get_directory(&mut transaction, &guild_id, Some("nhat")).await?;
+    assert_eq!(directory.len(), 1);
+    assert_eq!(directory[0].profile.tradition.as_deref(), Some("Zen"));
+
+    Ok(())
+  }
+
+  #[sqlx::test]
+  async fn test_dedications(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+    let guild_id = GuildId::new(123u64);
+
+    let dedication = Dedication::new(
+      guild_id,
+      UserId::new(123u64),
+      UserId::new(124u64),
+      "01JBPTWBXJNAKK288S3D89JK7G".to_owned(),
+      10,
+      "For you".to_owned(),
+    );
+    assert_eq!(
+      DatabaseHandler::add_dedication(&mut transaction, &dedication).await?,
+      1
+    );
+
+    // Each session can only be dedicated once
+    let dedication = Dedication::new(
+      guild_id,
+      UserId::new(123u64),
+      UserId::new(125u64),
+      "01JBPTWBXJNAKK288S3D89JK7G".to_owned(),
+      10,
+      "For someone else".to_owned(),
+    );
+    assert_eq!(
+      DatabaseHandler::add_dedication(&mut transaction, &dedication).await?,
+      0
+    );
+
+    let received =
+      DatabaseHandler::get_dedications_received(&mut transaction, &guild_id, &UserId::new(124u64))
+        .await?;
+    assert_eq!(received.len(), 1);
+    assert_eq!(received[0].user_id, UserId::new(123u64));
+    assert_eq!(received[0].message, "For you");
+
+    let given =
+      DatabaseHandler::get_dedications_given(&mut transaction, &guild_id, &UserId::new(123u64))
+        .await?;
+    assert_eq!(given.len(), 1);
+    assert_eq!(given[0].recipient_id, UserId::new(124u64));
+
+    assert!(DatabaseHandler::get_dedications_received(
+      &mut transaction,
+      &GuildId::new(456u64),
+      &UserId::new(124u64)
+    )
+    .await?
+    .is_empty());
+
+    Ok(())
+  }
+
+  #[sqlx::test(fixtures(path = "fixtures", scripts("streak_guard")))]
+  async fn test_streak_guard_reminders(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+
+    // 20:30 for members at UTC+1
+    let Some(now) = DateTime::from_timestamp(1_729_798_200, 0) else {
+      panic!("Expected valid timestamp");
+    };
+
+    // Only members who added time yesterday but not today, and whose hour has passed
+    let reminders = DatabaseHandler::get_streak_guard_reminders(&mut transaction, &now).await?;
+    assert_eq!(reminders.len(), 1);
+    assert_eq!(reminders[0].user_id, UserId::new(123u64));
+    assert_eq!(reminders[0].current_streak, 3);
+    assert_eq!(
+      reminders[0].local_date,
+      NaiveDate::from_ymd_opt(2024, 10, 24).unwrap_or_default()
+    );
+
+    // Only one reminder is sent per day
+    DatabaseHandler::mark_streak_guard_sent(&mut transaction, &reminders[0]).await?;
+    assert!(
+      DatabaseHandler::get_streak_guard_reminders(&mut transaction, &now)
+        .await?
+        .is_empty()
+    );
+
+    Ok(())
+  }
+
+  #[sqlx::test(fixtures(path = "fixtures", scripts("meditation")))]
+  async fn test_get_most_improved(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+    let guild_id = GuildId::new(123u64);
+
+    let Some(previous_start) = DateTime::from_timestamp(1_704_067_200, 0) else {
+      panic!("Expected valid timestamp");
+    };
+    let current_start = previous_start + ChronoDuration::days(1);
+    let end = current_start + ChronoDuration::days(1);
+
+    let improvements = DatabaseHandler::get_most_improved(
+      &mut transaction,
+      &guild_id,
+      &previous_start,
+      &current_start,
+      &end,
+      10,
+    )
+    .await?;
+
+    assert_eq!(improvements.len(), 2);
+    assert_eq!(improvements[0].user_id, UserId::new(124u64));
+    assert_eq!(improvements[0].increase(), 20);
+    assert_eq!(improvements[1].user_id, UserId::new(123u64));
+    assert_eq!(improvements[1].previous_minutes, 10);
+    assert_eq!(improvements[1].current_minutes, 15);
+
+    // Members with private stats aren't included
+    let tracking_profile =
+      TrackingProfile::new(guild_id, UserId::new(124u64)).stats_privacy(Privacy::Private);
+    DatabaseHandler::add_tracking_profile(&mut transaction, &tracking_profile).await?;
+
+    let improvements = DatabaseHandler::get_most_improved(
+      &mut transaction,
+      &guild_id,
+      &previous_start,
+      &current_start,
+      &end,
+      10,
+    )
+    .await?;
+
+    assert_eq!(improvements.len(), 1);
+    assert_eq!(improvements[0].user_id, UserId::new(123u64));
+
+    Ok(())
+  }
+
+  #[sqlx::test]
+  async fn test_mentorships(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+
+    let guild_id = GuildId::new(123u64);
+    let mentor_id = UserId::new(123u64);
+    let mentee_id = UserId::new(124u64);
+    let other_id = UserId::new(125u64);
+    let staff_id = UserId::new(456u64);
+
+    for (user_id, role) in [
+      (mentor_id, MentorRole::Mentor),
+      (mentee_id, MentorRole::Mentee),
+      (other_id, MentorRole::Mentee),
+    ] {
+      let signup = MentorSignup::new(guild_id, user_id, role, "metta".to_owned(), 0);
+      DatabaseHandler::add_mentor_signup(&mut transaction, &signup).await?;
+    }
+
+    // Signing up again updates the existing signup
+    let signup = MentorSignup::new(
+      guild_id,
+      mentor_id,
+      MentorRole::Mentor,
+      "zen".to_owned(),
+      60,
+    );
+    DatabaseHandler::add_mentor_signup(&mut transaction, &signup).await?;
+    let Some(saved) =
+      DatabaseHandler::get_mentor_signup(&mut transaction, &guild_id, &mentor_id).await?
+    else {
+      panic!("Expected the signup to exist");
+    };
+    assert_eq!(saved.role, MentorRole::Mentor);
+    assert_eq!((saved.interests.as_str(), saved.utc_offset), ("zen", 60));
+
+    let mentorship = Mentorship::new(guild_id, mentor_id, mentee_id, staff_id);
+    assert!(DatabaseHandler::add_mentorship(&mut transaction, &mentorship).await?);
+    // Members can only be in one active mentorship
+    let duplicate = Mentorship::new(guild_id, mentor_id, other_id, staff_id);
+    assert!(!DatabaseHandler::add_mentorship(&mut transaction, &duplicate).await?);
+
+    let unmatched =
+      DatabaseHandler::get_unmatched_mentor_signups(&mut transaction, &guild_id).await?;
+    assert_eq!(unmatched.len(), 1);
+    assert_eq!(unmatched[0].user_id, other_id);
+
+    let Some(active) =
+      DatabaseHandler::get_active_mentorship(&mut transaction, &guild_id, &mentee_id).await?
+    else {
+      panic!("Expected the mentorship to be active");
+    };
+    assert_eq!(active.mentor_id, mentor_id);
+
+    // Check-ins are due once the interval has passed without one
+    let later = Utc::now() + ChronoDuration::days(15);
+    let due = DatabaseHandler::get_due_mentorship_check_ins(&mut transaction, &later, 14).await?;
+    assert_eq!(due.len(), 1);
+    DatabaseHandler::mark_mentorship_reminded(&mut transaction, &due[0].id, &later).await?;
+    assert!(
+      DatabaseHandler::get_due_mentorship_check_ins(&mut transaction, &later, 14)
+        .await?
+        .is_empty()
+    );
+
+    assert_eq!(
+      DatabaseHandler::check_in_mentorship(&mut transaction, &guild_id, &mentee_id, &later).await?,
+      1
+    );
+    assert_eq!(
+      DatabaseHandler::check_in_mentorship(&mut transaction, &guild_id, &other_id, &later).await?,
+      0
+    );
+
+    assert_eq!(
+      DatabaseHandler::end_mentorship(&mut transaction, &guild_id, &mentorship.id).await?,
+      1
+    );
+    assert_eq!(
+      DatabaseHandler::end_mentorship(&mut transaction, &guild_id, &mentorship.id).await?,
+      0
+    );
+    assert_eq!(
+      DatabaseHandler::get_unmatched_mentor_signups(&mut transaction, &guild_id)
+        .await?
+        .len(),
+      3
+    );
+
+    assert_eq!(
+      DatabaseHandler::remove_mentor_signup(&mut transaction, &guild_id, &other_id).await?,
+      1
+    );
+    assert!(
+      DatabaseHandler::get_mentor_signup(&mut transaction, &guild_id, &other_id)
+        .await?
+        .is_none()
+    );
+
+    Ok(())
+  }
+
+  #[sqlx::test]
+  async fn test_tracking_profile_redact_minutes(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+
+    let guild_id = GuildId::new(123u64);
+    let user_id = UserId::new(123u64);
+
+    DatabaseHandler::add_tracking_profile(
+      &mut transaction,
+      &TrackingProfile::new(guild_id, user_id).tracking_redacted(true),
+    )
+    .await?;
+
+    let Some(profile) =
+      DatabaseHandler::get_tracking_profile(&mut transaction, &guild_id, &user_id).await?
+    else {
+      panic!("Expected the tracking profile to exist");
+    };
+    assert!(profile.tracking.redact_minutes);
+    // Redacting minutes is separate from anonymous tracking
+    assert_eq!(profile.tracking.privacy, Privacy::Public);
+
+    DatabaseHandler::update_tracking_profile(&mut transaction, &profile.tracking_redacted(false))
+      .await?;
+    let profile = DatabaseHandler::get_tracking_profile(&mut transaction, &guild_id, &user_id)
+      .await?
+      .unwrap_or_default();
+    assert!(!profile.tracking.redact_minutes);
+
+    Ok(())
+  }
+
+  #[sqlx::test(fixtures(path = "fixtures", scripts("meditation", "goal")))]
+  async fn test_goal_progress(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+
+    let Some(goal) =
+      DatabaseHandler::get_active_goal(&mut transaction, &GuildId::new(123u64)).await?
+    else {
+      panic!("Expected an active goal to exist");
+    };
+    assert_eq!(goal.period, GoalPeriod::Weekly);
+    assert_eq!(goal.target_minutes, 100);
+    assert_eq!(goal.channel_id, ChannelId::new(456u64));
+
+    // 10 minutes + 15 minutes 30 seconds + 20 minutes
+    assert_eq!(
+      DatabaseHandler::get_goal_progress(&mut transaction, &goal).await?,
+      45
+    );
+
+    // Closed goals are not active
+    assert!(
+      DatabaseHandler::get_active_goal(&mut transaction, &GuildId::new(456u64))
+        .await?
+        .is_none()
+    );
+    assert_eq!(
+      DatabaseHandler::get_active_goals(&mut transaction)
+        .await?
+        .len(),
+      1
+    );
+
+    Ok(())
+  }
+
+  #[sqlx::test(fixtures(path = "fixtures", scripts("goal")))]
+  async fn test_complete_goal(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+    let guild_id = GuildId::new(123u64);
+    let now = Utc::now();
+
+    // A goal can only be completed once
+    assert_eq!(
+      DatabaseHandler::complete_goal(&mut transaction, "01JBPTWBXJNAKK288S3D89JK8A", &now).await?,
+      1
+    );
+    assert_eq!(
+      DatabaseHandler::complete_goal(&mut transaction, "01JBPTWBXJNAKK288S3D89JK8A", &now).await?,
+      0
+    );
+    assert_eq!(
+      DatabaseHandler::close_goal(&mut transaction, &guild_id, "01JBPTWBXJNAKK288S3D89JK8A")
+        .await?,
+      0
+    );
+    assert!(
+      DatabaseHandler::get_active_goal(&mut transaction, &guild_id)
+        .await?
+        .is_none()
+    );
+
+    // Only one goal can be active at a time
+    let Some(goal) = Goal::new(
+      guild_id,
+      ChannelId::new(456u64),
+      GoalPeriod::Monthly,
+      1000,
+      now,
+      UserId::new(123u64),
+    ) else {
+      panic!("Expected goal period to be valid");
+    };
+    DatabaseHandler::add_goal(&mut transaction, &goal).await?;
+    DatabaseHandler::mark_goal_updated(&mut transaction, &goal.id, &now).await?;
+
+    let Some(active) = DatabaseHandler::get_active_goal(&mut transaction, &guild_id).await? else {
+      panic!("Expected new goal to be active");
+    };
+    assert_eq!(active.id, goal.id);
+    assert!(active.last_update_at.is_some());
+
+    let Some(duplicate) = Goal::new(
+      guild_id,
+      ChannelId::new(456u64),
+      GoalPeriod::Weekly,
+      100,
+      now,
+      UserId::new(123u64),
+    ) else {
+      panic!("Expected goal period to be valid");
+    };
+    assert!(DatabaseHandler::add_goal(&mut transaction, &duplicate)
+      .await
+      .is_err());
+
+    Ok(())
+  }
+
+  #[sqlx::test(fixtures(path = "fixtures", scripts("community_event")))]
+  async fn test_community_events(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+    let guild_id = GuildId::new(123u64);
+
+    // Titles are matched ignoring case
+    let Some(event) =
+      DatabaseHandler::get_community_event(&mut transaction, &guild_id, "book club").await?
+    else {
+      panic!("Expected an event called Book Club to exist");
+    };
+    assert_eq!(event.id, "01JBPTWBXJNAKK288S3D89JK9A");
+    assert_eq!(event.repeat_days, Some(7));
+    assert_eq!(event.description.as_deref(), Some("Reading together"));
+    assert!(!event.transcribe);
+
+    assert_eq!(
+      DatabaseHandler::get_community_event_titles(&mut transaction, &guild_id, "").await?,
+      vec!["Book Club", "Monthly Q&A"]
+    );
+    assert_eq!(
+      DatabaseHandler::get_community_event_titles(&mut transaction, &guild_id, "q&").await?,
+      vec!["Monthly Q&A"]
+    );
+
+    let events = DatabaseHandler::get_community_events_in_channel(
+      &mut transaction,
+      &guild_id,
+      &ChannelId::new(456u64),
+    )
+    .await?;
+    assert_eq!(events.len(), 1);
+
+    let third = DateTime::parse_from_rfc3339("2024-10-15T18:00:00Z")?.with_timezone(&Utc);
+    let user_id = UserId::new(124u64);
+    assert_eq!(
+      DatabaseHandler::add_event_rsvp(&mut transaction, &event.id, &user_id, &third).await?,
+      1
+    );
+    assert_eq!(
+      DatabaseHandler::add_event_rsvp(&mut transaction, &event.id, &user_id, &third).await?,
+      0
+    );
+    assert_eq!(
+      DatabaseHandler::get_event_rsvp_count(&mut transaction, &event.id, &third).await?,
+      1
+    );
+    assert_eq!(
+      DatabaseHandler::remove_event_rsvp(&mut transaction, &event.id, &user_id, &third).await?,
+      1
+    );
+    assert_eq!(
+      DatabaseHandler::get_event_rsvp_count(&mut transaction, &event.id, &third).await?,
+      0
+    );
+
+    let first = DateTime::parse_from_rfc3339("2024-10-01T18:00:00Z")?.with_timezone(&Utc);
+    assert_eq!(
+      DatabaseHandler::add_event_attendance(
+        &mut transaction,
+        &event.id,
+        &UserId::new(123u64),
+        &first,
+        AttendanceSource::Message
+      )
+      .await?,
+      0
+    );
+    assert_eq!(
+      DatabaseHandler::add_event_attendance(
+        &mut transaction,
+        &event.id,
+        &user_id,
+        &first,
+        AttendanceSource::Voice
+      )
+      .await?,
+      1
+    );
+
+    let stats = DatabaseHandler::get_event_occurrence_stats(&mut transaction, &event.id).await?;
+    assert_eq!(stats.len(), 2);
+    assert_eq!(
+      stats[0].occurrence,
+      DateTime::parse_from_rfc3339("2024-10-08T18:00:00Z")?.with_timezone(&Utc)
+    );
+    assert_eq!(
+      (stats[0].rsvps, stats[0].attended, stats[0].rsvps_attended),
+      (1, 1, 0)
+    );
+    assert_eq!(stats[1].occurrence, first);
+    assert_eq!(
+      (stats[1].rsvps, stats[1].attended, stats[1].rsvps_attended),
+      (2, 3, 2)
+    );
+
+    assert_eq!(
+      DatabaseHandler::get_event_unique_attendees(&mut transaction, &event.id).await?,
+      3
+    );
+
+    DatabaseHandler::set_event_transcription(&mut transaction, &event.id, true).await?;
+    assert!(
+      DatabaseHandler::get_community_event(&mut transaction, &guild_id, "Book Club")
+        .await?
+        .is_some_and(|event| event.transcribe)
+    );
+
+    Ok(())
+  }
+
+  #[sqlx::test]
+  async fn test_polls(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+    let guild_id = GuildId::new(123u64);
+    let now = Utc::now();
+
+    let poll = Poll::new(
+      guild_id,
+      ChannelId::new(456u64),
+      "Next month's theme?".to_owned(),
+      vec!["Metta".to_owned(), "Body scan".to_owned()],
+      false,
+      true,
+      Some(now + ChronoDuration::hours(1)),
+      UserId::new(789u64),
+    );
+    DatabaseHandler::add_poll(&mut transaction, &poll).await?;
+    DatabaseHandler::set_poll_message(&mut transaction, &poll.id, &MessageId::new(321u64)).await?;
+
+    let Some(saved) = DatabaseHandler::get_poll(&mut transaction, &poll.id).await? else {
+      panic!("Expected the poll to exist");
+    };
+    assert_eq!(saved.choices, vec!["Metta", "Body scan"]);
+    assert_eq!(saved.message_id, Some(MessageId::new(321u64)));
+    assert!(saved.anonymous);
+    assert!(!saved.is_closed(now));
+
+    let vote = PollVote {
+      user_id: UserId::new(1u64),
+      choice: 0,
+    };
+    assert_eq!(
+      DatabaseHandler::add_poll_vote(&mut transaction, &poll.id, vote).await?,
+      1
+    );
+    assert_eq!(
+      DatabaseHandler::add_poll_vote(&mut transaction, &poll.id, vote).await?,
+      0
+    );
+    DatabaseHandler::add_poll_vote(
+      &mut transaction,
+      &poll.id,
+      PollVote {
+        user_id: UserId::new(2u64),
+        choice: 1,
+      },
+    )
+    .await?;
+
+    let votes = DatabaseHandler::get_poll_votes(&mut transaction, &poll.id).await?;
+    assert_eq!(saved.tally(&votes), vec![1, 1]);
+
+    assert_eq!(
+      DatabaseHandler::clear_poll_votes(&mut transaction, &poll.id, &UserId::new(2u64)).await?,
+      1
+    );
+    assert_eq!(
+      DatabaseHandler::remove_poll_vote(&mut transaction, &poll.id, vote).await?,
+      1
+    );
+    assert!(DatabaseHandler::get_poll_votes(&mut transaction, &poll.id)
+      .await?
+      .is_empty());
+
+    assert!(DatabaseHandler::get_expired_polls(&mut transaction, &now)
+      .await?
+      .is_empty());
+    let later = now + ChronoDuration::hours(2);
+    assert_eq!(
+      DatabaseHandler::get_expired_polls(&mut transaction, &later)
+        .await?
+        .len(),
+      1
+    );
+
+    assert_eq!(
+      DatabaseHandler::close_poll(&mut transaction, &GuildId::new(456u64), &poll.id).await?,
+      0
+    );
+    assert_eq!(
+      DatabaseHandler::close_poll(&mut transaction, &guild_id, &poll.id).await?,
+      1
+    );
+    assert_eq!(
+      DatabaseHandler::close_poll(&mut transaction, &guild_id, &poll.id).await?,
+      0
+    );
+    assert!(DatabaseHandler::get_expired_polls(&mut transaction, &later)
+      .await?
+      .is_empty());
+
+    Ok(())
+  }
+
+  #[sqlx::test(fixtures(path = "fixtures", scripts("meditation")))]
+  async fn test_mood_days(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+    let guild_id = GuildId::new(123u64);
+    let user_id = UserId::new(123u64);
+    let date = |day| NaiveDate::from_ymd_opt(2024, 1, day).unwrap_or_default();
+
+    let checkin = MoodCheckin::new(guild_id, user_id, date(2), Mood::Calm, None);
+    DatabaseHandler::add_mood_checkin(&mut transaction, &checkin).await?;
+    // Checking in again on the same day replaces the earlier check-in
+    let checkin = MoodCheckin::new(
+      guild_id,
+      user_id,
+      date(2),
+      Mood::VeryCalm,
+      Some("Slept well".to_owned()),
+    );
+    DatabaseHandler::add_mood_checkin(&mut transaction, &checkin).await?;
+
+    let days =
+      DatabaseHandler::get_mood_days(&mut transaction, &guild_id, &user_id, date(3), 3, 0).await?;
+    assert_eq!(
+      days
+        .iter()
+        .map(|day| (day.date, day.mood, day.minutes))
+        .collect::<Vec<_>>(),
+      vec![
+        (date(1), None, 10),
+        (date(2), Some(5), 15),
+        (date(3), None, 0)
+      ]
+    );
+
+    // Minutes are counted on the day they were recorded in the member's UTC offset
+    let days =
+      DatabaseHandler::get_mood_days(&mut transaction, &guild_id, &user_id, date(3), 3, -60)
+        .await?;
+    assert_eq!(
+      days.iter().map(|day| day.minutes).collect::<Vec<_>>(),
+      vec![15, 0, 0]
+    );
+
+    Ok(())
+  }
+
+  #[sqlx::test(fixtures(path = "fixtures", scripts("meditation")))]
+  async fn test_sleep_days(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+    let guild_id = GuildId::new(123u64);
+    let user_id = UserId::new(123u64);
+    let date = |day| NaiveDate::from_ymd_opt(2024, 1, day).unwrap_or_default();
+
+    let metric = WellnessMetric::new(guild_id, user_id, date(1), 6.0);
+    DatabaseHandler::add_wellness_metric(&mut transaction, &metric).await?;
+    // Logging again for the same day replaces the earlier hours
+    let metric = WellnessMetric::new(guild_id, user_id, date(1), 7.5);
+    DatabaseHandler::add_wellness_metric(&mut transaction, &metric).await?;
+    // Sleep logged by other members isn't included
+    let metric = WellnessMetric::new(guild_id, UserId::new(456u64), date(2), 9.0);
+    DatabaseHandler::add_wellness_metric(&mut transaction, &metric).await?;
+
+    let days =
+      DatabaseHandler::get_sleep_days(&mut transaction, &guild_id, &user_id, date(3), 3, 0).await?;
+    assert_eq!(
+      days
+        .iter()
+        .map(|day| (day.date, day.hours, day.minutes))
+        .collect::<Vec<_>>(),
+      vec![
+        (date(1), Some(7.5), 10),
+        (date(2), None, 15),
+        (date(3), None, 0)
+      ]
+    );
+
+    Ok(())
+  }
+
+  #[sqlx::test]
+  async fn test_recurring_posts(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+    let guild_id = GuildId::new(123u64);
+
+    let post = RecurringPost::new(
+      guild_id,
+      ChannelId::new(456u64),
+      "Weekly check-in".to_owned(),
+      "How was your practice this week?".to_owned(),
+      Some("Check-in for {date}".to_owned()),
+      PostDay::Friday,
+      NaiveTime::from_hms_opt(18, 0, 0).unwrap_or_default(),
+      60,
+      UserId::new(789u64),
+    );
+    DatabaseHandler::add_recurring_post(&mut transaction, &post).await?;
+
+    let posts = DatabaseHandler::get_recurring_posts(&mut transaction, &guild_id).await?;
+    assert_eq!(posts.len(), 1);
+    assert_eq!(posts[0].weekday, Some(chrono::Weekday::Fri));
+    assert_eq!(posts[0].minute_of_day, 18 * 60);
+    assert_eq!(posts[0].thread_name.as_deref(), Some("Check-in for {date}"));
+
+    let due = post.next_post_at;
+    assert!(DatabaseHandler::get_due_recurring_posts(
+      &mut transaction,
+      &(due - ChronoDuration::minutes(1))
+    )
+    .await?
+    .is_empty());
+    assert_eq!(
+      DatabaseHandler::get_due_recurring_posts(&mut transaction, &due)
+        .await?
+        .len(),
+      1
+    );
+
+    // Each time can only be claimed once
+    let next = post.next_after(due);
+    assert_eq!(
+      DatabaseHandler::advance_recurring_post(&mut transaction, &post.id, &due, &next).await?,
+      1
+    );
+    assert_eq!(
+      DatabaseHandler::advance_recurring_post(&mut transaction, &post.id, &due, &next).await?,
+      0
+    );
+
+    assert_eq!(
+      DatabaseHandler::remove_recurring_post(&mut transaction, &guild_id, "WEEKLY CHECK-IN")
+        .await?,
+      1
+    );
+    assert!(
+      DatabaseHandler::get_recurring_posts(&mut transaction, &guild_id)
+        .await?
+        .is_empty()
+    );
+
+    Ok(())
+  }
+
+  #[sqlx::test]
+  async fn test_study_groups(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+    let guild_id = GuildId::new(123u64);
+    let user_id = UserId::new(789u64);
+
+    let study_group = StudyGroup::new(
+      guild_id,
+      "Sutta Circle".to_owned(),
+      "Satipatthana Sutta".to_owned(),
+      ChannelId::new(456u64),
+      UserId::new(100u64),
+    );
+    DatabaseHandler::add_study_group(&mut transaction, &study_group).await?;
+
+    let member = StudyGroupMember::new(&study_group.id, user_id);
+    assert!(DatabaseHandler::add_study_group_member(&mut transaction, &member).await?);
+    assert!(!DatabaseHandler::add_study_group_member(&mut transaction, &member).await?);
+
+    let week = NaiveDate::from_ymd_opt(2024, 11, 18).unwrap_or_default();
+    assert_eq!(
+      DatabaseHandler::update_study_group_progress(
+        &mut transaction,
+        &study_group.id,
+        &user_id,
+        "Section 3",
+        week
+      )
+      .await?,
+      1
+    );
+    assert_eq!(
+      DatabaseHandler::update_study_group_progress(
+        &mut transaction,
+        &study_group.id,
+        &UserId::new(790u64),
+        "Section 1",
+        week
+      )
+      .await?,
+      0
+    );
+
+    let members =
+      DatabaseHandler::get_study_group_members(&mut transaction, &study_group.id).await?;
+    assert_eq!(members.len(), 1);
+    assert_eq!(members[0].progress.as_deref(), Some("Section 3"));
+    assert_eq!(members[0].progress_week, Some(week));
+
+    let post = RecurringPost::new(
+      guild_id,
+      ChannelId::new(456u64),
+      "Sutta Circle discussion".to_owned(),
+      "What stood out to you this week?".to_owned(),
+      None,
+      PostDay::Sunday,
+      NaiveTime::from_hms_opt(18, 0, 0).unwrap_or_default(),
+      0,
+      UserId::new(100u64),
+    );
+    DatabaseHandler::set_study_group_prompts(&mut transaction, &study_group, &post).await?;
+
+    let Some(retrieved) =
+      DatabaseHandler::get_study_group(&mut transaction, &guild_id, "sutta circle").await?
+    else {
+      panic!("Expected study group to exist");
+    };
+    assert_eq!(retrieved.member_count, 1);
+    assert_eq!(
+      retrieved.recurring_post_id.as_deref(),
+      Some(post.id.as_str())
+    );
+    assert_eq!(retrieved.next_prompt_at, Some(post.next_post_at));
+
+    // Removing a group removes its members and discussion prompts
+    assert_eq!(
+      DatabaseHandler::remove_study_group(&mut transaction, &guild_id, "SUTTA CIRCLE").await?,
+      1
+    );
+    assert!(
+      DatabaseHandler::get_study_groups(&mut transaction, &guild_id)
+        .await?
+        .is_empty()
+    );
+    assert!(
+      DatabaseHandler::get_study_group_members(&mut transaction, &study_group.id)
+        .await?
+        .is_empty()
+    );
+    assert!(
+      DatabaseHandler::get_recurring_posts(&mut transaction, &guild_id)
+        .await?
+        .is_empty()
+    );
+
+    Ok(())
+  }
+
+  #[sqlx::test]
+  async fn test_feed_subscriptions(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+    let guild_id = GuildId::new(123u64);
+
+    let subscription = FeedSubscription::new(
+      guild_id,
+      ChannelId::new(456u64),
+      "https://example.com/talks.rss".to_owned(),
+      "Dharma Talks".to_owned(),
+      UserId::new(789u64),
+    );
+    DatabaseHandler::add_feed_subscription(&mut transaction, &subscription).await?;
+
+    let subscriptions =
+      DatabaseHandler::get_feed_subscriptions(&mut transaction, &guild_id).await?;
+    assert_eq!(subscriptions.len(), 1);
+    assert_eq!(subscriptions[0].title, "Dharma Talks");
+    assert_eq!(
+      DatabaseHandler::get_all_feed_subscriptions(&mut transaction)
+        .await?
+        .len(),
+      1
+    );
+
+    // Each item is only new once
+    assert!(DatabaseHandler::mark_feed_item_seen(&mut transaction, &subscription, "talk-1").await?);
+    assert!(
+      !DatabaseHandler::mark_feed_item_seen(&mut transaction, &subscription, "talk-1").await?
+    );
+    assert!(DatabaseHandler::mark_feed_item_seen(&mut transaction, &subscription, "talk-2").await?);
+
+    assert_eq!(
+      DatabaseHandler::remove_feed_subscription(&mut transaction, &guild_id, &subscription.id)
+        .await?,
+      1
+    );
+    assert!(
+      DatabaseHandler::get_feed_subscriptions(&mut transaction, &guild_id)
+        .await?
+        .is_empty()
+    );
+
+    Ok(())
+  }
+
+  #[sqlx::test]
+  async fn test_talks(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+    let guild_id = GuildId::new(123u64);
+
+    let talk = Talk::new(
+      guild_id,
+      "Body Scan".to_owned(),
+      "https://example.com/body-scan.mp3".to_owned(),
+      UserId::new(456u64),
+    );
+    DatabaseHandler::add_talk(&mut transaction, &talk).await?;
+    DatabaseHandler::add_talk(
+      &mut transaction,
+      &Talk::new(
+        guild_id,
+        "anapanasati".to_owned(),
+        "https://example.com/anapanasati.mp3".to_owned(),
+        UserId::new(456u64),
+      ),
+    )
+    .await?;
+
+    let found = DatabaseHandler::get_talk(&mut transaction, &guild_id, "body scan").await?;
+    assert_eq!(
+      found.map(|talk| talk.url).as_deref(),
+      Some("https://example.com/body-scan.mp3")
+    );
+    assert!(
+      DatabaseHandler::get_talk(&mut transaction, &GuildId::new(999u64), "Body Scan")
+        .await?
+        .is_none()
+    );
+
+    let talks = DatabaseHandler::get_talks(&mut transaction, &guild_id).await?;
+    assert_eq!(
+      talks
+        .iter()
+        .map(|talk| talk.name.as_str())
+        .collect::<Vec<_>>(),
+      vec!["anapanasati", "Body Scan"]
+    );
+
+    assert_eq!(
+      DatabaseHandler::remove_talk(&mut transaction, &guild_id, "BODY SCAN").await?,
+      1
+    );
+    assert_eq!(
+      DatabaseHandler::remove_talk(&mut transaction, &guild_id, "Body Scan").await?,
+      0
+    );
+    assert_eq!(
+      DatabaseHandler::get_talks(&mut transaction, &guild_id)
+        .await?
+        .len(),
+      1
+    );
+
+    Ok(())
+  }
+
+  #[sqlx::test]
+  async fn test_sutta_cache(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+
+    assert!(
+      DatabaseHandler::get_cached_sutta(&mut transaction, "mn10", "sujato")
+        .await?
+        .is_none()
+    );
+
+    let passage = SuttaPassage::new(
+      "mn10".to_owned(),
+      "sujato".to_owned(),
+      "Bhikkhu Sujato".to_owned(),
+      "en".to_owned(),
+      "Satipaṭṭhānasutta".to_owned(),
+      None,
+      "So I have heard.".to_owned(),
+    );
+    DatabaseHandler::cache_sutta(&mut transaction, &passage).await?;
+
+    // Caching the same translation again replaces it
+    let refreshed = SuttaPassage::new(
+      "mn10".to_owned(),
+      "sujato".to_owned(),
+      "Bhikkhu Sujato".to_owned(),
+      "en".to_owned(),
+      "Satipaṭṭhānasutta".to_owned(),
+      Some("Mindfulness Meditation".to_owned()),
+      "So I have heard. At one time the Buddha was staying in the land of the Kurus.".to_owned(),
+    );
+    DatabaseHandler::cache_sutta(&mut transaction, &refreshed).await?;
+
+    let Some(cached) =
+      DatabaseHandler::get_cached_sutta(&mut transaction, "mn10", "sujato").await?
+    else {
+      panic!("Expected the sutta to be cached");
+    };
+    assert_eq!(cached.id, passage.id);
+    assert_eq!(
+      cached.translated_title.as_deref(),
+      Some("Mindfulness Meditation")
+    );
+    assert!(cached.passage.ends_with("land of the Kurus."));
+    assert_eq!(cached.url(), "https://suttacentral.net/mn10/en/sujato");
+
+    assert!(
+      DatabaseHandler::get_cached_sutta(&mut transaction, "mn10", "bodhi")
+        .await?
+        .is_none()
+    );
+
+    Ok(())
+  }
+
+  #[sqlx::test]
+  async fn test_practice_prompts(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+    let guild_id = GuildId::new(123u64);
+    let staff_id = UserId::new(789u64);
+
+    let breath = PracticePrompt::new(
+      guild_id,
+      PromptCategory::Mindfulness,
+      "Notice three breaths before each meal.".to_owned(),
+      staff_id,
+    );
+    let thanks = PracticePrompt::new(
+      guild_id,
+      PromptCategory::Gratitude,
+      "Thank someone you usually overlook.".to_owned(),
+      staff_id,
+    );
+    DatabaseHandler::add_practice_prompt(&mut transaction, &breath).await?;
+    DatabaseHandler::add_practice_prompt(&mut transaction, &thanks).await?;
+
+    assert_eq!(
+      DatabaseHandler::get_practice_prompts(&mut transaction, &guild_id, None)
+        .await?
+        .len(),
+      2
+    );
+    let gratitude = DatabaseHandler::get_practice_prompts(
+      &mut transaction,
+      &guild_id,
+      Some(PromptCategory::Gratitude),
+    )
+    .await?;
+    assert_eq!(gratitude.len(), 1);
+    assert_eq!(gratitude[0].prompt, "Thank someone you usually overlook.");
+
+    assert_eq!(
+      DatabaseHandler::remove_practice_prompt(&mut transaction, &guild_id, &thanks.id).await?,
+      1
+    );
+    assert_eq!(
+      DatabaseHandler::remove_practice_prompt(&mut transaction, &guild_id, &thanks.id).await?,
+      0
+    );
+
+    Ok(())
+  }
+
+  #[sqlx::test]
+  async fn test_intention_reminders(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+    let guild_id = GuildId::new(123u64);
+
+    // 08:30 UTC
+    let Some(now) = DateTime::from_timestamp(1_729_758_600, 0) else {
+      panic!("Expected valid timestamp");
+    };
+
+    // Due at 07:00 UTC, 09:00 at UTC+1, and 09:00 UTC, which hasn't come yet
+    for (user_id, hour) in [(1u64, 7), (2u64, 9), (3u64, 9)] {
+      let subscription = IntentionSubscription::new(guild_id, UserId::new(user_id), hour);
+      DatabaseHandler::add_intention_subscription(&mut transaction, &subscription).await?;
+    }
+    let profile = TrackingProfile::new(guild_id, UserId::new(2u64)).utc_offset(60);
+    DatabaseHandler::add_tracking_profile(&mut transaction, &profile).await?;
+
+    let mut reminders = DatabaseHandler::get_intention_reminders(&mut transaction, &now).await?;
+    reminders.sort_by_key(|reminder| reminder.user_id);
+    assert_eq!(
+      reminders
+        .iter()
+        .map(|reminder| reminder.user_id)
+        .collect::<Vec<_>>(),
+      vec![UserId::new(1u64), UserId::new(2u64)]
+    );
+    assert_eq!(
+      reminders[0].local_date,
+      NaiveDate::from_ymd_opt(2024, 10, 24).unwrap_or_default()
+    );
+
+    // Only one reminder is sent per day
+    for reminder in &reminders {
+      DatabaseHandler::mark_intention_reminder_sent(&mut transaction, reminder).await?;
+    }
+    assert!(
+      DatabaseHandler::get_intention_reminders(&mut transaction, &now)
+        .await?
+        .is_empty()
+    );
+
+    // Changing the hour updates the existing subscription
+    let later = IntentionSubscription::new(guild_id, UserId::new(1u64), 10);
+    DatabaseHandler::add_intention_subscription(&mut transaction, &later).await?;
+    let Some(subscription) =
+      DatabaseHandler::get_intention_subscription(&mut transaction, &guild_id, &UserId::new(1u64))
+        .await?
+    else {
+      panic!("Expected the subscription to exist");
+    };
+    assert_eq!(subscription.reminder_hour, 10);
+
+    assert_eq!(
+      DatabaseHandler::remove_intention_subscription(
+        &mut transaction,
+        &guild_id,
+        &UserId::new(1u64)
+      )
+      .await?,
+      1
+    );
+
+    Ok(())
+  }
+
+  #[sqlx::test]
+  async fn test_scheduled_announcements(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+    let guild_id = GuildId::new(123u64);
+    let now = Utc::now();
+
+    let announcement = |hours| {
+      ScheduledAnnouncement::new(
+        guild_id,
+        ChannelId::new(456u64),
+        AnnouncementModal {
+          title: " Retreat day ".to_owned(),
+          message: "Join us on Saturday.".to_owned(),
+        },
+        now + ChronoDuration::hours(hours),
+        UserId::new(789u64),
+      )
+    };
+    let later = announcement(2);
+    let sooner = announcement(1);
+    DatabaseHandler::add_scheduled_announcement(&mut transaction, &later).await?;
+    DatabaseHandler::add_scheduled_announcement(&mut transaction, &sooner).await?;
+
+    let pending = DatabaseHandler::get_pending_announcements(&mut transaction, &guild_id).await?;
+    assert_eq!(
+      pending.iter().map(|a| a.id.as_str()).collect::<Vec<_>>(),
+      vec![sooner.id.as_str(), later.id.as_str()]
+    );
+    assert_eq!(pending[0].title, "Retreat day");
+
+    assert!(
+      DatabaseHandler::get_due_announcements(&mut transaction, &now)
+        .await?
+        .is_empty()
+    );
+    let due = DatabaseHandler::get_due_announcements(
+      &mut transaction,
+      &(now + ChronoDuration::minutes(90)),
+    )
+    .await?;
+    assert_eq!(due.len(), 1);
+
+    // Announcements can only be claimed for posting once
+    assert_eq!(
+      DatabaseHandler::mark_announcement_sent(&mut transaction, &sooner.id).await?,
+      1
+    );
+    assert_eq!(
+      DatabaseHandler::mark_announcement_sent(&mut transaction, &sooner.id).await?,
+      0
+    );
+
+    // Posted announcements, and other servers' announcements, can't be cancelled
+    assert_eq!(
+      DatabaseHandler::remove_scheduled_announcement(&mut transaction, &guild_id, &sooner.id)
+        .await?,
+      0
+    );
+    assert_eq!(
+      DatabaseHandler::remove_scheduled_announcement(
+        &mut transaction,
+        &GuildId::new(456u64),
+        &later.id
+      )
+      .await?,
+      0
+    );
+    assert_eq!(
+      DatabaseHandler::remove_scheduled_announcement(&mut transaction, &guild_id, &later.id)
+        .await?,
+      1
+    );
+    assert!(
+      DatabaseHandler::get_pending_announcements(&mut transaction, &guild_id)
+        .await?
+        .is_empty()
+    );
+
+    Ok(())
+  }
+
+  #[sqlx::test]
+  async fn test_suggestions(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+    let guild_id = GuildId::new(123u64);
+    let staff_id = UserId::new(999u64);
+
+    let suggestion = Suggestion::new(
+      guild_id,
+      ChannelId::new(456u64),
+      UserId::new(789u64),
+      "Add a weekly group sit".to_owned(),
+    );
+    DatabaseHandler::add_suggestion(&mut transaction, &suggestion).await?;
+    DatabaseHandler::set_suggestion_message(
+      &mut transaction,
+      &suggestion.id,
+      &MessageId::new(321u64),
+    )
+    .await?;
+
+    let Some(saved) =
+      DatabaseHandler::get_suggestion(&mut transaction, &guild_id, &suggestion.id).await?
+    else {
+      panic!("Expected the suggestion to exist");
+    };
+    assert_eq!(saved.status, SuggestionStatus::Open);
+    assert_eq!(saved.message_id, Some(MessageId::new(321u64)));
+    assert!(DatabaseHandler::get_suggestion(
+      &mut transaction,
+      &GuildId::new(456u64),
+      &suggestion.id
+    )
+    .await?
+    .is_none());
+
+    let voter = UserId::new(1u64);
+    DatabaseHandler::set_suggestion_vote(&mut transaction, &suggestion.id, &voter, true).await?;
+    DatabaseHandler::set_suggestion_vote(
+      &mut transaction,
+      &suggestion.id,
+      &UserId::new(2u64),
+      true,
+    )
+    .await?;
+    DatabaseHandler::set_suggestion_vote(&mut transaction, &suggestion.id, &voter, false).await?;
+    assert_eq!(
+      DatabaseHandler::get_suggestion_vote(&mut transaction, &suggestion.id, &voter).await?,
+      Some(false)
+    );
+    assert_eq!(
+      DatabaseHandler::get_suggestion_votes(&mut transaction, &suggestion.id).await?,
+      SuggestionVotes {
+        upvotes: 1,
+        downvotes: 1,
+      }
+    );
+    assert_eq!(
+      DatabaseHandler::remove_suggestion_vote(&mut transaction, &suggestion.id, &voter).await?,
+      1
+    );
+    assert_eq!(
+      DatabaseHandler::get_suggestion_vote(&mut transaction, &suggestion.id, &voter).await?,
+      None
+    );
+
+    assert_eq!(
+      DatabaseHandler::update_suggestion_status(
+        &mut transaction,
+        &guild_id,
+        "missing",
+        SuggestionStatus::Done,
+        None,
+        &staff_id,
+      )
+      .await?,
+      0
+    );
+    assert_eq!(
+      DatabaseHandler::update_suggestion_status(
+        &mut transaction,
+        &guild_id,
+        &suggestion.id,
+        SuggestionStatus::Planned,
+        Some("Starting next month"),
+        &staff_id,
+      )
+      .await?,
+      1
+    );
+
+    let Some(updated) =
+      DatabaseHandler::get_suggestion(&mut transaction, &guild_id, &suggestion.id).await?
+    else {
+      panic!("Expected the suggestion to exist");
+    };
+    assert_eq!(updated.status, SuggestionStatus::Planned);
+    assert_eq!(updated.status_note.as_deref(), Some("Starting next month"));
+    assert_eq!(updated.status_updated_by, Some(staff_id));
+
+    assert_eq!(
+      DatabaseHandler::get_suggestions_by_status(
+        &mut transaction,
+        &guild_id,
+        SuggestionStatus::Planned
+      )
+      .await?
+      .len(),
+      1
+    );
+    assert!(DatabaseHandler::get_suggestions_by_status(
+      &mut transaction,
+      &guild_id,
+      SuggestionStatus::Open
+    )
+    .await?
+    .is_empty());
+
+    Ok(())
+  }
+
+  #[sqlx::test]
+  async fn test_watchlist_and_reports(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+    let guild_id = GuildId::new(123u64);
+    let moderator = UserId::new(999u64);
+
+    let term = WatchlistTerm::new(guild_id, "spoiler".to_owned(), false, moderator);
+    assert_eq!(
+      DatabaseHandler::add_watchlist_term(&mut transaction, &term).await?,
+      1
+    );
+    let duplicate = WatchlistTerm::new(guild_id, "spoiler".to_owned(), false, moderator);
+    assert_eq!(
+      DatabaseHandler::add_watchlist_term(&mut transaction, &duplicate).await?,
+      0
+    );
+    let pattern = WatchlistTerm::new(guild_id, r"free\s+nitro".to_owned(), true, moderator);
+    DatabaseHandler::add_watchlist_term(&mut transaction, &pattern).await?;
+
+    let terms = DatabaseHandler::get_watchlist(&mut transaction, &guild_id).await?;
+    assert_eq!(terms.len(), 2);
+    assert!(
+      DatabaseHandler::get_watchlist(&mut transaction, &GuildId::new(456u64))
+        .await?
+        .is_empty()
+    );
+
+    assert_eq!(
+      DatabaseHandler::remove_watchlist_term(&mut transaction, &guild_id, "spoiler").await?,
+      1
+    );
+    assert_eq!(
+      DatabaseHandler::remove_watchlist_term(&mut transaction, &guild_id, "spoiler").await?,
+      0
+    );
+
+    let report = Report::new(
+      guild_id,
+      ChannelId::new(456u64),
+      MessageId::new(321u64),
+      UserId::new(789u64),
+      ReportSource::Watchlist,
+      "Get free  nitro here".to_owned(),
+    )
+    .matched_term(pattern.pattern.clone());
+    DatabaseHandler::add_report(&mut transaction, &report).await?;
+
+    let Some(saved) = DatabaseHandler::get_report(&mut transaction, &report.id).await? else {
+      panic!("Expected the report to exist");
+    };
+    assert_eq!(saved.source, ReportSource::Watchlist);
+    assert_eq!(saved.status, ReportStatus::Open);
+    assert_eq!(saved.matched_term, Some(pattern.pattern.clone()));
+    assert_eq!(saved.reported_by, None);
+
+    assert_eq!(
+      DatabaseHandler::set_report_status(
+        &mut transaction,
+        &report.id,
+        ReportStatus::Erased,
+        &moderator
+      )
+      .await?,
+      1
+    );
+    assert_eq!(
+      DatabaseHandler::set_report_status(
+        &mut transaction,
+        &report.id,
+        ReportStatus::Ignored,
+        &moderator
+      )
+      .await?,
+      0
+    );
+
+    let Some(handled) = DatabaseHandler::get_report(&mut transaction, &report.id).await? else {
+      panic!("Expected the report to exist");
+    };
+    assert_eq!(handled.status, ReportStatus::Erased);
+    assert_eq!(handled.handled_by, Some(moderator));
+
+    Ok(())
+  }
+
+  #[sqlx::test]
+  async fn test_warnings(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+    let guild_id = GuildId::new(123u64);
+    let user_id = UserId::new(789u64);
+    let moderator = UserId::new(999u64);
+
+    let first = Warning::new(guild_id, user_id, moderator, "Be kind".to_owned());
+    DatabaseHandler::add_warning(&mut transaction, &first).await?;
+    let timeout_until = Utc::now() + ChronoDuration::hours(1);
+    let second = Warning::new(guild_id, user_id, moderator, "Be respectful".to_owned())
+      .timeout_until(timeout_until)
+      .message_link("https://discord.com/channels/123/456/321".to_owned());
+    DatabaseHandler::add_warning(&mut transaction, &second).await?;
+    let other_guild = Warning::new(
+      GuildId::new(456u64),
+      user_id,
+      moderator,
+      "Be kind".to_owned(),
+    );
+    DatabaseHandler::add_warning(&mut transaction, &other_guild).await?;
+
+    let warnings = DatabaseHandler::get_warnings(&mut transaction, &guild_id, &user_id).await?;
+    assert_eq!(warnings.len(), 2);
+    assert_eq!(warnings[0].id, second.id);
+    assert_eq!(warnings[0].issued_by, moderator);
+    assert_eq!(
+      warnings[0].timeout_until.map(|until| until.timestamp()),
+      Some(timeout_until.timestamp())
+    );
+    assert!(warnings[0].message_link.is_some());
+    assert_eq!(warnings[1].id, first.id);
+    assert!(warnings[1].timeout_until.is_none());
+
+    assert!(
+      DatabaseHandler::get_warnings(&mut transaction, &guild_id, &UserId::new(1u64))
+        .await?
+        .is_empty()
+    );
+
+    Ok(())
+  }
+
+  #[sqlx::test]
+  async fn test_tickets(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+    let guild_id = GuildId::new(123u64);
+    let user_id = UserId::new(789u64);
+    let moderator = UserId::new(999u64);
+    let thread_id = ChannelId::new(456u64);
+
+    let settings = GuildSettings::new(guild_id).ticket_channel(Some(ChannelId::new(111u64)));
+    DatabaseHandler::update_guild_settings(&mut transaction, &settings).await?;
+    let ticket_guilds = DatabaseHandler::get_ticket_guilds(&mut transaction).await?;
+    assert_eq!(ticket_guilds.len(), 1);
+    assert_eq!(
+      ticket_guilds[0].ticket_channel,
+      Some(ChannelId::new(111u64))
+    );
+
+    let ticket = Ticket::new(guild_id, user_id, thread_id, Some("Help".to_owned()));
+    DatabaseHandler::add_ticket(&mut transaction, &ticket).await?;
+
+    let question = TicketMessage::new(&ticket.id, user_id, false, "Hi!".to_owned());
+    DatabaseHandler::add_ticket_message(&mut transaction, &question).await?;
+    let reply = TicketMessage::new(&ticket.id, moderator, true, "Hello!".to_owned());
+    DatabaseHandler::add_ticket_message(&mut transaction, &reply).await?;
+
+    let open = DatabaseHandler::get_open_tickets(&mut transaction, &user_id).await?;
+    assert_eq!(open.len(), 1);
+    assert_eq!(open[0].id, ticket.id);
+
+    let Some(by_thread) =
+      DatabaseHandler::get_ticket_by_thread(&mut transaction, &thread_id).await?
+    else {
+      panic!("Expected the ticket to exist");
+    };
+    assert_eq!(by_thread.subject, Some("Help".to_owned()));
+    assert!(by_thread.open);
+
+    let transcript = DatabaseHandler::get_ticket_transcript(&mut transaction, &ticket.id).await?;
+    assert_eq!(transcript.len(), 2);
+    assert!(!transcript[0].from_staff);
+    assert!(transcript[1].from_staff);
+
+    assert_eq!(
+      DatabaseHandler::close_ticket(&mut transaction, &ticket.id, &moderator).await?,
+      1
+    );
+    assert_eq!(
+      DatabaseHandler::close_ticket(&mut transaction, &ticket.id, &moderator).await?,
+      0
+    );
+    assert!(
+      DatabaseHandler::get_open_tickets(&mut transaction, &user_id)
+        .await?
+        .is_empty()
+    );
+
+    let Some(closed) = DatabaseHandler::get_ticket(&mut transaction, &guild_id, &ticket.id).await?
+    else {
+      panic!("Expected the ticket to exist");
+    };
+    assert!(!closed.open);
+    assert_eq!(closed.closed_by, Some(moderator));
+    assert!(closed.closed_at.is_some());
+
+    // Once closed, the member can open a new ticket
+    let next = Ticket::new(guild_id, user_id, ChannelId::new(458u64), None);
+    DatabaseHandler::add_ticket(&mut transaction, &next).await?;
+
+    Ok(())
+  }
+
+  #[sqlx::test]
+  async fn test_guild_emoji(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+    let guild_id = GuildId::new(123u64);
+
+    let check = GuildEmoji::new(guild_id, EmojiName::Check, "✔️".to_owned());
+    DatabaseHandler::set_guild_emoji(&mut transaction, &check).await?;
+    let info = GuildEmoji::new(guild_id, EmojiName::Info, "<:info:456>".to_owned());
+    DatabaseHandler::set_guild_emoji(&mut transaction, &info).await?;
+    let elsewhere = GuildEmoji::new(GuildId::new(789u64), EmojiName::Info, "💡".to_owned());
+    DatabaseHandler::set_guild_emoji(&mut transaction, &elsewhere).await?;
+
+    // Setting an emoji again replaces it
+    let replacement = GuildEmoji::new(guild_id, EmojiName::Check, "☑️".to_owned());
+    DatabaseHandler::set_guild_emoji(&mut transaction, &replacement).await?;
+
+    let emoji = DatabaseHandler::get_guild_emoji(&mut transaction, &guild_id).await?;
+    assert_eq!(emoji.len(), 2);
+    let Some(check) = emoji.iter().find(|emoji| emoji.name == EmojiName::Check) else {
+      panic!("Expected the check emoji to be replaced");
+    };
+    assert_eq!(check.emoji, "☑️");
+
+    assert_eq!(
+      DatabaseHandler::get_every_guild_emoji(&mut transaction)
+        .await?
+        .len(),
+      3
+    );
+
+    assert_eq!(
+      DatabaseHandler::remove_guild_emoji(&mut transaction, &guild_id, EmojiName::Check).await?,
+      1
+    );
+    assert_eq!(
+      DatabaseHandler::remove_guild_emoji(&mut transaction, &guild_id, EmojiName::Check).await?,
+      0
+    );
+    assert_eq!(
+      DatabaseHandler::get_guild_emoji(&mut transaction, &guild_id)
+        .await?
+        .len(),
+      1
+    );
+
+    Ok(())
+  }
+
+  #[sqlx::test]
+  async fn test_guild_features(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+    let guild_id = GuildId::new(123u64);
+
+    let starboard = GuildFeature::new(guild_id, Feature::Starboard, false);
+    DatabaseHandler::set_guild_feature(&mut transaction, &starboard).await?;
+    let search = GuildFeature::new(guild_id, Feature::AiSearch, false);
+    DatabaseHandler::set_guild_feature(&mut transaction, &search).await?;
+    let elsewhere = GuildFeature::new(GuildId::new(456u64), Feature::Quotes, false);
+    DatabaseHandler::set_guild_feature(&mut transaction, &elsewhere).await?;
+
+    // Turning a feature on again replaces the previous choice
+    let starboard = GuildFeature::new(guild_id, Feature::Starboard, true);
+    DatabaseHandler::set_guild_feature(&mut transaction, &starboard).await?;
+
+    let features = DatabaseHandler::get_guild_features(&mut transaction, &guild_id).await?;
+    assert_eq!(features.len(), 2);
+    let Some(starboard) = features
+      .iter()
+      .find(|feature| feature.feature == Feature::Starboard)
+    else {
+      panic!("Expected the starboard to be set");
+    };
+    assert!(starboard.enabled);
+
+    assert_eq!(
+      DatabaseHandler::get_every_guild_feature(&mut transaction)
+        .await?
+        .len(),
+      3
+    );
+
+    Ok(())
+  }
+
+  #[sqlx::test]
+  async fn test_maintenance(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+
+    // Maintenance is off until it's turned on
+    assert_eq!(
+      DatabaseHandler::get_maintenance(&mut transaction).await?,
+      Maintenance::default()
+    );
+
+    let on = Maintenance::new(true, Some("Database migration".to_owned()));
+    DatabaseHandler::set_maintenance(&mut transaction, &on).await?;
+    assert_eq!(
+      DatabaseHandler::get_maintenance(&mut transaction).await?,
+      on
+    );
+
+    let off = Maintenance::new(false, None);
+    DatabaseHandler::set_maintenance(&mut transaction, &off).await?;
+    assert_eq!(
+      DatabaseHandler::get_maintenance(&mut transaction).await?,
+      off
+    );
+
+    Ok(())
+  }
+
+  #[sqlx::test(fixtures(path = "fixtures", scripts("quote")))]
+  async fn test_backup_rows(pool: PgPool) -> Result<(), Error> {
+    let handler = DatabaseHandler { pool };
+    let mut transaction = handler.start_transaction().await?;
+    let guild_id = &GuildId::new(123u64);
+
+    let rows =
+      DatabaseHandler::get_backup_rows(&mut transaction, BackupTable::Quote, guild_id).await?;
+    let mut backup = Backup::new(*guild_id);
+    backup.add_table(BackupTable::Quote, &rows)?;
+    assert_eq!(backup.row_counts(), vec![(BackupTable::Quote, 8)]);
+
+    let empty =
+      DatabaseHandler::get_backup_rows(&mut transaction, BackupTable::Term, guild_id).await?;
+    assert_eq!(empty, "[]");
+
+    // Rows which already exist are skipped
+    assert_eq!(
+      DatabaseHandler::restore_backup_rows(&mut transaction, BackupTable::Quote, guild_id, &rows)
+        .await?,
+      0
+    );
+
+    DatabaseHandler::remove_quote(&mut transaction, guild_id, "01JBPTWBXJNAKK288S3D89JK7G").await?;
+    assert_eq!(
+      DatabaseHandler::restore_backup_rows(&mut transaction, BackupTable::Quote, guild_id, &rows)
+        .await?,
+      1
+    );
+    assert_eq!(
+      DatabaseHandler::get_all_quotes(&mut transaction, guild_id)
+        .await?
+        .len(),
+      8
+    );
+
+    // Rows for other guilds are skipped
+    DatabaseHandler::remove_quote(&mut transaction, guild_id, "01JBPTWBXJNAKK288S3D89JK7G").await?;
+    assert_eq!(
+      DatabaseHandler::restore_backup_rows(
+        &mut transaction,
+        BackupTable::Quote,
+        &GuildId::new(456u64),
+        &rows
+      )
+      .await?,
+      0
+    );
+
+    Ok(())
+  }
+}
@@ -7,5 +7,7 @@ VALUES
     ('01JBPTWBXJNAKK288S3D89JK7K', '124', '123', 'https://foo.bar/1238', 'A quick brown fox', CAST('2024-01-01 00:00:00+00' AS TIMESTAMPTZ)),
     ('01JBPTWBXJNAKK288S3D89JK7L', '124', '123', 'https://foo.bar/1239', 'Jumping over the lazy dog', CAST('2024-01-01 00:00:00+00' AS TIMESTAMPTZ)),
     ('01JBPV1XJNAKK288S3D89JK7M1', '125', '127', 'https://baz.bat/1240', 'This little piggy', CAST('2024-01-01 00:00:00+00' AS TIMESTAMPTZ)),
-    ('01JBPV1XJNAKK288S3D89JK7N2', '125', '127', 'https://baz.bat/1241', 'Pancakes > waffles', CAST('2024-01-01 00:00:00+00' AS TIMESTAMPTZ))
+    ('01JBPV1XJNAKK288S3D89JK7N2', '125', '127', 'https://baz.bat/1241', 'Pancakes > waffles', CAST('2024-01-01 00:00:00+00' AS TIMESTAMPTZ)),
+    ('01JBPV5QH2Y7ZK3M4N6P8R0S1T', '123', null, 'https://foo.bar/1242', 'A DM from Bloom', CAST('2024-01-01 00:00:00+00' AS TIMESTAMPTZ)),
+    ('01JBPV5QH2Y7ZK3M4N6P8R0S1V', '123', null, 'https://foo.bar/1243', null, CAST('2024-01-01 01:00:00+00' AS TIMESTAMPTZ))
 ;
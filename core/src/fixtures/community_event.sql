@@ -0,0 +1,20 @@
+INSERT INTO community_event (record_id, guild_id, channel_id, title, description, starts_at, duration_minutes, repeat_days, created_by)
+VALUES
+    ('01JBPTWBXJNAKK288S3D89JK9A', '123', '456', 'Book Club', 'Reading together', '2024-10-01 18:00:00+00', 60, 7, '123'),
+    ('01JBPTWBXJNAKK288S3D89JK9B', '123', '789', 'Monthly Q&A', null, '2024-10-05 16:00:00+00', 90, null, '123'),
+    ('01JBPTWBXJNAKK288S3D89JK9C', '456', '456', 'Book Club', null, '2024-10-01 18:00:00+00', 60, 7, '456')
+;
+
+INSERT INTO event_rsvp (record_id, event_id, user_id, occurrence)
+VALUES
+    ('01JBPTWBXJNAKK288S3D89JKAA', '01JBPTWBXJNAKK288S3D89JK9A', '123', '2024-10-01 18:00:00+00'),
+    ('01JBPTWBXJNAKK288S3D89JKAB', '01JBPTWBXJNAKK288S3D89JK9A', '124', '2024-10-01 18:00:00+00'),
+    ('01JBPTWBXJNAKK288S3D89JKAC', '01JBPTWBXJNAKK288S3D89JK9A', '123', '2024-10-08 18:00:00+00')
+;
+
+INSERT INTO event_attendance (record_id, event_id, user_id, occurrence, source)
+VALUES
+    ('01JBPTWBXJNAKK288S3D89JKBA', '01JBPTWBXJNAKK288S3D89JK9A', '123', '2024-10-01 18:00:00+00', 'voice'),
+    ('01JBPTWBXJNAKK288S3D89JKBB', '01JBPTWBXJNAKK288S3D89JK9A', '125', '2024-10-01 18:00:00+00', 'message'),
+    ('01JBPTWBXJNAKK288S3D89JKBC', '01JBPTWBXJNAKK288S3D89JK9A', '125', '2024-10-08 18:00:00+00', 'message')
+;
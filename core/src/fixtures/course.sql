@@ -0,0 +1,10 @@
+INSERT INTO course (record_id, course_name, participant_role, graduate_role, guild_id, dm_only_completion)
+VALUES
+    ('01JAJ8R3ZC3X9QK2W6N4S0B1A1', 'Mindfulness Course', '111', '222', '123', false),
+    ('01JAJ8R3ZC3X9QK2W6N4S0B1A2', 'Compassion Course', '333', '444', '123', true);
+
+INSERT INTO course_enrollment_code (record_id, code, course_name, guild_id, max_uses, uses, created_by, created_at)
+VALUES
+    ('01JAJ8T0V1M5E8R7D2C4F6H8J1', 'SINGLEUS', 'Mindfulness Course', '123', 1, 0, '789', '2024-10-01 00:00:00+00'),
+    ('01JAJ8T0V1M5E8R7D2C4F6H8J2', 'UNLIMITD', 'Mindfulness Course', '123', null, 5, '789', '2024-10-02 00:00:00+00'),
+    ('01JAJ8T0V1M5E8R7D2C4F6H8J3', 'USEDUP22', 'Compassion Course', '123', 2, 2, '789', '2024-10-03 00:00:00+00');
@@ -0,0 +1,5 @@
+INSERT INTO guild_goal (record_id, guild_id, channel_id, period, target_minutes, starts_at, ends_at, last_update_at, completed_at, closed, created_by)
+VALUES
+    ('01JBPTWBXJNAKK288S3D89JK8A', '123', '456', 'weekly', 100, '2024-01-01 00:00:00+00', '2024-01-08 00:00:00+00', null, null, false, '123'),
+    ('01JBPTWBXJNAKK288S3D89JK8B', '456', '789', 'monthly', 50, '2024-01-01 00:00:00+00', '2024-02-01 00:00:00+00', null, '2024-01-03 00:00:00+00', true, '123')
+;
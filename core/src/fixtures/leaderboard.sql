@@ -0,0 +1,22 @@
+INSERT INTO meditation (record_id, user_id, guild_id, meditation_minutes, meditation_seconds, occurred_at)
+VALUES
+    ('01JCQ4B7N2M8R5T1V6X9Z3K0A1', '201', '123', 30, 0, NOW()),
+    ('01JCQ4B7N2M8R5T1V6X9Z3K0A2', '201', '123', 15, 30, NOW()),
+    ('01JCQ4B7N2M8R5T1V6X9Z3K0A3', '202', '123', 60, 0, NOW()),
+    ('01JCQ4B7N2M8R5T1V6X9Z3K0A4', '202', '123', 90, 0, NOW() - INTERVAL '400 days'),
+    ('01JCQ4B7N2M8R5T1V6X9Z3K0A5', '203', '123', 5, 0, NOW()),
+    ('01JCQ4B7N2M8R5T1V6X9Z3K0A6', '203', '123', 5, 0, NOW()),
+    ('01JCQ4B7N2M8R5T1V6X9Z3K0A7', '203', '123', 5, 0, NOW()),
+    ('01JCQ4B7N2M8R5T1V6X9Z3K0A8', '204', '456', 120, 0, NOW())
+;
+
+INSERT INTO streak (record_id, user_id, guild_id, current_streak, longest_streak)
+VALUES
+    ('01JCQ4B7N2M8R5T1V6X9Z3K0B1', '201', '123', 12, 20),
+    ('01JCQ4B7N2M8R5T1V6X9Z3K0B2', '203', '123', 3, 3)
+;
+
+INSERT INTO tracking_profile (record_id, user_id, guild_id, anonymous_tracking)
+VALUES
+    ('01JCQ4B7N2M8R5T1V6X9Z3K0C1', '202', '123', TRUE)
+;
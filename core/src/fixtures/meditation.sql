@@ -0,0 +1,8 @@
+INSERT INTO meditation (record_id, user_id, guild_id, meditation_minutes, meditation_seconds, occurred_at)
+VALUES
+    ('01JBPTWBXJNAKK288S3D89JK7G', '123', '123', 10, 0, CAST('2024-01-01 00:00:00+00' AS TIMESTAMPTZ)),
+    ('01JBPTWBXJNAKK288S3D89JK7H', '123', '123', 15, 30, CAST('2024-01-02 00:00:00+00' AS TIMESTAMPTZ)),
+    ('01JBPTWBXJNAKK288S3D89JK7I', '124', '123', 20, 0, CAST('2024-01-02 00:00:00+00' AS TIMESTAMPTZ)),
+    ('01JBPV1XJNAKK288S3D89JK7M1', '123', '456', 30, 0, CAST('2024-01-01 00:00:00+00' AS TIMESTAMPTZ)),
+    ('01JBPV1XJNAKK288S3D89JK7N2', '123', '456', 45, 0, CAST('2024-01-03 00:00:00+00' AS TIMESTAMPTZ))
+;
@@ -0,0 +1,6 @@
+INSERT INTO star (record_id, starred_message_id, board_message_id, starred_channel_id, guild_id)
+VALUES
+    ('01JBPTWBXJNAKK288S3D89JK7G', '1001', '2001', '3001', '123'),
+    ('01JBPTWBXJNAKK288S3D89JK7H', '1002', '2002', '3002', '456'),
+    ('01JBPTWBXJNAKK288S3D89JK7I', '1003', '2003', '3001', null)
+;
@@ -0,0 +1,5 @@
+INSERT INTO steamkey (record_id, steam_key, reserved, used, guild_id, offer_id, offer_channel_id, offer_message_id, offer_expires_at)
+VALUES
+    ('01JBPTWBXJNAKK288S3D89JK7J', 'AAAAA-BBBBB-CCCCC', '123', false, '123', '1234567890', '456', '789', '2124-01-01 00:00:00+00'),
+    ('01JBPTWBXJNAKK288S3D89JK7K', 'DDDDD-EEEEE-FFFFF', '321', false, '123', '1234567891', '654', '987', '2024-01-01 00:00:00+00'),
+    ('01JBPTWBXJNAKK288S3D89JK7M', 'GGGGG-HHHHH-IIIII', null, false, '123', null, null, null, null)
@@ -0,0 +1,8 @@
+INSERT INTO steamkey (record_id, steam_key, reserved, reserved_at, used, guild_id, expires_at, offer_id, offer_channel_id, offer_message_id, offer_expires_at)
+VALUES
+    ('01JCP4M6W7Q0E3J8ZB6V1H2K3A', 'EXPIR-EDKEY-AAAAA', null, null, false, '123', '2024-01-01 00:00:00+00', null, null, null, null),
+    ('01JCP4M6W7Q0E3J8ZB6V1H2K3B', 'EXPIR-EDKEY-BBBBB', '321', NOW(), false, '123', '2024-01-01 00:00:00+00', '3456789012', '654', '987', '2124-01-01 00:00:00+00'),
+    ('01JCP4M6W7Q0E3J8ZB6V1H2K3C', 'EXPIR-EDKEY-CCCCC', null, null, true, '123', '2024-01-01 00:00:00+00', null, null, null, null),
+    ('01JCP4M6W7Q0E3J8ZB6V1H2K3D', 'STALE-RESER-DDDDD', '555', '2024-01-01 00:00:00+00', false, '123', null, null, null, null, null),
+    ('01JCP4M6W7Q0E3J8ZB6V1H2K3E', 'FRESH-RESER-EEEEE', '666', NOW(), false, '123', '2124-01-01 00:00:00+00', null, null, null, null),
+    ('01JCP4M6W7Q0E3J8ZB6V1H2K3F', 'VALID-UNTIL-FFFFF', null, null, false, '123', '2124-01-01 00:00:00+00', null, null, null, null)
@@ -0,0 +1,21 @@
+INSERT INTO tracking_profile (record_id, user_id, guild_id, utc_offset, streak_guard_hour)
+VALUES
+    ('01JB0G1R3MZQ1VX9D6T8WJ4K2A', '123', '123', 60, 20),
+    ('01JB0G1R3MZQ1VX9D6T8WJ4K2B', '124', '123', 0, 20),
+    ('01JB0G1R3MZQ1VX9D6T8WJ4K2C', '125', '123', 60, 22)
+;
+
+INSERT INTO streak (record_id, user_id, guild_id, current_streak, longest_streak)
+VALUES
+    ('01JB0G1R3MZQ1VX9D6T8WJ4K3A', '123', '123', 3, 3),
+    ('01JB0G1R3MZQ1VX9D6T8WJ4K3B', '124', '123', 3, 3),
+    ('01JB0G1R3MZQ1VX9D6T8WJ4K3C', '125', '123', 3, 3)
+;
+
+INSERT INTO meditation (record_id, user_id, guild_id, meditation_minutes, meditation_seconds, occurred_at)
+VALUES
+    ('01JB0G1R3MZQ1VX9D6T8WJ4K4A', '123', '123', 10, 0, CAST('2024-10-23 21:00:00+00' AS TIMESTAMPTZ)),
+    ('01JB0G1R3MZQ1VX9D6T8WJ4K4B', '124', '123', 10, 0, CAST('2024-10-23 21:00:00+00' AS TIMESTAMPTZ)),
+    ('01JB0G1R3MZQ1VX9D6T8WJ4K4C', '124', '123', 10, 0, CAST('2024-10-24 08:00:00+00' AS TIMESTAMPTZ)),
+    ('01JB0G1R3MZQ1VX9D6T8WJ4K4D', '125', '123', 10, 0, CAST('2024-10-23 08:00:00+00' AS TIMESTAMPTZ))
+;
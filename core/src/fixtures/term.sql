@@ -0,0 +1,7 @@
+INSERT INTO term (record_id, term_name, meaning, aliases, related, guild_id)
+VALUES
+    ('01JBPW3C8M2QK7N4R6T9V1X3Z5', 'Insight Meditation', 'Meditation aimed at seeing clearly.', ARRAY['Vipassana'], ARRAY['Jhana'], '123'),
+    ('01JBPW3C8M2QK7N4R6T9V1X3Z6', 'Metta', 'The practice of cultivating goodwill.', ARRAY['Loving-kindness'], NULL, '123'),
+    ('01JBPW3C8M2QK7N4R6T9V1X3Z7', 'Jhana', 'A state of deep meditative absorption.', NULL, ARRAY['Insight Meditation'], '123'),
+    ('01JBPW3C8M2QK7N4R6T9V1X3Z8', 'Maitri', 'A term from another server.', NULL, NULL, '456')
+;
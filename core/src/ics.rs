@@ -0,0 +1,294 @@
+//! Calendar feeds in the iCalendar format ([RFC 5545][rfc]), so members can subscribe to a
+//! server's events in their own calendar apps.
+//!
+//! [rfc]: https://www.rfc-editor.org/rfc/rfc5545
+
+use chrono::{DateTime, Datelike, Duration, Months, NaiveDate, Utc};
+
+use crate::data::calendar::DiscordEvent;
+use crate::data::community_event::CommunityEvent;
+
+/// Lines longer than this many bytes are folded, as the format requires.
+const MAX_LINE_LENGTH: usize = 75;
+
+/// When a [`CalendarEvent`] takes place.
+pub enum EventTime {
+  /// From one time until another.
+  Timed {
+    starts_at: DateTime<Utc>,
+    ends_at: DateTime<Utc>,
+  },
+  /// Across whole days, from `start` until the day before `end`.
+  AllDay { start: NaiveDate, end: NaiveDate },
+}
+
+/// A single event in a [`Calendar`], which may repeat every few days.
+pub struct CalendarEvent {
+  /// Identifies the event across updates to the feed, so calendar apps update it in place.
+  pub uid: String,
+  pub summary: String,
+  pub description: Option<String>,
+  pub location: Option<String>,
+  pub time: EventTime,
+  pub repeat_days: Option<i32>,
+}
+
+impl CalendarEvent {
+  fn render(&self, output: &mut String, now: DateTime<Utc>) {
+    push_line(output, "BEGIN:VEVENT");
+    push_line(output, &format!("UID:{}@bloombot", self.uid));
+    push_line(output, &format!("DTSTAMP:{}", format_time(now)));
+    match self.time {
+      EventTime::Timed { starts_at, ends_at } => {
+        push_line(output, &format!("DTSTART:{}", format_time(starts_at)));
+        push_line(output, &format!("DTEND:{}", format_time(ends_at)));
+      }
+      EventTime::AllDay { start, end } => {
+        push_line(
+          output,
+          &format!("DTSTART;VALUE=DATE:{}", format_date(start)),
+        );
+        push_line(output, &format!("DTEND;VALUE=DATE:{}", format_date(end)));
+      }
+    }
+    if let Some(rule) = self.repeat_days.and_then(recurrence_rule) {
+      push_line(output, &format!("RRULE:{rule}"));
+    }
+    push_line(output, &format!("SUMMARY:{}", escape(&self.summary)));
+    if let Some(description) = &self.description {
+      push_line(output, &format!("DESCRIPTION:{}", escape(description)));
+    }
+    if let Some(location) = &self.location {
+      push_line(output, &format!("LOCATION:{}", escape(location)));
+    }
+    push_line(output, "END:VEVENT");
+  }
+}
+
+impl From<&CommunityEvent> for CalendarEvent {
+  fn from(event: &CommunityEvent) -> Self {
+    Self {
+      uid: event.id.clone(),
+      summary: event.title.clone(),
+      description: event.description.clone(),
+      location: Some(channel_url(event.guild_id.get(), event.channel_id.get())),
+      time: EventTime::Timed {
+        starts_at: event.starts_at,
+        ends_at: event.starts_at + Duration::minutes(i64::from(event.duration_minutes)),
+      },
+      repeat_days: event.repeat_days,
+    }
+  }
+}
+
+impl From<&DiscordEvent> for CalendarEvent {
+  /// Events without an end time are shown as lasting an hour, since calendar apps need one.
+  fn from(event: &DiscordEvent) -> Self {
+    Self {
+      uid: event.id.clone(),
+      summary: event.name.clone(),
+      description: event.description.clone(),
+      location: event.location.clone(),
+      time: EventTime::Timed {
+        starts_at: event.starts_at,
+        ends_at: event
+          .ends_at
+          .unwrap_or(event.starts_at + Duration::hours(1)),
+      },
+      repeat_days: None,
+    }
+  }
+}
+
+/// A calendar of events, which can be rendered as a feed with [`Calendar::render`].
+pub struct Calendar {
+  pub name: String,
+  pub events: Vec<CalendarEvent>,
+}
+
+impl Calendar {
+  pub fn new(name: impl Into<String>) -> Self {
+    Self {
+      name: name.into(),
+      events: Vec::new(),
+    }
+  }
+
+  /// Adds an event to the calendar.
+  pub fn event(mut self, event: impl Into<CalendarEvent>) -> Self {
+    self.events.push(event.into());
+    self
+  }
+
+  /// Renders the calendar as an iCalendar feed, to serve with the `text/calendar` content
+  /// type.
+  pub fn render(&self, now: DateTime<Utc>) -> String {
+    let mut output = String::new();
+    push_line(&mut output, "BEGIN:VCALENDAR");
+    push_line(&mut output, "VERSION:2.0");
+    push_line(&mut output, "PRODID:-//Bloom Bot//Calendar//EN");
+    push_line(&mut output, "CALSCALE:GREGORIAN");
+    push_line(&mut output, "METHOD:PUBLISH");
+    push_line(&mut output, &format!("X-WR-CALNAME:{}", escape(&self.name)));
+    for event in &self.events {
+      event.render(&mut output, now);
+    }
+    push_line(&mut output, "END:VCALENDAR");
+
+    output
+  }
+}
+
+/// Returns the monthly meditation challenge for the month containing `today` and each of
+/// the following `months`, as all-day events.
+pub fn monthly_challenges(today: NaiveDate, months: u32) -> Vec<CalendarEvent> {
+  let Some(first) = today.with_day(1) else {
+    return Vec::new();
+  };
+
+  (0..=months)
+    .filter_map(|offset| {
+      let start = first.checked_add_months(Months::new(offset))?;
+      let end = start.checked_add_months(Months::new(1))?;
+      Some(CalendarEvent {
+        uid: format!("challenge-{}", start.format("%Y-%m")),
+        summary: format!("Monthly Meditation Challenge ({})", start.format("%B")),
+        description: Some(
+          "Join with /challenge join and add your time each day with /add.".to_owned(),
+        ),
+        location: None,
+        time: EventTime::AllDay { start, end },
+        repeat_days: None,
+      })
+    })
+    .collect()
+}
+
+/// A link to a channel, which opens it in Discord.
+pub fn channel_url(guild_id: u64, channel_id: u64) -> String {
+  format!("https://discord.com/channels/{guild_id}/{channel_id}")
+}
+
+/// Describes a repeat every `days`, using weeks where possible, since calendar apps show them
+/// more clearly.
+fn recurrence_rule(days: i32) -> Option<String> {
+  match days {
+    days if days <= 0 => None,
+    7 => Some("FREQ=WEEKLY".to_owned()),
+    days if days % 7 == 0 => Some(format!("FREQ=WEEKLY;INTERVAL={}", days / 7)),
+    1 => Some("FREQ=DAILY".to_owned()),
+    days => Some(format!("FREQ=DAILY;INTERVAL={days}")),
+  }
+}
+
+fn format_time(time: DateTime<Utc>) -> String {
+  time.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+fn format_date(date: NaiveDate) -> String {
+  date.format("%Y%m%d").to_string()
+}
+
+/// Escapes text for a property value.
+fn escape(text: &str) -> String {
+  text
+    .replace('\\', "\\\\")
+    .replace(';', "\\;")
+    .replace(',', "\\,")
+    .replace("\r\n", "\\n")
+    .replace('\n', "\\n")
+}
+
+/// Adds a line to the output, folding it onto continuation lines if it's too long. Lines
+/// are only folded between characters, so multi-byte characters aren't split.
+fn push_line(output: &mut String, line: &str) {
+  let mut length = 0;
+  for c in line.chars() {
+    if length + c.len_utf8() > MAX_LINE_LENGTH {
+      output.push_str("\r\n ");
+      // The leading space counts toward the length of the continuation line
+      length = 1;
+    }
+    output.push(c);
+    length += c.len_utf8();
+  }
+  output.push_str("\r\n");
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_recurrence_rule() {
+    assert_eq!(recurrence_rule(0), None);
+    assert_eq!(recurrence_rule(1).as_deref(), Some("FREQ=DAILY"));
+    assert_eq!(recurrence_rule(3).as_deref(), Some("FREQ=DAILY;INTERVAL=3"));
+    assert_eq!(recurrence_rule(7).as_deref(), Some("FREQ=WEEKLY"));
+    assert_eq!(
+      recurrence_rule(14).as_deref(),
+      Some("FREQ=WEEKLY;INTERVAL=2")
+    );
+  }
+
+  #[test]
+  fn test_escape() {
+    assert_eq!(
+      escape("Sit, then talk; bring tea\\cushions\nAll welcome"),
+      "Sit\\, then talk\\; bring tea\\\\cushions\\nAll welcome"
+    );
+  }
+
+  #[test]
+  fn test_push_line() {
+    let mut output = String::new();
+    push_line(&mut output, &format!("SUMMARY:{}", "é".repeat(50)));
+    assert!(output
+      .split("\r\n")
+      .all(|line| line.len() <= MAX_LINE_LENGTH));
+    assert_eq!(
+      output.replace("\r\n ", ""),
+      format!("SUMMARY:{}\r\n", "é".repeat(50))
+    );
+  }
+
+  #[test]
+  fn test_monthly_challenges() {
+    let date = |year, month, day| NaiveDate::from_ymd_opt(year, month, day).unwrap_or_default();
+    let challenges = monthly_challenges(date(2024, 11, 20), 2);
+
+    assert_eq!(challenges.len(), 3);
+    assert_eq!(challenges[0].uid, "challenge-2024-11");
+    assert_eq!(
+      challenges[2].summary,
+      "Monthly Meditation Challenge (January)"
+    );
+    let EventTime::AllDay { start, end } = challenges[1].time else {
+      panic!("Expected an all-day event");
+    };
+    assert_eq!((start, end), (date(2024, 12, 1), date(2025, 1, 1)));
+  }
+
+  #[test]
+  fn test_render() {
+    let now = DateTime::from_timestamp(1_732_000_000, 0).unwrap_or_default();
+    let calendar = Calendar::new("Bloom Events").event(CalendarEvent {
+      uid: "01JD0000000000000000000000".to_owned(),
+      summary: "Book Club".to_owned(),
+      description: None,
+      location: Some(channel_url(123, 456)),
+      time: EventTime::Timed {
+        starts_at: now,
+        ends_at: now + Duration::hours(1),
+      },
+      repeat_days: Some(7),
+    });
+
+    let feed = calendar.render(now);
+    assert!(feed.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+    assert!(feed.ends_with("END:VEVENT\r\nEND:VCALENDAR\r\n"));
+    assert!(feed.contains("\r\nDTSTART:20241119T070640Z\r\nDTEND:20241119T080640Z\r\n"));
+    assert!(feed.contains("\r\nRRULE:FREQ=WEEKLY\r\n"));
+    assert!(feed.contains("\r\nLOCATION:https://discord.com/channels/123/456\r\n"));
+  }
+}
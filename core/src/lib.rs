@@ -0,0 +1,59 @@
+//! The meditation tracking data model and database queries behind Bloom Bot, for use by
+//! companion tools, such as the website or data analysis scripts, without going through
+//! Discord.
+//!
+//! - [`data`] has a module for each kind of record, such as [`data::meditation`] and
+//!   [`data::tracking_profile`], with the types and queries for it.
+//! - [`database`] has the [`DatabaseHandler`][handler], which connects to the database,
+//!   applies migrations, and runs queries.
+//!
+//! Queries run in a transaction, so that several can be committed or rolled back together:
+//!
+//! ```no_run
+//! use bloombot_core::database::DatabaseHandler;
+//! use bloombot_core::time::Timeframe;
+//! use poise::serenity_prelude::{GuildId, UserId};
+//!
+//! # async fn example() -> anyhow::Result<()> {
+//! // Connects using the DATABASE_URL environment variable
+//! let db = DatabaseHandler::new().await?;
+//! let mut transaction = db.start_transaction().await?;
+//!
+//! let stats = DatabaseHandler::get_user_stats(
+//!   &mut transaction,
+//!   &GuildId::new(123),
+//!   &UserId::new(456),
+//!   &Timeframe::Monthly,
+//! )
+//! .await?;
+//! println!("{} minutes meditated", stats.all_minutes);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! IDs are Discord's, using the ID types from [`poise::serenity_prelude`]. Tools with their
+//! own connection pool can use [`DatabaseHandler::from_pool`][from_pool], which doesn't
+//! apply migrations.
+//!
+//! [handler]: database::DatabaseHandler
+//! [from_pool]: database::DatabaseHandler::from_pool
+
+#![warn(clippy::pedantic, clippy::unwrap_used, clippy::expect_used)]
+// Written as part of the bot, where none of the API was public
+#![allow(
+  clippy::missing_errors_doc,
+  clippy::missing_panics_doc,
+  clippy::must_use_candidate,
+  clippy::return_self_not_must_use,
+  clippy::too_many_lines
+)]
+
+#[macro_use(query)]
+extern crate sqlx;
+
+pub mod charts;
+pub mod data;
+pub mod database;
+pub mod ics;
+pub mod pagination;
+pub mod time;
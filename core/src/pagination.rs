@@ -0,0 +1,12 @@
+/// Which of a row's titles to show, for rows which can be listed in more than one way.
+#[derive(Debug, Copy, Clone)]
+pub enum PageType {
+  Standard,
+  Alternate,
+}
+
+/// A row which can be listed on a page, with a title and a body.
+pub trait PageRow {
+  fn title(&self, page_type: PageType) -> String;
+  fn body(&self) -> String;
+}
@@ -1,3 +1,4 @@
+use chrono::{Datelike, Months, NaiveDate, TimeDelta};
 use poise::ChoiceParameter;
 
 #[derive(ChoiceParameter)]
@@ -197,6 +198,63 @@ pub enum Timeframe {
   Daily,
 }
 
+impl Timeframe {
+  /// The timeframe to group a date range by, so that charts of the range have a readable
+  /// number of bars: days for up to two months, weeks for up to a year, months for up to
+  /// ten years, and years beyond that.
+  pub fn for_range(start: NaiveDate, end: NaiveDate) -> Self {
+    match (end - start).num_days() {
+      ..=62 => Self::Daily,
+      ..=366 => Self::Weekly,
+      ..=3660 => Self::Monthly,
+      _ => Self::Yearly,
+    }
+  }
+
+  /// The unit used by `date_trunc` in Postgres for this timeframe.
+  pub fn unit(&self) -> &'static str {
+    match self {
+      Self::Yearly => "year",
+      Self::Monthly => "month",
+      Self::Weekly => "week",
+      Self::Daily => "day",
+    }
+  }
+
+  /// The first day of the period containing `date`. Weeks start on Monday.
+  pub fn period_start(&self, date: NaiveDate) -> NaiveDate {
+    match self {
+      Self::Yearly => date.with_ordinal(1).unwrap_or(date),
+      Self::Monthly => date.with_day(1).unwrap_or(date),
+      Self::Weekly => date - TimeDelta::days(i64::from(date.weekday().num_days_from_monday())),
+      Self::Daily => date,
+    }
+  }
+
+  /// The first day of each period from the one containing `start` to the one containing
+  /// `end`, in order.
+  pub fn periods(&self, start: NaiveDate, end: NaiveDate) -> Vec<NaiveDate> {
+    let mut periods = vec![];
+    let mut period = self.period_start(start);
+
+    while period <= end {
+      periods.push(period);
+      let next = match self {
+        Self::Yearly => period.checked_add_months(Months::new(12)),
+        Self::Monthly => period.checked_add_months(Months::new(1)),
+        Self::Weekly => period.checked_add_signed(TimeDelta::weeks(1)),
+        Self::Daily => period.checked_add_signed(TimeDelta::days(1)),
+      };
+      let Some(next) = next else {
+        break;
+      };
+      period = next;
+    }
+
+    periods
+  }
+}
+
 #[derive(ChoiceParameter, PartialEq)]
 pub enum ChallengeTimeframe {
   #[name = "Monthly Challenge"]
@@ -209,6 +267,45 @@ pub enum ChallengeTimeframe {
 mod tests {
   use super::*;
 
+  #[test]
+  fn test_timeframe_periods() {
+    let date = |year, month, day| NaiveDate::from_ymd_opt(year, month, day).unwrap_or_default();
+
+    assert!(matches!(
+      Timeframe::for_range(date(2024, 10, 1), date(2024, 10, 31)),
+      Timeframe::Daily
+    ));
+    assert!(matches!(
+      Timeframe::for_range(date(2024, 1, 1), date(2024, 10, 31)),
+      Timeframe::Weekly
+    ));
+    assert!(matches!(
+      Timeframe::for_range(date(2020, 1, 1), date(2024, 10, 31)),
+      Timeframe::Monthly
+    ));
+    assert!(matches!(
+      Timeframe::for_range(date(2010, 1, 1), date(2024, 10, 31)),
+      Timeframe::Yearly
+    ));
+
+    // November 6, 2024 was a Wednesday
+    assert_eq!(
+      Timeframe::Weekly.periods(date(2024, 11, 6), date(2024, 11, 18)),
+      vec![date(2024, 11, 4), date(2024, 11, 11), date(2024, 11, 18)]
+    );
+    assert_eq!(
+      Timeframe::Monthly.periods(date(2024, 11, 15), date(2025, 1, 1)),
+      vec![date(2024, 11, 1), date(2024, 12, 1), date(2025, 1, 1)]
+    );
+    assert_eq!(
+      Timeframe::Daily.periods(date(2024, 11, 6), date(2024, 11, 7)),
+      vec![date(2024, 11, 6), date(2024, 11, 7)]
+    );
+    assert!(Timeframe::Daily
+      .periods(date(2024, 11, 7), date(2024, 11, 6))
+      .is_empty());
+  }
+
   #[test]
   fn test_choice_from_offset() {
     matches!(
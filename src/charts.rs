@@ -9,25 +9,84 @@ use std::path::PathBuf;
 
 use anyhow::{anyhow, Result};
 use charts_rs::{self, Align, BarChart, Box, LegendCategory};
-use charts_rs::{Series, SeriesCategory, TableCellStyle, TableChart};
+use charts_rs::{Series, SeriesCategory, TableCellStyle, TableChart, NIL_VALUE};
 use chrono::{Datelike, Duration, NaiveDate, Utc, Weekday};
+use regex::Regex;
 use tokio::fs::{self, File};
 use tokio::io::AsyncWriteExt;
 
 use crate::commands::helpers::time::Timeframe;
-use crate::commands::stats::{ChartStyle, LeaderboardType, SortBy, StatsType};
-use crate::data::stats::Timeframe as TimeframeStats;
+use crate::commands::stats::{ChartStyle, ServerChart, StatsType};
+use crate::data::mood_checkin::MoodDay;
+use crate::data::stats::{LeaderboardType, SortBy, Timeframe as TimeframeStats};
+use crate::data::wellness_metric::SleepDay;
+
+pub use bloombot_core::charts::ChartFormat;
+
+/// Renders an SVG chart in the image format.
+fn encode(format: ChartFormat, svg: &str) -> Result<Vec<u8>> {
+  Ok(match format {
+    ChartFormat::Standard => charts_rs::svg_to_webp(svg)?,
+    ChartFormat::HighRes => charts_rs::svg_to_png(&scale_svg(svg, 2.0)?)?,
+    ChartFormat::Svg => svg.as_bytes().to_vec(),
+  })
+}
+
+/// Scales an SVG by changing the size of the root element, keeping its coordinate system with
+/// a `viewBox`, so that it's rendered at a higher resolution.
+fn scale_svg(svg: &str, factor: f64) -> Result<String> {
+  let start = svg
+    .find("<svg")
+    .ok_or_else(|| anyhow!("Missing <svg> element"))?;
+  let end = start
+    + svg[start..]
+      .find('>')
+      .ok_or_else(|| anyhow!("Unterminated <svg> element"))?;
+  let tag = &svg[start..end];
+
+  let attribute = |name: &str| -> Result<f64> {
+    let pattern = Regex::new(&format!(r#"\s{name}="([0-9.]+)""#))?;
+    let value = pattern
+      .captures(tag)
+      .ok_or_else(|| anyhow!("Missing {name} on <svg> element"))?;
+    Ok(value[1].parse::<f64>()?)
+  };
+  let width = attribute("width")?;
+  let height = attribute("height")?;
+
+  let mut scaled = tag
+    .replacen(
+      &format!(r#"width="{width}""#),
+      &format!(r#"width="{}""#, width * factor),
+      1,
+    )
+    .replacen(
+      &format!(r#"height="{height}""#),
+      &format!(r#"height="{}""#, height * factor),
+      1,
+    );
+  if !tag.contains("viewBox") {
+    scaled.push_str(&format!(r#" viewBox="0 0 {width} {height}""#));
+  }
+
+  Ok(format!("{}{scaled}{}", &svg[..start], &svg[end..]))
+}
 
 #[derive(Debug)]
 pub struct Chart<'a> {
   file: File,
   path: PathBuf,
   filename: &'a str,
+  format: ChartFormat,
 }
 
 impl<'a> Chart<'a> {
   pub async fn new() -> Result<Self> {
-    let filename = "attachment.webp";
+    Self::with_format(ChartFormat::Standard).await
+  }
+
+  pub async fn with_format(format: ChartFormat) -> Result<Self> {
+    let filename = format.filename();
     let path = env::temp_dir().with_file_name(filename);
     let file = File::create(&path).await?;
 
@@ -35,6 +94,7 @@ impl<'a> Chart<'a> {
       file,
       path,
       filename,
+      format,
     })
   }
 
@@ -46,6 +106,7 @@ impl<'a> Chart<'a> {
       file,
       path,
       filename,
+      format: ChartFormat::Standard,
     })
   }
 
@@ -57,6 +118,7 @@ impl<'a> Chart<'a> {
       file,
       path,
       filename,
+      format: ChartFormat::Standard,
     })
   }
 
@@ -68,14 +130,22 @@ impl<'a> Chart<'a> {
     offset: i16,
     stats_type: &StatsType,
     chart_style: &ChartStyle,
+    previous: Option<&[TimeframeStats]>,
     bar_color: (u8, u8, u8, u8),
     light_mode: bool,
   ) -> Result<Self> {
     if stats.len() != 12 {
       return Err(anyhow!("Not enough stats to draw chart"));
     }
+    if let ChartStyle::Comparison = chart_style {
+      if previous.is_none_or(|previous| previous.len() != 12) {
+        return Err(anyhow!(
+          "Not enough previous stats to draw comparison chart"
+        ));
+      }
+    }
 
-    let title = if let ChartStyle::BarCombined = chart_style {
+    let title = if let ChartStyle::BarCombined | ChartStyle::Comparison = chart_style {
       String::new()
     } else {
       match stats_type {
@@ -84,7 +154,31 @@ impl<'a> Chart<'a> {
       }
     };
 
-    let series_data = if let ChartStyle::BarCombined = chart_style {
+    let series_data = if let ChartStyle::Comparison = chart_style {
+      // Overlay the previous 12 periods on the current 12, period by period
+      let values = |stats: &[TimeframeStats]| {
+        stats
+          .iter()
+          .map(|x| match stats_type {
+            StatsType::MeditationMinutes => x.sum.unwrap_or(0) as f32,
+            StatsType::MeditationCount => x.count.unwrap_or(0) as f32,
+          })
+          .collect::<Vec<f32>>()
+      };
+      let periods = match timeframe {
+        Timeframe::Daily => "Days",
+        Timeframe::Weekly => "Weeks",
+        Timeframe::Monthly => "Months",
+        Timeframe::Yearly => "Years",
+      };
+      vec![
+        Series::new(format!("Past 12 {periods}"), values(stats)),
+        Series::new(
+          format!("Previous 12 {periods}"),
+          values(previous.unwrap_or_default()),
+        ),
+      ]
+    } else if let ChartStyle::BarCombined = chart_style {
       let minutes = stats
         .iter()
         .map(|x| x.sum.unwrap_or(0) as f32)
@@ -208,31 +302,16 @@ impl<'a> Chart<'a> {
       bar_chart.y_axis_configs[0].axis_font_color = (216, 217, 218).into();
     }
 
-    if let ChartStyle::Bar = chart_style {
-    } else {
-      bar_chart.series_fill = true;
-      bar_chart.series_smooth = true;
-      bar_chart.series_list[0].category = Some(SeriesCategory::Line);
-      bar_chart.series_list[0].label_show = true;
-      bar_chart.series_label_font_size = 16.0;
-      bar_chart.series_label_font_weight = Some("bold".to_string());
-      bar_chart.series_label_formatter = "{t}".to_string();
-      bar_chart.x_boundary_gap = Some(false);
-      bar_chart.y_axis_hidden = true;
-      bar_chart.margin = Box {
-        left: 45.0,
-        top: 15.0,
-        right: 45.0,
-        bottom: 15.0,
-      };
-      // Add a second line
-      if let ChartStyle::BarCombined = chart_style {
-        bar_chart.y_axis_hidden = false;
-        bar_chart.y_axis_configs[0].axis_width = Some(0.0);
-        bar_chart.series_list[0].category = Some(SeriesCategory::Bar);
-        bar_chart.series_list[1].category = Some(SeriesCategory::Bar);
-        bar_chart.series_list[1].y_axis_index = 1;
-        bar_chart.series_list[1].label_show = true;
+    match chart_style {
+      ChartStyle::Bar => {}
+      ChartStyle::Comparison => {
+        bar_chart.series_colors = vec![
+          (bar_color.0, bar_color.1, bar_color.2, bar_color.3).into(),
+          (88, 101, 242, 255).into(),
+        ];
+        bar_chart.series_smooth = true;
+        bar_chart.series_list[1].category = Some(SeriesCategory::Line);
+        bar_chart.series_list[1].label_show = false;
         bar_chart.legend_show = Some(true);
         bar_chart.legend_category = LegendCategory::Rect;
         bar_chart.legend_align = Align::Left;
@@ -244,18 +323,361 @@ impl<'a> Chart<'a> {
           right: 0.0,
         });
       }
+      ChartStyle::Area | ChartStyle::BarCombined => {
+        bar_chart.series_fill = true;
+        bar_chart.series_smooth = true;
+        bar_chart.series_list[0].category = Some(SeriesCategory::Line);
+        bar_chart.series_list[0].label_show = true;
+        bar_chart.series_label_font_size = 16.0;
+        bar_chart.series_label_font_weight = Some("bold".to_string());
+        bar_chart.series_label_formatter = "{t}".to_string();
+        bar_chart.x_boundary_gap = Some(false);
+        bar_chart.y_axis_hidden = true;
+        bar_chart.margin = Box {
+          left: 45.0,
+          top: 15.0,
+          right: 45.0,
+          bottom: 15.0,
+        };
+        // Add a second line
+        if let ChartStyle::BarCombined = chart_style {
+          bar_chart.y_axis_hidden = false;
+          bar_chart.y_axis_configs[0].axis_width = Some(0.0);
+          bar_chart.series_list[0].category = Some(SeriesCategory::Bar);
+          bar_chart.series_list[1].category = Some(SeriesCategory::Bar);
+          bar_chart.series_list[1].y_axis_index = 1;
+          bar_chart.series_list[1].label_show = true;
+          bar_chart.legend_show = Some(true);
+          bar_chart.legend_category = LegendCategory::Rect;
+          bar_chart.legend_align = Align::Left;
+          bar_chart.legend_font_size = 16.0;
+          bar_chart.legend_margin = Some(Box {
+            top: 10.0,
+            left: 0.0,
+            bottom: 30.0,
+            right: 0.0,
+          });
+        }
+      }
     }
 
     let svg = bar_chart.svg()?;
-    let webp = charts_rs::svg_to_webp(&svg)?;
+    let image = encode(self.format, &svg)?;
+
+    AsyncWriteExt::write_all(&mut self.file, &image).await?;
+    AsyncWriteExt::flush(&mut self.file).await?;
+
+    Ok(Self {
+      file: self.file,
+      path: self.path,
+      filename: self.filename,
+      format: self.format,
+    })
+  }
+
+  /// Draws a server's stats for each day of the week or hour of the day, in UTC.
+  pub async fn activity(
+    self,
+    stats: &[TimeframeStats],
+    chart: &ServerChart,
+    stats_type: &StatsType,
+    bar_color: (u8, u8, u8, u8),
+    light_mode: bool,
+  ) -> Result<Self> {
+    let (x_labels, subject): (Vec<String>, &str) = match chart {
+      ServerChart::Weekday => (
+        ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"]
+          .map(String::from)
+          .to_vec(),
+        "Day",
+      ),
+      ServerChart::Hour => ((0..24).map(|hour| format!("{hour:02}")).collect(), "Hour"),
+      ServerChart::Timeline => return Err(anyhow!("Timeline charts are drawn with stats")),
+    };
+    if stats.len() != x_labels.len() {
+      return Err(anyhow!("Not enough stats to draw chart"));
+    }
+
+    let (title, series_name, values) = match stats_type {
+      StatsType::MeditationMinutes => (
+        format!("Minutes by {subject} (UTC)"),
+        "Minutes",
+        stats
+          .iter()
+          .map(|x| x.sum.unwrap_or(0) as f32)
+          .collect::<Vec<f32>>(),
+      ),
+      StatsType::MeditationCount => (
+        format!("Sessions by {subject} (UTC)"),
+        "Sessions",
+        stats
+          .iter()
+          .map(|x| x.count.unwrap_or(0) as f32)
+          .collect::<Vec<f32>>(),
+      ),
+    };
+
+    let bar_chart = Self::column_chart(title, series_name, values, x_labels, bar_color, light_mode);
+    self.write_svg(&bar_chart.svg()?).await
+  }
+
+  /// Draws stats for each period of a date range, labelled with the date each period starts.
+  pub async fn range(
+    self,
+    stats: &[(NaiveDate, TimeframeStats)],
+    timeframe: &Timeframe,
+    stats_type: &StatsType,
+    bar_color: (u8, u8, u8, u8),
+    light_mode: bool,
+  ) -> Result<Self> {
+    let label_format = match timeframe {
+      Timeframe::Yearly => "%Y",
+      Timeframe::Monthly => "%b %y",
+      Timeframe::Weekly | Timeframe::Daily => "%m/%d",
+    };
+    let x_labels = stats
+      .iter()
+      .map(|(date, _)| date.format(label_format).to_string())
+      .collect();
+    let subject = match timeframe {
+      Timeframe::Yearly => "Year",
+      Timeframe::Monthly => "Month",
+      Timeframe::Weekly => "Week",
+      Timeframe::Daily => "Day",
+    };
+
+    let (title, series_name, values) = match stats_type {
+      StatsType::MeditationMinutes => (
+        format!("Minutes by {subject}"),
+        "Minutes",
+        stats
+          .iter()
+          .map(|(_, x)| x.sum.unwrap_or(0) as f32)
+          .collect::<Vec<f32>>(),
+      ),
+      StatsType::MeditationCount => (
+        format!("Sessions by {subject}"),
+        "Sessions",
+        stats
+          .iter()
+          .map(|(_, x)| x.count.unwrap_or(0) as f32)
+          .collect::<Vec<f32>>(),
+      ),
+    };
+
+    let bar_chart = Self::column_chart(title, series_name, values, x_labels, bar_color, light_mode);
+    self.write_svg(&bar_chart.svg()?).await
+  }
+
+  /// Draws the number of members holding each role tier, with the tiers' thresholds as labels.
+  pub async fn roles(
+    self,
+    title: &str,
+    counts: &[u64],
+    x_labels: Vec<String>,
+    bar_color: (u8, u8, u8, u8),
+    light_mode: bool,
+  ) -> Result<Self> {
+    if counts.len() != x_labels.len() {
+      return Err(anyhow!("Not enough role counts to draw chart"));
+    }
+
+    let values = counts.iter().map(|count| *count as f32).collect();
+    let bar_chart = Self::column_chart(
+      title.to_owned(),
+      "Members",
+      values,
+      x_labels,
+      bar_color,
+      light_mode,
+    );
+    self.write_svg(&bar_chart.svg()?).await
+  }
+
+  /// Draws a member's daily meditation minutes as bars, with their mood check-ins as a line on
+  /// a second axis from 1 to 5. Days without a check-in are left as gaps in the line.
+  pub async fn mood(
+    self,
+    days: &[MoodDay],
+    bar_color: (u8, u8, u8, u8),
+    light_mode: bool,
+  ) -> Result<Self> {
+    let moods = days
+      .iter()
+      .map(|day| day.mood.map_or(NIL_VALUE, f32::from))
+      .collect();
+
+    self
+      .minutes_with_line(
+        "Mood and Meditation",
+        &days
+          .iter()
+          .map(|day| (day.date, day.minutes))
+          .collect::<Vec<_>>(),
+        ("Mood", moods, Some(1.0), Some(5.0)),
+        bar_color,
+        light_mode,
+      )
+      .await
+  }
+
+  /// Draws a member's daily meditation minutes as bars, with the hours they slept as a line on
+  /// a second axis. Days without logged sleep are left as gaps in the line.
+  pub async fn sleep(
+    self,
+    days: &[SleepDay],
+    bar_color: (u8, u8, u8, u8),
+    light_mode: bool,
+  ) -> Result<Self> {
+    let hours = days
+      .iter()
+      .map(|day| day.hours.unwrap_or(NIL_VALUE))
+      .collect();
+
+    self
+      .minutes_with_line(
+        "Sleep and Meditation",
+        &days
+          .iter()
+          .map(|day| (day.date, day.minutes))
+          .collect::<Vec<_>>(),
+        ("Sleep (hours)", hours, Some(0.0), None),
+        bar_color,
+        light_mode,
+      )
+      .await
+  }
+
+  /// Draws daily meditation minutes as bars, with another daily measure as a line on a second
+  /// axis, given as its name, values, and optional minimum and maximum.
+  async fn minutes_with_line(
+    self,
+    title: &str,
+    days: &[(NaiveDate, i64)],
+    (line_name, line_values, line_min, line_max): (&str, Vec<f32>, Option<f32>, Option<f32>),
+    bar_color: (u8, u8, u8, u8),
+    light_mode: bool,
+  ) -> Result<Self> {
+    if days.is_empty() {
+      return Err(anyhow!("No days to draw chart"));
+    }
+
+    let x_labels = days
+      .iter()
+      .map(|(date, _)| date.format("%m/%d").to_string())
+      .collect();
+    let minutes = days.iter().map(|(_, minutes)| *minutes as f32).collect();
+
+    let mut bar_chart = Self::column_chart(
+      title.to_owned(),
+      "Minutes",
+      minutes,
+      x_labels,
+      bar_color,
+      light_mode,
+    );
+    bar_chart
+      .series_list
+      .push(Series::new(line_name.to_owned(), line_values));
+    bar_chart.series_list[1].category = Some(SeriesCategory::Line);
+    bar_chart.series_list[1].y_axis_index = 1;
+    bar_chart.series_list[1].label_show = false;
+    bar_chart.series_colors.push((88, 101, 242, 255).into());
+    bar_chart.series_smooth = true;
 
-    AsyncWriteExt::write_all(&mut self.file, &webp).await?;
+    let mut line_axis = bar_chart.y_axis_configs[0].clone();
+    line_axis.axis_min = line_min;
+    line_axis.axis_max = line_max;
+    if line_max.is_some() {
+      line_axis.axis_split_number = 4;
+    }
+    bar_chart.y_axis_configs.push(line_axis);
+
+    bar_chart.x_axis_name_rotate = 120.0;
+    bar_chart.x_axis_name_gap = 5.0;
+    bar_chart.legend_show = Some(true);
+    bar_chart.legend_category = LegendCategory::Rect;
+    bar_chart.legend_align = Align::Left;
+    bar_chart.legend_font_size = 16.0;
+    bar_chart.legend_font_color = bar_chart.x_axis_font_color;
+    bar_chart.legend_margin = Some(Box {
+      top: 10.0,
+      left: 0.0,
+      bottom: 30.0,
+      right: 0.0,
+    });
+
+    self.write_svg(&bar_chart.svg()?).await
+  }
+
+  /// A bar chart with a single series, styled like the stats charts.
+  fn column_chart(
+    title: String,
+    series_name: &str,
+    values: Vec<f32>,
+    x_labels: Vec<String>,
+    bar_color: (u8, u8, u8, u8),
+    light_mode: bool,
+  ) -> BarChart {
+    let mut bar_chart = BarChart::new(vec![Series::new(series_name.to_owned(), values)], x_labels);
+    bar_chart.height = 480.0;
+    bar_chart.width = 640.0;
+    bar_chart.margin = Box {
+      left: 15.0,
+      top: 15.0,
+      right: 35.0,
+      bottom: 15.0,
+    };
+    bar_chart.grid_stroke_width = 0.5;
+    bar_chart.legend_show = Some(false);
+    bar_chart.title_text = title;
+    bar_chart.title_font_size = 30.0;
+    bar_chart.title_height = 35.0;
+    bar_chart.title_margin = Some(Box {
+      left: 0.0,
+      top: 5.0,
+      right: 0.0,
+      bottom: 10.0,
+    });
+    bar_chart.x_boundary_gap = Some(true);
+    bar_chart.x_axis_font_size = 14.0;
+    bar_chart.x_axis_height = 40.0;
+    bar_chart.y_axis_configs[0].axis_font_size = 22.0;
+    bar_chart.y_axis_configs[0].axis_split_number = 7;
+    bar_chart.series_colors = vec![(bar_color.0, bar_color.1, bar_color.2, bar_color.3).into()];
+    bar_chart.series_list[0].label_show = false;
+
+    if light_mode {
+      bar_chart.background_color = (227, 229, 232).into();
+      bar_chart.grid_stroke_color = (140, 140, 140).into();
+      bar_chart.title_font_color = (30, 31, 34).into();
+      bar_chart.x_axis_font_color = (30, 31, 34).into();
+      bar_chart.x_axis_stroke_color = (30, 31, 34).into();
+      bar_chart.y_axis_configs[0].axis_font_color = (30, 31, 34).into();
+    } else {
+      bar_chart.background_color = (30, 31, 34).into();
+      bar_chart.grid_stroke_color = (185, 184, 206).into();
+      bar_chart.title_font_color = (216, 217, 218).into();
+      bar_chart.x_axis_font_color = (216, 217, 218).into();
+      bar_chart.x_axis_stroke_color = (185, 184, 206).into();
+      bar_chart.y_axis_configs[0].axis_font_color = (216, 217, 218).into();
+    }
+
+    bar_chart
+  }
+
+  /// Writes a chart to the file in the chart's format.
+  async fn write_svg(mut self, svg: &str) -> Result<Self> {
+    let image = encode(self.format, svg)?;
+
+    AsyncWriteExt::write_all(&mut self.file, &image).await?;
     AsyncWriteExt::flush(&mut self.file).await?;
 
     Ok(Self {
       file: self.file,
       path: self.path,
       filename: self.filename,
+      format: self.format,
     })
   }
 
@@ -372,15 +794,16 @@ impl<'a> Chart<'a> {
     }
 
     let svg = leaderboard.svg()?;
-    let webp = charts_rs::svg_to_webp(&svg)?;
+    let image = encode(self.format, &svg)?;
 
-    AsyncWriteExt::write_all(&mut self.file, &webp).await?;
+    AsyncWriteExt::write_all(&mut self.file, &image).await?;
     AsyncWriteExt::flush(&mut self.file).await?;
 
     Ok(Self {
       file: self.file,
       path: self.path,
       filename: self.filename,
+      format: self.format,
     })
   }
 
@@ -388,12 +811,62 @@ impl<'a> Chart<'a> {
     self.path.clone()
   }
 
+  pub fn filename(&self) -> &str {
+    self.filename
+  }
+
+  pub fn format(&self) -> ChartFormat {
+    self.format
+  }
+
   pub fn url(&self) -> String {
     format!("attachment://{}", self.filename)
   }
 
+  /// Reads the rendered chart, for uploading to object storage.
+  pub async fn bytes(&self) -> Result<Vec<u8>> {
+    Ok(fs::read(self.path()).await?)
+  }
+
   pub async fn remove(&self) -> Result<()> {
     fs::remove_file(self.path()).await?;
     Ok(())
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_scale_svg() -> Result<()> {
+    let svg = r#"<svg width="600" height="400" viewBox="0 0 600 400" xmlns="http://www.w3.org/2000/svg"><rect width="600" height="400"/></svg>"#;
+    assert_eq!(
+      scale_svg(svg, 2.0)?,
+      r#"<svg width="1200" height="800" viewBox="0 0 600 400" xmlns="http://www.w3.org/2000/svg"><rect width="600" height="400"/></svg>"#
+    );
+
+    let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" width="300.5" height="200"><g/></svg>"#;
+    assert_eq!(
+      scale_svg(svg, 2.0)?,
+      r#"<svg xmlns="http://www.w3.org/2000/svg" width="601" height="400" viewBox="0 0 300.5 200"><g/></svg>"#
+    );
+
+    assert!(scale_svg("<svg><g/></svg>", 2.0).is_err());
+    assert!(scale_svg("not an svg", 2.0).is_err());
+
+    Ok(())
+  }
+
+  #[test]
+  fn test_chart_format_from_db() {
+    for format in [
+      ChartFormat::Standard,
+      ChartFormat::HighRes,
+      ChartFormat::Svg,
+    ] {
+      assert_eq!(ChartFormat::from_db(format.as_str()), format);
+    }
+    assert_eq!(ChartFormat::from_db("unknown"), ChartFormat::Standard);
+  }
+}
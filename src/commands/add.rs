@@ -2,14 +2,15 @@ use std::time::Duration;
 
 use anyhow::{anyhow, Context as AnyhowContext, Result};
 use chrono::{Duration as ChronoDuration, Utc};
+use poise::serenity_prelude::Mentionable;
 use poise::serenity_prelude::{builder::*, ButtonStyle, ChannelId, ComponentInteractionCollector};
 use poise::CreateReply;
 
 use crate::commands::helpers::common::Visibility;
 use crate::commands::helpers::database::{self, MessageType};
 use crate::commands::helpers::time::{self, MinusOffsetChoice, PlusOffsetChoice};
-use crate::commands::helpers::tracking;
-use crate::config::{BloomBotEmbed, CHANNELS, EMOJI};
+use crate::commands::helpers::{threads, tracking};
+use crate::config::{BloomBotEmbed, CHANNELS};
 use crate::data::meditation::Meditation;
 use crate::data::tracking_profile::{privacy, Privacy, Status};
 use crate::database::DatabaseHandler;
@@ -40,6 +41,7 @@ pub async fn add(
   plus_offset: Option<PlusOffsetChoice>,
   #[description = "Set visibility of response (defaults to public)"] privacy: Option<Privacy>,
 ) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
   let data = ctx.data();
 
   let guild_id = ctx
@@ -49,16 +51,40 @@ pub async fn add(
 
   let mut transaction = data.db.start_transaction_with_retry(5).await?;
 
+  let settings = data.settings.get(&data.db, guild_id).await?;
+  if let Some(tracking_channel) = settings.tracking_channel {
+    // Threads and forum posts in the tracking channel count as the tracking channel
+    if !threads::is_in_channel(ctx, ctx.channel_id(), tracking_channel).await {
+      ctx
+        .send(
+          CreateReply::default()
+            .content(format!(
+              "{} Please use `/add` in {} to track your time.",
+              emoji.mminfo,
+              tracking_channel.mention()
+            ))
+            .ephemeral(true),
+        )
+        .await?;
+      return Ok(());
+    }
+  }
+
   let tracking_profile =
     DatabaseHandler::get_tracking_profile(&mut transaction, &guild_id, &user_id)
       .await?
       .unwrap_or_default();
 
   let privacy = privacy!(privacy, tracking_profile.tracking.privacy);
+  // Silent tracking posts nothing to the channel, not even the anonymized entry
+  let silent = privacy && tracking_profile.tracking.silent;
+  // Redacted entries are confirmed privately, with only a note of the session posted publicly
+  let redacted = tracking_profile.tracking.redact_minutes;
+  let private_reply = privacy || redacted;
 
   // Usually not necessary, but defer to avoid possible unknown interaction
   // errors due to slow DB lookups, workload redeployment, etc.
-  if privacy {
+  if private_reply {
     ctx.defer_ephemeral().await?;
   } else {
     ctx.defer().await?;
@@ -91,7 +117,8 @@ pub async fn add(
     _ => Utc::now() + ChronoDuration::minutes(i64::from(offset)),
   };
 
-  let meditation = Meditation::new(guild_id, user_id, minutes, seconds, &datetime);
+  let meditation = Meditation::new(guild_id, user_id, minutes, seconds, &datetime)
+    .channel(tracking::segment_channel(ctx, &settings, ctx.channel_id()).await);
 
   DatabaseHandler::add_meditation_entry(&mut transaction, &meditation).await?;
 
@@ -106,6 +133,7 @@ pub async fn add(
     &minutes,
     &user_sum,
     privacy,
+    redacted,
   )
   .await?;
 
@@ -121,7 +149,7 @@ pub async fn add(
           .content(format!(
             "Are you sure you want to add **{minutes}** minutes to your meditation time?"
           ))
-          .ephemeral(privacy)
+          .ephemeral(private_reply)
           .components(vec![CreateActionRow::Buttons(vec![
             CreateButton::new(confirm_id.clone())
               .label("Yes")
@@ -156,23 +184,23 @@ pub async fn add(
           ctx,
           CreateInteractionResponse::UpdateMessage({
             if confirm {
-              if privacy {
+              if private_reply {
                 CreateInteractionResponseMessage::new()
                   .content(format!(
                     "Added **{minutes} minutes** to your meditation time! Your total meditation time is now {user_sum} minutes :tada:"
                   ))
-                  .ephemeral(privacy)
+                  .ephemeral(private_reply)
                   .components(Vec::new())
               } else {
                 CreateInteractionResponseMessage::new()
                   .content(&response)
-                  .ephemeral(privacy)
+                  .ephemeral(private_reply)
                   .components(Vec::new())
               }
             } else {
               CreateInteractionResponseMessage::new()
                 .content("Cancelled.")
-                .ephemeral(privacy)
+                .ephemeral(private_reply)
                 .components(Vec::new())
             }
           }),
@@ -185,8 +213,8 @@ pub async fn add(
               Ok(()) => {}
               Err(e) => {
                 check.edit(ctx, CreateReply::default()
-                  .content(format!("{} A fatal error occurred while trying to save your changes. Please contact staff for assistance.", EMOJI.mminfo))
-                  .ephemeral(privacy)).await?;
+                  .content(format!("{} A fatal error occurred while trying to save your changes. Please contact staff for assistance.", emoji.mminfo))
+                  .ephemeral(private_reply)).await?;
                 return Err(anyhow!("Could not send message: {e}"));
               }
             }
@@ -195,15 +223,15 @@ pub async fn add(
         Err(e) => {
           check
             .edit(ctx, CreateReply::default()
-              .content(format!("{} An error may have occurred. If your command failed, please contact staff for assistance.", EMOJI.mminfo))
-                .ephemeral(privacy)
+              .content(format!("{} An error may have occurred. If your command failed, please contact staff for assistance.", emoji.mminfo))
+                .ephemeral(private_reply)
             )
             .await?;
           return Err(anyhow!("Could not send message: {e}"));
         }
       }
 
-      if confirm && privacy {
+      if confirm && private_reply && !silent {
         ctx
           .channel_id()
           .send_message(ctx, CreateMessage::new().content(response))
@@ -255,9 +283,15 @@ pub async fn add(
     0
   };
 
-  let guild_time_in_hours = tracking::get_guild_hours(&mut transaction, &guild_id).await?;
+  let update_leaderboards = tracking::leaderboards_due(&mut transaction, &guild_id).await?;
+  let guild_milestone = tracking::get_guild_milestone(
+    &mut transaction,
+    &guild_id,
+    i64::from(minutes + (seconds / 60)),
+  )
+  .await?;
 
-  if privacy {
+  if private_reply {
     let private_response = format!(
       "Added **{minutes} minutes** to your meditation time! Your total meditation time is now {user_sum} minutes :tada:"
     );
@@ -269,10 +303,12 @@ pub async fn add(
     )
     .await?;
 
-    ctx
-      .channel_id()
-      .send_message(ctx, CreateMessage::new().content(response))
-      .await?;
+    if !silent {
+      ctx
+        .channel_id()
+        .send_message(ctx, CreateMessage::new().content(response))
+        .await?;
+    }
   } else {
     database::commit_and_say(
       ctx,
@@ -283,16 +319,23 @@ pub async fn add(
     .await?;
   }
 
-  tracking::post_guild_hours(&ctx, &guild_time_in_hours).await?;
+  tracking::post_guild_milestone(&ctx, guild_milestone).await?;
 
   let member = guild_id.member(ctx, user_id).await?;
-  tracking::update_time_roles(&ctx, &member, user_sum, privacy).await?;
+  tracking::update_time_roles(&ctx, &member, &tracking_profile.profile, user_sum, privacy).await?;
   if tracking_profile.streak.status == Status::Enabled {
-    tracking::update_streak_roles(&ctx, &member, user_streak, privacy).await?;
+    tracking::update_streak_roles(
+      &ctx,
+      &member,
+      &tracking_profile.profile,
+      user_streak,
+      privacy,
+    )
+    .await?;
   }
 
   // Spawn a Tokio task to update leaderboards every 10th add
-  if guild_time_in_hours.is_some() {
+  if update_leaderboards {
     tokio::spawn(events::leaderboards::update(
       module_path!(),
       ctx.serenity_context().http.clone(),
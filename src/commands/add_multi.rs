@@ -0,0 +1,347 @@
+use anyhow::{Context as AnyhowContext, Result};
+use chrono::{Duration as ChronoDuration, NaiveDate, Utc};
+use poise::serenity_prelude::{CreateEmbedFooter, CreateMessage, Mentionable};
+use poise::CreateReply;
+
+use crate::commands::helpers::common::Visibility;
+use crate::commands::helpers::database::{self, MessageType};
+use crate::commands::helpers::{threads, tracking};
+use crate::config::BloomBotEmbed;
+use crate::data::meditation::Meditation;
+use crate::data::tracking_profile::{privacy, Privacy, Status};
+use crate::database::DatabaseHandler;
+use crate::events;
+use crate::Context;
+
+/// How far back sessions can be dated. Older sessions can be added by staff.
+const MAX_DAYS_BACK: i64 = 30;
+
+/// Parses the date of a session, relative to the user's local date. Accepts
+/// `YYYY-MM-DD`, `today` or `yesterday`, defaulting to today when omitted.
+fn parse_session_date(input: Option<&str>, today: NaiveDate) -> Result<NaiveDate, String> {
+  let date = match input.map(|input| input.trim().to_lowercase()).as_deref() {
+    None | Some("" | "today") => today,
+    Some("yesterday") => today - ChronoDuration::days(1),
+    Some(input) => NaiveDate::parse_from_str(input, "%Y-%m-%d")
+      .map_err(|_| format!("`{input}` is not a valid date. Please use the format YYYY-MM-DD."))?,
+  };
+
+  if date > today {
+    return Err(format!("{date} is in the future."));
+  }
+
+  if date < today - ChronoDuration::days(MAX_DAYS_BACK) {
+    return Err(format!(
+      "{date} is more than {MAX_DAYS_BACK} days ago. Please contact staff to add older sessions."
+    ));
+  }
+
+  Ok(date)
+}
+
+/// Pairs up the minutes and dates given for each session, skipping unused pairs.
+fn collect_sessions(
+  pairs: &[(Option<i32>, Option<String>)],
+  today: NaiveDate,
+) -> Result<Vec<(i32, NaiveDate)>, String> {
+  let mut sessions = Vec::new();
+
+  for (index, (minutes, date)) in pairs.iter().enumerate() {
+    let number = index + 1;
+    match minutes {
+      Some(minutes) => {
+        let date = parse_session_date(date.as_deref(), today)
+          .map_err(|e| format!("Session {number}: {e}"))?;
+        sessions.push((*minutes, date));
+      }
+      None if date.is_some() => {
+        return Err(format!(
+          "Session {number}: A date was given without any minutes."
+        ));
+      }
+      None => {}
+    }
+  }
+
+  Ok(sessions)
+}
+
+/// Add several meditation entries at once
+///
+/// Adds up to five meditation sessions at once, each with its own number of minutes and date. Useful for catching up on sessions you weren't able to add at the time, e.g., after a weekend away.
+///
+/// Dates can be given as YYYY-MM-DD, "today" or "yesterday", and default to today. Sessions from more than 30 days ago can be added by staff.
+///
+/// All sessions are added together, so if any session is invalid, none are added.
+#[poise::command(
+  slash_command,
+  category = "Meditation Tracking",
+  rename = "addmulti",
+  guild_only
+)]
+#[allow(clippy::too_many_arguments)]
+pub async fn add_multi(
+  ctx: Context<'_>,
+  #[description = "Number of minutes for the first session"]
+  #[min = 1]
+  #[max = 300]
+  minutes_1: i32,
+  #[description = "Date of the first session (YYYY-MM-DD, defaults to today)"] date_1: Option<
+    String,
+  >,
+  #[description = "Number of minutes for the second session"]
+  #[min = 1]
+  #[max = 300]
+  minutes_2: Option<i32>,
+  #[description = "Date of the second session (YYYY-MM-DD, defaults to today)"] date_2: Option<
+    String,
+  >,
+  #[description = "Number of minutes for the third session"]
+  #[min = 1]
+  #[max = 300]
+  minutes_3: Option<i32>,
+  #[description = "Date of the third session (YYYY-MM-DD, defaults to today)"] date_3: Option<
+    String,
+  >,
+  #[description = "Number of minutes for the fourth session"]
+  #[min = 1]
+  #[max = 300]
+  minutes_4: Option<i32>,
+  #[description = "Date of the fourth session (YYYY-MM-DD, defaults to today)"] date_4: Option<
+    String,
+  >,
+  #[description = "Number of minutes for the fifth session"]
+  #[min = 1]
+  #[max = 300]
+  minutes_5: Option<i32>,
+  #[description = "Date of the fifth session (YYYY-MM-DD, defaults to today)"] date_5: Option<
+    String,
+  >,
+  #[description = "Set visibility of response (defaults to public)"] privacy: Option<Privacy>,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let data = ctx.data();
+
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+  let user_id = ctx.author().id;
+
+  let mut transaction = data.db.start_transaction_with_retry(5).await?;
+
+  let settings = data.settings.get(&data.db, guild_id).await?;
+  if let Some(tracking_channel) = settings.tracking_channel {
+    // Threads and forum posts in the tracking channel count as the tracking channel
+    if !threads::is_in_channel(ctx, ctx.channel_id(), tracking_channel).await {
+      ctx
+        .send(
+          CreateReply::default()
+            .content(format!(
+              "{} Please use `/addmulti` in {} to track your time.",
+              emoji.mminfo,
+              tracking_channel.mention()
+            ))
+            .ephemeral(true),
+        )
+        .await?;
+      return Ok(());
+    }
+  }
+
+  let tracking_profile =
+    DatabaseHandler::get_tracking_profile(&mut transaction, &guild_id, &user_id)
+      .await?
+      .unwrap_or_default();
+
+  let privacy = privacy!(privacy, tracking_profile.tracking.privacy);
+  let silent = privacy && tracking_profile.tracking.silent;
+  // Redacted entries are confirmed privately, with only a note of the session posted publicly
+  let redacted = tracking_profile.tracking.redact_minutes;
+  let private_reply = privacy || redacted;
+
+  if private_reply {
+    ctx.defer_ephemeral().await?;
+  } else {
+    ctx.defer().await?;
+  }
+
+  let now = Utc::now() + ChronoDuration::minutes(i64::from(tracking_profile.utc_offset));
+  let today = now.date_naive();
+
+  let pairs = [
+    (Some(minutes_1), date_1),
+    (minutes_2, date_2),
+    (minutes_3, date_3),
+    (minutes_4, date_4),
+    (minutes_5, date_5),
+  ];
+
+  let sessions = match collect_sessions(&pairs, today) {
+    Ok(sessions) => sessions,
+    Err(e) => {
+      ctx
+        .send(
+          CreateReply::default()
+            .content(format!("{} {e} No sessions have been added.", emoji.mminfo))
+            .ephemeral(true),
+        )
+        .await?;
+      return Ok(());
+    }
+  };
+
+  let mut session_list = Vec::with_capacity(sessions.len());
+  let channel_id = tracking::segment_channel(ctx, &settings, ctx.channel_id()).await;
+
+  for (minutes, date) in &sessions {
+    // Sessions for today use the current time, as with `/add`. Earlier sessions are
+    // placed at midday so they fall on the intended date.
+    let datetime = if *date == today {
+      now
+    } else {
+      date
+        .and_hms_opt(12, 0, 0)
+        .with_context(|| "Failed to assign time to session date")?
+        .and_utc()
+    };
+
+    let meditation = Meditation::new(guild_id, user_id, *minutes, 0, &datetime).channel(channel_id);
+    DatabaseHandler::add_meditation_entry(&mut transaction, &meditation).await?;
+
+    session_list.push(format!(
+      "- **{minutes} minutes** on {}",
+      date.format("%B %d, %Y")
+    ));
+  }
+
+  let total_minutes: i32 = sessions.iter().map(|(minutes, _)| minutes).sum();
+  let session_count = sessions.len();
+  let title = if session_count == 1 {
+    "1 Session Added".to_owned()
+  } else {
+    format!("{session_count} Sessions Added")
+  };
+
+  let user_sum =
+    DatabaseHandler::get_user_meditation_sum(&mut transaction, &guild_id, &user_id).await?;
+
+  let response = tracking::show_add_with_quote(
+    &ctx,
+    &mut transaction,
+    &guild_id,
+    &user_id,
+    &total_minutes,
+    &user_sum,
+    privacy,
+    redacted,
+  )
+  .await?;
+
+  let user_streak = if tracking_profile.streak.status == Status::Enabled {
+    let streak = DatabaseHandler::get_streak(&mut transaction, &guild_id, &user_id).await?;
+    streak.current
+  } else {
+    0
+  };
+
+  let update_leaderboards = tracking::leaderboards_due(&mut transaction, &guild_id).await?;
+  let guild_milestone =
+    tracking::get_guild_milestone(&mut transaction, &guild_id, i64::from(total_minutes)).await?;
+
+  let sessions_embed = BloomBotEmbed::new()
+    .title(title)
+    .field("Sessions", session_list.join("\n"), false)
+    .footer(CreateEmbedFooter::new(format!(
+      "Total: {total_minutes} minutes"
+    )));
+
+  if private_reply {
+    let private_response = format!(
+      "Added **{total_minutes} minutes** to your meditation time! Your total meditation time is now {user_sum} minutes :tada:"
+    );
+    database::commit_and_say(
+      ctx,
+      transaction,
+      MessageType::EmbedOnly(Box::new(sessions_embed.description(private_response))),
+      Visibility::Ephemeral,
+    )
+    .await?;
+
+    if !silent {
+      ctx
+        .channel_id()
+        .send_message(ctx, CreateMessage::new().content(response))
+        .await?;
+    }
+  } else {
+    database::commit_and_say(
+      ctx,
+      transaction,
+      MessageType::EmbedOnly(Box::new(sessions_embed.description(response))),
+      Visibility::Public,
+    )
+    .await?;
+  }
+
+  tracking::post_guild_milestone(&ctx, guild_milestone).await?;
+
+  let member = guild_id.member(ctx, user_id).await?;
+  tracking::update_time_roles(&ctx, &member, &tracking_profile.profile, user_sum, privacy).await?;
+  if tracking_profile.streak.status == Status::Enabled {
+    tracking::update_streak_roles(
+      &ctx,
+      &member,
+      &tracking_profile.profile,
+      user_streak,
+      privacy,
+    )
+    .await?;
+  }
+
+  if update_leaderboards {
+    tokio::spawn(events::leaderboards::update(
+      module_path!(),
+      ctx.serenity_context().http.clone(),
+      data.db.clone(),
+      guild_id,
+    ));
+  }
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_collect_sessions() {
+    let today = NaiveDate::from_ymd_opt(2024, 10, 21).unwrap_or_default();
+    let yesterday = NaiveDate::from_ymd_opt(2024, 10, 20).unwrap_or_default();
+    let saturday = NaiveDate::from_ymd_opt(2024, 10, 19).unwrap_or_default();
+
+    let pairs = [
+      (Some(20), Some("2024-10-19".to_owned())),
+      (Some(30), Some("Yesterday".to_owned())),
+      (None, None),
+      (Some(15), None),
+      (None, None),
+    ];
+    assert_eq!(
+      collect_sessions(&pairs, today),
+      Ok(vec![(20, saturday), (30, yesterday), (15, today)])
+    );
+
+    let pairs = [(Some(20), None), (None, Some("2024-10-19".to_owned()))];
+    assert!(collect_sessions(&pairs, today).is_err());
+
+    let pairs = [(Some(20), Some("2024-10-22".to_owned()))];
+    assert!(collect_sessions(&pairs, today).is_err());
+
+    let pairs = [(Some(20), Some("2024-09-01".to_owned()))];
+    assert!(collect_sessions(&pairs, today).is_err());
+
+    let pairs = [(Some(20), Some("19/10/2024".to_owned()))];
+    assert!(collect_sessions(&pairs, today).is_err());
+  }
+}
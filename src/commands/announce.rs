@@ -0,0 +1,246 @@
+use anyhow::{Context as AnyhowContext, Result};
+use chrono::{Duration as ChronoDuration, NaiveDate, NaiveTime, Utc};
+use poise::serenity_prelude::{
+  ChannelType, FormattedTimestamp, FormattedTimestampStyle, GuildChannel, Mentionable,
+};
+use poise::{ApplicationContext, CreateReply, Modal};
+
+use crate::config::BloomBotEmbed;
+use crate::data::scheduled_announcement::{AnnouncementModal, ScheduledAnnouncement};
+use crate::database::DatabaseHandler;
+use crate::{Context, Data as AppData, Error as AppError};
+
+/// The most announcements a server can have scheduled at once, which is also the most
+/// `/announce list` can show in one embed.
+const MAX_PENDING: usize = 25;
+
+/// Commands for scheduling announcements
+///
+/// Commands to compose an announcement and schedule it to be posted in a channel later, list scheduled announcements, or cancel one.
+///
+/// Requires `Manage Messages` permissions.
+#[poise::command(
+  slash_command,
+  required_permissions = "MANAGE_MESSAGES",
+  default_member_permissions = "MANAGE_MESSAGES",
+  category = "Moderator Commands",
+  subcommands("schedule", "list", "cancel"),
+  subcommand_required,
+  guild_only
+)]
+#[allow(clippy::unused_async)]
+pub async fn announce(_: Context<'_>) -> Result<()> {
+  Ok(())
+}
+
+/// Schedule an announcement
+///
+/// Opens a form to compose an announcement, which is posted as an embed in the chosen channel at the given date and time. The date and time are in the UTC offset set with `/customize offset`, or UTC if none has been set. Announcements in announcement channels are published if the server has turned on `/config autopublish`.
+#[poise::command(slash_command)]
+async fn schedule(
+  ctx: ApplicationContext<'_, AppData, AppError>,
+  #[description = "The channel to post the announcement in"]
+  #[channel_types("Text", "News")]
+  channel: GuildChannel,
+  #[description = "The date to post it (YYYY-MM-DD)"] date: String,
+  #[description = "The time to post it (HH:MM, 24-hour)"] time: String,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  if !matches!(channel.kind, ChannelType::Text | ChannelType::News) || channel.guild_id != guild_id
+  {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} Please choose a text or announcement channel in this server.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let (Ok(date), Ok(time)) = (
+    NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d"),
+    NaiveTime::parse_from_str(time.trim(), "%H:%M"),
+  ) else {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} Please enter the date as YYYY-MM-DD and the time as HH:MM, e.g., `2024-11-02` and `18:30`.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  };
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  if DatabaseHandler::get_pending_announcements(&mut transaction, &guild_id)
+    .await?
+    .len()
+    >= MAX_PENDING
+  {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} This server already has {MAX_PENDING} announcements scheduled. Please cancel one with `/announce cancel` first.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let utc_offset =
+    DatabaseHandler::get_tracking_profile(&mut transaction, &guild_id, &ctx.author().id)
+      .await?
+      .unwrap_or_default()
+      .utc_offset;
+  drop(transaction);
+  let send_at = date.and_time(time).and_utc() - ChronoDuration::minutes(i64::from(utc_offset));
+
+  if send_at < Utc::now() {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} The announcement must be scheduled for the future.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let Some(modal) = AnnouncementModal::execute(ctx).await? else {
+    return Ok(());
+  };
+
+  let announcement =
+    ScheduledAnnouncement::new(guild_id, channel.id, modal, send_at, ctx.author().id);
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+  DatabaseHandler::add_scheduled_announcement(&mut transaction, &announcement).await?;
+  DatabaseHandler::commit_transaction(transaction).await?;
+
+  let preview = BloomBotEmbed::new()
+    .title(&announcement.title)
+    .description(&announcement.message);
+  ctx
+    .send(
+      CreateReply::default()
+        .content(format!(
+          "{} Announcement scheduled for {} in {}. ID: `{}`",
+          emoji.mmcheck,
+          FormattedTimestamp::new(send_at.into(), Some(FormattedTimestampStyle::LongDateTime)),
+          channel.id.mention(),
+          announcement.id
+        ))
+        .embed(preview)
+        .ephemeral(true),
+    )
+    .await?;
+
+  Ok(())
+}
+
+/// List scheduled announcements
+///
+/// Lists the announcements scheduled in this server which haven't been posted yet, soonest first.
+#[poise::command(slash_command)]
+async fn list(ctx: Context<'_>) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+  let announcements =
+    DatabaseHandler::get_pending_announcements(&mut transaction, &guild_id).await?;
+  drop(transaction);
+
+  if announcements.is_empty() {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} No announcements are scheduled. Schedule one with `/announce schedule`.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let embed = announcements.iter().take(MAX_PENDING).fold(
+    BloomBotEmbed::new().title("Scheduled Announcements"),
+    |embed, announcement| {
+      embed.field(
+        &announcement.title,
+        format!(
+          "{} in {}\nBy {} · ID: `{}`",
+          FormattedTimestamp::new(
+            announcement.send_at.into(),
+            Some(FormattedTimestampStyle::LongDateTime)
+          ),
+          announcement.channel_id.mention(),
+          announcement.created_by.mention(),
+          announcement.id
+        ),
+        false,
+      )
+    },
+  );
+
+  ctx
+    .send(CreateReply::default().embed(embed).ephemeral(true))
+    .await?;
+
+  Ok(())
+}
+
+/// Cancel a scheduled announcement
+///
+/// Cancels an announcement which hasn't been posted yet. Find its ID with `/announce list`.
+#[poise::command(slash_command)]
+async fn cancel(
+  ctx: Context<'_>,
+  #[description = "The ID of the announcement to cancel"] id: String,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+  let removed =
+    DatabaseHandler::remove_scheduled_announcement(&mut transaction, &guild_id, id.trim()).await?;
+  DatabaseHandler::commit_transaction(transaction).await?;
+
+  let content = if removed == 0 {
+    format!(
+      "{} No scheduled announcement with that ID was found. It may have already been posted.",
+      emoji.mminfo
+    )
+  } else {
+    format!("{} Announcement cancelled.", emoji.mmcheck)
+  };
+  ctx
+    .send(CreateReply::default().content(content).ephemeral(true))
+    .await?;
+
+  Ok(())
+}
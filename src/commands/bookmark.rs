@@ -1,12 +1,15 @@
-use anyhow::{Context as AnyhowContext, Result};
-use poise::serenity_prelude::Message;
+use std::time::Duration;
+
+use anyhow::Result;
+use poise::serenity_prelude::{builder::*, ButtonStyle, ComponentInteractionCollector, Message};
 use poise::Context as PoiseContext;
 use poise::{ApplicationContext, CreateReply, Modal};
+use sqlx::{Postgres, Transaction};
 
 use crate::commands::helpers::common::{self, Visibility};
 use crate::commands::helpers::database::{self, MessageType};
 use crate::commands::helpers::pagination::{PageRowRef, PageType, Paginator};
-use crate::config::{EMOJI, ENTRIES_PER_PAGE};
+use crate::config::ENTRIES_PER_PAGE;
 use crate::data::bookmark::Bookmark;
 use crate::database::DatabaseHandler;
 use crate::{Context, Data as AppData, Error as AppError};
@@ -22,29 +25,53 @@ struct AddBookmarkModal {
 
 /// Add a message to your bookmarks
 ///
-/// Adds a message to your bookmarks.
+/// Adds a message to your bookmarks. Messages can also be bookmarked in DMs with Bloom, such as course links, and are listed when using `/bookmark list` in DMs.
 ///
 /// To use, right-click the message that you want to bookmark, then go to "Apps" > "Add to Bookmarks".
 #[poise::command(
   ephemeral,
   context_menu_command = "Add to Bookmarks",
-  category = "Context Menu Commands",
-  guild_only
+  category = "Context Menu Commands"
 )]
 pub async fn add_bookmark(
   ctx: ApplicationContext<'_, AppData, AppError>,
   #[description = "Message to bookmark"] message: Message,
 ) -> Result<()> {
-  let guild_id = ctx
-    .guild_id()
-    .with_context(|| "Failed to retrieve guild ID from context")?;
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  // Bookmarks added in DMs, or in servers Bloom isn't in, are stored without a guild
+  let guild_id = common::bloom_guild_id(PoiseContext::Application(ctx));
   let user_id = ctx.author().id;
 
   let supporter = common::is_supporter(PoiseContext::Application(ctx)).await?;
 
   let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+  let link = message.link();
+
+  // Updating an existing bookmark doesn't count towards the limit
+  if let Some(existing) =
+    DatabaseHandler::get_bookmark_by_link(&mut transaction, &user_id, &link).await?
+  {
+    if let Some(bookmark) = AddBookmarkModal::execute_with_defaults(
+      ctx,
+      AddBookmarkModal {
+        description: existing.description.clone(),
+      },
+    )
+    .await?
+    {
+      offer_update(
+        PoiseContext::Application(ctx),
+        transaction,
+        existing,
+        bookmark.description,
+      )
+      .await?;
+    }
+    return Ok(());
+  }
+
   let bookmark_count =
-    DatabaseHandler::get_bookmark_count(&mut transaction, &guild_id, &user_id).await?;
+    DatabaseHandler::get_bookmark_count(&mut transaction, guild_id, &user_id).await?;
 
   if !supporter && bookmark_count > 19 {
     ctx
@@ -52,7 +79,7 @@ pub async fn add_bookmark(
         CreateReply::default()
           .content(format!(
             "{} Sorry, you've reached the bookmark limit. Please remove one and try again.\n-# Subscription-based supporters can add unlimited bookmarks. [Learn more.](<https://discord.com/channels/244917432383176705/1030424719138246667/1031137243345211413>)",
-            EMOJI.mminfo
+            emoji.mminfo
           ))
           .ephemeral(true),
       )
@@ -61,14 +88,14 @@ pub async fn add_bookmark(
   }
 
   if let Some(bookmark) = AddBookmarkModal::execute(ctx).await? {
-    let new_bookmark = Bookmark::new(guild_id, user_id, message.link(), bookmark.description);
+    let new_bookmark = Bookmark::new(guild_id, user_id, link, bookmark.description);
 
     DatabaseHandler::add_bookmark(&mut transaction, &new_bookmark).await?;
 
     database::commit_and_say(
       PoiseContext::Application(ctx),
       transaction,
-      MessageType::TextOnly(format!("{} Bookmark has been added.", EMOJI.mmcheck)),
+      MessageType::TextOnly(format!("{} Bookmark has been added.", emoji.mmcheck)),
       Visibility::Ephemeral,
     )
     .await?;
@@ -77,6 +104,107 @@ pub async fn add_bookmark(
   Ok(())
 }
 
+/// Handles adding a message which the user has already bookmarked. If the description
+/// differs from the existing one, the user is offered the option to update it, rather
+/// than adding the message to their bookmarks a second time.
+async fn offer_update(
+  ctx: Context<'_>,
+  mut transaction: Transaction<'_, Postgres>,
+  mut existing: Bookmark,
+  description: Option<String>,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  if description == existing.description {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} This message is already in your bookmarks.\n-# ID: {}",
+            emoji.mminfo,
+            existing.id()
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let current = match &existing.description {
+    Some(current) => format!("> {current}"),
+    None => "> -# No description".to_owned(),
+  };
+  let new = match &description {
+    Some(new) => format!("> {new}"),
+    None => "> -# No description".to_owned(),
+  };
+
+  let ctx_id = ctx.id();
+  let update_id = format!("{ctx_id}update");
+  let cancel_id = format!("{ctx_id}cancel");
+
+  ctx
+    .send(
+      CreateReply::default()
+        .content(format!(
+          "{} This message is already in your bookmarks. Would you like to update the description?\n\n**Current:**\n{current}\n**New:**\n{new}",
+          emoji.mminfo
+        ))
+        .ephemeral(true)
+        .components(vec![CreateActionRow::Buttons(vec![
+          CreateButton::new(update_id.clone())
+            .label("Update")
+            .style(ButtonStyle::Success),
+          CreateButton::new(cancel_id.clone())
+            .label("Cancel")
+            .style(ButtonStyle::Danger),
+        ])]),
+    )
+    .await?;
+
+  // Loop through incoming interactions with the buttons
+  while let Some(press) = ComponentInteractionCollector::new(ctx)
+    // We defined our button IDs to start with `ctx_id`. If they don't, some other command's
+    // button was pressed
+    .filter(move |press| press.data.custom_id.starts_with(&ctx_id.to_string()))
+    // Timeout when no button has been pressed in one minute
+    .timeout(Duration::from_secs(60))
+    .await
+  {
+    if press.data.custom_id != update_id && press.data.custom_id != cancel_id {
+      // This is an unrelated button interaction
+      continue;
+    }
+
+    let content = if press.data.custom_id == update_id {
+      existing.description = description;
+      DatabaseHandler::update_bookmark(&mut transaction, &existing).await?;
+      DatabaseHandler::commit_transaction(transaction).await?;
+
+      format!("{} Bookmark has been updated.", emoji.mmcheck)
+    } else {
+      format!(
+        "{} Cancelled. Your bookmark has not been changed.",
+        emoji.mminfo
+      )
+    };
+
+    press
+      .create_response(
+        ctx,
+        CreateInteractionResponse::UpdateMessage(
+          CreateInteractionResponseMessage::new()
+            .content(content)
+            .components(Vec::new()),
+        ),
+      )
+      .await?;
+
+    return Ok(());
+  }
+
+  Ok(())
+}
+
 /// Manage your bookmarks
 ///
 /// View your bookmarks or remove a bookmark from your list.
@@ -84,8 +212,7 @@ pub async fn add_bookmark(
   slash_command,
   category = "Informational",
   subcommands("list", "add", "remove", "search"),
-  subcommand_required,
-  guild_only
+  subcommand_required
 )]
 #[allow(clippy::unused_async)]
 pub async fn bookmark(_: PoiseContext<'_, AppData, AppError>) -> Result<()> {
@@ -94,20 +221,18 @@ pub async fn bookmark(_: PoiseContext<'_, AppData, AppError>) -> Result<()> {
 
 /// List your bookmarks
 ///
-/// View a list of your bookmarks.
+/// View a list of your bookmarks. When used in DMs, lists the bookmarks you've added in DMs. With Bloom added to your account, the same bookmarks are also listed in servers Bloom isn't in.
 #[poise::command(slash_command)]
 async fn list(
   ctx: Context<'_>,
   #[description = "The page to show"] page: Option<usize>,
 ) -> Result<()> {
-  let guild_id = ctx
-    .guild_id()
-    .with_context(|| "Failed to retrieve guild ID from context")?;
+  let guild_id = common::bloom_guild_id(ctx);
   let user_id = ctx.author().id;
 
   let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
 
-  let bookmarks = DatabaseHandler::get_bookmarks(&mut transaction, &guild_id, &user_id).await?;
+  let bookmarks = DatabaseHandler::get_bookmarks(&mut transaction, guild_id, &user_id).await?;
   let bookmarks: Vec<PageRowRef> = bookmarks
     .iter()
     .map(|bookmark| bookmark as PageRowRef)
@@ -133,16 +258,27 @@ async fn add(
   #[description = "Include a short description (optional)"]
   description: Option<String>,
 ) -> Result<()> {
-  let guild_id = ctx
-    .guild_id()
-    .with_context(|| "Failed to retrieve guild ID from context")?;
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = common::bloom_guild_id(ctx);
   let user_id = ctx.author().id;
 
   let supporter = common::is_supporter(ctx).await?;
 
   let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+  let link = message.link();
+
+  // Updating an existing bookmark doesn't count towards the limit
+  if let Some(existing) =
+    DatabaseHandler::get_bookmark_by_link(&mut transaction, &user_id, &link).await?
+  {
+    // Leaving out the description keeps the existing one
+    let description = description.or_else(|| existing.description.clone());
+    offer_update(ctx, transaction, existing, description).await?;
+    return Ok(());
+  }
+
   let bookmark_count =
-    DatabaseHandler::get_bookmark_count(&mut transaction, &guild_id, &user_id).await?;
+    DatabaseHandler::get_bookmark_count(&mut transaction, guild_id, &user_id).await?;
 
   if !supporter && bookmark_count > 19 {
     ctx
@@ -150,7 +286,7 @@ async fn add(
         CreateReply::default()
           .content(format!(
             "{} Sorry, you've reached the bookmark limit. Please remove one and try again.\n-# Subscription-based supporters can add unlimited bookmarks. [Learn more.](<https://discord.com/channels/244917432383176705/1030424719138246667/1031137243345211413>)",
-            EMOJI.mminfo
+            emoji.mminfo
           ))
           .ephemeral(true),
       )
@@ -158,14 +294,14 @@ async fn add(
     return Ok(());
   }
 
-  let new_bookmark = Bookmark::new(guild_id, user_id, message.link(), description);
+  let new_bookmark = Bookmark::new(guild_id, user_id, link, description);
 
   DatabaseHandler::add_bookmark(&mut transaction, &new_bookmark).await?;
 
   database::commit_and_say(
     ctx,
     transaction,
-    MessageType::TextOnly(format!("{} Bookmark has been added.", EMOJI.mmcheck)),
+    MessageType::TextOnly(format!("{} Bookmark has been added.", emoji.mmcheck)),
     Visibility::Ephemeral,
   )
   .await?;
@@ -181,21 +317,22 @@ async fn remove(
   ctx: Context<'_>,
   #[description = "The ID of the bookmark to remove"] id: String,
 ) -> Result<()> {
-  let guild_id = ctx
-    .guild_id()
-    .with_context(|| "Failed to retrieve guild ID from context")?;
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = common::bloom_guild_id(ctx);
 
+  let user_id = ctx.author().id;
   let bookmark_id = id.to_ascii_uppercase().clone();
 
   let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
 
   let result =
-    DatabaseHandler::remove_bookmark(&mut transaction, &guild_id, bookmark_id.as_str()).await?;
+    DatabaseHandler::remove_bookmark(&mut transaction, guild_id, &user_id, bookmark_id.as_str())
+      .await?;
   if result > 0 {
     database::commit_and_say(
       ctx,
       transaction,
-      MessageType::TextOnly(format!("{} Bookmark has been removed.", EMOJI.mmcheck)),
+      MessageType::TextOnly(format!("{} Bookmark has been removed.", emoji.mmcheck)),
       Visibility::Ephemeral,
     )
     .await?;
@@ -205,7 +342,7 @@ async fn remove(
         CreateReply::default()
           .content(format!(
             "{} Bookmark not found. Please verify the ID and try again.",
-            EMOJI.mminfo
+            emoji.mminfo
           ))
           .ephemeral(true),
       )
@@ -226,15 +363,14 @@ async fn search(
   #[description = "One or more keywords in search engine format"] keyword: String,
   #[description = "The page to show"] page: Option<usize>,
 ) -> Result<()> {
-  let guild_id = ctx
-    .guild_id()
-    .with_context(|| "Failed to retrieve guild ID from context")?;
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = common::bloom_guild_id(ctx);
   let user_id = ctx.author().id;
 
   let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
 
   let bookmarks =
-    DatabaseHandler::search_bookmarks(&mut transaction, &guild_id, &user_id, &keyword).await?;
+    DatabaseHandler::search_bookmarks(&mut transaction, guild_id, &user_id, &keyword).await?;
 
   if bookmarks.is_empty() {
     ctx
@@ -242,7 +378,7 @@ async fn search(
         CreateReply::default()
           .content(format!(
             "{} No bookmarks match your search query.",
-            EMOJI.mminfo
+            emoji.mminfo
           ))
           .ephemeral(true),
       )
@@ -6,7 +6,7 @@ use poise::serenity_prelude::{builder::*, RoleId};
 use poise::{ChoiceParameter, CreateReply};
 
 use crate::commands::helpers::time::ChallengeTimeframe;
-use crate::config::{BloomBotEmbed, EMOJI, ROLES};
+use crate::config::{BloomBotEmbed, ROLES};
 use crate::data::tracking_profile::{Privacy, Status};
 use crate::database::DatabaseHandler;
 use crate::Context;
@@ -43,6 +43,7 @@ async fn join(
     ChallengeChoices,
   >,
 ) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
   let guild_id = ctx
     .guild_id()
     .with_context(|| "Failed to retrieve guild ID from context")?;
@@ -103,7 +104,7 @@ async fn join(
         ctx
           .say(format!(
             "Awesome, <@{}>! You have successfully joined the 365-day challenge {}",
-            member.user.id, EMOJI.pepeglow,
+            member.user.id, emoji.pepeglow,
           ))
           .await?;
 
@@ -0,0 +1,57 @@
+use anyhow::{Context as AnyhowContext, Result};
+use chrono::{Duration as ChronoDuration, Utc};
+use poise::ChoiceParameter;
+
+use crate::commands::helpers::common::Visibility;
+use crate::commands::helpers::database::{self, MessageType};
+use crate::data::mood_checkin::{Mood, MoodCheckin};
+use crate::database::DatabaseHandler;
+use crate::Context;
+
+/// Check in with how calm you feel
+///
+/// Records how calm you feel today, from 1 (very unsettled) to 5 (very calm), with an optional note. Checking in again on the same day replaces your earlier check-in. Days are in the UTC offset set with `/customize offset`, or UTC if none has been set.
+///
+/// See how your mood relates to your meditation with `/stats mood`. Check-ins are private.
+#[poise::command(slash_command, category = "Meditation Tracking", guild_only)]
+pub async fn checkin(
+  ctx: Context<'_>,
+  #[description = "How calm you feel"] mood: Mood,
+  #[description = "A note about how you feel (Optional)"]
+  #[max_length = 500]
+  note: Option<String>,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+  let user_id = ctx.author().id;
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  let utc_offset = DatabaseHandler::get_tracking_profile(&mut transaction, &guild_id, &user_id)
+    .await?
+    .unwrap_or_default()
+    .utc_offset;
+  let today = (Utc::now() + ChronoDuration::minutes(i64::from(utc_offset))).date_naive();
+
+  let note = note
+    .map(|note| note.trim().to_owned())
+    .filter(|note| !note.is_empty());
+  let checkin = MoodCheckin::new(guild_id, user_id, today, mood, note);
+  DatabaseHandler::add_mood_checkin(&mut transaction, &checkin).await?;
+
+  database::commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(format!(
+      "{} Checked in for today: **{}**. Thank you for taking a moment to notice how you feel. See how your mood relates to your meditation with `/stats mood`.",
+      emoji.mmcheck,
+      mood.name()
+    )),
+    Visibility::Ephemeral,
+  )
+  .await?;
+
+  Ok(())
+}
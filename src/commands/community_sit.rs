@@ -7,10 +7,11 @@ use poise::serenity_prelude::{FormattedTimestamp, FormattedTimestampStyle};
 use poise::serenity_prelude::{Mentionable, RoleId, ScheduledEventStatus};
 use poise::CreateReply;
 
-use crate::config::{BloomBotEmbed, CHANNELS, EMOJI, ROLES};
+use crate::config::{BloomBotEmbed, CHANNELS, ROLES};
 use crate::Context;
 
 async fn is_helper(ctx: Context<'_>) -> Result<bool> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
   let community_sit_helper = RoleId::from(ROLES.community_sit_helper);
   let has_role = match ctx.author_member().await {
     Some(member) => member.roles.contains(&community_sit_helper),
@@ -23,7 +24,7 @@ async fn is_helper(ctx: Context<'_>) -> Result<bool> {
         CreateReply::default()
           .content(format!(
             "{} This command requires the {} role.",
-            EMOJI.mminfo,
+            emoji.mminfo,
             community_sit_helper.mention()
           ))
           .allowed_mentions(CreateAllowedMentions::new().empty_roles())
@@ -58,6 +59,7 @@ pub async fn community_sit(_: Context<'_>) -> Result<()> {
 /// Starts a scheduled community sit event.
 #[poise::command(slash_command)]
 async fn start(ctx: Context<'_>) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
   ctx.defer_ephemeral().await?;
   let guild_id = ctx
     .guild_id()
@@ -128,7 +130,7 @@ async fn start(ctx: Context<'_>) -> Result<()> {
               ctx,
               CreateInteractionResponse::UpdateMessage(
                 CreateInteractionResponseMessage::new()
-                  .content(format!("{} Event started. Enjoy your sit!", EMOJI.mminfo))
+                  .content(format!("{} Event started. Enjoy your sit!", emoji.mminfo))
                   .ephemeral(true)
                   .embeds(Vec::new())
                   .components(Vec::new()),
@@ -209,7 +211,7 @@ async fn start(ctx: Context<'_>) -> Result<()> {
       CreateReply::default()
         .content(format!(
           "{} No eligible community sit event found. Please try again within 15 minutes of starting time.",
-          EMOJI.mminfo
+          emoji.mminfo
         ))
         .ephemeral(true),
     )
@@ -223,6 +225,7 @@ async fn start(ctx: Context<'_>) -> Result<()> {
 /// Ends an active community sit event.
 #[poise::command(slash_command)]
 async fn end(ctx: Context<'_>) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
   ctx.defer_ephemeral().await?;
   let guild_id = ctx
     .guild_id()
@@ -292,7 +295,7 @@ async fn end(ctx: Context<'_>) -> Result<()> {
                 CreateInteractionResponseMessage::new()
                   .content(format!(
                     "{} Event ended. Thank you for your assistance!",
-                    EMOJI.mminfo
+                    emoji.mminfo
                   ))
                   .ephemeral(true)
                   .embeds(Vec::new())
@@ -374,7 +377,7 @@ async fn end(ctx: Context<'_>) -> Result<()> {
       CreateReply::default()
         .content(format!(
           "{} No active community sit event found.",
-          EMOJI.mminfo
+          emoji.mminfo
         ))
         .ephemeral(true),
     )
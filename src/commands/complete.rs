@@ -1,59 +1,97 @@
 use anyhow::Result;
 use poise::serenity_prelude::{ChannelId, CreateMessage};
+use poise::CreateReply;
 
-use crate::config::{BloomBotEmbed, CHANNELS, EMOJI};
+use crate::config::{BloomBotEmbed, CHANNELS};
 use crate::database::DatabaseHandler;
 use crate::Context;
 
+async fn reply(ctx: Context<'_>, content: String) -> Result<()> {
+  ctx
+    .send(CreateReply::default().content(content).ephemeral(true))
+    .await?;
+
+  Ok(())
+}
+
 /// Indicate that you have completed a course
 ///
 /// Indicates that you have completed a course.
 ///
 /// Marks the specified course as complete, removing the participant role and awarding the graduate role for that course.
+///
+/// Can be used in DMs or in the server, unless the course has been set to only allow completion in DMs.
 #[poise::command(
   slash_command,
   category = "Secret",
   rename = "coursecomplete",
-  hide_in_help,
-  dm_only
+  hide_in_help
 )]
 pub async fn complete(
   ctx: Context<'_>,
   #[description = "The course you have completed"] course_name: String,
 ) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  ctx.defer_ephemeral().await?;
+
   let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
 
-  let Some(course) =
-    DatabaseHandler::get_course_in_dm(&mut transaction, course_name.as_str()).await?
-  else {
-    ctx
-      .say(format!(
+  let course = match ctx.guild_id() {
+    Some(guild_id) => {
+      DatabaseHandler::get_course(&mut transaction, &guild_id, course_name.as_str()).await?
+    }
+    None => DatabaseHandler::get_course_in_dm(&mut transaction, course_name.as_str()).await?,
+  };
+
+  drop(transaction);
+
+  let Some(course) = course else {
+    reply(
+      ctx,
+      format!(
         "{} Course not found. Please contact server staff for assistance.",
-        EMOJI.mminfo
-      ))
-      .await?;
+        emoji.mminfo
+      ),
+    )
+    .await?;
     return Ok(());
   };
 
-  let guild_id = course.guild_id;
+  if ctx.guild_id().is_some() && course.dm_only_completion {
+    reply(
+      ctx,
+      format!(
+        "{} This course can only be completed in DMs. Please send me `/coursecomplete` in a DM instead.",
+        emoji.mminfo
+      ),
+    )
+    .await?;
+    return Ok(());
+  }
+
+  let guild_id = ctx.guild_id().unwrap_or(course.guild_id);
 
   if guild_id.to_guild_cached(&ctx).is_none() {
-    ctx
-      .say(format!(
+    reply(
+      ctx,
+      format!(
         "{} Can't retrieve server information. Please contact server staff for assistance.",
-        EMOJI.mminfo
-      ))
-      .await?;
+        emoji.mminfo
+      ),
+    )
+    .await?;
     return Ok(());
   }
 
   let Ok(member) = guild_id.member(ctx, ctx.author().id).await else {
-    ctx
-      .say(format!(
+    reply(
+      ctx,
+      format!(
         "{} You don't appear to be a member of the server. If I'm mistaken, please contact server staff for assistance.",
-        EMOJI.mminfo
-      ))
-      .await?;
+        emoji.mminfo
+      ),
+    )
+    .await?;
     return Ok(());
   };
 
@@ -62,12 +100,14 @@ pub async fn complete(
     .has_role(ctx, guild_id, course.participant_role)
     .await?
   {
-    ctx
-      .say(format!(
+    reply(
+      ctx,
+      format!(
         "{} You are not in the course: **{course_name}**.",
-        EMOJI.mminfo
-      ))
-      .await?;
+        emoji.mminfo
+      ),
+    )
+    .await?;
     return Ok(());
   }
 
@@ -76,23 +116,25 @@ pub async fn complete(
     .has_role(ctx, guild_id, course.graduate_role)
     .await?
   {
-    ctx
-      .say(format!(
+    reply(
+      ctx,
+      format!(
         "{} You have already claimed the graduate role for course: **{course_name}**.",
-        EMOJI.mminfo
-      ))
-      .await?;
+        emoji.mminfo
+      ),
+    )
+    .await?;
     return Ok(());
   }
 
   member.add_role(ctx, course.graduate_role).await?;
   member.remove_role(ctx, course.participant_role).await?;
 
-  ctx
-    .say(format!(
-      ":tada: Congrats! You are now a graduate of the course: **{course_name}**!"
-    ))
-    .await?;
+  reply(
+    ctx,
+    format!(":tada: Congrats! You are now a graduate of the course: **{course_name}**!"),
+  )
+  .await?;
 
   // Log completion in staff logs
   let log_embed = BloomBotEmbed::new()
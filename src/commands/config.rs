@@ -0,0 +1,1938 @@
+use anyhow::{Context as AnyhowContext, Result};
+use chrono::{NaiveTime, Utc};
+use log::{info, warn};
+use poise::serenity_prelude::{parse_emoji, ChannelType, GuildChannel, Mentionable};
+use poise::{ChoiceParameter, CreateReply};
+
+use crate::commands::helpers::announcements;
+use crate::commands::helpers::common::Visibility;
+use crate::commands::helpers::database::{self, MessageType};
+use crate::commands::helpers::tracking;
+use crate::data::calendar::CalendarFeed;
+use crate::data::guild_emoji::{EmojiName, GuildEmoji};
+use crate::data::guild_feature::{Feature, GuildFeature};
+use crate::data::recurring_post::{PostDay, RecurringPost};
+use crate::data::retention::RetentionPolicy;
+use crate::database::DatabaseHandler;
+use crate::events::{self, improved};
+use crate::Context;
+
+#[derive(ChoiceParameter)]
+enum MilestoneUnit {
+  #[name = "hours"]
+  Hours,
+  #[name = "minutes"]
+  Minutes,
+}
+
+#[derive(ChoiceParameter)]
+enum AnnouncementPart {
+  #[name = "title"]
+  Title,
+  #[name = "message"]
+  Message,
+  #[name = "image"]
+  Image,
+  #[name = "all"]
+  All,
+}
+
+#[derive(ChoiceParameter)]
+enum Greeting {
+  #[name = "welcome"]
+  Welcome,
+  #[name = "farewell"]
+  Farewell,
+  #[name = "both"]
+  Both,
+}
+
+/// Commands for configuring Bloom
+///
+/// Commands to configure server-wide settings for Bloom.
+///
+/// Requires `Administrator` permissions.
+#[poise::command(
+  slash_command,
+  required_permissions = "ADMINISTRATOR",
+  default_member_permissions = "ADMINISTRATOR",
+  category = "Admin Commands",
+  subcommands(
+    "quotes",
+    "search",
+    "tracking",
+    "segments",
+    "milestones",
+    "improved",
+    "sitnow",
+    "greetings",
+    "tickets",
+    "keyclaims",
+    "announcements",
+    "autopublish",
+    "globalstats",
+    "calendar",
+    "emoji",
+    "features",
+    "recurring",
+    "retention",
+    "reload"
+  ),
+  subcommand_required,
+  guild_only
+)]
+#[allow(clippy::unused_async)]
+pub async fn config(_: Context<'_>) -> Result<()> {
+  Ok(())
+}
+
+/// Turn quotes on tracking confirmations on or off
+///
+/// Turns the random quote included with `/add` and `/import` confirmations on or off for this server.
+#[poise::command(slash_command)]
+async fn quotes(
+  ctx: Context<'_>,
+  #[description = "Include a random quote when members add time"] enabled: bool,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  let settings = DatabaseHandler::get_guild_settings(&mut transaction, &guild_id)
+    .await?
+    .quotes_on_add(enabled);
+  DatabaseHandler::update_guild_settings(&mut transaction, &settings).await?;
+
+  database::commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(format!(
+      "{} Quotes on tracking confirmations have been turned {}.",
+      emoji.mmcheck,
+      if enabled { "on" } else { "off" }
+    )),
+    Visibility::Ephemeral,
+  )
+  .await?;
+
+  ctx.data().settings.invalidate(guild_id);
+
+  Ok(())
+}
+
+/// Set the relevance threshold for glossary searches
+///
+/// Sets the maximum distance score for `/glossary search` results in this server, from 0.05 to 1. Lower values return fewer, more relevant results. Defaults to 0.3.
+#[poise::command(slash_command)]
+async fn search(
+  ctx: Context<'_>,
+  #[description = "Maximum distance score for results (lower is stricter; defaults to 0.3)"]
+  #[min = 0.05]
+  #[max = 1.0]
+  threshold: f64,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  let settings = DatabaseHandler::get_guild_settings(&mut transaction, &guild_id)
+    .await?
+    .search_threshold(threshold);
+  DatabaseHandler::update_guild_settings(&mut transaction, &settings).await?;
+
+  database::commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(format!(
+      "{} Glossary search threshold has been set to {threshold}.",
+      emoji.mmcheck
+    )),
+    Visibility::Ephemeral,
+  )
+  .await?;
+
+  ctx.data().settings.invalidate(guild_id);
+
+  Ok(())
+}
+
+/// Set the official tracking channel and tracking hints
+///
+/// Sets the official tracking channel for this server. Once set, `/add` can only be used in the tracking channel or its threads, and members who use it elsewhere are pointed to the tracking channel instead. The tracking channel can also be a forum, in which case `/add` can be used in any of its posts.
+///
+/// Members who describe a session in a plain message in the tracking channel, such as "sat 20 minutes", get a brief reminder to use `/add`. These hints can be turned off separately.
+///
+/// Run without any options to show the current settings.
+#[poise::command(slash_command)]
+async fn tracking(
+  ctx: Context<'_>,
+  #[description = "The official tracking channel"]
+  #[channel_types("Text", "Forum")]
+  channel: Option<GuildChannel>,
+  #[description = "Remind members to use /add when they describe a session in a message"]
+  hints: Option<bool>,
+  #[description = "Stop restricting /add to a tracking channel"] remove_channel: Option<bool>,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  let mut settings = DatabaseHandler::get_guild_settings(&mut transaction, &guild_id).await?;
+
+  if channel.is_none() && hints.is_none() && remove_channel != Some(true) {
+    let channel = match settings.tracking_channel {
+      Some(channel_id) => channel_id.mention().to_string(),
+      None => "None (`/add` can be used in any channel)".to_owned(),
+    };
+
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} **Tracking channel**: {channel}\n**Tracking hints**: {}",
+            emoji.mminfo,
+            if settings.tracking_hints { "on" } else { "off" }
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  if channel.is_some() && remove_channel == Some(true) {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} Please either set a tracking channel or remove it, not both.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let mut changes = Vec::new();
+
+  if let Some(channel) = channel {
+    if !matches!(channel.kind, ChannelType::Text | ChannelType::Forum)
+      || channel.guild_id != guild_id
+    {
+      ctx
+        .send(
+          CreateReply::default()
+            .content(format!(
+              "{} The tracking channel must be a text or forum channel in this server.",
+              emoji.mminfo
+            ))
+            .ephemeral(true),
+        )
+        .await?;
+      return Ok(());
+    }
+
+    settings = settings.tracking_channel(Some(channel.id));
+    changes.push(format!(
+      "Tracking channel has been set to {}.",
+      channel.mention()
+    ));
+  } else if remove_channel == Some(true) {
+    settings = settings.tracking_channel(None);
+    changes.push("Tracking channel has been removed.".to_owned());
+  }
+
+  if let Some(hints) = hints {
+    settings = settings.tracking_hints(hints);
+    changes.push(format!(
+      "Tracking hints have been turned {}.",
+      if hints { "on" } else { "off" }
+    ));
+  }
+
+  DatabaseHandler::update_guild_settings(&mut transaction, &settings).await?;
+
+  database::commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(format!("{} {}", emoji.mmcheck, changes.join(" "))),
+    Visibility::Ephemeral,
+  )
+  .await?;
+
+  ctx.data().settings.invalidate(guild_id);
+
+  Ok(())
+}
+
+/// Set how often server meditation milestones are celebrated
+///
+/// Sets how often the server's collective meditation time is celebrated in the tracking channel, e.g., every 1,000 hours or every 100,000 minutes. Each milestone is only ever announced once. Defaults to every 1,000 hours.
+///
+/// Run without any options to show the current setting.
+#[poise::command(slash_command)]
+async fn milestones(
+  ctx: Context<'_>,
+  #[description = "Celebrate every time the server total reaches a multiple of this amount"]
+  #[min = 1]
+  interval: Option<i64>,
+  #[description = "The unit for the interval (defaults to hours)"] unit: Option<MilestoneUnit>,
+  #[description = "Turn milestone announcements off"] disable: Option<bool>,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  let settings = DatabaseHandler::get_guild_settings(&mut transaction, &guild_id).await?;
+
+  let disable = disable == Some(true);
+
+  if interval.is_some() && disable {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} Please either set a milestone interval or turn milestones off, not both.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let milestone_interval = match interval {
+    Some(interval) => match unit.unwrap_or(MilestoneUnit::Hours) {
+      MilestoneUnit::Hours => Some(interval.saturating_mul(60)),
+      MilestoneUnit::Minutes => Some(interval),
+    },
+    None if disable => None,
+    None => {
+      let current = match settings.milestone_interval {
+        Some(interval) => format!("every {}", tracking::format_milestone(interval)),
+        None => "off".to_owned(),
+      };
+
+      ctx
+        .send(
+          CreateReply::default()
+            .content(format!("{} **Milestones**: {current}", emoji.mminfo))
+            .ephemeral(true),
+        )
+        .await?;
+      return Ok(());
+    }
+  };
+
+  let settings = settings.milestone_interval(milestone_interval);
+  DatabaseHandler::update_guild_settings(&mut transaction, &settings).await?;
+
+  let message = match milestone_interval {
+    Some(interval) => format!(
+      "Milestones will be celebrated every {}.",
+      tracking::format_milestone(interval)
+    ),
+    None => "Milestone announcements have been turned off.".to_owned(),
+  };
+
+  database::commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(format!("{} {message}", emoji.mmcheck)),
+    Visibility::Ephemeral,
+  )
+  .await?;
+
+  ctx.data().settings.invalidate(guild_id);
+
+  Ok(())
+}
+
+/// Set the channel for the monthly "most improved" shout-out
+///
+/// Sets the channel where the members who increased their meditation time the most are celebrated at the start of each month. The first shout-out is posted at the start of next month. Members whose stats are private are not included.
+///
+/// Run without any options to show the current setting.
+#[poise::command(slash_command)]
+async fn improved(
+  ctx: Context<'_>,
+  #[description = "The channel to post the shout-out in"]
+  #[channel_types("Text")]
+  channel: Option<GuildChannel>,
+  #[description = "Turn the shout-out off"] disable: Option<bool>,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  let settings = DatabaseHandler::get_guild_settings(&mut transaction, &guild_id).await?;
+
+  let disable = disable == Some(true);
+
+  if channel.is_some() && disable {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} Please either set a channel or turn the shout-out off, not both.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let message = if let Some(channel) = channel {
+    if channel.kind != ChannelType::Text || channel.guild_id != guild_id {
+      ctx
+        .send(
+          CreateReply::default()
+            .content(format!(
+              "{} The shout-out channel must be a text channel in this server.",
+              emoji.mminfo
+            ))
+            .ephemeral(true),
+        )
+        .await?;
+      return Ok(());
+    }
+
+    let settings = settings.improved_channel(Some(channel.id));
+    DatabaseHandler::update_guild_settings(&mut transaction, &settings).await?;
+
+    // Skip the current month, so a shout-out for last month isn't posted part way through
+    // this one.
+    if let Some((_, _, this_month)) = improved::last_month_bounds(Utc::now()) {
+      DatabaseHandler::mark_improved_posted(&mut transaction, &guild_id, this_month).await?;
+    }
+
+    format!(
+      "The most improved shout-out will be posted in {} at the start of each month.",
+      channel.mention()
+    )
+  } else if disable {
+    let settings = settings.improved_channel(None);
+    DatabaseHandler::update_guild_settings(&mut transaction, &settings).await?;
+
+    "The most improved shout-out has been turned off.".to_owned()
+  } else {
+    let current = match settings.improved_channel {
+      Some(channel_id) => channel_id.mention().to_string(),
+      None => "off".to_owned(),
+    };
+
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} **Most improved shout-out**: {current}",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  };
+
+  database::commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(format!("{} {message}", emoji.mmcheck)),
+    Visibility::Ephemeral,
+  )
+  .await?;
+
+  ctx.data().settings.invalidate(guild_id);
+
+  Ok(())
+}
+
+/// Set the channel where `/sitnow` sits are announced
+///
+/// Sets the channel where sits started with `/sitnow` are announced, so others can join. When no channel is set, sits are announced in the channel where `/sitnow` is used.
+///
+/// Run without any options to show the current setting.
+#[poise::command(slash_command)]
+async fn sitnow(
+  ctx: Context<'_>,
+  #[description = "The channel to announce sits in"]
+  #[channel_types("Text")]
+  channel: Option<GuildChannel>,
+  #[description = "Announce sits where /sitnow is used instead"] remove: Option<bool>,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  let settings = DatabaseHandler::get_guild_settings(&mut transaction, &guild_id).await?;
+
+  let remove = remove == Some(true);
+
+  if channel.is_some() && remove {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} Please either set a channel or remove it, not both.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let message = if let Some(channel) = channel {
+    if channel.kind != ChannelType::Text || channel.guild_id != guild_id {
+      ctx
+        .send(
+          CreateReply::default()
+            .content(format!(
+              "{} The sit channel must be a text channel in this server.",
+              emoji.mminfo
+            ))
+            .ephemeral(true),
+        )
+        .await?;
+      return Ok(());
+    }
+
+    let settings = settings.sit_channel(Some(channel.id));
+    DatabaseHandler::update_guild_settings(&mut transaction, &settings).await?;
+
+    format!("Sits will be announced in {}.", channel.mention())
+  } else if remove {
+    let settings = settings.sit_channel(None);
+    DatabaseHandler::update_guild_settings(&mut transaction, &settings).await?;
+
+    "Sits will be announced in the channel where `/sitnow` is used.".to_owned()
+  } else {
+    let current = match settings.sit_channel {
+      Some(channel_id) => channel_id.mention().to_string(),
+      None => "wherever `/sitnow` is used".to_owned(),
+    };
+
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!("{} **Sit channel**: {current}", emoji.mminfo))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  };
+
+  database::commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(format!("{} {message}", emoji.mmcheck)),
+    Visibility::Ephemeral,
+  )
+  .await?;
+
+  ctx.data().settings.invalidate(guild_id);
+
+  Ok(())
+}
+
+/// Set up welcome and farewell messages
+///
+/// Sets the channel and messages used to welcome members when they join and bid farewell when they leave. Messages can include `{user}` for the member and `{member_count}` for the number of members in the server. Members who need to complete membership screening are welcomed once they have.
+///
+/// Welcomes are off until a welcome message is set. Farewells use a default message until one is set. Either can be turned off again at any time.
+///
+/// Run without any options to show the current settings.
+#[poise::command(slash_command)]
+async fn greetings(
+  ctx: Context<'_>,
+  #[description = "The channel to post welcome and farewell messages in"]
+  #[channel_types("Text")]
+  channel: Option<GuildChannel>,
+  #[description = "The message for new members, e.g., Welcome, {user}!"]
+  #[max_length = 1000]
+  welcome: Option<String>,
+  #[description = "The message for members who leave, e.g., Farewell, {user}"]
+  #[max_length = 1000]
+  farewell: Option<String>,
+  #[description = "Turn welcome or farewell messages off"] turn_off: Option<Greeting>,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let welcome = welcome
+    .map(|welcome| welcome.trim().to_owned())
+    .filter(|welcome| !welcome.is_empty());
+  let farewell = farewell
+    .map(|farewell| farewell.trim().to_owned())
+    .filter(|farewell| !farewell.is_empty());
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  let mut settings = DatabaseHandler::get_guild_settings(&mut transaction, &guild_id).await?;
+
+  if channel.is_none() && welcome.is_none() && farewell.is_none() && turn_off.is_none() {
+    let channel = match settings.greeting_channel {
+      Some(channel_id) => channel_id.mention().to_string(),
+      None => "None (greetings are off)".to_owned(),
+    };
+    let welcome = settings.welcome_message.as_deref().unwrap_or("off");
+    let farewell = settings.farewell_message.as_deref().unwrap_or("off");
+
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} **Greeting channel**: {channel}\n**Welcome**: {welcome}\n**Farewell**: {farewell}",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let turn_off_welcome = matches!(turn_off, Some(Greeting::Welcome | Greeting::Both));
+  let turn_off_farewell = matches!(turn_off, Some(Greeting::Farewell | Greeting::Both));
+
+  if (welcome.is_some() && turn_off_welcome) || (farewell.is_some() && turn_off_farewell) {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} Please either set a message or turn it off, not both.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let mut changes = Vec::new();
+
+  if let Some(channel) = channel {
+    if channel.kind != ChannelType::Text || channel.guild_id != guild_id {
+      ctx
+        .send(
+          CreateReply::default()
+            .content(format!(
+              "{} The greeting channel must be a text channel in this server.",
+              emoji.mminfo
+            ))
+            .ephemeral(true),
+        )
+        .await?;
+      return Ok(());
+    }
+
+    settings = settings.greeting_channel(Some(channel.id));
+    changes.push(format!(
+      "Greetings will be posted in {}.",
+      channel.mention()
+    ));
+  }
+
+  if let Some(welcome) = welcome {
+    settings = settings.welcome_message(Some(welcome));
+    changes.push("The welcome message has been set.".to_owned());
+  } else if turn_off_welcome {
+    settings = settings.welcome_message(None);
+    changes.push("Welcome messages have been turned off.".to_owned());
+  }
+
+  if let Some(farewell) = farewell {
+    settings = settings.farewell_message(Some(farewell));
+    changes.push("The farewell message has been set.".to_owned());
+  } else if turn_off_farewell {
+    settings = settings.farewell_message(None);
+    changes.push("Farewell messages have been turned off.".to_owned());
+  }
+
+  if settings.greeting_channel.is_none()
+    && (settings.welcome_message.is_some() || settings.farewell_message.is_some())
+  {
+    changes.push("Set a channel to start posting greetings.".to_owned());
+  }
+
+  DatabaseHandler::update_guild_settings(&mut transaction, &settings).await?;
+
+  database::commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(format!("{} {}", emoji.mmcheck, changes.join(" "))),
+    Visibility::Ephemeral,
+  )
+  .await?;
+
+  ctx.data().settings.invalidate(guild_id);
+
+  Ok(())
+}
+
+/// Set the channel for support tickets
+///
+/// Sets the channel where private threads are created for support tickets. Once set, members can open a ticket with `/ticket open` or by sending Bloom a DM. Bloom needs permission to create private threads in the channel.
+///
+/// Run without any options to show the current setting.
+#[poise::command(slash_command)]
+async fn tickets(
+  ctx: Context<'_>,
+  #[description = "The channel to create ticket threads in"]
+  #[channel_types("Text")]
+  channel: Option<GuildChannel>,
+  #[description = "Turn tickets off"] turn_off: Option<bool>,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  let settings = DatabaseHandler::get_guild_settings(&mut transaction, &guild_id).await?;
+
+  let turn_off = turn_off == Some(true);
+
+  if channel.is_some() && turn_off {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} Please either set a channel or turn tickets off, not both.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let message = if let Some(channel) = channel {
+    if channel.kind != ChannelType::Text || channel.guild_id != guild_id {
+      ctx
+        .send(
+          CreateReply::default()
+            .content(format!(
+              "{} The ticket channel must be a text channel in this server.",
+              emoji.mminfo
+            ))
+            .ephemeral(true),
+        )
+        .await?;
+      return Ok(());
+    }
+
+    let settings = settings.ticket_channel(Some(channel.id));
+    DatabaseHandler::update_guild_settings(&mut transaction, &settings).await?;
+
+    format!(
+      "Tickets are on. Ticket threads will be created in {}.",
+      channel.mention()
+    )
+  } else if turn_off {
+    let settings = settings.ticket_channel(None);
+    DatabaseHandler::update_guild_settings(&mut transaction, &settings).await?;
+
+    "Tickets are off. Open tickets can still be replied to and closed.".to_owned()
+  } else {
+    let current = match settings.ticket_channel {
+      Some(channel_id) => channel_id.mention().to_string(),
+      None => "None (tickets are off)".to_owned(),
+    };
+
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!("{} **Ticket channel**: {current}", emoji.mminfo))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  };
+
+  database::commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(format!("{} {message}", emoji.mmcheck)),
+    Visibility::Ephemeral,
+  )
+  .await?;
+
+  ctx.data().settings.invalidate(guild_id);
+
+  Ok(())
+}
+
+/// Set the channel where giveaway winners can claim their key
+///
+/// Sets the channel where giveaway winners who don't accept DMs are pinged, with a button to claim their Playne key privately. When no channel is set, staff are asked to hand out the key manually instead.
+///
+/// Run without any options to show the current setting.
+#[poise::command(slash_command)]
+async fn keyclaims(
+  ctx: Context<'_>,
+  #[description = "The channel to ping winners in"]
+  #[channel_types("Text")]
+  channel: Option<GuildChannel>,
+  #[description = "Hand out keys manually instead"] remove: Option<bool>,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  let settings = DatabaseHandler::get_guild_settings(&mut transaction, &guild_id).await?;
+
+  let remove = remove == Some(true);
+
+  if channel.is_some() && remove {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} Please either set a channel or remove it, not both.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let message = if let Some(channel) = channel {
+    if channel.kind != ChannelType::Text || channel.guild_id != guild_id {
+      ctx
+        .send(
+          CreateReply::default()
+            .content(format!(
+              "{} The key claim channel must be a text channel in this server.",
+              emoji.mminfo
+            ))
+            .ephemeral(true),
+        )
+        .await?;
+      return Ok(());
+    }
+
+    let settings = settings.key_claim_channel(Some(channel.id));
+    DatabaseHandler::update_guild_settings(&mut transaction, &settings).await?;
+
+    format!(
+      "Winners who don't accept DMs will be pinged in {} to claim their key.",
+      channel.mention()
+    )
+  } else if remove {
+    let settings = settings.key_claim_channel(None);
+    DatabaseHandler::update_guild_settings(&mut transaction, &settings).await?;
+
+    "Keys for winners who don't accept DMs will need to be handed out manually.".to_owned()
+  } else {
+    let current = match settings.key_claim_channel {
+      Some(channel_id) => channel_id.mention().to_string(),
+      None => "None (keys are handed out manually)".to_owned(),
+    };
+
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!("{} **Key claim channel**: {current}", emoji.mminfo))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  };
+
+  database::commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(format!("{} {message}", emoji.mmcheck)),
+    Visibility::Ephemeral,
+  )
+  .await?;
+
+  ctx.data().settings.invalidate(guild_id);
+
+  Ok(())
+}
+
+/// Customize the monthly challenge winner announcement
+///
+/// Sets the title, message, and image of the announcement posted when a monthly challenge winner is picked. The title and message can include `{user}` for the winner, `{month}` and `{year}` for the challenge, and `{minutes}` for the winner's meditation time.
+///
+/// Parts which aren't set use the default announcement, and can be reset to it at any time.
+///
+/// Run without any options to preview the current announcement.
+#[poise::command(slash_command)]
+async fn announcements(
+  ctx: Context<'_>,
+  #[description = "The title, e.g., :tada: {month} Challenge Winner :tada:"]
+  #[max_length = 200]
+  title: Option<String>,
+  #[description = "The message, e.g., Congratulations to {user} on {minutes} minutes!"]
+  #[max_length = 2000]
+  message: Option<String>,
+  #[description = "The URL of an image to show"]
+  #[max_length = 500]
+  image: Option<String>,
+  #[description = "Reset part of the announcement to the default"] reset: Option<AnnouncementPart>,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let title = title
+    .map(|title| title.trim().to_owned())
+    .filter(|title| !title.is_empty());
+  let message = message
+    .map(|message| message.trim().to_owned())
+    .filter(|message| !message.is_empty());
+  let image = image
+    .map(|image| image.trim().to_owned())
+    .filter(|image| !image.is_empty());
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  let mut settings = DatabaseHandler::get_guild_settings(&mut transaction, &guild_id).await?;
+
+  if title.is_none() && message.is_none() && image.is_none() && reset.is_none() {
+    let profile =
+      DatabaseHandler::get_tracking_profile(&mut transaction, &guild_id, &ctx.author().id)
+        .await?
+        .unwrap_or_default()
+        .profile;
+    let preview = announcements::winner_embed(&settings, ctx.author(), &profile, Utc::now(), 1234);
+
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} Here's a preview of the winner announcement, with you as the winner:",
+            emoji.mminfo
+          ))
+          .embed(preview)
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  if image
+    .as_ref()
+    .is_some_and(|image| !image.starts_with("https://"))
+  {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} The image must be a link starting with `https://`.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let mut changes = Vec::new();
+
+  match reset {
+    Some(AnnouncementPart::Title) => {
+      settings = settings.winner_title(None);
+      changes.push("The title has been reset.");
+    }
+    Some(AnnouncementPart::Message) => {
+      settings = settings.winner_message(None);
+      changes.push("The message has been reset.");
+    }
+    Some(AnnouncementPart::Image) => {
+      settings = settings.winner_image(None);
+      changes.push("The image has been removed.");
+    }
+    Some(AnnouncementPart::All) => {
+      settings = settings
+        .winner_title(None)
+        .winner_message(None)
+        .winner_image(None);
+      changes.push("The announcement has been reset to the default.");
+    }
+    None => {}
+  }
+
+  if title.is_some() {
+    settings = settings.winner_title(title);
+    changes.push("The title has been set.");
+  }
+
+  if message.is_some() {
+    settings = settings.winner_message(message);
+    changes.push("The message has been set.");
+  }
+
+  if image.is_some() {
+    settings = settings.winner_image(image);
+    changes.push("The image has been set.");
+  }
+
+  DatabaseHandler::update_guild_settings(&mut transaction, &settings).await?;
+
+  database::commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(format!(
+      "{} {} Run `/config announcements` without any options to preview it.",
+      emoji.mmcheck,
+      changes.join(" ")
+    )),
+    Visibility::Ephemeral,
+  )
+  .await?;
+
+  ctx.data().settings.invalidate(guild_id);
+
+  Ok(())
+}
+
+/// Turn channel segments on or off
+///
+/// Turns channel segments on or off. When on, meditation is recorded with the channel it was tracked in, so that `/stats range` and `/stats leaderboard` can be filtered by channel. This is useful for servers which run separate tracking communities in different channels, such as one per language.
+///
+/// Meditation tracked in a thread or forum post counts toward the channel it belongs to. Meditation tracked while segments were off, or imported, isn't included in channel stats.
+///
+/// Run without any options to show the current setting.
+#[poise::command(slash_command)]
+async fn segments(
+  ctx: Context<'_>,
+  #[description = "Record the channel meditation is tracked in"] enabled: Option<bool>,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  let settings = DatabaseHandler::get_guild_settings(&mut transaction, &guild_id).await?;
+
+  let Some(enabled) = enabled else {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} **Channel segments**: {}",
+            emoji.mminfo,
+            if settings.channel_segments {
+              "on"
+            } else {
+              "off"
+            }
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  };
+
+  let settings = settings.channel_segments(enabled);
+  DatabaseHandler::update_guild_settings(&mut transaction, &settings).await?;
+
+  let message = if enabled {
+    "Meditation will be recorded with the channel it's tracked in. Stats and leaderboards can now be filtered by channel."
+  } else {
+    "Meditation will no longer be recorded with the channel it's tracked in."
+  };
+
+  database::commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(format!("{} {message}", emoji.mmcheck)),
+    Visibility::Ephemeral,
+  )
+  .await?;
+
+  ctx.data().settings.invalidate(guild_id);
+
+  Ok(())
+}
+
+/// Turn auto-publishing of announcements on or off
+///
+/// Turns auto-publishing on or off. When on, announcements Bloom posts in announcement channels, such as monthly challenge winners, server milestones, and "most improved" shout-outs, are published so that servers following the channel receive them.
+///
+/// Run without any options to show the current setting.
+#[poise::command(slash_command)]
+async fn autopublish(
+  ctx: Context<'_>,
+  #[description = "Publish announcements automatically"] enabled: Option<bool>,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  let settings = DatabaseHandler::get_guild_settings(&mut transaction, &guild_id).await?;
+
+  let Some(enabled) = enabled else {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} **Auto-publish**: {}",
+            emoji.mminfo,
+            if settings.auto_publish { "on" } else { "off" }
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  };
+
+  let settings = settings.auto_publish(enabled);
+  DatabaseHandler::update_guild_settings(&mut transaction, &settings).await?;
+
+  let message = if enabled {
+    "Announcements in announcement channels will be published automatically."
+  } else {
+    "Announcements will no longer be published automatically."
+  };
+
+  database::commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(format!("{} {message}", emoji.mmcheck)),
+    Visibility::Ephemeral,
+  )
+  .await?;
+
+  ctx.data().settings.invalidate(guild_id);
+
+  Ok(())
+}
+
+/// Reset the calendar feed link
+///
+/// Replaces the link members use to subscribe to this server's events with `/event calendar`, such as if it has been shared publicly. The old link stops working, and members will need to subscribe again using the new link.
+#[poise::command(slash_command)]
+async fn calendar(ctx: Context<'_>) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  DatabaseHandler::add_calendar_feed(&mut transaction, &CalendarFeed::new(guild_id)).await?;
+
+  database::commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(format!(
+      "{} The calendar feed link has been reset. The old link no longer works, and members can get the new one with `/event calendar`.",
+      emoji.mmcheck
+    )),
+    Visibility::Ephemeral,
+  )
+  .await?;
+
+  Ok(())
+}
+
+/// Include this server in global stats
+///
+/// Turns global stats on or off. When on, this server's meditation counts toward the anonymous totals shown across all participating servers with `/stats global`, such as the minutes meditated today and the number of active meditators.
+///
+/// Only totals are shared. No names, server names, or per-server numbers are shown, members with private stats are always left out, and totals are only shown once enough servers take part. Global stats are off by default.
+///
+/// Run without any options to show the current setting.
+#[poise::command(slash_command)]
+async fn globalstats(
+  ctx: Context<'_>,
+  #[description = "Count this server's meditation in global stats"] enabled: Option<bool>,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  let settings = DatabaseHandler::get_guild_settings(&mut transaction, &guild_id).await?;
+
+  let Some(enabled) = enabled else {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} **Global stats**: {}",
+            emoji.mminfo,
+            if settings.global_stats { "on" } else { "off" }
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  };
+
+  let settings = settings.global_stats(enabled);
+  DatabaseHandler::update_guild_settings(&mut transaction, &settings).await?;
+
+  let message = if enabled {
+    "This server's meditation will count toward the anonymous totals in `/stats global`. Members with private stats are always left out."
+  } else {
+    "This server's meditation will no longer count toward global stats."
+  };
+
+  database::commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(format!("{} {message}", emoji.mmcheck)),
+    Visibility::Ephemeral,
+  )
+  .await?;
+
+  ctx.data().settings.invalidate(guild_id);
+
+  Ok(())
+}
+
+/// Whether `emoji` looks like a Unicode emoji, rather than text or a custom emoji.
+fn is_unicode_emoji(emoji: &str) -> bool {
+  !emoji.is_empty()
+    && emoji.chars().count() <= 10
+    && !emoji
+      .chars()
+      .any(|c| c.is_ascii_alphabetic() || c.is_whitespace() || matches!(c, '<' | '>' | ':'))
+}
+
+/// Replace the emoji Bloom uses in this server
+///
+/// Replaces one of the emoji Bloom uses in its messages in this server, with a Unicode emoji or a custom emoji from this server. Without replacements, Bloom uses Unicode emoji, except in Meditation Mind, where its custom emoji are used.
+///
+/// Run without any options to show the current emoji, or with only an emoji name to show that emoji.
+#[poise::command(slash_command)]
+async fn emoji(
+  ctx: Context<'_>,
+  #[description = "The emoji to replace"] name: Option<EmojiName>,
+  #[description = "The replacement, either a Unicode emoji or a custom emoji from this server"]
+  #[max_length = 100]
+  replacement: Option<String>,
+  #[description = "Go back to the default emoji"] reset: Option<bool>,
+) -> Result<()> {
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+  let emoji = ctx.data().emoji.get(Some(guild_id));
+
+  let Some(name) = name else {
+    let current = (0..)
+      .map_while(EmojiName::from_index)
+      .map(|name| format!("**{}**: {}", name.name(), emoji.get(name)))
+      .collect::<Vec<_>>()
+      .join("\n");
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} Emoji used in this server:\n{current}",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  };
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  let message = if reset.unwrap_or(false) {
+    DatabaseHandler::remove_guild_emoji(&mut transaction, &guild_id, name).await?;
+    format!("The **{}** emoji has been reset.", name.name())
+  } else if let Some(replacement) = replacement {
+    let replacement = replacement.trim().to_owned();
+
+    let is_valid = match parse_emoji(&replacement) {
+      Some(custom) => guild_id.emoji(ctx.http(), custom.id).await.is_ok(),
+      None => is_unicode_emoji(&replacement),
+    };
+    if !is_valid {
+      ctx
+        .send(
+          CreateReply::default()
+            .content(format!(
+              "{} The replacement must be a Unicode emoji or a custom emoji from this server.",
+              emoji.mminfo
+            ))
+            .ephemeral(true),
+        )
+        .await?;
+      return Ok(());
+    }
+
+    let guild_emoji = GuildEmoji::new(guild_id, name, replacement.clone());
+    DatabaseHandler::set_guild_emoji(&mut transaction, &guild_emoji).await?;
+    format!("The **{}** emoji is now {replacement}.", name.name())
+  } else {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} **{}**: {}",
+            emoji.mminfo,
+            name.name(),
+            emoji.get(name)
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  };
+
+  database::commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(format!("{} {message}", emoji.mmcheck)),
+    Visibility::Ephemeral,
+  )
+  .await?;
+
+  ctx.data().emoji.reload(&ctx.data().db, guild_id).await?;
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_is_unicode_emoji() {
+    assert!(is_unicode_emoji("✅"));
+    assert!(is_unicode_emoji("ℹ️"));
+    assert!(is_unicode_emoji("1️⃣"));
+    assert!(is_unicode_emoji("👩‍👩‍👧"));
+
+    assert!(!is_unicode_emoji(""));
+    assert!(!is_unicode_emoji("check"));
+    assert!(!is_unicode_emoji(":white_check_mark:"));
+    assert!(!is_unicode_emoji("<:mmcheck:1279517233877483601>"));
+  }
+}
+
+/// Turn parts of Bloom on or off
+///
+/// Turns one of Bloom's features on or off in this server. Features are on unless they've been turned off here or in Bloom's config file. Changes take effect immediately.
+///
+/// Run without any options to show which features are on.
+#[poise::command(slash_command)]
+async fn features(
+  ctx: Context<'_>,
+  #[description = "The feature to turn on or off"] feature: Option<Feature>,
+  #[description = "Whether the feature is on"] enabled: Option<bool>,
+) -> Result<()> {
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+  let emoji = ctx.data().emoji.get(Some(guild_id));
+  let features = &ctx.data().features;
+
+  let (Some(feature), Some(enabled)) = (feature, enabled) else {
+    let current = (0..)
+      .map_while(Feature::from_index)
+      .filter(|listed| feature.is_none_or(|feature| feature == *listed))
+      .map(|feature| {
+        format!(
+          "**{}**: {} ({})",
+          feature.name(),
+          if features.enabled(Some(guild_id), feature) {
+            "on"
+          } else {
+            "off"
+          },
+          feature.description()
+        )
+      })
+      .collect::<Vec<_>>()
+      .join("\n");
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} Features in this server:\n{current}",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  };
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  let guild_feature = GuildFeature::new(guild_id, feature, enabled);
+  DatabaseHandler::set_guild_feature(&mut transaction, &guild_feature).await?;
+
+  database::commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(format!(
+      "{} The **{}** feature has been turned {}.",
+      emoji.mmcheck,
+      feature.name(),
+      if enabled { "on" } else { "off" }
+    )),
+    Visibility::Ephemeral,
+  )
+  .await?;
+
+  features.reload(&ctx.data().db, guild_id).await?;
+
+  Ok(())
+}
+
+/// The most recurring posts a server can have.
+const MAX_RECURRING_POSTS: usize = 10;
+
+/// Suggests the names of this server's recurring posts which contain `partial`.
+async fn autocomplete_recurring(ctx: Context<'_>, partial: &str) -> impl Iterator<Item = String> {
+  let posts = match ctx.guild_id() {
+    Some(guild_id) => match ctx.data().db.start_transaction_with_retry(5).await {
+      Ok(mut transaction) => DatabaseHandler::get_recurring_posts(&mut transaction, &guild_id)
+        .await
+        .unwrap_or_default(),
+      Err(_) => Vec::new(),
+    },
+    None => Vec::new(),
+  };
+
+  let partial = partial.to_lowercase();
+  posts
+    .into_iter()
+    .map(|post| post.name)
+    .filter(move |name| name.to_lowercase().contains(&partial))
+}
+
+/// Manage recurring posts
+///
+/// Commands to add, list, or remove messages Bloom posts on a schedule, such as a weekly check-in thread or a Friday gratitude prompt.
+#[poise::command(
+  slash_command,
+  subcommands("recurring_add", "recurring_list", "recurring_remove"),
+  subcommand_required
+)]
+#[allow(clippy::unused_async)]
+async fn recurring(_: Context<'_>) -> Result<()> {
+  Ok(())
+}
+
+/// Add a recurring post
+///
+/// Adds a message which Bloom posts in a channel every day, or on one day each week. The time is in the UTC offset set with `/customize offset`, or UTC if none has been set. The message and thread name can include `{date}` for the date of the post, e.g., Friday, November 8.
+///
+/// Give a thread name to open a thread on each post, such as for a weekly check-in.
+#[poise::command(slash_command, rename = "add")]
+async fn recurring_add(
+  ctx: Context<'_>,
+  #[description = "A name for the recurring post, used to remove it"]
+  #[max_length = 50]
+  name: String,
+  #[description = "The channel to post in"]
+  #[channel_types("Text", "News")]
+  channel: GuildChannel,
+  #[description = "Which days to post on"] day: PostDay,
+  #[description = "The time to post (HH:MM, 24-hour)"] time: String,
+  #[description = "The message to post, which can include {date}"]
+  #[max_length = 2000]
+  message: String,
+  #[description = "Open a thread on each post with this name, which can include {date}"]
+  #[max_length = 100]
+  thread_name: Option<String>,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  if !matches!(channel.kind, ChannelType::Text | ChannelType::News) || channel.guild_id != guild_id
+  {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} Please choose a text or announcement channel in this server.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let Ok(time) = NaiveTime::parse_from_str(time.trim(), "%H:%M") else {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} Please enter the time as HH:MM, e.g., `18:30`.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  };
+
+  let name = name.trim().to_owned();
+  let message = message.trim().to_owned();
+  let thread_name = thread_name
+    .map(|thread_name| thread_name.trim().to_owned())
+    .filter(|thread_name| !thread_name.is_empty());
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  let existing = DatabaseHandler::get_recurring_posts(&mut transaction, &guild_id).await?;
+  let problem = if existing
+    .iter()
+    .any(|post| post.name.eq_ignore_ascii_case(&name))
+  {
+    Some(format!(
+      "A recurring post called **{name}** already exists. Please choose another name."
+    ))
+  } else if existing.len() >= MAX_RECURRING_POSTS {
+    Some(format!(
+      "This server already has {MAX_RECURRING_POSTS} recurring posts. Please remove one with `/config recurring remove` first."
+    ))
+  } else if message.is_empty() {
+    Some("Please enter a message to post.".to_owned())
+  } else {
+    None
+  };
+  if let Some(problem) = problem {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!("{} {problem}", emoji.mminfo))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let utc_offset =
+    DatabaseHandler::get_tracking_profile(&mut transaction, &guild_id, &ctx.author().id)
+      .await?
+      .unwrap_or_default()
+      .utc_offset;
+
+  let post = RecurringPost::new(
+    guild_id,
+    channel.id,
+    name,
+    message,
+    thread_name,
+    day,
+    time,
+    utc_offset,
+    ctx.author().id,
+  );
+  DatabaseHandler::add_recurring_post(&mut transaction, &post).await?;
+
+  database::commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(format!(
+      "{} **{}** will be posted in {}. Schedule: {}. The first post is <t:{}:R>.",
+      emoji.mmcheck,
+      post.name,
+      channel.mention(),
+      post.schedule(),
+      post.next_post_at.timestamp()
+    )),
+    Visibility::Ephemeral,
+  )
+  .await?;
+
+  Ok(())
+}
+
+/// List recurring posts
+///
+/// Lists this server's recurring posts, with when each is next posted.
+#[poise::command(slash_command, rename = "list")]
+async fn recurring_list(ctx: Context<'_>) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+  let posts = DatabaseHandler::get_recurring_posts(&mut transaction, &guild_id).await?;
+  drop(transaction);
+
+  let content = if posts.is_empty() {
+    format!(
+      "{} No recurring posts have been set up. Add one with `/config recurring add`.",
+      emoji.mminfo
+    )
+  } else {
+    let posts = posts
+      .iter()
+      .map(|post| {
+        let thread = post
+          .thread_name
+          .as_ref()
+          .map(|thread_name| format!(", opening the thread **{thread_name}**"))
+          .unwrap_or_default();
+        format!(
+          "**{}**: {} in {}{thread}. Next post <t:{}:R>.",
+          post.name,
+          post.schedule(),
+          post.channel_id.mention(),
+          post.next_post_at.timestamp()
+        )
+      })
+      .collect::<Vec<_>>()
+      .join("\n");
+    format!("{} Recurring posts in this server:\n{posts}", emoji.mminfo)
+  };
+
+  ctx
+    .send(CreateReply::default().content(content).ephemeral(true))
+    .await?;
+
+  Ok(())
+}
+
+/// Remove a recurring post
+///
+/// Removes a recurring post, so it's no longer posted. Posts which have already been made aren't affected.
+#[poise::command(slash_command, rename = "remove")]
+async fn recurring_remove(
+  ctx: Context<'_>,
+  #[description = "The recurring post to remove"]
+  #[autocomplete = "autocomplete_recurring"]
+  name: String,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+  let removed =
+    DatabaseHandler::remove_recurring_post(&mut transaction, &guild_id, name.trim()).await?;
+
+  database::commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(if removed == 0 {
+      format!(
+        "{} No recurring post called **{name}** was found.",
+        emoji.mminfo
+      )
+    } else {
+      format!(
+        "{} **{name}** has been removed and will no longer be posted.",
+        emoji.mmcheck
+      )
+    }),
+    Visibility::Ephemeral,
+  )
+  .await?;
+
+  Ok(())
+}
+
+/// Manage data retention
+///
+/// Commands to choose how long this server's data is kept. Bloom keeps everything unless a retention policy says otherwise, and the policy is applied once a day.
+///
+/// New policies start as a dry run, which only reports what would be removed in the log channel, so you can check the reports before any data is removed.
+#[poise::command(
+  slash_command,
+  subcommands(
+    "retention_show",
+    "retention_set",
+    "retention_preview",
+    "retention_clear"
+  ),
+  subcommand_required
+)]
+#[allow(clippy::unused_async)]
+async fn retention(_: Context<'_>) -> Result<()> {
+  Ok(())
+}
+
+/// Describes each part of a retention policy, for `/config retention show` and `set`.
+fn describe_policy(policy: &RetentionPolicy) -> String {
+  let mut description = format!(
+    "**Erase records:** {}\n**Starboard posts for deleted messages:** {}\n**Meditation entries:** {}\n**Dry run:** {}",
+    policy
+      .erase_days
+      .map_or_else(|| "Kept indefinitely".to_owned(), |days| format!("Removed after {days} days")),
+    if policy.prune_star_messages { "Removed" } else { "Kept" },
+    policy.archive_years.map_or_else(
+      || "Kept indefinitely".to_owned(),
+      |years| format!("Archived after {years} years")
+    ),
+    if policy.dry_run {
+      "On, nothing will be removed"
+    } else {
+      "Off"
+    },
+  );
+  if let Some(last_run_at) = policy.last_run_at {
+    description.push_str(&format!(
+      "\n**Last run:** <t:{}:R>",
+      last_run_at.timestamp()
+    ));
+  }
+
+  description
+}
+
+/// Show the retention policy
+///
+/// Shows how long this server's data is kept, and when the policy was last applied.
+#[poise::command(slash_command, rename = "show")]
+async fn retention_show(ctx: Context<'_>) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+  let policy = DatabaseHandler::get_retention_policy(&mut transaction, &guild_id).await?;
+  drop(transaction);
+
+  let content = match policy {
+    Some(policy) => format!(
+      "{} **Retention policy**\n{}",
+      emoji.mminfo,
+      describe_policy(&policy)
+    ),
+    None => format!(
+      "{} This server has no retention policy, so all data is kept. Set one with `/config retention set`.",
+      emoji.mminfo
+    ),
+  };
+
+  ctx
+    .send(CreateReply::default().content(content).ephemeral(true))
+    .await?;
+
+  Ok(())
+}
+
+/// Set the retention policy
+///
+/// Changes how long this server's data is kept. Only the options given are changed.
+///
+/// Erase records can be removed after a number of days, and starboard posts can be removed when the starred message has been deleted. Meditation entries can be moved to an archive after a number of years. Archived entries are kept, but no longer count toward stats, streaks, or leaderboards.
+///
+/// Set a number of days or years to 0 to keep that data indefinitely. New policies start as a dry run, which only reports what would be removed.
+#[poise::command(slash_command, rename = "set")]
+async fn retention_set(
+  ctx: Context<'_>,
+  #[description = "Remove erase records after this many days (0 keeps them)"]
+  #[min = 0]
+  #[max = 3650]
+  erase_days: Option<i32>,
+  #[description = "Remove starboard posts when the starred message has been deleted"]
+  star_messages: Option<bool>,
+  #[description = "Archive meditation entries after this many years (0 keeps them)"]
+  #[min = 0]
+  #[max = 20]
+  archive_years: Option<i32>,
+  #[description = "Only report what would be removed, without removing anything"] dry_run: Option<
+    bool,
+  >,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  let mut policy = DatabaseHandler::get_retention_policy(&mut transaction, &guild_id)
+    .await?
+    .unwrap_or_else(|| RetentionPolicy::new(guild_id));
+  if let Some(erase_days) = erase_days {
+    policy = policy.erase_days(Some(erase_days).filter(|days| *days > 0));
+  }
+  if let Some(star_messages) = star_messages {
+    policy = policy.prune_star_messages(star_messages);
+  }
+  if let Some(archive_years) = archive_years {
+    policy = policy.archive_years(Some(archive_years).filter(|years| *years > 0));
+  }
+  if let Some(dry_run) = dry_run {
+    policy = policy.dry_run(dry_run);
+  }
+
+  DatabaseHandler::update_retention_policy(&mut transaction, &policy).await?;
+
+  let mut message = format!(
+    "{} Retention policy updated.\n{}",
+    emoji.mmcheck,
+    describe_policy(&policy)
+  );
+  if !policy.dry_run && !policy.is_empty() {
+    message.push_str("\n\nThis policy will remove data the next time it's applied. Use `/config retention preview` to see what will be removed.");
+  }
+
+  database::commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(message),
+    Visibility::Ephemeral,
+  )
+  .await?;
+
+  Ok(())
+}
+
+/// Preview the retention policy
+///
+/// Shows what the retention policy would remove or archive if it were applied now, without changing anything.
+#[poise::command(slash_command, rename = "preview")]
+async fn retention_preview(ctx: Context<'_>) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+  let policy = DatabaseHandler::get_retention_policy(&mut transaction, &guild_id).await?;
+  drop(transaction);
+
+  let Some(policy) = policy.filter(|policy| !policy.is_empty()) else {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} This server's retention policy keeps all data, so nothing would be removed.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  };
+
+  // Checking starred messages can take a while
+  ctx.defer_ephemeral().await?;
+
+  let policy = policy.dry_run(true);
+  let report = events::retention::preview(ctx.http(), &ctx.data().db, &policy).await?;
+
+  ctx
+    .send(
+      CreateReply::default()
+        .content(format!(
+          "{} If the retention policy were applied now:\n{}",
+          emoji.mminfo,
+          report.describe(&policy)
+        ))
+        .ephemeral(true),
+    )
+    .await?;
+
+  Ok(())
+}
+
+/// Remove the retention policy
+///
+/// Removes this server's retention policy, so all data is kept from now on. Data which has already been removed or archived isn't restored.
+#[poise::command(slash_command, rename = "clear")]
+async fn retention_clear(ctx: Context<'_>) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  let message = if DatabaseHandler::remove_retention_policy(&mut transaction, &guild_id).await? {
+    format!(
+      "{} Retention policy removed. All data will be kept from now on.",
+      emoji.mmcheck
+    )
+  } else {
+    format!(
+      "{} This server has no retention policy, so all data is already kept.",
+      emoji.mminfo
+    )
+  };
+
+  database::commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(message),
+    Visibility::Ephemeral,
+  )
+  .await?;
+
+  Ok(())
+}
+
+/// Reload Bloom's config file
+///
+/// Re-reads Bloom's config file (`bloombot.toml`) without restarting. Feature defaults, cooldowns, chart themes, and quiet hours take effect immediately. OpenAI settings require a restart. If the file isn't valid, the current settings are kept.
+#[poise::command(slash_command)]
+async fn reload(ctx: Context<'_>) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let bot_config = &ctx.data().bot_config;
+
+  let message = match bot_config.reload() {
+    Ok(config) => {
+      config
+        .rate_limits
+        .apply(&ctx.framework().options().commands);
+      info!("Reloaded config file {}", bot_config.path().display());
+      format!(
+        "{} Config file `{}` has been reloaded.",
+        emoji.mmcheck,
+        bot_config.path().display()
+      )
+    }
+    Err(e) => {
+      warn!("Error reloading config file: {e:?}");
+      format!(
+        "{} The config file could not be reloaded, so the current settings have been kept: {e:#}",
+        emoji.mmx
+      )
+    }
+  };
+
+  ctx
+    .send(CreateReply::default().content(message).ephemeral(true))
+    .await?;
+
+  Ok(())
+}
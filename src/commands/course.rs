@@ -3,17 +3,18 @@ use poise::serenity_prelude::{builder::*, ChannelId};
 use poise::CreateReply;
 
 use crate::commands::helpers::courses;
-use crate::config::{BloomBotEmbed, CHANNELS, EMOJI};
+use crate::config::{BloomBotEmbed, CHANNELS};
+use crate::data::course::Enrollment;
 use crate::database::DatabaseHandler;
 use crate::Context;
 
 /// Manage your course enrollments
 ///
-/// Join or leave a Meditation Mind course.
+/// Join or leave a Meditation Mind course, or enroll in a course with an enrollment code.
 #[poise::command(
   slash_command,
   category = "Utilities",
-  subcommands("join", "leave"),
+  subcommands("join", "enroll", "leave"),
   guild_only
 )]
 #[allow(clippy::unused_async)]
@@ -31,6 +32,7 @@ async fn join(
   #[rename = "course"]
   course_name: Option<String>,
 ) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
   ctx.defer_ephemeral().await?;
 
   let guild_id = ctx
@@ -59,7 +61,7 @@ async fn join(
         CreateReply::default()
           .content(format!(
             "{} You are already enrolled in the course: **{course_name}**.",
-            EMOJI.mminfo
+            emoji.mminfo
           ))
           .ephemeral(true),
       )
@@ -74,7 +76,7 @@ async fn join(
         CreateReply::default()
           .content(format!(
             "{} You have already completed the course: **{course_name}**.",
-            EMOJI.mminfo
+            emoji.mminfo
           ))
           .ephemeral(true),
       )
@@ -89,7 +91,7 @@ async fn join(
         CreateReply::default()
           .content(format!(
             "{} Failed to add the course role. Please try again or contact staff for assistance.",
-            EMOJI.mminfo
+            emoji.mminfo
           ))
           .ephemeral(true),
       )
@@ -138,6 +140,158 @@ async fn join(
   Ok(())
 }
 
+/// Enroll in a course with an enrollment code
+///
+/// Enroll in a Meditation Mind course using an enrollment code provided by staff.
+#[poise::command(slash_command)]
+async fn enroll(
+  ctx: Context<'_>,
+  #[description = "Your enrollment code"] code: String,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  ctx.defer_ephemeral().await?;
+
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let code = code.trim();
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+  let Some(enrollment_code) =
+    DatabaseHandler::get_enrollment_code(&mut transaction, &guild_id, code).await?
+  else {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} Enrollment code not found. Please check the code and try again, or contact staff for assistance.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  };
+
+  let Some(course) =
+    DatabaseHandler::get_course(&mut transaction, &guild_id, &enrollment_code.course_name).await?
+  else {
+    return Err(anyhow!(
+      "Enrollment code {} refers to missing course: {}",
+      enrollment_code.code,
+      enrollment_code.course_name
+    ));
+  };
+
+  let course_name = course.name;
+
+  let member = ctx
+    .author_member()
+    .await
+    .with_context(|| "Failed to retrieve Member from context, cache, or HTTP")?;
+
+  if member.roles.contains(&course.participant_role) {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} You are already enrolled in the course: **{course_name}**.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+
+    return Ok(());
+  }
+
+  if member.roles.contains(&course.graduate_role) {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} You have already completed the course: **{course_name}**.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+
+    return Ok(());
+  }
+
+  // The code may have been used up since it was retrieved, so only rely on the update
+  if enrollment_code.exhausted()
+    || DatabaseHandler::redeem_enrollment_code(&mut transaction, &guild_id, code).await? == 0
+  {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} This enrollment code has already been used. Please contact staff for assistance.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+
+    return Ok(());
+  }
+
+  let enrollment = Enrollment::new(
+    &course_name,
+    guild_id,
+    ctx.author().id,
+    &enrollment_code.code,
+  );
+  DatabaseHandler::record_course_enrollment(&mut transaction, &enrollment).await?;
+
+  if let Err(e) = member.add_role(ctx, course.participant_role).await {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} Failed to add the course role. Please try again or contact staff for assistance.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    // Dropping the transaction rolls back the redemption, so the code can be used again
+    return Err(anyhow!("Failed to add course role: {e}"));
+  }
+
+  DatabaseHandler::commit_transaction(transaction).await?;
+
+  ctx
+    .send(
+      CreateReply::default()
+        .content(format!(
+          ":tada: You are now enrolled in the course: **{course_name}**!\n\nIf you decide you would like to opt out of the course-specific channels at a later time, just use the `/course leave` command."
+        ))
+        .ephemeral(true),
+    )
+    .await?;
+
+  // Log enrollment in staff channel
+  let log_embed = BloomBotEmbed::new()
+    .title("New Course Enrollment")
+    .description(format!(
+      "<@{}> has enrolled in the course: **{course_name}**\n**Code**: `{}`",
+      ctx.author().id,
+      enrollment_code.code
+    ));
+
+  let log_channel = ChannelId::new(CHANNELS.logs);
+
+  log_channel
+    .send_message(ctx, CreateMessage::new().embed(log_embed))
+    .await?;
+
+  Ok(())
+}
+
 /// Leave a course
 ///
 /// Leave a Meditation Mind course.
@@ -148,6 +302,7 @@ async fn leave(
   #[rename = "course"]
   course_name: Option<String>,
 ) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
   ctx.defer_ephemeral().await?;
 
   let guild_id = ctx
@@ -176,7 +331,7 @@ async fn leave(
         CreateReply::default()
           .content(format!(
             "{} You are not currently enrolled in the course: **{course_name}**.",
-            EMOJI.mminfo
+            emoji.mminfo
           ))
           .ephemeral(true),
       )
@@ -191,7 +346,7 @@ async fn leave(
         CreateReply::default()
           .content(format!(
             "{} Failed to remove the course role. Please try again or contact staff for assistance.",
-            EMOJI.mminfo
+            emoji.mminfo
           ))
           .ephemeral(true),
       )
@@ -6,14 +6,14 @@ use crate::commands::helpers::common::Visibility;
 use crate::commands::helpers::courses;
 use crate::commands::helpers::database::{self, MessageType};
 use crate::commands::helpers::pagination::{PageRowRef, PageType, Paginator};
-use crate::config::{EMOJI, ENTRIES_PER_PAGE};
-use crate::data::course::Course;
+use crate::config::ENTRIES_PER_PAGE;
+use crate::data::course::{Course, EnrollmentCode};
 use crate::database::DatabaseHandler;
 use crate::Context;
 
 /// Commands for managing courses
 ///
-/// Commands to add, edit, list, or remove courses.
+/// Commands to add, edit, list, or remove courses, and to manage course enrollment codes.
 ///
 /// Requires `Administrator` permissions.
 #[poise::command(
@@ -21,7 +21,7 @@ use crate::Context;
   required_permissions = "ADMINISTRATOR",
   default_member_permissions = "ADMINISTRATOR",
   category = "Admin Commands",
-  subcommands("add", "remove", "edit", "list"),
+  subcommands("add", "remove", "edit", "list", "codes"),
   subcommand_required,
   guild_only
 )]
@@ -39,7 +39,10 @@ async fn add(
   #[description = "Name of the course"] course_name: String,
   #[description = "Role course participants are assumed to have"] participant_role: Role,
   #[description = "Role to be given to graduates"] graduate_role: Role,
+  #[description = "Only allow the course to be completed in DMs (defaults to false)"]
+  dm_only_completion: Option<bool>,
 ) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
   ctx.defer_ephemeral().await?;
 
   let guild_id = ctx
@@ -49,7 +52,7 @@ async fn add(
   let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
   if DatabaseHandler::course_exists(&mut transaction, &guild_id, course_name.as_str()).await? {
     ctx
-      .say(format!("{} Course already exists.", EMOJI.mminfo))
+      .say(format!("{} Course already exists.", emoji.mminfo))
       .await?;
     return Ok(());
   }
@@ -59,7 +62,7 @@ async fn add(
     ctx
       .say(format!(
         "{} The participant role must be in the same guild as the command.",
-        EMOJI.mminfo
+        emoji.mminfo
       ))
       .await?;
     return Ok(());
@@ -68,7 +71,7 @@ async fn add(
     ctx
       .say(format!(
         "{} The graduate role must be in the same guild as the command.",
-        EMOJI.mminfo
+        emoji.mminfo
       ))
       .await?;
     return Ok(());
@@ -79,7 +82,7 @@ async fn add(
     ctx
       .say(format!(
         "{} The participant role must not be a bot role.",
-        EMOJI.mminfo
+        emoji.mminfo
       ))
       .await?;
     return Ok(());
@@ -88,7 +91,7 @@ async fn add(
     ctx
       .say(format!(
         "{} The graduate role must not be a bot role.",
-        EMOJI.mminfo
+        emoji.mminfo
       ))
       .await?;
     return Ok(());
@@ -99,7 +102,7 @@ async fn add(
     ctx
       .say(format!(
         "{} The participant role must not be an administrator role.",
-        EMOJI.mminfo
+        emoji.mminfo
       ))
       .await?;
     return Ok(());
@@ -108,7 +111,7 @@ async fn add(
     ctx
       .say(format!(
         "{} The graduate role must not be an administrator role.",
-        EMOJI.mminfo
+        emoji.mminfo
       ))
       .await?;
     return Ok(());
@@ -118,20 +121,21 @@ async fn add(
     ctx
       .say(format!(
         "{} The participant role and the graduate role must not be the same.",
-        EMOJI.mminfo
+        emoji.mminfo
       ))
       .await?;
     return Ok(());
   }
 
-  let course = Course::new(course_name, participant_role.id, graduate_role.id, guild_id);
+  let course = Course::new(course_name, participant_role.id, graduate_role.id, guild_id)
+    .dm_only_completion(dm_only_completion.unwrap_or(false));
 
   DatabaseHandler::add_course(&mut transaction, &course).await?;
 
   database::commit_and_say(
     ctx,
     transaction,
-    MessageType::TextOnly(format!("{} Course has been added.", EMOJI.mmcheck)),
+    MessageType::TextOnly(format!("{} Course has been added.", emoji.mmcheck)),
     Visibility::Ephemeral,
   )
   .await?;
@@ -139,23 +143,25 @@ async fn add(
   Ok(())
 }
 
-/// Update the roles for an existing course
+/// Update the roles or settings for an existing course
 ///
-/// Updates the roles for an existing course.
+/// Updates the roles or completion settings for an existing course.
 #[poise::command(slash_command)]
 async fn edit(
   ctx: Context<'_>,
   #[description = "Name of the course"] course_name: String,
   #[description = "Role course participants are assumed to have"] participant_role: Option<Role>,
   #[description = "Role to be given to graduates"] graduate_role: Option<Role>,
+  #[description = "Only allow the course to be completed in DMs"] dm_only_completion: Option<bool>,
 ) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
   ctx.defer_ephemeral().await?;
 
-  if participant_role.is_none() && graduate_role.is_none() {
+  if participant_role.is_none() && graduate_role.is_none() && dm_only_completion.is_none() {
     ctx
       .send(
         CreateReply::default()
-          .content(format!("{} No changes were provided.", EMOJI.mminfo))
+          .content(format!("{} No changes were provided.", emoji.mminfo))
           .ephemeral(true),
       )
       .await?;
@@ -184,7 +190,7 @@ async fn edit(
         ctx
           .say(format!(
             "{} The participant role must be in the same guild as the command.",
-            EMOJI.mminfo
+            emoji.mminfo
           ))
           .await?;
         return Ok(());
@@ -193,7 +199,7 @@ async fn edit(
         ctx
           .say(format!(
             "{} The participant role must not be a bot role.",
-            EMOJI.mminfo
+            emoji.mminfo
           ))
           .await?;
         return Ok(());
@@ -202,7 +208,7 @@ async fn edit(
         ctx
           .say(format!(
             "{} The participant role must not be an administrator role.",
-            EMOJI.mminfo
+            emoji.mminfo
           ))
           .await?;
         return Ok(());
@@ -218,7 +224,7 @@ async fn edit(
         ctx
           .say(format!(
             "{} The graduate role must be in the same guild as the command.",
-            EMOJI.mminfo
+            emoji.mminfo
           ))
           .await?;
         return Ok(());
@@ -227,7 +233,7 @@ async fn edit(
         ctx
           .say(format!(
             "{} The graduate role must not be a bot role.",
-            EMOJI.mminfo
+            emoji.mminfo
           ))
           .await?;
         return Ok(());
@@ -236,7 +242,7 @@ async fn edit(
         ctx
           .say(format!(
             "{} The graduate role must not be an administrator role.",
-            EMOJI.mminfo
+            emoji.mminfo
           ))
           .await?;
         return Ok(());
@@ -251,20 +257,22 @@ async fn edit(
     ctx
       .say(format!(
         "{} The participant role and the graduate role must not be the same.",
-        EMOJI.mminfo
+        emoji.mminfo
       ))
       .await?;
     return Ok(());
   }
 
-  let course = Course::new(course_name, participant_role, graduate_role, guild_id);
+  let dm_only_completion = dm_only_completion.unwrap_or(course.dm_only_completion);
+  let course = Course::new(course_name, participant_role, graduate_role, guild_id)
+    .dm_only_completion(dm_only_completion);
 
   DatabaseHandler::update_course(&mut transaction, &course).await?;
 
   database::commit_and_say(
     ctx,
     transaction,
-    MessageType::TextOnly(format!("{} Course roles have been updated.", EMOJI.mmcheck)),
+    MessageType::TextOnly(format!("{} Course has been updated.", emoji.mmcheck)),
     Visibility::Ephemeral,
   )
   .await?;
@@ -306,6 +314,7 @@ async fn remove(
   ctx: Context<'_>,
   #[description = "Name of the course"] course_name: String,
 ) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
   ctx.defer_ephemeral().await?;
 
   let guild_id = ctx
@@ -315,7 +324,7 @@ async fn remove(
   let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
   if !DatabaseHandler::course_exists(&mut transaction, &guild_id, course_name.as_str()).await? {
     ctx
-      .say(format!("{} Course does not exist.", EMOJI.mminfo))
+      .say(format!("{} Course does not exist.", emoji.mminfo))
       .await?;
     return Ok(());
   }
@@ -325,7 +334,165 @@ async fn remove(
   database::commit_and_say(
     ctx,
     transaction,
-    MessageType::TextOnly(format!("{} Course has been removed.", EMOJI.mmcheck)),
+    MessageType::TextOnly(format!("{} Course has been removed.", emoji.mmcheck)),
+    Visibility::Ephemeral,
+  )
+  .await?;
+
+  Ok(())
+}
+
+/// Commands for managing course enrollment codes
+///
+/// Commands to create, list, or revoke codes which members can redeem with `/course enroll` to join a course.
+#[poise::command(slash_command, subcommands("create_code", "list_codes", "revoke_code"))]
+#[allow(clippy::unused_async)]
+async fn codes(_: Context<'_>) -> Result<()> {
+  Ok(())
+}
+
+/// Create an enrollment code for a course
+///
+/// Creates a code which members can redeem with `/course enroll` to be given the participant role for a course.
+///
+/// Codes are single-use by default. Set the maximum uses to 0 to create a code which can be used any number of times.
+#[poise::command(slash_command, rename = "create")]
+async fn create_code(
+  ctx: Context<'_>,
+  #[description = "Name of the course"] course_name: String,
+  #[description = "Number of times the code can be used (defaults to 1; 0 for unlimited)"]
+  #[min = 0]
+  #[max = 10000]
+  max_uses: Option<i32>,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  ctx.defer_ephemeral().await?;
+
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+  let Some(course) =
+    DatabaseHandler::get_course(&mut transaction, &guild_id, course_name.as_str()).await?
+  else {
+    courses::course_not_found(ctx, &mut transaction, guild_id, course_name).await?;
+    return Ok(());
+  };
+
+  let max_uses = match max_uses.unwrap_or(1) {
+    0 => None,
+    max_uses => Some(max_uses),
+  };
+
+  // Codes are random, so a collision is unlikely, but check anyway
+  let code = loop {
+    let code = {
+      let mut rng = ctx.data().rng.lock().await;
+      courses::generate_enrollment_code(&mut *rng)
+    };
+    if DatabaseHandler::get_enrollment_code(&mut transaction, &guild_id, &code)
+      .await?
+      .is_none()
+    {
+      break code;
+    }
+  };
+
+  let enrollment_code =
+    EnrollmentCode::new(&code, &course.name, guild_id, max_uses, ctx.author().id);
+
+  DatabaseHandler::add_enrollment_code(&mut transaction, &enrollment_code).await?;
+
+  let uses = match max_uses {
+    Some(1) => "It can be used once.".to_owned(),
+    Some(max_uses) => format!("It can be used {max_uses} times."),
+    None => "It can be used any number of times.".to_owned(),
+  };
+
+  database::commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(format!(
+      "{} Enrollment code for **{}** has been created: `{code}`\n\n{uses} Members can redeem it with `/course enroll`.",
+      emoji.mmcheck, course.name
+    )),
+    Visibility::Ephemeral,
+  )
+  .await?;
+
+  Ok(())
+}
+
+/// List all enrollment codes for a course
+///
+/// Lists all enrollment codes for a course, along with how many times each has been used.
+#[poise::command(slash_command, rename = "list")]
+async fn list_codes(
+  ctx: Context<'_>,
+  #[description = "Name of the course"] course_name: String,
+  #[description = "The page to show"] page: Option<usize>,
+) -> Result<()> {
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+  let Some(course) =
+    DatabaseHandler::get_course(&mut transaction, &guild_id, course_name.as_str()).await?
+  else {
+    courses::course_not_found(ctx, &mut transaction, guild_id, course_name).await?;
+    return Ok(());
+  };
+
+  let codes =
+    DatabaseHandler::get_enrollment_codes(&mut transaction, &guild_id, &course.name).await?;
+  let codes: Vec<PageRowRef> = codes.iter().map(|code| code as PageRowRef).collect();
+
+  drop(transaction);
+
+  Paginator::new(
+    format!("Enrollment Codes: {}", course.name),
+    &codes,
+    ENTRIES_PER_PAGE.default,
+  )
+  .paginate(ctx, page, PageType::Standard, Visibility::Ephemeral)
+  .await?;
+
+  Ok(())
+}
+
+/// Revoke an enrollment code
+///
+/// Revokes an enrollment code so that it can no longer be redeemed. Members who have already enrolled with the code keep their course role.
+#[poise::command(slash_command, rename = "revoke")]
+async fn revoke_code(
+  ctx: Context<'_>,
+  #[description = "The enrollment code to revoke"] code: String,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  ctx.defer_ephemeral().await?;
+
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  if DatabaseHandler::remove_enrollment_code(&mut transaction, &guild_id, code.trim()).await? == 0 {
+    ctx
+      .say(format!("{} Enrollment code does not exist.", emoji.mminfo))
+      .await?;
+    return Ok(());
+  }
+
+  database::commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(format!(
+      "{} Enrollment code has been revoked.",
+      emoji.mmcheck
+    )),
     Visibility::Ephemeral,
   )
   .await?;
@@ -1,32 +1,62 @@
 use anyhow::{Context as AnyhowContext, Result};
+use chrono::{Duration as ChronoDuration, Utc};
 use log::error;
-use poise::serenity_prelude::{builder::*, CreateAllowedMentions};
+use poise::serenity_prelude::{builder::*, ChannelId, CreateAllowedMentions, Mentionable};
 use poise::{ChoiceParameter, CreateReply};
 
+use crate::charts::ChartFormat;
 use crate::commands::helpers::common::Visibility;
 use crate::commands::helpers::database::{self, MessageType};
 use crate::commands::helpers::time::{self, MinusOffsetChoice, PlusOffsetChoice};
-use crate::config::{BloomBotEmbed, StreakRoles, EMOJI};
-use crate::data::tracking_profile::{Privacy, Status, TrackingProfile};
+use crate::commands::helpers::{profile_links, tracking};
+use crate::config::{BloomBotEmbed, StreakRoles, CHANNELS};
+use crate::data::profile_import::{ProfileImport, ProfileLink};
+use crate::data::tracking_profile::{privacy, Privacy, Status, TrackingProfile};
 use crate::database::DatabaseHandler;
+use crate::events;
+use crate::roles::RoleUpdate;
 use crate::Context;
 
+/// How long a token from `/customize link` can be used for.
+const LINK_EXPIRY_MINUTES: i64 = 60;
+
 #[derive(ChoiceParameter)]
-enum OnOff {
+enum Anonymous {
   #[name = "on"]
   On,
   #[name = "off"]
   Off,
+  #[name = "silent"]
+  Silent,
+}
+
+#[derive(ChoiceParameter)]
+enum SessionMinutes {
+  #[name = "shown"]
+  Shown,
+  #[name = "hidden"]
+  Hidden,
 }
 
 /// Customize your tracking experience
 ///
 /// Customize your meditation tracking experience.
 ///
-/// Set a UTC offset, make your stats or streak private, turn streak reporting off, or enable anonymous tracking.
+/// Set a UTC offset, make your stats or streak private, turn streak reporting off, enable anonymous or silent tracking, hide your session minutes, share details about your practice in the community directory, or copy your stats from another server running Bloom.
 #[poise::command(
   slash_command,
-  subcommands("show", "offset", "tracking", "streak", "stats"),
+  subcommands(
+    "show",
+    "offset",
+    "tracking",
+    "minutes",
+    "streak",
+    "guard",
+    "stats",
+    "profile",
+    "link",
+    "import_from"
+  ),
   category = "Meditation Tracking",
   guild_only
 )]
@@ -78,13 +108,26 @@ async fn show(ctx: Context<'_>) -> Result<()> {
         //.title("Meditation Tracking Customization Settings")
         .description(format!(
           //"**UTC Offset**: {}\n**Anonymous Tracking**: {}\n**Streak Reporting**: {}\n**Streak Visibility**: {}\n**Stats Visibility**: {}",
-          "```UTC Offset:           {}\nAnonymous Tracking:   {}\nStreak Reporting:     {}\nStreak Visibility:    {}\nStats Visibility:     {}```",
+          "```UTC Offset:           {}\nAnonymous Tracking:   {}\nSession Minutes:      {}\nStreak Reporting:     {}\nStreak Visibility:    {}\nStreak Guard:         {}\nStats Visibility:     {}\nOwn Stats Output:     {}\nChart Format:         {}\nDirectory Listing:    {}```",
           //Only show the offset (no time zone abbreviations)
           utc_offset.split_whitespace().next().with_context(|| "Failed to retrieve offset portion of time zone choice")?,
-          if tracking_profile.tracking.privacy == Privacy::Private { "On" } else { "Off" },
+          match (tracking_profile.tracking.privacy, tracking_profile.tracking.silent) {
+            (Privacy::Private, true) => "Silent",
+            (Privacy::Private, false) => "On",
+            (Privacy::Public, _) => "Off",
+          },
+          if tracking_profile.tracking.redact_minutes { "Hidden" } else { "Shown" },
           if tracking_profile.streak.status == Status::Enabled { "Enabled" } else { "Disabled" },
           if tracking_profile.streak.privacy == Privacy::Private { "Private" } else { "Public" },
+          tracking_profile.streak.guard_hour.map_or_else(|| "Off".to_string(), |hour| format!("{hour:02}:00")),
           if tracking_profile.stats.privacy == Privacy::Private { "Private" } else { "Public" },
+          if tracking_profile.stats.visibility == Privacy::Private { "Private" } else { "Public" },
+          match tracking_profile.stats.chart_format {
+            ChartFormat::Standard => "Standard",
+            ChartFormat::HighRes => "High Resolution",
+            ChartFormat::Svg => "SVG",
+          },
+          if tracking_profile.profile.listed { "Listed" } else { "Unlisted" },
         ))
     )
     .ephemeral(true))
@@ -106,6 +149,7 @@ async fn offset(
   #[rename = "eastern_hemisphere_offset"]
   plus_offset: Option<PlusOffsetChoice>,
 ) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
   let guild_id = ctx
     .guild_id()
     .with_context(|| "Failed to retrieve guild ID from context")?;
@@ -161,7 +205,7 @@ async fn offset(
     transaction,
     MessageType::TextOnly(format!(
       "{} UTC offset successfully updated.",
-      EMOJI.mmcheck
+      emoji.mmcheck
     )),
     Visibility::Ephemeral,
   )
@@ -175,11 +219,15 @@ async fn offset(
 /// Turn anonymous tracking on or off.
 ///
 /// When anonymous tracking is turned on, the anonymous entry is displayed in the channel to motivate others, but personal information (total meditation time, streak and role info) is shared with you privately via ephemeral messages.
+///
+/// When set to silent, nothing is displayed in the channel at all, and all feedback is shared with you privately via ephemeral messages.
 #[poise::command(slash_command)]
 async fn tracking(
   ctx: Context<'_>,
-  #[description = "Turn anonymous tracking on or off (Default is off)"] anonymous: OnOff,
+  #[description = "Turn anonymous tracking on, off or silent (Default is off)"]
+  anonymous: Anonymous,
 ) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
   let guild_id = ctx
     .guild_id()
     .with_context(|| "Failed to retrieve guild ID from context")?;
@@ -187,15 +235,18 @@ async fn tracking(
 
   let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
 
-  let tracking_privacy = match anonymous {
-    OnOff::On => Privacy::Private,
-    OnOff::Off => Privacy::Public,
+  let (tracking_privacy, tracking_silent) = match anonymous {
+    Anonymous::On => (Privacy::Private, false),
+    Anonymous::Off => (Privacy::Public, false),
+    Anonymous::Silent => (Privacy::Private, true),
   };
 
   if let Some(existing_profile) =
     DatabaseHandler::get_tracking_profile(&mut transaction, &guild_id, &user_id).await?
   {
-    if tracking_privacy == existing_profile.tracking.privacy {
+    if tracking_privacy == existing_profile.tracking.privacy
+      && tracking_silent == existing_profile.tracking.silent
+    {
       ctx
         .send(
           CreateReply::default()
@@ -212,13 +263,17 @@ async fn tracking(
 
     DatabaseHandler::update_tracking_profile(
       &mut transaction,
-      &existing_profile.tracking_privacy(tracking_privacy),
+      &existing_profile
+        .tracking_privacy(tracking_privacy)
+        .tracking_silent(tracking_silent),
     )
     .await?;
   } else {
     DatabaseHandler::add_tracking_profile(
       &mut transaction,
-      &TrackingProfile::new(guild_id, user_id).tracking_privacy(tracking_privacy),
+      &TrackingProfile::new(guild_id, user_id)
+        .tracking_privacy(tracking_privacy)
+        .tracking_silent(tracking_silent),
     )
     .await?;
   }
@@ -228,7 +283,7 @@ async fn tracking(
     transaction,
     MessageType::TextOnly(format!(
       "{} Anonymous tracking successfully turned **{}**.",
-      EMOJI.mmcheck,
+      emoji.mmcheck,
       anonymous.name()
     )),
     Visibility::Ephemeral,
@@ -238,6 +293,73 @@ async fn tracking(
   Ok(())
 }
 
+/// Show or hide your session minutes
+///
+/// Show or hide the minutes of your sessions in public tracking confirmations.
+///
+/// When hidden, the confirmation posted in the channel only notes that you logged a session, without how many minutes it was or your total meditation time. The details are shared with you privately via ephemeral messages, and your sessions still count fully toward your stats, streak, and roles.
+#[poise::command(slash_command)]
+async fn minutes(
+  ctx: Context<'_>,
+  #[description = "Show or hide your minutes in public confirmations (Default is shown)"]
+  minutes: SessionMinutes,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+  let user_id = ctx.author().id;
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  let redact_minutes = matches!(minutes, SessionMinutes::Hidden);
+
+  if let Some(existing_profile) =
+    DatabaseHandler::get_tracking_profile(&mut transaction, &guild_id, &user_id).await?
+  {
+    if redact_minutes == existing_profile.tracking.redact_minutes {
+      ctx
+        .send(
+          CreateReply::default()
+            .content(format!(
+              "Session minutes already **{}**. No changes made.",
+              minutes.name()
+            ))
+            .ephemeral(true),
+        )
+        .await?;
+
+      return Ok(());
+    }
+
+    DatabaseHandler::update_tracking_profile(
+      &mut transaction,
+      &existing_profile.tracking_redacted(redact_minutes),
+    )
+    .await?;
+  } else {
+    DatabaseHandler::add_tracking_profile(
+      &mut transaction,
+      &TrackingProfile::new(guild_id, user_id).tracking_redacted(redact_minutes),
+    )
+    .await?;
+  }
+
+  database::commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(format!(
+      "{} Session minutes will now be **{}** in public confirmations.",
+      emoji.mmcheck,
+      minutes.name()
+    )),
+    Visibility::Ephemeral,
+  )
+  .await?;
+
+  Ok(())
+}
+
 /// Enable/disable streaks or set streak privacy
 ///
 /// Enable/disable streak reporting or set your streak privacy.
@@ -251,6 +373,7 @@ async fn streak(
   #[description = "Set streak privacy (Defaults to public)"] privacy: Option<Privacy>,
   #[description = "Turn streak reporting on or off (Defaults to on)"] reporting: Option<Status>,
 ) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
   let guild_id = ctx
     .guild_id()
     .with_context(|| "Failed to retrieve guild ID from context")?;
@@ -296,27 +419,24 @@ async fn streak(
     if streak_disabled {
       let member = guild_id.member(ctx, user_id).await?;
 
-      let current_streak_roles = StreakRoles::get_users_current_roles(&member.roles);
+      let update = StreakRoles::get_users_current_roles(&member.roles)
+        .into_iter()
+        .fold(RoleUpdate::new(&member), RoleUpdate::remove);
 
-      for role in current_streak_roles {
-        match member.remove_role(ctx, role).await {
-          Ok(()) => {}
-          Err(err) => {
-            error!("Error removing role: {err}");
+      if let Err(err) = ctx.data().role_queue.apply(update).await {
+        error!("Error removing streak roles: {err}");
 
-            ctx
-              .send(
-                CreateReply::default()
-                  .content(format!(
-                    "{} An error occured while removing your streak role. Your settings have been saved, but your roles have not been updated. Please contact a moderator.",
-                    EMOJI.mminfo
-                  ))
-                  .allowed_mentions(CreateAllowedMentions::new())
-                  .ephemeral(true),
-              )
-              .await?;
-          }
-        }
+        ctx
+          .send(
+            CreateReply::default()
+              .content(format!(
+                "{} An error occured while removing your streak role. Your settings have been saved, but your roles have not been updated. Please contact a moderator.",
+                emoji.mminfo
+              ))
+              .allowed_mentions(CreateAllowedMentions::new())
+              .ephemeral(true),
+          )
+          .await?;
       }
     }
 
@@ -331,23 +451,22 @@ async fn streak(
 
       if let Some(earned_streak_role) = earned_streak_role {
         if !current_streak_roles.contains(&earned_streak_role.to_role_id()) {
-          match member.add_role(ctx, earned_streak_role.to_role_id()).await {
-            Ok(()) => {}
-            Err(err) => {
-              error!("Error adding role: {err}");
-
-              ctx
-                .send(
-                  CreateReply::default()
-                    .content(format!(
-                      "{} An error occured while adding your streak role. Your settings have been saved, but your roles have not been updated. Please contact a moderator.",
-                      EMOJI.mminfo
-                    ))
-                    .allowed_mentions(CreateAllowedMentions::new())
-                    .ephemeral(true),
-                )
-                .await?;
-            }
+          let update = RoleUpdate::new(&member).add(earned_streak_role.to_role_id());
+
+          if let Err(err) = ctx.data().role_queue.apply(update).await {
+            error!("Error adding streak role: {err}");
+
+            ctx
+              .send(
+                CreateReply::default()
+                  .content(format!(
+                    "{} An error occured while adding your streak role. Your settings have been saved, but your roles have not been updated. Please contact a moderator.",
+                    emoji.mminfo
+                  ))
+                  .allowed_mentions(CreateAllowedMentions::new())
+                  .ephemeral(true),
+              )
+              .await?;
           }
         }
       }
@@ -367,27 +486,24 @@ async fn streak(
     if streak_status == Status::Disabled {
       let member = guild_id.member(ctx, user_id).await?;
 
-      let current_streak_roles = StreakRoles::get_users_current_roles(&member.roles);
+      let update = StreakRoles::get_users_current_roles(&member.roles)
+        .into_iter()
+        .fold(RoleUpdate::new(&member), RoleUpdate::remove);
 
-      for role in current_streak_roles {
-        match member.remove_role(ctx, role).await {
-          Ok(()) => {}
-          Err(err) => {
-            error!("Error removing role: {err}");
+      if let Err(err) = ctx.data().role_queue.apply(update).await {
+        error!("Error removing streak roles: {err}");
 
-            ctx
-              .send(
-                CreateReply::default()
-                  .content(format!(
-                    "{} An error occured while removing your streak role. Your settings have been saved, but your roles have not been updated. Please contact a moderator.",
-                    EMOJI.mminfo
-                  ))
-                  .allowed_mentions(CreateAllowedMentions::new())
-                  .ephemeral(true),
-              )
-              .await?;
-          }
-        }
+        ctx
+          .send(
+            CreateReply::default()
+              .content(format!(
+                "{} An error occured while removing your streak role. Your settings have been saved, but your roles have not been updated. Please contact a moderator.",
+                emoji.mminfo
+              ))
+              .allowed_mentions(CreateAllowedMentions::new())
+              .ephemeral(true),
+          )
+          .await?;
       }
     }
   }
@@ -397,7 +513,7 @@ async fn streak(
     transaction,
     MessageType::TextOnly(format!(
       "{} Streak settings successfully updated.",
-      EMOJI.mmcheck
+      emoji.mmcheck
     )),
     Visibility::Ephemeral,
   )
@@ -406,16 +522,102 @@ async fn streak(
   Ok(())
 }
 
-/// Set stats privacy
+/// Turn the streak guard reminder on or off
 ///
-/// Set your stats privacy.
+/// Turn the streak guard reminder on or off, or change when it is sent.
 ///
-/// When stats are set to private, other members will be unable to view your stats using the /stats user command. When you view your own stats using the /stats user command, the response will be shown privately in an ephemeral message by default. This can be overridden by setting privacy to "public" when using the command.
+/// When turned on, you'll receive a gentle reminder by DM if you have an active streak but haven't added any time by the specified hour, in your local time as set with /customize offset. You'll receive at most one reminder per day, and only while streak reporting is enabled.
+#[poise::command(slash_command)]
+async fn guard(
+  ctx: Context<'_>,
+  #[description = "Turn the streak guard reminder on or off"] status: Status,
+  #[description = "Local hour to be reminded at, using the 24-hour clock (Defaults to 20, or 8 PM)"]
+  #[min = 0]
+  #[max = 23]
+  hour: Option<i16>,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+  let user_id = ctx.author().id;
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  let guard_hour = match status {
+    Status::Enabled => Some(hour.unwrap_or(20)),
+    Status::Disabled => None,
+  };
+
+  if let Some(existing_profile) =
+    DatabaseHandler::get_tracking_profile(&mut transaction, &guild_id, &user_id).await?
+  {
+    if guard_hour == existing_profile.streak.guard_hour {
+      ctx
+        .send(
+          CreateReply::default()
+            .content(
+              "Current settings already match specified settings. No changes made.".to_string(),
+            )
+            .ephemeral(true),
+        )
+        .await?;
+
+      return Ok(());
+    }
+
+    DatabaseHandler::update_tracking_profile(
+      &mut transaction,
+      &existing_profile.streak_guard(guard_hour),
+    )
+    .await?;
+  } else {
+    DatabaseHandler::add_tracking_profile(
+      &mut transaction,
+      &TrackingProfile::new(guild_id, user_id).streak_guard(guard_hour),
+    )
+    .await?;
+  }
+
+  let message = match guard_hour {
+    Some(hour) => format!(
+      "{} Streak guard turned **on**. You'll be reminded if you haven't added any time by {hour:02}:00.",
+      emoji.mmcheck
+    ),
+    None => format!("{} Streak guard turned **off**.", emoji.mmcheck),
+  };
+
+  database::commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(message),
+    Visibility::Ephemeral,
+  )
+  .await?;
+
+  Ok(())
+}
+
+/// Set stats privacy, default visibility, or chart format
+///
+/// Set your stats privacy, the default visibility of your own stats, or the default format of stats charts.
+///
+/// When stats are set to private, other members will be unable to view your stats using the /stats user command.
+///
+/// The visibility setting controls whether your own stats are shown publicly or privately in an ephemeral message when you use the /stats user command. If only privacy is specified, the visibility is set to match. Either can be overridden by setting privacy when using the command.
+///
+/// The chart format is used for the charts you request with /stats user and /stats server. High resolution and SVG charts look sharper on high-DPI displays.
 #[poise::command(slash_command)]
 async fn stats(
   ctx: Context<'_>,
-  #[description = "Set stats privacy (Defaults to public)"] privacy: Privacy,
+  #[description = "Set stats privacy (Defaults to public)"] privacy: Option<Privacy>,
+  #[description = "Set default visibility of your own stats (Defaults to stats privacy)"]
+  visibility: Option<Privacy>,
+  #[description = "Set default format of stats charts (Defaults to standard)"] chart_format: Option<
+    ChartFormat,
+  >,
 ) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
   let data = ctx.data();
 
   let guild_id = ctx
@@ -423,19 +625,42 @@ async fn stats(
     .with_context(|| "Failed to retrieve guild ID from context")?;
   let user_id = ctx.author().id;
 
+  if privacy.is_none() && visibility.is_none() && chart_format.is_none() {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} Please specify a privacy, visibility, and/or chart format setting.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+
+    return Ok(());
+  }
+
   let mut transaction = data.db.start_transaction_with_retry(5).await?;
 
   if let Some(existing_profile) =
     DatabaseHandler::get_tracking_profile(&mut transaction, &guild_id, &user_id).await?
   {
-    if privacy == existing_profile.stats.privacy {
+    let stats_privacy = privacy.unwrap_or(existing_profile.stats.privacy);
+    let stats_visibility = visibility
+      .or(privacy)
+      .unwrap_or(existing_profile.stats.visibility);
+    let stats_chart_format = chart_format.unwrap_or(existing_profile.stats.chart_format);
+
+    if (stats_privacy == existing_profile.stats.privacy)
+      && (stats_visibility == existing_profile.stats.visibility)
+      && (stats_chart_format == existing_profile.stats.chart_format)
+    {
       ctx
         .send(
           CreateReply::default()
-            .content(format!(
-              "Stats already set to **{}**. No changes made.",
-              privacy.name()
-            ))
+            .content(
+              "Current settings already match specified settings. No changes made.".to_string(),
+            )
             .ephemeral(true),
         )
         .await?;
@@ -445,13 +670,22 @@ async fn stats(
 
     DatabaseHandler::update_tracking_profile(
       &mut transaction,
-      &existing_profile.stats_privacy(privacy),
+      &existing_profile
+        .stats_privacy(stats_privacy)
+        .stats_visibility(stats_visibility)
+        .stats_chart_format(stats_chart_format),
     )
     .await?;
   } else {
+    let stats_privacy = privacy.unwrap_or_default();
+    let stats_visibility = visibility.unwrap_or(stats_privacy);
+
     DatabaseHandler::add_tracking_profile(
       &mut transaction,
-      &TrackingProfile::new(guild_id, user_id).stats_privacy(privacy),
+      &TrackingProfile::new(guild_id, user_id)
+        .stats_privacy(stats_privacy)
+        .stats_visibility(stats_visibility)
+        .stats_chart_format(chart_format.unwrap_or_default()),
     )
     .await?;
   }
@@ -460,9 +694,193 @@ async fn stats(
     ctx,
     transaction,
     MessageType::TextOnly(format!(
-      "{} Stats successfully set to **{}**.",
-      EMOJI.mmcheck,
-      privacy.name()
+      "{} Stats settings successfully updated.",
+      emoji.mmcheck
+    )),
+    Visibility::Ephemeral,
+  )
+  .await?;
+
+  Ok(())
+}
+
+/// Share details about your practice
+///
+/// Share optional details about your practice, such as your tradition, favorite teacher, and how many years you've been practicing.
+///
+/// Your details are only shown to other members if you choose to be listed in the community directory, which can be browsed using /directory. Run without any options to show your current details.
+///
+/// You can also set the name and pronouns Bloom uses when congratulating you in announcements, such as for time and streak roles or winning the monthly challenge. These are used whether or not you are listed in the directory.
+#[poise::command(slash_command)]
+async fn profile(
+  ctx: Context<'_>,
+  #[description = "Your practice tradition (e.g., Theravada, Zen, secular)"]
+  #[max_length = 100]
+  tradition: Option<String>,
+  #[description = "Your favorite teacher"]
+  #[max_length = 100]
+  teacher: Option<String>,
+  #[description = "How many years you've been practicing"]
+  #[min = 0]
+  #[max = 100]
+  years: Option<i16>,
+  #[description = "List your details in the community directory (Defaults to unlisted)"]
+  listed: Option<bool>,
+  #[description = "The name to use in announcements (Defaults to your mention)"]
+  #[max_length = 32]
+  name: Option<String>,
+  #[description = "Pronouns to show in announcements (e.g., she/her, they/them)"]
+  #[max_length = 32]
+  pronouns: Option<String>,
+  #[description = "Clear all of your details and remove you from the directory"] clear: Option<
+    bool,
+  >,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+  let user_id = ctx.author().id;
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  let existing_profile =
+    DatabaseHandler::get_tracking_profile(&mut transaction, &guild_id, &user_id).await?;
+
+  if tradition.is_none()
+    && teacher.is_none()
+    && years.is_none()
+    && listed.is_none()
+    && name.is_none()
+    && pronouns.is_none()
+    && clear.is_none()
+  {
+    let profile = existing_profile.unwrap_or_default().profile;
+
+    ctx
+      .send(
+        CreateReply::default()
+          .embed(
+            BloomBotEmbed::new()
+              .author(CreateEmbedAuthor::new("Practice Profile").icon_url(ctx.author().face()))
+              .description(format!(
+                "```Tradition:            {}\nFavorite Teacher:     {}\nYears Practicing:     {}\nDirectory Listing:    {}\nAnnouncement Name:    {}\nPronouns:             {}```",
+                profile.tradition.as_deref().unwrap_or("Not set"),
+                profile.teacher.as_deref().unwrap_or("Not set"),
+                profile
+                  .years_practicing
+                  .map_or_else(|| "Not set".to_owned(), |years| years.to_string()),
+                if profile.listed { "Listed" } else { "Unlisted" },
+                profile.display_name.as_deref().unwrap_or("Not set"),
+                profile.pronouns.as_deref().unwrap_or("Not set"),
+              )),
+          )
+          .ephemeral(true),
+      )
+      .await?;
+
+    return Ok(());
+  }
+
+  let clean = |value: Option<String>| value.map(|value| value.trim().to_owned());
+  let exists = existing_profile.is_some();
+  let tracking_profile =
+    existing_profile.unwrap_or_else(|| TrackingProfile::new(guild_id, user_id));
+
+  let tracking_profile = if clear == Some(true) {
+    tracking_profile
+      .profile_tradition(None)
+      .profile_teacher(None)
+      .profile_years(None)
+      .profile_listed(false)
+      .profile_display_name(None)
+      .profile_pronouns(None)
+  } else {
+    let tradition = clean(tradition).or(tracking_profile.profile.tradition.clone());
+    let teacher = clean(teacher).or(tracking_profile.profile.teacher.clone());
+    let years = years.or(tracking_profile.profile.years_practicing);
+    let listed = listed.unwrap_or(tracking_profile.profile.listed);
+    let name = clean(name).or(tracking_profile.profile.display_name.clone());
+    let pronouns = clean(pronouns).or(tracking_profile.profile.pronouns.clone());
+
+    tracking_profile
+      .profile_tradition(tradition.filter(|tradition| !tradition.is_empty()))
+      .profile_teacher(teacher.filter(|teacher| !teacher.is_empty()))
+      .profile_years(years)
+      .profile_listed(listed)
+      .profile_display_name(name.filter(|name| !name.is_empty()))
+      .profile_pronouns(pronouns.filter(|pronouns| !pronouns.is_empty()))
+  };
+
+  if exists {
+    DatabaseHandler::update_tracking_profile(&mut transaction, &tracking_profile).await?;
+  } else {
+    DatabaseHandler::add_tracking_profile(&mut transaction, &tracking_profile).await?;
+  }
+
+  let message = if clear == Some(true) {
+    format!(
+      "{} Practice details cleared. You are no longer listed in the directory.",
+      emoji.mmcheck
+    )
+  } else if tracking_profile.profile.listed {
+    format!(
+      "{} Practice details successfully updated. You are listed in the directory.",
+      emoji.mmcheck
+    )
+  } else {
+    format!(
+      "{} Practice details successfully updated. You are not listed in the directory, so your practice details are only visible to you.",
+      emoji.mmcheck
+    )
+  };
+
+  database::commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(message),
+    Visibility::Ephemeral,
+  )
+  .await?;
+
+  Ok(())
+}
+
+/// Create a token to copy your stats to another server
+///
+/// Create a single-use token for copying your meditation entries from this server to another server running Bloom.
+///
+/// Use the token with /customize import-from in the other server within an hour. Your entries are copied rather than moved, so they stay in this server as well. Only you can use the token, and creating a new one replaces any unused token from this server.
+#[poise::command(slash_command)]
+async fn link(ctx: Context<'_>) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+  let user_id = ctx.author().id;
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  let token = profile_links::token();
+  let expires_at = Utc::now() + ChronoDuration::minutes(LINK_EXPIRY_MINUTES);
+  DatabaseHandler::add_profile_link(
+    &mut transaction,
+    &ProfileLink::new(
+      profile_links::hash_token(&token),
+      guild_id,
+      user_id,
+      expires_at,
+    ),
+  )
+  .await?;
+
+  database::commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(format!(
+      "{} Your token is `{token}`\n\nIn the other server, use `/customize import-from` with this token to copy your meditation entries there. It can be used once, <t:{}:R>, and only by you. Your entries here won't be changed.",
+      emoji.mmcheck,
+      expires_at.timestamp()
     )),
     Visibility::Ephemeral,
   )
@@ -470,3 +888,182 @@ async fn stats(
 
   Ok(())
 }
+
+/// Copy your stats from another server
+///
+/// Copy your meditation entries from another server running Bloom, using a token created there with /customize link.
+///
+/// Entries are copied rather than moved, so they stay in the other server as well. Entries which have already been copied to this server, or which were themselves imported from elsewhere, are skipped. Your streak and roles are updated to reflect the copied entries.
+#[poise::command(slash_command, rename = "import-from")]
+async fn import_from(
+  ctx: Context<'_>,
+  #[description = "The token from /customize link in the other server"]
+  #[max_length = 50]
+  token: String,
+) -> Result<()> {
+  let data = ctx.data();
+  let emoji = data.emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+  let user_id = ctx.author().id;
+
+  let mut transaction = data.db.start_transaction_with_retry(5).await?;
+
+  let token_hash = profile_links::hash_token(&token);
+  // Tokens belonging to someone else are treated as unknown, so they can't be probed for
+  let Some(link) = DatabaseHandler::get_profile_link(&mut transaction, &token_hash)
+    .await?
+    .filter(|link| link.user_id == user_id)
+  else {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} That token is invalid, has expired, or has already been used. Please create a new one with `/customize link` in the server you'd like to copy your stats from.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  };
+
+  if link.guild_id == guild_id {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} That token was created in this server. Please use it in the server you'd like to copy your stats to.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let source_name = link
+    .guild_id
+    .name(ctx)
+    .unwrap_or_else(|| "the other server".to_owned());
+
+  let entries =
+    DatabaseHandler::get_importable_entries(&mut transaction, &link.guild_id, &guild_id, &user_id)
+      .await?;
+  if entries.is_empty() {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} There are no entries from {source_name} left to copy. Your token can still be used after you've added more time there.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  if DatabaseHandler::use_profile_link(&mut transaction, &token_hash).await? == 0 {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} That token has already been used.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  ctx.defer_ephemeral().await?;
+
+  let tracking_profile =
+    DatabaseHandler::get_tracking_profile(&mut transaction, &guild_id, &user_id)
+      .await?
+      .unwrap_or_default();
+  let privacy = privacy!(tracking_profile.tracking.privacy);
+
+  let import = ProfileImport::new(
+    guild_id,
+    user_id,
+    link.guild_id,
+    i32::try_from(entries.len()).unwrap_or(i32::MAX),
+  );
+  DatabaseHandler::add_profile_import(&mut transaction, &import, &entries).await?;
+
+  let imported_minutes = entries
+    .iter()
+    .map(|entry| i64::from(entry.minutes) + i64::from(entry.seconds) / 60)
+    .sum::<i64>();
+  let user_sum =
+    DatabaseHandler::get_user_meditation_sum(&mut transaction, &guild_id, &user_id).await?;
+  // Entries were added to past days, so the stored longest streak can't be relied on
+  let streak = DatabaseHandler::recalculate_streak(&mut transaction, &guild_id, &user_id).await?;
+
+  let mut description = format!(
+    "Copied **{} entries** ({imported_minutes} minutes) from {source_name}. Your total meditation time here is now {user_sum} minutes :tada:",
+    entries.len()
+  );
+  if tracking_profile.streak.status == Status::Enabled {
+    description.push_str(&format!(
+      "\n\nYour current meditation streak is {} days.",
+      streak.current
+    ));
+  }
+
+  // Posted without mentions, like logged retreats, so staff know where the entries came from
+  ChannelId::new(CHANNELS.logs)
+    .send_message(
+      ctx,
+      CreateMessage::new().embed(
+        BloomBotEmbed::new()
+          .title("Stats Imported")
+          .description(format!(
+            "{} copied {} entries ({imported_minutes} minutes) from {source_name} (`{}`).",
+            ctx.author().mention(),
+            entries.len(),
+            link.guild_id
+          ))
+          .footer(CreateEmbedFooter::new(format!("Import ID: {}", import.id))),
+      ),
+    )
+    .await?;
+
+  database::commit_and_say(
+    ctx,
+    transaction,
+    MessageType::EmbedOnly(Box::new(
+      BloomBotEmbed::new()
+        .title("Stats Imported")
+        .description(description),
+    )),
+    Visibility::Ephemeral,
+  )
+  .await?;
+
+  let member = guild_id.member(ctx, user_id).await?;
+  tracking::update_time_roles(&ctx, &member, &tracking_profile.profile, user_sum, privacy).await?;
+  if tracking_profile.streak.status == Status::Enabled {
+    tracking::update_streak_roles(
+      &ctx,
+      &member,
+      &tracking_profile.profile,
+      streak.current,
+      privacy,
+    )
+    .await?;
+  }
+
+  tokio::spawn(events::leaderboards::update(
+    module_path!(),
+    ctx.serenity_context().http.clone(),
+    data.db.clone(),
+    guild_id,
+  ));
+
+  Ok(())
+}
@@ -0,0 +1,127 @@
+use anyhow::{Context as AnyhowContext, Result};
+use chrono::{Duration as ChronoDuration, Utc};
+use poise::serenity_prelude::{Mentionable, User};
+use poise::CreateReply;
+
+use crate::commands::helpers::common::Visibility;
+use crate::commands::helpers::database::{self, MessageType};
+use crate::config::BloomBotEmbed;
+use crate::data::dedication::Dedication;
+use crate::data::tracking_profile::Privacy;
+use crate::database::DatabaseHandler;
+use crate::Context;
+
+/// How recently the session being dedicated must have been added, in hours.
+const MAX_SESSION_AGE: i64 = 24;
+
+/// Dedicate your last session to someone
+///
+/// Dedicates your most recent meditation session to another member, along with a message. The session still counts towards your own stats.
+///
+/// Only sessions from the last 24 hours can be dedicated, and each session can only be dedicated once. If you track anonymously, the length of the session is not shown.
+///
+/// Dedications can be viewed using `/dedications`.
+#[poise::command(slash_command, category = "Meditation Tracking", guild_only)]
+pub async fn dedicate(
+  ctx: Context<'_>,
+  #[description = "The member to dedicate your session to"] user: User,
+  #[description = "A message for the member"]
+  #[max_length = 300]
+  message: String,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+  let user_id = ctx.author().id;
+
+  if user.id == user_id || user.bot {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} Please choose another member to dedicate your session to.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  let tracking_profile =
+    DatabaseHandler::get_tracking_profile(&mut transaction, &guild_id, &user_id)
+      .await?
+      .unwrap_or_default();
+
+  // Sessions are dated using the member's UTC offset, so compare against their local time.
+  let now = Utc::now() + ChronoDuration::minutes(i64::from(tracking_profile.utc_offset));
+
+  let Some(meditation) =
+    DatabaseHandler::get_latest_meditation_entry(&mut transaction, &guild_id, &user_id)
+      .await?
+      .filter(|meditation| now - meditation.occurred_at <= ChronoDuration::hours(MAX_SESSION_AGE))
+  else {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} You haven't added a session in the last {MAX_SESSION_AGE} hours. Add one with `/add` and then try again.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  };
+
+  let message = message.trim().to_owned();
+  let dedication = Dedication::new(
+    guild_id,
+    user_id,
+    user.id,
+    meditation.id.clone(),
+    meditation.minutes,
+    message.clone(),
+  );
+
+  if DatabaseHandler::add_dedication(&mut transaction, &dedication).await? == 0 {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} Your last session has already been dedicated.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let session = if tracking_profile.tracking.privacy == Privacy::Private {
+    "their session".to_owned()
+  } else {
+    format!("their **{}-minute** session", meditation.minutes)
+  };
+
+  let embed = BloomBotEmbed::new()
+    .title(":sparkles: A Session Dedicated")
+    .description(format!(
+      "{} dedicated {session} to {}:\n\n> {message}",
+      ctx.author().mention(),
+      user.mention()
+    ));
+
+  database::commit_and_say(
+    ctx,
+    transaction,
+    MessageType::EmbedOnly(Box::new(embed)),
+    Visibility::Public,
+  )
+  .await?;
+
+  Ok(())
+}
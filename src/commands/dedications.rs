@@ -0,0 +1,59 @@
+use anyhow::{Context as AnyhowContext, Result};
+use poise::serenity_prelude::User;
+use poise::ChoiceParameter;
+
+use crate::commands::helpers::common::Visibility;
+use crate::commands::helpers::pagination::{PageRowRef, PageType, Paginator};
+use crate::config::ENTRIES_PER_PAGE;
+use crate::database::DatabaseHandler;
+use crate::Context;
+
+#[derive(ChoiceParameter)]
+enum DedicationType {
+  #[name = "received"]
+  Received,
+  #[name = "given"]
+  Given,
+}
+
+/// See dedicated sessions
+///
+/// Displays a list of sessions dedicated to you or a specified member, or the sessions they have dedicated to others.
+///
+/// Sessions can be dedicated using `/dedicate`.
+#[poise::command(slash_command, category = "Meditation Tracking", guild_only)]
+pub async fn dedications(
+  ctx: Context<'_>,
+  #[description = "The member to show dedications for (Defaults to you)"] user: Option<User>,
+  #[description = "Show dedications received or given (Defaults to received)"]
+  #[rename = "type"]
+  dedication_type: Option<DedicationType>,
+  #[description = "The page to show"] page: Option<usize>,
+) -> Result<()> {
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+  let user = user.as_ref().unwrap_or_else(|| ctx.author());
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  let (title, entries) = match dedication_type.unwrap_or(DedicationType::Received) {
+    DedicationType::Received => (
+      format!("Sessions Dedicated to {}", user.name),
+      DatabaseHandler::get_dedications_received(&mut transaction, &guild_id, &user.id).await?,
+    ),
+    DedicationType::Given => (
+      format!("Sessions Dedicated by {}", user.name),
+      DatabaseHandler::get_dedications_given(&mut transaction, &guild_id, &user.id).await?,
+    ),
+  };
+  let entries: Vec<PageRowRef> = entries.iter().map(|entry| entry as PageRowRef).collect();
+
+  drop(transaction);
+
+  Paginator::new(title, &entries, ENTRIES_PER_PAGE.default)
+    .paginate(ctx, page, PageType::Standard, Visibility::Ephemeral)
+    .await?;
+
+  Ok(())
+}
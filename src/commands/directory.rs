@@ -0,0 +1,48 @@
+use anyhow::{Context as AnyhowContext, Result};
+
+use crate::commands::helpers::common::Visibility;
+use crate::commands::helpers::pagination::{PageRowRef, PageType, Paginator};
+use crate::config::ENTRIES_PER_PAGE;
+use crate::database::DatabaseHandler;
+use crate::Context;
+
+/// Browse the community directory
+///
+/// Browse members who have chosen to share details about their practice, such as their tradition, favorite teacher, and years practicing.
+///
+/// Optionally search by tradition or teacher. To be listed yourself, use `/customize profile`.
+#[poise::command(slash_command, category = "Meditation Tracking", guild_only)]
+pub async fn directory(
+  ctx: Context<'_>,
+  #[description = "Search by tradition or favorite teacher"]
+  #[max_length = 100]
+  search: Option<String>,
+  #[description = "The page to show"] page: Option<usize>,
+) -> Result<()> {
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let search = search
+    .map(|search| search.trim().to_owned())
+    .filter(|search| !search.is_empty());
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  let entries =
+    DatabaseHandler::get_directory(&mut transaction, &guild_id, search.as_deref()).await?;
+  let entries: Vec<PageRowRef> = entries.iter().map(|entry| entry as PageRowRef).collect();
+
+  drop(transaction);
+
+  let title = match &search {
+    Some(_) => "Community Directory (Search Results)",
+    None => "Community Directory",
+  };
+
+  Paginator::new(title, &entries, ENTRIES_PER_PAGE.default)
+    .paginate(ctx, page, PageType::Standard, Visibility::Ephemeral)
+    .await?;
+
+  Ok(())
+}
@@ -1,21 +1,31 @@
 use std::time::Duration;
 
 use anyhow::{anyhow, Context as AnyhowContext, Result};
-use chrono::{NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use chrono::{Duration as ChronoDuration, NaiveDate, NaiveDateTime, NaiveTime, Utc};
 use poise::serenity_prelude::{builder::*, ChannelId, ChannelType, ComponentInteractionCollector};
+use poise::serenity_prelude::{ButtonStyle, GetMessages, Message, MessageId, User};
 use poise::serenity_prelude::{ComponentInteractionDataKind, CreateQuickModal, InputTextStyle};
-use poise::serenity_prelude::{Message, User};
 use poise::{ApplicationContext, ChoiceParameter, Context as PoiseContext, CreateReply};
 use sqlx::{Postgres, Transaction};
 
 use crate::commands::helpers::common::Visibility;
 use crate::commands::helpers::database::{self, MessageType};
 use crate::commands::helpers::pagination::{PageRowRef, PageType, Paginator};
-use crate::config::{BloomBotEmbed, CHANNELS, EMOJI, ENTRIES_PER_PAGE};
+use crate::commands::helpers::threads;
+use crate::config::{BloomBotEmbed, CHANNELS, ENTRIES_PER_PAGE};
 use crate::data::erase::Erase;
 use crate::database::DatabaseHandler;
 use crate::{Context, Data as AppData, Error as AppError};
 
+/// The most messages `/erase range` will delete at once.
+const MAX_RANGE_MESSAGES: usize = 100;
+
+/// The most messages `/erase range` will look through to find the end of the range.
+const MAX_RANGE_SCANNED: usize = 1000;
+
+/// The number of messages shown in the `/erase range` preview.
+const RANGE_PREVIEW_MESSAGES: usize = 5;
+
 #[derive(ChoiceParameter)]
 enum DateFormat {
   #[name = "YYYY-MM-DD (ISO 8601)"]
@@ -167,6 +177,7 @@ pub async fn erase_message(
   ctx: ApplicationContext<'_, AppData, AppError>,
   #[description = "The message to delete"] message: Message,
 ) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
   ctx.defer_ephemeral().await?;
   let ctx_id = ctx.id();
 
@@ -223,7 +234,7 @@ pub async fn erase_message(
           ctx,
           CreateInteractionResponse::UpdateMessage(
             CreateInteractionResponseMessage::new()
-              .content(format!("{} Erase cancelled.", EMOJI.mminfo))
+              .content(format!("{} Erase cancelled.", emoji.mminfo))
               .ephemeral(true)
               .components(Vec::new()),
           ),
@@ -258,7 +269,7 @@ pub async fn erase_message(
         .edit_response(
           ctx,
           EditInteractionResponse::new()
-            .content(format!("{} Erase cancelled.", EMOJI.mminfo))
+            .content(format!("{} Erase cancelled.", emoji.mminfo))
             .components(Vec::new()),
         )
         .await?;
@@ -302,7 +313,7 @@ pub async fn erase_message(
       CreateInteractionResponseMessage::new()
         .content(format!(
           "{} Message deleted. User will be notified via DM or private thread.",
-          EMOJI.mmcheck
+          emoji.mmcheck
         ))
         .ephemeral(true)
         .components(Vec::new()),
@@ -321,7 +332,7 @@ pub async fn erase_message(
     .edit(
       PoiseContext::Application(ctx),
       CreateReply::default()
-        .content(format!("{} Erase cancelled.", EMOJI.mminfo))
+        .content(format!("{} Erase cancelled.", emoji.mminfo))
         .components(Vec::new())
         .ephemeral(true),
     )
@@ -340,7 +351,7 @@ pub async fn erase_message(
   required_permissions = "MANAGE_MESSAGES",
   default_member_permissions = "MANAGE_MESSAGES",
   category = "Moderator Commands",
-  subcommands("message", "list", "populate"),
+  subcommands("message", "range", "list", "populate"),
   guild_only
 )]
 #[allow(clippy::unused_async)]
@@ -360,6 +371,7 @@ async fn message(
   reason: Option<String>,
   #[description = "Choose a predefined default reason"] default_reason: Option<DefaultReasons>,
 ) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
   ctx.defer_ephemeral().await?;
 
   let reason = reason.unwrap_or(default_reason.unwrap_or(DefaultReasons::None).response());
@@ -373,7 +385,7 @@ async fn message(
     transaction,
     MessageType::TextOnly(format!(
       "{} Message deleted. User will be notified via DM or private thread.",
-      EMOJI.mmcheck
+      emoji.mmcheck
     )),
     Visibility::Ephemeral,
   )
@@ -384,6 +396,193 @@ async fn message(
   Ok(())
 }
 
+/// Delete a range of messages from a user and notify them
+///
+/// Deletes every message from the author of the start message, from the start message to the end message, and notifies the user once via DM or private thread with an optional reason. Both messages must be in the same channel and from the same user, and up to 100 messages can be deleted at once.
+///
+/// A preview is shown before anything is deleted, so the range can be checked first.
+#[poise::command(slash_command)]
+async fn range(
+  ctx: Context<'_>,
+  #[description = "The first message to delete"] start: Message,
+  #[description = "The last message to delete"] end: Message,
+  #[max_length = 512] // Max length for audit log reason
+  #[description = "The reason for deleting the messages"]
+  reason: Option<String>,
+  #[description = "Choose a predefined default reason"] default_reason: Option<DefaultReasons>,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  ctx.defer_ephemeral().await?;
+
+  if start.channel_id != end.channel_id || start.author.id != end.author.id {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} The start and end messages must be in the same channel and from the same user.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let (start, end) = if start.id <= end.id {
+    (start, end)
+  } else {
+    (end, start)
+  };
+
+  let Some(messages) = messages_in_range(ctx, &start, &end).await? else {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} That range is too large. Up to {MAX_RANGE_MESSAGES} messages can be erased at once, within {MAX_RANGE_SCANNED} messages of the start message.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  };
+
+  let reason = reason.unwrap_or(default_reason.unwrap_or(DefaultReasons::None).response());
+
+  let preview = messages
+    .iter()
+    .take(RANGE_PREVIEW_MESSAGES)
+    .map(|message| {
+      let content = if message.content.is_empty() {
+        "*No text content*".to_owned()
+      } else if message.content.chars().count() > 100 {
+        format!(
+          "{}...",
+          message.content.chars().take(100).collect::<String>()
+        )
+      } else {
+        message.content.clone()
+      };
+      format!("> {}", content.replace('\n', " "))
+    })
+    .collect::<Vec<_>>()
+    .join("\n");
+  let more = if messages.len() > RANGE_PREVIEW_MESSAGES {
+    format!("\n-# and {} more", messages.len() - RANGE_PREVIEW_MESSAGES)
+  } else {
+    String::new()
+  };
+
+  let ctx_id = ctx.id();
+  let confirm_id = format!("{ctx_id}confirm");
+  let cancel_id = format!("{ctx_id}cancel");
+
+  ctx
+    .send(
+      CreateReply::default()
+        .embed(
+          BloomBotEmbed::new()
+            .title(format!(
+              "Erase {} {}?",
+              messages.len(),
+              if messages.len() == 1 {
+                "message"
+              } else {
+                "messages"
+              }
+            ))
+            .description(format!(
+              "**Channel**: <#{}>\n**Author**: {}\n**Reason**: {reason}",
+              start.channel_id, start.author
+            ))
+            .field("Preview", format!("{preview}{more}"), false),
+        )
+        .components(vec![CreateActionRow::Buttons(vec![
+          CreateButton::new(confirm_id.clone())
+            .label("Erase")
+            .style(ButtonStyle::Danger),
+          CreateButton::new(cancel_id.clone())
+            .label("Cancel")
+            .style(ButtonStyle::Secondary),
+        ])])
+        .ephemeral(true),
+    )
+    .await?;
+
+  let press = {
+    let confirm_id = confirm_id.clone();
+    ComponentInteractionCollector::new(ctx)
+      .author_id(ctx.author().id)
+      .filter(move |press| press.data.custom_id == confirm_id || press.data.custom_id == cancel_id)
+      .timeout(Duration::from_secs(60))
+      .await
+  };
+
+  let Some(press) = press else {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!("{} Erase timed out.", emoji.mminfo))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  };
+
+  if press.data.custom_id != confirm_id {
+    press
+      .create_response(
+        ctx,
+        CreateInteractionResponse::UpdateMessage(
+          CreateInteractionResponseMessage::new()
+            .content(format!("{} Erase cancelled.", emoji.mminfo))
+            .embeds(Vec::new())
+            .components(Vec::new()),
+        ),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  press
+    .create_response(
+      ctx,
+      CreateInteractionResponse::UpdateMessage(
+        CreateInteractionResponseMessage::new()
+          .content(format!("{} Erasing messages...", emoji.mminfo))
+          .embeds(Vec::new())
+          .components(Vec::new()),
+      ),
+    )
+    .await?;
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  let dm_embed = erase_range_and_log(ctx, &mut transaction, &messages, &reason).await?;
+
+  DatabaseHandler::commit_transaction(transaction).await?;
+
+  press
+    .edit_response(
+      ctx,
+      EditInteractionResponse::new().content(format!(
+        "{} {} deleted. User will be notified via DM or private thread.",
+        emoji.mmcheck,
+        if messages.len() == 1 {
+          "1 message".to_owned()
+        } else {
+          format!("{} messages", messages.len())
+        }
+      )),
+    )
+    .await?;
+
+  notify_user(ctx, &start, dm_embed).await?;
+
+  Ok(())
+}
+
 /// List erases for a user
 ///
 /// List erases for a specified user, with dates and links to notification messages, when available.
@@ -448,6 +647,7 @@ async fn populate(
   #[rename = "time"]
   erase_time: Option<NaiveTime>,
 ) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
   let guild_id = ctx
     .guild_id()
     .with_context(|| "Failed to retrieve guild ID from context")?;
@@ -470,7 +670,7 @@ async fn populate(
   database::commit_and_say(
     ctx,
     transaction,
-    MessageType::TextOnly(format!("{} Erase data has been added.", EMOJI.mmcheck)),
+    MessageType::TextOnly(format!("{} Erase data has been added.", emoji.mmcheck)),
     Visibility::Ephemeral,
   )
   .await?;
@@ -573,6 +773,178 @@ async fn erase_and_log(
   Ok(dm_embed)
 }
 
+/// Retrieves the messages from the author of `start` which were sent from `start` to `end`,
+/// inclusive and oldest first. Returns [`None`] if there are more than
+/// [`MAX_RANGE_MESSAGES`] of them, or if `end` isn't found within [`MAX_RANGE_SCANNED`]
+/// messages.
+async fn messages_in_range(
+  ctx: Context<'_>,
+  start: &Message,
+  end: &Message,
+) -> Result<Option<Vec<Message>>> {
+  let mut messages = vec![start.clone()];
+  if start.id == end.id {
+    return Ok(Some(messages));
+  }
+
+  let mut after: MessageId = start.id;
+  let mut scanned = 0;
+
+  loop {
+    let mut batch = start
+      .channel_id
+      .messages(ctx, GetMessages::new().after(after).limit(100))
+      .await?;
+    if batch.is_empty() {
+      // The end message was deleted while looking for it
+      break;
+    }
+    batch.sort_by_key(|message| message.id);
+
+    let mut past_end = false;
+    for message in batch {
+      if message.id > end.id {
+        past_end = true;
+        break;
+      }
+
+      after = message.id;
+      scanned += 1;
+
+      if message.author.id == start.author.id {
+        messages.push(message);
+      }
+    }
+
+    if past_end || after >= end.id {
+      break;
+    }
+    if messages.len() > MAX_RANGE_MESSAGES || scanned >= MAX_RANGE_SCANNED {
+      return Ok(None);
+    }
+  }
+
+  if messages.len() > MAX_RANGE_MESSAGES {
+    return Ok(None);
+  }
+
+  Ok(Some(messages))
+}
+
+/// Erases a range of messages from one user, logs the erase in the
+/// [`CHANNELS.logs`][logs] channel as a single entry, and returns an embed to be used for
+/// private notification. The `transaction` needs to be committed after this function is
+/// called or it will be rolled back and the erase will not be added to the database.
+///
+/// [logs]: crate::config::CHANNELS
+async fn erase_range_and_log(
+  ctx: Context<'_>,
+  transaction: &mut Transaction<'_, Postgres>,
+  messages: &[Message],
+  reason: &String,
+) -> Result<CreateEmbed> {
+  let Some(first) = messages.first() else {
+    return Err(anyhow!("No messages to erase"));
+  };
+  let channel_id = first.channel_id;
+
+  // Bulk deletion only works for 2 to 100 messages which are under two weeks old
+  let bulk_cutoff = Utc::now() - ChronoDuration::days(13);
+  if messages.len() > 1
+    && messages
+      .iter()
+      .all(|message| *message.timestamp > bulk_cutoff)
+  {
+    channel_id
+      .delete_messages(ctx, messages.iter().map(|message| message.id))
+      .await?;
+  } else {
+    for message in messages {
+      ctx
+        .http()
+        .delete_message(channel_id, message.id, Some(reason.as_str()))
+        .await?;
+    }
+  }
+
+  let occurred_at = Utc::now();
+
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+  let user_id = first.author.id;
+
+  let erase_count = DatabaseHandler::get_erases(transaction, &guild_id, &user_id)
+    .await?
+    .len()
+    + 1;
+  let erase_count_message = if erase_count == 1 {
+    "1 erase recorded".to_string()
+  } else {
+    format!("{erase_count} erases recorded")
+  };
+
+  let contents = messages
+    .iter()
+    .filter(|message| !message.content.is_empty())
+    .map(|message| message.content.as_str())
+    .collect::<Vec<_>>()
+    .join("\n");
+
+  let mut log_embed = BloomBotEmbed::new()
+    .title("Messages Deleted")
+    .description(format!(
+      "**Channel**: <#{channel_id}>\n**Author**: {} ({})\n**Messages**: {}\n**Reason**: {}",
+      first.author,
+      erase_count_message,
+      messages.len(),
+      reason,
+    ));
+  let mut dm_embed = BloomBotEmbed::new()
+    .title("Messages you sent have been deleted.")
+    .description(format!(
+      "**Messages**: {}\n**Reason**: {reason}",
+      messages.len()
+    ));
+
+  if !contents.is_empty() {
+    // If longer than 1018 characters (1024 max - 6 for backticks), truncate to 1015 (-3 for "...").
+    let contents = if contents.chars().count() > 1018 {
+      format!("{}...", contents.chars().take(1015).collect::<String>())
+    } else {
+      contents
+    };
+
+    log_embed = log_embed.field("Message Content", format!("```{contents}```"), false);
+    dm_embed = dm_embed.field("Message Content", format!("```{contents}```"), false);
+  }
+
+  log_embed = log_embed.footer(
+    CreateEmbedFooter::new(format!(
+      "Deleted by {} ({})",
+      ctx.author().name,
+      ctx.author().id
+    ))
+    .icon_url(ctx.author().avatar_url().unwrap_or_default()),
+  );
+
+  dm_embed = dm_embed.footer(CreateEmbedFooter::new(
+    "If you have any questions or concerns regarding this action, please contact a moderator. Replies sent to Bloom are not viewable by staff.",
+  ));
+
+  let log_channel = ChannelId::new(CHANNELS.logs);
+
+  let log_message = log_channel
+    .send_message(ctx, CreateMessage::new().embed(log_embed))
+    .await?;
+
+  let erase = Erase::new(guild_id, user_id, log_message.link(), reason, &occurred_at);
+
+  DatabaseHandler::add_erase(transaction, &erase).await?;
+
+  Ok(dm_embed)
+}
+
 /// Notifies a user of deletion via DM, or via private thread if a DM cannot be delivered.
 /// Private threads are created in the channel where the message was deleted, when possible.
 /// When that fails, the [`CHANNELS.private_thread_default`][ptd] channel is used as a fallback.
@@ -587,20 +959,19 @@ async fn notify_user(ctx: Context<'_>, message: &Message, dm_embed: CreateEmbed)
     .is_err()
   {
     // If the DM can't be delivered, we create a private thread.
-    let thread_channel = if message
-      .channel_id
-      .to_channel(&ctx)
+    // Messages in threads get the private thread in the thread's text channel.
+    let channel_id = threads::thread_parent(ctx, message.channel_id)
+      .await
+      .unwrap_or(message.channel_id);
+    let thread_channel = if threads::guild_channel(ctx, channel_id)
       .await
-      .is_ok_and(|channel| {
-        channel
-          .guild()
-          .is_some_and(|channel| channel.kind == ChannelType::Text)
-      }) {
-      message.channel_id
+      .is_some_and(|channel| channel.kind == ChannelType::Text)
+    {
+      channel_id
     } else {
-      // If the message wasn't deleted from a text channel in a server, we use a default channel
-      // to create the private thread. This avoids failure when the message was originally posted
-      // in a thread, forum, voice channel text chat, etc.
+      // If the message wasn't deleted from a text channel in a server, or a thread in one, we use
+      // a default channel to create the private thread. This avoids failure when the message was
+      // originally posted in a forum, voice channel text chat, etc.
       ChannelId::from(CHANNELS.private_thread_default)
     };
 
@@ -0,0 +1,748 @@
+use std::collections::HashMap;
+
+use anyhow::{Context as AnyhowContext, Result};
+use chrono::{DateTime, Duration as ChronoDuration, NaiveDate, NaiveTime, Utc};
+use poise::serenity_prelude::{
+  ChannelType, FormattedTimestamp, FormattedTimestampStyle, GuildChannel, Mentionable,
+};
+use poise::CreateReply;
+
+use crate::commands::helpers::common::Visibility;
+use crate::commands::helpers::database::{self, MessageType};
+use crate::commands::helpers::transcripts;
+use crate::config::BloomBotEmbed;
+use crate::data::calendar::CalendarFeed;
+use crate::data::community_event::{CommunityEvent, EventRepeat, OccurrenceStats};
+use crate::database::DatabaseHandler;
+use crate::Context;
+
+/// The number of past occurrences shown by `/event attendance`.
+const RECENT_OCCURRENCES: usize = 5;
+
+/// Suggests event titles containing `partial`. Returns no suggestions outside of a guild or
+/// if the lookup fails.
+async fn autocomplete_event(ctx: Context<'_>, partial: &str) -> impl Iterator<Item = String> {
+  let titles = match ctx.guild_id() {
+    Some(guild_id) => match ctx.data().db.start_transaction_with_retry(5).await {
+      Ok(mut transaction) => {
+        DatabaseHandler::get_community_event_titles(&mut transaction, &guild_id, partial)
+          .await
+          .unwrap_or_default()
+      }
+      Err(_) => Vec::new(),
+    },
+    None => Vec::new(),
+  };
+
+  titles.into_iter()
+}
+
+fn timestamp(datetime: DateTime<Utc>, style: FormattedTimestampStyle) -> FormattedTimestamp {
+  FormattedTimestamp::new(datetime.into(), Some(style))
+}
+
+fn schedule(event: &CommunityEvent) -> String {
+  let repeat = match event.repeat_days {
+    None => "Once".to_owned(),
+    Some(1) => "Daily".to_owned(),
+    Some(7) => "Weekly".to_owned(),
+    Some(14) => "Every two weeks".to_owned(),
+    Some(days) => format!("Every {days} days"),
+  };
+
+  format!(
+    "{repeat}, for {} minutes in {}",
+    event.duration_minutes,
+    event.channel_id.mention()
+  )
+}
+
+async fn reply_not_found(ctx: Context<'_>, title: &str) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  ctx
+    .send(
+      CreateReply::default()
+        .content(format!(
+          "{} No event called **{title}** was found.",
+          emoji.mminfo
+        ))
+        .ephemeral(true),
+    )
+    .await?;
+
+  Ok(())
+}
+
+/// Commands for community events
+///
+/// Commands to create recurring community events, such as a book club or Q&A, RSVP to them, view attendance, transcribe them, and subscribe to them in your calendar.
+#[poise::command(
+  slash_command,
+  category = "Utilities",
+  subcommands("create", "rsvp", "attendance", "transcript", "calendar"),
+  subcommand_required,
+  guild_only
+)]
+#[allow(clippy::unused_async)]
+pub async fn event(_: Context<'_>) -> Result<()> {
+  Ok(())
+}
+
+/// Create a community event
+///
+/// Creates a community event, which can be a one-off or repeat daily, weekly, or every two weeks. The start date and time are in the UTC offset set with `/customize offset`, or UTC if none has been set.
+///
+/// Members who send a message or join voice in the event channel while the event is running are counted as attending.
+///
+/// Requires `Manage Events` permissions.
+#[poise::command(slash_command, required_permissions = "MANAGE_EVENTS")]
+async fn create(
+  ctx: Context<'_>,
+  #[description = "The name of the event"]
+  #[max_length = 100]
+  title: String,
+  #[description = "The channel the event takes place in"]
+  #[channel_types("Text", "Voice", "Stage")]
+  channel: GuildChannel,
+  #[description = "The date of the first occurrence (YYYY-MM-DD)"] date: String,
+  #[description = "The start time (HH:MM, 24-hour)"] time: String,
+  #[description = "How long the event lasts, in minutes (defaults to 60)"]
+  #[min = 5]
+  #[max = 480]
+  duration: Option<i32>,
+  #[description = "How often the event repeats (defaults to not repeating)"] repeat: Option<
+    EventRepeat,
+  >,
+  #[description = "A description of the event"]
+  #[max_length = 1000]
+  description: Option<String>,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  if !matches!(
+    channel.kind,
+    ChannelType::Text | ChannelType::Voice | ChannelType::Stage
+  ) || channel.guild_id != guild_id
+  {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} Please choose a text, voice, or stage channel in this server.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let (Ok(date), Ok(time)) = (
+    NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d"),
+    NaiveTime::parse_from_str(time.trim(), "%H:%M"),
+  ) else {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} Please enter the date as YYYY-MM-DD and the time as HH:MM, e.g., `2024-11-02` and `18:30`.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  };
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  let title = title.trim().to_owned();
+  if DatabaseHandler::get_community_event(&mut transaction, &guild_id, &title)
+    .await?
+    .is_some()
+  {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} An event called **{title}** already exists. Please choose another name.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let utc_offset =
+    DatabaseHandler::get_tracking_profile(&mut transaction, &guild_id, &ctx.author().id)
+      .await?
+      .unwrap_or_default()
+      .utc_offset;
+  let starts_at = date.and_time(time).and_utc() - ChronoDuration::minutes(i64::from(utc_offset));
+
+  if starts_at < Utc::now() {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} The first occurrence must be in the future.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let event = CommunityEvent::new(
+    guild_id,
+    channel.id,
+    title,
+    description
+      .map(|description| description.trim().to_owned())
+      .filter(|description| !description.is_empty()),
+    starts_at,
+    duration.unwrap_or(60),
+    repeat.unwrap_or(EventRepeat::Never),
+    ctx.author().id,
+  );
+
+  DatabaseHandler::add_community_event(&mut transaction, &event).await?;
+
+  let mut embed = BloomBotEmbed::new()
+    .title(format!(":calendar: {}", event.title))
+    .description(format!(
+      "**Starts:** {}\n**Schedule:** {}\n\nRSVP with `/event rsvp`.",
+      timestamp(event.starts_at, FormattedTimestampStyle::LongDateTime),
+      schedule(&event)
+    ));
+  if let Some(description) = &event.description {
+    embed = embed.field("About", description, false);
+  }
+
+  database::commit_and_say(
+    ctx,
+    transaction,
+    MessageType::EmbedOnly(Box::new(embed)),
+    Visibility::Public,
+  )
+  .await?;
+
+  Ok(())
+}
+
+/// RSVP to the next occurrence of an event
+///
+/// Lets the organizers know you plan to attend the next occurrence of an event. Use the command again to cancel your RSVP.
+#[poise::command(slash_command)]
+async fn rsvp(
+  ctx: Context<'_>,
+  #[description = "The event to RSVP to"]
+  #[autocomplete = "autocomplete_event"]
+  event: String,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+  let user_id = ctx.author().id;
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  let Some(event) =
+    DatabaseHandler::get_community_event(&mut transaction, &guild_id, &event).await?
+  else {
+    return reply_not_found(ctx, &event).await;
+  };
+
+  let Some(occurrence) = event.next_occurrence(Utc::now()) else {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} **{}** has already taken place.",
+            emoji.mminfo, event.title
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  };
+
+  let message = if DatabaseHandler::add_event_rsvp(
+    &mut transaction,
+    &event.id,
+    &user_id,
+    &occurrence,
+  )
+  .await?
+    > 0
+  {
+    let rsvps =
+      DatabaseHandler::get_event_rsvp_count(&mut transaction, &event.id, &occurrence).await?;
+    format!(
+      "{} You're going to **{}** on {}! ({rsvps} going so far)\n-# Use this command again to cancel your RSVP.",
+      emoji.mmcheck,
+      event.title,
+      timestamp(occurrence, FormattedTimestampStyle::LongDateTime)
+    )
+  } else {
+    DatabaseHandler::remove_event_rsvp(&mut transaction, &event.id, &user_id, &occurrence).await?;
+    format!(
+      "{} Your RSVP to **{}** on {} has been cancelled.",
+      emoji.mmcheck,
+      event.title,
+      timestamp(occurrence, FormattedTimestampStyle::LongDateTime)
+    )
+  };
+
+  database::commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(message),
+    Visibility::Ephemeral,
+  )
+  .await?;
+
+  Ok(())
+}
+
+fn occurrence_line(occurrence: DateTime<Utc>, stats: Option<&OccurrenceStats>) -> String {
+  let (attended, rsvps, rsvps_attended) = stats.map_or((0, 0, 0), |stats| {
+    (stats.attended, stats.rsvps, stats.rsvps_attended)
+  });
+
+  format!(
+    "{}: **{attended}** attended, **{rsvps}** RSVPed ({rsvps_attended} of whom attended)",
+    timestamp(occurrence, FormattedTimestampStyle::ShortDate)
+  )
+}
+
+/// View attendance for an event
+///
+/// Shows RSVPs and attendance for the most recent occurrences of an event, along with RSVPs for the next occurrence.
+///
+/// Requires `Manage Events` permissions.
+#[poise::command(slash_command, required_permissions = "MANAGE_EVENTS")]
+async fn attendance(
+  ctx: Context<'_>,
+  #[description = "The event to show attendance for"]
+  #[autocomplete = "autocomplete_event"]
+  event: String,
+) -> Result<()> {
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  let Some(event) =
+    DatabaseHandler::get_community_event(&mut transaction, &guild_id, &event).await?
+  else {
+    return reply_not_found(ctx, &event).await;
+  };
+
+  let stats: HashMap<DateTime<Utc>, OccurrenceStats> =
+    DatabaseHandler::get_event_occurrence_stats(&mut transaction, &event.id)
+      .await?
+      .into_iter()
+      .map(|stats| (stats.occurrence, stats))
+      .collect();
+  let unique_attendees =
+    DatabaseHandler::get_event_unique_attendees(&mut transaction, &event.id).await?;
+
+  drop(transaction);
+
+  let now = Utc::now();
+  let mut description = format!("**Schedule:** {}\n", schedule(&event));
+
+  let next = event.next_occurrence(now);
+
+  match next {
+    Some(next) => {
+      let rsvps = stats.get(&next).map_or(0, |stats| stats.rsvps);
+      description.push_str(&format!(
+        "**Next:** {} (**{rsvps}** RSVPed)\n",
+        timestamp(next, FormattedTimestampStyle::LongDateTime)
+      ));
+    }
+    None => description.push_str("**Next:** None, this event has ended\n"),
+  }
+
+  // The next occurrence may already be running, but isn't over yet
+  let past: Vec<DateTime<Utc>> = event
+    .past_occurrences(now, RECENT_OCCURRENCES + 1)
+    .into_iter()
+    .filter(|occurrence| Some(*occurrence) != next)
+    .take(RECENT_OCCURRENCES)
+    .collect();
+
+  description.push_str(&format!("**Unique attendees:** {unique_attendees}\n"));
+
+  if past.is_empty() {
+    description.push_str("\nThis event hasn't taken place yet.");
+  } else {
+    let recent: Vec<&OccurrenceStats> = past
+      .iter()
+      .filter_map(|occurrence| stats.get(occurrence))
+      .collect();
+    let attended: i64 = recent.iter().map(|stats| stats.attended).sum();
+    let rsvps: i64 = recent.iter().map(|stats| stats.rsvps).sum();
+    let rsvps_attended: i64 = recent.iter().map(|stats| stats.rsvps_attended).sum();
+
+    #[allow(clippy::cast_precision_loss)]
+    let average = attended as f64 / past.len() as f64;
+    description.push_str(&format!("**Average attendance:** {average:.1}\n"));
+    if rsvps > 0 {
+      description.push_str(&format!(
+        "**RSVPs who attended:** {}%\n",
+        rsvps_attended * 100 / rsvps
+      ));
+    }
+
+    description.push_str("\n**Recent occurrences:**\n");
+    description.push_str(
+      &past
+        .iter()
+        .map(|occurrence| occurrence_line(*occurrence, stats.get(occurrence)))
+        .collect::<Vec<_>>()
+        .join("\n"),
+    );
+  }
+
+  ctx
+    .send(
+      CreateReply::default()
+        .embed(
+          BloomBotEmbed::new()
+            .title(format!(":bar_chart: {} Attendance", event.title))
+            .description(description),
+        )
+        .ephemeral(true),
+    )
+    .await?;
+
+  Ok(())
+}
+
+/// Subscribe to events in your calendar
+///
+/// Shows a link to this server's calendar feed, which you can add to most calendar apps, such as Google Calendar, Apple Calendar, or Outlook, to see upcoming events there.
+///
+/// The feed includes community events, scheduled events, and monthly challenges, and your calendar app will keep it up to date.
+#[poise::command(slash_command)]
+async fn calendar(ctx: Context<'_>) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let web_config = ctx.data().bot_config.get().web.clone();
+  if web_config.public_url.is_none() {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} Calendar feeds aren't available yet.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  // Feeds are created the first time a member asks for one.
+  let feed = match DatabaseHandler::get_calendar_feed(&mut transaction, &guild_id).await? {
+    Some(feed) => feed,
+    None => {
+      let feed = CalendarFeed::new(guild_id);
+      DatabaseHandler::add_calendar_feed(&mut transaction, &feed).await?;
+      feed
+    }
+  };
+
+  let feed_url = web_config
+    .calendar_url(&feed.token)
+    .with_context(|| "Failed to build calendar feed URL")?;
+
+  database::commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(format!(
+      "{} Add this link to your calendar app to subscribe to this server's events:\n<{feed_url}>\n\nLook for an option such as **Add calendar from URL** or **Subscribe to calendar**. Please don't share the link outside this server.",
+      emoji.mmcheck
+    )),
+    Visibility::Ephemeral,
+  )
+  .await?;
+
+  Ok(())
+}
+
+/// Transcribe community events
+///
+/// Commands to record events in voice channels and post a transcript afterwards. Only members who agree to be recorded are included.
+#[poise::command(
+  slash_command,
+  subcommands("transcript_allow", "transcript_start", "transcript_stop"),
+  subcommand_required
+)]
+#[allow(clippy::unused_async)]
+async fn transcript(_: Context<'_>) -> Result<()> {
+  Ok(())
+}
+
+/// Allow an event to be transcribed
+///
+/// Sets whether an event can be recorded and transcribed with `/event transcript start`. Only events in voice or stage channels can be transcribed.
+///
+/// Requires `Manage Events` permissions.
+#[poise::command(
+  slash_command,
+  rename = "allow",
+  required_permissions = "MANAGE_EVENTS"
+)]
+async fn transcript_allow(
+  ctx: Context<'_>,
+  #[description = "The event to allow or disallow transcripts for"]
+  #[autocomplete = "autocomplete_event"]
+  event: String,
+  #[description = "Whether the event can be transcribed"] allowed: bool,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  let Some(event) =
+    DatabaseHandler::get_community_event(&mut transaction, &guild_id, &event).await?
+  else {
+    return reply_not_found(ctx, &event).await;
+  };
+
+  DatabaseHandler::set_event_transcription(&mut transaction, &event.id, allowed).await?;
+
+  let message = if allowed {
+    format!(
+      "**{}** can now be transcribed with `/event transcript start`. Members are asked for their consent each time, and only those who agree are recorded.",
+      event.title
+    )
+  } else {
+    format!("**{}** can no longer be transcribed.", event.title)
+  };
+
+  database::commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(format!("{} {message}", emoji.mmcheck)),
+    Visibility::Ephemeral,
+  )
+  .await?;
+
+  Ok(())
+}
+
+/// Start transcribing an event
+///
+/// Joins the event's voice channel and starts recording. Members in the channel are asked whether they agree to be recorded, and only those who agree are included in the transcript.
+///
+/// The transcript is posted in a thread in the chosen channel when the recording is stopped with `/event transcript stop`.
+///
+/// Requires `Manage Events` permissions.
+#[poise::command(
+  slash_command,
+  rename = "start",
+  required_permissions = "MANAGE_EVENTS"
+)]
+async fn transcript_start(
+  ctx: Context<'_>,
+  #[description = "The event to transcribe"]
+  #[autocomplete = "autocomplete_event"]
+  event: String,
+  #[description = "The channel to post the transcript in"]
+  #[channel_types("Text")]
+  channel: GuildChannel,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  if !ctx.data().transcription.is_enabled() {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} Transcription isn't set up for Bloom. Please ask the bot's maintainers to choose a transcription provider.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  if channel.kind != ChannelType::Text || channel.guild_id != guild_id {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} The transcript must be posted in a text channel in this server.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+  let Some(event) =
+    DatabaseHandler::get_community_event(&mut transaction, &guild_id, &event).await?
+  else {
+    return reply_not_found(ctx, &event).await;
+  };
+  drop(transaction);
+
+  let is_voice = ctx
+    .guild()
+    .and_then(|guild| {
+      guild
+        .channels
+        .get(&event.channel_id)
+        .map(|channel| channel.kind)
+    })
+    .is_some_and(|kind| matches!(kind, ChannelType::Voice | ChannelType::Stage));
+  let problem = if !event.transcribe {
+    Some(format!(
+      "**{}** hasn't been allowed to be transcribed. Staff can allow it with `/event transcript allow`.",
+      event.title
+    ))
+  } else if !is_voice {
+    Some(format!(
+      "**{}** doesn't take place in a voice or stage channel.",
+      event.title
+    ))
+  } else if ctx.data().transcription.recording(guild_id).is_some() {
+    Some(
+      "An event is already being transcribed. Please stop it with `/event transcript stop` first."
+        .to_owned(),
+    )
+  } else {
+    None
+  };
+  if let Some(problem) = problem {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!("{} {problem}", emoji.mminfo))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let manager = songbird::get(ctx.serenity_context())
+    .await
+    .with_context(|| "Failed to retrieve voice client")?;
+  if manager.get(guild_id).is_some() {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} Bloom is already in a voice channel, such as for `/talk`. Please stop it with `/talk stop` first.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  ctx.defer_ephemeral().await?;
+
+  let recording = ctx
+    .data()
+    .transcription
+    .start(manager, guild_id, event.channel_id, channel.id, event.title)
+    .await?;
+  event
+    .channel_id
+    .send_message(ctx, transcripts::consent_prompt(&recording))
+    .await?;
+
+  ctx
+    .send(
+      CreateReply::default()
+        .content(format!(
+          "{} Recording **{}** in {}. Members have been asked for their consent, and only those who agree are recorded. Stop with `/event transcript stop` to post the transcript in {}.",
+          emoji.mmcheck,
+          recording.event_title,
+          recording.voice_channel.mention(),
+          recording.transcript_channel.mention()
+        ))
+        .ephemeral(true),
+    )
+    .await?;
+
+  Ok(())
+}
+
+/// Stop transcribing an event
+///
+/// Stops recording, leaves the voice channel, and posts the transcript. Transcribing the end of the recording may take a moment.
+///
+/// Requires `Manage Events` permissions.
+#[poise::command(slash_command, rename = "stop", required_permissions = "MANAGE_EVENTS")]
+async fn transcript_stop(ctx: Context<'_>) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  ctx.defer_ephemeral().await?;
+
+  let manager = songbird::get(ctx.serenity_context())
+    .await
+    .with_context(|| "Failed to retrieve voice client")?;
+  let Some((recording, lines)) = ctx.data().transcription.stop(manager, guild_id).await? else {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!("{} No event is being transcribed.", emoji.mminfo))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  };
+
+  let content = if lines.is_empty() {
+    format!(
+      "{} The recording has stopped, but nothing was transcribed, so no transcript has been posted. This can happen if nobody agreed to be recorded.",
+      emoji.mminfo
+    )
+  } else {
+    let intro = transcripts::post_transcript(ctx.serenity_context(), &recording, &lines).await?;
+    format!(
+      "{} The transcript of **{}** has been posted: {}",
+      emoji.mmcheck,
+      recording.event_title,
+      intro.link()
+    )
+  };
+
+  ctx
+    .send(CreateReply::default().content(content).ephemeral(true))
+    .await?;
+
+  Ok(())
+}
@@ -0,0 +1,247 @@
+use anyhow::{Context as AnyhowContext, Result};
+use log::info;
+use poise::serenity_prelude::{ChannelType, GuildChannel, Mentionable};
+use poise::CreateReply;
+
+use crate::commands::helpers::common::Visibility;
+use crate::commands::helpers::database::{self, MessageType};
+use crate::commands::helpers::pagination::{PageRowRef, PageType, Paginator};
+use crate::config::ENTRIES_PER_PAGE;
+use crate::data::feed::FeedSubscription;
+use crate::database::DatabaseHandler;
+use crate::feeds;
+use crate::Context;
+
+/// The most feeds a server can subscribe to.
+const MAX_FEEDS: usize = 10;
+
+/// Commands for managing feed subscriptions
+///
+/// Commands to subscribe channels to RSS or Atom feeds, such as dharma talks or blog posts. New items in each feed are posted to its channel.
+///
+/// Requires `Manage Messages` permissions.
+#[poise::command(
+  slash_command,
+  required_permissions = "MANAGE_MESSAGES",
+  default_member_permissions = "MANAGE_MESSAGES",
+  category = "Moderator Commands",
+  subcommands("add", "remove", "list"),
+  subcommand_required,
+  guild_only
+)]
+#[allow(clippy::unused_async)]
+pub async fn feed(_: Context<'_>) -> Result<()> {
+  Ok(())
+}
+
+/// Subscribe a channel to a feed
+///
+/// Subscribes a channel to an RSS or Atom feed. Feeds are checked every 15 minutes, and new items are posted to the channel. Items already in the feed when it's added aren't posted.
+#[poise::command(slash_command)]
+async fn add(
+  ctx: Context<'_>,
+  #[description = "The address of the RSS or Atom feed"]
+  #[max_length = 500]
+  url: String,
+  #[description = "The channel to post new items in"]
+  #[channel_types("Text", "News")]
+  channel: GuildChannel,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  if !matches!(channel.kind, ChannelType::Text | ChannelType::News) || channel.guild_id != guild_id
+  {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} Please choose a text or announcement channel.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let url = match feeds::parse_url(&url) {
+    Ok(url) => url,
+    Err(e) => {
+      ctx
+        .send(
+          CreateReply::default()
+            .content(format!("{} {e}.", emoji.mminfo))
+            .ephemeral(true),
+        )
+        .await?;
+      return Ok(());
+    }
+  };
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  let existing = DatabaseHandler::get_feed_subscriptions(&mut transaction, &guild_id).await?;
+  let problem = if existing
+    .iter()
+    .any(|subscription| subscription.url == url && subscription.channel_id == channel.id)
+  {
+    Some(format!(
+      "{} is already subscribed to this feed.",
+      channel.mention()
+    ))
+  } else if existing.len() >= MAX_FEEDS {
+    Some(format!(
+      "This server is already subscribed to {MAX_FEEDS} feeds. Please remove one with `/feed remove` first."
+    ))
+  } else {
+    None
+  };
+  if let Some(problem) = problem {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!("{} {problem}", emoji.mminfo))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  ctx.defer_ephemeral().await?;
+
+  let feed = match ctx.data().feeds.fetch(&url).await {
+    Ok(feed) => feed,
+    Err(e) => {
+      info!("Failed to add feed {url}: {e:?}");
+      ctx
+        .send(
+          CreateReply::default()
+            .content(format!(
+              "{} That feed couldn't be read: {e}. Please check the address and try again.",
+              emoji.mminfo
+            ))
+            .ephemeral(true),
+        )
+        .await?;
+      return Ok(());
+    }
+  };
+
+  let subscription = FeedSubscription::new(
+    guild_id,
+    channel.id,
+    url,
+    feed.title.clone(),
+    ctx.author().id,
+  );
+  DatabaseHandler::add_feed_subscription(&mut transaction, &subscription).await?;
+  // Items already in the feed are marked as seen, so only new ones are posted
+  for item in &feed.items {
+    DatabaseHandler::mark_feed_item_seen(&mut transaction, &subscription, &item.key).await?;
+  }
+
+  database::commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(format!(
+      "{} {} is now subscribed to **{}**. New items will be posted as they're published.",
+      emoji.mmcheck,
+      channel.mention(),
+      feed.title
+    )),
+    Visibility::Ephemeral,
+  )
+  .await?;
+
+  Ok(())
+}
+
+/// Unsubscribe a channel from a feed
+///
+/// Unsubscribes a channel from a feed, so new items are no longer posted. Items which have already been posted aren't affected.
+#[poise::command(slash_command)]
+async fn remove(
+  ctx: Context<'_>,
+  #[description = "The ID of the subscription, as shown by /feed list"] id: String,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  if DatabaseHandler::remove_feed_subscription(&mut transaction, &guild_id, id.trim()).await? == 0 {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} No feed subscription with ID `{}` was found.",
+            emoji.mminfo,
+            id.trim()
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  database::commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(format!(
+      "{} The feed subscription has been removed.",
+      emoji.mmcheck
+    )),
+    Visibility::Ephemeral,
+  )
+  .await?;
+
+  Ok(())
+}
+
+/// List feed subscriptions
+///
+/// Lists the feeds this server's channels are subscribed to, with their IDs.
+#[poise::command(slash_command)]
+async fn list(
+  ctx: Context<'_>,
+  #[description = "The page to show"] page: Option<usize>,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+  let subscriptions = DatabaseHandler::get_feed_subscriptions(&mut transaction, &guild_id).await?;
+  drop(transaction);
+
+  if subscriptions.is_empty() {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} No channels are subscribed to feeds. Add one with `/feed add`.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let entries: Vec<PageRowRef> = subscriptions
+    .iter()
+    .map(|subscription| subscription as PageRowRef)
+    .collect();
+
+  Paginator::new("Feed Subscriptions", &entries, ENTRIES_PER_PAGE.default)
+    .paginate(ctx, page, PageType::Standard, Visibility::Ephemeral)
+    .await?;
+
+  Ok(())
+}
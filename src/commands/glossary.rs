@@ -1,12 +1,15 @@
 use std::time::{Duration, Instant};
 
 use anyhow::{Context as AnyhowContext, Result};
-use log::info;
+use log::{info, warn};
 use pgvector::Vector;
 use poise::serenity_prelude::{builder::*, ChannelId, ComponentInteractionCollector};
 use poise::CreateReply;
 
+use crate::commands::helpers::terms::{self, autocomplete_term};
 use crate::config::{BloomBotEmbed, CHANNELS, ENTRIES_PER_PAGE};
+use crate::data::guild_feature::Feature;
+use crate::data::term::{Term, VectorSearch};
 use crate::database::DatabaseHandler;
 // use crate::pagination::{PageRowRef, Pagination};
 use crate::Context;
@@ -197,7 +200,9 @@ async fn list(
 #[poise::command(slash_command)]
 async fn info(
   ctx: Context<'_>,
-  #[description = "The term to show information about"] term: String,
+  #[description = "The term to show information about"]
+  #[autocomplete = "autocomplete_term"]
+  term: String,
 ) -> Result<()> {
   let guild_id = ctx
     .guild_id()
@@ -207,55 +212,11 @@ async fn info(
 
   let term_info = DatabaseHandler::get_term(&mut transaction, &guild_id, term.as_str()).await?;
   let mut embed = BloomBotEmbed::new();
+  let mut buttons = None;
 
   if let Some(term_info) = term_info {
-    embed = embed.title(term_info.name).description(term_info.meaning);
-    let usage = term_info.usage.unwrap_or(String::new());
-    if !usage.is_empty() {
-      embed = embed.field("Example of Usage:", usage, false);
-    }
-    let links = term_info.links.unwrap_or(Vec::new());
-    if !links.is_empty() {
-      embed = embed.field(
-        "Related Resources:",
-        {
-          let mut field = String::new();
-          let mut count = 1;
-
-          for link in links {
-            field.push_str(&format!("{count}. {link}\n"));
-            count += 1;
-          }
-
-          field
-        },
-        false,
-      );
-    }
-    let aliases = term_info.aliases.clone().unwrap_or(Vec::new());
-    if !aliases.is_empty() {
-      embed = embed.field(
-        "Aliases:",
-        {
-          let mut field = String::new();
-          let alias_count = aliases.len();
-
-          for (i, alias) in aliases.iter().enumerate() {
-            field.push_str(alias);
-            if i < (alias_count - 1) {
-              field.push_str(", ");
-            }
-          }
-
-          field
-        },
-        false,
-      );
-    }
-    let category = term_info.category.unwrap_or(String::new());
-    if !category.is_empty() {
-      embed = embed.footer(CreateEmbedFooter::new(format!("Categories: {category}")));
-    }
+    embed = terms::term_embed(&term_info);
+    buttons = terms::see_also_buttons(&term_info);
   } else {
     let possible_terms =
       DatabaseHandler::get_possible_terms(&mut transaction, &guild_id, term.as_str(), 0.7).await?;
@@ -265,51 +226,9 @@ async fn info(
         .first()
         .with_context(|| "Failed to retrieve first element of possible_terms")?;
 
-      embed = embed
-        .title(&possible_term.name)
-        .description(&possible_term.meaning);
-      let usage = possible_term.usage.clone().unwrap_or(String::new());
-      if !usage.is_empty() {
-        embed = embed.field("Example of Usage:", usage, false);
-      }
-      let links = possible_term.links.clone().unwrap_or(Vec::new());
-      if !links.is_empty() {
-        embed = embed.field(
-          "Related Resources:",
-          {
-            let mut field = String::new();
-            let mut count = 1;
-
-            for link in links {
-              field.push_str(&format!("{count}. {link}\n"));
-              count += 1;
-            }
-
-            field
-          },
-          false,
-        );
-      }
-      let aliases = possible_term.aliases.clone().unwrap_or(Vec::new());
-      if !aliases.is_empty() {
-        embed = embed.field(
-          "Aliases:",
-          {
-            let mut field = String::new();
-            let alias_count = aliases.len();
-
-            for (i, alias) in aliases.iter().enumerate() {
-              field.push_str(alias);
-              if i < (alias_count - 1) {
-                field.push_str(", ");
-              }
-            }
-
-            field
-          },
-          false,
-        );
-      }
+      embed = terms::term_embed(possible_term);
+      buttons = terms::see_also_buttons(possible_term);
+
       let category = possible_term.category.clone().unwrap_or(String::new());
       if category.is_empty() {
         embed = embed.footer(CreateEmbedFooter::new(format!(
@@ -351,18 +270,30 @@ async fn info(
     }
   }
 
-  ctx.send(CreateReply::default().embed(embed)).await?;
+  let mut reply = CreateReply::default().embed(embed);
+  if let Some(buttons) = buttons {
+    reply = reply.components(vec![buttons]);
+  }
+
+  ctx.send(reply).await?;
 
   Ok(())
 }
 
+/// The number of results shown on each page of `/glossary search` results.
+const SEARCH_RESULTS_PER_PAGE: usize = 3;
+
 /// Search glossary entries using keywords or phrases
 ///
-/// Searches glossary entries using keywords or phrases, leveraging AI to find the closest matches.
+/// Searches glossary entries using keywords or phrases, leveraging AI to find the closest matches. Returns up to 3 results by default, or up to 10 using the `results` option.
 #[poise::command(slash_command)]
 async fn search(
   ctx: Context<'_>,
   #[description = "The term to search for"] search: String,
+  #[description = "The maximum number of results to show (defaults to 3)"]
+  #[min = 1]
+  #[max = 10]
+  results: Option<i64>,
 ) -> Result<()> {
   ctx.defer().await?;
 
@@ -372,95 +303,230 @@ async fn search(
     .guild_id()
     .with_context(|| "Failed to retrieve guild ID from context")?;
 
+  let results = results.unwrap_or(3);
+
   let start_time = Instant::now();
   let mut transaction = data.db.start_transaction_with_retry(5).await?;
-  let vector = Vector::from(
-    data
+  let threshold = data
+    .settings
+    .get(&data.db, guild_id)
+    .await?
+    .search_threshold;
+
+  // Semantic search is unavailable if it's been turned off, the monthly cap has been
+  // reached, or the API is down
+  let semantic_results = if !data.features.enabled(Some(guild_id), Feature::AiSearch)
+    || data.embeddings.monthly_cap_reached(guild_id).await?
+  {
+    None
+  } else {
+    match data
       .embeddings
-      .create_embedding(search.clone(), ctx.author().id)
-      .await?,
-  );
-  let possible_terms =
-    DatabaseHandler::search_terms_by_vector(&mut transaction, &guild_id, &vector, 3).await?;
+      .create_embedding(search.clone(), guild_id, ctx.author().id)
+      .await
+    {
+      Ok(embedding) => Some(
+        DatabaseHandler::search_terms_by_vector(
+          &mut transaction,
+          &guild_id,
+          &Vector::from(embedding),
+          results,
+          threshold,
+        )
+        .await?,
+      ),
+      Err(e) => {
+        warn!("Falling back to similarity search for glossary search: {e:?}");
+        None
+      }
+    }
+  };
+
+  let fallback = semantic_results.is_none();
+  let fields = match semantic_results {
+    Some(possible_terms) => vector_search_fields(&possible_terms),
+    None => {
+      let possible_terms =
+        DatabaseHandler::get_possible_terms(&mut transaction, &guild_id, &search, 0.3).await?;
+      similarity_search_fields(&possible_terms, results)
+    }
+  };
   let search_time = start_time.elapsed();
 
-  let mut embed = BloomBotEmbed::new();
-  let mut terms_returned = 0;
-  embed = embed.title(format!("Search results for `{search}`"));
+  drop(transaction);
 
-  if possible_terms.is_empty() {
-    embed =
-      embed.description("No terms were found. Try browsing the glossary with `/glossary list`.");
-  } else {
-    for (index, possible_term) in possible_terms.iter().enumerate() {
-      // Set threshold for terms to include
-      if possible_term.distance_score.unwrap_or(1.0) > 0.3 {
-        continue;
-      }
-      let relevance_description = match possible_term.distance_score {
-        Some(score) => {
-          let similarity_score = (1.0 - score) * 100.0;
-          info!(
-            "Term {} has a similarity score of {}",
-            index + 1,
-            similarity_score
+  let pages: Vec<&[(String, String)]> = fields.chunks(SEARCH_RESULTS_PER_PAGE).collect();
+  let total_pages = pages.len();
+
+  let page_embed = |current_page: usize| {
+    let mut embed = BloomBotEmbed::new().title(format!("Search results for `{search}`"));
+
+    match pages.get(current_page) {
+      Some(page) => {
+        if fallback {
+          embed = embed.description(
+            "*AI search is currently unavailable, so these results are based on spelling similarity.*",
           );
-          match similarity_score.round() {
-            100.0..=f64::MAX => "Exact match",
-            // Adjust for cosine similarity
-            90.0..=99.0 => "High",
-            80.0..=89.0 => "Medium",
-            70.0..=79.0 => "Low",
-            // 80..=99 => "Very similar",
-            // 60..=79 => "Similar",
-            // 40..=59 => "Somewhat similar",
-            // 20..=39 => "Not very similar",
-            // 0..=19 => "Not similar",
-            _ => "Unknown",
-          }
         }
-        None => "Unknown",
-      };
+        for (name, value) in *page {
+          embed = embed.field(name, value, false);
+        }
+      }
+      None => {
+        embed = embed
+          .description("No terms were found. Try browsing the glossary with `/glossary list`.");
+      }
+    }
 
-      // If longer than 1024 (embed field max) - 45 (relevance message),
-      // truncate to 979 - 3 for "..."
-      let meaning = if possible_term.meaning.len() > 979 {
-        format!(
-          "{}...",
-          possible_term.meaning.chars().take(976).collect::<String>()
-        )
-      } else {
-        possible_term.meaning.clone()
-      };
+    let footer = if total_pages > 1 {
+      format!(
+        "Page {} of {total_pages}・Search took {}ms",
+        current_page + 1,
+        search_time.as_millis()
+      )
+    } else {
+      format!("Search took {}ms", search_time.as_millis())
+    };
 
-      embed = embed.field(
-        format!("Term {}: `{}`", index + 1, &possible_term.term_name),
-        format!(
-          // "```{meaning}```\n> Estimated relevance: *{relevance_description}*"
-          "{meaning}\n```Estimated relevance: {relevance_description}```\n** **"
-        ),
-        false,
-      );
+    embed.footer(CreateEmbedFooter::new(footer))
+  };
 
-      terms_returned += 1;
-    }
+  let ctx_id = ctx.id();
+  let prev_button_id = format!("{ctx_id}prev");
+  let next_button_id = format!("{ctx_id}next");
+
+  let mut current_page = 0;
+
+  let mut reply = CreateReply::default().embed(page_embed(current_page));
+  if total_pages > 1 {
+    reply = reply.components(vec![CreateActionRow::Buttons(vec![
+      CreateButton::new(&prev_button_id).label("Previous"),
+      CreateButton::new(&next_button_id).label("Next"),
+    ])]);
   }
 
-  embed = embed.footer(CreateEmbedFooter::new(format!(
-    "Search took {}ms",
-    search_time.as_millis()
-  )));
+  ctx.send(reply).await?;
 
-  if terms_returned == 0 {
-    embed =
-      embed.description("No terms were found. Try browsing the glossary with `/glossary list`.");
+  if total_pages <= 1 {
+    return Ok(());
   }
 
-  ctx.send(CreateReply::default().embed(embed)).await?;
+  // Loop through incoming interactions with the navigation buttons
+  while let Some(press) = ComponentInteractionCollector::new(ctx)
+    // We defined our button IDs to start with `ctx_id`. If they don't, some other command's
+    // button was pressed
+    .filter(move |press| press.data.custom_id.starts_with(&ctx_id.to_string()))
+    // Timeout when no navigation button has been pressed for 24 hours
+    .timeout(Duration::from_secs(3600 * 24))
+    .await
+  {
+    // Depending on which button was pressed, go to next or previous page
+    if press.data.custom_id == next_button_id {
+      current_page += 1;
+      if current_page >= total_pages {
+        current_page = 0;
+      }
+    } else if press.data.custom_id == prev_button_id {
+      current_page = current_page.checked_sub(1).unwrap_or(total_pages - 1);
+    } else {
+      // This is an unrelated button interaction
+      continue;
+    }
+
+    // Update the message with the new page contents
+    press
+      .create_response(
+        ctx.serenity_context(),
+        CreateInteractionResponse::UpdateMessage(
+          CreateInteractionResponseMessage::new().embed(page_embed(current_page)),
+        ),
+      )
+      .await?;
+  }
 
   Ok(())
 }
 
+/// Creates the embed fields for semantic search results, with an estimate of the relevance
+/// of each result.
+fn vector_search_fields(possible_terms: &[VectorSearch]) -> Vec<(String, String)> {
+  let mut fields = Vec::with_capacity(possible_terms.len());
+
+  for (index, possible_term) in possible_terms.iter().enumerate() {
+    let relevance_description = match possible_term.distance_score {
+      Some(score) => {
+        let similarity_score = (1.0 - score) * 100.0;
+        info!(
+          "Term {} has a similarity score of {}",
+          index + 1,
+          similarity_score
+        );
+        match similarity_score.round() {
+          100.0..=f64::MAX => "Exact match",
+          // Adjust for cosine similarity
+          90.0..=99.0 => "High",
+          80.0..=89.0 => "Medium",
+          70.0..=79.0 => "Low",
+          // 80..=99 => "Very similar",
+          // 60..=79 => "Similar",
+          // 40..=59 => "Somewhat similar",
+          // 20..=39 => "Not very similar",
+          // 0..=19 => "Not similar",
+          _ => "Unknown",
+        }
+      }
+      None => "Unknown",
+    };
+
+    // If longer than 1024 (embed field max) - 45 (relevance message),
+    // truncate to 979 - 3 for "..."
+    let meaning = if possible_term.meaning.len() > 979 {
+      format!(
+        "{}...",
+        possible_term.meaning.chars().take(976).collect::<String>()
+      )
+    } else {
+      possible_term.meaning.clone()
+    };
+
+    fields.push((
+      format!("Term {}: `{}`", index + 1, &possible_term.term_name),
+      format!(
+        // "```{meaning}```\n> Estimated relevance: *{relevance_description}*"
+        "{meaning}\n```Estimated relevance: {relevance_description}```\n** **"
+      ),
+    ));
+  }
+
+  fields
+}
+
+/// Creates the embed fields for up to `limit` trigram similarity search results, used when
+/// semantic search is unavailable.
+fn similarity_search_fields(possible_terms: &[Term], limit: i64) -> Vec<(String, String)> {
+  possible_terms
+    .iter()
+    .take(usize::try_from(limit).unwrap_or_default())
+    .enumerate()
+    .map(|(index, possible_term)| {
+      // Truncate to the embed field max of 1024, less 3 for "..." and 5 for spacing
+      let meaning = if possible_term.meaning.len() > 1016 {
+        format!(
+          "{}...",
+          possible_term.meaning.chars().take(1013).collect::<String>()
+        )
+      } else {
+        possible_term.meaning.clone()
+      };
+
+      (
+        format!("Term {}: `{}`", index + 1, &possible_term.name),
+        format!("{meaning}\n** **"),
+      )
+    })
+    .collect()
+}
+
 /// Suggest a term for the glossary
 ///
 /// Suggest a term for addition to the glossary.
@@ -0,0 +1,210 @@
+use anyhow::{Context as AnyhowContext, Result};
+use chrono::Utc;
+use poise::serenity_prelude::{ChannelType, CreateMessage, GuildChannel, Mentionable};
+use poise::CreateReply;
+
+use crate::commands::helpers::common::Visibility;
+use crate::commands::helpers::database::{self, MessageType};
+use crate::commands::helpers::threads;
+use crate::data::goal::{Goal, GoalPeriod};
+use crate::database::DatabaseHandler;
+use crate::events::goals;
+use crate::Context;
+
+/// Commands for managing server goals
+///
+/// Commands to start, check on, or cancel a collective meditation goal for the server.
+///
+/// Requires `Administrator` permissions.
+#[poise::command(
+  slash_command,
+  required_permissions = "ADMINISTRATOR",
+  default_member_permissions = "ADMINISTRATOR",
+  category = "Admin Commands",
+  subcommands("start", "status", "cancel"),
+  subcommand_required,
+  guild_only
+)]
+#[allow(clippy::unused_async)]
+pub async fn goal(_: Context<'_>) -> Result<()> {
+  Ok(())
+}
+
+/// Start a goal for this week or month
+///
+/// Starts a collective goal for the server, counting all time tracked by members during the current week (starting Monday) or month, in UTC.
+///
+/// Progress updates are posted daily in the specified channel, defaulting to the tracking channel if one has been set and it isn't a forum, or the current channel otherwise. The goal is celebrated as soon as it has been reached.
+#[poise::command(slash_command)]
+async fn start(
+  ctx: Context<'_>,
+  #[description = "Whether the goal is for this week or this month"] period: GoalPeriod,
+  #[description = "The number of minutes to meditate together"]
+  #[min = 1]
+  target_minutes: i64,
+  #[description = "The channel to post progress updates in"]
+  #[channel_types("Text")]
+  channel: Option<GuildChannel>,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  if let Some(active_goal) = DatabaseHandler::get_active_goal(&mut transaction, &guild_id).await? {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} A goal of **{}** minutes is already running. Please cancel it before starting a new one.",
+            emoji.mminfo, active_goal.target_minutes
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let channel_id = match channel {
+    Some(channel) if channel.kind == ChannelType::Text => channel.id,
+    Some(_) => {
+      ctx
+        .send(
+          CreateReply::default()
+            .content(format!("{} Please choose a text channel.", emoji.mminfo))
+            .ephemeral(true),
+        )
+        .await?;
+      return Ok(());
+    }
+    None => {
+      let tracking_channel = DatabaseHandler::get_guild_settings(&mut transaction, &guild_id)
+        .await?
+        .tracking_channel;
+      // Forums can't be posted in directly, so updates go to the current channel instead
+      match tracking_channel {
+        Some(channel_id)
+          if threads::guild_channel(ctx, channel_id)
+            .await
+            .is_some_and(|channel| channel.kind == ChannelType::Text) =>
+        {
+          channel_id
+        }
+        _ => ctx.channel_id(),
+      }
+    }
+  };
+
+  let now = Utc::now();
+  let goal = Goal::new(
+    guild_id,
+    channel_id,
+    period,
+    target_minutes,
+    now,
+    ctx.author().id,
+  )
+  .with_context(|| "Failed to determine goal period")?;
+
+  DatabaseHandler::add_goal(&mut transaction, &goal).await?;
+  DatabaseHandler::mark_goal_updated(&mut transaction, &goal.id, &now).await?;
+  let progress = DatabaseHandler::get_goal_progress(&mut transaction, &goal).await?;
+
+  database::commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(format!(
+      "{} Goal of **{target_minutes}** minutes started. Progress will be posted in {}.",
+      emoji.mmcheck,
+      channel_id.mention()
+    )),
+    Visibility::Ephemeral,
+  )
+  .await?;
+
+  channel_id
+    .send_message(
+      ctx,
+      CreateMessage::new().embed(goals::progress_embed(&goal, progress)),
+    )
+    .await?;
+
+  Ok(())
+}
+
+/// Show progress towards the current goal
+///
+/// Shows progress towards the server's current goal.
+#[poise::command(slash_command)]
+async fn status(ctx: Context<'_>) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  let Some(goal) = DatabaseHandler::get_active_goal(&mut transaction, &guild_id).await? else {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!("{} No goal is currently running.", emoji.mminfo))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  };
+
+  let progress = DatabaseHandler::get_goal_progress(&mut transaction, &goal).await?;
+
+  ctx
+    .send(
+      CreateReply::default()
+        .embed(goals::progress_embed(&goal, progress))
+        .ephemeral(true),
+    )
+    .await?;
+
+  Ok(())
+}
+
+/// Cancel the current goal
+///
+/// Cancels the server's current goal. No further progress updates will be posted.
+#[poise::command(slash_command)]
+async fn cancel(ctx: Context<'_>) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  let Some(goal) = DatabaseHandler::get_active_goal(&mut transaction, &guild_id).await? else {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!("{} No goal is currently running.", emoji.mminfo))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  };
+
+  DatabaseHandler::close_goal(&mut transaction, &guild_id, &goal.id).await?;
+
+  database::commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(format!(
+      "{} Goal of **{}** minutes cancelled.",
+      emoji.mmcheck, goal.target_minutes
+    )),
+    Visibility::Ephemeral,
+  )
+  .await?;
+
+  Ok(())
+}
@@ -0,0 +1,210 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use log::{error, info, warn};
+use poise::serenity_prelude::{
+  CacheHttp, ChannelType, Context as SerenityContext, CreateEmbed, CreateEmbedFooter,
+  CreateMessage, GuildId, Mentionable, Message, User,
+};
+
+use crate::config::BloomBotEmbed;
+use crate::data::guild_settings::GuildSettings;
+use crate::data::scheduled_announcement::ScheduledAnnouncement;
+use crate::data::tracking_profile::Profile;
+use crate::database::DatabaseHandler;
+
+/// How often to check for scheduled announcements which are due to be posted.
+const SEND_INTERVAL: Duration = Duration::from_secs(60);
+
+/// The title of the monthly challenge winner announcement, until staff set their own with
+/// `/config announcements`.
+pub const DEFAULT_WINNER_TITLE: &str = ":tada: Monthly Challenge Winner :tada:";
+
+/// The body of the monthly challenge winner announcement, until staff set their own with
+/// `/config announcements`.
+pub const DEFAULT_WINNER_MESSAGE: &str = "**Meditator in the Spotlight for {month}**\nCongratulations to **{user}** on winning our {month} challenge, with a meditation time of **{minutes}** minutes for the month!";
+
+/// Fills in the placeholders in a winner announcement template. Unknown placeholders are
+/// left as they are.
+pub fn render_winner(template: &str, user: &str, month: DateTime<Utc>, minutes: i64) -> String {
+  template
+    .replace("{user}", user)
+    .replace("{month}", &month.format("%B").to_string())
+    .replace("{year}", &month.format("%Y").to_string())
+    .replace("{minutes}", &minutes.to_string())
+}
+
+/// Creates the monthly challenge winner announcement from the server's template. Mentions
+/// don't work in embed titles, so `{user}` is the winner's name in the title and a mention
+/// in the body. The winner's preferred display name and pronouns from their [`Profile`] are
+/// used when set.
+pub fn winner_embed(
+  settings: &GuildSettings,
+  winner: &User,
+  profile: &Profile,
+  month: DateTime<Utc>,
+  minutes: i64,
+) -> CreateEmbed {
+  let title = settings
+    .winner_title
+    .as_deref()
+    .unwrap_or(DEFAULT_WINNER_TITLE);
+  let message = settings
+    .winner_message
+    .as_deref()
+    .unwrap_or(DEFAULT_WINNER_MESSAGE);
+
+  let embed = BloomBotEmbed::new()
+    .title(render_winner(
+      title,
+      &profile.announce_as(&winner.name),
+      month,
+      minutes,
+    ))
+    .description(render_winner(
+      message,
+      &profile.announce_as(&winner.mention().to_string()),
+      month,
+      minutes,
+    ))
+    .thumbnail(winner.avatar_url().unwrap_or_default())
+    .footer(CreateEmbedFooter::new(format!(
+      "Meditation Challenge for {} | Selected on {}",
+      month.format("%B %Y"),
+      Utc::now().format("%B %d, %Y")
+    )));
+
+  match &settings.winner_image {
+    Some(image) => embed.image(image),
+    None => embed,
+  }
+}
+
+/// Publishes an announcement Bloom has posted, so that servers following the channel
+/// receive it. Only announcements in announcement channels are published, and only when
+/// the server has turned on auto-publishing with `/config autopublish`.
+///
+/// The announcement has already been posted by the time this is called, so failures are
+/// logged rather than returned.
+pub async fn publish(
+  ctx: impl CacheHttp + Copy,
+  db: &DatabaseHandler,
+  guild_id: GuildId,
+  message: &Message,
+) {
+  let enabled = match db.start_transaction_with_retry(5).await {
+    Ok(mut transaction) => DatabaseHandler::get_guild_settings(&mut transaction, &guild_id)
+      .await
+      .is_ok_and(|settings| settings.auto_publish),
+    Err(_) => false,
+  };
+  if !enabled {
+    return;
+  }
+
+  let is_news = message
+    .channel_id
+    .to_channel(ctx)
+    .await
+    .is_ok_and(|channel| {
+      channel
+        .guild()
+        .is_some_and(|channel| channel.kind == ChannelType::News)
+    });
+  if !is_news {
+    return;
+  }
+
+  match message.channel_id.crosspost(ctx.http(), message.id).await {
+    Ok(_) => info!("Published announcement {} in guild {guild_id}", message.id),
+    Err(e) => warn!("Failed to publish announcement {}: {e}", message.id),
+  }
+}
+
+/// Posts a scheduled announcement, publishing it if the server auto-publishes. The
+/// announcement is marked as posted first, so it's never posted twice, even if posting
+/// fails.
+async fn send(
+  ctx: &SerenityContext,
+  db: &DatabaseHandler,
+  announcement: &ScheduledAnnouncement,
+) -> Result<()> {
+  let mut transaction = db.start_transaction_with_retry(5).await?;
+  if DatabaseHandler::mark_announcement_sent(&mut transaction, &announcement.id).await? == 0 {
+    return Ok(());
+  }
+  DatabaseHandler::commit_transaction(transaction).await?;
+
+  let embed = BloomBotEmbed::new()
+    .title(&announcement.title)
+    .description(&announcement.message);
+  let message = announcement
+    .channel_id
+    .send_message(ctx, CreateMessage::new().embed(embed))
+    .await?;
+  info!(
+    "Posted scheduled announcement {} in guild {}",
+    announcement.id, announcement.guild_id
+  );
+
+  publish(ctx, db, announcement.guild_id, &message).await;
+
+  Ok(())
+}
+
+async fn send_due(ctx: &SerenityContext, db: &DatabaseHandler) -> Result<()> {
+  let mut transaction = db.start_transaction_with_retry(5).await?;
+  let announcements = DatabaseHandler::get_due_announcements(&mut transaction, &Utc::now()).await?;
+  drop(transaction);
+
+  for announcement in announcements {
+    if let Err(e) = send(ctx, db, &announcement).await {
+      error!(
+        "Error posting scheduled announcement {}: {e:?}",
+        announcement.id
+      );
+    }
+  }
+
+  Ok(())
+}
+
+/// Periodically posts scheduled announcements which are due. Announcements which were due
+/// while the bot was offline are posted on the first run.
+pub async fn send_scheduled_periodically(ctx: SerenityContext, db: Arc<DatabaseHandler>) {
+  let mut interval = tokio::time::interval(SEND_INTERVAL);
+
+  loop {
+    interval.tick().await;
+
+    if let Err(e) = send_due(&ctx, &db).await {
+      error!("Error posting scheduled announcements: {e:?}");
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use chrono::TimeZone;
+
+  use super::*;
+
+  #[test]
+  fn test_render_winner() {
+    let month = Utc
+      .with_ymd_and_hms(2024, 10, 1, 0, 0, 0)
+      .single()
+      .unwrap_or_default();
+
+    assert_eq!(
+      render_winner(DEFAULT_WINNER_MESSAGE, "<@123>", month, 1234),
+      "**Meditator in the Spotlight for October**\nCongratulations to **<@123>** on winning our October challenge, with a meditation time of **1234** minutes for the month!"
+    );
+    assert_eq!(
+      render_winner("{user} won {month} {year}! {prize}", "Sam", month, 60),
+      "Sam won October 2024! {prize}"
+    );
+  }
+}
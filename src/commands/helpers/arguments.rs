@@ -0,0 +1,158 @@
+use std::error::Error;
+use std::num::{IntErrorKind, ParseFloatError, ParseIntError};
+
+use poise::serenity_prelude::{
+  ChannelParseError, MemberParseError, MessageParseError, RoleParseError, UserParseError,
+};
+
+/// Counts the single-character insertions, deletions, and substitutions needed to turn one
+/// string into the other, ignoring case.
+fn edit_distance(a: &str, b: &str) -> usize {
+  let a: Vec<char> = a.to_lowercase().chars().collect();
+  let b: Vec<char> = b.to_lowercase().chars().collect();
+
+  let mut previous: Vec<usize> = (0..=b.len()).collect();
+  for (i, a_char) in a.iter().enumerate() {
+    let mut current = vec![i + 1; b.len() + 1];
+    for (j, b_char) in b.iter().enumerate() {
+      let substitution = previous[j] + usize::from(a_char != b_char);
+      current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+    }
+    previous = current;
+  }
+
+  previous[b.len()]
+}
+
+/// Finds the choice closest to `input`. Choices which start with the input are preferred,
+/// followed by the choice with the smallest [`edit_distance`], as long as it's close enough
+/// to be a likely typo.
+fn closest_choice<'a>(input: &str, choices: &[&'a str]) -> Option<&'a str> {
+  let lowercase = input.trim().to_lowercase();
+  if lowercase.is_empty() {
+    return None;
+  }
+
+  if let Some(choice) = choices
+    .iter()
+    .find(|choice| choice.to_lowercase().starts_with(&lowercase))
+  {
+    return Some(choice);
+  }
+
+  choices
+    .iter()
+    .map(|choice| (edit_distance(&lowercase, choice), *choice))
+    .filter(|(distance, choice)| *distance <= choice.chars().count().max(4) / 2)
+    .min_by_key(|(distance, _)| *distance)
+    .map(|(_, choice)| choice)
+}
+
+/// Explains why an argument couldn't be parsed and how to fix it, for the most common
+/// mistakes. `choices` are the valid choices for the command's parameters, used to suggest
+/// the closest when the input isn't one of them. Other errors are shown as they are.
+pub fn parse_error_message(
+  error: &(dyn Error + Send + Sync + 'static),
+  input: Option<&str>,
+  choices: &[&str],
+) -> String {
+  let shown = input.map_or_else(|| "That".to_owned(), |input| format!("`{input}`"));
+
+  if let Some(error) = error.downcast_ref::<ParseIntError>() {
+    return match error.kind() {
+      IntErrorKind::PosOverflow | IntErrorKind::NegOverflow => {
+        format!("{shown} is out of range. Please enter a smaller number.")
+      }
+      _ => format!(
+        "{shown} isn't a whole number. Please enter a number without decimals or units, like `15`."
+      ),
+    };
+  }
+
+  if error.is::<ParseFloatError>() {
+    return format!("{shown} isn't a number. Please enter a number without units, like `2.5`.");
+  }
+
+  if error.is::<chrono::ParseError>() {
+    return format!(
+      "{shown} isn't a valid date. Please use the format YYYY-MM-DD, like `2024-01-31`."
+    );
+  }
+
+  if error.is::<UserParseError>() || error.is::<MemberParseError>() {
+    return format!(
+      "Couldn't find the user {shown}. Please pick a member from the list, or mention them."
+    );
+  }
+
+  if error.is::<ChannelParseError>() {
+    return format!(
+      "Couldn't find the channel {shown}. Please pick a channel from the list, or mention it."
+    );
+  }
+
+  if error.is::<RoleParseError>() {
+    return format!("Couldn't find the role {shown}. Please pick a role from the list.");
+  }
+
+  if error.is::<MessageParseError>() {
+    return format!(
+      "Couldn't find the message {shown}. Please use a message link, from **Copy Message Link**."
+    );
+  }
+
+  if let Some(choice) = input.and_then(|input| closest_choice(input, choices)) {
+    return format!("{shown} isn't a valid choice. Did you mean `{choice}`?");
+  }
+
+  match input {
+    Some(input) => format!("**Cannot parse `{input}` as argument: {error}**"),
+    None => format!("**{error}**"),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_closest_choice() {
+    let choices = ["Yearly", "Monthly", "Weekly", "Daily"];
+    assert_eq!(closest_choice("week", &choices), Some("Weekly"));
+    assert_eq!(closest_choice("montly", &choices), Some("Monthly"));
+    assert_eq!(closest_choice("DAILY", &choices), Some("Daily"));
+    assert_eq!(closest_choice("forever", &choices), None);
+    assert_eq!(closest_choice(" ", &choices), None);
+
+    assert_eq!(edit_distance("kitten", "sitting"), 3);
+    assert_eq!(edit_distance("", "abc"), 3);
+  }
+
+  fn message<T, E: Error + Send + Sync + 'static>(result: Result<T, E>, input: &str) -> String {
+    result
+      .err()
+      .map(|e| parse_error_message(&e, Some(input), &[]))
+      .unwrap_or_default()
+  }
+
+  #[test]
+  fn test_parse_error_message() {
+    assert_eq!(
+      message("99999999999".parse::<i32>(), "99999999999"),
+      "`99999999999` is out of range. Please enter a smaller number."
+    );
+    assert!(message("ten".parse::<i32>(), "ten").contains("isn't a whole number"));
+    assert!(message(
+      chrono::NaiveDate::parse_from_str("31/01/2024", "%Y-%m-%d"),
+      "31/01/2024"
+    )
+    .contains("YYYY-MM-DD"));
+
+    let error = std::io::Error::other("unknown choice");
+    assert_eq!(
+      parse_error_message(&error, Some("montly"), &["Weekly", "Monthly"]),
+      "`montly` isn't a valid choice. Did you mean `Monthly`?"
+    );
+    assert_eq!(parse_error_message(&error, None, &[]), "**unknown choice**");
+  }
+}
@@ -1,6 +1,8 @@
 use anyhow::Result;
-use poise::serenity_prelude::RoleId;
+use poise::serenity_prelude::{GuildId, RoleId, UserId};
+use poise::CreateReply;
 
+use crate::database::DatabaseHandler;
 use crate::{config::ROLES, data::tracking_profile::Privacy, Context};
 
 pub enum Visibility {
@@ -41,3 +43,53 @@ pub async fn is_supporter(ctx: Context<'_>) -> Result<bool> {
   };
   Ok(supporter)
 }
+
+/// Returns `true` if the author of the invoking interaction has the [`ROLES.staff`][roles]
+/// role, or `false` if they don't or can't be retrieved as a member.
+///
+/// [roles]: crate::config::ROLES
+pub async fn is_staff(ctx: Context<'_>) -> bool {
+  ctx
+    .author_member()
+    .await
+    .is_some_and(|member| member.roles.contains(&RoleId::from(ROLES.staff)))
+}
+
+/// The server the command was used in, if Bloom is in it. Returns `None` in DMs, and when
+/// Bloom is installed to the author's account and used in a server Bloom isn't in.
+pub fn bloom_guild_id(ctx: Context<'_>) -> Option<GuildId> {
+  ctx
+    .guild_id()
+    .filter(|guild_id| ctx.cache().guild(*guild_id).is_some())
+}
+
+/// The server whose tracking data personal commands show. This is the server the command was
+/// used in or, when used elsewhere with Bloom installed to the author's account, the server
+/// they most recently tracked meditation in. Returns `None` if there's no such server.
+pub async fn tracking_guild_id(ctx: Context<'_>) -> Result<Option<GuildId>> {
+  if let Some(guild_id) = bloom_guild_id(ctx) {
+    return Ok(Some(guild_id));
+  }
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+  DatabaseHandler::get_latest_meditation_guild(&mut transaction, &ctx.author().id).await
+}
+
+/// Finds the server whose data a personal command about `user_id` shows, with
+/// [`tracking_guild_id`]. Outside Bloom's servers, only the author's own data can be shown.
+/// If no server's data can be shown, tells the author why and returns `None`.
+pub async fn personal_guild_id(ctx: Context<'_>, user_id: UserId) -> Result<Option<GuildId>> {
+  let reason = if bloom_guild_id(ctx).is_none() && user_id != ctx.author().id {
+    "Other members' meditation can only be viewed in a server with Bloom."
+  } else if let Some(guild_id) = tracking_guild_id(ctx).await? {
+    return Ok(Some(guild_id));
+  } else {
+    "You haven't tracked any meditation yet. Use `/add` in a server with Bloom to get started."
+  };
+
+  ctx
+    .send(CreateReply::default().content(reason).ephemeral(true))
+    .await?;
+
+  Ok(None)
+}
@@ -0,0 +1,137 @@
+use std::sync::LazyLock;
+
+use anyhow::Result;
+use chrono::{Duration, Utc};
+use poise::serenity_prelude::{builder::*, ButtonStyle, ComponentInteraction};
+use poise::serenity_prelude::{ChannelId, Context as SerenityContext, GuildId, UserId};
+use serde::{Deserialize, Serialize};
+use ulid::Ulid;
+
+use crate::data::pending_interaction::{InteractionFlow, PendingInteraction};
+use crate::database::DatabaseHandler;
+
+const CONFIRM_PREFIX: &str = "confirm:";
+
+/// How long a confirmation prompt waits for an answer.
+pub const CONFIRMATION_TIMEOUT: Duration = Duration::seconds(60);
+
+/// Identifies this run of the bot, so prompts left unanswered by a previous run can be told
+/// apart from those still being waited on.
+static INSTANCE_ID: LazyLock<String> = LazyLock::new(|| Ulid::new().to_string());
+
+/// The state of a confirmation prompt, stored in its [`PendingInteraction`].
+#[derive(Serialize, Deserialize)]
+struct ConfirmationState {
+  /// The run of the bot waiting on the answer.
+  instance: String,
+}
+
+/// Creates a [`PendingInteraction`] for a confirmation prompt shown by this run of the bot.
+///
+/// # Errors
+/// Returns an error if the state can't be serialized.
+pub fn pending_interaction(
+  guild_id: Option<GuildId>,
+  user_id: UserId,
+  channel_id: ChannelId,
+) -> Result<PendingInteraction> {
+  let state = ConfirmationState {
+    instance: INSTANCE_ID.clone(),
+  };
+
+  Ok(PendingInteraction::new(
+    InteractionFlow::Confirmation,
+    guild_id,
+    user_id,
+    channel_id,
+    serde_json::to_string(&state)?,
+    Utc::now() + CONFIRMATION_TIMEOUT,
+  ))
+}
+
+/// Creates the Yes and No buttons for a confirmation prompt. The custom IDs include the ID
+/// of the prompt's [`PendingInteraction`], so presses can be handled from the global event
+/// handler if the bot restarts before the prompt is answered.
+pub fn buttons(prompt_id: &str) -> CreateActionRow {
+  CreateActionRow::Buttons(vec![
+    CreateButton::new(format!("{CONFIRM_PREFIX}{prompt_id}:yes"))
+      .label("Yes")
+      .style(ButtonStyle::Success),
+    CreateButton::new(format!("{CONFIRM_PREFIX}{prompt_id}:no"))
+      .label("No")
+      .style(ButtonStyle::Danger),
+  ])
+}
+
+/// Parses the custom ID of one of the confirmation [`buttons`], returning the ID of the
+/// prompt and whether Yes was pressed.
+pub fn parse_custom_id(custom_id: &str) -> Option<(&str, bool)> {
+  let (prompt_id, answer) = custom_id.strip_prefix(CONFIRM_PREFIX)?.rsplit_once(':')?;
+  match answer {
+    "yes" => Some((prompt_id, true)),
+    "no" => Some((prompt_id, false)),
+    _ => None,
+  }
+}
+
+/// Handles a press of one of the confirmation [`buttons`] for a prompt left unanswered by a
+/// previous run of the bot. The command which asked was waiting on the answer with its
+/// changes uncommitted, so the prompt is cancelled and the member is asked to try again.
+/// Presses for prompts shown by this run are left to the command's collector.
+pub async fn handle_orphaned(
+  ctx: &SerenityContext,
+  db: &DatabaseHandler,
+  press: &ComponentInteraction,
+  prompt_id: &str,
+) -> Result<()> {
+  let mut transaction = db.start_transaction_with_retry(5).await?;
+  let Some(pending_interaction) = DatabaseHandler::get_pending_interaction(
+    &mut transaction,
+    InteractionFlow::Confirmation,
+    prompt_id,
+  )
+  .await?
+  else {
+    return Ok(());
+  };
+
+  let state: ConfirmationState = serde_json::from_str(&pending_interaction.payload)?;
+  if state.instance == *INSTANCE_ID {
+    return Ok(());
+  }
+
+  DatabaseHandler::remove_pending_interaction(&mut transaction, prompt_id).await?;
+  DatabaseHandler::commit_transaction(transaction).await?;
+
+  press
+    .create_response(
+      ctx,
+      CreateInteractionResponse::UpdateMessage(
+        CreateInteractionResponseMessage::new()
+          .content("Cancelled, since Bloom restarted before you answered. Nothing was changed, so please use the command again.")
+          .components(Vec::new()),
+      ),
+    )
+    .await?;
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_parse_custom_id() {
+    assert_eq!(
+      parse_custom_id("confirm:01JEBN2Q7X4Y0Z9F3M5K8R6T1W:yes"),
+      Some(("01JEBN2Q7X4Y0Z9F3M5K8R6T1W", true))
+    );
+    assert_eq!(
+      parse_custom_id("confirm:01JEBN2Q7X4Y0Z9F3M5K8R6T1W:no"),
+      Some(("01JEBN2Q7X4Y0Z9F3M5K8R6T1W", false))
+    );
+    assert_eq!(parse_custom_id("confirm:01JEBN2Q7X4Y0Z9F3M5K8R6T1W"), None);
+    assert_eq!(parse_custom_id("redeem_key:1234567890"), None);
+  }
+}
@@ -0,0 +1,133 @@
+use std::fmt;
+use std::sync::PoisonError;
+use std::time::Duration;
+
+use anyhow::Result;
+use log::error;
+use poise::CreateReply;
+
+use crate::commands::helpers::common;
+use crate::Context;
+
+/// The error returned by [`check`] when a command is on cooldown, which the error handler
+/// turns into a message saying when the command is available again.
+#[derive(Debug)]
+pub struct CooldownHit {
+  pub remaining: Duration,
+}
+
+impl fmt::Display for CooldownHit {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "Command on cooldown for {:?}", self.remaining)
+  }
+}
+
+impl std::error::Error for CooldownHit {}
+
+/// Checks whether the command is on cooldown for the author, channel, or server. Staff
+/// aren't affected by cooldowns. Cooldowns are applied manually, rather than by poise, so
+/// that staff can bypass them.
+///
+/// This is run for each parent command as well as the command itself, so it only checks
+/// cooldowns. They're started by [`start`] once every check has passed.
+///
+/// # Errors
+/// Returns a [`CooldownHit`] if the command is on cooldown.
+pub async fn check(ctx: Context<'_>) -> Result<bool> {
+  // Most commands have no cooldown, so the author is only looked up when needed
+  if !has_cooldown(ctx) || common::is_staff(ctx).await {
+    return Ok(true);
+  }
+
+  let command = ctx.command();
+  let remaining = command
+    .cooldowns
+    .lock()
+    .unwrap_or_else(PoisonError::into_inner)
+    .remaining_cooldown(
+      ctx.cooldown_context(),
+      &command
+        .cooldown_config
+        .read()
+        .unwrap_or_else(PoisonError::into_inner),
+    );
+
+  match remaining {
+    Some(remaining) => Err(CooldownHit { remaining }.into()),
+    None => Ok(true),
+  }
+}
+
+/// Starts the command's cooldowns, unless the author is staff.
+pub async fn start(ctx: Context<'_>) {
+  if !has_cooldown(ctx) || common::is_staff(ctx).await {
+    return;
+  }
+
+  ctx
+    .command()
+    .cooldowns
+    .lock()
+    .unwrap_or_else(PoisonError::into_inner)
+    .start_cooldown(ctx.cooldown_context());
+}
+
+/// Whether any cooldown is configured for the command.
+fn has_cooldown(ctx: Context<'_>) -> bool {
+  let config = ctx
+    .command()
+    .cooldown_config
+    .read()
+    .unwrap_or_else(PoisonError::into_inner);
+
+  config.global.is_some()
+    || config.user.is_some()
+    || config.guild.is_some()
+    || config.channel.is_some()
+    || config.member.is_some()
+}
+
+fn cooldown_message(remaining: Duration) -> String {
+  // Round up, so a command is never said to be available before it is
+  let seconds = remaining.as_secs() + u64::from(remaining.subsec_nanos() > 0);
+  if seconds < 60 {
+    format!("This command is on cooldown. It will be available again in {seconds}s.")
+  } else {
+    format!(
+      "This command is on cooldown. It will be available again in {}m {}s.",
+      seconds / 60,
+      seconds % 60
+    )
+  }
+}
+
+/// Tells the author when a command they tried to use will be available again.
+pub async fn cooldown_hit(ctx: Context<'_>, remaining: Duration) {
+  if let Err(e) = ctx
+    .send(
+      CreateReply::default()
+        .content(cooldown_message(remaining))
+        .ephemeral(true),
+    )
+    .await
+  {
+    error!("While handling cooldown, could not send message: {e}");
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_cooldown_message() {
+    assert_eq!(
+      cooldown_message(Duration::from_millis(4200)),
+      "This command is on cooldown. It will be available again in 5s."
+    );
+    assert_eq!(
+      cooldown_message(Duration::from_secs(125)),
+      "This command is on cooldown. It will be available again in 2m 5s."
+    );
+  }
+}
@@ -1,12 +1,17 @@
 use anyhow::Result;
 use poise::serenity_prelude::GuildId;
 use poise::CreateReply;
+use rand::Rng;
 use sqlx::{Postgres, Transaction};
 
-use crate::config::EMOJI;
 use crate::database::DatabaseHandler;
 use crate::Context;
 
+/// Characters used in enrollment codes. Characters which are easily mistaken for one
+/// another, such as `0` and `O`, are left out.
+const ENROLLMENT_CODE_CHARSET: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+const ENROLLMENT_CODE_LENGTH: usize = 8;
+
 /// Checks the database for courses with names that meet a similarity threshold of 0.8
 /// (high similarity) and returns either the course with the highest similarity or `None`.
 pub async fn course_not_found(
@@ -15,13 +20,14 @@ pub async fn course_not_found(
   guild_id: GuildId,
   course_name: String,
 ) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
   let Some(possible_course) =
     DatabaseHandler::get_possible_course(transaction, &guild_id, course_name.as_str(), 0.8).await?
   else {
     ctx
       .send(
         CreateReply::default()
-          .content(format!("{} Course does not exist.", EMOJI.mminfo))
+          .content(format!("{} Course does not exist.", emoji.mminfo))
           .ephemeral(true),
       )
       .await?;
@@ -33,7 +39,7 @@ pub async fn course_not_found(
       CreateReply::default()
         .content(format!(
           "{} Course does not exist. Did you mean `{}`?",
-          EMOJI.mminfo, possible_course.name
+          emoji.mminfo, possible_course.name
         ))
         .ephemeral(true),
     )
@@ -41,3 +47,10 @@ pub async fn course_not_found(
 
   Ok(())
 }
+
+/// Generates a random code for enrolling in a course with `/course enroll`.
+pub fn generate_enrollment_code(rng: &mut impl Rng) -> String {
+  (0..ENROLLMENT_CODE_LENGTH)
+    .map(|_| char::from(ENROLLMENT_CODE_CHARSET[rng.gen_range(0..ENROLLMENT_CODE_CHARSET.len())]))
+    .collect()
+}
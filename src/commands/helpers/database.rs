@@ -4,7 +4,6 @@ use poise::CreateReply;
 use sqlx::{Postgres, Transaction};
 
 use crate::commands::helpers::common::Visibility;
-use crate::config::EMOJI;
 use crate::database::DatabaseHandler;
 use crate::Context;
 
@@ -35,6 +34,7 @@ pub async fn commit_and_say(
   message: MessageType,
   visibility: Visibility,
 ) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
   let ephemeral = match visibility {
     Visibility::Public => false,
     Visibility::Ephemeral => true,
@@ -59,7 +59,7 @@ pub async fn commit_and_say(
         Ok(()) => {}
         Err(e) => {
           _ = sent_message.edit(ctx, CreateReply::default()
-            .content(format!("{} A fatal error occurred while trying to save your changes. Please contact staff for assistance.", EMOJI.mminfo))
+            .content(format!("{} A fatal error occurred while trying to save your changes. Please contact staff for assistance.", emoji.mminfo))
             .ephemeral(true)).await;
 
           return Err(anyhow!("Could not send message: {e}"));
@@ -82,7 +82,7 @@ pub async fn commit_and_say(
       // We'll send a response to the channel to inform the user.
       _ = ctx
         .channel_id()
-        .say(&ctx, format!("{} An error may have occurred. If your command failed, please contact staff for assistance.", EMOJI.mminfo))
+        .say(&ctx, format!("{} An error may have occurred. If your command failed, please contact staff for assistance.", emoji.mminfo))
         .await;
 
       return Err(anyhow!("Could not send message: {e}"));
@@ -0,0 +1,289 @@
+use std::future::Future;
+use std::sync::Arc;
+
+use anyhow::Result;
+use poise::serenity_prelude::{builder::*, ChannelId};
+use poise::serenity_prelude::{ComponentInteractionCollector, CreateAllowedMentions};
+use poise::CreateReply;
+
+use crate::commands::helpers::confirmations;
+use crate::data::guild_feature::Feature;
+use crate::database::DatabaseHandler;
+use crate::emoji::EmojiSet;
+use crate::roles::RoleUpdate;
+use crate::Context;
+
+/// The Discord side of a command: where it was used, replying to its author, posting in
+/// channels, and updating roles. Command logic written against this trait, rather than
+/// [`Context`], can be run against the test database with a mock in place of Discord.
+pub trait Discord: Sync {
+  /// Whether the command was used in a DM rather than in a server.
+  fn in_dm(&self) -> bool;
+
+  /// The name of the command being run.
+  fn command_name(&self) -> &str;
+
+  /// The emoji for the server the command was used in.
+  fn emoji(&self) -> Arc<EmojiSet>;
+
+  /// Whether a feature is turned on in the server the command was used in.
+  fn feature_enabled(&self, feature: Feature) -> bool;
+
+  /// Replies to the author, without pinging anyone mentioned.
+  fn respond(&self, content: String, ephemeral: bool) -> impl Future<Output = Result<()>> + Send;
+
+  /// Posts a message in a channel, without pinging anyone mentioned.
+  fn post(&self, channel_id: ChannelId, content: String)
+    -> impl Future<Output = Result<()>> + Send;
+
+  /// Applies a role update and waits until it's done.
+  fn update_roles(&self, update: RoleUpdate) -> impl Future<Output = Result<()>> + Send;
+
+  /// Privately asks the author to confirm `prompt` with Yes and No buttons. Once a button
+  /// is pressed, the prompt is replaced with `confirmed` or "Cancelled." and the answer is
+  /// returned. Returns `None` if neither button is pressed within a minute.
+  fn confirm(
+    &self,
+    prompt: String,
+    confirmed: String,
+  ) -> impl Future<Output = Result<Option<bool>>> + Send;
+}
+
+impl Discord for Context<'_> {
+  fn in_dm(&self) -> bool {
+    poise::Context::guild_id(*self).is_none()
+  }
+
+  fn command_name(&self) -> &str {
+    &self.command().name
+  }
+
+  fn emoji(&self) -> Arc<EmojiSet> {
+    self.data().emoji.get(poise::Context::guild_id(*self))
+  }
+
+  fn feature_enabled(&self, feature: Feature) -> bool {
+    self
+      .data()
+      .features
+      .enabled(poise::Context::guild_id(*self), feature)
+  }
+
+  async fn respond(&self, content: String, ephemeral: bool) -> Result<()> {
+    self
+      .send(
+        CreateReply::default()
+          .content(content)
+          .allowed_mentions(CreateAllowedMentions::new())
+          .ephemeral(ephemeral),
+      )
+      .await?;
+
+    Ok(())
+  }
+
+  async fn post(&self, channel_id: ChannelId, content: String) -> Result<()> {
+    channel_id
+      .send_message(
+        self,
+        CreateMessage::new()
+          .content(content)
+          .allowed_mentions(CreateAllowedMentions::new()),
+      )
+      .await?;
+
+    Ok(())
+  }
+
+  async fn update_roles(&self, update: RoleUpdate) -> Result<()> {
+    self.data().role_queue.apply(update).await
+  }
+
+  async fn confirm(&self, prompt: String, confirmed: String) -> Result<Option<bool>> {
+    // The prompt is stored so that, if the bot restarts before it's answered, presses can
+    // still be told apart from those of an expired prompt. See `confirmations::handle_orphaned`.
+    let pending_interaction = confirmations::pending_interaction(
+      poise::Context::guild_id(*self),
+      self.author().id,
+      self.channel_id(),
+    )?;
+    let prompt_id = pending_interaction.id.clone();
+
+    let mut transaction = self.data().db.start_transaction_with_retry(5).await?;
+    DatabaseHandler::add_pending_interaction(&mut transaction, &pending_interaction).await?;
+    DatabaseHandler::commit_transaction(transaction).await?;
+
+    self
+      .send(
+        CreateReply::default()
+          .content(prompt)
+          .ephemeral(true)
+          .components(vec![confirmations::buttons(&prompt_id)]),
+      )
+      .await?;
+
+    let press = ComponentInteractionCollector::new(self)
+      .filter({
+        let prompt_id = prompt_id.clone();
+        move |press| {
+          confirmations::parse_custom_id(&press.data.custom_id)
+            .is_some_and(|(id, _)| id == prompt_id)
+        }
+      })
+      // Timeout when no button has been pressed in one minute
+      .timeout(confirmations::CONFIRMATION_TIMEOUT.to_std()?)
+      .await;
+
+    let mut transaction = self.data().db.start_transaction_with_retry(5).await?;
+    DatabaseHandler::remove_pending_interaction(&mut transaction, &prompt_id).await?;
+    DatabaseHandler::commit_transaction(transaction).await?;
+
+    // This happens when the user didn't press any button for 60 seconds
+    let Some(press) = press else {
+      return Ok(None);
+    };
+
+    let answer =
+      confirmations::parse_custom_id(&press.data.custom_id).is_some_and(|(_, answer)| answer);
+
+    press
+      .create_response(
+        self,
+        CreateInteractionResponse::UpdateMessage(
+          CreateInteractionResponseMessage::new()
+            .content(if answer {
+              confirmed
+            } else {
+              "Cancelled.".to_owned()
+            })
+            .components(Vec::new()),
+        ),
+      )
+      .await?;
+
+    Ok(Some(answer))
+  }
+}
+
+#[cfg(test)]
+pub mod mock {
+  use std::sync::{Mutex, PoisonError};
+
+  use anyhow::anyhow;
+  use poise::serenity_prelude::{GuildId, Member, RoleId, UserId};
+
+  use super::*;
+
+  /// Stands in for Discord in tests, recording what a command would have sent and done.
+  #[derive(Default)]
+  pub struct MockDiscord {
+    pub in_dm: bool,
+    pub command_name: String,
+    pub disabled_features: Vec<Feature>,
+    /// The answer given to [`Discord::confirm`].
+    pub answer: Option<bool>,
+    /// Whether [`Discord::update_roles`] fails, as if Discord couldn't be reached.
+    pub fail_role_updates: bool,
+    replies: Mutex<Vec<(String, bool)>>,
+    posts: Mutex<Vec<(ChannelId, String)>>,
+    role_updates: Mutex<Vec<RoleUpdate>>,
+    prompts: Mutex<Vec<String>>,
+  }
+
+  fn record<T>(list: &Mutex<Vec<T>>, item: T) {
+    list
+      .lock()
+      .unwrap_or_else(PoisonError::into_inner)
+      .push(item);
+  }
+
+  fn recorded<T: Clone>(list: &Mutex<Vec<T>>) -> Vec<T> {
+    list.lock().unwrap_or_else(PoisonError::into_inner).clone()
+  }
+
+  impl MockDiscord {
+    pub fn new(command_name: &str) -> Self {
+      Self {
+        command_name: command_name.to_owned(),
+        ..Default::default()
+      }
+    }
+
+    /// Replies sent to the author, with whether they were ephemeral.
+    pub fn replies(&self) -> Vec<(String, bool)> {
+      recorded(&self.replies)
+    }
+
+    pub fn posts(&self) -> Vec<(ChannelId, String)> {
+      recorded(&self.posts)
+    }
+
+    pub fn role_updates(&self) -> Vec<RoleUpdate> {
+      recorded(&self.role_updates)
+    }
+
+    /// Prompts the author was asked to confirm.
+    pub fn prompts(&self) -> Vec<String> {
+      recorded(&self.prompts)
+    }
+  }
+
+  /// A server member with the given roles, for commands which update roles.
+  pub fn member(guild_id: GuildId, user_id: UserId, roles: &[RoleId]) -> Result<Member> {
+    Ok(serde_json::from_value(serde_json::json!({
+      "guild_id": guild_id.to_string(),
+      "user": {
+        "id": user_id.to_string(),
+        "username": "member",
+        "discriminator": "0",
+        "avatar": null,
+      },
+      "roles": roles.iter().map(ToString::to_string).collect::<Vec<_>>(),
+      "joined_at": "2024-01-01T00:00:00+00:00",
+      "deaf": false,
+      "mute": false,
+      "flags": 0,
+    }))?)
+  }
+
+  impl Discord for MockDiscord {
+    fn in_dm(&self) -> bool {
+      self.in_dm
+    }
+
+    fn command_name(&self) -> &str {
+      &self.command_name
+    }
+
+    fn emoji(&self) -> Arc<EmojiSet> {
+      Arc::new(EmojiSet::fallback())
+    }
+
+    fn feature_enabled(&self, feature: Feature) -> bool {
+      !self.disabled_features.contains(&feature)
+    }
+
+    async fn respond(&self, content: String, ephemeral: bool) -> Result<()> {
+      record(&self.replies, (content, ephemeral));
+      Ok(())
+    }
+
+    async fn post(&self, channel_id: ChannelId, content: String) -> Result<()> {
+      record(&self.posts, (channel_id, content));
+      Ok(())
+    }
+
+    async fn update_roles(&self, update: RoleUpdate) -> Result<()> {
+      if self.fail_role_updates {
+        return Err(anyhow!("Failed to update roles: Discord is unavailable"));
+      }
+      record(&self.role_updates, update);
+      Ok(())
+    }
+
+    async fn confirm(&self, prompt: String, _confirmed: String) -> Result<Option<bool>> {
+      record(&self.prompts, prompt);
+      Ok(self.answer)
+    }
+  }
+}
@@ -0,0 +1,61 @@
+use poise::serenity_prelude::{CreateEmbed, Timestamp, User};
+use rand::Rng;
+
+use crate::config::BloomBotEmbed;
+
+/// The characters incident IDs are made of, leaving out ones which are easily confused,
+/// such as `0` and `O`, since members read them out to staff.
+const INCIDENT_ID_CHARS: &[u8] = b"23456789ABCDEFGHJKLMNPQRSTUVWXYZ";
+
+const INCIDENT_ID_LENGTH: usize = 8;
+
+/// The longest error shown in the log channel. Embed fields are limited to 1024 characters.
+const MAX_ERROR_LENGTH: usize = 1000;
+
+/// Generates a short ID for a command failure, which is shown to the member and logged
+/// with the error, so staff can find the error when the member asks for help.
+pub fn incident_id() -> String {
+  let mut rng = rand::thread_rng();
+  (0..INCIDENT_ID_LENGTH)
+    .map(|_| char::from(INCIDENT_ID_CHARS[rng.gen_range(0..INCIDENT_ID_CHARS.len())]))
+    .collect()
+}
+
+/// An embed describing a command failure, for the log channel.
+pub fn incident_embed(
+  incident_id: &str,
+  command: &str,
+  user: &User,
+  source: &str,
+  error: &str,
+) -> CreateEmbed {
+  let error = if error.chars().count() > MAX_ERROR_LENGTH {
+    format!(
+      "{}…",
+      error.chars().take(MAX_ERROR_LENGTH).collect::<String>()
+    )
+  } else {
+    error.to_owned()
+  };
+
+  BloomBotEmbed::new()
+    .title(format!("Command Error `{incident_id}`"))
+    .field("Command", format!("`/{command}`"), true)
+    .field("User", format!("{} ({})", user.name, user.id), true)
+    .field("Source", source, true)
+    .field("Error", format!("```\n{error}\n```"), false)
+    .timestamp(Timestamp::now())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_incident_id() {
+    let id = incident_id();
+    assert_eq!(id.len(), INCIDENT_ID_LENGTH);
+    assert!(id.bytes().all(|c| INCIDENT_ID_CHARS.contains(&c)));
+    assert_ne!(id, incident_id());
+  }
+}
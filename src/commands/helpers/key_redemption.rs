@@ -0,0 +1,640 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::{Duration as ChronoDuration, Utc};
+use log::{error, info};
+use poise::serenity_prelude::{builder::*, ButtonStyle, ChannelId, ComponentInteraction};
+use poise::serenity_prelude::{
+  Context as SerenityContext, CreateAllowedMentions, GuildId, Mentionable, User,
+};
+
+use crate::config::{BloomBotEmbed, CHANNELS};
+use crate::data::steam_key::{KeyOffer, SteamKey};
+use crate::database::DatabaseHandler;
+use crate::Context;
+
+const REDEEM_PREFIX: &str = "redeem_key:";
+const DECLINE_PREFIX: &str = "decline_key:";
+const CLAIM_PREFIX: &str = "claim_key:";
+
+/// How often to check for key offers which have expired without a response.
+const EXPIRY_INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+/// How often to check the key pool for expired keys and stale reservations.
+const HYGIENE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// How long a key can stay reserved without being offered before it's returned to the pool.
+/// Keys are offered as soon as they're reserved, so this only happens when a giveaway was
+/// interrupted.
+const STALE_RESERVATION: ChronoDuration = ChronoDuration::hours(24);
+
+/// The most keys listed in each section of the key pool summary.
+const MAX_LISTED_KEYS: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Response {
+  Redeem,
+  Decline,
+  /// Redeems an offer made in the key claim channel, showing the key only to the winner.
+  Claim,
+}
+
+/// Where a key offer was sent by [`send_offer`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OfferDelivery {
+  /// Sent to the winner by DM.
+  Dm,
+  /// The winner doesn't accept DMs, so they were pinged in the server's key claim channel.
+  ClaimChannel(ChannelId),
+}
+
+/// Creates the buttons for redeeming or declining a key offer. The custom IDs include the
+/// offer ID, so presses can be handled from the global event handler, even if the bot has
+/// restarted since the offer was sent.
+pub fn buttons(offer_id: &str) -> CreateActionRow {
+  CreateActionRow::Buttons(vec![
+    CreateButton::new(format!("{REDEEM_PREFIX}{offer_id}"))
+      .label("Redeem")
+      .style(ButtonStyle::Success),
+    CreateButton::new(format!("{DECLINE_PREFIX}{offer_id}"))
+      .label("Cancel")
+      .style(ButtonStyle::Danger),
+  ])
+}
+
+/// Creates the button for claiming a key offered in the key claim channel. The button is kept
+/// once the key is claimed, so the winner can see their key again.
+fn claim_button(offer_id: &str) -> CreateButton {
+  CreateButton::new(format!("{CLAIM_PREFIX}{offer_id}"))
+    .label("Claim privately")
+    .style(ButtonStyle::Success)
+}
+
+/// Creates the buttons for claiming or declining a key offered in the key claim channel.
+fn claim_buttons(offer_id: &str) -> CreateActionRow {
+  CreateActionRow::Buttons(vec![
+    claim_button(offer_id),
+    CreateButton::new(format!("{DECLINE_PREFIX}{offer_id}"))
+      .label("Cancel")
+      .style(ButtonStyle::Danger),
+  ])
+}
+
+/// Parses the custom ID of a key offer button, returning the [`Response`] and the offer ID.
+/// Returns [`None`] if the custom ID does not belong to a key offer.
+pub fn parse_custom_id(custom_id: &str) -> Option<(Response, &str)> {
+  if let Some(offer_id) = custom_id.strip_prefix(REDEEM_PREFIX) {
+    Some((Response::Redeem, offer_id))
+  } else if let Some(offer_id) = custom_id.strip_prefix(CLAIM_PREFIX) {
+    Some((Response::Claim, offer_id))
+  } else {
+    custom_id
+      .strip_prefix(DECLINE_PREFIX)
+      .map(|offer_id| (Response::Decline, offer_id))
+  }
+}
+
+fn offer_embed(winner: &User, guild_name: &str, instructions: &str) -> CreateEmbed {
+  BloomBotEmbed::new()
+    .title(":tada: You've won a key! :tada:")
+    .thumbnail(winner.avatar_url().unwrap_or_default())
+    .field(
+      "**Congratulations on winning the giveaway!** 🥳",
+      format!("You've won a key for [Playne: The Meditation Game](<https://store.steampowered.com/app/865540/PLAYNE__The_Meditation_Game/>) on Steam!\n\n**{instructions}**"),
+      false,
+    )
+    .footer(CreateEmbedFooter::new(format!(
+      "From {guild_name} | If you need any assistance, please contact server staff."
+    )))
+}
+
+/// Sends the winner a DM offering them the key reserved for them, and records the offer
+/// against the key so the winner's response can be handled by [`handle_response`]. If the
+/// DM could not be sent, the winner is pinged in the key claim channel set with
+/// `/config keyclaims` instead, with a button to claim the key privately. If neither could
+/// be sent, the key is returned to the pool and [`None`] is returned.
+pub async fn send_offer(
+  ctx: Context<'_>,
+  guild_id: GuildId,
+  winner: &User,
+  reserved_key: String,
+) -> Result<Option<OfferDelivery>> {
+  let guild_name = guild_id
+    .name(ctx)
+    .unwrap_or_else(|| "Host Server".to_owned());
+
+  let offer_id = ctx.id().to_string();
+  let mut conn = ctx.data().db.get_connection_with_retry(5).await?;
+
+  let dm_message = match winner.create_dm_channel(ctx).await {
+    Ok(dm_channel) => dm_channel
+      .send_message(
+        ctx,
+        CreateMessage::new()
+          .embed(offer_embed(
+            winner,
+            &guild_name,
+            "Would you like to redeem your key? If yes, press 'Redeem' below! Otherwise, click 'Cancel' to leave it for someone else :)",
+          ))
+          .components(vec![buttons(&offer_id)]),
+      )
+      .await
+      .ok(),
+    Err(_) => None,
+  };
+
+  let (delivery, message) = if let Some(message) = dm_message {
+    (OfferDelivery::Dm, message)
+  } else {
+    let claim_channel = ctx
+      .data()
+      .settings
+      .get(&ctx.data().db, guild_id)
+      .await?
+      .key_claim_channel;
+
+    let claim_message = match claim_channel {
+      Some(channel_id) => channel_id
+        .send_message(
+          ctx,
+          CreateMessage::new()
+            .content(format!(
+              "{}, I couldn't send you a DM, so your prize is waiting for you here!",
+              winner.mention()
+            ))
+            .embed(offer_embed(
+              winner,
+              &guild_name,
+              "Would you like to redeem your key? If yes, press 'Claim privately' below and only you will see it! Otherwise, click 'Cancel' to leave it for someone else :)",
+            ))
+            .components(vec![claim_buttons(&offer_id)])
+            .allowed_mentions(CreateAllowedMentions::new().users([winner.id])),
+        )
+        .await
+        .ok(),
+      None => None,
+    };
+
+    let Some(message) = claim_message else {
+      DatabaseHandler::unreserve_key(&mut conn, &guild_id, &reserved_key).await?;
+      return Ok(None);
+    };
+
+    (OfferDelivery::ClaimChannel(message.channel_id), message)
+  };
+
+  // Responses are handled by the event handler, using the offer recorded against the key
+  let offer = KeyOffer::new(
+    offer_id,
+    guild_id,
+    reserved_key,
+    winner.id,
+    message.channel_id,
+    message.id,
+    Utc::now() + ChronoDuration::hours(24),
+  );
+  DatabaseHandler::record_key_offer(&mut conn, &offer).await?;
+
+  Ok(Some(delivery))
+}
+
+fn redeem_link(key: &str) -> String {
+  format!("[Redeem your key](https://store.steampowered.com/account/registerkey?key={key})")
+}
+
+fn log_footer(winner: &User) -> CreateEmbedFooter {
+  CreateEmbedFooter::new(format!("{} ({})", winner.name, winner.id))
+    .icon_url(winner.avatar_url().unwrap_or_default())
+}
+
+fn timeout_embed(ctx: &SerenityContext, offer: &KeyOffer) -> CreateEmbed {
+  let guild_name = offer
+    .guild_id
+    .name(ctx)
+    .unwrap_or_else(|| "Host Server".to_owned());
+
+  BloomBotEmbed::new()
+    .title("**Congratulations on winning the giveaway!** 🥳")
+    .description(
+      "You've won a key for [Playne: The Meditation Game](<https://store.steampowered.com/app/865540/PLAYNE__The_Meditation_Game/>) on Steam!\n\n**Would you like to redeem your key? Please contact server staff and we'll get one to you!**",
+    )
+    .footer(CreateEmbedFooter::new(format!("From {guild_name}")))
+}
+
+async fn log_timeout(ctx: &SerenityContext, winner: &User) -> Result<()> {
+  let log_embed = BloomBotEmbed::new()
+    .title("**Key Offer Timed Out**")
+    .description(format!(
+      "Sent Playne key offer to <@{}>, but user did not respond within 24 hours. Key has been returned to the pool and user has been asked to contact a moderator if they wish to claim their key.",
+      winner.id
+    ))
+    .footer(log_footer(winner));
+
+  ChannelId::new(CHANNELS.logs)
+    .send_message(ctx, CreateMessage::new().embed(log_embed))
+    .await?;
+
+  Ok(())
+}
+
+/// Privately tells the winner the offer was resolved before their press could be handled,
+/// such as when they pressed two buttons at once or the offer expired in the meantime.
+async fn respond_resolved(ctx: &SerenityContext, press: &ComponentInteraction) -> Result<()> {
+  press
+    .create_response(
+      ctx,
+      CreateInteractionResponse::Message(
+        CreateInteractionResponseMessage::new()
+          .content("This offer has already been redeemed, declined, or has expired.")
+          .ephemeral(true),
+      ),
+    )
+    .await?;
+
+  Ok(())
+}
+
+/// Handles a press of one of the key offer [`buttons`]. The offer is resolved in the
+/// database before responding, so an offer can only ever be resolved once, regardless
+/// of how many times the buttons are pressed. The offer ID doubles as a redemption token,
+/// so pressing Redeem or Claim again shows the key that was redeemed.
+///
+/// Offers in the key claim channel can be seen by everyone, so the key is only ever shown
+/// to the winner, and presses by anyone else are turned away.
+pub async fn handle_response(
+  ctx: &SerenityContext,
+  db: &DatabaseHandler,
+  press: &ComponentInteraction,
+  response: Response,
+  offer_id: &str,
+) -> Result<()> {
+  let mut conn = db.get_connection_with_retry(5).await?;
+
+  let offer = DatabaseHandler::get_key_offer(&mut conn, offer_id).await?;
+
+  if offer
+    .as_ref()
+    .is_some_and(|offer| offer.user_id != press.user.id)
+  {
+    press
+      .create_response(
+        ctx,
+        CreateInteractionResponse::Message(
+          CreateInteractionResponseMessage::new()
+            .content("This key was won by someone else, so only they can claim it.")
+            .ephemeral(true),
+        ),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let Some(offer) = offer else {
+    // Redeeming an offer again, such as when the key message failed to send or the claimed
+    // key was dismissed, shows the same key rather than redeeming another
+    if response != Response::Decline {
+      if let Some(key) =
+        DatabaseHandler::get_redeemed_key(&mut conn, offer_id, &press.user.id).await?
+      {
+        press
+          .create_response(
+            ctx,
+            CreateInteractionResponse::Message(
+              CreateInteractionResponseMessage::new()
+                .content(format!(
+                  "You've already redeemed this key. Here it is again:\n```{key}```\n{}",
+                  redeem_link(&key)
+                ))
+                .ephemeral(response == Response::Claim),
+            ),
+          )
+          .await?;
+        return Ok(());
+      }
+    }
+
+    press
+      .create_response(
+        ctx,
+        CreateInteractionResponse::Message(
+          CreateInteractionResponseMessage::new()
+            .content("This offer is no longer available. If you need any assistance, please contact server staff.")
+            .ephemeral(true),
+        ),
+      )
+      .await?;
+    return Ok(());
+  };
+
+  if offer.expires_at <= Utc::now() {
+    if DatabaseHandler::withdraw_key_offer(&mut conn, &offer.guild_id, &offer.id).await? > 0 {
+      press
+        .create_response(
+          ctx,
+          CreateInteractionResponse::UpdateMessage(
+            CreateInteractionResponseMessage::new()
+              .embed(timeout_embed(ctx, &offer))
+              .components(Vec::new()),
+          ),
+        )
+        .await?;
+
+      log_timeout(ctx, &press.user).await?;
+    } else {
+      respond_resolved(ctx, press).await?;
+    }
+    return Ok(());
+  }
+
+  let winner = &press.user;
+  let log_channel = ChannelId::new(CHANNELS.logs);
+
+  match response {
+    Response::Redeem | Response::Claim => {
+      if DatabaseHandler::redeem_key_offer(&mut conn, &offer.guild_id, &offer.id).await? == 0 {
+        respond_resolved(ctx, press).await?;
+        return Ok(());
+      }
+      DatabaseHandler::record_steamkey_receipt(&mut conn, &offer.guild_id, &winner.id).await?;
+
+      let reserved_key = &offer.key;
+      let hyperlink = redeem_link(reserved_key);
+      let content = format!("Awesome! Here is your key:\n```{reserved_key}```\n{hyperlink}");
+
+      if response == Response::Claim {
+        press
+          .create_response(
+            ctx,
+            CreateInteractionResponse::Message(
+              CreateInteractionResponseMessage::new()
+                .content(content)
+                .ephemeral(true),
+            ),
+          )
+          .await?;
+
+        // Only the claim button is kept, so the winner can see their key again
+        offer
+          .channel_id
+          .edit_message(
+            ctx,
+            offer.message_id,
+            EditMessage::new().components(vec![CreateActionRow::Buttons(vec![claim_button(
+              &offer.id,
+            )])]),
+          )
+          .await?;
+      } else {
+        press
+          .create_response(
+            ctx,
+            CreateInteractionResponse::UpdateMessage(
+              CreateInteractionResponseMessage::new().components(Vec::new()),
+            ),
+          )
+          .await?;
+
+        press
+          .channel_id
+          .send_message(ctx, CreateMessage::new().content(content))
+          .await?;
+      }
+
+      let log_embed = BloomBotEmbed::new()
+        .title("**Key Redeemed**")
+        .description(format!(
+          "Playne key redeemed by <@{}>. Key has been marked as used.",
+          winner.id
+        ))
+        .footer(log_footer(winner));
+
+      log_channel
+        .send_message(ctx, CreateMessage::new().embed(log_embed))
+        .await?;
+    }
+    Response::Decline => {
+      if DatabaseHandler::withdraw_key_offer(&mut conn, &offer.guild_id, &offer.id).await? == 0 {
+        respond_resolved(ctx, press).await?;
+        return Ok(());
+      }
+
+      press
+        .create_response(
+          ctx,
+          CreateInteractionResponse::UpdateMessage(
+            CreateInteractionResponseMessage::new().components(Vec::new()),
+          ),
+        )
+        .await?;
+
+      press
+        .channel_id
+        .send_message(
+          ctx,
+          CreateMessage::new().content("Alright, we'll keep it for someone else. Congrats again!"),
+        )
+        .await?;
+
+      let log_embed = BloomBotEmbed::new()
+        .title("**Key Declined**")
+        .description(format!(
+          "Playne key declined by <@{}>. Key has been returned to the pool.",
+          winner.id
+        ))
+        .footer(log_footer(winner));
+
+      log_channel
+        .send_message(ctx, CreateMessage::new().embed(log_embed))
+        .await?;
+    }
+  }
+
+  Ok(())
+}
+
+/// Returns the keys of expired offers to the pool and lets the winners know they can
+/// contact staff if they still want a key.
+async fn expire_offers(ctx: &SerenityContext, db: &DatabaseHandler) -> Result<()> {
+  let mut conn = db.get_connection_with_retry(5).await?;
+  let offers = DatabaseHandler::get_expired_key_offers(&mut conn).await?;
+
+  for offer in offers {
+    if DatabaseHandler::withdraw_key_offer(&mut conn, &offer.guild_id, &offer.id).await? == 0 {
+      // Resolved by the winner in the meantime
+      continue;
+    }
+
+    info!("Key offer {} expired without a response", offer.id);
+
+    if let Err(e) = offer
+      .channel_id
+      .edit_message(
+        ctx,
+        offer.message_id,
+        EditMessage::new()
+          .embed(timeout_embed(ctx, &offer))
+          .components(Vec::new()),
+      )
+      .await
+    {
+      error!("Error updating expired key offer {}: {e:?}", offer.id);
+    }
+
+    let winner = offer.user_id.to_user(ctx).await?;
+    log_timeout(ctx, &winner).await?;
+  }
+
+  Ok(())
+}
+
+/// Periodically expires key offers which have not been responded to in time. Offers which
+/// expired while the bot was offline are expired on the first run.
+pub async fn expire_offers_periodically(ctx: SerenityContext, db: Arc<DatabaseHandler>) {
+  let mut interval = tokio::time::interval(EXPIRY_INTERVAL);
+
+  loop {
+    interval.tick().await;
+
+    if let Err(e) = expire_offers(&ctx, &db).await {
+      error!("Error expiring key offers: {e:?}");
+    }
+  }
+}
+
+/// Lists keys for the key pool summary, with who each was reserved for.
+fn list_keys(keys: &[SteamKey]) -> String {
+  let mut list = keys
+    .iter()
+    .take(MAX_LISTED_KEYS)
+    .map(|key| match key.reserved {
+      Some(user_id) => format!("- `{}` (reserved for <@{user_id}>)", key.key),
+      None => format!("- `{}`", key.key),
+    })
+    .collect::<Vec<_>>()
+    .join("\n");
+
+  if keys.len() > MAX_LISTED_KEYS {
+    list.push_str(&format!("\n- ...and {} more", keys.len() - MAX_LISTED_KEYS));
+  }
+
+  list
+}
+
+/// Summarizes the changes made to the key pool for staff, or returns [`None`] if nothing
+/// changed.
+fn hygiene_summary(expired: &[SteamKey], released: &[SteamKey]) -> Option<String> {
+  let mut sections = vec![];
+
+  if !expired.is_empty() {
+    sections.push(format!(
+      "**{} key(s) expired** and will no longer be handed out. Any reservations or offers for them have been cancelled. They can be removed with `/keys remove`.\n{}",
+      expired.len(),
+      list_keys(expired)
+    ));
+  }
+
+  if !released.is_empty() {
+    sections.push(format!(
+      "**{} key(s) were reserved for over {} hours** without being offered, and have been returned to the pool.\n{}",
+      released.len(),
+      STALE_RESERVATION.num_hours(),
+      list_keys(released)
+    ));
+  }
+
+  if sections.is_empty() {
+    None
+  } else {
+    Some(sections.join("\n\n"))
+  }
+}
+
+/// Flags expired keys and returns stale reservations to the pool, so giveaways never hand
+/// out keys which can't be redeemed. Staff are sent a summary if anything changed.
+async fn clean_key_pool(ctx: &SerenityContext, db: &DatabaseHandler) -> Result<()> {
+  let mut conn = db.get_connection_with_retry(5).await?;
+  let expired = DatabaseHandler::flag_expired_keys(&mut conn).await?;
+  let released =
+    DatabaseHandler::release_stale_keys(&mut conn, &(Utc::now() - STALE_RESERVATION)).await?;
+
+  let Some(summary) = hygiene_summary(&expired, &released) else {
+    return Ok(());
+  };
+
+  info!(
+    "Flagged {} expired keys and released {} stale reservations",
+    expired.len(),
+    released.len()
+  );
+
+  let log_embed = BloomBotEmbed::new()
+    .title("**Playne Key Pool Cleaned Up**")
+    .description(summary);
+
+  ChannelId::new(CHANNELS.logs)
+    .send_message(ctx, CreateMessage::new().embed(log_embed))
+    .await?;
+
+  Ok(())
+}
+
+/// Periodically cleans up the key pool with [`clean_key_pool`]. Keys which expired while the
+/// bot was offline are flagged on the first run.
+pub async fn clean_key_pool_periodically(ctx: SerenityContext, db: Arc<DatabaseHandler>) {
+  let mut interval = tokio::time::interval(HYGIENE_INTERVAL);
+
+  loop {
+    interval.tick().await;
+
+    if let Err(e) = clean_key_pool(&ctx, &db).await {
+      error!("Error cleaning up key pool: {e:?}");
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use poise::serenity_prelude::UserId;
+
+  use super::*;
+
+  #[test]
+  fn test_hygiene_summary() {
+    assert_eq!(hygiene_summary(&[], &[]), None);
+
+    let guild_id = GuildId::new(123u64);
+    let mut reserved = SteamKey::new(guild_id, "AAAAA-BBBBB-CCCCC");
+    reserved.reserved = Some(UserId::new(321u64));
+    let expired = [reserved, SteamKey::new(guild_id, "DDDDD-EEEEE-FFFFF")];
+
+    let summary = hygiene_summary(&expired, &[]).unwrap_or_default();
+    assert!(summary.starts_with("**2 key(s) expired**"));
+    assert!(summary.ends_with("- `AAAAA-BBBBB-CCCCC` (reserved for <@321>)\n- `DDDDD-EEEEE-FFFFF`"));
+    assert!(!summary.contains("returned to the pool"));
+
+    let released: Vec<SteamKey> = (0..MAX_LISTED_KEYS + 3)
+      .map(|i| SteamKey::new(guild_id, format!("KEY-{i}")))
+      .collect();
+    let summary = hygiene_summary(&[], &released).unwrap_or_default();
+    assert!(summary.contains("returned to the pool"));
+    assert!(summary.ends_with("\n- ...and 3 more"));
+  }
+
+  #[test]
+  fn test_parse_custom_id() {
+    assert_eq!(
+      parse_custom_id("redeem_key:1234567890"),
+      Some((Response::Redeem, "1234567890"))
+    );
+    assert_eq!(
+      parse_custom_id("claim_key:1234567890"),
+      Some((Response::Claim, "1234567890"))
+    );
+    assert_eq!(
+      parse_custom_id("decline_key:1234567890"),
+      Some((Response::Decline, "1234567890"))
+    );
+    assert_eq!(parse_custom_id("1234567890redeem"), None);
+  }
+}
@@ -0,0 +1,65 @@
+use std::fmt;
+
+use anyhow::Result;
+use log::error;
+use poise::CreateReply;
+
+use crate::commands::helpers::common;
+use crate::Context;
+
+/// Categories of commands which keep working during maintenance. They already require staff
+/// permissions, and keeping them available means maintenance can always be turned off.
+const STAFF_CATEGORIES: [&str; 2] = ["Admin Commands", "Moderator Commands"];
+
+/// The error returned by [`check`] when Bloom is undergoing maintenance, which the error
+/// handler turns into a message explaining why the command can't be used.
+#[derive(Debug)]
+pub struct MaintenanceMode {
+  pub message: String,
+}
+
+impl fmt::Display for MaintenanceMode {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "Bloom is undergoing maintenance")
+  }
+}
+
+impl std::error::Error for MaintenanceMode {}
+
+/// Checks whether the command can be used while Bloom is undergoing maintenance. Staff, and
+/// staff commands, aren't affected.
+///
+/// # Errors
+/// Returns a [`MaintenanceMode`] if Bloom is undergoing maintenance.
+pub async fn check(ctx: Context<'_>) -> Result<bool> {
+  let Some(message) = ctx.data().maintenance.message() else {
+    return Ok(true);
+  };
+
+  // Subcommands don't have a category, so use the top-level command's
+  let command = ctx
+    .parent_commands()
+    .first()
+    .copied()
+    .unwrap_or_else(|| ctx.command());
+  if command
+    .category
+    .as_deref()
+    .is_some_and(|category| STAFF_CATEGORIES.contains(&category))
+    || common::is_staff(ctx).await
+  {
+    return Ok(true);
+  }
+
+  Err(MaintenanceMode { message }.into())
+}
+
+/// Tells the author that Bloom is undergoing maintenance.
+pub async fn maintenance_hit(ctx: Context<'_>, message: String) {
+  if let Err(e) = ctx
+    .send(CreateReply::default().content(message).ephemeral(true))
+    .await
+  {
+    error!("While handling maintenance, could not send message: {e}");
+  }
+}
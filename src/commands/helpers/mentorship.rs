@@ -0,0 +1,175 @@
+use std::collections::HashSet;
+
+use poise::serenity_prelude::CreateEmbed;
+
+use crate::config::BloomBotEmbed;
+use crate::data::mentorship::{MentorRole, MentorSignup};
+
+/// How many days a mentorship can go without a check-in before both members are reminded.
+pub const CHECK_IN_DAYS: i32 = 14;
+
+/// Splits a signup's comma-separated interests into a normalized set, ignoring case and
+/// surrounding whitespace.
+fn interests(signup: &MentorSignup) -> HashSet<String> {
+  signup
+    .interests
+    .split(',')
+    .map(|interest| interest.trim().to_lowercase())
+    .filter(|interest| !interest.is_empty())
+    .collect()
+}
+
+/// Formats a UTC offset in minutes, e.g., `UTC`, `UTC+2`, or `UTC-3:30`.
+pub fn format_offset(utc_offset: i16) -> String {
+  let sign = if utc_offset < 0 { '-' } else { '+' };
+  let (hours, minutes) = (utc_offset.abs() / 60, utc_offset.abs() % 60);
+
+  match (hours, minutes) {
+    (0, 0) => "UTC".to_owned(),
+    (hours, 0) => format!("UTC{sign}{hours}"),
+    (hours, minutes) => format!("UTC{sign}{hours}:{minutes:02}"),
+  }
+}
+
+/// Proposes mentor and mentee pairs from unmatched signups. Pairs with the most shared
+/// interests are matched first, then those with the closest time zones, and mentees who
+/// signed up earliest are preferred when there's a tie. Each member appears in at most one
+/// pair, so some members may be left unmatched.
+pub fn propose_pairs(signups: &[MentorSignup]) -> Vec<(&MentorSignup, &MentorSignup)> {
+  let mentors: Vec<(&MentorSignup, HashSet<String>)> = signups
+    .iter()
+    .filter(|signup| signup.role == MentorRole::Mentor)
+    .map(|signup| (signup, interests(signup)))
+    .collect();
+  let mentees: Vec<(&MentorSignup, HashSet<String>)> = signups
+    .iter()
+    .filter(|signup| signup.role == MentorRole::Mentee)
+    .map(|signup| (signup, interests(signup)))
+    .collect();
+
+  let mut candidates = Vec::new();
+  for (mentee_index, (mentee, mentee_interests)) in mentees.iter().enumerate() {
+    for (mentor_index, (mentor, mentor_interests)) in mentors.iter().enumerate() {
+      let shared = mentor_interests.intersection(mentee_interests).count();
+      let distance = (mentor.utc_offset - mentee.utc_offset).abs();
+      candidates.push((shared, distance, mentee_index, mentor_index));
+    }
+  }
+  candidates.sort_by(|a, b| {
+    b.0
+      .cmp(&a.0)
+      .then(a.1.cmp(&b.1))
+      .then(a.2.cmp(&b.2))
+      .then(a.3.cmp(&b.3))
+  });
+
+  let mut matched_mentors = HashSet::new();
+  let mut matched_mentees = HashSet::new();
+  let mut pairs = Vec::new();
+  for (_, _, mentee_index, mentor_index) in candidates {
+    if matched_mentees.contains(&mentee_index) || matched_mentors.contains(&mentor_index) {
+      continue;
+    }
+    matched_mentees.insert(mentee_index);
+    matched_mentors.insert(mentor_index);
+    pairs.push((mentors[mentor_index].0, mentees[mentee_index].0));
+  }
+
+  pairs
+}
+
+/// Describes a proposed pair for staff to review.
+pub fn describe_pair(mentor: &MentorSignup, mentee: &MentorSignup) -> String {
+  let mut shared: Vec<String> = interests(mentor)
+    .intersection(&interests(mentee))
+    .cloned()
+    .collect();
+  shared.sort_unstable();
+
+  format!(
+    "<@{}> ({}) mentoring <@{}> ({}){}",
+    mentor.user_id,
+    format_offset(mentor.utc_offset),
+    mentee.user_id,
+    format_offset(mentee.utc_offset),
+    if shared.is_empty() {
+      String::new()
+    } else {
+      format!(" · Shared: {}", shared.join(", "))
+    }
+  )
+}
+
+/// Creates the introduction sent by DM to both members of a new mentorship.
+pub fn introduction(server: &str, mentor: &MentorSignup, mentee: &MentorSignup) -> CreateEmbed {
+  BloomBotEmbed::new()
+    .title(":handshake: Meet Your Mentorship Match")
+    .description(format!(
+      "You've been matched in the {server} mentorship program! <@{}> will be mentoring <@{}>. Say hello and find a time that works for you both.\n\nUse `/mentor checkin` after you meet, and we'll send you both a gentle reminder if it's been {CHECK_IN_DAYS} days since your last check-in.",
+      mentor.user_id, mentee.user_id
+    ))
+    .field(
+      "Mentor",
+      format!(
+        "<@{}>\n-# {} · {}",
+        mentor.user_id,
+        mentor.interests,
+        format_offset(mentor.utc_offset)
+      ),
+      true,
+    )
+    .field(
+      "Mentee",
+      format!(
+        "<@{}>\n-# {} · {}",
+        mentee.user_id,
+        mentee.interests,
+        format_offset(mentee.utc_offset)
+      ),
+      true,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+  use poise::serenity_prelude::{GuildId, UserId};
+
+  use super::*;
+
+  fn signup(user_id: u64, role: MentorRole, interests: &str, utc_offset: i16) -> MentorSignup {
+    MentorSignup::new(
+      GuildId::new(123u64),
+      UserId::new(user_id),
+      role,
+      interests.to_owned(),
+      utc_offset,
+    )
+  }
+
+  #[test]
+  fn test_format_offset() {
+    assert_eq!(format_offset(0), "UTC");
+    assert_eq!(format_offset(120), "UTC+2");
+    assert_eq!(format_offset(-210), "UTC-3:30");
+  }
+
+  #[test]
+  fn test_propose_pairs() {
+    let signups = vec![
+      signup(1, MentorRole::Mentor, "Metta, sleep", 0),
+      signup(2, MentorRole::Mentor, "zen", -300),
+      signup(3, MentorRole::Mentee, "Zen , koans", 60),
+      signup(4, MentorRole::Mentee, "metta", -300),
+      signup(5, MentorRole::Mentee, "breath", 0),
+    ];
+
+    let pairs: Vec<(u64, u64)> = propose_pairs(&signups)
+      .iter()
+      .map(|(mentor, mentee)| (mentor.user_id.get(), mentee.user_id.get()))
+      .collect();
+
+    // Shared interests outweigh time zones, and there are only two mentors
+    assert_eq!(pairs, vec![(1, 4), (2, 3)]);
+    assert!(propose_pairs(&signups[2..]).is_empty());
+  }
+}
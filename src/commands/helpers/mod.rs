@@ -1,6 +1,29 @@
+pub mod announcements;
+pub mod arguments;
 pub(super) mod common;
+pub mod confirmations;
+pub mod cooldowns;
 pub(super) mod courses;
 pub(super) mod database;
+pub mod discord;
+pub mod incidents;
+pub mod key_redemption;
+pub mod maintenance;
+pub mod mentorship;
 pub mod pagination;
-pub mod time;
+pub mod polls;
+pub mod profile_links;
+pub mod quick_add;
+pub(super) mod quotes;
+pub mod registration;
+pub mod retreats;
+pub mod streak_repairs;
+pub mod suggestions;
+pub mod terms;
+pub mod threads;
+pub mod tickets;
 pub(super) mod tracking;
+pub mod transcripts;
+pub mod watchlist;
+
+pub use bloombot_core::time;
@@ -1,29 +1,28 @@
 use std::fmt::Display;
-use std::time::Duration;
 
 use anyhow::Result;
-use poise::serenity_prelude::ComponentInteractionCollector;
+use chrono::{Duration, Utc};
+use poise::serenity_prelude::{ComponentInteraction, Context as SerenityContext};
 use poise::serenity_prelude::{CreateActionRow, CreateButton, CreateEmbed, CreateEmbedFooter};
 use poise::serenity_prelude::{CreateInteractionResponse, CreateInteractionResponseMessage};
 use poise::CreateReply;
+use serde::{Deserialize, Serialize};
 
 use crate::commands::helpers::common::Visibility;
 use crate::config::BloomBotEmbed;
+use crate::data::pending_interaction::{InteractionFlow, PendingInteraction};
+use crate::database::DatabaseHandler;
 use crate::Context;
 
-#[derive(Debug, Copy, Clone)]
-pub enum PageType {
-  Standard,
-  Alternate,
-}
-
-pub trait PageRow {
-  fn title(&self, page_type: PageType) -> String;
-  fn body(&self) -> String;
-}
+pub use bloombot_core::pagination::{PageRow, PageType};
 
 pub type PageRowRef<'a> = &'a (dyn PageRow + Send + Sync);
 
+const PAGE_PREFIX: &str = "page:";
+
+/// How long the navigation buttons keep working after a list is shown.
+const PAGINATION_EXPIRY: Duration = Duration::hours(24);
+
 pub struct Paginator<'a> {
   page_data: Vec<PaginationPage<'a>>,
   page_count: usize,
@@ -100,42 +99,30 @@ impl<'a> Paginator<'a> {
   }
 
   pub fn update_page_number(&self, current_page: usize, change_by: isize) -> usize {
-    if change_by < 0 {
-      if change_by.unsigned_abs() > current_page {
-        self.page_count - (change_by.unsigned_abs() - current_page)
-      } else {
-        current_page - change_by.unsigned_abs()
-      }
-    } else if current_page + change_by.unsigned_abs() >= self.page_count {
-      (current_page + change_by.unsigned_abs()) - self.page_count
-    } else {
-      current_page + change_by.unsigned_abs()
-    }
+    turn_page(current_page, change_by, self.page_count)
   }
 
-  pub fn create_page_embed(&self, page: usize, page_type: PageType) -> CreateEmbed {
-    let mut embed = BloomBotEmbed::new();
-    let page = self.get_page(page);
-
-    if let Some(page) = page {
-      // If it is a valid page that is empty, it must be page 0.
-      // This implies that there are no entries to display.
-      if page.is_empty() {
-        embed = embed
-          .title(self.title.clone())
-          .description("No entries have been added yet.");
-
-        embed
-      } else {
-        page.to_embed(self.title.as_str(), page_type).clone()
-      }
-    } else {
-      // This should never happen unless we have a bug in our pagination code
-      embed = embed
-        .title(self.title.clone())
-        .description("This page does not exist.");
-
-      embed
+  /// Renders every page, so the pages can be stored and shown without the entries.
+  fn render(&self, page_type: PageType) -> RenderedPages {
+    RenderedPages {
+      title: self.title.clone(),
+      pages: self
+        .page_data
+        .iter()
+        .map(|page| {
+          // If it is a valid page that is empty, it must be page 0.
+          // This implies that there are no entries to display.
+          if page.is_empty() {
+            RenderedPage {
+              description: "No entries have been added yet.".to_owned(),
+              fields: Vec::new(),
+              footer: None,
+            }
+          } else {
+            page.render(page_type)
+          }
+        })
+        .collect(),
     }
   }
 
@@ -144,6 +131,10 @@ impl<'a> Paginator<'a> {
   /// An optional `page` argument specifies the initial page, [`PageType`] allows for multiple
   /// page variations, and [`Visibility`] determines whether the pagination is displayed publicly
   /// or ephemerally, meaning via private in-channel messages.
+  ///
+  /// The rendered pages are stored as a [`PendingInteraction`], and presses of the navigation
+  /// buttons are handled by [`handle_press`], so the buttons keep working for 24 hours, even
+  /// if the bot restarts.
   pub async fn paginate(
     self,
     ctx: Context<'_>,
@@ -156,67 +147,169 @@ impl<'a> Paginator<'a> {
       Visibility::Ephemeral => true,
     };
 
-    // Define some unique identifiers for the navigation buttons
-    let ctx_id = ctx.id();
-    let prev_button_id = format!("{ctx_id}prev");
-    let next_button_id = format!("{ctx_id}next");
-
     let mut current_page = page.unwrap_or(0).saturating_sub(1);
 
     if self.get_page(current_page).is_none() {
       current_page = self.get_last_page_number();
     }
 
-    let first_page = self.create_page_embed(current_page, page_type);
-
-    ctx
-      .send({
-        let mut f = CreateReply::default();
-        if self.get_page_count() > 1 {
-          f = f.components(vec![CreateActionRow::Buttons(vec![
-            CreateButton::new(&prev_button_id).label("Previous"),
-            CreateButton::new(&next_button_id).label("Next"),
-          ])]);
-        }
-        f.embeds = vec![first_page];
-        f.ephemeral(ephemeral)
-      })
-      .await?;
-
-    // Loop through incoming interactions with the navigation buttons
-    while let Some(press) = ComponentInteractionCollector::new(ctx)
-      // We defined our button IDs to start with `ctx_id`. If they don't, some other command's
-      // button was pressed
-      .filter(move |press| press.data.custom_id.starts_with(&ctx_id.to_string()))
-      // Timeout when no navigation button has been pressed for 24 hours
-      .timeout(Duration::from_secs(3600 * 24))
-      .await
-    {
-      // Depending on which button was pressed, go to next or previous page
-      if press.data.custom_id == next_button_id {
-        current_page = self.update_page_number(current_page, 1);
-      } else if press.data.custom_id == prev_button_id {
-        current_page = self.update_page_number(current_page, -1);
-      } else {
-        // This is an unrelated button interaction
-        continue;
-      }
-
-      // Update the message with the new page contents
-      press
-        .create_response(
-          ctx,
-          CreateInteractionResponse::UpdateMessage(
-            CreateInteractionResponseMessage::new()
-              .embed(self.create_page_embed(current_page, page_type)),
-          ),
-        )
-        .await?;
+    let pages = self.render(page_type);
+    let mut reply = CreateReply::default()
+      .embed(pages.page_embed(current_page))
+      .ephemeral(ephemeral);
+
+    if self.get_page_count() > 1 {
+      let pending_interaction = PendingInteraction::new(
+        InteractionFlow::Pagination,
+        ctx.guild_id(),
+        ctx.author().id,
+        ctx.channel_id(),
+        serde_json::to_string(&pages)?,
+        Utc::now() + PAGINATION_EXPIRY,
+      );
+
+      let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+      DatabaseHandler::add_pending_interaction(&mut transaction, &pending_interaction).await?;
+      DatabaseHandler::commit_transaction(transaction).await?;
+
+      reply = reply.components(vec![page_buttons(
+        &pending_interaction.id,
+        current_page,
+        pages.pages.len(),
+      )]);
     }
+
+    ctx.send(reply).await?;
+
     Ok(())
   }
 }
 
+/// Works out the page `change_by` pages from `current_page`, wrapping around at either end.
+fn turn_page(current_page: usize, change_by: isize, page_count: usize) -> usize {
+  if change_by < 0 {
+    if change_by.unsigned_abs() > current_page {
+      page_count - (change_by.unsigned_abs() - current_page)
+    } else {
+      current_page - change_by.unsigned_abs()
+    }
+  } else if current_page + change_by.unsigned_abs() >= page_count {
+    (current_page + change_by.unsigned_abs()) - page_count
+  } else {
+    current_page + change_by.unsigned_abs()
+  }
+}
+
+/// Creates the navigation buttons shown with `current_page`. The custom IDs include the ID
+/// of the stored pages and the page each button goes to, so presses can be handled from the
+/// global event handler. The direction is included too, since custom IDs must be unique
+/// within a message, and both buttons go to the same page when there are only two.
+fn page_buttons(pages_id: &str, current_page: usize, page_count: usize) -> CreateActionRow {
+  let previous = turn_page(current_page, -1, page_count);
+  let next = turn_page(current_page, 1, page_count);
+
+  CreateActionRow::Buttons(vec![
+    CreateButton::new(format!("{PAGE_PREFIX}{pages_id}:{previous}:prev")).label("Previous"),
+    CreateButton::new(format!("{PAGE_PREFIX}{pages_id}:{next}:next")).label("Next"),
+  ])
+}
+
+/// Parses the custom ID of one of the [`page_buttons`], returning the ID of the stored pages
+/// and the page to show.
+pub fn parse_custom_id(custom_id: &str) -> Option<(&str, usize)> {
+  let (rest, _direction) = custom_id.strip_prefix(PAGE_PREFIX)?.rsplit_once(':')?;
+  let (pages_id, page) = rest.rsplit_once(':')?;
+  Some((pages_id, page.parse().ok()?))
+}
+
+/// Handles a press of one of the [`page_buttons`], showing the requested page. Once the
+/// pages have expired, the buttons are removed instead.
+pub async fn handle_press(
+  ctx: &SerenityContext,
+  db: &DatabaseHandler,
+  press: &ComponentInteraction,
+  pages_id: &str,
+  page: usize,
+) -> Result<()> {
+  let mut transaction = db.start_transaction_with_retry(5).await?;
+  let pending_interaction = DatabaseHandler::get_pending_interaction(
+    &mut transaction,
+    InteractionFlow::Pagination,
+    pages_id,
+  )
+  .await?;
+  drop(transaction);
+
+  let pages = match pending_interaction {
+    Some(pending_interaction) => Some(serde_json::from_str::<RenderedPages>(
+      &pending_interaction.payload,
+    )?),
+    None => None,
+  };
+
+  let response = match pages {
+    Some(pages) => {
+      let page = page.min(pages.pages.len().saturating_sub(1));
+      CreateInteractionResponseMessage::new()
+        .embed(pages.page_embed(page))
+        .components(vec![page_buttons(pages_id, page, pages.pages.len())])
+    }
+    None => CreateInteractionResponseMessage::new().components(Vec::new()),
+  };
+
+  press
+    .create_response(ctx, CreateInteractionResponse::UpdateMessage(response))
+    .await?;
+
+  Ok(())
+}
+
+/// The pages of a [`Paginator`], as stored in its [`PendingInteraction`].
+#[derive(Serialize, Deserialize)]
+struct RenderedPages {
+  title: String,
+  pages: Vec<RenderedPage>,
+}
+
+impl RenderedPages {
+  fn page_embed(&self, page: usize) -> CreateEmbed {
+    match self.pages.get(page) {
+      Some(page) => page.to_embed(&self.title),
+      // This should never happen unless we have a bug in our pagination code
+      None => BloomBotEmbed::new()
+        .title(self.title.clone())
+        .description("This page does not exist."),
+    }
+  }
+}
+
+#[derive(Serialize, Deserialize)]
+struct RenderedPage {
+  description: String,
+  fields: Vec<(String, String)>,
+  footer: Option<String>,
+}
+
+impl RenderedPage {
+  fn to_embed(&self, title: &str) -> CreateEmbed {
+    let mut embed = BloomBotEmbed::new()
+      .title(title)
+      .description(self.description.clone())
+      .fields(
+        self
+          .fields
+          .iter()
+          .map(|(name, value)| (name.clone(), value.clone(), false)),
+      );
+
+    if let Some(footer) = &self.footer {
+      embed = embed.footer(CreateEmbedFooter::new(footer.clone()));
+    }
+
+    embed
+  }
+}
+
 #[allow(clippy::module_name_repetitions)]
 pub struct PaginationPage<'a> {
   entries: Vec<&'a (dyn PageRow + Send + Sync)>,
@@ -230,27 +323,24 @@ impl PaginationPage<'_> {
     self.entries.is_empty()
   }
 
-  fn to_embed(&self, title: &str, page_type: PageType) -> CreateEmbed {
-    let mut embed = BloomBotEmbed::new().title(title).description(format!(
-      "Showing entries {} to {}.",
-      (self.page_number * self.entries_per_page) + 1,
-      (self.page_number * self.entries_per_page) + self.entries.len()
-    ));
-
-    let fields: Vec<(String, String, bool)> = self
-      .entries
-      .iter()
-      .map(|entry| (entry.title(page_type), entry.body(), false))
-      .collect();
-    embed = embed.fields(fields);
-
-    embed = embed.footer(CreateEmbedFooter::new(format!(
-      "Page {} of {}",
-      self.page_number + 1,
-      self.page_count
-    )));
-
-    embed
+  fn render(&self, page_type: PageType) -> RenderedPage {
+    RenderedPage {
+      description: format!(
+        "Showing entries {} to {}.",
+        (self.page_number * self.entries_per_page) + 1,
+        (self.page_number * self.entries_per_page) + self.entries.len()
+      ),
+      fields: self
+        .entries
+        .iter()
+        .map(|entry| (entry.title(page_type), entry.body()))
+        .collect(),
+      footer: Some(format!(
+        "Page {} of {}",
+        self.page_number + 1,
+        self.page_count
+      )),
+    }
   }
 }
 
@@ -271,4 +361,14 @@ mod tests {
     assert_eq!(Paginator::update_page_number(&test_data, 3, 2), 1);
     assert_eq!(Paginator::update_page_number(&test_data, 1, 2), 3);
   }
+
+  #[test]
+  fn test_parse_custom_id() {
+    assert_eq!(
+      parse_custom_id("page:01JEBN2Q7X4Y0Z9F3M5K8R6T1W:3:next"),
+      Some(("01JEBN2Q7X4Y0Z9F3M5K8R6T1W", 3))
+    );
+    assert_eq!(parse_custom_id("page:01JEBN2Q7X4Y0Z9F3M5K8R6T1W:3"), None);
+    assert_eq!(parse_custom_id("redeem_key:1234567890"), None);
+  }
 }
@@ -0,0 +1,357 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::Utc;
+use log::{error, info};
+use poise::serenity_prelude::{builder::*, ButtonStyle, ComponentInteraction};
+use poise::serenity_prelude::{
+  Context as SerenityContext, FormattedTimestamp, FormattedTimestampStyle, Mentionable, UserId,
+};
+use poise::ChoiceParameter;
+
+use crate::config::BloomBotEmbed;
+use crate::data::poll::{Poll, PollVote};
+use crate::database::DatabaseHandler;
+use crate::emoji::EmojiHandler;
+use crate::events::goals;
+
+const VOTE_PREFIX: &str = "poll_vote:";
+
+/// How often to check for polls which are due to close.
+const CLOSE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// The number of voters named under each choice of a poll which isn't anonymous.
+const MAX_VOTERS_SHOWN: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ChoiceParameter)]
+pub enum PollPreset {
+  #[name = "next month's challenge theme"]
+  ChallengeTheme,
+  #[name = "preferred time to sit"]
+  SitTime,
+  #[name = "usual sit length"]
+  SitLength,
+}
+
+impl PollPreset {
+  pub fn question(self) -> &'static str {
+    match self {
+      Self::ChallengeTheme => "What should next month's challenge theme be?",
+      Self::SitTime => "When do you usually prefer to sit?",
+      Self::SitLength => "How long are your sits usually?",
+    }
+  }
+
+  pub fn choices(self) -> Vec<String> {
+    let choices: &[&str] = match self {
+      Self::ChallengeTheme => &[
+        "Loving-kindness",
+        "Breath awareness",
+        "Body scan",
+        "Walking meditation",
+        "Noting practice",
+      ],
+      Self::SitTime => &[
+        "Early morning",
+        "Morning",
+        "Afternoon",
+        "Evening",
+        "Before bed",
+      ],
+      Self::SitLength => &[
+        "Under 10 minutes",
+        "10 to 20 minutes",
+        "20 to 30 minutes",
+        "30 to 60 minutes",
+        "Over an hour",
+      ],
+    };
+
+    choices.iter().map(|choice| (*choice).to_owned()).collect()
+  }
+}
+
+/// Creates the buttons for voting in a [`Poll`], five to a row. The custom IDs include the
+/// poll ID, so votes can be handled from the global event handler, even if the bot has
+/// restarted since the poll was posted.
+pub fn buttons(poll: &Poll) -> Vec<CreateActionRow> {
+  poll
+    .choices
+    .chunks(5)
+    .enumerate()
+    .map(|(row, choices)| {
+      CreateActionRow::Buttons(
+        choices
+          .iter()
+          .enumerate()
+          .map(|(column, choice)| {
+            let index = row * 5 + column;
+            CreateButton::new(format!("{VOTE_PREFIX}{}:{index}", poll.id))
+              .label(format!("{}. {choice}", index + 1))
+              .style(ButtonStyle::Secondary)
+          })
+          .collect(),
+      )
+    })
+    .collect()
+}
+
+/// Parses the custom ID of a poll button, returning the poll ID and the index of the choice.
+/// Returns [`None`] if the custom ID does not belong to a poll.
+pub fn parse_custom_id(custom_id: &str) -> Option<(&str, i16)> {
+  let (poll_id, choice) = custom_id.strip_prefix(VOTE_PREFIX)?.rsplit_once(':')?;
+  Some((poll_id, choice.parse().ok()?))
+}
+
+/// Creates an embed showing the current results of a [`Poll`]. Unless the poll is
+/// anonymous, the members who voted for each choice are listed.
+pub fn poll_embed(poll: &Poll, votes: &[PollVote]) -> CreateEmbed {
+  let counts = poll.tally(votes);
+  let total: u64 = counts.iter().sum();
+
+  let mut voters: BTreeMap<i16, Vec<UserId>> = BTreeMap::new();
+  for vote in votes {
+    voters.entry(vote.choice).or_default().push(vote.user_id);
+  }
+
+  let results = poll
+    .choices
+    .iter()
+    .zip(counts)
+    .enumerate()
+    .map(|(index, (choice, count))| {
+      let percent = if total > 0 { count * 100 / total } else { 0 };
+      let votes = if count == 1 { "vote" } else { "votes" };
+      #[allow(clippy::cast_possible_wrap)]
+      let bar = goals::progress_bar(count as i64, total.max(1) as i64);
+      let mut line = format!(
+        "**{}. {choice}**\n`{bar}` {count} {votes} ({percent}%)",
+        index + 1
+      );
+
+      if !poll.anonymous {
+        let names = i16::try_from(index)
+          .ok()
+          .and_then(|index| voters.get(&index))
+          .filter(|voters| !voters.is_empty());
+        if let Some(names) = names {
+          let mut shown = names
+            .iter()
+            .take(MAX_VOTERS_SHOWN)
+            .map(|user_id| user_id.mention().to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+          if names.len() > MAX_VOTERS_SHOWN {
+            shown.push_str(&format!(" and {} more", names.len() - MAX_VOTERS_SHOWN));
+          }
+          line.push_str(&format!("\n-# {shown}"));
+        }
+      }
+
+      line
+    })
+    .collect::<Vec<_>>()
+    .join("\n\n");
+
+  let status = if poll.is_closed(Utc::now()) {
+    "This poll has closed.".to_owned()
+  } else if let Some(closes_at) = poll.closes_at {
+    format!(
+      "This poll closes {}.",
+      FormattedTimestamp::new(
+        closes_at.into(),
+        Some(FormattedTimestampStyle::RelativeTime)
+      )
+    )
+  } else {
+    String::new()
+  };
+
+  let mut footer = vec![format!("Poll ID: {}", poll.id)];
+  if poll.multiple_choice {
+    footer.push("Multiple choice".to_owned());
+  }
+  if poll.anonymous {
+    footer.push("Anonymous".to_owned());
+  }
+
+  BloomBotEmbed::new()
+    .title(format!(":bar_chart: {}", poll.question))
+    .description(format!("{results}\n\n{status}").trim_end().to_owned())
+    .footer(CreateEmbedFooter::new(footer.join(" · ")))
+}
+
+async fn respond_ephemeral(
+  ctx: &SerenityContext,
+  press: &ComponentInteraction,
+  content: String,
+) -> Result<()> {
+  press
+    .create_response(
+      ctx,
+      CreateInteractionResponse::Message(
+        CreateInteractionResponseMessage::new()
+          .content(content)
+          .ephemeral(true),
+      ),
+    )
+    .await?;
+
+  Ok(())
+}
+
+/// Records a vote when a poll button is pressed. Pressing a choice which has already been
+/// voted for removes the vote. Unless the poll is multiple choice, voting for a new choice
+/// replaces the member's previous vote.
+pub async fn handle_vote(
+  ctx: &SerenityContext,
+  db: &DatabaseHandler,
+  emoji: &EmojiHandler,
+  press: &ComponentInteraction,
+  poll_id: &str,
+  choice: i16,
+) -> Result<()> {
+  let emoji = emoji.get(press.guild_id);
+  let mut transaction = db.start_transaction_with_retry(5).await?;
+
+  let poll = DatabaseHandler::get_poll(&mut transaction, poll_id)
+    .await?
+    .filter(|poll| !poll.is_closed(Utc::now()));
+  let Some(poll) = poll else {
+    return respond_ephemeral(
+      ctx,
+      press,
+      format!("{} This poll has closed.", emoji.mminfo),
+    )
+    .await;
+  };
+  let Some(choice_name) = usize::try_from(choice)
+    .ok()
+    .and_then(|index| poll.choices.get(index))
+  else {
+    return Ok(());
+  };
+
+  let vote = PollVote {
+    user_id: press.user.id,
+    choice,
+  };
+
+  let confirmation =
+    if DatabaseHandler::remove_poll_vote(&mut transaction, &poll.id, vote).await? > 0 {
+      format!("Your vote for **{choice_name}** has been removed.")
+    } else {
+      if !poll.multiple_choice {
+        DatabaseHandler::clear_poll_votes(&mut transaction, &poll.id, &vote.user_id).await?;
+      }
+      DatabaseHandler::add_poll_vote(&mut transaction, &poll.id, vote).await?;
+      format!("You voted for **{choice_name}**.")
+    };
+
+  let votes = DatabaseHandler::get_poll_votes(&mut transaction, &poll.id).await?;
+  DatabaseHandler::commit_transaction(transaction).await?;
+
+  press
+    .create_response(
+      ctx,
+      CreateInteractionResponse::UpdateMessage(
+        CreateInteractionResponseMessage::new().embed(poll_embed(&poll, &votes)),
+      ),
+    )
+    .await?;
+
+  press
+    .create_followup(
+      ctx,
+      CreateInteractionResponseFollowup::new()
+        .content(format!("{} {confirmation}", emoji.mmcheck))
+        .ephemeral(true),
+    )
+    .await?;
+
+  Ok(())
+}
+
+/// Closes a [`Poll`] and updates its message to show the final results, returning `false`
+/// if the poll had already been closed. Failing to update the message, e.g., because it has
+/// been deleted, doesn't prevent the poll from closing.
+pub async fn close(ctx: &SerenityContext, db: &DatabaseHandler, mut poll: Poll) -> Result<bool> {
+  let mut transaction = db.start_transaction_with_retry(5).await?;
+
+  if DatabaseHandler::close_poll(&mut transaction, &poll.guild_id, &poll.id).await? == 0 {
+    return Ok(false);
+  }
+
+  let votes = DatabaseHandler::get_poll_votes(&mut transaction, &poll.id).await?;
+  DatabaseHandler::commit_transaction(transaction).await?;
+
+  info!("Closed poll {} in guild {}", poll.id, poll.guild_id);
+
+  poll.closed = true;
+  if let Some(message_id) = poll.message_id {
+    if let Err(e) = poll
+      .channel_id
+      .edit_message(
+        ctx,
+        message_id,
+        EditMessage::new()
+          .embed(poll_embed(&poll, &votes))
+          .components(Vec::new()),
+      )
+      .await
+    {
+      error!("Error updating closed poll {}: {e:?}", poll.id);
+    }
+  }
+
+  Ok(true)
+}
+
+async fn close_expired(ctx: &SerenityContext, db: &DatabaseHandler) -> Result<()> {
+  let mut transaction = db.start_transaction_with_retry(5).await?;
+  let polls = DatabaseHandler::get_expired_polls(&mut transaction, &Utc::now()).await?;
+  drop(transaction);
+
+  for poll in polls {
+    let poll_id = poll.id.clone();
+    if let Err(e) = close(ctx, db, poll).await {
+      error!("Error closing poll {poll_id}: {e:?}");
+    }
+  }
+
+  Ok(())
+}
+
+/// Periodically closes polls which have reached their closing time. Polls which were due to
+/// close while the bot was offline are closed on the first run.
+pub async fn close_expired_periodically(ctx: SerenityContext, db: Arc<DatabaseHandler>) {
+  let mut interval = tokio::time::interval(CLOSE_INTERVAL);
+
+  loop {
+    interval.tick().await;
+
+    if let Err(e) = close_expired(&ctx, &db).await {
+      error!("Error closing expired polls: {e:?}");
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_parse_custom_id() {
+    assert_eq!(
+      parse_custom_id("poll_vote:01JBPTWBXJNAKK288S3D89JKCA:3"),
+      Some(("01JBPTWBXJNAKK288S3D89JKCA", 3))
+    );
+    assert_eq!(
+      parse_custom_id("poll_vote:01JBPTWBXJNAKK288S3D89JKCA"),
+      None
+    );
+    assert_eq!(parse_custom_id("redeem_key:1234567890"), None);
+  }
+}
@@ -0,0 +1,58 @@
+use rand::Rng;
+use sha2::{Digest, Sha256};
+
+/// The characters link tokens are made of, leaving out ones which are easily confused, such
+/// as `0` and `O`, since members may copy them by hand between servers.
+const TOKEN_CHARS: &[u8] = b"23456789ABCDEFGHJKLMNPQRSTUVWXYZ";
+
+/// Tokens are shown in groups of this many characters, separated by dashes.
+const TOKEN_GROUP_LENGTH: usize = 4;
+
+const TOKEN_GROUPS: usize = 5;
+
+/// Generates a token for `/customize link`, such as `7KQ2-MX9D-...`.
+pub fn token() -> String {
+  let mut rng = rand::thread_rng();
+  (0..TOKEN_GROUPS)
+    .map(|_| {
+      (0..TOKEN_GROUP_LENGTH)
+        .map(|_| char::from(TOKEN_CHARS[rng.gen_range(0..TOKEN_CHARS.len())]))
+        .collect::<String>()
+    })
+    .collect::<Vec<_>>()
+    .join("-")
+}
+
+/// Hashes a token for storage and lookup. Case, dashes, and whitespace are ignored, so a
+/// token still matches if it's retyped slightly differently.
+pub fn hash_token(token: &str) -> String {
+  let normalized: String = token
+    .chars()
+    .filter(|c| c.is_ascii_alphanumeric())
+    .map(|c| c.to_ascii_uppercase())
+    .collect();
+
+  hex::encode(Sha256::digest(normalized.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_token() {
+    let token = token();
+    assert_eq!(token.len(), TOKEN_GROUPS * (TOKEN_GROUP_LENGTH + 1) - 1);
+    assert!(token
+      .split('-')
+      .all(|group| group.len() == TOKEN_GROUP_LENGTH
+        && group.bytes().all(|c| TOKEN_CHARS.contains(&c))));
+  }
+
+  #[test]
+  fn test_hash_token() {
+    assert_eq!(hash_token("7KQ2-MX9D"), hash_token(" 7kq2 mx9d "));
+    assert_ne!(hash_token("7KQ2-MX9D"), hash_token("7KQ2-MX9E"));
+    assert_eq!(hash_token("7KQ2-MX9D").len(), 64);
+  }
+}
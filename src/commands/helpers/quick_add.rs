@@ -0,0 +1,174 @@
+use anyhow::Result;
+use chrono::{Duration as ChronoDuration, Utc};
+use poise::serenity_prelude::{builder::*, ButtonStyle, ComponentInteraction};
+use poise::serenity_prelude::{Context as SerenityContext, Message, UserId};
+
+use crate::commands::helpers::tracking;
+use crate::config::BloomBotEmbed;
+use crate::data::meditation::Meditation;
+use crate::database::DatabaseHandler;
+use crate::emoji::EmojiHandler;
+use crate::settings::SettingsHandler;
+
+const ADD_PREFIX: &str = "quick_add:";
+
+/// The title of the quick-add message, used to find earlier ones to replace.
+pub const TITLE: &str = ":stopwatch: Quick Add";
+
+/// The most durations a quick-add message can offer.
+pub const MAX_DURATIONS: usize = 4;
+
+/// The durations offered when staff don't choose their own, in minutes.
+pub const DEFAULT_DURATIONS: [i32; MAX_DURATIONS] = [10, 20, 30, 60];
+
+/// Formats a duration for a button, e.g., `20m`, `1h`, or `1h 30m`.
+fn duration_label(minutes: i32) -> String {
+  match (minutes / 60, minutes % 60) {
+    (0, minutes) => format!("{minutes}m"),
+    (hours, 0) => format!("{hours}h"),
+    (hours, minutes) => format!("{hours}h {minutes}m"),
+  }
+}
+
+/// Sorts the chosen durations and removes duplicates, falling back to [`DEFAULT_DURATIONS`]
+/// when none were chosen.
+pub fn durations(chosen: &[Option<i32>]) -> Vec<i32> {
+  let mut durations: Vec<i32> = chosen.iter().flatten().copied().collect();
+  if durations.is_empty() {
+    return DEFAULT_DURATIONS.to_vec();
+  }
+
+  durations.sort_unstable();
+  durations.dedup();
+  durations
+}
+
+/// Creates the quick-add message, with a button for each duration. The custom IDs include
+/// the duration, so presses can be handled from the global event handler, even if the bot
+/// has restarted since the message was posted.
+pub fn message(durations: &[i32]) -> CreateMessage {
+  let buttons = durations
+    .iter()
+    .map(|minutes| {
+      CreateButton::new(format!("{ADD_PREFIX}{minutes}"))
+        .label(duration_label(*minutes))
+        .style(ButtonStyle::Primary)
+    })
+    .collect();
+
+  CreateMessage::new()
+    .embed(BloomBotEmbed::new().title(TITLE).description(
+      "Finished a sit? Press a button to log it instantly. Only you will see the confirmation.\n\nFor other lengths, use `/add`.",
+    ))
+    .components(vec![CreateActionRow::Buttons(buttons)])
+}
+
+/// Whether a message is a quick-add message posted by the bot.
+pub fn is_quick_add(message: &Message, bot_id: UserId) -> bool {
+  message.author.id == bot_id
+    && message
+      .embeds
+      .first()
+      .is_some_and(|embed| embed.title.as_deref() == Some(TITLE))
+}
+
+/// Parses the custom ID of a quick-add button, returning the minutes to add. Returns [`None`]
+/// if the custom ID does not belong to a quick-add button.
+pub fn parse_custom_id(custom_id: &str) -> Option<i32> {
+  custom_id
+    .strip_prefix(ADD_PREFIX)?
+    .parse()
+    .ok()
+    .filter(|minutes| *minutes > 0)
+}
+
+async fn respond_ephemeral(
+  ctx: &SerenityContext,
+  press: &ComponentInteraction,
+  content: String,
+) -> Result<()> {
+  press
+    .create_response(
+      ctx,
+      CreateInteractionResponse::Message(
+        CreateInteractionResponseMessage::new()
+          .content(content)
+          .ephemeral(true),
+      ),
+    )
+    .await?;
+
+  Ok(())
+}
+
+/// Logs a session for whoever pressed a quick-add button, like `/add`, and privately confirms
+/// it. Time and streak roles are updated the next time they use `/add`.
+pub async fn handle_press(
+  ctx: &SerenityContext,
+  db: &DatabaseHandler,
+  emoji: &EmojiHandler,
+  settings: &SettingsHandler,
+  press: &ComponentInteraction,
+  minutes: i32,
+) -> Result<()> {
+  let emoji = emoji.get(press.guild_id);
+  let Some(guild_id) = press.guild_id else {
+    return Ok(());
+  };
+  let user_id = press.user.id;
+
+  let mut transaction = db.start_transaction_with_retry(5).await?;
+
+  let tracking_profile =
+    DatabaseHandler::get_tracking_profile(&mut transaction, &guild_id, &user_id)
+      .await?
+      .unwrap_or_default();
+
+  let datetime = Utc::now() + ChronoDuration::minutes(i64::from(tracking_profile.utc_offset));
+  let settings = settings.get(db, guild_id).await?;
+  let meditation = Meditation::new(guild_id, user_id, minutes, 0, &datetime)
+    .channel(tracking::segment_channel(ctx, &settings, press.channel_id).await);
+
+  DatabaseHandler::add_meditation_entry(&mut transaction, &meditation).await?;
+
+  let user_sum =
+    DatabaseHandler::get_user_meditation_sum(&mut transaction, &guild_id, &user_id).await?;
+  let guild_milestone =
+    tracking::get_guild_milestone(&mut transaction, &guild_id, i64::from(minutes)).await?;
+
+  DatabaseHandler::commit_transaction(transaction).await?;
+
+  respond_ephemeral(
+    ctx,
+    press,
+    format!(
+      "{} Added **{minutes} minutes** to your meditation time! Your total meditation time is now {user_sum} minutes :tada:",
+      emoji.mmcheck
+    ),
+  )
+  .await?;
+
+  tracking::announce_guild_milestone(ctx, db, Some(guild_id), guild_milestone).await
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_quick_add_buttons() {
+    assert_eq!(duration_label(20), "20m");
+    assert_eq!(duration_label(60), "1h");
+    assert_eq!(duration_label(90), "1h 30m");
+
+    assert_eq!(durations(&[None, None]), DEFAULT_DURATIONS.to_vec());
+    assert_eq!(
+      durations(&[Some(45), None, Some(15), Some(45)]),
+      vec![15, 45]
+    );
+
+    assert_eq!(parse_custom_id("quick_add:30"), Some(30));
+    assert_eq!(parse_custom_id("quick_add:0"), None);
+    assert_eq!(parse_custom_id("poll_vote:abc:1"), None);
+  }
+}
@@ -0,0 +1,20 @@
+use crate::database::DatabaseHandler;
+use crate::Context;
+
+/// Suggests quote authors whose names contain `partial`, for use with `/quote` and
+/// `/quotes list`. Returns no suggestions outside of a guild or if the lookup fails.
+pub async fn autocomplete_author(ctx: Context<'_>, partial: &str) -> impl Iterator<Item = String> {
+  let authors = match ctx.guild_id() {
+    Some(guild_id) => match ctx.data().db.start_transaction_with_retry(5).await {
+      Ok(mut transaction) => {
+        DatabaseHandler::get_quote_authors(&mut transaction, &guild_id, partial)
+          .await
+          .unwrap_or_default()
+      }
+      Err(_) => Vec::new(),
+    },
+    None => Vec::new(),
+  };
+
+  authors.into_iter()
+}
@@ -0,0 +1,73 @@
+use anyhow::{Error, Result};
+use log::info;
+use poise::serenity_prelude::{self as serenity, CreateCommand, GuildId};
+use poise::serenity_prelude::{InstallationContext, InteractionContext};
+use poise::Command;
+
+use crate::Data;
+
+/// Commands which members can install to their account, so they can use Bloom's personal
+/// features in DMs and in servers Bloom isn't in. Subcommands which need one of Bloom's
+/// servers are marked `guild_only`, so they're still limited to those servers.
+const USER_INSTALLABLE: [&str; 3] = ["stats", "streak", "bookmark"];
+
+/// Builds the application commands to register. When `user_install` is `true`, the
+/// [`USER_INSTALLABLE`] commands can also be installed by members. Discord only supports
+/// this for global commands.
+fn application_commands(
+  commands: &[Command<Data, Error>],
+  user_install: bool,
+) -> Vec<CreateCommand> {
+  let mut builders = vec![];
+  for command in commands {
+    if let Some(slash_command) = command.create_as_slash_command() {
+      builders.push(
+        if user_install && USER_INSTALLABLE.contains(&command.name.as_str()) {
+          slash_command
+            .integration_types(vec![InstallationContext::Guild, InstallationContext::User])
+            .contexts(vec![
+              InteractionContext::Guild,
+              InteractionContext::BotDm,
+              InteractionContext::PrivateChannel,
+            ])
+        } else {
+          slash_command
+        },
+      );
+    }
+    if let Some(context_menu_command) = command.create_as_context_menu_command() {
+      builders.push(context_menu_command);
+    }
+  }
+
+  builders
+}
+
+/// Registers commands globally, including user installs of personal commands.
+pub async fn register_globally(
+  http: impl AsRef<serenity::Http>,
+  commands: &[Command<Data, Error>],
+) -> Result<()> {
+  let builders = application_commands(commands, true);
+  info!("Registering {} commands...", builders.len());
+  serenity::Command::set_global_commands(http, builders).await?;
+
+  Ok(())
+}
+
+/// Registers commands in a single guild, such as a test guild. Commands registered this way
+/// can't be installed by members.
+pub async fn register_in_guild(
+  http: impl AsRef<serenity::Http>,
+  commands: &[Command<Data, Error>],
+  guild_id: GuildId,
+) -> Result<()> {
+  let builders = application_commands(commands, false);
+  info!(
+    "Registering {} commands in guild {guild_id}...",
+    builders.len()
+  );
+  guild_id.set_commands(http, builders).await?;
+
+  Ok(())
+}
@@ -0,0 +1,230 @@
+use anyhow::Result;
+use log::warn;
+use poise::serenity_prelude::{builder::*, ButtonStyle, ComponentInteraction};
+use poise::serenity_prelude::{Context as SerenityContext, Mentionable, User};
+
+use crate::config::BloomBotEmbed;
+use crate::data::retreat::{Retreat, RetreatStatus};
+use crate::database::DatabaseHandler;
+use crate::emoji::EmojiHandler;
+
+const DECISION_PREFIX: &str = "retreat:";
+
+/// Creates the Approve and Remove buttons for a logged retreat. The custom IDs include the
+/// retreat ID, so they can be handled from the global event handler.
+pub fn buttons(retreat_id: &str) -> Vec<CreateActionRow> {
+  vec![CreateActionRow::Buttons(vec![
+    CreateButton::new(format!("{DECISION_PREFIX}approve:{retreat_id}"))
+      .label("Approve")
+      .style(ButtonStyle::Success),
+    CreateButton::new(format!("{DECISION_PREFIX}remove:{retreat_id}"))
+      .label("Remove Entries")
+      .style(ButtonStyle::Danger),
+  ])]
+}
+
+/// Parses the custom ID of a retreat review button, returning whether the retreat was
+/// approved and the retreat ID. Returns [`None`] if the custom ID does not belong to a
+/// retreat review.
+pub fn parse_custom_id(custom_id: &str) -> Option<(bool, &str)> {
+  let (decision, retreat_id) = custom_id.strip_prefix(DECISION_PREFIX)?.split_once(':')?;
+  match decision {
+    "approve" => Some((true, retreat_id)),
+    "remove" => Some((false, retreat_id)),
+    _ => None,
+  }
+}
+
+/// Creates the embed staff review a logged retreat with.
+pub fn review_embed(retreat: &Retreat, user: &User, current_streak: i32) -> CreateEmbed {
+  BloomBotEmbed::new()
+    .title("Retreat Logged")
+    .description("The entries below have already been added. Review them if anything looks off, or leave them be.")
+    .author(CreateEmbedAuthor::new(&user.name).icon_url(user.face()))
+    .field("Member", user.mention().to_string(), true)
+    .field(
+      "Dates",
+      format!(
+        "{} to {}",
+        retreat.start_date.format("%B %-d"),
+        retreat.end_date.format("%B %-d, %Y")
+      ),
+      true,
+    )
+    .field("Current Streak", format!("{current_streak} days"), true)
+    .field(
+      "Entries",
+      format!(
+        "{} days × {} minutes = {} minutes",
+        retreat.days(),
+        retreat.minutes_per_day,
+        retreat.total_minutes()
+      ),
+      false,
+    )
+    .footer(CreateEmbedFooter::new(format!(
+      "Member ID: {} · Retreat ID: {}",
+      retreat.user_id, retreat.id
+    )))
+}
+
+async fn respond_ephemeral(
+  ctx: &SerenityContext,
+  press: &ComponentInteraction,
+  content: String,
+) -> Result<()> {
+  press
+    .create_response(
+      ctx,
+      CreateInteractionResponse::Message(
+        CreateInteractionResponseMessage::new()
+          .content(content)
+          .ephemeral(true),
+      ),
+    )
+    .await?;
+
+  Ok(())
+}
+
+/// Handles the Approve or Remove Entries button on a logged retreat. Removing deletes the
+/// retreat's entries and recalculates the member's streak, and the member is told by DM.
+/// Each retreat can only be reviewed once, after which the buttons are replaced with a note
+/// of the decision and who made it.
+pub async fn handle_decision(
+  ctx: &SerenityContext,
+  db: &DatabaseHandler,
+  emoji: &EmojiHandler,
+  press: &ComponentInteraction,
+  approve: bool,
+  retreat_id: &str,
+) -> Result<()> {
+  let emoji = emoji.get(press.guild_id);
+  let is_moderator = press
+    .member
+    .as_ref()
+    .and_then(|member| member.permissions)
+    .is_some_and(|permissions| permissions.manage_messages());
+  if !is_moderator {
+    return respond_ephemeral(
+      ctx,
+      press,
+      format!("{} Only moderators can review retreats.", emoji.mminfo),
+    )
+    .await;
+  }
+
+  let mut transaction = db.start_transaction_with_retry(5).await?;
+
+  let Some(retreat) = DatabaseHandler::get_retreat(&mut transaction, retreat_id).await? else {
+    return respond_ephemeral(
+      ctx,
+      press,
+      format!("{} This retreat could not be found.", emoji.mminfo),
+    )
+    .await;
+  };
+
+  let status = if approve {
+    RetreatStatus::Approved
+  } else {
+    RetreatStatus::Removed
+  };
+  if DatabaseHandler::set_retreat_status(&mut transaction, &retreat.id, status, &press.user.id)
+    .await?
+    == 0
+  {
+    return respond_ephemeral(
+      ctx,
+      press,
+      format!("{} This retreat has already been reviewed.", emoji.mminfo),
+    )
+    .await;
+  }
+
+  let (outcome, notice) = if approve {
+    (format!("Approved by {}.", press.user.mention()), None)
+  } else {
+    let removed = DatabaseHandler::remove_retreat_entries(&mut transaction, &retreat.id).await?;
+    let streak =
+      DatabaseHandler::recalculate_streak(&mut transaction, &retreat.guild_id, &retreat.user_id)
+        .await?;
+    (
+      format!(
+        "{removed} entries removed by {}. Streak is now {} days.",
+        press.user.mention(),
+        streak.current
+      ),
+      Some(format!(
+        "The entries for your retreat from {} to {} have been removed by staff. If you have questions, please open a ticket with `/ticket open`.",
+        retreat.start_date.format("%B %-d"),
+        retreat.end_date.format("%B %-d, %Y")
+      )),
+    )
+  };
+
+  DatabaseHandler::commit_transaction(transaction).await?;
+
+  let mut embeds: Vec<CreateEmbed> = press
+    .message
+    .embeds
+    .iter()
+    .cloned()
+    .map(CreateEmbed::from)
+    .collect();
+  if let Some(embed) = embeds.pop() {
+    embeds.push(embed.field("Reviewed", outcome, false));
+  }
+
+  press
+    .create_response(
+      ctx,
+      CreateInteractionResponse::UpdateMessage(
+        CreateInteractionResponseMessage::new()
+          .embeds(embeds)
+          .components(Vec::new()),
+      ),
+    )
+    .await?;
+
+  // Approval doesn't change anything for the member, so they're only told about removals
+  if let Some(notice) = notice {
+    if let Err(e) = retreat
+      .user_id
+      .direct_message(
+        ctx,
+        CreateMessage::new().embed(BloomBotEmbed::new().title("Retreat").description(notice)),
+      )
+      .await
+    {
+      warn!("Failed to send retreat review to {}: {e}", retreat.user_id);
+    }
+  }
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_parse_custom_id() {
+    assert_eq!(
+      parse_custom_id("retreat:approve:01JBPTWBXJNAKK288S3D89JKCA"),
+      Some((true, "01JBPTWBXJNAKK288S3D89JKCA"))
+    );
+    assert_eq!(
+      parse_custom_id("retreat:remove:01JBPTWBXJNAKK288S3D89JKCA"),
+      Some((false, "01JBPTWBXJNAKK288S3D89JKCA"))
+    );
+    assert_eq!(
+      parse_custom_id("retreat:maybe:01JBPTWBXJNAKK288S3D89JKCA"),
+      None
+    );
+    assert_eq!(
+      parse_custom_id("streak_repair:approve:01JBPTWBXJNAKK288S3D89JKCA"),
+      None
+    );
+  }
+}
@@ -0,0 +1,226 @@
+use anyhow::Result;
+use log::warn;
+use poise::serenity_prelude::{builder::*, ButtonStyle, ComponentInteraction};
+use poise::serenity_prelude::{Context as SerenityContext, Mentionable, User};
+
+use crate::config::BloomBotEmbed;
+use crate::data::streak_repair::{RepairStatus, StreakRepair};
+use crate::database::DatabaseHandler;
+use crate::emoji::EmojiHandler;
+
+const DECISION_PREFIX: &str = "streak_repair:";
+
+/// Creates the Approve and Deny buttons for a streak repair request. The custom IDs include
+/// the request ID, so they can be handled from the global event handler.
+pub fn buttons(repair_id: &str) -> Vec<CreateActionRow> {
+  vec![CreateActionRow::Buttons(vec![
+    CreateButton::new(format!("{DECISION_PREFIX}approve:{repair_id}"))
+      .label("Approve")
+      .style(ButtonStyle::Success),
+    CreateButton::new(format!("{DECISION_PREFIX}deny:{repair_id}"))
+      .label("Deny")
+      .style(ButtonStyle::Danger),
+  ])]
+}
+
+/// Parses the custom ID of a streak repair button, returning whether the request was
+/// approved and the request ID. Returns [`None`] if the custom ID does not belong to a
+/// streak repair.
+pub fn parse_custom_id(custom_id: &str) -> Option<(bool, &str)> {
+  let (decision, repair_id) = custom_id.strip_prefix(DECISION_PREFIX)?.split_once(':')?;
+  match decision {
+    "approve" => Some((true, repair_id)),
+    "deny" => Some((false, repair_id)),
+    _ => None,
+  }
+}
+
+/// Creates the embed staff review a streak repair request with.
+pub fn request_embed(repair: &StreakRepair, user: &User, current_streak: i32) -> CreateEmbed {
+  BloomBotEmbed::new()
+    .title("Streak Repair Request")
+    .author(CreateEmbedAuthor::new(&user.name).icon_url(user.face()))
+    .field("Member", user.mention().to_string(), true)
+    .field(
+      "Missed Day",
+      repair.date.format("%B %-d, %Y").to_string(),
+      true,
+    )
+    .field("Current Streak", format!("{current_streak} days"), true)
+    .field("Reason", &repair.reason, false)
+    .footer(CreateEmbedFooter::new(format!(
+      "Member ID: {} · Request ID: {}",
+      repair.user_id, repair.id
+    )))
+}
+
+async fn respond_ephemeral(
+  ctx: &SerenityContext,
+  press: &ComponentInteraction,
+  content: String,
+) -> Result<()> {
+  press
+    .create_response(
+      ctx,
+      CreateInteractionResponse::Message(
+        CreateInteractionResponseMessage::new()
+          .content(content)
+          .ephemeral(true),
+      ),
+    )
+    .await?;
+
+  Ok(())
+}
+
+/// Handles the Approve or Deny button on a streak repair request. Approving counts the day
+/// toward the member's streak and recalculates it. Each request can only be handled once,
+/// after which the buttons are replaced with a note of the decision and who made it, and
+/// the member is told by DM.
+pub async fn handle_decision(
+  ctx: &SerenityContext,
+  db: &DatabaseHandler,
+  emoji: &EmojiHandler,
+  press: &ComponentInteraction,
+  approve: bool,
+  repair_id: &str,
+) -> Result<()> {
+  let emoji = emoji.get(press.guild_id);
+  let is_moderator = press
+    .member
+    .as_ref()
+    .and_then(|member| member.permissions)
+    .is_some_and(|permissions| permissions.manage_messages());
+  if !is_moderator {
+    return respond_ephemeral(
+      ctx,
+      press,
+      format!(
+        "{} Only moderators can review streak repairs.",
+        emoji.mminfo
+      ),
+    )
+    .await;
+  }
+
+  let mut transaction = db.start_transaction_with_retry(5).await?;
+
+  let Some(repair) = DatabaseHandler::get_streak_repair(&mut transaction, repair_id).await? else {
+    return respond_ephemeral(
+      ctx,
+      press,
+      format!("{} This request could not be found.", emoji.mminfo),
+    )
+    .await;
+  };
+
+  let status = if approve {
+    RepairStatus::Approved
+  } else {
+    RepairStatus::Denied
+  };
+  if DatabaseHandler::set_streak_repair_status(&mut transaction, &repair.id, status, &press.user.id)
+    .await?
+    == 0
+  {
+    return respond_ephemeral(
+      ctx,
+      press,
+      format!("{} This request has already been handled.", emoji.mminfo),
+    )
+    .await;
+  }
+
+  let date = repair.date.format("%B %-d, %Y");
+  let (outcome, notice) = if approve {
+    let streak =
+      DatabaseHandler::recalculate_streak(&mut transaction, &repair.guild_id, &repair.user_id)
+        .await?;
+    (
+      format!(
+        "Approved by {}. Streak is now {} days.",
+        press.user.mention(),
+        streak.current
+      ),
+      format!(
+        "Your streak repair for {date} has been approved. Your current meditation streak is {} days.",
+        streak.current
+      ),
+    )
+  } else {
+    (
+      format!("Denied by {}.", press.user.mention()),
+      format!("Your streak repair for {date} has been denied. If you have questions, please open a ticket with `/ticket open`."),
+    )
+  };
+
+  DatabaseHandler::commit_transaction(transaction).await?;
+
+  let mut embeds: Vec<CreateEmbed> = press
+    .message
+    .embeds
+    .iter()
+    .cloned()
+    .map(CreateEmbed::from)
+    .collect();
+  if let Some(embed) = embeds.pop() {
+    embeds.push(embed.field("Handled", outcome, false));
+  }
+
+  press
+    .create_response(
+      ctx,
+      CreateInteractionResponse::UpdateMessage(
+        CreateInteractionResponseMessage::new()
+          .embeds(embeds)
+          .components(Vec::new()),
+      ),
+    )
+    .await?;
+
+  // The decision has been recorded, so members who don't accept DMs can check with /streak show
+  if let Err(e) = repair
+    .user_id
+    .direct_message(
+      ctx,
+      CreateMessage::new().embed(
+        BloomBotEmbed::new()
+          .title("Streak Repair")
+          .description(notice),
+      ),
+    )
+    .await
+  {
+    warn!(
+      "Failed to send streak repair decision to {}: {e}",
+      repair.user_id
+    );
+  }
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_parse_custom_id() {
+    assert_eq!(
+      parse_custom_id("streak_repair:approve:01JBPTWBXJNAKK288S3D89JKCA"),
+      Some((true, "01JBPTWBXJNAKK288S3D89JKCA"))
+    );
+    assert_eq!(
+      parse_custom_id("streak_repair:deny:01JBPTWBXJNAKK288S3D89JKCA"),
+      Some((false, "01JBPTWBXJNAKK288S3D89JKCA"))
+    );
+    assert_eq!(
+      parse_custom_id("streak_repair:maybe:01JBPTWBXJNAKK288S3D89JKCA"),
+      None
+    );
+    assert_eq!(
+      parse_custom_id("watchlist:erase:01JBPTWBXJNAKK288S3D89JKCA"),
+      None
+    );
+  }
+}
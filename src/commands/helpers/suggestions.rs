@@ -0,0 +1,209 @@
+use anyhow::Result;
+use poise::serenity_prelude::{builder::*, ButtonStyle, ComponentInteraction};
+use poise::serenity_prelude::{Context as SerenityContext, Mentionable};
+
+use crate::config::BloomBotEmbed;
+use crate::data::suggestion::{Suggestion, SuggestionVotes};
+use crate::database::DatabaseHandler;
+use crate::emoji::EmojiHandler;
+
+const VOTE_PREFIX: &str = "suggestion_vote:";
+
+/// Creates the upvote and downvote buttons for a [`Suggestion`], labeled with the current
+/// vote counts. The buttons are disabled once the suggestion no longer accepts votes, so
+/// the final counts stay visible.
+pub fn buttons(suggestion: &Suggestion, votes: SuggestionVotes) -> Vec<CreateActionRow> {
+  let closed = !suggestion.status.accepts_votes();
+
+  vec![CreateActionRow::Buttons(vec![
+    CreateButton::new(format!("{VOTE_PREFIX}up:{}", suggestion.id))
+      .label(format!("👍 {}", votes.upvotes))
+      .style(ButtonStyle::Secondary)
+      .disabled(closed),
+    CreateButton::new(format!("{VOTE_PREFIX}down:{}", suggestion.id))
+      .label(format!("👎 {}", votes.downvotes))
+      .style(ButtonStyle::Secondary)
+      .disabled(closed),
+  ])]
+}
+
+/// Parses the custom ID of a suggestion vote button, returning whether it is an upvote and
+/// the suggestion ID. Returns [`None`] if the custom ID does not belong to a suggestion.
+pub fn parse_custom_id(custom_id: &str) -> Option<(bool, &str)> {
+  let (vote, suggestion_id) = custom_id.strip_prefix(VOTE_PREFIX)?.split_once(':')?;
+  match vote {
+    "up" => Some((true, suggestion_id)),
+    "down" => Some((false, suggestion_id)),
+    _ => None,
+  }
+}
+
+/// Creates the embed for a [`Suggestion`]. The status is shown once staff have updated it,
+/// along with their note, if any.
+pub fn suggestion_embed(suggestion: &Suggestion) -> CreateEmbed {
+  let mut embed = BloomBotEmbed::new()
+    .description(&suggestion.content)
+    .footer(CreateEmbedFooter::new(format!(
+      "Suggestion ID: {}",
+      suggestion.id
+    )));
+
+  if let Some(updated_by) = suggestion.status_updated_by {
+    let note = match &suggestion.status_note {
+      Some(note) => format!("{note}\n-# Updated by {}", updated_by.mention()),
+      None => format!("-# Updated by {}", updated_by.mention()),
+    };
+    embed = embed.field(suggestion.status.label(), note, false);
+  }
+
+  embed
+}
+
+/// Updates the message a [`Suggestion`] was posted in to show its current status and votes.
+pub async fn update_message(
+  ctx: &SerenityContext,
+  suggestion: &Suggestion,
+  votes: SuggestionVotes,
+) -> Result<()> {
+  if let Some(message_id) = suggestion.message_id {
+    suggestion
+      .channel_id
+      .edit_message(
+        ctx,
+        message_id,
+        EditMessage::new()
+          .embed(suggestion_embed(suggestion))
+          .components(buttons(suggestion, votes)),
+      )
+      .await?;
+  }
+
+  Ok(())
+}
+
+async fn respond_ephemeral(
+  ctx: &SerenityContext,
+  press: &ComponentInteraction,
+  content: String,
+) -> Result<()> {
+  press
+    .create_response(
+      ctx,
+      CreateInteractionResponse::Message(
+        CreateInteractionResponseMessage::new()
+          .content(content)
+          .ephemeral(true),
+      ),
+    )
+    .await?;
+
+  Ok(())
+}
+
+/// Records a vote when a suggestion button is pressed. Pressing the same button again
+/// removes the vote, and pressing the other button changes it.
+pub async fn handle_vote(
+  ctx: &SerenityContext,
+  db: &DatabaseHandler,
+  emoji: &EmojiHandler,
+  press: &ComponentInteraction,
+  upvote: bool,
+  suggestion_id: &str,
+) -> Result<()> {
+  let Some(guild_id) = press.guild_id else {
+    return Ok(());
+  };
+  let emoji = emoji.get(Some(guild_id));
+
+  let mut transaction = db.start_transaction_with_retry(5).await?;
+
+  let Some(suggestion) =
+    DatabaseHandler::get_suggestion(&mut transaction, &guild_id, suggestion_id).await?
+  else {
+    return respond_ephemeral(
+      ctx,
+      press,
+      format!("{} This suggestion could not be found.", emoji.mminfo),
+    )
+    .await;
+  };
+
+  if !suggestion.status.accepts_votes() {
+    return respond_ephemeral(
+      ctx,
+      press,
+      format!("{} Voting has closed for this suggestion.", emoji.mminfo),
+    )
+    .await;
+  }
+
+  let user_id = press.user.id;
+  let (vote, a_vote) = if upvote {
+    ("upvote", "an upvote")
+  } else {
+    ("downvote", "a downvote")
+  };
+  let previous =
+    DatabaseHandler::get_suggestion_vote(&mut transaction, &suggestion.id, &user_id).await?;
+
+  let confirmation = if previous == Some(upvote) {
+    DatabaseHandler::remove_suggestion_vote(&mut transaction, &suggestion.id, &user_id).await?;
+    format!("Your {vote} has been removed.")
+  } else {
+    DatabaseHandler::set_suggestion_vote(&mut transaction, &suggestion.id, &user_id, upvote)
+      .await?;
+    if previous.is_some() {
+      format!("Your vote has been changed to {a_vote}.")
+    } else {
+      format!("You gave this suggestion {a_vote}.")
+    }
+  };
+
+  let votes = DatabaseHandler::get_suggestion_votes(&mut transaction, &suggestion.id).await?;
+  DatabaseHandler::commit_transaction(transaction).await?;
+
+  press
+    .create_response(
+      ctx,
+      CreateInteractionResponse::UpdateMessage(
+        CreateInteractionResponseMessage::new().components(buttons(&suggestion, votes)),
+      ),
+    )
+    .await?;
+
+  press
+    .create_followup(
+      ctx,
+      CreateInteractionResponseFollowup::new()
+        .content(format!("{} {confirmation}", emoji.mmcheck))
+        .ephemeral(true),
+    )
+    .await?;
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_parse_custom_id() {
+    assert_eq!(
+      parse_custom_id("suggestion_vote:up:01JBPTWBXJNAKK288S3D89JKCA"),
+      Some((true, "01JBPTWBXJNAKK288S3D89JKCA"))
+    );
+    assert_eq!(
+      parse_custom_id("suggestion_vote:down:01JBPTWBXJNAKK288S3D89JKCA"),
+      Some((false, "01JBPTWBXJNAKK288S3D89JKCA"))
+    );
+    assert_eq!(
+      parse_custom_id("suggestion_vote:sideways:01JBPTWBXJNAKK288S3D89JKCA"),
+      None
+    );
+    assert_eq!(
+      parse_custom_id("poll_vote:01JBPTWBXJNAKK288S3D89JKCA:3"),
+      None
+    );
+  }
+}
@@ -0,0 +1,151 @@
+use anyhow::Result;
+use poise::serenity_prelude::Context as SerenityContext;
+use poise::serenity_prelude::{builder::*, ButtonStyle, ComponentInteraction};
+
+use crate::config::BloomBotEmbed;
+use crate::data::term::Term;
+use crate::database::DatabaseHandler;
+use crate::Context;
+
+const SEE_ALSO_PREFIX: &str = "see_also:";
+
+/// Suggests glossary term names and aliases containing `partial`. Aliases are resolved to
+/// their terms on lookup, so either can be used wherever a term is expected. Returns no
+/// suggestions outside of a guild or if the lookup fails.
+pub async fn autocomplete_term(ctx: Context<'_>, partial: &str) -> impl Iterator<Item = String> {
+  let names = match ctx.guild_id() {
+    Some(guild_id) => match ctx.data().db.start_transaction_with_retry(5).await {
+      Ok(mut transaction) => DatabaseHandler::get_term_names(&mut transaction, &guild_id, partial)
+        .await
+        .unwrap_or_default(),
+      Err(_) => Vec::new(),
+    },
+    None => Vec::new(),
+  };
+
+  names.into_iter()
+}
+
+/// Creates an embed showing the full glossary entry for a [`Term`], as used by
+/// `/glossary info`.
+pub fn term_embed(term: &Term) -> CreateEmbed {
+  let mut embed = BloomBotEmbed::new()
+    .title(&term.name)
+    .description(&term.meaning);
+
+  if let Some(usage) = term.usage.as_ref().filter(|usage| !usage.is_empty()) {
+    embed = embed.field("Example of Usage:", usage, false);
+  }
+
+  if let Some(links) = term.links.as_ref().filter(|links| !links.is_empty()) {
+    let field = links
+      .iter()
+      .enumerate()
+      .map(|(i, link)| format!("{}. {link}\n", i + 1))
+      .collect::<String>();
+    embed = embed.field("Related Resources:", field, false);
+  }
+
+  if let Some(aliases) = term.aliases.as_ref().filter(|aliases| !aliases.is_empty()) {
+    embed = embed.field("Aliases:", aliases.join(", "), false);
+  }
+
+  if let Some(related) = term.related.as_ref().filter(|related| !related.is_empty()) {
+    embed = embed.field("See Also:", related.join(", "), false);
+  }
+
+  if let Some(category) = term
+    .category
+    .as_ref()
+    .filter(|category| !category.is_empty())
+  {
+    embed = embed.footer(CreateEmbedFooter::new(format!("Categories: {category}")));
+  }
+
+  embed
+}
+
+/// Creates a button for each of the first five "See also" entries of a [`Term`], which
+/// show the related entry when pressed. Returns [`None`] if the term has no related entries.
+///
+/// The custom IDs include the related term name, so presses can be handled from the global
+/// event handler for as long as the message exists.
+pub fn see_also_buttons(term: &Term) -> Option<CreateActionRow> {
+  let buttons: Vec<CreateButton> = term
+    .related
+    .as_ref()?
+    .iter()
+    .map(|name| format!("{SEE_ALSO_PREFIX}{name}"))
+    // Custom IDs cannot be longer than 100 characters
+    .filter(|custom_id| custom_id.chars().count() <= 100)
+    .take(5)
+    .map(|custom_id| {
+      let label = custom_id[SEE_ALSO_PREFIX.len()..].to_string();
+      CreateButton::new(custom_id)
+        .label(label)
+        .style(ButtonStyle::Secondary)
+    })
+    .collect();
+
+  if buttons.is_empty() {
+    None
+  } else {
+    Some(CreateActionRow::Buttons(buttons))
+  }
+}
+
+/// Parses the custom ID of a "See also" button, returning the related term name. Returns
+/// [`None`] if the custom ID does not belong to a "See also" button.
+pub fn parse_custom_id(custom_id: &str) -> Option<&str> {
+  custom_id.strip_prefix(SEE_ALSO_PREFIX)
+}
+
+/// Handles a press of one of the [`see_also_buttons`], showing the related entry to the
+/// user who pressed it.
+pub async fn handle_see_also(
+  ctx: &SerenityContext,
+  db: &DatabaseHandler,
+  press: &ComponentInteraction,
+  term_name: &str,
+) -> Result<()> {
+  let term = match press.guild_id {
+    Some(guild_id) => {
+      let mut transaction = db.start_transaction_with_retry(5).await?;
+      DatabaseHandler::get_term(&mut transaction, &guild_id, term_name).await?
+    }
+    None => None,
+  };
+
+  let response = match term {
+    Some(term) => {
+      let mut response = CreateInteractionResponseMessage::new().embed(term_embed(&term));
+      if let Some(buttons) = see_also_buttons(&term) {
+        response = response.components(vec![buttons]);
+      }
+      response
+    }
+    None => CreateInteractionResponseMessage::new().content(format!(
+      "The term `{term_name}` is no longer in the glossary."
+    )),
+  };
+
+  press
+    .create_response(
+      ctx,
+      CreateInteractionResponse::Message(response.ephemeral(true)),
+    )
+    .await?;
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_parse_custom_id() {
+    assert_eq!(parse_custom_id("see_also:Metta"), Some("Metta"));
+    assert_eq!(parse_custom_id("redeem_key:1234567890"), None);
+  }
+}
@@ -0,0 +1,47 @@
+use anyhow::Result;
+use poise::serenity_prelude::{CacheHttp, Channel, ChannelId, ChannelType, GuildChannel};
+
+/// Whether a channel type is a thread, including forum posts.
+pub fn is_thread(kind: ChannelType) -> bool {
+  matches!(
+    kind,
+    ChannelType::PublicThread | ChannelType::PrivateThread | ChannelType::NewsThread
+  )
+}
+
+/// Retrieves a guild channel, or [`None`] if it isn't one or can't be retrieved.
+pub async fn guild_channel(ctx: impl CacheHttp, channel_id: ChannelId) -> Option<GuildChannel> {
+  channel_id
+    .to_channel(ctx)
+    .await
+    .ok()
+    .and_then(Channel::guild)
+}
+
+/// Returns the channel a thread or forum post belongs to, or [`None`] if the channel
+/// isn't a thread.
+pub async fn thread_parent(ctx: impl CacheHttp, channel_id: ChannelId) -> Option<ChannelId> {
+  guild_channel(ctx, channel_id)
+    .await
+    .filter(|channel| is_thread(channel.kind))
+    .and_then(|channel| channel.parent_id)
+}
+
+/// Whether `channel_id` is `target`, or a thread or forum post in `target`.
+pub async fn is_in_channel(ctx: impl CacheHttp, channel_id: ChannelId, target: ChannelId) -> bool {
+  channel_id == target || thread_parent(ctx, channel_id).await == Some(target)
+}
+
+/// Formats a channel's name for logs, e.g., `general`, or `question (thread in #general)`
+/// for threads.
+pub async fn channel_label(ctx: impl CacheHttp + Copy, channel_id: ChannelId) -> Result<String> {
+  let name = channel_id.name(ctx).await?;
+
+  Ok(match thread_parent(ctx, channel_id).await {
+    Some(parent_id) => match parent_id.name(ctx).await {
+      Ok(parent) => format!("{name} (thread in #{parent})"),
+      Err(_) => format!("{name} (thread)"),
+    },
+    None => name,
+  })
+}
@@ -0,0 +1,176 @@
+use anyhow::Result;
+use poise::serenity_prelude::{builder::*, ChannelId, ChannelType, Context as SerenityContext};
+use poise::serenity_prelude::{GuildId, Mentionable, RoleId, User};
+use sqlx::{Postgres, Transaction};
+
+use crate::config::{BloomBotEmbed, ROLES};
+use crate::data::ticket::{Ticket, TicketMessage};
+use crate::database::DatabaseHandler;
+
+/// Discord's limit on thread names.
+const MAX_THREAD_NAME: usize = 100;
+
+/// Opens a [`Ticket`] for `user` by creating a private thread in `ticket_channel` and
+/// pinging staff in it. The ticket is added to the database, but the transaction is left
+/// for the caller to commit.
+pub async fn open(
+  ctx: &SerenityContext,
+  transaction: &mut Transaction<'_, Postgres>,
+  guild_id: GuildId,
+  ticket_channel: ChannelId,
+  user: &User,
+  subject: Option<String>,
+) -> Result<Ticket> {
+  let name = match &subject {
+    Some(subject) => format!("{}: {subject}", user.name),
+    None => format!("Ticket: {}", user.name),
+  };
+
+  let thread = ticket_channel
+    .create_thread(
+      ctx,
+      CreateThread::new(name.chars().take(MAX_THREAD_NAME).collect::<String>())
+        .kind(ChannelType::PrivateThread)
+        .invitable(false),
+    )
+    .await?;
+
+  let ticket = Ticket::new(guild_id, user.id, thread.id, subject);
+
+  let mut embed = BloomBotEmbed::new()
+    .title("New Ticket")
+    .author(CreateEmbedAuthor::new(&user.name).icon_url(user.face()))
+    .field("Member", format!("{} ({})", user.mention(), user.id), false);
+  if let Some(subject) = &ticket.subject {
+    embed = embed.field("Subject", subject, false);
+  }
+  embed = embed.field(
+    "Replying",
+    "Use `/ticket reply` here to reply to the member by DM. Other messages in this thread are only seen by staff. Use `/ticket close` when you're done.",
+    false,
+  );
+  embed = embed.footer(CreateEmbedFooter::new(format!("Ticket ID: {}", ticket.id)));
+
+  // Mentioning the staff role adds them to the private thread
+  thread
+    .send_message(
+      ctx,
+      CreateMessage::new()
+        .content(format!("<@&{}>", ROLES.staff))
+        .embed(embed)
+        .allowed_mentions(CreateAllowedMentions::new().roles(vec![RoleId::new(ROLES.staff)])),
+    )
+    .await?;
+
+  DatabaseHandler::add_ticket(transaction, &ticket).await?;
+
+  Ok(ticket)
+}
+
+/// Posts a message from the member in the ticket thread and adds it to the transcript.
+pub async fn relay_to_staff(
+  ctx: &SerenityContext,
+  transaction: &mut Transaction<'_, Postgres>,
+  ticket: &Ticket,
+  user: &User,
+  content: String,
+) -> Result<()> {
+  ticket
+    .thread_id
+    .send_message(
+      ctx,
+      CreateMessage::new().embed(
+        BloomBotEmbed::new()
+          .author(CreateEmbedAuthor::new(&user.name).icon_url(user.face()))
+          .description(&content),
+      ),
+    )
+    .await?;
+
+  let message = TicketMessage::new(&ticket.id, user.id, false, content);
+  DatabaseHandler::add_ticket_message(transaction, &message).await?;
+
+  Ok(())
+}
+
+/// Formats a ticket transcript as plain text for exporting.
+pub fn transcript(ticket: &Ticket, messages: &[TicketMessage]) -> String {
+  let mut transcript = format!(
+    "Ticket {}\nMember: {}\nSubject: {}\nOpened: {}\n",
+    ticket.id,
+    ticket.user_id,
+    ticket.subject.as_deref().unwrap_or("None"),
+    ticket.opened_at.format("%Y-%m-%d %H:%M UTC"),
+  );
+  if let Some(closed_at) = ticket.closed_at {
+    transcript.push_str(&format!(
+      "Closed: {}{}\n",
+      closed_at.format("%Y-%m-%d %H:%M UTC"),
+      ticket
+        .closed_by
+        .map(|closed_by| format!(" by {closed_by}"))
+        .unwrap_or_default()
+    ));
+  }
+  transcript.push('\n');
+
+  for message in messages {
+    transcript.push_str(&format!(
+      "[{}] {} {}:\n{}\n\n",
+      message.created_at.format("%Y-%m-%d %H:%M"),
+      if message.from_staff {
+        "Staff"
+      } else {
+        "Member"
+      },
+      message.author_id,
+      message.content
+    ));
+  }
+
+  transcript
+}
+
+#[cfg(test)]
+mod tests {
+  use chrono::{TimeZone, Utc};
+  use poise::serenity_prelude::UserId;
+
+  use super::*;
+
+  #[test]
+  fn test_transcript() {
+    let mut ticket = Ticket::new(
+      GuildId::new(123u64),
+      UserId::new(789u64),
+      ChannelId::new(456u64),
+      Some("Question about erases".to_owned()),
+    );
+    ticket.opened_at = Utc
+      .with_ymd_and_hms(2024, 11, 3, 14, 10, 0)
+      .single()
+      .unwrap_or_default();
+    ticket.closed_at = Some(
+      Utc
+        .with_ymd_and_hms(2024, 11, 3, 15, 0, 0)
+        .single()
+        .unwrap_or_default(),
+    );
+    ticket.closed_by = Some(UserId::new(999u64));
+
+    let mut question = TicketMessage::new(&ticket.id, UserId::new(789u64), false, "Hi!".to_owned());
+    question.created_at = ticket.opened_at;
+    let mut reply = TicketMessage::new(&ticket.id, UserId::new(999u64), true, "Hello!".to_owned());
+    reply.created_at = Utc
+      .with_ymd_and_hms(2024, 11, 3, 14, 20, 0)
+      .single()
+      .unwrap_or_default();
+
+    let transcript = transcript(&ticket, &[question, reply]);
+
+    assert!(transcript.contains("Subject: Question about erases\n"));
+    assert!(transcript.contains("Closed: 2024-11-03 15:00 UTC by 999\n"));
+    assert!(transcript.contains("[2024-11-03 14:10] Member 789:\nHi!\n"));
+    assert!(transcript.ends_with("[2024-11-03 14:20] Staff 999:\nHello!\n\n"));
+  }
+}
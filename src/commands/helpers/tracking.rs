@@ -1,55 +1,150 @@
 use anyhow::Result;
 use log::error;
-use poise::serenity_prelude::{ChannelId, CreateAllowedMentions, CreateMessage, GuildId};
+use poise::serenity_prelude::{CacheHttp, ChannelId, CreateMessage, GuildId};
 use poise::serenity_prelude::{Member, Mentionable, UserId};
-use poise::CreateReply;
 use sqlx::{Postgres, Transaction};
 
-use crate::config::{StreakRoles, TimeSumRoles, CHANNELS, EMOJI};
+use crate::commands::helpers::discord::Discord;
+use crate::commands::helpers::{announcements, threads};
+use crate::config::{BloomBotEmbed, StreakRoles, TimeSumRoles, CHANNELS};
+use crate::data::guild_feature::Feature;
+use crate::data::guild_settings::GuildSettings;
+use crate::data::milestone::Milestone;
+use crate::data::tracking_profile::Profile;
 use crate::database::DatabaseHandler;
+use crate::roles::RoleUpdate;
 use crate::Context;
 
-/// Queries the database for the total count of guild sessions and divides by 10. If there is no
-/// remainder, the function queries the database for the guild total of minutes meditated, divides
-/// this number by 60 to convert to hours, and returns this number. If the total count divided by
-/// 10 produces a remainder, the function returns `None`. This works as a trigger to announce the
-/// total minutes every 10th session added.
-pub async fn get_guild_hours(
+/// Maximum length of a quote shown with a tracking notification, in characters.
+const MAX_QUOTE_LENGTH: usize = 300;
+
+/// Queries the database for the total count of guild sessions and returns `true` for every
+/// 10th session added. This works as a trigger to refresh the leaderboards.
+pub async fn leaderboards_due(
   transaction: &mut Transaction<'_, Postgres>,
   guild_id: &GuildId,
-) -> Result<Option<i64>> {
+) -> Result<bool> {
   let guild_count = DatabaseHandler::get_guild_meditation_count(transaction, guild_id).await?;
-  if guild_count % 10 == 0 {
-    let guild_sum = DatabaseHandler::get_guild_meditation_sum(transaction, guild_id).await?;
-    Ok(Some(guild_sum / 60))
+  Ok(guild_count % 10 == 0)
+}
+
+/// The channel recorded with meditation tracked in `channel_id`, or `None` if the guild
+/// doesn't have channel segments turned on. Meditation tracked in a thread or forum post
+/// counts toward the channel it belongs to.
+pub async fn segment_channel(
+  ctx: impl CacheHttp,
+  settings: &GuildSettings,
+  channel_id: ChannelId,
+) -> Option<ChannelId> {
+  if !settings.channel_segments {
+    return None;
+  }
+
+  Some(
+    threads::thread_parent(ctx, channel_id)
+      .await
+      .unwrap_or(channel_id),
+  )
+}
+
+/// Returns the milestone crossed when the guild total of minutes meditated went from
+/// `previous_sum` to `sum`, given milestones every `interval` minutes. If more than one
+/// milestone was crossed at once, only the highest is returned.
+fn crossed_milestone(previous_sum: i64, sum: i64, interval: i64) -> Option<i64> {
+  if interval <= 0 {
+    return None;
+  }
+
+  let milestone = sum / interval * interval;
+  (milestone > 0 && previous_sum < milestone).then_some(milestone)
+}
+
+/// Checks whether adding `added_minutes` took the guild total of minutes meditated past one of
+/// the milestones configured in [`GuildSettings`][settings]. Newly reached milestones are
+/// recorded, so each is only returned once, regardless of how many times the total crosses it.
+///
+/// [settings]: crate::data::guild_settings::GuildSettings
+pub async fn get_guild_milestone(
+  transaction: &mut Transaction<'_, Postgres>,
+  guild_id: &GuildId,
+  added_minutes: i64,
+) -> Result<Option<i64>> {
+  let settings = DatabaseHandler::get_guild_settings(transaction, guild_id).await?;
+  let Some(interval) = settings.milestone_interval else {
+    return Ok(None);
+  };
+
+  let guild_sum = DatabaseHandler::get_guild_meditation_sum(transaction, guild_id).await?;
+  let Some(milestone) = crossed_milestone(guild_sum - added_minutes, guild_sum, interval) else {
+    return Ok(None);
+  };
+
+  let recorded =
+    DatabaseHandler::record_guild_milestone(transaction, &Milestone::new(*guild_id, milestone))
+      .await?;
+
+  Ok((recorded > 0).then_some(milestone))
+}
+
+/// Formats a number with commas separating each group of thousands.
+fn separate_thousands(number: i64) -> String {
+  let digits = number.unsigned_abs().to_string();
+  let mut separated = String::with_capacity(digits.len() + digits.len() / 3);
+
+  for (i, digit) in digits.chars().enumerate() {
+    if i > 0 && (digits.len() - i) % 3 == 0 {
+      separated.push(',');
+    }
+    separated.push(digit);
+  }
+
+  if number < 0 {
+    format!("-{separated}")
   } else {
-    Ok(None)
+    separated
   }
 }
 
-/// Announces the guild total of minutes meditated in the [`CHANNELS.tracking`][tracking] channel, using
-/// the value returned by [`get_guild_hours`] as a trigger to announce every 10th session added.
+/// Formats a milestone in hours where it is a whole number of hours, or minutes otherwise.
+pub fn format_milestone(minutes: i64) -> String {
+  if minutes % 60 == 0 {
+    format!("{} hours", separate_thousands(minutes / 60))
+  } else {
+    format!("{} minutes", separate_thousands(minutes))
+  }
+}
+
+/// Celebrates a milestone returned by [`get_guild_milestone`] in the [`CHANNELS.tracking`][tracking]
+/// channel, publishing it if that's an announcement channel and auto-publishing is on.
 ///
 /// [tracking]: crate::config::CHANNELS
-pub async fn post_guild_hours(ctx: &Context<'_>, guild_hours: &Option<i64>) -> Result<()> {
-  if let Some(guild_hours) = guild_hours {
-    if ctx.channel_id() == CHANNELS.tracking {
-      ctx
-        .say(format!(
-          "Awesome sauce! This server has collectively generated {guild_hours} hours of realmbreaking meditation!"
-        ))
-        .await?;
-    } else {
-      ChannelId::new(CHANNELS.tracking)
-        .send_message(
-          &ctx,
-          CreateMessage::new()
-            .content(format!(
-              "Awesome sauce! This server has collectively generated {guild_hours} hours of realmbreaking meditation!"
-            ))
-            .allowed_mentions(CreateAllowedMentions::new()),
-        )
-        .await?;
+pub async fn post_guild_milestone(ctx: &Context<'_>, milestone: Option<i64>) -> Result<()> {
+  announce_guild_milestone(*ctx, &ctx.data().db, ctx.guild_id(), milestone).await
+}
+
+/// Posts a milestone returned by [`get_guild_milestone`], for when there is no command
+/// context, such as button presses handled by the event handler.
+pub async fn announce_guild_milestone(
+  ctx: impl CacheHttp + Copy,
+  db: &DatabaseHandler,
+  guild_id: Option<GuildId>,
+  milestone: Option<i64>,
+) -> Result<()> {
+  if let Some(milestone) = milestone {
+    let embed = BloomBotEmbed::new()
+      .title(":tada: Server Milestone Reached! :tada:")
+      .description(format!(
+        "Awesome sauce! This server has collectively generated **{}** of realmbreaking meditation! Thank you to everyone who has sat with us along the way.",
+        format_milestone(milestone)
+      ));
+
+    // Sent as a standalone message rather than a reply, since replies to private
+    // tracking are ephemeral.
+    let announcement = ChannelId::new(CHANNELS.tracking)
+      .send_message(ctx, CreateMessage::new().embed(embed))
+      .await?;
+    if let Some(guild_id) = guild_id {
+      announcements::publish(ctx, db, guild_id, &announcement).await;
     }
   }
   Ok(())
@@ -58,13 +153,8 @@ pub async fn post_guild_hours(ctx: &Context<'_>, guild_hours: &Option<i64>) -> R
 /// Takes a `&str` and strips all asterisks (`*`), then escapes all other ASCII punctuation,
 /// except for underscores (`_`) and tildes (`~`). For Discord markdown, this prevents italics
 /// (or cancellation thereof) and all other markdown except for underline and strikethrough.
-/// This is the desired behavior for quotes, which permit normal markdown when displayed using
-/// [`quote`][quote], but are fully italicized when presented with an [`add`][add] or
-/// [`import`][import] notification.
-///
-/// [add]: crate::commands::add::add()
-/// [quote]: crate::commands::quote::quote()
-/// [import]: crate::commands::import::import()
+/// This is the desired behavior for quotes which have been truncated by [`format_quote`], since
+/// truncating can leave markdown unbalanced.
 pub fn minimize_markdown(text: &str) -> String {
   text
     .chars()
@@ -83,14 +173,47 @@ pub fn minimize_markdown(text: &str) -> String {
     .collect::<String>()
 }
 
+/// Formats a quote for display with an [`add`][add] or [`import`][import] notification. Quotes
+/// are shown as a block quote, so any markdown added by staff is displayed as intended.
+///
+/// Quotes longer than [`MAX_QUOTE_LENGTH`] are truncated at a word boundary. Since truncating can
+/// leave markdown unbalanced, truncated quotes have their markdown minimized with
+/// [`minimize_markdown`].
+///
+/// [add]: crate::commands::add::add()
+/// [import]: crate::commands::import::import()
+pub fn format_quote(text: &str) -> String {
+  let text = text.trim();
+
+  let text = if text.chars().count() > MAX_QUOTE_LENGTH {
+    // Leave room for the ellipsis
+    let truncated: String = text.chars().take(MAX_QUOTE_LENGTH - 1).collect();
+    let truncated = match truncated.rfind(char::is_whitespace) {
+      Some(index) => &truncated[..index],
+      None => truncated.as_str(),
+    };
+    format!("{}…", minimize_markdown(truncated.trim_end()))
+  } else {
+    text.to_string()
+  };
+
+  text
+    .lines()
+    .map(|line| format!("> {line}"))
+    .collect::<Vec<String>>()
+    .join("\n")
+}
+
 /// Displays confirmation of time added via [`add`][add] or [`import`][import] and attempts to
-/// include a random quote from the database. If a quote could not be fetched, the notification
-/// is posted with the quote omitted.
+/// include a random quote from the database. If a quote could not be fetched, or quotes on
+/// tracking notifications have been turned off for the guild, the notification is posted with
+/// the quote omitted.
 ///
 /// When called from [`add`][add], the notification is formatted for use as a reply to the slash
 /// command. When called from elsewhere ([`import`][import]), the notification is formatted for
 /// independent posting, directly to a channel, e.g., [`CHANNELS.tracking`][tracking].
-/// When `privacy` is set to `true`, notifications are anonymized.
+/// When `privacy` is set to `true`, notifications are anonymized. When `redact_minutes` is set
+/// to `true`, notifications only note that a session was logged, without the minutes or total.
 ///
 /// [add]: crate::commands::add::add()
 /// [import]: crate::commands::import::import()
@@ -103,151 +226,130 @@ pub async fn show_add_with_quote(
   minutes: &i32,
   user_sum: &i64,
   privacy: bool,
+  redact_minutes: bool,
 ) -> Result<String> {
-  let random_quote = DatabaseHandler::get_random_quote(transaction, guild_id).await?;
+  let settings = ctx.data().settings.get(&ctx.data().db, *guild_id).await?;
 
-  if let Some(random_quote) = random_quote {
-    let quote = minimize_markdown(&random_quote.quote);
+  let random_quote = if settings.quotes_on_add
+    && ctx
+      .data()
+      .features
+      .enabled(Some(*guild_id), Feature::Quotes)
+  {
+    DatabaseHandler::get_random_quote(transaction, guild_id).await?
+  } else {
+    None
+  };
+
+  let quote = match random_quote {
+    Some(random_quote) => format!("\n{}", format_quote(&random_quote.quote)),
+    None => String::new(),
+  };
 
+  if redact_minutes {
     if privacy {
       Ok(format!(
-        "Someone just added **{minutes} minutes** to their meditation time! :tada:\n*{quote}*"
-      ))
-    } else if ctx.command().name == "add" {
-      Ok(format!(
-        "Added **{minutes} minutes** to your meditation time! Your total meditation time is now {user_sum} minutes :tada:\n*{quote}*"
+        "Someone just logged a meditation session! :tada:{quote}"
       ))
     } else {
       Ok(format!(
-        "<@{user_id}> added **{minutes} minutes** to their meditation time! Their total meditation time is now {user_sum} minutes :tada:\n*{quote}*"
+        "<@{user_id}> just logged a meditation session! :tada:{quote}"
       ))
     }
   } else if privacy {
     Ok(format!(
-      "Someone just added **{minutes} minutes** to their meditation time! :tada:"
+      "Someone just added **{minutes} minutes** to their meditation time! :tada:{quote}"
     ))
-  } else if ctx.command().name == "add" {
+  } else if matches!(ctx.command().name.as_str(), "add" | "addmulti") {
     Ok(format!(
-      "Added **{minutes} minutes** to your meditation time! Your total meditation time is now {user_sum} minutes :tada:"
+      "Added **{minutes} minutes** to your meditation time! Your total meditation time is now {user_sum} minutes :tada:{quote}"
     ))
   } else {
     Ok(format!(
-      "<@{user_id}> added **{minutes} minutes** to their meditation time! Their total meditation time is now {user_sum} minutes :tada:"
+      "<@{user_id}> added **{minutes} minutes** to their meditation time! Their total meditation time is now {user_sum} minutes :tada:{quote}"
     ))
   }
 }
 
 /// Gets a user's [`TimeSumRoles`] and checks to see whether a new role should be added.
-/// If so, all previous [`TimeSumRoles`] are first removed, and if this fails, the user is
-/// notified and the operation is aborted. Since the new role has not been added, the
-/// removal will be attempted again on next add.
+/// If so, all previous [`TimeSumRoles`] are removed and the new role is added in a single
+/// update through the role queue. If this fails, the user is notified and the operation is
+/// aborted, and the update will be attempted again on next add.
 ///
-/// Once previous roles are removed, the new role is added and notification is sent as a
-/// reply to the slash command ([`add`][add]), or in the case of [`import`][import], directly
-/// to the [`CHANNELS.tracking`][tracking] channel or the originating DM. Notifications
-/// honor privacy settings using ephemeral messages, based on the `privacy` argument.
+/// Once the roles are updated, a notification is sent as a reply to the slash command
+/// ([`add`][add]), or in the case of [`import`][import], directly to the
+/// [`CHANNELS.tracking`][tracking] channel or the originating DM. Notifications honor
+/// privacy settings using ephemeral messages, based on the `privacy` argument, and address the
+/// member by their preferred name and pronouns from their [`Profile`].
 ///
 /// [add]: crate::commands::add::add()
 /// [import]: crate::commands::import::import()
 /// [tracking]: crate::config::CHANNELS
 pub async fn update_time_roles(
-  ctx: &Context<'_>,
+  discord: &impl Discord,
   member: &Member,
+  profile: &Profile,
   sum: i64,
   privacy: bool,
 ) -> Result<()> {
+  let emoji = discord.emoji();
+  let name = profile.announce_as(&member.mention().to_string());
   let current_time_roles = TimeSumRoles::get_users_current_roles(&member.roles);
   let updated_time_role = TimeSumRoles::from_sum(sum);
 
   if let Some(updated_time_role) = updated_time_role {
     if !current_time_roles.contains(&updated_time_role.to_role_id()) {
-      for role in current_time_roles {
-        match member.remove_role(ctx, role).await {
-          Ok(()) => {}
-          Err(err) => {
-            error!("Error removing role: {err}");
-            ctx
-              .send(
-                CreateReply::default()
-                  .content(format!(
-                    "{} An error occured while updating your time roles. Your entry has been saved, but your roles have not been updated. Please contact a moderator.",
-                    EMOJI.mminfo
-                  ))
-                  .allowed_mentions(CreateAllowedMentions::new())
-                  .ephemeral(true),
-              )
-              .await?;
-
-            return Ok(());
-          }
-        }
-      }
+      let update = current_time_roles
+        .into_iter()
+        .fold(RoleUpdate::new(member), RoleUpdate::remove)
+        .add(updated_time_role.to_role_id());
 
-      match member.add_role(ctx, updated_time_role.to_role_id()).await {
-        Ok(()) => {}
-        Err(err) => {
-          error!("Error adding role: {err}");
-          ctx
-            .send(
-              CreateReply::default()
-                .content(format!(
-                  "{} An error occured while updating your time roles. Your entry has been saved, but your roles have not been updated. Please contact a moderator.",
-                  EMOJI.mminfo
-                ))
-                .allowed_mentions(CreateAllowedMentions::new())
-                .ephemeral(true),
-            )
-            .await?;
+      if let Err(err) = discord.update_roles(update).await {
+        error!("Error updating time roles: {err}");
+        discord
+          .respond(
+            format!(
+              "{} An error occured while updating your time roles. Your entry has been saved, but your roles have not been updated. Please contact a moderator.",
+              emoji.mminfo
+            ),
+            true,
+          )
+          .await?;
 
-          return Ok(());
-        }
+        return Ok(());
       }
 
-      if ctx.command().name == "add" {
-        ctx
-          .send(
-            CreateReply::default()
-              .content(format!(
-                ":tada: Congrats to {}, your hard work is paying off! Your total meditation minutes have given you the <@&{}> role!",
-                member.mention(),
-                updated_time_role.to_role_id()
-              ))
-              .allowed_mentions(CreateAllowedMentions::new())
-              .ephemeral(privacy),
+      if matches!(discord.command_name(), "add" | "addmulti") {
+        discord
+          .respond(
+            format!(
+              ":tada: Congrats to {}, your hard work is paying off! Your total meditation minutes have given you the <@&{}> role!",
+              name,
+              updated_time_role.to_role_id()
+            ),
+            privacy,
           )
           .await?;
       } else {
-        let congrats = if ctx.guild_id().is_none() && privacy {
+        let congrats = if discord.in_dm() && privacy {
           format!(
             ":tada: Congrats {}, your hard work is paying off! Your total meditation minutes have given you the @{} role!",
-            member.mention(),
+            name,
             updated_time_role.to_role_icon()
           )
         } else {
           format!(
             ":tada: Congrats to {}, your hard work is paying off! Your total meditation minutes have given you the <@&{}> role!",
-            member.mention(),
+            name,
             updated_time_role.to_role_id()
           )
         };
 
         if privacy {
-          ctx
-            .send(
-              CreateReply::default()
-                .content(congrats)
-                .allowed_mentions(CreateAllowedMentions::new())
-                .ephemeral(privacy),
-            )
-            .await?;
+          discord.respond(congrats, privacy).await?;
         } else {
-          ChannelId::new(CHANNELS.tracking)
-            .send_message(
-              &ctx,
-              CreateMessage::new()
-                .content(congrats)
-                .allowed_mentions(CreateAllowedMentions::new()),
-            )
+          discord
+            .post(ChannelId::new(CHANNELS.tracking), congrats)
             .await?;
         }
       }
@@ -258,122 +360,94 @@ pub async fn update_time_roles(
 }
 
 /// Gets a user's [`StreakRoles`] and checks to see whether a new role should be added.
-/// If so, all previous [`StreakRoles`] are first removed, and if this fails, the user is
-/// notified and the operation is aborted. Since the new role has not been added, the
-/// removal will be attempted again on next add.
+/// If so, all previous [`StreakRoles`] are removed and the new role is added in a single
+/// update through the role queue. If this fails, the user is notified and the operation is
+/// aborted, and the update will be attempted again on next add.
 ///
-/// Once previous roles are removed, the new role is added and notification is sent as a
-/// reply to the slash command ([`add`][add]), or in the case of [`import`][import], directly
-/// to the [`CHANNELS.tracking`][tracking] channel or the originating DM. Notifications
-/// honor privacy settings using ephemeral messages, based on the `privacy` argument.
+/// Once the roles are updated, a notification is sent as a reply to the slash command
+/// ([`add`][add]), or in the case of [`import`][import], directly to the
+/// [`CHANNELS.tracking`][tracking] channel or the originating DM. Notifications honor
+/// privacy settings using ephemeral messages, based on the `privacy` argument, and address the
+/// member by their preferred name and pronouns from their [`Profile`].
+/// No notification is sent if streak announcements have been turned off in the server.
 ///
 /// [add]: crate::commands::add::add()
 /// [import]: crate::commands::import::import()
 /// [tracking]: crate::config::CHANNELS
 pub async fn update_streak_roles(
-  ctx: &Context<'_>,
+  discord: &impl Discord,
   member: &Member,
+  profile: &Profile,
   streak: i32,
   privacy: bool,
 ) -> Result<()> {
+  let emoji = discord.emoji();
+  let name = profile.announce_as(&member.mention().to_string());
   let current_streak_roles = StreakRoles::get_users_current_roles(&member.roles);
   #[allow(clippy::cast_sign_loss)]
   let updated_streak_role = StreakRoles::from_streak(streak as u64);
 
   if let Some(updated_streak_role) = updated_streak_role {
     if !current_streak_roles.contains(&updated_streak_role.to_role_id()) {
-      for role in current_streak_roles {
-        match member.remove_role(ctx, role).await {
-          Ok(()) => {}
-          Err(err) => {
-            error!("Error removing role: {err}");
-
-            ctx
-              .send(
-                CreateReply::default()
-                  .content(format!(
-                    "{} An error occured while updating your streak roles. Your entry has been saved, but your roles have not been updated. Please contact a moderator.",
-                    EMOJI.mminfo
-                  ))
-                  .allowed_mentions(CreateAllowedMentions::new())
-                  .ephemeral(true),
-              )
-              .await?;
-
-            return Ok(());
-          }
-        }
-      }
+      let update = current_streak_roles
+        .into_iter()
+        .fold(RoleUpdate::new(member), RoleUpdate::remove)
+        .add(updated_streak_role.to_role_id());
 
-      match member.add_role(ctx, updated_streak_role.to_role_id()).await {
-        Ok(()) => {}
-        Err(err) => {
-          error!("Error adding role: {err}");
-
-          ctx
-            .send(
-              CreateReply::default()
-                .content(format!(
-                  "{} An error occured while updating your streak roles. Your entry has been saved, but your roles have not been updated. Please contact a moderator.",
-                  EMOJI.mminfo
-                ))
-                .allowed_mentions(CreateAllowedMentions::new())
-                .ephemeral(true),
-            )
-            .await?;
+      if let Err(err) = discord.update_roles(update).await {
+        error!("Error updating streak roles: {err}");
 
-          return Ok(());
-        }
+        discord
+          .respond(
+            format!(
+              "{} An error occured while updating your streak roles. Your entry has been saved, but your roles have not been updated. Please contact a moderator.",
+              emoji.mminfo
+            ),
+            true,
+          )
+          .await?;
+
+        return Ok(());
+      }
+
+      if !discord.feature_enabled(Feature::StreakAnnouncements) {
+        return Ok(());
       }
 
-      if ctx.command().name == "add" {
-        ctx
-          .send(
-            CreateReply::default()
-              .content(format!(
-                ":tada: Congrats to {}, your hard work is paying off! Your current streak is {}, giving you the <@&{}> role!",
-                member.mention(),
-                streak,
-                updated_streak_role.to_role_id()
-              ))
-              .allowed_mentions(CreateAllowedMentions::new())
-              .ephemeral(privacy),
+      if matches!(discord.command_name(), "add" | "addmulti") {
+        discord
+          .respond(
+            format!(
+              ":tada: Congrats to {}, your hard work is paying off! Your current streak is {}, giving you the <@&{}> role!",
+              name,
+              streak,
+              updated_streak_role.to_role_id()
+            ),
+            privacy,
           )
           .await?;
       } else {
-        let congrats = if ctx.guild_id().is_none() && privacy {
+        let congrats = if discord.in_dm() && privacy {
           format!(
             ":tada: Congrats to {}, your hard work is paying off! Your current streak is {}, giving you the @{} role!",
-            member.mention(),
+            name,
             streak,
             updated_streak_role.to_role_icon()
           )
         } else {
           format!(
             ":tada: Congrats to {}, your hard work is paying off! Your current streak is {}, giving you the <@&{}> role!",
-            member.mention(),
+            name,
             streak,
             updated_streak_role.to_role_id()
           )
         };
 
         if privacy {
-          ctx
-            .send(
-              CreateReply::default()
-                .content(congrats)
-                .allowed_mentions(CreateAllowedMentions::new())
-                .ephemeral(privacy),
-            )
-            .await?;
+          discord.respond(congrats, privacy).await?;
         } else {
-          ChannelId::new(CHANNELS.tracking)
-            .send_message(
-              &ctx,
-              CreateMessage::new()
-                .content(congrats)
-                .allowed_mentions(CreateAllowedMentions::new()),
-            )
+          discord
+            .post(ChannelId::new(CHANNELS.tracking), congrats)
             .await?;
         }
       }
@@ -385,7 +459,30 @@ pub async fn update_streak_roles(
 
 #[cfg(test)]
 mod tests {
+  use chrono::Utc;
+  use poise::serenity_prelude::RoleId;
+  use sqlx::PgPool;
+
   use super::*;
+  use crate::commands::helpers::discord::mock::{self, MockDiscord};
+  use crate::data::meditation::Meditation;
+
+  #[test]
+  fn test_crossed_milestone() {
+    assert_eq!(crossed_milestone(59_950, 60_010, 60_000), Some(60_000));
+    assert_eq!(crossed_milestone(60_000, 60_010, 60_000), None);
+    assert_eq!(crossed_milestone(119_000, 240_500, 60_000), Some(240_000));
+    assert_eq!(crossed_milestone(10, 20, 60_000), None);
+    assert_eq!(crossed_milestone(59_950, 60_010, 0), None);
+  }
+
+  #[test]
+  fn test_format_milestone() {
+    assert_eq!(format_milestone(60_000), "1,000 hours");
+    assert_eq!(format_milestone(100_000), "100,000 minutes");
+    assert_eq!(format_milestone(60_000_000), "1,000,000 hours");
+    assert_eq!(format_milestone(600), "10 hours");
+  }
 
   #[test]
   fn test_minimize_markdown() {
@@ -410,4 +507,126 @@ mod tests {
       "A quote with single quotes \\(\\'\\'\\) and double quotes \\(\\\"\\\"\\) inside\\."
     );
   }
+
+  #[test]
+  fn test_format_quote() {
+    assert_eq!(
+      format_quote("A quote with **bold** and *italics* inside."),
+      "> A quote with **bold** and *italics* inside."
+    );
+    assert_eq!(
+      format_quote("A quote\nacross two lines."),
+      "> A quote\n> across two lines."
+    );
+
+    let long_quote = format!("{} *italics*", "word ".repeat(60));
+    let formatted = format_quote(&long_quote);
+    assert!(formatted.starts_with("> word word"));
+    assert!(formatted.ends_with("word…"));
+    assert!(formatted.chars().count() <= MAX_QUOTE_LENGTH + 2);
+    assert!(!formatted.contains('*'));
+  }
+
+  #[sqlx::test(
+    migrations = "core/migrations",
+    fixtures(path = "../../../core/src/fixtures", scripts("meditation"))
+  )]
+  async fn test_add_time_roles(pool: PgPool) -> Result<()> {
+    let db = DatabaseHandler::from_pool(pool);
+    let guild_id = GuildId::new(123u64);
+    let user_id = UserId::new(123u64);
+
+    // Adding 30 minutes to the 25 already tracked crosses the first time role
+    let mut transaction = db.start_transaction().await?;
+    let entry = Meditation::new(guild_id, user_id, 30, 0, &Utc::now());
+    DatabaseHandler::add_meditation_entry(&mut transaction, &entry).await?;
+    let sum =
+      DatabaseHandler::get_user_meditation_sum(&mut transaction, &guild_id, &user_id).await?;
+    DatabaseHandler::commit_transaction(transaction).await?;
+
+    let first_role = TimeSumRoles::One.to_role_id();
+    let member = mock::member(guild_id, user_id, &[RoleId::new(1u64)])?;
+    let discord = MockDiscord::new("add");
+    update_time_roles(&discord, &member, &Profile::default(), sum, false).await?;
+    assert_eq!(
+      discord.role_updates(),
+      vec![RoleUpdate::new(&member).add(first_role)]
+    );
+    let replies = discord.replies();
+    assert_eq!(replies.len(), 1);
+    assert!(replies[0].0.contains(&format!("<@&{first_role}>")));
+    assert!(!replies[0].1);
+
+    // Members who already have the role are left alone
+    let member = mock::member(guild_id, user_id, &[first_role])?;
+    let discord = MockDiscord::new("add");
+    update_time_roles(&discord, &member, &Profile::default(), sum, true).await?;
+    assert!(discord.role_updates().is_empty());
+    assert!(discord.replies().is_empty());
+
+    // The previous time role is replaced, and failures are reported privately
+    let discord = MockDiscord {
+      fail_role_updates: true,
+      ..MockDiscord::new("add")
+    };
+    update_time_roles(&discord, &member, &Profile::default(), sum + 50, false).await?;
+    let replies = discord.replies();
+    assert_eq!(replies.len(), 1);
+    assert!(replies[0].0.contains("roles have not been updated"));
+    assert!(replies[0].1);
+
+    let discord = MockDiscord::new("add");
+    update_time_roles(&discord, &member, &Profile::default(), sum + 50, false).await?;
+    assert_eq!(
+      discord.role_updates(),
+      vec![RoleUpdate::new(&member)
+        .remove(first_role)
+        .add(TimeSumRoles::Two.to_role_id())]
+    );
+
+    Ok(())
+  }
+
+  #[tokio::test]
+  async fn test_import_streak_roles() -> Result<()> {
+    let member = mock::member(GuildId::new(123u64), UserId::new(123u64), &[])?;
+    let egg = StreakRoles::Egg.to_role_id();
+
+    // Public imports are announced in the tracking channel, using the member's preferred name
+    let profile = Profile {
+      display_name: Some("Sam".to_owned()),
+      pronouns: Some("they/them".to_owned()),
+      ..Default::default()
+    };
+    let discord = MockDiscord::new("import");
+    update_streak_roles(&discord, &member, &profile, 7, false).await?;
+    assert_eq!(
+      discord.role_updates(),
+      vec![RoleUpdate::new(&member).add(egg)]
+    );
+    let posts = discord.posts();
+    assert_eq!(posts.len(), 1);
+    assert_eq!(posts[0].0, ChannelId::new(CHANNELS.tracking));
+    assert!(posts[0]
+      .1
+      .starts_with(":tada: Congrats to Sam (they/them),"));
+    assert!(discord.replies().is_empty());
+
+    // Roles are still given when streak announcements are off
+    let discord = MockDiscord {
+      disabled_features: vec![Feature::StreakAnnouncements],
+      ..MockDiscord::new("import")
+    };
+    update_streak_roles(&discord, &member, &Profile::default(), 7, false).await?;
+    assert_eq!(discord.role_updates().len(), 1);
+    assert!(discord.posts().is_empty());
+    assert!(discord.replies().is_empty());
+
+    // Short streaks don't earn a role
+    let discord = MockDiscord::new("import");
+    update_streak_roles(&discord, &member, &Profile::default(), 6, false).await?;
+    assert!(discord.role_updates().is_empty());
+
+    Ok(())
+  }
 }
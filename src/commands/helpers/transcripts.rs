@@ -0,0 +1,277 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use poise::serenity_prelude::Message;
+use poise::serenity_prelude::{builder::*, AutoArchiveDuration, ButtonStyle, ChannelType};
+use poise::serenity_prelude::{ComponentInteraction, Context as SerenityContext, Mentionable};
+
+use crate::config::BloomBotEmbed;
+use crate::emoji::EmojiHandler;
+use crate::transcription::{Recording, TranscriptLine, TranscriptionHandler};
+
+const CONSENT_PREFIX: &str = "transcript:";
+/// Messages are kept under Discord's limit of 2000 characters.
+const MAX_MESSAGE_LENGTH: usize = 1900;
+/// The longest thread name Discord allows.
+const MAX_THREAD_NAME: usize = 100;
+
+/// Creates the prompt asking members in the voice channel whether they agree to be
+/// recorded. The custom IDs include the recording ID, so they can be handled from the global
+/// event handler.
+pub fn consent_prompt(recording: &Recording) -> CreateMessage {
+  CreateMessage::new()
+    .embed(
+      BloomBotEmbed::new()
+        .title(":microphone2: This event is being transcribed")
+        .description(format!(
+          "Staff are recording **{}** to post a transcript in {} afterwards.\n\nOnly members who agree are recorded. If you don't, your voice won't be kept or transcribed. You can change your mind at any time while the recording is running, and if you opt out, anything you've said is left out of the transcript.",
+          recording.event_title,
+          recording.transcript_channel.mention()
+        )),
+    )
+    .components(vec![CreateActionRow::Buttons(vec![
+      CreateButton::new(format!("{CONSENT_PREFIX}consent:{}", recording.id))
+        .label("Include me")
+        .style(ButtonStyle::Success),
+      CreateButton::new(format!("{CONSENT_PREFIX}decline:{}", recording.id))
+        .label("Don't record me")
+        .style(ButtonStyle::Secondary),
+    ])])
+}
+
+/// Parses the custom ID of a consent button, returning whether the member consented and the
+/// recording ID. Returns [`None`] if the custom ID does not belong to a consent prompt.
+pub fn parse_custom_id(custom_id: &str) -> Option<(bool, &str)> {
+  let (choice, recording_id) = custom_id.strip_prefix(CONSENT_PREFIX)?.split_once(':')?;
+  match choice {
+    "consent" => Some((true, recording_id)),
+    "decline" => Some((false, recording_id)),
+    _ => None,
+  }
+}
+
+/// Handles the consent buttons on a recording's prompt. Each member's choice only applies
+/// to the recording the prompt belongs to.
+pub async fn handle_consent(
+  ctx: &SerenityContext,
+  emoji: &EmojiHandler,
+  transcription: &TranscriptionHandler,
+  press: &ComponentInteraction,
+  consent: bool,
+  recording_id: &str,
+) -> Result<()> {
+  let emoji = emoji.get(press.guild_id);
+
+  let content = match transcription.recording_by_id(recording_id) {
+    Some(recording) => {
+      recording.set_consent(press.user.id, consent);
+      if consent {
+        format!(
+          "{} Thanks! You'll be included in the transcript. You can opt out at any time before the recording ends.",
+          emoji.mmcheck
+        )
+      } else {
+        format!(
+          "{} You won't be recorded, and anything you've said will be left out of the transcript.",
+          emoji.mmcheck
+        )
+      }
+    }
+    None => format!("{} This recording has already ended.", emoji.mminfo),
+  };
+
+  press
+    .create_response(
+      ctx,
+      CreateInteractionResponse::Message(
+        CreateInteractionResponseMessage::new()
+          .content(content)
+          .ephemeral(true),
+      ),
+    )
+    .await?;
+
+  Ok(())
+}
+
+/// Formats the time since a recording started, e.g., `04:05` or `1:02:03`.
+fn format_offset(offset: Duration) -> String {
+  let seconds = offset.as_secs();
+  if seconds >= 3600 {
+    format!(
+      "{}:{:02}:{:02}",
+      seconds / 3600,
+      seconds / 60 % 60,
+      seconds % 60
+    )
+  } else {
+    format!("{:02}:{:02}", seconds / 60, seconds % 60)
+  }
+}
+
+/// Formats transcript lines as messages, each under Discord's length limit. Lines too long
+/// for a single message are split between words.
+fn transcript_messages(lines: &[TranscriptLine]) -> Vec<String> {
+  let mut messages = Vec::new();
+  let mut current = String::new();
+
+  for line in lines {
+    let mut text = format!(
+      "`{}` {}: {}",
+      format_offset(line.offset),
+      line.user_id.mention(),
+      line.text
+    );
+
+    while !text.is_empty() {
+      // Leaves room for the line break before the line
+      if text.len() < MAX_MESSAGE_LENGTH.saturating_sub(current.len()) {
+        if !current.is_empty() {
+          current.push('\n');
+        }
+        current.push_str(&text);
+        break;
+      }
+
+      if current.is_empty() {
+        // Split the line itself, at the last space which fits
+        let mut end = MAX_MESSAGE_LENGTH;
+        while !text.is_char_boundary(end) {
+          end -= 1;
+        }
+        let end = text[..end].rfind(' ').filter(|end| *end > 0).unwrap_or(end);
+        messages.push(text[..end].trim_end().to_owned());
+        text = text[end..].trim_start().to_owned();
+      } else {
+        messages.push(std::mem::take(&mut current));
+      }
+    }
+  }
+
+  if !current.is_empty() {
+    messages.push(current);
+  }
+
+  messages
+}
+
+/// Posts a finished transcript in the recording's transcript channel, as a thread under a
+/// message introducing it. Returns the introduction.
+pub async fn post_transcript(
+  ctx: &SerenityContext,
+  recording: &Recording,
+  lines: &[TranscriptLine],
+) -> Result<Message> {
+  let speakers = {
+    let mut speakers: Vec<_> = lines.iter().map(|line| line.user_id).collect();
+    speakers.sort_unstable();
+    speakers.dedup();
+    speakers.len()
+  };
+  let intro = recording
+    .transcript_channel
+    .send_message(
+      ctx,
+      CreateMessage::new()
+        .embed(
+          BloomBotEmbed::new()
+            .title(format!(":scroll: Transcript: {}", recording.event_title))
+            .description(format!(
+              "Recorded in {} on {}. Only members who agreed to be recorded are included.",
+              recording.voice_channel.mention(),
+              recording.started_at.format("%B %-d, %Y")
+            ))
+            .field("Speakers", speakers.to_string(), true)
+            .field("Lines", lines.len().to_string(), true),
+        )
+        .allowed_mentions(CreateAllowedMentions::new()),
+    )
+    .await?;
+
+  let thread_name = format!(
+    "{} transcript, {}",
+    recording.event_title,
+    recording.started_at.format("%B %-d")
+  )
+  .chars()
+  .take(MAX_THREAD_NAME)
+  .collect::<String>();
+  let thread = recording
+    .transcript_channel
+    .create_thread_from_message(
+      ctx,
+      intro.id,
+      CreateThread::new(thread_name)
+        .kind(ChannelType::PublicThread)
+        .auto_archive_duration(AutoArchiveDuration::OneWeek),
+    )
+    .await?;
+
+  for message in transcript_messages(lines) {
+    thread
+      .send_message(
+        ctx,
+        CreateMessage::new()
+          .content(message)
+          .allowed_mentions(CreateAllowedMentions::new()),
+      )
+      .await?;
+  }
+
+  Ok(intro)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use poise::serenity_prelude::UserId;
+
+  #[test]
+  fn test_parse_custom_id() {
+    assert_eq!(
+      parse_custom_id("transcript:consent:01JBPTWBXJNAKK288S3D89JKCA"),
+      Some((true, "01JBPTWBXJNAKK288S3D89JKCA"))
+    );
+    assert_eq!(
+      parse_custom_id("transcript:decline:01JBPTWBXJNAKK288S3D89JKCA"),
+      Some((false, "01JBPTWBXJNAKK288S3D89JKCA"))
+    );
+    assert_eq!(
+      parse_custom_id("transcript:maybe:01JBPTWBXJNAKK288S3D89JKCA"),
+      None
+    );
+    assert_eq!(
+      parse_custom_id("retreat:approve:01JBPTWBXJNAKK288S3D89JKCA"),
+      None
+    );
+  }
+
+  #[test]
+  fn test_format_offset() {
+    assert_eq!(format_offset(Duration::from_secs(245)), "04:05");
+    assert_eq!(format_offset(Duration::from_secs(3723)), "1:02:03");
+  }
+
+  #[test]
+  fn test_transcript_messages() {
+    let line = |seconds, text: &str| TranscriptLine {
+      user_id: UserId::new(123u64),
+      offset: Duration::from_secs(seconds),
+      text: text.to_owned(),
+    };
+
+    assert_eq!(
+      transcript_messages(&[line(5, "Welcome, everyone."), line(9, "Thank you.")]),
+      vec!["`00:05` <@123>: Welcome, everyone.\n`00:09` <@123>: Thank you."]
+    );
+
+    let long = "word ".repeat(500);
+    let messages = transcript_messages(&[line(0, "Hello."), line(10, long.trim_end())]);
+    assert_eq!(messages.len(), 3);
+    assert_eq!(messages[0], "`00:00` <@123>: Hello.");
+    assert!(messages
+      .iter()
+      .all(|message| message.len() <= MAX_MESSAGE_LENGTH));
+    assert!(messages[2].ends_with("word"));
+  }
+}
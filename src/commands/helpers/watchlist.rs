@@ -0,0 +1,264 @@
+use anyhow::Result;
+use chrono::Utc;
+use log::warn;
+use poise::serenity_prelude::{builder::*, ButtonStyle, ChannelId, ComponentInteraction};
+use poise::serenity_prelude::{Context as SerenityContext, Mentionable};
+
+use crate::config::{BloomBotEmbed, CHANNELS, ROLES};
+use crate::data::erase::Erase;
+use crate::data::report::{Report, ReportStatus};
+use crate::database::DatabaseHandler;
+use crate::emoji::EmojiHandler;
+
+const ACTION_PREFIX: &str = "watchlist:";
+
+/// What a moderator can do about a message flagged by the watchlist.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlagAction {
+  Ignore,
+  Erase,
+  Report,
+}
+
+impl FlagAction {
+  fn as_str(self) -> &'static str {
+    match self {
+      Self::Ignore => "ignore",
+      Self::Erase => "erase",
+      Self::Report => "report",
+    }
+  }
+
+  fn status(self) -> ReportStatus {
+    match self {
+      Self::Ignore => ReportStatus::Ignored,
+      Self::Erase => ReportStatus::Erased,
+      Self::Report => ReportStatus::Reported,
+    }
+  }
+}
+
+/// Creates the quick-action buttons for a flagged message. The custom IDs include the
+/// report ID, so they can be handled from the global event handler.
+pub fn buttons(report_id: &str) -> Vec<CreateActionRow> {
+  let button = |action: FlagAction, label: &str, style: ButtonStyle| {
+    CreateButton::new(format!("{ACTION_PREFIX}{}:{report_id}", action.as_str()))
+      .label(label)
+      .style(style)
+  };
+
+  vec![CreateActionRow::Buttons(vec![
+    button(FlagAction::Ignore, "Ignore", ButtonStyle::Secondary),
+    button(FlagAction::Erase, "Erase", ButtonStyle::Danger),
+    button(FlagAction::Report, "Report", ButtonStyle::Primary),
+  ])]
+}
+
+/// Parses the custom ID of a watchlist button, returning the action and the report ID.
+/// Returns [`None`] if the custom ID does not belong to the watchlist.
+pub fn parse_custom_id(custom_id: &str) -> Option<(FlagAction, &str)> {
+  let (action, report_id) = custom_id.strip_prefix(ACTION_PREFIX)?.split_once(':')?;
+  let action = match action {
+    "ignore" => FlagAction::Ignore,
+    "erase" => FlagAction::Erase,
+    "report" => FlagAction::Report,
+    _ => return None,
+  };
+
+  Some((action, report_id))
+}
+
+async fn respond_ephemeral(
+  ctx: &SerenityContext,
+  press: &ComponentInteraction,
+  content: String,
+) -> Result<()> {
+  press
+    .create_response(
+      ctx,
+      CreateInteractionResponse::Message(
+        CreateInteractionResponseMessage::new()
+          .content(content)
+          .ephemeral(true),
+      ),
+    )
+    .await?;
+
+  Ok(())
+}
+
+/// Escalates a flagged message to the moderation team, the same way as a report from the
+/// "Report Message" context menu.
+async fn escalate(ctx: &SerenityContext, report: &Report, moderator: &str) -> Result<()> {
+  let link = report
+    .message_id
+    .link(report.channel_id, Some(report.guild_id));
+
+  ChannelId::new(CHANNELS.reportchannel)
+    .send_message(
+      ctx,
+      CreateMessage::new()
+        .content(format!("<@&{}> Message Reported", ROLES.staff))
+        .embed(
+          BloomBotEmbed::new()
+            .description(&report.content)
+            .field("Link", format!("[Go to message]({link})"), false)
+            .footer(CreateEmbedFooter::new(format!(
+              "Author ID: {}\nEscalated from the watchlist by {moderator}",
+              report.user_id
+            ))),
+        ),
+    )
+    .await?;
+
+  Ok(())
+}
+
+/// Handles a quick-action button on a flagged message. Each flag can only be handled once,
+/// after which the buttons are replaced with a note of what was done and by whom.
+///
+/// Erasing a flagged message records an erase, but doesn't notify the member. Use `/erase`
+/// instead when they should be told why.
+pub async fn handle_action(
+  ctx: &SerenityContext,
+  db: &DatabaseHandler,
+  emoji: &EmojiHandler,
+  press: &ComponentInteraction,
+  action: FlagAction,
+  report_id: &str,
+) -> Result<()> {
+  let emoji = emoji.get(press.guild_id);
+  let is_moderator = press
+    .member
+    .as_ref()
+    .and_then(|member| member.permissions)
+    .is_some_and(|permissions| permissions.manage_messages());
+  if !is_moderator {
+    return respond_ephemeral(
+      ctx,
+      press,
+      format!(
+        "{} Only moderators can act on flagged messages.",
+        emoji.mminfo
+      ),
+    )
+    .await;
+  }
+
+  let mut transaction = db.start_transaction_with_retry(5).await?;
+
+  let Some(report) = DatabaseHandler::get_report(&mut transaction, report_id).await? else {
+    return respond_ephemeral(
+      ctx,
+      press,
+      format!("{} This flag could not be found.", emoji.mminfo),
+    )
+    .await;
+  };
+
+  if DatabaseHandler::set_report_status(
+    &mut transaction,
+    &report.id,
+    action.status(),
+    &press.user.id,
+  )
+  .await?
+    == 0
+  {
+    return respond_ephemeral(
+      ctx,
+      press,
+      format!("{} This flag has already been handled.", emoji.mminfo),
+    )
+    .await;
+  }
+
+  let outcome = match action {
+    FlagAction::Ignore => "Ignored",
+    FlagAction::Erase => {
+      let reason = match &report.matched_term {
+        Some(term) => format!("Watchlist match: {term}"),
+        None => "Watchlist match".to_owned(),
+      };
+
+      // The message may have already been deleted by its author or another moderator
+      if let Err(e) = ctx
+        .http
+        .delete_message(report.channel_id, report.message_id, Some(&reason))
+        .await
+      {
+        warn!("Failed to erase flagged message {}: {e}", report.message_id);
+      }
+
+      let erase = Erase::new(
+        report.guild_id,
+        report.user_id,
+        press.message.link(),
+        reason,
+        &Utc::now(),
+      );
+      DatabaseHandler::add_erase(&mut transaction, &erase).await?;
+
+      "Erased"
+    }
+    FlagAction::Report => {
+      escalate(ctx, &report, &press.user.name).await?;
+      "Reported"
+    }
+  };
+
+  DatabaseHandler::commit_transaction(transaction).await?;
+
+  let mut embeds: Vec<CreateEmbed> = press
+    .message
+    .embeds
+    .iter()
+    .cloned()
+    .map(CreateEmbed::from)
+    .collect();
+  if let Some(embed) = embeds.pop() {
+    embeds.push(embed.field(
+      "Handled",
+      format!("{outcome} by {}", press.user.mention()),
+      false,
+    ));
+  }
+
+  press
+    .create_response(
+      ctx,
+      CreateInteractionResponse::UpdateMessage(
+        CreateInteractionResponseMessage::new()
+          .embeds(embeds)
+          .components(Vec::new()),
+      ),
+    )
+    .await?;
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_parse_custom_id() {
+    assert_eq!(
+      parse_custom_id("watchlist:erase:01JBPTWBXJNAKK288S3D89JKCA"),
+      Some((FlagAction::Erase, "01JBPTWBXJNAKK288S3D89JKCA"))
+    );
+    assert_eq!(
+      parse_custom_id("watchlist:ignore:01JBPTWBXJNAKK288S3D89JKCA"),
+      Some((FlagAction::Ignore, "01JBPTWBXJNAKK288S3D89JKCA"))
+    );
+    assert_eq!(
+      parse_custom_id("watchlist:ban:01JBPTWBXJNAKK288S3D89JKCA"),
+      None
+    );
+    assert_eq!(
+      parse_custom_id("suggestion_vote:up:01JBPTWBXJNAKK288S3D89JKCA"),
+      None
+    );
+  }
+}
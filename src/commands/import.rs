@@ -1,10 +1,11 @@
 use std::str::FromStr;
+use std::time::Instant;
 
 use anyhow::{anyhow, Context as AnyhowContext, Result};
 use chrono::{DateTime, Duration, NaiveDateTime, TimeDelta, Utc};
 use csv::{Reader, ReaderBuilder, WriterBuilder};
 use log::info;
-use poise::serenity_prelude::{builder::*, ChannelId, Message, RoleId, User};
+use poise::serenity_prelude::{builder::*, ButtonStyle, ChannelId, Message, RoleId, User};
 use poise::{ChoiceParameter, CreateReply};
 use serde::{Deserialize, Serialize};
 use tokio::{fs, fs::File, io::AsyncWriteExt};
@@ -13,11 +14,16 @@ use ulid::Ulid;
 use crate::commands::helpers::common::Visibility;
 use crate::commands::helpers::database::{self, MessageType};
 use crate::commands::helpers::tracking;
-use crate::config::{BloomBotEmbed, CHANNELS, EMOJI, MEDITATION_MIND, ROLES};
-use crate::data::tracking_profile::{privacy, Privacy, Status};
+use crate::config::{BloomBotEmbed, CHANNELS, MEDITATION_MIND, ROLES};
+use crate::data::tracking_profile::{privacy, Status};
 use crate::database::DatabaseHandler;
+use crate::import_jobs::{ImportJob, PROGRESS_INTERVAL};
 use crate::Context;
 
+/// Number of entries inserted per query. Progress is reported and cancellation is checked
+/// between batches.
+const IMPORT_BATCH_SIZE: usize = 500;
+
 #[allow(dead_code)]
 #[derive(Debug, Deserialize)]
 struct InsightTimerRecord {
@@ -162,6 +168,32 @@ fn autodetect_source(rdr: &mut Reader<&[u8]>) -> Result<ImportSource> {
   Ok(ImportSource::Unknown)
 }
 
+fn progress_message(job: &ImportJob) -> String {
+  let processed = job.processed();
+  let total = job.total();
+  let percent = if total == 0 {
+    0
+  } else {
+    processed * 100 / total
+  };
+
+  let mut message = format!("Importing entries: {processed} of {total} ({percent}%).");
+  if let Some(eta) = job.eta() {
+    let seconds = eta.as_secs().max(1);
+    if seconds < 60 {
+      message.push_str(&format!(" About {seconds}s remaining."));
+    } else {
+      message.push_str(&format!(
+        " About {}m {}s remaining.",
+        seconds / 60,
+        seconds % 60
+      ));
+    }
+  }
+
+  message
+}
+
 fn process_finch_timer(content: &Vec<u8>) -> Result<Vec<u8>> {
   let mut entries: Vec<FinchTimerSessionRecord> = vec![];
   let records: FinchTimerSession = serde_json::from_slice(content.as_slice())?;
@@ -194,7 +226,7 @@ fn process_finch_breathing(content: &Vec<u8>) -> Result<Vec<u8>> {
 
 /// Import meditation entries from an app
 ///
-/// Imports meditation entries from a CSV or JSON file uploaded by the user.
+/// Imports meditation entries from a CSV or JSON file uploaded by the user. Progress is shown while entries are added, and the import can be cancelled until it finishes, in which case no entries are added.
 ///
 /// Supported sources include Insight Timer, VA Mindfulness Coach, Waking Up, Finch Breathing and Meditation Sessions, and Apple Health (requires pre-processing with Bloom Parser).
 #[poise::command(slash_command, category = "Meditation Tracking")]
@@ -206,13 +238,14 @@ pub async fn import(
   import_type: Option<ImportType>,
   #[description = "The user to import for (staff only)"] user: Option<User>,
 ) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
   ctx.defer_ephemeral().await?;
 
   if message.attachments.is_empty() {
     ctx
       .send(
         CreateReply::default()
-          .content(format!("{} No attachment found.", EMOJI.mminfo))
+          .content(format!("{} No attachment found.", emoji.mminfo))
           .ephemeral(true),
       )
       .await?;
@@ -245,7 +278,7 @@ pub async fn import(
         CreateReply::default()
           .content(format!(
             "{} You cannot import files uploaded by other users.",
-            EMOJI.mminfo
+            emoji.mminfo
           ))
           .ephemeral(true),
       )
@@ -265,7 +298,7 @@ pub async fn import(
         CreateReply::default()
           .content(format!(
             "{} File exceeds size limit. Please contact staff for assistance with importing large files.",
-            EMOJI.mminfo
+            emoji.mminfo
           ))
           .ephemeral(true),
       )
@@ -274,6 +307,22 @@ pub async fn import(
     return Ok(());
   }
 
+  let Some(import_job) = ctx.data().import_jobs.start(guild_id, user_id) else {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} An import is already running for this user. Please wait for it to finish or cancel it before starting another.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+
+    return Ok(());
+  };
+  let job = import_job.job();
+
   let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
 
   let tracking_profile =
@@ -282,6 +331,8 @@ pub async fn import(
       .unwrap_or_default();
 
   let privacy = privacy!(tracking_profile.tracking.privacy);
+  let silent = privacy && tracking_profile.tracking.silent;
+  let redacted = tracking_profile.tracking.redact_minutes;
 
   let import_type = import_type.unwrap_or(ImportType::NewEntries);
 
@@ -313,7 +364,7 @@ pub async fn import(
       ctx
         .send(
           CreateReply::default()
-            .content(format!("{} Unable to download attachment.", EMOJI.mminfo))
+            .content(format!("{} Unable to download attachment.", emoji.mminfo))
             .ephemeral(true),
         )
         .await?;
@@ -570,7 +621,7 @@ pub async fn import(
           CreateReply::default()
             .content(format!(
               "{} **Unrecognized file format.**\n-# Please use an unaltered data export. Supported sources include Insight Timer, VA Mindfulness Coach, Waking Up, Finch Breathing and Meditation Sessions, and Apple Health (requires please contact staff.",
-              EMOJI.mminfo
+              emoji.mminfo
             ))
             .ephemeral(true),
         )
@@ -591,7 +642,7 @@ pub async fn import(
           CreateReply::default()
             .content(format!(
               "{} **Unrecognized file format.**\n-# Please use an unaltered data export. Supported sources include Insight Timer, VA Mindfulness Coach, Waking Up, Finch Breathing and Meditation Sessions, and Apple Health (requires pre-processing with Bloom Parser). If you would like support for another format, please contact staff.",
-              EMOJI.mminfo
+              emoji.mminfo
             ))
             .ephemeral(true),
         )
@@ -614,7 +665,7 @@ pub async fn import(
     ctx
       .send(
         CreateReply::default()
-          .content(format!("{} No qualifying entries found.", EMOJI.mminfo))
+          .content(format!("{} No qualifying entries found.", emoji.mminfo))
           .ephemeral(true),
       )
       .await?;
@@ -622,45 +673,115 @@ pub async fn import(
     return Ok(());
   }
 
-  let mut sql_query =
-    "INSERT INTO meditation (record_id, user_id, meditation_minutes, meditation_seconds, guild_id, occurred_at) VALUES"
-      .to_owned();
-  let mut reversal_query = "DELETE FROM meditation WHERE record_id IN (".to_owned();
-  for (i, record) in user_data.iter().enumerate() {
-    let record_id = Ulid::new().to_string();
-    sql_query.push_str(" ('");
-    reversal_query.push('\'');
-    sql_query.push_str(&record_id);
-    reversal_query.push_str(&record_id);
-    sql_query.push_str("', '");
-    sql_query.push_str(&user_id.to_string());
-    sql_query.push_str("', '");
-    sql_query.push_str(&record.meditation_minutes.to_string());
-    sql_query.push_str("', '");
-    sql_query.push_str(&record.meditation_seconds.to_string());
-    sql_query.push_str("', '");
-    sql_query.push_str(&guild_id.to_string());
-    sql_query.push_str("', '");
-    sql_query.push_str(&record.occurred_at.to_rfc3339());
-    sql_query.push_str("')");
-    sql_query.push_str(if i + 1 < user_data.len() { "," } else { ";" });
-    reversal_query.push_str(if i + 1 < user_data.len() {
-      "', "
-    } else {
-      "');"
-    });
+  job.set_total(user_data.len());
+
+  let cancel_id = format!("{}cancel", ctx.id());
+  let cancel_button = CreateActionRow::Buttons(vec![CreateButton::new(cancel_id.clone())
+    .label("Cancel")
+    .style(ButtonStyle::Danger)]);
+  let progress = ctx
+    .send(
+      CreateReply::default()
+        .content(progress_message(job))
+        .components(vec![cancel_button.clone()])
+        .ephemeral(true),
+    )
+    .await?;
+  let cancel_listener = job.cancel_on_press(
+    ctx.serenity_context().shard.clone(),
+    ctx.serenity_context().http.clone(),
+    cancel_id,
+  );
+
+  let mut record_ids = Vec::with_capacity(user_data.len());
+  let mut result = 0;
+  let mut last_progress = Instant::now();
+  for batch in user_data.chunks(IMPORT_BATCH_SIZE) {
+    if job.is_cancelled() {
+      break;
+    }
+
+    let values: Vec<String> = batch
+      .iter()
+      .map(|record| {
+        let record_id = Ulid::new().to_string();
+        let values = format!(
+          "('{record_id}', '{user_id}', '{}', '{}', '{guild_id}', '{}')",
+          record.meditation_minutes,
+          record.meditation_seconds,
+          record.occurred_at.to_rfc3339()
+        );
+        record_ids.push(format!("'{record_id}'"));
+        values
+      })
+      .collect();
+    let sql_query = format!(
+      "INSERT INTO meditation (record_id, user_id, meditation_minutes, meditation_seconds, guild_id, occurred_at) VALUES {};",
+      values.join(", ")
+    );
+
+    result += DatabaseHandler::add_meditation_entry_batch(&mut transaction, &sql_query).await?;
+    job.advance(batch.len());
+
+    if last_progress.elapsed() >= PROGRESS_INTERVAL {
+      progress
+        .edit(
+          ctx,
+          CreateReply::default()
+            .content(progress_message(job))
+            .components(vec![cancel_button.clone()]),
+        )
+        .await?;
+      last_progress = Instant::now();
+    }
   }
 
+  // Once the listener has stopped, the import can no longer be cancelled
+  cancel_listener.abort();
   drop(user_data);
 
-  let result = DatabaseHandler::add_meditation_entry_batch(&mut transaction, &sql_query).await?;
+  if job.is_cancelled() {
+    DatabaseHandler::rollback_transaction(transaction).await?;
+    progress
+      .edit(
+        ctx,
+        CreateReply::default()
+          .content(format!(
+            "{} Import cancelled. No entries were added.",
+            emoji.mminfo
+          ))
+          .components(Vec::new()),
+      )
+      .await?;
+
+    return Ok(());
+  }
+
+  progress
+    .edit(
+      ctx,
+      CreateReply::default()
+        .content(format!(
+          "Imported {} of {} entries.",
+          job.processed(),
+          job.total()
+        ))
+        .components(Vec::new()),
+    )
+    .await?;
+
+  let reversal_query = format!(
+    "DELETE FROM meditation WHERE record_id IN ({});",
+    record_ids.join(", ")
+  );
+
   if result < 1 {
     ctx
       .send(
         CreateReply::default()
           .content(format!(
             "{} No entries added. Please try again or contact staff for assistance.",
-            EMOJI.mminfo
+            emoji.mminfo
           ))
           .ephemeral(true),
       )
@@ -678,6 +799,7 @@ pub async fn import(
     &total_minutes,
     &user_sum,
     privacy,
+    redacted,
   )
   .await?;
 
@@ -688,15 +810,20 @@ pub async fn import(
     0
   };
 
-  let guild_time_in_hours = tracking::get_guild_hours(&mut transaction, &guild_id).await?;
+  let guild_milestone = tracking::get_guild_milestone(
+    &mut transaction,
+    &guild_id,
+    i64::from(total_minutes + (total_seconds / 60)),
+  )
+  .await?;
 
   let h = (total_minutes + (total_seconds / 60)) / 60;
   let m = (total_minutes + (total_seconds / 60)) % 60;
   let s = total_seconds % 60;
 
-  let success_response = format!(
+  let mut success_response = format!(
     "{} Successfully added a total of {}h {}m {}s from {} {} imported from {}.",
-    EMOJI.mmcheck,
+    emoji.mmcheck,
     h,
     m,
     s,
@@ -705,6 +832,13 @@ pub async fn import(
     import_source,
   );
 
+  // The public post won't include the total, so include it here
+  if silent || redacted {
+    success_response.push_str(&format!(
+      " Your total meditation time is now {user_sum} minutes :tada:"
+    ));
+  }
+
   database::commit_and_say(
     ctx,
     transaction,
@@ -713,21 +847,30 @@ pub async fn import(
   )
   .await?;
 
-  ChannelId::new(CHANNELS.tracking)
-    .send_message(
-      &ctx,
-      CreateMessage::new()
-        .content(response)
-        .allowed_mentions(CreateAllowedMentions::new()),
-    )
-    .await?;
+  if !silent {
+    ChannelId::new(CHANNELS.tracking)
+      .send_message(
+        &ctx,
+        CreateMessage::new()
+          .content(response)
+          .allowed_mentions(CreateAllowedMentions::new()),
+      )
+      .await?;
+  }
 
-  tracking::post_guild_hours(&ctx, &guild_time_in_hours).await?;
+  tracking::post_guild_milestone(&ctx, guild_milestone).await?;
 
   let member = guild_id.member(ctx, user_id).await?;
-  tracking::update_time_roles(&ctx, &member, user_sum, privacy).await?;
+  tracking::update_time_roles(&ctx, &member, &tracking_profile.profile, user_sum, privacy).await?;
   if tracking_profile.streak.status == Status::Enabled {
-    tracking::update_streak_roles(&ctx, &member, user_streak, privacy).await?;
+    tracking::update_streak_roles(
+      &ctx,
+      &member,
+      &tracking_profile.profile,
+      user_streak,
+      privacy,
+    )
+    .await?;
   }
 
   let filename = format!("import_{}_{}.txt", user_id, Ulid::new().to_string());
@@ -0,0 +1,423 @@
+use anyhow::{Context as AnyhowContext, Result};
+use chrono::{Duration as ChronoDuration, NaiveTime, Utc};
+use poise::serenity_prelude::{ChannelType, CreateEmbedFooter, GuildChannel, Mentionable};
+use poise::{ChoiceParameter, CreateReply};
+
+use crate::commands::helpers::common::Visibility;
+use crate::commands::helpers::database::{self, MessageType};
+use crate::commands::helpers::pagination::{PageRowRef, PageType, Paginator};
+use crate::config::{BloomBotEmbed, ENTRIES_PER_PAGE};
+use crate::data::intention::{IntentionSubscription, PracticePrompt, PromptCategory};
+use crate::data::recurring_post::{PostDay, RecurringPost};
+use crate::data::tracking_profile::Status;
+use crate::database::DatabaseHandler;
+use crate::Context;
+
+/// The name of the recurring post which shares the day's intention.
+const DAILY_POST_NAME: &str = "Daily intention";
+
+/// Commands for daily intentions
+///
+/// Commands to get a practice prompt to carry through the day, chosen from prompts curated by staff, and to be sent it by DM each morning.
+#[poise::command(
+  slash_command,
+  category = "Utilities",
+  subcommands("today", "remind", "add", "list", "remove", "schedule", "unschedule"),
+  subcommand_required,
+  guild_only
+)]
+#[allow(clippy::unused_async)]
+pub async fn intention(_: Context<'_>) -> Result<()> {
+  Ok(())
+}
+
+/// Show today's intention
+///
+/// Shows today's practice prompt. The prompt changes each day, in your local time as set with /customize offset, and is the same for everyone. Choose a category for a prompt from that category instead.
+#[poise::command(slash_command)]
+async fn today(
+  ctx: Context<'_>,
+  #[description = "Only choose from this category"] category: Option<PromptCategory>,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+  let utc_offset =
+    DatabaseHandler::get_tracking_profile(&mut transaction, &guild_id, &ctx.author().id)
+      .await?
+      .unwrap_or_default()
+      .utc_offset;
+  let prompts =
+    DatabaseHandler::get_practice_prompts(&mut transaction, &guild_id, category).await?;
+  drop(transaction);
+
+  let today = (Utc::now() + ChronoDuration::minutes(i64::from(utc_offset))).date_naive();
+  let Some(intention) = PracticePrompt::for_day(&prompts, today) else {
+    let content = match category {
+      Some(category) => format!(
+        "{} There aren't any practice prompts in the {} category yet.",
+        emoji.mminfo,
+        category.name()
+      ),
+      None => format!(
+        "{} There aren't any practice prompts yet. Staff can add some with `/intention add`.",
+        emoji.mminfo
+      ),
+    };
+    ctx
+      .send(CreateReply::default().content(content).ephemeral(true))
+      .await?;
+    return Ok(());
+  };
+
+  let embed = BloomBotEmbed::new()
+    .title(":sunrise: Today's Intention")
+    .description(format!("> {}", intention.prompt))
+    .footer(CreateEmbedFooter::new(format!(
+      "Category: {} · Get this each morning with /intention remind",
+      intention.category.name()
+    )));
+
+  ctx
+    .send(CreateReply::default().embed(embed).ephemeral(true))
+    .await?;
+
+  Ok(())
+}
+
+/// Turn morning intention DMs on or off
+///
+/// Turn morning intention DMs on or off, or change when they are sent.
+///
+/// When turned on, you'll be sent the day's intention by DM at the specified hour, in your local time as set with /customize offset.
+#[poise::command(slash_command)]
+async fn remind(
+  ctx: Context<'_>,
+  #[description = "Turn morning intention DMs on or off"] status: Status,
+  #[description = "Local hour to be sent the intention at, using the 24-hour clock (Defaults to 7, or 7 AM)"]
+  #[min = 0]
+  #[max = 23]
+  hour: Option<i16>,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+  let user_id = ctx.author().id;
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  let content = match status {
+    Status::Enabled => {
+      let hour = hour.unwrap_or(7);
+      let subscription = IntentionSubscription::new(guild_id, user_id, hour);
+      DatabaseHandler::add_intention_subscription(&mut transaction, &subscription).await?;
+      format!(
+        "{} You'll be sent the day's intention by DM at {hour:02}:00 each day, in the UTC offset set with `/customize offset`. Please make sure you accept DMs from server members.",
+        emoji.mmcheck
+      )
+    }
+    Status::Disabled => {
+      if DatabaseHandler::remove_intention_subscription(&mut transaction, &guild_id, &user_id)
+        .await?
+        == 0
+      {
+        ctx
+          .send(
+            CreateReply::default()
+              .content(format!(
+                "{} You aren't being sent morning intentions. No changes made.",
+                emoji.mminfo
+              ))
+              .ephemeral(true),
+          )
+          .await?;
+        return Ok(());
+      }
+      format!(
+        "{} You'll no longer be sent morning intentions.",
+        emoji.mmcheck
+      )
+    }
+  };
+
+  database::commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(content),
+    Visibility::Ephemeral,
+  )
+  .await?;
+
+  Ok(())
+}
+
+/// Add a practice prompt
+///
+/// Adds a practice prompt to the pool which daily intentions are chosen from.
+///
+/// Requires `Manage Messages` permissions.
+#[poise::command(slash_command, required_permissions = "MANAGE_MESSAGES")]
+async fn add(
+  ctx: Context<'_>,
+  #[description = "The category of the prompt"] category: PromptCategory,
+  #[description = "The practice prompt, e.g., Notice three breaths before each meal"]
+  #[max_length = 500]
+  prompt: String,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let prompt = prompt.trim();
+  if prompt.is_empty() {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!("{} Please enter a practice prompt.", emoji.mminfo))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+  let practice_prompt = PracticePrompt::new(guild_id, category, prompt.to_owned(), ctx.author().id);
+  DatabaseHandler::add_practice_prompt(&mut transaction, &practice_prompt).await?;
+
+  database::commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(format!(
+      "{} Practice prompt added to the {} category. ID: `{}`",
+      emoji.mmcheck,
+      category.name(),
+      practice_prompt.id
+    )),
+    Visibility::Ephemeral,
+  )
+  .await?;
+
+  Ok(())
+}
+
+/// List practice prompts
+///
+/// Lists the practice prompts which daily intentions are chosen from, with their IDs.
+///
+/// Requires `Manage Messages` permissions.
+#[poise::command(slash_command, required_permissions = "MANAGE_MESSAGES")]
+async fn list(
+  ctx: Context<'_>,
+  #[description = "Only list prompts from this category"] category: Option<PromptCategory>,
+  #[description = "The page to show"] page: Option<usize>,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+  let prompts =
+    DatabaseHandler::get_practice_prompts(&mut transaction, &guild_id, category).await?;
+  drop(transaction);
+
+  if prompts.is_empty() {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} No practice prompts were found. Add one with `/intention add`.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let entries: Vec<PageRowRef> = prompts.iter().map(|prompt| prompt as PageRowRef).collect();
+
+  Paginator::new("Practice Prompts", &entries, ENTRIES_PER_PAGE.default)
+    .paginate(ctx, page, PageType::Standard, Visibility::Ephemeral)
+    .await?;
+
+  Ok(())
+}
+
+/// Remove a practice prompt
+///
+/// Removes a practice prompt, so it's no longer chosen as the daily intention.
+///
+/// Requires `Manage Messages` permissions.
+#[poise::command(slash_command, required_permissions = "MANAGE_MESSAGES")]
+async fn remove(
+  ctx: Context<'_>,
+  #[description = "The ID of the prompt, as shown by /intention list"] id: String,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  if DatabaseHandler::remove_practice_prompt(&mut transaction, &guild_id, id.trim()).await? == 0 {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} No practice prompt with ID `{}` was found.",
+            emoji.mminfo,
+            id.trim()
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  database::commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(format!("{} Practice prompt removed.", emoji.mmcheck)),
+    Visibility::Ephemeral,
+  )
+  .await?;
+
+  Ok(())
+}
+
+/// Post the daily intention to a channel
+///
+/// Posts the day's intention in a channel every day. The time is in the UTC offset set with `/customize offset`, or UTC if none has been set. Replaces the existing daily post, if any.
+///
+/// Requires `Manage Messages` permissions.
+#[poise::command(slash_command, required_permissions = "MANAGE_MESSAGES")]
+async fn schedule(
+  ctx: Context<'_>,
+  #[description = "The channel to post in"]
+  #[channel_types("Text", "News")]
+  channel: GuildChannel,
+  #[description = "The time to post (HH:MM, 24-hour)"] time: String,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let Ok(time) = NaiveTime::parse_from_str(time.trim(), "%H:%M") else {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} Please enter the time as HH:MM, e.g., `07:00`.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  };
+
+  if !matches!(channel.kind, ChannelType::Text | ChannelType::News) || channel.guild_id != guild_id
+  {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} Please choose a text or announcement channel.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  let utc_offset =
+    DatabaseHandler::get_tracking_profile(&mut transaction, &guild_id, &ctx.author().id)
+      .await?
+      .unwrap_or_default()
+      .utc_offset;
+
+  let post = RecurringPost::new(
+    guild_id,
+    channel.id,
+    DAILY_POST_NAME.to_owned(),
+    ":sunrise: **Today's Intention** · {date}\n\n> {intention}\n\n-# Get this each morning by DM with `/intention remind`.".to_owned(),
+    None,
+    PostDay::Daily,
+    time,
+    utc_offset,
+    ctx.author().id,
+  );
+  DatabaseHandler::remove_recurring_post(&mut transaction, &guild_id, DAILY_POST_NAME).await?;
+  DatabaseHandler::add_recurring_post(&mut transaction, &post).await?;
+
+  database::commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(format!(
+      "{} The daily intention will be posted in {}. Schedule: {}. The first post is <t:{}:R>.",
+      emoji.mmcheck,
+      channel.mention(),
+      post.schedule(),
+      post.next_post_at.timestamp()
+    )),
+    Visibility::Ephemeral,
+  )
+  .await?;
+
+  Ok(())
+}
+
+/// Stop posting the daily intention
+///
+/// Stops posting the day's intention in a channel. Posts which have already been made aren't affected.
+///
+/// Requires `Manage Messages` permissions.
+#[poise::command(slash_command, required_permissions = "MANAGE_MESSAGES")]
+async fn unschedule(ctx: Context<'_>) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  if DatabaseHandler::remove_recurring_post(&mut transaction, &guild_id, DAILY_POST_NAME).await?
+    == 0
+  {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} The daily intention isn't being posted. Set it up with `/intention schedule`.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  database::commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(format!(
+      "{} The daily intention will no longer be posted.",
+      emoji.mmcheck
+    )),
+    Visibility::Ephemeral,
+  )
+  .await?;
+
+  Ok(())
+}
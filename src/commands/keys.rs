@@ -1,6 +1,7 @@
 use std::time::Duration;
 
 use anyhow::{anyhow, Context as AnyhowContext, Result};
+use chrono::{NaiveDate, NaiveTime, Utc};
 use poise::serenity_prelude::{builder::*, ButtonStyle, ChannelId};
 use poise::serenity_prelude::{ComponentInteractionCollector, Mentionable, User};
 use poise::CreateReply;
@@ -8,7 +9,7 @@ use poise::CreateReply;
 use crate::commands::helpers::common::Visibility;
 use crate::commands::helpers::database::{self, MessageType};
 use crate::commands::helpers::pagination::{PageRowRef, PageType, Paginator};
-use crate::config::{BloomBotEmbed, CHANNELS, EMOJI, ENTRIES_PER_PAGE};
+use crate::config::{BloomBotEmbed, CHANNELS, ENTRIES_PER_PAGE};
 use crate::data::steam_key::{Recipient, SteamKey};
 use crate::database::DatabaseHandler;
 use crate::Context;
@@ -59,34 +60,75 @@ async fn list_keys(
 
 /// Add a Playne key to the database
 ///
-/// Adds a Playne key to the database.
+/// Adds a Playne key to the database. Keys with an expiry date are no longer handed out from the start of that day (UTC), and staff are notified once they expire.
 #[poise::command(slash_command, rename = "add")]
 async fn add_key(
   ctx: Context<'_>,
   #[description = "The Playne key to add"] key: String,
+  #[description = "The day the key expires, as YYYY-MM-DD (UTC)"]
+  #[max_length = 10]
+  expires: Option<String>,
 ) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
   let guild_id = ctx
     .guild_id()
     .with_context(|| "Failed to retrieve guild ID from context")?;
 
+  let expires_at = match expires {
+    Some(expires) => match NaiveDate::parse_from_str(expires.trim(), "%Y-%m-%d") {
+      Ok(date) if date > Utc::now().date_naive() => Some(date.and_time(NaiveTime::MIN).and_utc()),
+      Ok(_) => {
+        ctx
+          .send(
+            CreateReply::default()
+              .content(format!(
+                "{} The expiry date must be after today.",
+                emoji.mminfo
+              ))
+              .ephemeral(true),
+          )
+          .await?;
+        return Ok(());
+      }
+      Err(_) => {
+        ctx
+          .send(
+            CreateReply::default()
+              .content(format!(
+                "{} Please enter the expiry date as YYYY-MM-DD, e.g., `2025-01-31`.",
+                emoji.mminfo
+              ))
+              .ephemeral(true),
+          )
+          .await?;
+        return Ok(());
+      }
+    },
+    None => None,
+  };
+
   let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
   if DatabaseHandler::steam_key_exists(&mut transaction, &guild_id, key.as_str()).await? {
     ctx
       .send(
         CreateReply::default()
-          .content(format!("{} Key already exists.", EMOJI.mminfo))
+          .content(format!("{} Key already exists.", emoji.mminfo))
           .ephemeral(true),
       )
       .await?;
     return Ok(());
   }
 
-  DatabaseHandler::add_steam_key(&mut transaction, &SteamKey::new(guild_id, key)).await?;
+  let steam_key = match expires_at {
+    Some(expires_at) => SteamKey::new(guild_id, key).expiring(expires_at),
+    None => SteamKey::new(guild_id, key),
+  };
+  DatabaseHandler::add_steam_key(&mut transaction, &steam_key).await?;
 
   database::commit_and_say(
     ctx,
     transaction,
-    MessageType::TextOnly(format!("{} Key has been added.", EMOJI.mmcheck)),
+    MessageType::TextOnly(format!("{} Key has been added.", emoji.mmcheck)),
     Visibility::Ephemeral,
   )
   .await?;
@@ -102,6 +144,7 @@ async fn remove_key(
   ctx: Context<'_>,
   #[description = "The Playne key to remove"] key: String,
 ) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
   let guild_id = ctx
     .guild_id()
     .with_context(|| "Failed to retrieve guild ID from context")?;
@@ -111,7 +154,7 @@ async fn remove_key(
     ctx
       .send(
         CreateReply::default()
-          .content(format!("{} Key does not exist.", EMOJI.mminfo))
+          .content(format!("{} Key does not exist.", emoji.mminfo))
           .ephemeral(true),
       )
       .await?;
@@ -123,7 +166,7 @@ async fn remove_key(
   database::commit_and_say(
     ctx,
     transaction,
-    MessageType::TextOnly(format!("{} Key has been removed.", EMOJI.mmcheck)),
+    MessageType::TextOnly(format!("{} Key has been removed.", emoji.mmcheck)),
     Visibility::Ephemeral,
   )
   .await?;
@@ -136,6 +179,7 @@ async fn remove_key(
 /// Selects an unused Playne key from the database, returning it and marking it as used.
 #[poise::command(slash_command, rename = "use")]
 async fn use_key(ctx: Context<'_>) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
   ctx.defer_ephemeral().await?;
 
   let guild_id = ctx
@@ -148,7 +192,7 @@ async fn use_key(ctx: Context<'_>) -> Result<()> {
     ctx
       .send(
         CreateReply::default()
-          .content(format!("{} No unused keys found.", EMOJI.mminfo))
+          .content(format!("{} No unused keys found.", emoji.mminfo))
           .ephemeral(true),
       )
       .await?;
@@ -165,7 +209,7 @@ async fn use_key(ctx: Context<'_>) -> Result<()> {
     transaction,
     MessageType::TextOnly(format!(
       "{} Key retrieved and marked used: `{key}`",
-      EMOJI.mmcheck
+      emoji.mmcheck
     )),
     Visibility::Ephemeral,
   )
@@ -250,13 +294,14 @@ async fn update_recipient(
   #[min = 0]
   total_keys: Option<i16>,
 ) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
   if challenge_prize.is_none() && donator_perk.is_none() && total_keys.is_none() {
     ctx
       .send(
         CreateReply::default()
           .content(format!(
             "{} No input provided. Update aborted.",
-            EMOJI.mminfo
+            emoji.mminfo
           ))
           .ephemeral(true),
       )
@@ -287,7 +332,7 @@ async fn update_recipient(
         transaction,
         MessageType::TextOnly(format!(
           "{} Recipient has been added to the database.",
-          EMOJI.mmcheck
+          emoji.mmcheck
         )),
         Visibility::Ephemeral,
       )
@@ -300,7 +345,7 @@ async fn update_recipient(
         CreateReply::default()
           .content(format!(
             "{} No existing record for recipient. Please specify a number of keys to create a new record.",
-            EMOJI.mminfo
+            emoji.mminfo
           ))
           .ephemeral(true),
       )
@@ -419,7 +464,7 @@ async fn update_recipient(
   database::commit_and_say(
     ctx,
     transaction,
-    MessageType::TextOnly(format!("{} Recipient has been updated.", EMOJI.mmcheck)),
+    MessageType::TextOnly(format!("{} Recipient has been updated.", emoji.mmcheck)),
     Visibility::Ephemeral,
   )
   .await?;
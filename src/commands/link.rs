@@ -0,0 +1,69 @@
+use anyhow::Result;
+
+use crate::commands::helpers::common::Visibility;
+use crate::commands::helpers::database::{self, MessageType};
+use crate::data::account_link::{LinkCode, CODE_EXPIRY_MINUTES};
+use crate::database::DatabaseHandler;
+use crate::Context;
+
+/// Link your account to the Bloom dashboard
+///
+/// Get a one-time code for signing in to the Bloom dashboard with your Discord account.
+///
+/// Enter the code on the dashboard within 10 minutes. It can only be used once, and getting a new code replaces any unused one. Never share your code with anyone, since it lets whoever enters it act as you on the dashboard.
+///
+/// To sign out of the dashboard everywhere, such as if you've shared a code by mistake, set revoke to true.
+#[poise::command(slash_command, category = "Utilities")]
+pub async fn link(
+  ctx: Context<'_>,
+  #[description = "Sign out of the dashboard everywhere instead (Defaults to false)"]
+  revoke: Option<bool>,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let user_id = ctx.author().id;
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  if revoke == Some(true) {
+    let revoked = DatabaseHandler::revoke_api_tokens(&mut transaction, &user_id).await?;
+    let message = if revoked == 0 {
+      format!(
+        "{} You're not signed in to the dashboard anywhere. Any unused code has been cancelled.",
+        emoji.mminfo
+      )
+    } else {
+      format!(
+        "{} Signed out of the dashboard everywhere ({revoked} {}). Use `/link` to sign in again.",
+        emoji.mmcheck,
+        if revoked == 1 { "session" } else { "sessions" }
+      )
+    };
+
+    database::commit_and_say(
+      ctx,
+      transaction,
+      MessageType::TextOnly(message),
+      Visibility::Ephemeral,
+    )
+    .await?;
+
+    return Ok(());
+  }
+
+  let (code, link_code) = LinkCode::generate(user_id);
+  DatabaseHandler::add_link_code(&mut transaction, &link_code).await?;
+
+  database::commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(format!(
+      "{} Your code is `{code}`\n\nEnter it on the Bloom dashboard to sign in with your Discord account. It expires <t:{}:R> ({CODE_EXPIRY_MINUTES} minutes) and can only be used once. Never share it with anyone.",
+      emoji.mmcheck,
+      link_code.expires_at.timestamp()
+    )),
+    Visibility::Ephemeral,
+  )
+  .await?;
+
+  Ok(())
+}
@@ -1,20 +1,31 @@
 #![allow(clippy::too_many_arguments)]
 
+use std::collections::{BTreeMap, HashSet};
 use std::time::Duration;
 
 use anyhow::{anyhow, Context as AnyhowContext, Result};
-use chrono::{Datelike, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
-use poise::serenity_prelude::{builder::*, ButtonStyle};
-use poise::serenity_prelude::{ChannelId, Color, ComponentInteractionCollector, Mentionable, User};
+use chrono::{DateTime, Datelike, Duration as ChronoDuration, NaiveDate, NaiveDateTime};
+use chrono::{DurationRound, NaiveTime, SecondsFormat, TimeDelta, Timelike, Utc};
+use csv::{ReaderBuilder, Trim, WriterBuilder};
+use log::{error, info};
+use poise::serenity_prelude::{builder::*, Attachment, ButtonStyle, ChannelId, Color};
+use poise::serenity_prelude::{ComponentInteractionCollector, GuildId, Mentionable, User, UserId};
 use poise::{ChoiceParameter, CreateReply};
+use rand::Rng;
+use sha2::{Digest, Sha256};
+use ulid::Ulid;
 
 use crate::commands::helpers::common::Visibility;
 use crate::commands::helpers::database::{self, MessageType};
+use crate::commands::helpers::discord::Discord;
 use crate::commands::helpers::pagination::{PageRowRef, PageType, Paginator};
-use crate::config::{BloomBotEmbed, CHANNELS, ENTRIES_PER_PAGE};
+use crate::config::{BloomBotEmbed, CHANNELS, ENTRIES_PER_PAGE, MEDITATION_MIND};
+use crate::data::backup::{Backup, BackupTable};
 use crate::data::common::{Migration, MigrationType};
+use crate::data::maintenance::Maintenance;
 use crate::data::meditation::Meditation;
 use crate::database::DatabaseHandler;
+use crate::events::{integrity, role_sync, selfcheck};
 use crate::Context;
 
 #[derive(ChoiceParameter)]
@@ -25,14 +36,200 @@ enum DataType {
   CustomizationSettings,
 }
 
+#[derive(ChoiceParameter)]
+enum KeepSettings {
+  #[name = "new user's settings"]
+  New,
+  #[name = "old user's settings"]
+  Old,
+}
+
+#[derive(ChoiceParameter)]
+enum Toggle {
+  #[name = "on"]
+  On,
+  #[name = "off"]
+  Off,
+}
+
+/// Maximum file size for `/manage importcsv`, in bytes.
+const BACKFILL_MAX_FILE_SIZE: u32 = 1_048_576;
+/// Maximum number of distinct users in a single `/manage importcsv` file. Each user is
+/// checked for membership, so this keeps the number of lookups reasonable.
+const BACKFILL_MAX_USERS: usize = 100;
+/// Maximum number of minutes for a single backfilled entry.
+const BACKFILL_MAX_MINUTES: i32 = 1440;
+/// Number of entries inserted per query when backfilling.
+const BACKFILL_BATCH_SIZE: usize = 1000;
+/// Number of invalid rows or members shown in the `/manage importcsv` preview.
+const BACKFILL_PREVIEW_LINES: usize = 10;
+/// Maximum size of each part of a `/manage backup` file, in bytes. Backups larger than this
+/// are split into parts, to stay within Discord's attachment size limit.
+const BACKUP_PART_SIZE: usize = 8 * 1024 * 1024;
+/// Maximum number of parts in a backup, which is the number `/manage restore` accepts.
+const BACKUP_MAX_PARTS: usize = 5;
+/// The fewest sessions a member needs to be included in `/manage export-anon`, unless staff
+/// choose a different minimum.
+const ANON_EXPORT_MIN_SESSIONS: i64 = 10;
+/// Maximum size of a `/manage export-anon` file, in bytes, to stay within Discord's
+/// attachment size limit.
+const ANON_EXPORT_MAX_SIZE: usize = 8 * 1024 * 1024;
+
+#[derive(Debug, PartialEq)]
+struct BackfillEntry {
+  user_id: UserId,
+  occurred_at: DateTime<Utc>,
+  minutes: i32,
+}
+
+/// Parses a backfill CSV with `user_id`, `date`, and `minutes` columns. Dates may be given
+/// as `YYYY-MM-DD` (midnight UTC) or as RFC 3339 timestamps. Returns the valid entries,
+/// along with a description of each row which could not be parsed. Returns an error if
+/// the file is not a CSV with the expected headers.
+fn parse_backfill_csv(
+  content: &[u8],
+  now: DateTime<Utc>,
+) -> Result<(Vec<BackfillEntry>, Vec<String>)> {
+  let mut rdr = ReaderBuilder::new()
+    .trim(Trim::All)
+    .flexible(true)
+    .from_reader(content);
+
+  let headers = rdr.headers()?;
+  if headers != vec!["user_id", "date", "minutes"] {
+    return Err(anyhow!("Unexpected headers: {headers:?}"));
+  }
+
+  let mut entries = Vec::new();
+  let mut invalid = Vec::new();
+
+  for (i, result) in rdr.records().enumerate() {
+    // Line 1 is the header row
+    let line = i + 2;
+
+    let record = match result {
+      Ok(record) => record,
+      Err(e) => {
+        invalid.push(format!("Line {line}: {e}"));
+        continue;
+      }
+    };
+
+    let (Some(user_id), Some(date), Some(minutes)) = (record.get(0), record.get(1), record.get(2))
+    else {
+      invalid.push(format!("Line {line}: expected 3 columns"));
+      continue;
+    };
+
+    let Some(user_id) = user_id
+      .parse::<u64>()
+      .ok()
+      .filter(|id| *id > 0)
+      .map(UserId::new)
+    else {
+      invalid.push(format!("Line {line}: invalid user ID `{user_id}`"));
+      continue;
+    };
+
+    let occurred_at = match DateTime::parse_from_rfc3339(date) {
+      Ok(datetime) => Some(datetime.with_timezone(&Utc)),
+      Err(_) => NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .ok()
+        .map(|date| date.and_time(NaiveTime::MIN).and_utc()),
+    };
+    let Some(occurred_at) = occurred_at else {
+      invalid.push(format!("Line {line}: invalid date `{date}`"));
+      continue;
+    };
+    if occurred_at > now {
+      invalid.push(format!("Line {line}: date `{date}` is in the future"));
+      continue;
+    }
+
+    let Some(minutes) = minutes
+      .parse::<i32>()
+      .ok()
+      .filter(|minutes| (1..=BACKFILL_MAX_MINUTES).contains(minutes))
+    else {
+      invalid.push(format!(
+        "Line {line}: minutes must be between 1 and {BACKFILL_MAX_MINUTES}, got `{minutes}`"
+      ));
+      continue;
+    };
+
+    entries.push(BackfillEntry {
+      user_id,
+      occurred_at,
+      minutes,
+    });
+  }
+
+  Ok((entries, invalid))
+}
+
+/// Builds the queries for inserting backfilled entries in batches, along with a query
+/// which reverses the import, for the log channel.
+fn backfill_queries(guild_id: GuildId, entries: &[BackfillEntry]) -> (Vec<String>, String) {
+  let mut record_ids = Vec::with_capacity(entries.len());
+
+  let insert_queries = entries
+    .chunks(BACKFILL_BATCH_SIZE)
+    .map(|chunk| {
+      let values: Vec<String> = chunk
+        .iter()
+        .map(|entry| {
+          let record_id = Ulid::new().to_string();
+          let values = format!(
+            "('{record_id}', '{}', '{}', '0', '{guild_id}', '{}')",
+            entry.user_id,
+            entry.minutes,
+            entry.occurred_at.to_rfc3339()
+          );
+          record_ids.push(format!("'{record_id}'"));
+          values
+        })
+        .collect();
+
+      format!(
+        "INSERT INTO meditation (record_id, user_id, meditation_minutes, meditation_seconds, guild_id, occurred_at) VALUES {};",
+        values.join(", ")
+      )
+    })
+    .collect();
+
+  let reversal_query = format!(
+    "DELETE FROM meditation WHERE record_id IN ({});",
+    record_ids.join(", ")
+  );
+
+  (insert_queries, reversal_query)
+}
+
 /// Commands for managing meditation entries
 ///
-/// Commands to create, list, update, or delete meditation entries for a user, or completely reset a user's data.
+/// Commands to create, list, update, or delete meditation entries for a user, backfill entries for many users from a CSV file, completely reset a user's data, or recalculate streaks. Administrators can also monitor OpenAI API usage, check Bloom's configuration, back up or restore the server's data, export an anonymized dataset for research, fix members' time and streak roles, check stored stats for drift, and put Bloom into maintenance mode.
 ///
 /// Requires `Ban Members` permissions.
 #[poise::command(
   slash_command,
-  subcommands("create", "list", "update", "delete", "reset", "migrate"),
+  subcommands(
+    "create",
+    "list",
+    "update",
+    "delete",
+    "reset",
+    "migrate",
+    "importcsv",
+    "aiusage",
+    "selfcheck",
+    "backup",
+    "restore",
+    "export_anon",
+    "reconcile_roles",
+    "recalc_streak",
+    "integrity",
+    "maintenance"
+  ),
   subcommand_required,
   required_permissions = "BAN_MEMBERS",
   default_member_permissions = "BAN_MEMBERS",
@@ -435,7 +632,7 @@ async fn delete(
     return Ok(());
   };
 
-  DatabaseHandler::remove_meditation_entry(&mut transaction, &entry_id).await?;
+  DatabaseHandler::remove_meditation_entry(&mut transaction, &guild_id, &entry_id).await?;
 
   let description = if entry.seconds > 0 {
     format!(
@@ -638,6 +835,8 @@ async fn reset(
 /// Migrates meditation entries or customization settings
 ///
 /// Migrates all meditation entries or customization settings from one user account to another.
+///
+/// If the new account already has meditation entries, the entries from both accounts are combined and streaks for both accounts are recalculated. If both accounts have customization settings, only one set can be kept, so choose which with `keep_settings` (defaults to keeping the new account's settings). A summary is shown for confirmation before anything is changed.
 #[poise::command(slash_command)]
 async fn migrate(
   ctx: Context<'_>,
@@ -646,38 +845,410 @@ async fn migrate(
   #[description = "The type of data to migrate (Defaults to meditation entries)"]
   #[rename = "type"]
   data_type: Option<DataType>,
+  #[description = "Whose customization settings to keep if both users have them (Defaults to the new user's)"]
+  keep_settings: Option<KeepSettings>,
 ) -> Result<()> {
   let guild_id = ctx
     .guild_id()
     .with_context(|| "Failed to retrieve guild ID from context")?;
 
-  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
-
   //Default to meditation entries
-  let data_type = match data_type {
-    Some(data_type) => data_type,
-    None => DataType::MeditationEntries,
+  let data_type = data_type.unwrap_or(DataType::MeditationEntries);
+  let keep_settings = keep_settings.unwrap_or(KeepSettings::New);
+
+  let Some(description) = migrate_user(
+    &ctx,
+    &ctx.data().db,
+    guild_id,
+    old_user.id,
+    new_user.id,
+    &data_type,
+    &keep_settings,
+  )
+  .await?
+  else {
+    return Ok(());
   };
 
-  match data_type {
-    DataType::CustomizationSettings => {
-      let migration = Migration::new(
-        guild_id,
-        old_user.id,
-        new_user.id,
-        MigrationType::TrackingProfile,
-      );
-      DatabaseHandler::migrate_tracking_profile(&mut transaction, &migration).await?;
+  let log_embed = BloomBotEmbed::new()
+    .title(format!(
+      "{} Migrated",
+      match data_type {
+        DataType::CustomizationSettings => "Customization Settings",
+        DataType::MeditationEntries => "Meditation Entries",
+      }
+    ))
+    .description(description)
+    .footer(
+      CreateEmbedFooter::new(format!(
+        "Migrated by {} ({})",
+        ctx.author().name,
+        ctx.author().id
+      ))
+      .icon_url(ctx.author().avatar_url().unwrap_or_default()),
+    )
+    .clone();
+
+  let log_channel = ChannelId::new(CHANNELS.bloomlogs);
+
+  log_channel
+    .send_message(ctx, CreateMessage::new().embed(log_embed))
+    .await?;
+
+  Ok(())
+}
+
+/// Migrates data from `old_user` to `new_user` once the author has confirmed a summary of the
+/// changes. Returns the summary for the log if the migration was confirmed and committed.
+async fn migrate_user(
+  discord: &impl Discord,
+  db: &DatabaseHandler,
+  guild_id: GuildId,
+  old_user: UserId,
+  new_user: UserId,
+  data_type: &DataType,
+  keep_settings: &KeepSettings,
+) -> Result<Option<String>> {
+  if old_user == new_user {
+    discord
+      .respond(
+        format!(
+          "{} The users to migrate from and to must be different.",
+          discord.emoji().mminfo
+        ),
+        true,
+      )
+      .await?;
+    return Ok(None);
+  }
+
+  let mut transaction = db.start_transaction_with_retry(5).await?;
+
+  let migrate_entries = matches!(data_type, DataType::MeditationEntries);
+  let migrate_settings = matches!(data_type, DataType::CustomizationSettings);
+
+  let mut summary = Vec::new();
+
+  if migrate_entries {
+    let old_count =
+      DatabaseHandler::get_user_meditation_count(&mut transaction, &guild_id, &old_user).await?;
+    let old_sum =
+      DatabaseHandler::get_user_meditation_sum(&mut transaction, &guild_id, &old_user).await?;
+    let new_count =
+      DatabaseHandler::get_user_meditation_count(&mut transaction, &guild_id, &new_user).await?;
+    let new_sum =
+      DatabaseHandler::get_user_meditation_sum(&mut transaction, &guild_id, &new_user).await?;
+
+    summary.push(if new_count > 0 {
+      format!(
+        "**Meditation Entries**: {old_count} entries ({old_sum} minutes) will be combined with the {new_count} entries ({new_sum} minutes) {} already has, and streaks will be recalculated.",
+        new_user.mention()
+      )
+    } else {
+      format!("**Meditation Entries**: {old_count} entries ({old_sum} minutes) will be moved.")
+    });
+
+    let migration = Migration::new(
+      guild_id,
+      old_user,
+      new_user,
+      MigrationType::MeditationEntries,
+    );
+    DatabaseHandler::migrate_meditation_entries(&mut transaction, &migration).await?;
+  }
+
+  if migrate_settings {
+    let old_profile =
+      DatabaseHandler::get_tracking_profile(&mut transaction, &guild_id, &old_user).await?;
+    let new_profile =
+      DatabaseHandler::get_tracking_profile(&mut transaction, &guild_id, &new_user).await?;
+
+    let migration = Migration::new(guild_id, old_user, new_user, MigrationType::TrackingProfile);
+
+    match (old_profile, new_profile) {
+      (None, _) => {
+        summary.push(format!(
+          "**Customization Settings**: {} has no customization settings, so none will be migrated.",
+          old_user.mention()
+        ));
+      }
+      (Some(_), None) => {
+        summary.push("**Customization Settings**: Settings will be moved.".to_owned());
+        DatabaseHandler::migrate_tracking_profile(&mut transaction, &migration).await?;
+      }
+      (Some(_), Some(_)) => match keep_settings {
+        KeepSettings::Old => {
+          summary.push(format!(
+            "**Customization Settings**: Both users have settings. The settings of {} will replace those of {}.",
+            old_user.mention(),
+            new_user.mention()
+          ));
+          DatabaseHandler::remove_tracking_profile(&mut transaction, &guild_id, &new_user).await?;
+          DatabaseHandler::migrate_tracking_profile(&mut transaction, &migration).await?;
+        }
+        KeepSettings::New => {
+          summary.push(format!(
+            "**Customization Settings**: Both users have settings. The settings of {} will be kept, and those of {} will be removed.",
+            new_user.mention(),
+            old_user.mention()
+          ));
+          DatabaseHandler::remove_tracking_profile(&mut transaction, &guild_id, &old_user).await?;
+        }
+      },
     }
-    DataType::MeditationEntries => {
-      let migration = Migration::new(
-        guild_id,
-        old_user.id,
-        new_user.id,
-        MigrationType::MeditationEntries,
+  }
+
+  // Totals after the migration, reported once confirmed
+  let merged_totals = if migrate_entries {
+    DatabaseHandler::recalculate_streak(&mut transaction, &guild_id, &old_user).await?;
+    let streak =
+      DatabaseHandler::recalculate_streak(&mut transaction, &guild_id, &new_user).await?;
+    let count =
+      DatabaseHandler::get_user_meditation_count(&mut transaction, &guild_id, &new_user).await?;
+    let sum =
+      DatabaseHandler::get_user_meditation_sum(&mut transaction, &guild_id, &new_user).await?;
+
+    Some(format!(
+      "**Merged Totals**: {count} entries ({sum} minutes), with a current streak of {} days (longest: {} days)",
+      streak.current, streak.longest
+    ))
+  } else {
+    None
+  };
+
+  let prompt = format!(
+    "Are you sure you want to migrate all {} from {} to {}?\n\n{}",
+    data_type.name(),
+    old_user.mention(),
+    new_user.mention(),
+    summary.join("\n"),
+  );
+  let confirmed = match &merged_totals {
+    Some(merged_totals) => format!("Confirmed.\n\n{merged_totals}"),
+    None => "Confirmed.".to_owned(),
+  };
+
+  match discord.confirm(prompt, confirmed).await {
+    Ok(Some(true)) => {
+      DatabaseHandler::commit_transaction(transaction).await?;
+
+      let mut description = format!(
+        "**From**: <@{old_user}>\n**To**: <@{new_user}>\n\n{}",
+        summary.join("\n"),
       );
-      DatabaseHandler::migrate_meditation_entries(&mut transaction, &migration).await?;
+      if let Some(merged_totals) = merged_totals {
+        description.push_str(&format!("\n{merged_totals}"));
+      }
+
+      Ok(Some(description))
+    }
+    // Cancelled, or no button was pressed for 60 seconds
+    Ok(_) => Ok(None),
+    Err(e) => {
+      DatabaseHandler::rollback_transaction(transaction).await?;
+      Err(anyhow!(
+        "Failed to tell user that the {} were migrated: {}",
+        data_type.name(),
+        e
+      ))
+    }
+  }
+}
+
+/// Backfill meditation entries for multiple users from a CSV file
+///
+/// Backfills meditation entries from a CSV file, such as when restoring data from another bot. The file must have `user_id`, `date`, and `minutes` columns, with one entry per row. Dates may be given as `YYYY-MM-DD` (midnight UTC) or as full timestamps such as `2024-10-01T18:30:00Z`.
+///
+/// Rows for users who are not members of the server are skipped. A preview of the import is shown for confirmation before any entries are added.
+#[poise::command(slash_command)]
+async fn importcsv(
+  ctx: Context<'_>,
+  #[description = "CSV file with user_id, date, and minutes columns"] file: Attachment,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  ctx.defer_ephemeral().await?;
+
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  if file.size > BACKFILL_MAX_FILE_SIZE {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} File exceeds size limit of 1 MB. Please split it into smaller files.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let content = match file.download().await {
+    Ok(content) => content,
+    Err(e) => {
+      info!("Error downloading attachment for backfill: {e:?}");
+      ctx
+        .send(
+          CreateReply::default()
+            .content(format!("{} Unable to download attachment.", emoji.mminfo))
+            .ephemeral(true),
+        )
+        .await?;
+      return Ok(());
+    }
+  };
+
+  let (entries, invalid) = match parse_backfill_csv(&content, Utc::now()) {
+    Ok(parsed) => parsed,
+    Err(e) => {
+      info!("Failed to parse backfill CSV: {e}");
+      ctx
+        .send(
+          CreateReply::default()
+            .content(format!(
+              "{} **Unrecognized file format.**\n-# Please upload a CSV file with the headers `user_id,date,minutes`.",
+              emoji.mminfo
+            ))
+            .ephemeral(true),
+        )
+        .await?;
+      return Ok(());
+    }
+  };
+
+  drop(content);
+
+  // Number of entries and total minutes for each user
+  let mut totals: BTreeMap<UserId, (usize, i64)> = BTreeMap::new();
+  for entry in &entries {
+    let total = totals.entry(entry.user_id).or_default();
+    total.0 += 1;
+    total.1 += i64::from(entry.minutes);
+  }
+
+  if totals.len() > BACKFILL_MAX_USERS {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} File contains entries for {} users. Please split it into files with at most {BACKFILL_MAX_USERS} users each.",
+            emoji.mminfo,
+            totals.len()
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let mut non_members = Vec::new();
+  for user_id in totals.keys() {
+    if guild_id.member(ctx, *user_id).await.is_err() {
+      non_members.push(*user_id);
+    }
+  }
+  for user_id in &non_members {
+    totals.remove(user_id);
+  }
+
+  let entries: Vec<BackfillEntry> = entries
+    .into_iter()
+    .filter(|entry| totals.contains_key(&entry.user_id))
+    .collect();
+
+  if entries.is_empty() {
+    let reason = invalid
+      .iter()
+      .take(BACKFILL_PREVIEW_LINES)
+      .cloned()
+      .collect::<Vec<String>>()
+      .join("\n");
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} No entries to import for current members.\n{reason}",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let total_minutes: i64 = totals.values().map(|(_, minutes)| minutes).sum();
+  let entry_count = entries.len();
+
+  let mut members: Vec<(&UserId, &(usize, i64))> = totals.iter().collect();
+  members.sort_by(|a, b| b.1 .1.cmp(&a.1 .1));
+  let mut member_lines: Vec<String> = members
+    .iter()
+    .take(BACKFILL_PREVIEW_LINES)
+    .map(|(user_id, (count, minutes))| {
+      format!(
+        "{}: {count} {}, {minutes} minutes",
+        user_id.mention(),
+        if *count == 1 { "entry" } else { "entries" }
+      )
+    })
+    .collect();
+  if members.len() > BACKFILL_PREVIEW_LINES {
+    member_lines.push(format!(
+      "-# ...and {} more",
+      members.len() - BACKFILL_PREVIEW_LINES
+    ));
+  }
+
+  let mut preview_embed = BloomBotEmbed::new()
+    .title("Meditation Data Backfill")
+    .description(format!(
+      "**Entries**: {entry_count}\n**Members**: {}\n**Total Time**: {} minutes ({}h {}m)\n\nAre you sure you want to add these entries?",
+      totals.len(),
+      total_minutes,
+      total_minutes / 60,
+      total_minutes % 60
+    ))
+    .field("Members", member_lines.join("\n"), false);
+
+  if !invalid.is_empty() {
+    let mut invalid_lines: Vec<String> = invalid
+      .iter()
+      .take(BACKFILL_PREVIEW_LINES)
+      .cloned()
+      .collect();
+    if invalid.len() > BACKFILL_PREVIEW_LINES {
+      invalid_lines.push(format!(
+        "-# ...and {} more",
+        invalid.len() - BACKFILL_PREVIEW_LINES
+      ));
+    }
+    preview_embed = preview_embed.field(
+      format!("Skipped Rows ({})", invalid.len()),
+      invalid_lines.join("\n"),
+      false,
+    );
+  }
+
+  if !non_members.is_empty() {
+    let mut non_member_lines: Vec<String> = non_members
+      .iter()
+      .take(BACKFILL_PREVIEW_LINES)
+      .map(|user_id| format!("<@{user_id}> ({user_id})"))
+      .collect();
+    if non_members.len() > BACKFILL_PREVIEW_LINES {
+      non_member_lines.push(format!(
+        "-# ...and {} more",
+        non_members.len() - BACKFILL_PREVIEW_LINES
+      ));
     }
+    preview_embed = preview_embed.field(
+      format!("Skipped Non-Members ({})", non_members.len()),
+      non_member_lines.join("\n"),
+      false,
+    );
   }
 
   let ctx_id = ctx.id();
@@ -688,12 +1259,7 @@ async fn migrate(
   ctx
     .send(
       CreateReply::default()
-        .content(format!(
-          "Are you sure you want to migrate all {} from {} to {}?",
-          data_type.name(),
-          old_user.mention(),
-          new_user.mention(),
-        ))
+        .embed(preview_embed)
         .ephemeral(true)
         .components(vec![CreateActionRow::Buttons(vec![
           CreateButton::new(confirm_id.clone())
@@ -706,92 +1272,1008 @@ async fn migrate(
     )
     .await?;
 
-  // Loop through incoming interactions with the navigation buttons
+  // Loop through incoming interactions with the confirmation buttons
   while let Some(press) = ComponentInteractionCollector::new(ctx)
     // We defined our button IDs to start with `ctx_id`. If they don't, some other command's
     // button was pressed
     .filter(move |press| press.data.custom_id.starts_with(&ctx_id.to_string()))
-    // Timeout when no navigation button has been pressed in one minute
+    // Timeout when no button has been pressed in one minute
     .timeout(Duration::from_secs(60))
     .await
   {
-    // Depending on which button was pressed, go to next or previous page
     if press.data.custom_id != confirm_id && press.data.custom_id != cancel_id {
       // This is an unrelated button interaction
       continue;
     }
 
-    let confirmed = press.data.custom_id == confirm_id;
-
-    // Update the message with the new page contents
-    if confirmed {
-      match press
+    if press.data.custom_id == cancel_id {
+      press
         .create_response(
           ctx,
           CreateInteractionResponse::UpdateMessage(
             CreateInteractionResponseMessage::new()
-              .content("Confirmed.")
+              .content("Cancelled.")
+              .embeds(Vec::new())
               .components(Vec::new()),
           ),
         )
-        .await
-      {
-        Ok(()) => {
-          DatabaseHandler::commit_transaction(transaction).await?;
-
-          let log_embed = BloomBotEmbed::new()
-            .title(format!(
-              "{} Migrated",
-              match data_type {
-                DataType::CustomizationSettings => "Customization Settings",
-                DataType::MeditationEntries => "Meditation Entries",
-              }
-            ))
-            .description(format!(
-              "**From**: <@{}>\n**To**: <@{}>",
-              old_user.id, new_user.id,
-            ))
-            .footer(
-              CreateEmbedFooter::new(format!(
-                "Migrated by {} ({})",
-                ctx.author().name,
-                ctx.author().id
-              ))
-              .icon_url(ctx.author().avatar_url().unwrap_or_default()),
-            )
-            .clone();
-
-          let log_channel = ChannelId::new(CHANNELS.bloomlogs);
+        .await?;
+      return Ok(());
+    }
 
-          log_channel
-            .send_message(ctx, CreateMessage::new().embed(log_embed))
-            .await?;
+    let (insert_queries, reversal_query) = backfill_queries(guild_id, &entries);
 
-          return Ok(());
-        }
-        Err(e) => {
-          DatabaseHandler::rollback_transaction(transaction).await?;
-          return Err(anyhow!(
-            "Failed to tell user that the {} were migrated: {}",
-            data_type.name(),
-            e
-          ));
-        }
-      }
+    let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+    let mut added = 0;
+    for query in &insert_queries {
+      added += DatabaseHandler::add_meditation_entry_batch(&mut transaction, query).await?;
     }
 
-    press
+    match press
       .create_response(
         ctx,
         CreateInteractionResponse::UpdateMessage(
           CreateInteractionResponseMessage::new()
-            .content("Cancelled.")
+            .content(format!(
+              "{} Successfully added {added} {} for {} {}, totaling {total_minutes} minutes.",
+              emoji.mmcheck,
+              if added == 1 { "entry" } else { "entries" },
+              totals.len(),
+              if totals.len() == 1 {
+                "member"
+              } else {
+                "members"
+              },
+            ))
+            .embeds(Vec::new())
             .components(Vec::new()),
         ),
       )
-      .await?;
-  }
+      .await
+    {
+      Ok(()) => DatabaseHandler::commit_transaction(transaction).await?,
+      Err(e) => {
+        DatabaseHandler::rollback_transaction(transaction).await?;
+        return Err(anyhow!(
+          "Failed to tell user that the entries were backfilled: {e}"
+        ));
+      }
+    }
 
-  // This happens when the user didn't press any button for 60 seconds
-  Ok(())
+    let log_embed = BloomBotEmbed::new()
+      .title("Meditation Data Backfill")
+      .description(format!(
+        "**File**: {}\n**Entries Added**: {added}\n**Members**: {}\n**Total Time**: {total_minutes} minutes\n**Skipped Rows**: {}\n**Skipped Non-Members**: {}",
+        file.filename,
+        totals.len(),
+        invalid.len(),
+        non_members.len(),
+      ))
+      .footer(
+        CreateEmbedFooter::new(format!(
+          "Added by {} ({})",
+          ctx.author().name,
+          ctx.author().id
+        ))
+        .icon_url(ctx.author().avatar_url().unwrap_or_default()),
+      );
+
+    // Attach the query for reversing the import, in case it needs to be undone
+    let reversal_file = CreateAttachment::bytes(
+      reversal_query.into_bytes(),
+      format!("backfill_{guild_id}_{}.txt", Ulid::new()),
+    );
+
+    let log_channel = ChannelId::new(CHANNELS.bloomlogs);
+
+    log_channel
+      .send_files(ctx, [reversal_file], CreateMessage::new().embed(log_embed))
+      .await?;
+
+    return Ok(());
+  }
+
+  // This happens when the user didn't press any button for 60 seconds
+  Ok(())
+}
+
+/// Show OpenAI API usage and set a monthly spending cap
+///
+/// Shows OpenAI API usage for this server this month (UTC), with a breakdown of the last seven days and the estimated spend.
+///
+/// Optionally sets a soft monthly cap on estimated spend, in US dollars. Once the cap is reached, `/glossary search` is disabled until the start of next month. Set the cap to 0 to remove it.
+///
+/// Requires `Administrator` permissions.
+#[poise::command(
+  slash_command,
+  required_permissions = "ADMINISTRATOR",
+  default_member_permissions = "ADMINISTRATOR"
+)]
+async fn aiusage(
+  ctx: Context<'_>,
+  #[description = "Soft monthly cap on estimated spend, in US dollars (0 removes the cap)"]
+  #[min = 0.0]
+  monthly_cap: Option<f64>,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  let mut settings = DatabaseHandler::get_guild_settings(&mut transaction, &guild_id).await?;
+
+  if let Some(monthly_cap) = monthly_cap {
+    settings = settings.ai_monthly_cap((monthly_cap > 0.0).then_some(monthly_cap));
+    DatabaseHandler::update_guild_settings(&mut transaction, &settings).await?;
+  }
+
+  let today = Utc::now().date_naive();
+  let month_start = today
+    .with_day(1)
+    .with_context(|| "Failed to set day to 1")?;
+
+  let usage = DatabaseHandler::get_ai_usage(&mut transaction, &guild_id, month_start).await?;
+  let daily_usage = DatabaseHandler::get_daily_ai_usage(
+    &mut transaction,
+    &guild_id,
+    today - ChronoDuration::days(6),
+  )
+  .await?;
+
+  DatabaseHandler::commit_transaction(transaction).await?;
+
+  if monthly_cap.is_some() {
+    ctx.data().settings.invalidate(guild_id);
+  }
+
+  let cap = match settings.ai_monthly_cap {
+    Some(cap) => format!(
+      "${cap:.2}{}",
+      if usage.cost() >= cap {
+        " (reached, search is disabled)"
+      } else {
+        ""
+      }
+    ),
+    None => "None".to_string(),
+  };
+
+  let last_seven_days = if daily_usage.is_empty() {
+    "No usage in the last seven days.".to_string()
+  } else {
+    daily_usage
+      .iter()
+      .map(|day| {
+        format!(
+          "`{}`: {} requests, {} tokens (${:.4})",
+          day.usage_date.format("%Y-%m-%d"),
+          day.requests,
+          day.tokens,
+          day.cost()
+        )
+      })
+      .collect::<Vec<String>>()
+      .join("\n")
+  };
+
+  let embed = BloomBotEmbed::new()
+    .title("OpenAI API Usage")
+    .description(format!(
+      "**This month**: {} requests, {} tokens\n**Estimated spend**: ${:.4}\n**Monthly cap**: {cap}",
+      usage.requests,
+      usage.tokens,
+      usage.cost()
+    ))
+    .field("Last Seven Days", last_seven_days, false);
+
+  let mut reply = CreateReply::default().embed(embed).ephemeral(true);
+  if monthly_cap.is_some() {
+    reply = reply.content(format!("{} Monthly cap has been updated.", emoji.mmcheck));
+  }
+
+  ctx.send(reply).await?;
+
+  Ok(())
+}
+
+/// Check Bloom's configuration and connections
+///
+/// Checks that required environment variables are set, that the database is reachable and up to date, that the OpenAI API is reachable, and that the configured channels and roles exist and are accessible to Bloom.
+///
+/// Requires `Administrator` permissions.
+#[poise::command(
+  slash_command,
+  required_permissions = "ADMINISTRATOR",
+  default_member_permissions = "ADMINISTRATOR"
+)]
+async fn selfcheck(ctx: Context<'_>) -> Result<()> {
+  ctx.defer_ephemeral().await?;
+
+  let data = ctx.data();
+  let report = selfcheck::run(ctx.serenity_context(), &data.db, &data.embeddings).await;
+
+  ctx
+    .send(CreateReply::default().embed(report.embed()).ephemeral(true))
+    .await?;
+
+  Ok(())
+}
+
+/// Recalculate streaks from meditation entries
+///
+/// Recalculates and saves the current and longest streak for a user from all of their meditation entries. Longest streaks are otherwise only calculated once, so this is needed after entries are imported, migrated, or added in the past, or after a timezone change. Leave the user empty to recalculate the streaks of everyone in the server.
+#[poise::command(slash_command, rename = "recalc-streak")]
+async fn recalc_streak(
+  ctx: Context<'_>,
+  #[description = "The user to recalculate the streak for (Defaults to everyone)"] user: Option<
+    User,
+  >,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  ctx.defer_ephemeral().await?;
+
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let description = if let Some(user) = user {
+    let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+    let streak = DatabaseHandler::recalculate_streak(&mut transaction, &guild_id, &user.id).await?;
+    DatabaseHandler::commit_transaction(transaction).await?;
+
+    format!(
+      "Recalculated the streak for {}: current streak of {} days (longest: {} days).",
+      user.mention(),
+      streak.current,
+      streak.longest
+    )
+  } else {
+    let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+    let users = DatabaseHandler::get_meditating_users(&mut transaction, &guild_id).await?;
+    drop(transaction);
+
+    // Each streak is saved separately, so a failure doesn't undo the others
+    let mut failed = 0;
+    for user_id in &users {
+      let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+      match DatabaseHandler::recalculate_streak(&mut transaction, &guild_id, user_id).await {
+        Ok(_) => DatabaseHandler::commit_transaction(transaction).await?,
+        Err(e) => {
+          error!("Error recalculating streak for {user_id}: {e:?}");
+          failed += 1;
+        }
+      }
+    }
+
+    if failed > 0 {
+      format!(
+        "Recalculated streaks for {} of {} users. {failed} couldn't be recalculated.",
+        users.len() - failed,
+        users.len()
+      )
+    } else {
+      format!("Recalculated streaks for {} users.", users.len())
+    }
+  };
+
+  ctx
+    .send(
+      CreateReply::default()
+        .content(format!("{} {description}", emoji.mmcheck))
+        .allowed_mentions(CreateAllowedMentions::new())
+        .ephemeral(true),
+    )
+    .await?;
+
+  let log_embed = BloomBotEmbed::new()
+    .title("Streaks Recalculated")
+    .description(description)
+    .footer(
+      CreateEmbedFooter::new(format!(
+        "Recalculated by {} ({})",
+        ctx.author().name,
+        ctx.author().id
+      ))
+      .icon_url(ctx.author().avatar_url().unwrap_or_default()),
+    );
+
+  ChannelId::new(CHANNELS.bloomlogs)
+    .send_message(ctx, CreateMessage::new().embed(log_embed))
+    .await?;
+
+  Ok(())
+}
+
+/// Check stored stats against meditation entries
+///
+/// Cross-checks stored streaks, leaderboards, and time and streak roles against meditation entries, reporting any which have drifted, such as stale longest streaks or streaks of users who have left. Enable `fix` to recalculate stale streaks, delete streaks of users without entries or who have left, refresh stale leaderboards, and fix roles.
+#[poise::command(
+  slash_command,
+  required_permissions = "ADMINISTRATOR",
+  default_member_permissions = "ADMINISTRATOR"
+)]
+async fn integrity(
+  ctx: Context<'_>,
+  #[description = "Fix any drift found (Defaults to false)"] fix: Option<bool>,
+) -> Result<()> {
+  ctx.defer_ephemeral().await?;
+
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+  let fix = fix.unwrap_or(false);
+
+  let data = ctx.data();
+  let report = integrity::run(ctx.http(), &data.db, &data.role_queue, guild_id, fix).await?;
+
+  ctx
+    .send(CreateReply::default().embed(report.embed()).ephemeral(true))
+    .await?;
+
+  if fix && report.has_drift() {
+    let log_embed = report.embed().footer(
+      CreateEmbedFooter::new(format!(
+        "Fixed by {} ({})",
+        ctx.author().name,
+        ctx.author().id
+      ))
+      .icon_url(ctx.author().avatar_url().unwrap_or_default()),
+    );
+
+    ChannelId::new(CHANNELS.bloomlogs)
+      .send_message(ctx, CreateMessage::new().embed(log_embed))
+      .await?;
+  }
+
+  Ok(())
+}
+
+/// Turn maintenance mode on or off
+///
+/// Turns maintenance mode on or off. During maintenance, only staff can use commands, and other members are told that Bloom is undergoing maintenance, such as during migrations or incident response. Moderator and admin commands keep working. Maintenance applies to every server and stays on through restarts until it's turned off. Can only be used in the main server.
+#[poise::command(
+  slash_command,
+  required_permissions = "ADMINISTRATOR",
+  default_member_permissions = "ADMINISTRATOR"
+)]
+async fn maintenance(
+  ctx: Context<'_>,
+  #[description = "Turn maintenance mode on or off"] mode: Toggle,
+  #[description = "The reason shown to members (Optional)"]
+  #[max_length = 200]
+  reason: Option<String>,
+) -> Result<()> {
+  let data = ctx.data();
+  let emoji = data.emoji.get(ctx.guild_id());
+
+  // Maintenance affects every server, so only the main server's admins can change it
+  if ctx.guild_id() != Some(MEDITATION_MIND) {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} Maintenance mode can only be changed in the main server.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let maintenance = match mode {
+    Toggle::On => Maintenance::new(true, reason),
+    Toggle::Off => Maintenance::new(false, None),
+  };
+  data.maintenance.set(&data.db, maintenance.clone()).await?;
+  info!(
+    "Maintenance mode turned {} by {}",
+    mode.name(),
+    ctx.author().id
+  );
+
+  let status = if maintenance.enabled {
+    "Maintenance mode is on. Only staff can use commands until it's turned off."
+  } else {
+    "Maintenance mode is off. Everyone can use commands again."
+  };
+  ctx
+    .send(
+      CreateReply::default()
+        .content(format!("{} {status}", emoji.mmcheck))
+        .ephemeral(true),
+    )
+    .await?;
+
+  let mut log_embed = BloomBotEmbed::new()
+    .title(if maintenance.enabled {
+      "Maintenance Mode On"
+    } else {
+      "Maintenance Mode Off"
+    })
+    .footer(
+      CreateEmbedFooter::new(format!(
+        "Changed by {} ({})",
+        ctx.author().name,
+        ctx.author().id
+      ))
+      .icon_url(ctx.author().avatar_url().unwrap_or_default()),
+    );
+  if let Some(reason) = &maintenance.reason {
+    log_embed = log_embed.description(format!("**Reason**: {reason}"));
+  }
+
+  ChannelId::new(CHANNELS.bloomlogs)
+    .send_message(ctx, CreateMessage::new().embed(log_embed))
+    .await?;
+
+  Ok(())
+}
+
+/// Fix members' time and streak roles
+///
+/// Gives every member the time and streak roles their meditation has earned, and removes any they haven't, such as after entries have been edited or imported, or roles have been changed by hand. Members who have turned off streaks don't receive streak roles. This also runs daily.
+///
+/// Requires `Manage Roles` permissions.
+#[poise::command(
+  slash_command,
+  rename = "reconcile-roles",
+  required_permissions = "MANAGE_ROLES",
+  default_member_permissions = "MANAGE_ROLES"
+)]
+async fn reconcile_roles(ctx: Context<'_>) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  ctx.defer_ephemeral().await?;
+
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let summary =
+    role_sync::reconcile(ctx.http(), &ctx.data().db, &ctx.data().role_queue, guild_id).await?;
+
+  ctx
+    .send(
+      CreateReply::default()
+        .content(format!(
+          "{} Checked roles for {} members: {} added, {} removed.{}",
+          emoji.mmcheck,
+          summary.checked,
+          summary.added,
+          summary.removed,
+          if summary.failed > 0 {
+            format!(
+              " {} roles couldn't be updated. Please check that Bloom's role is above the time and streak roles.",
+              summary.failed
+            )
+          } else {
+            String::new()
+          }
+        ))
+        .ephemeral(true),
+    )
+    .await?;
+
+  info!(
+    "{} reconciled roles in {guild_id}: {} added, {} removed, {} failed",
+    ctx.author().name,
+    summary.added,
+    summary.removed,
+    summary.failed
+  );
+
+  Ok(())
+}
+
+/// Download a backup of this server's data
+///
+/// Creates a compressed backup of this server's settings, quotes, glossary terms, courses, and tracking data, which can be loaded into a fresh database with `/manage restore`. Large backups are split into parts.
+///
+/// Requires `Administrator` permissions.
+#[poise::command(
+  slash_command,
+  required_permissions = "ADMINISTRATOR",
+  default_member_permissions = "ADMINISTRATOR"
+)]
+async fn backup(ctx: Context<'_>) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  ctx.defer_ephemeral().await?;
+
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let mut backup = Backup::new(guild_id);
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+  for table in BackupTable::ALL {
+    let rows = DatabaseHandler::get_backup_rows(&mut transaction, table, &guild_id).await?;
+    backup.add_table(table, &rows)?;
+  }
+  drop(transaction);
+
+  let compressed = backup.to_gzip()?;
+  let parts: Vec<&[u8]> = compressed.chunks(BACKUP_PART_SIZE).collect();
+  if parts.len() > BACKUP_MAX_PARTS {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} This server's backup is too large to send as attachments. Please back up the database directly.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let counts = backup
+    .row_counts()
+    .iter()
+    .map(|(table, count)| format!("`{}`: {count}", table.as_str()))
+    .collect::<Vec<String>>()
+    .join("\n");
+  let filename = format!(
+    "bloom-backup-{guild_id}-{}.json.gz",
+    backup.created_at.format("%Y%m%d-%H%M%S")
+  );
+
+  for (index, part) in parts.iter().enumerate() {
+    let (name, content) = if parts.len() == 1 {
+      (
+        filename.clone(),
+        format!("{} **Backup created**\n{counts}", emoji.mmcheck),
+      )
+    } else {
+      (
+        format!("{filename}.part{}", index + 1),
+        if index == 0 {
+          format!(
+            "{} **Backup created** in {} parts. Attach them in order when restoring.\n{counts}",
+            emoji.mmcheck,
+            parts.len()
+          )
+        } else {
+          format!("Part {} of {}", index + 1, parts.len())
+        },
+      )
+    };
+
+    ctx
+      .send(
+        CreateReply::default()
+          .content(content)
+          .attachment(CreateAttachment::bytes(part.to_vec(), name))
+          .ephemeral(true),
+      )
+      .await?;
+  }
+
+  info!(
+    "Created backup of guild {guild_id} ({} bytes in {} parts) for {}",
+    compressed.len(),
+    parts.len(),
+    ctx.author().id
+  );
+
+  Ok(())
+}
+
+/// Replaces a user ID with a pseudonym, so that a member's entries can be grouped without
+/// revealing who they are. The pseudonym depends on `salt`, so it can't be found by hashing
+/// known user IDs.
+fn pseudonym(salt: &[u8], user_id: UserId) -> String {
+  let mut hasher = Sha256::new();
+  hasher.update(salt);
+  hasher.update(user_id.to_string().as_bytes());
+  hex::encode(&hasher.finalize()[..8])
+}
+
+/// Writes meditation entries as CSV, with members replaced by pseudonyms and times rounded
+/// down to the hour.
+fn anonymized_csv(entries: &[Meditation], salt: &[u8]) -> Result<Vec<u8>> {
+  let mut wtr = WriterBuilder::new().from_writer(vec![]);
+  wtr.write_record(["participant", "occurred_at", "minutes", "seconds"])?;
+  for entry in entries {
+    let occurred_at = entry
+      .occurred_at
+      .duration_trunc(TimeDelta::hours(1))
+      .unwrap_or(entry.occurred_at);
+    wtr.write_record([
+      pseudonym(salt, entry.user_id),
+      occurred_at.to_rfc3339_opts(SecondsFormat::Secs, true),
+      entry.minutes.to_string(),
+      entry.seconds.to_string(),
+    ])?;
+  }
+
+  Ok(wtr.into_inner()?)
+}
+
+/// Download an anonymized dataset of this server's meditation
+///
+/// Creates a CSV file of this server's meditation entries for community research, with each member replaced by a pseudonym and times rounded down to the hour. Pseudonyms are different in every export, so exports can't be linked to each other.
+///
+/// Members with fewer sessions than the minimum are left out, so that members who rarely meditate can't be singled out. Members whose stats are private are always left out.
+///
+/// Requires `Administrator` permissions.
+#[poise::command(
+  slash_command,
+  rename = "export-anon",
+  required_permissions = "ADMINISTRATOR",
+  default_member_permissions = "ADMINISTRATOR"
+)]
+async fn export_anon(
+  ctx: Context<'_>,
+  #[description = "Leave out members with fewer sessions than this (Defaults to 10)"]
+  #[min = 2]
+  #[max = 1000]
+  min_sessions: Option<i64>,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  ctx.defer_ephemeral().await?;
+
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+  let min_sessions = min_sessions.unwrap_or(ANON_EXPORT_MIN_SESSIONS);
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+  let entries =
+    DatabaseHandler::get_anonymizable_meditation(&mut transaction, &guild_id, min_sessions).await?;
+  let all_members = DatabaseHandler::get_meditating_users(&mut transaction, &guild_id)
+    .await?
+    .len();
+  drop(transaction);
+
+  let members = entries
+    .iter()
+    .map(|entry| entry.user_id)
+    .collect::<HashSet<_>>()
+    .len();
+  if members == 0 {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} No members have at least {min_sessions} sessions and public stats, so there's nothing to export.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let salt: [u8; 32] = rand::thread_rng().gen();
+  let csv = anonymized_csv(&entries, &salt)?;
+  if csv.len() > ANON_EXPORT_MAX_SIZE {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} This server's dataset is too large to send as an attachment. Try a higher minimum number of sessions.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let filename = format!(
+    "bloom-anon-{guild_id}-{}.csv",
+    Utc::now().format("%Y%m%d-%H%M%S")
+  );
+  ctx
+    .send(
+      CreateReply::default()
+        .content(format!(
+          "{} **Dataset created** with {} entries from {members} members. {} members with fewer than {min_sessions} sessions or private stats were left out.",
+          emoji.mmcheck,
+          entries.len(),
+          all_members.saturating_sub(members)
+        ))
+        .attachment(CreateAttachment::bytes(csv, filename))
+        .ephemeral(true),
+    )
+    .await?;
+
+  info!(
+    "Created anonymized dataset of guild {guild_id} ({} entries, minimum {min_sessions} sessions) for {}",
+    entries.len(),
+    ctx.author().id
+  );
+
+  Ok(())
+}
+
+/// Restore this server's data from a backup
+///
+/// Loads a backup created with `/manage backup` into the database. Existing data is kept, and only missing rows are added, so it's safe to restore into a database which already has some of the server's data. Attach the parts of a large backup in order.
+///
+/// Requires `Administrator` permissions.
+#[poise::command(
+  slash_command,
+  required_permissions = "ADMINISTRATOR",
+  default_member_permissions = "ADMINISTRATOR"
+)]
+async fn restore(
+  ctx: Context<'_>,
+  #[description = "The backup file, or its first part"] file: Attachment,
+  #[description = "The second part of the backup"] part2: Option<Attachment>,
+  #[description = "The third part of the backup"] part3: Option<Attachment>,
+  #[description = "The fourth part of the backup"] part4: Option<Attachment>,
+  #[description = "The fifth part of the backup"] part5: Option<Attachment>,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  ctx.defer_ephemeral().await?;
+
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let mut compressed = Vec::new();
+  for attachment in [Some(file), part2, part3, part4, part5]
+    .into_iter()
+    .flatten()
+  {
+    if attachment.size as usize > BACKUP_PART_SIZE {
+      ctx
+        .send(
+          CreateReply::default()
+            .content(format!(
+              "{} `{}` is larger than a backup part.",
+              emoji.mminfo, attachment.filename
+            ))
+            .ephemeral(true),
+        )
+        .await?;
+      return Ok(());
+    }
+
+    match attachment.download().await {
+      Ok(content) => compressed.extend(content),
+      Err(e) => {
+        info!("Error downloading attachment for restore: {e:?}");
+        ctx
+          .send(
+            CreateReply::default()
+              .content(format!(
+                "{} Unable to download `{}`.",
+                emoji.mminfo, attachment.filename
+              ))
+              .ephemeral(true),
+          )
+          .await?;
+        return Ok(());
+      }
+    }
+  }
+
+  let backup = match Backup::from_gzip(&compressed) {
+    Ok(backup) => backup,
+    Err(e) => {
+      info!("Failed to read backup: {e:?}");
+      ctx
+        .send(
+          CreateReply::default()
+            .content(format!(
+              "{} **Unable to read backup.**\n-# {e:#}. If the backup has several parts, please attach them all, in order.",
+              emoji.mminfo
+            ))
+            .ephemeral(true),
+        )
+        .await?;
+      return Ok(());
+    }
+  };
+  drop(compressed);
+
+  if backup.guild_id != guild_id {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} This backup is for a different server ({}).",
+            emoji.mminfo, backup.guild_id
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+  let mut restored = Vec::new();
+  for table in BackupTable::ALL {
+    let Some(rows) = backup.table(table) else {
+      continue;
+    };
+    let count =
+      DatabaseHandler::restore_backup_rows(&mut transaction, table, &guild_id, &rows).await?;
+    restored.push(format!("`{}`: {count}", table.as_str()));
+  }
+
+  database::commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(format!(
+      "{} **Backup from {} restored.** Rows added:\n{}",
+      emoji.mmcheck,
+      backup.created_at.format("%Y-%m-%d %H:%M UTC"),
+      restored.join("\n")
+    )),
+    Visibility::Ephemeral,
+  )
+  .await?;
+
+  // Settings, emoji, and features may have been restored, so they're loaded again
+  let data = ctx.data();
+  data.settings.invalidate(guild_id);
+  data.emoji.reload(&data.db, guild_id).await?;
+  data.features.reload(&data.db, guild_id).await?;
+
+  let log_embed = BloomBotEmbed::new()
+    .title("Backup Restored")
+    .description(format!(
+      "Backup from {} restored.\n{}",
+      backup.created_at.format("%Y-%m-%d %H:%M UTC"),
+      restored.join("\n")
+    ))
+    .footer(
+      CreateEmbedFooter::new(format!(
+        "Restored by {} ({})",
+        ctx.author().name,
+        ctx.author().id
+      ))
+      .icon_url(ctx.author().avatar_url().unwrap_or_default()),
+    );
+
+  ChannelId::new(CHANNELS.bloomlogs)
+    .send_message(ctx, CreateMessage::new().embed(log_embed))
+    .await?;
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use sqlx::PgPool;
+
+  use super::*;
+  use crate::commands::helpers::discord::mock::MockDiscord;
+
+  #[test]
+  fn test_parse_backfill_csv() -> Result<()> {
+    let now = NaiveDate::from_ymd_opt(2024, 10, 20)
+      .with_context(|| "Invalid date")?
+      .and_time(NaiveTime::MIN)
+      .and_utc();
+
+    let content = "user_id,date,minutes\n\
+      123,2024-10-01,30\n\
+      456, 2024-10-02T18:30:00+02:00 ,15\n\
+      abc,2024-10-01,30\n\
+      0,2024-10-01,30\n\
+      123,10/01/2024,30\n\
+      123,2025-01-01,30\n\
+      123,2024-10-01,0\n\
+      123,2024-10-01,2000\n\
+      123,2024-10-01\n";
+
+    let (entries, invalid) = parse_backfill_csv(content.as_bytes(), now)?;
+
+    assert_eq!(
+      entries,
+      vec![
+        BackfillEntry {
+          user_id: UserId::new(123),
+          occurred_at: NaiveDate::from_ymd_opt(2024, 10, 1)
+            .with_context(|| "Invalid date")?
+            .and_time(NaiveTime::MIN)
+            .and_utc(),
+          minutes: 30,
+        },
+        BackfillEntry {
+          user_id: UserId::new(456),
+          occurred_at: NaiveDate::from_ymd_opt(2024, 10, 2)
+            .with_context(|| "Invalid date")?
+            .and_hms_opt(16, 30, 0)
+            .with_context(|| "Invalid time")?
+            .and_utc(),
+          minutes: 15,
+        },
+      ]
+    );
+    assert_eq!(invalid.len(), 7);
+    assert!(invalid[0].starts_with("Line 4:"));
+    assert!(invalid[6].starts_with("Line 10:"));
+
+    assert!(parse_backfill_csv(b"user,date,minutes\n123,2024-10-01,30\n", now).is_err());
+
+    Ok(())
+  }
+
+  #[test]
+  fn test_anonymized_csv() -> Result<()> {
+    let occurred_at = DateTime::parse_from_rfc3339("2024-10-01T06:45:12Z")?.with_timezone(&Utc);
+    let entries = [
+      Meditation::new(GuildId::new(1), UserId::new(123), 30, 15, &occurred_at),
+      Meditation::new(GuildId::new(1), UserId::new(456), 10, 0, &occurred_at),
+      Meditation::new(GuildId::new(1), UserId::new(123), 5, 0, &occurred_at),
+    ];
+
+    let csv = String::from_utf8(anonymized_csv(&entries, b"salt")?)?;
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines[0], "participant,occurred_at,minutes,seconds");
+
+    let participant = pseudonym(b"salt", UserId::new(123));
+    assert_eq!(
+      lines[1],
+      format!("{participant},2024-10-01T06:00:00Z,30,15")
+    );
+    assert!(lines[3].starts_with(&participant));
+    assert!(!lines[2].starts_with(&participant));
+
+    // Pseudonyms differ between exports
+    assert_ne!(participant, pseudonym(b"pepper", UserId::new(123)));
+
+    Ok(())
+  }
+
+  #[sqlx::test(
+    migrations = "core/migrations",
+    fixtures(path = "../../core/src/fixtures", scripts("meditation"))
+  )]
+  async fn test_migrate_user(pool: PgPool) -> Result<()> {
+    let db = DatabaseHandler::from_pool(pool);
+    let guild_id = GuildId::new(123u64);
+    let old_user = UserId::new(124u64);
+    let new_user = UserId::new(123u64);
+    let entries = DataType::MeditationEntries;
+    let keep = KeepSettings::New;
+
+    let discord = MockDiscord::new("manage");
+    let log = migrate_user(&discord, &db, guild_id, new_user, new_user, &entries, &keep).await?;
+    assert_eq!(log, None);
+    assert!(discord.replies()[0].0.contains("must be different"));
+    assert!(discord.prompts().is_empty());
+
+    // Nothing is migrated unless the summary is confirmed
+    let discord = MockDiscord::new("manage");
+    let log = migrate_user(&discord, &db, guild_id, old_user, new_user, &entries, &keep).await?;
+    assert_eq!(log, None);
+    let prompts = discord.prompts();
+    assert_eq!(prompts.len(), 1);
+    assert!(prompts[0].contains("1 entries (20 minutes) will be combined with the 2 entries"));
+
+    let mut transaction = db.start_transaction().await?;
+    assert_eq!(
+      DatabaseHandler::get_user_meditation_count(&mut transaction, &guild_id, &old_user).await?,
+      1
+    );
+    DatabaseHandler::rollback_transaction(transaction).await?;
+
+    let discord = MockDiscord {
+      answer: Some(true),
+      ..MockDiscord::new("manage")
+    };
+    let log = migrate_user(&discord, &db, guild_id, old_user, new_user, &entries, &keep).await?;
+    assert!(log.is_some_and(|log| log.contains("**Merged Totals**: 3 entries")));
+
+    let mut transaction = db.start_transaction().await?;
+    assert_eq!(
+      DatabaseHandler::get_user_meditation_count(&mut transaction, &guild_id, &old_user).await?,
+      0
+    );
+    assert_eq!(
+      DatabaseHandler::get_user_meditation_count(&mut transaction, &guild_id, &new_user).await?,
+      3
+    );
+
+    Ok(())
+  }
 }
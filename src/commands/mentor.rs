@@ -0,0 +1,263 @@
+use anyhow::{Context as AnyhowContext, Result};
+use chrono::Utc;
+use poise::serenity_prelude::CreateEmbedAuthor;
+use poise::{ChoiceParameter, CreateReply};
+
+use crate::commands::helpers::common::Visibility;
+use crate::commands::helpers::database::{self, MessageType};
+use crate::commands::helpers::mentorship::{self, CHECK_IN_DAYS};
+use crate::commands::helpers::time::{self, MinusOffsetChoice, PlusOffsetChoice};
+use crate::config::BloomBotEmbed;
+use crate::data::mentorship::{MentorRole, MentorSignup};
+use crate::database::DatabaseHandler;
+use crate::Context;
+
+/// Commands for the mentorship program
+///
+/// Sign up to be matched with a mentor or mentee, check in with your match, or see your current signup and mentorship.
+#[poise::command(
+  slash_command,
+  category = "Utilities",
+  subcommands("signup", "withdraw", "checkin", "status"),
+  subcommand_required,
+  guild_only
+)]
+#[allow(clippy::unused_async)]
+pub async fn mentor(_: Context<'_>) -> Result<()> {
+  Ok(())
+}
+
+/// Sign up for the mentorship program
+///
+/// Sign up as a mentor or mentee. Staff periodically match members based on shared interests and time zones, and you'll both be introduced by DM when you're matched.
+///
+/// Your time zone defaults to the UTC offset set with `/customize offset`. Signing up again updates your existing signup.
+#[poise::command(slash_command)]
+async fn signup(
+  ctx: Context<'_>,
+  #[description = "Sign up as a mentor or mentee"] role: MentorRole,
+  #[description = "What you'd like to focus on, separated by commas (e.g., metta, sleep, zen)"]
+  #[max_length = 200]
+  interests: String,
+  #[description = "Specify a UTC offset for a Western Hemisphere time zone"]
+  #[rename = "western_hemisphere_offset"]
+  minus_offset: Option<MinusOffsetChoice>,
+  #[description = "Specify a UTC offset for an Eastern Hemisphere time zone"]
+  #[rename = "eastern_hemisphere_offset"]
+  plus_offset: Option<PlusOffsetChoice>,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+  let user_id = ctx.author().id;
+
+  let interests = interests.trim().to_owned();
+  if interests.is_empty() {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} Please share at least one interest, so we can find you a good match.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  let tracking_profile =
+    DatabaseHandler::get_tracking_profile(&mut transaction, &guild_id, &user_id)
+      .await?
+      .unwrap_or_default();
+  let Ok(utc_offset) =
+    time::offset_from_choice(minus_offset, plus_offset, tracking_profile.utc_offset)
+  else {
+    ctx
+      .send(
+        CreateReply::default()
+          .content("Cannot determine UTC offset based on the choice selected.".to_string())
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  };
+
+  let signup = MentorSignup::new(guild_id, user_id, role, interests, utc_offset);
+  DatabaseHandler::add_mentor_signup(&mut transaction, &signup).await?;
+
+  let matched = DatabaseHandler::get_active_mentorship(&mut transaction, &guild_id, &user_id)
+    .await?
+    .is_some();
+
+  database::commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(format!(
+      "{} You're signed up as a **{}** ({}).{}",
+      emoji.mmcheck,
+      role.name(),
+      mentorship::format_offset(utc_offset),
+      if matched {
+        " You'll be considered for a new match once your current mentorship ends."
+      } else {
+        " You'll be introduced to your match by DM, so please make sure you accept DMs from server members."
+      }
+    )),
+    Visibility::Ephemeral,
+  )
+  .await?;
+
+  Ok(())
+}
+
+/// Withdraw from the mentorship program
+///
+/// Removes your signup, so you won't be matched again. Any current mentorship continues until staff end it.
+#[poise::command(slash_command)]
+async fn withdraw(ctx: Context<'_>) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  if DatabaseHandler::remove_mentor_signup(&mut transaction, &guild_id, &ctx.author().id).await?
+    == 0
+  {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} You're not signed up for the mentorship program.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  database::commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(format!(
+      "{} You've withdrawn from the mentorship program and won't be matched again.",
+      emoji.mmcheck
+    )),
+    Visibility::Ephemeral,
+  )
+  .await?;
+
+  Ok(())
+}
+
+/// Check in with your mentorship match
+///
+/// Let us know you've met with your mentor or mentee recently. Check-in reminders are sent by DM when it's been two weeks since your last check-in.
+#[poise::command(slash_command)]
+async fn checkin(ctx: Context<'_>) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  if DatabaseHandler::check_in_mentorship(
+    &mut transaction,
+    &guild_id,
+    &ctx.author().id,
+    &Utc::now(),
+  )
+  .await?
+    == 0
+  {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} You're not in an active mentorship. Use `/mentor signup` to be matched.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  database::commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(format!(
+      "{} Thanks for checking in! We'll send you both a reminder if {CHECK_IN_DAYS} days go by without another check-in.",
+      emoji.mmcheck
+    )),
+    Visibility::Ephemeral,
+  )
+  .await?;
+
+  Ok(())
+}
+
+/// Show your mentorship status
+///
+/// Shows your mentorship program signup and your current match, if you have one.
+#[poise::command(slash_command)]
+async fn status(ctx: Context<'_>) -> Result<()> {
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+  let user_id = ctx.author().id;
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+  let signup = DatabaseHandler::get_mentor_signup(&mut transaction, &guild_id, &user_id).await?;
+  let active =
+    DatabaseHandler::get_active_mentorship(&mut transaction, &guild_id, &user_id).await?;
+  drop(transaction);
+
+  let signup = signup.map_or_else(
+    || "Not signed up".to_owned(),
+    |signup| {
+      format!(
+        "Signed up as a **{}** ({})\n-# Interests: {}",
+        signup.role.name(),
+        mentorship::format_offset(signup.utc_offset),
+        signup.interests
+      )
+    },
+  );
+  let active = active.map_or_else(
+    || "Not matched".to_owned(),
+    |active| {
+      let last_check_in = active.last_check_in.map_or_else(
+        || "No check-ins yet".to_owned(),
+        |checked_in_at| format!("Last check-in <t:{}:R>", checked_in_at.timestamp()),
+      );
+      if active.mentor_id == user_id {
+        format!("Mentoring <@{}>\n-# {last_check_in}", active.mentee_id)
+      } else {
+        format!("Mentored by <@{}>\n-# {last_check_in}", active.mentor_id)
+      }
+    },
+  );
+
+  ctx
+    .send(
+      CreateReply::default()
+        .embed(
+          BloomBotEmbed::new()
+            .author(CreateEmbedAuthor::new("Mentorship Program").icon_url(ctx.author().face()))
+            .field("Signup", signup, false)
+            .field("Current Mentorship", active, false),
+        )
+        .ephemeral(true),
+    )
+    .await?;
+
+  Ok(())
+}
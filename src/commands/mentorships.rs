@@ -0,0 +1,196 @@
+use anyhow::{Context as AnyhowContext, Result};
+use log::info;
+use poise::serenity_prelude::CreateMessage;
+use poise::CreateReply;
+
+use crate::commands::helpers::common::Visibility;
+use crate::commands::helpers::database::{self, MessageType};
+use crate::commands::helpers::discord::Discord;
+use crate::commands::helpers::mentorship;
+use crate::commands::helpers::pagination::{PageRowRef, PageType, Paginator};
+use crate::config::ENTRIES_PER_PAGE;
+use crate::data::mentorship::Mentorship;
+use crate::database::DatabaseHandler;
+use crate::Context;
+
+/// The most proposed pairs to list when asking staff to confirm a match.
+const MAX_LISTED_PAIRS: usize = 15;
+
+/// Commands for managing the mentorship program
+///
+/// Commands to match members who have signed up with `/mentor signup`, list active mentorships, or end a mentorship.
+///
+/// Requires `Manage Messages` permissions.
+#[poise::command(
+  slash_command,
+  required_permissions = "MANAGE_MESSAGES",
+  default_member_permissions = "MANAGE_MESSAGES",
+  category = "Moderator Commands",
+  subcommands("match_members", "list", "end"),
+  subcommand_required,
+  guild_only
+)]
+#[allow(clippy::unused_async)]
+pub async fn mentorships(_: Context<'_>) -> Result<()> {
+  Ok(())
+}
+
+/// Match mentors and mentees
+///
+/// Proposes pairs from members who have signed up and aren't in an active mentorship, based on shared interests and time zones. Once confirmed, each pair is introduced by DM.
+#[poise::command(slash_command, rename = "match")]
+async fn match_members(ctx: Context<'_>) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  let signups = DatabaseHandler::get_unmatched_mentor_signups(&mut transaction, &guild_id).await?;
+  let pairs = mentorship::propose_pairs(&signups);
+
+  if pairs.is_empty() {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} There aren't enough unmatched mentors and mentees to propose any pairs.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  // Keep the prompt within Discord's message length limit
+  let mut proposal = pairs
+    .iter()
+    .take(MAX_LISTED_PAIRS)
+    .map(|(mentor, mentee)| format!("- {}", mentorship::describe_pair(mentor, mentee)))
+    .collect::<Vec<_>>()
+    .join("\n");
+  if pairs.len() > MAX_LISTED_PAIRS {
+    proposal.push_str(&format!("\n- …and {} more", pairs.len() - MAX_LISTED_PAIRS));
+  }
+  let unmatched = signups.len() - pairs.len() * 2;
+  let prompt = format!(
+    "Proposed pairs:\n{proposal}\n\n{unmatched} {} will remain unmatched. Match these pairs and introduce them by DM?",
+    if unmatched == 1 { "member" } else { "members" }
+  );
+
+  if ctx.confirm(prompt, "Confirmed.".to_owned()).await? != Some(true) {
+    return Ok(());
+  }
+
+  for (mentor, mentee) in &pairs {
+    let mentorship = Mentorship::new(guild_id, mentor.user_id, mentee.user_id, ctx.author().id);
+    DatabaseHandler::add_mentorship(&mut transaction, &mentorship).await?;
+  }
+  DatabaseHandler::commit_transaction(transaction).await?;
+
+  let server = guild_id
+    .name(ctx)
+    .unwrap_or_else(|| "the server".to_owned());
+  let mut undelivered = Vec::new();
+  for (mentor, mentee) in &pairs {
+    let introduction = mentorship::introduction(&server, mentor, mentee);
+    for user_id in [mentor.user_id, mentee.user_id] {
+      if let Err(e) = user_id
+        .direct_message(ctx, CreateMessage::new().embed(introduction.clone()))
+        .await
+      {
+        info!("Failed to send mentorship introduction to user {user_id}: {e}");
+        undelivered.push(format!("<@{user_id}>"));
+      }
+    }
+  }
+
+  let mut message = format!(
+    "{} Matched {} {}.",
+    emoji.mmcheck,
+    pairs.len(),
+    if pairs.len() == 1 { "pair" } else { "pairs" }
+  );
+  if !undelivered.is_empty() {
+    message.push_str(&format!(
+      " Introductions couldn't be sent to {}, so please introduce them another way.",
+      undelivered.join(", ")
+    ));
+  }
+
+  ctx
+    .send(CreateReply::default().content(message).ephemeral(true))
+    .await?;
+
+  Ok(())
+}
+
+/// List active mentorships
+///
+/// Lists active mentorships, with when each was matched and when its members last checked in.
+#[poise::command(slash_command)]
+async fn list(
+  ctx: Context<'_>,
+  #[description = "The page to show"] page: Option<usize>,
+) -> Result<()> {
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+  let mentorships = DatabaseHandler::get_active_mentorships(&mut transaction, &guild_id).await?;
+  drop(transaction);
+
+  let entries: Vec<PageRowRef> = mentorships
+    .iter()
+    .map(|mentorship| mentorship as PageRowRef)
+    .collect();
+
+  Paginator::new("Active Mentorships", &entries, ENTRIES_PER_PAGE.default)
+    .paginate(ctx, page, PageType::Standard, Visibility::Ephemeral)
+    .await?;
+
+  Ok(())
+}
+
+/// End a mentorship
+///
+/// Ends an active mentorship. Both members return to the pool for future matches, unless they've withdrawn with `/mentor withdraw`.
+#[poise::command(slash_command)]
+async fn end(
+  ctx: Context<'_>,
+  #[description = "The ID of the mentorship to end"] id: String,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  if DatabaseHandler::end_mentorship(&mut transaction, &guild_id, id.trim()).await? == 0 {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} No active mentorship found with that ID.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  database::commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(format!("{} Mentorship has been ended.", emoji.mmcheck)),
+    Visibility::Ephemeral,
+  )
+  .await?;
+
+  Ok(())
+}
@@ -1,62 +1,123 @@
 mod add;
+mod add_multi;
+mod announce;
 mod bookmark;
 mod challenge;
+mod checkin;
 mod coffee;
 mod community_sit;
 mod complete;
+mod config;
 mod course;
 mod courses;
 mod customize;
+mod dedicate;
+mod dedications;
+mod directory;
 mod erase;
+mod event;
+mod feed;
 mod glossary;
+mod goal;
 mod hello;
 mod help;
 pub mod helpers;
 mod import;
+mod intention;
 mod keys;
+mod link;
 mod manage;
+mod mentor;
+mod mentorships;
+mod moderation;
 mod pick_winner;
 mod ping;
+mod poll;
+mod quick_add;
 mod quote;
 mod quotes;
+mod raffle;
 mod recent;
 mod remove_entry;
 mod report_message;
+mod retreat;
+mod sit_now;
+mod sleep;
 pub mod stats;
 mod streak;
+mod study_group;
 mod suggest;
+mod suggestions;
+mod sutta;
+mod talk;
 mod terms;
+mod ticket;
+mod token;
 mod uptime;
+mod warn;
+mod watchlist;
 mod whatis;
 
 pub use add::add;
+pub use add_multi::add_multi;
+pub use announce::announce;
 pub use bookmark::add_bookmark;
 pub use bookmark::bookmark;
 pub use challenge::challenge;
+pub use checkin::checkin;
 pub use coffee::coffee;
 pub use community_sit::community_sit;
 pub use complete::complete;
+pub use config::config;
 pub use course::course;
 pub use courses::courses;
 pub use customize::customize;
+pub use dedicate::dedicate;
+pub use dedications::dedications;
+pub use directory::directory;
 pub use erase::erase;
 pub use erase::erase_message;
+pub use event::event;
+pub use feed::feed;
 pub use glossary::glossary;
+pub use goal::goal;
 pub use hello::hello;
 pub use help::help;
 pub use import::import;
+pub use intention::intention;
 pub use keys::keys;
+pub use link::link;
 pub use manage::manage;
+pub use mentor::mentor;
+pub use mentorships::mentorships;
+pub use moderation::moderation;
 pub use pick_winner::pick_winner;
 pub use ping::ping;
+pub use poll::poll;
+pub use quick_add::quick_add;
 pub use quote::quote;
 pub use quotes::quotes;
+pub use raffle::raffle;
 pub use recent::recent;
 pub use remove_entry::remove_entry;
 pub use report_message::report_message;
+pub use retreat::retreat;
+pub use sit_now::log_session;
+pub use sit_now::sit_now;
+pub use sleep::sleep;
 pub use stats::stats;
 pub use streak::streak;
+pub use study_group::study_group;
 pub use suggest::suggest;
+pub use suggestions::suggestions;
+pub use sutta::sutta;
+pub use talk::talk;
 pub use terms::terms;
+pub use ticket::ticket;
+pub use token::token;
 pub use uptime::uptime;
+pub use warn::warn;
+pub use warn::warnings;
+pub use watchlist::watchlist;
+pub use whatis::define_terms;
 pub use whatis::whatis;
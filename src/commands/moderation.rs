@@ -0,0 +1,372 @@
+use anyhow::{Context as AnyhowContext, Result};
+use poise::serenity_prelude::{
+  builder::*, ChannelId, GuildChannel, Mentionable, PermissionOverwrite,
+};
+use poise::serenity_prelude::{PermissionOverwriteType, Permissions, RoleId};
+use poise::{ChoiceParameter, CreateReply};
+
+use crate::config::{BloomBotEmbed, CHANNELS};
+use crate::Context;
+
+/// The permissions denied to `@everyone` while a channel is locked.
+const LOCKED_PERMISSIONS: Permissions = Permissions::SEND_MESSAGES
+  .union(Permissions::SEND_MESSAGES_IN_THREADS)
+  .union(Permissions::CREATE_PUBLIC_THREADS);
+
+/// The slowmode intervals offered by Discord's channel settings.
+#[derive(ChoiceParameter, Clone, Copy)]
+enum Slowmode {
+  Off,
+  #[name = "5 seconds"]
+  FiveSeconds,
+  #[name = "10 seconds"]
+  TenSeconds,
+  #[name = "15 seconds"]
+  FifteenSeconds,
+  #[name = "30 seconds"]
+  ThirtySeconds,
+  #[name = "1 minute"]
+  OneMinute,
+  #[name = "2 minutes"]
+  TwoMinutes,
+  #[name = "5 minutes"]
+  FiveMinutes,
+  #[name = "10 minutes"]
+  TenMinutes,
+  #[name = "15 minutes"]
+  FifteenMinutes,
+  #[name = "30 minutes"]
+  ThirtyMinutes,
+  #[name = "1 hour"]
+  OneHour,
+  #[name = "2 hours"]
+  TwoHours,
+  #[name = "6 hours"]
+  SixHours,
+}
+
+impl Slowmode {
+  fn seconds(self) -> u16 {
+    match self {
+      Self::Off => 0,
+      Self::FiveSeconds => 5,
+      Self::TenSeconds => 10,
+      Self::FifteenSeconds => 15,
+      Self::ThirtySeconds => 30,
+      Self::OneMinute => 60,
+      Self::TwoMinutes => 120,
+      Self::FiveMinutes => 300,
+      Self::TenMinutes => 600,
+      Self::FifteenMinutes => 900,
+      Self::ThirtyMinutes => 1800,
+      Self::OneHour => 3600,
+      Self::TwoHours => 7200,
+      Self::SixHours => 21600,
+    }
+  }
+}
+
+/// Returns the channel's permission overwrites with [`LOCKED_PERMISSIONS`] denied to (or
+/// no longer denied to) the `@everyone` role. Other overwrites are left as they are.
+/// Returns [`None`] if the channel is already locked or unlocked.
+fn with_lock(
+  overwrites: &[PermissionOverwrite],
+  everyone: RoleId,
+  locked: bool,
+) -> Option<Vec<PermissionOverwrite>> {
+  let mut overwrites = overwrites.to_vec();
+  let position = overwrites
+    .iter()
+    .position(|overwrite| overwrite.kind == PermissionOverwriteType::Role(everyone));
+
+  let Some(position) = position else {
+    if !locked {
+      return None;
+    }
+    overwrites.push(PermissionOverwrite {
+      allow: Permissions::empty(),
+      deny: LOCKED_PERMISSIONS,
+      kind: PermissionOverwriteType::Role(everyone),
+    });
+    return Some(overwrites);
+  };
+
+  let overwrite = &mut overwrites[position];
+  if overwrite.deny.contains(Permissions::SEND_MESSAGES) == locked {
+    return None;
+  }
+
+  if locked {
+    overwrite.allow.remove(LOCKED_PERMISSIONS);
+    overwrite.deny.insert(LOCKED_PERMISSIONS);
+  } else {
+    overwrite.deny.remove(LOCKED_PERMISSIONS);
+  }
+
+  Some(overwrites)
+}
+
+/// Returns the chosen channel, or the channel the command was used in.
+async fn target_channel(ctx: Context<'_>, channel: Option<GuildChannel>) -> Result<GuildChannel> {
+  match channel {
+    Some(channel) => Ok(channel),
+    None => ctx
+      .guild_channel()
+      .await
+      .with_context(|| "Failed to retrieve channel from context"),
+  }
+}
+
+/// Logs a moderation action in the [`CHANNELS.logs`][logs] channel.
+///
+/// [logs]: crate::config::CHANNELS
+async fn log_action(
+  ctx: Context<'_>,
+  title: &str,
+  channel: &GuildChannel,
+  details: Option<String>,
+  reason: &str,
+) -> Result<()> {
+  let mut embed =
+    BloomBotEmbed::new()
+      .title(title)
+      .field("Channel", channel.mention().to_string(), true);
+  if let Some(details) = details {
+    embed = embed.field("Setting", details, true);
+  }
+  embed = embed.field("Reason", reason, false).footer(
+    CreateEmbedFooter::new(format!("By {} ({})", ctx.author().name, ctx.author().id))
+      .icon_url(ctx.author().avatar_url().unwrap_or_default()),
+  );
+
+  ChannelId::new(CHANNELS.logs)
+    .send_message(ctx, CreateMessage::new().embed(embed))
+    .await?;
+
+  Ok(())
+}
+
+/// Channel moderation shortcuts
+///
+/// Commands to set slowmode and to lock or unlock a channel during an incident. Each action is logged with its reason.
+///
+/// Requires `Manage Messages` permissions.
+#[poise::command(
+  slash_command,
+  rename = "mod",
+  required_permissions = "MANAGE_MESSAGES",
+  default_member_permissions = "MANAGE_MESSAGES",
+  category = "Moderator Commands",
+  subcommands("slowmode", "lock", "unlock"),
+  subcommand_required,
+  guild_only
+)]
+#[allow(clippy::unused_async)]
+pub async fn moderation(_: Context<'_>) -> Result<()> {
+  Ok(())
+}
+
+/// Set slowmode for a channel
+///
+/// Sets how long members must wait between messages in a channel. Defaults to the current channel.
+#[poise::command(slash_command)]
+async fn slowmode(
+  ctx: Context<'_>,
+  #[description = "How long members must wait between messages"] duration: Slowmode,
+  #[description = "The reason for the change"]
+  #[max_length = 512] // Max length for audit log reason
+  reason: String,
+  #[description = "The channel to set slowmode for (defaults to this channel)"]
+  #[channel_types("Text")]
+  channel: Option<GuildChannel>,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let channel = target_channel(ctx, channel).await?;
+
+  if channel.rate_limit_per_user.unwrap_or(0) == duration.seconds() {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} Slowmode in {} is already set to {}.",
+            emoji.mminfo,
+            channel.mention(),
+            duration.name().to_lowercase()
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  channel
+    .id
+    .edit(
+      ctx,
+      EditChannel::new()
+        .rate_limit_per_user(duration.seconds())
+        .audit_log_reason(&reason),
+    )
+    .await?;
+
+  let (title, response) = match duration {
+    Slowmode::Off => (
+      "Slowmode Disabled",
+      format!("Slowmode has been turned off in {}.", channel.mention()),
+    ),
+    _ => (
+      "Slowmode Enabled",
+      format!(
+        "Slowmode in {} has been set to {}.",
+        channel.mention(),
+        duration.name()
+      ),
+    ),
+  };
+
+  log_action(
+    ctx,
+    title,
+    &channel,
+    Some(duration.name().to_owned()),
+    &reason,
+  )
+  .await?;
+
+  ctx
+    .send(
+      CreateReply::default()
+        .content(format!("{} {response}", emoji.mmcheck))
+        .ephemeral(true),
+    )
+    .await?;
+
+  Ok(())
+}
+
+/// Lock a channel
+///
+/// Stops members from sending messages or starting threads in a channel until it is unlocked. Staff with their own channel permissions can still post. Defaults to the current channel.
+#[poise::command(slash_command)]
+async fn lock(
+  ctx: Context<'_>,
+  #[description = "The reason for locking the channel"]
+  #[max_length = 512] // Max length for audit log reason
+  reason: String,
+  #[description = "The channel to lock (defaults to this channel)"]
+  #[channel_types("Text")]
+  channel: Option<GuildChannel>,
+) -> Result<()> {
+  set_locked(ctx, channel, reason, true).await
+}
+
+/// Unlock a channel
+///
+/// Lets members send messages and start threads in a locked channel again. Defaults to the current channel.
+#[poise::command(slash_command)]
+async fn unlock(
+  ctx: Context<'_>,
+  #[description = "The reason for unlocking the channel"]
+  #[max_length = 512] // Max length for audit log reason
+  reason: String,
+  #[description = "The channel to unlock (defaults to this channel)"]
+  #[channel_types("Text")]
+  channel: Option<GuildChannel>,
+) -> Result<()> {
+  set_locked(ctx, channel, reason, false).await
+}
+
+async fn set_locked(
+  ctx: Context<'_>,
+  channel: Option<GuildChannel>,
+  reason: String,
+  locked: bool,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+  let channel = target_channel(ctx, channel).await?;
+  let state = if locked { "locked" } else { "unlocked" };
+
+  let Some(overwrites) = with_lock(
+    &channel.permission_overwrites,
+    guild_id.everyone_role(),
+    locked,
+  ) else {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} {} is already {state}.",
+            emoji.mminfo,
+            channel.mention()
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  };
+
+  channel
+    .id
+    .edit(
+      ctx,
+      EditChannel::new()
+        .permissions(overwrites)
+        .audit_log_reason(&reason),
+    )
+    .await?;
+
+  let title = if locked {
+    "Channel Locked"
+  } else {
+    "Channel Unlocked"
+  };
+  log_action(ctx, title, &channel, None, &reason).await?;
+
+  ctx
+    .send(
+      CreateReply::default()
+        .content(format!(
+          "{} {} has been {state}.",
+          emoji.mmcheck,
+          channel.mention()
+        ))
+        .ephemeral(true),
+    )
+    .await?;
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_with_lock() {
+    let everyone = RoleId::new(123u64);
+    let staff = PermissionOverwrite {
+      allow: Permissions::SEND_MESSAGES,
+      deny: Permissions::empty(),
+      kind: PermissionOverwriteType::Role(RoleId::new(456u64)),
+    };
+
+    let Some(locked) = with_lock(&[staff.clone()], everyone, true) else {
+      panic!("Expected the channel to be locked");
+    };
+    assert_eq!(locked.len(), 2);
+    assert_eq!(locked[0].allow, Permissions::SEND_MESSAGES);
+    assert_eq!(locked[1].kind, PermissionOverwriteType::Role(everyone));
+    assert!(locked[1].deny.contains(LOCKED_PERMISSIONS));
+    assert!(with_lock(&locked, everyone, true).is_none());
+
+    let Some(unlocked) = with_lock(&locked, everyone, false) else {
+      panic!("Expected the channel to be unlocked");
+    };
+    assert!(unlocked[1].deny.is_empty());
+    assert!(with_lock(&unlocked, everyone, false).is_none());
+    assert!(with_lock(&[staff], everyone, false).is_none());
+  }
+}
@@ -1,14 +1,14 @@
-use std::time::Duration;
-
 use anyhow::{Context as AnyhowContext, Result};
 use chrono::Months as ChronoMonths;
 use chrono::{DateTime, Datelike, NaiveDate, NaiveDateTime, NaiveTime, Utc};
 use futures::StreamExt;
-use poise::serenity_prelude::{builder::*, ButtonStyle};
-use poise::serenity_prelude::{ChannelId, ComponentInteractionCollector, Member, RoleId};
+use poise::serenity_prelude::builder::*;
+use poise::serenity_prelude::{ChannelId, Member, Mentionable, RoleId};
 use poise::{ChoiceParameter, CreateReply};
 
-use crate::config::{BloomBotEmbed, CHANNELS, EMOJI, ROLES};
+use crate::commands::helpers::announcements;
+use crate::commands::helpers::key_redemption::{self, OfferDelivery};
+use crate::config::{CHANNELS, ROLES};
 use crate::database::DatabaseHandler;
 use crate::Context;
 
@@ -35,209 +35,48 @@ async fn finalize_winner(
   minutes: i64,
   selected_date: DateTime<Utc>,
 ) -> Result<()> {
-  let now = Utc::now();
-  let guild_name = {
-    if let Some(guild) = ctx.guild() {
-      guild.name.clone()
-    } else {
-      "Host Server".to_owned()
-    }
-  };
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
 
-  let announcement_embed = BloomBotEmbed::new()
-    .title(":tada: Monthly Challenge Winner :tada:")
-    .description(format!(
-      "**Meditator in the Spotlight for {}**\nCongratulations to **{}** on winning our {} challenge, with a meditation time of **{}** minutes for the month!",
-      selected_date.format("%B"),
-      winner.user,
-      selected_date.format("%B"),
-      minutes
-    ))
-    .thumbnail(winner.user.avatar_url().unwrap_or_default())
-    .footer(CreateEmbedFooter::new(format!(
-      "Meditation Challenge for {} | Selected on {}",
-      selected_date.format("%B %Y"),
-      now.format("%B %d, %Y")
-    )));
-
-  let dm_embed = BloomBotEmbed::new()
-    .title(":tada: You've won a key! :tada:")
-    .thumbnail(winner.user.avatar_url().unwrap_or_default())
-    .field(
-      "**Congratulations on winning the giveaway!** 🥳",
-      "You've won a key for [Playne: The Meditation Game](<https://store.steampowered.com/app/865540/PLAYNE__The_Meditation_Game/>) on Steam!\n\n**Would you like to redeem your key? If yes, press 'Redeem' below! Otherwise, click 'Cancel' to leave it for someone else :)**",
-      false,
-    )
-    .footer(CreateEmbedFooter::new(format!(
-      "From {guild_name} | If you need any assistance, please contact server staff."
-    )));
+  let settings = ctx.data().settings.get(&ctx.data().db, guild_id).await?;
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+  let profile = DatabaseHandler::get_tracking_profile(&mut transaction, &guild_id, &winner.user.id)
+    .await?
+    .unwrap_or_default()
+    .profile;
+  drop(transaction);
+
+  let announcement_embed =
+    announcements::winner_embed(&settings, &winner.user, &profile, selected_date, minutes);
 
   let announcement_channel = ChannelId::new(CHANNELS.announcement);
-  let dm_channel = winner.user.create_dm_channel(ctx).await?;
 
-  announcement_channel
+  let announcement = announcement_channel
     .send_message(ctx, CreateMessage::new().embed(announcement_embed))
     .await?;
+  announcements::publish(ctx, &ctx.data().db, guild_id, &announcement).await;
 
-  let ctx_id = ctx.id();
-  let redeem_id = format!("{ctx_id}redeem");
-  let cancel_id = format!("{ctx_id}cancel");
-
-  let Ok(mut dm_message) = dm_channel
-    .send_message(
-      ctx,
-      CreateMessage::new()
-        .embed(dm_embed)
-        .components(vec![CreateActionRow::Buttons(vec![
-          CreateButton::new(redeem_id.clone())
-            .label("Redeem")
-            .style(ButtonStyle::Success),
-          CreateButton::new(cancel_id.clone())
-            .label("Cancel")
-            .style(ButtonStyle::Danger),
-        ])]),
-    )
-    .await
-  else {
-    ctx
-      .send(CreateReply::default().content(format!(
-        "{} Could not send DM to member. Please run `/usekey` and copy a key manually if they want one.\n\n**No key has been used.**",
-        EMOJI.mminfo
-      )))
-      .await?;
-    return Ok(());
-  };
-
-  ctx
-    .send(CreateReply::default().content(format!(
-      "{} Sent DM to {} and sent announcement!",
-      EMOJI.mmcheck, winner.user
-    )))
-    .await?;
-
-  // Loop through incoming interactions with the buttons
-  while let Some(press) = ComponentInteractionCollector::new(ctx)
-    // We defined our button IDs to start with `ctx_id`. If they don't, some other command's
-    // button was pressed
-    .filter(move |press| press.data.custom_id.starts_with(&ctx_id.to_string()))
-    // Timeout when no navigation button has been pressed for 24 hours
-    .timeout(Duration::from_secs(3600 * 24))
-    .await
+  let message = match key_redemption::send_offer(ctx, guild_id, &winner.user, reserved_key).await?
   {
-    // Depending on which button was pressed, confirm or cancel
-    if press.data.custom_id == redeem_id {
-      let mut conn = ctx.data().db.get_connection_with_retry(5).await?;
-      DatabaseHandler::mark_key_used(&mut conn, &reserved_key).await?;
-      let hyperlink = format!(
-        "[Redeem your key](https://store.steampowered.com/account/registerkey?key={reserved_key})"
-      );
-      let guild_id = &ctx
-        .guild_id()
-        .with_context(|| "Failed to retrieve guild ID from context")?;
-      DatabaseHandler::record_steamkey_receipt(&mut conn, guild_id, &winner.user.id).await?;
-
-      dm_message
-        .edit(ctx, EditMessage::new().components(Vec::new()))
-        .await?;
-
-      dm_channel
-        .send_message(
-          ctx,
-          CreateMessage::new().content(format!(
-            "Awesome! Here is your key:\n```{reserved_key}```\n{hyperlink}"
-          )),
-        )
-        .await?;
-
-      let log_embed = BloomBotEmbed::new()
-        .title("**Key Redeemed**")
-        .description(format!(
-          "Playne key redeemed by <@{}>. Key has been marked as used.",
-          winner.user.id
-        ))
-        .footer(
-          CreateEmbedFooter::new(format!("{} ({})", winner.user.name, winner.user.id))
-            .icon_url(winner.user.avatar_url().unwrap_or_default()),
-        );
-
-      let log_channel = ChannelId::new(CHANNELS.logs);
-
-      log_channel
-        .send_message(ctx, CreateMessage::new().embed(log_embed))
-        .await?;
-
-      return Ok(());
-    } else if press.data.custom_id == cancel_id {
-      let mut conn = ctx.data().db.get_connection_with_retry(5).await?;
-      DatabaseHandler::unreserve_key(&mut conn, &reserved_key).await?;
-
-      dm_message
-        .edit(ctx, EditMessage::new().components(Vec::new()))
-        .await?;
-
-      dm_channel
-        .send_message(
-          ctx,
-          CreateMessage::new().content("Alright, we'll keep it for someone else. Congrats again!"),
-        )
-        .await?;
-
-      let log_embed = BloomBotEmbed::new()
-        .title("**Key Declined**")
-        .description(format!(
-          "Playne key declined by <@{}>. Key has been returned to the pool.",
-          winner.user.id
-        ))
-        .footer(
-          CreateEmbedFooter::new(format!("{} ({})", winner.user.name, winner.user.id))
-            .icon_url(winner.user.avatar_url().unwrap_or_default()),
-        );
-
-      let log_channel = ChannelId::new(CHANNELS.logs);
-
-      log_channel
-        .send_message(ctx, CreateMessage::new().embed(log_embed))
-        .await?;
-
-      return Ok(());
-    }
-
-    // This is an unrelated button interaction
-    continue;
-  }
-
-  let timeout_embed = BloomBotEmbed::new()
-    .title("**Congratulations on winning the giveaway!** 🥳")
-    .description(
-      "You've won a key for [Playne: The Meditation Game](<https://store.steampowered.com/app/865540/PLAYNE__The_Meditation_Game/>) on Steam!\n\n**Would you like to redeem your key? Please contact server staff and we'll get one to you!**",
-    )
-    .footer(CreateEmbedFooter::new(format!("From {guild_name}")));
-
-  dm_message
-    .edit(
-      ctx,
-      EditMessage::new()
-        .embed(timeout_embed)
-        .components(Vec::new()),
-    )
-    .await?;
+    Some(OfferDelivery::Dm) => format!(
+      "{} Sent DM to {} and sent announcement!",
+      emoji.mmcheck, winner.user
+    ),
+    Some(OfferDelivery::ClaimChannel(channel_id)) => format!(
+      "{} Could not send DM to {}, so they were pinged in {} to claim their key privately. Sent announcement!",
+      emoji.mmcheck,
+      winner.user,
+      channel_id.mention()
+    ),
+    None => format!(
+      "{} Could not send DM to member. Please run `/usekey` and copy a key manually if they want one, or set a channel with `/config keyclaims` so winners can claim keys without DMs.\n\n**No key has been used.**",
+      emoji.mminfo
+    ),
+  };
 
-  let log_embed = BloomBotEmbed::new()
-    .title("**Key Offer Timed Out**")
-    .description(format!(
-      "Sent Playne key offer to <@{}>, but user did not respond within 24 hours. Key has been returned to the pool and user has been asked to contact a moderator if they wish to claim their key.",
-      winner.user.id
-    ))
-    .footer(
-      CreateEmbedFooter::new(format!("{} ({})", winner.user.name, winner.user.id))
-        .icon_url(winner.user.avatar_url().unwrap_or_default()),
-    );
-
-  let log_channel = ChannelId::new(CHANNELS.logs);
-
-  log_channel
-    .send_message(ctx, CreateMessage::new().embed(log_embed))
-    .await?;
+  ctx.send(CreateReply::default().content(message)).await?;
 
   Ok(())
 }
@@ -274,6 +113,7 @@ pub async fn pick_winner(
   #[description = "Include users who have already received a Playne key (defaults to false)"]
   allow_multiple_keys: Option<bool>,
 ) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
   ctx.defer_ephemeral().await?;
 
   let data = ctx.data();
@@ -288,7 +128,7 @@ pub async fn pick_winner(
     ctx
       .send(
         CreateReply::default()
-          .content(format!("{} No unused keys found.", EMOJI.mminfo))
+          .content(format!("{} No unused keys found.", emoji.mminfo))
           .ephemeral(true),
       )
       .await?;
@@ -398,7 +238,7 @@ pub async fn pick_winner(
       ctx
         .send(CreateReply::default().content(format!(
           "{} No unused keys found. Please add one and run `/usekey` to give them one if they want one.",
-          EMOJI.mminfo
+          emoji.mminfo
         )))
         .await?;
       return Ok(());
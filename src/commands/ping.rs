@@ -5,7 +5,7 @@ use crate::Context;
 
 /// Check the bot's latency
 ///
-/// Replies with the bot's latency.
+/// Replies with the bot's latency and the shard handling the request.
 #[poise::command(slash_command, category = "Utilities")]
 pub async fn ping(ctx: Context<'_>) -> Result<()> {
   let response = ctx
@@ -13,13 +13,17 @@ pub async fn ping(ctx: Context<'_>) -> Result<()> {
     .await?;
 
   let latency = ctx.ping().await;
+  let shard_id = ctx.serenity_context().shard_id;
+  let shard_count = ctx.cache().shard_count();
 
   response
     .edit(
       ctx,
       CreateReply::default().content(format!(
-        ":ping_pong: Pong! Latency is {}ms.",
-        latency.as_millis()
+        ":ping_pong: Pong! Latency is {}ms.\n-# Shard {} ({} total)",
+        latency.as_millis(),
+        shard_id.0,
+        shard_count
       )),
     )
     .await?;
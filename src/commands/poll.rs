@@ -0,0 +1,224 @@
+use anyhow::{Context as AnyhowContext, Result};
+use chrono::{Duration as ChronoDuration, Utc};
+use poise::CreateReply;
+
+use crate::commands::helpers::polls::{self, PollPreset};
+use crate::data::poll::Poll;
+use crate::database::DatabaseHandler;
+use crate::Context;
+
+const MIN_CHOICES: usize = 2;
+const MAX_CHOICES: usize = 10;
+
+/// Longest choice allowed, so that it fits on a button with its number.
+const MAX_CHOICE_LENGTH: usize = 70;
+
+/// Splits a comma-separated list of choices, checking that there are enough of them, that
+/// each fits on a button, and that none are repeated.
+fn parse_choices(choices: &str) -> Result<Vec<String>, String> {
+  let choices: Vec<String> = choices
+    .split(',')
+    .map(|choice| choice.trim().to_owned())
+    .filter(|choice| !choice.is_empty())
+    .collect();
+
+  if !(MIN_CHOICES..=MAX_CHOICES).contains(&choices.len()) {
+    return Err(format!(
+      "Please enter between {MIN_CHOICES} and {MAX_CHOICES} choices, separated by commas."
+    ));
+  }
+
+  if let Some(choice) = choices
+    .iter()
+    .find(|choice| choice.chars().count() > MAX_CHOICE_LENGTH)
+  {
+    return Err(format!(
+      "Choices can be up to {MAX_CHOICE_LENGTH} characters long, but **{choice}** is longer."
+    ));
+  }
+
+  for (index, choice) in choices.iter().enumerate() {
+    if choices[..index]
+      .iter()
+      .any(|previous| previous.eq_ignore_ascii_case(choice))
+    {
+      return Err(format!("**{choice}** is listed more than once."));
+    }
+  }
+
+  Ok(choices)
+}
+
+/// Commands for running polls
+///
+/// Commands to start a poll which members vote on using buttons, or close one early.
+///
+/// Requires `Manage Messages` permissions.
+#[poise::command(
+  slash_command,
+  required_permissions = "MANAGE_MESSAGES",
+  default_member_permissions = "MANAGE_MESSAGES",
+  category = "Moderator Commands",
+  subcommands("create", "close"),
+  subcommand_required,
+  guild_only
+)]
+#[allow(clippy::unused_async)]
+pub async fn poll(_: Context<'_>) -> Result<()> {
+  Ok(())
+}
+
+/// Start a poll
+///
+/// Starts a poll in the current channel. Either choose a preset, such as next month's challenge theme, or enter your own question and choices. A preset's question or choices can be replaced by entering your own.
+///
+/// By default, members can vote for one choice and the members who voted for each choice are shown. The poll stays open until closed with `/poll close`, unless a number of hours is given.
+#[poise::command(slash_command)]
+async fn create(
+  ctx: Context<'_>,
+  #[description = "Use a preset question and choices"] preset: Option<PollPreset>,
+  #[description = "The question to ask"]
+  #[max_length = 200]
+  question: Option<String>,
+  #[description = "The choices, separated by commas"]
+  #[max_length = 800]
+  choices: Option<String>,
+  #[description = "Allow members to vote for more than one choice (defaults to false)"]
+  multiple_choice: Option<bool>,
+  #[description = "Hide who voted for each choice (defaults to false)"] anonymous: Option<bool>,
+  #[description = "Close the poll after this many hours"]
+  #[min = 1]
+  #[max = 336]
+  hours: Option<i64>,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let question = question
+    .map(|question| question.trim().to_owned())
+    .filter(|question| !question.is_empty())
+    .or_else(|| preset.map(|preset| preset.question().to_owned()));
+  let choices = match choices {
+    Some(choices) => Some(parse_choices(&choices)),
+    None => preset.map(|preset| Ok(preset.choices())),
+  };
+
+  let (question, choices) = match (question, choices) {
+    (Some(question), Some(Ok(choices))) => (question, choices),
+    (_, Some(Err(e))) => {
+      ctx
+        .send(
+          CreateReply::default()
+            .content(format!("{} {e}", emoji.mminfo))
+            .ephemeral(true),
+        )
+        .await?;
+      return Ok(());
+    }
+    _ => {
+      ctx
+        .send(
+          CreateReply::default()
+            .content(format!(
+              "{} Please choose a preset, or enter a question and choices.",
+              emoji.mminfo
+            ))
+            .ephemeral(true),
+        )
+        .await?;
+      return Ok(());
+    }
+  };
+
+  let poll = Poll::new(
+    guild_id,
+    ctx.channel_id(),
+    question,
+    choices,
+    multiple_choice.unwrap_or(false),
+    anonymous.unwrap_or(false),
+    hours.map(|hours| Utc::now() + ChronoDuration::hours(hours)),
+    ctx.author().id,
+  );
+
+  // Saved before posting, so votes can be recorded as soon as the buttons appear
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+  DatabaseHandler::add_poll(&mut transaction, &poll).await?;
+  DatabaseHandler::commit_transaction(transaction).await?;
+
+  let reply = ctx
+    .send(
+      CreateReply::default()
+        .embed(polls::poll_embed(&poll, &[]))
+        .components(polls::buttons(&poll)),
+    )
+    .await?;
+  let message = reply.message().await?;
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+  DatabaseHandler::set_poll_message(&mut transaction, &poll.id, &message.id).await?;
+  DatabaseHandler::commit_transaction(transaction).await?;
+
+  Ok(())
+}
+
+/// Close a poll
+///
+/// Closes a poll, so that no more votes can be cast, and shows the final results. The poll ID is shown at the bottom of the poll.
+#[poise::command(slash_command)]
+async fn close(
+  ctx: Context<'_>,
+  #[description = "The ID of the poll to close"] id: String,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+  let poll = DatabaseHandler::get_poll(&mut transaction, id.trim())
+    .await?
+    .filter(|poll| poll.guild_id == guild_id);
+  drop(transaction);
+
+  let message = match poll {
+    Some(poll) if !poll.closed => {
+      if polls::close(ctx.serenity_context(), &ctx.data().db, poll).await? {
+        format!("{} The poll has been closed.", emoji.mmcheck)
+      } else {
+        format!("{} That poll has already been closed.", emoji.mminfo)
+      }
+    }
+    Some(_) => format!("{} That poll has already been closed.", emoji.mminfo),
+    None => format!("{} No poll with that ID was found.", emoji.mminfo),
+  };
+
+  ctx
+    .send(CreateReply::default().content(message).ephemeral(true))
+    .await?;
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_parse_choices() {
+    assert_eq!(
+      parse_choices("Metta, Body scan ,Walking,"),
+      Ok(vec![
+        "Metta".to_owned(),
+        "Body scan".to_owned(),
+        "Walking".to_owned()
+      ])
+    );
+    assert!(parse_choices("Metta").is_err());
+    assert!(parse_choices("a,b,c,d,e,f,g,h,i,j,k").is_err());
+    assert!(parse_choices("Metta, metta").is_err());
+    assert!(parse_choices(&format!("Metta, {}", "a".repeat(71))).is_err());
+  }
+}
@@ -0,0 +1,107 @@
+use anyhow::{Context as AnyhowContext, Result};
+use log::warn;
+use poise::serenity_prelude::Mentionable;
+use poise::CreateReply;
+
+use crate::commands::helpers::quick_add;
+use crate::Context;
+
+/// Commands for quick-add buttons
+///
+/// Commands to post buttons which members can press to log a session instantly.
+///
+/// Requires `Manage Messages` permissions.
+#[poise::command(
+  slash_command,
+  required_permissions = "MANAGE_MESSAGES",
+  default_member_permissions = "MANAGE_MESSAGES",
+  category = "Moderator Commands",
+  rename = "quickadd",
+  subcommands("setup"),
+  subcommand_required,
+  guild_only
+)]
+#[allow(clippy::unused_async)]
+pub async fn quick_add(_: Context<'_>) -> Result<()> {
+  Ok(())
+}
+
+/// Post quick-add buttons in the tracking channel
+///
+/// Posts and pins a message with up to four buttons for common durations in the tracking channel, or in this channel if no tracking channel is set. Pressing a button logs a session of that length for whoever pressed it, like `/add`, and only they see the confirmation.
+///
+/// Defaults to 10 minutes, 20 minutes, 30 minutes, and 1 hour. Running this again replaces the previous quick-add message.
+#[poise::command(slash_command)]
+async fn setup(
+  ctx: Context<'_>,
+  #[description = "A duration in minutes"]
+  #[min = 1]
+  #[max = 600]
+  first: Option<i32>,
+  #[description = "A duration in minutes"]
+  #[min = 1]
+  #[max = 600]
+  second: Option<i32>,
+  #[description = "A duration in minutes"]
+  #[min = 1]
+  #[max = 600]
+  third: Option<i32>,
+  #[description = "A duration in minutes"]
+  #[min = 1]
+  #[max = 600]
+  fourth: Option<i32>,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  ctx.defer_ephemeral().await?;
+
+  let settings = ctx.data().settings.get(&ctx.data().db, guild_id).await?;
+  let channel_id = settings
+    .tracking_channel
+    .unwrap_or_else(|| ctx.channel_id());
+  let durations = quick_add::durations(&[first, second, third, fourth]);
+
+  // Replace the previous quick-add message, so there's only one set of buttons pinned
+  let bot_id = ctx.cache().current_user().id;
+  match channel_id.pins(ctx).await {
+    Ok(pins) => {
+      for pin in pins
+        .iter()
+        .filter(|pin| quick_add::is_quick_add(pin, bot_id))
+      {
+        if let Err(e) = pin.delete(ctx).await {
+          warn!("Failed to remove previous quick-add message: {e}");
+        }
+      }
+    }
+    Err(e) => warn!("Failed to retrieve pins in {channel_id}: {e}"),
+  }
+
+  let message = channel_id
+    .send_message(ctx, quick_add::message(&durations))
+    .await?;
+
+  let pinned = if let Err(e) = message.pin(ctx).await {
+    warn!("Failed to pin quick-add message: {e}");
+    " It couldn't be pinned, so please check that Bloom has permission to pin messages there."
+  } else {
+    ""
+  };
+
+  ctx
+    .send(
+      CreateReply::default()
+        .content(format!(
+          "{} Quick-add buttons have been posted in {}.{pinned}",
+          emoji.mmcheck,
+          channel_id.mention()
+        ))
+        .ephemeral(true),
+    )
+    .await?;
+
+  Ok(())
+}
@@ -1,14 +1,26 @@
 use anyhow::{Context as AnyhowContext, Result};
+use poise::serenity_prelude::CreateEmbed;
 use poise::CreateReply;
 
 use crate::commands::helpers::common;
-use crate::config::{BloomBotEmbed, EMOJI};
+use crate::commands::helpers::quotes::autocomplete_author;
+use crate::config::BloomBotEmbed;
+use crate::data::guild_feature::Feature;
+use crate::data::quote::Quote;
 use crate::database::DatabaseHandler;
 use crate::Context;
 
+fn quote_embed(quote: Quote) -> CreateEmbed {
+  BloomBotEmbed::new().description(format!(
+    "{}\n\n\\― {}",
+    quote.quote,
+    quote.author.unwrap_or("Anonymous".to_string())
+  ))
+}
+
 /// Get a meditation/mindfulness quote
 ///
-/// Get a random meditation/mindfulness quote.
+/// Get a random meditation/mindfulness quote, optionally by a specific author.
 #[poise::command(
   slash_command,
   category = "Informational",
@@ -19,7 +31,26 @@ pub async fn quote(
   ctx: Context<'_>,
   #[description = "Refine quote pool with one or more keywords in search engine format"]
   keyword: Option<String>,
+  #[description = "Only show quotes by this author"]
+  #[autocomplete = "autocomplete_author"]
+  author: Option<String>,
 ) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+
+  if !ctx.data().features.enabled(ctx.guild_id(), Feature::Quotes) {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} Quotes are turned off in this server.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
   let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
 
   let guild_id = ctx
@@ -28,17 +59,17 @@ pub async fn quote(
 
   if let Some(keyword) = keyword {
     if common::is_supporter(ctx).await? {
-      if let Some(quote) =
-        DatabaseHandler::get_random_quote_with_keyword(&mut transaction, &guild_id, &keyword)
-          .await?
+      if let Some(quote) = DatabaseHandler::get_random_quote_with_keyword(
+        &mut transaction,
+        &guild_id,
+        &keyword,
+        author.as_deref(),
+      )
+      .await?
       {
-        let embed = BloomBotEmbed::new().description(format!(
-          "{}\n\n\\― {}",
-          quote.quote,
-          quote.author.unwrap_or("Anonymous".to_string())
-        ));
-
-        ctx.send(CreateReply::default().embed(embed)).await?;
+        ctx
+          .send(CreateReply::default().embed(quote_embed(quote)))
+          .await?;
 
         return Ok(());
       }
@@ -49,16 +80,33 @@ pub async fn quote(
             .ephemeral(true),
         )
         .await?;
+    } else {
+      ctx
+        .send(
+          CreateReply::default()
+            .content(format!(
+              "{} The keyword option is only available to [subscription-based donators]\
+              (<https://discord.com/channels/244917432383176705/1030424719138246667/1031137243345211413>).",
+              emoji.mminfo
+            ))
+            .ephemeral(true),
+        )
+        .await?;
     }
+  } else if let Some(author) = author {
+    if let Some(quote) =
+      DatabaseHandler::get_random_quote_by_author(&mut transaction, &guild_id, &author).await?
+    {
+      ctx
+        .send(CreateReply::default().embed(quote_embed(quote)))
+        .await?;
 
+      return Ok(());
+    }
     ctx
       .send(
         CreateReply::default()
-          .content(format!(
-            "{} The keyword option is only available to [subscription-based donators]\
-            (<https://discord.com/channels/244917432383176705/1030424719138246667/1031137243345211413>).",
-            EMOJI.mminfo
-          ))
+          .content("No quotes found by that author. Fetching random quote.")
           .ephemeral(true),
       )
       .await?;
@@ -69,13 +117,9 @@ pub async fn quote(
       ctx.say("No quotes found.").await?;
     }
     Some(quote) => {
-      let embed = BloomBotEmbed::new().description(format!(
-        "{}\n\n\\― {}",
-        quote.quote,
-        quote.author.unwrap_or("Anonymous".to_string())
-      ));
-
-      ctx.send(CreateReply::default().embed(embed)).await?;
+      ctx
+        .send(CreateReply::default().embed(quote_embed(quote)))
+        .await?;
     }
   }
 
@@ -4,7 +4,8 @@ use poise::{ApplicationContext, Context as PoiseContext, CreateReply, Modal};
 use crate::commands::helpers::common::Visibility;
 use crate::commands::helpers::database::{self, MessageType};
 use crate::commands::helpers::pagination::{PageRowRef, PageType, Paginator};
-use crate::config::{BloomBotEmbed, EMOJI, ENTRIES_PER_PAGE};
+use crate::commands::helpers::quotes::autocomplete_author;
+use crate::config::{BloomBotEmbed, ENTRIES_PER_PAGE};
 use crate::data::quote::{Quote, QuoteModal};
 use crate::database::DatabaseHandler;
 use crate::{Context, Data as AppData, Error as AppError};
@@ -35,6 +36,7 @@ pub async fn quotes(_: PoiseContext<'_, AppData, AppError>) -> Result<()> {
 /// Adds a quote to the database.
 #[poise::command(slash_command)]
 async fn add(ctx: ApplicationContext<'_, AppData, AppError>) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
   if let Some(quote_data) = QuoteModal::execute(ctx).await? {
     let guild_id = ctx
       .guild_id()
@@ -49,7 +51,7 @@ async fn add(ctx: ApplicationContext<'_, AppData, AppError>) -> Result<()> {
     database::commit_and_say(
       PoiseContext::Application(ctx),
       transaction,
-      MessageType::TextOnly(format!("{} Quote has been added.", EMOJI.mmcheck)),
+      MessageType::TextOnly(format!("{} Quote has been added.", emoji.mmcheck)),
       Visibility::Ephemeral,
     )
     .await?;
@@ -68,6 +70,7 @@ async fn edit(
   #[rename = "id"]
   quote_id: String,
 ) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
   let guild_id = ctx
     .guild_id()
     .with_context(|| "Failed to retrieve guild ID from context")?;
@@ -89,7 +92,7 @@ async fn edit(
       database::commit_and_say(
         PoiseContext::Application(ctx),
         transaction,
-        MessageType::TextOnly(format!("{} Quote has been edited.", EMOJI.mmcheck)),
+        MessageType::TextOnly(format!("{} Quote has been edited.", emoji.mmcheck)),
         Visibility::Ephemeral,
       )
       .await?;
@@ -98,7 +101,7 @@ async fn edit(
     ctx
       .send(
         CreateReply::default()
-          .content(format!("{} Invalid quote ID.", EMOJI.mminfo))
+          .content(format!("{} Invalid quote ID.", emoji.mminfo))
           .ephemeral(true),
       )
       .await?;
@@ -117,6 +120,7 @@ async fn remove(
   #[rename = "id"]
   quote_id: String,
 ) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
   let guild_id = ctx
     .guild_id()
     .with_context(|| "Failed to retrieve guild ID from context")?;
@@ -129,7 +133,7 @@ async fn remove(
     database::commit_and_say(
       ctx,
       transaction,
-      MessageType::TextOnly(format!("{} Quote has been removed.", EMOJI.mmcheck)),
+      MessageType::TextOnly(format!("{} Quote has been removed.", emoji.mmcheck)),
       Visibility::Ephemeral,
     )
     .await?;
@@ -137,7 +141,7 @@ async fn remove(
     ctx
       .send(
         CreateReply::default()
-          .content(format!("{} Quote does not exist.", EMOJI.mminfo))
+          .content(format!("{} Quote does not exist.", emoji.mminfo))
           .ephemeral(true),
       )
       .await?;
@@ -148,10 +152,13 @@ async fn remove(
 
 /// List all quotes in the database
 ///
-/// Lists all quotes in the database.
+/// Lists all quotes in the database, optionally only those by a specific author.
 #[poise::command(slash_command)]
 async fn list(
   ctx: Context<'_>,
+  #[description = "Only list quotes by this author"]
+  #[autocomplete = "autocomplete_author"]
+  author: Option<String>,
   #[description = "The page to show"] page: Option<usize>,
 ) -> Result<()> {
   let guild_id = ctx
@@ -160,7 +167,12 @@ async fn list(
 
   let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
 
-  let quotes = DatabaseHandler::get_all_quotes(&mut transaction, &guild_id).await?;
+  let quotes = match author {
+    Some(author) => {
+      DatabaseHandler::get_quotes_by_author(&mut transaction, &guild_id, &author).await?
+    }
+    None => DatabaseHandler::get_all_quotes(&mut transaction, &guild_id).await?,
+  };
   let quotes: Vec<PageRowRef> = quotes.iter().map(|quote| quote as PageRowRef).collect();
 
   drop(transaction);
@@ -183,6 +195,7 @@ async fn search(
   #[description = "One or more keywords in search engine format"] keyword: String,
   #[description = "The page to show"] page: Option<usize>,
 ) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
   let guild_id = ctx
     .guild_id()
     .with_context(|| "Failed to retrieve guild ID from context")?;
@@ -197,7 +210,7 @@ async fn search(
         CreateReply::default()
           .content(format!(
             "{} No quotes match your search query.",
-            EMOJI.mminfo
+            emoji.mminfo
           ))
           .ephemeral(true),
       )
@@ -226,6 +239,7 @@ async fn show(
   #[rename = "id"]
   quote_id: String,
 ) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
   let guild_id = ctx
     .guild_id()
     .with_context(|| "Failed to retrieve guild ID from context")?;
@@ -237,7 +251,7 @@ async fn show(
       ctx
         .send(
           CreateReply::default()
-            .content(format!("{} Invalid quote ID.", EMOJI.mminfo))
+            .content(format!("{} Invalid quote ID.", emoji.mminfo))
             .ephemeral(true),
         )
         .await?;
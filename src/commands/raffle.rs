@@ -0,0 +1,293 @@
+use std::collections::HashSet;
+use std::time::Duration;
+
+use anyhow::{Context as AnyhowContext, Result};
+use chrono::{Duration as ChronoDuration, Utc};
+use poise::serenity_prelude::{builder::*, ButtonStyle, ChannelId, ComponentInteractionCollector};
+use poise::serenity_prelude::{FormattedTimestamp, FormattedTimestampStyle, UserId};
+use poise::CreateReply;
+use rand::seq::SliceRandom;
+use tokio::time::Instant;
+
+use crate::commands::helpers::key_redemption::{self, OfferDelivery};
+use crate::config::{BloomBotEmbed, CHANNELS};
+use crate::database::DatabaseHandler;
+use crate::Context;
+
+/// Commands for running raffles
+///
+/// Commands for running raffles for Playne keys.
+///
+/// Requires `Administrator` permissions.
+#[poise::command(
+  slash_command,
+  required_permissions = "ADMINISTRATOR",
+  default_member_permissions = "ADMINISTRATOR",
+  category = "Admin Commands",
+  subcommands("start"),
+  guild_only
+)]
+#[allow(clippy::unused_async)]
+pub async fn raffle(_: Context<'_>) -> Result<()> {
+  Ok(())
+}
+
+/// Start a raffle for a Playne key
+///
+/// Starts a raffle for a Playne key in the current channel. Members enter by pressing the button on the raffle post, and a winner is drawn at random from the entrants once the raffle closes. The winner is then offered a key by DM, as with `/pickwinner`.
+///
+/// Entrants must have tracked at least the minimum number of minutes during the 30 days leading up to the raffle.
+#[poise::command(slash_command)]
+async fn start(
+  ctx: Context<'_>,
+  #[description = "How long the raffle stays open, in hours (defaults to 24 hours)"]
+  #[min = 1]
+  #[max = 168]
+  duration: Option<u32>,
+  #[description = "Minimum minutes tracked in the last 30 days to enter (defaults to 0 minutes)"]
+  #[min = 0]
+  minimum_minutes: Option<i64>,
+  #[description = "Allow users who have already received a Playne key to enter (defaults to false)"]
+  allow_multiple_keys: Option<bool>,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  ctx.defer_ephemeral().await?;
+
+  let data = ctx.data();
+
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let mut transaction = data.db.start_transaction_with_retry(5).await?;
+
+  if !DatabaseHandler::unused_key_exists(&mut transaction, &guild_id).await? {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!("{} No unused keys found.", emoji.mminfo))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  drop(transaction);
+
+  let duration = duration.unwrap_or(24);
+  let minimum_minutes = minimum_minutes.unwrap_or(0);
+  let allow_multiple_keys = allow_multiple_keys.unwrap_or(false);
+
+  let start_time = Utc::now();
+  let end_time = start_time + ChronoDuration::hours(i64::from(duration));
+  let eligibility_start = start_time - ChronoDuration::days(30);
+
+  let requirements = if minimum_minutes > 0 {
+    format!("\n\nTo enter, you must have tracked at least **{minimum_minutes}** minutes in the last 30 days.")
+  } else {
+    String::new()
+  };
+
+  let raffle_embed = BloomBotEmbed::new()
+    .title(":tickets: Playne Key Raffle :tickets:")
+    .description(format!(
+      "We're giving away a key for [Playne: The Meditation Game](<https://store.steampowered.com/app/865540/PLAYNE__The_Meditation_Game/>) on Steam! Press the button below to enter.{requirements}\n\nThe winner will be drawn {}.",
+      FormattedTimestamp::new(end_time.into(), Some(FormattedTimestampStyle::RelativeTime))
+    ));
+
+  let ctx_id = ctx.id();
+  let enter_id = format!("{ctx_id}enter");
+
+  let mut raffle_message = ctx
+    .channel_id()
+    .send_message(
+      ctx,
+      CreateMessage::new()
+        .embed(raffle_embed)
+        .components(vec![CreateActionRow::Buttons(vec![CreateButton::new(
+          enter_id.clone(),
+        )
+        .label("Enter")
+        .style(ButtonStyle::Primary)])]),
+    )
+    .await?;
+
+  ctx
+    .send(
+      CreateReply::default()
+        .content(format!(
+          "{} Raffle started! A winner will be drawn {}.",
+          emoji.mmcheck,
+          FormattedTimestamp::new(end_time.into(), Some(FormattedTimestampStyle::RelativeTime))
+        ))
+        .ephemeral(true),
+    )
+    .await?;
+
+  let deadline = Instant::now() + Duration::from_secs(u64::from(duration) * 60 * 60);
+  let mut entered = HashSet::new();
+  let mut entrants: Vec<UserId> = Vec::new();
+
+  // Loop through incoming interactions with the entry button until the raffle closes
+  while let Some(press) = ComponentInteractionCollector::new(ctx)
+    // We defined our button ID to start with `ctx_id`. If it doesn't, some other command's
+    // button was pressed
+    .filter(move |press| press.data.custom_id.starts_with(&ctx_id.to_string()))
+    .timeout(deadline.saturating_duration_since(Instant::now()))
+    .await
+  {
+    if press.data.custom_id != enter_id {
+      // This is an unrelated button interaction
+      continue;
+    }
+
+    let user_id = press.user.id;
+
+    let response = if entered.contains(&user_id) {
+      format!(
+        "{} You've already entered this raffle. Good luck!",
+        emoji.mminfo
+      )
+    } else {
+      let mut transaction = data.db.start_transaction_with_retry(5).await?;
+
+      if !allow_multiple_keys
+        && DatabaseHandler::steamkey_recipient_exists(&mut transaction, &guild_id, &user_id).await?
+      {
+        format!(
+          "{} Sorry, this raffle is only open to members who haven't received a Playne key before.",
+          emoji.mminfo
+        )
+      } else if minimum_minutes > 0
+        && DatabaseHandler::get_winner_candidate_meditation_sum(
+          &mut transaction,
+          &guild_id,
+          &user_id,
+          &eligibility_start,
+          &start_time,
+        )
+        .await?
+          < minimum_minutes
+      {
+        format!(
+          "{} Sorry, you need to have tracked at least {minimum_minutes} minutes in the last 30 days to enter this raffle.",
+          emoji.mminfo
+        )
+      } else {
+        entered.insert(user_id);
+        entrants.push(user_id);
+        format!("{} You're in! Good luck!", emoji.mmcheck)
+      }
+    };
+
+    press
+      .create_response(
+        ctx,
+        CreateInteractionResponse::Message(
+          CreateInteractionResponseMessage::new()
+            .content(response)
+            .ephemeral(true),
+        ),
+      )
+      .await?;
+
+    if Instant::now() >= deadline {
+      break;
+    }
+  }
+
+  {
+    let mut rng = data.rng.lock().await;
+    entrants.shuffle(&mut *rng);
+  }
+
+  let entry_count = entrants.len();
+  let entries = if entry_count == 1 { "entry" } else { "entries" };
+  let mut winner = None;
+
+  for user_id in entrants {
+    // Entrants may have left the server or been given a key since entering
+    let Ok(member) = guild_id.member(ctx, user_id).await else {
+      continue;
+    };
+
+    let mut transaction = data.db.start_transaction_with_retry(5).await?;
+
+    if !allow_multiple_keys
+      && DatabaseHandler::steamkey_recipient_exists(&mut transaction, &guild_id, &user_id).await?
+    {
+      continue;
+    }
+
+    let reserved_key = DatabaseHandler::reserve_key(&mut transaction, &guild_id, &user_id).await?;
+    DatabaseHandler::commit_transaction(transaction).await?;
+
+    winner = Some((member, reserved_key));
+    break;
+  }
+
+  let results = match &winner {
+    Some((member, _)) => format!(
+      "This raffle has closed, with **{entry_count}** {entries}. Congratulations to {} on winning a key for [Playne: The Meditation Game](<https://store.steampowered.com/app/865540/PLAYNE__The_Meditation_Game/>)! :tada:",
+      member.user
+    ),
+    None => "This raffle has closed. There were no eligible entries this time.".to_owned(),
+  };
+
+  raffle_message
+    .edit(
+      ctx,
+      EditMessage::new()
+        .embed(
+          BloomBotEmbed::new()
+            .title(":tickets: Playne Key Raffle :tickets:")
+            .description(results),
+        )
+        .components(Vec::new()),
+    )
+    .await?;
+
+  let log_channel = ChannelId::new(CHANNELS.logs);
+
+  let log_message = match winner {
+    Some((member, Some(reserved_key))) => {
+      match key_redemption::send_offer(ctx, guild_id, &member.user, reserved_key).await? {
+        Some(OfferDelivery::Dm) => format!(
+          "Raffle drawn with {entry_count} {entries}. Sent Playne key offer to winner {}.",
+          member.user
+        ),
+        Some(OfferDelivery::ClaimChannel(channel_id)) => format!(
+          "Raffle drawn with {entry_count} {entries}. Could not send DM to winner {}, so they were pinged in <#{channel_id}> to claim their key privately.",
+          member.user
+        ),
+        None => format!(
+          "Raffle drawn with {entry_count} {entries}, but could not send DM to winner {}. Please run `/usekey` and copy a key manually if they want one.\n\n**No key has been used.**",
+          member.user
+        ),
+      }
+    }
+    Some((member, None)) => format!(
+      "Raffle drawn with {entry_count} {entries}, but no unused keys were left for winner {}. Please add one and run `/usekey` to give them one if they want one.",
+      member.user
+    ),
+    None => format!("Raffle closed with {entry_count} {entries}, but no eligible winner was found."),
+  };
+
+  let log_embed = BloomBotEmbed::new()
+    .title("**Raffle Drawn**")
+    .description(log_message)
+    .footer(
+      CreateEmbedFooter::new(format!(
+        "Started by {} ({})",
+        ctx.author().name,
+        ctx.author().id
+      ))
+      .icon_url(ctx.author().avatar_url().unwrap_or_default()),
+    );
+
+  log_channel
+    .send_message(ctx, CreateMessage::new().embed(log_embed))
+    .await?;
+
+  Ok(())
+}
@@ -4,7 +4,7 @@ use poise::CreateReply;
 
 use crate::commands::helpers::common::Visibility;
 use crate::commands::helpers::database::{self, MessageType};
-use crate::config::{BloomBotEmbed, CHANNELS, EMOJI};
+use crate::config::{BloomBotEmbed, CHANNELS};
 use crate::database::DatabaseHandler;
 use crate::Context;
 
@@ -23,6 +23,7 @@ pub async fn remove_entry(
   ctx: Context<'_>,
   #[description = "The ID of the entry to remove"] id: String,
 ) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
   let guild_id = ctx
     .guild_id()
     .with_context(|| "Failed to retrieve guild ID from context")?;
@@ -37,7 +38,7 @@ pub async fn remove_entry(
         CreateReply::default()
           .content(format!(
             "{} No entry found with that ID.\n-# Use </recent:1135659962580865128> to view a list of your entries and their IDs.",
-            EMOJI.mminfo
+            emoji.mminfo
           ))
           .ephemeral(true),
       )
@@ -51,7 +52,7 @@ pub async fn remove_entry(
         CreateReply::default()
           .content(format!(
             "{} You can only remove your own entries.",
-            EMOJI.mminfo
+            emoji.mminfo
           ))
           .ephemeral(true),
       )
@@ -59,12 +60,12 @@ pub async fn remove_entry(
     return Ok(());
   }
 
-  DatabaseHandler::remove_meditation_entry(&mut transaction, id.as_str()).await?;
+  DatabaseHandler::remove_meditation_entry(&mut transaction, &guild_id, id.as_str()).await?;
 
   database::commit_and_say(
     ctx,
     transaction,
-    MessageType::TextOnly(format!("{} Entry has been removed.", EMOJI.mmcheck)),
+    MessageType::TextOnly(format!("{} Entry has been removed.", emoji.mmcheck)),
     Visibility::Ephemeral,
   )
   .await?;
@@ -1,8 +1,11 @@
-use anyhow::Result;
+use anyhow::{Context as AnyhowContext, Result};
 use poise::serenity_prelude::{builder::*, ChannelId, Message};
 use poise::CreateReply;
 
+use crate::commands::helpers::threads;
 use crate::config::{BloomBotEmbed, CHANNELS, ROLES};
+use crate::data::report::{Report, ReportSource};
+use crate::database::DatabaseHandler;
 use crate::Context;
 
 /// Report a message to server staff
@@ -20,11 +23,14 @@ pub async fn report_message(
   ctx: Context<'_>,
   #[description = "Message to report"] message: Message,
 ) -> Result<()> {
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
   let reporting_user = ctx.author();
   let report_channel_id = ChannelId::new(CHANNELS.reportchannel);
   let message_link = message.link().clone();
   let message_user = message.author;
-  let message_channel_name = message.channel_id.name(ctx).await?;
+  let message_channel_name = threads::channel_label(ctx, message.channel_id).await?;
 
   let message_content = if message.content.is_empty() {
     match message.attachments.first() {
@@ -35,6 +41,20 @@ pub async fn report_message(
     message.content.clone()
   };
 
+  let report = Report::new(
+    guild_id,
+    message.channel_id,
+    message.id,
+    message_user.id,
+    ReportSource::Member,
+    message_content.clone(),
+  )
+  .reported_by(reporting_user.id);
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+  DatabaseHandler::add_report(&mut transaction, &report).await?;
+  DatabaseHandler::commit_transaction(transaction).await?;
+
   report_channel_id
     .send_message(
       &ctx,
@@ -46,8 +66,12 @@ pub async fn report_message(
             .description(message_content)
             .field("Link", format!("[Go to message]({message_link})"), false)
             .footer(CreateEmbedFooter::new(format!(
-              "Author ID: {}\nReported via context menu in #{} by {} ({})",
-              &message_user.id, message_channel_name, reporting_user.name, reporting_user.id
+              "Author ID: {}\nReported via context menu in #{} by {} ({})\nReport ID: {}",
+              &message_user.id,
+              message_channel_name,
+              reporting_user.name,
+              reporting_user.id,
+              report.id
             )))
             .timestamp(message.timestamp),
         ),
@@ -0,0 +1,305 @@
+use anyhow::{Context as AnyhowContext, Result};
+use chrono::{Duration as ChronoDuration, NaiveDate, Utc};
+use poise::serenity_prelude::{ChannelId, CreateEmbedFooter, CreateMessage};
+use poise::CreateReply;
+
+use crate::commands::helpers::common::Visibility;
+use crate::commands::helpers::database::{self, MessageType};
+use crate::commands::helpers::{retreats, tracking};
+use crate::config::{BloomBotEmbed, CHANNELS};
+use crate::data::meditation::Meditation;
+use crate::data::retreat::Retreat;
+use crate::data::tracking_profile::{privacy, Privacy, Status};
+use crate::database::DatabaseHandler;
+use crate::events;
+use crate::Context;
+
+/// The longest retreat which can be logged at once, in days.
+const MAX_RETREAT_DAYS: i64 = 60;
+/// How far back a retreat can start. Older retreats can be added by staff.
+const MAX_DAYS_BACK: i64 = 90;
+
+/// Parses the first and last days of a retreat, relative to the user's local date.
+fn parse_retreat_dates(
+  start: &str,
+  end: &str,
+  today: NaiveDate,
+) -> Result<(NaiveDate, NaiveDate), String> {
+  let parse = |input: &str| {
+    NaiveDate::parse_from_str(input.trim(), "%Y-%m-%d").map_err(|_| {
+      format!(
+        "`{}` is not a valid date. Please use the format YYYY-MM-DD.",
+        input.trim()
+      )
+    })
+  };
+  let (start, end) = (parse(start)?, parse(end)?);
+
+  if end < start {
+    return Err("The retreat must end on or after the day it starts.".to_owned());
+  }
+
+  if end > today {
+    return Err(format!(
+      "{end} is in the future. Please log your retreat once it's over."
+    ));
+  }
+
+  if start < today - ChronoDuration::days(MAX_DAYS_BACK) {
+    return Err(format!(
+      "{start} is more than {MAX_DAYS_BACK} days ago. Please contact staff to add older retreats."
+    ));
+  }
+
+  if (end - start).num_days() + 1 > MAX_RETREAT_DAYS {
+    return Err(format!(
+      "Retreats of up to {MAX_RETREAT_DAYS} days can be logged at once. Please log longer retreats in parts."
+    ));
+  }
+
+  Ok((start, end))
+}
+
+/// Commands for meditation retreats
+///
+/// Commands to log the time you spent on a meditation retreat.
+#[poise::command(
+  slash_command,
+  category = "Meditation Tracking",
+  subcommands("log"),
+  subcommand_required,
+  guild_only
+)]
+#[allow(clippy::unused_async)]
+pub async fn retreat(_: Context<'_>) -> Result<()> {
+  Ok(())
+}
+
+/// Log a multi-day retreat
+///
+/// Adds a meditation entry for each day of a retreat, with the same time each day, so your streak continues through the retreat. Dates are given as YYYY-MM-DD, and retreats of up to 60 days from the past 90 days can be logged.
+///
+/// Logged retreats are shared with staff, who may follow up if anything looks off.
+#[poise::command(slash_command)]
+async fn log(
+  ctx: Context<'_>,
+  #[description = "The first day of the retreat (YYYY-MM-DD)"]
+  #[max_length = 10]
+  start: String,
+  #[description = "The last day of the retreat (YYYY-MM-DD)"]
+  #[max_length = 10]
+  end: String,
+  #[description = "Hours meditated each day (e.g., 6 or 7.5)"]
+  #[min = 0.5]
+  #[max = 16.0]
+  hours_per_day: f64,
+  #[description = "Set visibility of response (defaults to public)"] privacy: Option<Privacy>,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let data = ctx.data();
+
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+  let user_id = ctx.author().id;
+
+  let mut transaction = data.db.start_transaction_with_retry(5).await?;
+
+  let tracking_profile =
+    DatabaseHandler::get_tracking_profile(&mut transaction, &guild_id, &user_id)
+      .await?
+      .unwrap_or_default();
+
+  let privacy = privacy!(privacy, tracking_profile.tracking.privacy);
+  let private_reply = privacy || tracking_profile.tracking.redact_minutes;
+
+  let now = Utc::now() + ChronoDuration::minutes(i64::from(tracking_profile.utc_offset));
+  let today = now.date_naive();
+
+  let (start_date, end_date) = match parse_retreat_dates(&start, &end, today) {
+    Ok(dates) => dates,
+    Err(e) => {
+      ctx
+        .send(
+          CreateReply::default()
+            .content(format!("{} {e} No entries have been added.", emoji.mminfo))
+            .ephemeral(true),
+        )
+        .await?;
+      return Ok(());
+    }
+  };
+
+  if let Some(existing) = DatabaseHandler::get_overlapping_retreat(
+    &mut transaction,
+    &guild_id,
+    &user_id,
+    start_date,
+    end_date,
+  )
+  .await?
+  {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} You've already logged a retreat from {} to {}, which overlaps these dates. No entries have been added.",
+            emoji.mminfo,
+            existing.start_date.format("%B %-d"),
+            existing.end_date.format("%B %-d, %Y")
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  if private_reply {
+    ctx.defer_ephemeral().await?;
+  } else {
+    ctx.defer().await?;
+  }
+
+  // Between 30 and 960 minutes, as limited by the option
+  #[allow(clippy::cast_possible_truncation)]
+  let minutes_per_day = (hours_per_day * 60.0).round() as i32;
+  let retreat = Retreat::new(guild_id, user_id, start_date, end_date, minutes_per_day);
+
+  let mut entries = Vec::new();
+  for date in retreat.dates() {
+    // As with `/addmulti`, earlier days are placed at midday so they fall on the intended date
+    let datetime = if date == today {
+      now
+    } else {
+      date
+        .and_hms_opt(12, 0, 0)
+        .with_context(|| "Failed to assign time to retreat date")?
+        .and_utc()
+    };
+    entries.push(Meditation::new(
+      guild_id,
+      user_id,
+      minutes_per_day,
+      0,
+      &datetime,
+    ));
+  }
+  DatabaseHandler::add_retreat(&mut transaction, &retreat, &entries).await?;
+
+  let user_sum =
+    DatabaseHandler::get_user_meditation_sum(&mut transaction, &guild_id, &user_id).await?;
+  // Entries were added to past days, so the stored longest streak can't be relied on
+  let streak = DatabaseHandler::recalculate_streak(&mut transaction, &guild_id, &user_id).await?;
+
+  let update_leaderboards = tracking::leaderboards_due(&mut transaction, &guild_id).await?;
+  let guild_milestone =
+    tracking::get_guild_milestone(&mut transaction, &guild_id, retreat.total_minutes()).await?;
+
+  let mut description = format!(
+    "Added **{} minutes** across {} days of retreat. Your total meditation time is now {user_sum} minutes :tada:",
+    retreat.total_minutes(),
+    retreat.days()
+  );
+  if tracking_profile.streak.status == Status::Enabled {
+    description.push_str(&format!(
+      "\n\nYour current meditation streak is {} days.",
+      streak.current
+    ));
+  }
+
+  let embed = BloomBotEmbed::new()
+    .title("Retreat Logged")
+    .description(description)
+    .field(
+      "Dates",
+      format!(
+        "{} to {}",
+        start_date.format("%B %-d"),
+        end_date.format("%B %-d, %Y")
+      ),
+      true,
+    )
+    .field("Each Day", format!("{minutes_per_day} minutes"), true)
+    .footer(CreateEmbedFooter::new(
+      "If any of these dates are wrong, please open a ticket with /ticket open.",
+    ));
+
+  // Posted without mentions, like streak repairs, so logged retreats don't interrupt staff
+  ChannelId::new(CHANNELS.logs)
+    .send_message(
+      ctx,
+      CreateMessage::new()
+        .embed(retreats::review_embed(
+          &retreat,
+          ctx.author(),
+          streak.current,
+        ))
+        .components(retreats::buttons(&retreat.id)),
+    )
+    .await?;
+
+  database::commit_and_say(
+    ctx,
+    transaction,
+    MessageType::EmbedOnly(Box::new(embed)),
+    if private_reply {
+      Visibility::Ephemeral
+    } else {
+      Visibility::Public
+    },
+  )
+  .await?;
+
+  tracking::post_guild_milestone(&ctx, guild_milestone).await?;
+
+  let member = guild_id.member(ctx, user_id).await?;
+  tracking::update_time_roles(&ctx, &member, &tracking_profile.profile, user_sum, privacy).await?;
+  if tracking_profile.streak.status == Status::Enabled {
+    tracking::update_streak_roles(
+      &ctx,
+      &member,
+      &tracking_profile.profile,
+      streak.current,
+      privacy,
+    )
+    .await?;
+  }
+
+  if update_leaderboards {
+    tokio::spawn(events::leaderboards::update(
+      module_path!(),
+      ctx.serenity_context().http.clone(),
+      data.db.clone(),
+      guild_id,
+    ));
+  }
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_parse_retreat_dates() {
+    let today = NaiveDate::from_ymd_opt(2024, 11, 22).unwrap_or_default();
+    let date = |day| NaiveDate::from_ymd_opt(2024, 11, day).unwrap_or_default();
+
+    assert_eq!(
+      parse_retreat_dates("2024-11-08", " 2024-11-17 ", today),
+      Ok((date(8), date(17)))
+    );
+    assert_eq!(
+      parse_retreat_dates("2024-11-22", "2024-11-22", today),
+      Ok((date(22), date(22)))
+    );
+
+    // Backwards, in the future, too long ago, too long, and badly formatted
+    assert!(parse_retreat_dates("2024-11-17", "2024-11-08", today).is_err());
+    assert!(parse_retreat_dates("2024-11-20", "2024-11-23", today).is_err());
+    assert!(parse_retreat_dates("2024-08-01", "2024-08-10", today).is_err());
+    assert!(parse_retreat_dates("2024-09-01", "2024-11-10", today).is_err());
+    assert!(parse_retreat_dates("08/11/2024", "2024-11-17", today).is_err());
+  }
+}
@@ -0,0 +1,577 @@
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context as AnyhowContext, Result};
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use log::error;
+use poise::serenity_prelude::{builder::*, ButtonStyle, ComponentInteractionCollector};
+use poise::serenity_prelude::{
+  ChannelId, ComponentInteraction, FormattedTimestamp, FormattedTimestampStyle, Mentionable,
+  Message, UserId,
+};
+use poise::CreateReply;
+
+use crate::commands::helpers::tracking;
+use crate::config::BloomBotEmbed;
+use crate::data::meditation::Meditation;
+use crate::database::DatabaseHandler;
+use crate::Context;
+
+/// How long participants have to log their sit once it has ended.
+const LOG_WINDOW: Duration = Duration::from_secs(60 * 60);
+
+const ANNOUNCEMENT_TITLE: &str = ":lotus: Sitting Now";
+
+const SIT_ENDED: &str = "This sit has ended.";
+
+/// Which of the `/sitnow` messages a sit was read from.
+#[derive(Debug, PartialEq, Eq)]
+enum SitMessage {
+  /// The announcement, which is edited once the sit has ended.
+  Announcement { ended: bool },
+  /// The "Time's up!" message which had the log button.
+  TimesUp,
+}
+
+/// A sit read back from one of its `/sitnow` messages.
+#[derive(Debug, PartialEq, Eq)]
+struct ParsedSit {
+  message: SitMessage,
+  minutes: i32,
+  sitters: Vec<UserId>,
+}
+
+/// Finds the users mentioned in `text`, in order.
+fn mentioned_users(text: &str) -> Vec<UserId> {
+  text
+    .split("<@")
+    .skip(1)
+    .filter_map(|rest| {
+      let (id, _) = rest.split_once('>')?;
+      id.trim_start_matches('!')
+        .parse::<u64>()
+        .ok()
+        .filter(|id| *id != 0)
+        .map(UserId::new)
+    })
+    .collect()
+}
+
+/// Reads the length of a sit from the bolded **N-minute** in its messages.
+fn sit_minutes(text: &str) -> Option<i32> {
+  let (before, _) = text.split_once("-minute**")?;
+  before.rsplit_once("**")?.1.parse().ok()
+}
+
+/// Reads a sit from the content and embed (as its title and description) of a message Bloom
+/// sent for `/sitnow`. Returns `None` for any other message.
+fn parse_sit(content: &str, embed: Option<(&str, &str)>) -> Option<ParsedSit> {
+  if let Some((_, description)) = embed.filter(|(title, _)| *title == ANNOUNCEMENT_TITLE) {
+    let (_, sitting) = description.split_once("**Sitting:**")?;
+    return Some(ParsedSit {
+      message: SitMessage::Announcement {
+        ended: description.contains(SIT_ENDED),
+      },
+      minutes: sit_minutes(description)?,
+      sitters: mentioned_users(sitting),
+    });
+  }
+
+  let (sitting, _) = content.split_once("Time's up")?;
+  Some(ParsedSit {
+    message: SitMessage::TimesUp,
+    minutes: sit_minutes(content)?,
+    sitters: mentioned_users(sitting),
+  })
+}
+
+fn sitters_list(host: UserId, joiners: &HashSet<UserId>) -> String {
+  std::iter::once(host)
+    .chain(joiners.iter().copied())
+    .map(|user_id| user_id.mention().to_string())
+    .collect::<Vec<_>>()
+    .join(", ")
+}
+
+fn announcement_embed(
+  host: UserId,
+  minutes: i32,
+  ends_at: DateTime<Utc>,
+  joiners: &HashSet<UserId>,
+  ended: bool,
+) -> CreateEmbed {
+  let status = if ended {
+    format!("{SIT_ENDED} Thank you for sitting together!")
+  } else {
+    format!(
+      "The sit ends {}. Press **Join** to sit along!",
+      FormattedTimestamp::new(ends_at.into(), Some(FormattedTimestampStyle::RelativeTime))
+    )
+  };
+
+  BloomBotEmbed::new()
+    .title(ANNOUNCEMENT_TITLE)
+    .description(format!(
+      "{} is starting a **{minutes}-minute** sit.\n\n{status}\n\n**Sitting:** {}",
+      host.mention(),
+      sitters_list(host, joiners)
+    ))
+}
+
+async fn reply_ephemeral(ctx: Context<'_>, press: &ComponentInteraction, content: String) {
+  let response = CreateInteractionResponse::Message(
+    CreateInteractionResponseMessage::new()
+      .content(content)
+      .ephemeral(true),
+  );
+  if let Err(e) = press.create_response(ctx, response).await {
+    error!("Failed to respond to sit interaction: {e}");
+  }
+}
+
+/// Adds a sit for a participant at `ended_at`, using their UTC offset like `/add`. Sits count
+/// toward `channel_id`, the channel they were announced in. Returns the participant's new
+/// total, along with any guild milestone reached.
+async fn log_sit(
+  ctx: Context<'_>,
+  user_id: UserId,
+  minutes: i32,
+  ended_at: DateTime<Utc>,
+  channel_id: ChannelId,
+) -> Result<(i64, Option<i64>)> {
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  let tracking_profile =
+    DatabaseHandler::get_tracking_profile(&mut transaction, &guild_id, &user_id)
+      .await?
+      .unwrap_or_default();
+
+  let datetime = ended_at + ChronoDuration::minutes(i64::from(tracking_profile.utc_offset));
+  let settings = ctx.data().settings.get(&ctx.data().db, guild_id).await?;
+  let meditation = Meditation::new(guild_id, user_id, minutes, 0, &datetime)
+    .channel(tracking::segment_channel(ctx, &settings, channel_id).await);
+
+  DatabaseHandler::add_meditation_entry(&mut transaction, &meditation).await?;
+
+  let user_sum =
+    DatabaseHandler::get_user_meditation_sum(&mut transaction, &guild_id, &user_id).await?;
+  let guild_milestone =
+    tracking::get_guild_milestone(&mut transaction, &guild_id, i64::from(minutes)).await?;
+
+  DatabaseHandler::commit_transaction(transaction).await?;
+
+  Ok((user_sum, guild_milestone))
+}
+
+/// Start a sit and invite others to join
+///
+/// Announces that you're starting a sit of the specified length and invites others to join with a button. When the time is up, everyone who sat gets a button to log the sit. If you miss it, right-click the sit's messages and go to "Apps" > "Log This Session".
+///
+/// Sits are logged like `/add`. Time and streak roles are updated the next time you use `/add`.
+#[poise::command(
+  slash_command,
+  category = "Meditation Tracking",
+  rename = "sitnow",
+  guild_only
+)]
+pub async fn sit_now(
+  ctx: Context<'_>,
+  #[description = "How long you'll be sitting, in minutes"]
+  #[min = 1]
+  #[max = 120]
+  minutes: i32,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+  let host = ctx.author().id;
+
+  let settings = ctx.data().settings.get(&ctx.data().db, guild_id).await?;
+
+  let channel_id = settings.sit_channel.unwrap_or_else(|| ctx.channel_id());
+
+  let ctx_id = ctx.id();
+  let join_id = format!("{ctx_id}join");
+  let log_id = format!("{ctx_id}log");
+
+  let duration = Duration::from_secs(u64::try_from(minutes)? * 60);
+  let deadline = Instant::now() + duration;
+  let ends_at = Utc::now() + ChronoDuration::minutes(i64::from(minutes));
+  let mut joiners = HashSet::new();
+
+  let mut announcement = channel_id
+    .send_message(
+      ctx,
+      CreateMessage::new()
+        .embed(announcement_embed(host, minutes, ends_at, &joiners, false))
+        .components(vec![CreateActionRow::Buttons(vec![CreateButton::new(
+          join_id.clone(),
+        )
+        .label("Join")
+        .style(ButtonStyle::Success)])]),
+    )
+    .await?;
+
+  ctx
+    .send(
+      CreateReply::default()
+        .content(format!(
+          "{} Your sit has been announced in {}. Enjoy your sit!",
+          emoji.mmcheck,
+          channel_id.mention()
+        ))
+        .ephemeral(true),
+    )
+    .await?;
+
+  // Collect joins until the sit ends
+  loop {
+    let remaining = deadline.saturating_duration_since(Instant::now());
+    if remaining.is_zero() {
+      break;
+    }
+
+    let ctx_id = ctx_id.to_string();
+    let Some(press) = ComponentInteractionCollector::new(ctx)
+      .filter(move |press| press.data.custom_id.starts_with(&ctx_id))
+      .timeout(remaining)
+      .await
+    else {
+      break;
+    };
+
+    if press.data.custom_id != join_id {
+      continue;
+    }
+
+    let user_id = press.user.id;
+    if user_id == host || !joiners.insert(user_id) {
+      reply_ephemeral(
+        ctx,
+        &press,
+        format!("{} You're already part of this sit.", emoji.mminfo),
+      )
+      .await;
+      continue;
+    }
+
+    reply_ephemeral(
+      ctx,
+      &press,
+      format!(
+        "{} You've joined the sit. It ends {}.",
+        emoji.mmcheck,
+        FormattedTimestamp::new(ends_at.into(), Some(FormattedTimestampStyle::RelativeTime))
+      ),
+    )
+    .await;
+
+    announcement
+      .edit(
+        ctx,
+        EditMessage::new().embed(announcement_embed(host, minutes, ends_at, &joiners, false)),
+      )
+      .await?;
+  }
+
+  announcement
+    .edit(
+      ctx,
+      EditMessage::new()
+        .embed(announcement_embed(host, minutes, ends_at, &joiners, true))
+        .components(Vec::new()),
+    )
+    .await?;
+
+  let mut log_message = channel_id
+    .send_message(
+      ctx,
+      CreateMessage::new()
+        .content(format!(
+          "{} Time's up on the **{minutes}-minute** sit! Thank you for sitting together. Press the button below to log your sit.",
+          sitters_list(host, &joiners)
+        ))
+        .components(vec![CreateActionRow::Buttons(vec![CreateButton::new(
+          log_id.clone(),
+        )
+        .label(format!("Log {minutes} minutes"))
+        .style(ButtonStyle::Primary)])]),
+    )
+    .await?;
+
+  // Give everyone who sat a chance to log the sit
+  let log_deadline = Instant::now() + LOG_WINDOW;
+  let mut logged = HashSet::new();
+  loop {
+    if logged.len() == joiners.len() + 1 {
+      break;
+    }
+
+    let remaining = log_deadline.saturating_duration_since(Instant::now());
+    if remaining.is_zero() {
+      break;
+    }
+
+    let ctx_id = ctx_id.to_string();
+    let Some(press) = ComponentInteractionCollector::new(ctx)
+      .filter(move |press| press.data.custom_id.starts_with(&ctx_id))
+      .timeout(remaining)
+      .await
+    else {
+      break;
+    };
+
+    if press.data.custom_id != log_id {
+      continue;
+    }
+
+    let user_id = press.user.id;
+    if user_id != host && !joiners.contains(&user_id) {
+      reply_ephemeral(
+        ctx,
+        &press,
+        format!(
+          "{} Only members who joined this sit can log it. You can add your own time with `/add`.",
+          emoji.mminfo
+        ),
+      )
+      .await;
+      continue;
+    }
+
+    if logged.contains(&user_id) {
+      reply_ephemeral(
+        ctx,
+        &press,
+        format!("{} You've already logged this sit.", emoji.mminfo),
+      )
+      .await;
+      continue;
+    }
+
+    match log_sit(ctx, user_id, minutes, Utc::now(), channel_id).await {
+      Ok((user_sum, guild_milestone)) => {
+        logged.insert(user_id);
+        reply_ephemeral(
+          ctx,
+          &press,
+          format!(
+            "{} Added **{minutes} minutes** to your meditation time! Your total meditation time is now {user_sum} minutes :tada:",
+            emoji.mmcheck
+          ),
+        )
+        .await;
+        tracking::post_guild_milestone(&ctx, guild_milestone).await?;
+      }
+      Err(e) => {
+        error!("Failed to log sit for user {user_id}: {e:?}");
+        reply_ephemeral(
+          ctx,
+          &press,
+          format!(
+            "{} An error occurred while logging your sit. Please try again, or add it with `/add`.",
+            emoji.mminfo
+          ),
+        )
+        .await;
+      }
+    }
+  }
+
+  log_message
+    .edit(ctx, EditMessage::new().components(Vec::new()))
+    .await?;
+
+  Ok(())
+}
+
+/// Log a sit you forgot to log
+///
+/// Logs a `/sitnow` sit you took part in, for when you forgot to press the log button before it expired. The sit is logged like `/add`, at the time it ended.
+///
+/// To use, right-click the sit's announcement or its "Time's up!" message, then go to "Apps" > "Log This Session".
+#[poise::command(
+  ephemeral,
+  context_menu_command = "Log This Session",
+  category = "Context Menu Commands",
+  guild_only
+)]
+pub async fn log_session(
+  ctx: Context<'_>,
+  #[description = "Sit message to log"] message: Message,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+  let user_id = ctx.author().id;
+
+  let sit = if message.author.id == ctx.cache().current_user().id {
+    let embed = message
+      .embeds
+      .first()
+      .and_then(|embed| Some((embed.title.as_deref()?, embed.description.as_deref()?)));
+    parse_sit(&message.content, embed)
+  } else {
+    None
+  };
+
+  let Some(sit) = sit else {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} This isn't a `/sitnow` sit. Right-click a sit's announcement or its \"Time's up!\" message to log it.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  };
+
+  let ended_at = match sit.message {
+    SitMessage::Announcement { ended: false } => {
+      ctx
+        .send(
+          CreateReply::default()
+            .content(format!("{} This sit hasn't ended yet.", emoji.mminfo))
+            .ephemeral(true),
+        )
+        .await?;
+      return Ok(());
+    }
+    SitMessage::Announcement { ended: true } => {
+      message.timestamp.to_utc() + ChronoDuration::minutes(i64::from(sit.minutes))
+    }
+    SitMessage::TimesUp => message.timestamp.to_utc(),
+  };
+
+  if !sit.sitters.contains(&user_id) {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} Only members who joined this sit can log it. You can add your own time with `/add`.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let log_window = ChronoDuration::from_std(LOG_WINDOW)?;
+  if Utc::now() < ended_at + log_window {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} This sit can still be logged with the **Log {} minutes** button under its \"Time's up!\" message.",
+            emoji.mminfo, sit.minutes
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  // Sits logged with the button are added while the window is open, and sits logged here are
+  // added at the time they ended
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+  let utc_offset = DatabaseHandler::get_tracking_profile(&mut transaction, &guild_id, &user_id)
+    .await?
+    .unwrap_or_default()
+    .utc_offset;
+  let logged_from = ended_at + ChronoDuration::minutes(i64::from(utc_offset));
+  let already_logged =
+    DatabaseHandler::get_user_meditation_entries(&mut transaction, &guild_id, &user_id)
+      .await?
+      .iter()
+      .any(|entry| {
+        entry.minutes == sit.minutes
+          && entry.occurred_at >= logged_from
+          && entry.occurred_at <= logged_from + log_window
+      });
+  drop(transaction);
+
+  if already_logged {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!("{} You've already logged this sit.", emoji.mminfo))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let (user_sum, guild_milestone) =
+    log_sit(ctx, user_id, sit.minutes, ended_at, message.channel_id).await?;
+
+  ctx
+    .send(
+      CreateReply::default()
+        .content(format!(
+          "{} Added **{} minutes** to your meditation time! Your total meditation time is now {user_sum} minutes :tada:",
+          emoji.mmcheck, sit.minutes
+        ))
+        .ephemeral(true),
+    )
+    .await?;
+
+  tracking::post_guild_milestone(&ctx, guild_milestone).await?;
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_parse_sit() {
+    let host = UserId::new(123u64);
+    let joiner = UserId::new(456u64);
+    let joiners = HashSet::from([joiner]);
+
+    let description = format!(
+      "{} is starting a **20-minute** sit.\n\n{SIT_ENDED} Thank you for sitting together!\n\n**Sitting:** {}",
+      host.mention(),
+      sitters_list(host, &joiners)
+    );
+    assert_eq!(
+      parse_sit("", Some((ANNOUNCEMENT_TITLE, &description))),
+      Some(ParsedSit {
+        message: SitMessage::Announcement { ended: true },
+        minutes: 20,
+        sitters: vec![host, joiner],
+      })
+    );
+
+    let content = format!(
+      "{} Time's up on the **45-minute** sit! Thank you for sitting together. Press the button below to log your sit.",
+      sitters_list(host, &HashSet::new())
+    );
+    assert_eq!(
+      parse_sit(&content, None),
+      Some(ParsedSit {
+        message: SitMessage::TimesUp,
+        minutes: 45,
+        sitters: vec![host],
+      })
+    );
+
+    assert_eq!(parse_sit("<@123> Time's up!", None), None);
+    assert_eq!(parse_sit("Welcome!", Some(("Hello", &description))), None);
+    assert_eq!(
+      mentioned_users("<@!789> and <@0> and <@abc>"),
+      vec![UserId::new(789u64)]
+    );
+  }
+}
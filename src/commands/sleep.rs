@@ -0,0 +1,322 @@
+use anyhow::{Context as AnyhowContext, Result};
+use chrono::{Duration as ChronoDuration, NaiveDate, Utc};
+use csv::{ReaderBuilder, StringRecord};
+use log::info;
+use poise::serenity_prelude::{GuildId, Message, UserId};
+use poise::CreateReply;
+
+use crate::commands::helpers::common::Visibility;
+use crate::commands::helpers::database::{self, MessageType};
+use crate::data::wellness_metric::{self, WellnessMetric};
+use crate::database::DatabaseHandler;
+use crate::Context;
+
+/// The largest sleep file which can be imported, in bytes.
+const MAX_IMPORT_SIZE: u32 = 262_144;
+
+/// The most days which can be imported at once, about three years of nightly logs.
+const MAX_IMPORT_DAYS: usize = 1100;
+
+/// Commands for logging sleep
+///
+/// Commands to log or import the hours you sleep, so you can see how your sleep relates to your meditation with `/stats sleep`.
+#[poise::command(
+  slash_command,
+  category = "Meditation Tracking",
+  subcommands("log_sleep", "import"),
+  subcommand_required,
+  guild_only
+)]
+#[allow(clippy::unused_async)]
+pub async fn sleep(_: Context<'_>) -> Result<()> {
+  Ok(())
+}
+
+/// The member's current date, in the UTC offset set with `/customize offset`.
+async fn local_today(ctx: Context<'_>, guild_id: &GuildId, user_id: &UserId) -> Result<NaiveDate> {
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+  let utc_offset = DatabaseHandler::get_tracking_profile(&mut transaction, guild_id, user_id)
+    .await?
+    .unwrap_or_default()
+    .utc_offset;
+
+  Ok((Utc::now() + ChronoDuration::minutes(i64::from(utc_offset))).date_naive())
+}
+
+/// Log the hours you slept
+///
+/// Logs the hours you slept on a night, e.g., `7.5`, `7:30`, or `7h 30m`. Sleep is logged for the day you woke up, defaulting to today. Logging sleep again for the same day replaces what was logged before.
+///
+/// Sleep logs are private.
+#[poise::command(slash_command, rename = "log")]
+async fn log_sleep(
+  ctx: Context<'_>,
+  #[description = "The hours you slept, e.g., 7.5, 7:30, or 7h 30m"]
+  #[max_length = 20]
+  hours: String,
+  #[description = "The day you woke up, as YYYY-MM-DD (Defaults to today)"]
+  #[max_length = 10]
+  date: Option<String>,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+  let user_id = ctx.author().id;
+
+  let Some(sleep_hours) = wellness_metric::parse_sleep_hours(&hours) else {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} Please enter between 0 and 24 hours, e.g., `7.5`, `7:30`, or `7h 30m`.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  };
+
+  let today = local_today(ctx, &guild_id, &user_id).await?;
+  let date = match date {
+    Some(date) => match NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d") {
+      Ok(date) if date <= today => date,
+      Ok(_) => {
+        ctx
+          .send(
+            CreateReply::default()
+              .content(format!(
+                "{} Sleep can't be logged for a day in the future.",
+                emoji.mminfo
+              ))
+              .ephemeral(true),
+          )
+          .await?;
+        return Ok(());
+      }
+      Err(_) => {
+        ctx
+          .send(
+            CreateReply::default()
+              .content(format!(
+                "{} Please enter the date as YYYY-MM-DD, e.g., `2024-11-02`.",
+                emoji.mminfo
+              ))
+              .ephemeral(true),
+          )
+          .await?;
+        return Ok(());
+      }
+    },
+    None => today,
+  };
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+  let metric = WellnessMetric::new(guild_id, user_id, date, sleep_hours);
+  DatabaseHandler::add_wellness_metric(&mut transaction, &metric).await?;
+
+  database::commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(format!(
+      "{} Logged **{sleep_hours:.1}** hours of sleep for {}. See how your sleep relates to your meditation with `/stats sleep`.",
+      emoji.mmcheck,
+      date.format("%B %-d, %Y")
+    )),
+    Visibility::Ephemeral,
+  )
+  .await?;
+
+  Ok(())
+}
+
+/// Finds the date and hours columns of a sleep file, ignoring case.
+fn sleep_columns(headers: &StringRecord) -> Option<(usize, usize)> {
+  let find = |names: &[&str]| {
+    headers
+      .iter()
+      .position(|header| names.contains(&header.trim().to_lowercase().as_str()))
+  };
+
+  Some((
+    find(&["date", "day", "wake date"])?,
+    find(&["hours", "sleep", "sleep hours", "hours slept", "duration"])?,
+  ))
+}
+
+/// Reads the days and hours slept from a CSV file, skipping rows which can't be read or are
+/// after `today`. Returns the valid rows and the number skipped.
+fn parse_sleep_file(content: &[u8], today: NaiveDate) -> Option<(Vec<(NaiveDate, f32)>, usize)> {
+  let mut rdr = ReaderBuilder::new()
+    .flexible(true)
+    .trim(csv::Trim::All)
+    .from_reader(content);
+  let (date_column, hours_column) = sleep_columns(rdr.headers().ok()?)?;
+
+  let mut days = vec![];
+  let mut skipped = 0;
+  for record in rdr.records() {
+    let day = record.ok().and_then(|record| {
+      let date = NaiveDate::parse_from_str(record.get(date_column)?, "%Y-%m-%d")
+        .ok()
+        .filter(|date| *date <= today)?;
+      let hours = wellness_metric::parse_sleep_hours(record.get(hours_column)?)?;
+      Some((date, hours))
+    });
+    match day {
+      Some(day) => days.push(day),
+      None => skipped += 1,
+    }
+  }
+
+  Some((days, skipped))
+}
+
+/// Import sleep from a file
+///
+/// Imports the hours you slept from a CSV file with `Date` and `Hours` columns, such as an export from a sleep tracker or a spreadsheet. Dates must be YYYY-MM-DD, and hours can be written as `7.5`, `7:30`, or `7h 30m`.
+///
+/// Days which already have sleep logged are replaced. Rows which can't be read, or are in the future, are skipped.
+#[poise::command(slash_command)]
+async fn import(
+  ctx: Context<'_>,
+  #[description = "The message with the CSV file"] message: Message,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+  let user_id = ctx.author().id;
+
+  ctx.defer_ephemeral().await?;
+
+  if message.author.id != user_id {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} You cannot import files uploaded by other users.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let Some(attachment) = message.attachments.first() else {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!("{} No attachment found.", emoji.mminfo))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  };
+
+  if attachment.size > MAX_IMPORT_SIZE {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!("{} File exceeds size limit.", emoji.mminfo))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let content = match attachment.download().await {
+    Ok(content) => content,
+    Err(why) => {
+      info!("Error downloading attachment for sleep import: {:?}", why);
+      ctx
+        .send(
+          CreateReply::default()
+            .content(format!("{} Unable to download attachment.", emoji.mminfo))
+            .ephemeral(true),
+        )
+        .await?;
+      return Ok(());
+    }
+  };
+
+  let today = local_today(ctx, &guild_id, &user_id).await?;
+  let Some((days, skipped)) = parse_sleep_file(&content, today) else {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} Couldn't find the `Date` and `Hours` columns. Please upload a CSV file with a header row containing both.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  };
+
+  if days.is_empty() || days.len() > MAX_IMPORT_DAYS {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} Please upload a file with between 1 and {MAX_IMPORT_DAYS} days of sleep. {skipped} rows couldn't be read.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+  for (date, hours) in &days {
+    let metric = WellnessMetric::new(guild_id, user_id, *date, *hours);
+    DatabaseHandler::add_wellness_metric(&mut transaction, &metric).await?;
+  }
+
+  let skipped = if skipped > 0 {
+    format!(" {skipped} rows couldn't be read and were skipped.")
+  } else {
+    String::new()
+  };
+
+  database::commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(format!(
+      "{} Imported sleep for **{}** days.{skipped} See how your sleep relates to your meditation with `/stats sleep`.",
+      emoji.mmcheck,
+      days.len()
+    )),
+    Visibility::Ephemeral,
+  )
+  .await?;
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_parse_sleep_file() {
+    let today = NaiveDate::from_ymd_opt(2024, 11, 3).unwrap_or_default();
+    let date = |day| NaiveDate::from_ymd_opt(2024, 11, day).unwrap_or_default();
+
+    let content =
+      b"Date,Hours Slept\n2024-11-01,7.5\n2024-11-02,7h 30m\n11/02/2024,8\n2024-11-04,6\n";
+    assert_eq!(
+      parse_sleep_file(content, today),
+      Some((vec![(date(1), 7.5), (date(2), 7.5)], 2))
+    );
+
+    assert_eq!(
+      parse_sleep_file(b"When,Minutes\n2024-11-01,450\n", today),
+      None
+    );
+  }
+}
@@ -1,18 +1,157 @@
 #![allow(clippy::unused_async)]
 
+use std::pin::pin;
+use std::sync::Arc;
+
 use anyhow::{Context as AnyhowContext, Result};
-use log::info;
-use poise::serenity_prelude::{builder::*, CreateAllowedMentions, User};
+use chrono::{NaiveDate, TimeDelta, Utc};
+use futures::TryStreamExt;
+use log::{info, warn};
+use poise::serenity_prelude::{builder::*, ChannelId, CreateAllowedMentions, GuildId};
+use poise::serenity_prelude::{GuildChannel, User};
 use poise::{ChoiceParameter, CreateReply};
+use sqlx::{Postgres, Transaction};
 
-use crate::charts::Chart;
+use crate::bot_config::ChartTheme;
+use crate::chart_cache::{CachedImage, ChartKey};
+use crate::charts::{Chart, ChartFormat};
+use crate::commands::helpers::common::personal_guild_id;
 use crate::commands::helpers::time::Timeframe;
-use crate::config::{BloomBotEmbed, EMOJI, ROLES};
+use crate::config::{BloomBotEmbed, StreakRoles, TimeSumRoles, CHANNELS, ROLES};
+use crate::data::common;
+use crate::data::mood_checkin;
+use crate::data::stats::{LeaderboardType, SortBy, Timeframe as TimeframeStats};
 use crate::data::tracking_profile::{privacy, Privacy, Status};
+use crate::data::wellness_metric;
 use crate::database::DatabaseHandler;
+use crate::events::improved::{self, Comparison};
 use crate::events::leaderboards::{self, LEADERBOARDS};
+use crate::storage::ArtifactKind;
 use crate::Context;
 
+/// How many weeks of meditation are included in server activity charts.
+const ACTIVITY_WEEKS: i64 = 12;
+
+/// How many days of mood check-ins are included in mood charts.
+const MOOD_DAYS: i32 = 30;
+
+/// How many days of sleep are included in sleep charts.
+const SLEEP_DAYS: i32 = 30;
+
+/// The fewest servers which must have turned on global stats before totals are shown, so
+/// they can't be traced back to a single server.
+const MIN_GLOBAL_GUILDS: i64 = 3;
+
+/// Prepares a rendered chart for sending. When object storage is configured, the chart is
+/// uploaded and linked, and otherwise, or if the upload fails, it's attached.
+async fn chart_image(ctx: Context<'_>, chart: &Chart<'_>) -> Result<CachedImage> {
+  let bytes = chart.bytes().await?;
+
+  let storage = &ctx.data().storage;
+  if storage.is_enabled() {
+    match storage
+      .upload(
+        ArtifactKind::Chart,
+        chart.filename(),
+        bytes.clone(),
+        chart.format().content_type(),
+      )
+      .await
+    {
+      Ok(upload) => {
+        return Ok(CachedImage::Url {
+          url: upload.url,
+          embeddable: chart.format().is_embeddable(),
+        })
+      }
+      Err(e) => warn!("Failed to upload chart, attaching it instead: {e:?}"),
+    }
+  }
+
+  Ok(CachedImage::Attachment {
+    filename: chart.filename().to_owned(),
+    bytes: Arc::new(bytes),
+    embeddable: chart.format().is_embeddable(),
+  })
+}
+
+/// Creates a reply showing the chart in `embed`. Charts which can't be shown in embeds, such
+/// as SVG charts, are linked or attached below the embed instead.
+fn chart_reply(image: &CachedImage, embed: CreateEmbed) -> CreateReply {
+  match image {
+    CachedImage::Url {
+      url,
+      embeddable: true,
+    } => CreateReply::default().embed(embed.image(url)),
+    CachedImage::Url {
+      url,
+      embeddable: false,
+    } => CreateReply::default()
+      .embed(embed)
+      .content(format!("[Download chart]({url})")),
+    CachedImage::Attachment {
+      filename,
+      bytes,
+      embeddable,
+    } => {
+      let embed = if *embeddable {
+        embed.image(format!("attachment://{filename}"))
+      } else {
+        embed
+      };
+      CreateReply::default()
+        .embed(embed)
+        .attachment(CreateAttachment::bytes(bytes.to_vec(), filename.clone()))
+    }
+  }
+}
+
+/// Adds the total for the previous 12 periods, shown in comparison charts.
+fn add_comparison_field(
+  embed: CreateEmbed,
+  previous_stats: &[TimeframeStats],
+  stats_type: &StatsType,
+  timeframe_header: &str,
+) -> CreateEmbed {
+  let (label, total) = match stats_type {
+    StatsType::MeditationMinutes => (
+      "Minutes",
+      previous_stats
+        .iter()
+        .map(|stats| stats.sum.unwrap_or(0))
+        .sum::<i64>(),
+    ),
+    StatsType::MeditationCount => (
+      "Sessions",
+      previous_stats
+        .iter()
+        .map(|stats| stats.count.unwrap_or(0))
+        .sum::<i64>(),
+    ),
+  };
+
+  embed.field(
+    format!("{label} The 12 {timeframe_header} Before"),
+    format!("```{total}```"),
+    true,
+  )
+}
+
+/// The chart format chosen by the member running the command, with `/customize stats`.
+async fn default_format(
+  ctx: Context<'_>,
+  transaction: &mut Transaction<'_, Postgres>,
+  guild_id: &GuildId,
+) -> Result<ChartFormat> {
+  Ok(
+    DatabaseHandler::get_tracking_profile(transaction, guild_id, &ctx.author().id)
+      .await?
+      .unwrap_or_default()
+      .stats
+      .chart_format,
+  )
+}
+
 #[allow(clippy::module_name_repetitions)]
 #[derive(ChoiceParameter)]
 pub enum StatsType {
@@ -30,24 +169,34 @@ pub enum ChartStyle {
   Area,
   #[name = "bar chart (combined data)"]
   BarCombined,
+  #[name = "comparison with previous period"]
+  Comparison,
 }
 
 #[derive(ChoiceParameter)]
-pub enum SortBy {
-  #[name = "minutes"]
-  Minutes,
-  #[name = "sessions"]
-  Sessions,
-  #[name = "streak"]
+pub enum ServerChart {
+  #[name = "timeline"]
+  Timeline,
+  #[name = "day of the week"]
+  Weekday,
+  #[name = "hour of the day"]
+  Hour,
+}
+
+#[derive(ChoiceParameter)]
+pub enum RoleTiers {
+  #[name = "time roles"]
+  Time,
+  #[name = "streak roles"]
   Streak,
 }
 
 #[derive(ChoiceParameter)]
-pub enum LeaderboardType {
-  #[name = "Top 5"]
-  Top5,
-  #[name = "Top 10"]
-  Top10,
+enum RangeScope {
+  #[name = "user"]
+  User,
+  #[name = "server"]
+  Server,
 }
 
 #[derive(ChoiceParameter)]
@@ -64,9 +213,18 @@ enum Theme {
 #[poise::command(
   slash_command,
   category = "Meditation Tracking",
-  subcommands("user", "server", "leaderboard"),
-  subcommand_required,
-  guild_only
+  subcommands(
+    "user",
+    "server",
+    "global",
+    "range",
+    "roles",
+    "leaderboard",
+    "improved",
+    "mood",
+    "sleep"
+  ),
+  subcommand_required
 )]
 pub async fn stats(_: Context<'_>) -> Result<()> {
   Ok(())
@@ -77,6 +235,14 @@ pub async fn stats(_: Context<'_>) -> Result<()> {
 /// Shows stats for yourself or a specified user.
 ///
 /// Defaults to daily minutes for yourself. Optionally specify the user, type (minutes or session count), and/or timeframe (daily, weekly, monthly, or yearly).
+///
+/// Charts can also be shown in high resolution, or as an SVG file, for sharper charts on high-DPI displays. Your default format can be set using `/customize stats`.
+///
+/// Setting the visibility here will override your default stats visibility, which can be set using `/customize stats`.
+///
+/// Staff can view private stats for moderation purposes. These are always shown privately, and each view is recorded in the staff logs.
+///
+/// With Bloom added to your account, you can also see your own stats in DMs and other servers. These show the server you most recently tracked meditation in.
 #[poise::command(slash_command)]
 async fn user(
   ctx: Context<'_>,
@@ -89,17 +255,18 @@ async fn user(
   >,
   #[description = "The style of chart (Defaults to bar chart)"] style: Option<ChartStyle>,
   #[description = "Set visibility of response (Defaults to public)"] privacy: Option<Privacy>,
-  #[description = "Toggle between light mode and dark mode (Defaults to dark mode)"] theme: Option<
-    Theme,
-  >,
+  #[description = "Toggle between light mode and dark mode (Defaults to dark mode, unless configured otherwise)"]
+  theme: Option<Theme>,
+  #[description = "The image format of the chart (Defaults to your /customize stats setting)"]
+  format: Option<ChartFormat>,
 ) -> Result<()> {
-  let guild_id = ctx
-    .guild_id()
-    .with_context(|| "Failed to retrieve guild ID from context")?;
+  let user = user.unwrap_or_else(|| ctx.author().clone());
+  let Some(guild_id) = personal_guild_id(ctx, user.id).await? else {
+    return Ok(());
+  };
 
   let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
 
-  let user = user.unwrap_or_else(|| ctx.author().clone());
   let user_nick_or_name = user
     .nick_in(&ctx, guild_id)
     .await
@@ -110,7 +277,22 @@ async fn user(
       .await?
       .unwrap_or_default();
 
-  let privacy = privacy!(privacy, tracking_profile.stats.privacy);
+  let format = match format {
+    Some(format) => format,
+    None if ctx.author().id == user.id => tracking_profile.stats.chart_format,
+    None => default_format(ctx, &mut transaction, &guild_id).await?,
+  };
+
+  // Only staff can view another member's private stats, and always privately
+  let staff_override =
+    ctx.author().id != user.id && tracking_profile.stats.privacy == Privacy::Private;
+
+  // Your own stats follow your default visibility, while others' stats follow their privacy
+  let privacy = if ctx.author().id == user.id {
+    privacy!(privacy, tracking_profile.stats.visibility)
+  } else {
+    staff_override || privacy!(privacy, tracking_profile.stats.privacy)
+  };
 
   if privacy {
     ctx.defer_ephemeral().await?;
@@ -118,10 +300,7 @@ async fn user(
     ctx.defer().await?;
   }
 
-  if ctx.author().id != user.id
-    && tracking_profile.stats.privacy == Privacy::Private
-    && !ctx.author().has_role(&ctx, guild_id, ROLES.staff).await?
-  {
+  if staff_override && !ctx.author().has_role(&ctx, guild_id, ROLES.staff).await? {
     ctx
       .send(
         CreateReply::default()
@@ -150,9 +329,15 @@ async fn user(
   let stats =
     DatabaseHandler::get_user_stats(&mut transaction, &guild_id, &user.id, &timeframe).await?;
 
+  let title = if staff_override {
+    format!("Private Stats for {user_nick_or_name}")
+  } else {
+    format!("Stats for {user_nick_or_name}")
+  };
+
   let mut embed = BloomBotEmbed::new();
   embed = embed
-    .title(format!("Stats for {user_nick_or_name}"))
+    .title(title)
     .author(CreateEmbedAuthor::new(format!("{user_nick_or_name}'s Stats")).icon_url(user.face()));
 
   match stats_type {
@@ -207,7 +392,7 @@ async fn user(
       Theme::LightMode => true,
       Theme::DarkMode => false,
     },
-    None => false,
+    None => ctx.data().bot_config.get().charts.default_theme == ChartTheme::Light,
   };
 
   let chart_stats = DatabaseHandler::get_user_chart_stats(
@@ -216,10 +401,26 @@ async fn user(
     &user.id,
     &timeframe,
     tracking_profile.utc_offset,
+    0,
   )
   .await?;
+  let previous_stats = if let ChartStyle::Comparison = chart_style {
+    let previous_stats = DatabaseHandler::get_user_chart_stats(
+      &mut transaction,
+      &guild_id,
+      &user.id,
+      &timeframe,
+      tracking_profile.utc_offset,
+      12,
+    )
+    .await?;
+    embed = add_comparison_field(embed, &previous_stats, &stats_type, timeframe_header);
+    Some(previous_stats)
+  } else {
+    None
+  };
 
-  let chart = Chart::new()
+  let chart = Chart::with_format(format)
     .await?
     .stats(
       &chart_stats,
@@ -227,15 +428,12 @@ async fn user(
       tracking_profile.utc_offset,
       &stats_type,
       &chart_style,
+      previous_stats.as_deref(),
       bar_color,
       light_mode,
     )
     .await?;
 
-  let file_path = chart.path();
-
-  embed = embed.image(chart.url());
-
   let average = match stats_type {
     StatsType::MeditationMinutes => stats.timeframe_stats.sum.unwrap_or(0) / 12,
     StatsType::MeditationCount => stats.timeframe_stats.count.unwrap_or(0) / 12,
@@ -267,16 +465,37 @@ async fn user(
     )));
   }
 
-  ctx
-    .send({
-      let mut f = CreateReply::default().attachment(CreateAttachment::path(&file_path).await?);
-      f.embeds = vec![embed.clone()];
+  let image = chart_image(ctx, &chart).await?;
+  chart.remove().await?;
 
-      f
-    })
-    .await?;
+  if staff_override {
+    // Log every staff override of stats privacy, for accountability. The stats are only
+    // shown once the override has been logged.
+    let log_embed = BloomBotEmbed::new()
+      .title("Private Stats Viewed")
+      .description(format!(
+        "**Staff Member**: <@{}>\n**User**: <@{}>\n**Type**: {}\n**Timeframe**: {}",
+        ctx.author().id,
+        user.id,
+        stats_type_label,
+        timeframe.name()
+      ))
+      .footer(
+        CreateEmbedFooter::new(format!(
+          "Viewed by {} ({})",
+          ctx.author().name,
+          ctx.author().id
+        ))
+        .icon_url(ctx.author().avatar_url().unwrap_or_default()),
+      );
 
-  chart.remove().await?;
+    ChannelId::new(CHANNELS.logs)
+      .send_message(&ctx, CreateMessage::new().embed(log_embed))
+      .await
+      .with_context(|| "Failed to log staff override of stats privacy")?;
+  }
+
+  ctx.send(chart_reply(&image, embed)).await?;
 
   Ok(())
 }
@@ -285,20 +504,23 @@ async fn user(
 ///
 /// Shows stats for the whole server.
 ///
-/// Defaults to daily minutes. Optionally specify the type (minutes or session count) and/or timeframe (daily, weekly, monthly, or yearly).
-#[poise::command(slash_command)]
+/// Defaults to daily minutes. Optionally specify the type (minutes or session count), timeframe (daily, weekly, monthly, or yearly), and/or chart format. Use the chart option to see which days of the week or hours of the day the server meditates most.
+#[poise::command(slash_command, guild_only)]
 async fn server(
   ctx: Context<'_>,
   #[description = "The type of stats to get (Defaults to minutes)"]
   #[rename = "type"]
   stats_type: Option<StatsType>,
+  #[description = "What to chart, over time or by when members meditate (Defaults to timeline)"]
+  chart: Option<ServerChart>,
   #[description = "The timeframe to get the stats for (Defaults to daily)"] timeframe: Option<
     Timeframe,
   >,
   #[description = "The style of chart (Defaults to bar chart)"] style: Option<ChartStyle>,
-  #[description = "Toggle between light mode and dark mode (Defaults to dark mode)"] theme: Option<
-    Theme,
-  >,
+  #[description = "Toggle between light mode and dark mode (Defaults to dark mode, unless configured otherwise)"]
+  theme: Option<Theme>,
+  #[description = "The image format of the chart (Defaults to your /customize stats setting)"]
+  format: Option<ChartFormat>,
 ) -> Result<()> {
   ctx.defer().await?;
 
@@ -328,15 +550,36 @@ async fn server(
     Timeframe::Daily => "Days",
   };
 
-  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
-
-  let stats = DatabaseHandler::get_guild_stats(&mut transaction, &guild_id, &timeframe).await?;
-
   let mut embed = BloomBotEmbed::new();
   embed = embed
     .title(format!("Stats for {guild_name}"))
     .author(CreateEmbedAuthor::new(format!("{guild_name}'s Stats")).icon_url(guild_icon));
 
+  let light_mode = match theme {
+    Some(theme) => match theme {
+      Theme::LightMode => true,
+      Theme::DarkMode => false,
+    },
+    None => ctx.data().bot_config.get().charts.default_theme == ChartTheme::Light,
+  };
+
+  if let Some(chart @ (ServerChart::Weekday | ServerChart::Hour)) = chart {
+    return server_activity(
+      ctx,
+      guild_id,
+      embed,
+      &chart,
+      &stats_type,
+      light_mode,
+      format,
+    )
+    .await;
+  }
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  let stats = DatabaseHandler::get_guild_stats(&mut transaction, &guild_id, &timeframe).await?;
+
   match stats_type {
     StatsType::MeditationMinutes => {
       embed = embed
@@ -367,54 +610,601 @@ async fn server(
   }
 
   let bar_color = (253, 172, 46, 255);
-  let light_mode = match theme {
-    Some(theme) => match theme {
-      Theme::LightMode => true,
-      Theme::DarkMode => false,
+
+  let chart_stats =
+    DatabaseHandler::get_guild_chart_stats(&mut transaction, &guild_id, &timeframe, 0).await?;
+  let previous_stats = if let ChartStyle::Comparison = chart_style {
+    let previous_stats =
+      DatabaseHandler::get_guild_chart_stats(&mut transaction, &guild_id, &timeframe, 12).await?;
+    embed = add_comparison_field(embed, &previous_stats, &stats_type, timeframe_header);
+    Some(previous_stats)
+  } else {
+    None
+  };
+  let format = match format {
+    Some(format) => format,
+    None => default_format(ctx, &mut transaction, &guild_id).await?,
+  };
+
+  // The same chart is often requested by several members, so it's only rendered again
+  // once the cached chart expires or the stats change
+  let key = ChartKey::new(
+    guild_id,
+    Utc::now().date_naive(),
+    format!(
+      "{} {} {} {light_mode} {}",
+      timeframe.name(),
+      stats_type.name(),
+      chart_style.name(),
+      format.as_str()
+    ),
+    chart_stats.iter().chain(previous_stats.iter().flatten()),
+  );
+  let image = if let Some(image) = ctx.data().chart_cache.get(&key) {
+    image
+  } else {
+    let chart = Chart::with_format(format)
+      .await?
+      .stats(
+        &chart_stats,
+        &timeframe,
+        0,
+        &stats_type,
+        &chart_style,
+        previous_stats.as_deref(),
+        bar_color,
+        light_mode,
+      )
+      .await?;
+    let image = chart_image(ctx, &chart).await?;
+    chart.remove().await?;
+
+    ctx.data().chart_cache.insert(key, image.clone());
+    image
+  };
+
+  ctx.send(chart_reply(&image, embed)).await?;
+
+  Ok(())
+}
+
+/// Show anonymous stats across servers
+///
+/// Shows anonymous totals across all servers running Bloom which have chosen to take part, such as the minutes meditated today and the number of active meditators.
+///
+/// Only totals are shown. Members with private stats are left out, and nothing is shown until enough servers take part. Server admins can include their server using `/config globalstats`.
+#[poise::command(slash_command)]
+async fn global(ctx: Context<'_>) -> Result<()> {
+  let data = ctx.data();
+  let emoji = data.emoji.get(ctx.guild_id());
+
+  let mut transaction = data.db.start_transaction_with_retry(5).await?;
+
+  let stats = DatabaseHandler::get_global_stats(&mut transaction).await?;
+  if stats.guilds < MIN_GLOBAL_GUILDS {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} Not enough servers have turned on global stats yet. Server admins can include their server using `/config globalstats`.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let included = match ctx.guild_id() {
+    Some(guild_id) => Some(
+      DatabaseHandler::get_guild_settings(&mut transaction, &guild_id)
+        .await?
+        .global_stats,
+    ),
+    None => None,
+  };
+
+  let embed = BloomBotEmbed::new()
+    .title("Global Stats")
+    .description(format!(
+      "Anonymous totals across {} participating servers. Members with private stats aren't included.",
+      stats.guilds
+    ))
+    .field(
+      "Minutes Today",
+      format!("```{}```", stats.minutes_today),
+      true,
+    )
+    .field(
+      "Sessions Today",
+      format!("```{}```", stats.sessions_today),
+      true,
+    )
+    .field(
+      "Meditators Today",
+      format!("```{}```", stats.meditators_today),
+      true,
+    )
+    .field(
+      "Meditators This Week",
+      format!("```{}```", stats.meditators_week),
+      true,
+    )
+    .footer(CreateEmbedFooter::new(match included {
+      Some(true) => "This server is included. Today is the current UTC day.",
+      Some(false) => "This server isn't included. Admins can use /config globalstats to take part. Today is the current UTC day.",
+      None => "Today is the current UTC day.",
+    }));
+
+  ctx.send(CreateReply::default().embed(embed)).await?;
+
+  Ok(())
+}
+
+/// Whether the guild has channel segments turned on, needed to filter stats by channel.
+/// Tells the author how to turn them on if not.
+async fn segments_enabled(ctx: Context<'_>, guild_id: GuildId) -> Result<bool> {
+  if ctx
+    .data()
+    .settings
+    .get(&ctx.data().db, guild_id)
+    .await?
+    .channel_segments
+  {
+    return Ok(true);
+  }
+
+  ctx
+    .send(
+      CreateReply::default()
+        .content(format!(
+          "{} Stats can't be filtered by channel because channel segments are turned off. Staff can turn them on with `/config segments`.",
+          ctx.data().emoji.get(Some(guild_id)).mminfo
+        ))
+        .ephemeral(true),
+    )
+    .await?;
+
+  Ok(false)
+}
+
+/// Reads the start and end dates of a range, as YYYY-MM-DD. Returns an explanation if the
+/// dates can't be read, are out of order, or end after `today`.
+fn parse_range(start: &str, end: &str, today: NaiveDate) -> Result<(NaiveDate, NaiveDate), String> {
+  let parse = |date: &str| {
+    NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d").map_err(|_| {
+      format!("`{date}` isn't a valid date. Please use the format YYYY-MM-DD, like `2024-01-31`.")
+    })
+  };
+  let (start, end) = (parse(start)?, parse(end)?);
+
+  if start > end {
+    return Err("The start date must be on or before the end date.".to_owned());
+  }
+  if end > today {
+    return Err("The end date can't be in the future.".to_owned());
+  }
+
+  Ok((start, end))
+}
+
+/// Show stats for a date range
+///
+/// Shows stats for yourself, a specified user, or the whole server between two dates, as YYYY-MM-DD.
+///
+/// The chart shows each day, week, month, or year of the range, depending on how long it is. Dates are in the user's UTC offset, or in UTC for the server.
+///
+/// In servers with channel segments turned on, stats can be limited to meditation tracked in a channel.
+///
+/// Setting the visibility here will override your default stats visibility, which can be set using `/customize stats`. Other members' stats can't be shown if they're set to private.
+#[poise::command(slash_command, guild_only)]
+#[allow(clippy::too_many_arguments)]
+async fn range(
+  ctx: Context<'_>,
+  #[description = "The first day of the range, as YYYY-MM-DD"]
+  #[max_length = 10]
+  start: String,
+  #[description = "The last day of the range, as YYYY-MM-DD"]
+  #[max_length = 10]
+  end: String,
+  #[description = "Whether to show stats for a user or the server (Defaults to user)"]
+  scope: Option<RangeScope>,
+  #[description = "The user to get the stats of (Defaults to you)"] user: Option<User>,
+  #[description = "Only include meditation tracked in this channel"]
+  #[channel_types("Text", "Forum")]
+  channel: Option<GuildChannel>,
+  #[description = "The type of stats to get (Defaults to minutes)"]
+  #[rename = "type"]
+  stats_type: Option<StatsType>,
+  #[description = "Set visibility of response (Defaults to public)"] privacy: Option<Privacy>,
+  #[description = "Toggle between light mode and dark mode (Defaults to dark mode, unless configured otherwise)"]
+  theme: Option<Theme>,
+  #[description = "The image format of the chart (Defaults to your /customize stats setting)"]
+  format: Option<ChartFormat>,
+) -> Result<()> {
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+  let scope = scope.unwrap_or(RangeScope::User);
+  let stats_type = stats_type.unwrap_or(StatsType::MeditationMinutes);
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  let user = user.unwrap_or_else(|| ctx.author().clone());
+  let own_stats = ctx.author().id == user.id;
+  // Server stats follow the author's default visibility
+  let profile_user_id = match scope {
+    RangeScope::User => user.id,
+    RangeScope::Server => ctx.author().id,
+  };
+  let tracking_profile =
+    DatabaseHandler::get_tracking_profile(&mut transaction, &guild_id, &profile_user_id)
+      .await?
+      .unwrap_or_default();
+
+  let privacy = match scope {
+    RangeScope::User if own_stats => privacy!(privacy, tracking_profile.stats.visibility),
+    RangeScope::User => privacy!(privacy, tracking_profile.stats.privacy),
+    RangeScope::Server => privacy!(privacy, tracking_profile.stats.visibility),
+  };
+  if privacy {
+    ctx.defer_ephemeral().await?;
+  } else {
+    ctx.defer().await?;
+  }
+
+  let (name, icon, utc_offset) = match scope {
+    RangeScope::User => {
+      let name = user
+        .nick_in(&ctx, guild_id)
+        .await
+        .unwrap_or_else(|| user.global_name.as_ref().unwrap_or(&user.name).clone());
+
+      if !own_stats && tracking_profile.stats.privacy == Privacy::Private {
+        ctx
+          .send(
+            CreateReply::default()
+              .content(format!("Sorry, {name}'s stats are set to private."))
+              .ephemeral(true)
+              .allowed_mentions(CreateAllowedMentions::new()),
+          )
+          .await?;
+
+        return Ok(());
+      }
+
+      (name, user.face(), tracking_profile.utc_offset)
+    }
+    RangeScope::Server => match guild_id.to_guild_cached(&ctx) {
+      Some(guild) => (guild.name.clone(), guild.icon_url().unwrap_or_default(), 0),
+      None => (
+        "This Server".to_string(),
+        "https://cdn.discordapp.com/embed/avatars/3.png".to_string(),
+        0,
+      ),
     },
-    None => false,
   };
 
-  let chart_stats =
-    DatabaseHandler::get_guild_chart_stats(&mut transaction, &guild_id, &timeframe).await?;
+  let today = (Utc::now() + TimeDelta::minutes(i64::from(utc_offset))).date_naive();
+  let (start, end) = match parse_range(&start, &end, today) {
+    Ok(range) => range,
+    Err(message) => {
+      ctx
+        .send(CreateReply::default().content(message).ephemeral(true))
+        .await?;
+      return Ok(());
+    }
+  };
+
+  let channel_id = channel.map(|channel| channel.id);
+  if channel_id.is_some() && !segments_enabled(ctx, guild_id).await? {
+    return Ok(());
+  }
+
+  let timeframe = Timeframe::for_range(start, end);
+  let range_stats = match scope {
+    RangeScope::User => {
+      DatabaseHandler::get_user_range_stats(
+        &mut transaction,
+        &guild_id,
+        &user.id,
+        &timeframe,
+        start,
+        end,
+        utc_offset,
+        channel_id,
+      )
+      .await?
+    }
+    RangeScope::Server => {
+      DatabaseHandler::get_guild_range_stats(
+        &mut transaction,
+        &guild_id,
+        &timeframe,
+        start,
+        end,
+        channel_id,
+      )
+      .await?
+    }
+  };
+  let format = match format {
+    Some(format) => format,
+    None => default_format(ctx, &mut transaction, &guild_id).await?,
+  };
+  drop(transaction);
+
+  let (minutes, sessions) = range_stats
+    .iter()
+    .fold((0, 0), |(minutes, sessions), (_, stats)| {
+      (
+        minutes + stats.sum.unwrap_or(0),
+        sessions + stats.count.unwrap_or(0),
+      )
+    });
+
+  let in_channel = channel_id.map_or(String::new(), |channel_id| format!(" in <#{channel_id}>"));
+  let embed = BloomBotEmbed::new()
+    .title(format!("Stats for {name}"))
+    .author(CreateEmbedAuthor::new(format!("{name}'s Stats")).icon_url(icon))
+    .description(format!(
+      "From {} to {}{in_channel}",
+      start.format("%B %-d, %Y"),
+      end.format("%B %-d, %Y")
+    ))
+    .field("Meditation Minutes", format!("```{minutes}```"), true)
+    .field("Session Count", format!("```{sessions}```"), true)
+    .footer(CreateEmbedFooter::new(format!(
+      "Grouped by {}",
+      timeframe.unit()
+    )));
+
+  let light_mode = match theme {
+    Some(Theme::LightMode) => true,
+    Some(Theme::DarkMode) => false,
+    None => ctx.data().bot_config.get().charts.default_theme == ChartTheme::Light,
+  };
 
-  let chart = Chart::new()
+  let chart = Chart::with_format(format)
     .await?
-    .stats(
-      &chart_stats,
+    .range(
+      &range_stats,
       &timeframe,
-      0,
       &stats_type,
-      &chart_style,
-      bar_color,
+      (253, 172, 46, 255),
       light_mode,
     )
     .await?;
 
-  let file_path = chart.path();
+  let image = chart_image(ctx, &chart).await?;
+  chart.remove().await?;
 
-  embed = embed.image(chart.url());
+  ctx.send(chart_reply(&image, embed)).await?;
 
-  ctx
-    .send({
-      let mut f = CreateReply::default().attachment(CreateAttachment::path(&file_path).await?);
-      f.embeds = vec![embed.clone()];
+  Ok(())
+}
 
-      f
-    })
-    .await?;
+/// Shows when a server's members meditate, by day of the week or hour of the day, over
+/// the past [`ACTIVITY_WEEKS`] weeks.
+async fn server_activity(
+  ctx: Context<'_>,
+  guild_id: GuildId,
+  mut embed: CreateEmbed,
+  chart: &ServerChart,
+  stats_type: &StatsType,
+  light_mode: bool,
+  format: Option<ChartFormat>,
+) -> Result<()> {
+  let since = Utc::now() - TimeDelta::weeks(ACTIVITY_WEEKS);
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+  let (chart_stats, labels, peak_header) = match chart {
+    ServerChart::Weekday => (
+      DatabaseHandler::get_guild_weekday_stats(&mut transaction, &guild_id, &since).await?,
+      [
+        "Monday",
+        "Tuesday",
+        "Wednesday",
+        "Thursday",
+        "Friday",
+        "Saturday",
+        "Sunday",
+      ]
+      .map(String::from)
+      .to_vec(),
+      "Busiest Day (UTC)",
+    ),
+    ServerChart::Hour => (
+      DatabaseHandler::get_guild_hour_stats(&mut transaction, &guild_id, &since).await?,
+      (0..24).map(|hour| format!("{hour:02}:00")).collect(),
+      "Busiest Hour (UTC)",
+    ),
+    ServerChart::Timeline => unreachable!("Timeline charts are handled by server"),
+  };
+  let format = match format {
+    Some(format) => format,
+    None => default_format(ctx, &mut transaction, &guild_id).await?,
+  };
+  drop(transaction);
+
+  let value = |stats: &TimeframeStats| match stats_type {
+    StatsType::MeditationMinutes => stats.sum.unwrap_or(0),
+    StatsType::MeditationCount => stats.count.unwrap_or(0),
+  };
+  let total: i64 = chart_stats.iter().map(value).sum();
+  let peak = chart_stats
+    .iter()
+    .zip(&labels)
+    .filter(|(stats, _)| value(*stats) > 0)
+    .max_by_key(|(stats, _)| value(*stats))
+    .map_or("None", |(_, label)| label.as_str());
+
+  let total_header = match stats_type {
+    StatsType::MeditationMinutes => format!("Minutes The Past {ACTIVITY_WEEKS} Weeks"),
+    StatsType::MeditationCount => format!("Sessions The Past {ACTIVITY_WEEKS} Weeks"),
+  };
+  embed = embed
+    .field(total_header, format!("```{total}```"), true)
+    .field(peak_header, format!("```{peak}```"), true);
+
+  let key = ChartKey::new(
+    guild_id,
+    Utc::now().date_naive(),
+    format!(
+      "{} {} {light_mode} {}",
+      chart.name(),
+      stats_type.name(),
+      format.as_str()
+    ),
+    &chart_stats,
+  );
+  let image = if let Some(image) = ctx.data().chart_cache.get(&key) {
+    image
+  } else {
+    let chart = Chart::with_format(format)
+      .await?
+      .activity(
+        &chart_stats,
+        chart,
+        stats_type,
+        (253, 172, 46, 255),
+        light_mode,
+      )
+      .await?;
+    let image = chart_image(ctx, &chart).await?;
+    chart.remove().await?;
+
+    ctx.data().chart_cache.insert(key, image.clone());
+    image
+  };
+
+  ctx.send(chart_reply(&image, embed)).await?;
+
+  Ok(())
+}
+
+/// Show role distribution
+///
+/// Shows how many members hold each time or streak role tier.
+///
+/// Defaults to time roles. Optionally specify the roles (time or streak), theme (light mode or dark mode), and/or chart format.
+#[poise::command(slash_command, required_permissions = "MANAGE_ROLES", guild_only)]
+async fn roles(
+  ctx: Context<'_>,
+  #[description = "The roles to show (Defaults to time roles)"] roles: Option<RoleTiers>,
+  #[description = "Toggle between light mode and dark mode (Defaults to dark mode, unless configured otherwise)"]
+  theme: Option<Theme>,
+  #[description = "The image format of the chart (Defaults to your /customize stats setting)"]
+  format: Option<ChartFormat>,
+) -> Result<()> {
+  ctx.defer().await?;
+
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
 
+  let roles = roles.unwrap_or(RoleTiers::Time);
+  let light_mode = match theme {
+    Some(theme) => match theme {
+      Theme::LightMode => true,
+      Theme::DarkMode => false,
+    },
+    None => ctx.data().bot_config.get().charts.default_theme == ChartTheme::Light,
+  };
+
+  let mut time_counts = [0u64; TimeSumRoles::ALL.len()];
+  let mut streak_counts = [0u64; StreakRoles::ALL.len()];
+  let mut members = pin!(guild_id.members_iter(ctx.http()));
+  while let Some(member) = members.try_next().await? {
+    for role in &member.roles {
+      if let Some(tier) = TimeSumRoles::from_role_id(*role) {
+        time_counts[tier as usize] += 1;
+      } else if let Some(tier) = StreakRoles::from_role_id(*role) {
+        streak_counts[tier as usize] += 1;
+      }
+    }
+  }
+
+  let (title, counts, tiers): (&str, &[u64], Vec<(String, String)>) = match roles {
+    RoleTiers::Time => (
+      "Members by Time Role",
+      &time_counts,
+      TimeSumRoles::ALL
+        .iter()
+        .map(|tier| {
+          (
+            compact_number(tier.min_sum().unsigned_abs()),
+            format!("{} {}+ minutes", tier.to_role_icon(), tier.min_sum()),
+          )
+        })
+        .collect(),
+    ),
+    RoleTiers::Streak => (
+      "Members by Streak Role",
+      &streak_counts,
+      StreakRoles::ALL
+        .iter()
+        .map(|tier| {
+          (
+            compact_number(tier.min_streak()),
+            format!("{} {}+ days", tier.to_role_icon(), tier.min_streak()),
+          )
+        })
+        .collect(),
+    ),
+  };
+
+  let (x_labels, descriptions): (Vec<String>, Vec<String>) = tiers.into_iter().unzip();
+  let description = descriptions
+    .iter()
+    .zip(counts)
+    .map(|(tier, count)| format!("{tier}: **{count}**"))
+    .collect::<Vec<String>>()
+    .join("\n");
+  let embed = BloomBotEmbed::new()
+    .title(title)
+    .description(description)
+    .footer(CreateEmbedFooter::new(format!(
+      "{} members hold a role",
+      counts.iter().sum::<u64>()
+    )));
+
+  let format = match format {
+    Some(format) => format,
+    None => {
+      let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+      default_format(ctx, &mut transaction, &guild_id).await?
+    }
+  };
+  let chart = Chart::with_format(format)
+    .await?
+    .roles(title, counts, x_labels, (253, 172, 46, 255), light_mode)
+    .await?;
+  let image = chart_image(ctx, &chart).await?;
   chart.remove().await?;
 
+  ctx.send(chart_reply(&image, embed)).await?;
+
   Ok(())
 }
 
+/// Shortens a number for a chart label, such as 1000 to 1k.
+fn compact_number(number: u64) -> String {
+  if number >= 1000 && number % 1000 == 0 {
+    format!("{}k", number / 1000)
+  } else {
+    number.to_string()
+  }
+}
+
 /// Show tracking leaderboard
 ///
 /// Shows the tracking leaderboard, available in several configurations.
 ///
 /// Defaults to monthly top 5, sorted by minutes, in dark mode. Optionally specify the timeframe (daily, weekly, monthly, or yearly), sort (minutes, sessions, or streak), and theme (light mode or dark mode).
-#[poise::command(slash_command)]
+///
+/// In servers with channel segments turned on, the leaderboard can be limited to meditation tracked in a channel.
+#[poise::command(slash_command, guild_only)]
 async fn leaderboard(
   ctx: Context<'_>,
   #[description = "The leaderboard timeframe (Defaults to monthly)"] timeframe: Option<Timeframe>,
@@ -422,12 +1212,23 @@ async fn leaderboard(
   #[description = "The leaderboard type (Defaults to Top 5)"]
   #[rename = "type"]
   leaderboard_type: Option<LeaderboardType>,
-  #[description = "Toggle between light mode and dark mode (Defaults to dark mode)"] theme: Option<
-    Theme,
-  >,
+  #[description = "Only include meditation tracked in this channel"]
+  #[channel_types("Text", "Forum")]
+  channel: Option<GuildChannel>,
+  #[description = "Toggle between light mode and dark mode (Defaults to dark mode, unless configured otherwise)"]
+  theme: Option<Theme>,
 ) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
   ctx.defer().await?;
 
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+  let channel_id = channel.map(|channel| channel.id);
+  if channel_id.is_some() && !segments_enabled(ctx, guild_id).await? {
+    return Ok(());
+  }
+
   let timeframe = timeframe.unwrap_or(Timeframe::Monthly);
   let sort_by = sort.unwrap_or(SortBy::Minutes);
   let leaderboard_type = leaderboard_type.unwrap_or(LeaderboardType::Top5);
@@ -437,10 +1238,11 @@ async fn leaderboard(
       Theme::LightMode => true,
       Theme::DarkMode => false,
     },
-    None => false,
+    None => ctx.data().bot_config.get().charts.default_theme == ChartTheme::Light,
   };
 
-  if !light_mode {
+  // Pre-generated leaderboards only cover the whole server
+  if !light_mode && channel_id.is_none() {
     let chart = match timeframe {
       Timeframe::Yearly => match sort_by {
         SortBy::Minutes => match leaderboard_type {
@@ -519,7 +1321,7 @@ async fn leaderboard(
           CreateReply::default()
             .content(format!(
               "{} Sorry, no leaderboard data available.",
-              EMOJI.mminfo
+              emoji.mminfo
             ))
             .ephemeral(true)
             .allowed_mentions(CreateAllowedMentions::new()),
@@ -529,20 +1331,31 @@ async fn leaderboard(
     return Ok(());
   }
 
-  let guild_id = ctx
-    .guild_id()
-    .with_context(|| "Failed to retrieve guild ID from context")?;
-
   let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
 
-  let stats = DatabaseHandler::get_leaderboard_stats(
-    &mut transaction,
-    &guild_id,
-    &timeframe,
-    &sort_by,
-    &leaderboard_type,
-  )
-  .await?;
+  let stats = match channel_id {
+    Some(channel_id) => {
+      DatabaseHandler::get_channel_leaderboard_stats(
+        &mut transaction,
+        &guild_id,
+        &channel_id,
+        &timeframe,
+        &sort_by,
+        &leaderboard_type,
+      )
+      .await?
+    }
+    None => {
+      DatabaseHandler::get_leaderboard_stats(
+        &mut transaction,
+        &guild_id,
+        &timeframe,
+        &sort_by,
+        &leaderboard_type,
+      )
+      .await?
+    }
+  };
 
   let leaderboard_data = leaderboards::process_stats(ctx.http(), &guild_id, &stats).await?;
 
@@ -558,21 +1371,13 @@ async fn leaderboard(
       )
       .await?;
 
-    let file_path = chart.path();
-
-    let embed = BloomBotEmbed::new().image(chart.url());
+    let image = chart_image(ctx, &chart).await?;
+    chart.remove().await?;
 
     ctx
-      .send(
-        CreateReply::default()
-          .embed(embed)
-          .ephemeral(false)
-          .attachment(CreateAttachment::path(&file_path).await?),
-      )
+      .send(chart_reply(&image, BloomBotEmbed::new()).ephemeral(false))
       .await?;
 
-    chart.remove().await?;
-
     return Ok(());
   }
 
@@ -581,7 +1386,7 @@ async fn leaderboard(
       CreateReply::default()
         .content(format!(
           "{} Sorry, no leaderboard data available.",
-          EMOJI.mminfo
+          emoji.mminfo
         ))
         .ephemeral(true)
         .allowed_mentions(CreateAllowedMentions::new()),
@@ -590,3 +1395,261 @@ async fn leaderboard(
 
   Ok(())
 }
+
+/// Show the most improved members
+///
+/// Shows the members with the largest increase in minutes meditated, comparing the last 7 days to the 7 days before, or the last 30 days to the 30 days before.
+///
+/// Members whose stats are private are not included, and members with anonymous tracking are shown anonymously.
+#[poise::command(slash_command, guild_only)]
+async fn improved(
+  ctx: Context<'_>,
+  #[description = "The comparison to make (Defaults to week over week)"] comparison: Option<
+    Comparison,
+  >,
+) -> Result<()> {
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  ctx.defer().await?;
+
+  let comparison = comparison.unwrap_or(Comparison::Weekly);
+  let (previous_start, current_start, end) = comparison.bounds(Utc::now());
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  let improvements = DatabaseHandler::get_most_improved(
+    &mut transaction,
+    &guild_id,
+    &previous_start,
+    &current_start,
+    &end,
+    10,
+  )
+  .await?;
+
+  ctx
+    .send(
+      CreateReply::default()
+        .embed(improved::improved_embed(comparison, &improvements))
+        .allowed_mentions(CreateAllowedMentions::new()),
+    )
+    .await?;
+
+  Ok(())
+}
+
+/// Show how your mood relates to your meditation
+///
+/// Shows your mood check-ins from `/checkin` over the last 30 days, alongside the minutes you meditated each day, and how closely the two are related.
+///
+/// Mood stats are always shown privately.
+#[poise::command(slash_command, guild_only)]
+async fn mood(
+  ctx: Context<'_>,
+  #[description = "Toggle between light mode and dark mode (Defaults to dark mode, unless configured otherwise)"]
+  theme: Option<Theme>,
+  #[description = "The image format of the chart (Defaults to your /customize stats setting)"]
+  format: Option<ChartFormat>,
+) -> Result<()> {
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+  let user_id = ctx.author().id;
+
+  ctx.defer_ephemeral().await?;
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  let tracking_profile =
+    DatabaseHandler::get_tracking_profile(&mut transaction, &guild_id, &user_id)
+      .await?
+      .unwrap_or_default();
+  let format = format.unwrap_or(tracking_profile.stats.chart_format);
+  let today =
+    (Utc::now() + TimeDelta::minutes(i64::from(tracking_profile.utc_offset))).date_naive();
+
+  let days = DatabaseHandler::get_mood_days(
+    &mut transaction,
+    &guild_id,
+    &user_id,
+    today,
+    MOOD_DAYS,
+    tracking_profile.utc_offset,
+  )
+  .await?;
+
+  let moods: Vec<i16> = days.iter().filter_map(|day| day.mood).collect();
+  if moods.is_empty() {
+    ctx
+      .send(
+        CreateReply::default()
+          .content("You haven't checked in during the last 30 days. Use `/checkin` to record how you feel, and your mood will be shown here alongside your meditation.")
+          .ephemeral(true),
+      )
+      .await?;
+
+    return Ok(());
+  }
+
+  #[allow(clippy::cast_precision_loss)]
+  let average =
+    f64::from(moods.iter().map(|mood| i32::from(*mood)).sum::<i32>()) / moods.len() as f64;
+
+  let relationship = match mood_checkin::correlation(&days) {
+    Some(correlation) => format!(
+      "Over the last 30 days, there is {} correlation ({correlation:.2}) between your mood and the minutes you meditated.",
+      common::describe_correlation(correlation)
+    ),
+    None => "Check in on a few more days to see how your mood relates to your meditation.".to_owned(),
+  };
+
+  let embed = BloomBotEmbed::new()
+    .title("Mood and Meditation")
+    .author(
+      CreateEmbedAuthor::new(format!("{}'s Mood", ctx.author().name)).icon_url(ctx.author().face()),
+    )
+    .description(relationship)
+    .field(
+      "Check-ins The Past 30 Days",
+      format!("```{}```", moods.len()),
+      true,
+    )
+    .field("Average Mood", format!("```{average:.1} / 5```"), true);
+
+  let light_mode = match theme {
+    Some(Theme::LightMode) => true,
+    Some(Theme::DarkMode) => false,
+    None => ctx.data().bot_config.get().charts.default_theme == ChartTheme::Light,
+  };
+
+  let chart = Chart::with_format(format)
+    .await?
+    .mood(&days, (253, 172, 46, 255), light_mode)
+    .await?;
+
+  let image = chart_image(ctx, &chart).await?;
+  chart.remove().await?;
+
+  ctx.send(chart_reply(&image, embed)).await?;
+
+  Ok(())
+}
+
+/// Show how your sleep relates to your meditation
+///
+/// Shows the hours you slept over the last 30 days, logged with `/sleep`, alongside the minutes you meditated each day, and how closely the two are related.
+///
+/// Sleep stats are always shown privately.
+#[poise::command(slash_command, guild_only)]
+async fn sleep(
+  ctx: Context<'_>,
+  #[description = "Toggle between light mode and dark mode (Defaults to dark mode, unless configured otherwise)"]
+  theme: Option<Theme>,
+  #[description = "The image format of the chart (Defaults to your /customize stats setting)"]
+  format: Option<ChartFormat>,
+) -> Result<()> {
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+  let user_id = ctx.author().id;
+
+  ctx.defer_ephemeral().await?;
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  let tracking_profile =
+    DatabaseHandler::get_tracking_profile(&mut transaction, &guild_id, &user_id)
+      .await?
+      .unwrap_or_default();
+  let format = format.unwrap_or(tracking_profile.stats.chart_format);
+  let today =
+    (Utc::now() + TimeDelta::minutes(i64::from(tracking_profile.utc_offset))).date_naive();
+
+  let days = DatabaseHandler::get_sleep_days(
+    &mut transaction,
+    &guild_id,
+    &user_id,
+    today,
+    SLEEP_DAYS,
+    tracking_profile.utc_offset,
+  )
+  .await?;
+
+  let nights: Vec<f32> = days.iter().filter_map(|day| day.hours).collect();
+  if nights.is_empty() {
+    ctx
+      .send(
+        CreateReply::default()
+          .content("You haven't logged any sleep during the last 30 days. Use `/sleep log` or `/sleep import` to add your sleep, and it will be shown here alongside your meditation.")
+          .ephemeral(true),
+      )
+      .await?;
+
+    return Ok(());
+  }
+
+  #[allow(clippy::cast_precision_loss)]
+  let average = nights.iter().sum::<f32>() / nights.len() as f32;
+
+  let relationship = match wellness_metric::correlation(&days) {
+    Some(correlation) => format!(
+      "Over the last 30 days, there is {} correlation ({correlation:.2}) between the hours you slept and the minutes you meditated.",
+      common::describe_correlation(correlation)
+    ),
+    None => "Log a few more nights of sleep to see how it relates to your meditation.".to_owned(),
+  };
+
+  let embed = BloomBotEmbed::new()
+    .title("Sleep and Meditation")
+    .author(
+      CreateEmbedAuthor::new(format!("{}'s Sleep", ctx.author().name))
+        .icon_url(ctx.author().face()),
+    )
+    .description(relationship)
+    .field(
+      "Nights Logged The Past 30 Days",
+      format!("```{}```", nights.len()),
+      true,
+    )
+    .field("Average Sleep", format!("```{average:.1} hours```"), true);
+
+  let light_mode = match theme {
+    Some(Theme::LightMode) => true,
+    Some(Theme::DarkMode) => false,
+    None => ctx.data().bot_config.get().charts.default_theme == ChartTheme::Light,
+  };
+
+  let chart = Chart::with_format(format)
+    .await?
+    .sleep(&days, (253, 172, 46, 255), light_mode)
+    .await?;
+
+  let image = chart_image(ctx, &chart).await?;
+  chart.remove().await?;
+
+  ctx.send(chart_reply(&image, embed)).await?;
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_parse_range() {
+    let date = |month, day| NaiveDate::from_ymd_opt(2024, month, day).unwrap_or_default();
+    let today = date(11, 15);
+
+    assert_eq!(
+      parse_range("2024-10-01", " 2024-11-15", today),
+      Ok((date(10, 1), today))
+    );
+    assert!(parse_range("2024-11-02", "2024-11-01", today).is_err());
+    assert!(parse_range("2024-11-01", "2024-11-16", today).is_err());
+    assert!(parse_range("11/01/2024", "2024-11-02", today)
+      .is_err_and(|message| message.contains("YYYY-MM-DD")));
+  }
+}
@@ -1,30 +1,54 @@
 use anyhow::{Context as AnyhowContext, Result};
-use poise::serenity_prelude::User;
+use chrono::{Duration as ChronoDuration, NaiveDate, Utc};
+use poise::serenity_prelude::{ChannelId, CreateMessage, User};
+use poise::CreateReply;
 
-use crate::commands::helpers::common::Visibility;
+use crate::commands::helpers::common::{self, Visibility};
 use crate::commands::helpers::database::{self, MessageType};
+use crate::commands::helpers::streak_repairs;
+use crate::config::{CHANNELS, ROLES};
+use crate::data::streak_repair::StreakRepair;
 use crate::data::tracking_profile::Privacy;
 use crate::database::DatabaseHandler;
-use crate::{config::ROLES, Context};
+use crate::Context;
+
+/// How many days back a missed day can be repaired.
+const REPAIR_WINDOW_DAYS: i64 = 7;
+
+/// Commands for your meditation streak
+///
+/// Commands to see your meditation streak, or to ask staff to repair it.
+#[poise::command(
+  slash_command,
+  category = "Meditation Tracking",
+  subcommands("show", "repair"),
+  subcommand_required
+)]
+#[allow(clippy::unused_async)]
+pub async fn streak(_: Context<'_>) -> Result<()> {
+  Ok(())
+}
 
 /// See your current meditation streak
 ///
 /// Shows your current meditation streak. Setting the visibility here will override your custom streak privacy settings.
 ///
 /// Can also be used to check another member's streak, unless set to private.
-#[poise::command(slash_command, category = "Meditation Tracking", guild_only)]
-pub async fn streak(
+///
+/// With Bloom added to your account, you can also check your own streak in DMs and other servers. This shows your streak in the server you most recently tracked meditation in.
+#[poise::command(slash_command)]
+async fn show(
   ctx: Context<'_>,
   #[description = "The user to check the streak of"] user: Option<User>,
   #[description = "Set visibility of response (Defaults to public)"] privacy: Option<Privacy>,
 ) -> Result<()> {
-  let guild_id = ctx
-    .guild_id()
-    .with_context(|| "Failed to retrieve guild ID from context")?;
   let user_id = match &user {
     Some(user) => user.id,
     None => ctx.author().id,
   };
+  let Some(guild_id) = common::personal_guild_id(ctx, user_id).await? else {
+    return Ok(());
+  };
 
   let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
   let streak = DatabaseHandler::get_streak(&mut transaction, &guild_id, &user_id).await?;
@@ -115,3 +139,115 @@ pub async fn streak(
 
   Ok(())
 }
+
+/// Ask staff to repair your streak
+///
+/// Asks staff to count a day you missed toward your streak, for when it was broken by a technicality, such as Bloom being unavailable when you tried to log your session. Days up to a week ago can be repaired.
+///
+/// Approved days count toward your streak without adding any meditation time. You'll get a DM once staff have reviewed your request.
+#[poise::command(slash_command, guild_only)]
+async fn repair(
+  ctx: Context<'_>,
+  #[description = "Why the day should count toward your streak"]
+  #[max_length = 512]
+  reason: String,
+  #[description = "The day you missed, as YYYY-MM-DD (Defaults to yesterday)"]
+  #[max_length = 10]
+  date: Option<String>,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+  let user_id = ctx.author().id;
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  let utc_offset = DatabaseHandler::get_tracking_profile(&mut transaction, &guild_id, &user_id)
+    .await?
+    .unwrap_or_default()
+    .utc_offset;
+  let today = (Utc::now() + ChronoDuration::minutes(i64::from(utc_offset))).date_naive();
+  let earliest = today - ChronoDuration::days(REPAIR_WINDOW_DAYS);
+
+  let date = match date {
+    Some(date) => match NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d") {
+      Ok(date) => date,
+      Err(_) => {
+        ctx
+          .send(
+            CreateReply::default()
+              .content(format!(
+                "{} Please enter the date as YYYY-MM-DD, e.g., `2024-11-02`.",
+                emoji.mminfo
+              ))
+              .ephemeral(true),
+          )
+          .await?;
+        return Ok(());
+      }
+    },
+    None => today - ChronoDuration::days(1),
+  };
+
+  if date >= today || date < earliest {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} Only days from the past week can be repaired, from {} to yesterday.",
+            emoji.mminfo,
+            earliest.format("%B %-d")
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let repair = StreakRepair::new(guild_id, user_id, date, reason);
+  if !DatabaseHandler::add_streak_repair(&mut transaction, &repair).await? {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} You've already asked for {} to be repaired.",
+            emoji.mminfo,
+            date.format("%B %-d, %Y")
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let streak = DatabaseHandler::get_streak(&mut transaction, &guild_id, &user_id).await?;
+
+  // Posted without mentions, like watchlist flags, so requests don't interrupt staff
+  ChannelId::new(CHANNELS.logs)
+    .send_message(
+      ctx,
+      CreateMessage::new()
+        .embed(streak_repairs::request_embed(
+          &repair,
+          ctx.author(),
+          streak.current,
+        ))
+        .components(streak_repairs::buttons(&repair.id)),
+    )
+    .await?;
+
+  database::commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(format!(
+      "{} Your request to repair your streak for {} has been sent to staff. You'll get a DM once it has been reviewed.",
+      emoji.mmcheck,
+      date.format("%B %-d, %Y")
+    )),
+    Visibility::Ephemeral,
+  )
+  .await?;
+
+  Ok(())
+}
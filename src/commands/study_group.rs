@@ -0,0 +1,563 @@
+use anyhow::{Context as AnyhowContext, Result};
+use chrono::{Duration as ChronoDuration, NaiveTime, Utc};
+use poise::serenity_prelude::{ChannelType, GuildChannel, Mentionable};
+use poise::CreateReply;
+
+use crate::commands::helpers::common::Visibility;
+use crate::commands::helpers::database::{self, MessageType};
+use crate::commands::helpers::pagination::{PageRowRef, PageType, Paginator};
+use crate::config::ENTRIES_PER_PAGE;
+use crate::data::recurring_post::{PostDay, RecurringPost};
+use crate::data::study_group::{StudyGroup, StudyGroupMember};
+use crate::database::DatabaseHandler;
+use crate::Context;
+
+/// Suggests study group names containing `partial`. Returns no suggestions outside of a
+/// guild or if the lookup fails.
+async fn autocomplete_group(ctx: Context<'_>, partial: &str) -> impl Iterator<Item = String> {
+  let groups = match ctx.guild_id() {
+    Some(guild_id) => match ctx.data().db.start_transaction_with_retry(5).await {
+      Ok(mut transaction) => DatabaseHandler::get_study_groups(&mut transaction, &guild_id)
+        .await
+        .unwrap_or_default(),
+      Err(_) => Vec::new(),
+    },
+    None => Vec::new(),
+  };
+
+  let partial = partial.to_lowercase();
+  groups
+    .into_iter()
+    .map(|group| group.name)
+    .filter(move |name| name.to_lowercase().contains(&partial))
+}
+
+async fn reply_not_found(ctx: Context<'_>, name: &str) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  ctx
+    .send(
+      CreateReply::default()
+        .content(format!(
+          "{} No study group called **{name}** was found.",
+          emoji.mminfo
+        ))
+        .ephemeral(true),
+    )
+    .await?;
+
+  Ok(())
+}
+
+/// Commands for study groups
+///
+/// Commands to join a group reading a text together, such as a sutta or a book, share how far you've read each week, and see how the rest of the group is getting on.
+#[poise::command(
+  slash_command,
+  category = "Utilities",
+  rename = "studygroup",
+  subcommands(
+    "create", "prompts", "remove", "join", "leave", "update", "progress", "list"
+  ),
+  subcommand_required,
+  guild_only
+)]
+#[allow(clippy::unused_async)]
+pub async fn study_group(_: Context<'_>) -> Result<()> {
+  Ok(())
+}
+
+/// Create a study group
+///
+/// Creates a study group for reading a text together, discussed in the channel of your choice. Set up weekly discussion prompts with `/studygroup prompts`.
+///
+/// Requires `Manage Messages` permissions.
+#[poise::command(slash_command, required_permissions = "MANAGE_MESSAGES")]
+async fn create(
+  ctx: Context<'_>,
+  #[description = "The name of the group"]
+  #[max_length = 40]
+  name: String,
+  #[description = "The text the group is reading (e.g., Satipatthana Sutta)"]
+  #[max_length = 200]
+  reading: String,
+  #[description = "The channel the group discusses the text in"]
+  #[channel_types("Text", "News")]
+  channel: GuildChannel,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  if !matches!(channel.kind, ChannelType::Text | ChannelType::News) || channel.guild_id != guild_id
+  {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} Please choose a text or announcement channel in this server.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let name = name.trim().to_owned();
+  let reading = reading.trim().to_owned();
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  let problem = if name.is_empty() || reading.is_empty() {
+    Some("Please enter a name for the group and the text it's reading.".to_owned())
+  } else if DatabaseHandler::get_study_group(&mut transaction, &guild_id, &name)
+    .await?
+    .is_some()
+  {
+    Some(format!(
+      "A study group called **{name}** already exists. Please choose another name."
+    ))
+  } else {
+    None
+  };
+  if let Some(problem) = problem {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!("{} {problem}", emoji.mminfo))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let study_group = StudyGroup::new(guild_id, name, reading, channel.id, ctx.author().id);
+  DatabaseHandler::add_study_group(&mut transaction, &study_group).await?;
+
+  database::commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(format!(
+      "{} **{}** has been created for reading *{}* in {}. Members can join with `/studygroup join`.",
+      emoji.mmcheck,
+      study_group.name,
+      study_group.reading,
+      channel.mention()
+    )),
+    Visibility::Ephemeral,
+  )
+  .await?;
+
+  Ok(())
+}
+
+/// Set up discussion prompts for a study group
+///
+/// Posts a discussion prompt in the group's channel on a schedule, opening a thread on each one. The time is in the UTC offset set with `/customize offset`, or UTC if none has been set. Replaces the group's existing prompts, if any.
+///
+/// Requires `Manage Messages` permissions.
+#[poise::command(slash_command, required_permissions = "MANAGE_MESSAGES")]
+async fn prompts(
+  ctx: Context<'_>,
+  #[description = "The study group"]
+  #[autocomplete = "autocomplete_group"]
+  group: String,
+  #[description = "Which days to post on"] day: PostDay,
+  #[description = "The time to post (HH:MM, 24-hour)"] time: String,
+  #[description = "The discussion prompt, which can include {date}"]
+  #[max_length = 1500]
+  prompt: String,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let Ok(time) = NaiveTime::parse_from_str(time.trim(), "%H:%M") else {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} Please enter the time as HH:MM, e.g., `18:30`.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  };
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  let Some(study_group) =
+    DatabaseHandler::get_study_group(&mut transaction, &guild_id, group.trim()).await?
+  else {
+    return reply_not_found(ctx, &group).await;
+  };
+
+  let prompt = prompt.trim();
+  let post_name = format!("{} discussion", study_group.name);
+  let problem = if prompt.is_empty() {
+    Some("Please enter a discussion prompt.".to_owned())
+  } else if DatabaseHandler::get_recurring_posts(&mut transaction, &guild_id)
+    .await?
+    .iter()
+    .any(|post| {
+      post.name.eq_ignore_ascii_case(&post_name)
+        && study_group.recurring_post_id.as_ref() != Some(&post.id)
+    })
+  {
+    Some(format!(
+      "A recurring post called **{post_name}** already exists. Please rename or remove it with `/config recurring` first."
+    ))
+  } else {
+    None
+  };
+  if let Some(problem) = problem {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!("{} {problem}", emoji.mminfo))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let utc_offset =
+    DatabaseHandler::get_tracking_profile(&mut transaction, &guild_id, &ctx.author().id)
+      .await?
+      .unwrap_or_default()
+      .utc_offset;
+
+  let post = RecurringPost::new(
+    guild_id,
+    study_group.channel_id,
+    post_name,
+    format!(
+      "**{}** · *{}*\n\n{prompt}\n\n-# Share how far you've read with `/studygroup update`.",
+      study_group.name, study_group.reading
+    ),
+    Some(format!("{}: {{date}}", study_group.name)),
+    day,
+    time,
+    utc_offset,
+    ctx.author().id,
+  );
+  DatabaseHandler::set_study_group_prompts(&mut transaction, &study_group, &post).await?;
+
+  database::commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(format!(
+      "{} Discussion prompts for **{}** will be posted in {}. Schedule: {}. The first prompt is <t:{}:R>.",
+      emoji.mmcheck,
+      study_group.name,
+      study_group.channel_id.mention(),
+      post.schedule(),
+      post.next_post_at.timestamp()
+    )),
+    Visibility::Ephemeral,
+  )
+  .await?;
+
+  Ok(())
+}
+
+/// Remove a study group
+///
+/// Removes a study group, along with its members' progress and its discussion prompts. Prompts which have already been posted aren't affected.
+///
+/// Requires `Manage Messages` permissions.
+#[poise::command(slash_command, required_permissions = "MANAGE_MESSAGES")]
+async fn remove(
+  ctx: Context<'_>,
+  #[description = "The study group to remove"]
+  #[autocomplete = "autocomplete_group"]
+  group: String,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  if DatabaseHandler::remove_study_group(&mut transaction, &guild_id, group.trim()).await? == 0 {
+    return reply_not_found(ctx, &group).await;
+  }
+
+  database::commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(format!("{} **{group}** has been removed.", emoji.mmcheck)),
+    Visibility::Ephemeral,
+  )
+  .await?;
+
+  Ok(())
+}
+
+/// Join a study group
+///
+/// Joins a study group, so you can share your reading progress with `/studygroup update`.
+#[poise::command(slash_command)]
+async fn join(
+  ctx: Context<'_>,
+  #[description = "The study group to join"]
+  #[autocomplete = "autocomplete_group"]
+  group: String,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  let Some(study_group) =
+    DatabaseHandler::get_study_group(&mut transaction, &guild_id, group.trim()).await?
+  else {
+    return reply_not_found(ctx, &group).await;
+  };
+
+  let member = StudyGroupMember::new(&study_group.id, ctx.author().id);
+  let message = if DatabaseHandler::add_study_group_member(&mut transaction, &member).await? {
+    format!(
+      "{} You've joined **{}**, reading *{}* in {}. Share how far you've read each week with `/studygroup update`.",
+      emoji.mmcheck,
+      study_group.name,
+      study_group.reading,
+      study_group.channel_id.mention()
+    )
+  } else {
+    format!(
+      "{} You're already in **{}**.",
+      emoji.mminfo, study_group.name
+    )
+  };
+
+  database::commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(message),
+    Visibility::Ephemeral,
+  )
+  .await?;
+
+  Ok(())
+}
+
+/// Leave a study group
+///
+/// Leaves a study group. Your reading progress for the group is removed.
+#[poise::command(slash_command)]
+async fn leave(
+  ctx: Context<'_>,
+  #[description = "The study group to leave"]
+  #[autocomplete = "autocomplete_group"]
+  group: String,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  let Some(study_group) =
+    DatabaseHandler::get_study_group(&mut transaction, &guild_id, group.trim()).await?
+  else {
+    return reply_not_found(ctx, &group).await;
+  };
+
+  let message = if DatabaseHandler::remove_study_group_member(
+    &mut transaction,
+    &study_group.id,
+    &ctx.author().id,
+  )
+  .await?
+    == 0
+  {
+    format!("{} You're not in **{}**.", emoji.mminfo, study_group.name)
+  } else {
+    format!("{} You've left **{}**.", emoji.mmcheck, study_group.name)
+  };
+
+  database::commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(message),
+    Visibility::Ephemeral,
+  )
+  .await?;
+
+  Ok(())
+}
+
+/// Share your reading progress
+///
+/// Shares how far you've read this week, such as a chapter or section. Weeks start on Monday, in the UTC offset set with `/customize offset`. Updating again in the same week replaces your update.
+#[poise::command(slash_command)]
+async fn update(
+  ctx: Context<'_>,
+  #[description = "The study group"]
+  #[autocomplete = "autocomplete_group"]
+  group: String,
+  #[description = "How far you've read (e.g., Chapter 3)"]
+  #[max_length = 200]
+  progress: String,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+  let user_id = ctx.author().id;
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  let Some(study_group) =
+    DatabaseHandler::get_study_group(&mut transaction, &guild_id, group.trim()).await?
+  else {
+    return reply_not_found(ctx, &group).await;
+  };
+
+  let utc_offset = DatabaseHandler::get_tracking_profile(&mut transaction, &guild_id, &user_id)
+    .await?
+    .unwrap_or_default()
+    .utc_offset;
+  let today = (Utc::now() + ChronoDuration::minutes(i64::from(utc_offset))).date_naive();
+  let week = StudyGroupMember::week_of(today);
+
+  if DatabaseHandler::update_study_group_progress(
+    &mut transaction,
+    &study_group.id,
+    &user_id,
+    progress.trim(),
+    week,
+  )
+  .await?
+    == 0
+  {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} You're not in **{}**. Join with `/studygroup join` first.",
+            emoji.mminfo, study_group.name
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  database::commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(format!(
+      "{} Your progress in **{}** for the week of {} has been saved.",
+      emoji.mmcheck,
+      study_group.name,
+      week.format("%B %-d")
+    )),
+    Visibility::Ephemeral,
+  )
+  .await?;
+
+  Ok(())
+}
+
+/// Show a study group's reading progress
+///
+/// Shows each member of a study group with their latest reading progress, most recently updated first.
+#[poise::command(slash_command)]
+async fn progress(
+  ctx: Context<'_>,
+  #[description = "The study group"]
+  #[autocomplete = "autocomplete_group"]
+  group: String,
+  #[description = "The page to show"] page: Option<usize>,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  let Some(study_group) =
+    DatabaseHandler::get_study_group(&mut transaction, &guild_id, group.trim()).await?
+  else {
+    return reply_not_found(ctx, &group).await;
+  };
+  let members = DatabaseHandler::get_study_group_members(&mut transaction, &study_group.id).await?;
+  drop(transaction);
+
+  if members.is_empty() {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} Nobody has joined **{}** yet.",
+            emoji.mminfo, study_group.name
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let entries: Vec<PageRowRef> = members.iter().map(|member| member as PageRowRef).collect();
+
+  Paginator::new(
+    format!("{}: {}", study_group.name, study_group.reading),
+    &entries,
+    ENTRIES_PER_PAGE.default,
+  )
+  .paginate(ctx, page, PageType::Standard, Visibility::Ephemeral)
+  .await?;
+
+  Ok(())
+}
+
+/// List study groups
+///
+/// Lists this server's study groups, with what each is reading and when its next discussion prompt is posted.
+#[poise::command(slash_command)]
+async fn list(
+  ctx: Context<'_>,
+  #[description = "The page to show"] page: Option<usize>,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+  let groups = DatabaseHandler::get_study_groups(&mut transaction, &guild_id).await?;
+  drop(transaction);
+
+  if groups.is_empty() {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} There aren't any study groups in this server yet.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let entries: Vec<PageRowRef> = groups.iter().map(|group| group as PageRowRef).collect();
+
+  Paginator::new("Study Groups", &entries, ENTRIES_PER_PAGE.default)
+    .paginate(ctx, page, PageType::Standard, Visibility::Ephemeral)
+    .await?;
+
+  Ok(())
+}
@@ -1,13 +1,18 @@
-use anyhow::Result;
+use anyhow::{Context as AnyhowContext, Result};
 use poise::serenity_prelude::{builder::*, AutoArchiveDuration, ChannelId, ChannelType};
 use poise::CreateReply;
 
+use crate::commands::helpers::suggestions;
 use crate::config::{BloomBotEmbed, CHANNELS};
+use crate::data::suggestion::{Suggestion, SuggestionVotes};
+use crate::database::DatabaseHandler;
 use crate::Context;
 
 /// Submit an anonymous server suggestion
 ///
-/// Submits an anonymous suggestion to the server suggestions channel, with voting reactions and a thread for discussion.
+/// Submits an anonymous suggestion to the server suggestions channel, with voting buttons and a thread for discussion.
+///
+/// Staff will update the status of your suggestion as it is considered.
 ///
 /// *Note: Suggestions are posted anonymously, but server staff will be able to see who created a suggestion.*
 #[poise::command(
@@ -20,14 +25,27 @@ pub async fn suggest(
   ctx: Context<'_>,
   #[description = "The suggestion to add"] suggestion: String,
 ) -> Result<()> {
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let channel_id = ChannelId::new(CHANNELS.suggestion);
+  let record = Suggestion::new(guild_id, channel_id, ctx.author().id, suggestion.clone());
+
+  // Saved before posting, so votes can be recorded as soon as the buttons appear
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+  DatabaseHandler::add_suggestion(&mut transaction, &record).await?;
+  DatabaseHandler::commit_transaction(transaction).await?;
+
   // Log suggestion in staff channel
   let log_embed = BloomBotEmbed::new()
     .title("New Suggestion")
     .description(&suggestion)
     .author(CreateEmbedAuthor::new(&ctx.author().name).icon_url(ctx.author().face()))
     .footer(CreateEmbedFooter::new(format!(
-      "Author ID: {}",
-      &ctx.author().id
+      "Author ID: {} · Suggestion ID: {}",
+      &ctx.author().id,
+      record.id
     )))
     .clone();
 
@@ -37,18 +55,20 @@ pub async fn suggest(
     .send_message(ctx, CreateMessage::new().embed(log_embed))
     .await?;
 
-  // Post suggestion and reactions
-  let channel_id = ChannelId::new(CHANNELS.suggestion);
-
+  // Post suggestion with voting buttons
   let suggestion_message = channel_id
     .send_message(
       ctx,
-      CreateMessage::new().embed(BloomBotEmbed::new().description(&suggestion)),
+      CreateMessage::new()
+        .embed(suggestions::suggestion_embed(&record))
+        .components(suggestions::buttons(&record, SuggestionVotes::default())),
     )
     .await?;
 
-  suggestion_message.react(ctx, '✅').await?;
-  suggestion_message.react(ctx, '❌').await?;
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+  DatabaseHandler::set_suggestion_message(&mut transaction, &record.id, &suggestion_message.id)
+    .await?;
+  DatabaseHandler::commit_transaction(transaction).await?;
 
   // Start thread for suggestion
   channel_id
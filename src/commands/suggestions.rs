@@ -0,0 +1,150 @@
+use anyhow::{Context as AnyhowContext, Result};
+use log::error;
+use poise::CreateReply;
+
+use crate::commands::helpers::common::Visibility;
+use crate::commands::helpers::pagination::{PageRowRef, PageType, Paginator};
+use crate::commands::helpers::suggestions;
+use crate::config::ENTRIES_PER_PAGE;
+use crate::data::suggestion::SuggestionStatus;
+use crate::database::DatabaseHandler;
+use crate::Context;
+
+/// Commands for managing suggestions
+///
+/// Commands to update the status of a server suggestion or list suggestions by status.
+///
+/// Requires `Manage Messages` permissions.
+#[poise::command(
+  slash_command,
+  required_permissions = "MANAGE_MESSAGES",
+  default_member_permissions = "MANAGE_MESSAGES",
+  category = "Moderator Commands",
+  subcommands("status", "list"),
+  subcommand_required,
+  guild_only
+)]
+#[allow(clippy::unused_async)]
+pub async fn suggestions(_: Context<'_>) -> Result<()> {
+  Ok(())
+}
+
+/// Update the status of a suggestion
+///
+/// Updates the status of a suggestion, optionally with a note explaining the decision. The original suggestion is updated to show the new status. Once a suggestion is done or declined, voting on it is closed.
+///
+/// The suggestion ID is shown at the bottom of the suggestion.
+#[poise::command(slash_command)]
+async fn status(
+  ctx: Context<'_>,
+  #[description = "The ID of the suggestion to update"] id: String,
+  #[description = "The new status of the suggestion"] status: SuggestionStatus,
+  #[description = "A note explaining the decision"]
+  #[max_length = 500]
+  note: Option<String>,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+  let note = note
+    .map(|note| note.trim().to_owned())
+    .filter(|note| !note.is_empty());
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  if DatabaseHandler::update_suggestion_status(
+    &mut transaction,
+    &guild_id,
+    id.trim(),
+    status,
+    note.as_deref(),
+    &ctx.author().id,
+  )
+  .await?
+    == 0
+  {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} No suggestion with that ID was found.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let suggestion = DatabaseHandler::get_suggestion(&mut transaction, &guild_id, id.trim())
+    .await?
+    .with_context(|| "Failed to retrieve updated suggestion")?;
+  let votes = DatabaseHandler::get_suggestion_votes(&mut transaction, &suggestion.id).await?;
+  DatabaseHandler::commit_transaction(transaction).await?;
+
+  // The status is saved even if the message can't be updated, e.g., because it was deleted
+  if let Err(e) = suggestions::update_message(ctx.serenity_context(), &suggestion, votes).await {
+    error!("Error updating suggestion {}: {e:?}", suggestion.id);
+  }
+
+  ctx
+    .send(
+      CreateReply::default()
+        .content(format!(
+          "{} The suggestion has been marked as **{}**.",
+          emoji.mmcheck,
+          status.as_str()
+        ))
+        .ephemeral(true),
+    )
+    .await?;
+
+  Ok(())
+}
+
+/// List suggestions by status
+///
+/// Lists suggestions with a status, most recent first. Defaults to open suggestions.
+#[poise::command(slash_command)]
+async fn list(
+  ctx: Context<'_>,
+  #[description = "The status of suggestions to list (defaults to open)"] status: Option<
+    SuggestionStatus,
+  >,
+  #[description = "The page to show"] page: Option<usize>,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+  let status = status.unwrap_or(SuggestionStatus::Open);
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+  let entries =
+    DatabaseHandler::get_suggestions_by_status(&mut transaction, &guild_id, status).await?;
+  drop(transaction);
+
+  if entries.is_empty() {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} There are no {} suggestions.",
+            emoji.mminfo,
+            status.as_str()
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let entries: Vec<PageRowRef> = entries.iter().map(|entry| entry as PageRowRef).collect();
+
+  Paginator::new(status.label(), &entries, ENTRIES_PER_PAGE.default)
+    .paginate(ctx, page, PageType::Standard, Visibility::Ephemeral)
+    .await?;
+
+  Ok(())
+}
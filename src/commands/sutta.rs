@@ -0,0 +1,146 @@
+use anyhow::Result;
+use poise::serenity_prelude::CreateEmbedFooter;
+use poise::CreateReply;
+
+use crate::config::BloomBotEmbed;
+use crate::database::DatabaseHandler;
+use crate::suttas::{self, SuttaLookup, DEFAULT_TRANSLATOR};
+use crate::Context;
+
+/// The most characters of a sutta shown in the embed. Longer suttas are shortened, with a
+/// link to the full text.
+const MAX_PASSAGE_LENGTH: usize = 3500;
+
+/// Look up a sutta
+///
+/// Shows a sutta from SuttaCentral, such as MN 10 or SN 56.11, with a link to the full text. Long suttas are shortened.
+///
+/// Translations by Bhikkhu Sujato are shown by default. Choose another translator by their SuttaCentral ID, such as bodhi or brahmali, if they've translated the sutta.
+#[poise::command(slash_command, category = "Informational", guild_only)]
+pub async fn sutta(
+  ctx: Context<'_>,
+  #[description = "The sutta to show (e.g., MN 10 or SN 56.11)"]
+  #[max_length = 30]
+  reference: String,
+  #[description = "The SuttaCentral ID of the translator (e.g., sujato or bodhi)"]
+  #[max_length = 30]
+  translator: Option<String>,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+
+  let Some(uid) = suttas::parse_reference(&reference) else {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} `{}` isn't a sutta reference. Please enter a collection and number, e.g., `MN 10` or `SN 56.11`.",
+            emoji.mminfo,
+            reference.trim()
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  };
+  let translator = translator
+    .map(|translator| translator.trim().to_lowercase())
+    .filter(|translator| !translator.is_empty());
+
+  ctx.defer().await?;
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  let cached = DatabaseHandler::get_cached_sutta(
+    &mut transaction,
+    &uid,
+    translator.as_deref().unwrap_or(DEFAULT_TRANSLATOR),
+  )
+  .await?;
+  let passage = match cached {
+    Some(passage) => passage,
+    None => match ctx
+      .data()
+      .suttas
+      .lookup(&uid, translator.as_deref())
+      .await?
+    {
+      SuttaLookup::Found(passage) => {
+        DatabaseHandler::cache_sutta(&mut transaction, &passage).await?;
+        DatabaseHandler::commit_transaction(transaction).await?;
+        passage
+      }
+      SuttaLookup::NotFound => {
+        ctx
+          .send(
+            CreateReply::default()
+              .content(format!(
+                "{} No sutta called **{}** was found on SuttaCentral.",
+                emoji.mminfo,
+                suttas::display_reference(&uid)
+              ))
+              .ephemeral(true),
+          )
+          .await?;
+        return Ok(());
+      }
+      SuttaLookup::NoTranslation(available) => {
+        let content = if available.is_empty() {
+          format!(
+            "{} There's no translation of **{}** which can be shown here yet. You may find one at <https://suttacentral.net/{uid}>.",
+            emoji.mminfo,
+            suttas::display_reference(&uid)
+          )
+        } else {
+          format!(
+            "{} **{}** hasn't been translated by `{}`. Available translators: {}.",
+            emoji.mminfo,
+            suttas::display_reference(&uid),
+            translator.as_deref().unwrap_or(DEFAULT_TRANSLATOR),
+            available
+              .iter()
+              .map(|translation| format!(
+                "`{}` ({}, {})",
+                translation.author_uid, translation.author, translation.lang
+              ))
+              .collect::<Vec<_>>()
+              .join(", ")
+          )
+        };
+        ctx
+          .send(CreateReply::default().content(content).ephemeral(true))
+          .await?;
+        return Ok(());
+      }
+    },
+  };
+
+  let (text, shortened) = suttas::excerpt(&passage.passage, MAX_PASSAGE_LENGTH);
+  let description = if shortened {
+    format!(
+      "{text}\n\n[Read the full text on SuttaCentral]({})",
+      passage.url()
+    )
+  } else {
+    text
+  };
+
+  let embed = BloomBotEmbed::new()
+    .title(format!(
+      "{}: {}",
+      suttas::display_reference(&passage.uid),
+      passage
+        .translated_title
+        .as_deref()
+        .unwrap_or(&passage.title)
+    ))
+    .url(passage.url())
+    .description(description)
+    .footer(CreateEmbedFooter::new(format!(
+      "{} · Translated by {} · SuttaCentral",
+      passage.title, passage.author
+    )));
+
+  ctx.send(CreateReply::default().embed(embed)).await?;
+
+  Ok(())
+}
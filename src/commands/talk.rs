@@ -0,0 +1,558 @@
+use std::sync::Arc;
+
+use anyhow::{Context as AnyhowContext, Result};
+use poise::serenity_prelude::{CreateAllowedMentions, Mentionable, Role};
+use poise::CreateReply;
+use songbird::Songbird;
+
+use crate::commands::helpers::common::{self, Visibility};
+use crate::commands::helpers::database::{self, MessageType};
+use crate::commands::helpers::pagination::{PageRowRef, PageType, Paginator};
+use crate::config::ENTRIES_PER_PAGE;
+use crate::data::talk::Talk;
+use crate::database::DatabaseHandler;
+use crate::talks::{self, Enqueued, QueuedTalk, MAX_QUEUE_LENGTH};
+use crate::Context;
+
+/// Suggests talks from the library whose names contain `partial`. Returns no suggestions
+/// outside of a guild or if the lookup fails.
+async fn autocomplete_talk(ctx: Context<'_>, partial: &str) -> impl Iterator<Item = String> {
+  let talks = match ctx.guild_id() {
+    Some(guild_id) => match ctx.data().db.start_transaction_with_retry(5).await {
+      Ok(mut transaction) => DatabaseHandler::get_talks(&mut transaction, &guild_id)
+        .await
+        .unwrap_or_default(),
+      Err(_) => Vec::new(),
+    },
+    None => Vec::new(),
+  };
+
+  let partial = partial.to_lowercase();
+  talks
+    .into_iter()
+    .map(|talk| talk.name)
+    .filter(move |name| name.to_lowercase().contains(&partial))
+    .take(25)
+}
+
+/// Checks that the author can control talk playback. Staff always can. Otherwise, if the
+/// server has set a talk role with `/talk role`, the author needs that role.
+async fn can_play(ctx: Context<'_>) -> Result<bool> {
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+  let settings = ctx.data().settings.get(&ctx.data().db, guild_id).await?;
+  let Some(talk_role) = settings.talk_role else {
+    return Ok(true);
+  };
+
+  let allowed = common::is_staff(ctx).await
+    || ctx
+      .author_member()
+      .await
+      .is_some_and(|member| member.roles.contains(&talk_role));
+
+  if !allowed {
+    let emoji = ctx.data().emoji.get(ctx.guild_id());
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} Playing talks requires the {} role.",
+            emoji.mminfo,
+            talk_role.mention()
+          ))
+          .allowed_mentions(CreateAllowedMentions::new().empty_roles())
+          .ephemeral(true),
+      )
+      .await?;
+  }
+
+  Ok(allowed)
+}
+
+async fn voice_manager(ctx: Context<'_>) -> Result<Arc<Songbird>> {
+  songbird::get(ctx.serenity_context())
+    .await
+    .with_context(|| "Failed to retrieve voice client")
+}
+
+async fn reply(ctx: Context<'_>, content: String) -> Result<()> {
+  ctx
+    .send(CreateReply::default().content(content).ephemeral(true))
+    .await?;
+
+  Ok(())
+}
+
+/// Play dharma talks in voice
+///
+/// Commands to play dharma talks and guided meditations from the server's talk library in a voice channel. Talks are queued and played in order.
+///
+/// Staff can add talks to the library and limit playback to members with a chosen role.
+#[poise::command(
+  slash_command,
+  category = "Utilities",
+  subcommands(
+    "play", "queue", "pause", "resume", "skip", "stop", "list", "add", "remove", "role"
+  ),
+  subcommand_required,
+  guild_only
+)]
+#[allow(clippy::unused_async)]
+pub async fn talk(_: Context<'_>) -> Result<()> {
+  Ok(())
+}
+
+/// Play a talk in your voice channel
+///
+/// Plays a talk from the library in the voice channel you're in. If a talk is already playing, the talk is added to the queue.
+#[poise::command(slash_command, check = "can_play")]
+async fn play(
+  ctx: Context<'_>,
+  #[description = "The talk to play"]
+  #[autocomplete = "autocomplete_talk"]
+  talk: String,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let voice_channel = ctx.guild().and_then(|guild| {
+    guild
+      .voice_states
+      .get(&ctx.author().id)
+      .and_then(|state| state.channel_id)
+  });
+  let Some(voice_channel) = voice_channel else {
+    return reply(
+      ctx,
+      format!(
+        "{} Please join a voice channel first, then try again.",
+        emoji.mminfo
+      ),
+    )
+    .await;
+  };
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+  let found = DatabaseHandler::get_talk(&mut transaction, &guild_id, talk.trim()).await?;
+  drop(transaction);
+
+  let Some(found) = found else {
+    return reply(
+      ctx,
+      format!(
+        "{} No talk called **{}** is in the library. Use `/talk list` to see the talks available.",
+        emoji.mminfo,
+        talk.trim()
+      ),
+    )
+    .await;
+  };
+
+  if ctx.data().transcription.recording(guild_id).is_some() {
+    return reply(
+      ctx,
+      format!(
+        "{} Talks can't be played while an event is being transcribed.",
+        emoji.mminfo
+      ),
+    )
+    .await;
+  }
+
+  ctx.defer().await?;
+
+  let manager = voice_manager(ctx).await?;
+  let content = match ctx
+    .data()
+    .talks
+    .enqueue(manager, guild_id, voice_channel, &found, ctx.author().id)
+    .await?
+  {
+    Enqueued::Position(1) => format!(
+      ":headphones: Now playing **{}** in {}.",
+      found.name,
+      voice_channel.mention()
+    ),
+    Enqueued::Position(position) => format!(
+      "{} Added **{}** to the queue at position {position}.",
+      emoji.mmcheck, found.name
+    ),
+    Enqueued::QueueFull => format!(
+      "{} The queue already has {MAX_QUEUE_LENGTH} talks. Please wait for one to finish, or skip it with `/talk skip`.",
+      emoji.mminfo
+    ),
+    Enqueued::Busy(channel) => format!(
+      "{} Talks are already playing in {}. Please join that channel, or wait for the queue to finish.",
+      emoji.mminfo,
+      channel.mention()
+    ),
+  };
+
+  ctx.send(CreateReply::default().content(content)).await?;
+
+  Ok(())
+}
+
+/// Show the talk queue
+///
+/// Shows the talk that's playing and the talks queued after it.
+#[poise::command(slash_command)]
+async fn queue(ctx: Context<'_>) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let queued = match voice_manager(ctx).await?.get(guild_id) {
+    Some(call) => call.lock().await.queue().current_queue(),
+    None => Vec::new(),
+  };
+  if queued.is_empty() {
+    return reply(ctx, format!("{} No talks are playing.", emoji.mminfo)).await;
+  }
+
+  let lines: Vec<String> = queued
+    .iter()
+    .enumerate()
+    .map(|(index, track)| {
+      let talk = track.data::<QueuedTalk>();
+      let position = if index == 0 {
+        "**Now playing:**".to_owned()
+      } else {
+        format!("{index}.")
+      };
+      format!(
+        "{position} {} · Requested by {}",
+        talk.name,
+        talk.requested_by.mention()
+      )
+    })
+    .collect();
+
+  ctx
+    .send(
+      CreateReply::default()
+        .content(lines.join("\n"))
+        .allowed_mentions(CreateAllowedMentions::new().empty_users())
+        .ephemeral(true),
+    )
+    .await?;
+
+  Ok(())
+}
+
+/// Pause the current talk
+///
+/// Pauses the talk that's playing. Resume it with `/talk resume`.
+#[poise::command(slash_command, check = "can_play")]
+async fn pause(ctx: Context<'_>) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let paused = match voice_manager(ctx).await?.get(guild_id) {
+    Some(call) => call.lock().await.queue().pause().is_ok(),
+    None => false,
+  };
+
+  if paused {
+    ctx
+      .send(CreateReply::default().content(":pause_button: The talk has been paused."))
+      .await?;
+  } else {
+    reply(ctx, format!("{} No talks are playing.", emoji.mminfo)).await?;
+  }
+
+  Ok(())
+}
+
+/// Resume the current talk
+///
+/// Resumes a talk paused with `/talk pause`.
+#[poise::command(slash_command, check = "can_play")]
+async fn resume(ctx: Context<'_>) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let resumed = match voice_manager(ctx).await?.get(guild_id) {
+    Some(call) => call.lock().await.queue().resume().is_ok(),
+    None => false,
+  };
+
+  if resumed {
+    ctx
+      .send(CreateReply::default().content(":arrow_forward: The talk has been resumed."))
+      .await?;
+  } else {
+    reply(ctx, format!("{} No talks are playing.", emoji.mminfo)).await?;
+  }
+
+  Ok(())
+}
+
+/// Skip the current talk
+///
+/// Stops the talk that's playing and starts the next one in the queue.
+#[poise::command(slash_command, check = "can_play")]
+async fn skip(ctx: Context<'_>) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let skipped = match voice_manager(ctx).await?.get(guild_id) {
+    Some(call) => call.lock().await.queue().skip().is_ok(),
+    None => false,
+  };
+
+  if skipped {
+    ctx
+      .send(CreateReply::default().content(":track_next: The talk has been skipped."))
+      .await?;
+  } else {
+    reply(ctx, format!("{} No talks are playing.", emoji.mminfo)).await?;
+  }
+
+  Ok(())
+}
+
+/// Stop playing talks
+///
+/// Stops the talk that's playing, clears the queue, and leaves the voice channel.
+#[poise::command(slash_command, check = "can_play")]
+async fn stop(ctx: Context<'_>) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let manager = voice_manager(ctx).await?;
+  let Some(call) = manager.get(guild_id) else {
+    return reply(ctx, format!("{} No talks are playing.", emoji.mminfo)).await;
+  };
+  call.lock().await.queue().stop();
+  manager
+    .remove(guild_id)
+    .await
+    .with_context(|| "Failed to leave voice channel")?;
+
+  ctx
+    .send(CreateReply::default().content(":stop_button: Talks have been stopped."))
+    .await?;
+
+  Ok(())
+}
+
+/// List the talks in the library
+///
+/// Lists the dharma talks and guided meditations which can be played with `/talk play`.
+#[poise::command(slash_command)]
+async fn list(
+  ctx: Context<'_>,
+  #[description = "The page to show"] page: Option<usize>,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+  let talks = DatabaseHandler::get_talks(&mut transaction, &guild_id).await?;
+  drop(transaction);
+
+  if talks.is_empty() {
+    return reply(ctx, format!("{} The talk library is empty.", emoji.mminfo)).await;
+  }
+
+  let entries: Vec<PageRowRef> = talks.iter().map(|talk| talk as PageRowRef).collect();
+
+  Paginator::new("Talk Library", &entries, ENTRIES_PER_PAGE.default)
+    .paginate(ctx, page, PageType::Standard, Visibility::Ephemeral)
+    .await?;
+
+  Ok(())
+}
+
+/// Add a talk to the library
+///
+/// Adds a dharma talk or guided meditation to the library, so it can be played with `/talk play`. The URL must link directly to an audio file, such as an MP3.
+///
+/// Requires `Manage Messages` permissions.
+#[poise::command(slash_command, required_permissions = "MANAGE_MESSAGES")]
+async fn add(
+  ctx: Context<'_>,
+  #[description = "The name of the talk"]
+  #[max_length = 100]
+  name: String,
+  #[description = "A direct link to the audio file"]
+  #[max_length = 500]
+  url: String,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let url = match talks::parse_url(&url) {
+    Ok(url) => url,
+    Err(e) => return reply(ctx, format!("{} {e}.", emoji.mminfo)).await,
+  };
+  let name = name.trim().to_owned();
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  if DatabaseHandler::get_talk(&mut transaction, &guild_id, &name)
+    .await?
+    .is_some()
+  {
+    return reply(
+      ctx,
+      format!(
+        "{} A talk called **{name}** is already in the library.",
+        emoji.mminfo
+      ),
+    )
+    .await;
+  }
+
+  let talk = Talk::new(guild_id, name, url, ctx.author().id);
+  DatabaseHandler::add_talk(&mut transaction, &talk).await?;
+
+  database::commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(format!(
+      "{} **{}** has been added to the talk library.",
+      emoji.mmcheck, talk.name
+    )),
+    Visibility::Ephemeral,
+  )
+  .await?;
+
+  Ok(())
+}
+
+/// Remove a talk from the library
+///
+/// Removes a talk from the library. If the talk is queued, it still plays.
+///
+/// Requires `Manage Messages` permissions.
+#[poise::command(slash_command, required_permissions = "MANAGE_MESSAGES")]
+async fn remove(
+  ctx: Context<'_>,
+  #[description = "The talk to remove"]
+  #[autocomplete = "autocomplete_talk"]
+  name: String,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  if DatabaseHandler::remove_talk(&mut transaction, &guild_id, name.trim()).await? == 0 {
+    return reply(
+      ctx,
+      format!(
+        "{} No talk called **{}** is in the library.",
+        emoji.mminfo,
+        name.trim()
+      ),
+    )
+    .await;
+  }
+
+  database::commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(format!(
+      "{} **{}** has been removed from the talk library.",
+      emoji.mmcheck,
+      name.trim()
+    )),
+    Visibility::Ephemeral,
+  )
+  .await?;
+
+  Ok(())
+}
+
+/// Limit who can play talks
+///
+/// Sets the role members need to play, pause, skip, or stop talks. Staff can always control playback. Without a role, everyone can.
+///
+/// Run without any options to show the current role.
+///
+/// Requires `Manage Messages` permissions.
+#[poise::command(slash_command, required_permissions = "MANAGE_MESSAGES")]
+async fn role(
+  ctx: Context<'_>,
+  #[description = "The role needed to play talks"] role: Option<Role>,
+  #[description = "Let everyone play talks"] remove: Option<bool>,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let remove = remove == Some(true);
+  if role.is_some() && remove {
+    return reply(
+      ctx,
+      format!(
+        "{} Please either set a role or remove it, not both.",
+        emoji.mminfo
+      ),
+    )
+    .await;
+  }
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+  let settings = DatabaseHandler::get_guild_settings(&mut transaction, &guild_id).await?;
+
+  let message = if let Some(role) = role {
+    DatabaseHandler::update_guild_settings(&mut transaction, &settings.talk_role(Some(role.id)))
+      .await?;
+    format!("Only members with {} can now play talks.", role.mention())
+  } else if remove {
+    DatabaseHandler::update_guild_settings(&mut transaction, &settings.talk_role(None)).await?;
+    "Everyone can now play talks.".to_owned()
+  } else {
+    let current = match settings.talk_role {
+      Some(role_id) => format!("Members with {}", role_id.mention()),
+      None => "Everyone".to_owned(),
+    };
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} **Who can play talks**: {current}",
+            emoji.mminfo
+          ))
+          .allowed_mentions(CreateAllowedMentions::new().empty_roles())
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  };
+
+  database::commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(format!("{} {message}", emoji.mmcheck)),
+    Visibility::Ephemeral,
+  )
+  .await?;
+
+  ctx.data().settings.invalidate(guild_id);
+
+  Ok(())
+}
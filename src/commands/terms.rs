@@ -7,14 +7,14 @@ use sqlx::{Postgres, Transaction};
 
 use crate::commands::helpers::common::Visibility;
 use crate::commands::helpers::database::{self, MessageType};
-use crate::config::EMOJI;
+use crate::commands::helpers::terms::autocomplete_term;
 use crate::data::term::{Term, TermModal};
 use crate::database::DatabaseHandler;
 use crate::{Context, Data as AppData, Error as AppError};
 
 /// Commands for managing glossary entries
 ///
-/// Commands to add, remove, or edit glossary entries.
+/// Commands to add, remove, or edit glossary entries, or manage their aliases and related entries.
 ///
 /// Requires `Manage Roles` permissions.
 #[poise::command(
@@ -22,7 +22,7 @@ use crate::{Context, Data as AppData, Error as AppError};
   required_permissions = "MANAGE_ROLES",
   default_member_permissions = "MANAGE_ROLES",
   category = "Moderator Commands",
-  subcommands("add", "remove", "edit", "update_embeddings"),
+  subcommands("add", "remove", "edit", "alias", "related", "update_embeddings"),
   subcommand_required,
   guild_only
 )]
@@ -41,17 +41,37 @@ async fn add(
   #[rename = "term"]
   term_name: String,
 ) -> Result<()> {
-  if let Some(term_data) = TermModal::execute(ctx).await? {
-    let guild_id = ctx
-      .guild_id()
-      .with_context(|| "Failed to retrieve guild ID from context")?;
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
 
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  if DatabaseHandler::term_name_exists(&mut transaction, &guild_id, term_name.as_str()).await? {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} `{term_name}` is already in use as the name or alias of a term.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  drop(transaction);
+
+  if let Some(term_data) = TermModal::execute(ctx).await? {
     let vector = Vector::from(
       ctx
         .data()
         .embeddings
         .create_embedding(
           format!("{term_name} {}", term_data.meaning),
+          guild_id,
           ctx.author().id,
         )
         .await?,
@@ -67,7 +87,7 @@ async fn add(
           CreateReply::default()
             .content(format!(
               "{} Failed to add term. Please try again.",
-              EMOJI.mmx
+              emoji.mmx
             ))
             .ephemeral(true),
         )
@@ -78,7 +98,7 @@ async fn add(
     database::commit_and_say(
       PoiseContext::Application(ctx),
       transaction,
-      MessageType::TextOnly(format!("{} Term has been added.", EMOJI.mmcheck)),
+      MessageType::TextOnly(format!("{} Term has been added.", emoji.mmcheck)),
       Visibility::Ephemeral,
     )
     .await?;
@@ -95,8 +115,10 @@ async fn edit(
   ctx: ApplicationContext<'_, AppData, AppError>,
   #[description = "The term to edit"]
   #[rename = "term"]
+  #[autocomplete = "autocomplete_term"]
   term_name: String,
 ) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
   let guild_id = ctx
     .guild_id()
     .with_context(|| "Failed to retrieve guild ID from context")?;
@@ -116,6 +138,8 @@ async fn edit(
     return Ok(());
   };
 
+  // The term may have been specified by one of its aliases
+  let term_name = existing_term.name.clone();
   let existing_meaning = existing_term.meaning.clone();
   let defaults = TermModal::from(existing_term);
 
@@ -129,6 +153,7 @@ async fn edit(
           .embeddings
           .create_embedding(
             format!("{} {}", term_name, term_data.meaning),
+            guild_id,
             ctx.author().id,
           )
           .await?,
@@ -143,7 +168,7 @@ async fn edit(
           CreateReply::default()
             .content(format!(
               "{} Failed to edit term. Please try again.",
-              EMOJI.mmx
+              emoji.mmx
             ))
             .ephemeral(true),
         )
@@ -154,7 +179,7 @@ async fn edit(
     database::commit_and_say(
       PoiseContext::Application(ctx),
       transaction,
-      MessageType::TextOnly(format!("{} Term has been edited.", EMOJI.mmcheck)),
+      MessageType::TextOnly(format!("{} Term has been edited.", emoji.mmcheck)),
       Visibility::Ephemeral,
     )
     .await?;
@@ -171,33 +196,37 @@ async fn remove(
   ctx: Context<'_>,
   #[description = "The term to remove"]
   #[rename = "term"]
+  #[autocomplete = "autocomplete_term"]
   term_name: String,
 ) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
   let guild_id = ctx
     .guild_id()
     .with_context(|| "Failed to retrieve guild ID from context")?;
 
   let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
-  if !DatabaseHandler::term_exists(&mut transaction, &guild_id, term_name.as_str()).await? {
+  let Some(existing_term) =
+    DatabaseHandler::get_term(&mut transaction, &guild_id, term_name.as_str()).await?
+  else {
     ctx
       .send(
         CreateReply::default()
-          .content(format!("{} Term does not exist.", EMOJI.mminfo))
+          .content(format!("{} Term does not exist.", emoji.mminfo))
           .ephemeral(true),
       )
       .await?;
     return Ok(());
-  }
+  };
 
   if let Err(e) =
-    DatabaseHandler::remove_term(&mut transaction, &guild_id, term_name.as_str()).await
+    DatabaseHandler::remove_term(&mut transaction, &guild_id, existing_term.name.as_str()).await
   {
     ctx
       .send(
         CreateReply::default()
           .content(format!(
             "{} Failed to remove term. Please try again.",
-            EMOJI.mmx
+            emoji.mmx
           ))
           .ephemeral(true),
       )
@@ -208,7 +237,316 @@ async fn remove(
   database::commit_and_say(
     ctx,
     transaction,
-    MessageType::TextOnly(format!("{} Term has been removed.", EMOJI.mmcheck)),
+    MessageType::TextOnly(format!("{} Term has been removed.", emoji.mmcheck)),
+    Visibility::Ephemeral,
+  )
+  .await?;
+
+  Ok(())
+}
+
+/// Commands for managing term aliases
+///
+/// Commands to add or remove aliases for glossary entries.
+#[poise::command(slash_command, subcommands("add_alias", "remove_alias"))]
+#[allow(clippy::unused_async)]
+async fn alias(_: Context<'_>) -> Result<()> {
+  Ok(())
+}
+
+/// Add an alias to a term
+///
+/// Adds an alias to a term in the glossary. The alias cannot already be in use as the name or alias of any term.
+#[poise::command(slash_command, rename = "add")]
+async fn add_alias(
+  ctx: Context<'_>,
+  #[description = "The term to add an alias to"]
+  #[rename = "term"]
+  #[autocomplete = "autocomplete_term"]
+  term_name: String,
+  #[description = "The alias to add"] alias: String,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let alias = alias.trim();
+
+  if alias.is_empty() || alias.contains(',') {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} Aliases cannot be empty or contain commas.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  let Some(existing_term) =
+    DatabaseHandler::get_term(&mut transaction, &guild_id, term_name.as_str()).await?
+  else {
+    term_not_found(ctx, &mut transaction, guild_id, term_name).await?;
+    return Ok(());
+  };
+
+  if DatabaseHandler::term_name_exists(&mut transaction, &guild_id, alias).await? {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} `{alias}` is already in use as the name or alias of a term.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  DatabaseHandler::add_term_alias(&mut transaction, &guild_id, &existing_term.name, alias).await?;
+
+  database::commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(format!(
+      "{} Alias `{alias}` has been added to `{}`.",
+      emoji.mmcheck, existing_term.name
+    )),
+    Visibility::Ephemeral,
+  )
+  .await?;
+
+  Ok(())
+}
+
+/// Remove an alias from a term
+///
+/// Removes an alias from a term in the glossary.
+#[poise::command(slash_command, rename = "remove")]
+async fn remove_alias(
+  ctx: Context<'_>,
+  #[description = "The term to remove an alias from"]
+  #[rename = "term"]
+  #[autocomplete = "autocomplete_term"]
+  term_name: String,
+  #[description = "The alias to remove"] alias: String,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let alias = alias.trim();
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  let Some(existing_term) =
+    DatabaseHandler::get_term(&mut transaction, &guild_id, term_name.as_str()).await?
+  else {
+    term_not_found(ctx, &mut transaction, guild_id, term_name).await?;
+    return Ok(());
+  };
+
+  if DatabaseHandler::remove_term_alias(&mut transaction, &guild_id, &existing_term.name, alias)
+    .await?
+    == 0
+  {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} `{}` does not have the alias `{alias}`.",
+            emoji.mminfo, existing_term.name
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  database::commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(format!(
+      "{} Alias `{alias}` has been removed from `{}`.",
+      emoji.mmcheck, existing_term.name
+    )),
+    Visibility::Ephemeral,
+  )
+  .await?;
+
+  Ok(())
+}
+
+/// Commands for managing related terms
+///
+/// Commands to add or remove the related terms shown as "See also" entries for glossary entries.
+#[poise::command(slash_command, subcommands("add_related", "remove_related"))]
+#[allow(clippy::unused_async)]
+async fn related(_: Context<'_>) -> Result<()> {
+  Ok(())
+}
+
+/// Add a related term to a term
+///
+/// Adds a related term to a term in the glossary. Related terms are shown as "See also" entries in `/glossary info`, and are ranked higher in `/glossary search` results for the term.
+#[poise::command(slash_command, rename = "add")]
+async fn add_related(
+  ctx: Context<'_>,
+  #[description = "The term to add a related term to"]
+  #[rename = "term"]
+  #[autocomplete = "autocomplete_term"]
+  term_name: String,
+  #[description = "The related term"]
+  #[rename = "related"]
+  #[autocomplete = "autocomplete_term"]
+  related_name: String,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  let Some(existing_term) =
+    DatabaseHandler::get_term(&mut transaction, &guild_id, term_name.as_str()).await?
+  else {
+    term_not_found(ctx, &mut transaction, guild_id, term_name).await?;
+    return Ok(());
+  };
+
+  let Some(related_term) =
+    DatabaseHandler::get_term(&mut transaction, &guild_id, related_name.as_str()).await?
+  else {
+    term_not_found(ctx, &mut transaction, guild_id, related_name).await?;
+    return Ok(());
+  };
+
+  if related_term.name == existing_term.name {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} A term cannot be related to itself.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  if existing_term
+    .related
+    .as_ref()
+    .is_some_and(|related| related.contains(&related_term.name))
+  {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} `{}` is already related to `{}`.",
+            emoji.mminfo, related_term.name, existing_term.name
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  DatabaseHandler::add_related_term(
+    &mut transaction,
+    &guild_id,
+    &existing_term.name,
+    &related_term.name,
+  )
+  .await?;
+
+  database::commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(format!(
+      "{} `{}` has been added as a related term for `{}`.",
+      emoji.mmcheck, related_term.name, existing_term.name
+    )),
+    Visibility::Ephemeral,
+  )
+  .await?;
+
+  Ok(())
+}
+
+/// Remove a related term from a term
+///
+/// Removes a related term from a term in the glossary.
+#[poise::command(slash_command, rename = "remove")]
+async fn remove_related(
+  ctx: Context<'_>,
+  #[description = "The term to remove a related term from"]
+  #[rename = "term"]
+  #[autocomplete = "autocomplete_term"]
+  term_name: String,
+  #[description = "The related term to remove"]
+  #[rename = "related"]
+  #[autocomplete = "autocomplete_term"]
+  related_name: String,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  let Some(existing_term) =
+    DatabaseHandler::get_term(&mut transaction, &guild_id, term_name.as_str()).await?
+  else {
+    term_not_found(ctx, &mut transaction, guild_id, term_name).await?;
+    return Ok(());
+  };
+
+  // Resolve aliases, falling back to the name as given
+  let related_name = DatabaseHandler::get_term(&mut transaction, &guild_id, &related_name)
+    .await?
+    .map_or(related_name, |related_term| related_term.name);
+
+  if DatabaseHandler::remove_related_term(
+    &mut transaction,
+    &guild_id,
+    &existing_term.name,
+    &related_name,
+  )
+  .await?
+    == 0
+  {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} `{related_name}` is not a related term for `{}`.",
+            emoji.mminfo, existing_term.name
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  database::commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(format!(
+      "{} `{related_name}` has been removed as a related term for `{}`.",
+      emoji.mmcheck, existing_term.name
+    )),
     Visibility::Ephemeral,
   )
   .await?;
@@ -221,6 +559,7 @@ async fn remove(
 /// Updates embeddings for all terms.
 #[poise::command(slash_command)]
 async fn update_embeddings(ctx: Context<'_>) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
   ctx.defer_ephemeral().await?;
 
   let guild_id = ctx
@@ -244,6 +583,7 @@ async fn update_embeddings(ctx: Context<'_>) -> Result<()> {
         .embeddings
         .create_embedding(
           format!("{} {}", term.name, existing_term.meaning),
+          guild_id,
           ctx.author().id,
         )
         .await?,
@@ -263,7 +603,7 @@ async fn update_embeddings(ctx: Context<'_>) -> Result<()> {
     transaction,
     MessageType::TextOnly(format!(
       "{} Term embeddings have been updated.",
-      EMOJI.mmcheck
+      emoji.mmcheck
     )),
     Visibility::Ephemeral,
   )
@@ -281,6 +621,7 @@ async fn term_not_found(
   guild_id: GuildId,
   term_name: String,
 ) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
   let possible_terms =
     DatabaseHandler::get_possible_terms(transaction, &guild_id, term_name.as_str(), 0.8).await?;
 
@@ -290,7 +631,7 @@ async fn term_not_found(
         CreateReply::default()
           .content(format!(
             "{} Term does not exist. Did you mean one of these?\n{}",
-            EMOJI.mminfo,
+            emoji.mminfo,
             possible_terms
               .iter()
               .map(|term| format!("`{}`", term.name))
@@ -306,7 +647,7 @@ async fn term_not_found(
         CreateReply::default()
           .content(format!(
             "{} Term does not exist. Did you mean `{}`?",
-            EMOJI.mminfo, possible_term.name
+            emoji.mminfo, possible_term.name
           ))
           .ephemeral(true),
       )
@@ -315,7 +656,7 @@ async fn term_not_found(
     ctx
       .send(
         CreateReply::default()
-          .content(format!("{} Term does not exist.", EMOJI.mminfo))
+          .content(format!("{} Term does not exist.", emoji.mminfo))
           .ephemeral(true),
       )
       .await?;
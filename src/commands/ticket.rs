@@ -0,0 +1,401 @@
+use anyhow::{Context as AnyhowContext, Result};
+use log::warn;
+use poise::serenity_prelude::{builder::*, Mentionable};
+use poise::CreateReply;
+
+use crate::commands::helpers::tickets;
+use crate::config::BloomBotEmbed;
+use crate::data::ticket::{Ticket, TicketMessage};
+use crate::database::DatabaseHandler;
+use crate::storage::ArtifactKind;
+use crate::Context;
+
+/// Retrieves the ticket discussed in the thread where the command was used, letting the
+/// user know if there isn't one.
+async fn ticket_in_channel(ctx: Context<'_>) -> Result<Option<Ticket>> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+  let ticket = DatabaseHandler::get_ticket_by_thread(&mut transaction, &ctx.channel_id()).await?;
+  drop(transaction);
+
+  if ticket.is_none() {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} This command can only be used in a ticket thread.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+  }
+
+  Ok(ticket)
+}
+
+/// Commands for private support tickets
+///
+/// Commands to open a private support ticket with staff, and for staff to reply to, close, and export tickets.
+///
+/// Members can also open a ticket by sending Bloom a DM. Staff discuss each ticket in a private thread, and replies are sent to the member by DM.
+#[poise::command(
+  slash_command,
+  category = "Utilities",
+  subcommands("open", "reply", "close", "export"),
+  subcommand_required,
+  guild_only
+)]
+#[allow(clippy::unused_async)]
+pub async fn ticket(_: Context<'_>) -> Result<()> {
+  Ok(())
+}
+
+/// Open a private ticket with staff
+///
+/// Opens a private support ticket with staff. Staff replies are sent to you by DM, and anything you send Bloom by DM is added to your ticket until it's closed.
+#[poise::command(slash_command)]
+async fn open(
+  ctx: Context<'_>,
+  #[description = "What you'd like help with"]
+  #[max_length = 200]
+  subject: String,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let settings = ctx.data().settings.get(&ctx.data().db, guild_id).await?;
+  let Some(ticket_channel) = settings.ticket_channel else {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} Tickets aren't available in this server.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  };
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  let open_tickets = DatabaseHandler::get_open_tickets(&mut transaction, &ctx.author().id).await?;
+  if open_tickets
+    .iter()
+    .any(|ticket| ticket.guild_id == guild_id)
+  {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} You already have an open ticket. Send Bloom a DM to add to it.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  // Staff replies are sent by DM, so make sure they can be delivered first
+  if ctx
+    .author()
+    .direct_message(
+      ctx,
+      CreateMessage::new().content(format!(
+        "{} Your ticket about **{subject}** has been opened. Staff replies will arrive here, and anything you send here will be added to your ticket.",
+        emoji.mmcheck
+      )),
+    )
+    .await
+    .is_err()
+  {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} Bloom couldn't send you a DM. Please allow direct messages from server members so that staff can reply, then try again.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  tickets::open(
+    ctx.serenity_context(),
+    &mut transaction,
+    guild_id,
+    ticket_channel,
+    ctx.author(),
+    Some(subject),
+  )
+  .await?;
+  DatabaseHandler::commit_transaction(transaction).await?;
+
+  ctx
+    .send(
+      CreateReply::default()
+        .content(format!(
+          "{} Your ticket has been opened. Staff will reply by DM.",
+          emoji.mmcheck
+        ))
+        .ephemeral(true),
+    )
+    .await?;
+
+  Ok(())
+}
+
+/// Reply to a ticket
+///
+/// Sends a reply to the member by DM. Replies are sent on behalf of staff, without your name. Use in a ticket thread.
+///
+/// Requires `Manage Messages` permissions.
+#[poise::command(slash_command, required_permissions = "MANAGE_MESSAGES")]
+async fn reply(
+  ctx: Context<'_>,
+  #[description = "The reply to send to the member"]
+  #[max_length = 2000]
+  message: String,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let Some(ticket) = ticket_in_channel(ctx).await? else {
+    return Ok(());
+  };
+
+  if !ticket.open {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!("{} This ticket has been closed.", emoji.mminfo))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let guild_name = ticket
+    .guild_id
+    .name(ctx)
+    .unwrap_or_else(|| "the server".to_owned());
+
+  let delivered = ticket
+    .user_id
+    .direct_message(
+      ctx,
+      CreateMessage::new().embed(
+        BloomBotEmbed::new()
+          .title(format!("Reply from the staff of {guild_name}"))
+          .description(&message)
+          .footer(CreateEmbedFooter::new(
+            "Reply here to respond. Your reply will be added to your ticket.",
+          )),
+      ),
+    )
+    .await
+    .is_ok();
+
+  if !delivered {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} The reply couldn't be delivered. The member may have left the server or turned off DMs.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+  let reply = TicketMessage::new(&ticket.id, ctx.author().id, true, message.clone());
+  DatabaseHandler::add_ticket_message(&mut transaction, &reply).await?;
+  DatabaseHandler::commit_transaction(transaction).await?;
+
+  ctx
+    .send(
+      CreateReply::default().embed(
+        BloomBotEmbed::new()
+          .title("Reply Sent")
+          .description(message)
+          .footer(
+            CreateEmbedFooter::new(format!("Sent by {}", ctx.author().name))
+              .icon_url(ctx.author().avatar_url().unwrap_or_default()),
+          ),
+      ),
+    )
+    .await?;
+
+  Ok(())
+}
+
+/// Close a ticket
+///
+/// Closes the ticket, lets the member know, and archives the thread. Messages the member sends afterwards open a new ticket. Use in a ticket thread.
+///
+/// Requires `Manage Messages` permissions.
+#[poise::command(slash_command, required_permissions = "MANAGE_MESSAGES")]
+async fn close(
+  ctx: Context<'_>,
+  #[description = "A closing note for the member"]
+  #[max_length = 1000]
+  note: Option<String>,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let Some(ticket) = ticket_in_channel(ctx).await? else {
+    return Ok(());
+  };
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  if DatabaseHandler::close_ticket(&mut transaction, &ticket.id, &ctx.author().id).await? == 0 {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!("{} This ticket is already closed.", emoji.mminfo))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  if let Some(note) = &note {
+    let closing_note = TicketMessage::new(&ticket.id, ctx.author().id, true, note.clone());
+    DatabaseHandler::add_ticket_message(&mut transaction, &closing_note).await?;
+  }
+
+  DatabaseHandler::commit_transaction(transaction).await?;
+
+  let mut notification = format!("{} Your ticket has been closed by staff.", emoji.mminfo);
+  if let Some(note) = &note {
+    notification.push_str(&format!("\n\n{note}"));
+  }
+  notification
+    .push_str("\n\n-# If you need anything else, send a message here to open a new ticket.");
+  let notified = ticket
+    .user_id
+    .direct_message(ctx, CreateMessage::new().content(notification))
+    .await
+    .is_ok();
+
+  ctx
+    .send(CreateReply::default().content(format!(
+      "{} Ticket closed by {}.{}",
+      emoji.mmcheck,
+      ctx.author().mention(),
+      if notified {
+        ""
+      } else {
+        " The member could not be notified by DM."
+      }
+    )))
+    .await?;
+
+  ticket
+    .thread_id
+    .edit_thread(ctx, EditThread::new().archived(true).locked(true))
+    .await?;
+
+  Ok(())
+}
+
+/// Export a ticket transcript
+///
+/// Exports the transcript of a ticket as a text file. Use in a ticket thread, or specify the ticket ID.
+///
+/// Requires `Manage Messages` permissions.
+#[poise::command(slash_command, required_permissions = "MANAGE_MESSAGES")]
+async fn export(
+  ctx: Context<'_>,
+  #[description = "The ticket ID (defaults to the ticket for this thread)"] ticket_id: Option<
+    String,
+  >,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let ticket = match ticket_id {
+    Some(ticket_id) => {
+      let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+      let ticket = DatabaseHandler::get_ticket(&mut transaction, &guild_id, &ticket_id).await?;
+      if ticket.is_none() {
+        ctx
+          .send(
+            CreateReply::default()
+              .content(format!("{} No ticket found with that ID.", emoji.mminfo))
+              .ephemeral(true),
+          )
+          .await?;
+      }
+      ticket
+    }
+    None => ticket_in_channel(ctx).await?,
+  };
+  let Some(ticket) = ticket else {
+    return Ok(());
+  };
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+  let messages = DatabaseHandler::get_ticket_transcript(&mut transaction, &ticket.id).await?;
+  drop(transaction);
+
+  let transcript = tickets::transcript(&ticket, &messages);
+  let filename = format!("ticket-{}.txt", ticket.id);
+  let content = format!(
+    "{} Transcript for ticket `{}` ({} messages)",
+    emoji.mmcheck,
+    ticket.id,
+    messages.len()
+  );
+
+  // Link the transcript from object storage when it's configured, falling back to an attachment
+  let storage = &ctx.data().storage;
+  if storage.is_enabled() {
+    match storage
+      .upload(
+        ArtifactKind::Export,
+        &filename,
+        transcript.clone().into_bytes(),
+        "text/plain; charset=utf-8",
+      )
+      .await
+    {
+      Ok(upload) => {
+        let expiry = upload
+          .expires_at
+          .map(|expires_at| format!("\n-# This link expires <t:{}:R>.", expires_at.timestamp()))
+          .unwrap_or_default();
+        ctx
+          .send(
+            CreateReply::default()
+              .content(format!("{content}: [{filename}]({}){expiry}", upload.url))
+              .ephemeral(true),
+          )
+          .await?;
+        return Ok(());
+      }
+      Err(e) => warn!("Failed to upload ticket transcript, attaching it instead: {e:?}"),
+    }
+  }
+
+  ctx
+    .send(
+      CreateReply::default()
+        .content(content)
+        .attachment(CreateAttachment::bytes(transcript.into_bytes(), filename))
+        .ephemeral(true),
+    )
+    .await?;
+
+  Ok(())
+}
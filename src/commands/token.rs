@@ -0,0 +1,260 @@
+use anyhow::{Context as AnyhowContext, Result};
+use poise::serenity_prelude::CreateEmbedFooter;
+use poise::CreateReply;
+
+use crate::commands::helpers::common::Visibility;
+use crate::commands::helpers::database::{self, MessageType};
+use crate::config::BloomBotEmbed;
+use crate::data::session_webhook::{WebhookToken, DEFAULT_HOURLY_LIMIT, MAX_DAYS_BACK};
+use crate::data::session_webhook::{MAX_MINUTES, MAX_TOKENS};
+use crate::database::DatabaseHandler;
+use crate::Context;
+
+/// Suggests the names of the author's tokens containing `partial`. Returns no suggestions
+/// outside of a guild or if the lookup fails.
+async fn autocomplete_token(ctx: Context<'_>, partial: &str) -> impl Iterator<Item = String> {
+  let names = match ctx.guild_id() {
+    Some(guild_id) => match ctx.data().db.start_transaction_with_retry(5).await {
+      Ok(mut transaction) => {
+        DatabaseHandler::get_webhook_tokens(&mut transaction, &guild_id, &ctx.author().id)
+          .await
+          .unwrap_or_default()
+          .into_iter()
+          .map(|token| token.name)
+          .collect()
+      }
+      Err(_) => Vec::new(),
+    },
+    None => Vec::new(),
+  };
+
+  let partial = partial.to_lowercase();
+  names
+    .into_iter()
+    .filter(move |name| name.to_lowercase().contains(&partial))
+}
+
+/// Manage quick-log tokens
+///
+/// Commands to create, list, and revoke personal tokens for logging sessions from other apps and devices, such as iOS Shortcuts, Tasker, Zapier, IFTTT, or a smartwatch.
+///
+/// Tokens can only log sessions for you in this server. They can't view or change anything else.
+#[poise::command(
+  slash_command,
+  category = "Meditation Tracking",
+  subcommands("create", "list", "revoke"),
+  subcommand_required,
+  guild_only
+)]
+#[allow(clippy::unused_async)]
+pub async fn token(_: Context<'_>) -> Result<()> {
+  Ok(())
+}
+
+/// Create a quick-log token
+///
+/// Creates a personal token for logging sessions in this server with a single tap, from an iOS Shortcut, a Tasker task, or another automation.
+///
+/// Give each token a name for the app or device using it, so you can tell them apart and revoke one without affecting the others. Each token can log a limited number of sessions an hour, 10 unless you choose otherwise. The token is only shown once, and you should never share it with anyone.
+#[poise::command(slash_command)]
+async fn create(
+  ctx: Context<'_>,
+  #[description = "A name for the app or device using the token, such as \"Apple Watch\""]
+  #[max_length = 50]
+  name: String,
+  #[description = "The most sessions the token can log each hour (Defaults to 10)"]
+  #[min = 1]
+  #[max = 60]
+  hourly_limit: Option<i32>,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+  let user_id = ctx.author().id;
+
+  let Some(session_url) = ctx.data().bot_config.get().web.session_url() else {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} Logging sessions from other apps isn't available yet.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  };
+
+  let name = name.trim();
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  let tokens = DatabaseHandler::get_webhook_tokens(&mut transaction, &guild_id, &user_id).await?;
+  if tokens
+    .iter()
+    .any(|token| token.name.to_lowercase() == name.to_lowercase())
+  {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} You already have a token called **{name}**. Please choose another name, or revoke it with `/token revoke` first.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let hourly_limit = hourly_limit.unwrap_or(DEFAULT_HOURLY_LIMIT);
+  let (token, webhook_token) = WebhookToken::generate(guild_id, user_id, name, hourly_limit);
+  if !DatabaseHandler::add_webhook_token(&mut transaction, &webhook_token).await? {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} You already have {MAX_TOKENS} tokens. Please revoke one you no longer use with `/token revoke` first.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  database::commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(format!(
+      "{} Created **{name}**. Your token is `{token}`\n\nTo log a session, send a `POST` request to <{session_url}> with a JSON body such as:\n```json\n{{ \"token\": \"{token}\", \"minutes\": 20 }}\n```\nIn iOS Shortcuts, use the **Get Contents of URL** action with the method set to POST and the request body set to JSON. Add a `timestamp` such as `\"2024-12-03T07:30:00Z\"` to log a session at another time in the last {MAX_DAYS_BACK} days. Sessions can be up to {MAX_MINUTES} minutes long, and this token can log up to {hourly_limit} an hour.\n\nThis token won't be shown again. Never share it with anyone.",
+      emoji.mmcheck
+    )),
+    Visibility::Ephemeral,
+  )
+  .await?;
+
+  Ok(())
+}
+
+/// List your quick-log tokens
+///
+/// Lists your quick-log tokens in this server, with their hourly limits and when they were last used.
+#[poise::command(slash_command)]
+async fn list(ctx: Context<'_>) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+  let tokens =
+    DatabaseHandler::get_webhook_tokens(&mut transaction, &guild_id, &ctx.author().id).await?;
+  drop(transaction);
+
+  if tokens.is_empty() {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} You don't have any tokens. Create one with `/token create`.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let description = tokens
+    .iter()
+    .map(|token| {
+      let last_used = token.last_used_at.map_or_else(
+        || "never used".to_owned(),
+        |last_used_at| format!("last used <t:{}:R>", last_used_at.timestamp()),
+      );
+      format!(
+        "- **{}**: up to {} sessions an hour, {last_used}",
+        token.name, token.hourly_limit
+      )
+    })
+    .collect::<Vec<_>>()
+    .join("\n");
+
+  ctx
+    .send(
+      CreateReply::default()
+        .embed(
+          BloomBotEmbed::new()
+            .title("Quick-Log Tokens")
+            .description(description)
+            .footer(CreateEmbedFooter::new(format!(
+              "{} of {MAX_TOKENS} tokens",
+              tokens.len()
+            ))),
+        )
+        .ephemeral(true),
+    )
+    .await?;
+
+  Ok(())
+}
+
+/// Revoke a quick-log token
+///
+/// Revokes one of your quick-log tokens, so it can no longer log sessions. Set all to true to revoke every token you have in this server.
+#[poise::command(slash_command)]
+async fn revoke(
+  ctx: Context<'_>,
+  #[description = "The token to revoke"]
+  #[autocomplete = "autocomplete_token"]
+  name: Option<String>,
+  #[description = "Revoke all of your tokens (Defaults to false)"] all: Option<bool>,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+  let user_id = ctx.author().id;
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  let message = if all == Some(true) {
+    match DatabaseHandler::remove_webhook_tokens(&mut transaction, &guild_id, &user_id).await? {
+      0 => format!("{} You don't have any tokens.", emoji.mminfo),
+      revoked => format!(
+        "{} Revoked {revoked} {}. They can no longer log sessions.",
+        emoji.mmcheck,
+        if revoked == 1 { "token" } else { "tokens" }
+      ),
+    }
+  } else if let Some(name) = name {
+    if DatabaseHandler::remove_webhook_token(&mut transaction, &guild_id, &user_id, &name).await? {
+      format!(
+        "{} Revoked **{name}**. It can no longer log sessions.",
+        emoji.mmcheck
+      )
+    } else {
+      format!(
+        "{} You don't have a token called **{name}**. Use `/token list` to see your tokens.",
+        emoji.mminfo
+      )
+    }
+  } else {
+    format!(
+      "{} Please choose a token to revoke, or set all to true to revoke every token.",
+      emoji.mminfo
+    )
+  };
+
+  database::commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(message),
+    Visibility::Ephemeral,
+  )
+  .await?;
+
+  Ok(())
+}
@@ -0,0 +1,322 @@
+use std::cmp::Reverse;
+
+use anyhow::{Context as AnyhowContext, Result};
+use chrono::{Duration as ChronoDuration, Utc};
+use poise::serenity_prelude::{builder::*, ChannelId, Mentionable, Timestamp, User};
+use poise::{ChoiceParameter, CreateReply};
+
+use crate::commands::helpers::common::Visibility;
+use crate::commands::helpers::database::{self, MessageType};
+use crate::commands::helpers::pagination::{PageRow, PageRowRef, PageType, Paginator};
+use crate::config::{BloomBotEmbed, CHANNELS, ENTRIES_PER_PAGE};
+use crate::data::erase::Erase;
+use crate::data::warning::Warning;
+use crate::database::DatabaseHandler;
+use crate::Context;
+
+/// The number of warnings after which staff are prompted to consider escalating.
+const ESCALATION_THRESHOLD: usize = 3;
+
+#[derive(ChoiceParameter)]
+enum DateFormat {
+  #[name = "YYYY-MM-DD (ISO 8601)"]
+  Ymd,
+  #[name = "DD Month YYYY"]
+  Dmy,
+}
+
+/// The timeout lengths offered by Discord's member menu.
+#[derive(ChoiceParameter, Clone, Copy)]
+enum TimeoutLength {
+  #[name = "60 seconds"]
+  OneMinute,
+  #[name = "5 minutes"]
+  FiveMinutes,
+  #[name = "10 minutes"]
+  TenMinutes,
+  #[name = "1 hour"]
+  OneHour,
+  #[name = "1 day"]
+  OneDay,
+  #[name = "1 week"]
+  OneWeek,
+}
+
+impl TimeoutLength {
+  fn duration(self) -> ChronoDuration {
+    match self {
+      Self::OneMinute => ChronoDuration::minutes(1),
+      Self::FiveMinutes => ChronoDuration::minutes(5),
+      Self::TenMinutes => ChronoDuration::minutes(10),
+      Self::OneHour => ChronoDuration::hours(1),
+      Self::OneDay => ChronoDuration::days(1),
+      Self::OneWeek => ChronoDuration::weeks(1),
+    }
+  }
+}
+
+/// An entry in a member's moderation history, which includes both warnings and erases.
+enum HistoryEntry<'a> {
+  Warning(&'a Warning),
+  Erase(&'a Erase),
+}
+
+impl PageRow for HistoryEntry<'_> {
+  fn title(&self, page_type: PageType) -> String {
+    match self {
+      Self::Warning(warning) => format!("Warning · {}", warning.title(page_type)),
+      Self::Erase(erase) => format!("Erase · {}", erase.title(page_type)),
+    }
+  }
+
+  fn body(&self) -> String {
+    match self {
+      Self::Warning(warning) => warning.body(),
+      Self::Erase(erase) => erase.body(),
+    }
+  }
+}
+
+/// Suggests escalating once a member has been warned [`ESCALATION_THRESHOLD`] times or more.
+fn escalation_note(warnings: usize, erases: usize) -> Option<String> {
+  if warnings < ESCALATION_THRESHOLD {
+    return None;
+  }
+
+  Some(format!(
+    "This member now has {warnings} warnings and {erases} {}. Consider a longer timeout or raising it with the moderation team.",
+    if erases == 1 { "erase" } else { "erases" }
+  ))
+}
+
+/// Warn a member
+///
+/// Warns a member, optionally timing them out, and records the warning. The member is notified by DM when possible. Once a member has several warnings, staff are prompted to consider escalating.
+///
+/// Requires `Manage Messages` permissions. Timeouts also require `Moderate Members` permissions for Bloom.
+#[poise::command(
+  slash_command,
+  required_permissions = "MANAGE_MESSAGES",
+  default_member_permissions = "MANAGE_MESSAGES",
+  category = "Moderator Commands",
+  guild_only
+)]
+pub async fn warn(
+  ctx: Context<'_>,
+  #[description = "The member to warn"] user: User,
+  #[description = "The reason for the warning (shown to the member)"]
+  #[max_length = 512] // Max length for audit log reason
+  reason: String,
+  #[description = "Also time out the member"] timeout: Option<TimeoutLength>,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  if user.bot || user.id == ctx.author().id {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!("{} You can't warn that user.", emoji.mminfo))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let mut warning = Warning::new(guild_id, user.id, ctx.author().id, reason.clone());
+
+  if let Some(timeout) = timeout {
+    let timeout_until = Utc::now() + timeout.duration();
+    guild_id
+      .edit_member(
+        ctx,
+        user.id,
+        EditMember::new()
+          .disable_communication_until_datetime(Timestamp::from_unix_timestamp(
+            timeout_until.timestamp(),
+          )?)
+          .audit_log_reason(&reason),
+      )
+      .await?;
+    warning = warning.timeout_until(timeout_until);
+  }
+
+  let mut dm_embed = BloomBotEmbed::new()
+    .title("Warning")
+    .description(format!(
+      "You have received a warning from the staff of {}.\n\n**Reason:** {reason}",
+      guild_id
+        .name(ctx)
+        .unwrap_or_else(|| "the server".to_owned())
+    ))
+    .footer(CreateEmbedFooter::new(
+      "If you have any questions or concerns regarding this action, please contact staff via ModMail.",
+    ));
+  if let Some(timeout_until) = warning.timeout_until {
+    dm_embed = dm_embed.field(
+      "Timeout",
+      format!(
+        "You can participate again <t:{}:R>.",
+        timeout_until.timestamp()
+      ),
+      false,
+    );
+  }
+  let notified = user
+    .direct_message(ctx, CreateMessage::new().embed(dm_embed))
+    .await
+    .is_ok();
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  let warnings = DatabaseHandler::get_warnings(&mut transaction, &guild_id, &user.id)
+    .await?
+    .len()
+    + 1;
+  let erases = DatabaseHandler::get_erases(&mut transaction, &guild_id, &user.id)
+    .await?
+    .len();
+
+  let mut log_embed = BloomBotEmbed::new()
+    .title("Member Warned")
+    .author(CreateEmbedAuthor::new(&user.name).icon_url(user.face()))
+    .field("Member", format!("{} ({})", user.mention(), user.id), false)
+    .field("Reason", &reason, false)
+    .field(
+      "History",
+      format!("{warnings} warnings · {erases} erases"),
+      true,
+    )
+    .field("Notified", if notified { "Yes" } else { "No" }, true);
+  if let Some(timeout_until) = warning.timeout_until {
+    log_embed = log_embed.field(
+      "Timed Out Until",
+      format!("<t:{}:f>", timeout_until.timestamp()),
+      true,
+    );
+  }
+  log_embed = log_embed.footer(
+    CreateEmbedFooter::new(format!(
+      "Warned by {} ({})",
+      ctx.author().name,
+      ctx.author().id
+    ))
+    .icon_url(ctx.author().avatar_url().unwrap_or_default()),
+  );
+
+  let log_message = ChannelId::new(CHANNELS.logs)
+    .send_message(ctx, CreateMessage::new().embed(log_embed))
+    .await?;
+  warning = warning.message_link(log_message.link());
+
+  DatabaseHandler::add_warning(&mut transaction, &warning).await?;
+
+  let mut response = format!("{} {} has been warned.", emoji.mmcheck, user.mention());
+  if !notified {
+    response.push_str(" They could not be notified by DM.");
+  }
+  if let Some(note) = escalation_note(warnings, erases) {
+    response.push_str(&format!("\n\n{} {note}", emoji.mminfo));
+  }
+
+  database::commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(response),
+    Visibility::Ephemeral,
+  )
+  .await?;
+
+  Ok(())
+}
+
+/// List warnings for a member
+///
+/// Lists a member's warnings together with their erases, newest first, with links to the log messages when available.
+///
+/// Requires `Manage Messages` permissions.
+#[poise::command(
+  slash_command,
+  required_permissions = "MANAGE_MESSAGES",
+  default_member_permissions = "MANAGE_MESSAGES",
+  category = "Moderator Commands",
+  guild_only
+)]
+pub async fn warnings(
+  ctx: Context<'_>,
+  #[description = "The member to show warnings for"] user: User,
+  #[description = "The page to show"] page: Option<usize>,
+  #[description = "Date format (Defaults to YYYY-MM-DD)"] date_format: Option<DateFormat>,
+) -> Result<()> {
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+  let warnings = DatabaseHandler::get_warnings(&mut transaction, &guild_id, &user.id).await?;
+  let erases = DatabaseHandler::get_erases(&mut transaction, &guild_id, &user.id).await?;
+  drop(transaction);
+
+  let mut history: Vec<(_, HistoryEntry)> = warnings
+    .iter()
+    .map(|warning| (warning.occurred_at, HistoryEntry::Warning(warning)))
+    .chain(
+      erases
+        .iter()
+        .map(|erase| (erase.occurred_at(), HistoryEntry::Erase(erase))),
+    )
+    .collect();
+  history.sort_by_key(|(occurred_at, _)| Reverse(*occurred_at));
+
+  let entries: Vec<PageRowRef> = history
+    .iter()
+    .map(|(_, entry)| entry as PageRowRef)
+    .collect();
+
+  let title = {
+    let user_nick_or_name = user
+      .nick_in(&ctx, guild_id)
+      .await
+      .unwrap_or_else(|| user.global_name.as_ref().unwrap_or(&user.name).clone());
+    format!(
+      "History for {user_nick_or_name} ({} warnings, {} erases)",
+      warnings.len(),
+      erases.len()
+    )
+  };
+
+  let page_type = match date_format {
+    Some(DateFormat::Dmy) => PageType::Alternate,
+    _ => PageType::Standard,
+  };
+
+  let visibility = if ctx.channel_id() == CHANNELS.logs {
+    Visibility::Public
+  } else {
+    Visibility::Ephemeral
+  };
+
+  Paginator::new(title, &entries, ENTRIES_PER_PAGE.default)
+    .paginate(ctx, page, page_type, visibility)
+    .await?;
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_escalation_note() {
+    assert!(escalation_note(1, 4).is_none());
+    assert!(escalation_note(ESCALATION_THRESHOLD - 1, 0).is_none());
+
+    let Some(note) = escalation_note(ESCALATION_THRESHOLD, 1) else {
+      panic!("Expected an escalation note");
+    };
+    assert!(note.contains("3 warnings and 1 erase."));
+  }
+}
@@ -0,0 +1,212 @@
+use anyhow::{Context as AnyhowContext, Result};
+use poise::CreateReply;
+
+use crate::commands::helpers::common::Visibility;
+use crate::commands::helpers::database::{self, MessageType};
+use crate::commands::helpers::pagination::{PageRowRef, PageType, Paginator};
+use crate::config::ENTRIES_PER_PAGE;
+use crate::data::watchlist::WatchlistTerm;
+use crate::database::DatabaseHandler;
+use crate::Context;
+
+async fn autocomplete_pattern(ctx: Context<'_>, partial: &str) -> impl Iterator<Item = String> {
+  let terms = match ctx.guild_id() {
+    Some(guild_id) => match ctx.data().db.start_transaction_with_retry(5).await {
+      Ok(mut transaction) => DatabaseHandler::get_watchlist(&mut transaction, &guild_id)
+        .await
+        .unwrap_or_default(),
+      Err(_) => Vec::new(),
+    },
+    None => Vec::new(),
+  };
+
+  let partial = partial.to_lowercase();
+  terms
+    .into_iter()
+    .map(|term| term.pattern)
+    .filter(move |pattern| pattern.to_lowercase().contains(&partial))
+    .take(25)
+}
+
+/// Commands for managing the message watchlist
+///
+/// Commands to add, remove, or list watchlist terms. Messages which match a term are quietly flagged in the staff log channel, with buttons to ignore, erase, or report them.
+///
+/// Requires `Manage Messages` permissions.
+#[poise::command(
+  slash_command,
+  required_permissions = "MANAGE_MESSAGES",
+  default_member_permissions = "MANAGE_MESSAGES",
+  category = "Moderator Commands",
+  subcommands("add", "remove", "list"),
+  subcommand_required,
+  guild_only
+)]
+#[allow(clippy::unused_async)]
+pub async fn watchlist(_: Context<'_>) -> Result<()> {
+  Ok(())
+}
+
+/// Add a term to the watchlist
+///
+/// Adds a word or phrase to the watchlist. Terms match whole words, ignoring case. To match a pattern instead, set `regex` to true and enter a regular expression.
+#[poise::command(slash_command)]
+async fn add(
+  ctx: Context<'_>,
+  #[description = "The word, phrase, or pattern to watch for"]
+  #[max_length = 200]
+  term: String,
+  #[description = "Treat the term as a regular expression (defaults to false)"] regex: Option<bool>,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let term = term.trim();
+  if term.is_empty() {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} Please enter a term to watch for.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let term = WatchlistTerm::new(
+    guild_id,
+    term.to_owned(),
+    regex.unwrap_or(false),
+    ctx.author().id,
+  );
+
+  if let Err(e) = term.matcher() {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} That isn't a valid regular expression:\n```{e}```",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  if DatabaseHandler::add_watchlist_term(&mut transaction, &term).await? == 0 {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} `{}` is already on the watchlist.",
+            emoji.mminfo, term.pattern
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  database::commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(format!(
+      "{} `{}` has been added to the watchlist.",
+      emoji.mmcheck, term.pattern
+    )),
+    Visibility::Ephemeral,
+  )
+  .await?;
+
+  Ok(())
+}
+
+/// Remove a term from the watchlist
+///
+/// Removes a term from the watchlist. Messages which were already flagged are not affected.
+#[poise::command(slash_command)]
+async fn remove(
+  ctx: Context<'_>,
+  #[description = "The term to remove"]
+  #[autocomplete = "autocomplete_pattern"]
+  term: String,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  if DatabaseHandler::remove_watchlist_term(&mut transaction, &guild_id, &term).await? == 0 {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} `{term}` is not on the watchlist.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  database::commit_and_say(
+    ctx,
+    transaction,
+    MessageType::TextOnly(format!(
+      "{} `{term}` has been removed from the watchlist.",
+      emoji.mmcheck
+    )),
+    Visibility::Ephemeral,
+  )
+  .await?;
+
+  Ok(())
+}
+
+/// List the terms on the watchlist
+///
+/// Lists all terms on the watchlist, in alphabetical order.
+#[poise::command(slash_command)]
+async fn list(
+  ctx: Context<'_>,
+  #[description = "The page to show"] page: Option<usize>,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+  let terms = DatabaseHandler::get_watchlist(&mut transaction, &guild_id).await?;
+  drop(transaction);
+
+  if terms.is_empty() {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!("{} The watchlist is empty.", emoji.mminfo))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let terms: Vec<PageRowRef> = terms.iter().map(|term| term as PageRowRef).collect();
+
+  Paginator::new("Watchlist", &terms, ENTRIES_PER_PAGE.default)
+    .paginate(ctx, page, PageType::Standard, Visibility::Ephemeral)
+    .await?;
+
+  Ok(())
+}
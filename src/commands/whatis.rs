@@ -1,8 +1,10 @@
 use anyhow::{Context as AnyhowContext, Result};
-use poise::serenity_prelude::CreateEmbedFooter;
+use poise::serenity_prelude::{CreateEmbedFooter, Message};
 use poise::CreateReply;
 
+use crate::commands::helpers::terms::autocomplete_term;
 use crate::config::BloomBotEmbed;
+use crate::data::term::Term;
 use crate::database::DatabaseHandler;
 use crate::Context;
 
@@ -12,7 +14,9 @@ use crate::Context;
 #[poise::command(slash_command, category = "Informational", guild_only)]
 pub async fn whatis(
   ctx: Context<'_>,
-  #[description = "The term to show information about"] term: String,
+  #[description = "The term to show information about"]
+  #[autocomplete = "autocomplete_term"]
+  term: String,
 ) -> Result<()> {
   let guild_id = ctx
     .guild_id()
@@ -107,3 +111,149 @@ pub async fn whatis(
 
   Ok(())
 }
+
+/// The maximum number of terms defined by the "Define Terms" context menu command.
+const MAX_DEFINED_TERMS: usize = 10;
+
+/// Checks whether `name` appears in `content` as a whole word or phrase, ignoring case.
+fn mentions(content: &str, name: &str) -> bool {
+  let name = name.to_lowercase();
+  if name.is_empty() {
+    return false;
+  }
+
+  let content = content.to_lowercase();
+  content.match_indices(&name).any(|(start, _)| {
+    let end = start + name.len();
+    let before = content[..start].chars().next_back();
+    let after = content[end..].chars().next();
+    !before.is_some_and(char::is_alphanumeric) && !after.is_some_and(char::is_alphanumeric)
+  })
+}
+
+/// Returns the names of the [`Term`]s mentioned in `content` by name or alias, in the
+/// order they are listed.
+fn find_mentioned_terms<'a>(content: &str, terms: &'a [Term]) -> Vec<&'a str> {
+  terms
+    .iter()
+    .filter(|term| {
+      mentions(content, &term.name)
+        || term
+          .aliases
+          .iter()
+          .flatten()
+          .any(|alias| mentions(content, alias))
+    })
+    .map(|term| term.name.as_str())
+    .collect()
+}
+
+/// Define glossary terms in a message
+///
+/// Shows short definitions for any glossary terms mentioned in a message, by name or alias.
+///
+/// To use, right-click the message that you want to look up terms in, then go to "Apps" > "Define Terms".
+#[poise::command(
+  ephemeral,
+  context_menu_command = "Define Terms",
+  category = "Context Menu Commands",
+  guild_only
+)]
+pub async fn define_terms(
+  ctx: Context<'_>,
+  #[description = "Message to look up terms in"] message: Message,
+) -> Result<()> {
+  let emoji = ctx.data().emoji.get(ctx.guild_id());
+  let guild_id = ctx
+    .guild_id()
+    .with_context(|| "Failed to retrieve guild ID from context")?;
+
+  let mut transaction = ctx.data().db.start_transaction_with_retry(5).await?;
+
+  let terms = DatabaseHandler::get_term_list(&mut transaction, &guild_id).await?;
+  let mut term_names = find_mentioned_terms(&message.content, &terms);
+  // The term list is sorted in descending order
+  term_names.reverse();
+
+  if term_names.is_empty() {
+    ctx
+      .send(
+        CreateReply::default()
+          .content(format!(
+            "{} No glossary terms were found in this message.",
+            emoji.mminfo
+          ))
+          .ephemeral(true),
+      )
+      .await?;
+    return Ok(());
+  }
+
+  let mut embed = BloomBotEmbed::new().title("Terms in this message");
+
+  for term_name in term_names.iter().take(MAX_DEFINED_TERMS) {
+    let Some(term) = DatabaseHandler::get_term(&mut transaction, &guild_id, term_name).await?
+    else {
+      continue;
+    };
+
+    let one_liner = term
+      .meaning
+      .split_once('\n')
+      .map_or(term.meaning.as_str(), |(one_liner, _)| one_liner);
+
+    // Embed field values are limited to 1024 characters
+    let definition = if one_liner.chars().count() > 1024 {
+      format!("{}...", one_liner.chars().take(1021).collect::<String>())
+    } else {
+      one_liner.to_string()
+    };
+
+    embed = embed.field(term.name, definition, false);
+  }
+
+  if term_names.len() > MAX_DEFINED_TERMS {
+    embed = embed.footer(CreateEmbedFooter::new(format!(
+      "Showing {MAX_DEFINED_TERMS} of {} terms. Use /glossary info to read any entry in full.",
+      term_names.len()
+    )));
+  } else {
+    embed = embed.footer(CreateEmbedFooter::new(
+      "Use /glossary info to read any entry in full.",
+    ));
+  }
+
+  ctx
+    .send(CreateReply::default().embed(embed).ephemeral(true))
+    .await?;
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_find_mentioned_terms() {
+    let terms = vec![
+      Term::new(123u64, "Metta", "Goodwill", None).aliases(Some("Loving-kindness".to_string())),
+      Term::new(123u64, "Jhana", "Absorption", None),
+      Term::new(123u64, "Insight Meditation", "Seeing clearly", None)
+        .aliases(Some("Vipassana".to_string())),
+    ];
+
+    assert_eq!(
+      find_mentioned_terms(
+        "I've been practicing loving-kindness and vipassana.",
+        &terms
+      ),
+      vec!["Metta", "Insight Meditation"]
+    );
+    assert_eq!(
+      find_mentioned_terms("Is INSIGHT MEDITATION the same as jhana?", &terms),
+      vec!["Jhana", "Insight Meditation"]
+    );
+    assert!(find_mentioned_terms("Jhanas and mettaverse", &terms).is_empty());
+  }
+}
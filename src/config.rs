@@ -94,7 +94,6 @@ pub struct SimpleEmoji<'a> {
   pub name: &'a str,
 }
 
-#[allow(dead_code)]
 pub struct BloomEmoji<'a> {
   pub pepeglow: SimpleEmoji<'a>,
   pub aww: SimpleEmoji<'a>,
@@ -104,6 +103,9 @@ pub struct BloomEmoji<'a> {
   pub mmcheck: SimpleEmoji<'a>,
 }
 
+/// Bloom's custom emoji, which belong to the Meditation Mind server. Use
+/// [`EmojiHandler`][crate::emoji::EmojiHandler] to get the emoji for a server, which falls
+/// back to Unicode emoji in other servers.
 pub const EMOJI: BloomEmoji = BloomEmoji {
   pepeglow: SimpleEmoji {
     animated: false,
@@ -171,6 +173,24 @@ pub enum TimeSumRoles {
 }
 
 impl TimeSumRoles {
+  pub const ALL: [Self; 15] = [
+    TimeSumRoles::One,
+    TimeSumRoles::Two,
+    TimeSumRoles::Three,
+    TimeSumRoles::Four,
+    TimeSumRoles::Five,
+    TimeSumRoles::Six,
+    TimeSumRoles::Seven,
+    TimeSumRoles::Eight,
+    TimeSumRoles::Nine,
+    TimeSumRoles::Ten,
+    TimeSumRoles::Eleven,
+    TimeSumRoles::Twelve,
+    TimeSumRoles::Thirteen,
+    TimeSumRoles::Fourteen,
+    TimeSumRoles::Fifteen,
+  ];
+
   pub fn to_role_id(&self) -> serenity::RoleId {
     serenity::RoleId::new(match self {
       TimeSumRoles::One => 504641899890475018,
@@ -191,7 +211,7 @@ impl TimeSumRoles {
     })
   }
 
-  fn from_role_id(id: serenity::RoleId) -> Option<TimeSumRoles> {
+  pub fn from_role_id(id: serenity::RoleId) -> Option<TimeSumRoles> {
     match <u64>::from(id) {
       504641899890475018 => Some(TimeSumRoles::One),
       504641945596067851 => Some(TimeSumRoles::Two),
@@ -246,6 +266,27 @@ impl TimeSumRoles {
     roles
   }
 
+  /// The fewest minutes needed for the role, matching [`TimeSumRoles::from_sum`].
+  pub fn min_sum(&self) -> i64 {
+    match self {
+      TimeSumRoles::One => 50,
+      TimeSumRoles::Two => 100,
+      TimeSumRoles::Three => 150,
+      TimeSumRoles::Four => 250,
+      TimeSumRoles::Five => 500,
+      TimeSumRoles::Six => 1000,
+      TimeSumRoles::Seven => 2000,
+      TimeSumRoles::Eight => 5000,
+      TimeSumRoles::Nine => 10000,
+      TimeSumRoles::Ten => 20000,
+      TimeSumRoles::Eleven => 50000,
+      TimeSumRoles::Twelve => 100000,
+      TimeSumRoles::Thirteen => 120000,
+      TimeSumRoles::Fourteen => 150000,
+      TimeSumRoles::Fifteen => 200000,
+    }
+  }
+
   pub fn from_sum(sum: i64) -> Option<TimeSumRoles> {
     match sum {
       i64::MIN..=49 => None,
@@ -283,6 +324,19 @@ pub enum StreakRoles {
 }
 
 impl StreakRoles {
+  pub const ALL: [Self; 10] = [
+    StreakRoles::Egg,
+    StreakRoles::HatchingChick,
+    StreakRoles::BabyChick,
+    StreakRoles::Chicken,
+    StreakRoles::Dove,
+    StreakRoles::Owl,
+    StreakRoles::Eagle,
+    StreakRoles::Dragon,
+    StreakRoles::Alien,
+    StreakRoles::SpaceInvader,
+  ];
+
   pub fn to_role_id(&self) -> serenity::RoleId {
     serenity::RoleId::new(match self {
       StreakRoles::Egg => 857242224390832158,
@@ -313,6 +367,22 @@ impl StreakRoles {
     }
   }
 
+  /// The shortest streak needed for the role, in days, matching [`StreakRoles::from_streak`].
+  pub fn min_streak(&self) -> u64 {
+    match self {
+      StreakRoles::Egg => 7,
+      StreakRoles::HatchingChick => 14,
+      StreakRoles::BabyChick => 28,
+      StreakRoles::Chicken => 35,
+      StreakRoles::Dove => 56,
+      StreakRoles::Owl => 70,
+      StreakRoles::Eagle => 140,
+      StreakRoles::Dragon => 365,
+      StreakRoles::Alien => 730,
+      StreakRoles::SpaceInvader => 1825,
+    }
+  }
+
   pub fn from_streak(streak: u64) -> Option<StreakRoles> {
     match streak {
       0..=6 => None,
@@ -343,7 +413,7 @@ impl StreakRoles {
     roles
   }
 
-  fn from_role_id(id: serenity::RoleId) -> Option<StreakRoles> {
+  pub fn from_role_id(id: serenity::RoleId) -> Option<StreakRoles> {
     match <u64>::from(id) {
       857242224390832158 => Some(StreakRoles::Egg),
       857242222529347584 => Some(StreakRoles::HatchingChick),
@@ -359,3 +429,27 @@ impl StreakRoles {
     }
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_role_thresholds() {
+    for role in TimeSumRoles::ALL {
+      assert_eq!(TimeSumRoles::from_sum(role.min_sum()), Some(role));
+    }
+    assert_eq!(
+      TimeSumRoles::from_sum(TimeSumRoles::One.min_sum() - 1),
+      None
+    );
+
+    for role in StreakRoles::ALL {
+      assert_eq!(StreakRoles::from_streak(role.min_streak()), Some(role));
+    }
+    assert_eq!(
+      StreakRoles::from_streak(StreakRoles::Egg.min_streak() - 1),
+      None
+    );
+  }
+}
@@ -1,12 +0,0 @@
-pub mod bookmark;
-pub mod common;
-pub mod course;
-pub mod erase;
-pub mod meditation;
-pub mod pick_winner;
-pub mod quote;
-pub mod star_message;
-pub mod stats;
-pub mod steam_key;
-pub mod term;
-pub mod tracking_profile;
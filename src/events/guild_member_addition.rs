@@ -0,0 +1,27 @@
+use anyhow::Result;
+use poise::serenity_prelude::{Context, Member};
+
+use crate::database::DatabaseHandler;
+use crate::events::helpers::greetings;
+use crate::settings::SettingsHandler;
+
+pub async fn guild_member_addition(
+  ctx: &Context,
+  database: &DatabaseHandler,
+  settings: &SettingsHandler,
+  new_member: &Member,
+) -> Result<()> {
+  // Members who still need to complete membership screening are welcomed once they have
+  if new_member.user.bot || new_member.pending {
+    return Ok(());
+  }
+
+  greetings::welcome(
+    ctx,
+    database,
+    settings,
+    new_member.guild_id,
+    &new_member.user,
+  )
+  .await
+}
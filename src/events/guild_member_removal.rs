@@ -1,24 +1,20 @@
 use anyhow::Result;
-use poise::serenity_prelude::{ChannelId, Context, CreateMessage, User};
+use poise::serenity_prelude::{Context, GuildId, User};
 
-use crate::config::{BloomBotEmbed, CHANNELS};
+use crate::database::DatabaseHandler;
+use crate::events::helpers::greetings;
+use crate::settings::SettingsHandler;
 
-pub async fn guild_member_removal(ctx: &Context, user: &User) -> Result<()> {
-  let welcome_channel = ChannelId::new(CHANNELS.welcome);
+pub async fn guild_member_removal(
+  ctx: &Context,
+  database: &DatabaseHandler,
+  settings: &SettingsHandler,
+  guild_id: &GuildId,
+  user: &User,
+) -> Result<()> {
+  if user.bot {
+    return Ok(());
+  }
 
-  welcome_channel
-    .send_message(
-      &ctx,
-      CreateMessage::new().embed(
-        BloomBotEmbed::new()
-          .title("Member Left")
-          .description(format!(
-            "We wish you well on your future endeavors, {} :pray:",
-            user.name
-          )),
-      ),
-    )
-    .await?;
-
-  Ok(())
+  greetings::farewell(ctx, database, settings, *guild_id, user).await
 }
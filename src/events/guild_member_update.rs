@@ -1,7 +1,11 @@
 use anyhow::Result;
 use poise::serenity_prelude::{ChannelId, Context, CreateMessage, Member, RoleId};
 
-use crate::config::{BloomBotEmbed, CHANNELS, EMOJI, ROLES};
+use crate::config::{BloomBotEmbed, CHANNELS, ROLES};
+use crate::database::DatabaseHandler;
+use crate::emoji::EmojiHandler;
+use crate::events::helpers::greetings;
+use crate::settings::SettingsHandler;
 
 enum UpdateType {
   BecamePatreonDonator,
@@ -28,6 +32,9 @@ impl UpdateType {
 
 pub async fn guild_member_update(
   ctx: &Context,
+  database: &DatabaseHandler,
+  emoji: &EmojiHandler,
+  settings: &SettingsHandler,
   old_if_available: &Option<Member>,
   new: &Option<Member>,
 ) -> Result<()> {
@@ -35,6 +42,7 @@ pub async fn guild_member_update(
     return Ok(());
   };
   let Some(new) = new else { return Ok(()) };
+  let emoji = emoji.get(Some(new.guild_id));
 
   if let Some(update_type) = UpdateType::get_type(old, new) {
     match update_type {
@@ -48,7 +56,7 @@ pub async fn guild_member_update(
               .description(format!(
                 "Please welcome <@{}> as a new donator on Patreon.\n\nThank you for your generosity! It helps keep this community alive {}",
                 new.user.id,
-                EMOJI.loveit
+                emoji.loveit
               ))
             )
           )
@@ -63,7 +71,7 @@ pub async fn guild_member_update(
               .title(":tada: New Donator :tada:")
               .description(format!(
                 "Please welcome <@{}> as a new donator on Ko-fi.\n\nThank you for your generosity! It helps keep this community alive {}",
-                EMOJI.loveit,
+                emoji.loveit,
                 new.user.id
               ))
             )
@@ -80,12 +88,14 @@ pub async fn guild_member_update(
                   .title(":tada: A new member has arrived! :tada:")
                   .description(format!(
                     "Welcome to the Meditation Mind community, <@{}>!\n\nCheck out <id:customize> to grab some roles and [customize your community experience](<https://meditationmind.org/curating-your-experience/>).\n\nWe're glad you've joined us! {}",
-                    new.user.id, EMOJI.aww
+                    new.user.id, emoji.aww
                   ))
                   .thumbnail("https://meditationmind.org/wp-content/uploads/2020/04/Webp.net-resizeimage-1.png")
             )
           )
           .await?;
+
+        greetings::welcome(ctx, database, settings, new.guild_id, &new.user).await?;
       }
     }
   }
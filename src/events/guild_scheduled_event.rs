@@ -0,0 +1,97 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use poise::serenity_prelude::{GuildId, ScheduledEvent, ScheduledEventStatus, Timestamp};
+
+use crate::data::calendar::DiscordEvent;
+use crate::database::DatabaseHandler;
+use crate::ics;
+
+/// Keeps the copy of a scheduled event used by calendar feeds up to date. Events which have
+/// finished or been cancelled are removed, so feeds only show upcoming and ongoing events.
+pub async fn guild_scheduled_event_update(
+  database: &DatabaseHandler,
+  event: &ScheduledEvent,
+) -> Result<()> {
+  let mut transaction = database.start_transaction().await?;
+
+  match discord_event(event) {
+    Some(discord_event) => {
+      DatabaseHandler::add_discord_event(&mut transaction, &discord_event).await?;
+    }
+    None => {
+      DatabaseHandler::remove_discord_event(
+        &mut transaction,
+        &event.guild_id,
+        &event.id.to_string(),
+      )
+      .await?;
+    }
+  }
+
+  transaction.commit().await?;
+
+  Ok(())
+}
+
+pub async fn guild_scheduled_event_delete(
+  database: &DatabaseHandler,
+  event: &ScheduledEvent,
+) -> Result<()> {
+  let mut transaction = database.start_transaction().await?;
+  DatabaseHandler::remove_discord_event(&mut transaction, &event.guild_id, &event.id.to_string())
+    .await?;
+  transaction.commit().await?;
+
+  Ok(())
+}
+
+/// Replaces the copies of a guild's scheduled events with the current list, since events
+/// may have changed while the bot was offline.
+pub async fn sync(
+  database: &DatabaseHandler,
+  guild_id: GuildId,
+  events: &[ScheduledEvent],
+) -> Result<()> {
+  let events: Vec<DiscordEvent> = events.iter().filter_map(discord_event).collect();
+
+  let mut transaction = database.start_transaction().await?;
+  DatabaseHandler::replace_discord_events(&mut transaction, &guild_id, &events).await?;
+  transaction.commit().await?;
+
+  Ok(())
+}
+
+/// Converts a scheduled event for calendar feeds, unless it has finished or been cancelled.
+fn discord_event(event: &ScheduledEvent) -> Option<DiscordEvent> {
+  if !matches!(
+    event.status,
+    ScheduledEventStatus::Scheduled | ScheduledEventStatus::Active
+  ) {
+    return None;
+  }
+
+  // External events name a place, while others take place in a channel.
+  let location = event
+    .metadata
+    .as_ref()
+    .and_then(|metadata| metadata.location.clone())
+    .or_else(|| {
+      event
+        .channel_id
+        .map(|channel_id| ics::channel_url(event.guild_id.get(), channel_id.get()))
+    });
+
+  Some(DiscordEvent {
+    id: event.id.to_string(),
+    guild_id: event.guild_id,
+    name: event.name.clone(),
+    description: event.description.clone(),
+    location,
+    starts_at: to_utc(event.start_time)?,
+    ends_at: event.end_time.and_then(to_utc),
+  })
+}
+
+fn to_utc(timestamp: Timestamp) -> Option<DateTime<Utc>> {
+  DateTime::from_timestamp(timestamp.unix_timestamp(), 0)
+}
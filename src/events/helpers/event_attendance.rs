@@ -0,0 +1,52 @@
+use anyhow::Result;
+use chrono::Utc;
+use log::info;
+use poise::serenity_prelude::{ChannelId, GuildId, UserId};
+
+use crate::data::community_event::AttendanceSource;
+use crate::database::DatabaseHandler;
+
+/// Records a member as attending any community event which is running in a channel.
+pub async fn record(
+  database: &DatabaseHandler,
+  guild_id: GuildId,
+  channel_id: ChannelId,
+  user_id: UserId,
+  source: AttendanceSource,
+) -> Result<()> {
+  let mut transaction = database.start_transaction().await?;
+  let events =
+    DatabaseHandler::get_community_events_in_channel(&mut transaction, &guild_id, &channel_id)
+      .await?;
+
+  let now = Utc::now();
+  let mut recorded = false;
+  for event in events {
+    let Some(occurrence) = event.current_occurrence(now) else {
+      continue;
+    };
+
+    if DatabaseHandler::add_event_attendance(
+      &mut transaction,
+      &event.id,
+      &user_id,
+      &occurrence,
+      source,
+    )
+    .await?
+      > 0
+    {
+      info!(
+        "Recorded attendance for user {user_id} at event {}",
+        event.id
+      );
+      recorded = true;
+    }
+  }
+
+  if recorded {
+    DatabaseHandler::commit_transaction(transaction).await?;
+  }
+
+  Ok(())
+}
@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use log::{error, info, warn};
+use poise::serenity_prelude::{Context as SerenityContext, CreateEmbedAuthor, CreateMessage};
+
+use crate::config::BloomBotEmbed;
+use crate::data::feed::FeedSubscription;
+use crate::database::DatabaseHandler;
+use crate::feeds::{Feed, FeedHandler, FeedItem};
+
+/// How often to check feeds for new items.
+const CHECK_INTERVAL: Duration = Duration::from_secs(60 * 15);
+
+/// The most new items posted from a feed in one check. Any others are marked as seen
+/// without being posted, so a feed which suddenly republishes its archive doesn't flood
+/// the channel.
+const MAX_ITEMS_PER_CHECK: usize = 3;
+
+/// The longest summary shown in a post, in characters.
+const MAX_SUMMARY_LENGTH: usize = 400;
+
+/// The longest embed title Discord allows.
+const MAX_TITLE_LENGTH: usize = 256;
+
+fn truncate(text: &str, max_chars: usize) -> String {
+  if text.chars().count() <= max_chars {
+    return text.to_owned();
+  }
+  let truncated: String = text.chars().take(max_chars - 1).collect();
+  format!("{}…", truncated.trim_end())
+}
+
+async fn post_item(
+  ctx: &SerenityContext,
+  subscription: &FeedSubscription,
+  feed_title: &str,
+  item: &FeedItem,
+) -> Result<()> {
+  let mut embed = BloomBotEmbed::new()
+    .author(CreateEmbedAuthor::new(truncate(
+      feed_title,
+      MAX_TITLE_LENGTH,
+    )))
+    .title(truncate(&item.title, MAX_TITLE_LENGTH));
+  if let Some(link) = &item.link {
+    embed = embed.url(link);
+  }
+  if let Some(summary) = &item.summary {
+    embed = embed.description(truncate(summary, MAX_SUMMARY_LENGTH));
+  }
+  if let Some(published) = item.published {
+    embed = embed.timestamp(published);
+  }
+
+  subscription
+    .channel_id
+    .send_message(ctx, CreateMessage::new().embed(embed))
+    .await?;
+
+  Ok(())
+}
+
+/// Posts the new items in a feed. Items are marked as seen before they're posted, so each
+/// item is only posted once, even if posting fails.
+async fn check_subscription(
+  ctx: &SerenityContext,
+  db: &DatabaseHandler,
+  subscription: &FeedSubscription,
+  feed: &Feed,
+) -> Result<()> {
+  let mut transaction = db.start_transaction_with_retry(5).await?;
+  let mut new_items = Vec::new();
+  for item in &feed.items {
+    if DatabaseHandler::mark_feed_item_seen(&mut transaction, subscription, &item.key).await? {
+      new_items.push(item);
+    }
+  }
+  DatabaseHandler::commit_transaction(transaction).await?;
+
+  if new_items.len() > MAX_ITEMS_PER_CHECK {
+    info!(
+      "Skipped {} items from feed subscription {} in guild {}",
+      new_items.len() - MAX_ITEMS_PER_CHECK,
+      subscription.id,
+      subscription.guild_id
+    );
+  }
+
+  // Feeds list the newest items first, so the newest are posted, oldest first
+  for item in new_items.into_iter().take(MAX_ITEMS_PER_CHECK).rev() {
+    post_item(ctx, subscription, &feed.title, item).await?;
+  }
+
+  Ok(())
+}
+
+async fn check_feeds(
+  ctx: &SerenityContext,
+  db: &DatabaseHandler,
+  feeds: &FeedHandler,
+) -> Result<()> {
+  let mut transaction = db.start_transaction_with_retry(5).await?;
+  let subscriptions = DatabaseHandler::get_all_feed_subscriptions(&mut transaction).await?;
+  drop(transaction);
+
+  // Feeds followed in more than one channel are only downloaded once
+  let mut fetched: HashMap<String, Option<Feed>> = HashMap::new();
+  for subscription in subscriptions {
+    if !fetched.contains_key(&subscription.url) {
+      let feed = match feeds.fetch(&subscription.url).await {
+        Ok(feed) => Some(feed),
+        Err(e) => {
+          warn!("Failed to fetch feed {}: {e:?}", subscription.url);
+          None
+        }
+      };
+      fetched.insert(subscription.url.clone(), feed);
+    }
+    let Some(Some(feed)) = fetched.get(&subscription.url) else {
+      continue;
+    };
+
+    if let Err(e) = check_subscription(ctx, db, &subscription, feed).await {
+      error!(
+        "Error posting feed subscription {} in guild {}: {e:?}",
+        subscription.id, subscription.guild_id
+      );
+    }
+  }
+
+  Ok(())
+}
+
+/// Periodically checks subscribed feeds, posting new items to their channels.
+pub async fn poll_periodically(
+  ctx: SerenityContext,
+  db: Arc<DatabaseHandler>,
+  feeds: Arc<FeedHandler>,
+) {
+  let mut interval = tokio::time::interval(CHECK_INTERVAL);
+
+  loop {
+    interval.tick().await;
+
+    if let Err(e) = check_feeds(&ctx, &db, &feeds).await {
+      error!("Error checking feeds: {e:?}");
+    }
+  }
+}
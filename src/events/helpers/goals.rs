@@ -0,0 +1,177 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::{Duration as ChronoDuration, Utc};
+use log::{error, info};
+use poise::serenity_prelude::{
+  Context as SerenityContext, CreateEmbed, CreateMessage, FormattedTimestamp,
+  FormattedTimestampStyle,
+};
+
+use crate::bot_config::BotConfigHandler;
+use crate::config::BloomBotEmbed;
+use crate::data::goal::{Goal, GoalPeriod};
+use crate::database::DatabaseHandler;
+
+/// How often to check goal progress.
+const CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// How long to wait between progress updates for a goal.
+const UPDATE_INTERVAL: ChronoDuration = ChronoDuration::days(1);
+
+const BAR_LENGTH: i64 = 20;
+
+/// Renders progress towards a target as a bar of [`BAR_LENGTH`] segments.
+pub fn progress_bar(progress: i64, target: i64) -> String {
+  let filled = if target > 0 {
+    (progress.max(0) * BAR_LENGTH / target).min(BAR_LENGTH)
+  } else {
+    BAR_LENGTH
+  };
+
+  #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
+  let (filled, empty) = (filled as usize, (BAR_LENGTH - filled) as usize);
+
+  format!("{}{}", "█".repeat(filled), "░".repeat(empty))
+}
+
+fn period_name(goal: &Goal) -> &'static str {
+  match goal.period {
+    GoalPeriod::Weekly => "Weekly",
+    GoalPeriod::Monthly => "Monthly",
+  }
+}
+
+/// Creates an embed showing progress towards a [`Goal`].
+pub fn progress_embed(goal: &Goal, progress: i64) -> CreateEmbed {
+  let percent = if goal.target_minutes > 0 {
+    progress * 100 / goal.target_minutes
+  } else {
+    100
+  };
+
+  BloomBotEmbed::new()
+    .title(format!(":dart: {} Goal Progress", period_name(goal)))
+    .description(format!(
+      "Together, we've meditated **{progress}** of **{}** minutes so far! ({percent}%)\n`{}`\n\nThe goal ends {}.",
+      goal.target_minutes,
+      progress_bar(progress, goal.target_minutes),
+      FormattedTimestamp::new(goal.ends_at.into(), Some(FormattedTimestampStyle::RelativeTime))
+    ))
+}
+
+fn completed_embed(goal: &Goal, progress: i64) -> CreateEmbed {
+  BloomBotEmbed::new()
+    .title(format!(":tada: {} Goal Reached! :tada:", period_name(goal)))
+    .description(format!(
+      "We did it! Together, we've meditated **{progress}** minutes, reaching our goal of **{}** minutes. Thank you to everyone who sat with us!\n`{}`",
+      goal.target_minutes,
+      progress_bar(progress, goal.target_minutes)
+    ))
+}
+
+fn ended_embed(goal: &Goal, progress: i64) -> CreateEmbed {
+  BloomBotEmbed::new()
+    .title(format!("{} Goal Ended", period_name(goal)))
+    .description(format!(
+      "Our goal has ended. Together, we meditated **{progress}** of **{}** minutes. Every minute counts, so thank you to everyone who sat with us!\n`{}`",
+      goal.target_minutes,
+      progress_bar(progress, goal.target_minutes)
+    ))
+}
+
+/// Checks progress for an active [`Goal`], celebrating when it has been reached, closing it
+/// once it has ended, and otherwise posting a progress update at most once per
+/// [`UPDATE_INTERVAL`]. Changes are only committed once the message has been posted, so
+/// anything which fails is retried on the next check.
+async fn check_goal(ctx: &SerenityContext, db: &DatabaseHandler, goal: &Goal) -> Result<()> {
+  let mut transaction = db.start_transaction_with_retry(5).await?;
+  let progress = DatabaseHandler::get_goal_progress(&mut transaction, goal).await?;
+  let now = Utc::now();
+
+  let embed = if progress >= goal.target_minutes {
+    if DatabaseHandler::complete_goal(&mut transaction, &goal.id, &now).await? == 0 {
+      return Ok(());
+    }
+    info!("Goal {} reached in guild {}", goal.id, goal.guild_id);
+    completed_embed(goal, progress)
+  } else if now >= goal.ends_at {
+    if DatabaseHandler::close_goal(&mut transaction, &goal.guild_id, &goal.id).await? == 0 {
+      return Ok(());
+    }
+    info!("Goal {} ended in guild {}", goal.id, goal.guild_id);
+    ended_embed(goal, progress)
+  } else if goal
+    .last_update_at
+    .is_none_or(|last_update_at| now - last_update_at >= UPDATE_INTERVAL)
+  {
+    DatabaseHandler::mark_goal_updated(&mut transaction, &goal.id, &now).await?;
+    progress_embed(goal, progress)
+  } else {
+    return Ok(());
+  };
+
+  goal
+    .channel_id
+    .send_message(ctx, CreateMessage::new().embed(embed))
+    .await?;
+
+  DatabaseHandler::commit_transaction(transaction).await?;
+
+  Ok(())
+}
+
+async fn check_goals(ctx: &SerenityContext, db: &DatabaseHandler) -> Result<()> {
+  let mut transaction = db.start_transaction_with_retry(5).await?;
+  let goals = DatabaseHandler::get_active_goals(&mut transaction).await?;
+  drop(transaction);
+
+  for goal in goals {
+    if let Err(e) = check_goal(ctx, db, &goal).await {
+      error!("Error checking goal {}: {e:?}", goal.id);
+    }
+  }
+
+  Ok(())
+}
+
+/// Periodically checks progress for all active guild goals, except during quiet hours.
+pub async fn check_goals_periodically(
+  ctx: SerenityContext,
+  db: Arc<DatabaseHandler>,
+  bot_config: Arc<BotConfigHandler>,
+) {
+  let mut interval = tokio::time::interval(CHECK_INTERVAL);
+
+  loop {
+    interval.tick().await;
+
+    if bot_config.get().is_quiet(Utc::now()) {
+      continue;
+    }
+
+    if let Err(e) = check_goals(&ctx, &db).await {
+      error!("Error checking goals: {e:?}");
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_progress_bar() {
+    assert_eq!(progress_bar(0, 1000), "░".repeat(20));
+    assert_eq!(
+      progress_bar(500, 1000),
+      format!("{}{}", "█".repeat(10), "░".repeat(10))
+    );
+    assert_eq!(
+      progress_bar(999, 1000),
+      format!("{}{}", "█".repeat(19), "░")
+    );
+    assert_eq!(progress_bar(2500, 1000), "█".repeat(20));
+  }
+}
@@ -0,0 +1,118 @@
+use anyhow::Result;
+use poise::serenity_prelude::{Context, CreateMessage, GuildId, Mentionable, User};
+
+use crate::config::BloomBotEmbed;
+use crate::database::DatabaseHandler;
+use crate::settings::SettingsHandler;
+
+/// Fills in the placeholders in a welcome or farewell message template. Unknown
+/// placeholders are left as they are.
+pub fn render(template: &str, user: &str, member_count: Option<u64>) -> String {
+  let member_count = match member_count {
+    Some(count) => count.to_string(),
+    None => "many".to_owned(),
+  };
+
+  template
+    .replace("{user}", user)
+    .replace("{member_count}", &member_count)
+}
+
+async fn member_count(ctx: &Context, guild_id: GuildId) -> Option<u64> {
+  if let Some(guild) = guild_id.to_guild_cached(ctx) {
+    return Some(guild.member_count);
+  }
+
+  guild_id
+    .to_partial_guild_with_counts(ctx)
+    .await
+    .ok()
+    .and_then(|guild| guild.approximate_member_count)
+}
+
+/// Posts the server's welcome message for a new member, if welcomes are turned on.
+pub async fn welcome(
+  ctx: &Context,
+  database: &DatabaseHandler,
+  settings: &SettingsHandler,
+  guild_id: GuildId,
+  user: &User,
+) -> Result<()> {
+  let settings = settings.get(database, guild_id).await?;
+
+  let (Some(channel_id), Some(template)) = (settings.greeting_channel, &settings.welcome_message)
+  else {
+    return Ok(());
+  };
+
+  let message = render(
+    template,
+    &user.mention().to_string(),
+    member_count(ctx, guild_id).await,
+  );
+
+  // Sent as plain text, so the new member is pinged
+  channel_id
+    .send_message(ctx, CreateMessage::new().content(message))
+    .await?;
+
+  Ok(())
+}
+
+/// Posts the server's farewell message for a member who left, if farewells are turned on.
+/// The member is named rather than mentioned, since mentions of members who have left
+/// often can't be resolved.
+pub async fn farewell(
+  ctx: &Context,
+  database: &DatabaseHandler,
+  settings: &SettingsHandler,
+  guild_id: GuildId,
+  user: &User,
+) -> Result<()> {
+  let settings = settings.get(database, guild_id).await?;
+
+  let (Some(channel_id), Some(template)) = (settings.greeting_channel, &settings.farewell_message)
+  else {
+    return Ok(());
+  };
+
+  let message = render(template, &user.name, member_count(ctx, guild_id).await);
+
+  channel_id
+    .send_message(
+      ctx,
+      CreateMessage::new().embed(
+        BloomBotEmbed::new()
+          .title("Member Left")
+          .description(message),
+      ),
+    )
+    .await?;
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_render() {
+    assert_eq!(
+      render(
+        "Welcome, {user}! You are member #{member_count}.",
+        "<@123>",
+        Some(42)
+      ),
+      "Welcome, <@123>! You are member #42."
+    );
+    assert_eq!(
+      render(
+        "{user} joined {member_count} others {unknown}",
+        "Alex",
+        None
+      ),
+      "Alex joined many others {unknown}"
+    );
+  }
+}
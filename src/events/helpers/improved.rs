@@ -0,0 +1,237 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::{DateTime, Datelike, Duration as ChronoDuration, Months, NaiveDate, Utc};
+use log::{error, info};
+use poise::serenity_prelude::{
+  Context as SerenityContext, CreateEmbed, CreateMessage, Mentionable,
+};
+use poise::ChoiceParameter;
+
+use crate::bot_config::BotConfigHandler;
+use crate::commands::helpers::announcements;
+use crate::config::BloomBotEmbed;
+use crate::data::guild_settings::GuildSettings;
+use crate::data::stats::Improvement;
+use crate::database::DatabaseHandler;
+
+/// How often to check whether the monthly shout-outs are due.
+const CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// The number of members named in the monthly shout-out.
+const SHOUTOUT_LIMIT: i64 = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ChoiceParameter)]
+pub enum Comparison {
+  #[name = "week over week"]
+  Weekly,
+  #[name = "month over month"]
+  Monthly,
+}
+
+impl Comparison {
+  /// Returns the start of the previous timeframe, the start of the current timeframe, and
+  /// the end of the current timeframe, ending at `now`. Weeks are the last 7 days and months
+  /// the last 30 days, so that partial weeks and months don't skew the comparison.
+  pub fn bounds(self, now: DateTime<Utc>) -> (DateTime<Utc>, DateTime<Utc>, DateTime<Utc>) {
+    let length = match self {
+      Self::Weekly => ChronoDuration::days(7),
+      Self::Monthly => ChronoDuration::days(30),
+    };
+
+    (now - length - length, now - length, now)
+  }
+
+  fn description(self) -> &'static str {
+    match self {
+      Self::Weekly => "the last 7 days compared to the 7 days before",
+      Self::Monthly => "the last 30 days compared to the 30 days before",
+    }
+  }
+}
+
+/// Returns the first day of the month before last, the first day of last month, and the
+/// first day of this month, as of `now`. Used to compare last month to the month before it.
+pub fn last_month_bounds(now: DateTime<Utc>) -> Option<(NaiveDate, NaiveDate, NaiveDate)> {
+  let today = now.date_naive();
+  let this_month = NaiveDate::from_ymd_opt(today.year(), today.month(), 1)?;
+  let last_month = this_month.checked_sub_months(Months::new(1))?;
+  let month_before = this_month.checked_sub_months(Months::new(2))?;
+
+  Some((month_before, last_month, this_month))
+}
+
+fn improvement_list(improvements: &[Improvement]) -> String {
+  improvements
+    .iter()
+    .enumerate()
+    .map(|(i, improvement)| {
+      let name = if improvement.anonymous_tracking {
+        "Anonymous".to_owned()
+      } else {
+        improvement.user_id.mention().to_string()
+      };
+      format!(
+        "{}. {name}: **+{}** minutes ({} → {})",
+        i + 1,
+        improvement.increase(),
+        improvement.previous_minutes,
+        improvement.current_minutes
+      )
+    })
+    .collect::<Vec<_>>()
+    .join("\n")
+}
+
+/// Creates an embed listing the members who improved the most for a [`Comparison`].
+pub fn improved_embed(comparison: Comparison, improvements: &[Improvement]) -> CreateEmbed {
+  let description = if improvements.is_empty() {
+    "No one has increased their meditation time yet. Every minute counts!".to_owned()
+  } else {
+    improvement_list(improvements)
+  };
+
+  BloomBotEmbed::new()
+    .title(":chart_with_upwards_trend: Most Improved")
+    .description(format!(
+      "Minutes meditated in {}:\n\n{description}",
+      comparison.description()
+    ))
+}
+
+fn shoutout_embed(month: NaiveDate, improvements: &[Improvement]) -> CreateEmbed {
+  BloomBotEmbed::new()
+    .title(format!(
+      ":seedling: Most Improved for {}",
+      month.format("%B %Y")
+    ))
+    .description(format!(
+      "A big shout-out to the members who grew their practice the most last month!\n\n{}",
+      improvement_list(improvements)
+    ))
+}
+
+/// Posts the "most improved" shout-out for last month in a guild. The shout-out is marked as
+/// posted even when no one improved, so that it's only checked once per month, but is only
+/// committed once the message has been posted, so a failed post is retried on the next check.
+async fn post_shoutout(
+  ctx: &SerenityContext,
+  db: &DatabaseHandler,
+  settings: &GuildSettings,
+  (month_before, last_month, this_month): (NaiveDate, NaiveDate, NaiveDate),
+) -> Result<()> {
+  let Some(channel_id) = settings.improved_channel else {
+    return Ok(());
+  };
+  let (Some(previous_start), Some(current_start), Some(end)) = (
+    month_before.and_hms_opt(0, 0, 0),
+    last_month.and_hms_opt(0, 0, 0),
+    this_month.and_hms_opt(0, 0, 0),
+  ) else {
+    return Ok(());
+  };
+
+  let mut transaction = db.start_transaction_with_retry(5).await?;
+  let improvements = DatabaseHandler::get_most_improved(
+    &mut transaction,
+    &settings.guild_id,
+    &previous_start.and_utc(),
+    &current_start.and_utc(),
+    &end.and_utc(),
+    SHOUTOUT_LIMIT,
+  )
+  .await?;
+
+  DatabaseHandler::mark_improved_posted(&mut transaction, &settings.guild_id, this_month).await?;
+
+  if !improvements.is_empty() {
+    let announcement = channel_id
+      .send_message(
+        ctx,
+        CreateMessage::new().embed(shoutout_embed(last_month, &improvements)),
+      )
+      .await?;
+    announcements::publish(ctx, db, settings.guild_id, &announcement).await;
+    info!(
+      "Posted most improved shout-out in guild {}",
+      settings.guild_id
+    );
+  }
+
+  DatabaseHandler::commit_transaction(transaction).await?;
+
+  Ok(())
+}
+
+async fn check_shoutouts(ctx: &SerenityContext, db: &DatabaseHandler) -> Result<()> {
+  let Some(bounds) = last_month_bounds(Utc::now()) else {
+    return Ok(());
+  };
+
+  let mut transaction = db.start_transaction_with_retry(5).await?;
+  let due = DatabaseHandler::get_guilds_improved_due(&mut transaction, bounds.2).await?;
+  drop(transaction);
+
+  for settings in due {
+    if let Err(e) = post_shoutout(ctx, db, &settings, bounds).await {
+      error!(
+        "Error posting most improved shout-out for guild {}: {e:?}",
+        settings.guild_id
+      );
+    }
+  }
+
+  Ok(())
+}
+
+/// Periodically posts the monthly "most improved" shout-out in guilds which have turned it on.
+/// Shout-outs which are due during quiet hours are posted once the quiet hours end.
+pub async fn post_shoutouts_periodically(
+  ctx: SerenityContext,
+  db: Arc<DatabaseHandler>,
+  bot_config: Arc<BotConfigHandler>,
+) {
+  let mut interval = tokio::time::interval(CHECK_INTERVAL);
+
+  loop {
+    interval.tick().await;
+
+    if bot_config.get().is_quiet(Utc::now()) {
+      continue;
+    }
+
+    if let Err(e) = check_shoutouts(&ctx, &db).await {
+      error!("Error checking most improved shout-outs: {e:?}");
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_last_month_bounds() {
+    let date = |year, month, day| NaiveDate::from_ymd_opt(year, month, day).unwrap_or_default();
+    let now = |year, month, day| {
+      date(year, month, day)
+        .and_hms_opt(15, 0, 0)
+        .unwrap_or_default()
+        .and_utc()
+    };
+
+    assert_eq!(
+      last_month_bounds(now(2024, 10, 23)),
+      Some((date(2024, 8, 1), date(2024, 9, 1), date(2024, 10, 1)))
+    );
+    assert_eq!(
+      last_month_bounds(now(2025, 1, 1)),
+      Some((date(2024, 11, 1), date(2024, 12, 1), date(2025, 1, 1)))
+    );
+    assert_eq!(
+      last_month_bounds(now(2024, 3, 31)),
+      Some((date(2024, 1, 1), date(2024, 2, 1), date(2024, 3, 1)))
+    );
+  }
+}
@@ -0,0 +1,316 @@
+use std::collections::{HashMap, HashSet};
+use std::pin::pin;
+
+use anyhow::Result;
+use futures::TryStreamExt;
+use poise::serenity_prelude::{CreateEmbed, GuildId, Http, Mentionable, Timestamp, UserId};
+use poise::ChoiceParameter;
+
+use crate::commands::helpers::time::Timeframe;
+use crate::config::{BloomBotEmbed, MEDITATION_MIND};
+use crate::data::stats::LeaderboardUser;
+use crate::database::DatabaseHandler;
+use crate::events::role_sync::{self, Summary};
+use crate::roles::RoleQueueHandler;
+
+/// The most users listed for each kind of drift in the report embed.
+const MAX_LISTED_USERS: usize = 10;
+
+/// Stored stats which no longer match the meditation entries they're derived from.
+#[derive(Debug, Default)]
+pub struct Report {
+  pub members: usize,
+  pub checked_streaks: usize,
+  /// Users whose stored current or longest streak differs from their entries.
+  pub stale_streaks: Vec<UserId>,
+  /// Users with a stored streak but no meditation entries.
+  pub orphaned_streaks: Vec<UserId>,
+  /// Users with a stored streak who are no longer in the server.
+  pub departed_streaks: Vec<UserId>,
+  /// The number of leaderboard rows which differ from the entries, for each timeframe.
+  pub stale_leaderboards: Vec<(&'static str, usize)>,
+  /// The number of members whose time or streak roles are wrong. Only checked in the main
+  /// server, where the roles are.
+  pub stale_roles: Option<usize>,
+  /// The outcome of fixing roles, if drift was fixed.
+  pub role_fixes: Option<Summary>,
+  pub fixed: bool,
+}
+
+impl Report {
+  pub fn has_drift(&self) -> bool {
+    !self.stale_streaks.is_empty()
+      || !self.orphaned_streaks.is_empty()
+      || !self.departed_streaks.is_empty()
+      || self.stale_leaderboards.iter().any(|(_, stale)| *stale > 0)
+      || self.stale_roles.is_some_and(|stale| stale > 0)
+  }
+
+  fn user_list(label: &str, users: &[UserId]) -> String {
+    let mut line = format!("{label}: {}", users.len());
+    if !users.is_empty() {
+      let listed = users
+        .iter()
+        .take(MAX_LISTED_USERS)
+        .map(|user_id| user_id.mention().to_string())
+        .collect::<Vec<_>>()
+        .join(" ");
+      line.push_str(&format!(" ({listed}"));
+      if users.len() > MAX_LISTED_USERS {
+        line.push_str(&format!(" and {} more", users.len() - MAX_LISTED_USERS));
+      }
+      line.push(')');
+    }
+
+    line
+  }
+
+  pub fn embed(&self) -> CreateEmbed {
+    let description = match (self.has_drift(), self.fixed) {
+      (false, _) => "No drift found.",
+      (true, false) => "Drift found. Run again with `fix` enabled to fix it.",
+      (true, true) => "Drift found and fixed.",
+    };
+
+    let streaks = [
+      format!("Checked: {}", self.checked_streaks),
+      Self::user_list("Stale", &self.stale_streaks),
+      Self::user_list("Without entries", &self.orphaned_streaks),
+      Self::user_list("Departed users", &self.departed_streaks),
+    ]
+    .join("\n");
+
+    let leaderboards = self
+      .stale_leaderboards
+      .iter()
+      .map(|(timeframe, stale)| format!("{timeframe}: {stale} stale rows"))
+      .collect::<Vec<_>>()
+      .join("\n");
+
+    let roles = match (self.stale_roles, &self.role_fixes) {
+      (None, _) => "Not checked outside the main server".to_owned(),
+      (Some(stale), None) => format!("{stale} of {} members need changes", self.members),
+      (Some(stale), Some(summary)) => format!(
+        "{stale} of {} members needed changes: {} roles added, {} removed, {} failed",
+        self.members, summary.added, summary.removed, summary.failed
+      ),
+    };
+
+    BloomBotEmbed::new()
+      .title("Integrity Check")
+      .description(description)
+      .field("Streaks", streaks, false)
+      .field("Leaderboards", leaderboards, false)
+      .field("Roles", roles, false)
+      .timestamp(Timestamp::now())
+  }
+}
+
+/// Counts the leaderboard rows which don't match the stats calculated from the entries,
+/// including rows for members who shouldn't be on the leaderboard and members missing from it.
+fn leaderboard_drift(shown: &[LeaderboardUser], expected: &[LeaderboardUser]) -> usize {
+  let stats = |users: &[LeaderboardUser]| -> HashMap<_, _> {
+    users
+      .iter()
+      .map(|user| {
+        (
+          user.name.clone(),
+          (user.minutes, user.sessions, user.streak),
+        )
+      })
+      .collect()
+  };
+  let shown = stats(shown);
+  let expected = stats(expected);
+
+  let changed = expected
+    .iter()
+    .filter(|(name, stats)| shown.get(*name) != Some(*stats))
+    .count();
+  let extra = shown
+    .keys()
+    .filter(|name| !expected.contains_key(*name))
+    .count();
+
+  changed + extra
+}
+
+/// Compares stored streaks with streaks recalculated from every entry. Streaks of users who
+/// have no entries, or have left the server, are counted separately, since they're never
+/// recalculated. When fixing, streaks are saved as recalculated and the others are deleted.
+/// Otherwise, the recalculated streaks are discarded.
+async fn check_streaks(
+  db: &DatabaseHandler,
+  guild_id: GuildId,
+  members: &HashSet<UserId>,
+  report: &mut Report,
+  fix: bool,
+) -> Result<()> {
+  let mut transaction = db.start_transaction_with_retry(5).await?;
+  let stored: HashMap<_, _> = DatabaseHandler::get_guild_streaks(&mut transaction, &guild_id)
+    .await?
+    .into_iter()
+    .map(|streak| (streak.user_id(), (streak.current, streak.longest)))
+    .collect();
+  let meditating: HashSet<_> = DatabaseHandler::get_meditating_users(&mut transaction, &guild_id)
+    .await?
+    .into_iter()
+    .collect();
+
+  for user_id in stored.keys() {
+    if !meditating.contains(user_id) {
+      report.orphaned_streaks.push(*user_id);
+    } else if !members.contains(user_id) {
+      report.departed_streaks.push(*user_id);
+    }
+  }
+
+  for user_id in meditating
+    .iter()
+    .filter(|user_id| members.contains(user_id))
+  {
+    let streak = DatabaseHandler::recalculate_streak(&mut transaction, &guild_id, user_id).await?;
+    report.checked_streaks += 1;
+    if stored.get(user_id).copied().unwrap_or_default() != (streak.current, streak.longest) {
+      report.stale_streaks.push(*user_id);
+    }
+  }
+
+  if fix {
+    for user_id in report
+      .orphaned_streaks
+      .iter()
+      .chain(&report.departed_streaks)
+    {
+      DatabaseHandler::delete_streak(&mut transaction, &guild_id, user_id).await?;
+    }
+    DatabaseHandler::commit_transaction(transaction).await?;
+  }
+
+  Ok(())
+}
+
+/// Compares each leaderboard with the stats calculated from the entries, refreshing any which
+/// differ when fixing.
+async fn check_leaderboards(
+  db: &DatabaseHandler,
+  guild_id: GuildId,
+  report: &mut Report,
+  fix: bool,
+) -> Result<()> {
+  for timeframe in [
+    Timeframe::Daily,
+    Timeframe::Weekly,
+    Timeframe::Monthly,
+    Timeframe::Yearly,
+  ] {
+    let mut transaction = db.start_transaction_with_retry(5).await?;
+    let shown =
+      DatabaseHandler::get_leaderboard_users(&mut transaction, &guild_id, &timeframe).await?;
+    let expected =
+      DatabaseHandler::calculate_leaderboard_users(&mut transaction, &guild_id, &timeframe).await?;
+
+    let stale = leaderboard_drift(&shown, &expected);
+    if fix && stale > 0 {
+      DatabaseHandler::refresh_leaderboard(&mut transaction, &timeframe).await?;
+      DatabaseHandler::commit_transaction(transaction).await?;
+    }
+
+    report.stale_leaderboards.push((timeframe.name(), stale));
+  }
+
+  Ok(())
+}
+
+/// Cross-checks a server's stored streaks, leaderboards, and time and streak roles against
+/// its meditation entries. With `fix`, stale streaks are recalculated, streaks of users
+/// without entries or who have left are deleted, stale leaderboards are refreshed, and roles
+/// are reconciled.
+///
+/// # Errors
+/// Returns an error if the server's members or stats can't be retrieved, or drift can't be
+/// fixed.
+pub async fn run(
+  http: &Http,
+  db: &DatabaseHandler,
+  role_queue: &RoleQueueHandler,
+  guild_id: GuildId,
+  fix: bool,
+) -> Result<Report> {
+  let mut report = Report {
+    fixed: fix,
+    ..Default::default()
+  };
+
+  let check_roles = guild_id == MEDITATION_MIND;
+  let progress = if check_roles {
+    role_sync::member_progress(db, guild_id).await?
+  } else {
+    HashMap::new()
+  };
+
+  let mut member_ids = HashSet::new();
+  let mut members_with_roles = Vec::new();
+  let mut members = pin!(guild_id.members_iter(http));
+  while let Some(member) = members.try_next().await? {
+    if member.user.bot {
+      continue;
+    }
+    member_ids.insert(member.user.id);
+    if check_roles {
+      members_with_roles.push(member);
+    }
+  }
+  report.members = member_ids.len();
+
+  // Streaks are checked before roles, since checking roles saves members' current streaks
+  check_streaks(db, guild_id, &member_ids, &mut report, fix).await?;
+  check_leaderboards(db, guild_id, &mut report, fix).await?;
+
+  if check_roles {
+    let mut stale = 0;
+    for member in &members_with_roles {
+      let update =
+        role_sync::member_role_changes(db, member, progress.get(&member.user.id)).await?;
+      if !update.is_empty() {
+        stale += 1;
+      }
+    }
+    report.stale_roles = Some(stale);
+
+    if fix && stale > 0 {
+      report.role_fixes = Some(role_sync::reconcile(http, db, role_queue, guild_id).await?);
+    }
+  }
+
+  Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn user(name: &str, minutes: i64, sessions: i64, streak: Option<i32>) -> LeaderboardUser {
+    LeaderboardUser {
+      name: Some(name.to_owned()),
+      minutes: Some(minutes),
+      sessions: Some(sessions),
+      streak,
+      ..Default::default()
+    }
+  }
+
+  #[test]
+  fn test_leaderboard_drift() {
+    let expected = [user("123", 30, 2, Some(2)), user("124", 10, 1, None)];
+
+    let shown = [user("123", 30, 2, Some(2)), user("124", 10, 1, None)];
+    assert_eq!(leaderboard_drift(&shown, &expected), 0);
+
+    // Changed stats, a missing member, and a member who shouldn't be shown are all drift
+    let shown = [user("123", 20, 1, Some(2)), user("125", 5, 1, None)];
+    assert_eq!(leaderboard_drift(&shown, &expected), 3);
+
+    assert_eq!(leaderboard_drift(&[], &[]), 0);
+  }
+}
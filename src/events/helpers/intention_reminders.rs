@@ -0,0 +1,88 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::Utc;
+use log::{error, info};
+use poise::serenity_prelude::{Context as SerenityContext, CreateMessage};
+
+use crate::config::BloomBotEmbed;
+use crate::data::intention::{IntentionReminder, PracticePrompt};
+use crate::database::DatabaseHandler;
+
+/// How often to check for members who are due the day's intention.
+const CHECK_INTERVAL: Duration = Duration::from_secs(60 * 10);
+
+/// Sends the day's intention by DM. The reminder is marked as sent even if the DM fails, or
+/// if the server has no practice prompts, so that it isn't retried every check.
+async fn send_reminder(
+  ctx: &SerenityContext,
+  db: &DatabaseHandler,
+  reminder: &IntentionReminder,
+) -> Result<()> {
+  let mut transaction = db.start_transaction_with_retry(5).await?;
+  DatabaseHandler::mark_intention_reminder_sent(&mut transaction, reminder).await?;
+  let prompts =
+    DatabaseHandler::get_practice_prompts(&mut transaction, &reminder.guild_id, None).await?;
+  DatabaseHandler::commit_transaction(transaction).await?;
+
+  let Some(intention) = PracticePrompt::for_day(&prompts, reminder.local_date) else {
+    return Ok(());
+  };
+
+  let server = reminder
+    .guild_id
+    .name(ctx)
+    .unwrap_or_else(|| "the server".to_owned());
+
+  let embed = BloomBotEmbed::new()
+    .title(":sunrise: Today's Intention")
+    .description(format!(
+      "Good morning! Here's today's intention from {server}:\n\n> {}\n\n-# You can change the time or turn these off with `/intention remind`.",
+      intention.prompt
+    ));
+
+  if let Err(e) = reminder
+    .user_id
+    .direct_message(ctx, CreateMessage::new().embed(embed))
+    .await
+  {
+    info!(
+      "Failed to send intention reminder to user {}: {e}",
+      reminder.user_id
+    );
+  }
+
+  Ok(())
+}
+
+async fn check_reminders(ctx: &SerenityContext, db: &DatabaseHandler) -> Result<()> {
+  let mut transaction = db.start_transaction_with_retry(5).await?;
+  let reminders = DatabaseHandler::get_intention_reminders(&mut transaction, &Utc::now()).await?;
+  drop(transaction);
+
+  for reminder in reminders {
+    if let Err(e) = send_reminder(ctx, db, &reminder).await {
+      error!(
+        "Error sending intention reminder to user {}: {e:?}",
+        reminder.user_id
+      );
+    }
+  }
+
+  Ok(())
+}
+
+/// Periodically sends the day's intention to members who have asked to be prompted each
+/// morning.
+pub async fn remind_periodically(ctx: SerenityContext, db: Arc<DatabaseHandler>) {
+  let mut interval = tokio::time::interval(CHECK_INTERVAL);
+
+  loop {
+    interval.tick().await;
+
+    if let Err(e) = check_reminders(&ctx, &db).await {
+      error!("Error checking intention reminders: {e:?}");
+    }
+  }
+}
@@ -8,8 +8,7 @@ use tokio::time;
 
 use crate::charts::Chart;
 use crate::commands::helpers::time::Timeframe;
-use crate::commands::stats::{LeaderboardType, SortBy};
-use crate::data::stats::LeaderboardUser;
+use crate::data::stats::{LeaderboardType, LeaderboardUser, SortBy};
 use crate::database::DatabaseHandler;
 
 #[allow(dead_code)]
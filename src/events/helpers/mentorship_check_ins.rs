@@ -0,0 +1,83 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::Utc;
+use log::{error, info};
+use poise::serenity_prelude::{Context as SerenityContext, CreateMessage};
+
+use crate::commands::helpers::mentorship::CHECK_IN_DAYS;
+use crate::config::BloomBotEmbed;
+use crate::data::mentorship::Mentorship;
+use crate::database::DatabaseHandler;
+
+/// How often to check for mentorships which are due a check-in reminder.
+const CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Reminds both members of a mentorship to check in, by DM. The reminder is marked as sent
+/// even if the DMs fail, so that it isn't retried every check.
+async fn send_reminder(
+  ctx: &SerenityContext,
+  db: &DatabaseHandler,
+  mentorship: &Mentorship,
+) -> Result<()> {
+  let mut transaction = db.start_transaction_with_retry(5).await?;
+  DatabaseHandler::mark_mentorship_reminded(&mut transaction, &mentorship.id, &Utc::now()).await?;
+  DatabaseHandler::commit_transaction(transaction).await?;
+
+  let server = mentorship
+    .guild_id
+    .name(ctx)
+    .unwrap_or_else(|| "the server".to_owned());
+
+  let embed = BloomBotEmbed::new()
+    .title(":handshake: Mentorship Check-In")
+    .description(format!(
+      "It's been a while since <@{}> and <@{}> checked in through the {server} mentorship program. If you've met recently, let us know with `/mentor checkin`. If not, now might be a nice time to reach out!\n\n-# If the mentorship has run its course, let a moderator know and they can end it.",
+      mentorship.mentor_id, mentorship.mentee_id
+    ));
+
+  for user_id in [mentorship.mentor_id, mentorship.mentee_id] {
+    if let Err(e) = user_id
+      .direct_message(ctx, CreateMessage::new().embed(embed.clone()))
+      .await
+    {
+      info!("Failed to send mentorship check-in reminder to user {user_id}: {e}");
+    }
+  }
+
+  Ok(())
+}
+
+async fn check_mentorships(ctx: &SerenityContext, db: &DatabaseHandler) -> Result<()> {
+  let mut transaction = db.start_transaction_with_retry(5).await?;
+  let mentorships =
+    DatabaseHandler::get_due_mentorship_check_ins(&mut transaction, &Utc::now(), CHECK_IN_DAYS)
+      .await?;
+  drop(transaction);
+
+  for mentorship in mentorships {
+    if let Err(e) = send_reminder(ctx, db, &mentorship).await {
+      error!(
+        "Error sending check-in reminder for mentorship {}: {e:?}",
+        mentorship.id
+      );
+    }
+  }
+
+  Ok(())
+}
+
+/// Periodically reminds members of active mentorships to check in when they haven't in a
+/// while.
+pub async fn remind_periodically(ctx: SerenityContext, db: Arc<DatabaseHandler>) {
+  let mut interval = tokio::time::interval(CHECK_INTERVAL);
+
+  loop {
+    interval.tick().await;
+
+    if let Err(e) = check_mentorships(&ctx, &db).await {
+      error!("Error checking mentorship check-ins: {e:?}");
+    }
+  }
+}
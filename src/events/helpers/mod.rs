@@ -1,3 +1,19 @@
 pub mod chart_stats;
+pub mod event_attendance;
+pub mod feeds;
+pub mod goals;
+pub mod greetings;
+pub mod improved;
+pub mod integrity;
+pub mod intention_reminders;
 pub mod leaderboards;
+pub mod mentorship_check_ins;
+pub mod pending_interactions;
+pub mod recurring_posts;
+pub mod retention;
+pub mod role_sync;
+pub mod selfcheck;
 pub mod starboard;
+pub mod streak_guard;
+pub mod tickets;
+pub mod watchlist;
@@ -0,0 +1,31 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use log::{error, info};
+
+use crate::database::DatabaseHandler;
+
+/// How often to remove expired pending interactions.
+const CLEANUP_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+async fn remove_expired(db: &DatabaseHandler) -> Result<u64> {
+  let mut transaction = db.start_transaction_with_retry(5).await?;
+  let removed = DatabaseHandler::remove_expired_pending_interactions(&mut transaction).await?;
+  DatabaseHandler::commit_transaction(transaction).await?;
+
+  Ok(removed)
+}
+
+/// Periodically removes paginators and confirmations whose buttons have expired.
+pub async fn remove_expired_periodically(db: Arc<DatabaseHandler>) {
+  loop {
+    match remove_expired(&db).await {
+      Ok(0) => {}
+      Ok(removed) => info!("Removed {removed} expired pending interactions"),
+      Err(e) => error!("Error removing expired pending interactions: {e:?}"),
+    }
+
+    tokio::time::sleep(CLEANUP_INTERVAL).await;
+  }
+}
@@ -0,0 +1,128 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use log::{error, info, warn};
+use poise::serenity_prelude::{
+  AutoArchiveDuration, ChannelType, Context as SerenityContext, CreateMessage, CreateThread,
+};
+
+use crate::commands::helpers::announcements;
+use crate::data::intention::PracticePrompt;
+use crate::data::recurring_post::RecurringPost;
+use crate::database::DatabaseHandler;
+
+/// How often to check for recurring posts which are due.
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Posts which are more than this late, such as when Bloom was offline, are skipped rather
+/// than posted at an unexpected time.
+const MAX_LATENESS: ChronoDuration = ChronoDuration::hours(1);
+
+/// The longest thread name Discord allows.
+const MAX_THREAD_NAME: usize = 100;
+
+/// Makes a recurring post which is due, then moves it on to its next time. The post is moved
+/// on first, so it's never made twice, even if posting fails.
+async fn post(
+  ctx: &SerenityContext,
+  db: &DatabaseHandler,
+  post: &RecurringPost,
+  now: DateTime<Utc>,
+) -> Result<()> {
+  let next_post_at = post.next_after(now);
+  let mut transaction = db.start_transaction_with_retry(5).await?;
+  if DatabaseHandler::advance_recurring_post(
+    &mut transaction,
+    &post.id,
+    &post.next_post_at,
+    &next_post_at,
+  )
+  .await?
+    == 0
+  {
+    return Ok(());
+  }
+  DatabaseHandler::commit_transaction(transaction).await?;
+
+  if now - post.next_post_at > MAX_LATENESS {
+    warn!(
+      "Skipped recurring post {} in guild {}, which was due at {}",
+      post.id, post.guild_id, post.next_post_at
+    );
+    return Ok(());
+  }
+
+  let date = post.local_date(post.next_post_at);
+  let mut content = RecurringPost::render(&post.message, date);
+  if content.contains("{intention}") {
+    let mut transaction = db.start_transaction_with_retry(5).await?;
+    let prompts =
+      DatabaseHandler::get_practice_prompts(&mut transaction, &post.guild_id, None).await?;
+    drop(transaction);
+
+    let Some(intention) = PracticePrompt::for_day(&prompts, date) else {
+      warn!(
+        "Skipped recurring post {} in guild {}, which has no practice prompts",
+        post.id, post.guild_id
+      );
+      return Ok(());
+    };
+    content = intention.render(&content);
+  }
+
+  let message = post
+    .channel_id
+    .send_message(ctx, CreateMessage::new().content(content))
+    .await?;
+  info!("Made recurring post {} in guild {}", post.id, post.guild_id);
+  announcements::publish(ctx, db, post.guild_id, &message).await;
+
+  if let Some(thread_name) = &post.thread_name {
+    let thread_name = RecurringPost::render(thread_name, date)
+      .chars()
+      .take(MAX_THREAD_NAME)
+      .collect::<String>();
+    post
+      .channel_id
+      .create_thread_from_message(
+        ctx,
+        message.id,
+        CreateThread::new(thread_name)
+          .kind(ChannelType::PublicThread)
+          .auto_archive_duration(AutoArchiveDuration::OneWeek),
+      )
+      .await?;
+  }
+
+  Ok(())
+}
+
+async fn post_due(ctx: &SerenityContext, db: &DatabaseHandler) -> Result<()> {
+  let now = Utc::now();
+  let mut transaction = db.start_transaction_with_retry(5).await?;
+  let posts = DatabaseHandler::get_due_recurring_posts(&mut transaction, &now).await?;
+  drop(transaction);
+
+  for recurring_post in posts {
+    if let Err(e) = post(ctx, db, &recurring_post, now).await {
+      error!("Error making recurring post {}: {e:?}", recurring_post.id);
+    }
+  }
+
+  Ok(())
+}
+
+/// Periodically makes recurring posts which are due.
+pub async fn post_periodically(ctx: SerenityContext, db: Arc<DatabaseHandler>) {
+  let mut interval = tokio::time::interval(CHECK_INTERVAL);
+
+  loop {
+    interval.tick().await;
+
+    if let Err(e) = post_due(&ctx, &db).await {
+      error!("Error making recurring posts: {e:?}");
+    }
+  }
+}
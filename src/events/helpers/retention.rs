@@ -0,0 +1,280 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::{Duration as ChronoDuration, Utc};
+use log::{error, info, warn};
+use poise::serenity_prelude::{
+  ChannelId, Context as SerenityContext, CreateMessage, Error as SerenityError, GuildId, Http,
+  HttpError,
+};
+use tokio::time::Instant;
+
+use crate::config::{BloomBotEmbed, CHANNELS};
+use crate::data::retention::{RetentionPolicy, RetentionSummary};
+use crate::database::DatabaseHandler;
+
+/// How often each guild's retention policy is applied.
+const RETENTION_INTERVAL: ChronoDuration = ChronoDuration::days(1);
+
+/// How often to check for retention policies which are due. Policies are checked far more
+/// often than they're applied, so that a policy is still applied daily when the bot restarts
+/// more often than that.
+const CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// The most starred messages checked for deletion in each guild per run, since each check is
+/// a request to Discord. Messages are checked least recently checked first, so every message
+/// is eventually checked.
+const STAR_CHECKS_PER_RUN: i64 = 100;
+
+/// What applying a guild's [`RetentionPolicy`] removed, or would remove in a dry run.
+#[derive(Debug, Default)]
+pub struct Report {
+  pub summary: RetentionSummary,
+  /// Starboard posts whose starred message has been deleted.
+  pub star_messages: u64,
+}
+
+impl Report {
+  pub fn is_empty(&self) -> bool {
+    self.summary == RetentionSummary::default() && self.star_messages == 0
+  }
+
+  /// Describes the report, in the past tense unless it's for a dry run.
+  pub fn describe(&self, policy: &RetentionPolicy) -> String {
+    let (removed, archived) = if policy.dry_run {
+      ("would be removed", "would be archived")
+    } else {
+      ("removed", "archived")
+    };
+
+    let mut lines = Vec::new();
+    if let Some(days) = policy.erase_days {
+      lines.push(format!(
+        "**Erase records older than {days} days:** {} {removed}",
+        self.summary.erases
+      ));
+    }
+    if policy.prune_star_messages {
+      lines.push(format!(
+        "**Starboard posts for deleted messages:** {} {removed}",
+        self.star_messages
+      ));
+    }
+    if let Some(years) = policy.archive_years {
+      lines.push(format!(
+        "**Meditation entries older than {years} years:** {} {archived}",
+        self.summary.meditations
+      ));
+    }
+
+    lines.join("\n")
+  }
+}
+
+/// Whether a request failed because the message, or its channel, no longer exists.
+fn is_not_found(error: &SerenityError) -> bool {
+  matches!(
+    error,
+    SerenityError::Http(HttpError::UnsuccessfulRequest(response))
+      if response.status_code.as_u16() == 404
+  )
+}
+
+/// Checks whether a batch of a guild's starred messages still exist, removing the
+/// starboard posts for any which have been deleted, unless `dry_run` is set. Returns the
+/// number of deleted messages found.
+async fn prune_star_messages(
+  http: &Http,
+  db: &DatabaseHandler,
+  guild_id: GuildId,
+  dry_run: bool,
+) -> Result<u64> {
+  let mut transaction = db.start_transaction_with_retry(5).await?;
+  let star_messages =
+    DatabaseHandler::get_star_messages_to_check(&mut transaction, &guild_id, STAR_CHECKS_PER_RUN)
+      .await?;
+
+  let mut checked = Vec::new();
+  let mut deleted = 0;
+  for star_message in star_messages {
+    match star_message
+      .starred_channel
+      .message(http, star_message.starred_message)
+      .await
+    {
+      Ok(_) => checked.push(star_message.id),
+      Err(e) if is_not_found(&e) => {
+        deleted += 1;
+        if dry_run {
+          continue;
+        }
+
+        // Staff may have already removed the post, so failing to delete it isn't an error
+        if let Err(e) = ChannelId::new(CHANNELS.starchannel)
+          .delete_message(http, star_message.board_message)
+          .await
+        {
+          warn!(
+            "Failed to delete starboard message {} for deleted message {}: {e}",
+            star_message.board_message, star_message.starred_message
+          );
+        }
+        DatabaseHandler::remove_star_message(&mut transaction, &guild_id, &star_message.id).await?;
+      }
+      // Left unchecked, so it's retried on the next run
+      Err(e) => warn!(
+        "Failed to check starred message {}: {e}",
+        star_message.starred_message
+      ),
+    }
+  }
+
+  // Dry runs don't mark messages as checked, so they're checked again once the policy is
+  // applied for real
+  if !dry_run {
+    DatabaseHandler::mark_star_messages_checked(&mut transaction, &checked).await?;
+  }
+  DatabaseHandler::commit_transaction(transaction).await?;
+
+  Ok(deleted)
+}
+
+/// Works out what a guild's [`RetentionPolicy`] would remove, without removing anything.
+///
+/// # Errors
+/// Returns an error if the counts or starred messages can't be retrieved.
+pub async fn preview(
+  http: &Http,
+  db: &DatabaseHandler,
+  policy: &RetentionPolicy,
+) -> Result<Report> {
+  let mut transaction = db.start_transaction_with_retry(5).await?;
+  let summary = DatabaseHandler::preview_retention(&mut transaction, policy, Utc::now()).await?;
+  drop(transaction);
+
+  let star_messages = if policy.prune_star_messages {
+    prune_star_messages(http, db, policy.guild_id, true).await?
+  } else {
+    0
+  };
+
+  Ok(Report {
+    summary,
+    star_messages,
+  })
+}
+
+/// Applies a guild's [`RetentionPolicy`], or only works out what it would remove if the
+/// policy is a dry run.
+///
+/// # Errors
+/// Returns an error if the database can't be updated or starred messages can't be
+/// retrieved.
+pub async fn apply(http: &Http, db: &DatabaseHandler, policy: &RetentionPolicy) -> Result<Report> {
+  let report = if policy.dry_run {
+    preview(http, db, policy).await?
+  } else {
+    let mut transaction = db.start_transaction_with_retry(5).await?;
+    let summary = DatabaseHandler::apply_retention(&mut transaction, policy, Utc::now()).await?;
+    DatabaseHandler::commit_transaction(transaction).await?;
+
+    let star_messages = if policy.prune_star_messages {
+      prune_star_messages(http, db, policy.guild_id, false).await?
+    } else {
+      0
+    };
+
+    Report {
+      summary,
+      star_messages,
+    }
+  };
+
+  let mut transaction = db.start_transaction_with_retry(5).await?;
+  DatabaseHandler::mark_retention_run(&mut transaction, &policy.guild_id).await?;
+  DatabaseHandler::commit_transaction(transaction).await?;
+
+  Ok(report)
+}
+
+/// Applies every guild's retention policy which hasn't been applied in the last
+/// [`RETENTION_INTERVAL`], posting a report in the [`CHANNELS.logs`][logs] channel for each
+/// guild where anything was, or would be, removed.
+///
+/// [logs]: crate::config::CHANNELS
+async fn apply_all(http: &Http, db: &DatabaseHandler) -> Result<()> {
+  let mut transaction = db.start_transaction_with_retry(5).await?;
+  let policies = DatabaseHandler::get_retention_policies(&mut transaction).await?;
+  drop(transaction);
+
+  let now = Utc::now();
+  for policy in policies
+    .iter()
+    .filter(|policy| !policy.is_empty() && policy.is_due(now, RETENTION_INTERVAL))
+  {
+    let report = match apply(http, db, policy).await {
+      Ok(report) => report,
+      Err(e) => {
+        error!(
+          "Error applying retention policy for {}: {e:?}",
+          policy.guild_id
+        );
+        continue;
+      }
+    };
+
+    info!(
+      "Applied retention policy for {}{}: {:?}",
+      policy.guild_id,
+      if policy.dry_run { " (dry run)" } else { "" },
+      report
+    );
+    if report.is_empty() {
+      continue;
+    }
+
+    let title = if policy.dry_run {
+      "Data Retention (Dry Run)"
+    } else {
+      "Data Retention"
+    };
+    let mut description = format!(
+      "Server: `{}`\n\n{}",
+      policy.guild_id,
+      report.describe(policy)
+    );
+    if policy.dry_run {
+      description.push_str("\n\nNothing has been removed. Turn off dry run with `/config retention set` to apply this policy.");
+    }
+
+    if let Err(e) = ChannelId::new(CHANNELS.logs)
+      .send_message(
+        http,
+        CreateMessage::new().embed(BloomBotEmbed::new().title(title).description(description)),
+      )
+      .await
+    {
+      error!(
+        "Failed to post retention report for {}: {e}",
+        policy.guild_id
+      );
+    }
+  }
+
+  Ok(())
+}
+
+/// Periodically applies every guild's retention policy, once a day. The first check waits
+/// for [`CHECK_INTERVAL`], rather than running as soon as the bot starts.
+pub async fn apply_periodically(ctx: SerenityContext, db: Arc<DatabaseHandler>) {
+  let mut interval = tokio::time::interval_at(Instant::now() + CHECK_INTERVAL, CHECK_INTERVAL);
+
+  loop {
+    interval.tick().await;
+
+    if let Err(e) = apply_all(&ctx.http, &db).await {
+      error!("Error applying retention policies: {e:?}");
+    }
+  }
+}
@@ -0,0 +1,253 @@
+use std::collections::HashMap;
+use std::pin::pin;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::{TimeDelta, Utc};
+use futures::TryStreamExt;
+use log::{error, info};
+use poise::serenity_prelude::{Context as SerenityContext, GuildId, Http, Member, UserId};
+
+use crate::config::{StreakRoles, TimeSumRoles, MEDITATION_MIND};
+use crate::data::stats::MemberProgress;
+use crate::database::DatabaseHandler;
+use crate::roles::{RoleQueueHandler, RoleUpdate};
+
+/// How often roles are reconciled in the main server.
+const RECONCILE_INTERVAL: Duration = Duration::from_secs(60 * 60 * 24);
+
+/// Streaks end when a member hasn't meditated for this long, so only members who have
+/// meditated more recently need their streak calculated.
+const STREAK_LOOKBACK: TimeDelta = TimeDelta::days(3);
+
+/// The outcome of reconciling a server's roles.
+#[derive(Debug, Default)]
+pub struct Summary {
+  pub checked: usize,
+  pub added: usize,
+  pub removed: usize,
+  pub failed: usize,
+}
+
+/// Works out the changes needed for a member to hold exactly the time and streak roles
+/// they've earned. Any other time or streak roles are removed, and roles which aren't time or
+/// streak roles are kept.
+fn role_changes(
+  member: &Member,
+  time_role: Option<&TimeSumRoles>,
+  streak_role: Option<&StreakRoles>,
+) -> RoleUpdate {
+  let time_role = time_role.map(TimeSumRoles::to_role_id);
+  let streak_role = streak_role.map(StreakRoles::to_role_id);
+
+  let update = member
+    .roles
+    .iter()
+    .filter(|role| {
+      (TimeSumRoles::from_role_id(**role).is_some() && Some(**role) != time_role)
+        || (StreakRoles::from_role_id(**role).is_some() && Some(**role) != streak_role)
+    })
+    .fold(RoleUpdate::new(member), |update, role| update.remove(*role));
+
+  [time_role, streak_role]
+    .into_iter()
+    .flatten()
+    .fold(update, RoleUpdate::add)
+}
+
+/// Works out the streak role a member has earned. Members who have turned off streaks, or
+/// haven't meditated recently enough to have a streak, haven't earned one.
+async fn earned_streak_role(
+  db: &DatabaseHandler,
+  guild_id: GuildId,
+  progress: &MemberProgress,
+) -> Result<Option<StreakRoles>> {
+  if !progress.streaks_active || Utc::now() - progress.last_meditation > STREAK_LOOKBACK {
+    return Ok(None);
+  }
+
+  let mut transaction = db.start_transaction_with_retry(5).await?;
+  let streak = DatabaseHandler::get_streak(&mut transaction, &guild_id, &progress.user_id).await?;
+  DatabaseHandler::commit_transaction(transaction).await?;
+
+  #[allow(clippy::cast_sign_loss)]
+  Ok(StreakRoles::from_streak(streak.current.max(0) as u64))
+}
+
+/// Works out the changes needed for a member to hold exactly the time and streak roles
+/// they've earned, given their progress from [`member_progress`].
+///
+/// # Errors
+/// Returns an error if the member's streak can't be calculated.
+pub async fn member_role_changes(
+  db: &DatabaseHandler,
+  member: &Member,
+  progress: Option<&MemberProgress>,
+) -> Result<RoleUpdate> {
+  let time_role = progress.and_then(|progress| TimeSumRoles::from_sum(progress.minutes));
+  let streak_role = match progress {
+    Some(progress) => earned_streak_role(db, member.guild_id, progress).await?,
+    None => None,
+  };
+
+  Ok(role_changes(
+    member,
+    time_role.as_ref(),
+    streak_role.as_ref(),
+  ))
+}
+
+/// Retrieves the progress of every member who has meditated in a server.
+///
+/// # Errors
+/// Returns an error if the progress can't be retrieved.
+pub async fn member_progress(
+  db: &DatabaseHandler,
+  guild_id: GuildId,
+) -> Result<HashMap<UserId, MemberProgress>> {
+  let mut transaction = db.start_transaction_with_retry(5).await?;
+  let progress = DatabaseHandler::get_member_progress(&mut transaction, &guild_id)
+    .await?
+    .into_iter()
+    .map(|progress| (progress.user_id, progress))
+    .collect();
+
+  Ok(progress)
+}
+
+/// Gives every member of a server the time and streak roles their meditation has earned,
+/// removing any they haven't. Roles are otherwise only updated when members add time, so
+/// this fixes roles after entries are edited, removed, or imported, or roles are changed by
+/// hand. No announcements are made for roles given this way.
+///
+/// # Errors
+/// Returns an error if members or their progress can't be retrieved. Failures to update
+/// a member's roles are logged and counted in the [`Summary`]. Updates go through the
+/// [`RoleQueueHandler`], so they're paced to stay within Discord's rate limits.
+pub async fn reconcile(
+  http: &Http,
+  db: &DatabaseHandler,
+  role_queue: &RoleQueueHandler,
+  guild_id: GuildId,
+) -> Result<Summary> {
+  let progress = member_progress(db, guild_id).await?;
+
+  let mut summary = Summary::default();
+  let mut queued = Vec::new();
+  let mut members = pin!(guild_id.members_iter(http));
+  while let Some(member) = members.try_next().await? {
+    if member.user.bot {
+      continue;
+    }
+
+    summary.checked += 1;
+    match member_role_changes(db, &member, progress.get(&member.user.id)).await {
+      Ok(update) if update.is_empty() => {}
+      Ok(update) => {
+        let counts = (update.added(), update.removed());
+        queued.push((member.user.id, counts, role_queue.enqueue(update)));
+      }
+      Err(e) => {
+        error!("Error reconciling roles for {}: {e:?}", member.user.id);
+        summary.failed += 1;
+      }
+    }
+  }
+
+  info!(
+    "Queued role updates for {} of {} members in {guild_id}",
+    queued.len(),
+    summary.checked
+  );
+  for (user_id, (added, removed), outcome) in queued {
+    match outcome.await {
+      Ok(Ok(())) => {
+        summary.added += added;
+        summary.removed += removed;
+      }
+      Ok(Err(e)) => {
+        error!("Error updating roles for {user_id}: {e}");
+        summary.failed += 1;
+      }
+      Err(e) => {
+        error!("Role update for {user_id} was dropped: {e}");
+        summary.failed += 1;
+      }
+    }
+  }
+
+  Ok(summary)
+}
+
+/// Periodically reconciles roles in the main server, where the time and streak roles are.
+pub async fn reconcile_periodically(
+  ctx: SerenityContext,
+  db: Arc<DatabaseHandler>,
+  role_queue: Arc<RoleQueueHandler>,
+) {
+  let mut interval = tokio::time::interval(RECONCILE_INTERVAL);
+
+  loop {
+    interval.tick().await;
+
+    match reconcile(&ctx.http, &db, &role_queue, MEDITATION_MIND).await {
+      Ok(summary) => info!(
+        "Reconciled roles for {} members: {} added, {} removed, {} failed",
+        summary.checked, summary.added, summary.removed, summary.failed
+      ),
+      Err(e) => error!("Error reconciling roles: {e:?}"),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use poise::serenity_prelude::RoleId;
+
+  use super::*;
+
+  fn member(roles: Vec<RoleId>) -> Member {
+    let mut member = Member::default();
+    member.guild_id = GuildId::new(1u64);
+    member.roles = roles;
+    member
+  }
+
+  #[test]
+  fn test_role_changes() {
+    let other_role = RoleId::new(123u64);
+
+    // Missing roles are added, and other roles are kept
+    let update = role_changes(
+      &member(vec![other_role]),
+      Some(&TimeSumRoles::Two),
+      Some(&StreakRoles::Egg),
+    );
+    assert_eq!((update.added(), update.removed()), (2, 0));
+
+    // Stale and duplicate tiers are removed
+    let update = role_changes(
+      &member(vec![
+        TimeSumRoles::One.to_role_id(),
+        TimeSumRoles::Two.to_role_id(),
+        StreakRoles::Owl.to_role_id(),
+        other_role,
+      ]),
+      Some(&TimeSumRoles::Two),
+      None,
+    );
+    assert_eq!((update.added(), update.removed()), (0, 2));
+
+    // Members with the right roles aren't changed
+    let update = role_changes(
+      &member(vec![
+        TimeSumRoles::Five.to_role_id(),
+        StreakRoles::Dove.to_role_id(),
+      ]),
+      Some(&TimeSumRoles::Five),
+      Some(&StreakRoles::Dove),
+    );
+    assert!(update.is_empty());
+  }
+}
@@ -0,0 +1,348 @@
+use std::env;
+use std::sync::Arc;
+
+use log::{error, info, warn};
+use poise::serenity_prelude::{ChannelId, Context as SerenityContext, CreateEmbed, CreateMessage};
+use poise::serenity_prelude::{RoleId, Timestamp};
+
+use crate::config::{BloomBotEmbed, CHANNELS, MEDITATION_MIND, ROLES};
+use crate::database::DatabaseHandler;
+use crate::embeddings::OpenAIHandler;
+
+/// Environment variables Bloom can't run without.
+const REQUIRED_VARS: [&str; 3] = ["DISCORD_TOKEN", "DATABASE_URL", "OPENAI_API_KEY"];
+
+/// Optional environment variables which must be numbers when set.
+const NUMERIC_VARS: [&str; 3] = ["TEST_GUILD_ID", "OPENAI_TIMEOUT", "OPENAI_MAX_RETRIES"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckStatus {
+  Passed,
+  Warning,
+  Failed,
+}
+
+impl CheckStatus {
+  fn symbol(self) -> &'static str {
+    match self {
+      Self::Passed => "✅",
+      Self::Warning => "⚠️",
+      Self::Failed => "❌",
+    }
+  }
+}
+
+/// The result of a single check, such as whether a channel is accessible.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Check {
+  pub name: String,
+  pub status: CheckStatus,
+  pub detail: String,
+}
+
+impl Check {
+  fn new(name: impl Into<String>, status: CheckStatus, detail: impl Into<String>) -> Self {
+    Self {
+      name: name.into(),
+      status,
+      detail: detail.into(),
+    }
+  }
+}
+
+/// The results of every check, grouped by what they check.
+#[derive(Debug)]
+pub struct Report {
+  pub sections: Vec<(&'static str, Vec<Check>)>,
+}
+
+impl Report {
+  pub fn failures(&self) -> impl Iterator<Item = &Check> {
+    self
+      .sections
+      .iter()
+      .flat_map(|(_, checks)| checks)
+      .filter(|check| check.status == CheckStatus::Failed)
+  }
+
+  pub fn passed(&self) -> bool {
+    self.failures().next().is_none()
+  }
+
+  fn section_lines(checks: &[Check]) -> String {
+    checks
+      .iter()
+      .map(|check| {
+        format!(
+          "{} **{}**: {}",
+          check.status.symbol(),
+          check.name,
+          check.detail
+        )
+      })
+      .collect::<Vec<_>>()
+      .join("\n")
+  }
+
+  /// An embed with the result of every check.
+  pub fn embed(&self) -> CreateEmbed {
+    let embed = BloomBotEmbed::new()
+      .title("Self-check")
+      .description(if self.passed() {
+        "All checks passed."
+      } else {
+        "Some checks failed."
+      });
+
+    self
+      .sections
+      .iter()
+      .fold(embed, |embed, (section, checks)| {
+        embed.field(*section, Self::section_lines(checks), false)
+      })
+      .timestamp(Timestamp::now())
+  }
+
+  /// An embed with only the checks which failed, for the log channel.
+  fn failures_embed(&self) -> CreateEmbed {
+    BloomBotEmbed::new()
+      .title("Self-check failed")
+      .description(
+        self
+          .failures()
+          .map(|check| format!("❌ **{}**: {}", check.name, check.detail))
+          .collect::<Vec<_>>()
+          .join("\n"),
+      )
+      .timestamp(Timestamp::now())
+  }
+
+  /// Writes every check to the log, at a level matching its status.
+  fn log(&self) {
+    info!(target: "bloombot::selfcheck", "Self-check report:");
+    for (section, checks) in &self.sections {
+      for check in checks {
+        match check.status {
+          CheckStatus::Passed => {
+            info!(target: "bloombot::selfcheck", "\t[{section}] {}: {}", check.name, check.detail);
+          }
+          CheckStatus::Warning => {
+            warn!(target: "bloombot::selfcheck", "\t[{section}] {}: {}", check.name, check.detail);
+          }
+          CheckStatus::Failed => {
+            error!(target: "bloombot::selfcheck", "\t[{section}] {}: {}", check.name, check.detail);
+          }
+        }
+      }
+    }
+  }
+}
+
+/// Checks that required environment variables are set, and that optional numeric variables
+/// are numbers. Variables are read with `lookup`, so the checks can be tested without
+/// changing the environment.
+fn check_env(lookup: impl Fn(&str) -> Option<String>) -> Vec<Check> {
+  let required =
+    REQUIRED_VARS.iter().map(
+      |var| match lookup(var).filter(|value| !value.trim().is_empty()) {
+        Some(_) => Check::new(*var, CheckStatus::Passed, "Set"),
+        None => Check::new(*var, CheckStatus::Failed, "Missing"),
+      },
+    );
+
+  let numeric = NUMERIC_VARS.iter().filter_map(|var| {
+    let value = lookup(var).filter(|value| !value.trim().is_empty())?;
+    Some(if value.trim().parse::<u64>().is_ok() {
+      Check::new(*var, CheckStatus::Passed, value.trim())
+    } else {
+      Check::new(
+        *var,
+        CheckStatus::Failed,
+        format!("\"{value}\" is not a number"),
+      )
+    })
+  });
+
+  required.chain(numeric).collect()
+}
+
+async fn check_database(db: &DatabaseHandler) -> Vec<Check> {
+  match db.get_pending_migrations().await {
+    Ok(pending) if pending.is_empty() => vec![
+      Check::new("Connection", CheckStatus::Passed, "Connected"),
+      Check::new("Migrations", CheckStatus::Passed, "Up to date"),
+    ],
+    Ok(pending) => vec![
+      Check::new("Connection", CheckStatus::Passed, "Connected"),
+      Check::new(
+        "Migrations",
+        CheckStatus::Failed,
+        format!("{} pending: {}", pending.len(), pending.join(", ")),
+      ),
+    ],
+    Err(e) => vec![Check::new(
+      "Connection",
+      CheckStatus::Failed,
+      format!("{e:#}"),
+    )],
+  }
+}
+
+async fn check_openai(embeddings: &OpenAIHandler) -> Vec<Check> {
+  vec![match embeddings.check_connection().await {
+    Ok(()) => Check::new("API", CheckStatus::Passed, "Reachable"),
+    // Glossary search falls back to similarity search, so Bloom still works without it
+    Err(e) => Check::new("API", CheckStatus::Warning, format!("{e:#}")),
+  }]
+}
+
+async fn check_channels(ctx: &SerenityContext) -> Vec<Check> {
+  let channels = [
+    ("welcome", CHANNELS.welcome),
+    ("announcement", CHANNELS.announcement),
+    ("logs", CHANNELS.logs),
+    ("bloomlogs", CHANNELS.bloomlogs),
+    ("starchannel", CHANNELS.starchannel),
+    ("reportchannel", CHANNELS.reportchannel),
+    ("donators", CHANNELS.donators),
+    ("suggestion", CHANNELS.suggestion),
+    ("tracking", CHANNELS.tracking),
+    ("private_thread_default", CHANNELS.private_thread_default),
+  ];
+
+  let mut checks = Vec::with_capacity(channels.len());
+  for (name, id) in channels {
+    checks.push(match ChannelId::new(id).to_channel(ctx).await {
+      Ok(channel) => Check::new(name, CheckStatus::Passed, channel.to_string()),
+      Err(e) => Check::new(name, CheckStatus::Failed, format!("Not accessible ({e})")),
+    });
+  }
+
+  checks
+}
+
+async fn check_roles(ctx: &SerenityContext) -> Vec<Check> {
+  let roles = [
+    ("welcome_team", ROLES.welcome_team),
+    ("meditation_challenger", ROLES.meditation_challenger),
+    ("meditation_challenger_365", ROLES.meditation_challenger_365),
+    ("patreon", ROLES.patreon),
+    ("kofi", ROLES.kofi),
+    ("staff", ROLES.staff),
+    ("community_sit_helper", ROLES.community_sit_helper),
+  ];
+
+  let guild_roles = match MEDITATION_MIND.roles(ctx).await {
+    Ok(guild_roles) => guild_roles,
+    Err(e) => {
+      return vec![Check::new(
+        "Server",
+        CheckStatus::Failed,
+        format!("Roles not accessible ({e})"),
+      )]
+    }
+  };
+
+  roles
+    .into_iter()
+    .map(|(name, id)| match guild_roles.get(&RoleId::new(id)) {
+      Some(role) => Check::new(name, CheckStatus::Passed, format!("@{}", role.name)),
+      None => Check::new(name, CheckStatus::Failed, "Not found"),
+    })
+    .collect()
+}
+
+/// Runs every check: environment variables, the database connection and migrations, the
+/// OpenAI API, and the channels and roles configured in [`config`][crate::config].
+pub async fn run(
+  ctx: &SerenityContext,
+  db: &DatabaseHandler,
+  embeddings: &OpenAIHandler,
+) -> Report {
+  Report {
+    sections: vec![
+      ("Environment", check_env(|var| env::var(var).ok())),
+      ("Database", check_database(db).await),
+      ("OpenAI", check_openai(embeddings).await),
+      ("Channels", check_channels(ctx).await),
+      ("Roles", check_roles(ctx).await),
+    ],
+  }
+}
+
+/// Runs every check on startup, writing the report to the log and posting any failures to
+/// the [`CHANNELS.logs`][logs] channel.
+///
+/// [logs]: crate::config::CHANNELS
+pub async fn run_on_startup(
+  ctx: SerenityContext,
+  db: Arc<DatabaseHandler>,
+  embeddings: Arc<OpenAIHandler>,
+) {
+  let report = run(&ctx, &db, &embeddings).await;
+  report.log();
+
+  if report.passed() {
+    return;
+  }
+
+  if let Err(e) = ChannelId::new(CHANNELS.logs)
+    .send_message(&ctx, CreateMessage::new().embed(report.failures_embed()))
+    .await
+  {
+    error!("Error posting self-check failures: {e:?}");
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_check_env() {
+    let checks = check_env(|var| match var {
+      "DISCORD_TOKEN" => Some("token".to_owned()),
+      "DATABASE_URL" => Some(" ".to_owned()),
+      "OPENAI_TIMEOUT" => Some("10".to_owned()),
+      "OPENAI_MAX_RETRIES" => Some("three".to_owned()),
+      _ => None,
+    });
+
+    let status = |name: &str| {
+      checks
+        .iter()
+        .find(|check| check.name == name)
+        .map(|check| check.status)
+    };
+
+    assert_eq!(status("DISCORD_TOKEN"), Some(CheckStatus::Passed));
+    assert_eq!(status("DATABASE_URL"), Some(CheckStatus::Failed));
+    assert_eq!(status("OPENAI_API_KEY"), Some(CheckStatus::Failed));
+    assert_eq!(status("OPENAI_TIMEOUT"), Some(CheckStatus::Passed));
+    assert_eq!(status("OPENAI_MAX_RETRIES"), Some(CheckStatus::Failed));
+    // Optional variables which aren't set aren't checked
+    assert_eq!(status("TEST_GUILD_ID"), None);
+  }
+
+  #[test]
+  fn test_report_passed() {
+    let mut report = Report {
+      sections: vec![(
+        "Environment",
+        vec![Check::new("DISCORD_TOKEN", CheckStatus::Passed, "Set")],
+      )],
+    };
+    report.sections.push((
+      "OpenAI",
+      vec![Check::new("API", CheckStatus::Warning, "Timed out")],
+    ));
+    assert!(report.passed());
+
+    report.sections.push((
+      "Roles",
+      vec![Check::new("staff", CheckStatus::Failed, "Not found")],
+    ));
+    assert!(!report.passed());
+    assert_eq!(report.failures().count(), 1);
+  }
+}
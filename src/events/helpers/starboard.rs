@@ -1,8 +1,9 @@
 use anyhow::Result;
-use poise::serenity_prelude::{builder::*, ChannelId, Context};
-use poise::serenity_prelude::{MessageFlags, Reaction, ReactionType};
+use poise::serenity_prelude::{builder::*, ChannelId, ChannelType, Context, GuildId};
+use poise::serenity_prelude::{MessageFlags, MessageUpdateEvent, Reaction, ReactionType};
 use sqlx::{Postgres, Transaction};
 
+use crate::commands::helpers::threads;
 use crate::config::{BloomBotEmbed, CHANNELS, EMOTES, MIN_STARS};
 use crate::data::star_message::StarMessage;
 use crate::database::DatabaseHandler;
@@ -10,6 +11,7 @@ use crate::database::DatabaseHandler;
 async fn create_star_message(
   ctx: &Context,
   transaction: &mut Transaction<'_, Postgres>,
+  guild_id: GuildId,
   reaction: &Reaction,
   star_count: u64,
 ) -> Result<()> {
@@ -17,22 +19,32 @@ async fn create_star_message(
     return Ok(());
   }
 
+  // Messages in private threads stay private, and threads in the starboard itself are
+  // treated like the starboard
+  if let Some(channel) = threads::guild_channel(ctx, reaction.channel_id).await {
+    if channel.kind == ChannelType::PrivateThread
+      || (threads::is_thread(channel.kind)
+        && channel
+          .parent_id
+          .is_some_and(|parent| parent == CHANNELS.starchannel))
+    {
+      return Ok(());
+    }
+  }
+
   let starred_message = reaction.message(&ctx).await?;
-  let author_nick_or_name = match reaction.guild_id {
-    Some(guild_id) => starred_message
-      .author
-      .nick_in(&ctx, guild_id)
-      .await
-      .unwrap_or_else(|| {
-        starred_message
-          .author
-          .global_name
-          .as_ref()
-          .unwrap_or(&starred_message.author.name)
-          .clone()
-      }),
-    None => starred_message.author.name.clone(),
-  };
+  let author_nick_or_name = starred_message
+    .author
+    .nick_in(&ctx, guild_id)
+    .await
+    .unwrap_or_else(|| {
+      starred_message
+        .author
+        .global_name
+        .as_ref()
+        .unwrap_or(&starred_message.author.name)
+        .clone()
+    });
 
   let message_type = if starred_message
     .flags
@@ -157,6 +169,7 @@ async fn create_star_message(
   };
 
   let star_message = StarMessage::new(
+    guild_id,
     reaction.channel_id,
     reaction.message_id,
     starboard_message.id,
@@ -174,6 +187,9 @@ pub async fn add_star(
   let ReactionType::Unicode(emoji) = &reaction.emoji else {
     return Ok(());
   };
+  let Some(guild_id) = reaction.guild_id else {
+    return Ok(());
+  };
 
   if emoji == EMOTES.star && reaction.channel_id != CHANNELS.starchannel {
     // Get count of star emojis on message.
@@ -188,10 +204,10 @@ pub async fn add_star(
     let mut transaction = database.start_transaction().await?;
 
     let Some(star_message) =
-      DatabaseHandler::get_star_message(&mut transaction, &reaction.message_id).await?
+      DatabaseHandler::get_star_message(&mut transaction, &guild_id, &reaction.message_id).await?
     else {
       // No message found in the database. Create a new starboard message and return.
-      create_star_message(ctx, &mut transaction, reaction, star_count).await?;
+      create_star_message(ctx, &mut transaction, guild_id, reaction, star_count).await?;
       transaction.commit().await?;
       return Ok(());
     };
@@ -228,9 +244,9 @@ pub async fn add_star(
       starboard_channel
         .delete_message(&ctx, starboard_message.id)
         .await?;
-      DatabaseHandler::remove_star_message(&mut transaction, &star_message.id).await?;
+      DatabaseHandler::remove_star_message(&mut transaction, &guild_id, &star_message.id).await?;
 
-      create_star_message(ctx, &mut transaction, reaction, star_count).await?;
+      create_star_message(ctx, &mut transaction, guild_id, reaction, star_count).await?;
       transaction.commit().await?;
     }
   }
@@ -246,11 +262,14 @@ pub async fn remove_star(
   let ReactionType::Unicode(emoji) = &reaction.emoji else {
     return Ok(());
   };
+  let Some(guild_id) = reaction.guild_id else {
+    return Ok(());
+  };
 
   if emoji == EMOTES.star {
     let mut transaction = database.start_transaction().await?;
     let Some(star_message) =
-      DatabaseHandler::get_star_message(&mut transaction, &reaction.message_id).await?
+      DatabaseHandler::get_star_message(&mut transaction, &guild_id, &reaction.message_id).await?
     else {
       return Ok(());
     };
@@ -270,7 +289,7 @@ pub async fn remove_star(
       starboard_channel
         .delete_message(&ctx, star_message.board_message)
         .await?;
-      DatabaseHandler::remove_star_message(&mut transaction, &star_message.id).await?;
+      DatabaseHandler::remove_star_message(&mut transaction, &guild_id, &star_message.id).await?;
       transaction.commit().await?;
 
       return Ok(());
@@ -306,12 +325,85 @@ pub async fn remove_star(
       starboard_channel
         .delete_message(&ctx, starboard_message.id)
         .await?;
-      DatabaseHandler::remove_star_message(&mut transaction, &star_message.id).await?;
+      DatabaseHandler::remove_star_message(&mut transaction, &guild_id, &star_message.id).await?;
 
-      create_star_message(ctx, &mut transaction, reaction, star_count).await?;
+      create_star_message(ctx, &mut transaction, guild_id, reaction, star_count).await?;
       transaction.commit().await?;
     }
   }
 
   Ok(())
 }
+
+/// Updates the starboard repost of a message when the original message is edited, so the
+/// starboard reflects what the author currently has to say.
+pub async fn sync_edit(
+  ctx: &Context,
+  database: &DatabaseHandler,
+  event: &MessageUpdateEvent,
+) -> Result<()> {
+  let Some(guild_id) = event.guild_id else {
+    return Ok(());
+  };
+  // Only content edits need to be synced. Other updates, such as embeds being resolved
+  // for links, don't change what was posted by the author.
+  let Some(content) = &event.content else {
+    return Ok(());
+  };
+
+  let mut transaction = database.start_transaction().await?;
+  let Some(star_message) =
+    DatabaseHandler::get_star_message(&mut transaction, &guild_id, &event.id).await?
+  else {
+    return Ok(());
+  };
+  drop(transaction);
+
+  let starboard_channel = ChannelId::new(CHANNELS.starchannel);
+  let mut starboard_message = starboard_channel
+    .message(&ctx, star_message.board_message)
+    .await?;
+
+  // Messages created by the previous bot can't be edited, and will be recreated the next
+  // time the starred message is starred or unstarred.
+  if starboard_message.author.id != ctx.cache.current_user().id {
+    return Ok(());
+  }
+
+  // Tenor GIFs are reposted as a link to the GIF, rather than an embed.
+  if starboard_message.content.starts_with("[★]") {
+    if content.starts_with("https://tenor.com") && content.split_whitespace().count() == 1 {
+      let starred_message_link = star_message
+        .starred_message
+        .link(star_message.starred_channel, Some(guild_id));
+      starboard_message
+        .edit(
+          ctx,
+          EditMessage::new().content(format!(
+            "[★]({content}) [Click to jump to message.]({starred_message_link})"
+          )),
+        )
+        .await?;
+    }
+    return Ok(());
+  }
+
+  // If the starred message was embed-only, the repost is a copy of that embed, which
+  // should be left as is.
+  if content.is_empty() {
+    return Ok(());
+  }
+
+  let updated_embeds: Vec<CreateEmbed> = starboard_message
+    .embeds
+    .clone()
+    .into_iter()
+    .map(|embed| CreateEmbed::from(embed).description(content.clone()))
+    .collect();
+
+  starboard_message
+    .edit(ctx, EditMessage::new().embeds(updated_embeds))
+    .await?;
+
+  Ok(())
+}
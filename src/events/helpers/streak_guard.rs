@@ -0,0 +1,87 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::Utc;
+use log::{error, info};
+use poise::serenity_prelude::{Context as SerenityContext, CreateMessage};
+
+use crate::config::BloomBotEmbed;
+use crate::data::tracking_profile::StreakGuardReminder;
+use crate::database::DatabaseHandler;
+
+/// How often to check for streaks which need a reminder.
+const CHECK_INTERVAL: Duration = Duration::from_secs(60 * 10);
+
+/// Sends a streak guard reminder by DM. The reminder is marked as sent even if the DM fails,
+/// e.g., because the member doesn't accept DMs, so that it isn't retried every check.
+async fn send_reminder(
+  ctx: &SerenityContext,
+  db: &DatabaseHandler,
+  reminder: &StreakGuardReminder,
+) -> Result<()> {
+  let mut transaction = db.start_transaction_with_retry(5).await?;
+  DatabaseHandler::mark_streak_guard_sent(&mut transaction, reminder).await?;
+  DatabaseHandler::commit_transaction(transaction).await?;
+
+  let server = reminder
+    .guild_id
+    .name(ctx)
+    .unwrap_or_else(|| "the server".to_owned());
+  let days = if reminder.current_streak == 1 {
+    "1 day".to_owned()
+  } else {
+    format!("{} days", reminder.current_streak)
+  };
+
+  let embed = BloomBotEmbed::new()
+    .title(":seedling: Streak Guard")
+    .description(format!(
+      "Just a gentle reminder: you haven't added any meditation time in {server} today, and your **{days}** streak ends at midnight. Even a few minutes counts. There's no pressure, though. Be kind to yourself either way!\n\n-# You can turn these reminders off with `/customize guard`."
+    ));
+
+  if let Err(e) = reminder
+    .user_id
+    .direct_message(ctx, CreateMessage::new().embed(embed))
+    .await
+  {
+    info!(
+      "Failed to send streak guard reminder to user {}: {e}",
+      reminder.user_id
+    );
+  }
+
+  Ok(())
+}
+
+async fn check_streaks(ctx: &SerenityContext, db: &DatabaseHandler) -> Result<()> {
+  let mut transaction = db.start_transaction_with_retry(5).await?;
+  let reminders =
+    DatabaseHandler::get_streak_guard_reminders(&mut transaction, &Utc::now()).await?;
+  drop(transaction);
+
+  for reminder in reminders {
+    if let Err(e) = send_reminder(ctx, db, &reminder).await {
+      error!(
+        "Error sending streak guard reminder to user {}: {e:?}",
+        reminder.user_id
+      );
+    }
+  }
+
+  Ok(())
+}
+
+/// Periodically reminds members who have turned on the streak guard when their streak is
+/// about to lapse.
+pub async fn remind_periodically(ctx: SerenityContext, db: Arc<DatabaseHandler>) {
+  let mut interval = tokio::time::interval(CHECK_INTERVAL);
+
+  loop {
+    interval.tick().await;
+
+    if let Err(e) = check_streaks(&ctx, &db).await {
+      error!("Error checking streak guard reminders: {e:?}");
+    }
+  }
+}
@@ -0,0 +1,92 @@
+use anyhow::Result;
+use log::info;
+use poise::serenity_prelude::{Context, CreateMessage, Message, ReactionType};
+
+use crate::commands::helpers::tickets;
+use crate::database::DatabaseHandler;
+use crate::emoji::EmojiHandler;
+
+/// Relays a DM from a member to staff. Messages are added to the member's open ticket,
+/// or open a new ticket in the first server with tickets turned on which they belong to.
+/// DMs from members of servers without tickets are ignored.
+pub async fn relay_dm(
+  ctx: &Context,
+  database: &DatabaseHandler,
+  emoji: &EmojiHandler,
+  message: &Message,
+) -> Result<()> {
+  let mut content = message.content.clone();
+  for attachment in &message.attachments {
+    if !content.is_empty() {
+      content.push('\n');
+    }
+    content.push_str(&attachment.url);
+  }
+  if content.is_empty() {
+    return Ok(());
+  }
+
+  let mut transaction = database.start_transaction_with_retry(5).await?;
+
+  let open_tickets =
+    DatabaseHandler::get_open_tickets(&mut transaction, &message.author.id).await?;
+
+  if let Some(ticket) = open_tickets.first() {
+    tickets::relay_to_staff(ctx, &mut transaction, ticket, &message.author, content).await?;
+    DatabaseHandler::commit_transaction(transaction).await?;
+    message
+      .react(ctx, ReactionType::Unicode("📨".to_owned()))
+      .await?;
+    return Ok(());
+  }
+
+  let mut ticket_guild = None;
+  for settings in DatabaseHandler::get_ticket_guilds(&mut transaction).await? {
+    let Some(ticket_channel) = settings.ticket_channel else {
+      continue;
+    };
+    if settings
+      .guild_id
+      .member(ctx, message.author.id)
+      .await
+      .is_ok()
+    {
+      ticket_guild = Some((settings.guild_id, ticket_channel));
+      break;
+    }
+  }
+  let Some((guild_id, ticket_channel)) = ticket_guild else {
+    return Ok(());
+  };
+
+  let ticket = tickets::open(
+    ctx,
+    &mut transaction,
+    guild_id,
+    ticket_channel,
+    &message.author,
+    None,
+  )
+  .await?;
+  tickets::relay_to_staff(ctx, &mut transaction, &ticket, &message.author, content).await?;
+  DatabaseHandler::commit_transaction(transaction).await?;
+
+  info!(
+    "Opened ticket {} for {} in guild {guild_id}",
+    ticket.id, message.author.id
+  );
+
+  message
+    .channel_id
+    .send_message(
+      ctx,
+      CreateMessage::new().content(format!(
+        "{} Thanks for reaching out. Your message has been passed on to the staff of {}, and their replies will arrive here. Anything else you send here will be added to your ticket.",
+        emoji.get(Some(guild_id)).mmcheck,
+        guild_id.name(ctx).unwrap_or_else(|| "the server".to_owned())
+      )),
+    )
+    .await?;
+
+  Ok(())
+}
@@ -0,0 +1,122 @@
+use anyhow::Result;
+use log::info;
+use poise::serenity_prelude::{builder::*, ChannelId, Context, GetMessages, GuildId, Message};
+
+use crate::commands::helpers::watchlist;
+use crate::config::{BloomBotEmbed, CHANNELS};
+use crate::data::report::{Report, ReportSource};
+use crate::data::watchlist::WatchlistTerm;
+use crate::database::DatabaseHandler;
+
+/// The number of earlier messages in the channel shown with a flagged message.
+const CONTEXT_MESSAGES: u8 = 3;
+
+fn truncate(content: &str, length: usize) -> String {
+  if content.chars().count() > length {
+    format!("{}...", content.chars().take(length).collect::<String>())
+  } else {
+    content.to_owned()
+  }
+}
+
+/// Checks a message against the server's watchlist. Matching messages are quietly flagged
+/// in the [`CHANNELS.logs`][logs] channel, with the messages before it for context and
+/// buttons for acting on it, and recorded as a [`Report`].
+///
+/// [logs]: crate::config::CHANNELS
+pub async fn check(
+  ctx: &Context,
+  database: &DatabaseHandler,
+  guild_id: GuildId,
+  message: &Message,
+) -> Result<()> {
+  if message.content.is_empty() {
+    return Ok(());
+  }
+
+  let mut transaction = database.start_transaction().await?;
+  let terms = DatabaseHandler::get_watchlist(&mut transaction, &guild_id).await?;
+
+  let Some(term) = WatchlistTerm::first_match(&terms, &message.content) else {
+    return Ok(());
+  };
+
+  let report = Report::new(
+    guild_id,
+    message.channel_id,
+    message.id,
+    message.author.id,
+    ReportSource::Watchlist,
+    message.content.clone(),
+  )
+  .matched_term(term.pattern.clone());
+  DatabaseHandler::add_report(&mut transaction, &report).await?;
+
+  let mut earlier = message
+    .channel_id
+    .messages(
+      ctx,
+      GetMessages::new()
+        .before(message.id)
+        .limit(CONTEXT_MESSAGES),
+    )
+    .await
+    .unwrap_or_default();
+  earlier.sort_by_key(|earlier| earlier.id);
+  let context = earlier
+    .iter()
+    .map(|earlier| {
+      format!(
+        "**{}**: {}",
+        earlier.author.name,
+        truncate(&earlier.content.replace('\n', " "), 200)
+      )
+    })
+    .collect::<Vec<_>>()
+    .join("\n");
+
+  let mut embed = BloomBotEmbed::new()
+    .title("Watchlist Match")
+    .author(CreateEmbedAuthor::new(&message.author.name).icon_url(message.author.face()))
+    .description(truncate(&message.content, 2000))
+    .field("Matched", format!("`{}`", term.pattern), true)
+    .field("Link", format!("[Go to message]({})", message.link()), true)
+    .footer(CreateEmbedFooter::new(format!(
+      "Author ID: {} · Report ID: {}",
+      message.author.id, report.id
+    )))
+    .timestamp(message.timestamp);
+  if !context.is_empty() {
+    embed = embed.field("Context", truncate(&context, 1000), false);
+  }
+
+  // Posted without mentions, so flags don't interrupt staff like member reports do
+  ChannelId::new(CHANNELS.logs)
+    .send_message(
+      ctx,
+      CreateMessage::new()
+        .embed(embed)
+        .components(watchlist::buttons(&report.id)),
+    )
+    .await?;
+
+  DatabaseHandler::commit_transaction(transaction).await?;
+
+  info!(
+    "Flagged message {} in guild {guild_id} for matching the watchlist",
+    message.id
+  );
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_truncate() {
+    assert_eq!(truncate("short", 10), "short");
+    assert_eq!(truncate("a longer message", 8), "a longer...");
+  }
+}
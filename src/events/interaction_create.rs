@@ -0,0 +1,55 @@
+use anyhow::Result;
+use poise::serenity_prelude::{Context, Interaction};
+
+use crate::commands::helpers::{confirmations, key_redemption, pagination, polls, quick_add};
+use crate::commands::helpers::{
+  retreats, streak_repairs, suggestions, terms, transcripts, watchlist,
+};
+use crate::database::DatabaseHandler;
+use crate::emoji::EmojiHandler;
+use crate::settings::SettingsHandler;
+use crate::transcription::TranscriptionHandler;
+
+pub async fn interaction_create(
+  ctx: &Context,
+  database: &DatabaseHandler,
+  emoji: &EmojiHandler,
+  settings: &SettingsHandler,
+  transcription: &TranscriptionHandler,
+  interaction: &Interaction,
+) -> Result<()> {
+  // Commands are dispatched by poise, and most components are handled by the collector of
+  // the command that created them. Only components which outlive their command are handled here.
+  let Interaction::Component(press) = interaction else {
+    return Ok(());
+  };
+
+  if let Some((response, offer_id)) = key_redemption::parse_custom_id(&press.data.custom_id) {
+    key_redemption::handle_response(ctx, database, press, response, offer_id).await?;
+  } else if let Some(term_name) = terms::parse_custom_id(&press.data.custom_id) {
+    terms::handle_see_also(ctx, database, press, term_name).await?;
+  } else if let Some((poll_id, choice)) = polls::parse_custom_id(&press.data.custom_id) {
+    polls::handle_vote(ctx, database, emoji, press, poll_id, choice).await?;
+  } else if let Some((upvote, suggestion_id)) = suggestions::parse_custom_id(&press.data.custom_id)
+  {
+    suggestions::handle_vote(ctx, database, emoji, press, upvote, suggestion_id).await?;
+  } else if let Some((action, report_id)) = watchlist::parse_custom_id(&press.data.custom_id) {
+    watchlist::handle_action(ctx, database, emoji, press, action, report_id).await?;
+  } else if let Some(minutes) = quick_add::parse_custom_id(&press.data.custom_id) {
+    quick_add::handle_press(ctx, database, emoji, settings, press, minutes).await?;
+  } else if let Some((approve, repair_id)) = streak_repairs::parse_custom_id(&press.data.custom_id)
+  {
+    streak_repairs::handle_decision(ctx, database, emoji, press, approve, repair_id).await?;
+  } else if let Some((approve, retreat_id)) = retreats::parse_custom_id(&press.data.custom_id) {
+    retreats::handle_decision(ctx, database, emoji, press, approve, retreat_id).await?;
+  } else if let Some((consent, recording_id)) = transcripts::parse_custom_id(&press.data.custom_id)
+  {
+    transcripts::handle_consent(ctx, emoji, transcription, press, consent, recording_id).await?;
+  } else if let Some((pages_id, page)) = pagination::parse_custom_id(&press.data.custom_id) {
+    pagination::handle_press(ctx, database, press, pages_id, page).await?;
+  } else if let Some((prompt_id, _)) = confirmations::parse_custom_id(&press.data.custom_id) {
+    confirmations::handle_orphaned(ctx, database, press, prompt_id).await?;
+  }
+
+  Ok(())
+}
@@ -0,0 +1,178 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use log::warn;
+use poise::serenity_prelude::{Context, CreateAllowedMentions, CreateMessage, Message};
+
+use crate::commands::helpers::threads;
+use crate::data::community_event::AttendanceSource;
+use crate::database::DatabaseHandler;
+use crate::emoji::EmojiHandler;
+use crate::events::helpers::{event_attendance, tickets, watchlist};
+use crate::settings::SettingsHandler;
+
+/// How long a tracking hint stays in the channel before it is removed.
+const HINT_LIFETIME: Duration = Duration::from_secs(60);
+
+/// Longest session, in minutes, which is recognized in a message. Anything longer is more
+/// likely to be a total than a single session.
+const MAX_HINT_MINUTES: i64 = 600;
+
+/// Words which suggest a message is describing a meditation session.
+const SESSION_WORDS: [&str; 7] = [
+  "sat",
+  "sit",
+  "meditated",
+  "meditation",
+  "practiced",
+  "practised",
+  "session",
+];
+
+fn unit_in_minutes(unit: &str) -> Option<i64> {
+  match unit {
+    "m" | "min" | "mins" | "minute" | "minutes" => Some(1),
+    "h" | "hr" | "hrs" | "hour" | "hours" => Some(60),
+    _ => None,
+  }
+}
+
+/// Looks for a description of a meditation session in a message, such as "sat 20 minutes"
+/// or "meditated for 1 hour and 15 mins", and returns the total number of minutes described.
+fn session_minutes(content: &str) -> Option<i64> {
+  let content = content.to_lowercase();
+  let words: Vec<&str> = content
+    .split_whitespace()
+    .map(|word| word.trim_matches(|c: char| !c.is_alphanumeric()))
+    .filter(|word| !word.is_empty())
+    .collect();
+
+  if !words.iter().any(|word| SESSION_WORDS.contains(word)) {
+    return None;
+  }
+
+  let mut minutes = 0;
+  let mut words = words.into_iter().peekable();
+
+  while let Some(word) = words.next() {
+    let digits = word.chars().take_while(char::is_ascii_digit).count();
+    if digits == 0 {
+      continue;
+    }
+
+    let (number, suffix) = word.split_at(digits);
+    let Ok(number) = number.parse::<i64>() else {
+      continue;
+    };
+
+    // Units may be attached to the number ("20min") or follow it ("20 minutes")
+    let unit = if suffix.is_empty() {
+      words
+        .next_if(|next| unit_in_minutes(next).is_some())
+        .unwrap_or_default()
+    } else {
+      suffix
+    };
+
+    if let Some(multiplier) = unit_in_minutes(unit) {
+      minutes += number.saturating_mul(multiplier);
+    }
+  }
+
+  (1..=MAX_HINT_MINUTES).contains(&minutes).then_some(minutes)
+}
+
+pub async fn message_create(
+  ctx: &Context,
+  database: &DatabaseHandler,
+  emoji: &EmojiHandler,
+  settings: &SettingsHandler,
+  message: &Message,
+) -> Result<()> {
+  if message.author.bot {
+    return Ok(());
+  }
+
+  let Some(guild_id) = message.guild_id else {
+    return tickets::relay_dm(ctx, database, emoji, message).await;
+  };
+
+  if let Err(e) = event_attendance::record(
+    database,
+    guild_id,
+    message.channel_id,
+    message.author.id,
+    AttendanceSource::Message,
+  )
+  .await
+  {
+    warn!("Failed to record event attendance: {e:?}");
+  }
+
+  if let Err(e) = watchlist::check(ctx, database, guild_id, message).await {
+    warn!("Failed to check message against the watchlist: {e:?}");
+  }
+
+  // This runs for every message, so check the content before going to the database
+  let Some(minutes) = session_minutes(&message.content) else {
+    return Ok(());
+  };
+
+  let settings = settings.get(database, guild_id).await?;
+
+  let Some(tracking_channel) = settings
+    .tracking_channel
+    .filter(|_| settings.tracking_hints)
+  else {
+    return Ok(());
+  };
+  if !threads::is_in_channel(ctx, message.channel_id, tracking_channel).await {
+    return Ok(());
+  }
+
+  let hint = message
+    .channel_id
+    .send_message(
+      ctx,
+      CreateMessage::new()
+        .content(format!(
+          "{} Sounds like a lovely session! To track your **{minutes} minutes**, use `/add minutes:{minutes}`.\n-# This message will disappear in a minute.",
+          emoji.get(Some(guild_id)).mminfo
+        ))
+        .reference_message(message)
+        .allowed_mentions(CreateAllowedMentions::new()),
+    )
+    .await?;
+
+  // Hints can't be ephemeral, so tidy them up instead
+  let ctx = ctx.clone();
+  tokio::spawn(async move {
+    tokio::time::sleep(HINT_LIFETIME).await;
+    if let Err(e) = hint.delete(&ctx).await {
+      warn!("Failed to delete tracking hint {}: {e}", hint.id);
+    }
+  });
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_session_minutes() {
+    assert_eq!(session_minutes("sat 20 minutes"), Some(20));
+    assert_eq!(session_minutes("Sat for 45min."), Some(45));
+    assert_eq!(
+      session_minutes("Meditated for 1 hour and 15 mins this morning"),
+      Some(75)
+    );
+    assert_eq!(session_minutes("Lovely session today, 2h!"), Some(120));
+    assert_eq!(session_minutes("20 minutes"), None);
+    assert_eq!(session_minutes("I sat on the bus"), None);
+    assert_eq!(session_minutes("sat 1.5 hours"), None);
+    assert_eq!(session_minutes("sat 2000 minutes"), None);
+    assert_eq!(session_minutes("sat for 20 while listening"), None);
+  }
+}
@@ -1,20 +1,41 @@
 use anyhow::Result;
-use poise::serenity_prelude::MessageId;
+use log::warn;
+use poise::serenity_prelude::{ChannelId, Context, GuildId, MessageId};
 
+use crate::config::CHANNELS;
 use crate::database::DatabaseHandler;
 
 pub async fn message_delete(
+  ctx: &Context,
   database: &DatabaseHandler,
+  guild_id: Option<GuildId>,
   deleted_message_id: &MessageId,
 ) -> Result<()> {
+  // Only guild messages can be starred.
+  let Some(guild_id) = guild_id else {
+    return Ok(());
+  };
+
   let mut transaction = database.start_transaction().await?;
 
   let star_message =
-    DatabaseHandler::get_star_message(&mut transaction, deleted_message_id).await?;
+    DatabaseHandler::get_star_message(&mut transaction, &guild_id, deleted_message_id).await?;
 
   if let Some(star_message) = star_message {
+    // Remove the repost so the starboard doesn't keep content the author has deleted.
+    // It may already have been removed by staff, so failing to delete it isn't an error.
+    if let Err(e) = ChannelId::new(CHANNELS.starchannel)
+      .delete_message(ctx, star_message.board_message)
+      .await
+    {
+      warn!(
+        "Failed to delete starboard message {} for deleted message {}: {e}",
+        star_message.board_message, deleted_message_id
+      );
+    }
+
     let star_message_id = star_message.id;
-    DatabaseHandler::remove_star_message(&mut transaction, &star_message_id).await?;
+    DatabaseHandler::remove_star_message(&mut transaction, &guild_id, &star_message_id).await?;
   }
 
   transaction.commit().await?;
@@ -0,0 +1,20 @@
+use anyhow::Result;
+use poise::serenity_prelude::{Context, MessageUpdateEvent};
+
+use crate::data::guild_feature::Feature;
+use crate::database::DatabaseHandler;
+use crate::events::helpers::starboard;
+use crate::features::FeatureHandler;
+
+pub async fn message_update(
+  ctx: &Context,
+  database: &DatabaseHandler,
+  features: &FeatureHandler,
+  event: &MessageUpdateEvent,
+) -> Result<()> {
+  if features.enabled(event.guild_id, Feature::Starboard) {
+    starboard::sync_edit(ctx, database, event).await?;
+  }
+
+  Ok(())
+}
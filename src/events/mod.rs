@@ -1,15 +1,39 @@
 mod guild_create;
+mod guild_member_addition;
 mod guild_member_removal;
 mod guild_member_update;
+pub mod guild_scheduled_event;
 mod helpers;
+mod interaction_create;
+mod message_create;
 mod message_delete;
+mod message_update;
 mod reaction_add;
 mod reaction_remove;
+mod voice_state_update;
 
 pub use guild_create::guild_create;
+pub use guild_member_addition::guild_member_addition;
 pub use guild_member_removal::guild_member_removal;
 pub use guild_member_update::guild_member_update;
+pub use guild_scheduled_event::{guild_scheduled_event_delete, guild_scheduled_event_update};
+pub use helpers::feeds;
+pub use helpers::goals;
+pub use helpers::improved;
+pub use helpers::integrity;
+pub use helpers::intention_reminders;
 pub use helpers::leaderboards;
+pub use helpers::mentorship_check_ins;
+pub use helpers::pending_interactions;
+pub use helpers::recurring_posts;
+pub use helpers::retention;
+pub use helpers::role_sync;
+pub use helpers::selfcheck;
+pub use helpers::streak_guard;
+pub use interaction_create::interaction_create;
+pub use message_create::message_create;
 pub use message_delete::message_delete;
+pub use message_update::message_update;
 pub use reaction_add::reaction_add;
 pub use reaction_remove::reaction_remove;
+pub use voice_state_update::voice_state_update;
@@ -1,13 +1,17 @@
 use anyhow::{Context as AnyhowContext, Result};
 use poise::serenity_prelude::{builder::*, ChannelId, Context, Reaction, ReactionType};
 
+use crate::commands::helpers::threads;
 use crate::config::{BloomBotEmbed, CHANNELS, EMOTES, ROLES};
+use crate::data::guild_feature::Feature;
 use crate::database::DatabaseHandler;
 use crate::events::helpers::starboard;
+use crate::features::FeatureHandler;
 
 pub async fn reaction_add(
   ctx: &Context,
   database: &DatabaseHandler,
+  features: &FeatureHandler,
   add_reaction: &Reaction,
 ) -> Result<()> {
   if add_reaction.user_id.is_none() {
@@ -21,7 +25,9 @@ pub async fn reaction_add(
   }
 
   check_report(ctx, add_reaction).await?;
-  starboard::add_star(ctx, database, add_reaction).await?;
+  if features.enabled(add_reaction.guild_id, Feature::Starboard) {
+    starboard::add_star(ctx, database, add_reaction).await?;
+  }
 
   Ok(())
 }
@@ -39,7 +45,7 @@ async fn check_report(ctx: &Context, reaction: &Reaction) -> Result<()> {
       let message = reaction.message(&ctx).await?;
       let message_link = message.link().clone();
       let message_user = message.author;
-      let message_channel_name = message.channel_id.name(ctx).await?;
+      let message_channel_name = threads::channel_label(ctx, message.channel_id).await?;
       let reporting_user = reaction.user(&ctx).await?;
 
       let message_content = if message.content.is_empty() {
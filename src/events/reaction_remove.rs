@@ -1,15 +1,20 @@
 use anyhow::Result;
 use poise::serenity_prelude::{Context, Reaction};
 
+use crate::data::guild_feature::Feature;
 use crate::database::DatabaseHandler;
 use crate::events::helpers::starboard;
+use crate::features::FeatureHandler;
 
 pub async fn reaction_remove(
   ctx: &Context,
   database: &DatabaseHandler,
+  features: &FeatureHandler,
   remove_reaction: &Reaction,
 ) -> Result<()> {
-  starboard::remove_star(ctx, database, remove_reaction).await?;
+  if features.enabled(remove_reaction.guild_id, Feature::Starboard) {
+    starboard::remove_star(ctx, database, remove_reaction).await?;
+  }
 
   Ok(())
 }
@@ -0,0 +1,34 @@
+use anyhow::Result;
+use poise::serenity_prelude::VoiceState;
+
+use crate::data::community_event::AttendanceSource;
+use crate::database::DatabaseHandler;
+use crate::events::helpers::event_attendance;
+
+pub async fn voice_state_update(
+  database: &DatabaseHandler,
+  old: Option<&VoiceState>,
+  new: &VoiceState,
+) -> Result<()> {
+  let (Some(guild_id), Some(channel_id)) = (new.guild_id, new.channel_id) else {
+    return Ok(());
+  };
+
+  // Only joining a channel counts, not muting, deafening, etc.
+  if old.is_some_and(|old| old.channel_id == Some(channel_id)) {
+    return Ok(());
+  }
+
+  if new.member.as_ref().is_some_and(|member| member.user.bot) {
+    return Ok(());
+  }
+
+  event_attendance::record(
+    database,
+    guild_id,
+    channel_id,
+    new.user_id,
+    AttendanceSource::Voice,
+  )
+  .await
+}
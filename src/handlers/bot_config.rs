@@ -0,0 +1,470 @@
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::io::ErrorKind;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, PoisonError, RwLock};
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Timelike, Utc};
+use poise::Command;
+use serde::Deserialize;
+
+use crate::data::guild_feature::Feature;
+
+/// The file read when `BLOOMBOT_CONFIG` isn't set, relative to the working directory.
+const DEFAULT_PATH: &str = "bloombot.toml";
+
+/// Non-secret settings, read from an optional TOML file. Every setting has a default, so
+/// the file only needs to include the settings being changed. Secrets, such as tokens and
+/// the database URL, stay in environment variables.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BotConfig {
+  pub features: FeatureDefaults,
+  pub rate_limits: RateLimits,
+  pub charts: Charts,
+  pub quiet_hours: Option<QuietHours>,
+  pub openai: OpenAI,
+  pub transcription: Transcription,
+  pub web: Web,
+  pub errors: Errors,
+}
+
+/// Whether each [`Feature`] is on in servers which haven't turned it on or off with
+/// `/config features`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FeatureDefaults {
+  pub starboard: bool,
+  pub quotes: bool,
+  pub streak_announcements: bool,
+  pub ai_search: bool,
+}
+
+impl Default for FeatureDefaults {
+  fn default() -> Self {
+    Self {
+      starboard: true,
+      quotes: true,
+      streak_announcements: true,
+      ai_search: true,
+    }
+  }
+}
+
+impl FeatureDefaults {
+  pub fn enabled(&self, feature: Feature) -> bool {
+    match feature {
+      Feature::Starboard => self.starboard,
+      Feature::Quotes => self.quotes,
+      Feature::StreakAnnouncements => self.streak_announcements,
+      Feature::AiSearch => self.ai_search,
+    }
+  }
+}
+
+/// A command's cooldowns in seconds, for each user, channel, and server. A cooldown given
+/// as just a number of seconds is a per-user cooldown.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(from = "CooldownSetting")]
+pub struct Cooldown {
+  pub user: Option<u64>,
+  pub channel: Option<u64>,
+  pub guild: Option<u64>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum CooldownSetting {
+  User(u64),
+  Scoped(ScopedCooldown),
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ScopedCooldown {
+  user: Option<u64>,
+  channel: Option<u64>,
+  guild: Option<u64>,
+}
+
+impl From<CooldownSetting> for Cooldown {
+  fn from(setting: CooldownSetting) -> Self {
+    match setting {
+      CooldownSetting::User(seconds) => Self {
+        user: Some(seconds),
+        ..Default::default()
+      },
+      CooldownSetting::Scoped(ScopedCooldown {
+        user,
+        channel,
+        guild,
+      }) => Self {
+        user,
+        channel,
+        guild,
+      },
+    }
+  }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RateLimits {
+  /// Cooldowns keyed by the command's full name, such as `"glossary search"`.
+  pub cooldowns: HashMap<String, Cooldown>,
+}
+
+impl RateLimits {
+  /// Applies the configured cooldowns to `commands` and their subcommands. Commands without
+  /// a configured cooldown have theirs removed, so that removing a cooldown from the file
+  /// takes effect on reload. Per-member cooldowns set on commands themselves are kept.
+  pub fn apply<U, E>(&self, commands: &[Command<U, E>]) {
+    for command in commands {
+      let cooldown = self
+        .cooldowns
+        .get(&command.qualified_name)
+        .copied()
+        .unwrap_or_default();
+
+      let mut config = command
+        .cooldown_config
+        .write()
+        .unwrap_or_else(PoisonError::into_inner);
+      config.user = cooldown.user.map(Duration::from_secs);
+      config.channel = cooldown.channel.map(Duration::from_secs);
+      config.guild = cooldown.guild.map(Duration::from_secs);
+      drop(config);
+
+      self.apply(&command.subcommands);
+    }
+  }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChartTheme {
+  #[default]
+  Dark,
+  Light,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Charts {
+  /// The theme used for `/stats` charts when members don't choose one.
+  pub default_theme: ChartTheme,
+}
+
+/// Hours during which scheduled announcements, such as goal progress and "most improved"
+/// shout-outs, are held until the quiet hours end. Hours are in UTC, from `start` up to but
+/// not including `end`, and may wrap around midnight.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct QuietHours {
+  pub start: u32,
+  pub end: u32,
+}
+
+impl QuietHours {
+  pub fn contains(&self, now: DateTime<Utc>) -> bool {
+    let hour = now.hour();
+    if self.start <= self.end {
+      (self.start..self.end).contains(&hour)
+    } else {
+      hour >= self.start || hour < self.end
+    }
+  }
+}
+
+/// Settings for [`OpenAIHandler`][crate::embeddings::OpenAIHandler]. The `OPENAI_TIMEOUT`
+/// and `OPENAI_MAX_RETRIES` environment variables take precedence when set. These are read
+/// on startup, so changes require a restart.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OpenAI {
+  /// The time to wait for a single request, in seconds.
+  pub timeout: Option<u64>,
+  /// The number of times to retry a request after a transient error.
+  pub max_retries: Option<u32>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TranscriptionProvider {
+  #[default]
+  Disabled,
+  /// OpenAI's speech-to-text API, or any server compatible with it.
+  OpenAI,
+}
+
+/// Settings for [`TranscriptionHandler`][crate::transcription::TranscriptionHandler], used
+/// to transcribe community events with `/event transcript`. The API key is read from the
+/// `TRANSCRIPTION_API_KEY` environment variable, or `OPENAI_API_KEY` if that isn't set.
+/// These are read on startup, so changes require a restart.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Transcription {
+  /// The speech-to-text service used. Transcription is disabled unless one is chosen.
+  pub provider: TranscriptionProvider,
+  /// The base URL of an OpenAI-compatible API, such as a self-hosted Whisper server.
+  pub api_base: Option<String>,
+  /// The speech-to-text model, `whisper-1` if not set.
+  pub model: Option<String>,
+  /// The language spoken in events, as an ISO 639-1 code such as `en`. Detected
+  /// automatically if not set.
+  pub language: Option<String>,
+}
+
+/// Settings for the web server, which serves the calendar feeds members subscribe to with
+/// `/event calendar`, and the webhook members log sessions with using tokens from
+/// `/token create`.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Web {
+  /// The address the server listens on, such as `0.0.0.0:8080`. The server isn't started
+  /// unless this is set. Changes require a restart.
+  pub listen: Option<SocketAddr>,
+  /// The URL members reach the server at, such as `https://bloom.example.com`, usually
+  /// through a reverse proxy. Feeds and tokens aren't offered unless this is set.
+  pub public_url: Option<String>,
+}
+
+impl Web {
+  /// The URL of the calendar feed with the given token, if feeds are being served.
+  pub fn calendar_url(&self, token: &str) -> Option<String> {
+    self
+      .public_url
+      .as_ref()
+      .map(|public_url| format!("{}/calendar/{token}.ics", public_url.trim_end_matches('/')))
+  }
+
+  /// The URL of the session webhook, if it's being served.
+  pub fn session_url(&self) -> Option<String> {
+    self
+      .public_url
+      .as_ref()
+      .map(|public_url| format!("{}/webhooks/session", public_url.trim_end_matches('/')))
+  }
+}
+
+/// How command failures are reported.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Errors {
+  /// Whether command failures are posted to the log channel, along with their incident ID.
+  pub post_to_log_channel: bool,
+}
+
+impl Default for Errors {
+  fn default() -> Self {
+    Self {
+      post_to_log_channel: true,
+    }
+  }
+}
+
+impl BotConfig {
+  /// Whether scheduled announcements should be held, because it's during quiet hours.
+  pub fn is_quiet(&self, now: DateTime<Utc>) -> bool {
+    self
+      .quiet_hours
+      .is_some_and(|quiet_hours| quiet_hours.contains(now))
+  }
+
+  fn parse(content: &str) -> Result<Self> {
+    let config: Self = toml::from_str(content)?;
+
+    if let Some(quiet_hours) = config.quiet_hours {
+      if quiet_hours.start > 23 || quiet_hours.end > 23 {
+        bail!("Quiet hours must be between 0 and 23");
+      }
+    }
+
+    Ok(config)
+  }
+
+  /// Reads the config file at `path`, or uses the defaults if there isn't one.
+  ///
+  /// # Errors
+  /// Returns an error if the file can't be read or isn't valid.
+  pub fn load(path: &Path) -> Result<Self> {
+    match fs::read_to_string(path) {
+      Ok(content) => {
+        Self::parse(&content).with_context(|| format!("Invalid config file {}", path.display()))
+      }
+      Err(e) if e.kind() == ErrorKind::NotFound => Ok(Self::default()),
+      Err(e) => Err(e).with_context(|| format!("Failed to read config file {}", path.display())),
+    }
+  }
+}
+
+/// Keeps the [`BotConfig`] in memory, so it can be read anywhere and reloaded with
+/// `/config reload` without restarting.
+pub struct BotConfigHandler {
+  path: PathBuf,
+  config: RwLock<Arc<BotConfig>>,
+}
+
+impl BotConfigHandler {
+  /// Loads the config file named in the optional `BLOOMBOT_CONFIG` environment variable,
+  /// or `bloombot.toml` in the working directory.
+  ///
+  /// # Errors
+  /// Returns an error if the file exists but can't be read or isn't valid.
+  pub fn new() -> Result<Self> {
+    let path = match env::var("BLOOMBOT_CONFIG") {
+      Ok(path) if !path.is_empty() => PathBuf::from(path),
+      _ => PathBuf::from(DEFAULT_PATH),
+    };
+    let config = BotConfig::load(&path)?;
+
+    Ok(Self {
+      path,
+      config: RwLock::new(Arc::new(config)),
+    })
+  }
+
+  pub fn from_config(config: BotConfig) -> Self {
+    Self {
+      path: PathBuf::from(DEFAULT_PATH),
+      config: RwLock::new(Arc::new(config)),
+    }
+  }
+
+  pub fn path(&self) -> &Path {
+    &self.path
+  }
+
+  pub fn get(&self) -> Arc<BotConfig> {
+    self
+      .config
+      .read()
+      .unwrap_or_else(PoisonError::into_inner)
+      .clone()
+  }
+
+  /// Re-reads the config file. If it isn't valid, the current config is kept.
+  ///
+  /// # Errors
+  /// Returns an error if the file exists but can't be read or isn't valid.
+  pub fn reload(&self) -> Result<Arc<BotConfig>> {
+    let config = Arc::new(BotConfig::load(&self.path)?);
+    *self.config.write().unwrap_or_else(PoisonError::into_inner) = config.clone();
+
+    Ok(config)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use chrono::NaiveDate;
+
+  #[test]
+  fn test_parse() -> Result<()> {
+    assert_eq!(BotConfig::parse("")?, BotConfig::default());
+
+    let config = BotConfig::parse(
+      r#"
+      [features]
+      ai_search = false
+
+      [rate_limits.cooldowns]
+      "glossary search" = 10
+      quote = { user = 30, channel = 10 }
+
+      [charts]
+      default_theme = "light"
+
+      [quiet_hours]
+      start = 22
+      end = 7
+
+      [transcription]
+      provider = "openai"
+      language = "en"
+
+      [web]
+      listen = "127.0.0.1:8080"
+      public_url = "https://example.com/"
+
+      [errors]
+      post_to_log_channel = false
+      "#,
+    )?;
+    assert!(!config.features.enabled(Feature::AiSearch));
+    assert!(config.features.enabled(Feature::Starboard));
+    assert_eq!(
+      config.rate_limits.cooldowns.get("glossary search"),
+      Some(&Cooldown {
+        user: Some(10),
+        ..Default::default()
+      })
+    );
+    assert_eq!(
+      config.rate_limits.cooldowns.get("quote"),
+      Some(&Cooldown {
+        user: Some(30),
+        channel: Some(10),
+        guild: None,
+      })
+    );
+    assert_eq!(config.charts.default_theme, ChartTheme::Light);
+    assert_eq!(config.quiet_hours, Some(QuietHours { start: 22, end: 7 }));
+    assert_eq!(config.openai, OpenAI::default());
+    assert_eq!(config.transcription.provider, TranscriptionProvider::OpenAI);
+    assert_eq!(config.transcription.language.as_deref(), Some("en"));
+    assert_eq!(
+      BotConfig::default().transcription.provider,
+      TranscriptionProvider::Disabled
+    );
+    assert_eq!(
+      config.web.listen,
+      Some(SocketAddr::from(([127, 0, 0, 1], 8080)))
+    );
+    assert_eq!(
+      config.web.calendar_url("abc123").as_deref(),
+      Some("https://example.com/calendar/abc123.ics")
+    );
+    assert_eq!(BotConfig::default().web.calendar_url("abc123"), None);
+    assert_eq!(
+      config.web.session_url().as_deref(),
+      Some("https://example.com/webhooks/session")
+    );
+    assert!(!config.errors.post_to_log_channel);
+    assert!(BotConfig::default().errors.post_to_log_channel);
+
+    assert!(BotConfig::parse("[features]\nstarbord = false").is_err());
+    assert!(BotConfig::parse("[quiet_hours]\nstart = 22\nend = 24").is_err());
+    assert!(BotConfig::parse("[rate_limits.cooldowns]\nquote = { server = 10 }").is_err());
+    assert!(BotConfig::parse("[transcription]\nprovider = \"whisperx\"").is_err());
+
+    Ok(())
+  }
+
+  #[test]
+  fn test_quiet_hours_contains() {
+    let at = |hour| {
+      NaiveDate::from_ymd_opt(2024, 11, 6)
+        .and_then(|date| date.and_hms_opt(hour, 30, 0))
+        .unwrap_or_default()
+        .and_utc()
+    };
+
+    let overnight = QuietHours { start: 22, end: 7 };
+    assert!(overnight.contains(at(23)));
+    assert!(overnight.contains(at(0)));
+    assert!(overnight.contains(at(6)));
+    assert!(!overnight.contains(at(7)));
+    assert!(!overnight.contains(at(12)));
+
+    let afternoon = QuietHours { start: 12, end: 14 };
+    assert!(afternoon.contains(at(13)));
+    assert!(!afternoon.contains(at(14)));
+    assert!(!afternoon.contains(at(11)));
+  }
+}
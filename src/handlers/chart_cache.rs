@@ -0,0 +1,204 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, PoisonError, RwLock};
+use std::time::{Duration, Instant};
+
+use chrono::NaiveDate;
+use poise::serenity_prelude::GuildId;
+
+use crate::data::stats::Timeframe as TimeframeStats;
+
+/// How long a rendered chart is reused. Charts are also keyed by their data, so new
+/// meditation time is shown right away, and this only limits how long unused charts are kept.
+const CHART_TTL: Duration = Duration::from_secs(60 * 5);
+
+/// Identifies a rendered chart by everything which affects how it looks.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ChartKey {
+  guild_id: GuildId,
+  /// The day the chart was rendered, since the axis labels depend on it.
+  date: NaiveDate,
+  options: String,
+  data_hash: u64,
+}
+
+impl ChartKey {
+  /// Creates a key for a chart of `stats`, where `options` describes the chart's
+  /// timeframe, type, style, and theme.
+  pub fn new<'a>(
+    guild_id: GuildId,
+    date: NaiveDate,
+    options: String,
+    stats: impl IntoIterator<Item = &'a TimeframeStats>,
+  ) -> Self {
+    let mut hasher = DefaultHasher::new();
+    for stat in stats {
+      stat.sum.hash(&mut hasher);
+      stat.count.hash(&mut hasher);
+    }
+
+    Self {
+      guild_id,
+      date,
+      options,
+      data_hash: hasher.finish(),
+    }
+  }
+}
+
+/// A rendered chart, as the image itself, for attaching, or as a link to it in object storage.
+/// Charts which aren't `embeddable` can't be shown as embed images.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CachedImage {
+  Attachment {
+    filename: String,
+    bytes: Arc<Vec<u8>>,
+    embeddable: bool,
+  },
+  Url {
+    url: String,
+    embeddable: bool,
+  },
+}
+
+struct CachedChart {
+  image: CachedImage,
+  rendered_at: Instant,
+}
+
+/// Keeps recently rendered server charts in memory, so members asking for the same chart
+/// within [`CHART_TTL`] get it without it being rendered again.
+pub struct ChartCacheHandler {
+  charts: RwLock<HashMap<ChartKey, CachedChart>>,
+  ttl: Duration,
+}
+
+impl Default for ChartCacheHandler {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl ChartCacheHandler {
+  pub fn new() -> Self {
+    Self::with_ttl(CHART_TTL)
+  }
+
+  fn with_ttl(ttl: Duration) -> Self {
+    Self {
+      charts: RwLock::new(HashMap::new()),
+      ttl,
+    }
+  }
+
+  pub fn get(&self, key: &ChartKey) -> Option<CachedImage> {
+    self.get_at(key, Instant::now())
+  }
+
+  /// Caches a chart, removing any which have expired.
+  pub fn insert(&self, key: ChartKey, image: CachedImage) {
+    self.insert_at(key, image, Instant::now());
+  }
+
+  fn get_at(&self, key: &ChartKey, now: Instant) -> Option<CachedImage> {
+    self
+      .charts
+      .read()
+      .unwrap_or_else(PoisonError::into_inner)
+      .get(key)
+      .filter(|cached| now.duration_since(cached.rendered_at) < self.ttl)
+      .map(|cached| cached.image.clone())
+  }
+
+  fn insert_at(&self, key: ChartKey, image: CachedImage, now: Instant) {
+    let mut charts = self.charts.write().unwrap_or_else(PoisonError::into_inner);
+    charts.retain(|_, cached| now.duration_since(cached.rendered_at) < self.ttl);
+    charts.insert(
+      key,
+      CachedChart {
+        image,
+        rendered_at: now,
+      },
+    );
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn stats(sums: &[i64]) -> Vec<TimeframeStats> {
+    sums
+      .iter()
+      .map(|sum| TimeframeStats {
+        sum: Some(*sum),
+        count: Some(1),
+      })
+      .collect()
+  }
+
+  #[test]
+  fn test_chart_cache() {
+    let cache = ChartCacheHandler::with_ttl(Duration::from_secs(60));
+    let guild_id = GuildId::new(123u64);
+    let date = NaiveDate::from_ymd_opt(2024, 11, 6).unwrap_or_default();
+    let key = ChartKey::new(
+      guild_id,
+      date,
+      "daily minutes".to_owned(),
+      &stats(&[10, 20]),
+    );
+    let now = Instant::now();
+
+    assert!(cache.get_at(&key, now).is_none());
+
+    let image = CachedImage::Url {
+      url: "https://example.com/chart.webp".to_owned(),
+      embeddable: true,
+    };
+    cache.insert_at(key.clone(), image.clone(), now);
+    assert_eq!(
+      cache.get_at(&key, now + Duration::from_secs(30)),
+      Some(image.clone())
+    );
+    assert!(cache.get_at(&key, now + Duration::from_secs(60)).is_none());
+
+    // Charts with different data, options, or dates aren't reused
+    let changed = [
+      ChartKey::new(
+        guild_id,
+        date,
+        "daily minutes".to_owned(),
+        &stats(&[10, 25]),
+      ),
+      ChartKey::new(guild_id, date, "daily count".to_owned(), &stats(&[10, 20])),
+      ChartKey::new(
+        guild_id,
+        date.succ_opt().unwrap_or_default(),
+        "daily minutes".to_owned(),
+        &stats(&[10, 20]),
+      ),
+      ChartKey::new(
+        GuildId::new(456u64),
+        date,
+        "daily minutes".to_owned(),
+        &stats(&[10, 20]),
+      ),
+    ];
+    for other in &changed {
+      assert!(cache.get_at(other, now).is_none());
+    }
+
+    // Expired charts are removed when another is cached
+    cache.insert_at(changed[0].clone(), image, now + Duration::from_secs(90));
+    assert_eq!(
+      cache
+        .charts
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .len(),
+      1
+    );
+  }
+}
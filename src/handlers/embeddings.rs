@@ -1,42 +1,131 @@
 use std::env;
+use std::sync::Arc;
+use std::time::Duration;
 
 use anyhow::{anyhow, Context, Result};
+use async_openai::error::OpenAIError;
 use async_openai::{config::OpenAIConfig, types::CreateEmbeddingRequestArgs, Client};
-use poise::serenity_prelude::UserId;
+use chrono::{Datelike, Utc};
+use log::{error, warn};
+use poise::serenity_prelude::{GuildId, UserId};
+use tokio::time;
+
+use crate::bot_config::OpenAI;
+use crate::database::DatabaseHandler;
+
+/// The default time to wait for a single request, in seconds.
+const DEFAULT_TIMEOUT: u64 = 10;
+/// The default number of times to retry a request after a transient error.
+const DEFAULT_MAX_RETRIES: u32 = 3;
+/// The delay before the first retry, doubled for each subsequent retry.
+const INITIAL_RETRY_DELAY: Duration = Duration::from_millis(500);
 
 pub struct OpenAIHandler {
   client: Client<OpenAIConfig>,
+  db: Arc<DatabaseHandler>,
+  timeout: Duration,
+  max_retries: u32,
 }
 
 impl OpenAIHandler {
   /// Creates and configures a client to interact with the [OpenAI API], using the default
   /// v1 API base url and an API key specified in the `OPENAI_API_KEY` environment variable.
+  /// Token usage is recorded per guild using the provided [`DatabaseHandler`].
+  ///
+  /// The timeout for each request, in seconds, and the number of retries after transient
+  /// errors are taken from the optional `OPENAI_TIMEOUT` and `OPENAI_MAX_RETRIES` environment
+  /// variables, respectively, or otherwise from the `[openai]` section of the config file.
   ///
   /// # Errors
-  /// Returns an error if the `OPENAI_API_KEY` environment variable is missing.
+  /// Returns an error if the `OPENAI_API_KEY` environment variable is missing, or if the
+  /// optional variables are not valid numbers.
   ///
   /// [OpenAI API]: https://platform.openai.com/docs/api-reference/introduction
-  pub fn new() -> Result<Self> {
+  pub fn new(db: Arc<DatabaseHandler>, config: &OpenAI) -> Result<Self> {
     let api_key =
       env::var("OPENAI_API_KEY").with_context(|| "Missing OPENAI_API_KEY environment variable")?;
     let config = OpenAIConfig::new().with_api_key(api_key);
     let client = Client::with_config(config);
 
-    Ok(Self { client })
+    let timeout = match env::var("OPENAI_TIMEOUT") {
+      Ok(timeout) if !timeout.is_empty() => timeout
+        .parse::<u64>()
+        .with_context(|| "OPENAI_TIMEOUT must be a number of seconds")?,
+      _ => config.timeout.unwrap_or(DEFAULT_TIMEOUT),
+    };
+    let max_retries = match env::var("OPENAI_MAX_RETRIES") {
+      Ok(max_retries) if !max_retries.is_empty() => max_retries
+        .parse::<u32>()
+        .with_context(|| "OPENAI_MAX_RETRIES must be a number")?,
+      _ => config.max_retries.unwrap_or(DEFAULT_MAX_RETRIES),
+    };
+
+    Ok(Self {
+      client,
+      db,
+      timeout: Duration::from_secs(timeout),
+      max_retries,
+    })
   }
 
   /// Creates an embedding vector representing the input text, using a ``UserID`` as the unique end-user identifier.
+  /// The tokens used are recorded against the guild's usage.
+  ///
+  /// Requests which time out or fail with a transient error are retried with exponential
+  /// backoff, up to the configured number of retries.
   ///
   /// # Errors
-  /// Returns an error if more than one embedding was generated.
-  pub async fn create_embedding(&self, input: String, user: UserId) -> Result<Vec<f32>> {
+  /// Returns an error if the request failed after all retries, or if more than one embedding
+  /// was generated.
+  pub async fn create_embedding(
+    &self,
+    input: String,
+    guild_id: GuildId,
+    user: UserId,
+  ) -> Result<Vec<f32>> {
     let request = CreateEmbeddingRequestArgs::default()
       .model("text-embedding-ada-002")
       .input(input)
       .user(user.to_string())
       .build()?;
 
-    let embeddings = self.client.embeddings().create(request).await?;
+    let mut retries = 0;
+    let mut retry_delay = INITIAL_RETRY_DELAY;
+
+    let embeddings = loop {
+      let error = match time::timeout(
+        self.timeout,
+        self.client.embeddings().create(request.clone()),
+      )
+      .await
+      {
+        Ok(Ok(embeddings)) => break embeddings,
+        Ok(Err(e)) if !is_transient(&e) => return Err(e.into()),
+        Ok(Err(e)) => anyhow!(e),
+        Err(_) => anyhow!(
+          "Embedding request timed out after {}s",
+          self.timeout.as_secs()
+        ),
+      };
+
+      if retries >= self.max_retries {
+        return Err(error.context(format!("Embedding request failed after {retries} retries")));
+      }
+
+      retries += 1;
+      warn!("Embedding request failed, retrying in {retry_delay:?} (retry {retries}): {error:?}");
+
+      time::sleep(retry_delay).await;
+      retry_delay *= 2;
+    };
+
+    // Failing to record usage shouldn't prevent the embedding from being used
+    if let Err(e) = self
+      .record_usage(guild_id, embeddings.usage.total_tokens)
+      .await
+    {
+      error!("Error recording OpenAI usage for guild {guild_id}: {e:?}");
+    }
 
     let embedding = match embeddings.data.len() {
       1 => embeddings.data[0].embedding.clone(),
@@ -50,4 +139,59 @@ impl OpenAIHandler {
 
     Ok(embedding)
   }
+
+  /// Checks that the [OpenAI API] can be reached with the configured API key, by listing the
+  /// available models. This doesn't use any tokens, so it isn't recorded as usage.
+  ///
+  /// # Errors
+  /// Returns an error if the request failed or timed out.
+  ///
+  /// [OpenAI API]: https://platform.openai.com/docs/api-reference/models/list
+  pub async fn check_connection(&self) -> Result<()> {
+    time::timeout(self.timeout, self.client.models().list())
+      .await
+      .map_err(|_| anyhow!("Request timed out after {}s", self.timeout.as_secs()))??;
+
+    Ok(())
+  }
+
+  async fn record_usage(&self, guild_id: GuildId, tokens: u32) -> Result<()> {
+    let mut conn = self.db.get_connection_with_retry(5).await?;
+    DatabaseHandler::record_ai_usage(&mut conn, &guild_id, i64::from(tokens)).await
+  }
+
+  /// Checks whether the estimated spend for the guild this month (UTC) has reached the
+  /// guild's soft monthly cap, if one has been set.
+  pub async fn monthly_cap_reached(&self, guild_id: GuildId) -> Result<bool> {
+    let mut transaction = self.db.start_transaction_with_retry(5).await?;
+
+    let Some(cap) = DatabaseHandler::get_guild_settings(&mut transaction, &guild_id)
+      .await?
+      .ai_monthly_cap
+    else {
+      return Ok(false);
+    };
+
+    let month_start = Utc::now()
+      .date_naive()
+      .with_day(1)
+      .with_context(|| "Failed to set day to 1")?;
+    let usage = DatabaseHandler::get_ai_usage(&mut transaction, &guild_id, month_start).await?;
+
+    Ok(usage.cost() >= cap)
+  }
+}
+
+/// Checks whether an [`OpenAIError`] is likely to be resolved by retrying the request, such
+/// as a network error or a server-side error, as opposed to a problem with the request itself.
+fn is_transient(error: &OpenAIError) -> bool {
+  match error {
+    OpenAIError::Reqwest(_) => true,
+    // Invalid requests (including invalid API keys) and exceeded quotas won't succeed on retry
+    OpenAIError::ApiError(api_error) => !matches!(
+      api_error.r#type.as_deref(),
+      Some("invalid_request_error" | "insufficient_quota")
+    ),
+    _ => false,
+  }
 }
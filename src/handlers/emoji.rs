@@ -0,0 +1,215 @@
+use std::collections::HashMap;
+use std::sync::{Arc, PoisonError, RwLock};
+
+use anyhow::Result;
+use poise::serenity_prelude::GuildId;
+
+use crate::config::{EMOJI, MEDITATION_MIND};
+use crate::data::guild_emoji::{EmojiName, GuildEmoji};
+use crate::database::DatabaseHandler;
+
+/// The emoji Bloom uses in a server's messages. Fields are named after the custom emoji in
+/// [`EMOJI`], and can be used directly in `format!`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EmojiSet {
+  pub pepeglow: String,
+  pub aww: String,
+  pub loveit: String,
+  pub mminfo: String,
+  pub mmx: String,
+  pub mmcheck: String,
+}
+
+impl EmojiSet {
+  /// Unicode emoji, which work in every server and in DMs.
+  pub fn fallback() -> Self {
+    Self {
+      pepeglow: "✨".to_owned(),
+      aww: "🥰".to_owned(),
+      loveit: "😍".to_owned(),
+      mminfo: "ℹ️".to_owned(),
+      mmx: "❌".to_owned(),
+      mmcheck: "✅".to_owned(),
+    }
+  }
+
+  /// The custom emoji in [`EMOJI`], which belong to the Meditation Mind server.
+  pub fn custom() -> Self {
+    Self {
+      pepeglow: EMOJI.pepeglow.to_string(),
+      aww: EMOJI.aww.to_string(),
+      loveit: EMOJI.loveit.to_string(),
+      mminfo: EMOJI.mminfo.to_string(),
+      mmx: EMOJI.mmx.to_string(),
+      mmcheck: EMOJI.mmcheck.to_string(),
+    }
+  }
+
+  /// The emoji used in a server which hasn't replaced any. Custom emoji only work in the
+  /// server they belong to, so other servers get the Unicode fallbacks.
+  pub fn default_for(guild_id: GuildId) -> Self {
+    if guild_id == MEDITATION_MIND {
+      Self::custom()
+    } else {
+      Self::fallback()
+    }
+  }
+
+  /// The server's emoji, with its replacements applied to the defaults.
+  pub fn for_guild(guild_id: GuildId, replacements: &[GuildEmoji]) -> Self {
+    let mut set = Self::default_for(guild_id);
+    for replacement in replacements {
+      *set.get_mut(replacement.name) = replacement.emoji.clone();
+    }
+    set
+  }
+
+  pub fn get(&self, name: EmojiName) -> &str {
+    match name {
+      EmojiName::Info => &self.mminfo,
+      EmojiName::Check => &self.mmcheck,
+      EmojiName::Cross => &self.mmx,
+      EmojiName::Glow => &self.pepeglow,
+      EmojiName::Aww => &self.aww,
+      EmojiName::LoveIt => &self.loveit,
+    }
+  }
+
+  fn get_mut(&mut self, name: EmojiName) -> &mut String {
+    match name {
+      EmojiName::Info => &mut self.mminfo,
+      EmojiName::Check => &mut self.mmcheck,
+      EmojiName::Cross => &mut self.mmx,
+      EmojiName::Glow => &mut self.pepeglow,
+      EmojiName::Aww => &mut self.aww,
+      EmojiName::LoveIt => &mut self.loveit,
+    }
+  }
+}
+
+/// Keeps each server's emoji in memory, so messages don't need a database query to find
+/// them. Servers without replacements aren't stored, and use [`EmojiSet::default_for`].
+pub struct EmojiHandler {
+  guilds: RwLock<HashMap<GuildId, Arc<EmojiSet>>>,
+  fallback: Arc<EmojiSet>,
+  custom: Arc<EmojiSet>,
+}
+
+impl EmojiHandler {
+  /// Loads every server's replacement emoji from the database.
+  ///
+  /// # Errors
+  /// Returns an error if the replacements can't be retrieved.
+  pub async fn new(db: &DatabaseHandler) -> Result<Self> {
+    let mut transaction = db.start_transaction_with_retry(5).await?;
+    let replacements = DatabaseHandler::get_every_guild_emoji(&mut transaction).await?;
+    drop(transaction);
+
+    Ok(Self::from_replacements(replacements))
+  }
+
+  fn from_replacements(replacements: Vec<GuildEmoji>) -> Self {
+    let mut by_guild: HashMap<GuildId, Vec<GuildEmoji>> = HashMap::new();
+    for replacement in replacements {
+      by_guild
+        .entry(replacement.guild_id)
+        .or_default()
+        .push(replacement);
+    }
+
+    let guilds = by_guild
+      .into_iter()
+      .map(|(guild_id, replacements)| {
+        (
+          guild_id,
+          Arc::new(EmojiSet::for_guild(guild_id, &replacements)),
+        )
+      })
+      .collect();
+
+    Self {
+      guilds: RwLock::new(guilds),
+      fallback: Arc::new(EmojiSet::fallback()),
+      custom: Arc::new(EmojiSet::custom()),
+    }
+  }
+
+  /// Returns the emoji for a server, or the Unicode fallbacks outside of servers.
+  pub fn get(&self, guild_id: Option<GuildId>) -> Arc<EmojiSet> {
+    let Some(guild_id) = guild_id else {
+      return Arc::clone(&self.fallback);
+    };
+
+    if let Some(set) = self
+      .guilds
+      .read()
+      .unwrap_or_else(PoisonError::into_inner)
+      .get(&guild_id)
+    {
+      return Arc::clone(set);
+    }
+
+    if guild_id == MEDITATION_MIND {
+      Arc::clone(&self.custom)
+    } else {
+      Arc::clone(&self.fallback)
+    }
+  }
+
+  /// Reloads a server's emoji from the database, after its replacements have changed.
+  ///
+  /// # Errors
+  /// Returns an error if the replacements can't be retrieved.
+  pub async fn reload(&self, db: &DatabaseHandler, guild_id: GuildId) -> Result<()> {
+    let mut transaction = db.start_transaction_with_retry(5).await?;
+    let replacements = DatabaseHandler::get_guild_emoji(&mut transaction, &guild_id).await?;
+    drop(transaction);
+
+    self.replace(guild_id, &replacements);
+
+    Ok(())
+  }
+
+  fn replace(&self, guild_id: GuildId, replacements: &[GuildEmoji]) {
+    let mut guilds = self.guilds.write().unwrap_or_else(PoisonError::into_inner);
+    if replacements.is_empty() {
+      guilds.remove(&guild_id);
+    } else {
+      guilds.insert(
+        guild_id,
+        Arc::new(EmojiSet::for_guild(guild_id, replacements)),
+      );
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_get() {
+    let other_guild = GuildId::new(123u64);
+    let handler = EmojiHandler::from_replacements(vec![GuildEmoji::new(
+      other_guild,
+      EmojiName::Check,
+      "<:done:456>".to_owned(),
+    )]);
+
+    // Custom emoji are only used in the server they belong to
+    assert_eq!(*handler.get(Some(MEDITATION_MIND)), EmojiSet::custom());
+    assert_eq!(
+      *handler.get(Some(GuildId::new(789u64))),
+      EmojiSet::fallback()
+    );
+    assert_eq!(*handler.get(None), EmojiSet::fallback());
+
+    // Replacements only affect the emoji they replace
+    let set = handler.get(Some(other_guild));
+    assert_eq!(set.mmcheck, "<:done:456>");
+    assert_eq!(set.mminfo, EmojiSet::fallback().mminfo);
+
+    handler.replace(other_guild, &[]);
+    assert_eq!(*handler.get(Some(other_guild)), EmojiSet::fallback());
+  }
+}
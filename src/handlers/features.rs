@@ -0,0 +1,139 @@
+use std::collections::HashMap;
+use std::sync::{Arc, PoisonError, RwLock};
+
+use anyhow::Result;
+use poise::serenity_prelude::GuildId;
+
+use crate::bot_config::BotConfigHandler;
+use crate::data::guild_feature::{Feature, GuildFeature};
+use crate::database::DatabaseHandler;
+
+/// Keeps the features each server has turned on or off in memory, so they can be checked
+/// for every message or reaction without a database query.
+pub struct FeatureHandler {
+  guilds: RwLock<HashMap<GuildId, HashMap<Feature, bool>>>,
+  bot_config: Arc<BotConfigHandler>,
+}
+
+impl FeatureHandler {
+  /// Loads the features every server has turned on or off from the database. Servers which
+  /// haven't chosen use the defaults in the [`BotConfig`][crate::bot_config::BotConfig].
+  ///
+  /// # Errors
+  /// Returns an error if the features can't be retrieved.
+  pub async fn new(db: &DatabaseHandler, bot_config: Arc<BotConfigHandler>) -> Result<Self> {
+    let mut transaction = db.start_transaction_with_retry(5).await?;
+    let features = DatabaseHandler::get_every_guild_feature(&mut transaction).await?;
+    drop(transaction);
+
+    Ok(Self::from_features(features, bot_config))
+  }
+
+  fn from_features(features: Vec<GuildFeature>, bot_config: Arc<BotConfigHandler>) -> Self {
+    let mut guilds: HashMap<GuildId, HashMap<Feature, bool>> = HashMap::new();
+    for feature in features {
+      guilds
+        .entry(feature.guild_id)
+        .or_default()
+        .insert(feature.feature, feature.enabled);
+    }
+
+    Self {
+      guilds: RwLock::new(guilds),
+      bot_config,
+    }
+  }
+
+  /// Whether a feature is on in a server. Servers which haven't turned a feature on or off
+  /// use the configured default. Features are always on outside of servers.
+  pub fn enabled(&self, guild_id: Option<GuildId>, feature: Feature) -> bool {
+    let Some(guild_id) = guild_id else {
+      return true;
+    };
+
+    self
+      .guilds
+      .read()
+      .unwrap_or_else(PoisonError::into_inner)
+      .get(&guild_id)
+      .and_then(|features| features.get(&feature))
+      .copied()
+      .unwrap_or_else(|| self.bot_config.get().features.enabled(feature))
+  }
+
+  /// Reloads a server's features from the database, after they've been turned on or off.
+  ///
+  /// # Errors
+  /// Returns an error if the features can't be retrieved.
+  pub async fn reload(&self, db: &DatabaseHandler, guild_id: GuildId) -> Result<()> {
+    let mut transaction = db.start_transaction_with_retry(5).await?;
+    let features = DatabaseHandler::get_guild_features(&mut transaction, &guild_id).await?;
+    drop(transaction);
+
+    self.replace(guild_id, &features);
+
+    Ok(())
+  }
+
+  fn replace(&self, guild_id: GuildId, features: &[GuildFeature]) {
+    let features = features
+      .iter()
+      .map(|feature| (feature.feature, feature.enabled))
+      .collect();
+    self
+      .guilds
+      .write()
+      .unwrap_or_else(PoisonError::into_inner)
+      .insert(guild_id, features);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::bot_config::{BotConfig, FeatureDefaults};
+
+  #[test]
+  fn test_enabled() {
+    let guild_id = GuildId::new(123u64);
+    let handler = FeatureHandler::from_features(
+      vec![
+        GuildFeature::new(guild_id, Feature::Starboard, false),
+        GuildFeature::new(guild_id, Feature::Quotes, true),
+      ],
+      Arc::new(BotConfigHandler::from_config(BotConfig::default())),
+    );
+
+    assert!(!handler.enabled(Some(guild_id), Feature::Starboard));
+    assert!(handler.enabled(Some(guild_id), Feature::Quotes));
+    assert!(handler.enabled(Some(guild_id), Feature::AiSearch));
+    assert!(handler.enabled(Some(GuildId::new(456u64)), Feature::Starboard));
+    assert!(handler.enabled(None, Feature::Starboard));
+
+    handler.replace(
+      guild_id,
+      &[GuildFeature::new(guild_id, Feature::Starboard, true)],
+    );
+    assert!(handler.enabled(Some(guild_id), Feature::Starboard));
+  }
+
+  #[test]
+  fn test_enabled_defaults() {
+    let guild_id = GuildId::new(123u64);
+    let handler = FeatureHandler::from_features(
+      vec![GuildFeature::new(guild_id, Feature::AiSearch, true)],
+      Arc::new(BotConfigHandler::from_config(BotConfig {
+        features: FeatureDefaults {
+          ai_search: false,
+          ..FeatureDefaults::default()
+        },
+        ..BotConfig::default()
+      })),
+    );
+
+    assert!(handler.enabled(Some(guild_id), Feature::AiSearch));
+    assert!(!handler.enabled(Some(GuildId::new(456u64)), Feature::AiSearch));
+    assert!(handler.enabled(Some(GuildId::new(456u64)), Feature::Starboard));
+    assert!(handler.enabled(None, Feature::AiSearch));
+  }
+}
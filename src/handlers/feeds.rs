@@ -0,0 +1,306 @@
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use reqwest::{Client, Url};
+use roxmltree::{Document, Node};
+
+/// The time to wait for a feed to download.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(20);
+/// The largest feed which will be read, in bytes. Podcast feeds with years of episodes can
+/// be large, but anything past this is unlikely to be a feed.
+const MAX_FEED_SIZE: usize = 5 * 1024 * 1024;
+
+/// An RSS or Atom feed.
+#[derive(Debug)]
+pub struct Feed {
+  pub title: String,
+  /// The items in the feed, in the order the feed lists them, which is usually newest first.
+  pub items: Vec<FeedItem>,
+}
+
+/// An item in a feed, such as a talk or a blog post.
+#[derive(Debug, PartialEq)]
+pub struct FeedItem {
+  /// Identifies the item, so it's only posted once. Uses the item's GUID or ID, or its link
+  /// or title if it has neither.
+  pub key: String,
+  pub title: String,
+  pub link: Option<String>,
+  /// The item's description as plain text.
+  pub summary: Option<String>,
+  pub published: Option<DateTime<Utc>>,
+}
+
+/// Returns the trimmed text of the first child element called `name`, if it isn't empty.
+fn child_text(node: Node, name: &str) -> Option<String> {
+  node
+    .children()
+    .find(|child| child.has_tag_name(name))
+    .and_then(|child| child.text())
+    .map(str::trim)
+    .filter(|text| !text.is_empty())
+    .map(str::to_owned)
+}
+
+/// Converts an HTML description to plain text, collapsing whitespace.
+fn strip_html(html: &str) -> String {
+  let mut text = String::with_capacity(html.len());
+  let mut in_tag = false;
+  for c in html.chars() {
+    match c {
+      '<' => in_tag = true,
+      '>' if in_tag => {
+        in_tag = false;
+        text.push(' ');
+      }
+      _ if !in_tag => text.push(c),
+      _ => {}
+    }
+  }
+
+  let text = text
+    .replace("&nbsp;", " ")
+    .replace("&quot;", "\"")
+    .replace("&#39;", "'")
+    .replace("&lt;", "<")
+    .replace("&gt;", ">")
+    .replace("&amp;", "&");
+
+  text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn parse_date(date: &str) -> Option<DateTime<Utc>> {
+  DateTime::parse_from_rfc2822(date)
+    .or_else(|_| DateTime::parse_from_rfc3339(date))
+    .ok()
+    .map(|date| date.with_timezone(&Utc))
+}
+
+fn rss_item(item: Node) -> Option<FeedItem> {
+  let title = child_text(item, "title");
+  let link = child_text(item, "link");
+  let key = child_text(item, "guid")
+    .or_else(|| link.clone())
+    .or_else(|| title.clone())?;
+
+  Some(FeedItem {
+    key,
+    title: title.unwrap_or_else(|| "Untitled".to_owned()),
+    link,
+    summary: child_text(item, "description")
+      .map(|description| strip_html(&description))
+      .filter(|summary| !summary.is_empty()),
+    published: child_text(item, "pubDate")
+      .or_else(|| child_text(item, "date"))
+      .and_then(|date| parse_date(&date)),
+  })
+}
+
+fn atom_entry(entry: Node) -> Option<FeedItem> {
+  let title = child_text(entry, "title");
+  // Prefer the link to the page itself over links to enclosures or comments
+  let link = entry
+    .children()
+    .filter(|child| child.has_tag_name("link"))
+    .find(|link| link.attribute("rel").is_none_or(|rel| rel == "alternate"))
+    .and_then(|link| link.attribute("href"))
+    .map(str::to_owned);
+  let key = child_text(entry, "id")
+    .or_else(|| link.clone())
+    .or_else(|| title.clone())?;
+
+  Some(FeedItem {
+    key,
+    title: title.unwrap_or_else(|| "Untitled".to_owned()),
+    link,
+    summary: child_text(entry, "summary")
+      .or_else(|| child_text(entry, "content"))
+      .map(|summary| strip_html(&summary))
+      .filter(|summary| !summary.is_empty()),
+    published: child_text(entry, "published")
+      .or_else(|| child_text(entry, "updated"))
+      .and_then(|date| parse_date(&date)),
+  })
+}
+
+/// Parses an RSS (0.9x, 1.0, or 2.0) or Atom feed.
+///
+/// # Errors
+/// Returns an error if the document isn't XML, or isn't a feed.
+pub fn parse_feed(xml: &str) -> Result<Feed> {
+  let document = Document::parse(xml).with_context(|| "The feed is not valid XML")?;
+  let root = document.root_element();
+
+  match root.tag_name().name() {
+    "feed" => Ok(Feed {
+      title: child_text(root, "title").unwrap_or_else(|| "Untitled feed".to_owned()),
+      items: root
+        .children()
+        .filter(|child| child.has_tag_name("entry"))
+        .filter_map(atom_entry)
+        .collect(),
+    }),
+    // RSS 2.0 nests items in the channel, while RSS 1.0 places them alongside it
+    "rss" | "RDF" => {
+      let title = root
+        .descendants()
+        .find(|node| node.has_tag_name("channel"))
+        .and_then(|channel| child_text(channel, "title"))
+        .unwrap_or_else(|| "Untitled feed".to_owned());
+      Ok(Feed {
+        title,
+        items: root
+          .descendants()
+          .filter(|node| node.has_tag_name("item"))
+          .filter_map(rss_item)
+          .collect(),
+      })
+    }
+    _ => bail!("The document is not an RSS or Atom feed"),
+  }
+}
+
+/// Checks that a feed URL is a web address, returning it in normalized form.
+///
+/// # Errors
+/// Returns an error if the URL is invalid or isn't HTTP or HTTPS.
+pub fn parse_url(url: &str) -> Result<String> {
+  let url = Url::parse(url.trim()).with_context(|| "The feed URL is not a valid URL")?;
+  if !matches!(url.scheme(), "http" | "https") || url.host_str().is_none() {
+    bail!("The feed URL must be a web address starting with https:// or http://");
+  }
+
+  Ok(url.to_string())
+}
+
+/// Downloads RSS and Atom feeds.
+pub struct FeedHandler {
+  client: Client,
+}
+
+impl FeedHandler {
+  /// Creates the HTTP client used to download feeds.
+  ///
+  /// # Errors
+  /// Returns an error if the client can't be created.
+  pub fn new() -> Result<Self> {
+    let client = Client::builder()
+      .timeout(REQUEST_TIMEOUT)
+      .build()
+      .with_context(|| "Failed to create feed client")?;
+
+    Ok(Self { client })
+  }
+
+  /// Downloads and parses a feed.
+  ///
+  /// # Errors
+  /// Returns an error if the feed can't be downloaded, is too large, or isn't a feed.
+  pub async fn fetch(&self, url: &str) -> Result<Feed> {
+    let response = self.client.get(url).send().await?;
+    let status = response.status();
+    if !status.is_success() {
+      bail!("The feed returned status {status}");
+    }
+
+    let bytes = response.bytes().await?;
+    if bytes.len() > MAX_FEED_SIZE {
+      bail!("The feed is too large");
+    }
+
+    parse_feed(&String::from_utf8_lossy(&bytes))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_parse_rss() -> Result<()> {
+    let feed = parse_feed(
+      r#"<?xml version="1.0"?>
+      <rss version="2.0">
+        <channel>
+          <title>Dharma Talks</title>
+          <item>
+            <title>Patience</title>
+            <link>https://example.com/talks/patience</link>
+            <guid>talk-2</guid>
+            <description>&lt;p&gt;On &lt;b&gt;patience&lt;/b&gt; &amp;amp; kindness&lt;/p&gt;</description>
+            <pubDate>Fri, 22 Nov 2024 18:00:00 +0000</pubDate>
+          </item>
+          <item>
+            <title>Generosity</title>
+            <link>https://example.com/talks/generosity</link>
+          </item>
+        </channel>
+      </rss>"#,
+    )?;
+
+    assert_eq!(feed.title, "Dharma Talks");
+    assert_eq!(feed.items.len(), 2);
+    assert_eq!(feed.items[0].key, "talk-2");
+    assert_eq!(
+      feed.items[0].summary.as_deref(),
+      Some("On patience & kindness")
+    );
+    assert_eq!(
+      feed.items[0].published.map(|date| date.timestamp()),
+      Some(1_732_298_400)
+    );
+    assert_eq!(feed.items[1].key, "https://example.com/talks/generosity");
+    assert_eq!(feed.items[1].summary, None);
+
+    Ok(())
+  }
+
+  #[test]
+  fn test_parse_atom() -> Result<()> {
+    let feed = parse_feed(
+      r#"<?xml version="1.0" encoding="utf-8"?>
+      <feed xmlns="http://www.w3.org/2005/Atom">
+        <title>Meditation Blog</title>
+        <entry>
+          <title>Sitting with restlessness</title>
+          <link rel="enclosure" href="https://example.com/audio.mp3"/>
+          <link href="https://example.com/restlessness"/>
+          <id>urn:uuid:1225c695</id>
+          <updated>2024-11-20T09:30:00Z</updated>
+          <summary>Notes on restlessness.</summary>
+        </entry>
+      </feed>"#,
+    )?;
+
+    assert_eq!(feed.title, "Meditation Blog");
+    assert_eq!(
+      feed.items,
+      vec![FeedItem {
+        key: "urn:uuid:1225c695".to_owned(),
+        title: "Sitting with restlessness".to_owned(),
+        link: Some("https://example.com/restlessness".to_owned()),
+        summary: Some("Notes on restlessness.".to_owned()),
+        published: parse_date("2024-11-20T09:30:00Z"),
+      }]
+    );
+
+    Ok(())
+  }
+
+  #[test]
+  fn test_parse_invalid() {
+    assert!(parse_feed("<html><body>Not a feed</body></html>").is_err());
+    assert!(parse_feed("Not XML").is_err());
+  }
+
+  #[test]
+  fn test_parse_url() {
+    assert_eq!(
+      parse_url(" https://example.com/feed ").ok().as_deref(),
+      Some("https://example.com/feed")
+    );
+    assert!(parse_url("ftp://example.com/feed").is_err());
+    assert!(parse_url("example.com/feed").is_err());
+  }
+}
@@ -0,0 +1,209 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
+use std::time::{Duration, Instant};
+
+use poise::serenity_prelude::{
+  ComponentInteractionCollector, CreateInteractionResponse, GuildId, Http, ShardMessenger, UserId,
+};
+use tokio::task::JoinHandle;
+
+/// How often an import's progress message is updated.
+pub const PROGRESS_INTERVAL: Duration = Duration::from_secs(3);
+
+/// How long the Cancel button is listened for. Imports are expected to finish well before this.
+const CANCEL_TIMEOUT: Duration = Duration::from_secs(60 * 15);
+
+/// The progress of a running import, shared with its Cancel button.
+#[derive(Debug)]
+pub struct ImportJob {
+  total: AtomicUsize,
+  processed: AtomicUsize,
+  cancelled: AtomicBool,
+  /// When the total was set, which is when rows start being imported.
+  counting_since: OnceLock<Instant>,
+}
+
+impl ImportJob {
+  fn new() -> Self {
+    Self {
+      total: AtomicUsize::new(0),
+      processed: AtomicUsize::new(0),
+      cancelled: AtomicBool::new(false),
+      counting_since: OnceLock::new(),
+    }
+  }
+
+  /// Sets the number of rows to import, once the file has been read.
+  pub fn set_total(&self, total: usize) {
+    self.set_total_at(total, Instant::now());
+  }
+
+  fn set_total_at(&self, total: usize, now: Instant) {
+    self.total.store(total, Ordering::Relaxed);
+    let _ = self.counting_since.set(now);
+  }
+
+  pub fn total(&self) -> usize {
+    self.total.load(Ordering::Relaxed)
+  }
+
+  pub fn processed(&self) -> usize {
+    self.processed.load(Ordering::Relaxed)
+  }
+
+  /// Records that more rows have been imported.
+  pub fn advance(&self, rows: usize) {
+    self.processed.fetch_add(rows, Ordering::Relaxed);
+  }
+
+  pub fn cancel(&self) {
+    self.cancelled.store(true, Ordering::Relaxed);
+  }
+
+  pub fn is_cancelled(&self) -> bool {
+    self.cancelled.load(Ordering::Relaxed)
+  }
+
+  /// Estimates the time remaining from the rate rows have been imported so far, or `None`
+  /// if no rows have been imported yet.
+  pub fn eta(&self) -> Option<Duration> {
+    self.eta_at(Instant::now())
+  }
+
+  fn eta_at(&self, now: Instant) -> Option<Duration> {
+    let counting_since = self.counting_since.get()?;
+    let processed = u32::try_from(self.processed())
+      .ok()
+      .filter(|rows| *rows > 0)?;
+    let remaining = u32::try_from(self.total().saturating_sub(self.processed())).ok()?;
+
+    Some(now.duration_since(*counting_since) / processed * remaining)
+  }
+
+  /// Cancels the import when the button with `custom_id` is pressed. The returned task should
+  /// be aborted once the import can no longer be cancelled.
+  pub fn cancel_on_press(
+    self: &Arc<Self>,
+    shard: ShardMessenger,
+    http: Arc<Http>,
+    custom_id: String,
+  ) -> JoinHandle<()> {
+    let job = Arc::clone(self);
+    tokio::spawn(async move {
+      if let Some(press) = ComponentInteractionCollector::new(shard)
+        .filter(move |press| press.data.custom_id == custom_id)
+        .timeout(CANCEL_TIMEOUT)
+        .await
+      {
+        job.cancel();
+        // The import replaces the message once it has stopped
+        let _ = press
+          .create_response(&http, CreateInteractionResponse::Acknowledge)
+          .await;
+      }
+    })
+  }
+}
+
+/// Removes a job from the [`ImportJobsHandler`] when dropped, so that jobs are removed
+/// however the import ends.
+pub struct ImportJobGuard<'a> {
+  jobs: &'a ImportJobsHandler,
+  key: (GuildId, UserId),
+  job: Arc<ImportJob>,
+}
+
+impl ImportJobGuard<'_> {
+  pub fn job(&self) -> &Arc<ImportJob> {
+    &self.job
+  }
+}
+
+impl Drop for ImportJobGuard<'_> {
+  fn drop(&mut self) {
+    self
+      .jobs
+      .jobs
+      .lock()
+      .unwrap_or_else(PoisonError::into_inner)
+      .remove(&self.key);
+  }
+}
+
+/// Tracks running imports, so that each user only has one import running in a server at a
+/// time.
+#[derive(Default)]
+pub struct ImportJobsHandler {
+  jobs: Mutex<HashMap<(GuildId, UserId), Arc<ImportJob>>>,
+}
+
+impl ImportJobsHandler {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Starts tracking an import for a user, or returns `None` if they already have one running.
+  pub fn start(&self, guild_id: GuildId, user_id: UserId) -> Option<ImportJobGuard<'_>> {
+    let mut jobs = self.jobs.lock().unwrap_or_else(PoisonError::into_inner);
+    let key = (guild_id, user_id);
+    if jobs.contains_key(&key) {
+      return None;
+    }
+
+    let job = Arc::new(ImportJob::new());
+    jobs.insert(key, Arc::clone(&job));
+
+    Some(ImportJobGuard {
+      jobs: self,
+      key,
+      job,
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_import_jobs() {
+    let jobs = ImportJobsHandler::new();
+    let guild_id = GuildId::new(123u64);
+    let user_id = UserId::new(456u64);
+
+    let guard = jobs.start(guild_id, user_id);
+    assert!(guard.is_some());
+    assert!(jobs.start(guild_id, user_id).is_none());
+    assert!(jobs.start(guild_id, UserId::new(789u64)).is_some());
+
+    // Finished imports no longer block new ones
+    drop(guard);
+    assert!(jobs.start(guild_id, user_id).is_some());
+  }
+
+  #[test]
+  fn test_import_job_eta() {
+    let started_at = Instant::now();
+    let job = ImportJob::new();
+    assert_eq!(job.eta_at(started_at), None);
+
+    job.set_total_at(400, started_at);
+    assert_eq!(job.eta_at(started_at + Duration::from_secs(5)), None);
+
+    job.advance(100);
+    assert_eq!(
+      job.eta_at(started_at + Duration::from_secs(5)),
+      Some(Duration::from_secs(15))
+    );
+
+    job.advance(300);
+    assert_eq!(
+      job.eta_at(started_at + Duration::from_secs(20)),
+      Some(Duration::ZERO)
+    );
+    assert!(!job.is_cancelled());
+    job.cancel();
+    assert!(job.is_cancelled());
+  }
+}
@@ -0,0 +1,102 @@
+use std::sync::{PoisonError, RwLock};
+
+use anyhow::Result;
+
+use crate::data::maintenance::Maintenance;
+use crate::database::DatabaseHandler;
+
+/// Keeps whether Bloom is undergoing maintenance in memory, so it can be checked before
+/// every command without a database query.
+pub struct MaintenanceHandler {
+  maintenance: RwLock<Maintenance>,
+}
+
+impl MaintenanceHandler {
+  /// Loads the maintenance setting from the database, so maintenance survives restarts.
+  ///
+  /// # Errors
+  /// Returns an error if the setting can't be retrieved.
+  pub async fn new(db: &DatabaseHandler) -> Result<Self> {
+    let mut transaction = db.start_transaction_with_retry(5).await?;
+    let maintenance = DatabaseHandler::get_maintenance(&mut transaction).await?;
+    drop(transaction);
+
+    Ok(Self::from_maintenance(maintenance))
+  }
+
+  fn from_maintenance(maintenance: Maintenance) -> Self {
+    Self {
+      maintenance: RwLock::new(maintenance),
+    }
+  }
+
+  fn get(&self) -> Maintenance {
+    self
+      .maintenance
+      .read()
+      .unwrap_or_else(PoisonError::into_inner)
+      .clone()
+  }
+
+  /// The message shown to members who use a command during maintenance, or `None` if Bloom
+  /// isn't undergoing maintenance.
+  pub fn message(&self) -> Option<String> {
+    let maintenance = self.get();
+    if !maintenance.enabled {
+      return None;
+    }
+
+    Some(match maintenance.reason {
+      Some(reason) => format!(
+        "🛠️ Bloom is undergoing maintenance ({reason}). Please try again a little later. Thanks for your patience!"
+      ),
+      None => "🛠️ Bloom is undergoing maintenance. Please try again a little later. Thanks for your patience!".to_owned(),
+    })
+  }
+
+  /// Saves the maintenance setting, then applies it.
+  ///
+  /// # Errors
+  /// Returns an error if the setting can't be saved, in which case it isn't applied.
+  pub async fn set(&self, db: &DatabaseHandler, maintenance: Maintenance) -> Result<()> {
+    let mut transaction = db.start_transaction_with_retry(5).await?;
+    DatabaseHandler::set_maintenance(&mut transaction, &maintenance).await?;
+    DatabaseHandler::commit_transaction(transaction).await?;
+
+    self.replace(maintenance);
+
+    Ok(())
+  }
+
+  fn replace(&self, maintenance: Maintenance) {
+    *self
+      .maintenance
+      .write()
+      .unwrap_or_else(PoisonError::into_inner) = maintenance;
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_message() {
+    let handler = MaintenanceHandler::from_maintenance(Maintenance::default());
+    assert_eq!(handler.message(), None);
+
+    handler.replace(Maintenance::new(true, None));
+    assert_eq!(
+      handler.message().as_deref(),
+      Some("🛠️ Bloom is undergoing maintenance. Please try again a little later. Thanks for your patience!")
+    );
+
+    handler.replace(Maintenance::new(
+      true,
+      Some("database migration".to_owned()),
+    ));
+    assert!(handler
+      .message()
+      .is_some_and(|message| message.contains("(database migration)")));
+  }
+}
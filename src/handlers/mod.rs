@@ -1,2 +1,17 @@
-pub mod database;
+pub mod bot_config;
+pub mod chart_cache;
 pub mod embeddings;
+pub mod emoji;
+pub mod features;
+pub mod feeds;
+pub mod import_jobs;
+pub mod maintenance;
+pub mod roles;
+pub mod settings;
+pub mod storage;
+pub mod suttas;
+pub mod talks;
+pub mod transcription;
+pub mod web;
+
+pub use bloombot_core::database;
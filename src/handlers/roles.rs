@@ -0,0 +1,311 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use log::{info, warn};
+use poise::serenity_prelude::{EditMember, Error as SerenityError, GuildId, Http, HttpError};
+use poise::serenity_prelude::{Member, RoleId, UserId};
+use tokio::sync::{mpsc, oneshot};
+
+/// The most updates taken from the queue at once. Updates for the same member within a batch
+/// are combined into one.
+const BATCH_SIZE: usize = 50;
+
+/// How many times an update is retried when Discord is rate limiting or unavailable.
+const MAX_RETRIES: u32 = 4;
+
+/// How long to wait before the first retry. The wait doubles with each retry.
+const INITIAL_BACKOFF: Duration = Duration::from_secs(2);
+
+/// Progress is logged after this many updates while a backlog is being worked through.
+const PROGRESS_INTERVAL: usize = 100;
+
+/// Roles to add to and remove from a member.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoleUpdate {
+  guild_id: GuildId,
+  user_id: UserId,
+  /// The member's roles when the update was made, used to set all of their roles at once
+  /// when more than one role changes.
+  current: Vec<RoleId>,
+  add: Vec<RoleId>,
+  remove: Vec<RoleId>,
+}
+
+impl RoleUpdate {
+  pub fn new(member: &Member) -> Self {
+    Self {
+      guild_id: member.guild_id,
+      user_id: member.user.id,
+      current: member.roles.clone(),
+      add: Vec::new(),
+      remove: Vec::new(),
+    }
+  }
+
+  /// Adds a role, unless the member already has it.
+  pub fn add(mut self, role: RoleId) -> Self {
+    self.remove.retain(|removed| *removed != role);
+    if !self.current.contains(&role) && !self.add.contains(&role) {
+      self.add.push(role);
+    }
+    self
+  }
+
+  /// Removes a role, if the member has it.
+  pub fn remove(mut self, role: RoleId) -> Self {
+    self.add.retain(|added| *added != role);
+    if self.current.contains(&role) && !self.remove.contains(&role) {
+      self.remove.push(role);
+    }
+    self
+  }
+
+  pub fn added(&self) -> usize {
+    self.add.len()
+  }
+
+  pub fn removed(&self) -> usize {
+    self.remove.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.add.is_empty() && self.remove.is_empty()
+  }
+
+  fn is_for(&self, other: &Self) -> bool {
+    self.guild_id == other.guild_id && self.user_id == other.user_id
+  }
+
+  /// Applies a later update for the same member on top of this one.
+  fn merge(self, other: &Self) -> Self {
+    let merged = other
+      .add
+      .iter()
+      .fold(self, |update, role| update.add(*role));
+    other
+      .remove
+      .iter()
+      .fold(merged, |update, role| update.remove(*role))
+  }
+
+  /// The member's roles once the update is applied.
+  fn roles(&self) -> Vec<RoleId> {
+    self
+      .current
+      .iter()
+      .filter(|role| !self.remove.contains(role))
+      .chain(&self.add)
+      .copied()
+      .collect()
+  }
+}
+
+struct QueuedUpdate {
+  update: RoleUpdate,
+  done: oneshot::Sender<Result<(), String>>,
+}
+
+/// Combines queued updates for the same member, keeping the order in which members were
+/// first queued. Each combined update keeps every sender, so all are told the outcome.
+fn coalesce(
+  queued: impl IntoIterator<Item = QueuedUpdate>,
+) -> Vec<(RoleUpdate, Vec<oneshot::Sender<Result<(), String>>>)> {
+  let mut combined: Vec<(RoleUpdate, Vec<_>)> = Vec::new();
+  for QueuedUpdate { update, done } in queued {
+    if let Some((existing, senders)) = combined
+      .iter_mut()
+      .find(|(existing, _)| existing.is_for(&update))
+    {
+      *existing = existing.clone().merge(&update);
+      senders.push(done);
+    } else {
+      combined.push((update, vec![done]));
+    }
+  }
+
+  combined
+}
+
+fn is_retryable(error: &SerenityError) -> bool {
+  match error {
+    SerenityError::Http(HttpError::UnsuccessfulRequest(response)) => {
+      let status = response.status_code.as_u16();
+      status == 429 || (500..600).contains(&status)
+    }
+    _ => false,
+  }
+}
+
+/// Applies an update with as few requests as possible: a single role is added or removed
+/// directly, while several are set together by editing the member.
+async fn apply_update(http: &Http, update: &RoleUpdate) -> Result<(), SerenityError> {
+  match (update.add.as_slice(), update.remove.as_slice()) {
+    ([], []) => Ok(()),
+    ([role], []) => {
+      http
+        .add_member_role(update.guild_id, update.user_id, *role, None)
+        .await
+    }
+    ([], [role]) => {
+      http
+        .remove_member_role(update.guild_id, update.user_id, *role, None)
+        .await
+    }
+    _ => update
+      .guild_id
+      .edit_member(
+        http,
+        update.user_id,
+        EditMember::new().roles(update.roles()),
+      )
+      .await
+      .map(|_| ()),
+  }
+}
+
+async fn apply_with_backoff(http: &Http, update: &RoleUpdate) -> Result<(), SerenityError> {
+  let mut backoff = INITIAL_BACKOFF;
+  let mut retries = 0;
+
+  loop {
+    match apply_update(http, update).await {
+      Err(e) if retries < MAX_RETRIES && is_retryable(&e) => {
+        warn!(
+          "Role update for {} failed, retrying in {}s: {e}",
+          update.user_id,
+          backoff.as_secs()
+        );
+        tokio::time::sleep(backoff).await;
+        backoff *= 2;
+        retries += 1;
+      }
+      result => return result,
+    }
+  }
+}
+
+async fn process(http: Arc<Http>, mut receiver: mpsc::UnboundedReceiver<QueuedUpdate>) {
+  let mut batch = Vec::with_capacity(BATCH_SIZE);
+  let mut applied = 0;
+
+  while receiver.recv_many(&mut batch, BATCH_SIZE).await > 0 {
+    for (update, senders) in coalesce(batch.drain(..)) {
+      let result = apply_with_backoff(&http, &update)
+        .await
+        .map_err(|e| e.to_string());
+      for sender in senders {
+        // The caller may not be waiting for the outcome
+        let _ = sender.send(result.clone());
+      }
+
+      applied += 1;
+      if applied % PROGRESS_INTERVAL == 0 && !receiver.is_empty() {
+        info!(
+          "Applied {applied} role updates, {} still queued",
+          receiver.len()
+        );
+      }
+    }
+
+    if receiver.is_empty() {
+      applied = 0;
+    }
+  }
+}
+
+/// Applies role changes one member at a time, in the order they're queued, so that large
+/// numbers of changes, such as from role reconciliation, don't run into Discord's rate
+/// limits. Rate limited requests are retried with backoff.
+pub struct RoleQueueHandler {
+  sender: mpsc::UnboundedSender<QueuedUpdate>,
+}
+
+impl RoleQueueHandler {
+  pub fn new(http: Arc<Http>) -> Self {
+    let (sender, receiver) = mpsc::unbounded_channel();
+    tokio::spawn(process(http, receiver));
+
+    Self { sender }
+  }
+
+  /// Queues an update, returning a receiver for its outcome.
+  pub fn enqueue(&self, update: RoleUpdate) -> oneshot::Receiver<Result<(), String>> {
+    let (done, receiver) = oneshot::channel();
+    if let Err(mpsc::error::SendError(queued)) = self.sender.send(QueuedUpdate { update, done }) {
+      let _ = queued
+        .done
+        .send(Err("Role update queue has stopped".to_owned()));
+    }
+
+    receiver
+  }
+
+  /// Queues an update and waits for it to be applied.
+  ///
+  /// # Errors
+  /// Returns an error if the roles couldn't be updated.
+  pub async fn apply(&self, update: RoleUpdate) -> Result<()> {
+    if update.is_empty() {
+      return Ok(());
+    }
+
+    self
+      .enqueue(update)
+      .await?
+      .map_err(|e| anyhow!("Failed to update roles: {e}"))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn update(user_id: u64, current: &[u64]) -> RoleUpdate {
+    RoleUpdate {
+      guild_id: GuildId::new(1u64),
+      user_id: UserId::new(user_id),
+      current: current.iter().map(|role| RoleId::new(*role)).collect(),
+      add: Vec::new(),
+      remove: Vec::new(),
+    }
+  }
+
+  #[test]
+  fn test_role_update() {
+    let update = update(10, &[100, 200])
+      .add(RoleId::new(100))
+      .add(RoleId::new(300))
+      .remove(RoleId::new(200))
+      .remove(RoleId::new(400));
+
+    assert_eq!(update.add, vec![RoleId::new(300)]);
+    assert_eq!(update.remove, vec![RoleId::new(200)]);
+    assert_eq!(update.roles(), vec![RoleId::new(100), RoleId::new(300)]);
+
+    // A later change to the same role replaces an earlier one
+    let update = update.remove(RoleId::new(300)).add(RoleId::new(200));
+    assert!(update.is_empty());
+  }
+
+  #[test]
+  fn test_coalesce() {
+    let queued = [
+      update(10, &[100]).add(RoleId::new(200)),
+      update(20, &[]).add(RoleId::new(200)),
+      update(10, &[100]).remove(RoleId::new(100)),
+    ]
+    .into_iter()
+    .map(|update| QueuedUpdate {
+      update,
+      done: oneshot::channel().0,
+    });
+
+    let combined = coalesce(queued);
+    assert_eq!(combined.len(), 2);
+    assert_eq!(combined[0].0.user_id, UserId::new(10));
+    assert_eq!(combined[0].0.roles(), vec![RoleId::new(200)]);
+    assert_eq!(combined[0].1.len(), 2);
+    assert_eq!(combined[1].0.user_id, UserId::new(20));
+  }
+}
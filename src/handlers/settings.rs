@@ -0,0 +1,131 @@
+use std::collections::HashMap;
+use std::sync::{Arc, PoisonError, RwLock};
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use poise::serenity_prelude::GuildId;
+
+use crate::data::guild_settings::GuildSettings;
+use crate::database::DatabaseHandler;
+
+/// How long settings are kept before they're loaded from the database again. Changes made
+/// with `/config` are applied immediately in the process which made them, so this only
+/// limits how long other processes, when shards are split across processes, use old settings.
+const SETTINGS_TTL: Duration = Duration::from_secs(30);
+
+struct CachedSettings {
+  settings: Arc<GuildSettings>,
+  loaded_at: Instant,
+}
+
+/// Keeps each server's [`GuildSettings`] in memory, so they can be checked for every
+/// message or member event without a database query. Settings are loaded when first needed
+/// and reloaded once they're older than [`SETTINGS_TTL`], or after they've been changed.
+pub struct SettingsHandler {
+  guilds: RwLock<HashMap<GuildId, CachedSettings>>,
+  ttl: Duration,
+}
+
+impl Default for SettingsHandler {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl SettingsHandler {
+  pub fn new() -> Self {
+    Self::with_ttl(SETTINGS_TTL)
+  }
+
+  fn with_ttl(ttl: Duration) -> Self {
+    Self {
+      guilds: RwLock::new(HashMap::new()),
+      ttl,
+    }
+  }
+
+  /// Returns a server's settings, loading them from the database if they aren't cached or
+  /// are out of date.
+  ///
+  /// # Errors
+  /// Returns an error if the settings can't be retrieved.
+  pub async fn get(&self, db: &DatabaseHandler, guild_id: GuildId) -> Result<Arc<GuildSettings>> {
+    if let Some(settings) = self.cached(guild_id, Instant::now()) {
+      return Ok(settings);
+    }
+
+    let mut transaction = db.start_transaction_with_retry(5).await?;
+    let settings =
+      Arc::new(DatabaseHandler::get_guild_settings(&mut transaction, &guild_id).await?);
+    drop(transaction);
+
+    self.insert(guild_id, settings.clone(), Instant::now());
+
+    Ok(settings)
+  }
+
+  /// Removes a server's settings from the cache, so they're loaded from the database the
+  /// next time they're needed. Call this after the settings have been changed and the
+  /// change has been committed.
+  pub fn invalidate(&self, guild_id: GuildId) {
+    self
+      .guilds
+      .write()
+      .unwrap_or_else(PoisonError::into_inner)
+      .remove(&guild_id);
+  }
+
+  fn cached(&self, guild_id: GuildId, now: Instant) -> Option<Arc<GuildSettings>> {
+    self
+      .guilds
+      .read()
+      .unwrap_or_else(PoisonError::into_inner)
+      .get(&guild_id)
+      .filter(|cached| now.duration_since(cached.loaded_at) < self.ttl)
+      .map(|cached| cached.settings.clone())
+  }
+
+  fn insert(&self, guild_id: GuildId, settings: Arc<GuildSettings>, loaded_at: Instant) {
+    self
+      .guilds
+      .write()
+      .unwrap_or_else(PoisonError::into_inner)
+      .insert(
+        guild_id,
+        CachedSettings {
+          settings,
+          loaded_at,
+        },
+      );
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_cached() {
+    let handler = SettingsHandler::with_ttl(Duration::from_secs(30));
+    let guild_id = GuildId::new(123u64);
+    let now = Instant::now();
+
+    assert!(handler.cached(guild_id, now).is_none());
+
+    handler.insert(
+      guild_id,
+      Arc::new(GuildSettings::new(guild_id).quotes_on_add(false)),
+      now,
+    );
+    assert!(handler
+      .cached(guild_id, now + Duration::from_secs(10))
+      .is_some_and(|settings| !settings.quotes_on_add));
+    assert!(handler
+      .cached(guild_id, now + Duration::from_secs(30))
+      .is_none());
+    assert!(handler.cached(GuildId::new(456u64), now).is_none());
+
+    handler.invalidate(guild_id);
+    assert!(handler.cached(guild_id, now).is_none());
+  }
+}
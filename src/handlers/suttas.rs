@@ -0,0 +1,303 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use reqwest::{Client, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+use crate::data::sutta::SuttaPassage;
+
+/// The SuttaCentral API, which serves suttas and their translations.
+const API_URL: &str = "https://suttacentral.net/api";
+/// The translator used when none is chosen, if the sutta has a translation by them.
+pub const DEFAULT_TRANSLATOR: &str = "sujato";
+/// The time to wait for a single request to SuttaCentral.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// A translation of a sutta available on SuttaCentral.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Translation {
+  pub author: String,
+  pub author_uid: String,
+  pub lang: String,
+  /// Only segmented translations can be fetched as text.
+  #[serde(default)]
+  segmented: bool,
+}
+
+#[derive(Deserialize)]
+struct Suttaplex {
+  original_title: String,
+  translated_title: Option<String>,
+  #[serde(default)]
+  translations: Vec<Translation>,
+}
+
+#[derive(Deserialize)]
+struct BilaraText {
+  #[serde(default)]
+  translation_text: HashMap<String, String>,
+  #[serde(default)]
+  keys_order: Vec<String>,
+}
+
+/// The result of looking up a sutta on SuttaCentral.
+pub enum SuttaLookup {
+  Found(SuttaPassage),
+  /// No sutta with this ID exists.
+  NotFound,
+  /// The sutta exists, but hasn't been translated by the chosen translator. Includes the
+  /// translations which are available.
+  NoTranslation(Vec<Translation>),
+}
+
+/// Converts a reference such as `MN 10` or `sn 56.11` to a SuttaCentral ID such as `mn10`
+/// or `sn56.11`. Returns [`None`] if the reference isn't a collection followed by a number.
+pub fn parse_reference(reference: &str) -> Option<String> {
+  let uid: String = reference
+    .chars()
+    .filter(|c| !c.is_whitespace())
+    .flat_map(char::to_lowercase)
+    .collect();
+
+  let number_at = uid.find(|c: char| c.is_ascii_digit())?;
+  let (collection, number) = uid.split_at(number_at);
+  if collection.is_empty()
+    || !collection.chars().all(|c| c.is_ascii_lowercase())
+    || !number
+      .chars()
+      .all(|c| c.is_ascii_digit() || c == '.' || c == '-')
+  {
+    return None;
+  }
+
+  Some(uid)
+}
+
+/// Formats a SuttaCentral ID for display, e.g., `sn56.11` as `SN 56.11`.
+pub fn display_reference(uid: &str) -> String {
+  match uid.find(|c: char| c.is_ascii_digit()) {
+    Some(number_at) => format!("{} {}", uid[..number_at].to_uppercase(), &uid[number_at..]),
+    None => uid.to_uppercase(),
+  }
+}
+
+/// Joins the segments of a translation into text, with a line for each paragraph. Segment
+/// IDs such as `mn10:2.3` are grouped by section, so `mn10:2.1` to `mn10:2.3` form one
+/// paragraph. Section `0` holds the title, which is shown separately, so it's left out.
+fn join_segments(keys_order: &[String], segments: &HashMap<String, String>) -> String {
+  let mut paragraphs: Vec<String> = Vec::new();
+  let mut current_section = None;
+
+  for key in keys_order {
+    let Some(text) = segments.get(key) else {
+      continue;
+    };
+    let text = text.trim();
+    let section = key
+      .split_once(':')
+      .map_or(key.as_str(), |(_, number)| number)
+      .split('.')
+      .next()
+      .unwrap_or_default();
+    if section == "0" || text.is_empty() {
+      continue;
+    }
+
+    match paragraphs.last_mut() {
+      Some(paragraph) if current_section == Some(section) => {
+        paragraph.push(' ');
+        paragraph.push_str(text);
+      }
+      _ => paragraphs.push(text.to_owned()),
+    }
+    current_section = Some(section);
+  }
+
+  paragraphs.join("\n")
+}
+
+/// Shortens a passage to at most `max_chars` characters, ending at a paragraph or word
+/// break where possible. Returns whether the passage was shortened.
+pub fn excerpt(passage: &str, max_chars: usize) -> (String, bool) {
+  if passage.chars().count() <= max_chars {
+    return (passage.to_owned(), false);
+  }
+
+  let cut: String = passage.chars().take(max_chars).collect();
+  let end = cut
+    .rfind('\n')
+    .filter(|end| *end > cut.len() / 2)
+    .or_else(|| cut.rfind(' '))
+    .unwrap_or(cut.len());
+
+  (format!("{}…", cut[..end].trim_end()), true)
+}
+
+/// Fetches suttas from SuttaCentral. Fetched suttas are cached in the database by the
+/// `/sutta` command, so each translation is only fetched occasionally.
+pub struct SuttaHandler {
+  client: Client,
+}
+
+impl SuttaHandler {
+  /// Creates the HTTP client used for requests to SuttaCentral.
+  ///
+  /// # Errors
+  /// Returns an error if the client can't be created.
+  pub fn new() -> Result<Self> {
+    let client = Client::builder()
+      .timeout(REQUEST_TIMEOUT)
+      .build()
+      .with_context(|| "Failed to create SuttaCentral client")?;
+
+    Ok(Self { client })
+  }
+
+  /// Sends a GET request, returning [`None`] if SuttaCentral has nothing at `path`.
+  async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<Option<T>> {
+    let response = self.client.get(format!("{API_URL}/{path}")).send().await?;
+    let status = response.status();
+    if status == StatusCode::NOT_FOUND {
+      return Ok(None);
+    }
+    let text = response.text().await?;
+    if !status.is_success() {
+      bail!("SuttaCentral request failed with status {status}: {text}");
+    }
+
+    // Unknown suttas return an empty response rather than an error
+    if text.trim().is_empty() || text.trim() == "null" {
+      return Ok(None);
+    }
+    serde_json::from_str(&text)
+      .map(Some)
+      .with_context(|| format!("Failed to parse SuttaCentral response for {path}"))
+  }
+
+  /// Looks up a sutta by its SuttaCentral ID, in the chosen translation or the default one.
+  /// If no translator is chosen and the default translator hasn't translated the sutta, the
+  /// first English translation is used, or any translation if there's no English one.
+  ///
+  /// # Errors
+  /// Returns an error if SuttaCentral can't be reached or returns something unexpected.
+  pub async fn lookup(&self, uid: &str, translator: Option<&str>) -> Result<SuttaLookup> {
+    let Some(suttaplex) = self
+      .get::<Vec<Suttaplex>>(&format!("suttaplex/{uid}"))
+      .await?
+      .and_then(|mut suttaplex| suttaplex.pop())
+    else {
+      return Ok(SuttaLookup::NotFound);
+    };
+
+    let available: Vec<Translation> = suttaplex
+      .translations
+      .into_iter()
+      .filter(|translation| translation.segmented)
+      .collect();
+    let chosen = match translator {
+      Some(translator) => available
+        .iter()
+        .find(|translation| translation.author_uid.eq_ignore_ascii_case(translator)),
+      None => available
+        .iter()
+        .find(|translation| translation.author_uid == DEFAULT_TRANSLATOR)
+        .or_else(|| {
+          available
+            .iter()
+            .find(|translation| translation.lang == "en")
+        })
+        .or_else(|| available.first()),
+    };
+    let Some(chosen) = chosen else {
+      return Ok(SuttaLookup::NoTranslation(available));
+    };
+
+    let Some(text) = self
+      .get::<BilaraText>(&format!(
+        "bilarasuttas/{uid}/{}?lang={}",
+        chosen.author_uid, chosen.lang
+      ))
+      .await?
+    else {
+      return Ok(SuttaLookup::NoTranslation(available));
+    };
+
+    Ok(SuttaLookup::Found(SuttaPassage::new(
+      uid.to_owned(),
+      chosen.author_uid.clone(),
+      chosen.author.clone(),
+      chosen.lang.clone(),
+      suttaplex.original_title,
+      suttaplex
+        .translated_title
+        .filter(|title| !title.trim().is_empty()),
+      join_segments(&text.keys_order, &text.translation_text),
+    )))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_parse_reference() {
+    assert_eq!(parse_reference("MN 10"), Some("mn10".to_owned()));
+    assert_eq!(parse_reference(" sn 56.11 "), Some("sn56.11".to_owned()));
+    assert_eq!(parse_reference("AN4.41"), Some("an4.41".to_owned()));
+    assert_eq!(parse_reference("Dhp 1-20"), Some("dhp1-20".to_owned()));
+    assert_eq!(parse_reference("snp 1.8"), Some("snp1.8".to_owned()));
+
+    assert_eq!(parse_reference(""), None);
+    assert_eq!(parse_reference("10"), None);
+    assert_eq!(parse_reference("mn"), None);
+    assert_eq!(parse_reference("mn 10/../x"), None);
+
+    assert_eq!(display_reference("sn56.11"), "SN 56.11");
+    assert_eq!(display_reference("dhp1-20"), "DHP 1-20");
+  }
+
+  #[test]
+  fn test_join_segments() {
+    let keys_order: Vec<String> = ["mn10:0.1", "mn10:0.2", "mn10:1.1", "mn10:1.2", "mn10:2.1"]
+      .iter()
+      .map(|key| (*key).to_owned())
+      .collect();
+    let segments: HashMap<String, String> = [
+      ("mn10:0.1", "Middle Discourses 10 "),
+      ("mn10:0.2", "Mindfulness Meditation "),
+      ("mn10:1.1", "So I have heard. "),
+      (
+        "mn10:1.2",
+        "At one time the Buddha was staying in the land of the Kurus. ",
+      ),
+      ("mn10:2.1", "“Mendicants!” "),
+    ]
+    .into_iter()
+    .map(|(key, text)| (key.to_owned(), text.to_owned()))
+    .collect();
+
+    assert_eq!(
+      join_segments(&keys_order, &segments),
+      "So I have heard. At one time the Buddha was staying in the land of the Kurus.\n“Mendicants!”"
+    );
+  }
+
+  #[test]
+  fn test_excerpt() {
+    assert_eq!(
+      excerpt("So I have heard.", 100),
+      ("So I have heard.".to_owned(), false)
+    );
+    assert_eq!(
+      excerpt("So I have heard. At one time", 20),
+      ("So I have heard. At…".to_owned(), true)
+    );
+    assert_eq!(
+      excerpt("So I have heard.\nAt one time the Buddha", 30),
+      ("So I have heard.…".to_owned(), true)
+    );
+  }
+}
@@ -0,0 +1,161 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use log::warn;
+use poise::serenity_prelude::{ChannelId, GuildId, UserId};
+use reqwest::{Client, Url};
+use songbird::input::HttpRequest;
+use songbird::tracks::Track;
+use songbird::{Event, EventContext, EventHandler as VoiceEventHandler, Songbird};
+
+use crate::data::talk::Talk;
+
+/// How often an idle call is checked for, to leave voice channels once the queue is empty.
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+/// The most talks which can be queued in a guild at once, including the one playing.
+pub const MAX_QUEUE_LENGTH: usize = 10;
+
+/// A queued talk, attached to its track so the queue can be shown.
+pub struct QueuedTalk {
+  pub name: String,
+  pub requested_by: UserId,
+}
+
+/// Where a talk was queued by [`TalkHandler::enqueue`].
+pub enum Enqueued {
+  /// The talk is at this position in the queue, where `1` means it's playing now.
+  Position(usize),
+  /// The queue already holds [`MAX_QUEUE_LENGTH`] talks.
+  QueueFull,
+  /// Talks are already playing in another voice channel.
+  Busy(ChannelId),
+}
+
+/// Leaves the voice channel once nothing is left to play.
+struct LeaveWhenIdle {
+  manager: Arc<Songbird>,
+  guild_id: GuildId,
+}
+
+#[async_trait]
+impl VoiceEventHandler for LeaveWhenIdle {
+  async fn act(&self, _ctx: &EventContext<'_>) -> Option<Event> {
+    let call = self.manager.get(self.guild_id)?;
+    if !call.lock().await.queue().is_empty() {
+      return None;
+    }
+
+    if let Err(e) = self.manager.remove(self.guild_id).await {
+      warn!("Failed to leave voice in {}: {e:?}", self.guild_id);
+    }
+    Some(Event::Cancel)
+  }
+}
+
+/// Checks that a talk URL is a web address, returning it in normalized form.
+///
+/// # Errors
+/// Returns an error if the URL is invalid or isn't HTTP or HTTPS.
+pub fn parse_url(url: &str) -> Result<String> {
+  let url = Url::parse(url.trim()).with_context(|| "The talk URL is not a valid URL")?;
+  if !matches!(url.scheme(), "http" | "https") || url.host_str().is_none() {
+    bail!("The talk URL must be a web address starting with https:// or http://");
+  }
+
+  Ok(url.to_string())
+}
+
+/// Streams talks from a guild's library into voice channels, using a queue for each guild.
+pub struct TalkHandler {
+  client: Client,
+}
+
+impl TalkHandler {
+  /// Creates the HTTP client used to stream talks. There's no overall timeout, since talks
+  /// are streamed for as long as they play.
+  ///
+  /// # Errors
+  /// Returns an error if the client can't be created.
+  pub fn new() -> Result<Self> {
+    let client = Client::builder()
+      .connect_timeout(Duration::from_secs(15))
+      .build()
+      .with_context(|| "Failed to create talk client")?;
+
+    Ok(Self { client })
+  }
+
+  /// Joins `channel_id` if needed and adds a talk to the end of the guild's queue. Playback
+  /// starts right away if the queue was empty.
+  ///
+  /// # Errors
+  /// Returns an error if the voice channel can't be joined.
+  pub async fn enqueue(
+    &self,
+    manager: Arc<Songbird>,
+    guild_id: GuildId,
+    channel_id: ChannelId,
+    talk: &Talk,
+    requested_by: UserId,
+  ) -> Result<Enqueued> {
+    if let Some(call) = manager.get(guild_id) {
+      let call = call.lock().await;
+      if let Some(current) = call.current_channel() {
+        if current.0.get() != channel_id.get() && !call.queue().is_empty() {
+          return Ok(Enqueued::Busy(ChannelId::new(current.0.get())));
+        }
+      }
+    }
+
+    let joined = manager.get(guild_id).is_none();
+    let call = manager
+      .join(guild_id, channel_id)
+      .await
+      .with_context(|| format!("Failed to join voice channel {channel_id}"))?;
+    let mut call = call.lock().await;
+
+    if joined {
+      call.add_global_event(
+        Event::Periodic(IDLE_CHECK_INTERVAL, None),
+        LeaveWhenIdle {
+          manager: Arc::clone(&manager),
+          guild_id,
+        },
+      );
+    }
+    if call.queue().len() >= MAX_QUEUE_LENGTH {
+      return Ok(Enqueued::QueueFull);
+    }
+
+    let input = HttpRequest::new(self.client.clone(), talk.url.clone());
+    let track = Track::new_with_data(
+      input.into(),
+      Arc::new(QueuedTalk {
+        name: talk.name.clone(),
+        requested_by,
+      }),
+    );
+    call.enqueue(track).await;
+
+    Ok(Enqueued::Position(call.queue().len()))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_parse_url() {
+    assert_eq!(
+      parse_url(" https://example.com/talks/metta.mp3 ")
+        .ok()
+        .as_deref(),
+      Some("https://example.com/talks/metta.mp3")
+    );
+    assert!(parse_url("file:///talks/metta.mp3").is_err());
+    assert!(parse_url("metta.mp3").is_err());
+  }
+}
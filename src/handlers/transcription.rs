@@ -0,0 +1,466 @@
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::num::NonZeroU64;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use async_openai::config::OpenAIConfig;
+use async_openai::types::{AudioInput, CreateTranscriptionRequestArgs};
+use async_openai::Client;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use log::warn;
+use poise::serenity_prelude::{ChannelId, GuildId, UserId};
+use songbird::driver::DecodeMode;
+use songbird::events::context_data::VoiceTick;
+use songbird::{
+  Config, CoreEvent, Event, EventContext, EventHandler as VoiceEventHandler, Songbird,
+};
+use tokio::sync::Semaphore;
+use tokio::task::JoinHandle;
+use ulid::Ulid;
+
+use crate::bot_config::{Transcription, TranscriptionProvider};
+
+/// The model used when none is configured.
+const DEFAULT_MODEL: &str = "whisper-1";
+/// Voice is received in 20 ms ticks.
+const TICK: Duration = Duration::from_millis(20);
+/// The sample rate audio is sent for transcription at. Speech-to-text models work at 16 kHz,
+/// so Discord's 48 kHz audio is downsampled to keep uploads small.
+const SAMPLE_RATE: u32 = 16_000;
+/// Discord's 48 kHz stereo audio has six samples for each sample at [`SAMPLE_RATE`] in mono.
+const SAMPLES_PER_OUTPUT: usize = 6;
+/// The ticks of silence after which someone is considered to have finished speaking.
+const SILENCE_TICKS: u32 = 50;
+/// Utterances shorter than this are most likely coughs or other noise, so they're skipped.
+const MIN_UTTERANCE_SAMPLES: usize = SAMPLE_RATE as usize / 2;
+/// Long stretches of speech are split, so each part can be transcribed as it's recorded.
+const MAX_UTTERANCE_SAMPLES: usize = SAMPLE_RATE as usize * 30;
+/// The most utterances transcribed at once for each recording.
+const MAX_CONCURRENT_REQUESTS: usize = 3;
+
+/// A line of a transcript, spoken by one member.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TranscriptLine {
+  pub user_id: UserId,
+  /// The time since the recording started.
+  pub offset: Duration,
+  pub text: String,
+}
+
+/// Sends audio to the configured speech-to-text provider.
+struct Transcriber {
+  client: Client<OpenAIConfig>,
+  model: String,
+  language: Option<String>,
+}
+
+impl Transcriber {
+  async fn transcribe(&self, wav: Vec<u8>) -> Result<String> {
+    let mut request = CreateTranscriptionRequestArgs::default();
+    request
+      .file(AudioInput::from_vec_u8("utterance.wav".to_owned(), wav))
+      .model(&self.model);
+    if let Some(language) = &self.language {
+      request.language(language);
+    }
+
+    let response = self
+      .client
+      .audio()
+      .transcribe(request.build()?)
+      .await
+      .with_context(|| "Transcription request failed")?;
+
+    Ok(response.text.trim().to_owned())
+  }
+}
+
+/// Audio from one member, collected until they stop speaking.
+struct Utterance {
+  user_id: UserId,
+  started_tick: u64,
+  /// Mono audio at [`SAMPLE_RATE`].
+  samples: Vec<i16>,
+  silent_ticks: u32,
+}
+
+#[derive(Default)]
+struct RecordingState {
+  /// Members who have agreed to be recorded. Nobody else's voice is kept.
+  consented: HashSet<UserId>,
+  /// The member speaking on each audio stream.
+  speakers: HashMap<u32, UserId>,
+  utterances: HashMap<u32, Utterance>,
+  ticks: u64,
+  pending: Vec<JoinHandle<Option<TranscriptLine>>>,
+}
+
+/// A community event being recorded for transcription.
+pub struct Recording {
+  /// Identifies the recording in the custom IDs of its consent buttons.
+  pub id: String,
+  pub event_title: String,
+  pub voice_channel: ChannelId,
+  /// The channel the transcript is posted in once the recording stops.
+  pub transcript_channel: ChannelId,
+  pub started_at: DateTime<Utc>,
+  state: Mutex<RecordingState>,
+  transcriber: Arc<Transcriber>,
+  requests: Arc<Semaphore>,
+}
+
+impl Recording {
+  fn state(&self) -> MutexGuard<'_, RecordingState> {
+    self.state.lock().unwrap_or_else(PoisonError::into_inner)
+  }
+
+  /// Starts transcribing an utterance in the background, unless it's too short to be speech.
+  fn transcribe(&self, state: &mut RecordingState, utterance: Utterance) {
+    if utterance.samples.len() < MIN_UTTERANCE_SAMPLES {
+      return;
+    }
+
+    let transcriber = Arc::clone(&self.transcriber);
+    let requests = Arc::clone(&self.requests);
+    state.pending.push(tokio::spawn(async move {
+      let _permit = requests.acquire_owned().await.ok()?;
+      match transcriber.transcribe(wav(&utterance.samples)).await {
+        Ok(text) if !text.is_empty() => Some(TranscriptLine {
+          user_id: utterance.user_id,
+          offset: TICK * u32::try_from(utterance.started_tick).unwrap_or(u32::MAX),
+          text,
+        }),
+        Ok(_) => None,
+        Err(e) => {
+          warn!("Failed to transcribe utterance: {e:?}");
+          None
+        }
+      }
+    }));
+  }
+
+  fn process_tick(&self, tick: &VoiceTick) {
+    let mut state = self.state();
+    state.ticks += 1;
+    let now = state.ticks;
+
+    for (ssrc, data) in &tick.speaking {
+      let Some(user_id) = state.speakers.get(ssrc).copied() else {
+        continue;
+      };
+      let Some(audio) = &data.decoded_voice else {
+        continue;
+      };
+      if !state.consented.contains(&user_id) {
+        continue;
+      }
+
+      let utterance = state.utterances.entry(*ssrc).or_insert_with(|| Utterance {
+        user_id,
+        started_tick: now,
+        samples: Vec::with_capacity(MAX_UTTERANCE_SAMPLES),
+        silent_ticks: 0,
+      });
+      utterance.silent_ticks = 0;
+      utterance.samples.extend(downsample(audio));
+    }
+
+    for (ssrc, utterance) in &mut state.utterances {
+      if !tick.speaking.contains_key(ssrc) {
+        utterance.silent_ticks += 1;
+      }
+    }
+
+    let finished: Vec<u32> = state
+      .utterances
+      .iter()
+      .filter(|(_, utterance)| {
+        utterance.silent_ticks >= SILENCE_TICKS || utterance.samples.len() >= MAX_UTTERANCE_SAMPLES
+      })
+      .map(|(ssrc, _)| *ssrc)
+      .collect();
+    for ssrc in finished {
+      if let Some(utterance) = state.utterances.remove(&ssrc) {
+        self.transcribe(&mut state, utterance);
+      }
+    }
+  }
+
+  /// Records whether a member agrees to be recorded. Withdrawing consent discards anything
+  /// they're saying, and leaves their lines out of the transcript.
+  pub fn set_consent(&self, user_id: UserId, consent: bool) {
+    let mut state = self.state();
+    if consent {
+      state.consented.insert(user_id);
+    } else {
+      state.consented.remove(&user_id);
+      state
+        .utterances
+        .retain(|_, utterance| utterance.user_id != user_id);
+    }
+  }
+
+  pub fn has_consented(&self, user_id: UserId) -> bool {
+    self.state().consented.contains(&user_id)
+  }
+
+  /// Transcribes anything still being said, then waits for every transcription to finish.
+  /// Returns the transcript in order, with lines only from members who still consent.
+  async fn finish(&self) -> Vec<TranscriptLine> {
+    let (pending, consented) = {
+      let mut state = self.state();
+      let utterances: Vec<Utterance> = state
+        .utterances
+        .drain()
+        .map(|(_, utterance)| utterance)
+        .collect();
+      for utterance in utterances {
+        self.transcribe(&mut state, utterance);
+      }
+      (std::mem::take(&mut state.pending), state.consented.clone())
+    };
+
+    let mut lines = Vec::with_capacity(pending.len());
+    for handle in pending {
+      if let Ok(Some(line)) = handle.await {
+        lines.push(line);
+      }
+    }
+
+    lines.retain(|line| consented.contains(&line.user_id));
+    lines.sort_by_key(|line| line.offset);
+    lines
+  }
+}
+
+/// Receives voice for a [`Recording`].
+#[derive(Clone)]
+struct Receiver {
+  recording: Arc<Recording>,
+}
+
+#[async_trait]
+impl VoiceEventHandler for Receiver {
+  async fn act(&self, ctx: &EventContext<'_>) -> Option<Event> {
+    match ctx {
+      EventContext::SpeakingStateUpdate(speaking) => {
+        if let Some(user_id) = speaking
+          .user_id
+          .and_then(|user_id| NonZeroU64::new(user_id.0))
+        {
+          self
+            .recording
+            .state()
+            .speakers
+            .insert(speaking.ssrc, UserId::from(user_id));
+        }
+      }
+      EventContext::VoiceTick(tick) => self.recording.process_tick(tick),
+      _ => {}
+    }
+
+    None
+  }
+}
+
+/// Converts 48 kHz stereo audio to mono at [`SAMPLE_RATE`], by averaging each group of
+/// samples.
+fn downsample(audio: &[i16]) -> impl Iterator<Item = i16> + '_ {
+  audio.chunks(SAMPLES_PER_OUTPUT).map(|chunk| {
+    let sum: i32 = chunk.iter().map(|sample| i32::from(*sample)).sum();
+    i16::try_from(sum / i32::try_from(chunk.len()).unwrap_or(1)).unwrap_or_default()
+  })
+}
+
+/// Encodes mono audio at [`SAMPLE_RATE`] as a 16-bit PCM WAV file.
+fn wav(samples: &[i16]) -> Vec<u8> {
+  let data_len = u32::try_from(samples.len() * 2).unwrap_or(u32::MAX);
+  let mut wav = Vec::with_capacity(44 + samples.len() * 2);
+  wav.extend_from_slice(b"RIFF");
+  wav.extend_from_slice(&data_len.saturating_add(36).to_le_bytes());
+  wav.extend_from_slice(b"WAVEfmt ");
+  wav.extend_from_slice(&16u32.to_le_bytes());
+  // PCM, one channel
+  wav.extend_from_slice(&1u16.to_le_bytes());
+  wav.extend_from_slice(&1u16.to_le_bytes());
+  wav.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+  wav.extend_from_slice(&(SAMPLE_RATE * 2).to_le_bytes());
+  wav.extend_from_slice(&2u16.to_le_bytes());
+  wav.extend_from_slice(&16u16.to_le_bytes());
+  wav.extend_from_slice(b"data");
+  wav.extend_from_slice(&data_len.to_le_bytes());
+  for sample in samples {
+    wav.extend_from_slice(&sample.to_le_bytes());
+  }
+
+  wav
+}
+
+/// Records community events in voice and transcribes them with the configured provider.
+/// Only members who consent are recorded, and audio is discarded once it's transcribed.
+pub struct TranscriptionHandler {
+  transcriber: Option<Arc<Transcriber>>,
+  recordings: Mutex<HashMap<GuildId, Arc<Recording>>>,
+}
+
+impl TranscriptionHandler {
+  /// Creates the client for the configured provider, if transcription is enabled.
+  ///
+  /// # Errors
+  /// Returns an error if a provider is configured but no API key is set.
+  pub fn new(config: &Transcription) -> Result<Self> {
+    let transcriber = match config.provider {
+      TranscriptionProvider::Disabled => None,
+      TranscriptionProvider::OpenAI => {
+        let api_key = env::var("TRANSCRIPTION_API_KEY")
+          .or_else(|_| env::var("OPENAI_API_KEY"))
+          .with_context(|| {
+            "Missing TRANSCRIPTION_API_KEY or OPENAI_API_KEY environment variable"
+          })?;
+        let mut openai_config = OpenAIConfig::new().with_api_key(api_key);
+        if let Some(api_base) = &config.api_base {
+          openai_config = openai_config.with_api_base(api_base);
+        }
+
+        Some(Arc::new(Transcriber {
+          client: Client::with_config(openai_config),
+          model: config
+            .model
+            .clone()
+            .unwrap_or_else(|| DEFAULT_MODEL.to_owned()),
+          language: config.language.clone(),
+        }))
+      }
+    };
+
+    Ok(Self {
+      transcriber,
+      recordings: Mutex::new(HashMap::new()),
+    })
+  }
+
+  fn recordings(&self) -> MutexGuard<'_, HashMap<GuildId, Arc<Recording>>> {
+    self
+      .recordings
+      .lock()
+      .unwrap_or_else(PoisonError::into_inner)
+  }
+
+  pub fn is_enabled(&self) -> bool {
+    self.transcriber.is_some()
+  }
+
+  /// Returns the recording in progress in a guild, if any.
+  pub fn recording(&self, guild_id: GuildId) -> Option<Arc<Recording>> {
+    self.recordings().get(&guild_id).cloned()
+  }
+
+  /// Returns the recording with the given ID, if it's still in progress.
+  pub fn recording_by_id(&self, recording_id: &str) -> Option<Arc<Recording>> {
+    self
+      .recordings()
+      .values()
+      .find(|recording| recording.id == recording_id)
+      .cloned()
+  }
+
+  /// Joins a voice channel and starts recording members who consent.
+  ///
+  /// # Errors
+  /// Returns an error if transcription is disabled, a recording is already in progress in
+  /// the guild, or the voice channel can't be joined.
+  pub async fn start(
+    &self,
+    manager: Arc<Songbird>,
+    guild_id: GuildId,
+    voice_channel: ChannelId,
+    transcript_channel: ChannelId,
+    event_title: String,
+  ) -> Result<Arc<Recording>> {
+    let Some(transcriber) = &self.transcriber else {
+      bail!("Transcription is disabled");
+    };
+    if self.recording(guild_id).is_some() {
+      bail!("A recording is already in progress in guild {guild_id}");
+    }
+
+    let recording = Arc::new(Recording {
+      id: Ulid::new().to_string(),
+      event_title,
+      voice_channel,
+      transcript_channel,
+      started_at: Utc::now(),
+      state: Mutex::new(RecordingState::default()),
+      transcriber: Arc::clone(transcriber),
+      requests: Arc::new(Semaphore::new(MAX_CONCURRENT_REQUESTS)),
+    });
+
+    let call = manager
+      .join(guild_id, voice_channel)
+      .await
+      .with_context(|| format!("Failed to join voice channel {voice_channel}"))?;
+    {
+      let mut call = call.lock().await;
+      call.set_config(Config::default().decode_mode(DecodeMode::Decode));
+      let receiver = Receiver {
+        recording: Arc::clone(&recording),
+      };
+      call.add_global_event(CoreEvent::SpeakingStateUpdate.into(), receiver.clone());
+      call.add_global_event(CoreEvent::VoiceTick.into(), receiver);
+    }
+
+    self.recordings().insert(guild_id, Arc::clone(&recording));
+
+    Ok(recording)
+  }
+
+  /// Stops recording, leaves the voice channel, and returns the finished transcript.
+  /// Returns [`None`] if nothing was being recorded.
+  ///
+  /// # Errors
+  /// Returns an error if the voice channel can't be left.
+  pub async fn stop(
+    &self,
+    manager: Arc<Songbird>,
+    guild_id: GuildId,
+  ) -> Result<Option<(Arc<Recording>, Vec<TranscriptLine>)>> {
+    let Some(recording) = self.recordings().remove(&guild_id) else {
+      return Ok(None);
+    };
+
+    manager
+      .remove(guild_id)
+      .await
+      .with_context(|| "Failed to leave voice channel")?;
+    let lines = recording.finish().await;
+
+    Ok(Some((recording, lines)))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_downsample() {
+    let audio = [
+      100, 200, 300, 400, 500, 600, -600, -600, -600, -600, -600, -600, 50,
+    ];
+    assert_eq!(downsample(&audio).collect::<Vec<_>>(), vec![350, -600, 50]);
+  }
+
+  #[test]
+  fn test_wav() {
+    let wav = wav(&[1, -1]);
+    assert_eq!(wav.len(), 48);
+    assert_eq!(&wav[..4], b"RIFF");
+    assert_eq!(&wav[4..8], &40u32.to_le_bytes());
+    assert_eq!(&wav[24..28], &16_000u32.to_le_bytes());
+    assert_eq!(&wav[40..44], &4u32.to_le_bytes());
+    assert_eq!(&wav[44..], &[1, 0, 255, 255]);
+  }
+}
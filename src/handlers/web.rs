@@ -0,0 +1,178 @@
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use anyhow::Result;
+use chrono::Utc;
+use http_body_util::{BodyExt, Full, Limited};
+use hyper::body::{Bytes, Incoming};
+use hyper::header::{HeaderValue, CONTENT_TYPE};
+use hyper::server::conn::http1;
+use hyper::service::service_fn;
+use hyper::{Method, Request, Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use log::{error, info};
+use tokio::net::TcpListener;
+
+use crate::data::session_webhook::{InvalidSession, SessionPayload};
+use crate::database::DatabaseHandler;
+
+type WebResponse = Response<Full<Bytes>>;
+
+/// The largest request body accepted by the session webhook. Sessions are only a few fields.
+const MAX_BODY_BYTES: usize = 4096;
+
+/// Serves calendar feeds at `/calendar/{token}.ics` and the session webhook at
+/// `/webhooks/session`, until the bot shuts down. Set the `listen` address in the `[web]`
+/// section of the config file to start the server.
+pub async fn serve(addr: SocketAddr, db: Arc<DatabaseHandler>) {
+  let listener = match TcpListener::bind(addr).await {
+    Ok(listener) => listener,
+    Err(e) => {
+      error!("Error starting web server on {addr}: {e}");
+      return;
+    }
+  };
+  info!("Serving web requests on {addr}");
+
+  loop {
+    let stream = match listener.accept().await {
+      Ok((stream, _)) => stream,
+      Err(e) => {
+        error!("Error accepting web connection: {e}");
+        continue;
+      }
+    };
+
+    let db = db.clone();
+    tokio::spawn(async move {
+      let service = service_fn(move |request| {
+        let db = db.clone();
+        async move { Ok::<_, Infallible>(handle(&db, request).await) }
+      });
+
+      if let Err(e) = http1::Builder::new()
+        .serve_connection(TokioIo::new(stream), service)
+        .await
+      {
+        error!("Error serving web connection: {e}");
+      }
+    });
+  }
+}
+
+async fn handle(db: &DatabaseHandler, request: Request<Incoming>) -> WebResponse {
+  match route(db, request).await {
+    Ok(response) => response,
+    Err(e) => {
+      error!("Error handling web request: {e:?}");
+      text(StatusCode::INTERNAL_SERVER_ERROR, "Something went wrong.")
+    }
+  }
+}
+
+async fn route(db: &DatabaseHandler, request: Request<Incoming>) -> Result<WebResponse> {
+  let path = request.uri().path();
+
+  if let Some(token) = path
+    .strip_prefix("/calendar/")
+    .and_then(|path| path.strip_suffix(".ics"))
+  {
+    if request.method() != Method::GET {
+      return Ok(text(StatusCode::METHOD_NOT_ALLOWED, "Method not allowed."));
+    }
+    return calendar(db, token).await;
+  }
+
+  if path == "/webhooks/session" {
+    if request.method() != Method::POST {
+      return Ok(text(StatusCode::METHOD_NOT_ALLOWED, "Method not allowed."));
+    }
+    return log_session(db, request).await;
+  }
+
+  Ok(text(StatusCode::NOT_FOUND, "Not found."))
+}
+
+/// Renders the calendar feed with the given token. Unknown tokens, such as those which have
+/// been reset with `/config calendar`, aren't found.
+async fn calendar(db: &DatabaseHandler, token: &str) -> Result<WebResponse> {
+  let now = Utc::now();
+
+  let mut transaction = db.start_transaction_with_retry(5).await?;
+  let calendar = DatabaseHandler::get_calendar(&mut transaction, token, now).await?;
+  drop(transaction);
+
+  match calendar {
+    Some(calendar) => Ok(
+      Response::builder()
+        .status(StatusCode::OK)
+        .header(CONTENT_TYPE, "text/calendar; charset=utf-8")
+        .body(Full::new(Bytes::from(calendar.render(now))))?,
+    ),
+    None => Ok(text(StatusCode::NOT_FOUND, "Not found.")),
+  }
+}
+
+/// Logs a session sent by an automation, for the member whose token is included in the
+/// [`SessionPayload`]. Each token is limited to its hourly limit of sessions.
+async fn log_session(db: &DatabaseHandler, request: Request<Incoming>) -> Result<WebResponse> {
+  let Ok(body) = Limited::new(request.into_body(), MAX_BODY_BYTES)
+    .collect()
+    .await
+  else {
+    return Ok(text(
+      StatusCode::PAYLOAD_TOO_LARGE,
+      "The request body is too large.",
+    ));
+  };
+
+  let payload = match serde_json::from_slice::<SessionPayload>(&body.to_bytes()) {
+    Ok(payload) => payload,
+    Err(e) => {
+      return Ok(text_owned(
+        StatusCode::BAD_REQUEST,
+        format!("The request body isn't a valid session: {e}."),
+      ));
+    }
+  };
+
+  let mut transaction = db.start_transaction_with_retry(5).await?;
+  match DatabaseHandler::log_webhook_session(&mut transaction, &payload, Utc::now()).await {
+    Ok(Some(_)) => {
+      DatabaseHandler::commit_transaction(transaction).await?;
+      Ok(text_owned(
+        StatusCode::OK,
+        format!("Logged a session of {} minutes.", payload.minutes),
+      ))
+    }
+    Ok(None) => Ok(text(
+      StatusCode::UNAUTHORIZED,
+      "The token doesn't exist. It may have been revoked.",
+    )),
+    Err(e) => match e.downcast_ref::<InvalidSession>() {
+      Some(InvalidSession::RateLimited) => Ok(text_owned(
+        StatusCode::TOO_MANY_REQUESTS,
+        format!("{}.", InvalidSession::RateLimited),
+      )),
+      Some(invalid) => Ok(text_owned(StatusCode::BAD_REQUEST, format!("{invalid}."))),
+      None => Err(e),
+    },
+  }
+}
+
+/// A plain text response.
+fn text(status: StatusCode, body: &'static str) -> WebResponse {
+  text_owned(status, body.to_owned())
+}
+
+/// A plain text response with a body built at runtime.
+fn text_owned(status: StatusCode, body: String) -> WebResponse {
+  let mut response = Response::new(Full::new(Bytes::from(body)));
+  *response.status_mut() = status;
+  response.headers_mut().insert(
+    CONTENT_TYPE,
+    HeaderValue::from_static("text/plain; charset=utf-8"),
+  );
+  response
+}
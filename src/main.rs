@@ -1,38 +1,64 @@
 #![warn(clippy::pedantic, clippy::unwrap_used, clippy::expect_used)]
 #![allow(clippy::too_many_lines)]
 
-#[macro_use(query)]
-extern crate sqlx;
-
 use std::env;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::ops::Range;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::Instant;
 
-use anyhow::{anyhow, Context as ErrorContext, Error, Result};
+use anyhow::{anyhow, bail, Context as ErrorContext, Error, Result};
+use bloombot_core::{data, ics};
 use dotenvy::dotenv;
 use log::{error, info};
-use poise::serenity_prelude::{ActivityData, Channel, Client, GatewayIntents, GuildId};
+use poise::serenity_prelude::{ActivityData, Channel, ChannelId, Client, CreateMessage};
 use poise::serenity_prelude::{Context as SerenityContext, FullEvent as Event};
+use poise::serenity_prelude::{GatewayIntents, GuildId};
 use poise::Context as PoiseContext;
 use poise::{builtins, CreateReply, Framework, FrameworkError, FrameworkOptions};
 use rand::rngs::SmallRng;
 use rand::SeedableRng;
+use songbird::SerenityInit;
 use tokio::sync::Mutex;
 
+use crate::bot_config::BotConfigHandler;
+use crate::chart_cache::ChartCacheHandler;
+use crate::commands::helpers::cooldowns::{self, CooldownHit};
+use crate::commands::helpers::maintenance::{self, MaintenanceMode};
+use crate::commands::helpers::registration;
+use crate::commands::helpers::{announcements, arguments, incidents, key_redemption, polls};
 use crate::commands::{
-  add, add_bookmark, bookmark, challenge, coffee, community_sit, complete, course, courses,
-  customize, erase, erase_message, glossary, hello, help, import, keys, manage, pick_winner, ping,
-  quote, quotes, recent, remove_entry, report_message, stats, streak, suggest, terms, uptime,
-  whatis,
+  add, add_bookmark, add_multi, announce, bookmark, challenge, checkin, coffee, community_sit,
+  complete, config, course, courses, customize, dedicate, dedications, define_terms, directory,
+  erase, erase_message, event, feed, glossary, goal, hello, help, import, intention, keys, link,
+  log_session, manage, mentor, mentorships, moderation, pick_winner, ping, poll, quick_add, quote,
+  quotes, raffle, recent, remove_entry, report_message, retreat, sit_now, sleep, stats, streak,
+  study_group, suggest, suggestions, sutta, talk, terms, ticket, token, uptime, warn, warnings,
+  watchlist, whatis,
 };
+use crate::config::CHANNELS;
 use crate::database::DatabaseHandler;
 use crate::embeddings::OpenAIHandler;
-use crate::handlers::{database, embeddings};
+use crate::emoji::EmojiHandler;
+use crate::features::FeatureHandler;
+use crate::feeds::FeedHandler;
+use crate::handlers::maintenance::MaintenanceHandler;
+use crate::handlers::{
+  bot_config, chart_cache, database, embeddings, emoji, features, feeds, import_jobs, roles,
+  settings, storage, suttas, talks, transcription, web,
+};
+use crate::import_jobs::ImportJobsHandler;
+use crate::roles::RoleQueueHandler;
+use crate::settings::SettingsHandler;
+use crate::storage::StorageHandler;
+use crate::suttas::SuttaHandler;
+use crate::talks::TalkHandler;
+use crate::transcription::TranscriptionHandler;
 
 mod charts;
 mod commands;
 mod config;
-mod data;
 mod events;
 mod handlers;
 
@@ -40,10 +66,121 @@ pub struct Data {
   pub db: Arc<DatabaseHandler>,
   pub rng: Arc<Mutex<SmallRng>>,
   pub embeddings: Arc<OpenAIHandler>,
+  pub emoji: Arc<EmojiHandler>,
+  pub features: Arc<FeatureHandler>,
+  pub bot_config: Arc<BotConfigHandler>,
+  pub settings: Arc<SettingsHandler>,
+  pub storage: Arc<StorageHandler>,
+  pub suttas: Arc<SuttaHandler>,
+  pub feeds: Arc<FeedHandler>,
+  pub talks: Arc<TalkHandler>,
+  pub transcription: Arc<TranscriptionHandler>,
+  pub chart_cache: Arc<ChartCacheHandler>,
+  pub role_queue: Arc<RoleQueueHandler>,
+  pub import_jobs: Arc<ImportJobsHandler>,
+  pub maintenance: Arc<MaintenanceHandler>,
   pub bloom_start_time: Instant,
+  /// Whether the background jobs have been started. See [`start_background_jobs`].
+  pub background_started: AtomicBool,
 }
 pub type Context<'a> = PoiseContext<'a, Data, Error>;
 
+/// Gateway sharding strategy, configured with the `SHARD_COUNT` and `SHARD_RANGE`
+/// environment variables:
+/// - Neither set: run a single shard.
+/// - `SHARD_COUNT=auto`: run the number of shards recommended by Discord.
+/// - `SHARD_COUNT=<total>`: run all shards in this process.
+/// - `SHARD_COUNT=<total>` and `SHARD_RANGE=<first>-<last>`: run shards `first` through
+///   `last` (inclusive) in this process, allowing shards to be split across processes.
+#[derive(Debug, PartialEq)]
+enum Sharding {
+  Single,
+  Auto,
+  Fixed(u32),
+  Range(Range<u32>, u32),
+}
+
+impl Sharding {
+  fn from_env() -> Result<Self> {
+    Self::parse(
+      env::var("SHARD_COUNT").ok().as_deref(),
+      env::var("SHARD_RANGE").ok().as_deref(),
+    )
+  }
+
+  fn parse(count: Option<&str>, range: Option<&str>) -> Result<Self> {
+    let count = count.map(str::trim).filter(|count| !count.is_empty());
+    let range = range.map(str::trim).filter(|range| !range.is_empty());
+
+    let Some(count) = count else {
+      if range.is_some() {
+        bail!("SHARD_RANGE requires SHARD_COUNT to be set");
+      }
+      return Ok(Self::Single);
+    };
+
+    if count.eq_ignore_ascii_case("auto") {
+      if range.is_some() {
+        bail!("SHARD_RANGE requires a numeric SHARD_COUNT");
+      }
+      return Ok(Self::Auto);
+    }
+
+    let total = count
+      .parse::<u32>()
+      .with_context(|| format!("Invalid SHARD_COUNT \"{count}\""))?;
+    if total == 0 {
+      bail!("SHARD_COUNT must be at least 1");
+    }
+
+    let Some(range) = range else {
+      return Ok(Self::Fixed(total));
+    };
+
+    let (first, last) = range
+      .split_once('-')
+      .with_context(|| format!("Invalid SHARD_RANGE \"{range}\", expected <first>-<last>"))?;
+    let first = first
+      .trim()
+      .parse::<u32>()
+      .with_context(|| format!("Invalid SHARD_RANGE \"{range}\""))?;
+    let last = last
+      .trim()
+      .parse::<u32>()
+      .with_context(|| format!("Invalid SHARD_RANGE \"{range}\""))?;
+    if first > last || last >= total {
+      bail!("SHARD_RANGE \"{range}\" does not fit within {total} shards");
+    }
+
+    Ok(Self::Range(first..last + 1, total))
+  }
+
+  /// Whether this process runs shard 0. Only one process should register commands,
+  /// so when shards are split across processes, the process running shard 0 does it.
+  fn includes_first_shard(&self) -> bool {
+    match self {
+      Self::Range(range, _) => range.start == 0,
+      _ => true,
+    }
+  }
+}
+
+impl Display for Sharding {
+  fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+    match self {
+      Self::Single => write!(f, "a single shard"),
+      Self::Auto => write!(f, "the recommended number of shards"),
+      Self::Fixed(total) => write!(f, "{total} shards"),
+      Self::Range(range, total) => write!(
+        f,
+        "shards {}-{} of {total}",
+        range.start,
+        range.end.saturating_sub(1)
+      ),
+    }
+  }
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
   dotenv().ok();
@@ -53,33 +190,69 @@ async fn main() -> Result<()> {
   let token =
     env::var("DISCORD_TOKEN").with_context(|| "Missing DISCORD_TOKEN environment variable")?;
   let test_guild = env::var("TEST_GUILD_ID");
+  let sharding = Sharding::from_env()?;
+  let register_commands = sharding.includes_first_shard();
+  let bot_config = Arc::new(BotConfigHandler::new()?);
+  info!("Using config file {}", bot_config.path().display());
 
   let intents = GatewayIntents::GUILDS
     | GatewayIntents::GUILD_MODERATION
     | GatewayIntents::GUILD_MESSAGES
+    | GatewayIntents::MESSAGE_CONTENT
     | GatewayIntents::GUILD_MESSAGE_REACTIONS
     | GatewayIntents::DIRECT_MESSAGES
-    | GatewayIntents::GUILD_MEMBERS;
+    | GatewayIntents::GUILD_MEMBERS
+    | GatewayIntents::GUILD_VOICE_STATES
+    | GatewayIntents::GUILD_SCHEDULED_EVENTS;
 
   let framework = Framework::builder()
     .options(FrameworkOptions {
       commands: vec![
         keys(),
+        link(),
         courses(),
         pick_winner(),
+        raffle(),
         erase(),
         manage(),
         quotes(),
         terms(),
         challenge(),
+        goal(),
+        poll(),
+        quick_add(),
+        mentorships(),
+        announce(),
+        suggestions(),
+        ticket(),
+        warn(),
+        warnings(),
+        watchlist(),
+        feed(),
         customize(),
+        token(),
+        config(),
         add(),
+        add_multi(),
+        retreat(),
+        checkin(),
+        dedicate(),
+        dedications(),
+        directory(),
+        mentor(),
+        event(),
+        study_group(),
+        intention(),
+        sit_now(),
         import(),
+        sleep(),
         recent(),
         remove_entry(),
         stats(),
         streak(),
         whatis(),
+        sutta(),
+        talk(),
         glossary(),
         bookmark(),
         quote(),
@@ -92,11 +265,17 @@ async fn main() -> Result<()> {
         suggest(),
         complete(),
         add_bookmark(),
+        define_terms(),
         erase_message(),
         report_message(),
+        log_session(),
         community_sit(),
       ],
       event_handler: |ctx, event, _framework, data| Box::pin(event_handler(ctx, event, data)),
+      // Cooldowns are checked and started here, rather than by poise, so staff can bypass them
+      manual_cooldowns: true,
+      command_check: Some(|ctx| Box::pin(command_check(ctx))),
+      pre_command: |ctx| Box::pin(cooldowns::start(ctx)),
       on_error: |error| {
         Box::pin(async move {
           error_handler(error).await;
@@ -106,20 +285,50 @@ async fn main() -> Result<()> {
     })
     .setup(|ctx, _ready, framework| {
       Box::pin(async move {
-        if let Ok(test_guild) = test_guild {
-          info!("Registering commands in test guild {test_guild}");
-
-          let guild_id = GuildId::new(test_guild.parse::<u64>()?);
-          builtins::register_in_guild(ctx, &framework.options().commands, guild_id).await?;
-        } else {
-          info!("Registering commands globally");
-          builtins::register_globally(ctx, &framework.options().commands).await?;
+        match (register_commands, test_guild) {
+          (false, _) => {
+            info!("Skipping command registration, as shard 0 is run by another process");
+          }
+          (true, Ok(test_guild)) => {
+            info!("Registering commands in test guild {test_guild}");
+
+            let guild_id = GuildId::new(test_guild.parse::<u64>()?);
+            registration::register_in_guild(ctx, &framework.options().commands, guild_id).await?;
+          }
+          (true, Err(_)) => {
+            info!("Registering commands globally");
+            registration::register_globally(ctx, &framework.options().commands).await?;
+          }
         }
+        bot_config
+          .get()
+          .rate_limits
+          .apply(&framework.options().commands);
+        let db = Arc::new(DatabaseHandler::new().await?);
+
+        if let Some(listen) = bot_config.get().web.listen {
+          tokio::spawn(web::serve(listen, db.clone()));
+        }
+
         Ok(Data {
-          db: Arc::new(DatabaseHandler::new().await?),
+          embeddings: Arc::new(OpenAIHandler::new(db.clone(), &bot_config.get().openai)?),
+          emoji: Arc::new(EmojiHandler::new(&db).await?),
+          features: Arc::new(FeatureHandler::new(&db, bot_config.clone()).await?),
+          transcription: Arc::new(TranscriptionHandler::new(&bot_config.get().transcription)?),
+          bot_config,
+          settings: Arc::new(SettingsHandler::new()),
+          storage: Arc::new(StorageHandler::new()?),
+          suttas: Arc::new(SuttaHandler::new()?),
+          feeds: Arc::new(FeedHandler::new()?),
+          talks: Arc::new(TalkHandler::new()?),
+          chart_cache: Arc::new(ChartCacheHandler::new()),
+          role_queue: Arc::new(RoleQueueHandler::new(ctx.http.clone())),
+          import_jobs: Arc::new(ImportJobsHandler::new()),
+          maintenance: Arc::new(MaintenanceHandler::new(&db).await?),
+          db,
           rng: Arc::new(Mutex::new(SmallRng::from_entropy())),
-          embeddings: Arc::new(OpenAIHandler::new()?),
           bloom_start_time: Instant::now(),
+          background_started: AtomicBool::new(false),
         })
       })
     })
@@ -127,6 +336,7 @@ async fn main() -> Result<()> {
 
   let mut client = Client::builder(&token, intents)
     .framework(framework)
+    .register_songbird()
     .await
     .map_err(|e| anyhow!(e))?;
 
@@ -139,19 +349,40 @@ async fn main() -> Result<()> {
     shard_manager.shutdown_all().await;
   });
 
-  client
-    .start()
-    .await
-    .map_err(|e| anyhow!("Error starting client: {e}"))
+  info!("Starting client with {sharding}");
+
+  match sharding {
+    Sharding::Single => client.start().await,
+    Sharding::Auto => client.start_autosharded().await,
+    Sharding::Fixed(total) => client.start_shards(total).await,
+    Sharding::Range(range, total) => client.start_shard_range(range, total).await,
+  }
+  .map_err(|e| anyhow!("Error starting client: {e}"))
+}
+
+/// Runs before every command, and each of its parent commands. Maintenance is checked first,
+/// so members aren't told a command is on cooldown when they couldn't use it anyway.
+async fn command_check(ctx: Context<'_>) -> Result<bool, Error> {
+  Ok(maintenance::check(ctx).await? && cooldowns::check(ctx).await?)
 }
 
 async fn error_handler(error: FrameworkError<'_, Data, Error>) {
   match error {
     FrameworkError::Command { ctx, error, .. } => {
-      match ctx.say("An error occurred while running the command").await {
+      let incident_id = incidents::incident_id();
+      match ctx
+        .send(
+          CreateReply::default()
+            .content(format!(
+              "An error occurred while running the command. If you need help, please contact staff with incident ID `{incident_id}`."
+            ))
+            .ephemeral(true),
+        )
+        .await
+      {
         Ok(_) => {}
         Err(e) => {
-          error!("While handling error, could not send message: {e}");
+          error!("[{incident_id}] While handling error, could not send message: {e}");
         }
       };
 
@@ -160,7 +391,7 @@ async fn error_handler(error: FrameworkError<'_, Data, Error>) {
       let channel = if let Ok(channel) = channel_id.to_channel(ctx).await {
         Some(channel)
       } else {
-        error!("While handling error, could not get channel {channel_id}");
+        error!("[{incident_id}] While handling error, could not get channel {channel_id}");
         None
       };
 
@@ -182,8 +413,8 @@ async fn error_handler(error: FrameworkError<'_, Data, Error>) {
       let user = ctx.author();
 
       error!(
-        "\x1B[1m/{}\x1B[0m failed with error: {:?}",
-        command.name, error
+        "[{incident_id}] \x1B[1m/{}\x1B[0m failed with error: {:?}",
+        command.qualified_name, error
       );
       error!("\tSource: {source}");
 
@@ -192,15 +423,53 @@ async fn error_handler(error: FrameworkError<'_, Data, Error>) {
       }
 
       error!("\tUser: {} ({})", user.name, user.id);
+      error!("\tShard: {}", ctx.serenity_context().shard_id.0);
+
+      if ctx.data().bot_config.get().errors.post_to_log_channel {
+        let embed = incidents::incident_embed(
+          &incident_id,
+          &command.qualified_name,
+          user,
+          &source,
+          &format!("{error:?}"),
+        );
+        if let Err(e) = ChannelId::new(CHANNELS.logs)
+          .send_message(ctx, CreateMessage::new().embed(embed))
+          .await
+        {
+          error!("[{incident_id}] While handling error, could not post to log channel: {e}");
+        }
+      }
+    }
+    FrameworkError::CommandCheckFailed {
+      error: Some(error),
+      ctx,
+      ..
+    } if error.is::<CooldownHit>() => {
+      if let Ok(hit) = error.downcast::<CooldownHit>() {
+        cooldowns::cooldown_hit(ctx, hit.remaining).await;
+      }
+    }
+    FrameworkError::CommandCheckFailed {
+      error: Some(error),
+      ctx,
+      ..
+    } if error.is::<MaintenanceMode>() => {
+      if let Ok(maintenance) = error.downcast::<MaintenanceMode>() {
+        maintenance::maintenance_hit(ctx, maintenance.message).await;
+      }
     }
     FrameworkError::ArgumentParse {
       error, input, ctx, ..
     } => {
-      let response = if let Some(input) = input {
-        format!("**Cannot parse `{input}` as argument: {error}**")
-      } else {
-        format!("**{error}**")
-      };
+      let choices: Vec<&str> = ctx
+        .command()
+        .parameters
+        .iter()
+        .flat_map(|parameter| &parameter.choices)
+        .map(|choice| choice.name.as_str())
+        .collect();
+      let response = arguments::parse_error_message(&*error, input.as_deref(), &choices);
 
       match ctx
         .send(CreateReply::default().content(response).ephemeral(true))
@@ -226,30 +495,81 @@ async fn event_handler(ctx: &SerenityContext, event: &Event, data: &Data) -> Res
   match event {
     Event::GuildCreate { guild, .. } => {
       events::guild_create(ctx, database, &guild.id).await?;
+      events::guild_scheduled_event::sync(database, guild.id, &guild.scheduled_events).await?;
+    }
+    Event::GuildScheduledEventCreate { event } | Event::GuildScheduledEventUpdate { event } => {
+      events::guild_scheduled_event_update(database, event).await?;
+    }
+    Event::GuildScheduledEventDelete { event } => {
+      events::guild_scheduled_event_delete(database, event).await?;
+    }
+    Event::GuildMemberAddition { new_member } => {
+      events::guild_member_addition(ctx, database, &data.settings, new_member).await?;
     }
-    Event::GuildMemberRemoval { user, .. } => {
-      events::guild_member_removal(ctx, user).await?;
+    Event::GuildMemberRemoval { guild_id, user, .. } => {
+      events::guild_member_removal(ctx, database, &data.settings, guild_id, user).await?;
     }
     Event::GuildMemberUpdate {
       old_if_available,
       new,
       ..
     } => {
-      events::guild_member_update(ctx, old_if_available, new).await?;
+      events::guild_member_update(
+        ctx,
+        database,
+        &data.emoji,
+        &data.settings,
+        old_if_available,
+        new,
+      )
+      .await?;
+    }
+    Event::InteractionCreate { interaction } => {
+      events::interaction_create(
+        ctx,
+        database,
+        &data.emoji,
+        &data.settings,
+        &data.transcription,
+        interaction,
+      )
+      .await?;
+    }
+    Event::Message { new_message } => {
+      events::message_create(ctx, database, &data.emoji, &data.settings, new_message).await?;
     }
     Event::MessageDelete {
-      deleted_message_id, ..
+      deleted_message_id,
+      guild_id,
+      ..
     } => {
-      events::message_delete(database, deleted_message_id).await?;
+      events::message_delete(ctx, database, *guild_id, deleted_message_id).await?;
+    }
+    Event::MessageUpdate { event, .. } => {
+      events::message_update(ctx, database, &data.features, event).await?;
     }
     Event::ReactionAdd { add_reaction } => {
-      events::reaction_add(ctx, database, add_reaction).await?;
+      events::reaction_add(ctx, database, &data.features, add_reaction).await?;
     }
     Event::ReactionRemove { removed_reaction } => {
-      events::reaction_remove(ctx, database, removed_reaction).await?;
+      events::reaction_remove(ctx, database, &data.features, removed_reaction).await?;
     }
-    Event::Ready { .. } => {
-      info!("Connected!");
+    Event::Ready { data_about_bot } => {
+      match &data_about_bot.shard {
+        Some(shard) => info!("Connected! (shard {}, {} total)", shard.id.0, shard.total),
+        None => info!("Connected!"),
+      }
+
+      // Only one process needs to run the background jobs, so they're started by whichever
+      // runs shard 0, and only the first time it connects.
+      if data_about_bot
+        .shard
+        .as_ref()
+        .is_none_or(|shard| shard.id.0 == 0)
+        && !data.background_started.swap(true, Ordering::SeqCst)
+      {
+        start_background_jobs(ctx, data);
+      }
 
       let default_activity_text = "Tracking your meditations";
       info!(
@@ -258,11 +578,88 @@ async fn event_handler(ctx: &SerenityContext, event: &Event, data: &Data) -> Res
       );
       ctx.set_activity(Some(ActivityData::custom(default_activity_text)));
     }
+    Event::VoiceStateUpdate { old, new } => {
+      events::voice_state_update(database, old.as_ref(), new).await?;
+    }
     _ => {}
   }
   Ok(())
 }
 
+/// Starts the jobs which run for as long as the bot does, such as sending reminders and
+/// expiring key offers. Each job only needs to run in one process.
+fn start_background_jobs(ctx: &SerenityContext, data: &Data) {
+  let database = &data.db;
+
+  // Key offers are expired and the key pool is cleaned up by the same process, so expired
+  // keys are only reported once.
+  tokio::spawn(key_redemption::expire_offers_periodically(
+    ctx.clone(),
+    database.clone(),
+  ));
+  tokio::spawn(key_redemption::clean_key_pool_periodically(
+    ctx.clone(),
+    database.clone(),
+  ));
+  tokio::spawn(events::goals::check_goals_periodically(
+    ctx.clone(),
+    database.clone(),
+    data.bot_config.clone(),
+  ));
+  tokio::spawn(events::improved::post_shoutouts_periodically(
+    ctx.clone(),
+    database.clone(),
+    data.bot_config.clone(),
+  ));
+  tokio::spawn(events::streak_guard::remind_periodically(
+    ctx.clone(),
+    database.clone(),
+  ));
+  tokio::spawn(events::mentorship_check_ins::remind_periodically(
+    ctx.clone(),
+    database.clone(),
+  ));
+  tokio::spawn(events::intention_reminders::remind_periodically(
+    ctx.clone(),
+    database.clone(),
+  ));
+  tokio::spawn(polls::close_expired_periodically(
+    ctx.clone(),
+    database.clone(),
+  ));
+  tokio::spawn(announcements::send_scheduled_periodically(
+    ctx.clone(),
+    database.clone(),
+  ));
+  tokio::spawn(events::recurring_posts::post_periodically(
+    ctx.clone(),
+    database.clone(),
+  ));
+  tokio::spawn(events::feeds::poll_periodically(
+    ctx.clone(),
+    database.clone(),
+    data.feeds.clone(),
+  ));
+  tokio::spawn(events::selfcheck::run_on_startup(
+    ctx.clone(),
+    database.clone(),
+    data.embeddings.clone(),
+  ));
+  tokio::spawn(storage::remove_expired_periodically(data.storage.clone()));
+  tokio::spawn(events::pending_interactions::remove_expired_periodically(
+    database.clone(),
+  ));
+  tokio::spawn(events::role_sync::reconcile_periodically(
+    ctx.clone(),
+    database.clone(),
+    data.role_queue.clone(),
+  ));
+  tokio::spawn(events::retention::apply_periodically(
+    ctx.clone(),
+    database.clone(),
+  ));
+}
+
 #[allow(clippy::unwrap_used)]
 #[cfg(unix)]
 async fn wait_until_shutdown() {
@@ -294,3 +691,42 @@ async fn wait_until_shutdown() {
       v = s2.recv() => v.unwrap(),
   );
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_sharding_parse() -> Result<()> {
+    assert_eq!(Sharding::parse(None, None)?, Sharding::Single);
+    assert_eq!(Sharding::parse(Some(""), Some(" "))?, Sharding::Single);
+    assert_eq!(Sharding::parse(Some("auto"), None)?, Sharding::Auto);
+    assert_eq!(Sharding::parse(Some("4"), None)?, Sharding::Fixed(4));
+    assert_eq!(
+      Sharding::parse(Some("8"), Some("4-7"))?,
+      Sharding::Range(4..8, 8)
+    );
+    assert_eq!(
+      Sharding::parse(Some("8"), Some("0-0"))?,
+      Sharding::Range(0..1, 8)
+    );
+
+    assert!(Sharding::parse(None, Some("0-3")).is_err());
+    assert!(Sharding::parse(Some("auto"), Some("0-3")).is_err());
+    assert!(Sharding::parse(Some("0"), None).is_err());
+    assert!(Sharding::parse(Some("four"), None).is_err());
+    assert!(Sharding::parse(Some("4"), Some("3")).is_err());
+    assert!(Sharding::parse(Some("4"), Some("2-1")).is_err());
+    assert!(Sharding::parse(Some("4"), Some("2-4")).is_err());
+
+    Ok(())
+  }
+
+  #[test]
+  fn test_sharding_includes_first_shard() {
+    assert!(Sharding::Single.includes_first_shard());
+    assert!(Sharding::Fixed(4).includes_first_shard());
+    assert!(Sharding::Range(0..2, 4).includes_first_shard());
+    assert!(!Sharding::Range(2..4, 4).includes_first_shard());
+  }
+}